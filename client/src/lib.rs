@@ -0,0 +1,252 @@
+//! Typed client for CREAM's contracts on a Freenet node.
+//!
+//! [`CreamClient`] wraps a node connection with async methods that send one
+//! request and wait for its answer, parsing contract states into their
+//! `cream-common` types: `get_directory`, `update_storefront`,
+//! `subscribe_user_contract`, `place_order` and so on. A refusal by the
+//! contract comes back as [`Error::Rejected`] with the reason the node gave.
+//!
+//! The connection is anything implementing [`Connection`]: the native
+//! `WebApi` directly, or in the browser a [`ChannelConnection`] — the WASM
+//! `WebApi` delivers responses to a callback, which feeds its channel.
+//!
+//! Callers that drive their own connection instead (the UI's node loop
+//! routes every response through one handler) use the same request builders
+//! in [`request`] and response helpers in [`response`].
+
+use std::fmt;
+
+use freenet_stdlib::client_api::{ClientError, ClientRequest, ContractResponse, HostResponse};
+use freenet_stdlib::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cream_common::directory::DirectoryState;
+use cream_common::inbox::InboxState;
+use cream_common::order::Order;
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+
+pub mod request;
+pub mod response;
+
+use response::{contract_error, extract_get_response_state};
+
+/// Why a request didn't get the answer it was after.
+#[derive(Debug)]
+pub enum Error {
+    /// The request couldn't be sent.
+    Send(String),
+    /// The node reported an error other than a contract refusal, or the
+    /// connection closed.
+    Node(ClientError),
+    /// The contract refused the request; the node's reason.
+    Rejected(String),
+    /// The state came back but didn't parse as the expected type.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Send(e) => write!(f, "request not sent: {e}"),
+            Error::Node(e) => write!(f, "node error: {e}"),
+            Error::Rejected(cause) => write!(f, "contract refused the request: {cause}"),
+            Error::Parse(e) => write!(f, "state did not parse: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ClientError> for Error {
+    fn from(err: ClientError) -> Self {
+        match contract_error(&err) {
+            Some(cause) => Error::Rejected(cause.to_string()),
+            None => Error::Node(err),
+        }
+    }
+}
+
+/// A connection to a node: requests out, responses back in arrival order.
+#[allow(async_fn_in_trait)] // used on one thread; no Send bound wanted in WASM
+pub trait Connection {
+    async fn send(&mut self, request: ClientRequest<'static>) -> Result<(), Error>;
+    async fn recv(&mut self) -> Result<HostResponse, ClientError>;
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl Connection for freenet_stdlib::client_api::WebApi {
+    async fn send(&mut self, request: ClientRequest<'static>) -> Result<(), Error> {
+        freenet_stdlib::client_api::WebApi::send(self, request)
+            .await
+            .map_err(|e| Error::Send(e.to_string()))
+    }
+
+    async fn recv(&mut self) -> Result<HostResponse, ClientError> {
+        freenet_stdlib::client_api::WebApi::recv(self).await
+    }
+}
+
+/// The browser `WebApi` and the channel its result handler feeds.
+#[cfg(target_family = "wasm")]
+pub struct ChannelConnection {
+    pub api: freenet_stdlib::client_api::WebApi,
+    pub responses: futures::channel::mpsc::UnboundedReceiver<Result<HostResponse, ClientError>>,
+}
+
+#[cfg(target_family = "wasm")]
+impl Connection for ChannelConnection {
+    async fn send(&mut self, request: ClientRequest<'static>) -> Result<(), Error> {
+        self.api.send(request).await.map_err(|e| Error::Send(format!("{e:?}")))
+    }
+
+    async fn recv(&mut self) -> Result<HostResponse, ClientError> {
+        use futures::StreamExt;
+        match self.responses.next().await {
+            Some(response) => response,
+            None => Err(freenet_stdlib::client_api::ErrorKind::Disconnect.into()),
+        }
+    }
+}
+
+/// Typed requests over a [`Connection`].
+///
+/// Each method waits for its own answer, discarding anything else that
+/// arrives first (notifications for other subscriptions, say). There is no
+/// timeout here: wrap the call in the runtime's own where one is wanted.
+pub struct CreamClient<C> {
+    conn: C,
+}
+
+impl<C: Connection> CreamClient<C> {
+    pub fn new(conn: C) -> Self {
+        CreamClient { conn }
+    }
+
+    /// The underlying connection, for requests this client doesn't cover.
+    pub fn connection(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    pub fn into_inner(self) -> C {
+        self.conn
+    }
+
+    /// GET contract `id` and parse its state as `S`.
+    pub async fn get_state<S: DeserializeOwned>(&mut self, id: ContractInstanceId) -> Result<S, Error> {
+        self.conn.send(request::get(id, false)).await?;
+        let resp = self
+            .answer(|resp| {
+                matches!(resp, ContractResponse::GetResponse { key, .. } if *key.id() == id)
+            })
+            .await?;
+        let bytes = extract_get_response_state(&resp).unwrap_or_default();
+        serde_json::from_slice(&bytes).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    pub async fn get_directory(&mut self, id: ContractInstanceId) -> Result<DirectoryState, Error> {
+        self.get_state(id).await
+    }
+
+    pub async fn get_storefront(&mut self, id: ContractInstanceId) -> Result<StorefrontState, Error> {
+        self.get_state(id).await
+    }
+
+    pub async fn get_user_contract(&mut self, id: ContractInstanceId) -> Result<UserContractState, Error> {
+        self.get_state(id).await
+    }
+
+    pub async fn get_inbox(&mut self, id: ContractInstanceId) -> Result<InboxState, Error> {
+        self.get_state(id).await
+    }
+
+    /// PUT `contract` with initial `state`, subscribing to it if `subscribe`.
+    pub async fn put_state<S: Serialize>(
+        &mut self,
+        contract: ContractContainer,
+        state: &S,
+        subscribe: bool,
+    ) -> Result<(), Error> {
+        let id = *contract.key().id();
+        let bytes = serde_json::to_vec(state).expect("serialization should not fail");
+        self.conn.send(request::put(contract, bytes, subscribe)).await?;
+        self.answer(|resp| matches!(resp, ContractResponse::PutResponse { key } if *key.id() == id))
+            .await?;
+        Ok(())
+    }
+
+    /// UPDATE contract `key` with the full `state`; the contract merges it.
+    pub async fn update_state<S: Serialize>(&mut self, key: ContractKey, state: &S) -> Result<(), Error> {
+        self.conn.send(request::update_state(key, state)).await?;
+        self.answer(|resp| {
+            matches!(resp, ContractResponse::UpdateResponse { key: updated, .. } if updated.id() == key.id())
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn update_storefront(&mut self, key: ContractKey, storefront: &StorefrontState) -> Result<(), Error> {
+        self.update_state(key, storefront).await
+    }
+
+    pub async fn update_user_contract(&mut self, key: ContractKey, state: &UserContractState) -> Result<(), Error> {
+        self.update_state(key, state).await
+    }
+
+    /// Place `order` in the storefront at `key`: add it to `storefront`, our
+    /// copy of the state, and send that. The order must already be signed.
+    pub async fn place_order(
+        &mut self,
+        key: ContractKey,
+        storefront: &mut StorefrontState,
+        order: Order,
+    ) -> Result<(), Error> {
+        storefront.orders.insert(order.id.clone(), order);
+        self.update_storefront(key, storefront).await
+    }
+
+    /// SUBSCRIBE to contract `id`. Its updates then arrive through
+    /// [`CreamClient::next_update`].
+    pub async fn subscribe(&mut self, id: ContractInstanceId) -> Result<(), Error> {
+        self.conn.send(request::subscribe(id)).await?;
+        let resp = self
+            .answer(|resp| {
+                matches!(resp, ContractResponse::SubscribeResponse { key, .. } if *key.id() == id)
+            })
+            .await?;
+        if response::is_subscribe_success(&resp) {
+            Ok(())
+        } else {
+            Err(Error::Rejected(format!("subscription to {id} refused")))
+        }
+    }
+
+    pub async fn subscribe_user_contract(&mut self, id: ContractInstanceId) -> Result<(), Error> {
+        self.subscribe(id).await
+    }
+
+    /// Wait for the next update notification for contract `id` and parse the
+    /// state it carries as `S`.
+    pub async fn next_update<S: DeserializeOwned>(&mut self, id: ContractInstanceId) -> Result<S, Error> {
+        let resp = self
+            .answer(|resp| {
+                matches!(resp, ContractResponse::UpdateNotification { key, .. } if *key.id() == id)
+            })
+            .await?;
+        let bytes = response::extract_notification_bytes(&resp).unwrap_or_default();
+        serde_json::from_slice(&bytes).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Receive until a contract response satisfying `wanted` arrives, or an
+    /// error does.
+    async fn answer(&mut self, wanted: impl Fn(&ContractResponse) -> bool) -> Result<HostResponse, Error> {
+        loop {
+            let resp = self.conn.recv().await?;
+            match &resp {
+                HostResponse::ContractResponse(contract_resp) if wanted(contract_resp) => return Ok(resp),
+                other => tracing::debug!("CreamClient: discarding {:?}", other),
+            }
+        }
+    }
+}
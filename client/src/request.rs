@@ -0,0 +1,58 @@
+//! Builders for the contract requests CREAM sends.
+//!
+//! Each is the plain `ClientRequest` with the options CREAM always uses, so
+//! callers that manage their own connection (the UI's node loop, the test
+//! helpers' retry loops) build the same requests the
+//! [`CreamClient`](crate::CreamClient) sends.
+
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest};
+use freenet_stdlib::prelude::*;
+use serde::Serialize;
+
+/// GET the state of contract `id`, with its code if `return_contract_code`.
+pub fn get(id: ContractInstanceId, return_contract_code: bool) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Get {
+        key: id,
+        return_contract_code,
+        subscribe: false,
+        blocking_subscribe: false,
+    })
+}
+
+/// PUT `contract` with initial state `state`, subscribing to it if `subscribe`.
+pub fn put(contract: ContractContainer, state: Vec<u8>, subscribe: bool) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Put {
+        contract,
+        state: WrappedState::new(state),
+        related_contracts: RelatedContracts::default(),
+        subscribe,
+        blocking_subscribe: false,
+    })
+}
+
+/// UPDATE contract `key` with the full serialized state `state`.
+pub fn update(key: ContractKey, state: Vec<u8>) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Update {
+        key,
+        data: UpdateData::State(State::from(state)),
+    })
+}
+
+/// UPDATE contract `key` with the serialized delta `delta`.
+pub fn update_delta(key: ContractKey, delta: Vec<u8>) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Update {
+        key,
+        data: UpdateData::Delta(StateDelta::from(delta)),
+    })
+}
+
+/// UPDATE contract `key` with `state`, serialized as JSON like every CREAM
+/// contract state.
+pub fn update_state<S: Serialize>(key: ContractKey, state: &S) -> ClientRequest<'static> {
+    update(key, serde_json::to_vec(state).expect("serialization should not fail"))
+}
+
+/// SUBSCRIBE to updates of contract `id`.
+pub fn subscribe(id: ContractInstanceId) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Subscribe { key: id, summary: None })
+}
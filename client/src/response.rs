@@ -0,0 +1,105 @@
+//! Classifying and unpacking what the node sends back.
+
+use freenet_stdlib::client_api::{
+    ClientError, ContractError, ContractResponse, ErrorKind, HostResponse, RequestError,
+};
+use freenet_stdlib::prelude::*;
+
+/// Check if a HostResponse is a ContractResponse::UpdateNotification.
+pub fn is_update_notification(resp: &HostResponse) -> bool {
+    matches!(
+        resp,
+        HostResponse::ContractResponse(ContractResponse::UpdateNotification { .. })
+    )
+}
+
+/// Check if a HostResponse is a SubscribeResponse with subscribed=true.
+pub fn is_subscribe_success(resp: &HostResponse) -> bool {
+    matches!(
+        resp,
+        HostResponse::ContractResponse(ContractResponse::SubscribeResponse {
+            subscribed: true,
+            ..
+        })
+    )
+}
+
+/// Check if a HostResponse is a PutResponse.
+pub fn is_put_response(resp: &HostResponse) -> bool {
+    matches!(
+        resp,
+        HostResponse::ContractResponse(ContractResponse::PutResponse { .. })
+    )
+}
+
+/// Check if a HostResponse is an UpdateResponse.
+pub fn is_update_response(resp: &HostResponse) -> bool {
+    matches!(
+        resp,
+        HostResponse::ContractResponse(ContractResponse::UpdateResponse { .. })
+    )
+}
+
+/// Check if a HostResponse is a GetResponse.
+pub fn is_get_response(resp: &HostResponse) -> bool {
+    matches!(
+        resp,
+        HostResponse::ContractResponse(ContractResponse::GetResponse { .. })
+    )
+}
+
+/// The contract error behind a failed request, if that's what it was.
+pub fn contract_error(err: &ClientError) -> Option<&ContractError> {
+    match err.kind() {
+        ErrorKind::RequestError(RequestError::ContractError(e)) => Some(e),
+        _ => None,
+    }
+}
+
+/// Check if an error is the node refusing an UPDATE to `key`; returns the
+/// reason it gave.
+pub fn update_rejection(err: &ClientError, key: &ContractKey) -> Option<String> {
+    match contract_error(err)? {
+        ContractError::Update { key: rejected, cause } if rejected.id() == key.id() => {
+            Some(cause.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Check if an error is the node refusing a PUT of `key`; returns the reason
+/// it gave.
+pub fn put_rejection(err: &ClientError, key: &ContractKey) -> Option<String> {
+    match contract_error(err)? {
+        ContractError::Put { key: rejected, cause } if rejected.id() == key.id() => {
+            Some(cause.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Extract the state bytes from a GetResponse.
+pub fn extract_get_response_state(resp: &HostResponse) -> Option<Vec<u8>> {
+    if let HostResponse::ContractResponse(ContractResponse::GetResponse { state, .. }) = resp {
+        Some(state.as_ref().to_vec())
+    } else {
+        None
+    }
+}
+
+/// Extract UpdateNotification bytes from a HostResponse.
+pub fn extract_notification_bytes(resp: &HostResponse) -> Option<Vec<u8>> {
+    if let HostResponse::ContractResponse(ContractResponse::UpdateNotification { update, .. }) =
+        resp
+    {
+        let bytes = match update {
+            UpdateData::State(s) => s.as_ref().to_vec(),
+            UpdateData::Delta(d) => d.as_ref().to_vec(),
+            UpdateData::StateAndDelta { state, .. } => state.as_ref().to_vec(),
+            _ => return None,
+        };
+        Some(bytes)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,68 @@
+//! Generates the contract artifact manifest for [`artifacts`](src/artifacts.rs).
+//!
+//! Reads the checked-in state schema versions (`contracts/schema-versions`)
+//! and, if the contracts have been built, the manifest `cargo make
+//! contract-manifest` writes next to them: one `name code-hash
+//! schema-version` line per contract. `CREAM_CONTRACT_MANIFEST` overrides
+//! where to look for it.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+const DEFAULT_MANIFEST: &str = "../target/wasm32-unknown-unknown/release/cream_contracts.manifest";
+
+/// Non-comment lines of a whitespace-separated table.
+fn rows(text: &str) -> impl Iterator<Item = Vec<&str>> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| !fields.is_empty())
+}
+
+fn main() {
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let schemas_path = dir.join("../contracts/schema-versions");
+    let manifest_path = env::var("CREAM_CONTRACT_MANIFEST")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| dir.join(DEFAULT_MANIFEST));
+    println!("cargo:rerun-if-changed={}", schemas_path.display());
+    println!("cargo:rerun-if-changed={}", manifest_path.display());
+    println!("cargo:rerun-if-env-changed=CREAM_CONTRACT_MANIFEST");
+
+    let schemas = fs::read_to_string(&schemas_path)
+        .unwrap_or_else(|e| panic!("can't read {}: {e}", schemas_path.display()));
+    let mut out = String::from("pub const SCHEMA_VERSIONS: &[(&str, u32)] = &[\n");
+    for fields in rows(&schemas) {
+        let [name, version] = fields[..] else {
+            panic!("bad line in {}: {}", schemas_path.display(), fields.join(" "));
+        };
+        let version: u32 = version.parse().expect("schema version must be a number");
+        writeln!(out, "    ({name:?}, {version}),").unwrap();
+    }
+    out.push_str("];\n\n");
+
+    // No manifest just means the contracts haven't been built (or were built
+    // without the manifest task); the runtime checks report that.
+    match fs::read_to_string(&manifest_path) {
+        Ok(manifest) => {
+            out.push_str("pub const MANIFEST: Option<&[super::ContractArtifact]> = Some(&[\n");
+            for fields in rows(&manifest) {
+                let [name, code_hash, version] = fields[..] else {
+                    panic!("bad line in {}: {}", manifest_path.display(), fields.join(" "));
+                };
+                let version: u32 = version.parse().expect("schema version must be a number");
+                writeln!(
+                    out,
+                    "    super::ContractArtifact {{ name: {name:?}, code_hash: {code_hash:?}, schema_version: {version} }},"
+                )
+                .unwrap();
+            }
+            out.push_str("]);\n");
+        }
+        Err(_) => out.push_str("pub const MANIFEST: Option<&[super::ContractArtifact]> = None;\n"),
+    }
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("artifact_manifest.rs");
+    fs::write(&out_path, out).unwrap();
+}
@@ -0,0 +1,165 @@
+//! Which contract WASM this build expects.
+//!
+//! The UI, the simulator and the tests embed the contract WASMs from
+//! `target/` with `include_bytes!`, so nothing stops a binary from shipping
+//! WASM built before a change to the state types it was compiled against.
+//! Contract keys derive from the code, so stale WASM doesn't just misbehave:
+//! it puts its state under different keys from everyone else's.
+//!
+//! `cargo make build-contracts` (and `-dev`) ends by writing a manifest of
+//! each contract's blake3 code hash and the state schema version it was
+//! built at, from `contracts/schema-versions`. This crate's build script
+//! compiles that manifest in, and [`check_embedded`] compares embedded code
+//! against it.
+
+use std::fmt;
+
+/// One built contract, as recorded in the manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractArtifact {
+    /// The contract's WASM name: `user` for `cream_user_contract.wasm`.
+    pub name: &'static str,
+    /// blake3 hash of the WASM, 64 hex digits (as `b3sum` prints it).
+    pub code_hash: &'static str,
+    /// State schema version the WASM was built at.
+    pub schema_version: u32,
+}
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/artifact_manifest.rs"));
+}
+
+/// The manifest compiled into this build, or `None` if the contracts hadn't
+/// been built with it.
+pub fn manifest() -> Option<&'static [ContractArtifact]> {
+    generated::MANIFEST
+}
+
+/// The current state schema version of contract `name`.
+pub fn schema_version(name: &str) -> Option<u32> {
+    generated::SCHEMA_VERSIONS.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+
+/// Embedded contract code that can't be trusted to match this build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArtifactError {
+    /// Built without a manifest, so nothing is pinned.
+    NoManifest,
+    /// A contract missing from the manifest or the schema versions.
+    Unknown { name: String },
+    /// The embedded code isn't the code the manifest recorded.
+    CodeMismatch {
+        name: String,
+        embedded: String,
+        pinned: String,
+    },
+    /// The WASM was built before the contract's schema version was bumped.
+    StaleSchema { name: String, built: u32, current: u32 },
+}
+
+const REBUILD: &str = "rebuild with `cargo make build-contracts-dev` (or `build-contracts`), then rebuild this binary";
+
+impl fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactError::NoManifest => {
+                write!(f, "no contract manifest was compiled in; {REBUILD}")
+            }
+            ArtifactError::Unknown { name } => {
+                write!(f, "contract {name} is not in the contract manifest; {REBUILD}")
+            }
+            ArtifactError::CodeMismatch { name, embedded, pinned } => write!(
+                f,
+                "STALE WASM: embedded {name} contract has code hash {embedded}, \
+                 but the manifest pins {pinned}; {REBUILD}"
+            ),
+            ArtifactError::StaleSchema { name, built, current } => write!(
+                f,
+                "STALE WASM: {name} contract was built at state schema v{built}, \
+                 but this build expects v{current}; {REBUILD}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+/// Lowercase hex of a code hash.
+pub fn hex(code_hash: &[u8]) -> String {
+    code_hash.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Check that contract `name`'s code, with blake3 hash `code_hash` (as in a
+/// contract key), is the code the manifest pins, at the current schema
+/// version.
+pub fn check_code_hash(name: &str, code_hash: &[u8]) -> Result<(), ArtifactError> {
+    let manifest = manifest().ok_or(ArtifactError::NoManifest)?;
+    check_against(manifest, schema_version(name), name, code_hash)
+}
+
+/// Check embedded WASM for contract `name` against the manifest.
+pub fn check_embedded(name: &str, wasm: &[u8]) -> Result<(), ArtifactError> {
+    check_code_hash(name, blake3::hash(wasm).as_bytes())
+}
+
+fn check_against(
+    manifest: &[ContractArtifact],
+    current: Option<u32>,
+    name: &str,
+    code_hash: &[u8],
+) -> Result<(), ArtifactError> {
+    let unknown = || ArtifactError::Unknown { name: name.to_string() };
+    let artifact = manifest.iter().find(|a| a.name == name).ok_or_else(unknown)?;
+    let current = current.ok_or_else(unknown)?;
+    let embedded = hex(code_hash);
+    if !embedded.eq_ignore_ascii_case(artifact.code_hash) {
+        return Err(ArtifactError::CodeMismatch {
+            name: name.to_string(),
+            embedded,
+            pinned: artifact.code_hash.to_string(),
+        });
+    }
+    if artifact.schema_version != current {
+        return Err(ArtifactError::StaleSchema {
+            name: name.to_string(),
+            built: artifact.schema_version,
+            current,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_code_must_match_the_manifest_and_schema() {
+        let wasm = b"\0asm fake contract";
+        let hash = hex(blake3::hash(wasm).as_bytes());
+        let hash: &'static str = Box::leak(hash.into_boxed_str());
+        let manifest = [ContractArtifact { name: "user", code_hash: hash, schema_version: 2 }];
+        let code = blake3::hash(wasm);
+
+        assert_eq!(check_against(&manifest, Some(2), "user", code.as_bytes()), Ok(()));
+        assert!(matches!(
+            check_against(&manifest, Some(2), "user", blake3::hash(b"rebuilt").as_bytes()),
+            Err(ArtifactError::CodeMismatch { .. })
+        ));
+        assert_eq!(
+            check_against(&manifest, Some(3), "user", code.as_bytes()),
+            Err(ArtifactError::StaleSchema { name: "user".into(), built: 2, current: 3 })
+        );
+        assert_eq!(
+            check_against(&manifest, Some(2), "inbox", code.as_bytes()),
+            Err(ArtifactError::Unknown { name: "inbox".into() })
+        );
+    }
+
+    #[test]
+    fn every_contract_has_a_schema_version() {
+        for name in ["directory", "storefront", "user", "inbox", "market_directory", "name_registry"] {
+            assert!(schema_version(name).is_some(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Balance attestations: a customer's signed statement of their CURD balance,
+//! carried on large orders.
+//!
+//! The storefront contract can't read the customer's user contract, so on its
+//! own it has to trust the client's balance check. Instead the customer's
+//! delegate derives the balance from the user contract state it is handed —
+//! refusing states that don't verify against its own key — and signs a
+//! [`BalanceAttestation`]. A storefront with an
+//! [`attestation_threshold`](crate::storefront::StorefrontInfo::attestation_threshold)
+//! takes no new order above it without a fresh attestation that
+//! [covers](BalanceAttestation::covers) the deposit.
+//!
+//! The attestation also records how many ledger entries the balance was
+//! derived from, so a supplier can compare it with the live user contract.
+
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::Signature;
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::order::Order;
+use crate::timestamps::max_clock_skew;
+use crate::user_contract::UserContractState;
+
+/// How long before an order its attestation may have been made.
+pub const MAX_ATTESTATION_AGE_MINUTES: i64 = 60;
+
+/// A user's signed balance, as derived from their user contract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceAttestation {
+    /// Whose balance; the signer.
+    pub user: UserId,
+    /// Ledger balance (checkpoint plus live entries, conflicting debits
+    /// left out) when attested.
+    pub balance: u64,
+    /// Ledger entries the balance was derived from, folded ones included.
+    pub ledger_entries: u64,
+    pub attested_at: DateTime<Utc>,
+    /// User's signature over [`BalanceAttestation::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BalanceAttestation {
+    /// An unsigned attestation of `state`'s balance at `at`.
+    pub fn of(state: &UserContractState, at: DateTime<Utc>) -> Self {
+        BalanceAttestation {
+            user: state.owner.clone(),
+            balance: state.derive_balance(),
+            ledger_entries: state.checkpoint_tx_count + state.ledger.len() as u64,
+            attested_at: at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    /// Serialize the attestation (excluding signature) for signing/verification.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableAttestation {
+            domain: "cream-balance-attestation-v1",
+            user: &self.user,
+            balance: self.balance,
+            ledger_entries: self.ledger_entries,
+            attested_at: &self.attested_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the attestation is signed by its user.
+    pub fn verify(&self) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            // Dev mode skips signatures, like the rest of the contracts.
+            true
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            self.user.0.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// Whether the attestation backs `order`: it is the customer's, made in
+    /// the [`MAX_ATTESTATION_AGE_MINUTES`] before the order was placed, and
+    /// the balance covers the deposit. The signature is checked separately,
+    /// by [`BalanceAttestation::verify`].
+    pub fn covers(&self, order: &Order) -> bool {
+        self.user == order.customer
+            && self.attested_at >= order.created_at - Duration::minutes(MAX_ATTESTATION_AGE_MINUTES)
+            && self.attested_at <= order.created_at + max_clock_skew()
+            && self.balance >= order.deposit_amount
+    }
+}
+
+#[derive(Serialize)]
+struct SignableAttestation<'a> {
+    domain: &'static str,
+    user: &'a UserId,
+    balance: u64,
+    ledger_entries: u64,
+    attested_at: &'a DateTime<Utc>,
+}
@@ -0,0 +1,431 @@
+//! State integrity checks.
+//!
+//! Contracts validate each state they accept, but only one contract at a
+//! time. An audit re-checks stored states after the fact — every signature
+//! and stamp, each ledger's cached balance — and checks what no single
+//! contract can: that CURD moved between user contracts by double entry, so
+//! every debit in one ledger has the matching credit in its counterparty's,
+//! and vice versa.
+//!
+//! The checks here are pure; fetching states is the caller's job (the
+//! `cream-auditor` tool walks a live network, the integration harness its
+//! test fixtures). Signatures are skipped under the `dev` feature, as in the
+//! contracts.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+use crate::directory::DirectoryState;
+use crate::identity::ROOT_USER_NAME;
+use crate::storefront::{order_signed_by_customer, StorefrontState};
+use crate::user_contract::UserContractState;
+use crate::wallet::TransactionKind;
+
+/// What an audit finding is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// The contract's state could not be fetched or parsed.
+    Unreadable,
+    /// A signature or provenance stamp does not verify.
+    Signature,
+    /// `balance_curds` differs from the balance derived from the ledger.
+    BalanceCache,
+    /// The same ledger entry appears more than once.
+    DuplicateEntry,
+    /// A debit with no matching credit in the receiver's ledger.
+    UnmatchedDebit,
+    /// A credit with no matching debit in the sender's ledger.
+    UnmatchedCredit,
+    /// A voucher credit for a voucher root paid out to another contract.
+    VoucherRedeemedElsewhere,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Benign or explainable, e.g. a known merge race or pruned history.
+    Warning,
+    Error,
+}
+
+/// One problem found in one contract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Which contract, e.g. `"storefront Valley Farm"`.
+    pub contract: String,
+    pub check: Check,
+    pub severity: Severity,
+    pub detail: String,
+}
+
+impl Finding {
+    pub fn error(contract: &str, check: Check, detail: String) -> Self {
+        Finding {
+            contract: contract.to_string(),
+            check,
+            severity: Severity::Error,
+            detail,
+        }
+    }
+
+    pub fn warning(contract: &str, check: Check, detail: String) -> Self {
+        Finding {
+            severity: Severity::Warning,
+            ..Finding::error(contract, check, detail)
+        }
+    }
+}
+
+/// A machine-readable audit report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Contracts whose state was checked, by kind.
+    pub directories: usize,
+    pub storefronts: usize,
+    pub user_contracts: usize,
+    pub findings: Vec<Finding>,
+}
+
+impl AuditReport {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        AuditReport {
+            started_at,
+            finished_at: started_at,
+            directories: 0,
+            storefronts: 0,
+            user_contracts: 0,
+            findings: Vec::new(),
+        }
+    }
+
+    pub fn errors(&self) -> usize {
+        self.findings.iter().filter(|f| f.severity == Severity::Error).count()
+    }
+
+    /// Whether the audit found no errors (warnings are allowed).
+    pub fn is_clean(&self) -> bool {
+        self.errors() == 0
+    }
+}
+
+/// Every directory entry must be signed by its supplier.
+pub fn audit_directory(contract: &str, directory: &DirectoryState) -> Vec<Finding> {
+    directory
+        .entries
+        .values()
+        .filter(|entry| !entry.verify_signature())
+        .map(|entry| {
+            Finding::error(
+                contract,
+                Check::Signature,
+                format!("entry for {} is not signed by its supplier", entry.name),
+            )
+        })
+        .collect()
+}
+
+/// The storefront's info, products and orders must carry valid signatures
+/// and stamps from their owners (the supplier or its staff, or the ordering
+/// customer), and its staff grants must be signed by the supplier.
+pub fn audit_storefront(contract: &str, storefront: &StorefrontState, owner: &VerifyingKey) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if cfg!(feature = "dev") {
+        return findings;
+    }
+    let mut bad = |detail: String| findings.push(Finding::error(contract, Check::Signature, detail));
+    for (key, grant) in &storefront.staff {
+        if grant.key != *key || !grant.verify(owner) {
+            bad(format!("staff grant for {} is not signed by the supplier", grant.name));
+        }
+    }
+    let suppliers = storefront.supplier_keys(owner);
+    if !storefront.info.verify_provenance(&suppliers) {
+        bad("storefront info stamp does not verify".to_string());
+    }
+    for (id, signed) in &storefront.products {
+        if !suppliers.contains(signed.writer(owner)) || !signed.verify_signature(signed.writer(owner)) {
+            bad(format!("product {} is not signed by the supplier", id.0));
+        } else if !signed.verify_provenance(&suppliers) {
+            bad(format!("product {} stamp does not verify", id.0));
+        }
+    }
+    for (id, order) in &storefront.orders {
+        if !order_signed_by_customer(order) {
+            bad(format!("order {} is not signed by its customer", id.0));
+        }
+    }
+    findings
+}
+
+/// The state must be signed by `owner`, its checkpoint, mint records and
+/// stamped credits must verify, the cached balance must match the ledger,
+/// and no entry may appear twice.
+pub fn audit_user_contract(contract: &str, state: &UserContractState, owner: &VerifyingKey) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if !cfg!(feature = "dev") {
+        let checks = [
+            (state.validate(owner), "state is not signed by its owner"),
+            (state.checkpoint_valid(owner), "ledger checkpoint is not signed by the owner"),
+            (state.mints_valid(owner), "mint credits are not backed by signed mint records"),
+            (state.ledger_provenance_valid(), "a stamped credit does not verify"),
+        ];
+        for (ok, detail) in checks {
+            if !ok {
+                findings.push(Finding::error(contract, Check::Signature, detail.to_string()));
+            }
+        }
+    }
+    let derived = state.derive_balance();
+    if state.balance_curds != derived {
+        findings.push(Finding::error(
+            contract,
+            Check::BalanceCache,
+            format!("balance_curds is {} but the ledger gives {}", state.balance_curds, derived),
+        ));
+    }
+    let (_, duplicates) = deduplicated_balance(state);
+    if duplicates > 0 {
+        findings.push(Finding::warning(
+            contract,
+            Check::DuplicateEntry,
+            format!("{} duplicate ledger entries (merge race)", duplicates),
+        ));
+    }
+    findings
+}
+
+/// The balance with repeated entries (same `tx_ref` and kind) counted once,
+/// and how many repeats there were. Freenet merge races can duplicate
+/// entries; this is the balance the ledger was meant to have.
+pub fn deduplicated_balance(state: &UserContractState) -> (u64, usize) {
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    let balance = state.ledger.iter().fold(state.checkpoint_balance, |acc, tx| {
+        if !seen.insert((&tx.tx_ref, &tx.kind)) {
+            duplicates += 1;
+            return acc;
+        }
+        match tx.kind {
+            TransactionKind::Credit => acc.saturating_add(tx.amount),
+            TransactionKind::Debit => acc.saturating_sub(tx.amount),
+        }
+    });
+    (balance, duplicates)
+}
+
+/// Double-entry check across `ledgers` (contract label and state). Each
+/// debit must have a credit with the same `tx_ref` and amount in the
+/// receiver's ledger, and each credit a debit in the sender's. Counterparties
+/// are matched by user name, case-insensitively; entries whose counterparty
+/// isn't among `ledgers` (root issuance, escrow placeholders, users not
+/// audited) aren't checked. A missing entry in a counterparty that has
+/// checkpointed its ledger may have been pruned, so it is only a warning.
+///
+/// A voucher credit must also be the one root paid out: when root is among
+/// `ledgers`, its redemption debit (or checkpoint record) has to name this
+/// contract, so one voucher can't be credited to several.
+pub fn audit_ledgers(ledgers: &[(&str, &UserContractState)]) -> Vec<Finding> {
+    let by_name: HashMap<String, &UserContractState> = ledgers
+        .iter()
+        .map(|(_, state)| (state.name.to_lowercase(), *state))
+        .collect();
+    let root = by_name.get(&ROOT_USER_NAME.to_lowercase());
+
+    let mut findings = Vec::new();
+    for (contract, state) in ledgers {
+        for tx in &state.ledger {
+            let voucher = state.vouchers.iter().find(|v| tx.kind == TransactionKind::Credit && v.tx_ref() == tx.tx_ref);
+            if let (Some(voucher), Some(root)) = (voucher, root) {
+                if let Some(redeemer) = root.voucher_redeemer(&voucher.id) {
+                    if !voucher.redeemed_by(root, &state.name) {
+                        findings.push(Finding::error(
+                            contract,
+                            Check::VoucherRedeemedElsewhere,
+                            format!("voucher {} was paid out to {}, not {}", voucher.id, redeemer, state.name),
+                        ));
+                    }
+                    continue;
+                }
+            }
+            let (counterparty, expected, check) = match tx.kind {
+                TransactionKind::Debit => (&tx.receiver, TransactionKind::Credit, Check::UnmatchedDebit),
+                TransactionKind::Credit => (&tx.sender, TransactionKind::Debit, Check::UnmatchedCredit),
+            };
+            let Some(other) = by_name.get(&counterparty.to_lowercase()) else {
+                continue;
+            };
+            if std::ptr::eq(*other, *state) {
+                continue;
+            }
+            let matched = other
+                .ledger
+                .iter()
+                .any(|t| t.kind == expected && t.tx_ref == tx.tx_ref && t.amount == tx.amount);
+            if matched {
+                continue;
+            }
+            let detail = format!(
+                "{:?} {} of {} CURD has no matching {:?} in {}'s ledger",
+                tx.kind, tx.tx_ref, tx.amount, expected, counterparty
+            );
+            findings.push(if other.checkpoint_tx_count > 0 {
+                Finding::warning(contract, check, detail)
+            } else {
+                Finding::error(contract, check, detail)
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::UserId;
+    use crate::tolls::TollRates;
+    use crate::voucher::{Voucher, VoucherError};
+    use crate::wallet::WalletTransaction;
+    use ed25519_dalek::{Signature, Signer, SigningKey};
+
+    fn tx(kind: TransactionKind, amount: u64, sender: &str, receiver: &str, tx_ref: &str) -> WalletTransaction {
+        WalletTransaction {
+            id: 0,
+            kind,
+            amount,
+            description: String::new(),
+            sender: sender.into(),
+            receiver: receiver.into(),
+            tx_ref: tx_ref.into(),
+            timestamp: "2026-01-01T00:00:00.000Z".into(),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn user(name: &str, ledger: Vec<WalletTransaction>) -> (SigningKey, UserContractState) {
+        let key = SigningKey::from_bytes(&[name.len() as u8; 32]);
+        let mut state = UserContractState {
+            owner: UserId(key.verifying_key()),
+            name: name.into(),
+            origin_supplier: String::new(),
+            current_supplier: String::new(),
+            balance_curds: 0,
+            invited_by: String::new(),
+            ledger,
+            next_tx_id: 0,
+            toll_rates: TollRates::default(),
+            checkpoint_balance: 0,
+            checkpoint_tx_count: 0,
+            checkpoint_at: None,
+            pruned_lightning_hashes: Default::default(),
+            checkpoint_proof: None,
+            spending_key_debits: Default::default(),
+            mint_records: Vec::new(),
+            settlement_statements: Vec::new(),
+            vouchers: Vec::new(),
+            seq: 0,
+            updated_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        state.balance_curds = state.derive_balance();
+        state.signature = key.sign(&state.signable_bytes());
+        (key, state)
+    }
+
+    #[test]
+    fn paired_transfer_is_clean() {
+        let (alice_key, alice) = user(
+            "Alice",
+            vec![
+                tx(TransactionKind::Credit, 100, "__cream_root__", "Alice", "genesis:alice"),
+                tx(TransactionKind::Debit, 40, "Alice", "Bob", "alice:1:1"),
+            ],
+        );
+        let (bob_key, bob) = user("Bob", vec![tx(TransactionKind::Credit, 40, "alice", "Bob", "alice:1:1")]);
+        assert!(audit_user_contract("alice", &alice, &alice_key.verifying_key()).is_empty());
+        assert!(audit_user_contract("bob", &bob, &bob_key.verifying_key()).is_empty());
+        assert!(audit_ledgers(&[("alice", &alice), ("bob", &bob)]).is_empty());
+    }
+
+    #[test]
+    fn one_sided_transfer_and_stale_cache_are_reported() {
+        let (_, alice) = user("Alice", vec![tx(TransactionKind::Credit, 40, "Bob", "Alice", "bob:1:1")]);
+        let (bob_key, mut bob) = user("Bob", vec![tx(TransactionKind::Credit, 100, "__cream_root__", "Bob", "genesis:bob")]);
+        let findings = audit_ledgers(&[("alice", &alice), ("bob", &bob)]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check, Check::UnmatchedCredit);
+        assert_eq!(findings[0].severity, Severity::Error);
+
+        // Pruned history on the counterparty's side only warns
+        bob.checkpoint_tx_count = 10;
+        let findings = audit_ledgers(&[("alice", &alice), ("bob", &bob)]);
+        assert_eq!(findings[0].severity, Severity::Warning);
+
+        bob.balance_curds += 1;
+        let findings = audit_user_contract("bob", &bob, &bob_key.verifying_key());
+        assert!(findings.iter().any(|f| f.check == Check::BalanceCache));
+    }
+
+    #[test]
+    fn a_voucher_credits_only_the_contract_root_paid() {
+        let issuer = SigningKey::from_bytes(&[9u8; 32]);
+        let mut voucher = Voucher {
+            id: "v-1".into(),
+            amount: 50,
+            issuer: UserId(issuer.verifying_key()),
+            issuer_name: "Gary".into(),
+            note: String::new(),
+            issued_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        voucher.signature = issuer.sign(&voucher.signable_bytes());
+        let (_, mut root) = user(
+            ROOT_USER_NAME,
+            vec![
+                tx(TransactionKind::Credit, 50, "Gary", ROOT_USER_NAME, &voucher.escrow_tx_ref()),
+                tx(TransactionKind::Debit, 50, ROOT_USER_NAME, "Alice", &voucher.tx_ref()),
+            ],
+        );
+        // The same voucher redeemed into two contracts; root paid Alice
+        let redeemed = |name: &str| {
+            let (_, mut state) = user(name, vec![voucher.credit(1, name, "2026-01-01T00:01:00.000Z".into())]);
+            state.vouchers.push(voucher.clone());
+            state
+        };
+        let (alice, bob) = (redeemed("Alice"), redeemed("Bob"));
+        assert_eq!(voucher.check_redeemable(&root), Err(VoucherError::AlreadyRedeemed));
+        assert!(voucher.redeemed_by(&root, "alice"));
+
+        let findings = audit_ledgers(&[("root", &root), ("alice", &alice), ("bob", &bob)]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].contract, "bob");
+        assert_eq!(findings[0].check, Check::VoucherRedeemedElsewhere);
+
+        // Root's checkpoint keeps the redeemer once the debit is pruned
+        root.checkpoint(0, Utc::now());
+        assert!(root.ledger.is_empty());
+        assert_eq!(root.voucher_redeemer("v-1"), Some("Alice"));
+        assert_eq!(voucher.check_redeemable(&root), Err(VoucherError::AlreadyRedeemed));
+        let findings = audit_ledgers(&[("root", &root), ("alice", &alice), ("bob", &bob)]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].contract, "bob");
+    }
+
+    #[test]
+    fn duplicate_entries_count_once() {
+        let credit = tx(TransactionKind::Credit, 100, "__cream_root__", "Alice", "genesis:alice");
+        let (_, alice) = user("Alice", vec![credit.clone(), credit]);
+        assert_eq!(deduplicated_balance(&alice), (100, 1));
+    }
+}
@@ -0,0 +1,149 @@
+//! Customers a supplier refuses to serve.
+//!
+//! The supplier signs a [`BlockEntry`] for each customer they block, with a
+//! note the customer sees explaining how to appeal, and the entries travel in
+//! the storefront state. Lifting a block is a newer entry with `blocked`
+//! cleared, so a replayed older entry can't re-impose or lift it.
+//!
+//! A blocked customer's orders already on the storefront stand; new orders
+//! and messages from them are refused when an update is merged in the
+//! contract (see [`StorefrontState::permits`](crate::storefront::StorefrontState::permits)).
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+
+/// The supplier's decision about one customer.
+///
+/// The latest entry per customer (by `updated_at`) is the one in force.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockEntry {
+    pub customer: UserId,
+    /// Cleared when the block is lifted; the entry stays listed.
+    pub blocked: bool,
+    /// Shown to the customer: why they were blocked and how to appeal.
+    #[serde(default)]
+    pub appeal_note: String,
+    pub updated_at: DateTime<Utc>,
+    /// Storefront owner's signature over [`BlockEntry::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableBlockEntry<'a> {
+    domain: &'static str,
+    customer: &'a UserId,
+    blocked: bool,
+    appeal_note: &'a str,
+    updated_at: &'a DateTime<Utc>,
+}
+
+impl BlockEntry {
+    /// Serialize the entry for signing (everything except signature).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableBlockEntry {
+            domain: "cream-block-entry",
+            customer: &self.customer,
+            blocked: self.blocked,
+            appeal_note: &self.appeal_note,
+            updated_at: &self.updated_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the storefront `owner` signed this entry.
+    pub fn verify(&self, owner: &VerifyingKey) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            let _ = owner;
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            owner.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+}
+
+/// Fold `theirs` into `ours`, keeping the latest entry per customer.
+pub fn merge_blocklist(ours: &mut BTreeMap<UserId, BlockEntry>, theirs: BTreeMap<UserId, BlockEntry>) {
+    for (customer, entry) in theirs {
+        match ours.get(&customer) {
+            Some(existing) if existing.updated_at >= entry.updated_at => {}
+            _ => {
+                ours.insert(customer, entry);
+            }
+        }
+    }
+}
+
+/// The block in force against `customer`, if any.
+pub fn block_on<'a>(blocklist: &'a BTreeMap<UserId, BlockEntry>, customer: &UserId) -> Option<&'a BlockEntry> {
+    blocklist.get(customer).filter(|entry| entry.blocked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ed25519_dalek::SigningKey;
+
+    fn entry(customer: &VerifyingKey, blocked: bool, updated_at: DateTime<Utc>) -> BlockEntry {
+        BlockEntry {
+            customer: UserId(*customer),
+            blocked,
+            appeal_note: "Email the farm to appeal".into(),
+            updated_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn latest_entry_wins() {
+        let pat = UserId(SigningKey::from_bytes(&[2u8; 32]).verifying_key());
+        let now = Utc::now();
+
+        let mut blocklist = BTreeMap::new();
+        let blocked = entry(&pat.0, true, now);
+        merge_blocklist(&mut blocklist, BTreeMap::from([(pat.clone(), blocked.clone())]));
+        assert!(block_on(&blocklist, &pat).is_some());
+
+        let lifted = entry(&pat.0, false, now + Duration::days(7));
+        merge_blocklist(&mut blocklist, BTreeMap::from([(pat.clone(), lifted)]));
+        assert!(block_on(&blocklist, &pat).is_none());
+
+        // A replayed older block doesn't re-impose it
+        merge_blocklist(&mut blocklist, BTreeMap::from([(pat.clone(), blocked)]));
+        assert!(block_on(&blocklist, &pat).is_none());
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn only_the_owner_may_block() {
+        use ed25519_dalek::Signer;
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let pat = SigningKey::from_bytes(&[2u8; 32]);
+
+        let mut blocked = entry(&pat.verifying_key(), true, Utc::now());
+        blocked.signature = owner.sign(&blocked.signable_bytes());
+        assert!(blocked.verify(&owner.verifying_key()));
+
+        // The customer can't lift their own block
+        let mut lifted = BlockEntry { blocked: false, ..blocked.clone() };
+        lifted.signature = pat.sign(&lifted.signable_bytes());
+        assert!(!lifted.verify(&owner.verifying_key()));
+        blocked.appeal_note.clear();
+        assert!(!blocked.verify(&owner.verifying_key()));
+    }
+}
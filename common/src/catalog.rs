@@ -0,0 +1,519 @@
+//! Product interchange format for bulk import and export.
+//!
+//! Suppliers with a large range keep it in a spreadsheet. A [`ProductRecord`]
+//! is one row of it: the fields a supplier edits, without ids they can't
+//! know or signatures. Files are either CSV with a header row naming the
+//! columns (in any order, case-insensitive; see [`CSV_COLUMNS`]) or a JSON
+//! array of records. Exported files re-import cleanly, updating the products
+//! they came from by `id`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::currency::Curds;
+use crate::limits::{MAX_DESCRIPTION_LEN, MAX_NAME_LEN, MAX_PRODUCTS_PER_STOREFRONT};
+use crate::product::{AttributeKind, Product, ProductAttributes, ProductCategory, ProductId, Subcategory};
+use crate::storefront::SignedProduct;
+
+/// Columns of an exported CSV file, in order. Only `name`, `category`,
+/// `price_curd` and `quantity_total` are required on import.
+pub const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "name",
+    "category",
+    "subcategory",
+    "price_curd",
+    "quantity_total",
+    "fat_percent",
+    "aging_days",
+    "description",
+];
+
+/// One product in an import or export file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProductRecord {
+    /// The product this row updates; empty to add a new product.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    /// Category label, e.g. `Cheese`; unknown labels become `Other`.
+    pub category: String,
+    /// Subcategory label under `category`, e.g. `Hard`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subcategory: Option<String>,
+    /// In curds.
+    pub price_curd: Curds,
+    pub quantity_total: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fat_percent: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aging_days: Option<u32>,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// A row that can't be imported, and why. `row` counts as a spreadsheet
+/// does: the CSV header is row 1; JSON entries count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportError {
+    pub row: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.row == 0 {
+            write!(f, "{}", self.reason)
+        } else {
+            write!(f, "row {}: {}", self.row, self.reason)
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+fn file_error(reason: impl Into<String>) -> Vec<ImportError> {
+    vec![ImportError { row: 0, reason: reason.into() }]
+}
+
+impl ProductRecord {
+    pub fn from_product(product: &Product) -> Self {
+        let (category, subcategory) = product.taxonomy();
+        ProductRecord {
+            id: Some(product.id.0.clone()),
+            name: product.name.clone(),
+            category: category.label().to_string(),
+            subcategory: subcategory.map(|s| s.label().to_string()),
+            price_curd: product.price_curd,
+            quantity_total: product.quantity_total,
+            fat_percent: product.attributes.fat_percent,
+            aging_days: product.attributes.aging_days,
+            description: product.description.clone(),
+        }
+    }
+
+    /// Check the record and resolve its category labels.
+    fn taxonomy(&self) -> Result<(ProductCategory, Option<Subcategory>), String> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("name is empty".into());
+        }
+        if name.len() > MAX_NAME_LEN {
+            return Err(format!("name is {} bytes (max {MAX_NAME_LEN})", name.len()));
+        }
+        if self.description.len() > MAX_DESCRIPTION_LEN {
+            return Err(format!(
+                "description is {} bytes (max {MAX_DESCRIPTION_LEN})",
+                self.description.len()
+            ));
+        }
+        if self.price_curd == 0 {
+            return Err("price_curd must be at least 1".into());
+        }
+        let label = self.category.trim();
+        if label.is_empty() {
+            return Err("category is empty".into());
+        }
+        let category = ProductCategory::from_label(label);
+        let subcategory = match self.subcategory.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(sub) => Some(category.subcategory_from_label(sub).ok_or_else(|| {
+                format!("{sub:?} is not a type of {}", category.label())
+            })?),
+        };
+        let attributes = category.attributes();
+        if let Some(fat) = self.fat_percent {
+            if !attributes.contains(&AttributeKind::FatPercent) {
+                return Err(format!("fat_percent doesn't apply to {}", category.label()));
+            }
+            if !(0.0..=100.0).contains(&fat) {
+                return Err(format!("fat_percent {fat} is not between 0 and 100"));
+            }
+        }
+        if self.aging_days.is_some() && !attributes.contains(&AttributeKind::AgingDays) {
+            return Err(format!("aging_days doesn't apply to {}", category.label()));
+        }
+        Ok((category, subcategory))
+    }
+
+    /// Write the record's fields onto `product`. Returns whether anything changed.
+    fn apply(&self, product: &mut Product) -> Result<bool, String> {
+        let (category, subcategory) = self.taxonomy()?;
+        let attributes = ProductAttributes {
+            fat_percent: self.fat_percent,
+            aging_days: self.aging_days,
+            extra: product.attributes.extra.clone(),
+        };
+        let before = product.clone();
+        product.name = self.name.trim().to_string();
+        product.description = self.description.trim().to_string();
+        product.category = category;
+        product.subcategory = subcategory;
+        product.attributes = attributes;
+        product.price_curd = self.price_curd;
+        product.quantity_total = self.quantity_total;
+        Ok(*product != before)
+    }
+}
+
+/// Export products as CSV, one row each, with a [`CSV_COLUMNS`] header.
+pub fn records_to_csv(records: &[ProductRecord]) -> String {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push_str("\r\n");
+    for r in records {
+        let fields = [
+            r.id.clone().unwrap_or_default(),
+            r.name.clone(),
+            r.category.clone(),
+            r.subcategory.clone().unwrap_or_default(),
+            r.price_curd.to_string(),
+            r.quantity_total.to_string(),
+            r.fat_percent.map(|f| f.to_string()).unwrap_or_default(),
+            r.aging_days.map(|d| d.to_string()).unwrap_or_default(),
+            r.description.clone(),
+        ];
+        let quoted: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&quoted.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Export products as a pretty-printed JSON array.
+pub fn records_to_json(records: &[ProductRecord]) -> String {
+    serde_json::to_string_pretty(records).expect("serialization should not fail")
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) || field.trim() != field {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Split CSV text into rows of fields (RFC 4180: quoted fields may hold
+/// commas, newlines and doubled quotes). Blank lines are dropped; each row
+/// keeps its 1-based line number.
+fn split_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, Vec<ImportError>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (in_quotes, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            (true, '"') => in_quotes = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+            (false, '"') if field.is_empty() => in_quotes = true,
+            (false, ',') => row.push(std::mem::take(&mut field)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n' | '\r') => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|f| !f.is_empty()) {
+                    rows.push((row_line, std::mem::take(&mut row)));
+                }
+                line += 1;
+                row_line = line;
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(vec![ImportError { row: row_line, reason: "unterminated quoted field".into() }]);
+    }
+    row.push(field);
+    if row.iter().any(|f| !f.is_empty()) {
+        rows.push((row_line, row));
+    }
+    Ok(rows)
+}
+
+fn parse_csv(text: &str) -> Result<Vec<(usize, ProductRecord)>, Vec<ImportError>> {
+    let mut rows = split_csv(text)?.into_iter();
+    let Some((_, header)) = rows.next() else {
+        return Err(file_error("the file is empty"));
+    };
+    let mut columns = BTreeMap::new();
+    for (i, name) in header.iter().enumerate() {
+        let name = name.trim().to_ascii_lowercase();
+        if !CSV_COLUMNS.contains(&name.as_str()) {
+            return Err(file_error(format!("unknown column {name:?}")));
+        }
+        if columns.insert(name.clone(), i).is_some() {
+            return Err(file_error(format!("column {name:?} appears twice")));
+        }
+    }
+    for required in ["name", "category", "price_curd", "quantity_total"] {
+        if !columns.contains_key(required) {
+            return Err(file_error(format!("missing column {required:?}")));
+        }
+    }
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (row, fields) in rows {
+        let cell = |name: &str| -> &str {
+            columns
+                .get(name)
+                .and_then(|&i| fields.get(i))
+                .map(|f| f.trim())
+                .unwrap_or("")
+        };
+        let optional = |name: &str| Some(cell(name).to_string()).filter(|s| !s.is_empty());
+        fn number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| format!("{name} {value:?} is not a number"))
+        }
+        fn optional_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<Option<T>, String> {
+            if value.is_empty() { Ok(None) } else { number(name, value).map(Some) }
+        }
+        let record = (|| {
+            Ok::<_, String>(ProductRecord {
+                id: optional("id"),
+                name: cell("name").to_string(),
+                category: cell("category").to_string(),
+                subcategory: optional("subcategory"),
+                price_curd: number("price_curd", cell("price_curd"))?,
+                quantity_total: number("quantity_total", cell("quantity_total"))?,
+                fat_percent: optional_number("fat_percent", cell("fat_percent"))?,
+                aging_days: optional_number("aging_days", cell("aging_days"))?,
+                description: cell("description").to_string(),
+            })
+        })();
+        match record {
+            Ok(record) => records.push((row, record)),
+            Err(reason) => errors.push(ImportError { row, reason }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_json(text: &str) -> Result<Vec<(usize, ProductRecord)>, Vec<ImportError>> {
+    let values: Vec<serde_json::Value> =
+        serde_json::from_str(text).map_err(|e| file_error(format!("not a JSON array: {e}")))?;
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        match serde_json::from_value(value) {
+            Ok(record) => records.push((i + 1, record)),
+            Err(e) => errors.push(ImportError { row: i + 1, reason: e.to_string() }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse an import file: a JSON array if it starts with `[`, CSV otherwise.
+/// Records are checked individually; every bad row is reported.
+pub fn parse_records(text: &str) -> Result<Vec<(usize, ProductRecord)>, Vec<ImportError>> {
+    let records = if text.trim_start_matches('\u{feff}').trim_start().starts_with('[') {
+        parse_json(text)?
+    } else {
+        parse_csv(text)?
+    };
+    if records.is_empty() {
+        return Err(file_error("the file has no products"));
+    }
+    let mut ids = BTreeSet::new();
+    let errors: Vec<ImportError> = records
+        .iter()
+        .filter_map(|(row, record)| {
+            let reason = match record.taxonomy() {
+                Err(reason) => reason,
+                Ok(_) => match &record.id {
+                    Some(id) if !ids.insert(id.clone()) => format!("id {id:?} appears twice"),
+                    _ => return None,
+                },
+            };
+            Some(ImportError { row: *row, reason })
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(records)
+    } else {
+        Err(errors)
+    }
+}
+
+/// The products an import adds or changes, unsigned, for the caller to sign
+/// and merge into its storefront in one update.
+///
+/// A record whose `id` names an existing product updates it (keeping its
+/// `created_at`); records without an id, or with one this storefront doesn't
+/// have, add a new product. Records that change nothing are skipped, so
+/// re-importing an export is a no-op.
+pub fn plan_import(
+    records: &[(usize, ProductRecord)],
+    existing: &BTreeMap<ProductId, SignedProduct>,
+    now: DateTime<Utc>,
+) -> Result<Vec<Product>, Vec<ImportError>> {
+    let mut products = Vec::new();
+    let mut errors = Vec::new();
+    let mut added = 0;
+    for (row, record) in records {
+        let current = record
+            .id
+            .as_ref()
+            .and_then(|id| existing.get(&ProductId(id.clone())));
+        let mut product = match current {
+            Some(signed) => signed.product.clone(),
+            None => {
+                added += 1;
+                Product {
+                    id: ProductId(format!("p-{}-{added}", now.timestamp_millis())),
+                    name: String::new(),
+                    description: String::new(),
+                    category: ProductCategory::Other(String::new()),
+                    subcategory: None,
+                    attributes: ProductAttributes::default(),
+                    price_curd: 0,
+                    quantity_total: 0,
+                    expiry_date: None,
+                    updated_at: now,
+                    created_at: now,
+                    low_stock: None,
+                    paused: false,
+                    special: None,
+                    wholesale: None,
+                    extra: Default::default(),
+                }
+            }
+        };
+        match record.apply(&mut product) {
+            Ok(changed) if changed || current.is_none() => {
+                // A new price can leave a special no cheaper than the listing
+                if product.special.is_some_and(|special| !special.is_valid(product.price_curd)) {
+                    product.special = None;
+                }
+                product.updated_at = now;
+                products.push(product);
+            }
+            Ok(_) => {}
+            Err(reason) => errors.push(ImportError { row: *row, reason }),
+        }
+    }
+    let total = existing.len() + added;
+    if total > MAX_PRODUCTS_PER_STOREFRONT {
+        errors.push(ImportError {
+            row: 0,
+            reason: format!(
+                "the import would give the storefront {total} products (max {MAX_PRODUCTS_PER_STOREFRONT})"
+            ),
+        });
+    }
+    if errors.is_empty() {
+        Ok(products)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(product: Product) -> SignedProduct {
+        SignedProduct {
+            product,
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            provenance: None,
+            price_history: Vec::new(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn csv_export_round_trips_and_reimport_changes_nothing() {
+        let now = Utc::now();
+        let records = vec![
+            ProductRecord {
+                name: "Aged Cheddar".into(),
+                category: "Cheese".into(),
+                subcategory: Some("Hard".into()),
+                price_curd: 1_200,
+                quantity_total: 8,
+                fat_percent: Some(33.5),
+                aging_days: Some(180),
+                description: "Sharp, \"crumbly\",\nclothbound".into(),
+                ..Default::default()
+            },
+            ProductRecord {
+                name: "Raw milk 2L".into(),
+                category: "milk".into(),
+                price_curd: 500,
+                quantity_total: 20,
+                ..Default::default()
+            },
+        ];
+        let parsed = parse_records(&records_to_csv(&records)).unwrap();
+        assert_eq!(parsed.iter().map(|(_, r)| r.clone()).collect::<Vec<_>>(), records);
+        assert_eq!(parsed[1].0, 4, "a quoted newline doesn't shift row numbers");
+
+        let planned = plan_import(&parsed, &BTreeMap::new(), now).unwrap();
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[0].subcategory, Some(Subcategory::Hard));
+        assert_eq!(planned[1].category, ProductCategory::Milk);
+        assert_ne!(planned[0].id, planned[1].id);
+
+        let existing: BTreeMap<_, _> =
+            planned.into_iter().map(|p| (p.id.clone(), signed(p))).collect();
+        let exported: Vec<ProductRecord> =
+            existing.values().map(|s| ProductRecord::from_product(&s.product)).collect();
+        for text in [records_to_csv(&exported), records_to_json(&exported)] {
+            let reparsed = parse_records(&text).unwrap();
+            assert!(plan_import(&reparsed, &existing, now).unwrap().is_empty());
+        }
+
+        let mut cheaper = exported.clone();
+        cheaper[0].price_curd = 1_000;
+        let later = now + chrono::Duration::seconds(1);
+        let planned = plan_import(&parse_records(&records_to_csv(&cheaper)).unwrap(), &existing, later).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].id.0, cheaper[0].id.clone().unwrap());
+        assert_eq!(planned[0].price_curd, 1_000);
+        assert_eq!(planned[0].created_at, now);
+        assert_eq!(planned[0].updated_at, later);
+    }
+
+    #[test]
+    fn every_bad_row_is_reported() {
+        let csv = "Name,Category,Subcategory,Price_CURD,Quantity_Total,Fat_Percent\n\
+                   Butter,Butter,Salted,300,5,82\n\
+                   ,Milk,,100,1,\n\
+                   Greek milk,Milk,Greek,100,1,\n\
+                   Kefir,Kefir,,abc,1,\n\
+                   Quark,Other,,200,1,12\n";
+        let errors = parse_records(csv).unwrap_err();
+        assert_eq!(errors.iter().map(|e| e.row).collect::<Vec<_>>(), vec![5]);
+        assert!(errors[0].to_string().starts_with("row 5: price_curd \"abc\""));
+
+        let csv = csv.replace("abc", "150");
+        let rows: Vec<usize> = parse_records(&csv).unwrap_err().iter().map(|e| e.row).collect();
+        assert_eq!(rows, vec![3, 4, 6]);
+
+        assert!(parse_records("name,colour\nx,red\n").unwrap_err()[0].reason.contains("colour"));
+        assert!(parse_records("name,category\n").is_err());
+        assert!(parse_records("[{\"name\": \"x\"}]").unwrap_err()[0].row == 1);
+    }
+}
@@ -0,0 +1,126 @@
+//! Injectable time source for time-dependent logic.
+//!
+//! Contract-side code takes `now` as a plain argument, which the contracts'
+//! entry points read from [`host_now`]. Off-chain callers — the UI's expiry loop, schedule badges, the
+//! integration harness — read `now` from a [`Clock`] instead of calling
+//! `Utc::now()` directly, so tests can substitute a [`MockClock`] and move
+//! time forward deterministically.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of the current time.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to. Millisecond resolution; shareable
+/// across tasks behind an `Arc`.
+#[derive(Debug)]
+pub struct MockClock {
+    millis: AtomicI64,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self {
+            millis: AtomicI64::new(start.timestamp_millis()),
+        }
+    }
+
+    /// Jump to `at`.
+    pub fn set(&self, at: DateTime<Utc>) {
+        self.millis.store(at.timestamp_millis(), Ordering::SeqCst);
+    }
+
+    /// Move the clock forward (or back, for a negative `by`).
+    pub fn advance(&self, by: Duration) {
+        self.millis.fetch_add(by.num_milliseconds(), Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp_millis(self.millis.load(Ordering::SeqCst))
+            .expect("mock clock out of range")
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for std::sync::Arc<C> {
+    fn now(&self) -> DateTime<Utc> {
+        (**self).now()
+    }
+}
+
+/// The contract host's clock, which contract entry points check updates
+/// against. Under WASM it is the node's. Built natively — the contract
+/// harness, the fuzz smoke test — it is the clock [`set_host_clock`]
+/// installed on this thread, or the system clock; the stdlib's own clock
+/// reads nothing there.
+#[cfg(feature = "contract")]
+pub fn host_now() -> DateTime<Utc> {
+    #[cfg(target_family = "wasm")]
+    {
+        freenet_stdlib::time::now()
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        HOST_CLOCK.with(|clock| clock.borrow().as_ref().map(|clock| clock.now())).unwrap_or_else(|| {
+            let since_epoch = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system clock before 1970");
+            DateTime::from_timestamp(since_epoch.as_secs() as i64, since_epoch.subsec_nanos())
+                .expect("system clock out of range")
+        })
+    }
+}
+
+#[cfg(all(feature = "contract", not(target_family = "wasm")))]
+thread_local! {
+    static HOST_CLOCK: std::cell::RefCell<Option<std::sync::Arc<dyn Clock>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// Have [`host_now`] read `clock` on this thread, or the system clock again
+/// for `None`. Native builds only: under WASM contracts read the node's clock.
+#[cfg(all(feature = "contract", not(target_family = "wasm")))]
+pub fn set_host_clock(clock: Option<std::sync::Arc<dyn Clock>>) {
+    HOST_CLOCK.with(|host| *host.borrow_mut() = clock);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_moves_only_when_told() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::days(2));
+        assert_eq!(clock.now(), start + Duration::days(2));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -0,0 +1,237 @@
+//! Contact details only a supplier's customers can read.
+//!
+//! The phone, email and address in [`StorefrontInfo`] are public to everyone
+//! following the storefront. A supplier can instead seal them into a
+//! [`SealedContact`]: the details are encrypted once under a fresh content
+//! key, and that key is wrapped for the customer of every order still in
+//! progress, under a key the supplier and that customer agree on (X25519
+//! between their ed25519 identities, bound to the order). Anyone else sees
+//! only ciphertext.
+//!
+//! The supplier wraps a copy for themselves, so they can re-seal as orders
+//! come and go. Each seal uses a new content key, so a customer whose orders
+//! are done can't read details changed after them.
+//!
+//! Sealing and opening need the `contact` feature; contracts only carry the
+//! sealed form.
+//!
+//! [`StorefrontInfo`]: crate::storefront::StorefrontInfo
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "contact")]
+use base64::engine::general_purpose::STANDARD as BASE64;
+#[cfg(feature = "contact")]
+use base64::Engine;
+#[cfg(feature = "contact")]
+use chacha20poly1305::aead::{Aead, KeyInit};
+#[cfg(feature = "contact")]
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+#[cfg(feature = "contact")]
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::order::{Order, OrderId};
+
+/// A supplier's contact details, as sealed into a [`SealedContact`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContactDetails {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ContactDetails {
+    pub fn is_empty(&self) -> bool {
+        self.phone.is_none() && self.email.is_none() && self.address.is_none()
+    }
+}
+
+/// A content key encrypted for one reader. Both fields are base64.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WrappedKey {
+    pub nonce: String,
+    pub key: String,
+}
+
+/// Contact details encrypted for the supplier and the customers of their
+/// orders in progress.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedContact {
+    /// Nonce and ciphertext of the [`ContactDetails`] as JSON; base64.
+    pub nonce: String,
+    pub ciphertext: String,
+    /// The content key for the supplier.
+    pub owner: WrappedKey,
+    /// The content key for each order's customer.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub orders: BTreeMap<OrderId, WrappedKey>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The customers who may read sealed contact details, by order: those of
+/// orders that aren't fulfilled, cancelled or expired.
+pub fn active_recipients<'a>(orders: impl IntoIterator<Item = &'a Order>) -> BTreeMap<OrderId, UserId> {
+    orders
+        .into_iter()
+        .filter(|order| !order.status.is_terminal())
+        .map(|order| (order.id.clone(), order.customer.clone()))
+        .collect()
+}
+
+impl SealedContact {
+    /// Whether the content key is wrapped for exactly the orders in
+    /// `recipients`; if not, the supplier should re-seal.
+    pub fn sealed_for(&self, recipients: &BTreeMap<OrderId, UserId>) -> bool {
+        self.orders.keys().eq(recipients.keys())
+    }
+}
+
+#[cfg(feature = "contact")]
+impl SealedContact {
+    /// Seal `details` for `supplier` and the customers in `recipients`.
+    /// `seed` must be fresh random bytes: the content key and every nonce
+    /// are derived from it.
+    pub fn seal(
+        details: &ContactDetails,
+        supplier: &SigningKey,
+        recipients: &BTreeMap<OrderId, UserId>,
+        seed: [u8; 32],
+    ) -> Self {
+        let content_key = blake3::derive_key("cream contact content v1", &seed);
+        let plaintext = serde_json::to_vec(details).expect("serialization should not fail");
+        let nonce = derive_nonce(&seed, b"content");
+        let own_key = agreed_key(supplier, &supplier.verifying_key(), None);
+        SealedContact {
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(encrypt(&content_key, &nonce, &plaintext)),
+            owner: WrappedKey::wrap(&content_key, &own_key, derive_nonce(&seed, b"owner")),
+            orders: recipients
+                .iter()
+                .map(|(order, customer)| {
+                    let wrapping_key = agreed_key(supplier, &customer.0, Some(order));
+                    let nonce = derive_nonce(&seed, order.0.as_bytes());
+                    (order.clone(), WrappedKey::wrap(&content_key, &wrapping_key, nonce))
+                })
+                .collect(),
+            extra: Default::default(),
+        }
+    }
+
+    /// Open the details as `reader`: the storefront's owner `supplier` with
+    /// no `order`, or the customer of `order`. `None` if it isn't theirs to
+    /// read or doesn't decrypt.
+    pub fn open(&self, reader: &SigningKey, supplier: &VerifyingKey, order: Option<&OrderId>) -> Option<ContactDetails> {
+        let wrapped = match order {
+            Some(order) => self.orders.get(order)?,
+            None => &self.owner,
+        };
+        let content_key = wrapped.unwrap(&agreed_key(reader, supplier, order))?;
+        let plaintext = decrypt(&content_key, &self.nonce, &self.ciphertext)?;
+        serde_json::from_slice(&plaintext).ok()
+    }
+
+    /// The same details sealed afresh for `recipients`, by their `supplier`.
+    pub fn reseal(&self, supplier: &SigningKey, recipients: &BTreeMap<OrderId, UserId>, seed: [u8; 32]) -> Option<Self> {
+        let details = self.open(supplier, &supplier.verifying_key(), None)?;
+        Some(Self::seal(&details, supplier, recipients, seed))
+    }
+}
+
+#[cfg(feature = "contact")]
+impl WrappedKey {
+    fn wrap(content_key: &[u8; 32], wrapping_key: &[u8; 32], nonce: [u8; 12]) -> Self {
+        WrappedKey {
+            nonce: BASE64.encode(nonce),
+            key: BASE64.encode(encrypt(wrapping_key, &nonce, content_key)),
+        }
+    }
+
+    fn unwrap(&self, wrapping_key: &[u8; 32]) -> Option<[u8; 32]> {
+        decrypt(wrapping_key, &self.nonce, &self.key)?.try_into().ok()
+    }
+}
+
+/// The key `own` and `other` agree on for `order` (`None` for the
+/// supplier's own copy): X25519 between their identity keys, bound to the
+/// order so each order's customer gets a key of their own.
+#[cfg(feature = "contact")]
+fn agreed_key(own: &SigningKey, other: &VerifyingKey, order: Option<&OrderId>) -> [u8; 32] {
+    let shared = other.to_montgomery().mul_clamped(own.to_scalar_bytes());
+    let mut hasher = blake3::Hasher::new_derive_key("cream contact wrap v1");
+    hasher.update(shared.as_bytes());
+    hasher.update(order.map_or(&b""[..], |order| order.0.as_bytes()));
+    *hasher.finalize().as_bytes()
+}
+
+#[cfg(feature = "contact")]
+fn derive_nonce(seed: &[u8; 32], label: &[u8]) -> [u8; 12] {
+    let mut hasher = blake3::Hasher::new_derive_key("cream contact nonce v1");
+    hasher.update(seed);
+    hasher.update(label);
+    let mut nonce = [0u8; 12];
+    nonce.copy_from_slice(&hasher.finalize().as_bytes()[..12]);
+    nonce
+}
+
+#[cfg(feature = "contact")]
+fn encrypt(key: &[u8; 32], nonce: &[u8; 12], plaintext: &[u8]) -> Vec<u8> {
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(Nonce::from_slice(nonce), plaintext)
+        .expect("encryption should not fail")
+}
+
+#[cfg(feature = "contact")]
+fn decrypt(key: &[u8; 32], nonce: &str, ciphertext: &str) -> Option<Vec<u8>> {
+    let nonce = BASE64.decode(nonce).ok().filter(|nonce| nonce.len() == 12)?;
+    let ciphertext = BASE64.decode(ciphertext).ok()?;
+    ChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .ok()
+}
+
+#[cfg(all(test, feature = "contact"))]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    #[test]
+    fn only_the_supplier_and_their_order_customers_can_open() {
+        let supplier = key(1);
+        let customer = key(2);
+        let stranger = key(3);
+        let order = OrderId("o-1".into());
+        let details = ContactDetails {
+            phone: Some("0412 345 678".into()),
+            address: Some("42 Dairy Lane".into()),
+            ..Default::default()
+        };
+        let recipients = [(order.clone(), UserId(customer.verifying_key()))].into_iter().collect();
+        let sealed = SealedContact::seal(&details, &supplier, &recipients, [7; 32]);
+        let owner = supplier.verifying_key();
+
+        assert_eq!(sealed.open(&supplier, &owner, None), Some(details.clone()));
+        assert_eq!(sealed.open(&customer, &owner, Some(&order)), Some(details.clone()));
+        assert_eq!(sealed.open(&stranger, &owner, Some(&order)), None);
+        assert_eq!(sealed.open(&customer, &owner, Some(&OrderId("o-2".into()))), None);
+        assert!(!sealed.ciphertext.contains("Dairy"));
+
+        // Re-sealed without the order, the customer can't read it any more
+        let resealed = sealed.reseal(&supplier, &BTreeMap::new(), [8; 32]).unwrap();
+        assert!(resealed.sealed_for(&BTreeMap::new()));
+        assert_eq!(resealed.open(&customer, &owner, Some(&order)), None);
+        assert_eq!(resealed.open(&supplier, &owner, None), Some(details));
+    }
+}
@@ -0,0 +1,190 @@
+//! Signed supplier credentials (verification badges).
+//!
+//! A certifier — a food-safety regulator, an industry body, or the guardian
+//! federation itself — attests something about a supplier ("certified raw
+//! milk producer") by signing a [`Credential`] with its published key. The
+//! credential travels in the directory next to the supplier's entry; the
+//! directory contract only accepts credentials from the certifier keys it was
+//! built with, so a badge in the UI means a trusted certifier vouched for it.
+//!
+//! Certifiers can reissue a credential (the newest per supplier, certifier
+//! and kind wins) with a later expiry, or revoke it.
+
+use chrono::{DateTime, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::Signature;
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+
+/// What a credential attests.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CredentialKind {
+    /// Licensed to produce and sell raw milk.
+    RawMilkProducer,
+    /// Certified organic.
+    Organic,
+    /// Holds a food business / dairy licence.
+    FoodSafetyLicence,
+    /// Herd tested free of disease (e.g. TB, brucellosis).
+    HerdHealthTested,
+    Other(String),
+}
+
+impl CredentialKind {
+    pub fn label(&self) -> &str {
+        match self {
+            CredentialKind::RawMilkProducer => "Certified raw milk producer",
+            CredentialKind::Organic => "Certified organic",
+            CredentialKind::FoodSafetyLicence => "Licensed food business",
+            CredentialKind::HerdHealthTested => "Herd health tested",
+            CredentialKind::Other(label) => label,
+        }
+    }
+
+    /// Stable slug used in credential ids.
+    fn slug(&self) -> String {
+        match self {
+            CredentialKind::RawMilkProducer => "raw-milk".into(),
+            CredentialKind::Organic => "organic".into(),
+            CredentialKind::FoodSafetyLicence => "food-safety".into(),
+            CredentialKind::HerdHealthTested => "herd-health".into(),
+            CredentialKind::Other(label) => format!("other:{label}"),
+        }
+    }
+}
+
+/// A certifier's signed attestation about one supplier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credential {
+    pub supplier: UserId,
+    pub kind: CredentialKind,
+    /// The certifier's published key.
+    pub issuer: UserId,
+    /// The certifier's display name, e.g. "NSW Food Authority".
+    pub issuer_name: String,
+    /// Certifier's own reference, e.g. a licence number.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub issued_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Set when the certifier withdraws the credential.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub revoked: bool,
+    /// Issuer's signature over [`Credential::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableCredential<'a> {
+    domain: &'static str,
+    supplier: &'a UserId,
+    kind: &'a CredentialKind,
+    issuer: &'a UserId,
+    issuer_name: &'a str,
+    reference: Option<&'a str>,
+    issued_at: &'a DateTime<Utc>,
+    expires_at: Option<&'a DateTime<Utc>>,
+    revoked: bool,
+}
+
+impl Credential {
+    /// Key the credential is held under in the directory: one slot per
+    /// supplier, issuer and kind.
+    pub fn id(&self) -> String {
+        format!("{}/{}/{}", self.supplier, self.issuer, self.kind.slug())
+    }
+
+    /// Serialize the credential for signing (everything except signature).
+    /// The domain tag keeps a certifier's signatures on other messages from
+    /// passing as credentials.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableCredential {
+            domain: "cream-credential",
+            supplier: &self.supplier,
+            kind: &self.kind,
+            issuer: &self.issuer,
+            issuer_name: &self.issuer_name,
+            reference: self.reference.as_deref(),
+            issued_at: &self.issued_at,
+            expires_at: self.expires_at.as_ref(),
+            revoked: self.revoked,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Verify the credential was signed by its issuer and the issuer is one
+    /// of the trusted `certifiers`.
+    pub fn verify(&self, certifiers: &[UserId]) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            let _ = certifiers;
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            certifiers.contains(&self.issuer)
+                && self.issuer.0.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// Whether the credential should show as a badge at `now`.
+    pub fn is_current(&self, now: DateTime<Utc>) -> bool {
+        !self.revoked && self.expires_at.is_none_or(|expiry| now < expiry)
+    }
+}
+
+/// Parse a comma-separated list of certifier keys (hex), skipping blanks and
+/// anything malformed.
+pub fn parse_certifier_keys(list: &str) -> Vec<UserId> {
+    list.split(',')
+        .filter_map(|hex| hex.trim().parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    #[test]
+    fn credential_verifies_only_for_trusted_issuers() {
+        let certifier = SigningKey::from_bytes(&[7u8; 32]);
+        let issuer = UserId(certifier.verifying_key());
+        let now = Utc::now();
+        let mut credential = Credential {
+            supplier: UserId(SigningKey::from_bytes(&[8u8; 32]).verifying_key()),
+            kind: CredentialKind::RawMilkProducer,
+            issuer: issuer.clone(),
+            issuer_name: "Dairy Safe".into(),
+            reference: Some("RM-1234".into()),
+            issued_at: now,
+            expires_at: Some(now + Duration::days(365)),
+            revoked: false,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        credential.signature = certifier.sign(&credential.signable_bytes());
+
+        assert!(credential.verify(std::slice::from_ref(&issuer)));
+        assert!(credential.is_current(now));
+        assert!(!credential.is_current(now + Duration::days(366)));
+        #[cfg(not(feature = "dev"))]
+        {
+            assert!(!credential.verify(&[]));
+            let mut tampered = credential.clone();
+            tampered.kind = CredentialKind::Organic;
+            assert!(!tampered.verify(std::slice::from_ref(&issuer)));
+        }
+
+        let keys = format!(" {issuer} ,nonsense,");
+        assert_eq!(parse_certifier_keys(&keys), vec![issuer]);
+    }
+}
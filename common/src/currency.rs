@@ -1,4 +1,345 @@
-/// Format an amount in curds for display.
-pub fn format_amount(amount_curds: u64) -> String {
-    format!("{amount_curds} CURD")
+//! The CURD denomination, and amount and date formatting.
+//!
+//! Amounts are whole numbers of [`Curds`], the minor unit: [`CURDS_PER_CURD`]
+//! of them make one CURD, as cents make a dollar. Contract state only ever
+//! holds curds; CURD with decimals exists on screen, through
+//! [`format_amount_with`], and in forms, through [`parse_amount`].
+//!
+//! Everything shown to users goes through a [`Locale`], so prices and dates
+//! follow the reader's conventions: `1,250.50 CURD` and `17 Oct 2026, 3:05 pm`
+//! in Australia, `1.250,50 CURD` and `17. Okt. 2026, 15:05` in Germany.
+//! How many decimals an amount shows is the reader's [`Precision`].
+
+use std::fmt;
+
+use chrono::{Datelike, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// An amount in curds, the minor unit of CURD. Every price, fee, deposit and
+/// balance in contract state is one.
+pub type Curds = u64;
+
+/// Decimal places in one CURD.
+pub const CURD_DECIMALS: u32 = 2;
+
+/// Curds in one CURD.
+pub const CURDS_PER_CURD: Curds = 10u64.pow(CURD_DECIMALS);
+
+/// How many decimal places amounts are shown with. Chosen by the user;
+/// amounts themselves are always exact.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Precision {
+    /// Decimals only where there are any: `5 CURD`, `5.50 CURD`.
+    #[default]
+    Auto,
+    /// Always to the curd: `5.00 CURD`.
+    Curds,
+    /// Rounded to the nearest CURD, halves up: `6 CURD` for 5.50.
+    Whole,
+}
+
+impl Precision {
+    pub const ALL: [Precision; 3] = [Precision::Auto, Precision::Curds, Precision::Whole];
+
+    /// Stable name, for pickers and storage.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Precision::Auto => "auto",
+            Precision::Curds => "curds",
+            Precision::Whole => "whole",
+        }
+    }
+
+    pub fn from_tag(tag: &str) -> Option<Precision> {
+        Precision::ALL.into_iter().find(|p| p.tag() == tag)
+    }
+}
+
+/// A display locale. `en-AU` is the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en-AU")]
+    EnAu,
+    #[serde(rename = "de-DE")]
+    DeDe,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::EnAu, Locale::DeDe];
+
+    /// BCP 47 language tag.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::EnAu => "en-AU",
+            Locale::DeDe => "de-DE",
+        }
+    }
+
+    /// The locale's name in its own language, for pickers.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Locale::EnAu => "English (Australia)",
+            Locale::DeDe => "Deutsch",
+        }
+    }
+
+    /// The closest supported locale for a language tag such as `de`,
+    /// `en-GB` or a POSIX `de_AT.UTF-8`. Only the language is matched.
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let language = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::EnAu),
+            "de" => Some(Locale::DeDe),
+            _ => None,
+        }
+    }
+
+    fn group_separator(self) -> char {
+        match self {
+            Locale::EnAu => ',',
+            Locale::DeDe => '.',
+        }
+    }
+
+    fn decimal_separator(self) -> char {
+        match self {
+            Locale::EnAu => '.',
+            Locale::DeDe => ',',
+        }
+    }
+
+    fn month_abbrev(self, month0: u32) -> &'static str {
+        const EN: [&str; 12] = [
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ];
+        const DE: [&str; 12] = [
+            "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.", "Dez.",
+        ];
+        match self {
+            Locale::EnAu => EN[month0 as usize],
+            Locale::DeDe => DE[month0 as usize],
+        }
+    }
+
+    fn weekday_abbrev(self, from_monday: u32) -> &'static str {
+        const EN: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+        const DE: [&str; 7] = ["Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa.", "So."];
+        match self {
+            Locale::EnAu => EN[from_monday as usize],
+            Locale::DeDe => DE[from_monday as usize],
+        }
+    }
+}
+
+/// Format an amount in curds for display (en-AU).
+pub fn format_amount(amount_curds: Curds) -> String {
+    format_amount_in(amount_curds, Locale::default())
+}
+
+/// Format an amount in curds for display in `locale`, with digit grouping.
+pub fn format_amount_in(amount_curds: Curds, locale: Locale) -> String {
+    format_amount_with(amount_curds, locale, Precision::default())
+}
+
+/// Format an amount in curds as CURD in `locale`, to `precision`:
+/// `1,250.50 CURD`.
+pub fn format_amount_with(amount_curds: Curds, locale: Locale, precision: Precision) -> String {
+    let (whole, fraction) = match precision {
+        Precision::Whole => {
+            let rounded = amount_curds.saturating_add(CURDS_PER_CURD / 2) / CURDS_PER_CURD;
+            (rounded, None)
+        }
+        Precision::Auto if amount_curds.is_multiple_of(CURDS_PER_CURD) => (amount_curds / CURDS_PER_CURD, None),
+        _ => (amount_curds / CURDS_PER_CURD, Some(amount_curds % CURDS_PER_CURD)),
+    };
+    let digits = whole.to_string();
+    let mut text = String::with_capacity(digits.len() + digits.len() / 3 + 8);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            text.push(locale.group_separator());
+        }
+        text.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        text.push(locale.decimal_separator());
+        text.push_str(&format!("{:0width$}", fraction, width = CURD_DECIMALS as usize));
+    }
+    format!("{text} CURD")
+}
+
+/// Why a typed-in amount couldn't be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    Empty,
+    /// Not a plain decimal number.
+    Malformed,
+    /// More decimal places than a CURD has.
+    TooPrecise,
+    TooLarge,
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::Empty => write!(f, "Enter an amount"),
+            AmountError::Malformed => write!(f, "Not an amount"),
+            AmountError::TooPrecise => write!(f, "At most {} decimal places", CURD_DECIMALS),
+            AmountError::TooLarge => write!(f, "Amount is too large"),
+        }
+    }
+}
+
+/// Read an amount of CURD typed by a user, such as `5`, `5.5`, `5,50` or
+/// `12.50 CURD`, into curds. Either `.` or `,` may separate the decimals
+/// (number inputs always give `.`); digit grouping isn't accepted, so
+/// `1,250` is 1.25 CURD, not 1250.
+pub fn parse_amount(input: &str) -> Result<Curds, AmountError> {
+    let mut text = input.trim();
+    let suffix_at = text.len().saturating_sub(4);
+    if text.get(suffix_at..).is_some_and(|suffix| suffix.eq_ignore_ascii_case("curd")) {
+        text = text[..suffix_at].trim_end();
+    }
+    if text.is_empty() {
+        return Err(AmountError::Empty);
+    }
+    let (whole, fraction) = match text.split_once(['.', ',']) {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (text, ""),
+    };
+    let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(AmountError::Malformed);
+    }
+    if fraction.len() > CURD_DECIMALS as usize {
+        return Err(AmountError::TooPrecise);
+    }
+
+    let whole: Curds = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| AmountError::TooLarge)? };
+    let fraction: Curds = format!("{:0<width$}", fraction, width = CURD_DECIMALS as usize)
+        .parse()
+        .map_err(|_| AmountError::Malformed)?;
+    whole
+        .checked_mul(CURDS_PER_CURD)
+        .and_then(|curds| curds.checked_add(fraction))
+        .ok_or(AmountError::TooLarge)
+}
+
+/// `amount_curds` as it would be typed back into a form: `5.50`, or `5` when
+/// whole. [`parse_amount`] reads it back unchanged.
+pub fn amount_input(amount_curds: Curds) -> String {
+    let whole = amount_curds / CURDS_PER_CURD;
+    let fraction = amount_curds % CURDS_PER_CURD;
+    if fraction == 0 {
+        whole.to_string()
+    } else {
+        format!("{}.{:0width$}", whole, fraction, width = CURD_DECIMALS as usize)
+    }
+}
+
+/// Format a calendar date, e.g. `17 Oct 2026` / `17. Okt. 2026`.
+pub fn format_date(date: &impl Datelike, locale: Locale) -> String {
+    let month = locale.month_abbrev(date.month0());
+    match locale {
+        Locale::EnAu => format!("{} {} {}", date.day(), month, date.year()),
+        Locale::DeDe => format!("{}. {} {}", date.day(), month, date.year()),
+    }
+}
+
+/// Format a time of day, e.g. `3:05 pm` / `15:05`.
+pub fn format_time(time: &impl Timelike, locale: Locale) -> String {
+    match locale {
+        Locale::EnAu => {
+            let (pm, hour) = time.hour12();
+            format!("{}:{:02} {}", hour, time.minute(), if pm { "pm" } else { "am" })
+        }
+        Locale::DeDe => format!("{:02}:{:02}", time.hour(), time.minute()),
+    }
+}
+
+/// Format a date and time, e.g. `17 Oct 2026, 3:05 pm`.
+pub fn format_datetime<T: Datelike + Timelike>(at: &T, locale: Locale) -> String {
+    format!("{}, {}", format_date(at, locale), format_time(at, locale))
+}
+
+/// Format a time within the coming week, e.g. `Sat 3:05 pm` / `Sa. 15:05`.
+pub fn format_weekday_time<T: Datelike + Timelike>(at: &T, locale: Locale) -> String {
+    let weekday = locale.weekday_abbrev(at.weekday().num_days_from_monday());
+    format!("{} {}", weekday, format_time(at, locale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn amounts_and_dates_follow_the_locale() {
+        assert_eq!(format_amount(0), "0 CURD");
+        assert_eq!(format_amount(999), "9.99 CURD");
+        assert_eq!(format_amount(125_000), "1,250 CURD");
+        assert_eq!(format_amount_in(123_456_750, Locale::DeDe), "1.234.567,50 CURD");
+
+        let at = Utc.with_ymd_and_hms(2026, 10, 17, 15, 5, 0).unwrap();
+        assert_eq!(format_datetime(&at, Locale::EnAu), "17 Oct 2026, 3:05 pm");
+        assert_eq!(format_datetime(&at, Locale::DeDe), "17. Okt. 2026, 15:05");
+        assert_eq!(format_weekday_time(&at, Locale::EnAu), "Sat 3:05 pm");
+        assert_eq!(format_weekday_time(&at, Locale::DeDe), "Sa. 15:05");
+        let midnight = Utc.with_ymd_and_hms(2026, 3, 1, 0, 30, 0).unwrap();
+        assert_eq!(format_time(&midnight, Locale::EnAu), "12:30 am");
+    }
+
+    #[test]
+    fn precision_chooses_the_decimals_shown() {
+        let en = Locale::EnAu;
+        assert_eq!(format_amount_with(550, en, Precision::Auto), "5.50 CURD");
+        assert_eq!(format_amount_with(500, en, Precision::Auto), "5 CURD");
+        assert_eq!(format_amount_with(500, en, Precision::Curds), "5.00 CURD");
+        assert_eq!(format_amount_with(5, en, Precision::Curds), "0.05 CURD");
+        assert_eq!(format_amount_with(550, en, Precision::Whole), "6 CURD");
+        assert_eq!(format_amount_with(549, en, Precision::Whole), "5 CURD");
+        assert_eq!(format_amount_with(u64::MAX, en, Precision::Whole), "184,467,440,737,095,516 CURD");
+        for precision in Precision::ALL {
+            assert_eq!(Precision::from_tag(precision.tag()), Some(precision));
+        }
+    }
+
+    #[test]
+    fn typed_amounts_parse_to_curds() {
+        assert_eq!(parse_amount("5"), Ok(500));
+        assert_eq!(parse_amount("5.5"), Ok(550));
+        assert_eq!(parse_amount(" 5.50 "), Ok(550));
+        assert_eq!(parse_amount("5,50"), Ok(550));
+        assert_eq!(parse_amount("0.05"), Ok(5));
+        assert_eq!(parse_amount(".5"), Ok(50));
+        assert_eq!(parse_amount("12.50 CURD"), Ok(1_250));
+        assert_eq!(parse_amount("7curd"), Ok(700));
+        assert_eq!(parse_amount(""), Err(AmountError::Empty));
+        assert_eq!(parse_amount("CURD"), Err(AmountError::Empty));
+        assert_eq!(parse_amount("."), Err(AmountError::Malformed));
+        assert_eq!(parse_amount("-5"), Err(AmountError::Malformed));
+        assert_eq!(parse_amount("1,250.00"), Err(AmountError::Malformed));
+        assert_eq!(parse_amount("5e2"), Err(AmountError::Malformed));
+        assert_eq!(parse_amount("5.555"), Err(AmountError::TooPrecise));
+        assert_eq!(parse_amount("184467440737095516.16"), Err(AmountError::TooLarge));
+        assert_eq!(parse_amount("184467440737095516.15"), Ok(u64::MAX));
+
+        for curds in [0, 5, 50, 500, 550, 1_234_567] {
+            assert_eq!(parse_amount(&amount_input(curds)), Ok(curds));
+        }
+    }
+
+    #[test]
+    fn locale_tags_round_trip_and_match_by_language() {
+        for locale in Locale::ALL {
+            assert_eq!(Locale::from_tag(locale.tag()), Some(locale));
+            let json = serde_json::to_string(&locale).unwrap();
+            assert_eq!(json, format!("\"{}\"", locale.tag()));
+        }
+        assert_eq!(Locale::from_tag("de_AT.UTF-8"), Some(Locale::DeDe));
+        assert_eq!(Locale::from_tag("EN-gb"), Some(Locale::EnAu));
+        assert_eq!(Locale::from_tag("fr-FR"), None);
+        assert_eq!(Locale::from_tag(""), None);
+    }
 }
@@ -0,0 +1,178 @@
+//! Optional home delivery: the zones a supplier serves and what each costs.
+//!
+//! A storefront with no zones is pickup-only. A delivery order names the zone
+//! it was priced against; when the order is placed, the storefront contract
+//! checks that the zone exists, covers the address, and charges the fee the
+//! order carries. Later zone changes don't reach orders already placed.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::currency::Curds;
+use crate::location::GeoLocation;
+use crate::order::{Order, OrderStatus};
+
+/// The area a delivery zone covers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ZoneArea {
+    /// Every address in one of these postcodes.
+    Postcodes(BTreeSet<String>),
+    /// Every point inside this polygon (vertices in order, implicitly closed).
+    Polygon(Vec<GeoLocation>),
+}
+
+/// A delivery zone with a flat fee.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryZone {
+    pub name: String,
+    pub area: ZoneArea,
+    /// Delivery fee in curds.
+    pub fee_curd: Curds,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DeliveryZone {
+    /// Whether an address with this postcode and/or location is in the zone.
+    pub fn covers(&self, postcode: &str, location: Option<&GeoLocation>) -> bool {
+        match &self.area {
+            ZoneArea::Postcodes(postcodes) => postcodes.contains(postcode.trim()),
+            ZoneArea::Polygon(vertices) => location.is_some_and(|p| point_in_polygon(p, vertices)),
+        }
+    }
+}
+
+/// Where and how a delivery order is to be delivered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryDetails {
+    pub address: String,
+    pub postcode: String,
+    /// Coordinates of the address, needed for polygon zones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<GeoLocation>,
+    /// Name of the zone the fee was calculated from.
+    pub zone: String,
+    /// Delivery fee in curds, on top of the order total.
+    pub fee_curd: Curds,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The cheapest zone covering an address, if any.
+pub fn zone_for<'a>(
+    zones: &'a [DeliveryZone],
+    postcode: &str,
+    location: Option<&GeoLocation>,
+) -> Option<&'a DeliveryZone> {
+    zones
+        .iter()
+        .filter(|z| z.covers(postcode, location))
+        .min_by_key(|z| z.fee_curd)
+}
+
+/// Delivery fee for an address, or `None` if no zone serves it.
+pub fn delivery_fee(zones: &[DeliveryZone], postcode: &str, location: Option<&GeoLocation>) -> Option<u64> {
+    zone_for(zones, postcode, location).map(|z| z.fee_curd)
+}
+
+/// Whether a delivery is to an address its named zone covers, at that zone's fee.
+pub fn delivery_served(zones: &[DeliveryZone], delivery: &DeliveryDetails) -> bool {
+    zones.iter().any(|z| {
+        z.name == delivery.zone
+            && z.fee_curd == delivery.fee_curd
+            && z.covers(&delivery.postcode, delivery.location.as_ref())
+    })
+}
+
+/// Whether an order still awaiting hand-over is a delivery the zones don't serve.
+pub fn order_delivery_unserved(zones: &[DeliveryZone], order: &Order) -> bool {
+    let pending = matches!(order.status, OrderStatus::Reserved { .. } | OrderStatus::Paid);
+    pending && order.delivery.as_ref().is_some_and(|d| !delivery_served(zones, d))
+}
+
+/// Ray-casting point-in-polygon test on latitude/longitude. Adequate for
+/// delivery areas, which are small enough for the earth to be flat.
+pub fn point_in_polygon(point: &GeoLocation, vertices: &[GeoLocation]) -> bool {
+    if vertices.len() < 3 {
+        return false;
+    }
+    let (x, y) = (point.longitude, point.latitude);
+    let mut inside = false;
+    let mut j = vertices.len() - 1;
+    for i in 0..vertices.len() {
+        let (xi, yi) = (vertices[i].longitude, vertices[i].latitude);
+        let (xj, yj) = (vertices[j].longitude, vertices[j].latitude);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn postcode_zone(name: &str, postcodes: &[&str], fee: u64) -> DeliveryZone {
+        DeliveryZone {
+            name: name.into(),
+            area: ZoneArea::Postcodes(postcodes.iter().map(|p| p.to_string()).collect()),
+            fee_curd: fee,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn cheapest_covering_zone_sets_the_fee() {
+        let zones = vec![
+            postcode_zone("Town", &["2450"], 5),
+            postcode_zone("District", &["2450", "2452"], 12),
+        ];
+        assert_eq!(delivery_fee(&zones, "2450", None), Some(5));
+        assert_eq!(delivery_fee(&zones, " 2452 ", None), Some(12));
+        assert_eq!(delivery_fee(&zones, "3000", None), None);
+    }
+
+    #[test]
+    fn polygon_zone_covers_inner_points_only() {
+        let square = vec![
+            GeoLocation::new(-30.0, 153.0),
+            GeoLocation::new(-30.0, 153.2),
+            GeoLocation::new(-30.2, 153.2),
+            GeoLocation::new(-30.2, 153.0),
+        ];
+        assert!(point_in_polygon(&GeoLocation::new(-30.1, 153.1), &square));
+        assert!(!point_in_polygon(&GeoLocation::new(-30.3, 153.1), &square));
+
+        let zone = DeliveryZone {
+            name: "Coast".into(),
+            area: ZoneArea::Polygon(square),
+            fee_curd: 8,
+            extra: Default::default(),
+        };
+        assert!(!zone.covers("2450", None));
+    }
+
+    #[test]
+    fn delivery_must_match_its_zone_and_fee() {
+        let zones = vec![postcode_zone("Town", &["2450"], 5)];
+        let mut delivery = DeliveryDetails {
+            address: "1 Main St".into(),
+            postcode: "2450".into(),
+            location: None,
+            zone: "Town".into(),
+            fee_curd: 5,
+            extra: Default::default(),
+        };
+        assert!(delivery_served(&zones, &delivery));
+        delivery.fee_curd = 1;
+        assert!(!delivery_served(&zones, &delivery));
+        delivery.fee_curd = 5;
+        delivery.postcode = "3000".into();
+        assert!(!delivery_served(&zones, &delivery));
+    }
+}
@@ -1,15 +1,26 @@
 use std::collections::BTreeMap;
+use std::fmt;
 
 use chrono::{DateTime, Utc};
-use ed25519_dalek::Signature;
-#[cfg(not(feature = "dev"))]
-use ed25519_dalek::Verifier;
+use ed25519_dalek::{Signature, VerifyingKey};
 use freenet_stdlib::prelude::ContractKey;
 use serde::{Deserialize, Serialize};
 
+use crate::credential::Credential;
 use crate::identity::UserId;
+use crate::limits::{
+    check_count, check_len, check_opt_len, LimitError, MAX_CONTACT_FIELD_LEN, MAX_DESCRIPTION_LEN,
+    MAX_DIRECTORY_ENTRIES, MAX_ENTRY_HISTORY, MAX_NAME_LEN,
+};
 use crate::location::GeoLocation;
+use crate::metrics::ContractMetrics;
+use crate::moderation::{ModerationAction, ModerationRecord};
 use crate::product::ProductCategory;
+use crate::registry::normalize_name;
+use crate::signing::{self, Domain};
+use crate::timestamps::{
+    check_not_future, check_opt_not_future, check_order, check_recent, check_unchanged, TimestampError,
+};
 
 /// A single supplier's entry in the global directory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +40,11 @@ pub struct DirectoryEntry {
     #[serde(default)]
     pub inbox_contract_key: Option<ContractKey>,
     pub updated_at: DateTime<Utc>,
+    /// When the supplier took their current name; earlier claims win when
+    /// two suppliers want the same one. `None` on entries from before names
+    /// were unique, which count from `updated_at`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_claimed_at: Option<DateTime<Utc>>,
     pub signature: Signature,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
@@ -38,7 +54,17 @@ pub struct DirectoryEntry {
 impl DirectoryEntry {
     /// Serialize the signable fields (everything except signature).
     pub fn signable_bytes(&self) -> Vec<u8> {
-        let signable = SignableDirectoryEntry {
+        signing::signable_bytes(Domain::DirectoryEntry, &self.signable_fields())
+    }
+
+    /// The signable fields as bare JSON, as entries were signed before
+    /// [`crate::signing`]. Still accepted before [`crate::signing::legacy_cutoff`].
+    pub fn legacy_signable_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.signable_fields()).expect("serialization should not fail")
+    }
+
+    fn signable_fields(&self) -> SignableDirectoryEntry<'_> {
+        SignableDirectoryEntry {
             supplier: &self.supplier,
             name: &self.name,
             description: &self.description,
@@ -50,8 +76,8 @@ impl DirectoryEntry {
             user_contract_key: self.user_contract_key.as_ref(),
             inbox_contract_key: self.inbox_contract_key.as_ref(),
             updated_at: &self.updated_at,
-        };
-        serde_json::to_vec(&signable).expect("serialization should not fail")
+            name_claimed_at: self.name_claimed_at.as_ref(),
+        }
     }
 
     /// Verify that the entry was signed by the supplier's key.
@@ -63,8 +89,10 @@ impl DirectoryEntry {
         }
         #[cfg(not(feature = "dev"))]
         {
-            let msg = self.signable_bytes();
-            self.supplier.0.verify(&msg, &self.signature).is_ok()
+            signing::verify(&self.supplier.0, &self.signature, self.updated_at, &self.signable_bytes(), || {
+                self.legacy_signable_bytes()
+            })
+            .is_some()
         }
     }
 }
@@ -82,12 +110,27 @@ struct SignableDirectoryEntry<'a> {
     user_contract_key: Option<&'a ContractKey>,
     inbox_contract_key: Option<&'a ContractKey>,
     updated_at: &'a DateTime<Utc>,
+    /// Left out when unset, so entries signed before it existed still verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name_claimed_at: Option<&'a DateTime<Utc>>,
 }
 
 /// The full directory state: a map of supplier entries.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DirectoryState {
     pub entries: BTreeMap<UserId, DirectoryEntry>,
+    /// Guardian moderation records, latest per supplier.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub moderation: BTreeMap<UserId, ModerationRecord>,
+    /// Certifier-signed credentials, keyed by [`Credential::id`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub credentials: BTreeMap<String, Credential>,
+    /// Superseded versions of each supplier's entry, newest first and at most
+    /// [`MAX_ENTRY_HISTORY`] per supplier. They keep their supplier
+    /// signatures, so a client can check who changed what (see
+    /// [`DirectoryState::changes_since`]).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub history: BTreeMap<UserId, Vec<DirectoryEntry>>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
@@ -95,23 +138,317 @@ pub struct DirectoryState {
 
 impl DirectoryState {
     /// Merge another directory state into this one.
-    /// Uses set-union with Last-Writer-Wins per supplier (by `updated_at`).
+    /// Uses set-union with Last-Writer-Wins per supplier (by `updated_at`),
+    /// and by `issued_at` per supplier for moderation records and per slot
+    /// for credentials. Entries replaced by newer ones move into the history.
+    /// Entries, history and credentials of removed suppliers are dropped, and
+    /// entries that lose a name to another supplier are set back: a name
+    /// held here stays with its holder, and a name new to this state goes to
+    /// the earliest claim (see [`DirectoryState::name_holder`]).
     pub fn merge(&mut self, other: DirectoryState) {
+        let incumbents: BTreeMap<String, UserId> = self
+            .entries
+            .values()
+            .map(|e| (normalize_name(&e.name), e.supplier.clone()))
+            .collect();
+        let mut superseded = Vec::new();
         for (id, entry) in other.entries {
             match self.entries.get(&id) {
                 Some(existing) if existing.updated_at >= entry.updated_at => {
                     // Keep existing (it's newer or same age)
+                    superseded.push(entry);
                 }
                 _ => {
-                    self.entries.insert(id, entry);
+                    superseded.extend(self.entries.insert(id, entry));
                 }
             }
         }
+        superseded.extend(other.history.into_values().flatten());
+        for entry in superseded {
+            self.history.entry(entry.supplier.clone()).or_default().push(entry);
+        }
+        for (id, record) in other.moderation {
+            match self.moderation.get(&id) {
+                Some(existing) if existing.issued_at >= record.issued_at => {}
+                _ => {
+                    self.moderation.insert(id, record);
+                }
+            }
+        }
+        for (id, credential) in other.credentials {
+            match self.credentials.get(&id) {
+                Some(existing) if existing.issued_at >= credential.issued_at => {}
+                _ => {
+                    self.credentials.insert(id, credential);
+                }
+            }
+        }
+        let moderation = &self.moderation;
+        let listed = |id: &UserId| {
+            moderation
+                .get(id)
+                .is_none_or(|r| r.action != ModerationAction::Remove)
+        };
+        self.entries.retain(|id, _| listed(id));
+        self.credentials.retain(|_, c| listed(&c.supplier));
+
+        self.tidy_history();
+        self.settle_names(&incumbents);
+        self.tidy_history();
+    }
+
+    /// Keep in the history only versions older than the current entry,
+    /// newest first, whichever order states were merged in.
+    fn tidy_history(&mut self) {
+        let entries = &self.entries;
+        self.history.retain(|id, versions| {
+            let Some(current) = entries.get(id) else { return false };
+            versions.retain(|v| v.updated_at < current.updated_at);
+            versions.sort_by_key(|v| std::cmp::Reverse(v.updated_at));
+            versions.dedup_by(|a, b| a.updated_at == b.updated_at);
+            versions.truncate(MAX_ENTRY_HISTORY);
+            !versions.is_empty()
+        });
     }
 
-    /// Validate all entries have correct signatures.
+    /// Validate all entries, current and historical, have correct signatures
+    /// and historical versions are held under their own supplier.
     pub fn validate_all_signatures(&self) -> bool {
         self.entries.values().all(|e| e.verify_signature())
+            && self.history.iter().all(|(id, versions)| {
+                versions
+                    .iter()
+                    .all(|v| v.supplier == *id && v.verify_signature())
+            })
+    }
+
+    /// Validate every moderation record is signed by the guardians' group key.
+    pub fn validate_moderation(&self, moderator: Option<&VerifyingKey>) -> bool {
+        self.moderation
+            .iter()
+            .all(|(id, r)| r.supplier == *id && r.verify(moderator))
+    }
+
+    /// Validate every credential is held under its own id and signed by one
+    /// of the trusted `certifiers`.
+    pub fn validate_credentials(&self, certifiers: &[UserId]) -> bool {
+        self.credentials
+            .iter()
+            .all(|(id, c)| *id == c.id() && c.verify(certifiers))
+    }
+
+    /// Credentials to show as badges for `supplier` at `now`.
+    pub fn badges(&self, supplier: &UserId, now: DateTime<Utc>) -> Vec<&Credential> {
+        self.credentials
+            .values()
+            .filter(|c| c.supplier == *supplier && c.is_current(now))
+            .collect()
+    }
+
+    /// Entries to list at `now`: those not removed or currently suspended.
+    pub fn listed_entries(&self, now: DateTime<Utc>) -> impl Iterator<Item = &DirectoryEntry> {
+        self.entries.values().filter(move |e| {
+            self.moderation
+                .get(&e.supplier)
+                .is_none_or(|r| !r.hides_entry(now))
+        })
+    }
+
+    /// Check the state against the size limits in [`crate::limits`].
+    pub fn check_limits(&self) -> Result<(), LimitError> {
+        check_count("directory entries", self.entries.len(), MAX_DIRECTORY_ENTRIES)?;
+        check_count("entry histories", self.history.len(), MAX_DIRECTORY_ENTRIES)?;
+        for versions in self.history.values() {
+            check_count("entry history", versions.len(), MAX_ENTRY_HISTORY)?;
+        }
+        for entry in self.entries.values().chain(self.history.values().flatten()) {
+            check_len("supplier name", &entry.name, MAX_NAME_LEN)?;
+            check_len("supplier description", &entry.description, MAX_DESCRIPTION_LEN)?;
+            check_opt_len("postcode", entry.postcode.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+            check_opt_len("locality", entry.locality.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+        }
+        check_count("moderation records", self.moderation.len(), MAX_DIRECTORY_ENTRIES)?;
+        for record in self.moderation.values() {
+            check_len("moderation reason", &record.reason, MAX_DESCRIPTION_LEN)?;
+        }
+        check_count("credentials", self.credentials.len(), MAX_DIRECTORY_ENTRIES)?;
+        for credential in self.credentials.values() {
+            check_len("certifier name", &credential.issuer_name, MAX_NAME_LEN)?;
+            check_opt_len("credential reference", credential.reference.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+            if let crate::credential::CredentialKind::Other(label) = &credential.kind {
+                check_len("credential label", label, MAX_NAME_LEN)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`]. A future-dated entry would win every merge.
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for entry in self.entries.values().chain(self.history.values().flatten()) {
+            check_not_future("directory entry updated_at", entry.updated_at, now)?;
+            check_opt_not_future("directory entry name_claimed_at", entry.name_claimed_at, now)?;
+            check_order("directory entry updated_at", entry.updated_at, "name_claimed_at", entry.claimed_at())?;
+        }
+        for record in self.moderation.values() {
+            check_not_future("moderation issued_at", record.issued_at, now)?;
+        }
+        for credential in self.credentials.values() {
+            check_not_future("credential issued_at", credential.issued_at, now)?;
+        }
+        Ok(())
+    }
+}
+
+/// Another supplier already holds the name an entry tries to take.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameTaken {
+    pub name: String,
+    pub holder: UserId,
+}
+
+impl fmt::Display for NameTaken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "supplier name \"{}\" is already taken by {}", self.name, self.holder)
+    }
+}
+
+impl std::error::Error for NameTaken {}
+
+impl DirectoryEntry {
+    /// When the supplier took the entry's name; see [`DirectoryEntry::name_claimed_at`].
+    pub fn claimed_at(&self) -> DateTime<Utc> {
+        self.name_claimed_at.unwrap_or(self.updated_at)
+    }
+
+    /// Whether this entry's claim to its name beats `other`'s when neither
+    /// holds it yet: the earlier claim wins and ties go to the smaller key.
+    fn outranks(&self, other: &DirectoryEntry) -> bool {
+        (self.claimed_at(), self.supplier.0.as_bytes()) < (other.claimed_at(), other.supplier.0.as_bytes())
+    }
+}
+
+// Supplier names are unique, compared as [`normalize_name`]s, so "Green
+// Farm" and "green farm" are the same name.
+impl DirectoryState {
+    /// The entry holding `name`, if any.
+    pub fn name_holder(&self, name: &str) -> Option<&DirectoryEntry> {
+        let key = normalize_name(name);
+        self.entries
+            .values()
+            .filter(|e| normalize_name(&e.name) == key)
+            .reduce(|holder, e| if e.outranks(holder) { e } else { holder })
+    }
+
+    /// Whether `supplier` may list under `name`: it is free or already theirs.
+    pub fn is_name_available(&self, name: &str, supplier: &UserId) -> bool {
+        self.name_holder(name).is_none_or(|holder| holder.supplier == *supplier)
+    }
+
+    /// Check that no entry in `update` that would replace its supplier's
+    /// entry here takes a name another supplier holds here, whenever they
+    /// claimed it. The merge would set such an entry back; this lets the
+    /// contract tell the registrant instead.
+    pub fn check_names(&self, update: &DirectoryState) -> Result<(), Box<NameTaken>> {
+        for entry in update.entries.values() {
+            if self
+                .entries
+                .get(&entry.supplier)
+                .is_some_and(|current| current.updated_at >= entry.updated_at)
+            {
+                continue;
+            }
+            if let Some(holder) = self.name_holder(&entry.name) {
+                if holder.supplier != entry.supplier {
+                    return Err(Box::new(NameTaken { name: entry.name.clone(), holder: holder.supplier.clone() }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `update` doesn't redate a supplier's claim to the name
+    /// they hold here: earlier claims win, so backdating one would let it
+    /// outrank others.
+    pub fn check_timestamp_progress(&self, update: &DirectoryState) -> Result<(), TimestampError> {
+        for entry in update.entries.values() {
+            let same_name = self.entries.get(&entry.supplier).filter(|current| {
+                current.updated_at < entry.updated_at
+                    && normalize_name(&current.name) == normalize_name(&entry.name)
+            });
+            if let Some(current) = same_name {
+                check_unchanged("directory entry name_claimed_at", current.claimed_at(), entry.claimed_at())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every entry in `update` that claims a name its supplier
+    /// doesn't hold here — a new listing or a rename — claimed it within the
+    /// allowed skew of `now`, the contract host's clock. Claims can't be
+    /// backdated to outrank the ones they arrive with.
+    pub fn check_claims_recent(&self, update: &DirectoryState, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for entry in update.entries.values() {
+            let renamed = self
+                .entries
+                .get(&entry.supplier)
+                .is_none_or(|current| normalize_name(&current.name) != normalize_name(&entry.name));
+            if renamed {
+                check_recent("directory entry name_claimed_at", entry.claimed_at(), now)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Give every name shared by several entries, as concurrent updates can
+    /// leave them, to the supplier in `incumbents` that held it before the
+    /// merge or, for a name new to this state, to the earliest claim. The
+    /// others fall back to their supplier's newest earlier version under a
+    /// free name, or leave the directory if there is none.
+    fn settle_names(&mut self, incumbents: &BTreeMap<String, UserId>) {
+        let incumbent = |e: &DirectoryEntry| incumbents.get(&normalize_name(&e.name)) == Some(&e.supplier);
+        loop {
+            let mut holders: BTreeMap<String, &DirectoryEntry> = BTreeMap::new();
+            for entry in self.entries.values() {
+                let holder = holders.entry(normalize_name(&entry.name)).or_insert(entry);
+                let wins = match (incumbent(entry), incumbent(holder)) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    _ => entry.outranks(holder),
+                };
+                if wins {
+                    *holder = entry;
+                }
+            }
+            let taken: BTreeMap<String, UserId> = holders
+                .into_iter()
+                .map(|(name, holder)| (name, holder.supplier.clone()))
+                .collect();
+            let free = |id: &UserId, name: &str| taken.get(&normalize_name(name)).is_none_or(|holder| holder == id);
+            let losers: Vec<UserId> = self
+                .entries
+                .values()
+                .filter(|e| !free(&e.supplier, &e.name))
+                .map(|e| e.supplier.clone())
+                .collect();
+            if losers.is_empty() {
+                return;
+            }
+            for id in losers {
+                let fallback = self
+                    .history
+                    .get(&id)
+                    .and_then(|versions| versions.iter().find(|v| free(&id, &v.name)).cloned());
+                match fallback {
+                    Some(version) => {
+                        self.entries.insert(id, version);
+                    }
+                    None => {
+                        self.entries.remove(&id);
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -119,12 +456,29 @@ impl DirectoryState {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DirectorySummary {
     pub timestamps: BTreeMap<UserId, DateTime<Utc>>,
+    /// Supplier ID -> `issued_at` of the moderation record held.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub moderation: BTreeMap<UserId, DateTime<Utc>>,
+    /// Credential id -> `issued_at` of the credential held.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub credentials: BTreeMap<String, DateTime<Utc>>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl DirectoryState {
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        ContractMetrics::new(state_bytes)
+            .count("entries", self.entries.len())
+            .count("moderation", self.moderation.len())
+            .count("credentials", self.credentials.len())
+            .count("history", self.history.values().map(Vec::len).sum())
+            .updated("entries", self.entries.values().map(|e| e.updated_at).max())
+    }
+
     pub fn summarize(&self) -> DirectorySummary {
         DirectorySummary {
             timestamps: self
@@ -132,13 +486,25 @@ impl DirectoryState {
                 .iter()
                 .map(|(id, entry)| (id.clone(), entry.updated_at))
                 .collect(),
+            moderation: self
+                .moderation
+                .iter()
+                .map(|(id, record)| (id.clone(), record.issued_at))
+                .collect(),
+            credentials: self
+                .credentials
+                .iter()
+                .map(|(id, credential)| (id.clone(), credential.issued_at))
+                .collect(),
             extra: Default::default(),
         }
     }
 
-    /// Compute a delta: entries in self that are newer than what the summary reports.
+    /// Compute a delta: entries, moderation records and credentials in self
+    /// that are newer than what the summary reports. Newer entries bring
+    /// their history along.
     pub fn delta(&self, summary: &DirectorySummary) -> DirectoryState {
-        let entries = self
+        let entries: BTreeMap<UserId, DirectoryEntry> = self
             .entries
             .iter()
             .filter(|(id, entry)| {
@@ -149,9 +515,606 @@ impl DirectoryState {
             })
             .map(|(id, entry)| (id.clone(), entry.clone()))
             .collect();
+        let moderation = self
+            .moderation
+            .iter()
+            .filter(|(id, record)| {
+                summary
+                    .moderation
+                    .get(*id)
+                    .is_none_or(|ts| record.issued_at > *ts)
+            })
+            .map(|(id, record)| (id.clone(), record.clone()))
+            .collect();
+        let credentials = self
+            .credentials
+            .iter()
+            .filter(|(id, credential)| {
+                summary
+                    .credentials
+                    .get(*id)
+                    .is_none_or(|ts| credential.issued_at > *ts)
+            })
+            .map(|(id, credential)| (id.clone(), credential.clone()))
+            .collect();
+        let history = self
+            .history
+            .iter()
+            .filter(|(id, _)| entries.contains_key(*id))
+            .map(|(id, versions)| (id.clone(), versions.clone()))
+            .collect();
         DirectoryState {
             entries,
+            moderation,
+            credentials,
+            history,
+            extra: Default::default(),
+        }
+    }
+}
+
+// ─── Change history ─────────────────────────────────────────────────────────
+
+/// A part of a directory entry that can change between versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryField {
+    Name,
+    Description,
+    /// Location, postcode or locality.
+    Location,
+    Categories,
+    StorefrontKey,
+    UserContractKey,
+    InboxContractKey,
+}
+
+impl EntryField {
+    pub fn label(self) -> &'static str {
+        match self {
+            EntryField::Name => "name",
+            EntryField::Description => "description",
+            EntryField::Location => "location",
+            EntryField::Categories => "categories",
+            EntryField::StorefrontKey => "storefront",
+            EntryField::UserContractKey => "user contract",
+            EntryField::InboxContractKey => "inbox",
+        }
+    }
+
+    /// Fields pointing at the supplier's contracts. Changing one redirects
+    /// customers elsewhere, which is what a hijacked key would do first.
+    pub fn is_sensitive(self) -> bool {
+        matches!(
+            self,
+            EntryField::StorefrontKey | EntryField::UserContractKey | EntryField::InboxContractKey
+        )
+    }
+
+    /// The fields that differ between two versions of an entry.
+    pub fn changed(old: &DirectoryEntry, new: &DirectoryEntry) -> Vec<EntryField> {
+        let mut fields = Vec::new();
+        if old.name != new.name {
+            fields.push(EntryField::Name);
+        }
+        if old.description != new.description {
+            fields.push(EntryField::Description);
+        }
+        if old.location != new.location || old.postcode != new.postcode || old.locality != new.locality {
+            fields.push(EntryField::Location);
+        }
+        if old.categories != new.categories {
+            fields.push(EntryField::Categories);
+        }
+        if old.storefront_key != new.storefront_key {
+            fields.push(EntryField::StorefrontKey);
+        }
+        if old.user_contract_key != new.user_contract_key {
+            fields.push(EntryField::UserContractKey);
+        }
+        if old.inbox_contract_key != new.inbox_contract_key {
+            fields.push(EntryField::InboxContractKey);
+        }
+        fields
+    }
+}
+
+/// What happened in a [`DirectoryChange`].
+#[derive(Debug, Clone)]
+pub enum DirectoryChangeKind<'a> {
+    /// The supplier's first known entry.
+    Listed,
+    /// The entry replaced `previous`.
+    Updated {
+        previous: &'a DirectoryEntry,
+        fields: Vec<EntryField>,
+    },
+    /// The guardians suspended, removed or reinstated the supplier.
+    Moderated(&'a ModerationRecord),
+}
+
+/// One change to the directory, as reported by [`DirectoryState::changes_since`].
+#[derive(Debug, Clone)]
+pub struct DirectoryChange<'a> {
+    /// The entry as of this change (the current one for moderation).
+    pub entry: &'a DirectoryEntry,
+    pub at: DateTime<Utc>,
+    pub kind: DirectoryChangeKind<'a>,
+}
+
+impl DirectoryChange<'_> {
+    /// Whether the change repointed one of the supplier's contracts.
+    pub fn is_suspicious(&self) -> bool {
+        match &self.kind {
+            DirectoryChangeKind::Updated { fields, .. } => fields.iter().any(|f| f.is_sensitive()),
+            _ => false,
+        }
+    }
+}
+
+impl DirectoryState {
+    /// Changes made after `since`, newest first: entry versions from the
+    /// history and moderation records. A supplier whose history is full may
+    /// have been listed before its oldest kept version, so no `Listed` change
+    /// is reported for it.
+    pub fn changes_since(&self, since: DateTime<Utc>) -> Vec<DirectoryChange<'_>> {
+        let mut changes = Vec::new();
+        for (id, current) in &self.entries {
+            let history = self.history.get(id).map(Vec::as_slice).unwrap_or_default();
+            let versions: Vec<&DirectoryEntry> = std::iter::once(current).chain(history).collect();
+            for (i, entry) in versions.iter().enumerate() {
+                if entry.updated_at <= since {
+                    break;
+                }
+                let kind = match versions.get(i + 1) {
+                    Some(previous) => DirectoryChangeKind::Updated {
+                        previous,
+                        fields: EntryField::changed(previous, entry),
+                    },
+                    None if history.len() < MAX_ENTRY_HISTORY => DirectoryChangeKind::Listed,
+                    None => continue,
+                };
+                changes.push(DirectoryChange { entry, at: entry.updated_at, kind });
+            }
+            if let Some(record) = self.moderation.get(id).filter(|r| r.issued_at > since) {
+                changes.push(DirectoryChange {
+                    entry: current,
+                    at: record.issued_at,
+                    kind: DirectoryChangeKind::Moderated(record),
+                });
+            }
+        }
+        changes.sort_by_key(|c| std::cmp::Reverse(c.at));
+        changes
+    }
+}
+
+// ─── Sharding ───────────────────────────────────────────────────────────────
+
+/// A geographic shard of the directory: one contract per Australian state or
+/// territory, so clients only replicate suppliers near them.
+///
+/// A supplier's shard is derived from their postcode's leading digits (the
+/// Australia Post ranges), not the postcode dataset, so contracts can check it
+/// cheaply. A few border postcodes land in the neighbouring state's shard;
+/// clients subscribe to neighbours as well, so those suppliers stay visible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum DirectoryShard {
+    Act,
+    Nsw,
+    Nt,
+    Qld,
+    Sa,
+    Tas,
+    Vic,
+    Wa,
+}
+
+impl DirectoryShard {
+    pub const ALL: [DirectoryShard; 8] = [
+        DirectoryShard::Act,
+        DirectoryShard::Nsw,
+        DirectoryShard::Nt,
+        DirectoryShard::Qld,
+        DirectoryShard::Sa,
+        DirectoryShard::Tas,
+        DirectoryShard::Vic,
+        DirectoryShard::Wa,
+    ];
+
+    /// State/territory abbreviation, e.g. "NSW".
+    pub fn label(self) -> &'static str {
+        match self {
+            DirectoryShard::Act => "ACT",
+            DirectoryShard::Nsw => "NSW",
+            DirectoryShard::Nt => "NT",
+            DirectoryShard::Qld => "QLD",
+            DirectoryShard::Sa => "SA",
+            DirectoryShard::Tas => "TAS",
+            DirectoryShard::Vic => "VIC",
+            DirectoryShard::Wa => "WA",
+        }
+    }
+
+    /// The shard a 4-digit postcode belongs to, or `None` if it isn't one.
+    pub fn for_postcode(postcode: &str) -> Option<DirectoryShard> {
+        let postcode = postcode.trim();
+        if postcode.len() != 4 {
+            return None;
+        }
+        let n: u16 = postcode.parse().ok()?;
+        let shard = match n {
+            200..=299 | 2600..=2618 | 2900..=2920 => DirectoryShard::Act,
+            800..=999 => DirectoryShard::Nt,
+            1000..=2999 => DirectoryShard::Nsw,
+            3000..=3999 | 8000..=8999 => DirectoryShard::Vic,
+            4000..=4999 | 9000..=9999 => DirectoryShard::Qld,
+            5000..=5999 => DirectoryShard::Sa,
+            6000..=6999 => DirectoryShard::Wa,
+            7000..=7999 => DirectoryShard::Tas,
+            _ => return None,
+        };
+        Some(shard)
+    }
+
+    /// The shard an entry belongs to. Entries without a postcode have none and
+    /// can only live in the unsharded (legacy) directory.
+    pub fn for_entry(entry: &DirectoryEntry) -> Option<DirectoryShard> {
+        entry.postcode.as_deref().and_then(DirectoryShard::for_postcode)
+    }
+
+    /// Shards sharing a land border (or, for Tasmania, the strait) with this one.
+    pub fn neighbours(self) -> &'static [DirectoryShard] {
+        use DirectoryShard::*;
+        match self {
+            Act => &[Nsw],
+            Nsw => &[Act, Qld, Sa, Vic],
+            Nt => &[Qld, Sa, Wa],
+            Qld => &[Nsw, Nt, Sa],
+            Sa => &[Nsw, Nt, Qld, Vic, Wa],
+            Tas => &[Vic],
+            Vic => &[Nsw, Sa, Tas],
+            Wa => &[Nt, Sa],
+        }
+    }
+
+    /// The shards a client in `postcode` should follow: its own plus its
+    /// neighbours. Without a usable postcode, every shard.
+    pub fn nearby(postcode: Option<&str>) -> Vec<DirectoryShard> {
+        match postcode.and_then(DirectoryShard::for_postcode) {
+            Some(home) => {
+                let mut shards = vec![home];
+                shards.extend_from_slice(home.neighbours());
+                shards
+            }
+            None => DirectoryShard::ALL.to_vec(),
+        }
+    }
+}
+
+/// Parameters of a directory shard contract. Deterministic per shard, so every
+/// client derives the same contract key. The unsharded directory uses empty
+/// parameters instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryShardParameters {
+    pub shard: DirectoryShard,
+}
+
+impl DirectoryShardParameters {
+    pub fn new(shard: DirectoryShard) -> Self {
+        DirectoryShardParameters { shard }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("serialization should not fail")
+    }
+
+    /// Parse contract parameters: `None` for the unsharded directory (empty
+    /// parameters), otherwise the shard.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<DirectoryShard>, serde_json::Error> {
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice::<DirectoryShardParameters>(bytes).map(|p| Some(p.shard))
+    }
+}
+
+impl DirectoryState {
+    /// Whether every entry belongs in `shard` (`None` = unsharded, anything goes).
+    pub fn belongs_to_shard(&self, shard: Option<DirectoryShard>) -> bool {
+        match shard {
+            None => true,
+            Some(shard) => self
+                .entries
+                .values()
+                .all(|e| DirectoryShard::for_entry(e) == Some(shard)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn postcodes_map_to_state_shards() {
+        assert_eq!(DirectoryShard::for_postcode("2450"), Some(DirectoryShard::Nsw));
+        assert_eq!(DirectoryShard::for_postcode("2600"), Some(DirectoryShard::Act));
+        assert_eq!(DirectoryShard::for_postcode("2913"), Some(DirectoryShard::Act));
+        assert_eq!(DirectoryShard::for_postcode("0810"), Some(DirectoryShard::Nt));
+        assert_eq!(DirectoryShard::for_postcode("3000"), Some(DirectoryShard::Vic));
+        assert_eq!(DirectoryShard::for_postcode("4000"), Some(DirectoryShard::Qld));
+        assert_eq!(DirectoryShard::for_postcode("7000"), Some(DirectoryShard::Tas));
+        assert_eq!(DirectoryShard::for_postcode("810"), None);
+        assert_eq!(DirectoryShard::for_postcode("abcd"), None);
+        assert_eq!(DirectoryShard::for_postcode("0100"), None);
+    }
+
+    #[test]
+    fn nearby_includes_neighbours_or_everything() {
+        let nearby = DirectoryShard::nearby(Some("7000"));
+        assert_eq!(nearby, vec![DirectoryShard::Tas, DirectoryShard::Vic]);
+        assert_eq!(DirectoryShard::nearby(None).len(), DirectoryShard::ALL.len());
+        assert_eq!(DirectoryShard::nearby(Some("nope")).len(), DirectoryShard::ALL.len());
+
+        // Neighbourhood is symmetric
+        for shard in DirectoryShard::ALL {
+            for n in shard.neighbours() {
+                assert!(n.neighbours().contains(&shard), "{:?} ↔ {:?}", shard, n);
+            }
+        }
+    }
+
+    #[test]
+    fn newest_moderation_record_wins_and_travels_in_delta() {
+        use crate::moderation::ModerationRecord;
+        use chrono::Duration;
+
+        let supplier = UserId(ed25519_dalek::SigningKey::from_bytes(&[4u8; 32]).verifying_key());
+        let record = |action, issued_at| ModerationRecord {
+            supplier: supplier.clone(),
+            action,
+            reason: "test".into(),
+            issued_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        let now = Utc::now();
+        let mut dir = DirectoryState::default();
+        dir.moderation.insert(supplier.clone(), record(ModerationAction::Remove, now));
+
+        let mut older = DirectoryState::default();
+        older.moderation.insert(
+            supplier.clone(),
+            record(ModerationAction::Reinstate, now - Duration::hours(1)),
+        );
+        let stale_summary = older.summarize();
+        dir.merge(older);
+        assert_eq!(dir.moderation[&supplier].action, ModerationAction::Remove);
+
+        let delta = dir.delta(&stale_summary);
+        assert_eq!(delta.moderation[&supplier].action, ModerationAction::Remove);
+        assert!(dir.delta(&dir.summarize()).moderation.is_empty());
+    }
+
+    #[test]
+    fn newest_credential_wins_and_removed_suppliers_lose_theirs() {
+        use crate::credential::{Credential, CredentialKind};
+        use crate::moderation::ModerationRecord;
+        use chrono::Duration;
+
+        let supplier = UserId(ed25519_dalek::SigningKey::from_bytes(&[4u8; 32]).verifying_key());
+        let issuer = UserId(ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]).verifying_key());
+        let now = Utc::now();
+        let credential = |issued_at, revoked| Credential {
+            supplier: supplier.clone(),
+            kind: CredentialKind::RawMilkProducer,
+            issuer: issuer.clone(),
+            issuer_name: "Dairy Safe".into(),
+            reference: None,
+            issued_at,
+            expires_at: None,
+            revoked,
+            signature: Signature::from_bytes(&[0u8; 64]),
             extra: Default::default(),
+        };
+        let with = |c: Credential| {
+            let mut dir = DirectoryState::default();
+            dir.credentials.insert(c.id(), c);
+            dir
+        };
+
+        let mut dir = with(credential(now - Duration::days(1), false));
+        assert_eq!(dir.badges(&supplier, now).len(), 1);
+        let stale_summary = dir.summarize();
+
+        // Revocation is newer, so it replaces the credential; the old one can't come back
+        dir.merge(with(credential(now, true)));
+        dir.merge(with(credential(now - Duration::days(1), false)));
+        assert!(dir.badges(&supplier, now).is_empty());
+        assert_eq!(dir.delta(&stale_summary).credentials.len(), 1);
+        assert!(dir.delta(&dir.summarize()).credentials.is_empty());
+
+        let mut removal = with(credential(now + Duration::days(1), false));
+        removal.moderation.insert(
+            supplier.clone(),
+            ModerationRecord {
+                supplier: supplier.clone(),
+                action: ModerationAction::Remove,
+                reason: "fraud".into(),
+                issued_at: now,
+                signature: Signature::from_bytes(&[0u8; 64]),
+                extra: Default::default(),
+            },
+        );
+        dir.merge(removal);
+        assert!(dir.credentials.is_empty());
+    }
+
+    #[test]
+    fn superseded_entries_move_to_history_and_key_swaps_are_flagged() {
+        use chrono::Duration;
+        use ed25519_dalek::{Signer, SigningKey};
+        use freenet_stdlib::prelude::{ContractCode, Parameters};
+
+        let signer = SigningKey::from_bytes(&[4u8; 32]);
+        let supplier = UserId(signer.verifying_key());
+        let start = Utc::now() - Duration::days(10);
+        let version = |day: i64, name: &str, storefront: u8| {
+            let mut entry = DirectoryEntry {
+                supplier: supplier.clone(),
+                name: name.into(),
+                description: "Raw milk".into(),
+                location: GeoLocation::new(-28.8, 153.3),
+                postcode: Some("2480".into()),
+                locality: None,
+                categories: vec![ProductCategory::Milk],
+                storefront_key: ContractKey::from_params_and_code(
+                    Parameters::from(vec![storefront]),
+                    ContractCode::from(vec![0u8]),
+                ),
+                user_contract_key: None,
+                inbox_contract_key: None,
+                updated_at: start + Duration::days(day),
+                name_claimed_at: None,
+                signature: Signature::from_bytes(&[0u8; 64]),
+                extra: Default::default(),
+            };
+            entry.signature = signer.sign(&entry.signable_bytes());
+            let mut dir = DirectoryState::default();
+            dir.entries.insert(supplier.clone(), entry);
+            dir
+        };
+
+        let mut dir = version(0, "Farm", 1);
+        let stale_summary = dir.summarize();
+        dir.merge(version(2, "Farm", 2));
+        dir.merge(version(1, "Green Farm", 1));
+        assert_eq!(dir.history[&supplier].len(), 2);
+        assert!(dir.validate_all_signatures());
+
+        let changes = dir.changes_since(start + Duration::hours(12));
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].is_suspicious());
+        assert!(matches!(
+            &changes[1].kind,
+            DirectoryChangeKind::Updated { fields, .. } if fields == &[EntryField::Name]
+        ));
+        let everything = dir.changes_since(start - Duration::days(1));
+        assert!(matches!(everything.last().unwrap().kind, DirectoryChangeKind::Listed));
+
+        // A peer behind us gets the history with the newer entry, in any order
+        let mut peer = version(0, "Farm", 1);
+        peer.merge(dir.delta(&stale_summary));
+        assert_eq!(peer.history[&supplier].len(), 2);
+        assert!(peer.history[&supplier][0].updated_at > peer.history[&supplier][1].updated_at);
+
+        // History is bounded
+        for day in 3..10 {
+            dir.merge(version(day, "Farm", 1));
+        }
+        assert_eq!(dir.history[&supplier].len(), MAX_ENTRY_HISTORY);
+        assert!(dir.check_limits().is_ok());
+        #[cfg(not(feature = "dev"))]
+        {
+            dir.history.get_mut(&supplier).unwrap()[0].name = "Forged".into();
+            assert!(!dir.validate_all_signatures());
         }
     }
+
+    #[test]
+    fn names_stay_with_their_holder_and_claims_must_be_fresh() {
+        use chrono::Duration;
+        use freenet_stdlib::prelude::{ContractCode, Parameters};
+
+        let start = Utc::now() - Duration::days(10);
+        let supplier = |seed: u8| UserId(ed25519_dalek::SigningKey::from_bytes(&[seed; 32]).verifying_key());
+        let listing = |seed: u8, name: &str, day: i64| {
+            let entry = DirectoryEntry {
+                supplier: supplier(seed),
+                name: name.into(),
+                description: "Eggs".into(),
+                location: GeoLocation::new(-30.4, 153.0),
+                postcode: Some("2450".into()),
+                locality: None,
+                categories: vec![],
+                storefront_key: ContractKey::from_params_and_code(
+                    Parameters::from(vec![seed]),
+                    ContractCode::from(vec![0u8]),
+                ),
+                user_contract_key: None,
+                inbox_contract_key: None,
+                updated_at: start + Duration::days(day),
+                name_claimed_at: Some(start + Duration::days(day)),
+                signature: Signature::from_bytes(&[0u8; 64]),
+                extra: Default::default(),
+            };
+            DirectoryState { entries: [(entry.supplier.clone(), entry)].into_iter().collect(), ..Default::default() }
+        };
+
+        // Supplier 2 was listed as "Hilltop" before renaming to a name
+        // supplier 1 had already claimed; supplier 3 never had another name.
+        let mut first = listing(1, "Green Farm", 1);
+        first.merge(listing(2, "Hilltop", 0));
+        let mut second = listing(2, "green  farm", 2);
+        second.merge(listing(3, "GREEN FARM", 3));
+        first.merge(second.clone());
+        assert_eq!(first.name_holder("green farm").unwrap().supplier, supplier(1));
+        assert_eq!(first.entries[&supplier(2)].name, "Hilltop");
+        assert!(!first.entries.contains_key(&supplier(3)));
+        assert!(!first.history.contains_key(&supplier(2)));
+        assert!(first.is_name_available("Hilltop", &supplier(2)));
+        assert!(!first.is_name_available("Green Farm", &supplier(4)));
+
+        // Between claims new to a state the earliest wins, but a claim
+        // arriving later doesn't take a name from a holder, however early
+        let mut fresh = DirectoryState::default();
+        fresh.merge(listing(3, "GREEN FARM", 3));
+        fresh.merge(second.clone());
+        assert_eq!(fresh.name_holder("green farm").unwrap().supplier, supplier(3));
+        let mut both = listing(3, "GREEN FARM", 3);
+        both.entries.extend(listing(1, "Green Farm", 1).entries);
+        let mut merged = DirectoryState::default();
+        merged.merge(both);
+        assert_eq!(merged.name_holder("green farm").unwrap().supplier, supplier(1));
+
+        // A later registration under a held name is refused outright
+        let taken = first.check_names(&listing(4, "Green farm", 5)).unwrap_err();
+        assert_eq!(taken.holder, supplier(1));
+        assert!(first.check_names(&listing(4, "Red Farm", 5)).is_ok());
+
+        // New listings and renames must claim their name now, not backdate it
+        let now = start + Duration::days(5);
+        assert!(first.check_claims_recent(&listing(4, "Red Farm", 5), now).is_ok());
+        assert!(matches!(
+            first.check_claims_recent(&listing(4, "Red Farm", 4), now),
+            Err(TimestampError::Stale { .. })
+        ));
+        assert!(first.check_claims_recent(&listing(2, "Hilltop", 4), now).is_ok());
+        assert!(first.check_claims_recent(&listing(2, "Red Farm", 4), now).is_err());
+
+        // The holder may update their entry but not backdate their claim
+        let mut backdated = listing(1, "Green Farm", 6);
+        backdated.entries.get_mut(&supplier(1)).unwrap().name_claimed_at = Some(start);
+        assert!(first.check_timestamp_progress(&backdated).is_err());
+        let mut updated = backdated.clone();
+        updated.entries.get_mut(&supplier(1)).unwrap().name_claimed_at = Some(start + Duration::days(1));
+        assert!(first.check_timestamp_progress(&updated).is_ok());
+        assert!(first.check_names(&updated).is_ok());
+    }
+
+    #[test]
+    fn shard_parameters_are_distinct_and_round_trip() {
+        let keys: std::collections::BTreeSet<Vec<u8>> = DirectoryShard::ALL
+            .iter()
+            .map(|s| DirectoryShardParameters::new(*s).to_bytes())
+            .collect();
+        assert_eq!(keys.len(), DirectoryShard::ALL.len());
+
+        let bytes = DirectoryShardParameters::new(DirectoryShard::Qld).to_bytes();
+        assert_eq!(
+            DirectoryShardParameters::from_bytes(&bytes).unwrap(),
+            Some(DirectoryShard::Qld)
+        );
+        assert_eq!(DirectoryShardParameters::from_bytes(&[]).unwrap(), None);
+    }
 }
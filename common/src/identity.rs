@@ -27,20 +27,29 @@ impl Serialize for UserId {
     }
 }
 
-impl<'de> Deserialize<'de> for UserId {
-    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let s = String::deserialize(deserializer)?;
+impl std::str::FromStr for UserId {
+    type Err = String;
+
+    /// Parse the 64-char hex form produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 64 {
-            return Err(serde::de::Error::custom("UserId hex must be 64 chars"));
+            return Err("UserId hex must be 64 chars".into());
         }
         let mut bytes = [0u8; 32];
         for (i, chunk) in s.as_bytes().chunks(2).enumerate() {
-            let hex = std::str::from_utf8(chunk).map_err(serde::de::Error::custom)?;
-            bytes[i] = u8::from_str_radix(hex, 16).map_err(serde::de::Error::custom)?;
+            let hex = std::str::from_utf8(chunk).map_err(|e| e.to_string())?;
+            bytes[i] = u8::from_str_radix(hex, 16).map_err(|e| e.to_string())?;
         }
         VerifyingKey::from_bytes(&bytes)
             .map(UserId)
-            .map_err(serde::de::Error::custom)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for UserId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
     }
 }
 
@@ -82,6 +91,26 @@ pub struct UserIdentity {
     pub user_id: UserId,
 }
 
+/// A content id: `prefix` then 32 hex digits hashing who (`author`) made
+/// what (`subject`), when, and a random `nonce`. See [`crate::order::OrderId::derive`].
+pub(crate) fn content_id(prefix: &str, author: &UserId, subject: &str, at: chrono::DateTime<chrono::Utc>, nonce: u64) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(author.0.as_bytes());
+    hasher.update(&(subject.len() as u64).to_le_bytes());
+    hasher.update(subject.as_bytes());
+    hasher.update(&at.timestamp().to_le_bytes());
+    hasher.update(&at.timestamp_subsec_nanos().to_le_bytes());
+    hasher.update(&nonce.to_le_bytes());
+    let digest: String = hasher.finalize().as_bytes()[..16].iter().map(|b| format!("{b:02x}")).collect();
+    format!("{prefix}{digest}")
+}
+
+/// Whether `id` is `prefix` then 32 lowercase hex digits, as [`content_id`] makes.
+pub(crate) fn is_content_id(id: &str, prefix: &str) -> bool {
+    id.strip_prefix(prefix)
+        .is_some_and(|hex| hex.len() == 32 && hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')))
+}
+
 /// Derive a deterministic ed25519 signing key from a name and password.
 ///
 /// Uses a single unified derivation domain (`cream-user-signing-key-v1`)
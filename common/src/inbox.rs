@@ -5,6 +5,9 @@ use ed25519_dalek::VerifyingKey;
 use serde::{Deserialize, Serialize};
 
 use crate::identity::UserId;
+use crate::limits::{check_count, check_len, LimitError, MAX_INBOX_BODY_LEN, MAX_INBOX_MESSAGES, MAX_NAME_LEN};
+use crate::metrics::ContractMetrics;
+use crate::timestamps::{check_not_future, TimestampError};
 
 /// Unique identifier for an inbox message (random u64).
 pub type MessageId = u64;
@@ -113,6 +116,35 @@ impl InboxState {
         true
     }
 
+    /// Check the state against the size limits in [`crate::limits`].
+    pub fn check_limits(&self) -> Result<(), LimitError> {
+        check_count("inbox messages", self.messages.len(), MAX_INBOX_MESSAGES)?;
+        for message in self.messages.values() {
+            check_len("sender name", &message.from_name, MAX_NAME_LEN)?;
+            check_len("message body", &message.body, MAX_INBOX_BODY_LEN)?;
+        }
+        Ok(())
+    }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`]. A future-dated message would outlive pruning.
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        check_not_future("inbox updated_at", self.updated_at, now)?;
+        for message in self.messages.values() {
+            check_not_future("message created_at", message.created_at, now)?;
+        }
+        Ok(())
+    }
+
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        ContractMetrics::new(state_bytes)
+            .count("messages", self.messages.len())
+            .updated("state", Some(self.updated_at))
+            .updated("messages", self.messages.values().map(|m| m.created_at).max())
+    }
+
     /// Summarize: return set of known message IDs.
     pub fn summarize(&self) -> InboxSummary {
         InboxSummary {
@@ -1,15 +1,42 @@
 mod postcodes_data;
+pub mod artifacts;
+pub mod attestation;
+pub mod audit;
+pub mod blocklist;
+pub mod catalog;
+pub mod clock;
+pub mod contact;
+pub mod credential;
 pub mod currency;
+pub mod delivery;
 pub mod directory;
 pub mod identity;
+pub mod limits;
 pub mod location;
 pub mod inbox;
 pub mod market;
+pub mod message;
+pub mod metrics;
+pub mod migration;
+pub mod moderation;
+pub mod onboarding;
 pub mod order;
+pub mod pickup;
 pub mod postcode;
 pub mod product;
+pub mod provenance;
+pub mod registry;
+pub mod related;
+pub mod retry;
+pub mod settlement;
+pub mod signing;
+pub mod signing_request;
+pub mod staff;
 pub mod storefront;
+pub mod template;
+pub mod timestamps;
 pub mod user_contract;
+pub mod voucher;
 pub mod wallet;
 pub mod wallet_backend;
 pub mod lightning_gateway;
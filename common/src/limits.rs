@@ -0,0 +1,117 @@
+//! Size limits on contract state.
+//!
+//! Every contract checks its merged state against these bounds so that a
+//! hostile update cannot grow the state without limit. Order-thread message
+//! bodies and attachments are bounded separately by
+//! [`crate::message::MAX_MESSAGE_BODY`] and
+//! [`crate::message::MAX_ATTACHMENT_BYTES`].
+
+use std::fmt;
+
+use crate::user_contract::PRUNE_THRESHOLD;
+
+/// Maximum length of a storefront, product or directory name, in bytes.
+pub const MAX_NAME_LEN: usize = 100;
+/// Maximum length of a storefront, product or directory description, in bytes.
+pub const MAX_DESCRIPTION_LEN: usize = 2000;
+/// Maximum length of a single contact field (phone, email, address, postcode, locality).
+pub const MAX_CONTACT_FIELD_LEN: usize = 200;
+/// Maximum number of product listings on one storefront.
+pub const MAX_PRODUCTS_PER_STOREFRONT: usize = 500;
+/// Maximum number of delivery zones on one storefront.
+pub const MAX_DELIVERY_ZONES: usize = 50;
+/// Maximum number of pickup locations on one storefront.
+pub const MAX_PICKUP_LOCATIONS: usize = 20;
+/// Maximum number of orders held on one storefront.
+pub const MAX_ORDERS_PER_STOREFRONT: usize = 10_000;
+/// Maximum number of months of compacted order history on one storefront.
+pub const MAX_ORDER_HISTORY_MONTHS: usize = 600;
+/// Maximum number of settlement statements on one user contract (ten years
+/// of weekly statements).
+pub const MAX_SETTLEMENT_STATEMENTS: usize = 520;
+/// Maximum number of customers on one storefront's blocklist.
+pub const MAX_BLOCKLIST_ENTRIES: usize = 1000;
+/// Maximum number of messages in one order's conversation thread.
+pub const MAX_THREAD_MESSAGES: usize = 200;
+/// Maximum number of messages with attachments in one order's thread.
+pub const MAX_THREAD_ATTACHMENTS: usize = 10;
+/// Maximum length of an inbox message body, in bytes.
+pub const MAX_INBOX_BODY_LEN: usize = 4000;
+/// Maximum number of messages held in one inbox.
+pub const MAX_INBOX_MESSAGES: usize = 1000;
+/// Maximum number of supplier entries in one directory (or directory shard).
+pub const MAX_DIRECTORY_ENTRIES: usize = 50_000;
+/// Maximum number of superseded versions of one supplier's directory entry kept
+/// as change history.
+pub const MAX_ENTRY_HISTORY: usize = 5;
+/// Maximum number of names in the username registry.
+pub const MAX_REGISTRY_NAMES: usize = 100_000;
+/// Maximum length of a ledger entry's description, in bytes.
+pub const MAX_TX_DESCRIPTION_LEN: usize = 200;
+/// Maximum number of ledger entries on a user contract.
+///
+/// Owners checkpoint once the ledger passes [`PRUNE_THRESHOLD`]; the headroom
+/// above it covers credits appended by others before the owner next compacts.
+pub const MAX_LEDGER_ENTRIES: usize = 2 * PRUNE_THRESHOLD;
+
+/// A contract state exceeds one of the size limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    /// A text field is longer than allowed.
+    FieldTooLong {
+        field: &'static str,
+        len: usize,
+        max: usize,
+    },
+    /// A collection holds more items than allowed.
+    TooManyItems {
+        what: &'static str,
+        count: usize,
+        max: usize,
+    },
+    /// The ledger has outgrown [`MAX_LEDGER_ENTRIES`] and must be checkpointed.
+    LedgerNeedsCompaction { entries: usize, max: usize },
+}
+
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitError::FieldTooLong { field, len, max } => {
+                write!(f, "{field} is {len} bytes (max {max})")
+            }
+            LimitError::TooManyItems { what, count, max } => {
+                write!(f, "too many {what}: {count} (max {max})")
+            }
+            LimitError::LedgerNeedsCompaction { entries, max } => {
+                write!(f, "ledger has {entries} entries (max {max}); checkpoint required")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+/// Check that `value` is at most `max` bytes long.
+pub fn check_len(field: &'static str, value: &str, max: usize) -> Result<(), LimitError> {
+    if value.len() > max {
+        return Err(LimitError::FieldTooLong {
+            field,
+            len: value.len(),
+            max,
+        });
+    }
+    Ok(())
+}
+
+/// Check an optional text field; `None` always passes.
+pub fn check_opt_len(field: &'static str, value: Option<&str>, max: usize) -> Result<(), LimitError> {
+    value.map_or(Ok(()), |v| check_len(field, v, max))
+}
+
+/// Check that a collection holds at most `max` items.
+pub fn check_count(what: &'static str, count: usize, max: usize) -> Result<(), LimitError> {
+    if count > max {
+        return Err(LimitError::TooManyItems { what, count, max });
+    }
+    Ok(())
+}
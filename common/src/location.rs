@@ -35,6 +35,48 @@ impl GeoLocation {
     }
 }
 
+/// Short "food miles" label for a distance, e.g. "< 1 km" or "42 km".
+pub fn food_miles_label(km: f64) -> String {
+    if km < 1.0 {
+        "< 1 km".to_string()
+    } else {
+        format!("{:.0} km", km)
+    }
+}
+
+/// Sort items by distance from `origin`, closest first.
+///
+/// Items without a location (or everything, when `origin` is unknown) keep
+/// their relative order and sort after located items.
+pub fn sort_by_proximity<T>(
+    items: &mut [T],
+    origin: Option<&GeoLocation>,
+    location_of: impl Fn(&T) -> Option<&GeoLocation>,
+) {
+    let Some(origin) = origin else {
+        return;
+    };
+    items.sort_by(|a, b| {
+        let da = location_of(a).map(|l| origin.distance_km(l)).unwrap_or(f64::MAX);
+        let db = location_of(b).map(|l| origin.distance_km(l)).unwrap_or(f64::MAX);
+        da.total_cmp(&db)
+    });
+}
+
+/// Total kilometres travelled by goods from each of `sources` to `destination`.
+///
+/// Each source is one shipment (e.g. one order), so repeated suppliers count
+/// once per appearance.
+pub fn total_food_miles<'a>(
+    destination: &GeoLocation,
+    sources: impl IntoIterator<Item = &'a GeoLocation>,
+) -> f64 {
+    sources
+        .into_iter()
+        .map(|src| src.distance_km(destination))
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,4 +95,41 @@ mod tests {
         // NYC to LA is ~3944 km
         assert!((dist - 3944.0).abs() < 50.0);
     }
+
+    #[test]
+    fn test_food_miles_label() {
+        assert_eq!(food_miles_label(0.4), "< 1 km");
+        assert_eq!(food_miles_label(12.6), "13 km");
+    }
+
+    #[test]
+    fn test_sort_by_proximity() {
+        let sydney = GeoLocation::new(-33.8688, 151.2093);
+        let newcastle = GeoLocation::new(-32.9283, 151.7817);
+        let wollongong = GeoLocation::new(-34.4278, 150.8931);
+        let mut items = vec![
+            ("none", None),
+            ("newcastle", Some(newcastle)),
+            ("wollongong", Some(wollongong)),
+        ];
+        sort_by_proximity(&mut items, Some(&sydney), |(_, loc)| loc.as_ref());
+        let order: Vec<&str> = items.iter().map(|(name, _)| *name).collect();
+        assert_eq!(order, vec!["wollongong", "newcastle", "none"]);
+    }
+
+    #[test]
+    fn test_sort_by_proximity_without_origin_is_noop() {
+        let mut items = vec![(2, Some(GeoLocation::new(1.0, 1.0))), (1, None)];
+        sort_by_proximity(&mut items, None, |(_, loc)| loc.as_ref());
+        assert_eq!(items[0].0, 2);
+    }
+
+    #[test]
+    fn test_total_food_miles() {
+        let home = GeoLocation::new(40.7128, -74.0060);
+        let la = GeoLocation::new(34.0522, -118.2437);
+        let single = home.distance_km(&la);
+        let total = total_food_miles(&home, [&la, &la, &home]);
+        assert!((total - 2.0 * single).abs() < 0.001);
+    }
 }
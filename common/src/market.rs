@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::identity::UserId;
 use crate::location::GeoLocation;
+use crate::metrics::ContractMetrics;
+use crate::timestamps::{check_not_future, TimestampError};
 
 /// A single scheduled market event (one day).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -147,6 +149,15 @@ impl MarketDirectoryState {
     pub fn validate_all_signatures(&self) -> bool {
         self.entries.values().all(|e| e.verify_signature())
     }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`]. A future-dated entry would win every merge.
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for entry in self.entries.values() {
+            check_not_future("market updated_at", entry.updated_at, now)?;
+        }
+        Ok(())
+    }
 }
 
 /// Summary of market directory state: market name -> last updated timestamp.
@@ -159,6 +170,14 @@ pub struct MarketDirectorySummary {
 }
 
 impl MarketDirectoryState {
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        ContractMetrics::new(state_bytes)
+            .count("entries", self.entries.len())
+            .updated("entries", self.entries.values().map(|e| e.updated_at).max())
+    }
+
     pub fn summarize(&self) -> MarketDirectorySummary {
         MarketDirectorySummary {
             timestamps: self
@@ -0,0 +1,320 @@
+//! Order-level conversation threads between a customer and a supplier.
+//!
+//! Each order on a storefront may carry a thread of signed messages. Only the
+//! order's customer and the storefront owner may post to it; messages are
+//! append-only and merge by set-union.
+//!
+//! A message may carry one small [`MessageAttachment`] (e.g. a photo of a
+//! damaged product), held inline as base64 so that it travels with the
+//! storefront state.
+
+use std::collections::BTreeMap;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::order::{Order, OrderId};
+
+/// Maximum length of a message body, in bytes.
+pub const MAX_MESSAGE_BODY: usize = 2000;
+/// Maximum size of an attachment's decoded payload, in bytes.
+pub const MAX_ATTACHMENT_BYTES: usize = 64 * 1024;
+/// Maximum length of an attachment's file name, in bytes.
+pub const MAX_ATTACHMENT_NAME: usize = 100;
+/// Image types the thread renders inline; any other attachment is offered as
+/// a download. SVG is deliberately absent, as it can carry script.
+pub const INLINE_IMAGE_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Unique message identifier within a thread.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MessageId(pub String);
+
+/// A small file carried inline in a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageAttachment {
+    pub file_name: String,
+    /// MIME type as reported by the sender, e.g. `image/jpeg`.
+    pub mime_type: String,
+    /// The file's bytes, standard base64 with padding.
+    pub data: String,
+}
+
+impl MessageAttachment {
+    /// Encode `bytes` as an attachment.
+    pub fn new(file_name: String, mime_type: String, bytes: &[u8]) -> Self {
+        MessageAttachment {
+            file_name,
+            mime_type,
+            data: BASE64.encode(bytes),
+        }
+    }
+
+    /// The decoded payload, or `None` if `data` is not valid base64.
+    pub fn decode(&self) -> Option<Vec<u8>> {
+        BASE64.decode(&self.data).ok()
+    }
+
+    /// Whether the thread should show this attachment as an image.
+    pub fn is_image(&self) -> bool {
+        INLINE_IMAGE_TYPES.contains(&self.mime_type.as_str())
+    }
+
+    /// A `data:` URL for rendering or downloading the attachment.
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+
+    /// Named, typed, and within [`MAX_ATTACHMENT_BYTES`] once decoded. The
+    /// encoded length is checked first so an oversized payload is rejected
+    /// without decoding it.
+    pub fn is_valid(&self) -> bool {
+        let name_ok = !self.file_name.trim().is_empty() && self.file_name.len() <= MAX_ATTACHMENT_NAME;
+        let type_ok = is_mime_type(&self.mime_type);
+        let size_ok = self.data.len() <= MAX_ATTACHMENT_BYTES.div_ceil(3) * 4
+            && self.decode().is_some_and(|bytes| !bytes.is_empty() && bytes.len() <= MAX_ATTACHMENT_BYTES);
+        name_ok && type_ok && size_ok
+    }
+}
+
+/// A plausible `type/subtype` MIME type: short, ASCII, no parameters.
+fn is_mime_type(mime: &str) -> bool {
+    let token = |t: &str| {
+        !t.is_empty() && t.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$&^_.+-".contains(&b))
+    };
+    mime.len() <= MAX_ATTACHMENT_NAME
+        && mime.split_once('/').is_some_and(|(kind, sub)| token(kind) && token(sub))
+}
+
+/// A message posted to an order's thread.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderMessage {
+    pub id: MessageId,
+    /// The order this message is about.
+    pub order_id: OrderId,
+    pub author: UserId,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    /// The earlier message in the same thread this one answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<MessageId>,
+    /// A file sent with the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachment: Option<MessageAttachment>,
+    /// Author's signature over [`OrderMessage::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableOrderMessage<'a> {
+    id: &'a MessageId,
+    order_id: &'a OrderId,
+    author: &'a UserId,
+    body: &'a str,
+    created_at: &'a DateTime<Utc>,
+    reply_to: &'a Option<MessageId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attachment: Option<&'a MessageAttachment>,
+}
+
+impl OrderMessage {
+    /// Serialize message fields for signing (everything except signature).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableOrderMessage {
+            id: &self.id,
+            order_id: &self.order_id,
+            author: &self.author,
+            body: &self.body,
+            created_at: &self.created_at,
+            reply_to: &self.reply_to,
+            attachment: self.attachment.as_ref(),
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    pub fn verify_signature(&self) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            let msg = self.signable_bytes();
+            self.author.0.verify(&msg, &self.signature).is_ok()
+        }
+    }
+
+    /// Well-formed and signed by its author. Whether the author may post to
+    /// the thread is checked against the order with [`may_post`]. A message
+    /// with an attachment may have an empty body.
+    pub fn is_valid(&self) -> bool {
+        let has_content = !self.body.trim().is_empty() || self.attachment.is_some();
+        has_content
+            && self.body.len() <= MAX_MESSAGE_BODY
+            && self.attachment.as_ref().is_none_or(MessageAttachment::is_valid)
+            && self.verify_signature()
+    }
+}
+
+/// Whether `author` is a participant in `order`'s thread: its customer or one
+/// of the storefront's `suppliers` (the owner and its staff).
+pub fn may_post(order: &Order, suppliers: &[VerifyingKey], author: &UserId) -> bool {
+    suppliers.contains(&author.0) || *author == order.customer
+}
+
+/// The messages posted about one order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrderThread {
+    pub messages: BTreeMap<MessageId, OrderMessage>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OrderThread {
+    /// Messages oldest first.
+    pub fn chronological(&self) -> Vec<&OrderMessage> {
+        let mut messages: Vec<_> = self.messages.values().collect();
+        messages.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        messages
+    }
+
+    /// Add a message, keeping the first copy seen of any id.
+    pub fn insert(&mut self, message: OrderMessage) {
+        self.messages.entry(message.id.clone()).or_insert(message);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::order::{DepositTier, OrderStatus};
+    use crate::product::ProductId;
+    use chrono::Duration;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn order_for(customer: &SigningKey) -> Order {
+        Order {
+            id: OrderId("o-1".into()),
+            product_id: ProductId("p-1".into()),
+            customer: UserId(customer.verifying_key()),
+            quantity: 1,
+            deposit_tier: DepositTier::Reserve2Days,
+            deposit_amount: 1,
+            total_price: 10,
+            status: OrderStatus::Paid,
+            created_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        }
+    }
+
+    /// A message on `order_id` signed by `author`; storefront tests share it.
+    pub(crate) fn message(
+        author: &SigningKey,
+        order_id: &str,
+        id: &str,
+        body: &str,
+        at: DateTime<Utc>,
+    ) -> OrderMessage {
+        let mut msg = OrderMessage {
+            id: MessageId(id.into()),
+            order_id: OrderId(order_id.into()),
+            author: UserId(author.verifying_key()),
+            body: body.into(),
+            created_at: at,
+            reply_to: None,
+            attachment: None,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        msg.signature = author.sign(&msg.signable_bytes());
+        msg
+    }
+
+    #[test]
+    fn only_customer_and_owner_may_post() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let stranger = SigningKey::from_bytes(&[3u8; 32]);
+        let order = order_for(&customer);
+
+        assert!(may_post(&order, &[owner.verifying_key()], &UserId(owner.verifying_key())));
+        assert!(may_post(&order, &[owner.verifying_key()], &UserId(customer.verifying_key())));
+        assert!(!may_post(&order, &[owner.verifying_key()], &UserId(stranger.verifying_key())));
+    }
+
+    #[test]
+    fn signed_message_is_valid_and_body_is_bounded() {
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        assert!(message(&customer, "o-1", "m-1", "Can I pick up Saturday?", Utc::now()).is_valid());
+        assert!(!message(&customer, "o-1", "m-2", "   ", Utc::now()).is_valid());
+        let long = "x".repeat(MAX_MESSAGE_BODY + 1);
+        assert!(!message(&customer, "o-1", "m-3", &long, Utc::now()).is_valid());
+    }
+
+    #[test]
+    fn attachments_are_bounded_and_signed() {
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let with = |attachment: MessageAttachment| {
+            let mut msg = message(&customer, "o-1", "m-1", "", Utc::now());
+            msg.attachment = Some(attachment);
+            msg.signature = customer.sign(&msg.signable_bytes());
+            msg
+        };
+
+        let photo = MessageAttachment::new("dent.jpg".into(), "image/jpeg".into(), &[0xff, 0xd8, 0xff]);
+        assert!(photo.is_image());
+        assert_eq!(photo.decode().unwrap(), vec![0xff, 0xd8, 0xff]);
+        assert!(with(photo.clone()).is_valid());
+
+        let limit = vec![0u8; MAX_ATTACHMENT_BYTES];
+        assert!(with(MessageAttachment::new("a.bin".into(), "application/octet-stream".into(), &limit)).is_valid());
+        let over = vec![0u8; MAX_ATTACHMENT_BYTES + 1];
+        assert!(!with(MessageAttachment::new("a.bin".into(), "application/octet-stream".into(), &over)).is_valid());
+
+        let garbled = MessageAttachment { data: "not base64!".into(), ..photo.clone() };
+        assert!(!with(garbled).is_valid());
+        let untyped = MessageAttachment { mime_type: "jpeg".into(), ..photo.clone() };
+        assert!(!with(untyped).is_valid());
+
+        // The attachment is covered by the signature
+        #[cfg(not(feature = "dev"))]
+        {
+            let mut swapped = with(photo);
+            swapped.attachment = Some(MessageAttachment::new("x.png".into(), "image/png".into(), &[1]));
+            assert!(!swapped.verify_signature());
+        }
+    }
+
+    #[test]
+    fn chronological_orders_by_time() {
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let now = Utc::now();
+        let mut thread = OrderThread::default();
+        thread.insert(message(&customer, "o-1", "m-b", "second", now));
+        thread.insert(message(&customer, "o-1", "m-a", "first", now - Duration::minutes(5)));
+        let bodies: Vec<_> = thread.chronological().iter().map(|m| m.body.as_str()).collect();
+        assert_eq!(bodies, vec!["first", "second"]);
+    }
+}
@@ -0,0 +1,96 @@
+//! Compact contract metrics for monitoring.
+//!
+//! A monitor that only watches contracts grow — the guardian, the auditor —
+//! doesn't need a contract's state or even a delta of it. Passing
+//! [`metrics_request`] as the summary to a contract's `get_state_delta` (a
+//! subscription's summary, say) makes the contract answer with a JSON
+//! [`ContractMetrics`] instead: how many of each thing the state holds, when
+//! each last changed, and how big the state is.
+//!
+//! The request is a summary no contract would produce itself, so ordinary
+//! delta sync is unaffected.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The summary that selects metrics over a delta.
+#[derive(Serialize, Deserialize)]
+struct SummaryRequest {
+    metrics: bool,
+}
+
+/// Summary bytes asking a contract for its [`ContractMetrics`].
+pub fn metrics_request() -> Vec<u8> {
+    serde_json::to_vec(&SummaryRequest { metrics: true }).expect("serialization should not fail")
+}
+
+/// Whether `summary` is a [`metrics_request`] rather than a state summary.
+pub fn is_metrics_request(summary: &[u8]) -> bool {
+    serde_json::from_slice::<SummaryRequest>(summary).is_ok_and(|request| request.metrics)
+}
+
+/// Size and shape of one contract state.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContractMetrics {
+    /// Serialized size of the state.
+    pub state_bytes: usize,
+    /// Number of items in each collection, by field name.
+    pub counts: BTreeMap<String, usize>,
+    /// Latest timestamp in each collection (or of the state itself) that has one.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_updated: BTreeMap<String, DateTime<Utc>>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ContractMetrics {
+    pub fn new(state_bytes: usize) -> Self {
+        ContractMetrics { state_bytes, ..Default::default() }
+    }
+
+    /// Record that `section` holds `count` items.
+    pub fn count(mut self, section: &str, count: usize) -> Self {
+        self.counts.insert(section.to_string(), count);
+        self
+    }
+
+    /// Record when `section` last changed, if it ever has.
+    pub fn updated(mut self, section: &str, at: Option<DateTime<Utc>>) -> Self {
+        if let Some(at) = at {
+            self.last_updated.insert(section.to_string(), at);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_request_selects_metrics() {
+        assert!(is_metrics_request(&metrics_request()));
+        assert!(!is_metrics_request(b""));
+        assert!(!is_metrics_request(br#"{"metrics":false}"#));
+        // An ordinary summary is not a request
+        assert!(!is_metrics_request(br#"{"products":{},"orders":{}}"#));
+    }
+
+    #[test]
+    fn metrics_round_trip() {
+        let now = Utc::now();
+        let metrics = ContractMetrics::new(1234)
+            .count("orders", 3)
+            .updated("orders", Some(now))
+            .updated("products", None);
+        assert_eq!(metrics.counts["orders"], 3);
+        assert!(!metrics.last_updated.contains_key("products"));
+
+        let bytes = serde_json::to_vec(&metrics).unwrap();
+        let back: ContractMetrics = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(back, metrics);
+    }
+}
@@ -0,0 +1,383 @@
+//! Moving a supplier to a new Freenet node.
+//!
+//! A supplier's contracts live wherever the network keeps them, but on a
+//! small network their own node may be the only holder. Before retiring it
+//! they export a [`MigrationBundle`] — the storefront, user contract and
+//! inbox states with the keys they live under — and import it on the new
+//! node, which re-PUTs each state.
+//!
+//! The contract keys are derived from the contract code and the owner's
+//! verifying key, so on import each contract is rebuilt from the supplier's
+//! key and must come out under the key it was exported from
+//! ([`check_key`]): same owner, same code, nothing to re-link in the
+//! directory. [`MigrationBundle::check_owner`] checks every state in the
+//! bundle belongs to the key doing the import.
+//!
+//! Customers find the supplier's node through the rendezvous service, so
+//! the import ends by pointing that at the new address with a
+//! [`RendezvousRecord`]: a fresh registration signature over the new
+//! address and contract keys, plus the address signature heartbeats carry.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier};
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::inbox::InboxState;
+use crate::storefront::StorefrontState;
+use crate::user_contract::UserContractState;
+
+/// Identifies a CREAM migration file.
+pub const MIGRATION_FORMAT: &str = "cream-migration";
+/// Current migration file version.
+pub const MIGRATION_VERSION: u32 = 1;
+
+/// A supplier's contract states, exported from one node for another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationBundle {
+    pub format: String,
+    pub version: u32,
+    pub supplier: UserId,
+    /// The supplier's name, as they log in with.
+    pub name: String,
+    pub storefront_key: String,
+    pub storefront: StorefrontState,
+    #[serde(default)]
+    pub user_contract_key: Option<String>,
+    #[serde(default)]
+    pub user_contract: Option<UserContractState>,
+    #[serde(default)]
+    pub inbox_key: Option<String>,
+    #[serde(default)]
+    pub inbox: Option<InboxState>,
+    pub exported_at: DateTime<Utc>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MigrationError {
+    NotABundle,
+    UnsupportedVersion(u32),
+    /// The bundle belongs to a different supplier than the one importing it.
+    WrongSupplier,
+    /// A state in the bundle isn't owned by the bundle's supplier.
+    OwnerMismatch(&'static str),
+    /// A state in the bundle fails its contract's validation.
+    InvalidState(&'static str),
+    /// A contract rebuilt on the new node doesn't have its old key.
+    KeyChanged {
+        contract: &'static str,
+        exported: String,
+        rebuilt: String,
+    },
+    Serde(String),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotABundle => write!(f, "This file is not a CREAM migration bundle"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported migration bundle version {}", v),
+            Self::WrongSupplier => write!(f, "This bundle belongs to a different supplier"),
+            Self::OwnerMismatch(what) => write!(f, "The {} in this bundle has a different owner", what),
+            Self::InvalidState(what) => write!(f, "The {} in this bundle does not validate", what),
+            Self::KeyChanged { contract, exported, rebuilt } => write!(
+                f,
+                "The {} contract would move from {} to {}; is the new node running a different CREAM release?",
+                contract, exported, rebuilt
+            ),
+            Self::Serde(e) => write!(f, "Invalid migration bundle: {}", e),
+        }
+    }
+}
+
+impl MigrationBundle {
+    /// A bundle of `storefront`, stored under `storefront_key`. The supplier
+    /// is the storefront's owner.
+    pub fn new(name: &str, storefront_key: String, storefront: StorefrontState, exported_at: DateTime<Utc>) -> Self {
+        Self {
+            format: MIGRATION_FORMAT.to_string(),
+            version: MIGRATION_VERSION,
+            supplier: storefront.info.owner.clone(),
+            name: name.to_string(),
+            storefront_key,
+            storefront,
+            user_contract_key: None,
+            user_contract: None,
+            inbox_key: None,
+            inbox: None,
+            exported_at,
+        }
+    }
+
+    pub fn with_user_contract(mut self, key: String, state: UserContractState) -> Self {
+        self.user_contract_key = Some(key);
+        self.user_contract = Some(state);
+        self
+    }
+
+    pub fn with_inbox(mut self, key: String, state: InboxState) -> Self {
+        self.inbox_key = Some(key);
+        self.inbox = Some(state);
+        self
+    }
+
+    pub fn to_json(&self) -> Result<String, MigrationError> {
+        serde_json::to_string_pretty(self).map_err(|e| MigrationError::Serde(e.to_string()))
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self, MigrationError> {
+        let value: serde_json::Value = serde_json::from_str(contents).map_err(|_| MigrationError::NotABundle)?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(MIGRATION_FORMAT) {
+            return Err(MigrationError::NotABundle);
+        }
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version != MIGRATION_VERSION {
+            return Err(MigrationError::UnsupportedVersion(version));
+        }
+        serde_json::from_value(value).map_err(|e| MigrationError::Serde(e.to_string()))
+    }
+
+    /// Check the bundle is `importer`'s: its supplier, owning every state in
+    /// it, with a storefront their contract accepts.
+    pub fn check_owner(&self, importer: &UserId) -> Result<(), MigrationError> {
+        if self.supplier != *importer {
+            return Err(MigrationError::WrongSupplier);
+        }
+        if self.storefront.info.owner != self.supplier {
+            return Err(MigrationError::OwnerMismatch("storefront"));
+        }
+        if self.user_contract.as_ref().is_some_and(|uc| uc.owner != self.supplier) {
+            return Err(MigrationError::OwnerMismatch("user contract"));
+        }
+        if self.inbox.as_ref().is_some_and(|inbox| inbox.owner != self.supplier) {
+            return Err(MigrationError::OwnerMismatch("inbox"));
+        }
+        if !self.storefront.validate(&self.supplier.0) {
+            return Err(MigrationError::InvalidState("storefront"));
+        }
+        if self.user_contract.as_ref().is_some_and(|uc| !uc.validate(&self.supplier.0)) {
+            return Err(MigrationError::InvalidState("user contract"));
+        }
+        Ok(())
+    }
+}
+
+/// Check a contract rebuilt on the new node has the key it was exported
+/// under.
+pub fn check_key(contract: &'static str, exported: &str, rebuilt: &str) -> Result<(), MigrationError> {
+    if exported == rebuilt {
+        Ok(())
+    } else {
+        Err(MigrationError::KeyChanged {
+            contract,
+            exported: exported.to_string(),
+            rebuilt: rebuilt.to_string(),
+        })
+    }
+}
+
+/// The name a supplier registers with the rendezvous service under.
+pub fn rendezvous_name(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
+}
+
+/// What the rendezvous service's registration signature covers.
+pub fn registration_message(
+    name: &str,
+    address: &str,
+    storefront_key: &str,
+    user_contract_key: Option<&str>,
+    inbox_contract_key: Option<&str>,
+) -> String {
+    format!(
+        "{}|{}|{}|{}|{}",
+        name,
+        address,
+        storefront_key,
+        user_contract_key.unwrap_or(""),
+        inbox_contract_key.unwrap_or("")
+    )
+}
+
+/// What the rendezvous service's heartbeat (address) signature covers.
+pub fn address_message(name: &str, address: &str) -> String {
+    format!("{}|{}", name, address)
+}
+
+/// A supplier's rendezvous entry for a new node address, with both the
+/// registration and the address signature, hex-encoded as the service
+/// takes them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RendezvousRecord {
+    pub name: String,
+    pub address: String,
+    pub storefront_key: String,
+    pub user_contract_key: Option<String>,
+    pub inbox_contract_key: Option<String>,
+    pub public_key: String,
+    pub signature: String,
+    pub address_signature: String,
+}
+
+impl RendezvousRecord {
+    /// Sign the entry for `name` at `address` with the supplier's key.
+    pub fn sign(
+        key: &SigningKey,
+        name: &str,
+        address: &str,
+        storefront_key: &str,
+        user_contract_key: Option<&str>,
+        inbox_contract_key: Option<&str>,
+    ) -> Self {
+        let registration = registration_message(name, address, storefront_key, user_contract_key, inbox_contract_key);
+        let moved = address_message(name, address);
+        Self {
+            name: name.to_string(),
+            address: address.to_string(),
+            storefront_key: storefront_key.to_string(),
+            user_contract_key: user_contract_key.map(str::to_string),
+            inbox_contract_key: inbox_contract_key.map(str::to_string),
+            public_key: UserId(key.verifying_key()).to_string(),
+            signature: to_hex(&key.sign(registration.as_bytes()).to_bytes()),
+            address_signature: to_hex(&key.sign(moved.as_bytes()).to_bytes()),
+        }
+    }
+
+    /// Check both signatures are `owner`'s.
+    pub fn verify(&self, owner: &UserId) -> bool {
+        if self.public_key != owner.to_string() {
+            return false;
+        }
+        let registration = registration_message(
+            &self.name,
+            &self.address,
+            &self.storefront_key,
+            self.user_contract_key.as_deref(),
+            self.inbox_contract_key.as_deref(),
+        );
+        let moved = address_message(&self.name, &self.address);
+        verify_hex(owner, &registration, &self.signature) && verify_hex(owner, &moved, &self.address_signature)
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn verify_hex(key: &UserId, message: &str, signature_hex: &str) -> bool {
+    if signature_hex.len() != 128 || !signature_hex.is_ascii() {
+        return false;
+    }
+    let bytes: Option<Vec<u8>> = (0..signature_hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&signature_hex[i..i + 2], 16).ok())
+        .collect();
+    let Some(signature) = bytes.and_then(|b| Signature::from_slice(&b).ok()) else {
+        return false;
+    };
+    key.0.verify(message.as_bytes(), &signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::location::GeoLocation;
+    use crate::storefront::StorefrontInfo;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn storefront(owner: &UserId) -> StorefrontState {
+        StorefrontState {
+            info: StorefrontInfo {
+                owner: owner.clone(),
+                name: "Test Farm".into(),
+                description: "".into(),
+                location: GeoLocation::new(0.0, 0.0),
+                schedule: None,
+                timezone: None,
+                phone: None,
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        }
+    }
+
+    fn inbox(owner: &UserId) -> InboxState {
+        InboxState {
+            owner: owner.clone(),
+            messages: BTreeMap::new(),
+            updated_at: Utc::now(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn bundle_round_trips_and_rejects_other_files() {
+        let owner = UserId(key(1).verifying_key());
+        let bundle = MigrationBundle::new("Test Farm", "sf-key".into(), storefront(&owner), Utc::now())
+            .with_inbox("ib-key".into(), inbox(&owner));
+        let restored = MigrationBundle::from_json(&bundle.to_json().unwrap()).unwrap();
+        assert_eq!(restored.supplier, owner);
+        assert_eq!(restored.inbox_key.as_deref(), Some("ib-key"));
+
+        assert_eq!(MigrationBundle::from_json("{}").unwrap_err(), MigrationError::NotABundle);
+        let future = bundle.to_json().unwrap().replace("\"version\": 1", "\"version\": 9");
+        assert_eq!(MigrationBundle::from_json(&future).unwrap_err(), MigrationError::UnsupportedVersion(9));
+    }
+
+    #[test]
+    fn only_the_owner_can_import() {
+        let owner = UserId(key(1).verifying_key());
+        let other = UserId(key(2).verifying_key());
+        let bundle = MigrationBundle::new("Test Farm", "sf-key".into(), storefront(&owner), Utc::now());
+        assert_eq!(bundle.check_owner(&owner), Ok(()));
+        assert_eq!(bundle.check_owner(&other), Err(MigrationError::WrongSupplier));
+
+        let foreign_inbox = bundle.with_inbox("ib-key".into(), inbox(&other));
+        assert_eq!(foreign_inbox.check_owner(&owner), Err(MigrationError::OwnerMismatch("inbox")));
+    }
+
+    #[test]
+    fn keys_must_carry_over() {
+        assert_eq!(check_key("storefront", "abc", "abc"), Ok(()));
+        assert!(matches!(
+            check_key("storefront", "abc", "def"),
+            Err(MigrationError::KeyChanged { contract: "storefront", .. })
+        ));
+    }
+
+    #[test]
+    fn rendezvous_record_carries_both_signatures() {
+        let supplier = key(1);
+        let owner = UserId(supplier.verifying_key());
+        let record = RendezvousRecord::sign(&supplier, "test-farm", "ws://new:3001", "sf", Some("uc"), None);
+        assert!(record.verify(&owner));
+        assert!(!record.verify(&UserId(key(2).verifying_key())));
+
+        let mut moved = record.clone();
+        moved.address = "ws://elsewhere:3001".into();
+        assert!(!moved.verify(&owner));
+    }
+}
@@ -0,0 +1,248 @@
+//! Supplier onboarding: what a new storefront still lacks.
+//!
+//! A storefront is usable as soon as it's deployed, but customers can't find
+//! it, order from it or reach the supplier until a few things are set up.
+//! [`Onboarding::assess`] works out which of those [`OnboardingStep`]s are
+//! done from the contract state itself — nothing is recorded as "completed" —
+//! so a step undone later (the last product removed, say) shows as pending
+//! again.
+
+use std::collections::BTreeSet;
+
+use crate::directory::DirectoryEntry;
+use crate::storefront::StorefrontState;
+
+/// One thing a new supplier sets up, in the order the checklist walks them
+/// through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum OnboardingStep {
+    /// Listed in the directory with a description.
+    Listing,
+    /// At least one product for sale.
+    Products,
+    /// Opening hours with at least one open slot.
+    Schedule,
+    /// A phone number, email or address customers can reach them on.
+    Contact,
+    /// Registered with the rendezvous service, so customers can connect to
+    /// their node by name.
+    Rendezvous,
+}
+
+impl OnboardingStep {
+    /// Every step, in checklist order.
+    pub const ALL: [OnboardingStep; 5] = [
+        OnboardingStep::Listing,
+        OnboardingStep::Products,
+        OnboardingStep::Schedule,
+        OnboardingStep::Contact,
+        OnboardingStep::Rendezvous,
+    ];
+
+    /// Short label for the checklist.
+    pub fn label(self) -> &'static str {
+        match self {
+            OnboardingStep::Listing => "List your storefront in the directory",
+            OnboardingStep::Products => "Add your first product",
+            OnboardingStep::Schedule => "Set your opening hours",
+            OnboardingStep::Contact => "Add contact details",
+            OnboardingStep::Rendezvous => "Register so customers can find you by name",
+        }
+    }
+}
+
+/// Which onboarding steps a supplier has done.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Onboarding {
+    done: BTreeSet<OnboardingStep>,
+}
+
+impl Onboarding {
+    /// Assess a storefront, its owner's directory entry (if listed) and
+    /// whether the owner's node is registered with the rendezvous service,
+    /// which isn't contract state and so is passed in.
+    pub fn assess(
+        storefront: &StorefrontState,
+        listing: Option<&DirectoryEntry>,
+        rendezvous_registered: bool,
+    ) -> Self {
+        let info = &storefront.info;
+        let filled = |field: &Option<String>| field.as_deref().is_some_and(|v| !v.trim().is_empty());
+        let mut done = BTreeSet::new();
+        if listing.is_some_and(|entry| {
+            entry.supplier == info.owner && !entry.description.trim().is_empty()
+        }) {
+            done.insert(OnboardingStep::Listing);
+        }
+        if !storefront.products.is_empty() {
+            done.insert(OnboardingStep::Products);
+        }
+        if info.schedule.as_ref().is_some_and(|s| !s.is_always_closed()) {
+            done.insert(OnboardingStep::Schedule);
+        }
+        if filled(&info.phone) || filled(&info.email) || filled(&info.address) || info.sealed_contact.is_some() {
+            done.insert(OnboardingStep::Contact);
+        }
+        if rendezvous_registered {
+            done.insert(OnboardingStep::Rendezvous);
+        }
+        Onboarding { done }
+    }
+
+    pub fn is_done(&self, step: OnboardingStep) -> bool {
+        self.done.contains(&step)
+    }
+
+    /// The step to do next: the first not done, in checklist order. `None`
+    /// once onboarding is complete.
+    pub fn current(&self) -> Option<OnboardingStep> {
+        OnboardingStep::ALL.into_iter().find(|step| !self.is_done(*step))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Steps done, out of how many.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.done.len(), OnboardingStep::ALL.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+    use ed25519_dalek::{Signature, SigningKey};
+    use freenet_stdlib::prelude::{ContractCode, ContractKey, Parameters};
+
+    use super::*;
+    use crate::identity::UserId;
+    use crate::location::GeoLocation;
+    use crate::product::{Product, ProductCategory, ProductId};
+    use crate::storefront::{SignedProduct, StorefrontInfo, WeeklySchedule};
+
+    fn new_storefront(owner: &UserId) -> StorefrontState {
+        StorefrontState {
+            info: StorefrontInfo {
+                owner: owner.clone(),
+                name: "Test Farm".into(),
+                description: "".into(),
+                location: GeoLocation::new(0.0, 0.0),
+                schedule: None,
+                timezone: None,
+                phone: None,
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        }
+    }
+
+    fn listing(owner: &UserId, description: &str) -> DirectoryEntry {
+        DirectoryEntry {
+            supplier: owner.clone(),
+            name: "Test".into(),
+            description: description.into(),
+            location: GeoLocation::new(0.0, 0.0),
+            postcode: None,
+            locality: None,
+            categories: Vec::new(),
+            storefront_key: ContractKey::from_params_and_code(
+                Parameters::from(vec![1u8]),
+                ContractCode::from(vec![0u8]),
+            ),
+            user_contract_key: None,
+            inbox_contract_key: None,
+            updated_at: Utc::now(),
+            name_claimed_at: None,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    fn product() -> SignedProduct {
+        let now = Utc::now();
+        SignedProduct {
+            product: Product {
+                id: ProductId("p-1".into()),
+                name: "Milk".into(),
+                description: "".into(),
+                category: ProductCategory::Milk,
+                subcategory: None,
+                attributes: Default::default(),
+                price_curd: 500,
+                quantity_total: 10,
+                expiry_date: None,
+                updated_at: now,
+                created_at: now,
+                low_stock: None,
+                paused: false,
+                special: None,
+                wholesale: None,
+                extra: Default::default(),
+            },
+            signature: Signature::from_bytes(&[0u8; 64]),
+            provenance: None,
+            price_history: Vec::new(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn steps_complete_in_any_order_and_current_is_the_first_missing() {
+        let owner = UserId(SigningKey::from_bytes(&[1u8; 32]).verifying_key());
+        let mut sf = new_storefront(&owner);
+
+        let fresh = Onboarding::assess(&sf, None, false);
+        assert_eq!(fresh.current(), Some(OnboardingStep::Listing));
+        assert_eq!(fresh.progress(), (0, 5));
+
+        // A listing without a description doesn't count yet
+        let bare = listing(&owner, " ");
+        assert!(!Onboarding::assess(&sf, Some(&bare), false).is_done(OnboardingStep::Listing));
+
+        // A closed-all-week schedule isn't opening hours
+        sf.info.schedule = Some(WeeklySchedule::new());
+        sf.info.email = Some("farm@example.com".into());
+        let listed = listing(&owner, "Raw milk from the valley");
+        let partway = Onboarding::assess(&sf, Some(&listed), false);
+        assert!(partway.is_done(OnboardingStep::Listing));
+        assert!(partway.is_done(OnboardingStep::Contact));
+        assert!(!partway.is_done(OnboardingStep::Schedule));
+        assert_eq!(partway.current(), Some(OnboardingStep::Products));
+
+        let mut hours = WeeklySchedule::new();
+        hours.set_range(0, 16, 34, true);
+        sf.info.schedule = Some(hours);
+        sf.products.insert(ProductId("p-1".into()), product());
+        let almost = Onboarding::assess(&sf, Some(&listed), false);
+        assert_eq!(almost.current(), Some(OnboardingStep::Rendezvous));
+        assert!(Onboarding::assess(&sf, Some(&listed), true).is_complete());
+
+        // Another supplier's listing doesn't count, and removing the last
+        // product puts that step back
+        let other = UserId(SigningKey::from_bytes(&[2u8; 32]).verifying_key());
+        sf.products.clear();
+        let regressed = Onboarding::assess(&sf, Some(&listing(&other, "Not mine")), true);
+        assert_eq!(regressed.current(), Some(OnboardingStep::Listing));
+        assert!(!regressed.is_done(OnboardingStep::Products));
+        assert_eq!(regressed.progress(), (3, 5));
+    }
+}
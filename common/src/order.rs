@@ -4,8 +4,13 @@ use chrono::{DateTime, Utc};
 use ed25519_dalek::Signature;
 use serde::{Deserialize, Serialize};
 
+use crate::currency::Curds;
 use crate::identity::UserId;
 use crate::product::ProductId;
+use crate::provenance::is_zero;
+use crate::timestamps::{
+    check_not_future, check_order, check_unchanged, check_within, max_clock_skew, TimestampError,
+};
 
 /// Where the customer will collect the order.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,12 +19,33 @@ pub enum CollectionPoint {
     FarmGate,
     /// Pickup at a farmer's market venue.
     Market { market_name: String },
+    /// Pickup at one of the storefront's own
+    /// [`PickupLocation`](crate::pickup::PickupLocation)s, by name.
+    Pickup { location_name: String },
 }
 
 /// Unique order identifier.
+///
+/// Orders are identified by their content ([`OrderId::derive`]), so two tabs
+/// placing orders in the same millisecond don't collide and nobody can place
+/// an order under an id that names someone else's. Orders placed before
+/// that keep their timestamp ids ("o-1712345678901").
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct OrderId(pub String);
 
+impl OrderId {
+    /// The id of `customer`'s order for `product_id` placed at `created_at`,
+    /// with a random `nonce` telling apart orders placed at the same instant.
+    pub fn derive(customer: &UserId, product_id: &ProductId, created_at: DateTime<Utc>, nonce: u64) -> Self {
+        OrderId(crate::identity::content_id("o-", customer, &product_id.0, created_at, nonce))
+    }
+
+    /// Whether this is a content id rather than a legacy timestamp id.
+    pub fn is_content_id(&self) -> bool {
+        crate::identity::is_content_id(&self.0, "o-")
+    }
+}
+
 /// How much deposit the customer puts down to reserve a product.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DepositTier {
@@ -51,13 +77,84 @@ impl DepositTier {
     }
 
     /// Calculate the required deposit amount for a given total price.
-    pub fn calculate_deposit(self, total_price: u64) -> u64 {
+    pub fn calculate_deposit(self, total_price: Curds) -> Curds {
         match self {
             DepositTier::Reserve2Days => total_price / 10,
             DepositTier::Reserve1Week => total_price / 5,
             DepositTier::FullPayment => total_price,
         }
     }
+
+    /// How long an order at this tier stays reserved before it expires.
+    pub fn hold_period(self) -> chrono::Duration {
+        match self {
+            DepositTier::Reserve2Days => chrono::Duration::days(2),
+            DepositTier::Reserve1Week => chrono::Duration::weeks(1),
+            DepositTier::FullPayment => chrono::Duration::days(365),
+        }
+    }
+}
+
+/// How long a customer has to counter-sign a fulfillment before the supplier
+/// may confirm it on their behalf.
+pub const FULFILLMENT_AUTO_CONFIRM_HOURS: i64 = 72;
+
+/// A supplier's terms for customers cancelling their own orders: the whole
+/// escrowed deposit back within `full_refund_hours` of placing the order,
+/// less `late_penalty_percent` after that. The penalty goes to the supplier.
+///
+/// Suppliers cancelling an order always refund it in full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CancellationPolicy {
+    pub full_refund_hours: u32,
+    /// Share of the deposit kept on a late cancellation, 0–100.
+    pub late_penalty_percent: u8,
+}
+
+impl CancellationPolicy {
+    /// Whether the penalty is a percentage (at most 100).
+    pub fn is_valid(&self) -> bool {
+        self.late_penalty_percent <= 100
+    }
+
+    /// Until when an order placed at `placed_at` cancels for a full refund.
+    pub fn full_refund_until(&self, placed_at: DateTime<Utc>) -> DateTime<Utc> {
+        placed_at + chrono::Duration::hours(i64::from(self.full_refund_hours))
+    }
+
+    /// What the supplier keeps of `deposit` when an order placed at
+    /// `placed_at` is cancelled at `cancelled_at`. Rounded down, in the
+    /// customer's favour.
+    pub fn penalty(&self, deposit: Curds, placed_at: DateTime<Utc>, cancelled_at: DateTime<Utc>) -> Curds {
+        if cancelled_at <= self.full_refund_until(placed_at) {
+            return 0;
+        }
+        let percent = u64::from(self.late_penalty_percent.min(100));
+        // Widened so large deposits can't overflow
+        (u128::from(deposit) * u128::from(percent) / 100) as u64
+    }
+
+    /// What the customer gets back of `deposit`: everything but the penalty.
+    pub fn refund(&self, deposit: Curds, placed_at: DateTime<Utc>, cancelled_at: DateTime<Utc>) -> Curds {
+        deposit - self.penalty(deposit, placed_at, cancelled_at)
+    }
+
+    /// One-line summary for checkout, e.g. "Free cancellation within 2
+    /// days; 50% of the deposit kept after that".
+    pub fn describe(&self) -> String {
+        let window = match self.full_refund_hours {
+            0 => None,
+            1 => Some("1 hour".to_string()),
+            24 => Some("1 day".to_string()),
+            h if h % 24 == 0 => Some(format!("{} days", h / 24)),
+            h => Some(format!("{h} hours")),
+        };
+        match (window, self.late_penalty_percent) {
+            (_, 0) => "Free cancellation".to_string(),
+            (None, p) => format!("{p}% of the deposit kept on cancellation"),
+            (Some(w), p) => format!("Free cancellation within {w}; {p}% of the deposit kept after that"),
+        }
+    }
 }
 
 /// Monotonic order status. Higher ordinal always wins in merge.
@@ -67,7 +164,9 @@ pub enum OrderStatus {
     Reserved { expires_at: DateTime<Utc> },
     /// Full payment received.
     Paid,
-    /// Product handed over to customer.
+    /// Supplier says the product was handed over; awaiting the customer's receipt.
+    FulfillmentProposed { proposed_at: DateTime<Utc> },
+    /// Product handed over to customer, confirmed by a [`FulfillmentReceipt`].
     Fulfilled,
     /// Order cancelled (by customer or supplier).
     Cancelled,
@@ -83,15 +182,22 @@ impl OrderStatus {
             OrderStatus::Paid => 1,
             OrderStatus::Cancelled => 2,
             OrderStatus::Expired => 2,
-            OrderStatus::Fulfilled => 3,
+            OrderStatus::FulfillmentProposed { .. } => 3,
+            OrderStatus::Fulfilled => 4,
         }
     }
 
+    /// Nothing more can happen to the order: fulfilled, cancelled or expired.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderStatus::Fulfilled | OrderStatus::Cancelled | OrderStatus::Expired)
+    }
+
     /// Human-readable status label.
     pub fn label(&self) -> &'static str {
         match self {
             OrderStatus::Reserved { .. } => "Reserved",
             OrderStatus::Paid => "Paid",
+            OrderStatus::FulfillmentProposed { .. } => "Awaiting confirmation",
             OrderStatus::Fulfilled => "Fulfilled",
             OrderStatus::Cancelled => "Cancelled",
             OrderStatus::Expired => "Expired",
@@ -99,15 +205,19 @@ impl OrderStatus {
     }
 
     /// Returns true if transitioning from self to `next` is valid.
+    ///
+    /// Fulfillment is a two-party handshake: the supplier proposes, then the
+    /// customer's receipt (or an auto-confirm after the window) completes it.
     pub fn can_transition_to(&self, next: &OrderStatus) -> bool {
         matches!(
             (self, next),
             (OrderStatus::Reserved { .. }, OrderStatus::Paid)
-                | (OrderStatus::Reserved { .. }, OrderStatus::Fulfilled)
+                | (OrderStatus::Reserved { .. }, OrderStatus::FulfillmentProposed { .. })
                 | (OrderStatus::Reserved { .. }, OrderStatus::Cancelled)
                 | (OrderStatus::Reserved { .. }, OrderStatus::Expired)
-                | (OrderStatus::Paid, OrderStatus::Fulfilled)
+                | (OrderStatus::Paid, OrderStatus::FulfillmentProposed { .. })
                 | (OrderStatus::Paid, OrderStatus::Cancelled)
+                | (OrderStatus::FulfillmentProposed { .. }, OrderStatus::Fulfilled)
         )
     }
 }
@@ -118,22 +228,152 @@ impl fmt::Display for OrderStatus {
     }
 }
 
+impl fmt::Display for CollectionPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectionPoint::FarmGate => f.write_str("Farm gate"),
+            CollectionPoint::Market { market_name } => write!(f, "{} market", market_name),
+            CollectionPoint::Pickup { location_name } => f.write_str(location_name),
+        }
+    }
+}
+
 impl fmt::Display for DepositTier {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(self.label())
     }
 }
 
+/// Confirmation that an order was handed over, required for `Fulfilled`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FulfillmentReceipt {
+    /// When the supplier proposed fulfillment (copied from the proposal status).
+    pub proposed_at: DateTime<Utc>,
+    pub confirmed_at: DateTime<Utc>,
+    /// Signed by the supplier because the customer let the confirmation
+    /// window lapse, rather than counter-signed by the customer.
+    #[serde(default)]
+    pub auto_confirmed: bool,
+    /// Customer's (or, if auto-confirmed, the supplier's) signature over
+    /// `storefront::receipt_signable_bytes`.
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl FulfillmentReceipt {
+    /// Earliest time the supplier may auto-confirm a proposal made at `proposed_at`.
+    pub fn auto_confirm_after(proposed_at: DateTime<Utc>) -> DateTime<Utc> {
+        proposed_at + chrono::Duration::hours(FULFILLMENT_AUTO_CONFIRM_HOURS)
+    }
+}
+
+/// Prefix of an encoded [`PickupToken`].
+const PICKUP_TOKEN_PREFIX: &str = "cream-pickup:";
+
+/// A customer's signed "I have collected this order", shown as a QR code at
+/// pickup and scanned (or typed in) by the supplier.
+///
+/// The signature is a customer-signed [`FulfillmentReceipt`] proposed and
+/// confirmed at `issued_at`, so a verified token can be attached to the
+/// order as-is and the storefront contract accepts the `Fulfilled` status
+/// without a separate confirmation round-trip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickupToken {
+    pub order_id: OrderId,
+    /// Whole seconds, so the token survives its text encoding.
+    pub issued_at: DateTime<Utc>,
+    pub signature: Signature,
+}
+
+impl PickupToken {
+    /// Bytes the customer signs for `order` at `issued_at`.
+    pub fn signable_bytes(order: &Order, issued_at: &DateTime<Utc>) -> Vec<u8> {
+        crate::storefront::receipt_signable_bytes(order, issued_at, issued_at, false)
+    }
+
+    /// Text form carried by the QR code:
+    /// `cream-pickup:<order id>:<unix seconds>:<signature hex>`.
+    pub fn encode(&self) -> String {
+        let signature: String = self.signature.to_bytes().iter().map(|b| format!("{b:02x}")).collect();
+        format!(
+            "{PICKUP_TOKEN_PREFIX}{}:{}:{signature}",
+            self.order_id.0,
+            self.issued_at.timestamp()
+        )
+    }
+
+    /// Parse the output of [`PickupToken::encode`]. Surrounding whitespace is
+    /// ignored (scanners often append a newline).
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let rest = text
+            .trim()
+            .strip_prefix(PICKUP_TOKEN_PREFIX)
+            .ok_or("Not a pickup token")?;
+        // Order ids may contain ':', so split the fixed fields off the end
+        let mut fields = rest.rsplitn(3, ':');
+        let (Some(signature), Some(secs), Some(order_id)) = (fields.next(), fields.next(), fields.next()) else {
+            return Err("Malformed pickup token".into());
+        };
+        let secs: i64 = secs.parse().map_err(|_| "Bad pickup token timestamp")?;
+        let issued_at = DateTime::from_timestamp(secs, 0).ok_or("Bad pickup token timestamp")?;
+        if signature.len() != 128 {
+            return Err("Bad pickup token signature".into());
+        }
+        let mut bytes = [0u8; 64];
+        for (i, chunk) in signature.as_bytes().chunks(2).enumerate() {
+            let hex = std::str::from_utf8(chunk).map_err(|e| e.to_string())?;
+            bytes[i] = u8::from_str_radix(hex, 16).map_err(|_| "Bad pickup token signature")?;
+        }
+        Ok(Self {
+            order_id: OrderId(order_id.to_string()),
+            issued_at,
+            signature: Signature::from_bytes(&bytes),
+        })
+    }
+
+    /// Whether the token is for `order` and signed by its customer.
+    pub fn verify(&self, order: &Order) -> bool {
+        if self.order_id != order.id {
+            return false;
+        }
+        #[cfg(feature = "dev")]
+        {
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            use ed25519_dalek::Verifier;
+            let msg = Self::signable_bytes(order, &self.issued_at);
+            order.customer.0.verify(&msg, &self.signature).is_ok()
+        }
+    }
+
+    /// The customer receipt this token stands for.
+    pub fn into_receipt(self) -> FulfillmentReceipt {
+        FulfillmentReceipt {
+            proposed_at: self.issued_at,
+            confirmed_at: self.issued_at,
+            auto_confirmed: false,
+            signature: self.signature,
+            extra: Default::default(),
+        }
+    }
+}
+
 /// An order placed by a customer for a product.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Order {
     pub id: OrderId,
     pub product_id: ProductId,
     pub customer: UserId,
     pub quantity: u32,
     pub deposit_tier: DepositTier,
-    pub deposit_amount: u64,
-    pub total_price: u64,
+    /// In curds, as are all amounts (see [`crate::currency`]).
+    pub deposit_amount: Curds,
+    pub total_price: Curds,
     pub status: OrderStatus,
     pub created_at: DateTime<Utc>,
     /// Customer's signature over the order data.
@@ -146,6 +386,350 @@ pub struct Order {
     /// Not included in SignableOrder so existing signatures remain valid.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub collection_point: Option<CollectionPoint>,
+    /// Delivery address and fee, for delivery orders; `None` for pickup.
+    /// Signed with the order when present; the storefront contract checks
+    /// a new order's against the supplier's delivery zones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<crate::delivery::DeliveryDetails>,
+    /// Who last changed this order's status (customer or supplier).
+    /// Not included in SignableOrder so existing signatures remain valid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<crate::provenance::WriterStamp>,
+    /// Fulfillment receipt; present once the order is `Fulfilled`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<FulfillmentReceipt>,
+    /// Changes the customer asked for after placing the order, oldest first.
+    /// Not included in SignableOrder: each amendment carries its own
+    /// signatures (see [`OrderAmendment`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub amendments: Vec<OrderAmendment>,
+    /// Every status the order has had, oldest first, with when and by whom.
+    /// Not included in SignableOrder; covered by the provenance stamp.
+    /// Empty for orders placed before it was recorded.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub status_history: Vec<StatusChange>,
+    /// The storefront's cancellation policy when the order was placed; the
+    /// terms any refund is held to. Signed with the order when present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancellation_policy: Option<CancellationPolicy>,
+    /// Deposit returned to the customer, set when the order is cancelled.
+    /// `None` for orders cancelled before refunds were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refund_amount: Option<Curds>,
+    /// The customer's balance when placing the order, for storefronts that
+    /// require one above a threshold. Not included in SignableOrder: it
+    /// carries the customer's own signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance_attestation: Option<crate::attestation::BalanceAttestation>,
+    /// Random input to the order's content id; 0 for orders with a legacy id.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub nonce: u64,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Order {
+    /// Whether the order's id is the one its content derives. Legacy
+    /// timestamp ids can't be checked and always pass.
+    pub fn id_matches_content(&self) -> bool {
+        !self.id.is_content_id() || self.id == OrderId::derive(&self.customer, &self.product_id, self.created_at, self.nonce)
+    }
+
+    /// Whether `other` is this order at another stage rather than a different
+    /// order filed under the same id.
+    pub fn same_order(&self, other: &Order) -> bool {
+        self.id == other.id && self.customer == other.customer && self.product_id == other.product_id
+    }
+
+    /// Move the order to `status`, recording the change in its history.
+    pub fn set_status(&mut self, status: OrderStatus, actor: Option<UserId>, at: DateTime<Utc>) {
+        self.status_history.push(StatusChange::new(status.clone(), actor, at));
+        self.status = status;
+    }
+
+    /// Check the order's timestamps against `now`, the contract host's clock:
+    /// none in the future, none before the order was placed, and no
+    /// reservation held longer than its tier allows.
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        check_not_future("order created_at", self.created_at, now)?;
+        match self.status {
+            OrderStatus::Reserved { expires_at } => {
+                let latest = self.created_at + self.deposit_tier.hold_period() + max_clock_skew();
+                check_within("reservation expires_at", expires_at, latest)?;
+            }
+            OrderStatus::FulfillmentProposed { proposed_at } => {
+                check_not_future("fulfillment proposed_at", proposed_at, now)?;
+                check_order(
+                    "fulfillment proposed_at",
+                    proposed_at,
+                    "order created_at",
+                    self.created_at,
+                )?;
+            }
+            _ => {}
+        }
+        if let Some(receipt) = &self.receipt {
+            check_not_future("receipt proposed_at", receipt.proposed_at, now)?;
+            check_not_future("receipt confirmed_at", receipt.confirmed_at, now)?;
+        }
+        for amendment in &self.amendments {
+            check_not_future("amendment requested_at", amendment.requested_at, now)?;
+            if let Some(ack) = &amendment.acknowledgement {
+                check_not_future("amendment acknowledged_at", ack.acknowledged_at, now)?;
+            }
+        }
+        for change in &self.status_history {
+            check_not_future("status change at", change.at, now)?;
+        }
+        if let Some(attestation) = &self.balance_attestation {
+            check_not_future("balance attested_at", attestation.attested_at, now)?;
+        }
+        Ok(())
+    }
+
+    /// Check that an update to this order leaves the timestamps fixed when
+    /// it was placed alone: when it was created and, while it is still
+    /// reserved, when the reservation lapses. A receipt answering a
+    /// proposal must carry the time the proposal was made, so a supplier
+    /// can't backdate it past the auto-confirm window.
+    pub fn check_timestamp_progress(&self, update: &Order) -> Result<(), TimestampError> {
+        check_unchanged("order created_at", self.created_at, update.created_at)?;
+        if let (
+            OrderStatus::Reserved { expires_at: from },
+            OrderStatus::Reserved { expires_at: to },
+        ) = (&self.status, &update.status)
+        {
+            check_unchanged("reservation expires_at", *from, *to)?;
+        }
+        if let (OrderStatus::FulfillmentProposed { proposed_at }, Some(receipt)) = (&self.status, &update.receipt) {
+            check_unchanged("receipt proposed_at", *proposed_at, receipt.proposed_at)?;
+        }
+        Ok(())
+    }
+
+    /// Whether the status history reads forward, as the merge does: each
+    /// change a higher [`OrderStatus::ordinal`] than the one before, none
+    /// earlier than the one before (or than the order), and none beyond the
+    /// order's current status. A history may lag the status when a writer
+    /// didn't record a change.
+    pub fn status_history_valid(&self) -> bool {
+        let mut at = self.created_at;
+        let mut previous: Option<&OrderStatus> = None;
+        for change in &self.status_history {
+            if change.at < at || previous.is_some_and(|p| p.ordinal() >= change.status.ordinal()) {
+                return false;
+            }
+            at = change.at;
+            previous = Some(&change.status);
+        }
+        previous.is_none_or(|last| last.ordinal() <= self.status.ordinal())
+    }
+
+    /// The refund due on cancelling the order at `at` as `canceller`: the
+    /// escrowed deposit, less the policy's penalty when it's the customer
+    /// cancelling.
+    pub fn refund_due(&self, canceller: Option<&UserId>, at: DateTime<Utc>) -> Curds {
+        let deposit = self.escrowed_deposit();
+        match &self.cancellation_policy {
+            Some(policy) if canceller == Some(&self.customer) => policy.refund(deposit, self.created_at, at),
+            _ => deposit,
+        }
+    }
+
+    /// Mark the order cancelled by `canceller` at `at`, recording the refund
+    /// due. Returns the refund.
+    pub fn cancel(&mut self, canceller: UserId, at: DateTime<Utc>) -> Curds {
+        let refund = self.refund_due(Some(&canceller), at);
+        self.set_status(OrderStatus::Cancelled, Some(canceller), at);
+        self.refund_amount = Some(refund);
+        refund
+    }
+
+    /// Whether the recorded refund is the one the order's terms allow: none
+    /// before cancellation, and on cancellation what [`Order::refund_due`]
+    /// gives for whoever cancelled it, when, and never more than the escrowed
+    /// deposit. A recorded refund needs the `Cancelled` change in the
+    /// history, made by the writer of the order's stamp. A cancellation
+    /// without a recorded refund predates them.
+    pub fn cancellation_refund_valid(&self) -> bool {
+        if self.cancellation_policy.is_some_and(|p| !p.is_valid()) {
+            return false;
+        }
+        let Some(refund) = self.refund_amount else {
+            return true;
+        };
+        if self.status != OrderStatus::Cancelled || refund > self.escrowed_deposit() {
+            return false;
+        }
+        let writer = self.provenance.as_ref().map(|stamp| stamp.last_writer);
+        let cancelled = self.status_history.iter().rev().find(|c| c.status == OrderStatus::Cancelled);
+        cancelled.is_some_and(|change| {
+            let Some(actor) = change.actor.as_ref().filter(|actor| Some(actor.0) == writer) else {
+                return false;
+            };
+            refund == self.refund_due(Some(actor), change.at)
+        })
+    }
+
+    /// Price per unit, as placed.
+    pub fn unit_price(&self) -> Curds {
+        self.total_price / u64::from(self.quantity.max(1))
+    }
+
+    /// The latest amendment the supplier accepted, if any.
+    pub fn accepted_amendment(&self) -> Option<&OrderAmendment> {
+        self.amendments.iter().rev().find(|a| a.is_accepted())
+    }
+
+    /// The amendment awaiting the supplier's answer, if any.
+    pub fn pending_amendment(&self) -> Option<&OrderAmendment> {
+        self.amendments.last().filter(|a| a.acknowledgement.is_none())
+    }
+
+    /// Quantity after any accepted amendment.
+    pub fn current_quantity(&self) -> u32 {
+        self.accepted_amendment().map_or(self.quantity, |a| a.quantity)
+    }
+
+    /// Total price after any accepted amendment.
+    pub fn current_total_price(&self) -> Curds {
+        self.accepted_amendment().map_or(self.total_price, |a| a.total_price)
+    }
+
+    /// Deposit held in escrow after any accepted amendment.
+    pub fn current_deposit(&self) -> Curds {
+        self.accepted_amendment().map_or(self.deposit_amount, |a| a.deposit_amount)
+    }
+
+    /// Deposit in escrow: the current deposit, plus the top-up paid with a
+    /// pending amendment that raises it.
+    pub fn escrowed_deposit(&self) -> Curds {
+        let current = self.current_deposit();
+        current + self.pending_amendment().map_or(0, |a| a.deposit_amount.saturating_sub(current))
+    }
+
+    /// Collection point after any accepted amendment.
+    pub fn current_collection_point(&self) -> Option<&CollectionPoint> {
+        match self.accepted_amendment() {
+            Some(amendment) => amendment.collection_point.as_ref(),
+            None => self.collection_point.as_ref(),
+        }
+    }
+
+    /// Whether the customer may ask for a change: the order isn't settled
+    /// or being handed over, and no earlier request is still unanswered.
+    pub fn can_amend(&self) -> bool {
+        matches!(self.status, OrderStatus::Reserved { .. } | OrderStatus::Paid)
+            && self.pending_amendment().is_none()
+    }
+
+    /// An unsigned amendment to `quantity` and `collection_point`, with the
+    /// price at the order's unit price and the deposit recalculated for the
+    /// order's tier.
+    pub fn amendment(
+        &self,
+        quantity: u32,
+        collection_point: Option<CollectionPoint>,
+        requested_at: DateTime<Utc>,
+    ) -> OrderAmendment {
+        let total_price = self.unit_price() * u64::from(quantity);
+        OrderAmendment {
+            seq: self.amendments.len() as u32 + 1,
+            quantity,
+            collection_point,
+            total_price,
+            deposit_amount: self.deposit_tier.calculate_deposit(total_price),
+            requested_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            acknowledgement: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// Where the amendment chain stands, for delta sync: two steps per
+    /// amendment (requested, answered).
+    pub fn amendment_progress(&self) -> u32 {
+        let answered = self.amendments.iter().filter(|a| a.acknowledgement.is_some()).count();
+        (self.amendments.len() + answered) as u32
+    }
+}
+
+/// One entry in an order's [`Order::status_history`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusChange {
+    pub status: OrderStatus,
+    pub at: DateTime<Utc>,
+    /// Who made the change: the customer or a supplier key. `None` for a
+    /// change nobody made, such as a reservation expiring.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<UserId>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StatusChange {
+    pub fn new(status: OrderStatus, actor: Option<UserId>, at: DateTime<Utc>) -> Self {
+        StatusChange { status, at, actor, extra: Default::default() }
+    }
+}
+
+/// A customer's change to a placed order — a new quantity and/or collection
+/// point — before fulfillment.
+///
+/// Amendments form a chain on the order: numbered from 1, each signed by the
+/// customer over the order and its new terms, and each answered by the
+/// supplier before the next may be made. An accepted amendment replaces the
+/// order's terms; the deposit follows the new total at the order's tier, and
+/// the difference moves through escrow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderAmendment {
+    /// Position in the order's chain, from 1.
+    pub seq: u32,
+    pub quantity: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection_point: Option<CollectionPoint>,
+    /// `quantity` at the order's unit price.
+    pub total_price: Curds,
+    /// Deposit for `total_price` at the order's tier.
+    pub deposit_amount: Curds,
+    pub requested_at: DateTime<Utc>,
+    /// Customer's signature over `storefront::amendment_signable_bytes`.
+    pub signature: Signature,
+    /// The supplier's answer; the amendment is pending until present.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledgement: Option<AmendmentAck>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl OrderAmendment {
+    pub fn is_accepted(&self) -> bool {
+        self.acknowledgement.as_ref().is_some_and(|ack| ack.accepted)
+    }
+
+    /// The same request, ignoring the supplier's answer.
+    pub fn same_request(&self, other: &OrderAmendment) -> bool {
+        OrderAmendment {
+            acknowledgement: None,
+            ..self.clone()
+        } == OrderAmendment {
+            acknowledgement: None,
+            ..other.clone()
+        }
+    }
+}
+
+/// The supplier's answer to an [`OrderAmendment`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AmendmentAck {
+    /// Whether the supplier agreed to the change.
+    pub accepted: bool,
+    pub acknowledged_at: DateTime<Utc>,
+    /// Supplier's signature over `storefront::amendment_ack_signable_bytes`.
+    pub signature: Signature,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
@@ -162,20 +746,42 @@ mod tests {
         assert_eq!(DepositTier::FullPayment.calculate_deposit(1000), 1000);
     }
 
+    #[test]
+    fn content_ids_name_their_order() {
+        let alice = UserId(ed25519_dalek::SigningKey::from_bytes(&[5u8; 32]).verifying_key());
+        let bob = UserId(ed25519_dalek::SigningKey::from_bytes(&[6u8; 32]).verifying_key());
+        let milk = ProductId("p-1".into());
+        let at = Utc::now();
+        let id = OrderId::derive(&alice, &milk, at, 7);
+        assert!(id.is_content_id());
+        assert_eq!(id, OrderId::derive(&alice, &milk, at, 7));
+        assert_ne!(id, OrderId::derive(&alice, &milk, at, 8));
+        assert_ne!(id, OrderId::derive(&bob, &milk, at, 7));
+        assert!(!OrderId("o-1712345678901".into()).is_content_id());
+    }
+
     #[test]
     fn test_status_transitions() {
         let reserved = OrderStatus::Reserved {
             expires_at: Utc::now(),
         };
+        let proposed = OrderStatus::FulfillmentProposed {
+            proposed_at: Utc::now(),
+        };
         assert!(reserved.can_transition_to(&OrderStatus::Paid));
         assert!(reserved.can_transition_to(&OrderStatus::Cancelled));
         assert!(reserved.can_transition_to(&OrderStatus::Expired));
-        assert!(reserved.can_transition_to(&OrderStatus::Fulfilled));
+        assert!(reserved.can_transition_to(&proposed));
+        // Fulfillment needs the customer's receipt: no direct jump to Fulfilled
+        assert!(!reserved.can_transition_to(&OrderStatus::Fulfilled));
 
-        assert!(OrderStatus::Paid.can_transition_to(&OrderStatus::Fulfilled));
+        assert!(OrderStatus::Paid.can_transition_to(&proposed));
+        assert!(!OrderStatus::Paid.can_transition_to(&OrderStatus::Fulfilled));
         assert!(OrderStatus::Paid.can_transition_to(&OrderStatus::Cancelled));
         assert!(!OrderStatus::Paid.can_transition_to(&OrderStatus::Expired));
 
+        assert!(proposed.can_transition_to(&OrderStatus::Fulfilled));
+        assert!(!proposed.can_transition_to(&OrderStatus::Cancelled));
         assert!(!OrderStatus::Fulfilled.can_transition_to(&OrderStatus::Cancelled));
     }
 
@@ -185,8 +791,164 @@ mod tests {
             expires_at: Utc::now(),
         };
         assert!(reserved.ordinal() < OrderStatus::Paid.ordinal());
-        assert!(OrderStatus::Paid.ordinal() < OrderStatus::Fulfilled.ordinal());
+        let proposed = OrderStatus::FulfillmentProposed {
+            proposed_at: Utc::now(),
+        };
+        assert!(OrderStatus::Paid.ordinal() < proposed.ordinal());
+        assert!(OrderStatus::Cancelled.ordinal() < proposed.ordinal());
+        assert!(proposed.ordinal() < OrderStatus::Fulfilled.ordinal());
         assert!(reserved.ordinal() < OrderStatus::Cancelled.ordinal());
         assert!(reserved.ordinal() < OrderStatus::Expired.ordinal());
     }
+
+    #[test]
+    fn cancellation_refunds_follow_the_policy_for_customers_only() {
+        use ed25519_dalek::SigningKey;
+
+        let customer = UserId(SigningKey::from_bytes(&[3u8; 32]).verifying_key());
+        let supplier = UserId(SigningKey::from_bytes(&[4u8; 32]).verifying_key());
+        let placed_at = Utc::now() - chrono::Duration::days(3);
+        let policy = CancellationPolicy { full_refund_hours: 24, late_penalty_percent: 25 };
+        assert_eq!(policy.describe(), "Free cancellation within 1 day; 25% of the deposit kept after that");
+        assert_eq!(policy.penalty(1000, placed_at, placed_at + chrono::Duration::hours(24)), 0);
+        assert_eq!(policy.refund(1000, placed_at, placed_at + chrono::Duration::hours(25)), 750);
+        // Rounded in the customer's favour
+        assert_eq!(policy.penalty(999, placed_at, placed_at + chrono::Duration::hours(25)), 249);
+
+        let order = Order {
+            id: OrderId("o-1".into()),
+            product_id: ProductId("p-1".into()),
+            customer: customer.clone(),
+            quantity: 2,
+            deposit_tier: DepositTier::FullPayment,
+            deposit_amount: 1000,
+            total_price: 1000,
+            status: OrderStatus::Paid,
+            created_at: placed_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: Some(policy),
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        };
+        assert!(order.cancellation_refund_valid());
+        let stamp = |writer: &UserId| {
+            Some(crate::provenance::WriterStamp {
+                last_writer: writer.0,
+                written_at: Utc::now(),
+                signature: Signature::from_bytes(&[0u8; 64]),
+                extra: Default::default(),
+            })
+        };
+
+        let mut early = order.clone();
+        assert_eq!(early.cancel(customer.clone(), placed_at + chrono::Duration::hours(1)), 1000);
+        early.provenance = stamp(&customer);
+        assert!(early.cancellation_refund_valid());
+
+        let mut late = order.clone();
+        assert_eq!(late.cancel(customer.clone(), Utc::now()), 750);
+        late.provenance = stamp(&customer);
+        assert!(late.cancellation_refund_valid());
+        late.refund_amount = Some(1000);
+        assert!(!late.cancellation_refund_valid());
+
+        // The supplier cancelling refunds in full, however late
+        let mut by_supplier = order.clone();
+        assert_eq!(by_supplier.cancel(supplier.clone(), Utc::now()), 1000);
+        by_supplier.provenance = stamp(&supplier);
+        assert!(by_supplier.cancellation_refund_valid());
+        by_supplier.refund_amount = Some(750);
+        assert!(!by_supplier.cancellation_refund_valid());
+
+        // The canceller must be the one who stamped the order, and be named
+        let mut claimed = order.clone();
+        claimed.cancel(supplier.clone(), Utc::now());
+        claimed.provenance = stamp(&customer);
+        assert!(!claimed.cancellation_refund_valid());
+        let mut anonymous = late.clone();
+        anonymous.refund_amount = Some(1000);
+        anonymous.status_history.last_mut().unwrap().actor = None;
+        assert!(!anonymous.cancellation_refund_valid());
+
+        // A refund needs a recorded cancellation, and can't exceed the deposit
+        let mut unrecorded = order.clone();
+        unrecorded.status = OrderStatus::Cancelled;
+        unrecorded.refund_amount = Some(1000);
+        assert!(!unrecorded.cancellation_refund_valid());
+        let mut generous = by_supplier.clone();
+        generous.deposit_amount = 500;
+        generous.refund_amount = Some(1000);
+        assert!(!generous.cancellation_refund_valid());
+
+        // No refund on an order that isn't cancelled, and no penalty over 100%
+        let mut premature = order.clone();
+        premature.refund_amount = Some(0);
+        assert!(!premature.cancellation_refund_valid());
+        let mut greedy = order;
+        greedy.cancellation_policy = Some(CancellationPolicy { full_refund_hours: 0, late_penalty_percent: 150 });
+        assert!(!greedy.cancellation_refund_valid());
+    }
+
+    #[test]
+    fn test_pickup_token_round_trip_and_receipt() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let customer = SigningKey::from_bytes(&[3u8; 32]);
+        let order = Order {
+            id: OrderId("o-12:34".into()),
+            product_id: ProductId("p-1".into()),
+            customer: UserId(customer.verifying_key()),
+            quantity: 2,
+            deposit_tier: DepositTier::FullPayment,
+            deposit_amount: 500,
+            total_price: 500,
+            status: OrderStatus::Paid,
+            created_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        };
+        let issued_at = DateTime::from_timestamp(Utc::now().timestamp(), 0).unwrap();
+        let token = PickupToken {
+            order_id: order.id.clone(),
+            issued_at,
+            signature: customer.sign(&PickupToken::signable_bytes(&order, &issued_at)),
+        };
+
+        let decoded = PickupToken::decode(&format!("{}\n", token.encode())).unwrap();
+        assert_eq!(decoded, token);
+        assert!(decoded.verify(&order));
+        assert!(PickupToken::decode("cream-pickup:o-1:nope:00").is_err());
+        assert!(PickupToken::decode("hello").is_err());
+
+        let mut other = order.clone();
+        other.id = OrderId("o-99".into());
+        assert!(!decoded.verify(&other));
+
+        // The token doubles as the customer's fulfillment receipt
+        let mut fulfilled = order.clone();
+        fulfilled.status = OrderStatus::Fulfilled;
+        fulfilled.receipt = Some(decoded.into_receipt());
+        let owner = SigningKey::from_bytes(&[4u8; 32]).verifying_key();
+        assert!(crate::storefront::fulfillment_receipt_valid(&fulfilled, &[owner]));
+    }
 }
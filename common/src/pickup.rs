@@ -0,0 +1,79 @@
+//! Pickup locations: places away from the farm where a supplier hands over
+//! orders, such as a farmers' market stall.
+//!
+//! A storefront lists its pickup locations by name, each with where it is and
+//! when the supplier is there. An order collected at one names it in its
+//! [`CollectionPoint`]; the storefront contract checks that a pending order's
+//! location is still listed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::location::GeoLocation;
+use crate::order::{CollectionPoint, Order, OrderStatus};
+use crate::storefront::WeeklySchedule;
+
+/// A place other than the farm where orders can be collected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PickupLocation {
+    pub name: String,
+    pub location: GeoLocation,
+    /// When the supplier is there; `None` = by arrangement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<WeeklySchedule>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The listed pickup location called `name`, if any.
+pub fn pickup_location<'a>(locations: &'a [PickupLocation], name: &str) -> Option<&'a PickupLocation> {
+    locations.iter().find(|l| l.name == name)
+}
+
+/// Whether every location has a name of its own.
+pub fn names_unique(locations: &[PickupLocation]) -> bool {
+    locations.iter().enumerate().all(|(i, location)| {
+        !location.name.trim().is_empty() && !locations[..i].iter().any(|earlier| earlier.name == location.name)
+    })
+}
+
+/// Whether an order still awaiting hand-over is to be collected at a pickup
+/// location that isn't listed. As with delivery zones, settled orders are not
+/// re-checked, so a supplier can retire a location once its orders are done.
+pub fn order_pickup_unlisted(locations: &[PickupLocation], order: &Order) -> bool {
+    let pending = matches!(order.status, OrderStatus::Reserved { .. } | OrderStatus::Paid);
+    pending
+        && matches!(
+            order.current_collection_point(),
+            Some(CollectionPoint::Pickup { location_name }) if pickup_location(locations, location_name).is_none()
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market(name: &str) -> PickupLocation {
+        PickupLocation {
+            name: name.into(),
+            location: GeoLocation::new(-30.3, 153.1),
+            schedule: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn names_must_be_present_and_distinct() {
+        assert!(names_unique(&[market("Bellingen"), market("Dorrigo")]));
+        assert!(!names_unique(&[market("Bellingen"), market("Bellingen")]));
+        assert!(!names_unique(&[market(" ")]));
+        assert!(names_unique(&[]));
+    }
+
+    #[test]
+    fn locations_are_found_by_name() {
+        let locations = vec![market("Bellingen"), market("Dorrigo")];
+        assert_eq!(pickup_location(&locations, "Dorrigo").map(|l| l.name.as_str()), Some("Dorrigo"));
+        assert!(pickup_location(&locations, "Coffs").is_none());
+    }
+}
@@ -85,7 +85,7 @@ pub fn nearest_postcode(location: &GeoLocation) -> Option<PostcodeInfo> {
             let db = location.distance_km(&GeoLocation::new(b.1, b.2));
             da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
         })
-        .map(|e| entry_to_info(e))
+        .map(entry_to_info)
 }
 
 /// Look up full info for a postcode (first locality match).
@@ -1,10 +1,23 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-/// Unique product identifier (timestamp-based, monotonically increasing).
+use crate::currency::Curds;
+use crate::identity::UserId;
+
+/// Unique product identifier. New listings get a content id
+/// ([`ProductId::derive`]); older ones are timestamp-based ("p-1712345678901").
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct ProductId(pub String);
 
+impl ProductId {
+    /// The id of `supplier`'s listing first called `name`, created at
+    /// `created_at`, with a random `nonce`. The name may change later; the id
+    /// doesn't.
+    pub fn derive(supplier: &UserId, name: &str, created_at: DateTime<Utc>, nonce: u64) -> Self {
+        ProductId(crate::identity::content_id("p-", supplier, name, created_at, nonce))
+    }
+}
+
 /// Category of raw dairy product.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProductCategory {
@@ -17,20 +30,528 @@ pub enum ProductCategory {
     Other(String),
 }
 
+/// Second level of the category taxonomy, e.g. `Raw` under `Milk`.
+/// Which subcategories apply to a category is given by [`ProductCategory::subcategories`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Subcategory {
+    Raw,
+    Pasteurized,
+    A2,
+    Hard,
+    Soft,
+    Fresh,
+    Salted,
+    Unsalted,
+    Cultured,
+    Single,
+    Double,
+    Clotted,
+    Sour,
+    Natural,
+    Greek,
+    Other(String),
+}
+
+impl Subcategory {
+    pub fn label(&self) -> &str {
+        match self {
+            Subcategory::Raw => "Raw",
+            Subcategory::Pasteurized => "Pasteurized",
+            Subcategory::A2 => "A2",
+            Subcategory::Hard => "Hard",
+            Subcategory::Soft => "Soft",
+            Subcategory::Fresh => "Fresh",
+            Subcategory::Salted => "Salted",
+            Subcategory::Unsalted => "Unsalted",
+            Subcategory::Cultured => "Cultured",
+            Subcategory::Single => "Single",
+            Subcategory::Double => "Double",
+            Subcategory::Clotted => "Clotted",
+            Subcategory::Sour => "Sour",
+            Subcategory::Natural => "Natural",
+            Subcategory::Greek => "Greek",
+            Subcategory::Other(label) => label,
+        }
+    }
+}
+
+/// A typed product attribute a category can carry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// Butterfat content, percent.
+    FatPercent,
+    /// Maturation time, days.
+    AgingDays,
+}
+
+impl AttributeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            AttributeKind::FatPercent => "Fat %",
+            AttributeKind::AgingDays => "Aged (days)",
+        }
+    }
+}
+
+const MILK_SUBCATEGORIES: &[Subcategory] = &[Subcategory::Raw, Subcategory::Pasteurized, Subcategory::A2];
+const CHEESE_SUBCATEGORIES: &[Subcategory] = &[Subcategory::Hard, Subcategory::Soft, Subcategory::Fresh];
+const BUTTER_SUBCATEGORIES: &[Subcategory] = &[Subcategory::Salted, Subcategory::Unsalted, Subcategory::Cultured];
+const CREAM_SUBCATEGORIES: &[Subcategory] =
+    &[Subcategory::Single, Subcategory::Double, Subcategory::Clotted, Subcategory::Sour];
+const YOGURT_SUBCATEGORIES: &[Subcategory] = &[Subcategory::Natural, Subcategory::Greek];
+
+impl ProductCategory {
+    /// Top-level categories of the taxonomy, in display order.
+    pub const TOP_LEVEL: [ProductCategory; 6] = [
+        ProductCategory::Milk,
+        ProductCategory::Cheese,
+        ProductCategory::Butter,
+        ProductCategory::Cream,
+        ProductCategory::Yogurt,
+        ProductCategory::Kefir,
+    ];
+
+    pub fn label(&self) -> &str {
+        match self {
+            ProductCategory::Milk => "Milk",
+            ProductCategory::Cheese => "Cheese",
+            ProductCategory::Butter => "Butter",
+            ProductCategory::Cream => "Cream",
+            ProductCategory::Yogurt => "Yogurt",
+            ProductCategory::Kefir => "Kefir",
+            ProductCategory::Other(label) => label,
+        }
+    }
+
+    /// Parse a category label (case-insensitive); unknown labels become `Other`.
+    pub fn from_label(label: &str) -> Self {
+        Self::TOP_LEVEL
+            .into_iter()
+            .find(|c| c.label().eq_ignore_ascii_case(label))
+            .unwrap_or_else(|| ProductCategory::Other(label.to_string()))
+    }
+
+    /// Subcategories defined under this category.
+    pub fn subcategories(&self) -> &'static [Subcategory] {
+        match self {
+            ProductCategory::Milk => MILK_SUBCATEGORIES,
+            ProductCategory::Cheese => CHEESE_SUBCATEGORIES,
+            ProductCategory::Butter => BUTTER_SUBCATEGORIES,
+            ProductCategory::Cream => CREAM_SUBCATEGORIES,
+            ProductCategory::Yogurt => YOGURT_SUBCATEGORIES,
+            ProductCategory::Kefir | ProductCategory::Other(_) => &[],
+        }
+    }
+
+    /// The subcategory of this category with the given label (case-insensitive).
+    /// "Pasteurised" is accepted for `Pasteurized`.
+    pub fn subcategory_from_label(&self, label: &str) -> Option<Subcategory> {
+        let label = if label.eq_ignore_ascii_case("pasteurised") { "Pasteurized" } else { label };
+        self.subcategories()
+            .iter()
+            .find(|s| s.label().eq_ignore_ascii_case(label))
+            .cloned()
+    }
+
+    /// Attributes suppliers can record for products in this category.
+    pub fn attributes(&self) -> &'static [AttributeKind] {
+        match self {
+            ProductCategory::Cheese => &[AttributeKind::FatPercent, AttributeKind::AgingDays],
+            ProductCategory::Other(_) => &[],
+            _ => &[AttributeKind::FatPercent],
+        }
+    }
+
+    /// Map a legacy free-text category (from before the taxonomy), such as
+    /// `Other("A2 Milk")` or `Other("hard cheese")`, onto the taxonomy.
+    pub fn parse_legacy(label: &str) -> Option<(ProductCategory, Option<Subcategory>)> {
+        let label = label.trim();
+        let (qualifier, head) = match label.rsplit_once(char::is_whitespace) {
+            Some((qualifier, head)) => (Some(qualifier.trim()), head),
+            None => (None, label),
+        };
+        let category = Self::from_label(head);
+        if matches!(category, ProductCategory::Other(_)) {
+            return None;
+        }
+        match qualifier {
+            None => Some((category, None)),
+            Some(q) => {
+                let sub = category.subcategory_from_label(q)?;
+                Some((category, Some(sub)))
+            }
+        }
+    }
+}
+
+/// Typed attributes for a product. Which ones apply depends on the category.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProductAttributes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fat_percent: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aging_days: Option<u32>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProductAttributes {
+    pub fn is_empty(&self) -> bool {
+        self.fat_percent.is_none() && self.aging_days.is_none() && self.extra.is_empty()
+    }
+
+    /// Human-readable summary, e.g. "4.0% fat · aged 180 days".
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(fat) = self.fat_percent {
+            parts.push(format!("{:.1}% fat", fat));
+        }
+        if let Some(days) = self.aging_days {
+            parts.push(format!("aged {} days", days));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// When a listing counts as low on stock, and what to do about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LowStockRule {
+    /// Alert once fewer than this many units are available.
+    pub threshold: u32,
+    /// Also pause the listing once it's low.
+    #[serde(default)]
+    pub auto_pause: bool,
+}
+
+/// A time-limited promotional price. Part of the signed listing, so only the
+/// supplier (or staff) can put a product on special.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Special {
+    /// Price in curds while the special runs.
+    pub price_curd: Curds,
+    pub starts_at: DateTime<Utc>,
+    /// The first instant the special no longer applies.
+    pub ends_at: DateTime<Utc>,
+}
+
+impl Special {
+    /// A discount on `regular_price` over a non-empty window.
+    pub fn is_valid(&self, regular_price: u64) -> bool {
+        self.price_curd > 0 && self.price_curd < regular_price && self.starts_at < self.ends_at
+    }
+
+    /// Whether the special applies at `at`.
+    pub fn covers(&self, at: DateTime<Utc>) -> bool {
+        self.starts_at <= at && at < self.ends_at
+    }
+
+    /// Time left until the special ends, e.g. "2d 4h left" or "35 min left";
+    /// `None` once it's over.
+    pub fn time_left(&self, now: DateTime<Utc>) -> Option<String> {
+        let secs = u64::try_from((self.ends_at - now).num_seconds()).ok().filter(|s| *s > 0)?;
+        Some(match secs {
+            1..=3_599 => format!("{} min left", secs.div_ceil(60)),
+            3_600..=86_399 => format!("{}h {}m left", secs / 3_600, secs % 3_600 / 60),
+            _ => format!("{}d {}h left", secs / 86_400, secs % 86_400 / 3_600),
+        })
+    }
+}
+
+/// A lower unit price for orders of at least `min_quantity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PriceTier {
+    pub min_quantity: u32,
+    /// Price in curds per unit.
+    pub price_curd: Curds,
+}
+
+/// Terms for a wholesale listing: sold to other suppliers (shops, other
+/// farms) only, in quantities of at least `min_quantity`, optionally cheaper
+/// per unit the more is ordered. Part of the signed listing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WholesaleTerms {
+    pub min_quantity: u32,
+    /// Volume discounts, by ascending `min_quantity`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PriceTier>,
+}
+
+impl WholesaleTerms {
+    /// A minimum of at least one, and tiers above it that get strictly
+    /// cheaper than `regular_price` as the quantity rises.
+    pub fn is_valid(&self, regular_price: u64) -> bool {
+        let mut last = (self.min_quantity.saturating_sub(1), regular_price);
+        self.min_quantity > 0
+            && self.tiers.iter().all(|tier| {
+                let ok = tier.min_quantity > last.0 && tier.price_curd > 0 && tier.price_curd < last.1;
+                last = (tier.min_quantity, tier.price_curd);
+                ok
+            })
+    }
+
+    /// The unit price of `quantity` units, from a unit price of `base`: the
+    /// deepest tier reached, unless `base` (a special, say) is lower still.
+    pub fn unit_price(&self, base: Curds, quantity: u32) -> Curds {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| quantity >= tier.min_quantity)
+            .map_or(base, |tier| tier.price_curd.min(base))
+    }
+
+    /// "Min 10 · 20+ at 9.00 · 50+ at 8.00", with prices formatted by `format`.
+    pub fn summary(&self, format: impl Fn(Curds) -> String) -> String {
+        std::iter::once(format!("Min {}", self.min_quantity))
+            .chain(self.tiers.iter().map(|tier| format!("{}+ at {}", tier.min_quantity, format(tier.price_curd))))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
 /// A product listing in a supplier's storefront.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Product {
     pub id: ProductId,
     pub name: String,
     pub description: String,
     pub category: ProductCategory,
-    /// Price in smallest CURD unit.
-    pub price_curd: u64,
+    /// Position under `category` in the taxonomy.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subcategory: Option<Subcategory>,
+    #[serde(default, skip_serializing_if = "ProductAttributes::is_empty")]
+    pub attributes: ProductAttributes,
+    /// Price in curds.
+    pub price_curd: Curds,
     pub quantity_total: u32,
     pub expiry_date: Option<DateTime<Utc>>,
     pub updated_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    /// Low-stock alerting for the supplier's dashboard.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub low_stock: Option<LowStockRule>,
+    /// Not taking new orders. Part of the signed listing, so only the
+    /// supplier (or staff) can pause or resume it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
+    /// Promotional price, current or scheduled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub special: Option<Special>,
+    /// Set on listings sold only to other suppliers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wholesale: Option<WholesaleTerms>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
+
+impl Product {
+    /// The special running at `at`, if any.
+    pub fn special_at(&self, at: DateTime<Utc>) -> Option<&Special> {
+        self.special.as_ref().filter(|special| special.covers(at))
+    }
+
+    /// The unit price an order placed at `at` pays: the special's while one
+    /// runs, the listed price otherwise.
+    pub fn effective_price(&self, at: DateTime<Utc>) -> Curds {
+        self.special_at(at).map_or(self.price_curd, |special| special.price_curd)
+    }
+
+    /// The unit price an order of `quantity` placed at `at` pays: the
+    /// effective price, less any wholesale volume discount.
+    pub fn unit_price_for(&self, quantity: u32, at: DateTime<Utc>) -> Curds {
+        let base = self.effective_price(at);
+        self.wholesale.as_ref().map_or(base, |terms| terms.unit_price(base, quantity))
+    }
+
+    /// Whether an order may be for `quantity` units: at least the wholesale
+    /// minimum, if there is one.
+    pub fn accepts_quantity(&self, quantity: u32) -> bool {
+        self.wholesale.as_ref().is_none_or(|terms| quantity >= terms.min_quantity)
+    }
+
+    /// Whether `available` units is under the listing's low-stock threshold.
+    pub fn is_low_on_stock(&self, available: u32) -> bool {
+        self.low_stock.is_some_and(|rule| available < rule.threshold)
+    }
+
+    /// Whether the listing should be paused now that `available` units are
+    /// left: it's low, set to pause itself, and not paused already.
+    pub fn should_auto_pause(&self, available: u32) -> bool {
+        !self.paused && self.low_stock.is_some_and(|rule| rule.auto_pause) && self.is_low_on_stock(available)
+    }
+
+    /// Category and subcategory, reading legacy free-text categories through
+    /// the taxonomy so unmigrated listings still filter correctly.
+    pub fn taxonomy(&self) -> (ProductCategory, Option<Subcategory>) {
+        if let (ProductCategory::Other(label), None) = (&self.category, &self.subcategory) {
+            if let Some(parsed) = ProductCategory::parse_legacy(label) {
+                return parsed;
+            }
+        }
+        (self.category.clone(), self.subcategory.clone())
+    }
+
+    /// "Milk › Raw", or just "Milk".
+    pub fn category_label(&self) -> String {
+        match self.taxonomy() {
+            (category, Some(sub)) => format!("{} › {}", category.label(), sub.label()),
+            (category, None) => category.label().to_string(),
+        }
+    }
+
+    /// Rewrite a legacy free-text category into the taxonomy, bumping
+    /// `updated_at` so the change wins LWW merge. Returns true if the product
+    /// changed (and so must be re-signed).
+    pub fn migrate_legacy_category(&mut self, now: DateTime<Utc>) -> bool {
+        let (category, subcategory) = self.taxonomy();
+        if category == self.category && subcategory == self.subcategory {
+            return false;
+        }
+        self.category = category;
+        self.subcategory = subcategory;
+        self.updated_at = now;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn product(category: ProductCategory) -> Product {
+        let now = Utc::now();
+        Product {
+            id: ProductId("p-1".into()),
+            name: "Test".into(),
+            description: "".into(),
+            category,
+            subcategory: None,
+            attributes: ProductAttributes::default(),
+            price_curd: 100,
+            quantity_total: 1,
+            expiry_date: None,
+            updated_at: now,
+            created_at: now,
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn legacy_categories_map_onto_taxonomy() {
+        assert_eq!(
+            ProductCategory::parse_legacy("A2 Milk"),
+            Some((ProductCategory::Milk, Some(Subcategory::A2)))
+        );
+        assert_eq!(
+            ProductCategory::parse_legacy("pasteurised milk"),
+            Some((ProductCategory::Milk, Some(Subcategory::Pasteurized)))
+        );
+        assert_eq!(ProductCategory::parse_legacy("cheese"), Some((ProductCategory::Cheese, None)));
+        assert_eq!(ProductCategory::parse_legacy("Ghee"), None);
+        // Subcategory must belong to the category
+        assert_eq!(ProductCategory::parse_legacy("Greek Milk"), None);
+    }
+
+    #[test]
+    fn migrate_rewrites_only_recognised_legacy_categories() {
+        let mut legacy = product(ProductCategory::Other("Hard Cheese".into()));
+        let later = legacy.updated_at + chrono::Duration::seconds(1);
+        assert!(legacy.migrate_legacy_category(later));
+        assert_eq!(legacy.category, ProductCategory::Cheese);
+        assert_eq!(legacy.subcategory, Some(Subcategory::Hard));
+        assert_eq!(legacy.updated_at, later);
+        assert!(!legacy.migrate_legacy_category(later));
+
+        let mut ghee = product(ProductCategory::Other("Ghee".into()));
+        assert!(!ghee.migrate_legacy_category(later));
+        assert_eq!(ghee.category_label(), "Ghee");
+    }
+
+    #[test]
+    fn unset_taxonomy_fields_are_not_serialized() {
+        // Keeps existing product signatures valid
+        let json = serde_json::to_value(product(ProductCategory::Milk)).unwrap();
+        assert!(json.get("subcategory").is_none());
+        assert!(json.get("attributes").is_none());
+        assert!(json.get("low_stock").is_none());
+        assert!(json.get("paused").is_none());
+        assert!(json.get("special").is_none());
+        assert!(json.get("wholesale").is_none());
+
+        let mut p = product(ProductCategory::Cheese);
+        p.subcategory = Some(Subcategory::Hard);
+        p.attributes.aging_days = Some(180);
+        let back: Product = serde_json::from_value(serde_json::to_value(&p).unwrap()).unwrap();
+        assert_eq!(back.subcategory, Some(Subcategory::Hard));
+        assert_eq!(back.attributes.summary(), "aged 180 days");
+    }
+
+    #[test]
+    fn low_stock_rule_pauses_only_when_asked() {
+        let mut p = product(ProductCategory::Milk);
+        assert!(!p.is_low_on_stock(0));
+
+        p.low_stock = Some(LowStockRule { threshold: 3, auto_pause: false });
+        assert!(p.is_low_on_stock(2));
+        assert!(!p.is_low_on_stock(3));
+        assert!(!p.should_auto_pause(0));
+
+        p.low_stock = Some(LowStockRule { threshold: 3, auto_pause: true });
+        assert!(p.should_auto_pause(2));
+        assert!(!p.should_auto_pause(3));
+        p.paused = true;
+        assert!(!p.should_auto_pause(2));
+    }
+
+    #[test]
+    fn specials_apply_only_within_their_window() {
+        let mut p = product(ProductCategory::Milk);
+        p.price_curd = 100;
+        let start = p.created_at + chrono::Duration::days(1);
+        let special = Special { price_curd: 80, starts_at: start, ends_at: start + chrono::Duration::days(2) };
+        assert!(special.is_valid(100));
+        assert!(!Special { price_curd: 120, ..special }.is_valid(100));
+        assert!(!Special { ends_at: start, ..special }.is_valid(100));
+
+        p.special = Some(special);
+        assert_eq!(p.effective_price(start - chrono::Duration::seconds(1)), 100);
+        assert_eq!(p.effective_price(start), 80);
+        assert_eq!(p.effective_price(special.ends_at), 100);
+
+        assert_eq!(special.time_left(start).as_deref(), Some("2d 0h left"));
+        assert_eq!(special.time_left(special.ends_at - chrono::Duration::seconds(90)).as_deref(), Some("2 min left"));
+        assert_eq!(special.time_left(special.ends_at), None);
+    }
+
+    #[test]
+    fn wholesale_tiers_price_by_quantity() {
+        let mut crate_of_milk = product(ProductCategory::Milk);
+        crate_of_milk.wholesale = Some(WholesaleTerms {
+            min_quantity: 10,
+            tiers: vec![
+                PriceTier { min_quantity: 20, price_curd: 90 },
+                PriceTier { min_quantity: 50, price_curd: 80 },
+            ],
+        });
+        let terms = crate_of_milk.wholesale.clone().unwrap();
+        assert!(terms.is_valid(100));
+        let now = Utc::now();
+        assert!(!crate_of_milk.accepts_quantity(9));
+        assert_eq!(crate_of_milk.unit_price_for(10, now), 100);
+        assert_eq!(crate_of_milk.unit_price_for(20, now), 90);
+        assert_eq!(crate_of_milk.unit_price_for(75, now), 80);
+        // A special below the tier price still wins
+        assert_eq!(terms.unit_price(70, 50), 70);
+
+        let rising = WholesaleTerms { min_quantity: 10, tiers: vec![PriceTier { min_quantity: 20, price_curd: 110 }] };
+        assert!(!rising.is_valid(100));
+        let below_minimum = WholesaleTerms { min_quantity: 10, tiers: vec![PriceTier { min_quantity: 5, price_curd: 90 }] };
+        assert!(!below_minimum.is_valid(100));
+        assert!(!WholesaleTerms { min_quantity: 0, tiers: Vec::new() }.is_valid(100));
+    }
+}
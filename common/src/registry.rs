@@ -0,0 +1,306 @@
+//! Username registry: unique monikers mapped to their owners' user contracts.
+//!
+//! Monikers elsewhere are free text, so two users can both call themselves
+//! "Alice". The registry contract holds one owner-signed [`NameClaim`] per
+//! normalized name. The first claim to reach a registry holds the name;
+//! later claims by other owners are ignored, however early they are dated. Transfers, invites and messages resolve names through
+//! [`NameRegistryState::lookup`].
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::Signature;
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::limits::{check_count, check_len, LimitError, MAX_NAME_LEN, MAX_REGISTRY_NAMES};
+use crate::timestamps::{check_not_future, check_order, check_recent, check_unchanged, TimestampError};
+use crate::metrics::ContractMetrics;
+
+/// Canonical form of a name for uniqueness: trimmed, lowercased, with runs
+/// of whitespace collapsed, so "Alice" and " alice " are the same name.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An owner's signed claim to a name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NameClaim {
+    /// The name as the owner wrote it (display form).
+    pub name: String,
+    pub owner: UserId,
+    /// Encoded key of the owner's user contract.
+    pub user_contract_key: String,
+    /// When the name was first claimed, within the allowed skew of the
+    /// registry's clock at the time.
+    pub claimed_at: DateTime<Utc>,
+    /// Last change by the owner (e.g. a new user contract key).
+    pub updated_at: DateTime<Utc>,
+    /// Owner's signature over [`NameClaim::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableNameClaim<'a> {
+    domain: &'static str,
+    name: &'a str,
+    owner: &'a UserId,
+    user_contract_key: &'a str,
+    claimed_at: &'a DateTime<Utc>,
+    updated_at: &'a DateTime<Utc>,
+}
+
+impl NameClaim {
+    /// Serialize the claim for signing (everything except signature).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableNameClaim {
+            domain: "cream-name-claim",
+            name: &self.name,
+            owner: &self.owner,
+            user_contract_key: &self.user_contract_key,
+            claimed_at: &self.claimed_at,
+            updated_at: &self.updated_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Verify the claim is signed by its owner.
+    pub fn verify_signature(&self) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            self.owner.0.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// Whether this claim should replace `existing` for the same name: only
+    /// the owner's own later update does. A claim by anyone else never
+    /// displaces the one already held, since `claimed_at` is the claimant's
+    /// word and could be backdated.
+    fn supersedes(&self, existing: &NameClaim) -> bool {
+        self.owner == existing.owner && self.updated_at > existing.updated_at
+    }
+}
+
+/// The registry: normalized name → claim.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameRegistryState {
+    pub claims: BTreeMap<String, NameClaim>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NameRegistryState {
+    /// Merge another registry into this one, claim by claim (see [`NameClaim`]
+    /// for the conflict rule).
+    pub fn merge(&mut self, other: NameRegistryState) {
+        for (key, claim) in other.claims {
+            match self.claims.get(&key) {
+                Some(existing) if !claim.supersedes(existing) => {}
+                _ => {
+                    self.claims.insert(key, claim);
+                }
+            }
+        }
+    }
+
+    /// Validate that every claim is owner-signed and filed under its normalized name.
+    pub fn validate_all_signatures(&self) -> bool {
+        self.claims
+            .iter()
+            .all(|(key, claim)| *key == normalize_name(&claim.name) && claim.verify_signature())
+    }
+
+    /// Check the state against the size limits in [`crate::limits`].
+    pub fn check_limits(&self) -> Result<(), LimitError> {
+        check_count("registered names", self.claims.len(), MAX_REGISTRY_NAMES)?;
+        for claim in self.claims.values() {
+            check_len("registered name", &claim.name, MAX_NAME_LEN)?;
+            check_len("user contract key", &claim.user_contract_key, MAX_NAME_LEN)?;
+        }
+        Ok(())
+    }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`].
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for claim in self.claims.values() {
+            check_not_future("claim claimed_at", claim.claimed_at, now)?;
+            check_not_future("claim updated_at", claim.updated_at, now)?;
+            check_order("claim updated_at", claim.updated_at, "claimed_at", claim.claimed_at)?;
+        }
+        Ok(())
+    }
+
+    /// Check that every claim in `update` to a name not already its owner's
+    /// here was made within the allowed skew of `now`, the contract host's
+    /// clock, so a new claim can't be backdated.
+    pub fn check_claims_recent(&self, update: &NameRegistryState, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for (key, claim) in &update.claims {
+            if self.claims.get(key).is_none_or(|e| e.owner != claim.owner) {
+                check_recent("claim claimed_at", claim.claimed_at, now)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `update` doesn't redate an owner's existing claim, which
+    /// would pass it off as older than it is.
+    pub fn check_timestamp_progress(&self, update: &NameRegistryState) -> Result<(), TimestampError> {
+        for (key, claim) in &update.claims {
+            if let Some(existing) = self.claims.get(key).filter(|e| e.owner == claim.owner) {
+                check_unchanged("claim claimed_at", existing.claimed_at, claim.claimed_at)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The claim holding `name`, if any.
+    pub fn lookup(&self, name: &str) -> Option<&NameClaim> {
+        self.claims.get(&normalize_name(name))
+    }
+
+    /// Whether `owner` may claim `name`: it is free or already theirs.
+    pub fn is_available(&self, name: &str, owner: &UserId) -> bool {
+        self.lookup(name).is_none_or(|claim| claim.owner == *owner)
+    }
+
+    /// Names registered to `owner`.
+    pub fn names_of<'a>(&'a self, owner: &'a UserId) -> impl Iterator<Item = &'a NameClaim> + 'a {
+        self.claims.values().filter(move |claim| claim.owner == *owner)
+    }
+}
+
+/// Summary of registry state: normalized name → (owner, last update).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NameRegistrySummary {
+    pub versions: BTreeMap<String, (UserId, DateTime<Utc>)>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NameRegistryState {
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        ContractMetrics::new(state_bytes)
+            .count("claims", self.claims.len())
+            .updated("claims", self.claims.values().map(|c| c.updated_at).max())
+    }
+
+    pub fn summarize(&self) -> NameRegistrySummary {
+        NameRegistrySummary {
+            versions: self
+                .claims
+                .iter()
+                .map(|(key, claim)| (key.clone(), (claim.owner.clone(), claim.updated_at)))
+                .collect(),
+            extra: Default::default(),
+        }
+    }
+
+    /// Compute a delta: claims the summarizer lacks or holds in another version.
+    pub fn delta(&self, summary: &NameRegistrySummary) -> NameRegistryState {
+        let claims = self
+            .claims
+            .iter()
+            .filter(|(key, claim)| {
+                summary
+                    .versions
+                    .get(*key)
+                    .is_none_or(|(owner, updated_at)| *owner != claim.owner || *updated_at != claim.updated_at)
+            })
+            .map(|(key, claim)| (key.clone(), claim.clone()))
+            .collect();
+        NameRegistryState {
+            claims,
+            extra: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ed25519_dalek::SigningKey;
+
+    fn claim(seed: u8, name: &str, claimed_at: DateTime<Utc>) -> NameClaim {
+        NameClaim {
+            name: name.into(),
+            owner: UserId(SigningKey::from_bytes(&[seed; 32]).verifying_key()),
+            user_contract_key: format!("uc-{seed}"),
+            claimed_at,
+            updated_at: claimed_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    fn registry(claims: &[NameClaim]) -> NameRegistryState {
+        NameRegistryState {
+            claims: claims.iter().map(|c| (normalize_name(&c.name), c.clone())).collect(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn held_names_stay_with_their_owner_and_new_claims_must_be_fresh() {
+        let now = Utc::now();
+        let first = claim(1, "Alice", now - Duration::hours(1));
+        let second = claim(2, " alice ", now);
+        let backdated = claim(3, "ALICE", now - Duration::days(365));
+
+        let mut a = registry(std::slice::from_ref(&first));
+        a.merge(registry(std::slice::from_ref(&second)));
+        a.merge(registry(std::slice::from_ref(&backdated)));
+        assert_eq!(a.lookup("ALICE").unwrap().owner, first.owner);
+        assert!(!a.is_available("Alice", &second.owner));
+
+        // An earlier date doesn't take a name from whoever holds it
+        let mut b = registry(std::slice::from_ref(&second));
+        b.merge(registry(std::slice::from_ref(&first)));
+        assert_eq!(b.lookup("alice").unwrap().owner, second.owner);
+
+        let empty = NameRegistryState::default();
+        assert!(empty.check_claims_recent(&registry(std::slice::from_ref(&second)), now).is_ok());
+        assert!(matches!(
+            empty.check_claims_recent(&registry(&[backdated]), now),
+            Err(TimestampError::Stale { .. })
+        ));
+        // The owner's own claim may be old when they update it
+        assert!(a.check_claims_recent(&registry(&[first]), now).is_ok());
+    }
+
+    #[test]
+    fn owner_update_replaces_their_claim_and_travels_in_delta() {
+        let now = Utc::now();
+        let original = claim(1, "Alice", now - Duration::hours(1));
+        let mut reg = registry(std::slice::from_ref(&original));
+        let summary = reg.summarize();
+
+        let mut moved = original.clone();
+        moved.user_contract_key = "uc-new".into();
+        moved.updated_at = now;
+        let mut updated = reg.clone();
+        updated.merge(registry(&[moved]));
+        reg.merge(updated.delta(&summary));
+
+        assert_eq!(reg.lookup("alice").unwrap().user_contract_key, "uc-new");
+        assert!(reg.delta(&reg.summarize()).claims.is_empty());
+    }
+}
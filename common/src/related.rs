@@ -0,0 +1,285 @@
+//! Cross-validation between storefront and directory contracts, through
+//! Freenet's related contracts.
+//!
+//! A storefront is only valid while its owner has an entry in the directory
+//! (or one of its shards), and a directory entry is only valid if the
+//! storefront it points at belongs to the same supplier. Together they keep
+//! orphaned storefronts, and entries that claim someone else's storefront,
+//! off the network.
+//!
+//! Each check asks the node for the related states it needs with
+//! [`ValidateResult::RequestRelated`] and decides once they arrive.
+
+use std::collections::HashMap;
+
+use ed25519_dalek::VerifyingKey;
+use freenet_stdlib::prelude::{ContractInstanceId, RelatedContracts, State, ValidateResult};
+
+use crate::directory::{DirectoryShard, DirectoryShardParameters, DirectoryState};
+use crate::identity::UserId;
+use crate::storefront::StorefrontState;
+
+/// Instance ids of every directory contract — the unsharded directory and
+/// each shard — for directory code whose blake3 hash is `code_hash` (64 hex
+/// digits, as printed by `b3sum`). `None` if the hash doesn't parse.
+pub fn directory_instance_ids(code_hash: &str) -> Option<Vec<ContractInstanceId>> {
+    let code_hash = code_hash.trim();
+    if code_hash.len() != 64 || !code_hash.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&code_hash[2 * i..2 * i + 2], 16).ok()?;
+    }
+    // An instance id is the hash of the code hash followed by the parameters
+    let id = |params: Vec<u8>| {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&bytes);
+        hasher.update(&params);
+        ContractInstanceId::new(*hasher.finalize().as_bytes())
+    };
+    Some(
+        std::iter::once(Vec::new())
+            .chain(DirectoryShard::ALL.iter().map(|shard| DirectoryShardParameters::new(*shard).to_bytes()))
+            .map(id)
+            .collect(),
+    )
+}
+
+/// Related states the node has fetched so far: `Some(None)` for contracts it
+/// looked for and couldn't find, absent for ones not yet requested.
+fn fetched<'a>(related: &'a RelatedContracts<'static>) -> HashMap<ContractInstanceId, &'a Option<State<'static>>> {
+    related.states().map(|(id, state)| (*id, state)).collect()
+}
+
+/// Whether the storefront owned by `owner` is listed in one of
+/// `directories`. Valid as soon as one of them has an entry for the owner;
+/// requests the directories not yet fetched before giving up. No directories
+/// (cross-validation not configured) means no check.
+pub fn validate_storefront_listing(
+    owner: &VerifyingKey,
+    directories: &[ContractInstanceId],
+    related: &RelatedContracts<'static>,
+) -> ValidateResult {
+    if directories.is_empty() {
+        return ValidateResult::Valid;
+    }
+    let fetched = fetched(related);
+    let supplier = UserId(*owner);
+    let mut missing = Vec::new();
+    for id in directories {
+        match fetched.get(id) {
+            None => missing.push(*id),
+            Some(Some(state)) => {
+                let listed = serde_json::from_slice::<DirectoryState>(state.as_ref())
+                    .is_ok_and(|directory| directory.entries.contains_key(&supplier));
+                if listed {
+                    return ValidateResult::Valid;
+                }
+            }
+            Some(None) => {}
+        }
+    }
+    if missing.is_empty() {
+        ValidateResult::Invalid
+    } else {
+        ValidateResult::RequestRelated(missing)
+    }
+}
+
+/// Whether every entry in `directory` points at a storefront owned by the
+/// entry's supplier. Storefronts the node can't find are allowed — suppliers
+/// register in the directory before publishing their storefront, whose own
+/// contract then checks the listing — but one owned by someone else, or a
+/// contract that isn't a storefront, makes the directory invalid.
+pub fn validate_directory_storefronts(
+    directory: &DirectoryState,
+    related: &RelatedContracts<'static>,
+) -> ValidateResult {
+    let fetched = fetched(related);
+    let mut missing = Vec::new();
+    for entry in directory.entries.values() {
+        let id = *entry.storefront_key.id();
+        match fetched.get(&id) {
+            None => {
+                if !missing.contains(&id) {
+                    missing.push(id);
+                }
+            }
+            Some(Some(state)) => {
+                let owned = serde_json::from_slice::<StorefrontState>(state.as_ref())
+                    .is_ok_and(|storefront| storefront.info.owner == entry.supplier);
+                if !owned {
+                    return ValidateResult::Invalid;
+                }
+            }
+            Some(None) => {}
+        }
+    }
+    if missing.is_empty() {
+        ValidateResult::Valid
+    } else {
+        ValidateResult::RequestRelated(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+    use ed25519_dalek::{Signature, SigningKey};
+    use freenet_stdlib::prelude::{ContractCode, ContractKey, Parameters};
+
+    use crate::directory::DirectoryEntry;
+    use crate::location::GeoLocation;
+    use crate::storefront::StorefrontInfo;
+
+    fn key(seed: u8) -> VerifyingKey {
+        SigningKey::from_bytes(&[seed; 32]).verifying_key()
+    }
+
+    fn storefront_key(owner: &VerifyingKey) -> ContractKey {
+        let params = serde_json::to_vec(&serde_json::json!({ "owner": owner })).unwrap();
+        ContractKey::from_params_and_code(Parameters::from(params), ContractCode::from(b"storefront".to_vec()))
+    }
+
+    fn storefront(owner: &VerifyingKey) -> StorefrontState {
+        StorefrontState {
+            info: StorefrontInfo {
+                owner: UserId(*owner),
+                name: "Valley Farm".into(),
+                description: String::new(),
+                location: GeoLocation::new(0.0, 0.0),
+                schedule: None,
+                timezone: None,
+                phone: None,
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products: BTreeMap::new(),
+            orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        }
+    }
+
+    /// A directory listing `supplier` with a storefront at `storefront`.
+    fn directory(supplier: &VerifyingKey, storefront: ContractKey) -> DirectoryState {
+        let entry = DirectoryEntry {
+            supplier: UserId(*supplier),
+            name: "Valley Farm".into(),
+            description: String::new(),
+            location: GeoLocation::new(0.0, 0.0),
+            postcode: None,
+            locality: None,
+            categories: Vec::new(),
+            storefront_key: storefront,
+            user_contract_key: None,
+            inbox_contract_key: None,
+            updated_at: Utc::now(),
+            name_claimed_at: None,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        let mut directory = DirectoryState::default();
+        directory.entries.insert(entry.supplier.clone(), entry);
+        directory
+    }
+
+    fn related<T: serde::Serialize>(states: &[(ContractInstanceId, Option<&T>)]) -> RelatedContracts<'static> {
+        let map: HashMap<_, _> = states
+            .iter()
+            .map(|(id, state)| (*id, state.map(|s| State::from(serde_json::to_vec(s).unwrap()))))
+            .collect();
+        RelatedContracts::from(map)
+    }
+
+    #[test]
+    fn directory_ids_match_the_deployed_contracts() {
+        let code = ContractCode::from(b"directory wasm".to_vec());
+        let hex: String = code.hash().as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+
+        let ids = directory_instance_ids(&hex).unwrap();
+        assert_eq!(ids.len(), 1 + DirectoryShard::ALL.len());
+        assert_eq!(ids[0], ContractInstanceId::from_params_and_code(Parameters::from(vec![]), &code));
+        let vic = DirectoryShardParameters::new(DirectoryShard::Vic).to_bytes();
+        assert!(ids.contains(&ContractInstanceId::from_params_and_code(Parameters::from(vic), &code)));
+
+        assert!(directory_instance_ids("not a hash").is_none());
+    }
+
+    #[test]
+    fn storefront_must_be_listed() {
+        let (owner, stranger) = (key(1), key(2));
+        let code = ContractCode::from(b"directory wasm".to_vec());
+        let hex: String = code.hash().as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+        let ids = directory_instance_ids(&hex).unwrap();
+
+        // Nothing fetched yet: ask for every directory
+        let none = RelatedContracts::from(HashMap::new());
+        assert_eq!(
+            validate_storefront_listing(&owner, &ids, &none),
+            ValidateResult::RequestRelated(ids.clone())
+        );
+
+        // Listed in one shard: valid without waiting for the others
+        let listing = directory(&owner, storefront_key(&owner));
+        let in_shard = related(&[(ids[3], Some(&listing))]);
+        assert_eq!(validate_storefront_listing(&owner, &ids, &in_shard), ValidateResult::Valid);
+
+        // Every directory fetched, none listing the owner: orphaned
+        let elsewhere = directory(&stranger, storefront_key(&stranger));
+        let everywhere: Vec<_> = ids.iter().map(|id| (*id, Some(&elsewhere))).collect();
+        assert_eq!(
+            validate_storefront_listing(&owner, &ids, &related(&everywhere)),
+            ValidateResult::Invalid
+        );
+
+        // Not configured: no check
+        assert_eq!(validate_storefront_listing(&owner, &[], &none), ValidateResult::Valid);
+    }
+
+    #[test]
+    fn directory_entry_must_point_at_its_suppliers_storefront() {
+        let (supplier, victim) = (key(1), key(2));
+
+        let honest = directory(&supplier, storefront_key(&supplier));
+        let id = *storefront_key(&supplier).id();
+        assert_eq!(
+            validate_directory_storefronts(&honest, &RelatedContracts::from(HashMap::new())),
+            ValidateResult::RequestRelated(vec![id])
+        );
+        let own = storefront(&supplier);
+        assert_eq!(
+            validate_directory_storefronts(&honest, &related(&[(id, Some(&own))])),
+            ValidateResult::Valid
+        );
+        // Not published yet
+        assert_eq!(
+            validate_directory_storefronts(&honest, &related::<StorefrontState>(&[(id, None)])),
+            ValidateResult::Valid
+        );
+
+        // An entry claiming the victim's storefront
+        let spoof = directory(&supplier, storefront_key(&victim));
+        let victims = storefront(&victim);
+        assert_eq!(
+            validate_directory_storefronts(&spoof, &related(&[(*storefront_key(&victim).id(), Some(&victims))])),
+            ValidateResult::Invalid
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Bounded retry with exponential backoff and jitter.
+//!
+//! Runtime-agnostic: callers supply the sleep function (`tokio::time::sleep`,
+//! `gloo_timers::future::sleep`, ...), so the same policy works in the
+//! guardian, the integration tests and the WASM UI.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often and how long to wait between attempts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first. `None` retries until cancelled.
+    pub max_attempts: Option<u32>,
+    /// Delay before the first retry.
+    pub initial_delay: Duration,
+    /// Upper bound on any single delay (before jitter).
+    pub max_delay: Duration,
+    /// Factor applied to the delay after each retry (1 = fixed interval).
+    pub multiplier: u32,
+    /// Fraction of each delay randomised away, in `0.0..=1.0`.
+    /// A delay `d` becomes a value in `[d * (1 - jitter), d]`.
+    pub jitter: f64,
+}
+
+impl RetryPolicy {
+    /// Fixed-interval polling with no jitter and no attempt limit.
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            max_attempts: None,
+            initial_delay: interval,
+            max_delay: interval,
+            multiplier: 1,
+            jitter: 0.0,
+        }
+    }
+
+    /// Exponential backoff (doubling, 20% jitter) with no attempt limit.
+    pub fn exponential(initial_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts: None,
+            initial_delay,
+            max_delay,
+            multiplier: 2,
+            jitter: 0.2,
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Un-jittered delay before retry number `retry` (0-based).
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.max(1).saturating_pow(retry);
+        self.initial_delay
+            .saturating_mul(factor)
+            .min(self.max_delay)
+    }
+
+    /// Start a backoff sequence. `seed` drives the jitter; any value works,
+    /// but concurrent retriers should use different seeds.
+    pub fn backoff(&self, seed: u64) -> Backoff {
+        Backoff {
+            policy: self.clone(),
+            retries: 0,
+            // xorshift state must be non-zero
+            rng: seed | 1,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// 5 attempts, 500ms doubling to at most 10s.
+    fn default() -> Self {
+        Self::exponential(Duration::from_millis(500), Duration::from_secs(10)).with_max_attempts(5)
+    }
+}
+
+/// Stateful delay sequence for one retry loop.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    policy: RetryPolicy,
+    retries: u32,
+    rng: u64,
+}
+
+impl Backoff {
+    /// Delay before the next attempt, or `None` once `max_attempts` is reached.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if let Some(max) = self.policy.max_attempts {
+            if self.retries + 1 >= max {
+                return None;
+            }
+        }
+        let base = self.policy.base_delay(self.retries);
+        self.retries += 1;
+        if self.policy.jitter <= 0.0 {
+            return Some(base);
+        }
+        let cut = base.mul_f64(self.policy.jitter * self.next_unit());
+        Some(base.saturating_sub(cut))
+    }
+
+    /// Number of retries handed out so far.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Restart the sequence (e.g. after a reconnect succeeds).
+    pub fn reset(&mut self) {
+        self.retries = 0;
+    }
+
+    /// Uniform sample in `[0, 1)` (xorshift64*).
+    fn next_unit(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let x = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Shared flag for stopping a retry loop from elsewhere.
+///
+/// Checked before each attempt and after each sleep; an in-flight attempt or
+/// sleep is not interrupted.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Why a retry loop gave up.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryError<E> {
+    /// Every allowed attempt failed; carries the last error.
+    Exhausted { attempts: u32, last_error: E },
+    /// The cancellation token fired; carries the last error, if any attempt ran.
+    Cancelled { last_error: Option<E> },
+}
+
+impl<E> RetryError<E> {
+    /// The most recent operation error, if any.
+    pub fn into_last_error(self) -> Option<E> {
+        match self {
+            RetryError::Exhausted { last_error, .. } => Some(last_error),
+            RetryError::Cancelled { last_error } => last_error,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RetryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryError::Exhausted { attempts, last_error } => {
+                write!(f, "gave up after {} attempts: {}", attempts, last_error)
+            }
+            RetryError::Cancelled { last_error: Some(e) } => write!(f, "cancelled: {}", e),
+            RetryError::Cancelled { last_error: None } => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// Run `op` until it succeeds, the backoff is exhausted, or `cancel` fires.
+///
+/// `op` receives the 0-based attempt number. `sleep` is called with each
+/// backoff delay between attempts.
+pub async fn retry<T, E, Op, OpFut, Sleep, SleepFut>(
+    mut backoff: Backoff,
+    cancel: &CancellationToken,
+    mut sleep: Sleep,
+    mut op: Op,
+) -> Result<T, RetryError<E>>
+where
+    Op: FnMut(u32) -> OpFut,
+    OpFut: Future<Output = Result<T, E>>,
+    Sleep: FnMut(Duration) -> SleepFut,
+    SleepFut: Future<Output = ()>,
+{
+    let mut attempt = 0u32;
+    let mut last_error = None;
+    loop {
+        if cancel.is_cancelled() {
+            return Err(RetryError::Cancelled { last_error });
+        }
+        match op(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                attempt += 1;
+                match backoff.next_delay() {
+                    Some(delay) => {
+                        last_error = Some(e);
+                        sleep(delay).await;
+                    }
+                    None => {
+                        return Err(RetryError::Exhausted {
+                            attempts: attempt,
+                            last_error: e,
+                        })
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::task::{Context, Poll, Waker};
+
+    /// Drive a future that never actually waits (sleeps here are recorded, not slept).
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = std::pin::pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn base_delay_doubles_up_to_max() {
+        let policy = RetryPolicy::exponential(Duration::from_millis(100), Duration::from_millis(500));
+        let delays: Vec<u128> = (0..5).map(|r| policy.base_delay(r).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let policy = RetryPolicy::fixed(Duration::from_millis(1_000)).with_jitter(0.5);
+        let mut backoff = policy.backoff(42);
+        for _ in 0..100 {
+            let d = backoff.next_delay().unwrap();
+            assert!(d >= Duration::from_millis(500) && d <= Duration::from_millis(1_000));
+        }
+    }
+
+    #[test]
+    fn max_attempts_bounds_delays() {
+        let policy = RetryPolicy::fixed(Duration::from_millis(10)).with_max_attempts(3);
+        let mut backoff = policy.backoff(1);
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_some());
+        assert!(backoff.next_delay().is_none());
+        backoff.reset();
+        assert!(backoff.next_delay().is_some());
+    }
+
+    #[test]
+    fn retry_succeeds_after_failures() {
+        let slept = RefCell::new(Vec::new());
+        let policy = RetryPolicy::fixed(Duration::from_millis(10)).with_max_attempts(5);
+        let result: Result<u32, RetryError<&str>> = block_on(retry(
+            policy.backoff(7),
+            &CancellationToken::new(),
+            |d| {
+                slept.borrow_mut().push(d);
+                std::future::ready(())
+            },
+            |attempt| std::future::ready(if attempt < 2 { Err("not yet") } else { Ok(attempt) }),
+        ));
+        assert_eq!(result, Ok(2));
+        assert_eq!(slept.borrow().len(), 2);
+    }
+
+    #[test]
+    fn retry_reports_exhaustion() {
+        let policy = RetryPolicy::fixed(Duration::ZERO).with_max_attempts(3);
+        let result: Result<(), _> = block_on(retry(
+            policy.backoff(7),
+            &CancellationToken::new(),
+            |_| std::future::ready(()),
+            |attempt| std::future::ready(Err(attempt)),
+        ));
+        assert_eq!(result, Err(RetryError::Exhausted { attempts: 3, last_error: 2 }));
+    }
+
+    #[test]
+    fn retry_stops_when_cancelled() {
+        let cancel = CancellationToken::new();
+        let policy = RetryPolicy::fixed(Duration::ZERO);
+        let result: Result<(), _> = block_on(retry(
+            policy.backoff(7),
+            &cancel,
+            |_| std::future::ready(()),
+            |attempt| {
+                if attempt == 3 {
+                    cancel.cancel();
+                }
+                std::future::ready(Err(attempt))
+            },
+        ));
+        assert_eq!(result, Err(RetryError::Cancelled { last_error: Some(3) }));
+    }
+}
@@ -0,0 +1,350 @@
+//! Settlement statements: a supplier's escrow income, period by period.
+//!
+//! Each fulfilled order settles its deposit from root's escrow into the
+//! supplier's user contract as a ledger credit. A [`SettlementStatement`]
+//! groups those credits by week or month — the orders covered, the gross, the
+//! tolls the supplier paid over the same period and the net — so suppliers
+//! can reconcile their CURD income against fulfilled orders.
+//!
+//! Statements are signed by the supplier and kept on their user contract
+//! alongside the ledger, so a period's figures stay checkable after its
+//! entries are folded into a ledger checkpoint.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::wallet::{csv_field, TransactionKind, WalletTransaction};
+
+/// Description of the ledger credit settling an order's escrow; the order id
+/// follows.
+pub const SETTLEMENT_DESCRIPTION_PREFIX: &str = "Escrow settlement for order ";
+
+/// Description of the debit paying an inbox message toll.
+pub const INBOX_TOLL_DESCRIPTION: &str = "Inbox message toll";
+
+/// Description of the debit paying a chat session toll.
+pub const SESSION_TOLL_DESCRIPTION: &str = "Chat session toll";
+
+/// Ledger description for settling `order_id`'s escrow.
+pub fn settlement_description(order_id: &str) -> String {
+    format!("{SETTLEMENT_DESCRIPTION_PREFIX}{order_id}")
+}
+
+/// The order a ledger entry settles, if it is an escrow settlement credit.
+pub fn settled_order(tx: &WalletTransaction) -> Option<&str> {
+    if tx.kind != TransactionKind::Credit {
+        return None;
+    }
+    tx.description.strip_prefix(SETTLEMENT_DESCRIPTION_PREFIX)
+}
+
+/// Whether a ledger entry is a toll the contract's owner paid.
+pub fn is_toll(tx: &WalletTransaction) -> bool {
+    tx.kind == TransactionKind::Debit
+        && (tx.description == INBOX_TOLL_DESCRIPTION || tx.description == SESSION_TOLL_DESCRIPTION)
+}
+
+/// How long a statement covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SettlementPeriod {
+    /// Monday to Sunday (UTC).
+    Week,
+    /// A calendar month (UTC).
+    Month,
+}
+
+impl SettlementPeriod {
+    pub fn label(self) -> &'static str {
+        match self {
+            SettlementPeriod::Week => "Weekly",
+            SettlementPeriod::Month => "Monthly",
+        }
+    }
+
+    /// First day of the period `date` falls in.
+    pub fn start_of(self, date: NaiveDate) -> NaiveDate {
+        match self {
+            SettlementPeriod::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+            SettlementPeriod::Month => date.with_day(1).expect("every month has a first day"),
+        }
+    }
+
+    /// First day after the period starting on `start`.
+    pub fn end_of(self, start: NaiveDate) -> NaiveDate {
+        match self {
+            SettlementPeriod::Week => start + Days::new(7),
+            SettlementPeriod::Month => start + Months::new(1),
+        }
+    }
+}
+
+/// A supplier's settled escrow income for one week or month.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettlementStatement {
+    pub period: SettlementPeriod,
+    /// First day of the period; see [`SettlementPeriod::start_of`].
+    pub starts: NaiveDate,
+    /// Orders whose escrow settled in the period.
+    pub orders: Vec<String>,
+    /// Total settled, in CURD.
+    pub gross_curd: u64,
+    /// Tolls paid in the period, in CURD.
+    pub tolls_curd: u64,
+    /// Gross less tolls (zero if the tolls exceed it).
+    pub net_curd: u64,
+    pub issued_at: DateTime<Utc>,
+    /// Supplier's signature over [`SettlementStatement::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableStatement<'a> {
+    domain: &'static str,
+    period: SettlementPeriod,
+    starts: &'a NaiveDate,
+    orders: &'a [String],
+    gross_curd: u64,
+    tolls_curd: u64,
+    net_curd: u64,
+    issued_at: &'a DateTime<Utc>,
+}
+
+impl SettlementStatement {
+    /// Serialize the statement for signing (everything except signature).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableStatement {
+            domain: "cream-settlement-statement",
+            period: self.period,
+            starts: &self.starts,
+            orders: &self.orders,
+            gross_curd: self.gross_curd,
+            tolls_curd: self.tolls_curd,
+            net_curd: self.net_curd,
+            issued_at: &self.issued_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the supplier (`owner` of the user contract) signed it.
+    pub fn verify(&self, owner: &VerifyingKey) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            let _ = owner;
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            owner.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// First day after the period.
+    pub fn ends(&self) -> NaiveDate {
+        self.period.end_of(self.starts)
+    }
+
+    /// Which statement this is: one per period.
+    pub fn id(&self) -> (SettlementPeriod, NaiveDate) {
+        (self.period, self.starts)
+    }
+
+    /// Render as CSV: a summary row for the period, then one row per order.
+    pub fn to_csv(&self, supplier: &str) -> String {
+        let last_day = self.ends() - Days::new(1);
+        let mut out = String::from("supplier,period,from,to,orders,gross_curd,tolls_curd,net_curd,issued_at\n");
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            csv_field(supplier),
+            self.period.label().to_lowercase(),
+            self.starts,
+            last_day,
+            self.orders.len(),
+            self.gross_curd,
+            self.tolls_curd,
+            self.net_curd,
+            self.issued_at.to_rfc3339(),
+        ));
+        out.push_str("\norder_id\n");
+        for order in &self.orders {
+            out.push_str(&csv_field(order));
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Statements (unsigned) for every `period` in which `ledger` settled escrow,
+/// oldest first.
+pub fn compile_statements(
+    ledger: &[WalletTransaction],
+    period: SettlementPeriod,
+    issued_at: DateTime<Utc>,
+) -> Vec<SettlementStatement> {
+    let period_of = |tx: &WalletTransaction| {
+        DateTime::parse_from_rfc3339(&tx.timestamp)
+            .ok()
+            .map(|at| period.start_of(at.with_timezone(&Utc).date_naive()))
+    };
+    let mut statements: BTreeMap<NaiveDate, SettlementStatement> = BTreeMap::new();
+    for tx in ledger {
+        let (Some(order), Some(starts)) = (settled_order(tx), period_of(tx)) else {
+            continue;
+        };
+        let statement = statements.entry(starts).or_insert_with(|| SettlementStatement {
+            period,
+            starts,
+            orders: Vec::new(),
+            gross_curd: 0,
+            tolls_curd: 0,
+            net_curd: 0,
+            issued_at,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        });
+        statement.orders.push(order.to_string());
+        statement.gross_curd = statement.gross_curd.saturating_add(tx.amount);
+    }
+    for tx in ledger.iter().filter(|tx| is_toll(tx)) {
+        if let Some(statement) = period_of(tx).and_then(|starts| statements.get_mut(&starts)) {
+            statement.tolls_curd = statement.tolls_curd.saturating_add(tx.amount);
+        }
+    }
+    statements
+        .into_values()
+        .map(|mut statement| {
+            statement.orders.sort();
+            statement.net_curd = statement.gross_curd.saturating_sub(statement.tolls_curd);
+            statement
+        })
+        .collect()
+}
+
+/// The compiled statements worth (re-)issuing: periods without a statement
+/// yet, and periods where orders have settled since theirs was issued.
+///
+/// A period whose ledger entries have since been checkpointed compiles to
+/// fewer orders than its statement covers; that statement is kept.
+pub fn statements_to_issue(
+    existing: &[SettlementStatement],
+    compiled: Vec<SettlementStatement>,
+) -> Vec<SettlementStatement> {
+    compiled
+        .into_iter()
+        .filter(|statement| {
+            existing
+                .iter()
+                .find(|issued| issued.id() == statement.id())
+                .is_none_or(|issued| statement.orders.iter().any(|order| !issued.orders.contains(order)))
+        })
+        .collect()
+}
+
+/// Fold `theirs` into `ours`: one statement per period, the latest issued
+/// winning, oldest period first.
+pub fn merge_statements(ours: &mut Vec<SettlementStatement>, theirs: &[SettlementStatement]) {
+    for statement in theirs {
+        match ours.iter_mut().find(|mine| mine.id() == statement.id()) {
+            Some(mine) if mine.issued_at >= statement.issued_at => {}
+            Some(mine) => *mine = statement.clone(),
+            None => ours.push(statement.clone()),
+        }
+    }
+    ours.sort_by(|a, b| a.starts.cmp(&b.starts).then_with(|| a.period.cmp(&b.period)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: TransactionKind, amount: u64, description: String, timestamp: &str) -> WalletTransaction {
+        WalletTransaction {
+            id: 0,
+            kind,
+            amount,
+            description,
+            sender: "root".into(),
+            receiver: "Gary".into(),
+            tx_ref: format!("{timestamp}:{amount}"),
+            timestamp: timestamp.into(),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn ledger() -> Vec<WalletTransaction> {
+        vec![
+            // Monday 2 and Sunday 8 March 2026: the same week
+            entry(TransactionKind::Credit, 100, settlement_description("o-1"), "2026-03-02T09:00:00.000Z"),
+            entry(TransactionKind::Credit, 50, settlement_description("o-2"), "2026-03-08T18:00:00.000Z"),
+            entry(TransactionKind::Debit, 3, SESSION_TOLL_DESCRIPTION.into(), "2026-03-04T10:00:00.000Z"),
+            // The next week, same month
+            entry(TransactionKind::Credit, 70, settlement_description("o-3"), "2026-03-09T08:00:00.000Z"),
+            // Not settlements or tolls
+            entry(TransactionKind::Credit, 1000, "Faucet".into(), "2026-03-03T08:00:00.000Z"),
+            entry(TransactionKind::Debit, 20, "Order deposit: Emma".into(), "2026-03-03T09:00:00.000Z"),
+        ]
+    }
+
+    #[test]
+    fn statements_group_settlements_by_period() {
+        let now = Utc::now();
+        let weekly = compile_statements(&ledger(), SettlementPeriod::Week, now);
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(weekly[0].starts, NaiveDate::from_ymd_opt(2026, 3, 2).unwrap());
+        assert_eq!(weekly[0].orders, ["o-1", "o-2"]);
+        assert_eq!((weekly[0].gross_curd, weekly[0].tolls_curd, weekly[0].net_curd), (150, 3, 147));
+        assert_eq!((weekly[1].gross_curd, weekly[1].tolls_curd, weekly[1].net_curd), (70, 0, 70));
+
+        let monthly = compile_statements(&ledger(), SettlementPeriod::Month, now);
+        assert_eq!(monthly.len(), 1);
+        assert_eq!(monthly[0].orders, ["o-1", "o-2", "o-3"]);
+        assert_eq!(monthly[0].ends(), NaiveDate::from_ymd_opt(2026, 4, 1).unwrap());
+        assert_eq!(monthly[0].net_curd, 217);
+    }
+
+    #[test]
+    fn only_new_settlements_reissue_a_statement() {
+        let issued = compile_statements(&ledger(), SettlementPeriod::Month, Utc::now());
+        assert!(statements_to_issue(&issued, compile_statements(&ledger(), SettlementPeriod::Month, Utc::now())).is_empty());
+
+        // The ledger was checkpointed: fewer entries left, statement kept
+        let pruned = ledger()[3..].to_vec();
+        assert!(statements_to_issue(&issued, compile_statements(&pruned, SettlementPeriod::Month, Utc::now())).is_empty());
+
+        // A late settlement in the same month
+        let mut later = ledger();
+        later.push(entry(TransactionKind::Credit, 40, settlement_description("o-4"), "2026-03-30T08:00:00.000Z"));
+        let reissue = statements_to_issue(&issued, compile_statements(&later, SettlementPeriod::Month, Utc::now()));
+        assert_eq!(reissue.len(), 1);
+        assert_eq!(reissue[0].gross_curd, 260);
+
+        let mut merged = issued.clone();
+        merge_statements(&mut merged, &reissue);
+        assert_eq!(merged, reissue);
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn statements_verify_against_the_supplier() {
+        use ed25519_dalek::{Signer, SigningKey};
+        let gary = SigningKey::from_bytes(&[3u8; 32]);
+        let mut statement = compile_statements(&ledger(), SettlementPeriod::Month, Utc::now()).remove(0);
+        statement.signature = gary.sign(&statement.signable_bytes());
+        assert!(statement.verify(&gary.verifying_key()));
+
+        statement.net_curd += 1;
+        assert!(!statement.verify(&gary.verifying_key()));
+    }
+}
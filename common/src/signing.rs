@@ -0,0 +1,172 @@
+//! The bytes product listings, orders, directory entries and ledger
+//! checkpoints are signed over.
+//!
+//! These used to be signed over bare JSON, each built its own way, so
+//! nothing stopped a signature over one kind of object from checking out
+//! as another whose fields serialize alike. [`signable_bytes`] now prefixes
+//! the scheme version and a [`Domain`] tag, so a signature is only ever
+//! good for the kind of object it was made for.
+//!
+//! What follows the tag is canonical JSON of a fixed set of the object's
+//! fields: each type lists them in a `Signable*` struct, so a field added to
+//! the type later isn't signed until it's added there too, and every JSON
+//! object's keys are sorted, so the bytes depend only on the values. (A
+//! binary encoding like bincode can't carry the extension fields the
+//! locations, policies and attributes inside these objects keep.)
+//!
+//! Objects signed before the scheme keep verifying: [`verify`] accepts the
+//! legacy bytes too and says which scheme matched, but only for objects
+//! dated before [`legacy_cutoff`]. Signing always uses the current scheme,
+//! so each object moves over the next time its owner writes it, and a
+//! legacy signature can't vouch for anything newer.
+
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Leads every signable message; bump it if the layout below changes.
+const SCHEME_TAG: &[u8] = b"cream-sig-v2";
+
+/// The kind of object a signature is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Domain {
+    Product,
+    Order,
+    DirectoryEntry,
+    LedgerCheckpoint,
+}
+
+impl Domain {
+    fn tag(self) -> &'static str {
+        match self {
+            Domain::Product => "product",
+            Domain::Order => "order",
+            Domain::DirectoryEntry => "directory-entry",
+            Domain::LedgerCheckpoint => "ledger-checkpoint",
+        }
+    }
+}
+
+/// The scheme a signature verified under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// Bare JSON, from before domain separation.
+    Legacy,
+    /// [`signable_bytes`].
+    Current,
+}
+
+/// Bytes to sign for `fields` of a `domain` object: scheme tag, domain tag,
+/// then the fields as canonical JSON, the tags each followed by a zero byte.
+pub fn signable_bytes<T: Serialize>(domain: Domain, fields: &T) -> Vec<u8> {
+    let value = serde_json::to_value(fields).expect("serialization should not fail");
+    let json = serde_json::to_vec(&canonical(value)).expect("serialization should not fail");
+    let tag = domain.tag().as_bytes();
+    let mut bytes = Vec::with_capacity(SCHEME_TAG.len() + tag.len() + json.len() + 2);
+    bytes.extend_from_slice(SCHEME_TAG);
+    bytes.push(0);
+    bytes.extend_from_slice(tag);
+    bytes.push(0);
+    bytes.extend_from_slice(&json);
+    bytes
+}
+
+/// Objects dated from 2026-11-01 on must be signed under the current
+/// scheme: every client writing by then signs that way.
+pub fn legacy_cutoff() -> DateTime<Utc> {
+    DateTime::from_timestamp(1_793_491_200, 0).expect("valid timestamp")
+}
+
+/// The scheme under which `signature` is `key`'s over an object whose
+/// current bytes are `current` and legacy bytes `legacy()`; `None` if it's
+/// neither. `dated` is the signed timestamp of the object (its `updated_at`
+/// or equivalent): legacy signatures only count before [`legacy_cutoff`].
+pub fn verify(
+    key: &VerifyingKey,
+    signature: &Signature,
+    dated: DateTime<Utc>,
+    current: &[u8],
+    legacy: impl FnOnce() -> Vec<u8>,
+) -> Option<Scheme> {
+    if key.verify(current, signature).is_ok() {
+        Some(Scheme::Current)
+    } else if dated < legacy_cutoff() && key.verify(&legacy(), signature).is_ok() {
+        Some(Scheme::Legacy)
+    } else {
+        None
+    }
+}
+
+/// `value` with every object's keys in sorted order, whichever map type
+/// `serde_json` was built with.
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(entries.into_iter().map(|(key, value)| (key, canonical(value))).collect())
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical).collect()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use serde_json::json;
+
+    #[test]
+    fn domains_never_share_signatures() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let fields = json!({ "id": "p-1", "quantity": 3 });
+        let product = signable_bytes(Domain::Product, &fields);
+        let order = signable_bytes(Domain::Order, &fields);
+        assert_ne!(product, order);
+
+        let signature = key.sign(&product);
+        let legacy = || serde_json::to_vec(&fields).unwrap();
+        let now = Utc::now();
+        assert_eq!(verify(&key.verifying_key(), &signature, now, &product, legacy), Some(Scheme::Current));
+        assert_eq!(verify(&key.verifying_key(), &signature, now, &order, legacy), None);
+    }
+
+    #[test]
+    fn bytes_ignore_field_order_and_legacy_signatures_still_verify() {
+        #[derive(Serialize)]
+        struct Ab {
+            a: u32,
+            b: u32,
+        }
+        #[derive(Serialize)]
+        struct Ba {
+            b: u32,
+            a: u32,
+        }
+        let ab = signable_bytes(Domain::DirectoryEntry, &Ab { a: 1, b: 2 });
+        assert_eq!(ab, signable_bytes(Domain::DirectoryEntry, &Ba { b: 2, a: 1 }));
+        assert!(ab.ends_with(br#"{"a":1,"b":2}"#));
+
+        let key = SigningKey::from_bytes(&[2; 32]);
+        let legacy = serde_json::to_vec(&Ba { b: 2, a: 1 }).unwrap();
+        let signature = key.sign(&legacy);
+        let before = legacy_cutoff() - chrono::Duration::seconds(1);
+        let scheme = verify(&key.verifying_key(), &signature, before, &ab, || legacy.clone());
+        assert_eq!(scheme, Some(Scheme::Legacy));
+    }
+
+    #[test]
+    fn legacy_signatures_expire_at_the_cutoff() {
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let fields = json!({ "id": "p-1", "quantity": 3 });
+        let legacy = serde_json::to_vec(&fields).unwrap();
+        let current = signable_bytes(Domain::Product, &fields);
+        let signature = key.sign(&legacy);
+        let verify_at = |dated| verify(&key.verifying_key(), &signature, dated, &current, || legacy.clone());
+        assert_eq!(verify_at(legacy_cutoff() - chrono::Duration::days(30)), Some(Scheme::Legacy));
+        assert_eq!(verify_at(legacy_cutoff()), None);
+        assert_eq!(verify_at(legacy_cutoff() + chrono::Duration::days(30)), None);
+    }
+}
@@ -0,0 +1,558 @@
+//! What guardians are asked to sign, and when they agree to.
+//!
+//! Guardians don't sign whatever bytes they're handed. A client asks for a
+//! [`SigningRequest`] — the state or record to be signed, with the context
+//! needed to judge it — and each guardian checks it with
+//! [`SigningPolicy::check`] against its own subscribed copies of the root
+//! user contract and, for settlements, the supplier's storefront. Only then
+//! does it produce a share, over [`SigningRequest::message`].
+//!
+//! A root update is judged by the debits it adds: those whose `tx_ref`
+//! isn't already a debit in the guardian's copy. A settlement may only add
+//! escrow settlement debits paying the named supplier for their fulfilled
+//! orders, each at most the order's escrowed deposit and each once — root's
+//! checkpoints remember the orders they folded settlements for. Any other root update
+//! may not add settlement debits at all, and its debits are held to
+//! [`SigningPolicy::max_root_debit`]. Root's stamp on the credit side of a
+//! payment is only given once the matching debit is on root.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::credential::Credential;
+use crate::currency::Curds;
+use crate::identity::{UserId, ROOT_USER_NAME};
+use crate::moderation::ModerationRecord;
+use crate::order::{OrderId, OrderStatus};
+use crate::provenance::{StateSection, WriterStamp};
+use crate::settlement::SETTLEMENT_DESCRIPTION_PREFIX;
+use crate::storefront::StorefrontState;
+use crate::user_contract::{MintRecord, UserContractState};
+use crate::wallet::{TransactionKind, WalletTransaction};
+
+/// Something to be signed with the guardians' group key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SigningRequest {
+    /// The root user contract's first state, signed at bootstrap.
+    Genesis { root_state: UserContractState },
+    /// A CURD issuance.
+    Mint { record: MintRecord },
+    /// A root state paying fulfilled orders' escrow out to `supplier`.
+    Settlement {
+        supplier: UserId,
+        root_state: UserContractState,
+    },
+    /// Any other root state: transfers from root, new toll rates.
+    RootLedger { root_state: UserContractState },
+    /// Root's stamp on the credit side of one of its payments, written at
+    /// `written_at`: what lets the receiver's contract accept the credit.
+    RootCredit {
+        credit: WalletTransaction,
+        written_at: DateTime<Utc>,
+    },
+    /// A decision on a supplier's listing.
+    Moderation { record: ModerationRecord },
+    /// A credential for a supplier.
+    Credential { credential: Credential },
+}
+
+impl SigningRequest {
+    /// The bytes the group signature is over.
+    pub fn message(&self) -> Vec<u8> {
+        match self {
+            SigningRequest::Genesis { root_state }
+            | SigningRequest::Settlement { root_state, .. }
+            | SigningRequest::RootLedger { root_state } => root_state.signable_bytes(),
+            SigningRequest::Mint { record } => record.signable_bytes(),
+            SigningRequest::Moderation { record } => record.signable_bytes(),
+            SigningRequest::Credential { credential } => credential.signable_bytes(),
+            SigningRequest::RootCredit { credit, written_at } => {
+                WriterStamp::signable_bytes(StateSection::LedgerCredit, written_at, &credit.credit_provenance_bytes())
+            }
+        }
+    }
+
+    /// Short name for logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SigningRequest::Genesis { .. } => "genesis",
+            SigningRequest::Mint { .. } => "mint",
+            SigningRequest::Settlement { .. } => "settlement",
+            SigningRequest::RootLedger { .. } => "root ledger",
+            SigningRequest::Moderation { .. } => "moderation",
+            SigningRequest::Credential { .. } => "credential",
+            SigningRequest::RootCredit { .. } => "root credit",
+        }
+    }
+}
+
+/// A guardian's limits on what it signs.
+#[derive(Debug, Clone, Default)]
+pub struct SigningPolicy {
+    /// Largest single mint; `None` for no limit.
+    pub max_mint: Option<Curds>,
+    /// Largest single debit from root outside a settlement; `None` for no
+    /// limit.
+    pub max_root_debit: Option<Curds>,
+}
+
+/// The guardian's own copies of the contracts a request is judged against.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContractView<'a> {
+    /// The root user contract, if the guardian has it.
+    pub root: Option<&'a UserContractState>,
+    /// The settling supplier's storefront, if the guardian has it.
+    pub storefront: Option<&'a StorefrontState>,
+}
+
+/// Why a guardian won't sign a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyViolation {
+    /// A genesis state was offered, but the root contract already exists.
+    AlreadyBootstrapped,
+    /// A genesis state with a ledger, balance or mints.
+    NotGenesis,
+    /// A mint of nothing.
+    ZeroMint,
+    MintTooLarge { amount: Curds, max: Curds },
+    /// The mint id is already on root.
+    AlreadyMinted(String),
+    /// The guardian has no copy of the root contract to check against.
+    NoRootView,
+    /// The guardian has no copy of the supplier's storefront.
+    NoStorefrontView,
+    /// The storefront the guardian has belongs to someone else.
+    WrongStorefront,
+    DebitTooLarge { tx_ref: String, amount: Curds, max: Curds },
+    /// A settlement debit outside a settlement request.
+    UnverifiedSettlement(String),
+    /// A settlement adding a debit that isn't a settlement.
+    NotASettlement(String),
+    /// A settlement for an order not in the supplier's storefront.
+    UnknownOrder(String),
+    OrderNotFulfilled { order: String, status: OrderStatus },
+    /// A settlement paying more than the order's escrowed deposit.
+    OverSettled { order: String, amount: Curds, deposit: Curds },
+    /// The order's escrow has already been settled.
+    AlreadySettled(String),
+    /// A settlement debit paying someone other than the settling supplier.
+    WrongReceiver { tx_ref: String, receiver: String },
+    /// A credit to stamp that root hasn't paid: no debit on root with the
+    /// same `tx_ref`, amount and receiver, or not naming root as its payer.
+    UnpaidRootCredit(String),
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::AlreadyBootstrapped => write!(f, "root contract already exists"),
+            PolicyViolation::NotGenesis => write!(f, "genesis state must have an empty ledger"),
+            PolicyViolation::ZeroMint => write!(f, "mint amount must be positive"),
+            PolicyViolation::MintTooLarge { amount, max } => {
+                write!(f, "mint of {amount} curds is over this guardian's limit of {max}")
+            }
+            PolicyViolation::AlreadyMinted(id) => write!(f, "mint {id} is already recorded"),
+            PolicyViolation::NoRootView => write!(f, "no copy of the root contract to check against"),
+            PolicyViolation::NoStorefrontView => write!(f, "no copy of the supplier's storefront to check against"),
+            PolicyViolation::WrongStorefront => write!(f, "storefront belongs to another supplier"),
+            PolicyViolation::DebitTooLarge { tx_ref, amount, max } => {
+                write!(f, "debit {tx_ref} of {amount} curds is over this guardian's limit of {max}")
+            }
+            PolicyViolation::UnverifiedSettlement(order) => {
+                write!(f, "settlement of order {order} must be asked for as a settlement")
+            }
+            PolicyViolation::NotASettlement(tx_ref) => write!(f, "debit {tx_ref} is not an escrow settlement"),
+            PolicyViolation::UnknownOrder(order) => write!(f, "order {order} is not in the supplier's storefront"),
+            PolicyViolation::OrderNotFulfilled { order, status } => {
+                write!(f, "order {order} is {status}, not fulfilled")
+            }
+            PolicyViolation::OverSettled { order, amount, deposit } => {
+                write!(f, "settlement of {amount} curds for order {order} exceeds its {deposit} curd deposit")
+            }
+            PolicyViolation::AlreadySettled(order) => write!(f, "order {order} is already settled"),
+            PolicyViolation::WrongReceiver { tx_ref, receiver } => {
+                write!(f, "settlement {tx_ref} pays {receiver}, not the settling supplier")
+            }
+            PolicyViolation::UnpaidRootCredit(tx_ref) => write!(f, "credit {tx_ref} has no matching debit on root"),
+        }
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+/// The order a root debit settles, if it is an escrow settlement.
+fn settlement_debit(tx: &WalletTransaction) -> Option<&str> {
+    if tx.kind != TransactionKind::Debit {
+        return None;
+    }
+    tx.description.strip_prefix(SETTLEMENT_DESCRIPTION_PREFIX)
+}
+
+/// Debits in `proposed` that aren't debits in `current`.
+fn added_debits<'a>(
+    current: &'a UserContractState,
+    proposed: &'a UserContractState,
+) -> impl Iterator<Item = &'a WalletTransaction> {
+    proposed.ledger.iter().filter(move |tx| {
+        tx.kind == TransactionKind::Debit
+            && !current
+                .ledger
+                .iter()
+                .any(|t| t.kind == TransactionKind::Debit && t.tx_ref == tx.tx_ref)
+    })
+}
+
+impl SigningPolicy {
+    /// Whether a guardian holding `view` may sign `request`.
+    pub fn check(&self, request: &SigningRequest, view: ContractView<'_>) -> Result<(), PolicyViolation> {
+        match request {
+            SigningRequest::Genesis { root_state } => {
+                if view.root.is_some() {
+                    return Err(PolicyViolation::AlreadyBootstrapped);
+                }
+                if !root_state.ledger.is_empty() || root_state.balance_curds != 0 || !root_state.mint_records.is_empty()
+                {
+                    return Err(PolicyViolation::NotGenesis);
+                }
+                Ok(())
+            }
+            SigningRequest::Mint { record } => {
+                if record.amount == 0 {
+                    return Err(PolicyViolation::ZeroMint);
+                }
+                if let Some(max) = self.max_mint.filter(|max| record.amount > *max) {
+                    return Err(PolicyViolation::MintTooLarge { amount: record.amount, max });
+                }
+                if view.root.is_some_and(|root| root.mint_records.iter().any(|r| r.id == record.id)) {
+                    return Err(PolicyViolation::AlreadyMinted(record.id.clone()));
+                }
+                Ok(())
+            }
+            SigningRequest::Settlement { supplier, root_state } => {
+                let root = view.root.ok_or(PolicyViolation::NoRootView)?;
+                let storefront = view.storefront.ok_or(PolicyViolation::NoStorefrontView)?;
+                if storefront.info.owner != *supplier {
+                    return Err(PolicyViolation::WrongStorefront);
+                }
+                let mut settling = BTreeSet::new();
+                for tx in added_debits(root, root_state) {
+                    let order_id =
+                        settlement_debit(tx).ok_or_else(|| PolicyViolation::NotASettlement(tx.tx_ref.clone()))?;
+                    let to_supplier = tx.receiver.eq_ignore_ascii_case(&storefront.info.name)
+                        || tx.receiver == supplier.to_string();
+                    if !to_supplier {
+                        return Err(PolicyViolation::WrongReceiver {
+                            tx_ref: tx.tx_ref.clone(),
+                            receiver: tx.receiver.clone(),
+                        });
+                    }
+                    let order = storefront
+                        .orders
+                        .get(&OrderId(order_id.to_string()))
+                        .ok_or_else(|| PolicyViolation::UnknownOrder(order_id.to_string()))?;
+                    if order.status != OrderStatus::Fulfilled {
+                        return Err(PolicyViolation::OrderNotFulfilled {
+                            order: order_id.to_string(),
+                            status: order.status.clone(),
+                        });
+                    }
+                    let deposit = order.escrowed_deposit();
+                    if tx.amount > deposit {
+                        return Err(PolicyViolation::OverSettled {
+                            order: order_id.to_string(),
+                            amount: tx.amount,
+                            deposit,
+                        });
+                    }
+                    if root.escrow_settled(order_id) || !settling.insert(order_id) {
+                        return Err(PolicyViolation::AlreadySettled(order_id.to_string()));
+                    }
+                }
+                Ok(())
+            }
+            SigningRequest::RootLedger { root_state } => {
+                let root = view.root.ok_or(PolicyViolation::NoRootView)?;
+                for tx in added_debits(root, root_state) {
+                    if let Some(order_id) = settlement_debit(tx) {
+                        return Err(PolicyViolation::UnverifiedSettlement(order_id.to_string()));
+                    }
+                    if let Some(max) = self.max_root_debit.filter(|max| tx.amount > *max) {
+                        return Err(PolicyViolation::DebitTooLarge {
+                            tx_ref: tx.tx_ref.clone(),
+                            amount: tx.amount,
+                            max,
+                        });
+                    }
+                }
+                Ok(())
+            }
+            SigningRequest::RootCredit { credit, .. } => {
+                let root = view.root.ok_or(PolicyViolation::NoRootView)?;
+                let paid = root.ledger.iter().any(|tx| {
+                    tx.kind == TransactionKind::Debit
+                        && tx.tx_ref == credit.tx_ref
+                        && tx.amount == credit.amount
+                        && tx.receiver == credit.receiver
+                });
+                let as_root = credit.kind == TransactionKind::Credit
+                    && credit.sender == ROOT_USER_NAME
+                    && credit.payer.as_ref() == Some(&root.owner);
+                if !paid || !as_root {
+                    return Err(PolicyViolation::UnpaidRootCredit(credit.tx_ref.clone()));
+                }
+                Ok(())
+            }
+            SigningRequest::Moderation { .. } | SigningRequest::Credential { .. } => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
+
+    use chrono::Utc;
+    use ed25519_dalek::{Signature, SigningKey};
+
+    use super::*;
+    use crate::location::GeoLocation;
+    use crate::order::{DepositTier, Order};
+    use crate::product::ProductId;
+    use crate::settlement::settlement_description;
+    use crate::storefront::StorefrontInfo;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn root() -> UserContractState {
+        UserContractState {
+            owner: UserId(key(1).verifying_key()),
+            name: "root".into(),
+            origin_supplier: String::new(),
+            current_supplier: String::new(),
+            balance_curds: 1_000,
+            invited_by: String::new(),
+            toll_rates: Default::default(),
+            checkpoint_balance: 0,
+            checkpoint_tx_count: 0,
+            checkpoint_at: None,
+            pruned_lightning_hashes: HashSet::new(),
+            checkpoint_proof: None,
+            spending_key_debits: Default::default(),
+            mint_records: Vec::new(),
+            settlement_statements: Vec::new(),
+            vouchers: Vec::new(),
+            ledger: Vec::new(),
+            next_tx_id: 0,
+            seq: 0,
+            updated_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    fn debit(tx_ref: &str, amount: Curds, description: String) -> WalletTransaction {
+        WalletTransaction {
+            id: 0,
+            kind: TransactionKind::Debit,
+            amount,
+            description,
+            sender: "root".into(),
+            receiver: "Gary".into(),
+            tx_ref: tx_ref.into(),
+            timestamp: "2026-03-02T09:00:00.000Z".into(),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn order(id: &str, status: OrderStatus) -> Order {
+        Order {
+            id: OrderId(id.into()),
+            product_id: ProductId("p-1".into()),
+            customer: UserId(key(3).verifying_key()),
+            quantity: 1,
+            deposit_tier: DepositTier::Reserve2Days,
+            deposit_amount: 10,
+            total_price: 100,
+            status,
+            created_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        }
+    }
+
+    fn storefront(owner: &UserId, orders: Vec<Order>) -> StorefrontState {
+        StorefrontState {
+            info: StorefrontInfo {
+                owner: owner.clone(),
+                name: "Gary".into(),
+                description: "".into(),
+                location: GeoLocation::new(0.0, 0.0),
+                schedule: None,
+                timezone: None,
+                phone: None,
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products: BTreeMap::new(),
+            orders: orders.into_iter().map(|order| (order.id.clone(), order)).collect(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn settlements_need_a_fulfilled_order_of_the_supplier() {
+        let supplier = UserId(key(2).verifying_key());
+        let current = root();
+        let shop = storefront(
+            &supplier,
+            vec![order("o-1", OrderStatus::Fulfilled), order("o-2", OrderStatus::Paid)],
+        );
+        let view = ContractView { root: Some(&current), storefront: Some(&shop) };
+        let policy = SigningPolicy::default();
+        let settle = |tx: WalletTransaction| {
+            let mut root_state = current.clone();
+            root_state.ledger.push(tx);
+            SigningRequest::Settlement { supplier: supplier.clone(), root_state }
+        };
+
+        assert_eq!(policy.check(&settle(debit("r:1", 10, settlement_description("o-1"))), view), Ok(()));
+        assert_eq!(
+            policy.check(&settle(debit("r:1", 10, settlement_description("o-2"))), view),
+            Err(PolicyViolation::OrderNotFulfilled { order: "o-2".into(), status: OrderStatus::Paid })
+        );
+        assert_eq!(
+            policy.check(&settle(debit("r:1", 11, settlement_description("o-1"))), view),
+            Err(PolicyViolation::OverSettled { order: "o-1".into(), amount: 11, deposit: 10 })
+        );
+        assert_eq!(
+            policy.check(&settle(debit("r:1", 10, settlement_description("o-9"))), view),
+            Err(PolicyViolation::UnknownOrder("o-9".into()))
+        );
+        assert_eq!(
+            policy.check(&settle(debit("r:1", 10, "Refund".into())), view),
+            Err(PolicyViolation::NotASettlement("r:1".into()))
+        );
+
+        // Once settled, the order can't be settled again
+        let mut settled = current.clone();
+        settled.ledger.push(debit("r:1", 10, settlement_description("o-1")));
+        let view = ContractView { root: Some(&settled), ..view };
+        let mut again = settled.clone();
+        again.ledger.push(debit("r:2", 10, settlement_description("o-1")));
+        let request = SigningRequest::Settlement { supplier: supplier.clone(), root_state: again.clone() };
+        assert_eq!(policy.check(&request, view), Err(PolicyViolation::AlreadySettled("o-1".into())));
+
+        // Nor once root's checkpoint has folded the settlement
+        settled.checkpoint(0, Utc::now());
+        assert!(settled.ledger.is_empty());
+        let view = ContractView { root: Some(&settled), storefront: Some(&shop) };
+        let request = SigningRequest::Settlement { supplier: supplier.clone(), root_state: again };
+        assert_eq!(policy.check(&request, view), Err(PolicyViolation::AlreadySettled("o-1".into())));
+    }
+
+    #[test]
+    fn settlements_pay_only_the_settling_supplier() {
+        let supplier = UserId(key(2).verifying_key());
+        let current = root();
+        let shop = storefront(&supplier, vec![order("o-1", OrderStatus::Fulfilled)]);
+        let view = ContractView { root: Some(&current), storefront: Some(&shop) };
+        let settle = |receiver: String| {
+            let mut tx = debit("r:1", 10, settlement_description("o-1"));
+            tx.receiver = receiver;
+            let mut root_state = current.clone();
+            root_state.ledger.push(tx);
+            SigningRequest::Settlement { supplier: supplier.clone(), root_state }
+        };
+
+        let policy = SigningPolicy::default();
+        assert_eq!(policy.check(&settle("gary".into()), view), Ok(()));
+        assert_eq!(policy.check(&settle(supplier.to_string()), view), Ok(()));
+        assert_eq!(
+            policy.check(&settle("Mallory".into()), view),
+            Err(PolicyViolation::WrongReceiver { tx_ref: "r:1".into(), receiver: "Mallory".into() })
+        );
+    }
+
+    #[test]
+    fn other_root_updates_cant_settle_and_are_limited() {
+        let current = root();
+        let view = ContractView { root: Some(&current), storefront: None };
+        let policy = SigningPolicy { max_mint: None, max_root_debit: Some(500) };
+        let update = |tx: WalletTransaction| {
+            let mut root_state = current.clone();
+            root_state.ledger.push(tx);
+            SigningRequest::RootLedger { root_state }
+        };
+
+        assert_eq!(policy.check(&update(debit("r:1", 500, "Faucet".into())), view), Ok(()));
+        assert_eq!(
+            policy.check(&update(debit("r:1", 501, "Faucet".into())), view),
+            Err(PolicyViolation::DebitTooLarge { tx_ref: "r:1".into(), amount: 501, max: 500 })
+        );
+        assert_eq!(
+            policy.check(&update(debit("r:1", 10, settlement_description("o-1"))), view),
+            Err(PolicyViolation::UnverifiedSettlement("o-1".into()))
+        );
+        assert_eq!(
+            policy.check(&update(debit("r:1", 10, "Faucet".into())), ContractView::default()),
+            Err(PolicyViolation::NoRootView)
+        );
+    }
+
+    #[test]
+    fn root_credits_are_stamped_only_once_paid() {
+        let mut current = root();
+        current.ledger.push(debit("r:1", 10, "Faucet".into()));
+        let view = ContractView { root: Some(&current), storefront: None };
+        let policy = SigningPolicy::default();
+        let stamp = |tx_ref: &str, amount: Curds, receiver: &str| {
+            let mut credit = debit(tx_ref, amount, "Faucet".into());
+            credit.kind = TransactionKind::Credit;
+            credit.sender = ROOT_USER_NAME.into();
+            credit.receiver = receiver.into();
+            credit.payer = Some(current.owner.clone());
+            SigningRequest::RootCredit { credit, written_at: Utc::now() }
+        };
+
+        assert_eq!(policy.check(&stamp("r:1", 10, "Gary"), view), Ok(()));
+        for (tx_ref, amount, receiver) in [("r:2", 10, "Gary"), ("r:1", 11, "Gary"), ("r:1", 10, "Mallory")] {
+            assert_eq!(
+                policy.check(&stamp(tx_ref, amount, receiver), view),
+                Err(PolicyViolation::UnpaidRootCredit(tx_ref.into()))
+            );
+        }
+    }
+}
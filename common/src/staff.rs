@@ -0,0 +1,189 @@
+//! Staff keys: people a supplier authorizes to work on their storefront.
+//!
+//! A farm with employees shouldn't have to share its root key. The supplier
+//! signs a [`StaffGrant`] for each staff member's key, naming what they may
+//! do — manage products, handle orders, edit the storefront's details — and
+//! the grants travel in the storefront state.
+//!
+//! Signatures by a listed staff key verify like the owner's, and a revoked
+//! grant stays listed so the member's past work still does. What a staff
+//! member may change *now* is enforced when an update is merged in the
+//! contract (see [`StorefrontState::permits`](crate::storefront::StorefrontState::permits)).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Utc};
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+
+/// Something a staff member may do on the storefront.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum StaffPermission {
+    /// Add, edit and restock products.
+    Products,
+    /// Hand over, cancel and answer changes to orders, and message customers.
+    Orders,
+    /// Edit the storefront's details: schedule, contact details, delivery zones.
+    Storefront,
+}
+
+impl StaffPermission {
+    pub const ALL: [StaffPermission; 3] = [StaffPermission::Products, StaffPermission::Orders, StaffPermission::Storefront];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StaffPermission::Products => "Manage products",
+            StaffPermission::Orders => "Fulfill orders",
+            StaffPermission::Storefront => "Change schedule and details",
+        }
+    }
+}
+
+/// The supplier's authorization of one staff key.
+///
+/// The latest grant per key (by `updated_at`) is the one in force.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StaffGrant {
+    pub key: UserId,
+    /// Who the key belongs to, for the supplier's own reference.
+    pub name: String,
+    pub permissions: BTreeSet<StaffPermission>,
+    pub updated_at: DateTime<Utc>,
+    /// Revoked grants keep the key listed but allow nothing new.
+    #[serde(default)]
+    pub revoked: bool,
+    /// Storefront owner's signature over [`StaffGrant::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct SignableStaffGrant<'a> {
+    domain: &'static str,
+    key: &'a UserId,
+    name: &'a str,
+    permissions: &'a BTreeSet<StaffPermission>,
+    updated_at: &'a DateTime<Utc>,
+    revoked: bool,
+}
+
+impl StaffGrant {
+    /// Serialize the grant for signing (everything except signature).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableStaffGrant {
+            domain: "cream-staff-grant",
+            key: &self.key,
+            name: &self.name,
+            permissions: &self.permissions,
+            updated_at: &self.updated_at,
+            revoked: self.revoked,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the storefront `owner` signed this grant.
+    pub fn verify(&self, owner: &VerifyingKey) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            let _ = owner;
+            #[allow(clippy::needless_return)]
+            return true;
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            owner.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// Whether the grant currently allows `permission`.
+    pub fn allows(&self, permission: StaffPermission) -> bool {
+        !self.revoked && self.permissions.contains(&permission)
+    }
+}
+
+/// Fold `theirs` into `ours`, keeping the latest grant per key.
+pub fn merge_staff(ours: &mut BTreeMap<UserId, StaffGrant>, theirs: BTreeMap<UserId, StaffGrant>) {
+    for (key, grant) in theirs {
+        match ours.get(&key) {
+            Some(existing) if existing.updated_at >= grant.updated_at => {}
+            _ => {
+                ours.insert(key, grant);
+            }
+        }
+    }
+}
+
+/// Whether `writer` may make a change needing `permission`: the owner always
+/// may, a staff member only while their grant allows it.
+pub fn may_write(
+    staff: &BTreeMap<UserId, StaffGrant>,
+    owner: &VerifyingKey,
+    writer: &VerifyingKey,
+    permission: StaffPermission,
+) -> bool {
+    writer == owner || staff.get(&UserId(*writer)).is_some_and(|grant| grant.allows(permission))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use ed25519_dalek::SigningKey;
+
+    fn grant(key: &VerifyingKey, permissions: &[StaffPermission], updated_at: DateTime<Utc>) -> StaffGrant {
+        StaffGrant {
+            key: UserId(*key),
+            name: "Sam".into(),
+            permissions: permissions.iter().copied().collect(),
+            updated_at,
+            revoked: false,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn latest_grant_wins_and_revocation_allows_nothing() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let sam = SigningKey::from_bytes(&[2u8; 32]).verifying_key();
+        let now = Utc::now();
+
+        let mut staff = BTreeMap::new();
+        let hired = grant(&sam, &[StaffPermission::Products, StaffPermission::Orders], now);
+        merge_staff(&mut staff, BTreeMap::from([(hired.key.clone(), hired.clone())]));
+        assert!(may_write(&staff, &owner, &sam, StaffPermission::Orders));
+        assert!(!may_write(&staff, &owner, &sam, StaffPermission::Storefront));
+        assert!(may_write(&staff, &owner, &owner, StaffPermission::Storefront));
+
+        let revoked = StaffGrant { revoked: true, updated_at: now + Duration::days(30), ..hired.clone() };
+        merge_staff(&mut staff, BTreeMap::from([(revoked.key.clone(), revoked)]));
+        assert!(!may_write(&staff, &owner, &sam, StaffPermission::Orders));
+
+        // A replayed older grant doesn't undo the revocation
+        merge_staff(&mut staff, BTreeMap::from([(hired.key.clone(), hired)]));
+        assert!(staff[&UserId(sam)].revoked);
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn only_the_owner_may_grant() {
+        use ed25519_dalek::Signer;
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let sam = SigningKey::from_bytes(&[2u8; 32]);
+
+        let mut hired = grant(&sam.verifying_key(), &[StaffPermission::Products], Utc::now());
+        hired.signature = sam.sign(&hired.signable_bytes());
+        assert!(!hired.verify(&owner.verifying_key()));
+
+        hired.signature = owner.sign(&hired.signable_bytes());
+        assert!(hired.verify(&owner.verifying_key()));
+        hired.permissions.insert(StaffPermission::Storefront);
+        assert!(!hired.verify(&owner.verifying_key()));
+    }
+}
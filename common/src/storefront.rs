@@ -1,6 +1,6 @@
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Offset, TimeZone, Utc};
 #[cfg(not(feature = "dev"))]
 use ed25519_dalek::Verifier;
 use ed25519_dalek::{Signature, VerifyingKey};
@@ -8,26 +8,65 @@ use serde::{Deserialize, Serialize};
 
 use std::collections::BTreeSet;
 
+use crate::blocklist::{block_on, merge_blocklist, BlockEntry};
+use crate::clock::Clock;
+use crate::contact::SealedContact;
+use crate::currency::Curds;
+use crate::delivery::{order_delivery_unserved, DeliveryZone};
 use crate::identity::UserId;
+use crate::limits::{
+    check_count, check_len, check_opt_len, LimitError, MAX_BLOCKLIST_ENTRIES, MAX_CONTACT_FIELD_LEN,
+    MAX_DELIVERY_ZONES, MAX_DESCRIPTION_LEN, MAX_NAME_LEN, MAX_ORDERS_PER_STOREFRONT, MAX_ORDER_HISTORY_MONTHS,
+    MAX_PICKUP_LOCATIONS, MAX_PRODUCTS_PER_STOREFRONT, MAX_THREAD_ATTACHMENTS, MAX_THREAD_MESSAGES,
+};
 use crate::location::GeoLocation;
-use crate::order::{Order, OrderId};
+use crate::timestamps::{
+    check_not_future, check_opt_not_future, check_order, check_recent, check_unchanged, TimestampError,
+};
+use crate::message::{may_post, MessageId, OrderThread};
+use crate::metrics::ContractMetrics;
+use crate::order::{CancellationPolicy, CollectionPoint, FulfillmentReceipt, Order, OrderAmendment, OrderId};
+use crate::pickup::{names_unique, order_pickup_unlisted, PickupLocation};
 use crate::product::{Product, ProductId};
+use crate::provenance::{is_zero, StateSection, WriterStamp};
+use crate::signing::{self, Domain};
+use crate::staff::{may_write, merge_staff, StaffGrant, StaffPermission};
 
 use crate::order::OrderStatus;
 
 /// Signed product listing (supplier must sign).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SignedProduct {
     pub product: Product,
     pub signature: Signature,
+    /// Who last wrote this listing, over [`SignedProduct::provenance_bytes`].
+    /// Not covered by `signature`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<WriterStamp>,
+    /// Past prices as `(since, price_curd)`, oldest first, capped at
+    /// [`PRICE_HISTORY_CAP`]. Built by the writer from the version it
+    /// replaces and covered by its provenance stamp, not by `signature`; an
+    /// unstamped listing's history holds only its signed price (see
+    /// [`SignedProduct::price_history_consistent`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub price_history: Vec<(DateTime<Utc>, u64)>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+/// Maximum number of price changes kept per product.
+pub const PRICE_HISTORY_CAP: usize = 32;
+
 impl SignedProduct {
     /// Serialize the product for signing/verification.
     pub fn signable_bytes(&self) -> Vec<u8> {
+        product_signable_bytes(&self.product)
+    }
+
+    /// The whole product as bare JSON, as listings were signed before
+    /// [`crate::signing`]. Still accepted before [`crate::signing::legacy_cutoff`].
+    pub fn legacy_signable_bytes(&self) -> Vec<u8> {
         serde_json::to_vec(&self.product).expect("serialization should not fail")
     }
 
@@ -40,9 +79,112 @@ impl SignedProduct {
         }
         #[cfg(not(feature = "dev"))]
         {
-            let msg = self.signable_bytes();
-            owner.verify(&msg, &self.signature).is_ok()
+            signing::verify(owner, &self.signature, self.product.updated_at, &self.signable_bytes(), || {
+                self.legacy_signable_bytes()
+            })
+            .is_some()
+        }
+    }
+
+    /// Bytes covered by the provenance stamp: the signed listing plus its
+    /// price history. Listings without a history keep their stamps.
+    pub fn provenance_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.signable_bytes();
+        if !self.price_history.is_empty() {
+            bytes.extend(serde_json::to_vec(&self.price_history).expect("serialization should not fail"));
+        }
+        bytes
+    }
+
+    /// Whether the history is consistent with the signed listing: no point is
+    /// dated after `updated_at`, and a point at `updated_at` has the signed
+    /// price. Earlier points only the stamp vouches for, so an unstamped
+    /// listing may have none.
+    pub fn price_history_consistent(&self) -> bool {
+        self.price_history.iter().all(|point| self.vouches_for(point))
+            && (self.provenance.is_some() || self.signature_covers_history())
+    }
+
+    /// Whether the history holds nothing but the signed price.
+    fn signature_covers_history(&self) -> bool {
+        self.price_history.iter().all(|&(at, _)| at == self.product.updated_at)
+    }
+
+    fn vouches_for(&self, (at, price): &(DateTime<Utc>, u64)) -> bool {
+        *at < self.product.updated_at || (*at == self.product.updated_at && *price == self.product.price_curd)
+    }
+
+    /// Fold another version's history and both versions' current prices into
+    /// ours, as a writer does before stamping a new version. Only price
+    /// *changes* are kept; the oldest are dropped past the cap. Points a
+    /// version's signed listing doesn't vouch for are dropped, and where two
+    /// points share a timestamp a signed price wins, ours first.
+    pub fn merge_price_history(&mut self, other: &SignedProduct) {
+        // (at, rank, price): sorting puts our signed price, then theirs, ahead
+        // of history points at the same time
+        let mut points: Vec<(DateTime<Utc>, u8, u64)> = self
+            .price_history
+            .iter()
+            .filter(|point| self.vouches_for(point))
+            .chain(other.price_history.iter().filter(|point| other.vouches_for(point)))
+            .map(|&(at, price)| (at, 2, price))
+            .chain([
+                (self.product.updated_at, 0, self.product.price_curd),
+                (other.product.updated_at, 1, other.product.price_curd),
+            ])
+            .collect();
+        points.sort();
+        points.dedup_by_key(|(at, _, _)| *at);
+        let mut points: Vec<(DateTime<Utc>, u64)> = points.into_iter().map(|(at, _, price)| (at, price)).collect();
+        // Keep the point where each price took effect
+        points.dedup_by(|later, earlier| later.1 == earlier.1);
+        if points.len() > PRICE_HISTORY_CAP {
+            points.drain(..points.len() - PRICE_HISTORY_CAP);
+        }
+        self.price_history = points;
+    }
+
+    /// Price history ending at the current price, for charting.
+    pub fn price_points(&self) -> Vec<(DateTime<Utc>, u64)> {
+        let mut points = self.price_history.clone();
+        if points.last().map(|(_, price)| *price) != Some(self.product.price_curd) {
+            points.push((self.product.updated_at, self.product.price_curd));
         }
+        points
+    }
+
+    /// The price before the current one, if the listing has changed price.
+    pub fn previous_price(&self) -> Option<u64> {
+        let points = self.price_points();
+        points.len().checked_sub(2).map(|i| points[i].1)
+    }
+
+    /// The price in effect at `at`, or `None` if `at` predates the history
+    /// this replica holds.
+    pub fn price_at(&self, at: DateTime<Utc>) -> Option<u64> {
+        let mut points = self.price_points();
+        points.sort();
+        points.iter().rev().find(|(since, _)| *since <= at).map(|(_, price)| *price)
+    }
+
+    /// Whether the provenance stamp (if any) was written by one of the
+    /// storefront's `suppliers` over this listing and its price history. A
+    /// stamp from before histories were stamped covers only the listing, and
+    /// so only a history the signature covers.
+    pub fn verify_provenance(&self, suppliers: &[VerifyingKey]) -> bool {
+        self.provenance.as_ref().is_none_or(|stamp| {
+            suppliers.contains(&stamp.last_writer)
+                && (stamp.verify(StateSection::Product, &self.provenance_bytes())
+                    || (self.signature_covers_history()
+                        && (stamp.verify(StateSection::Product, &self.signable_bytes())
+                            || (self.product.updated_at < signing::legacy_cutoff()
+                                && stamp.verify(StateSection::Product, &self.legacy_signable_bytes())))))
+        })
+    }
+
+    /// Who signed this listing: its stamp's writer, or `owner` if unstamped.
+    pub fn writer<'a>(&'a self, owner: &'a VerifyingKey) -> &'a VerifyingKey {
+        self.provenance.as_ref().map_or(owner, |stamp| &stamp.last_writer)
     }
 }
 
@@ -82,6 +224,11 @@ impl WeeklySchedule {
         Self { bits: [0u8; 42] }
     }
 
+    /// Whether every slot of the week is closed.
+    pub fn is_always_closed(&self) -> bool {
+        self.bits.iter().all(|byte| *byte == 0)
+    }
+
     /// Check if a specific half-hour slot is open.
     /// `day` 0–6 (Mon–Sun), `slot` 0–47.
     pub fn is_open(&self, day: u8, slot: u8) -> bool {
@@ -134,11 +281,10 @@ impl WeeklySchedule {
         ranges
     }
 
-    /// Check if the schedule is currently open given a UTC offset in minutes.
-    #[cfg(feature = "std")]
-    pub fn is_currently_open(&self, utc_offset_minutes: i32) -> bool {
-        let now = chrono::Utc::now();
-        self.is_open_at(now, utc_offset_minutes)
+    /// Check if the schedule is open at `clock`'s current time given a UTC
+    /// offset in minutes.
+    pub fn is_currently_open(&self, clock: &impl Clock, utc_offset_minutes: i32) -> bool {
+        self.is_open_at(clock.now(), utc_offset_minutes)
     }
 
     /// Check if the schedule is open at a specific UTC time given an offset.
@@ -224,8 +370,47 @@ impl Default for WeeklySchedule {
     }
 }
 
+/// Current UTC offset in minutes of the IANA timezone `timezone` at `at`,
+/// daylight saving included. `None` for an unknown zone name.
+pub fn utc_offset_minutes(timezone: &str, at: DateTime<Utc>) -> Option<i32> {
+    let tz: chrono_tz::Tz = timezone.parse().ok()?;
+    Some(tz.offset_from_utc_datetime(&at.naive_utc()).fix().local_minus_utc() / 60)
+}
+
+/// Offset for reading a schedule in `timezone` at `at`; UTC when the zone
+/// is missing or unknown.
+fn schedule_offset(timezone: Option<&str>, at: DateTime<Utc>) -> i32 {
+    timezone.and_then(|tz| utc_offset_minutes(tz, at)).unwrap_or(0)
+}
+
+/// Whether a supplier with `schedule`, kept in the IANA `timezone`, is open at `now`.
+pub fn is_open_now(schedule: &WeeklySchedule, timezone: Option<&str>, now: DateTime<Utc>) -> bool {
+    schedule.is_open_at(now, schedule_offset(timezone, now))
+}
+
+/// When the supplier next opens after `now`, in their local time: the start
+/// of the first open slot that follows a closed one, within the next week.
+/// `None` if the schedule has no open slots.
+pub fn next_opening(
+    schedule: &WeeklySchedule,
+    timezone: Option<&str>,
+    now: DateTime<Utc>,
+) -> Option<DateTime<FixedOffset>> {
+    const SLOT_SECS: i64 = 1800;
+    let first = (now.timestamp().div_euclid(SLOT_SECS) + 1) * SLOT_SECS;
+    let open_at = |t: DateTime<Utc>| schedule.is_open_at(t, schedule_offset(timezone, t));
+    (0..8 * 48).find_map(|i| {
+        let t = DateTime::from_timestamp(first + i * SLOT_SECS, 0)?;
+        if !open_at(t) || open_at(t - chrono::Duration::seconds(SLOT_SECS)) {
+            return None;
+        }
+        let offset = FixedOffset::east_opt(schedule_offset(timezone, t) * 60)?;
+        Some(t.with_timezone(&offset))
+    })
+}
+
 /// Basic information about a storefront.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorefrontInfo {
     pub owner: UserId,
     pub name: String,
@@ -245,11 +430,129 @@ pub struct StorefrontInfo {
     /// Empty set or missing key = all products.
     #[serde(default)]
     pub market_products: BTreeMap<String, BTreeSet<ProductId>>,
+    /// Delivery zones and fees. Empty = pickup only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delivery_zones: Vec<DeliveryZone>,
+    /// Places away from the farm where orders can be collected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pickup_locations: Vec<PickupLocation>,
+    /// Contact details only customers with an order in progress can read,
+    /// kept apart from the public `phone`, `email` and `address`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sealed_contact: Option<SealedContact>,
+    /// Terms for customers cancelling their orders; `None` = always a full refund.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancellation_policy: Option<CancellationPolicy>,
+    /// New orders priced above this many curds must carry the customer's
+    /// [`BalanceAttestation`](crate::attestation::BalanceAttestation)
+    /// covering the deposit; `None` = never required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attestation_threshold: Option<Curds>,
+    /// Who last wrote the info section (schedule, contact details, ...).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<WriterStamp>,
+    /// Write sequence number, bumped by the owner on every info write. Covered
+    /// by the provenance stamp, so a relay can't inflate it.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub seq: u64,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+impl StorefrontInfo {
+    /// Serialize the info (without its provenance stamp) for stamping/verification.
+    pub fn provenance_bytes(&self) -> Vec<u8> {
+        let unstamped = StorefrontInfo {
+            provenance: None,
+            ..self.clone()
+        };
+        serde_json::to_vec(&unstamped).expect("serialization should not fail")
+    }
+
+    /// Whether the provenance stamp (if any) was written by one of the
+    /// storefront's `suppliers` over this info.
+    pub fn verify_provenance(&self, suppliers: &[VerifyingKey]) -> bool {
+        self.provenance.as_ref().is_none_or(|stamp| {
+            suppliers.contains(&stamp.last_writer)
+                && stamp.verify(StateSection::StorefrontInfo, &self.provenance_bytes())
+        })
+    }
+}
+
+/// Terminal orders are kept this long after the end of the month they were
+/// placed in, then folded into [`StorefrontState::order_history`].
+pub const ORDER_RETENTION_DAYS: i64 = 90;
+
+/// What became of the orders placed in one month, once compacted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MonthlyOrderStats {
+    /// Orders placed in the month.
+    pub orders: u32,
+    /// Of those, how many were fulfilled.
+    pub fulfilled: u32,
+    /// Total price of the fulfilled orders.
+    pub revenue_curd: u64,
+    /// The orders folded in, with the total each fulfilled one brought. The
+    /// counts above are tallied from these, so replicas that folded different
+    /// orders combine without counting any twice.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub folded: BTreeMap<OrderId, Option<u64>>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MonthlyOrderStats {
+    fn record(&mut self, id: OrderId, order: &Order) {
+        let revenue = (order.status == OrderStatus::Fulfilled).then(|| order.current_total_price());
+        self.fold(id, revenue);
+    }
+
+    /// Fold one order in. Should two replicas disagree on what it brought,
+    /// the larger is kept, so merges stay order-independent.
+    fn fold(&mut self, id: OrderId, revenue: Option<u64>) {
+        let entry = self.folded.entry(id).or_insert(revenue);
+        *entry = (*entry).max(revenue);
+    }
+
+    /// Recount the totals from the folded orders.
+    fn tally(&mut self) {
+        self.orders = u32::try_from(self.folded.len()).unwrap_or(u32::MAX);
+        self.fulfilled = u32::try_from(self.folded.values().flatten().count()).unwrap_or(u32::MAX);
+        self.revenue_curd = self.folded.values().flatten().fold(0u64, |sum, r| sum.saturating_add(*r));
+    }
+
+    /// Whether the totals are exactly those of the folded orders.
+    pub fn tallies(&self) -> bool {
+        let mut tallied = self.clone();
+        tallied.tally();
+        tallied == *self
+    }
+}
+
+/// Fold the orders in `stats` into `history`'s total for `month`. Orders
+/// already folded there aren't counted again; `stats`' own totals are
+/// ignored in favour of a recount.
+fn merge_order_history(history: &mut BTreeMap<String, MonthlyOrderStats>, month: String, stats: MonthlyOrderStats) {
+    let total = history.entry(month).or_default();
+    for (id, revenue) in stats.folded {
+        total.fold(id, revenue);
+    }
+    total.tally();
+}
+
+/// The `order_history` key for orders placed at `at`.
+pub fn order_month(at: &DateTime<Utc>) -> String {
+    format!("{:04}-{:02}", at.year(), at.month())
+}
+
+/// The first instant after the month `at` falls in.
+fn end_of_month(at: &DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let (year, month) = if at.month() == 12 { (at.year() + 1, 1) } else { (at.year(), at.month() + 1) };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()
+}
+
 /// Parameters that make each storefront contract unique.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorefrontParameters {
@@ -257,11 +560,24 @@ pub struct StorefrontParameters {
 }
 
 /// The full storefront state: info + products + orders.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StorefrontState {
     pub info: StorefrontInfo,
     pub products: BTreeMap<ProductId, SignedProduct>,
     pub orders: BTreeMap<OrderId, Order>,
+    /// Conversation threads between each order's customer and the supplier.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub threads: BTreeMap<OrderId, OrderThread>,
+    /// Keys the owner has authorized to work on the storefront, and what each may do.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub staff: BTreeMap<UserId, StaffGrant>,
+    /// Customers the owner refuses to serve, with a note on how to appeal.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub blocklist: BTreeMap<UserId, BlockEntry>,
+    /// Totals for orders compacted out of `orders`, keyed by the month they
+    /// were placed in ("YYYY-MM"). See [`StorefrontState::compact_orders`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub order_history: BTreeMap<String, MonthlyOrderStats>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
@@ -270,7 +586,8 @@ pub struct StorefrontState {
 impl StorefrontState {
     /// Compute available quantity for a product by subtracting active order quantities.
     ///
-    /// Active orders are those with status `Reserved` or `Paid` (ordinals 0 and 1).
+    /// Active orders are those `Reserved`, `Paid` or awaiting fulfillment confirmation,
+    /// counted at their amended quantity.
     pub fn available_quantity(&self, product_id: &ProductId) -> u32 {
         let total = self
             .products
@@ -283,14 +600,126 @@ impl StorefrontState {
             .values()
             .filter(|o| {
                 o.product_id == *product_id
-                    && matches!(o.status, OrderStatus::Reserved { .. } | OrderStatus::Paid)
+                    && matches!(
+                        o.status,
+                        OrderStatus::Reserved { .. } | OrderStatus::Paid | OrderStatus::FulfillmentProposed { .. }
+                    )
             })
-            .map(|o| o.quantity)
+            .map(|o| o.current_quantity())
             .sum();
 
         total.saturating_sub(reserved)
     }
 
+    /// Listings under their low-stock threshold, with how many units each
+    /// has available.
+    pub fn low_stock(&self) -> Vec<(&SignedProduct, u32)> {
+        self.products
+            .values()
+            .map(|sp| (sp, self.available_quantity(&sp.product.id)))
+            .filter(|(sp, available)| sp.product.is_low_on_stock(*available))
+            .collect()
+    }
+
+    /// Listings set to pause themselves that orders placed since the
+    /// listing was last edited have brought low on stock. A listing the
+    /// supplier resumes (or restocks) while still low stays open until the
+    /// next order.
+    pub fn products_to_pause(&self) -> Vec<ProductId> {
+        self.products
+            .values()
+            .filter(|sp| sp.product.should_auto_pause(self.available_quantity(&sp.product.id)))
+            .filter(|sp| {
+                self.orders
+                    .values()
+                    .any(|o| o.product_id == sp.product.id && o.created_at > sp.product.updated_at)
+            })
+            .map(|sp| sp.product.id.clone())
+            .collect()
+    }
+
+    /// The listing's current price, if it differs from the unit price
+    /// `order` is locked at and the order is still open.
+    pub fn price_changed_since(&self, order: &Order) -> Option<u64> {
+        if !matches!(order.status, OrderStatus::Reserved { .. } | OrderStatus::Paid) {
+            return None;
+        }
+        let product = &self.products.get(&order.product_id)?.product;
+        // Orders placed on a special keep its price
+        if product.special.is_some_and(|special| special.price_curd == order.unit_price()) {
+            return None;
+        }
+        // Compare like with like: the price this order's quantity gets now
+        let current = product.wholesale.as_ref().map_or(product.price_curd, |terms| {
+            terms.unit_price(product.price_curd, order.quantity)
+        });
+        (current != order.unit_price()).then_some(current)
+    }
+
+    /// The unit price `order` must have been placed at: the listing's price
+    /// at `created_at`, from our version and `update`'s together. `None` if
+    /// the listing is unknown or its history doesn't reach back that far.
+    fn locked_price(&self, update: &StorefrontState, order: &Order) -> Option<u64> {
+        let ours = self.products.get(&order.product_id);
+        let theirs = update.products.get(&order.product_id);
+        let mut product = match (ours, theirs) {
+            (Some(a), Some(b)) if b.product.updated_at > a.product.updated_at => b.clone(),
+            (Some(a), _) => a.clone(),
+            (None, Some(b)) => b.clone(),
+            (None, None) => return None,
+        };
+        if let (Some(a), Some(b)) = (ours, theirs) {
+            product.merge_price_history(if product.product == a.product { b } else { a });
+        }
+        product.price_at(order.created_at)
+    }
+
+    /// The newer of our listing and `update`'s for `product_id`.
+    fn newest_listing<'a>(&'a self, update: &'a StorefrontState, product_id: &ProductId) -> Option<&'a SignedProduct> {
+        match (self.products.get(product_id), update.products.get(product_id)) {
+            (Some(a), Some(b)) if b.product.updated_at > a.product.updated_at => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    }
+
+    /// Whether the newer of our listing and `update`'s for `product_id` is
+    /// paused.
+    fn listing_paused(&self, update: &StorefrontState, product_id: &ProductId) -> bool {
+        self.newest_listing(update, product_id).is_some_and(|sp| sp.product.paused)
+    }
+
+    /// Whether new `order` is over the attestation threshold of the info
+    /// that wins the merge with `update`.
+    fn attestation_required(&self, update: &StorefrontState, order: &Order) -> bool {
+        let info = if info_is_newer(&self.info, &update.info) { &self.info } else { &update.info };
+        info.attestation_threshold.is_some_and(|threshold| order.total_price > threshold)
+    }
+
+    /// Whether new `order` is priced as the listing was when placed: at the
+    /// special running then (in our version or `update`'s), or else at
+    /// [`Self::locked_price`], less the newer listing's wholesale volume
+    /// discount. Passes if the listing or its history is unknown. When it was
+    /// placed is the customer's word, so the contract also holds new orders
+    /// to the host's clock (see [`Self::check_orders_recent`]).
+    fn order_price_locked(&self, update: &StorefrontState, order: &Order) -> bool {
+        let terms = self.newest_listing(update, &order.product_id).and_then(|sp| sp.product.wholesale.as_ref());
+        let priced = |unit: u64| {
+            let unit = terms.map_or(unit, |terms| terms.unit_price(unit, order.quantity));
+            unit.checked_mul(u64::from(order.quantity)) == Some(order.total_price)
+        };
+        let specials: Vec<u64> = [self.products.get(&order.product_id), update.products.get(&order.product_id)]
+            .into_iter()
+            .flatten()
+            .filter_map(|sp| sp.product.special_at(order.created_at))
+            .map(|special| special.price_curd)
+            .collect();
+        if !specials.is_empty() {
+            return specials.into_iter().any(priced);
+        }
+        self.locked_price(update, order).is_none_or(priced)
+    }
+
     /// Transition all `Reserved` orders whose `expires_at` has passed to `Expired`.
     /// Returns `true` if any orders were changed.
     pub fn expire_orders(&mut self, now: DateTime<Utc>) -> bool {
@@ -298,7 +727,7 @@ impl StorefrontState {
         for order in self.orders.values_mut() {
             if let OrderStatus::Reserved { expires_at } = order.status {
                 if expires_at < now {
-                    order.status = OrderStatus::Expired;
+                    order.set_status(OrderStatus::Expired, None, expires_at);
                     changed = true;
                 }
             }
@@ -306,61 +735,279 @@ impl StorefrontState {
         changed
     }
 
+    /// The latest time the supplier is known to have written anything: info
+    /// and order stamps (not the customer's) and product listings. Contracts
+    /// have no clock, so this stands in for "now" when compacting.
+    pub fn activity_clock(&self) -> Option<DateTime<Utc>> {
+        let info = self.info.provenance.iter().map(|stamp| stamp.written_at);
+        let products = self.products.values().map(|signed| signed.product.updated_at);
+        let orders = self.orders.values().filter_map(|order| {
+            order
+                .provenance
+                .as_ref()
+                .filter(|stamp| stamp.last_writer != order.customer.0)
+                .map(|stamp| stamp.written_at)
+        });
+        info.chain(products).chain(orders).max()
+    }
+
+    /// Fold terminal orders into [`StorefrontState::order_history`], with
+    /// their threads, bounding the state while recent history stays intact.
+    ///
+    /// A month is compacted once it ended [`ORDER_RETENTION_DAYS`] before
+    /// `now` and every order placed in it is terminal. Orders still held for
+    /// a month that is already compacted — from a replica that hadn't
+    /// compacted it yet — are folded in too, unless already counted, so
+    /// replicas compacting at different times converge. Returns `true` if
+    /// anything changed.
+    pub fn compact_orders(&mut self, now: DateTime<Utc>) -> bool {
+        let cutoff = now - Duration::days(ORDER_RETENTION_DAYS);
+        // Per month placed: whether it has aged out, whether an order is
+        // still open, and the terminal orders
+        let mut months: BTreeMap<String, (bool, bool, Vec<OrderId>)> = BTreeMap::new();
+        for (id, order) in &self.orders {
+            let (_, open, terminal) = months.entry(order_month(&order.created_at)).or_insert_with(|| {
+                let aged = end_of_month(&order.created_at).is_some_and(|end| end <= cutoff);
+                (aged, false, Vec::new())
+            });
+            if order.status.is_terminal() {
+                terminal.push(id.clone());
+            } else {
+                *open = true;
+            }
+        }
+        let mut changed = false;
+        for (month, (aged, open, terminal)) in months {
+            let due = aged && !open;
+            // Open orders in an already compacted month stay until they settle
+            if terminal.is_empty() || !(due || self.order_history.contains_key(&month)) {
+                continue;
+            }
+            let mut stats = MonthlyOrderStats::default();
+            for id in terminal {
+                if let Some(order) = self.orders.remove(&id) {
+                    stats.record(id.clone(), &order);
+                }
+                self.threads.remove(&id);
+            }
+            merge_order_history(&mut self.order_history, month, stats);
+            changed = true;
+        }
+        changed
+    }
+
     /// Merge another storefront state into this one.
     ///
-    /// - Products: LWW by `updated_at`
-    /// - Orders: set-union, monotonic status (higher ordinal wins)
+    /// - Info: the higher `seq` wins outright; on equal `seq`, take the
+    ///   update's unless ours carries a newer writer stamp
+    /// - Products: strict LWW by `updated_at` (ties keep ours); price history
+    ///   is the union of both sides
+    /// - Orders: set-union, monotonic status (higher ordinal wins). An update
+    ///   that moves a known order to `Fulfilled` is only taken if it carries a
+    ///   valid fulfillment receipt. Amendment chains only grow (see
+    ///   [`merge_amendments`]), whichever side's status wins.
+    /// - Threads: set-union of messages, keeping only those whose author is the
+    ///   order's customer or the supplier (checked against the merged orders,
+    ///   since a delta may carry messages without their order).
+    /// - Staff: LWW per key by `updated_at`, merged first so the other
+    ///   sections see the combined list.
+    /// - Order history: per month, the union of the orders folded in; then
+    ///   old terminal orders are compacted into it as of
+    ///   [`StorefrontState::activity_clock`], so every replica holding the
+    ///   same sections compacts them the same way.
+    ///
+    /// Writer stamps travel with the winning version of each section. Whether
+    /// the update's writers were *allowed* to make its changes is checked
+    /// beforehand with [`StorefrontState::permits`].
     pub fn merge(&mut self, other: StorefrontState) {
-        // Merge info: single-owner, always take update's info so schedule/timezone
-        // and other metadata changes propagate.
-        self.info = other.info;
+        merge_staff(&mut self.staff, other.staff);
+        merge_blocklist(&mut self.blocklist, other.blocklist);
+
+        // Merge info: single-owner, take update's info so schedule/timezone
+        // and other metadata changes propagate. A replayed or stale relay of
+        // an older info must not roll back a newer one.
+        if !info_is_newer(&self.info, &other.info) {
+            self.info = other.info;
+        }
 
-        // Merge products (LWW by updated_at)
+        // Merge products (LWW by updated_at), each with the price history its
+        // writer stamped
         for (id, signed) in other.products {
-            match self.products.get(&id) {
-                Some(existing) if existing.product.updated_at >= signed.product.updated_at => {
-                    // Keep existing
+            match self.products.get_mut(&id) {
+                Some(existing) if existing.product.updated_at >= signed.product.updated_at => {}
+                Some(existing) => {
+                    *existing = signed;
                 }
-                _ => {
+                None => {
                     self.products.insert(id, signed);
                 }
             }
         }
 
-        // Merge orders (union + monotonic status)
-        for (id, order) in other.orders {
-            match self.orders.get(&id) {
-                Some(existing) if existing.status.ordinal() >= order.status.ordinal() => {
-                    // Keep existing (higher or equal status)
-                }
-                _ => {
-                    self.orders.insert(id, order);
+        // Merge orders (union + monotonic status, amendment chains alongside)
+        let suppliers = self.supplier_keys(&self.info.owner.0);
+        for (id, mut order) in other.orders {
+            let Some(existing) = self.orders.get_mut(&id) else {
+                self.orders.insert(id, order);
+                continue;
+            };
+            // A different order under the same id never replaces ours
+            if !existing.same_order(&order) {
+                continue;
+            }
+            let amendments = std::mem::take(&mut order.amendments);
+            // Supplier can't settle unilaterally: no receipt, no Fulfilled
+            let advances = existing.status.ordinal() < order.status.ordinal()
+                && (order.status != OrderStatus::Fulfilled || fulfillment_receipt_valid(&order, &suppliers));
+            if advances {
+                order.amendments = std::mem::take(&mut existing.amendments);
+                *existing = order;
+            }
+            merge_amendments(existing, amendments);
+        }
+
+        // Merge threads (union, participants only)
+        for (order_id, thread) in other.threads {
+            let Some(order) = self.orders.get(&order_id) else {
+                continue;
+            };
+            for (_, message) in thread.messages {
+                if message.order_id == order_id && may_post(order, &suppliers, &message.author) {
+                    self.threads.entry(order_id.clone()).or_default().insert(message);
                 }
             }
         }
+
+        for (month, stats) in other.order_history {
+            merge_order_history(&mut self.order_history, month, stats);
+        }
+        if let Some(now) = self.activity_clock() {
+            self.compact_orders(now);
+        }
     }
 
-    /// Validate all products are signed by the owner and orders are signed by customers.
+    /// Validate all products are signed by the supplier and orders are signed by customers.
+    ///
+    /// The supplier is the owner or any key on the owner-signed staff list;
+    /// see [`StorefrontState::permits`] for what each may change.
     pub fn validate(&self, owner: &VerifyingKey) -> bool {
+        // Staff grants must be the owner's, filed under the key they grant
+        if self.staff.iter().any(|(key, grant)| grant.key != *key || !grant.verify(owner)) {
+            return false;
+        }
+        // So must blocks, filed under the customer they block
+        if self
+            .blocklist
+            .iter()
+            .any(|(customer, entry)| entry.customer != *customer || !entry.verify(owner))
+        {
+            return false;
+        }
+        let suppliers = self.supplier_keys(owner);
+
+        // Pickup locations are told apart by name, and pending orders must
+        // be collected at one still listed
+        if !names_unique(&self.info.pickup_locations)
+            || self.orders.values().any(|o| order_pickup_unlisted(&self.info.pickup_locations, o))
+        {
+            return false;
+        }
+
+        // A sequenced info must be stamped, or anyone could claim a high seq
+        if self.info.seq > 0 && self.info.provenance.is_none() {
+            return false;
+        }
+
+        // Orders are filed under their own id, which names their content
+        if self.orders.iter().any(|(id, o)| *id != o.id || !o.id_matches_content()) {
+            return false;
+        }
+
+        if self.orders.values().any(|o| !amendments_valid(o, &suppliers) || !o.status_history_valid()) {
+            return false;
+        }
+
+        // Specials must be discounts over a real window
+        if self
+            .products
+            .values()
+            .any(|sp| sp.product.special.is_some_and(|special| !special.is_valid(sp.product.price_curd)))
+        {
+            return false;
+        }
+
+        // Price histories can't run past, or contradict, the signed listing
+        if self.products.values().any(|sp| !sp.price_history_consistent()) {
+            return false;
+        }
+
+        // Wholesale minimums and tiers must make sense against the price
+        if self
+            .products
+            .values()
+            .any(|sp| sp.product.wholesale.as_ref().is_some_and(|terms| !terms.is_valid(sp.product.price_curd)))
+        {
+            return false;
+        }
+
+        // Monthly totals are only ever a count of the orders folded in
+        if self.order_history.values().any(|stats| !stats.tallies()) {
+            return false;
+        }
+
+        // Refunds on cancellation follow the order's cancellation policy
+        if self.info.cancellation_policy.is_some_and(|p| !p.is_valid())
+            || self.orders.values().any(|o| !o.cancellation_refund_valid())
+        {
+            return false;
+        }
+
         #[cfg(feature = "dev")]
         {
-            let _ = owner;
+            let _ = suppliers;
             #[allow(clippy::needless_return)]
             return true;
         }
         #[cfg(not(feature = "dev"))]
         {
-            // All products must be signed by the storefront owner
+            if !self.info.verify_provenance(&suppliers) {
+                return false;
+            }
+
+            // All products must be signed (and stamped) by the supplier
             for signed in self.products.values() {
-                if !signed.verify_signature(owner) {
+                if !signed.verify_signature(signed.writer(owner)) || !signed.verify_provenance(&suppliers) {
                     return false;
                 }
             }
 
             // All orders must be signed by the customer
             for order in self.orders.values() {
-                let msg = order_signable_bytes(order);
-                if order.customer.0.verify(&msg, &order.signature).is_err() {
+                if !order_signed_by_customer(order) {
+                    return false;
+                }
+
+                // Status changes may be stamped by the supplier or the customer
+                if let Some(stamp) = &order.provenance {
+                    let writer_ok = suppliers.contains(&stamp.last_writer) || stamp.last_writer == order.customer.0;
+                    if !writer_ok || !stamp.verify(StateSection::Order, &order_provenance_bytes(order)) {
+                        return false;
+                    }
+                }
+
+                // Status changes are the customer's or the supplier's to make
+                let mut actors = order.status_history.iter().filter_map(|change| change.actor.as_ref());
+                if actors.any(|a| *a != order.customer && !suppliers.contains(&a.0)) {
+                    return false;
+                }
+
+                // Fulfilled orders need the customer's (or an auto-confirmed) receipt
+                if order.status == OrderStatus::Fulfilled && !fulfillment_receipt_valid(order, &suppliers) {
+                    return false;
+                }
+
+                // A balance attestation must be the customer's own
+                if order.balance_attestation.as_ref().is_some_and(|a| !a.verify()) {
                     return false;
                 }
 
@@ -371,23 +1018,639 @@ impl StorefrontState {
                 }
             }
 
-            true
+            // Thread messages must be signed by their author, and where the
+            // order travels with them, the author must be one of its parties
+            for (order_id, thread) in &self.threads {
+                let order = self.orders.get(order_id);
+                for message in thread.messages.values() {
+                    if message.order_id != *order_id || !message.is_valid() {
+                        return false;
+                    }
+                    if order.is_some_and(|o| !may_post(o, &suppliers, &message.author)) {
+                        return false;
+                    }
+                }
+            }
+
+            true
+        }
+    }
+
+    /// The keys that sign for the supplier: `owner` and everyone on the staff
+    /// list, revoked or not, so their earlier work still verifies.
+    pub fn supplier_keys(&self, owner: &VerifyingKey) -> Vec<VerifyingKey> {
+        std::iter::once(*owner).chain(self.staff.keys().map(|key| key.0)).collect()
+    }
+
+    /// Whether each change `update` would make to this state was written by a
+    /// key allowed to make it: the owner, or a staff member whose grant (with
+    /// `update`'s staff list folded in) covers that kind of change. Checked
+    /// before [`StorefrontState::merge`], on an update that has passed
+    /// [`StorefrontState::validate`].
+    ///
+    /// Customers' own writes — orders, pickup confirmations, amendment
+    /// requests, messages — need no grant, but a new order must be priced at
+    /// the listing's price (or special, less any wholesale volume discount)
+    /// when it was placed, meet a wholesale listing's minimum, and carry the
+    /// storefront's cancellation policy, deliveries must be to an address
+    /// one of our zones serves at its fee, orders over the attestation
+    /// threshold must carry a balance attestation covering their deposit,
+    /// paused listings take no new orders, and customers on the blocklist
+    /// (with `update`'s folded in) may place no new orders or messages. A revoked member's earlier work stays where
+    /// it already is, but isn't accepted anywhere new.
+    pub fn permits(&self, update: &StorefrontState, owner: &VerifyingKey) -> bool {
+        let mut staff = self.staff.clone();
+        merge_staff(&mut staff, update.staff.clone());
+        let allowed = |writer: &VerifyingKey, permission| may_write(&staff, owner, writer, permission);
+        let mut blocklist = self.blocklist.clone();
+        merge_blocklist(&mut blocklist, update.blocklist.clone());
+
+        // Info that would replace ours (unstamped info predates staff)
+        if !info_is_newer(&self.info, &update.info) && update.info != self.info {
+            if let Some(stamp) = &update.info.provenance {
+                if !allowed(&stamp.last_writer, StaffPermission::Storefront) {
+                    return false;
+                }
+            }
+        }
+
+        // New or newer listings
+        for (id, signed) in &update.products {
+            let newer = self
+                .products
+                .get(id)
+                .is_none_or(|existing| signed.product.updated_at > existing.product.updated_at);
+            if newer && !allowed(signed.writer(owner), StaffPermission::Products) {
+                return false;
+            }
+        }
+
+        // Status changes the supplier stamped, and answers to amendments
+        let order_keys: Vec<VerifyingKey> = std::iter::once(*owner)
+            .chain(staff.keys().map(|key| key.0))
+            .filter(|key| allowed(key, StaffPermission::Orders))
+            .collect();
+        for (id, order) in &update.orders {
+            let Some(existing) = self.orders.get(id) else {
+                if block_on(&blocklist, &order.customer).is_some() {
+                    return false;
+                }
+                // Paused listings take no new orders
+                if self.listing_paused(update, &order.product_id) {
+                    return false;
+                }
+                // Only the customer places an order
+                if order.status_history.iter().any(|c| c.actor.as_ref().is_some_and(|a| *a != order.customer)) {
+                    return false;
+                }
+                // Wholesale listings take no order under their minimum
+                if self
+                    .newest_listing(update, &order.product_id)
+                    .is_some_and(|sp| !sp.product.accepts_quantity(order.quantity))
+                {
+                    return false;
+                }
+                // New orders are locked at the listing's price when placed
+                if !self.order_price_locked(update, order) {
+                    return false;
+                }
+                // ... and carry the cancellation policy in force: ours, or
+                // one the owner sets in this same update
+                if order.cancellation_policy != self.info.cancellation_policy
+                    && !(order.cancellation_policy == update.info.cancellation_policy
+                        && self.owner_sets_policy(update, owner))
+                {
+                    return false;
+                }
+                // Deliveries must fall within one of our zones, at its fee
+                if order_delivery_unserved(&self.info.delivery_zones, order) {
+                    return false;
+                }
+                // Large orders need a fresh balance attestation
+                if self.attestation_required(update, order)
+                    && !order.balance_attestation.as_ref().is_some_and(|a| a.covers(order))
+                {
+                    return false;
+                }
+                continue;
+            };
+            if order.status.ordinal() > existing.status.ordinal() {
+                if let Some(stamp) = &order.provenance {
+                    if stamp.last_writer != order.customer.0 && !allowed(&stamp.last_writer, StaffPermission::Orders) {
+                        return false;
+                    }
+                }
+                // The history only grows, and what's added is the stamp writer's
+                if !order.status_history.starts_with(&existing.status_history) {
+                    return false;
+                }
+                let writer = order.provenance.as_ref().map(|stamp| stamp.last_writer);
+                let added = &order.status_history[existing.status_history.len()..];
+                if added.iter().any(|c| c.actor.as_ref().is_some_and(|a| Some(a.0) != writer)) {
+                    return false;
+                }
+            }
+            for (i, amendment) in order.amendments.iter().enumerate() {
+                let answered = existing.amendments.get(i).is_some_and(|a| a.acknowledgement.is_some());
+                if amendment.acknowledgement.is_some() && !answered && !ack_signed_by(order, amendment, &order_keys) {
+                    return false;
+                }
+            }
+        }
+
+        // New messages from the supplier's side
+        for (order_id, thread) in &update.threads {
+            let Some(order) = self.orders.get(order_id).or_else(|| update.orders.get(order_id)) else {
+                continue;
+            };
+            let held = self.threads.get(order_id);
+            for (message_id, message) in &thread.messages {
+                let new = held.is_none_or(|t| !t.messages.contains_key(message_id));
+                if new && message.author != order.customer && !allowed(&message.author.0, StaffPermission::Orders) {
+                    return false;
+                }
+                if new && block_on(&blocklist, &message.author).is_some() {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Whether `update` changes the cancellation policy with newer info
+    /// stamped by the owner, so orders placed under the new policy are
+    /// accepted with it. Customers write orders, not info.
+    fn owner_sets_policy(&self, update: &StorefrontState, owner: &VerifyingKey) -> bool {
+        update.info.cancellation_policy != self.info.cancellation_policy
+            && info_is_newer(&update.info, &self.info)
+            && update.info.provenance.as_ref().is_some_and(|stamp| {
+                stamp.last_writer == *owner
+                    && stamp.verify(StateSection::StorefrontInfo, &update.info.provenance_bytes())
+            })
+    }
+
+    /// Check the state against the size limits in [`crate::limits`].
+    pub fn check_limits(&self) -> Result<(), LimitError> {
+        check_len("storefront name", &self.info.name, MAX_NAME_LEN)?;
+        check_len("storefront description", &self.info.description, MAX_DESCRIPTION_LEN)?;
+        check_opt_len("phone", self.info.phone.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+        check_opt_len("email", self.info.email.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+        check_opt_len("address", self.info.address.as_deref(), MAX_CONTACT_FIELD_LEN)?;
+        check_count("products", self.products.len(), MAX_PRODUCTS_PER_STOREFRONT)?;
+        for signed in self.products.values() {
+            check_len("product name", &signed.product.name, MAX_NAME_LEN)?;
+            check_len("product description", &signed.product.description, MAX_DESCRIPTION_LEN)?;
+        }
+        check_count("delivery zones", self.info.delivery_zones.len(), MAX_DELIVERY_ZONES)?;
+        for zone in &self.info.delivery_zones {
+            check_len("delivery zone name", &zone.name, MAX_NAME_LEN)?;
+        }
+        check_count("pickup locations", self.info.pickup_locations.len(), MAX_PICKUP_LOCATIONS)?;
+        for location in &self.info.pickup_locations {
+            check_len("pickup location name", &location.name, MAX_NAME_LEN)?;
+        }
+        if let Some(sealed) = &self.info.sealed_contact {
+            check_len("sealed contact details", &sealed.ciphertext, MAX_DESCRIPTION_LEN)?;
+            check_count("sealed contact keys", sealed.orders.len(), MAX_ORDERS_PER_STOREFRONT)?;
+            for wrapped in std::iter::once(&sealed.owner).chain(sealed.orders.values()) {
+                check_len("sealed contact nonce", &wrapped.nonce, MAX_NAME_LEN)?;
+                check_len("sealed contact key", &wrapped.key, MAX_NAME_LEN)?;
+            }
+            check_len("sealed contact nonce", &sealed.nonce, MAX_NAME_LEN)?;
+        }
+        check_count("orders", self.orders.len(), MAX_ORDERS_PER_STOREFRONT)?;
+        for delivery in self.orders.values().filter_map(|o| o.delivery.as_ref()) {
+            check_len("delivery address", &delivery.address, MAX_CONTACT_FIELD_LEN)?;
+            check_len("delivery postcode", &delivery.postcode, MAX_CONTACT_FIELD_LEN)?;
+        }
+        for thread in self.threads.values() {
+            check_count("thread messages", thread.messages.len(), MAX_THREAD_MESSAGES)?;
+            let attachments = thread.messages.values().filter(|m| m.attachment.is_some()).count();
+            check_count("thread attachments", attachments, MAX_THREAD_ATTACHMENTS)?;
+        }
+        check_count("order history months", self.order_history.len(), MAX_ORDER_HISTORY_MONTHS)?;
+        for stats in self.order_history.values() {
+            check_count("folded orders", stats.folded.len(), MAX_ORDERS_PER_STOREFRONT)?;
+        }
+        check_count("blocklist entries", self.blocklist.len(), MAX_BLOCKLIST_ENTRIES)?;
+        for entry in self.blocklist.values() {
+            check_len("appeal note", &entry.appeal_note, MAX_DESCRIPTION_LEN)?;
+        }
+        Ok(())
+    }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`].
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        let info_written = self.info.provenance.as_ref().map(|p| p.written_at);
+        check_opt_not_future("storefront info written_at", info_written, now)?;
+        for signed in self.products.values() {
+            let product = &signed.product;
+            check_not_future("product created_at", product.created_at, now)?;
+            check_not_future("product updated_at", product.updated_at, now)?;
+            check_order("product updated_at", product.updated_at, "created_at", product.created_at)?;
+            for (at, _) in &signed.price_history {
+                check_not_future("price change", *at, now)?;
+            }
+        }
+        for order in self.orders.values() {
+            order.check_timestamps(now)?;
+        }
+        for thread in self.threads.values() {
+            for message in thread.messages.values() {
+                check_not_future("message created_at", message.created_at, now)?;
+            }
+        }
+        for grant in self.staff.values() {
+            check_not_future("staff grant updated_at", grant.updated_at, now)?;
+        }
+        for entry in self.blocklist.values() {
+            check_not_future("blocklist updated_at", entry.updated_at, now)?;
+        }
+        Ok(())
+    }
+
+    /// Check that every order in `update` new to this state was placed within
+    /// the allowed skew of `now`, the contract host's clock. New orders are
+    /// priced as the listing was when placed, so a backdated one could catch
+    /// a special that has ended or a price since raised.
+    pub fn check_orders_recent(&self, update: &StorefrontState, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        for (id, order) in &update.orders {
+            if !self.orders.contains_key(id) {
+                check_recent("order created_at", order.created_at, now)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `update` leaves the timestamps fixed at creation alone:
+    /// when each product and order was created, and when a reservation lapses.
+    pub fn check_timestamp_progress(&self, update: &StorefrontState) -> Result<(), TimestampError> {
+        for (id, signed) in &update.products {
+            if let Some(existing) = self.products.get(id) {
+                check_unchanged("product created_at", existing.product.created_at, signed.product.created_at)?;
+            }
+        }
+        for (id, order) in &update.orders {
+            if let Some(existing) = self.orders.get(id) {
+                existing.check_timestamp_progress(order)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which parts of a storefront differ between two versions of it.
+///
+/// Lets the UI update only what a notification actually changed (and skip
+/// notifications that changed nothing) instead of re-rendering the whole
+/// storefront.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StorefrontDiff {
+    /// The info section (name, schedule, contact details, ...) or the
+    /// blocklist changed.
+    pub info: bool,
+    /// Products added, changed or removed.
+    pub products: BTreeSet<ProductId>,
+    /// Orders added, changed or removed.
+    pub orders: BTreeSet<OrderId>,
+    /// Order threads whose messages changed.
+    pub threads: BTreeSet<OrderId>,
+    /// Products whose available quantity may have moved because an order
+    /// for them changed.
+    pub stock: BTreeSet<ProductId>,
+}
+
+impl StorefrontDiff {
+    pub fn is_empty(&self) -> bool {
+        !self.info
+            && self.products.is_empty()
+            && self.orders.is_empty()
+            && self.threads.is_empty()
+            && self.stock.is_empty()
+    }
+
+    /// Everything in `state` (e.g. a storefront seen for the first time).
+    pub fn all(state: &StorefrontState) -> Self {
+        StorefrontState::diff_from(None, state)
+    }
+}
+
+impl StorefrontState {
+    /// What changed going from `self` to `newer`.
+    pub fn diff(&self, newer: &StorefrontState) -> StorefrontDiff {
+        Self::diff_from(Some(self), newer)
+    }
+
+    fn diff_from(older: Option<&StorefrontState>, newer: &StorefrontState) -> StorefrontDiff {
+        let info =
+            older.is_none_or(|o| o.info != newer.info || o.blocklist != newer.blocklist || o.extra != newer.extra);
+        let products = changed_keys(older.map(|o| &o.products), &newer.products);
+        let orders = changed_keys(older.map(|o| &o.orders), &newer.orders);
+        let threads = changed_keys(older.map(|o| &o.threads), &newer.threads);
+        let stock = orders
+            .iter()
+            .flat_map(|id| {
+                let before = older.and_then(|o| o.orders.get(id));
+                [before, newer.orders.get(id)]
+            })
+            .flatten()
+            .map(|order| order.product_id.clone())
+            .collect();
+        StorefrontDiff { info, products, orders, threads, stock }
+    }
+}
+
+/// Keys added, removed or with a different value in `newer`.
+fn changed_keys<K: Ord + Clone, V: PartialEq>(
+    older: Option<&BTreeMap<K, V>>,
+    newer: &BTreeMap<K, V>,
+) -> BTreeSet<K> {
+    let Some(older) = older else {
+        return newer.keys().cloned().collect();
+    };
+    let updated = newer
+        .iter()
+        .filter(|(k, v)| older.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone());
+    let removed = older.keys().filter(|k| !newer.contains_key(*k)).cloned();
+    updated.chain(removed).collect()
+}
+
+/// Serialize a product listing for signing (everything except extension fields).
+pub fn product_signable_bytes(product: &Product) -> Vec<u8> {
+    let signable = SignableProduct {
+        id: &product.id,
+        name: &product.name,
+        description: &product.description,
+        category: &product.category,
+        subcategory: &product.subcategory,
+        attributes: &product.attributes,
+        price_curd: product.price_curd,
+        quantity_total: product.quantity_total,
+        expiry_date: &product.expiry_date,
+        updated_at: &product.updated_at,
+        created_at: &product.created_at,
+        low_stock: &product.low_stock,
+        paused: product.paused,
+        special: &product.special,
+        wholesale: &product.wholesale,
+    };
+    signing::signable_bytes(Domain::Product, &signable)
+}
+
+/// Serialize order fields for signing (everything except signature).
+pub fn order_signable_bytes(order: &Order) -> Vec<u8> {
+    signing::signable_bytes(Domain::Order, &signable_order(order))
+}
+
+/// Whether `order` carries its customer's signature, under either scheme
+/// (see [`crate::signing`]).
+pub fn order_signed_by_customer(order: &Order) -> bool {
+    signing::verify(&order.customer.0, &order.signature, order.created_at, &order_signable_bytes(order), || {
+        order_json(order)
+    })
+    .is_some()
+}
+
+/// The signed order fields as bare JSON: what orders were signed over before
+/// [`crate::signing`], and what receipt, amendment and provenance bytes
+/// still start with.
+fn order_json(order: &Order) -> Vec<u8> {
+    serde_json::to_vec(&signable_order(order)).expect("serialization should not fail")
+}
+
+fn signable_order(order: &Order) -> SignableOrder<'_> {
+    SignableOrder {
+        id: &order.id,
+        product_id: &order.product_id,
+        customer: &order.customer,
+        quantity: order.quantity,
+        deposit_tier: &order.deposit_tier,
+        total_price: order.total_price,
+        created_at: &order.created_at,
+        cancellation_policy: &order.cancellation_policy,
+        delivery: &order.delivery,
+    }
+}
+
+/// Bytes signed for a fulfillment receipt: the signed order fields plus the
+/// proposal and confirmation times.
+pub fn receipt_signable_bytes(
+    order: &Order,
+    proposed_at: &DateTime<Utc>,
+    confirmed_at: &DateTime<Utc>,
+    auto_confirmed: bool,
+) -> Vec<u8> {
+    let mut bytes = order_json(order);
+    bytes.extend_from_slice(proposed_at.to_rfc3339().as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(confirmed_at.to_rfc3339().as_bytes());
+    bytes.push(auto_confirmed as u8);
+    bytes
+}
+
+/// Whether `ours` should be kept over `theirs`: the higher `seq` wins
+/// outright; on equal `seq`, ours only if it carries a newer writer stamp.
+fn info_is_newer(ours: &StorefrontInfo, theirs: &StorefrontInfo) -> bool {
+    match ours.seq.cmp(&theirs.seq) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => matches!(
+            (&ours.provenance, &theirs.provenance),
+            (Some(ours), Some(theirs)) if ours.is_newer_than(Some(theirs))
+        ),
+    }
+}
+
+/// Whether a `Fulfilled` order carries an acceptable receipt: counter-signed
+/// by the customer, or auto-confirmed by one of the `suppliers` once the
+/// confirmation window has lapsed. The receipt must answer the proposal the
+/// status history records, if it records one; the contract also holds it to
+/// the proposal it saw (see [`Order::check_timestamp_progress`]) and keeps
+/// `confirmed_at` out of the future (see [`Order::check_timestamps`]).
+pub fn fulfillment_receipt_valid(order: &Order, suppliers: &[VerifyingKey]) -> bool {
+    let Some(receipt) = &order.receipt else {
+        return false;
+    };
+    if receipt.confirmed_at < receipt.proposed_at {
+        return false;
+    }
+    let recorded = order.status_history.iter().rev().find_map(|change| match change.status {
+        OrderStatus::FulfillmentProposed { proposed_at } => Some(proposed_at),
+        _ => None,
+    });
+    if recorded.is_some_and(|proposed_at| proposed_at != receipt.proposed_at) {
+        return false;
+    }
+    let signers = if receipt.auto_confirmed {
+        if receipt.confirmed_at < FulfillmentReceipt::auto_confirm_after(receipt.proposed_at) {
+            return false;
+        }
+        suppliers
+    } else {
+        std::slice::from_ref(&order.customer.0)
+    };
+    #[cfg(feature = "dev")]
+    {
+        let _ = signers;
+        #[allow(clippy::needless_return)]
+        return true;
+    }
+    #[cfg(not(feature = "dev"))]
+    {
+        let msg = receipt_signable_bytes(
+            order,
+            &receipt.proposed_at,
+            &receipt.confirmed_at,
+            receipt.auto_confirmed,
+        );
+        signers.iter().any(|signer| signer.verify(&msg, &receipt.signature).is_ok())
+    }
+}
+
+/// Bytes the customer signs for an amendment: the signed order fields plus
+/// the amendment's terms.
+pub fn amendment_signable_bytes(order: &Order, amendment: &OrderAmendment) -> Vec<u8> {
+    let signable = SignableAmendment {
+        seq: amendment.seq,
+        quantity: amendment.quantity,
+        collection_point: &amendment.collection_point,
+        total_price: amendment.total_price,
+        deposit_amount: amendment.deposit_amount,
+        requested_at: &amendment.requested_at,
+    };
+    let mut bytes = order_json(order);
+    bytes.extend(serde_json::to_vec(&signable).expect("serialization should not fail"));
+    bytes
+}
+
+/// Bytes the supplier signs to answer an amendment.
+pub fn amendment_ack_signable_bytes(
+    order: &Order,
+    amendment: &OrderAmendment,
+    accepted: bool,
+    acknowledged_at: &DateTime<Utc>,
+) -> Vec<u8> {
+    let mut bytes = amendment_signable_bytes(order, amendment);
+    bytes.extend_from_slice(acknowledged_at.to_rfc3339().as_bytes());
+    bytes.push(accepted as u8);
+    bytes
+}
+
+/// Whether an order's amendment chain is well formed: numbered from 1, each
+/// answered before the next is made and no earlier than it was requested,
+/// with a positive quantity at the order's unit price and the deposit its
+/// tier requires; requests signed by the customer and answers by one of the
+/// `suppliers`.
+pub fn amendments_valid(order: &Order, suppliers: &[VerifyingKey]) -> bool {
+    let mut not_before = order.created_at;
+    for (i, amendment) in order.amendments.iter().enumerate() {
+        let last = i + 1 == order.amendments.len();
+        let total_price = order.unit_price() * u64::from(amendment.quantity);
+        if amendment.seq as usize != i + 1
+            || amendment.quantity == 0
+            || amendment.total_price != total_price
+            || amendment.deposit_amount != order.deposit_tier.calculate_deposit(total_price)
+            || amendment.requested_at < not_before
+        {
+            return false;
+        }
+        match &amendment.acknowledgement {
+            Some(ack) if ack.acknowledged_at < amendment.requested_at => return false,
+            Some(ack) => not_before = ack.acknowledged_at,
+            None if !last => return false,
+            None => {}
+        }
+    }
+    #[cfg(feature = "dev")]
+    {
+        let _ = suppliers;
+        #[allow(clippy::needless_return)]
+        return true;
+    }
+    #[cfg(not(feature = "dev"))]
+    {
+        order.amendments.iter().all(|amendment| {
+            let requested = order
+                .customer
+                .0
+                .verify(&amendment_signable_bytes(order, amendment), &amendment.signature)
+                .is_ok();
+            requested && (amendment.acknowledgement.is_none() || ack_signed_by(order, amendment, suppliers))
+        })
+    }
+}
+
+/// Whether `amendment`'s answer is signed by one of `keys`.
+fn ack_signed_by(order: &Order, amendment: &OrderAmendment, keys: &[VerifyingKey]) -> bool {
+    let Some(ack) = &amendment.acknowledgement else {
+        return false;
+    };
+    #[cfg(feature = "dev")]
+    {
+        let _ = (order, ack, keys);
+        #[allow(clippy::needless_return)]
+        return true;
+    }
+    #[cfg(not(feature = "dev"))]
+    {
+        let msg = amendment_ack_signable_bytes(order, amendment, ack.accepted, &ack.acknowledged_at);
+        keys.iter().any(|key| key.verify(&msg, &ack.signature).is_ok())
+    }
+}
+
+/// Fold another copy's amendment chain into `order`'s. Chains only grow: an
+/// answer fills in the matching pending request, and a request one past the
+/// end is appended while the order can still be amended. Where the two
+/// copies disagree about a request, ours stands.
+fn merge_amendments(order: &mut Order, theirs: Vec<OrderAmendment>) {
+    for amendment in theirs {
+        let Some(index) = (amendment.seq as usize).checked_sub(1) else {
+            continue;
+        };
+        if index == order.amendments.len() {
+            if order.can_amend() {
+                order.amendments.push(amendment);
+            }
+        } else if let Some(ours) = order.amendments.get_mut(index) {
+            if ours.acknowledgement.is_none() && amendment.acknowledgement.is_some() && ours.same_request(&amendment) {
+                *ours = amendment;
+            }
         }
     }
 }
 
-/// Serialize order fields for signing (everything except signature).
-pub fn order_signable_bytes(order: &Order) -> Vec<u8> {
-    let signable = SignableOrder {
-        id: &order.id,
-        product_id: &order.product_id,
-        customer: &order.customer,
-        quantity: order.quantity,
-        deposit_tier: &order.deposit_tier,
-        total_price: order.total_price,
-        created_at: &order.created_at,
-    };
-    serde_json::to_vec(&signable).expect("serialization should not fail")
+/// Bytes covered by an order's provenance stamp: the signed order fields plus
+/// its current status and status history (the parts that change after placement).
+pub fn order_provenance_bytes(order: &Order) -> Vec<u8> {
+    let mut bytes = order_json(order);
+    bytes.extend(serde_json::to_vec(&order.status).expect("serialization should not fail"));
+    // Orders from before the history was recorded keep their stamps
+    if !order.status_history.is_empty() {
+        bytes.extend(serde_json::to_vec(&order.status_history).expect("serialization should not fail"));
+    }
+    bytes
+}
+
+#[derive(Serialize)]
+struct SignableProduct<'a> {
+    id: &'a ProductId,
+    name: &'a str,
+    description: &'a str,
+    category: &'a crate::product::ProductCategory,
+    subcategory: &'a Option<crate::product::Subcategory>,
+    attributes: &'a crate::product::ProductAttributes,
+    price_curd: Curds,
+    quantity_total: u32,
+    expiry_date: &'a Option<DateTime<Utc>>,
+    updated_at: &'a DateTime<Utc>,
+    created_at: &'a DateTime<Utc>,
+    low_stock: &'a Option<crate::product::LowStockRule>,
+    paused: bool,
+    special: &'a Option<crate::product::Special>,
+    wholesale: &'a Option<crate::product::WholesaleTerms>,
 }
 
 #[derive(Serialize)]
@@ -399,6 +1662,20 @@ struct SignableOrder<'a> {
     deposit_tier: &'a crate::order::DepositTier,
     total_price: u64,
     created_at: &'a DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancellation_policy: &'a Option<crate::order::CancellationPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delivery: &'a Option<crate::delivery::DeliveryDetails>,
+}
+
+#[derive(Serialize)]
+struct SignableAmendment<'a> {
+    seq: u32,
+    quantity: u32,
+    collection_point: &'a Option<CollectionPoint>,
+    total_price: u64,
+    deposit_amount: u64,
+    requested_at: &'a DateTime<Utc>,
 }
 
 /// Summary of storefront state: IDs -> timestamps.
@@ -406,12 +1683,38 @@ struct SignableOrder<'a> {
 pub struct StorefrontSummary {
     pub product_timestamps: BTreeMap<ProductId, DateTime<Utc>>,
     pub order_timestamps: BTreeMap<OrderId, (DateTime<Utc>, u8)>, // (created_at, status_ordinal)
+    /// Message ids held per order thread.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub thread_messages: BTreeMap<OrderId, BTreeSet<MessageId>>,
+    /// [`Order::amendment_progress`] per amended order.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub order_amendments: BTreeMap<OrderId, u32>,
+    /// Months already folded into order history.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub compacted_months: BTreeSet<String>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl StorefrontState {
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        let messages = self.threads.values().flat_map(|t| t.messages.values());
+        ContractMetrics::new(state_bytes)
+            .count("products", self.products.len())
+            .count("orders", self.orders.len())
+            .count("threads", self.threads.len())
+            .count("messages", self.threads.values().map(|t| t.messages.len()).sum())
+            .count("staff", self.staff.len())
+            .count("blocklist", self.blocklist.len())
+            .count("order_history", self.order_history.len())
+            .updated("products", self.products.values().map(|sp| sp.product.updated_at).max())
+            .updated("orders", self.orders.values().map(|o| o.created_at).max())
+            .updated("messages", messages.map(|m| m.created_at).max())
+    }
+
     pub fn summarize(&self) -> StorefrontSummary {
         StorefrontSummary {
             product_timestamps: self
@@ -424,11 +1727,25 @@ impl StorefrontState {
                 .iter()
                 .map(|(id, o)| (id.clone(), (o.created_at, o.status.ordinal())))
                 .collect(),
+            thread_messages: self
+                .threads
+                .iter()
+                .map(|(id, t)| (id.clone(), t.messages.keys().cloned().collect()))
+                .collect(),
+            order_amendments: self
+                .orders
+                .iter()
+                .filter(|(_, o)| !o.amendments.is_empty())
+                .map(|(id, o)| (id.clone(), o.amendment_progress()))
+                .collect(),
+            compacted_months: self.order_history.keys().cloned().collect(),
             extra: Default::default(),
         }
     }
 
-    /// Compute delta: products newer than summary, orders with higher status or missing.
+    /// Compute delta: products newer than summary, orders with higher status,
+    /// further-along amendments or missing (unless terminal in a month the
+    /// summary has compacted), and thread messages the summary doesn't hold.
     pub fn delta(&self, summary: &StorefrontSummary) -> StorefrontState {
         let products = self
             .products
@@ -445,19 +1762,46 @@ impl StorefrontState {
         let orders = self
             .orders
             .iter()
+            .filter(|(_, order)| {
+                !(order.status.is_terminal() && summary.compacted_months.contains(&order_month(&order.created_at)))
+            })
             .filter(|(id, order)| {
                 summary
                     .order_timestamps
                     .get(*id)
                     .is_none_or(|(_, ord)| order.status.ordinal() > *ord)
+                    || order.amendment_progress() > summary.order_amendments.get(*id).copied().unwrap_or(0)
             })
             .map(|(id, o)| (id.clone(), o.clone()))
             .collect();
 
+        let threads = self
+            .threads
+            .iter()
+            .filter_map(|(id, thread)| {
+                let known = summary.thread_messages.get(id);
+                let messages: BTreeMap<_, _> = thread
+                    .messages
+                    .iter()
+                    .filter(|(mid, _)| known.is_none_or(|k| !k.contains(*mid)))
+                    .map(|(mid, m)| (mid.clone(), m.clone()))
+                    .collect();
+                (!messages.is_empty()).then(|| {
+                    (id.clone(), OrderThread { messages, extra: Default::default() })
+                })
+            })
+            .collect();
+
         StorefrontState {
             info: self.info.clone(),
             products,
             orders,
+            threads,
+            // Always whole: the other sections' signatures may need it
+            staff: self.staff.clone(),
+            blocklist: self.blocklist.clone(),
+            // Whole too: at most one small entry per month
+            order_history: self.order_history.clone(),
             extra: Default::default(),
         }
     }
@@ -467,8 +1811,9 @@ impl StorefrontState {
 mod tests {
     use super::*;
     use crate::identity::UserId;
+    use crate::message::tests::message;
     use crate::order::{DepositTier, Order, OrderId};
-    use crate::product::ProductId;
+    use crate::product::{LowStockRule, ProductId, Special};
     use chrono::{Duration, Utc};
     use ed25519_dalek::{Signature, SigningKey};
 
@@ -486,10 +1831,21 @@ mod tests {
                 email: None,
                 address: None,
                 market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
                 extra: Default::default(),
             },
             products: BTreeMap::new(),
             orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
             extra: Default::default(),
         }
     }
@@ -509,10 +1865,159 @@ mod tests {
             signature: Signature::from_bytes(&[0u8; 64]),
             escrow_token: None,
             collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
             extra: Default::default(),
         }
     }
 
+    #[test]
+    fn diff_reports_changed_sections() {
+        let older = dummy_storefront();
+        assert!(older.diff(&older.clone()).is_empty());
+        assert!(StorefrontDiff::all(&older).info);
+
+        let mut newer = older.clone();
+        newer.info.phone = Some("0400 000 000".into());
+        let order = dummy_order("o-1", OrderStatus::Paid);
+        newer.orders.insert(order.id.clone(), order.clone());
+        let diff = older.diff(&newer);
+        assert!(diff.info);
+        assert!(diff.products.is_empty());
+        assert_eq!(diff.orders, [order.id.clone()].into());
+        assert_eq!(diff.stock, [ProductId("p-1".into())].into());
+
+        // Status change only: the order and its product's stock, nothing else
+        let mut later = newer.clone();
+        later.orders.get_mut(&order.id).unwrap().status = OrderStatus::Cancelled;
+        let diff = newer.diff(&later);
+        assert!(!diff.info);
+        assert_eq!(diff.orders, [order.id.clone()].into());
+        assert_eq!(diff.stock, [ProductId("p-1".into())].into());
+
+        // Removal counts as a change
+        let diff = later.diff(&older);
+        assert_eq!(diff.orders, [order.id].into());
+    }
+
+    #[test]
+    fn check_limits_rejects_oversized_description() {
+        let mut sf = dummy_storefront();
+        assert!(sf.check_limits().is_ok());
+        sf.info.description = "x".repeat(crate::limits::MAX_DESCRIPTION_LEN + 1);
+        assert!(matches!(
+            sf.check_limits(),
+            Err(LimitError::FieldTooLong { field: "storefront description", .. })
+        ));
+    }
+
+    #[test]
+    fn check_limits_caps_attachments_per_thread() {
+        use crate::limits::MAX_THREAD_ATTACHMENTS;
+        use crate::message::MessageAttachment;
+
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let mut sf = dummy_storefront();
+        let thread = sf.threads.entry(OrderId("o".into())).or_default();
+        for i in 0..=MAX_THREAD_ATTACHMENTS {
+            let mut msg = message(&customer, "o", &format!("m-{i}"), "Ready Saturday?", Utc::now());
+            msg.attachment = Some(MessageAttachment::new("a.png".into(), "image/png".into(), &[1, 2, 3]));
+            thread.insert(msg);
+        }
+        assert!(matches!(
+            sf.check_limits(),
+            Err(LimitError::TooManyItems { what: "thread attachments", .. })
+        ));
+    }
+
+    #[test]
+    fn new_deliveries_must_fall_within_a_zone() {
+        use crate::delivery::{DeliveryDetails, ZoneArea};
+        use ed25519_dalek::Signer;
+
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let mut sf = dummy_storefront();
+        let owner = sf.info.owner.0;
+        sf.info.delivery_zones.push(DeliveryZone {
+            name: "Town".into(),
+            area: ZoneArea::Postcodes(["2450".to_string()].into()),
+            fee_curd: 5,
+            extra: Default::default(),
+        });
+        let delivered_to = |postcode: &str| {
+            let mut order = dummy_order("d-1", OrderStatus::Paid);
+            order.delivery = Some(DeliveryDetails {
+                address: "1 Main Rd".into(),
+                postcode: postcode.into(),
+                location: None,
+                zone: "Town".into(),
+                fee_curd: 5,
+                extra: Default::default(),
+            });
+            order.signature = customer.sign(&order_signable_bytes(&order));
+            let mut update = sf.clone();
+            update.orders.insert(order.id.clone(), order);
+            update
+        };
+        assert!(!sf.permits(&delivered_to("3000"), &owner));
+        let placed = delivered_to("2450");
+        assert!(sf.permits(&placed, &owner));
+
+        // The address is signed with the order, so a relay can't redirect it
+        let mut redirected = placed.orders[&OrderId("d-1".into())].clone();
+        assert!(order_signed_by_customer(&redirected));
+        redirected.delivery.as_mut().unwrap().address = "2 Other Rd".into();
+        assert!(!order_signed_by_customer(&redirected));
+
+        // Once placed, retiring the zone leaves the order, and the state, valid
+        sf.merge(placed);
+        sf.info.delivery_zones.clear();
+        assert!(sf.validate(&owner));
+        assert!(sf.permits(&sf.clone(), &owner));
+    }
+
+    #[test]
+    fn pending_pickup_must_name_a_listed_location() {
+        let mut sf = dummy_storefront();
+        let owner = sf.info.owner.0;
+        sf.info.pickup_locations.push(PickupLocation {
+            name: "Bellingen market".into(),
+            location: GeoLocation::new(-30.45, 152.9),
+            schedule: None,
+            extra: Default::default(),
+        });
+        let mut order = dummy_order("p-1", OrderStatus::Paid);
+        order.collection_point = Some(CollectionPoint::Pickup { location_name: "Bellingen market".into() });
+        assert!(!order_pickup_unlisted(&sf.info.pickup_locations, &order));
+
+        order.collection_point = Some(CollectionPoint::Pickup { location_name: "Dorrigo market".into() });
+        sf.orders.insert(order.id.clone(), order.clone());
+        assert!(!sf.validate(&owner));
+
+        // Once handed over, retiring the location doesn't invalidate the order
+        order.status = OrderStatus::Cancelled;
+        sf.info.pickup_locations.clear();
+        assert!(!order_pickup_unlisted(&sf.info.pickup_locations, &order));
+        sf.orders.clear();
+
+        // Two locations under one name can't be told apart
+        let bellingen = PickupLocation {
+            name: "Bellingen market".into(),
+            location: GeoLocation::new(-30.45, 152.9),
+            schedule: None,
+            extra: Default::default(),
+        };
+        sf.info.pickup_locations = vec![bellingen.clone(), bellingen];
+        assert!(!sf.validate(&owner));
+    }
+
     #[test]
     fn expire_orders_transitions_past_reserved() {
         let mut sf = dummy_storefront();
@@ -625,6 +2130,54 @@ mod tests {
         assert!(!sched.is_open_at(tuesday_10am, 0));
     }
 
+    #[test]
+    fn currently_open_follows_the_clock() {
+        use crate::clock::MockClock;
+
+        let mut sched = WeeklySchedule::new();
+        sched.set_range(0, 18, 34, true);
+        // Monday 2024-01-01 08:00 UTC: half an hour before opening
+        let clock = MockClock::new(
+            chrono::DateTime::parse_from_rfc3339("2024-01-01T08:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        );
+        assert!(!sched.is_currently_open(&clock, 0));
+        clock.advance(Duration::hours(1));
+        assert!(sched.is_currently_open(&clock, 0));
+        clock.advance(Duration::hours(8));
+        assert!(!sched.is_currently_open(&clock, 0));
+    }
+
+    #[test]
+    fn open_now_and_next_opening_follow_daylight_saving() {
+        let mut sched = WeeklySchedule::new();
+        // Saturday 9:00–12:00 local
+        sched.set_range(5, 18, 24, true);
+        let sydney = Some("Australia/Sydney");
+
+        // Sat 2024-01-06 23:30 UTC = Sun 10:30 AEDT (+11): closed
+        let sun = chrono::DateTime::parse_from_rfc3339("2024-01-06T23:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert!(!is_open_now(&sched, sydney, sun));
+        // Fri 2024-01-05 23:30 UTC = Sat 10:30 AEDT: open
+        let sat = sun - Duration::days(1);
+        assert!(is_open_now(&sched, sydney, sat));
+
+        // Next opening is the following Saturday 9:00 local, +11:00 in summer
+        let next = next_opening(&sched, sydney, sun).unwrap();
+        assert_eq!(next.to_rfc3339(), "2024-01-13T09:00:00+11:00");
+
+        // In winter Sydney is +10:00
+        let winter = chrono::DateTime::parse_from_rfc3339("2024-07-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(next_opening(&sched, sydney, winter).unwrap().offset().local_minus_utc(), 10 * 3600);
+
+        assert!(next_opening(&WeeklySchedule::new(), sydney, sun).is_none());
+    }
+
     #[test]
     fn weekly_schedule_slot_time_conversion() {
         assert_eq!(WeeklySchedule::slot_to_time(0), (0, 0));
@@ -686,6 +2239,13 @@ mod tests {
             email: None,
             address: None,
             market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
             extra: Default::default(),
         };
         let json = serde_json::to_string(&info_old).unwrap();
@@ -716,6 +2276,983 @@ mod tests {
         assert!(!sf.expire_orders(Utc::now()));
     }
 
+    fn stamp(section: StateSection, content: &[u8], at: chrono::DateTime<Utc>) -> WriterStamp {
+        use ed25519_dalek::Signer;
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let msg = WriterStamp::signable_bytes(section, &at, content);
+        WriterStamp {
+            last_writer: key.verifying_key(),
+            written_at: at,
+            signature: key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_newer_stamped_info() {
+        let now = Utc::now();
+        let mut ours = dummy_storefront();
+        ours.info.phone = Some("new".into());
+        ours.info.provenance = Some(stamp(StateSection::StorefrontInfo, &ours.info.provenance_bytes(), now));
+
+        let mut stale = dummy_storefront();
+        stale.info.phone = Some("old".into());
+        stale.info.provenance = Some(stamp(
+            StateSection::StorefrontInfo,
+            &stale.info.provenance_bytes(),
+            now - Duration::minutes(10),
+        ));
+
+        ours.merge(stale);
+        assert_eq!(ours.info.phone.as_deref(), Some("new"));
+
+        // Unstamped updates still propagate (older clients)
+        let mut unstamped = dummy_storefront();
+        unstamped.info.phone = Some("legacy".into());
+        ours.merge(unstamped);
+        assert_eq!(ours.info.phone.as_deref(), Some("legacy"));
+        assert!(ours.info.provenance.is_none());
+    }
+
+    #[test]
+    fn merge_ignores_replayed_info_with_lower_seq() {
+        let now = Utc::now();
+        let mut ours = dummy_storefront();
+        ours.info.seq = 2;
+        ours.info.phone = Some("new".into());
+        ours.info.provenance = Some(stamp(StateSection::StorefrontInfo, &ours.info.provenance_bytes(), now));
+
+        // An earlier write, replayed with a later stamp time
+        let mut replayed = dummy_storefront();
+        replayed.info.seq = 1;
+        replayed.info.phone = Some("old".into());
+        replayed.info.provenance = Some(stamp(
+            StateSection::StorefrontInfo,
+            &replayed.info.provenance_bytes(),
+            now + Duration::minutes(10),
+        ));
+        ours.merge(replayed);
+        assert_eq!(ours.info.phone.as_deref(), Some("new"));
+
+        // Nor can an unsequenced (legacy) update roll it back
+        let mut unsequenced = dummy_storefront();
+        unsequenced.info.phone = Some("legacy".into());
+        ours.merge(unsequenced);
+        assert_eq!(ours.info.phone.as_deref(), Some("new"));
+    }
+
+    #[test]
+    fn validate_rejects_unstamped_seq() {
+        let mut sf = dummy_storefront();
+        sf.info.seq = 1;
+        assert!(!sf.validate(&sf.info.owner.0));
+    }
+
+    #[test]
+    fn merge_carries_order_stamp_with_winning_status() {
+        let mut ours = dummy_storefront();
+        ours.orders.insert(OrderId("o".into()), dummy_order("o", OrderStatus::Paid));
+
+        let mut theirs = dummy_storefront();
+        let proposed_at = Utc::now();
+        let mut proposed = dummy_order("o", OrderStatus::FulfillmentProposed { proposed_at });
+        proposed.provenance = Some(stamp(StateSection::Order, &order_provenance_bytes(&proposed), Utc::now()));
+        theirs.orders.insert(OrderId("o".into()), proposed.clone());
+
+        ours.merge(theirs);
+        let merged = &ours.orders[&OrderId("o".into())];
+        assert_eq!(merged.status, OrderStatus::FulfillmentProposed { proposed_at });
+        assert_eq!(merged.provenance, proposed.provenance);
+    }
+
+    /// Receipt signed by the customer (or the owner, if auto-confirmed) of the dummy fixtures.
+    fn receipt(order: &Order, proposed_at: DateTime<Utc>, confirmed_at: DateTime<Utc>, auto_confirmed: bool) -> FulfillmentReceipt {
+        use ed25519_dalek::Signer;
+        let key = SigningKey::from_bytes(if auto_confirmed { &[1u8; 32] } else { &[2u8; 32] });
+        let msg = receipt_signable_bytes(order, &proposed_at, &confirmed_at, auto_confirmed);
+        FulfillmentReceipt {
+            proposed_at,
+            confirmed_at,
+            auto_confirmed,
+            signature: key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn merge_rejects_fulfilled_without_receipt() {
+        let proposed_at = Utc::now();
+        let mut ours = dummy_storefront();
+        ours.orders.insert(
+            OrderId("o".into()),
+            dummy_order("o", OrderStatus::FulfillmentProposed { proposed_at }),
+        );
+
+        let mut theirs = dummy_storefront();
+        theirs.orders.insert(OrderId("o".into()), dummy_order("o", OrderStatus::Fulfilled));
+        ours.merge(theirs);
+        assert!(matches!(
+            ours.orders[&OrderId("o".into())].status,
+            OrderStatus::FulfillmentProposed { .. }
+        ));
+
+        let mut theirs = dummy_storefront();
+        let mut confirmed = dummy_order("o", OrderStatus::Fulfilled);
+        confirmed.receipt = Some(receipt(&confirmed, proposed_at, proposed_at + Duration::hours(1), false));
+        theirs.orders.insert(OrderId("o".into()), confirmed);
+        ours.merge(theirs);
+        assert_eq!(ours.orders[&OrderId("o".into())].status, OrderStatus::Fulfilled);
+    }
+
+    /// `order`'s next amendment to `quantity`, signed by the dummy customer.
+    fn amendment(order: &Order, quantity: u32, requested_at: DateTime<Utc>) -> OrderAmendment {
+        use ed25519_dalek::Signer;
+        let mut amendment = order.amendment(quantity, None, requested_at);
+        amendment.signature = SigningKey::from_bytes(&[2u8; 32]).sign(&amendment_signable_bytes(order, &amendment));
+        amendment
+    }
+
+    /// The dummy owner's answer to `amendment`.
+    fn answer(order: &Order, amendment: &mut OrderAmendment, accepted: bool, acknowledged_at: DateTime<Utc>) {
+        use ed25519_dalek::Signer;
+        let msg = amendment_ack_signable_bytes(order, amendment, accepted, &acknowledged_at);
+        amendment.acknowledgement = Some(crate::order::AmendmentAck {
+            accepted,
+            acknowledged_at,
+            signature: SigningKey::from_bytes(&[1u8; 32]).sign(&msg),
+            extra: Default::default(),
+        });
+    }
+
+    #[test]
+    fn accepted_amendment_changes_terms_after_merge() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let order = dummy_order("o", OrderStatus::Paid);
+        let requested_at = order.created_at + Duration::hours(1);
+        let mut ours = dummy_storefront();
+        ours.orders.insert(order.id.clone(), order.clone());
+
+        // The customer's request arrives; nothing changes until it's answered
+        let mut request = amendment(&order, 3, requested_at);
+        assert_eq!((request.total_price, request.deposit_amount), (300, 30));
+        let mut theirs = dummy_storefront();
+        let mut requested = order.clone();
+        requested.amendments.push(request.clone());
+        assert!(amendments_valid(&requested, &[owner]));
+        theirs.orders.insert(order.id.clone(), requested.clone());
+        ours.merge(theirs.clone());
+        let merged = &ours.orders[&order.id];
+        assert!(merged.pending_amendment().is_some() && !merged.can_amend());
+        assert_eq!((merged.current_quantity(), merged.current_deposit()), (1, 10));
+        assert!(ours.delta(&dummy_storefront().summarize()).orders.contains_key(&order.id));
+
+        // The supplier accepts
+        answer(&order, &mut request, true, requested_at + Duration::hours(1));
+        let mut accepted = order.clone();
+        accepted.amendments.push(request);
+        assert!(amendments_valid(&accepted, &[owner]));
+        let summary = ours.summarize();
+        theirs.orders.insert(order.id.clone(), accepted);
+        assert!(theirs.delta(&summary).orders.contains_key(&order.id));
+        ours.merge(theirs);
+        let merged = &ours.orders[&order.id];
+        assert_eq!((merged.current_quantity(), merged.current_total_price(), merged.current_deposit()), (3, 300, 30));
+        assert!(merged.can_amend());
+    }
+
+    #[test]
+    fn malformed_amendment_chains_are_rejected() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let order = dummy_order("o", OrderStatus::Paid);
+        let requested_at = order.created_at + Duration::hours(1);
+
+        // Deposit not recalculated for the new total
+        let mut cheap = order.clone();
+        let mut request = order.amendment(5, None, requested_at);
+        request.deposit_amount = 10;
+        cheap.amendments.push(request);
+        assert!(!amendments_valid(&cheap, &[owner]));
+
+        // A second request while the first is unanswered
+        let mut eager = order.clone();
+        eager.amendments.push(amendment(&order, 2, requested_at));
+        let mut next = amendment(&eager, 4, requested_at);
+        next.seq = 2;
+        eager.amendments.push(next);
+        assert!(!amendments_valid(&eager, &[owner]));
+
+        // Not signed by the customer
+        if !cfg!(feature = "dev") {
+            let mut forged = order.clone();
+            forged.amendments.push(order.amendment(2, None, requested_at));
+            assert!(!amendments_valid(&forged, &[owner]));
+        }
+
+        // Too late once handover has been proposed
+        let mut ours = dummy_storefront();
+        let mut handed_over = order.clone();
+        handed_over.status = OrderStatus::FulfillmentProposed { proposed_at: requested_at };
+        ours.orders.insert(order.id.clone(), handed_over);
+        let mut theirs = dummy_storefront();
+        let mut late = order.clone();
+        late.amendments.push(amendment(&order, 2, requested_at));
+        theirs.orders.insert(order.id.clone(), late);
+        ours.merge(theirs);
+        assert!(ours.orders[&order.id].amendments.is_empty());
+    }
+
+    #[test]
+    fn merge_keeps_only_participant_messages() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let stranger = SigningKey::from_bytes(&[3u8; 32]);
+        let mut ours = dummy_storefront();
+        ours.orders.insert(OrderId("o".into()), dummy_order("o", OrderStatus::Paid));
+
+        // A delta carrying messages without their order
+        let mut theirs = dummy_storefront();
+        let thread = theirs.threads.entry(OrderId("o".into())).or_default();
+        thread.insert(message(&customer, "o", "m-1", "Ready Saturday?", Utc::now()));
+        thread.insert(message(&owner, "o", "m-2", "Ready Saturday?", Utc::now()));
+        thread.insert(message(&stranger, "o", "m-3", "Ready Saturday?", Utc::now()));
+        theirs
+            .threads
+            .entry(OrderId("unknown".into()))
+            .or_default()
+            .insert(message(&customer, "unknown", "m-4", "Ready Saturday?", Utc::now()));
+        ours.merge(theirs);
+
+        let ids: Vec<_> = ours.threads[&OrderId("o".into())].messages.keys().map(|m| m.0.as_str()).collect();
+        assert_eq!(ids, vec!["m-1", "m-2"]);
+        assert!(!ours.threads.contains_key(&OrderId("unknown".into())));
+
+        // Only messages the summary lacks travel in the delta
+        let mut summary = ours.summarize();
+        summary.thread_messages.get_mut(&OrderId("o".into())).unwrap().remove(&MessageId("m-2".into()));
+        let delta = ours.delta(&summary);
+        let ids: Vec<_> = delta.threads[&OrderId("o".into())].messages.keys().map(|m| m.0.as_str()).collect();
+        assert_eq!(ids, vec!["m-2"]);
+    }
+
+    #[test]
+    fn auto_confirm_requires_window_to_lapse() {
+        let proposed_at = Utc::now() - Duration::hours(80);
+        let owner = dummy_storefront().info.owner.0;
+        let mut order = dummy_order("o", OrderStatus::Fulfilled);
+
+        order.receipt = Some(receipt(&order, proposed_at, proposed_at + Duration::hours(1), true));
+        assert!(!fulfillment_receipt_valid(&order, &[owner]));
+
+        let after = FulfillmentReceipt::auto_confirm_after(proposed_at);
+        order.receipt = Some(receipt(&order, proposed_at, after, true));
+        assert!(fulfillment_receipt_valid(&order, &[owner]));
+
+        // Confirmation can't predate the proposal
+        order.receipt = Some(receipt(&order, proposed_at, proposed_at - Duration::hours(1), false));
+        assert!(!fulfillment_receipt_valid(&order, &[owner]));
+    }
+
+    #[test]
+    fn receipts_answer_the_recorded_proposal() {
+        use crate::order::StatusChange;
+        let proposed_at = Utc::now() - Duration::hours(1);
+        let backdated = proposed_at - Duration::hours(80);
+        let owner = dummy_storefront().info.owner.0;
+        let proposal = OrderStatus::FulfillmentProposed { proposed_at };
+        let mut proposed = dummy_order("o", proposal.clone());
+        proposed.created_at = backdated - Duration::hours(1);
+        proposed.status_history.push(StatusChange::new(proposal, Some(UserId(owner)), proposed_at));
+
+        // Auto-confirmed at once by claiming an older proposal
+        let mut fulfilled = Order { status: OrderStatus::Fulfilled, ..proposed.clone() };
+        let now = Utc::now();
+        fulfilled.receipt = Some(receipt(&fulfilled, backdated, now, true));
+        assert!(!fulfillment_receipt_valid(&fulfilled, &[owner]));
+        assert!(proposed.check_timestamp_progress(&fulfilled).is_err());
+
+        // Confirmed in the future
+        fulfilled.receipt = Some(receipt(&fulfilled, proposed_at, now + Duration::days(4), true));
+        assert!(fulfilled.check_timestamps(now).is_err());
+    }
+
+    fn staff_grant(owner: &SigningKey, staff: &SigningKey, permissions: &[StaffPermission], at: DateTime<Utc>) -> StaffGrant {
+        use ed25519_dalek::Signer;
+        let mut grant = StaffGrant {
+            key: UserId(staff.verifying_key()),
+            name: "Sam".into(),
+            permissions: permissions.iter().copied().collect(),
+            updated_at: at,
+            revoked: false,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        grant.signature = owner.sign(&grant.signable_bytes());
+        grant
+    }
+
+    #[test]
+    fn staff_may_only_make_granted_changes() {
+        use ed25519_dalek::Signer;
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let sam = SigningKey::from_bytes(&[4u8; 32]);
+        let now = Utc::now();
+        let ours = dummy_storefront();
+        let grant = staff_grant(&owner, &sam, &[StaffPermission::Products], now);
+
+        // Sam lists a product, signed and stamped with their own key
+        let mut update = dummy_storefront();
+        update.staff.insert(grant.key.clone(), grant.clone());
+        let mut product = dummy_product(500, now);
+        product.signature = sam.sign(&product.signable_bytes());
+        let msg = WriterStamp::signable_bytes(StateSection::Product, &now, &product.provenance_bytes());
+        product.provenance = Some(WriterStamp {
+            last_writer: sam.verifying_key(),
+            written_at: now,
+            signature: sam.sign(&msg),
+            extra: Default::default(),
+        });
+        update.products.insert(product.product.id.clone(), product.clone());
+        assert!(update.validate(&owner.verifying_key()));
+        assert!(ours.permits(&update, &owner.verifying_key()));
+
+        // ...but may not message customers without the Orders permission
+        let order = dummy_order("o-1", OrderStatus::Paid);
+        let mut chatty = ours.clone();
+        chatty.orders.insert(order.id.clone(), order.clone());
+        let mut reply = chatty.clone();
+        reply.staff.insert(grant.key.clone(), grant.clone());
+        reply
+            .threads
+            .entry(order.id.clone())
+            .or_default()
+            .insert(message(&sam, "o-1", "m-1", "Ready Saturday?", Utc::now()));
+        assert!(!chatty.permits(&reply, &owner.verifying_key()));
+
+        // Once revoked, Sam's listing still verifies but new ones are refused
+        let revoked = StaffGrant { revoked: true, updated_at: now + Duration::days(1), ..grant.clone() };
+        let revoked = StaffGrant { signature: owner.sign(&revoked.signable_bytes()), ..revoked };
+        let mut merged = ours.clone();
+        merged.merge(update.clone());
+        merged.staff.insert(revoked.key.clone(), revoked);
+        assert!(merged.validate(&owner.verifying_key()));
+        let mut restock = update.clone();
+        restock.products.get_mut(&product.product.id).unwrap().product.updated_at = now + Duration::days(2);
+        assert!(!merged.permits(&restock, &owner.verifying_key()));
+
+        // Only the owner can hand out grants
+        if !cfg!(feature = "dev") {
+            let mut forged = update.clone();
+            forged.staff.insert(grant.key.clone(), staff_grant(&sam, &sam, &StaffPermission::ALL, now));
+            assert!(!forged.validate(&owner.verifying_key()));
+        }
+    }
+
+    #[test]
+    fn new_orders_carry_the_cancellation_policy_in_force() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let policy = CancellationPolicy { full_refund_hours: 24, late_penalty_percent: 50 };
+        let mut ours = dummy_storefront();
+        ours.info.cancellation_policy = Some(policy);
+
+        let order = dummy_order("o-1", OrderStatus::Reserved { expires_at: Utc::now() + Duration::days(2) });
+        let mut dodged = dummy_storefront();
+        dodged.orders.insert(order.id.clone(), order.clone());
+        assert!(!ours.permits(&dodged, &owner));
+
+        let mut agreed = dummy_storefront();
+        agreed.orders.insert(order.id.clone(), Order { cancellation_policy: Some(policy), ..order.clone() });
+        assert!(ours.permits(&agreed, &owner));
+
+        // Dropping the policy takes newer info stamped by the owner
+        dodged.info.seq = 1;
+        assert!(!ours.permits(&dodged, &owner));
+        dodged.info.provenance = Some(stamp(StateSection::StorefrontInfo, &dodged.info.provenance_bytes(), Utc::now()));
+        assert!(ours.permits(&dodged, &owner));
+
+        // A penalty over 100% is no policy at all
+        ours.info.cancellation_policy = Some(CancellationPolicy { late_penalty_percent: 101, ..policy });
+        assert!(!ours.validate(&owner));
+    }
+
+    #[test]
+    fn large_orders_need_a_balance_attestation() {
+        use crate::attestation::{BalanceAttestation, MAX_ATTESTATION_AGE_MINUTES};
+        use ed25519_dalek::Signer;
+
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let mut ours = dummy_storefront();
+        ours.info.attestation_threshold = Some(50);
+
+        let order = dummy_order("o-1", OrderStatus::Reserved { expires_at: Utc::now() + Duration::days(2) });
+        let attest = |balance: u64, at: DateTime<Utc>| {
+            let mut attestation = BalanceAttestation {
+                user: order.customer.clone(),
+                balance,
+                ledger_entries: 3,
+                attested_at: at,
+                signature: Signature::from_bytes(&[0u8; 64]),
+                extra: Default::default(),
+            };
+            attestation.signature = customer.sign(&attestation.signable_bytes());
+            attestation
+        };
+        let place = |ours: &StorefrontState, attestation: Option<BalanceAttestation>| {
+            let mut update = ours.clone();
+            let order = Order { balance_attestation: attestation, ..order.clone() };
+            update.orders.insert(order.id.clone(), order);
+            update
+        };
+
+        assert!(!ours.permits(&place(&ours, None), &owner));
+        // The balance must cover the deposit, and be recent
+        assert!(!ours.permits(&place(&ours, Some(attest(9, order.created_at))), &owner));
+        let stale = order.created_at - Duration::minutes(MAX_ATTESTATION_AGE_MINUTES + 1);
+        assert!(!ours.permits(&place(&ours, Some(attest(10, stale))), &owner));
+        let fresh = place(&ours, Some(attest(10, order.created_at - Duration::minutes(5))));
+        assert!(ours.permits(&fresh, &owner));
+        // ... and be the customer's own
+        let mut theirs = attest(10, order.created_at);
+        theirs.user = UserId(owner);
+        assert!(!ours.permits(&place(&ours, Some(theirs)), &owner));
+
+        // Orders at or under the threshold need none
+        ours.info.attestation_threshold = Some(100);
+        assert!(ours.permits(&place(&ours, None), &owner));
+    }
+
+    #[test]
+    fn low_stock_listings_pause_and_refuse_new_orders() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let t0 = Utc::now() - Duration::days(1);
+        let mut ours = dummy_storefront();
+        let mut listing = dummy_product(100, t0);
+        listing.product.low_stock = Some(LowStockRule { threshold: 9, auto_pause: true });
+        ours.products.insert(listing.product.id.clone(), listing.clone());
+        ours.orders.insert(OrderId("o-1".into()), dummy_order("o-1", OrderStatus::Paid));
+        assert!(ours.low_stock().is_empty());
+
+        ours.orders.insert(OrderId("o-2".into()), dummy_order("o-2", OrderStatus::Paid));
+        assert_eq!(ours.low_stock().len(), 1);
+        assert_eq!(ours.products_to_pause(), vec![ProductId("p-1".into())]);
+
+        let mut paused = listing.clone();
+        paused.product.paused = true;
+        paused.product.updated_at = t0 + Duration::hours(1);
+        ours.products.insert(paused.product.id.clone(), paused.clone());
+        assert!(ours.products_to_pause().is_empty());
+
+        let order = dummy_order("o-3", OrderStatus::Reserved { expires_at: Utc::now() + Duration::days(2) });
+        let mut update = dummy_storefront();
+        update.orders.insert(order.id.clone(), order);
+        assert!(!ours.permits(&update, &owner));
+
+        // Resuming in the same update as the order lets it through
+        let mut resumed = paused;
+        resumed.product.paused = false;
+        resumed.product.updated_at = t0 + Duration::hours(2);
+        update.products.insert(resumed.product.id.clone(), resumed.clone());
+        assert!(ours.permits(&update, &owner));
+
+        // Resumed after the orders that made it low: stays open until the next one
+        resumed.product.updated_at = Utc::now() + Duration::seconds(1);
+        ours.products.insert(resumed.product.id.clone(), resumed);
+        assert_eq!(ours.low_stock().len(), 1);
+        assert!(ours.products_to_pause().is_empty());
+    }
+
+    #[test]
+    fn blocked_customers_may_not_order_or_message() {
+        use ed25519_dalek::Signer;
+        let owner = SigningKey::from_bytes(&[1u8; 32]);
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let now = Utc::now();
+        let mut ours = dummy_storefront();
+        let earlier = dummy_order("o-1", OrderStatus::Paid);
+        ours.orders.insert(earlier.id.clone(), earlier.clone());
+
+        let mut entry = BlockEntry {
+            customer: UserId(customer.verifying_key()),
+            blocked: true,
+            appeal_note: "Call the farm to talk it over".into(),
+            updated_at: now,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        entry.signature = owner.sign(&entry.signable_bytes());
+        let mut block = dummy_storefront();
+        block.blocklist.insert(entry.customer.clone(), entry.clone());
+        assert!(block.validate(&owner.verifying_key()));
+        assert!(ours.permits(&block, &owner.verifying_key()));
+        ours.merge(block);
+
+        let order = dummy_order("o-2", OrderStatus::Reserved { expires_at: now + Duration::days(2) });
+        let mut update = dummy_storefront();
+        update.orders.insert(order.id.clone(), order);
+        assert!(!ours.permits(&update, &owner.verifying_key()));
+
+        let mut chat = dummy_storefront();
+        chat.threads
+            .entry(earlier.id.clone())
+            .or_default()
+            .insert(message(&customer, "o-1", "m-1", "Ready Saturday?", Utc::now()));
+        assert!(!ours.permits(&chat, &owner.verifying_key()));
+
+        // Their earlier order still moves along
+        let mut cancelled = dummy_storefront();
+        cancelled.orders.insert(earlier.id.clone(), Order { status: OrderStatus::Cancelled, ..earlier });
+        assert!(ours.permits(&cancelled, &owner.verifying_key()));
+
+        // Lifting the block lets them order again
+        let mut lifted = BlockEntry { blocked: false, updated_at: now + Duration::days(1), ..entry };
+        lifted.signature = owner.sign(&lifted.signable_bytes());
+        ours.blocklist.insert(lifted.customer.clone(), lifted);
+        assert!(ours.permits(&update, &owner.verifying_key()));
+
+        // Only the owner can block
+        if !cfg!(feature = "dev") {
+            let mut forged = dummy_storefront();
+            let lifted = ours.blocklist.values().next().unwrap().clone();
+            let mut entry = BlockEntry { blocked: true, updated_at: now + Duration::days(2), ..lifted };
+            entry.signature = customer.sign(&entry.signable_bytes());
+            forged.blocklist.insert(entry.customer.clone(), entry);
+            assert!(!forged.validate(&owner.verifying_key()));
+        }
+    }
+
+    fn placed(id: &str, status: OrderStatus, created_at: DateTime<Utc>) -> (OrderId, Order) {
+        (OrderId(id.into()), Order { created_at, ..dummy_order(id, status) })
+    }
+
+    #[test]
+    fn compaction_folds_only_settled_old_months() {
+        let at = |month, day| Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap();
+        let mut sf = dummy_storefront();
+        sf.orders.extend([
+            placed("jan-1", OrderStatus::Fulfilled, at(1, 5)),
+            placed("jan-2", OrderStatus::Cancelled, at(1, 20)),
+            placed("feb-1", OrderStatus::Fulfilled, at(2, 3)),
+            placed("feb-2", OrderStatus::Reserved { expires_at: at(2, 5) }, at(2, 4)),
+            placed("apr-1", OrderStatus::Fulfilled, at(4, 2)),
+        ]);
+        sf.threads.insert(OrderId("jan-1".into()), OrderThread::default());
+
+        // January ended 90 days before May 2nd; April hasn't aged enough
+        assert!(sf.compact_orders(at(5, 2)));
+        assert_eq!(sf.order_history.get("2026-01").map(totals), Some((2, 1, 100)));
+        // February still has an open order, so it stays whole
+        assert!(!sf.order_history.contains_key("2026-02"));
+        let left: Vec<_> = sf.orders.keys().map(|id| id.0.as_str()).collect();
+        assert_eq!(left, ["apr-1", "feb-1", "feb-2"]);
+        assert!(sf.threads.is_empty());
+        assert!(!sf.compact_orders(at(5, 2)));
+    }
+
+    fn totals(stats: &MonthlyOrderStats) -> (u32, u32, u64) {
+        (stats.orders, stats.fulfilled, stats.revenue_curd)
+    }
+
+    #[test]
+    fn replicas_compacting_at_different_times_converge() {
+        let at = |month, day| Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap();
+        let jan = [
+            placed("jan-1", OrderStatus::Fulfilled, at(1, 5)),
+            placed("jan-2", OrderStatus::Fulfilled, at(1, 9)),
+            placed("jan-3", OrderStatus::Expired, at(1, 14)),
+        ];
+        let june = dummy_product(500, at(6, 1));
+
+        // Peer A saw two January orders and compacted them once its clock
+        // (a product listed in June) passed the retention window
+        let mut a = dummy_storefront();
+        a.orders.extend(jan[..2].iter().cloned());
+        a.products.insert(ProductId("p-1".into()), june.clone());
+        a.compact_orders(a.activity_clock().unwrap());
+        assert!(a.orders.is_empty());
+        assert_eq!(a.order_history["2026-01"].orders, 2);
+
+        // Peer B knows all three and hasn't compacted yet
+        let mut b = dummy_storefront();
+        b.orders.extend(jan.iter().cloned());
+        b.orders.extend([placed("may-1", OrderStatus::Fulfilled, at(5, 20))]);
+
+        let mut a_then_b = a.clone();
+        a_then_b.merge(b.clone());
+        let mut b_then_a = b.clone();
+        b_then_a.merge(a.clone());
+        assert_eq!(a_then_b, b_then_a);
+        assert_eq!(totals(&b_then_a.order_history["2026-01"]), (3, 2, 200));
+        assert_eq!(b_then_a.orders.keys().map(|id| id.0.as_str()).collect::<Vec<_>>(), ["may-1"]);
+
+        // A stale relay of a compacted order is folded straight back in
+        let mut relayed = b_then_a.clone();
+        relayed.merge(b.delta(&StorefrontSummary::default()));
+        assert_eq!(relayed, b_then_a);
+
+        // And a peer that has compacted doesn't ask for January's orders again
+        let delta = b.delta(&b_then_a.summarize());
+        assert!(delta.orders.keys().all(|id| id.0 == "may-1"));
+
+        // Peer C compacted a different pair; neither A's nor C's total
+        // stands alone, the orders both folded are counted once
+        let mut c = dummy_storefront();
+        c.orders.extend(jan[1..].iter().cloned());
+        c.products.insert(ProductId("p-1".into()), june);
+        c.compact_orders(c.activity_clock().unwrap());
+        assert_eq!(totals(&c.order_history["2026-01"]), (2, 1, 100));
+        let mut a_then_c = a.clone();
+        a_then_c.merge(c.clone());
+        let mut c_then_a = c.clone();
+        c_then_a.merge(a.clone());
+        assert_eq!(a_then_c, c_then_a);
+        assert_eq!(a_then_c.order_history["2026-01"], b_then_a.order_history["2026-01"]);
+    }
+
+    #[test]
+    fn order_history_totals_count_the_folded_orders() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let at = |month, day| Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap();
+        let mut ours = dummy_storefront();
+        ours.orders.extend([placed("jan-1", OrderStatus::Fulfilled, at(1, 5))]);
+        ours.compact_orders(at(6, 1));
+        assert!(ours.validate(&owner));
+
+        // A relay can't inflate a month by rewriting its totals...
+        let mut inflated = ours.clone();
+        let stats = inflated.order_history.get_mut("2026-01").unwrap();
+        stats.orders = u32::MAX;
+        stats.revenue_curd = u64::MAX;
+        assert!(!inflated.validate(&owner));
+
+        // ...and a merge recounts them from the orders folded in
+        ours.merge(inflated);
+        assert_eq!(totals(&ours.order_history["2026-01"]), (1, 1, 100));
+    }
+
+    fn dummy_product(price_curd: u64, updated_at: DateTime<Utc>) -> SignedProduct {
+        SignedProduct {
+            product: Product {
+                id: ProductId("p-1".into()),
+                name: "Milk".into(),
+                description: "".into(),
+                category: crate::product::ProductCategory::Milk,
+                subcategory: None,
+                attributes: Default::default(),
+                price_curd,
+                quantity_total: 10,
+                expiry_date: None,
+                updated_at,
+                created_at: updated_at,
+                low_stock: None,
+                paused: false,
+                special: None,
+                wholesale: None,
+                extra: Default::default(),
+            },
+            signature: Signature::from_bytes(&[0u8; 64]),
+            provenance: None,
+            price_history: Vec::new(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn writers_record_price_changes_and_merges_keep_them() {
+        let t0 = Utc::now() - Duration::days(3);
+        let (t1, t2, t3) = (t0 + Duration::days(1), t0 + Duration::days(2), t0 + Duration::days(3));
+        let mut ours = dummy_storefront();
+        ours.products.insert(ProductId("p-1".into()), dummy_product(500, t0));
+
+        for (price, at) in [(600, t1), (600, t2), (450, t3)] {
+            let mut written = dummy_product(price, at);
+            written.merge_price_history(&ours.products[&ProductId("p-1".into())]);
+            let mut theirs = dummy_storefront();
+            theirs.products.insert(ProductId("p-1".into()), written);
+            ours.merge(theirs);
+        }
+
+        let sp = &ours.products[&ProductId("p-1".into())];
+        assert_eq!(sp.product.price_curd, 450);
+        // Quantity-only updates (same price) don't add points
+        assert_eq!(sp.price_history, vec![(t0, 500), (t1, 600), (t3, 450)]);
+        assert_eq!(sp.previous_price(), Some(600));
+
+        // A stale replica merging in takes the writer's history
+        let mut stale = dummy_storefront();
+        stale.products.insert(ProductId("p-1".into()), dummy_product(500, t0));
+        stale.merge(ours.clone());
+        assert_eq!(stale.products[&ProductId("p-1".into())].price_history, sp.price_history);
+
+        // ...and merging adds no points of its own: a relay's older version
+        // with extra history changes nothing
+        let mut relayed = dummy_product(300, t0 - Duration::days(1));
+        relayed.price_history = vec![(t0 - Duration::days(2), 1), (t0 - Duration::days(1), 300)];
+        let mut theirs = dummy_storefront();
+        theirs.products.insert(ProductId("p-1".into()), relayed);
+        stale.merge(theirs);
+        assert_eq!(stale.products[&ProductId("p-1".into())].price_history, sp.price_history);
+    }
+
+    #[test]
+    fn price_history_is_capped() {
+        let t0 = Utc::now() - Duration::days(100);
+        let mut sp = dummy_product(0, t0);
+        for i in 1..=(PRICE_HISTORY_CAP as i64 + 10) {
+            let before = sp.clone();
+            sp.product.price_curd = i as u64;
+            sp.product.updated_at = t0 + Duration::hours(i);
+            sp.merge_price_history(&before);
+        }
+        assert_eq!(sp.price_history.len(), PRICE_HISTORY_CAP);
+        assert_eq!(sp.price_history.last().map(|p| p.1), Some(sp.product.price_curd));
+        assert_eq!(sp.price_points().len(), PRICE_HISTORY_CAP);
+    }
+
+    #[test]
+    fn price_history_must_agree_with_the_signed_listing() {
+        use ed25519_dalek::Signer;
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let owner = key.verifying_key();
+        let t0 = Utc::now() - Duration::days(3);
+        let t1 = t0 + Duration::days(1);
+        let stamp = |sp: &SignedProduct| {
+            let msg = WriterStamp::signable_bytes(StateSection::Product, &t1, &sp.provenance_bytes());
+            WriterStamp { last_writer: owner, written_at: t1, signature: key.sign(&msg), extra: Default::default() }
+        };
+        let mut sf = dummy_storefront();
+        let mut sp = dummy_product(500, t1);
+        sp.signature = key.sign(&sp.signable_bytes());
+        sp.price_history = vec![(t0, 400), (t1, 500)];
+        // Nothing vouches for the earlier point until the writer stamps it
+        assert!(!sp.price_history_consistent());
+        sp.provenance = Some(stamp(&sp));
+        assert!(sp.price_history_consistent());
+        assert!(sp.verify_provenance(&[owner]));
+        sf.products.insert(ProductId("p-1".into()), sp.clone());
+        assert!(sf.validate(&owner));
+
+        // A relay can't date a point after the signed listing...
+        let mut ahead = sf.clone();
+        ahead.products.get_mut(&ProductId("p-1".into())).unwrap().price_history.push((t1 + Duration::hours(1), 1));
+        assert!(!ahead.validate(&owner));
+
+        // ...nor contradict its signed price
+        let mut contradicted = sf.clone();
+        contradicted.products.get_mut(&ProductId("p-1".into())).unwrap().price_history[1] = (t1, 1);
+        assert!(!contradicted.validate(&owner));
+
+        // Merging drops such points rather than adopting them
+        let mut forged = sp.clone();
+        forged.price_history = vec![(t0, 400), (t1, 1), (t1 + Duration::hours(1), 1)];
+        let mut merged = sp.clone();
+        merged.merge_price_history(&forged);
+        assert_eq!(merged.price_history, vec![(t0, 400), (t1, 500)]);
+        assert!(merged.price_history_consistent());
+
+        // An unstamped listing carries only its signed price
+        let mut unstamped = sp.clone();
+        unstamped.provenance = None;
+        unstamped.price_history = vec![(t1, 500)];
+        assert!(unstamped.price_history_consistent());
+        unstamped.price_history.insert(0, (t0, 400));
+        assert!(!unstamped.price_history_consistent());
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn price_history_is_covered_by_the_writer_stamp() {
+        use ed25519_dalek::Signer;
+        let key = SigningKey::from_bytes(&[1u8; 32]);
+        let owner = key.verifying_key();
+        let t0 = Utc::now() - Duration::days(3);
+        let t1 = t0 + Duration::days(1);
+        let stamp = |content: &[u8]| {
+            let msg = WriterStamp::signable_bytes(StateSection::Product, &t1, content);
+            WriterStamp { last_writer: owner, written_at: t1, signature: key.sign(&msg), extra: Default::default() }
+        };
+        let mut sp = dummy_product(500, t1);
+        sp.signature = key.sign(&sp.signable_bytes());
+        sp.price_history = vec![(t0, 400), (t1, 500)];
+        sp.provenance = Some(stamp(&sp.provenance_bytes()));
+        let mut sf = dummy_storefront();
+        sf.products.insert(ProductId("p-1".into()), sp.clone());
+        assert!(sf.validate(&owner));
+
+        // A relay can't slip in an earlier point the writer didn't stamp
+        let mut padded = sf.clone();
+        padded.products.get_mut(&ProductId("p-1".into())).unwrap().price_history.insert(0, (t0 - Duration::days(1), 1));
+        assert!(!padded.validate(&owner));
+
+        // A stamp over the listing alone covers only the signed price
+        let mut listing_only = sp.clone();
+        listing_only.provenance = Some(stamp(&listing_only.signable_bytes()));
+        assert!(!listing_only.verify_provenance(&[owner]));
+        listing_only.price_history = vec![(t1, 500)];
+        assert!(listing_only.verify_provenance(&[owner]));
+    }
+
+    #[test]
+    fn new_orders_are_locked_at_the_price_when_placed() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let t0 = Utc::now() - Duration::days(2);
+        let t1 = t0 + Duration::days(1);
+        let mut ours = dummy_storefront();
+        ours.products.insert(ProductId("p-1".into()), dummy_product(100, t0));
+        let mut written = dummy_product(150, t1);
+        written.merge_price_history(&ours.products[&ProductId("p-1".into())]);
+        let mut repriced = dummy_storefront();
+        repriced.products.insert(ProductId("p-1".into()), written);
+        ours.merge(repriced);
+        let sp = &ours.products[&ProductId("p-1".into())];
+        assert_eq!(sp.price_at(t0 - Duration::hours(1)), None);
+        assert_eq!(sp.price_at(t0 + Duration::hours(1)), Some(100));
+        assert_eq!(sp.price_at(t1 + Duration::hours(1)), Some(150));
+
+        let placed = |price: u64, at| {
+            let mut order = dummy_order("o-1", OrderStatus::Paid);
+            order.quantity = 2;
+            order.total_price = price * 2;
+            order.created_at = at;
+            let mut update = dummy_storefront();
+            update.orders.insert(order.id.clone(), order);
+            update
+        };
+        // Placed before the price rise, at the old price
+        assert!(ours.permits(&placed(100, t0 + Duration::hours(1)), &owner));
+        // Placed after it, at the old price
+        assert!(!ours.permits(&placed(100, t1 + Duration::hours(1)), &owner));
+        assert!(ours.permits(&placed(150, t1 + Duration::hours(1)), &owner));
+        // Older than the history we hold: can't tell, so allowed
+        assert!(ours.permits(&placed(80, t0 - Duration::hours(1)), &owner));
+
+        // An order already held isn't re-checked, but shows the change
+        let old = placed(100, t0 + Duration::hours(1));
+        ours.merge(old.clone());
+        assert!(ours.permits(&old, &owner));
+        assert_eq!(ours.price_changed_since(&ours.orders[&OrderId("o-1".into())]), Some(150));
+    }
+
+    #[test]
+    fn orders_placed_during_a_special_pay_its_price() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let t0 = Utc::now() - Duration::days(3);
+        let mut listing = dummy_product(100, t0);
+        let special = Special { price_curd: 80, starts_at: t0 + Duration::days(1), ends_at: t0 + Duration::days(2) };
+        listing.product.special = Some(special);
+        let mut ours = dummy_storefront();
+        ours.products.insert(listing.product.id.clone(), listing);
+
+        let placed = |price: u64, at| {
+            let mut order = dummy_order("o-1", OrderStatus::Paid);
+            order.total_price = price;
+            order.created_at = at;
+            let mut update = dummy_storefront();
+            update.orders.insert(order.id.clone(), order);
+            update
+        };
+        let during = special.starts_at + Duration::hours(1);
+        assert!(ours.permits(&placed(80, during), &owner));
+        assert!(!ours.permits(&placed(100, during), &owner));
+        assert!(!ours.permits(&placed(80, special.ends_at), &owner));
+        assert!(ours.permits(&placed(100, special.ends_at), &owner));
+
+        // ... but once it has ended, an order can't be backdated into it
+        let now = special.ends_at + Duration::hours(1);
+        assert!(ours.check_orders_recent(&placed(80, now), now).is_ok());
+        assert!(matches!(ours.check_orders_recent(&placed(80, during), now), Err(TimestampError::Stale { .. })));
+
+        // Held orders from the special aren't flagged as repriced
+        ours.merge(placed(80, during));
+        assert_eq!(ours.price_changed_since(&ours.orders[&OrderId("o-1".into())]), None);
+
+        // A "special" dearer than the listing isn't one
+        let sp = ours.products.get_mut(&ProductId("p-1".into())).unwrap();
+        sp.product.special = Some(Special { price_curd: 120, ..special });
+        assert!(!ours.validate(&owner));
+    }
+
+    #[test]
+    fn wholesale_orders_meet_the_minimum_at_the_tier_price() {
+        use crate::product::{PriceTier, WholesaleTerms};
+
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let t0 = Utc::now() - Duration::days(1);
+        let mut listing = dummy_product(100, t0);
+        listing.product.wholesale = Some(WholesaleTerms {
+            min_quantity: 10,
+            tiers: vec![PriceTier { min_quantity: 50, price_curd: 80 }],
+        });
+        let mut ours = dummy_storefront();
+        ours.products.insert(listing.product.id.clone(), listing);
+
+        let placed = |quantity: u32, total_price: u64| {
+            let mut order = dummy_order("o-1", OrderStatus::Paid);
+            order.quantity = quantity;
+            order.total_price = total_price;
+            let mut update = dummy_storefront();
+            update.orders.insert(order.id.clone(), order);
+            update
+        };
+        assert!(!ours.permits(&placed(5, 500), &owner));
+        assert!(ours.permits(&placed(10, 1_000), &owner));
+        assert!(ours.permits(&placed(50, 4_000), &owner));
+        assert!(!ours.permits(&placed(50, 5_000), &owner));
+
+        // Held tier-priced orders aren't flagged as repriced
+        ours.merge(placed(50, 4_000));
+        assert_eq!(ours.price_changed_since(&ours.orders[&OrderId("o-1".into())]), None);
+
+        // Tiers must get cheaper
+        let sp = ours.products.get_mut(&ProductId("p-1".into())).unwrap();
+        sp.product.wholesale = Some(WholesaleTerms {
+            min_quantity: 10,
+            tiers: vec![PriceTier { min_quantity: 50, price_curd: 120 }],
+        });
+        assert!(!ours.validate(&owner));
+    }
+
+    #[test]
+    fn order_ids_must_name_their_content() {
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let mut order = dummy_order("o", OrderStatus::Paid);
+        order.nonce = 42;
+        order.id = OrderId::derive(&order.customer, &order.product_id, order.created_at, order.nonce);
+        let mut sf = dummy_storefront();
+        sf.orders.insert(order.id.clone(), order.clone());
+        assert!(order.id_matches_content());
+
+        // Someone else's order can't be filed under this one's id
+        let mut spoofed = sf.clone();
+        let other = UserId(SigningKey::from_bytes(&[9u8; 32]).verifying_key());
+        spoofed.orders.insert(order.id.clone(), Order { customer: other.clone(), ..order.clone() });
+        assert!(!spoofed.validate(&owner));
+
+        // Nor is an order kept under another id
+        let mut misfiled = dummy_storefront();
+        misfiled.orders.insert(OrderId("o-elsewhere".into()), order.clone());
+        assert!(!misfiled.validate(&owner));
+
+        // A colliding legacy id doesn't overwrite the order already held
+        let mut ours = dummy_storefront();
+        ours.orders.insert(OrderId("o".into()), dummy_order("o", OrderStatus::Paid));
+        let mut theirs = dummy_storefront();
+        theirs.orders.insert(
+            OrderId("o".into()),
+            Order { customer: other, ..dummy_order("o", OrderStatus::Cancelled) },
+        );
+        ours.merge(theirs);
+        assert_eq!(ours.orders[&OrderId("o".into())].status, OrderStatus::Paid);
+    }
+
+    #[test]
+    fn info_provenance_bytes_ignore_stamp() {
+        let mut sf = dummy_storefront();
+        let before = sf.info.provenance_bytes();
+        sf.info.provenance = Some(stamp(StateSection::StorefrontInfo, &before, Utc::now()));
+        assert_eq!(sf.info.provenance_bytes(), before);
+        assert!(sf.info.verify_provenance(&[sf.info.owner.0]));
+    }
+
     #[test]
     fn unknown_fields_preserved_on_round_trip() {
         // Simulate a future version writing a new field into StorefrontInfo
@@ -730,6 +3267,13 @@ mod tests {
             email: None,
             address: None,
             market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
             extra: Default::default(),
         };
 
@@ -751,4 +3295,108 @@ mod tests {
             &serde_json::json!("hello from v2")
         );
     }
+
+    #[test]
+    fn status_history_only_grows_forward() {
+        use crate::order::StatusChange;
+        let owner = SigningKey::from_bytes(&[1u8; 32]).verifying_key();
+        let placed_at = Utc::now() - Duration::days(1);
+        let mut order = dummy_order("o-1", OrderStatus::Paid);
+        order.created_at = placed_at;
+        order.status_history.push(StatusChange::new(OrderStatus::Paid, Some(order.customer.clone()), placed_at));
+        assert!(order.status_history_valid());
+        let mut ours = dummy_storefront();
+        ours.orders.insert(order.id.clone(), order.clone());
+
+        // The supplier hands it over
+        let proposed_at = placed_at + Duration::hours(2);
+        let mut proposed = order.clone();
+        proposed.set_status(OrderStatus::FulfillmentProposed { proposed_at }, Some(UserId(owner)), proposed_at);
+        proposed.provenance = Some(stamp(StateSection::Order, &order_provenance_bytes(&proposed), proposed_at));
+        assert!(proposed.status_history_valid());
+        let mut update = dummy_storefront();
+        update.orders.insert(proposed.id.clone(), proposed.clone());
+        assert!(ours.permits(&update, &owner));
+
+        // Backdated before the change it follows
+        let mut backdated = proposed.clone();
+        backdated.status_history[1].at = placed_at - Duration::hours(1);
+        assert!(!backdated.status_history_valid());
+
+        // Recorded as the customer's doing by the supplier
+        let mut misattributed = proposed.clone();
+        misattributed.status_history[1].actor = Some(order.customer.clone());
+        let mut update = dummy_storefront();
+        update.orders.insert(misattributed.id.clone(), misattributed);
+        assert!(!ours.permits(&update, &owner));
+
+        // Rewriting the placement
+        let mut rewritten = proposed.clone();
+        rewritten.status_history.remove(0);
+        let mut update = dummy_storefront();
+        update.orders.insert(rewritten.id.clone(), rewritten);
+        assert!(!ours.permits(&update, &owner));
+
+        // Recorded beyond the order's status
+        let mut ahead = order.clone();
+        ahead.status_history.push(StatusChange::new(OrderStatus::Cancelled, None, proposed_at));
+        assert!(!ahead.status_history_valid());
+    }
+
+    #[test]
+    fn timestamps_are_checked_against_the_host_clock() {
+        let now = Utc::now();
+        let mut update = dummy_storefront();
+        update.products.insert(ProductId("p-1".into()), dummy_product(100, now - Duration::hours(1)));
+        let order = dummy_order("o-1", OrderStatus::Reserved { expires_at: now + Duration::days(2) });
+        update.orders.insert(order.id.clone(), order.clone());
+        assert_eq!(update.check_timestamps(now), Ok(()));
+
+        // A product dated next year would win every merge
+        let mut ahead = update.clone();
+        ahead.products.get_mut(&ProductId("p-1".into())).unwrap().product.updated_at = now + Duration::days(365);
+        assert!(matches!(ahead.check_timestamps(now), Err(TimestampError::InFuture { .. })));
+
+        // A 2-day hold that never lapses
+        let mut forever = order.clone();
+        forever.status = OrderStatus::Reserved { expires_at: now + Duration::days(3650) };
+        let mut held = update.clone();
+        held.orders.insert(forever.id.clone(), forever.clone());
+        assert!(matches!(held.check_timestamps(now), Err(TimestampError::TooLong { .. })));
+
+        // Extending a reservation already placed
+        let mut ours = dummy_storefront();
+        ours.orders.insert(order.id.clone(), order.clone());
+        let mut extended = order.clone();
+        extended.status = OrderStatus::Reserved { expires_at: now + Duration::days(2) + Duration::minutes(5) };
+        let mut update = dummy_storefront();
+        update.orders.insert(extended.id.clone(), extended);
+        assert!(update.check_timestamps(now).is_ok());
+        assert!(matches!(ours.check_timestamp_progress(&update), Err(TimestampError::Changed { .. })));
+
+        // Paying leaves the timestamps alone
+        let mut paid = order.clone();
+        paid.set_status(OrderStatus::Paid, Some(order.customer.clone()), now);
+        let mut update = dummy_storefront();
+        update.orders.insert(paid.id.clone(), paid);
+        assert_eq!(ours.check_timestamp_progress(&update), Ok(()));
+    }
+
+    #[test]
+    fn orders_signed_under_either_scheme_verify() {
+        use ed25519_dalek::Signer;
+        let customer = SigningKey::from_bytes(&[2u8; 32]);
+        let mut order = dummy_order("o-1", OrderStatus::Paid);
+
+        order.signature = customer.sign(&order_signable_bytes(&order));
+        assert!(order_signed_by_customer(&order));
+
+        // Placed before domain separation
+        order.signature = customer.sign(&order_json(&order));
+        assert!(order_signed_by_customer(&order));
+
+        // A listing signature is no good on an order
+        order.signature = customer.sign(&product_signable_bytes(&dummy_product(100, order.created_at).product));
+        assert!(!order_signed_by_customer(&order));
+    }
 }
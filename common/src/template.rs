@@ -0,0 +1,208 @@
+//! Storefront templates for onboarding new suppliers.
+//!
+//! Members of a cooperative tend to sell the same range on the same days. A
+//! [`StorefrontTemplate`] is the reusable shape of a storefront — its
+//! categories, opening schedule and standard products — without stock, ids,
+//! orders or signatures. An established supplier exports one; a new supplier
+//! picks it at registration and starts with a ready-made storefront whose
+//! products they sign as their own.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::catalog::{plan_import, ImportError, ProductRecord};
+use crate::limits::MAX_NAME_LEN;
+use crate::product::Product;
+use crate::storefront::{StorefrontState, WeeklySchedule};
+
+/// The reusable structure of a storefront.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorefrontTemplate {
+    /// Where the template came from, e.g. the exporting storefront's name.
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<WeeklySchedule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Category labels the storefront sells, sorted.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Standard products, without ids and with no stock.
+    #[serde(default)]
+    pub products: Vec<ProductRecord>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl StorefrontTemplate {
+    /// The template of an existing storefront: its schedule, timezone and
+    /// products (by name), with ids and quantities cleared.
+    pub fn from_storefront(sf: &StorefrontState) -> Self {
+        let mut products: Vec<ProductRecord> = sf
+            .products
+            .values()
+            .map(|signed| ProductRecord {
+                id: None,
+                quantity_total: 0,
+                ..ProductRecord::from_product(&signed.product)
+            })
+            .collect();
+        products.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.name.cmp(&b.name)));
+        let mut categories: Vec<String> = products.iter().map(|p| p.category.clone()).collect();
+        categories.dedup();
+        StorefrontTemplate {
+            name: sf.info.name.clone(),
+            schedule: sf.info.schedule.clone(),
+            timezone: sf.info.timezone.clone(),
+            categories,
+            products,
+            extra: Default::default(),
+        }
+    }
+
+    /// Pretty-printed JSON, the template file format.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("serialization should not fail")
+    }
+
+    /// Read a template file. Every product is checked as an import row
+    /// would be; `row` in the errors counts products from 1.
+    pub fn from_json(text: &str) -> Result<Self, Vec<ImportError>> {
+        let file_error = |reason: String| vec![ImportError { row: 0, reason }];
+        let template: StorefrontTemplate = serde_json::from_str(text.trim_start_matches('\u{feff}'))
+            .map_err(|e| file_error(format!("not a storefront template: {e}")))?;
+        if template.name.len() > MAX_NAME_LEN {
+            return Err(file_error(format!(
+                "template name is {} bytes (max {MAX_NAME_LEN})",
+                template.name.len()
+            )));
+        }
+        // Only the rows are checked; the stamp doesn't matter (and contract
+        // builds have no clock)
+        template.instantiate(DateTime::UNIX_EPOCH)?;
+        Ok(template)
+    }
+
+    /// The template's products as new, unsigned products of a fresh
+    /// storefront, stamped `now`. Quantities come from the template (zero
+    /// for exported templates); the supplier stocks them afterwards.
+    pub fn instantiate(&self, now: DateTime<Utc>) -> Result<Vec<Product>, Vec<ImportError>> {
+        let rows: Vec<(usize, ProductRecord)> = self
+            .products
+            .iter()
+            .enumerate()
+            .map(|(i, record)| (i + 1, ProductRecord { id: None, ..record.clone() }))
+            .collect();
+        plan_import(&rows, &BTreeMap::new(), now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::UserId;
+    use crate::location::GeoLocation;
+    use crate::product::{ProductAttributes, ProductCategory, ProductId};
+    use crate::storefront::{SignedProduct, StorefrontInfo};
+    use ed25519_dalek::{Signature, SigningKey};
+
+    fn storefront_with(products: &[(&str, ProductCategory)]) -> StorefrontState {
+        let now = Utc::now();
+        let mut schedule = WeeklySchedule::new();
+        schedule.set_range(0, 16, 24, true);
+        StorefrontState {
+            info: StorefrontInfo {
+                owner: UserId(SigningKey::from_bytes(&[1u8; 32]).verifying_key()),
+                name: "Valley Farm".into(),
+                description: "Raw dairy".into(),
+                location: GeoLocation::new(0.0, 0.0),
+                schedule: Some(schedule),
+                timezone: Some("Australia/Sydney".into()),
+                phone: Some("0400 000 000".into()),
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products: products
+                .iter()
+                .enumerate()
+                .map(|(i, (name, category))| {
+                    let id = ProductId(format!("p{i}"));
+                    let product = Product {
+                        id: id.clone(),
+                        name: name.to_string(),
+                        description: String::new(),
+                        category: category.clone(),
+                        subcategory: None,
+                        attributes: ProductAttributes::default(),
+                        price_curd: 500,
+                        quantity_total: 12,
+                        expiry_date: None,
+                        updated_at: now,
+                        created_at: now,
+                        low_stock: None,
+                        paused: false,
+                        special: None,
+                        wholesale: None,
+                        extra: Default::default(),
+                    };
+                    let signed = SignedProduct {
+                        product,
+                        signature: Signature::from_bytes(&[0u8; 64]),
+                        provenance: None,
+                        price_history: Vec::new(),
+                        extra: Default::default(),
+                    };
+                    (id, signed)
+                })
+                .collect(),
+            orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn template_keeps_structure_and_drops_stock() {
+        let sf = storefront_with(&[
+            ("Raw milk 2L", ProductCategory::Milk),
+            ("Cheddar", ProductCategory::Cheese),
+            ("Brie", ProductCategory::Cheese),
+        ]);
+        let template = StorefrontTemplate::from_storefront(&sf);
+        assert_eq!(template.categories, vec!["Cheese", "Milk"]);
+        assert_eq!(template.schedule, sf.info.schedule);
+        assert!(template.products.iter().all(|p| p.id.is_none() && p.quantity_total == 0));
+        // Contact details are the exporting supplier's own
+        assert!(!template.to_json().contains("0400"));
+
+        let read = StorefrontTemplate::from_json(&template.to_json()).unwrap();
+        assert_eq!(read, template);
+        let products = read.instantiate(Utc::now()).unwrap();
+        assert_eq!(products.len(), 3);
+        assert!(products.iter().all(|p| !sf.products.contains_key(&p.id)));
+    }
+
+    #[test]
+    fn template_with_bad_product_is_rejected() {
+        let mut template = StorefrontTemplate::from_storefront(&storefront_with(&[("Kefir", ProductCategory::Kefir)]));
+        template.products[0].price_curd = 0;
+        let errors = StorefrontTemplate::from_json(&template.to_json()).unwrap_err();
+        assert_eq!(errors[0].row, 1);
+        assert!(StorefrontTemplate::from_json("{\"products\": 3}").is_err());
+    }
+}
@@ -0,0 +1,229 @@
+//! Timestamp sanity checks on contract updates.
+//!
+//! Merges resolve conflicts by timestamp — last writer wins, reservations
+//! lapse at `expires_at` — so a timestamp set far in the future wins every
+//! merge and never lapses. Contracts check each incoming update against the
+//! host's clock: nothing may be dated more than [`MAX_CLOCK_SKEW_MINUTES`]
+//! ahead of it, and timestamps must be in order within a record (nothing
+//! updated before it was created, no reservation longer than its tier's hold
+//! period). Checks against the state an update lands on — fields that must
+//! never change or never move backwards — are per state type.
+//!
+//! Only updates are checked. A state that has been stored already is judged
+//! by the clock at the time it arrived, and re-validating it against a later
+//! or skewed clock would make replication depend on the peer's clock.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// How far ahead of the contract host's clock a timestamp may be, to allow
+/// for clients whose clocks run fast.
+pub const MAX_CLOCK_SKEW_MINUTES: i64 = 10;
+
+/// [`MAX_CLOCK_SKEW_MINUTES`] as a duration.
+pub fn max_clock_skew() -> Duration {
+    Duration::minutes(MAX_CLOCK_SKEW_MINUTES)
+}
+
+/// An update carries a timestamp that can't be right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampError {
+    /// Dated further ahead of the host's clock than the allowed skew.
+    InFuture {
+        field: &'static str,
+        at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    },
+    /// Dated further behind the host's clock than the allowed skew, where
+    /// the timestamp records something happening now.
+    Stale {
+        field: &'static str,
+        at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    },
+    /// Dated before a timestamp it must follow.
+    OutOfOrder {
+        field: &'static str,
+        at: DateTime<Utc>,
+        after: &'static str,
+        earliest: DateTime<Utc>,
+    },
+    /// A window (such as a reservation) longer than allowed.
+    TooLong {
+        field: &'static str,
+        at: DateTime<Utc>,
+        latest: DateTime<Utc>,
+    },
+    /// A timestamp that may never change was changed.
+    Changed {
+        field: &'static str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    },
+    /// A timestamp that may only move forward moved backwards by more than
+    /// the allowed skew.
+    Rewound {
+        field: &'static str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    },
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimestampError::InFuture { field, at, now } => {
+                write!(f, "{field} {at} is in the future (now {now})")
+            }
+            TimestampError::Stale { field, at, now } => {
+                write!(f, "{field} {at} is in the past (now {now})")
+            }
+            TimestampError::OutOfOrder { field, at, after, earliest } => {
+                write!(f, "{field} {at} is before {after} {earliest}")
+            }
+            TimestampError::TooLong { field, at, latest } => {
+                write!(f, "{field} {at} is later than allowed ({latest})")
+            }
+            TimestampError::Changed { field, from, to } => {
+                write!(f, "{field} changed from {from} to {to}")
+            }
+            TimestampError::Rewound { field, from, to } => {
+                write!(f, "{field} moved back from {from} to {to}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimestampError {}
+
+/// Check that `at` is no more than the allowed skew ahead of `now`.
+pub fn check_not_future(
+    field: &'static str,
+    at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if at > now + max_clock_skew() {
+        return Err(TimestampError::InFuture { field, at, now });
+    }
+    Ok(())
+}
+
+/// Check an optional timestamp; `None` always passes.
+pub fn check_opt_not_future(
+    field: &'static str,
+    at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    at.map_or(Ok(()), |at| check_not_future(field, at, now))
+}
+
+/// Check that `at` is no more than the allowed skew behind `now`, for a
+/// timestamp that says when something new in the update happened.
+pub fn check_recent(
+    field: &'static str,
+    at: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if at < now - max_clock_skew() {
+        return Err(TimestampError::Stale { field, at, now });
+    }
+    Ok(())
+}
+
+/// Check that `at` is not before `earliest` (the `after` timestamp).
+pub fn check_order(
+    field: &'static str,
+    at: DateTime<Utc>,
+    after: &'static str,
+    earliest: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if at < earliest {
+        return Err(TimestampError::OutOfOrder { field, at, after, earliest });
+    }
+    Ok(())
+}
+
+/// Check that `at` is no later than `latest`.
+pub fn check_within(
+    field: &'static str,
+    at: DateTime<Utc>,
+    latest: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if at > latest {
+        return Err(TimestampError::TooLong { field, at, latest });
+    }
+    Ok(())
+}
+
+/// Check that a timestamp fixed at creation is unchanged.
+pub fn check_unchanged(
+    field: &'static str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if from != to {
+        return Err(TimestampError::Changed { field, from, to });
+    }
+    Ok(())
+}
+
+/// Check that a timestamp that only moves forward hasn't moved back by more
+/// than the allowed skew (successive writes may come from different devices).
+pub fn check_not_rewound(
+    field: &'static str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<(), TimestampError> {
+    if to + max_clock_skew() < from {
+        return Err(TimestampError::Rewound { field, from, to });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn future_timestamps_are_allowed_up_to_the_skew() {
+        let now = Utc::now();
+        assert!(check_not_future("updated_at", now, now).is_ok());
+        assert!(check_not_future("updated_at", now + max_clock_skew(), now).is_ok());
+        assert!(check_not_future("updated_at", now - Duration::days(365), now).is_ok());
+        let next_year = now + Duration::days(365);
+        assert_eq!(
+            check_not_future("updated_at", next_year, now),
+            Err(TimestampError::InFuture { field: "updated_at", at: next_year, now })
+        );
+        assert!(check_opt_not_future("checkpoint_at", None, now).is_ok());
+    }
+
+    #[test]
+    fn recent_timestamps_are_allowed_down_to_the_skew() {
+        let now = Utc::now();
+        assert!(check_recent("claimed_at", now, now).is_ok());
+        assert!(check_recent("claimed_at", now - max_clock_skew(), now).is_ok());
+        let last_week = now - Duration::days(7);
+        assert_eq!(
+            check_recent("claimed_at", last_week, now),
+            Err(TimestampError::Stale { field: "claimed_at", at: last_week, now })
+        );
+    }
+
+    #[test]
+    fn ordering_and_monotonicity() {
+        let t0 = Utc::now();
+        let t1 = t0 + Duration::hours(1);
+        assert!(check_order("updated_at", t1, "created_at", t0).is_ok());
+        assert!(check_order("updated_at", t0, "created_at", t1).is_err());
+        assert!(check_within("expires_at", t0, t1).is_ok());
+        assert!(check_within("expires_at", t1, t0).is_err());
+        assert!(check_unchanged("created_at", t0, t0).is_ok());
+        assert!(check_unchanged("created_at", t0, t1).is_err());
+        assert!(check_not_rewound("updated_at", t0, t1).is_ok());
+        assert!(check_not_rewound("updated_at", t1, t0).is_err());
+        // A device a little behind the last writer is tolerated
+        assert!(check_not_rewound("updated_at", t1, t1 - max_clock_skew()).is_ok());
+    }
+}
@@ -1,14 +1,28 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
 
 use chrono::{DateTime, Utc};
 use ed25519_dalek::{Signature, VerifyingKey};
 #[cfg(not(feature = "dev"))]
 use ed25519_dalek::Verifier;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::identity::UserId;
+use crate::limits::{
+    check_count, check_len, check_opt_len, LimitError, MAX_LEDGER_ENTRIES, MAX_NAME_LEN, MAX_ORDERS_PER_STOREFRONT,
+    MAX_SETTLEMENT_STATEMENTS, MAX_TX_DESCRIPTION_LEN,
+};
+use crate::metrics::ContractMetrics;
+use crate::provenance::is_zero;
+use crate::settlement::{
+    merge_statements, settlement_description, SettlementStatement, SETTLEMENT_DESCRIPTION_PREFIX,
+};
+use crate::signing::{self, Domain};
+use crate::timestamps::{check_not_future, check_not_rewound, check_opt_not_future, max_clock_skew, TimestampError};
 use crate::tolls::TollRates;
-use crate::wallet::{TransactionKind, WalletTransaction};
+use crate::voucher::{Voucher, VOUCHER_ESCROW_TX_PREFIX, VOUCHER_TX_PREFIX};
+use crate::wallet::{apply_transaction, TransactionKind, WalletTransaction};
 
 /// Prune when ledger exceeds this many entries.
 pub const PRUNE_THRESHOLD: usize = 500;
@@ -56,6 +70,29 @@ pub struct UserContractState {
     /// Lightning payment hashes from pruned transactions (preserved for double-mint prevention).
     #[serde(default)]
     pub pruned_lightning_hashes: HashSet<String>,
+    /// Signed record of which entries the checkpoint folded. Absent for
+    /// legacy checkpoints, which merge without pruning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_proof: Option<LedgerCheckpoint>,
+    /// `tx_ref`s of debits authorised by the spending key (counted against its daily limit).
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub spending_key_debits: BTreeSet<String>,
+    /// Signed CURD issuance records (root contract only), append-only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mint_records: Vec<MintRecord>,
+    /// Owner-signed settlement statements (suppliers), one per period, oldest
+    /// first. See [`crate::settlement`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub settlement_statements: Vec<SettlementStatement>,
+    /// Issuer-signed vouchers redeemed into this contract, one per id; each
+    /// backs the ledger credit that paid it out. See [`crate::voucher`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vouchers: Vec<Voucher>,
+    /// Owner's write sequence number, bumped on every re-signed write. The
+    /// higher `seq` wins a metadata merge, so a replayed older state can't
+    /// roll back a newer one.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub seq: u64,
     /// Timestamp for LWW merge.
     pub updated_at: DateTime<Utc>,
     /// Owner's signature over the state.
@@ -65,10 +102,222 @@ pub struct UserContractState {
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
+/// The entries folded into a ledger checkpoint, signed by the contract owner
+/// (the FROST group key for the root contract).
+///
+/// Replicas drop any ledger entry the checkpoint folds when they adopt it,
+/// so pruning propagates on merge. Only this checkpoint's entries are listed
+/// by hash; everything earlier checkpoints folded is covered by a position in
+/// the ledger's canonical order, so the proof doesn't grow with the ledger's
+/// history. Entries that arrive late with an older timestamp still count on
+/// top of the checkpoint, unless they sort before that position, where they
+/// can't be told from the entries folded there: an update built on the
+/// checkpoint may not add one (see [`LedgerCheckpoint::is_late`]), and its
+/// writer must date it afresh.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerCheckpoint {
+    /// [`folded_entry_hash`] of every entry folded by this checkpoint. Legacy
+    /// proofs list those of all earlier checkpoints too.
+    pub folded: BTreeSet<String>,
+    /// Position of the last entry folded by this or any earlier checkpoint.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub through: Option<LedgerPosition>,
+    /// Position of the last entry folded by earlier checkpoints: every entry
+    /// at or before it is covered by the checkpoint balance.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub earlier_through: Option<LedgerPosition>,
+    /// Who each voucher redemption folded by this or any earlier checkpoint
+    /// paid out to, by voucher id (root contract only). See [`crate::voucher`].
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub voucher_redeemers: BTreeMap<String, String>,
+    /// The amount escrowed for each voucher whose escrow credit this or any
+    /// earlier checkpoint folded, by voucher id (root contract only).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub voucher_escrows: BTreeMap<String, u64>,
+    /// Orders whose escrow settlement debits this or any earlier checkpoint
+    /// folded (root contract only), so none is settled twice.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub settled_orders: BTreeSet<String>,
+    /// Owner's signature over [`UserContractState::checkpoint_signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LedgerCheckpoint {
+    /// Whether this checkpoint covers `tx`, so a replica adopting it drops it.
+    pub fn folds(&self, tx: &WalletTransaction) -> bool {
+        self.covers_position(tx) || self.folded.contains(&folded_entry_hash(tx))
+    }
+
+    /// Whether `tx` sorts at or before the position earlier checkpoints
+    /// folded through, yet isn't an entry this one folded: it arrived after
+    /// the checkpoints it predates, which would drop it uncounted.
+    pub fn is_late(&self, tx: &WalletTransaction) -> bool {
+        self.covers_position(tx) && !self.folded.contains(&folded_entry_hash(tx))
+    }
+
+    fn covers_position(&self, tx: &WalletTransaction) -> bool {
+        self.earlier_through.as_ref().is_some_and(|through| LedgerPosition::of(tx) <= *through)
+    }
+}
+
+/// Where an entry sorts in the canonical ledger order (see `sort_ledger`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LedgerPosition {
+    pub timestamp: String,
+    pub kind: TransactionKind,
+    pub tx_ref: String,
+}
+
+impl LedgerPosition {
+    pub fn of(tx: &WalletTransaction) -> Self {
+        LedgerPosition { timestamp: tx.timestamp.clone(), kind: tx.kind.clone(), tx_ref: tx.tx_ref.clone() }
+    }
+}
+
+/// `tx_ref` prefix of the ledger credit that books a mint; the rest is the
+/// [`MintRecord::id`].
+pub const MINT_TX_PREFIX: &str = "mint:";
+
+/// One issuance of CURD, signed by the contract owner — on the root contract
+/// the guardians' FROST group key, so minting takes a threshold of guardians.
+///
+/// The record is kept when its ledger credit is folded into a checkpoint, so
+/// the full issuance history stays auditable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MintRecord {
+    /// Unique per ceremony.
+    pub id: String,
+    /// CURD issued.
+    pub amount: u64,
+    /// Why the guardians minted (e.g. "Lightning peg-in reserve top-up").
+    pub reason: String,
+    pub minted_at: DateTime<Utc>,
+    /// Owner's signature over [`MintRecord::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl MintRecord {
+    /// Serialize the record (excluding signature) for signing/verification.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableMint {
+            domain: "cream-mint-v1",
+            id: &self.id,
+            amount: self.amount,
+            reason: &self.reason,
+            minted_at: &self.minted_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the record is signed by `owner`.
+    pub fn verify(&self, owner: &VerifyingKey) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            // Dev mode skips signatures (DKG key ≠ trusted-dealer key).
+            let _ = owner;
+            true
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            owner.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// `tx_ref` of the ledger credit that books this mint.
+    pub fn tx_ref(&self) -> String {
+        format!("{MINT_TX_PREFIX}{}", self.id)
+    }
+
+    /// The ledger credit that books this mint to `receiver` (the root user).
+    pub fn credit(&self, id: u32, receiver: &str) -> WalletTransaction {
+        WalletTransaction {
+            id,
+            kind: TransactionKind::Credit,
+            amount: self.amount,
+            description: self.reason.clone(),
+            sender: receiver.to_string(),
+            receiver: receiver.to_string(),
+            tx_ref: self.tx_ref(),
+            timestamp: self.minted_at.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+}
+
+/// The root (guardians' group) key that root stamps its payments with,
+/// fixed at build time (hex). Unset, root can only pay out mints and
+/// vouchers outside dev builds.
+#[cfg(not(feature = "dev"))]
+fn root_key() -> Option<VerifyingKey> {
+    option_env!("CREAM_ROOT_KEY")
+        .and_then(|hex| hex.parse::<UserId>().ok())
+        .map(|id| id.0)
+}
+
+/// Whether a new credit shows who paid it in a way the contract can check:
+/// mints and voucher payouts by their signed records, anything else by its
+/// payer's stamp — for root's payments, a stamp under `root`. Unstamped
+/// credits are refused whoever signs the update, so no one can credit
+/// CURD that wasn't paid.
+#[cfg(not(feature = "dev"))]
+fn credit_vouched(tx: &WalletTransaction, root: Option<&VerifyingKey>) -> bool {
+    if tx.tx_ref.starts_with(MINT_TX_PREFIX) || tx.tx_ref.starts_with(VOUCHER_TX_PREFIX) {
+        return true;
+    }
+    let paid_by_root = tx.sender == crate::identity::ROOT_USER_NAME;
+    tx.stamped_by_payer()
+        && (!paid_by_root || root.is_some_and(|root| tx.payer.as_ref().is_some_and(|payer| payer.0 == *root)))
+}
+
+/// Audit hash of a ledger entry: the first 16 bytes of SHA-256 over its
+/// `tx_ref` and kind, hex-encoded.
+pub fn folded_entry_hash(tx: &WalletTransaction) -> String {
+    entry_hash(&tx.tx_ref, &tx.kind)
+}
+
+fn entry_hash(tx_ref: &str, kind: &TransactionKind) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tx_ref.as_bytes());
+    hasher.update(match kind {
+        TransactionKind::Credit => b"\0credit".as_slice(),
+        TransactionKind::Debit => b"\0debit".as_slice(),
+    });
+    hasher.finalize()[..16].iter().fold(String::with_capacity(32), |mut out, b| {
+        let _ = write!(out, "{b:02x}");
+        out
+    })
+}
+
 /// Parameters that make each user contract unique (same pattern as StorefrontParameters).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserContractParameters {
     pub owner: VerifyingKey,
+    /// Optional limited key (e.g. for a child or shop assistant) that may
+    /// debit within fixed limits. Absent for ordinary contracts, so their
+    /// parameters — and contract keys — are unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spending_key: Option<SpendingKeyLimits>,
+}
+
+/// A secondary key and the debit limits the contract enforces for it.
+/// The owner key is never limited.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendingKeyLimits {
+    pub key: VerifyingKey,
+    /// Largest single debit, in CURD.
+    pub max_per_tx: u64,
+    /// Total debits per UTC day, in CURD.
+    pub max_per_day: u64,
 }
 
 /// Summary for delta sync protocol.
@@ -81,6 +330,9 @@ pub struct UserContractSummary {
     /// Number of pruned transactions (checkpoint prefix count).
     #[serde(default)]
     pub checkpoint_tx_count: u64,
+    /// Write sequence number of the summarizer's copy.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub seq: u64,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
@@ -101,11 +353,337 @@ impl UserContractState {
             checkpoint_balance: self.checkpoint_balance,
             checkpoint_tx_count: self.checkpoint_tx_count,
             checkpoint_at: &self.checkpoint_at,
+            seq: self.seq,
             updated_at: &self.updated_at,
         };
         serde_json::to_vec(&signable).expect("serialization should not fail")
     }
 
+    /// Start a new owner write: bump `seq` and stamp `updated_at`. The caller
+    /// then re-signs over [`signable_bytes`](Self::signable_bytes).
+    pub fn touch(&mut self, now: DateTime<Utc>) {
+        self.seq += 1;
+        self.updated_at = now;
+    }
+
+    /// Serialize the checkpoint for signing: everything a replica relies on
+    /// when it adopts the checkpoint and prunes the folded entries.
+    pub fn checkpoint_signable_bytes(&self) -> Vec<u8> {
+        signing::signable_bytes(Domain::LedgerCheckpoint, &self.checkpoint_fields())
+    }
+
+    /// The checkpoint fields as bare JSON, as checkpoints were signed before
+    /// [`crate::signing`]. Still accepted before [`crate::signing::legacy_cutoff`].
+    pub fn legacy_checkpoint_signable_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&self.checkpoint_fields()).expect("serialization should not fail")
+    }
+
+    fn checkpoint_fields(&self) -> SignableCheckpoint<'_> {
+        SignableCheckpoint {
+            owner: &self.owner,
+            checkpoint_balance: self.checkpoint_balance,
+            checkpoint_tx_count: self.checkpoint_tx_count,
+            checkpoint_at: &self.checkpoint_at,
+            folded: self.checkpoint_proof.as_ref().map(|p| &p.folded),
+            through: self.checkpoint_proof.as_ref().and_then(|p| p.through.as_ref()),
+            earlier_through: self.checkpoint_proof.as_ref().and_then(|p| p.earlier_through.as_ref()),
+            voucher_redeemers: self
+                .checkpoint_proof
+                .as_ref()
+                .map(|p| &p.voucher_redeemers)
+                .filter(|redeemers| !redeemers.is_empty()),
+            voucher_escrows: self
+                .checkpoint_proof
+                .as_ref()
+                .map(|p| &p.voucher_escrows)
+                .filter(|escrows| !escrows.is_empty()),
+            settled_orders: self
+                .checkpoint_proof
+                .as_ref()
+                .map(|p| &p.settled_orders)
+                .filter(|orders| !orders.is_empty()),
+        }
+    }
+
+    /// Whether the checkpoint proof (if any) is signed by `owner` and folds no
+    /// more entries than the checkpoint counts. Legacy checkpoints folded
+    /// entries before proofs existed, so fewer is allowed.
+    pub fn checkpoint_valid(&self, owner: &VerifyingKey) -> bool {
+        let Some(proof) = &self.checkpoint_proof else {
+            return true;
+        };
+        if proof.folded.len() as u64 > self.checkpoint_tx_count {
+            return false;
+        }
+        #[cfg(feature = "dev")]
+        {
+            let _ = owner;
+            true
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            // A proof without checkpoint_at has nothing dating it before the cutoff
+            let dated = self.checkpoint_at.unwrap_or(DateTime::<Utc>::MAX_UTC);
+            signing::verify(owner, &proof.signature, dated, &self.checkpoint_signable_bytes(), || {
+                self.legacy_checkpoint_signable_bytes()
+            })
+            .is_some()
+        }
+    }
+
+    /// Whether every mint record is signed by `owner` with a unique id, and
+    /// every live mint credit in the ledger is backed by a record of the same
+    /// amount.
+    pub fn mints_valid(&self, owner: &VerifyingKey) -> bool {
+        let mut ids = HashSet::new();
+        if !self.mint_records.iter().all(|record| ids.insert(&record.id) && record.verify(owner)) {
+            return false;
+        }
+        self.ledger
+            .iter()
+            .filter(|tx| tx.tx_ref.starts_with(MINT_TX_PREFIX))
+            .all(|tx| {
+                tx.kind == TransactionKind::Credit
+                    && self
+                        .mint_records
+                        .iter()
+                        .any(|record| record.tx_ref() == tx.tx_ref && record.amount == tx.amount)
+            })
+    }
+
+    /// Whether every voucher is signed by its issuer with a unique id, and
+    /// every live voucher credit in the ledger is backed by a voucher of the
+    /// same amount.
+    pub fn vouchers_valid(&self) -> bool {
+        let mut ids = HashSet::new();
+        if !self.vouchers.iter().all(|voucher| ids.insert(&voucher.id) && voucher.verify()) {
+            return false;
+        }
+        self.ledger
+            .iter()
+            .filter(|tx| tx.tx_ref.starts_with(VOUCHER_TX_PREFIX) && tx.kind == TransactionKind::Credit)
+            .all(|tx| {
+                self.vouchers
+                    .iter()
+                    .any(|voucher| voucher.tx_ref() == tx.tx_ref && voucher.amount == tx.amount)
+            })
+    }
+
+    /// Whether the voucher with this id has been redeemed into this contract.
+    pub fn has_redeemed(&self, voucher_id: &str) -> bool {
+        self.vouchers.iter().any(|voucher| voucher.id == voucher_id)
+    }
+
+    /// Who root paid the voucher with this id out to: the receiver of its
+    /// live redemption debit, or of the one a checkpoint folded.
+    pub fn voucher_redeemer(&self, voucher_id: &str) -> Option<&str> {
+        let tx_ref = format!("{VOUCHER_TX_PREFIX}{voucher_id}");
+        self.ledger
+            .iter()
+            .find(|tx| tx.tx_ref == tx_ref && tx.kind == TransactionKind::Debit)
+            .map(|tx| tx.receiver.as_str())
+            .or_else(|| {
+                let proof = self.checkpoint_proof.as_ref()?;
+                proof.voucher_redeemers.get(voucher_id).map(String::as_str)
+            })
+    }
+
+    /// How much the voucher with this id has escrowed with root: the amount
+    /// of its live escrow credit, or of the one a checkpoint folded.
+    pub fn voucher_escrow(&self, voucher_id: &str) -> Option<u64> {
+        let tx_ref = format!("{VOUCHER_ESCROW_TX_PREFIX}{voucher_id}");
+        self.ledger
+            .iter()
+            .find(|tx| tx.tx_ref == tx_ref && tx.kind == TransactionKind::Credit)
+            .map(|tx| tx.amount)
+            .or_else(|| {
+                let proof = self.checkpoint_proof.as_ref()?;
+                proof.voucher_escrows.get(voucher_id).copied()
+            })
+    }
+
+    /// Whether root has paid this order's escrow out: a live settlement
+    /// debit for it, or one a checkpoint folded.
+    pub fn escrow_settled(&self, order_id: &str) -> bool {
+        let description = settlement_description(order_id);
+        self.ledger
+            .iter()
+            .any(|tx| tx.kind == TransactionKind::Debit && tx.description == description)
+            || self
+                .checkpoint_proof
+                .as_ref()
+                .is_some_and(|proof| proof.settled_orders.contains(order_id))
+    }
+
+    /// Whether the latest checkpoint folded the entry with this `tx_ref` and
+    /// kind. Entries folded by earlier checkpoints aren't listed.
+    pub fn folded_entry(&self, tx_ref: &str, kind: &TransactionKind) -> bool {
+        self.checkpoint_proof
+            .as_ref()
+            .is_some_and(|proof| proof.folded.contains(&entry_hash(tx_ref, kind)))
+    }
+
+    /// Whether every settlement statement is signed by `owner`, one per period.
+    pub fn statements_valid(&self, owner: &VerifyingKey) -> bool {
+        let mut ids = HashSet::new();
+        self.settlement_statements
+            .iter()
+            .all(|statement| ids.insert(statement.id()) && statement.verify(owner))
+    }
+
+    /// Total CURD ever minted into this contract.
+    pub fn total_minted(&self) -> u64 {
+        self.mint_records
+            .iter()
+            .fold(0u64, |sum, record| sum.saturating_add(record.amount))
+    }
+
+    /// Whether every stamped ledger entry carries a valid payer stamp.
+    pub fn ledger_provenance_valid(&self) -> bool {
+        self.ledger.iter().all(WalletTransaction::verify_provenance)
+    }
+
+    /// Validate that the state is signed by the owner or, if the contract has
+    /// one, its spending key, that any checkpoint and mint records are signed
+    /// by the owner, that vouchers back their credits, and that stamped
+    /// credits verify.
+    pub fn validate_for(&self, params: &UserContractParameters) -> bool {
+        (self.validate(&params.owner)
+            || params
+                .spending_key
+                .as_ref()
+                .is_some_and(|limits| self.validate(&limits.key)))
+            && self.checkpoint_valid(&params.owner)
+            && self.mints_valid(&params.owner)
+            && self.statements_valid(&params.owner)
+            && self.vouchers_valid()
+            && self.ledger_provenance_valid()
+    }
+
+    /// Validate an incoming update against the contract's parameters: owner-signed
+    /// (or credit-only) updates as [`validate_update`](Self::validate_update),
+    /// otherwise a spending-key debit within its limits. Credits stamped by
+    /// their payer must carry a valid stamp, and mints need an owner-signed
+    /// record — which is what lets guardians append a mint without
+    /// re-signing the whole state. An update that would overdraw the ledger
+    /// once merged is refused (see [`overdrawn_by`](Self::overdrawn_by)).
+    pub fn validate_update_for(
+        &self,
+        update: &UserContractState,
+        params: &UserContractParameters,
+        now: DateTime<Utc>,
+    ) -> bool {
+        (self.validate_update(update, &params.owner)
+            || params
+                .spending_key
+                .as_ref()
+                .is_some_and(|limits| self.validate_spending_key_update(update, limits, now)))
+            && update.checkpoint_valid(&params.owner)
+            && update.mints_valid(&params.owner)
+            && update.statements_valid(&params.owner)
+            && update.vouchers_valid()
+            && update.ledger_provenance_valid()
+            && !self.overdrawn_by(update)
+    }
+
+    /// Whether merging `update` would leave a debit conflicting that isn't
+    /// already (see [`conflicting_debits`](Self::conflicting_debits)). A
+    /// skipped debit still has its credit counted in the counterparty's
+    /// ledger, so such an update must be refused rather than merged.
+    pub fn overdrawn_by(&self, update: &UserContractState) -> bool {
+        let conflicting: HashSet<(&str, &TransactionKind)> = self
+            .conflicting_debits()
+            .into_iter()
+            .map(|tx| (tx.tx_ref.as_str(), &tx.kind))
+            .collect();
+        let mut merged = self.clone();
+        merged.merge(update.clone());
+        merged
+            .conflicting_debits()
+            .iter()
+            .any(|tx| !conflicting.contains(&(tx.tx_ref.as_str(), &tx.kind)))
+    }
+
+    /// Validate an update signed by the spending key: it may only add debits
+    /// (declared in `spending_key_debits`), each within `max_per_tx`, without
+    /// the key's debits on any day exceeding `max_per_day`. A new debit can't
+    /// be dated before the key's latest existing debit, so limits can't be
+    /// dodged by backdating, nor after `now` (give or take clock skew), so they
+    /// can't be dodged by post-dating either; a debit whose timestamp isn't
+    /// RFC 3339 has no day to count against and is rejected.
+    pub fn validate_spending_key_update(
+        &self,
+        update: &UserContractState,
+        limits: &SpendingKeyLimits,
+        now: DateTime<Utc>,
+    ) -> bool {
+        if self.metadata_changed(update) || !update.validate(&limits.key) {
+            return false;
+        }
+
+        let existing_keys: HashSet<(&str, &TransactionKind)> =
+            self.ledger.iter().map(|tx| (tx.tx_ref.as_str(), &tx.kind)).collect();
+        let new_entries: Vec<&WalletTransaction> = update
+            .ledger
+            .iter()
+            .filter(|tx| !existing_keys.contains(&(tx.tx_ref.as_str(), &tx.kind)))
+            .collect();
+
+        let latest_existing = self
+            .ledger
+            .iter()
+            .filter(|tx| tx.kind == TransactionKind::Debit && self.spending_key_debits.contains(&tx.tx_ref))
+            .filter_map(|tx| parse_timestamp(&tx.timestamp))
+            .max();
+        let mut new_debits = Vec::with_capacity(new_entries.len());
+        for tx in &new_entries {
+            let Some(at) = parse_timestamp(&tx.timestamp) else {
+                return false;
+            };
+            let ok = tx.kind == TransactionKind::Debit
+                && update.spending_key_debits.contains(&tx.tx_ref)
+                && tx.amount <= limits.max_per_tx
+                && at <= now + max_clock_skew()
+                && latest_existing.is_none_or(|latest| at >= latest);
+            if !ok {
+                return false;
+            }
+            new_debits.push((at, tx.amount));
+        }
+
+        // Daily totals over all spending-key debits, existing and new
+        let mut per_day: BTreeMap<chrono::NaiveDate, u64> = BTreeMap::new();
+        let key_debits = self
+            .ledger
+            .iter()
+            .filter(|tx| tx.kind == TransactionKind::Debit && self.spending_key_debits.contains(&tx.tx_ref))
+            .filter_map(|tx| Some((parse_timestamp(&tx.timestamp)?, tx.amount)))
+            .chain(new_debits);
+        for (at, amount) in key_debits {
+            let total = per_day.entry(at.date_naive()).or_default();
+            *total = total.saturating_add(amount);
+            if *total > limits.max_per_day {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether `update` changes anything other than the ledger.
+    fn metadata_changed(&self, update: &UserContractState) -> bool {
+        update.name != self.name
+            || update.origin_supplier != self.origin_supplier
+            || update.current_supplier != self.current_supplier
+            || update.invited_by != self.invited_by
+            || update.owner != self.owner
+            || update.toll_rates != self.toll_rates
+            || update.checkpoint_balance != self.checkpoint_balance
+            || update.checkpoint_tx_count != self.checkpoint_tx_count
+            || update.checkpoint_at != self.checkpoint_at
+            || update.checkpoint_proof != self.checkpoint_proof
+            || update.seq != self.seq
+    }
+
     /// Validate that the state is signed by the owner.
     pub fn validate(&self, owner: &VerifyingKey) -> bool {
         #[cfg(feature = "dev")]
@@ -115,6 +693,7 @@ impl UserContractState {
             // so root contract signatures from the live guardians won't match
             // the trusted-dealer key embedded in the contract parameters.
             let _ = owner;
+            #[allow(clippy::needless_return)]
             return true;
         }
         #[cfg(not(feature = "dev"))]
@@ -126,7 +705,9 @@ impl UserContractState {
 
     /// Validate an incoming update for merge.
     ///
-    /// - If the update only adds Credit entries (no debits, no metadata changes), accept without signature check
+    /// - Every new credit must show who paid it (see `credit_vouched`), or the update is refused
+    /// - If the update only adds Credit entries (no debits, no metadata changes), accept without
+    ///   signature check
     /// - If the update contains Debit entries or metadata changes, require owner signature
     pub fn validate_update(&self, update: &UserContractState, owner: &VerifyingKey) -> bool {
         #[cfg(feature = "dev")]
@@ -159,26 +740,29 @@ impl UserContractState {
             .filter(|tx| !existing_keys.contains(&(tx.tx_ref.clone(), tx.kind.clone())))
             .collect();
 
-        // Check if metadata changed (includes checkpoint changes)
-        let metadata_changed = update.name != self.name
-            || update.origin_supplier != self.origin_supplier
-            || update.current_supplier != self.current_supplier
-            || update.invited_by != self.invited_by
-            || update.owner != self.owner
-            || update.toll_rates != self.toll_rates
-            || update.checkpoint_balance != self.checkpoint_balance
-            || update.checkpoint_tx_count != self.checkpoint_tx_count
-            || update.checkpoint_at != self.checkpoint_at;
+        // Check if metadata changed (includes checkpoint changes). Marking
+        // debits as spending-key debits counts them against its budget, so
+        // that needs a signature too.
+        let metadata_changed = self.metadata_changed(update)
+            || !update.spending_key_debits.is_subset(&self.spending_key_debits);
 
-        // If all new entries are credits and no metadata changed, accept without sig
-        let all_credits = new_entries
+        // Every new credit must show who paid it, signed or not
+        let root = root_key();
+        let credits_vouched = new_entries
             .iter()
-            .all(|tx| tx.kind == TransactionKind::Credit);
+            .filter(|tx| tx.kind == TransactionKind::Credit)
+            .all(|tx| credit_vouched(tx, root.as_ref()));
+        if !credits_vouched {
+            return false;
+        }
+
+        // If all new entries are credits and no metadata changed, accept
+        // without sig
         let has_debits = new_entries
             .iter()
             .any(|tx| tx.kind == TransactionKind::Debit);
 
-        if !has_debits && all_credits && !metadata_changed {
+        if !has_debits && !metadata_changed {
             return true;
         }
 
@@ -187,22 +771,68 @@ impl UserContractState {
     }
 
     /// Derive balance from the transaction ledger, starting from checkpoint_balance.
+    ///
+    /// Debits that would overdraw the balance at their position in the ledger
+    /// are skipped (see [`conflicting_debits`](Self::conflicting_debits)).
     pub fn derive_balance(&self) -> u64 {
+        fold_ledger(self.checkpoint_balance, &self.ledger).0
+    }
+
+    /// Debits rejected by the double-spend rule.
+    ///
+    /// Concurrent spends from two devices can each be valid against the balance
+    /// they were made from, yet overdraw it once both are merged. Walking the
+    /// ledger in canonical order, any debit larger than the running balance is
+    /// marked conflicting and left out of the balance. Every replica holding
+    /// the same entries marks the same debits, so merges still converge. The
+    /// contract refuses the update that brings such a debit in, so they only
+    /// turn up in local merges and states from before that check.
+    pub fn conflicting_debits(&self) -> Vec<&WalletTransaction> {
+        fold_ledger(self.checkpoint_balance, &self.ledger)
+            .1
+            .into_iter()
+            .map(|idx| &self.ledger[idx])
+            .collect()
+    }
+
+    /// Ledger entries the owner wrote from devices other than `device`,
+    /// oldest first: what another login of the same account added.
+    pub fn entries_from_other_devices(&self, device: &str) -> Vec<&WalletTransaction> {
         self.ledger
             .iter()
-            .fold(self.checkpoint_balance, |acc, tx| match tx.kind {
-                TransactionKind::Credit => acc.saturating_add(tx.amount),
-                TransactionKind::Debit => acc.saturating_sub(tx.amount),
-            })
+            .filter(|tx| tx.device.as_deref().is_some_and(|d| d != device))
+            .collect()
+    }
+
+    /// Every device other than `device` that wrote to the ledger, with the
+    /// timestamp of its latest entry.
+    pub fn other_devices(&self, device: &str) -> BTreeMap<&str, &str> {
+        let mut latest: BTreeMap<&str, &str> = BTreeMap::new();
+        for tx in self.entries_from_other_devices(device) {
+            let seen = latest.entry(tx.device.as_deref().unwrap_or_default()).or_default();
+            if tx.timestamp.as_str() > *seen {
+                *seen = &tx.timestamp;
+            }
+        }
+        latest
     }
 
     /// Merge another state into this one.
     ///
     /// Hybrid strategy:
     /// - `invited_by`, `origin_supplier`: immutable (preserve if already set)
-    /// - `current_supplier`, `updated_at`, `signature`: LWW (newer wins)
-    /// - `ledger`: append-only union (dedup by tx_ref + kind)
-    /// - `balance_curds`: re-derived from merged ledger
+    /// - `current_supplier`, `updated_at`, `signature`: LWW (newer wins; two
+    ///   devices writing the same `seq` at the same instant are ordered by
+    ///   signature, so every replica picks the same one)
+    /// - `ledger`: append-only union (dedup by tx_ref + kind), canonically ordered,
+    ///   minus entries folded into the adopted checkpoint. An entry both
+    ///   sides hold keeps a device tag over none, and the lower of two, so
+    ///   the kept copy doesn't depend on merge order
+    /// - `balance_curds`: re-derived from merged ledger; overdrawing debits are
+    ///   marked conflicting rather than driving the balance negative
+    /// - `settlement_statements`: one per period, the latest issued wins
+    /// - `vouchers`: append-only union by voucher id, so a voucher is only
+    ///   ever redeemed once into a contract
     pub fn merge(&mut self, other: UserContractState) {
         // Checkpoint LWW: newer checkpoint_at wins
         let other_cp_at = other.checkpoint_at;
@@ -211,11 +841,13 @@ impl UserContractState {
                 self.checkpoint_balance = other.checkpoint_balance;
                 self.checkpoint_tx_count = other.checkpoint_tx_count;
                 self.checkpoint_at = other_cp_at;
+                self.checkpoint_proof = other.checkpoint_proof.clone();
             }
             (None, Some(_)) => {
                 self.checkpoint_balance = other.checkpoint_balance;
                 self.checkpoint_tx_count = other.checkpoint_tx_count;
                 self.checkpoint_at = other_cp_at;
+                self.checkpoint_proof = other.checkpoint_proof.clone();
             }
             _ => {}
         }
@@ -223,12 +855,33 @@ impl UserContractState {
         for hash in &other.pruned_lightning_hashes {
             self.pruned_lightning_hashes.insert(hash.clone());
         }
+        // Spending-key debit markers: set union
+        self.spending_key_debits.extend(other.spending_key_debits.iter().cloned());
+        // Mint records: append-only union by id, oldest first
+        for record in &other.mint_records {
+            if !self.mint_records.iter().any(|mine| mine.id == record.id) {
+                self.mint_records.push(record.clone());
+            }
+        }
+        self.mint_records
+            .sort_by(|a, b| a.minted_at.cmp(&b.minted_at).then_with(|| a.id.cmp(&b.id)));
+        // Settlement statements: one per period, latest issued wins
+        merge_statements(&mut self.settlement_statements, &other.settlement_statements);
+        // Vouchers: append-only union by id, oldest first
+        for voucher in &other.vouchers {
+            if !self.has_redeemed(&voucher.id) {
+                self.vouchers.push(voucher.clone());
+            }
+        }
+        self.vouchers
+            .sort_by(|a, b| a.issued_at.cmp(&b.issued_at).then_with(|| a.id.cmp(&b.id)));
 
         // Append-only ledger union (dedup by tx_ref + kind)
-        let existing_keys: HashSet<(String, TransactionKind)> = self
+        let existing_keys: HashMap<(String, TransactionKind), usize> = self
             .ledger
             .iter()
-            .map(|tx| (tx.tx_ref.clone(), tx.kind.clone()))
+            .enumerate()
+            .map(|(idx, tx)| ((tx.tx_ref.clone(), tx.kind.clone()), idx))
             .collect();
 
         // Collect existing Lightning payment hashes (live + pruned) to prevent double-minting
@@ -241,7 +894,17 @@ impl UserContractState {
 
         for tx in other.ledger {
             let key = (tx.tx_ref.clone(), tx.kind.clone());
-            if existing_keys.contains(&key) {
+            if let Some(&idx) = existing_keys.get(&key) {
+                // Same entry, tagged differently by two replicas
+                let kept = &mut self.ledger[idx].device;
+                let prefer = match (&tx.device, &*kept) {
+                    (Some(theirs), Some(ours)) => theirs < ours,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                };
+                if prefer {
+                    *kept = tx.device;
+                }
                 continue;
             }
             // Reject transactions with a lightning_payment_hash already in the ledger or pruned set
@@ -252,11 +915,19 @@ impl UserContractState {
             }
             self.ledger.push(tx);
         }
-        // Sort by timestamp for display consistency
-        self.ledger.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-
-        // LWW for metadata fields
-        if other.updated_at > self.updated_at {
+        // Entries already folded into the checkpoint are covered by its balance
+        if let Some(proof) = &self.checkpoint_proof {
+            self.ledger.retain(|tx| !proof.folds(tx));
+        }
+        // Canonical order: every replica must walk the ledger identically for
+        // double-spend detection to converge.
+        sort_ledger(&mut self.ledger);
+
+        // LWW for metadata fields: higher seq first, updated_at breaks ties
+        // (and orders states written before sequencing)
+        if (other.seq, other.updated_at, other.signature.to_bytes())
+            > (self.seq, self.updated_at, self.signature.to_bytes())
+        {
             let preserved_origin = self.origin_supplier.clone();
             let preserved_invited = self.invited_by.clone();
             let preserved_ledger = std::mem::take(&mut self.ledger);
@@ -265,6 +936,7 @@ impl UserContractState {
             self.name = other.name;
             self.current_supplier = other.current_supplier;
             self.toll_rates = other.toll_rates;
+            self.seq = other.seq;
             self.updated_at = other.updated_at;
             self.signature = other.signature;
 
@@ -288,12 +960,27 @@ impl UserContractState {
         self.next_tx_id = self.ledger.iter().map(|tx| tx.id).max().unwrap_or(0) + 1;
     }
 
+    /// Compact [`ContractMetrics`] for monitors; `state_bytes` is the
+    /// serialized state's size.
+    pub fn metrics(&self, state_bytes: usize) -> ContractMetrics {
+        ContractMetrics::new(state_bytes)
+            .count("ledger", self.ledger.len())
+            .count("checkpointed", self.checkpoint_tx_count as usize)
+            .count("mint_records", self.mint_records.len())
+            .count("settlement_statements", self.settlement_statements.len())
+            .count("vouchers", self.vouchers.len())
+            .count("pruned_lightning_hashes", self.pruned_lightning_hashes.len())
+            .updated("state", Some(self.updated_at))
+            .updated("checkpoint", self.checkpoint_at)
+    }
+
     /// Produce a summary for the delta sync protocol.
     pub fn summarize(&self) -> UserContractSummary {
         UserContractSummary {
             updated_at: Some(self.updated_at),
             ledger_len: self.ledger.len(),
             checkpoint_tx_count: self.checkpoint_tx_count,
+            seq: self.seq,
             extra: Default::default(),
         }
     }
@@ -303,6 +990,7 @@ impl UserContractState {
         match summary.updated_at {
             Some(ts)
                 if self.updated_at <= ts
+                    && self.seq <= summary.seq
                     && self.ledger.len() <= summary.ledger_len
                     && self.checkpoint_tx_count <= summary.checkpoint_tx_count =>
             {
@@ -315,13 +1003,15 @@ impl UserContractState {
     /// Perform a checkpoint: fold current ledger into checkpoint_balance and prune old entries.
     ///
     /// Keeps the last `keep_recent` entries for display. `now` is the checkpoint timestamp.
+    /// The pruned entries are recorded in `checkpoint_proof`, whose signature the
+    /// caller must then set over [`checkpoint_signable_bytes`](Self::checkpoint_signable_bytes).
     /// Returns the number of pruned entries.
     pub fn checkpoint(&mut self, keep_recent: usize, now: DateTime<Utc>) -> usize {
         if self.ledger.is_empty() {
             return 0;
         }
-
-        let new_balance = self.derive_balance();
+        // The pruned prefix must be the canonical one for `through` to cover it
+        sort_ledger(&mut self.ledger);
 
         // Extract lightning hashes from entries that will be pruned
         let prune_count = self.ledger.len().saturating_sub(keep_recent);
@@ -330,23 +1020,61 @@ impl UserContractState {
                 self.pruned_lightning_hashes.insert(hash.clone());
             }
         }
+        // Earlier folds are covered by where they ended; a legacy proof has no
+        // such position, so its list carries over once more
+        let previous = self.checkpoint_proof.take();
+        let earlier_through = previous.as_ref().and_then(|p| p.through.clone());
+        // Redemptions and settlements stay on record once their debits are
+        // pruned, so a voucher or an order's escrow can't be paid out twice,
+        // and so do voucher escrows, so a funded voucher stays redeemable
+        let mut voucher_redeemers = previous.as_ref().map(|p| p.voucher_redeemers.clone()).unwrap_or_default();
+        let mut voucher_escrows = previous.as_ref().map(|p| p.voucher_escrows.clone()).unwrap_or_default();
+        let mut settled_orders = previous.as_ref().map(|p| p.settled_orders.clone()).unwrap_or_default();
+        for tx in self.ledger.iter().take(prune_count) {
+            match tx.kind {
+                TransactionKind::Debit => {
+                    if let Some(id) = tx.tx_ref.strip_prefix(VOUCHER_TX_PREFIX) {
+                        voucher_redeemers.entry(id.to_string()).or_insert_with(|| tx.receiver.clone());
+                    }
+                    if let Some(order_id) = tx.description.strip_prefix(SETTLEMENT_DESCRIPTION_PREFIX) {
+                        settled_orders.insert(order_id.to_string());
+                    }
+                }
+                TransactionKind::Credit => {
+                    if let Some(id) = tx.tx_ref.strip_prefix(VOUCHER_ESCROW_TX_PREFIX) {
+                        voucher_escrows.entry(id.to_string()).or_insert(tx.amount);
+                    }
+                }
+            }
+        }
+        let mut folded = match previous {
+            Some(p) if p.through.is_none() => p.folded,
+            _ => BTreeSet::new(),
+        };
+        folded.extend(self.ledger.iter().take(prune_count).map(folded_entry_hash));
+        let through = self.ledger[..prune_count].last().map(LedgerPosition::of).max(earlier_through.clone());
+        self.checkpoint_proof = Some(LedgerCheckpoint {
+            folded,
+            through,
+            earlier_through,
+            voucher_redeemers,
+            voucher_escrows,
+            settled_orders,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        });
+
+        // The checkpoint_balance now covers everything up to (but not including)
+        // the remaining ledger entries. Folding the pruned prefix with the same
+        // rules as derive_balance keeps conflicting debits out of it.
+        let (prefix_balance, _) = fold_ledger(self.checkpoint_balance, &self.ledger[..prune_count]);
 
         // Keep only the last `keep_recent` entries
         if prune_count > 0 {
             self.ledger = self.ledger.split_off(prune_count);
         }
 
-        // Update checkpoint fields: the checkpoint_balance now covers everything
-        // up to (but not including) the remaining ledger entries.
-        // So checkpoint_balance = new_balance - contribution_of_remaining_entries
-        let remaining_contribution =
-            self.ledger
-                .iter()
-                .fold(0i64, |acc, tx| match tx.kind {
-                    TransactionKind::Credit => acc + tx.amount as i64,
-                    TransactionKind::Debit => acc - tx.amount as i64,
-                });
-        self.checkpoint_balance = (new_balance as i64 - remaining_contribution) as u64;
+        self.checkpoint_balance = prefix_balance;
         self.checkpoint_tx_count += prune_count as u64;
         self.checkpoint_at = Some(now);
 
@@ -355,6 +1083,124 @@ impl UserContractState {
 
         prune_count
     }
+
+    /// Check the state against the size limits in [`crate::limits`].
+    ///
+    /// A ledger past [`MAX_LEDGER_ENTRIES`] is rejected until the owner
+    /// checkpoints it.
+    pub fn check_limits(&self) -> Result<(), LimitError> {
+        check_len("user name", &self.name, MAX_NAME_LEN)?;
+        check_len("origin supplier", &self.origin_supplier, MAX_NAME_LEN)?;
+        check_len("current supplier", &self.current_supplier, MAX_NAME_LEN)?;
+        check_len("invited by", &self.invited_by, MAX_NAME_LEN)?;
+        if self.ledger.len() > MAX_LEDGER_ENTRIES {
+            return Err(LimitError::LedgerNeedsCompaction {
+                entries: self.ledger.len(),
+                max: MAX_LEDGER_ENTRIES,
+            });
+        }
+        for tx in &self.ledger {
+            check_len("transaction description", &tx.description, MAX_TX_DESCRIPTION_LEN)?;
+            check_opt_len("transaction device", tx.device.as_deref(), MAX_NAME_LEN)?;
+        }
+        for record in &self.mint_records {
+            check_len("mint reason", &record.reason, MAX_TX_DESCRIPTION_LEN)?;
+        }
+        check_count("settlement statements", self.settlement_statements.len(), MAX_SETTLEMENT_STATEMENTS)?;
+        for voucher in &self.vouchers {
+            check_len("voucher issuer name", &voucher.issuer_name, MAX_NAME_LEN)?;
+            check_len("voucher note", &voucher.note, MAX_TX_DESCRIPTION_LEN)?;
+        }
+        for statement in &self.settlement_statements {
+            check_count("settled orders", statement.orders.len(), MAX_ORDERS_PER_STOREFRONT)?;
+        }
+        Ok(())
+    }
+
+    /// Check an update's timestamps against `now`, the contract host's clock;
+    /// see [`crate::timestamps`]. Ledger entries with unparseable timestamps
+    /// are left to the ledger checks.
+    pub fn check_timestamps(&self, now: DateTime<Utc>) -> Result<(), TimestampError> {
+        check_not_future("user contract updated_at", self.updated_at, now)?;
+        check_opt_not_future("checkpoint_at", self.checkpoint_at, now)?;
+        for tx in &self.ledger {
+            if let Ok(at) = DateTime::parse_from_rfc3339(&tx.timestamp) {
+                check_not_future("transaction timestamp", at.with_timezone(&Utc), now)?;
+            }
+        }
+        for record in &self.mint_records {
+            check_not_future("mint minted_at", record.minted_at, now)?;
+        }
+        for statement in &self.settlement_statements {
+            check_not_future("statement issued_at", statement.issued_at, now)?;
+        }
+        for voucher in &self.vouchers {
+            check_not_future("voucher issued_at", voucher.issued_at, now)?;
+        }
+        Ok(())
+    }
+
+    /// Check that `update` doesn't move `updated_at` back while advancing the
+    /// write sequence, and, when it is built on this state's checkpoint,
+    /// doesn't add a ledger entry dated before it (see
+    /// [`LedgerCheckpoint::is_late`]). An update from a replica that hasn't
+    /// adopted the checkpoint still holds the entries it folded, so those
+    /// are left to the merge.
+    pub fn check_timestamp_progress(&self, update: &UserContractState) -> Result<(), TimestampError> {
+        if update.seq > self.seq {
+            check_not_rewound("user contract updated_at", self.updated_at, update.updated_at)?;
+        }
+        let Some(proof) = self.checkpoint_proof.as_ref().filter(|_| update.checkpoint_tx_count >= self.checkpoint_tx_count)
+        else {
+            return Ok(());
+        };
+        let known: HashSet<(&str, &TransactionKind)> =
+            self.ledger.iter().map(|tx| (tx.tx_ref.as_str(), &tx.kind)).collect();
+        let late = update
+            .ledger
+            .iter()
+            .find(|tx| !known.contains(&(tx.tx_ref.as_str(), &tx.kind)) && proof.is_late(tx));
+        if let Some(tx) = late {
+            let earliest = proof.earlier_through.as_ref().and_then(|p| parse_timestamp(&p.timestamp));
+            return Err(TimestampError::OutOfOrder {
+                field: "transaction timestamp",
+                at: parse_timestamp(&tx.timestamp).unwrap_or(DateTime::<Utc>::MIN_UTC),
+                after: "ledger checkpoint",
+                earliest: earliest.or(self.checkpoint_at).unwrap_or(DateTime::<Utc>::MIN_UTC),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A ledger timestamp as UTC, if it's RFC 3339.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|at| at.with_timezone(&Utc))
+}
+
+/// Sort ledger entries canonically: by timestamp, then credits before debits,
+/// then by tx_ref.
+fn sort_ledger(ledger: &mut [WalletTransaction]) {
+    ledger.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.kind.cmp(&b.kind))
+            .then_with(|| a.tx_ref.cmp(&b.tx_ref))
+    });
+}
+
+/// Fold ledger entries onto `opening`, returning the closing balance and the
+/// indices of debits skipped for overdrawing.
+fn fold_ledger(opening: u64, ledger: &[WalletTransaction]) -> (u64, Vec<usize>) {
+    let mut balance = opening;
+    let mut conflicts = Vec::new();
+    for (idx, tx) in ledger.iter().enumerate() {
+        match apply_transaction(balance, tx) {
+            Some(next) => balance = next,
+            None => conflicts.push(idx),
+        }
+    }
+    (balance, conflicts)
 }
 
 #[derive(Serialize)]
@@ -370,9 +1216,39 @@ struct SignableUserContract<'a> {
     checkpoint_balance: u64,
     checkpoint_tx_count: u64,
     checkpoint_at: &'a Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "is_zero")]
+    seq: u64,
     updated_at: &'a DateTime<Utc>,
 }
 
+#[derive(Serialize)]
+struct SignableCheckpoint<'a> {
+    owner: &'a UserId,
+    checkpoint_balance: u64,
+    checkpoint_tx_count: u64,
+    checkpoint_at: &'a Option<DateTime<Utc>>,
+    folded: Option<&'a BTreeSet<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    through: Option<&'a LedgerPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    earlier_through: Option<&'a LedgerPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    voucher_redeemers: Option<&'a BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    voucher_escrows: Option<&'a BTreeMap<String, u64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    settled_orders: Option<&'a BTreeSet<String>>,
+}
+
+#[derive(Serialize)]
+struct SignableMint<'a> {
+    domain: &'static str,
+    id: &'a str,
+    amount: u64,
+    reason: &'a str,
+    minted_at: &'a DateTime<Utc>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -392,6 +1268,11 @@ mod tests {
             checkpoint_tx_count: 0,
             checkpoint_at: None,
             pruned_lightning_hashes: HashSet::new(),
+            checkpoint_proof: None,
+            spending_key_debits: Default::default(),
+            mint_records: Vec::new(),
+            settlement_statements: Vec::new(),
+            vouchers: Vec::new(),
             ledger: vec![WalletTransaction {
                 id: 0,
                 kind: TransactionKind::Credit,
@@ -402,9 +1283,13 @@ mod tests {
                 tx_ref: "root:1000:42".into(),
                 timestamp: "2026-01-01T00:00:00.000Z".into(),
                 lightning_payment_hash: None,
+                payer: None,
+                provenance: None,
+                device: None,
                 extra: Default::default(),
             }],
             next_tx_id: 1,
+            seq: 0,
             updated_at,
             signature: Signature::from_bytes(&[0u8; 64]),
             extra: Default::default(),
@@ -424,6 +1309,29 @@ mod tests {
         assert_eq!(state.balance_curds, 10_000);
     }
 
+    #[test]
+    fn merge_higher_seq_wins_over_later_timestamp() {
+        let t1 = Utc::now() - chrono::Duration::hours(1);
+        let t2 = Utc::now();
+        let mut state = dummy_state(t1);
+        state.seq = 3;
+        state.current_supplier = "Emma".into();
+        // A replayed state from an earlier write, relayed with a skewed clock
+        let mut replayed = dummy_state(t2);
+        replayed.seq = 2;
+        state.merge(replayed);
+        assert_eq!(state.current_supplier, "Emma");
+        assert_eq!(state.seq, 3);
+
+        let mut next = dummy_state(t1);
+        next.seq = 3;
+        next.touch(t1);
+        next.current_supplier = "Iris".into();
+        state.merge(next);
+        assert_eq!(state.current_supplier, "Iris");
+        assert_eq!(state.updated_at, t1);
+    }
+
     #[test]
     fn merge_preserves_origin_supplier() {
         let t1 = Utc::now() - chrono::Duration::hours(1);
@@ -463,6 +1371,9 @@ mod tests {
             tx_ref: "alice:2000:99".into(),
             timestamp: "2026-01-01T00:01:00.000Z".into(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         });
         state.merge(older);
@@ -498,6 +1409,9 @@ mod tests {
             tx_ref: "test:1:1".into(),
             timestamp: "2026-01-02T00:00:00.000Z".into(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         });
         assert_eq!(state.derive_balance(), 9_500);
@@ -506,20 +1420,13 @@ mod tests {
     #[test]
     fn validate_update_credit_only_accepted_without_sig() {
         let state = dummy_state(Utc::now());
+        let payer = SigningKey::from_bytes(&[4u8; 32]);
         let mut update = state.clone();
-        // Add a credit entry to the update
-        update.ledger.push(WalletTransaction {
-            id: 1,
-            kind: TransactionKind::Credit,
-            amount: 500,
-            description: "Third-party credit".into(),
-            sender: "Bob".into(),
-            receiver: "Alice".into(),
-            tx_ref: "bob:1234:1".into(),
-            timestamp: "2026-01-02T00:00:00.000Z".into(),
-            lightning_payment_hash: None,
-            extra: Default::default(),
-        });
+        // Add a credit entry to the update, stamped by its payer
+        let mut credit = make_tx(1, TransactionKind::Credit, 500, "bob:1234:1");
+        credit.payer = Some(UserId(payer.verifying_key()));
+        credit.provenance = Some(credit_stamp(&payer, &credit));
+        update.ledger.push(credit);
         update.signature = Signature::from_bytes(&[0u8; 64]); // invalid sig
 
         let key = SigningKey::from_bytes(&[3u8; 32]);
@@ -530,6 +1437,107 @@ mod tests {
         );
     }
 
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn root_credits_need_the_root_stamp() {
+        use ed25519_dalek::Signer;
+        let root = SigningKey::from_bytes(&[7u8; 32]);
+        let forger = SigningKey::from_bytes(&[8u8; 32]);
+        let mut credit = make_tx(1, TransactionKind::Credit, 1_000_000, "root:1234:1");
+        credit.sender = crate::identity::ROOT_USER_NAME.into();
+
+        // Unstamped, or stamped by someone claiming to be root
+        assert!(!credit_vouched(&credit, Some(&root.verifying_key())));
+        credit.payer = Some(UserId(forger.verifying_key()));
+        credit.provenance = Some(credit_stamp(&forger, &credit));
+        assert!(!credit_vouched(&credit, Some(&root.verifying_key())));
+
+        // Root's own stamp, as long as the contract knows root's key
+        credit.payer = Some(UserId(root.verifying_key()));
+        credit.provenance = Some(credit_stamp(&root, &credit));
+        assert!(credit_vouched(&credit, Some(&root.verifying_key())));
+        assert!(!credit_vouched(&credit, None));
+
+        // Mints and voucher payouts are checked against their records instead
+        let mut payout = make_tx(2, TransactionKind::Credit, 50, "voucher:v-1");
+        payout.sender = crate::identity::ROOT_USER_NAME.into();
+        assert!(credit_vouched(&payout, None));
+
+        // The owner's signature doesn't let an unstamped credit in either
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let state = dummy_state(Utc::now());
+        let mut update = state.clone();
+        update.ledger.push(make_tx(3, TransactionKind::Credit, 1_000_000, "self:1:1"));
+        update.signature = owner.sign(&update.signable_bytes());
+        assert!(!state.validate_update(&update, &owner.verifying_key()));
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn stamped_credit_must_verify() {
+        let state = dummy_state(Utc::now());
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let payer = SigningKey::from_bytes(&[4u8; 32]);
+        let params = UserContractParameters { owner: owner.verifying_key(), spending_key: None };
+
+        let mut credit = make_tx(1, TransactionKind::Credit, 250, "bob:5678:1");
+        credit.payer = Some(UserId(payer.verifying_key()));
+        credit.provenance = Some(credit_stamp(&payer, &credit));
+        let mut update = state.clone();
+        update.ledger.push(credit.clone());
+        assert!(state.validate_update_for(&update, &params, Utc::now()));
+
+        // Inflating the amount breaks the payer's stamp
+        update.ledger.last_mut().unwrap().amount = 25_000;
+        assert!(!state.validate_update_for(&update, &params, Utc::now()));
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn peer_credits_need_their_payers_stamp() {
+        let state = dummy_state(Utc::now());
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let payer = SigningKey::from_bytes(&[4u8; 32]);
+        let stranger = SigningKey::from_bytes(&[5u8; 32]);
+        let params = UserContractParameters { owner: owner.verifying_key(), spending_key: None };
+        let with_credit = |credit: &WalletTransaction| {
+            let mut update = state.clone();
+            update.ledger.push(credit.clone());
+            update
+        };
+
+        // Unstamped
+        let mut credit = make_tx(1, TransactionKind::Credit, 250, "bob:5678:1");
+        assert!(!state.validate_update_for(&with_credit(&credit), &params, Utc::now()));
+
+        // Re-stamped by a stranger, naming the payer or not
+        credit.payer = Some(UserId(payer.verifying_key()));
+        credit.provenance = Some(credit_stamp(&stranger, &credit));
+        assert!(!state.validate_update_for(&with_credit(&credit), &params, Utc::now()));
+        credit.payer = None;
+        credit.provenance = Some(credit_stamp(&stranger, &credit));
+        assert!(!state.validate_update_for(&with_credit(&credit), &params, Utc::now()));
+
+        // The payer's own stamp
+        credit.payer = Some(UserId(payer.verifying_key()));
+        credit.provenance = Some(credit_stamp(&payer, &credit));
+        assert!(state.validate_update_for(&with_credit(&credit), &params, Utc::now()));
+    }
+
+    fn credit_stamp(key: &SigningKey, credit: &WalletTransaction) -> crate::provenance::WriterStamp {
+        use crate::provenance::{StateSection, WriterStamp};
+        use ed25519_dalek::Signer;
+
+        let written_at = Utc::now();
+        let msg = WriterStamp::signable_bytes(StateSection::LedgerCredit, &written_at, &credit.credit_provenance_bytes());
+        WriterStamp {
+            last_writer: key.verifying_key(),
+            written_at,
+            signature: key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+
     #[cfg(not(feature = "dev"))]
     #[test]
     fn validate_update_debit_rejected_without_sig() {
@@ -546,6 +1554,9 @@ mod tests {
             tx_ref: "eve:1234:1".into(),
             timestamp: "2026-01-02T00:00:00.000Z".into(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         });
         update.signature = Signature::from_bytes(&[0u8; 64]); // invalid sig
@@ -572,6 +1583,132 @@ mod tests {
         );
     }
 
+    fn debit(tx_ref: &str, amount: u64, timestamp: &str) -> WalletTransaction {
+        WalletTransaction {
+            id: 1,
+            kind: TransactionKind::Debit,
+            amount,
+            description: "Order deposit".into(),
+            sender: "Alice".into(),
+            receiver: "__cream_root__".into(),
+            tx_ref: tx_ref.into(),
+            timestamp: timestamp.into(),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+
+    /// `state` plus `debits` made with the spending key, signed by it.
+    fn spend(state: &UserContractState, key: &SigningKey, debits: &[WalletTransaction]) -> UserContractState {
+        use ed25519_dalek::Signer;
+        let mut update = state.clone();
+        for tx in debits {
+            update.ledger.push(tx.clone());
+            update.spending_key_debits.insert(tx.tx_ref.clone());
+        }
+        update.signature = key.sign(&update.signable_bytes());
+        update
+    }
+
+    #[test]
+    fn spending_key_debits_enforce_limits() {
+        let spend_key = SigningKey::from_bytes(&[9u8; 32]);
+        let limits = SpendingKeyLimits {
+            key: spend_key.verifying_key(),
+            max_per_tx: 500,
+            max_per_day: 800,
+        };
+        let now = Utc::now();
+        let state = dummy_state(now);
+
+        let ok = spend(&state, &spend_key, &[debit("kid:1", 400, "2026-01-02T09:00:00.000Z")]);
+        assert!(state.validate_spending_key_update(&ok, &limits, now));
+
+        let too_big = spend(&state, &spend_key, &[debit("kid:1", 600, "2026-01-02T09:00:00.000Z")]);
+        assert!(!state.validate_spending_key_update(&too_big, &limits, now));
+
+        // Two debits on one day over the daily budget
+        let mut after_first = state.clone();
+        after_first.merge(ok.clone());
+        let second = spend(&after_first, &spend_key, &[debit("kid:2", 450, "2026-01-02T15:00:00.000Z")]);
+        assert!(!after_first.validate_spending_key_update(&second, &limits, now));
+        // ...but fine the next day
+        let next_day = spend(&after_first, &spend_key, &[debit("kid:2", 450, "2026-01-03T08:00:00.000Z")]);
+        assert!(after_first.validate_spending_key_update(&next_day, &limits, now));
+
+        // Backdating to an empty day is rejected
+        let backdated = spend(&after_first, &spend_key, &[debit("kid:3", 450, "2026-01-01T08:00:00.000Z")]);
+        assert!(!after_first.validate_spending_key_update(&backdated, &limits, now));
+    }
+
+    #[test]
+    fn spending_key_debits_cannot_invent_a_fresh_day() {
+        let spend_key = SigningKey::from_bytes(&[9u8; 32]);
+        let limits = SpendingKeyLimits {
+            key: spend_key.verifying_key(),
+            max_per_tx: 500,
+            max_per_day: 800,
+        };
+        let now = Utc::now();
+        let state = dummy_state(now);
+        let today = now.to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let mut spent = state.clone();
+        spent.merge(spend(&state, &spend_key, &[debit("kid:1", 500, &today)]));
+
+        // Post-dating into tomorrow's empty budget is rejected...
+        let tomorrow = (now + chrono::Duration::days(1)).to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let postdated = spend(&spent, &spend_key, &[debit("kid:2", 500, &tomorrow)]);
+        assert!(!spent.validate_spending_key_update(&postdated, &limits, now));
+
+        // ...as is a timestamp that sorts after today but names no real day
+        let garbage = spend(&spent, &spend_key, &[debit("kid:2", 500, "zzzz-99-99")]);
+        assert!(!spent.validate_spending_key_update(&garbage, &limits, now));
+
+        // A clock a minute ahead is tolerated
+        let skewed = (now + chrono::Duration::minutes(1)).to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        let ahead = spend(&spent, &spend_key, &[debit("kid:2", 200, &skewed)]);
+        assert!(spent.validate_spending_key_update(&ahead, &limits, now));
+    }
+
+    #[test]
+    fn spending_key_cannot_change_metadata_or_hide_debits() {
+        use ed25519_dalek::Signer;
+        let spend_key = SigningKey::from_bytes(&[9u8; 32]);
+        let limits = SpendingKeyLimits {
+            key: spend_key.verifying_key(),
+            max_per_tx: 500,
+            max_per_day: 800,
+        };
+        let now = Utc::now();
+        let state = dummy_state(now);
+
+        let mut renamed = spend(&state, &spend_key, &[]);
+        renamed.current_supplier = "Emma".into();
+        renamed.signature = spend_key.sign(&renamed.signable_bytes());
+        assert!(!state.validate_spending_key_update(&renamed, &limits, now));
+
+        // A debit not declared as a spending-key debit escapes the budget: rejected
+        let mut undeclared = state.clone();
+        undeclared.ledger.push(debit("kid:1", 100, "2026-01-02T09:00:00.000Z"));
+        undeclared.signature = spend_key.sign(&undeclared.signable_bytes());
+        assert!(!state.validate_spending_key_update(&undeclared, &limits, now));
+    }
+
+    #[test]
+    fn spending_key_parameters_are_optional_on_the_wire() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let params = UserContractParameters {
+            owner: key.verifying_key(),
+            spending_key: None,
+        };
+        // Contracts without a spending key keep their existing parameter bytes
+        let json = serde_json::to_value(&params).unwrap();
+        assert_eq!(json.as_object().unwrap().len(), 1);
+    }
+
     #[test]
     fn delta_returns_some_when_newer() {
         let state = dummy_state(Utc::now());
@@ -579,6 +1716,7 @@ mod tests {
             updated_at: None,
             ledger_len: 0,
             checkpoint_tx_count: 0,
+            seq: 0,
             extra: Default::default(),
         };
         assert!(state.delta(&summary).is_some());
@@ -592,6 +1730,7 @@ mod tests {
             updated_at: Some(now),
             ledger_len: 1,
             checkpoint_tx_count: 0,
+            seq: 0,
             extra: Default::default(),
         };
         assert!(state.delta(&summary).is_none());
@@ -605,6 +1744,7 @@ mod tests {
             updated_at: Some(now),
             ledger_len: 0, // summary has fewer ledger entries
             checkpoint_tx_count: 0,
+            seq: 0,
             extra: Default::default(),
         };
         assert!(state.delta(&summary).is_some());
@@ -619,6 +1759,7 @@ mod tests {
             updated_at: Some(now),
             ledger_len: 1,
             checkpoint_tx_count: 0,
+            seq: 0,
             extra: Default::default(),
         };
         assert!(state.delta(&summary).is_some());
@@ -637,10 +1778,27 @@ mod tests {
             tx_ref: tx_ref.into(),
             timestamp: format!("2026-01-01T00:{:02}:00.000Z", id),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         }
     }
 
+    #[test]
+    fn oversized_ledger_needs_checkpoint() {
+        let mut state = dummy_state(Utc::now());
+        for i in 1..=MAX_LEDGER_ENTRIES as u32 {
+            state.ledger.push(make_tx(i, TransactionKind::Credit, 1, &format!("ref-{i}")));
+        }
+        assert!(matches!(
+            state.check_limits(),
+            Err(LimitError::LedgerNeedsCompaction { .. })
+        ));
+        state.checkpoint(PRUNE_KEEP_RECENT, Utc::now());
+        assert!(state.check_limits().is_ok());
+    }
+
     #[test]
     fn checkpoint_preserves_balance() {
         let mut state = dummy_state(Utc::now());
@@ -715,6 +1873,221 @@ mod tests {
         assert_eq!(state.checkpoint_at, Some(t3));
     }
 
+    #[test]
+    fn merge_adopting_checkpoint_prunes_folded_entries() {
+        let t1 = Utc::now() - chrono::Duration::hours(1);
+        let mut stale = dummy_state(t1);
+        for i in 1..5 {
+            stale.ledger.push(make_tx(i, TransactionKind::Credit, 100, &format!("ref-{i}")));
+        }
+        stale.balance_curds = stale.derive_balance();
+
+        let mut compacted = stale.clone();
+        compacted.checkpoint(1, Utc::now());
+        compacted.updated_at = Utc::now();
+
+        // A late credit the compacting replica never saw survives the merge
+        stale.ledger.push(make_tx(5, TransactionKind::Credit, 7, "late"));
+        stale.merge(compacted.clone());
+
+        assert_eq!(stale.checkpoint_balance, compacted.checkpoint_balance);
+        assert_eq!(stale.ledger.len(), 2);
+        assert!(stale.ledger.iter().any(|tx| tx.tx_ref == "late"));
+        assert_eq!(stale.balance_curds, compacted.balance_curds + 7);
+    }
+
+    #[test]
+    fn checkpoint_proofs_list_only_their_own_entries() {
+        let t1 = Utc::now() - chrono::Duration::hours(1);
+        let mut stale = dummy_state(t1);
+        for i in 1..=6 {
+            stale.ledger.push(make_tx(i, TransactionKind::Credit, 100, &format!("ref-{i}")));
+        }
+        stale.balance_curds = stale.derive_balance();
+        let balance = stale.balance_curds;
+
+        let mut compacted = stale.clone();
+        compacted.checkpoint(4, Utc::now());
+        compacted.checkpoint(1, Utc::now());
+        let proof = compacted.checkpoint_proof.clone().unwrap();
+        assert_eq!(proof.folded.len(), 3);
+        assert!(proof.earlier_through < proof.through);
+
+        // A replica that missed both checkpoints still drops everything folded
+        stale.merge(compacted.clone());
+        assert_eq!(stale.ledger.len(), 1);
+        assert_eq!(stale.balance_curds, balance);
+    }
+
+    #[test]
+    fn late_credits_before_a_checkpoint_are_refused_not_dropped() {
+        let mut state = dummy_state(Utc::now());
+        for i in 1..=6 {
+            state.ledger.push(make_tx(i * 2, TransactionKind::Credit, 100, &format!("ref-{i}")));
+        }
+        state.checkpoint(4, Utc::now());
+        state.checkpoint(1, Utc::now());
+
+        // A credit dated among the entries the earlier checkpoint folded
+        let mut late = state.clone();
+        late.ledger.push(make_tx(3, TransactionKind::Credit, 50, "late"));
+        assert!(state.checkpoint_proof.as_ref().unwrap().is_late(&late.ledger[1]));
+        assert!(matches!(
+            state.check_timestamp_progress(&late),
+            Err(TimestampError::OutOfOrder { after: "ledger checkpoint", .. })
+        ));
+
+        // Dated after it, the same credit counts on top of the checkpoint
+        let mut redated = state.clone();
+        redated.ledger.push(make_tx(13, TransactionKind::Credit, 50, "late"));
+        assert!(state.check_timestamp_progress(&redated).is_ok());
+        let balance = state.derive_balance();
+        state.merge(redated);
+        assert_eq!(state.derive_balance(), balance + 50);
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn checkpoint_proof_must_be_owner_signed() {
+        use ed25519_dalek::Signer;
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let mut state = dummy_state(Utc::now());
+        state.ledger.push(make_tx(1, TransactionKind::Credit, 100, "ref-1"));
+        state.checkpoint(0, Utc::now());
+        assert!(!state.checkpoint_valid(&key.verifying_key()));
+
+        let signature = key.sign(&state.checkpoint_signable_bytes());
+        state.checkpoint_proof.as_mut().unwrap().signature = signature;
+        assert!(state.checkpoint_valid(&key.verifying_key()));
+
+        state.checkpoint_balance += 1_000;
+        assert!(!state.checkpoint_valid(&key.verifying_key()));
+    }
+
+    fn mint(id: &str, amount: u64, key: &SigningKey) -> MintRecord {
+        use ed25519_dalek::Signer;
+        let mut record = MintRecord {
+            id: id.into(),
+            amount,
+            reason: "Reserve top-up".into(),
+            minted_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        record.signature = key.sign(&record.signable_bytes());
+        record
+    }
+
+    #[test]
+    fn mint_records_merge_and_book_credits() {
+        let key = SigningKey::from_bytes(&[3u8; 32]);
+        let mut state = dummy_state(Utc::now());
+        let mut update = state.clone();
+        let record = mint("m-1", 5_000, &key);
+        update.ledger.push(record.credit(1, "__cream_root__"));
+        update.mint_records.push(record.clone());
+        let params = UserContractParameters { owner: key.verifying_key(), spending_key: None };
+        assert!(state.validate_update_for(&update, &params, Utc::now()));
+
+        state.merge(update.clone());
+        state.merge(update);
+        assert_eq!(state.mint_records, vec![record]);
+        assert_eq!(state.total_minted(), 5_000);
+        assert_eq!(state.balance_curds, 15_000);
+
+        // Records outlive their credits in a checkpoint
+        state.checkpoint(0, Utc::now());
+        assert!(state.ledger.is_empty());
+        assert_eq!(state.total_minted(), 5_000);
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn mint_needs_owner_signed_record() {
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let params = UserContractParameters { owner: owner.verifying_key(), spending_key: None };
+        let state = dummy_state(Utc::now());
+
+        // A mint credit with no record is rejected
+        let record = mint("m-1", 5_000, &owner);
+        let mut update = state.clone();
+        update.ledger.push(record.credit(1, "__cream_root__"));
+        assert!(!state.validate_update_for(&update, &params, Utc::now()));
+
+        // So is a record signed by anyone but the owner
+        let forged = mint("m-1", 5_000, &SigningKey::from_bytes(&[4u8; 32]));
+        update.mint_records.push(forged);
+        assert!(!state.validate_update_for(&update, &params, Utc::now()));
+
+        // Or one that doesn't cover the credited amount
+        update.mint_records = vec![record.clone()];
+        update.ledger.last_mut().unwrap().amount = 50_000;
+        assert!(!state.validate_update_for(&update, &params, Utc::now()));
+
+        update.ledger.last_mut().unwrap().amount = record.amount;
+        assert!(state.validate_update_for(&update, &params, Utc::now()));
+    }
+
+    fn voucher(id: &str, amount: u64, key: &SigningKey) -> Voucher {
+        use ed25519_dalek::Signer;
+        let mut voucher = Voucher {
+            id: id.into(),
+            amount,
+            issuer: UserId(key.verifying_key()),
+            issuer_name: "Gary".into(),
+            note: String::new(),
+            issued_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        voucher.signature = key.sign(&voucher.signable_bytes());
+        voucher
+    }
+
+    #[test]
+    fn vouchers_back_their_credits_and_redeem_once() {
+        let gary = SigningKey::from_bytes(&[4u8; 32]);
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let params = UserContractParameters { owner: owner.verifying_key(), spending_key: None };
+        let mut state = dummy_state(Utc::now());
+        let gift = voucher("v-1", 500, &gary);
+
+        // A voucher credit with no voucher behind it is rejected
+        let mut update = state.clone();
+        update.ledger.push(gift.credit(1, "Alice", "2026-02-01T00:00:00.000Z".into()));
+        assert!(!update.vouchers_valid());
+        update.vouchers.push(gift.clone());
+        assert!(state.validate_update_for(&update, &params, Utc::now()));
+
+        // Replaying the redemption, or a second copy of the voucher, changes nothing
+        state.merge(update.clone());
+        let mut replay = update.clone();
+        replay.ledger.push(gift.credit(2, "Alice", "2026-02-02T00:00:00.000Z".into()));
+        replay.vouchers.push(gift.clone());
+        assert!(!replay.vouchers_valid());
+        state.merge(update);
+        assert_eq!(state.vouchers, vec![gift.clone()]);
+        assert!(state.has_redeemed("v-1"));
+        assert_eq!(state.balance_curds, 10_500);
+
+        // Root pays each voucher out once, and only once it is funded
+        let mut root = dummy_state(Utc::now());
+        assert_eq!(gift.check_redeemable(&root), Err(crate::voucher::VoucherError::Unfunded));
+        root.ledger.push(make_tx(1, TransactionKind::Credit, 500, &gift.escrow_tx_ref()));
+        assert_eq!(gift.check_redeemable(&root), Ok(()));
+        // The escrow stays on record through successive checkpoints
+        root.checkpoint(0, Utc::now());
+        root.ledger.push(make_tx(2, TransactionKind::Credit, 100, "other"));
+        root.checkpoint(0, Utc::now());
+        assert_eq!(root.voucher_escrow("v-1"), Some(500));
+        assert_eq!(gift.check_redeemable(&root), Ok(()));
+        let dearer = voucher("v-1", 5_000, &gary);
+        assert_eq!(dearer.check_redeemable(&root), Err(crate::voucher::VoucherError::Unfunded));
+        root.ledger.push(make_tx(3, TransactionKind::Debit, 500, &gift.tx_ref()));
+        root.checkpoint(0, Utc::now());
+        assert_eq!(gift.check_redeemable(&root), Err(crate::voucher::VoucherError::AlreadyRedeemed));
+    }
+
     #[test]
     fn merge_pruned_lightning_hashes_union() {
         let t1 = Utc::now() - chrono::Duration::hours(1);
@@ -763,6 +2136,154 @@ mod tests {
         assert_eq!(state.derive_balance(), 15_000);
     }
 
+    // ─── Double-spend tests ─────────────────────────────────────────────────
+
+    #[test]
+    fn merge_concurrent_debits_cannot_overdraw() {
+        let t1 = Utc::now() - chrono::Duration::hours(1);
+        let t2 = Utc::now();
+
+        // Two devices each spend 6_000 of the same 10_000 balance
+        let mut device_a = dummy_state(t1);
+        device_a.ledger.push(make_tx(1, TransactionKind::Debit, 6_000, "spend:a"));
+        device_a.balance_curds = device_a.derive_balance();
+        let mut device_b = dummy_state(t2);
+        device_b.ledger.push(make_tx(2, TransactionKind::Debit, 6_000, "spend:b"));
+        device_b.balance_curds = device_b.derive_balance();
+        assert_eq!(device_a.balance_curds, 4_000);
+        assert_eq!(device_b.balance_curds, 4_000);
+
+        let mut merged_ab = device_a.clone();
+        merged_ab.merge(device_b.clone());
+        let mut merged_ba = device_b;
+        merged_ba.merge(device_a);
+
+        // Both debits stay in the ledger; the later one is marked conflicting
+        assert_eq!(merged_ab.ledger.len(), 3);
+        assert_eq!(merged_ab.balance_curds, 4_000);
+        let conflicts: Vec<&str> = merged_ab.conflicting_debits().iter().map(|tx| tx.tx_ref.as_str()).collect();
+        assert_eq!(conflicts, vec!["spend:b"]);
+
+        // Merge order doesn't matter
+        assert_eq!(merged_ba.balance_curds, 4_000);
+        let conflicts: Vec<&str> = merged_ba.conflicting_debits().iter().map(|tx| tx.tx_ref.as_str()).collect();
+        assert_eq!(conflicts, vec!["spend:b"]);
+    }
+
+    #[test]
+    fn updates_that_overdraw_are_refused() {
+        use ed25519_dalek::Signer;
+        let owner = SigningKey::from_bytes(&[3u8; 32]);
+        let params = UserContractParameters { owner: owner.verifying_key(), spending_key: None };
+        let signed = |mut state: UserContractState| {
+            state.balance_curds = state.derive_balance();
+            state.signature = owner.sign(&state.signable_bytes());
+            state
+        };
+        let now = Utc::now();
+        let mut stored = dummy_state(now);
+        stored.ledger.push(make_tx(1, TransactionKind::Debit, 6_000, "spend:a"));
+        let stored = signed(stored);
+
+        // Another device spent 6_000 of the same 10_000 without seeing spend:a
+        let mut other_device = dummy_state(now);
+        other_device.ledger.push(make_tx(2, TransactionKind::Debit, 6_000, "spend:b"));
+        let other_device = signed(other_device);
+        assert!(stored.overdrawn_by(&other_device));
+        assert!(!stored.validate_update_for(&other_device, &params, now));
+
+        // A debit the balance covers goes through
+        let mut covered = stored.clone();
+        covered.ledger.push(make_tx(2, TransactionKind::Debit, 4_000, "spend:b"));
+        let covered = signed(covered);
+        assert!(!stored.overdrawn_by(&covered));
+        assert!(stored.validate_update_for(&covered, &params, now));
+    }
+
+    #[test]
+    fn two_devices_writing_at_once_converge_and_are_told_apart() {
+        let at = Utc::now();
+        // Two logins of the same account write the same seq at the same instant
+        let mut laptop = dummy_state(at);
+        laptop.seq = 4;
+        laptop.current_supplier = "Gary".into();
+        laptop.signature = Signature::from_bytes(&[1u8; 64]);
+        laptop.ledger.push(WalletTransaction {
+            device: Some("laptop".into()),
+            ..debit("spend:laptop", 1_000, "2026-01-02T00:00:00.000Z")
+        });
+        let mut phone = dummy_state(at);
+        phone.seq = 4;
+        phone.current_supplier = "Emma".into();
+        phone.signature = Signature::from_bytes(&[2u8; 64]);
+        phone.ledger.push(WalletTransaction {
+            device: Some("phone".into()),
+            ..debit("spend:phone", 2_000, "2026-01-02T00:00:01.000Z")
+        });
+
+        let mut on_laptop = laptop.clone();
+        on_laptop.merge(phone.clone());
+        let mut on_phone = phone.clone();
+        on_phone.merge(laptop.clone());
+
+        // Both spends kept, and the same write wins the metadata either way
+        assert_eq!(on_laptop.ledger, on_phone.ledger);
+        assert_eq!(on_laptop.balance_curds, 7_000);
+        assert_eq!(on_laptop.current_supplier, "Emma");
+        assert_eq!(on_phone.current_supplier, "Emma");
+
+        let from_phone: Vec<&str> =
+            on_laptop.entries_from_other_devices("laptop").iter().map(|tx| tx.tx_ref.as_str()).collect();
+        assert_eq!(from_phone, vec!["spend:phone"]);
+        assert_eq!(
+            on_laptop.other_devices("laptop").into_iter().collect::<Vec<_>>(),
+            vec![("phone", "2026-01-02T00:00:01.000Z")]
+        );
+
+        // An entry one replica has untagged keeps its tag whichever way round
+        let mut untagged = laptop.clone();
+        untagged.ledger[1].device = None;
+        let mut merged = untagged.clone();
+        merged.merge(laptop.clone());
+        assert_eq!(merged.ledger[1].device.as_deref(), Some("laptop"));
+        let mut merged = laptop;
+        merged.merge(untagged);
+        assert_eq!(merged.ledger[1].device.as_deref(), Some("laptop"));
+    }
+
+    #[test]
+    fn conflicting_debit_resolves_after_later_credit_elsewhere() {
+        let mut state = dummy_state(Utc::now());
+        state.ledger.push(make_tx(1, TransactionKind::Debit, 8_000, "spend:1"));
+        state.ledger.push(make_tx(2, TransactionKind::Debit, 8_000, "spend:2"));
+        assert_eq!(state.derive_balance(), 2_000);
+        assert_eq!(state.conflicting_debits().len(), 1);
+
+        // A credit ordered before the second debit makes it affordable
+        let mut other = state.clone();
+        let mut credit = make_tx(3, TransactionKind::Credit, 6_000, "topup:1");
+        credit.timestamp = "2026-01-01T00:01:30.000Z".into();
+        other.ledger.push(credit);
+        state.merge(other);
+
+        assert!(state.conflicting_debits().is_empty());
+        assert_eq!(state.balance_curds, 0);
+    }
+
+    #[test]
+    fn checkpoint_excludes_conflicting_debits() {
+        let mut state = dummy_state(Utc::now());
+        state.ledger.push(make_tx(1, TransactionKind::Debit, 9_000, "spend:1"));
+        state.ledger.push(make_tx(2, TransactionKind::Debit, 9_000, "spend:2"));
+        state.ledger.push(make_tx(3, TransactionKind::Credit, 100, "tx:3"));
+        let expected = state.derive_balance();
+        assert_eq!(expected, 1_100);
+
+        state.checkpoint(1, Utc::now());
+        assert_eq!(state.checkpoint_balance, 1_000);
+        assert_eq!(state.derive_balance(), expected);
+    }
+
     #[test]
     fn double_checkpoint_accumulates() {
         let mut state = dummy_state(Utc::now());
@@ -0,0 +1,252 @@
+//! Gift and credit vouchers: a code worth a fixed amount of CURD, redeemable
+//! once.
+//!
+//! A supplier (or the root, for promotions) signs a [`Voucher`] and hands out
+//! its [`code`](Voucher::code). Issuing moves the voucher's value from the
+//! supplier into root's escrow under [`Voucher::escrow_tx_ref`]; redeeming
+//! pays it from root to the customer under [`Voucher::tx_ref`], and the
+//! customer's user contract keeps the signed voucher to back that credit.
+//!
+//! The redemption debit on root's ledger is keyed by the voucher id, so a
+//! second redemption merges away there, and it names the one contract the
+//! voucher paid out to; root's checkpoints keep that name once the debit is
+//! pruned. Each user contract refuses a voucher credit without a matching
+//! voucher and keeps one voucher per id, but can't see root: a credit for a
+//! voucher root paid to someone else is caught by
+//! [`crate::audit::audit_ledgers`].
+
+use std::fmt;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::Signature;
+#[cfg(not(feature = "dev"))]
+use ed25519_dalek::Verifier;
+use serde::{Deserialize, Serialize};
+
+use crate::identity::UserId;
+use crate::user_contract::UserContractState;
+use crate::wallet::{TransactionKind, WalletTransaction};
+
+/// `tx_ref` prefix of the credit paying a voucher out to its redeemer; the
+/// rest is the [`Voucher::id`].
+pub const VOUCHER_TX_PREFIX: &str = "voucher:";
+
+/// `tx_ref` prefix of the issuer's payment into root's escrow.
+pub const VOUCHER_ESCROW_TX_PREFIX: &str = "voucher-escrow:";
+
+/// Prefix of a voucher code, so a pasted code is recognisable.
+pub const VOUCHER_CODE_PREFIX: &str = "CREAM-V1-";
+
+/// A signed promise of `amount` CURD to whoever redeems it first.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Voucher {
+    /// Unique per voucher.
+    pub id: String,
+    /// CURD paid out on redemption.
+    pub amount: u64,
+    /// Who signed the voucher and funded it.
+    pub issuer: UserId,
+    /// Issuer's display name, shown to the redeemer.
+    pub issuer_name: String,
+    /// Message for the recipient (e.g. "Happy birthday!").
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    pub issued_at: DateTime<Utc>,
+    /// Issuer's signature over [`Voucher::signable_bytes`].
+    pub signature: Signature,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Why a voucher code can't be redeemed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoucherError {
+    /// Not a voucher code, or a damaged one.
+    Malformed,
+    /// The signature doesn't match the issuer.
+    BadSignature,
+    /// Issued for nothing.
+    ZeroAmount,
+    /// The issuer's escrow payment isn't on root's ledger.
+    Unfunded,
+    /// Root has already paid it out.
+    AlreadyRedeemed,
+}
+
+impl fmt::Display for VoucherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VoucherError::Malformed => write!(f, "not a valid voucher code"),
+            VoucherError::BadSignature => write!(f, "voucher signature does not match its issuer"),
+            VoucherError::ZeroAmount => write!(f, "voucher is worth nothing"),
+            VoucherError::Unfunded => write!(f, "voucher has not been funded by its issuer yet"),
+            VoucherError::AlreadyRedeemed => write!(f, "voucher has already been redeemed"),
+        }
+    }
+}
+
+impl std::error::Error for VoucherError {}
+
+impl Voucher {
+    /// Serialize the voucher (excluding signature) for signing/verification.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let signable = SignableVoucher {
+            domain: "cream-voucher-v1",
+            id: &self.id,
+            amount: self.amount,
+            issuer: &self.issuer,
+            issuer_name: &self.issuer_name,
+            note: &self.note,
+            issued_at: &self.issued_at,
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the voucher is signed by its issuer.
+    pub fn verify(&self) -> bool {
+        #[cfg(feature = "dev")]
+        {
+            // Dev mode skips signatures, like the rest of the user contract.
+            true
+        }
+        #[cfg(not(feature = "dev"))]
+        {
+            self.issuer.0.verify(&self.signable_bytes(), &self.signature).is_ok()
+        }
+    }
+
+    /// `tx_ref` of the redemption: root's debit and the redeemer's credit.
+    pub fn tx_ref(&self) -> String {
+        format!("{VOUCHER_TX_PREFIX}{}", self.id)
+    }
+
+    /// `tx_ref` of the issuer's payment into root's escrow.
+    pub fn escrow_tx_ref(&self) -> String {
+        format!("{VOUCHER_ESCROW_TX_PREFIX}{}", self.id)
+    }
+
+    /// Ledger description of the redemption.
+    pub fn description(&self) -> String {
+        format!("Voucher from {}", self.issuer_name)
+    }
+
+    /// The code to hand to the recipient: the signed voucher, base64url.
+    pub fn code(&self) -> String {
+        let json = serde_json::to_vec(self).expect("serialization should not fail");
+        format!("{VOUCHER_CODE_PREFIX}{}", BASE64.encode(json))
+    }
+
+    /// Decode and check a voucher code. Surrounding whitespace is ignored.
+    pub fn from_code(code: &str) -> Result<Voucher, VoucherError> {
+        let encoded = code.trim().strip_prefix(VOUCHER_CODE_PREFIX).ok_or(VoucherError::Malformed)?;
+        let json = BASE64.decode(encoded).map_err(|_| VoucherError::Malformed)?;
+        let voucher: Voucher = serde_json::from_slice(&json).map_err(|_| VoucherError::Malformed)?;
+        if voucher.amount == 0 {
+            return Err(VoucherError::ZeroAmount);
+        }
+        if !voucher.verify() {
+            return Err(VoucherError::BadSignature);
+        }
+        Ok(voucher)
+    }
+
+    /// Check `root`'s ledger before paying the voucher out: the issuer must
+    /// have escrowed its value (unless the root issued it), and root must not
+    /// have paid it already. Escrows and redemptions folded into any
+    /// checkpoint count; so does an escrow folded into the latest one by a
+    /// checkpoint from before escrows were recorded.
+    pub fn check_redeemable(&self, root: &UserContractState) -> Result<(), VoucherError> {
+        if root.voucher_redeemer(&self.id).is_some() || root.folded_entry(&self.tx_ref(), &TransactionKind::Debit) {
+            return Err(VoucherError::AlreadyRedeemed);
+        }
+        let legacy_escrow = || root.folded_entry(&self.escrow_tx_ref(), &TransactionKind::Credit);
+        let funded = self.issuer == root.owner
+            || root.voucher_escrow(&self.id).map_or_else(legacy_escrow, |amount| amount == self.amount);
+        if !funded {
+            return Err(VoucherError::Unfunded);
+        }
+        Ok(())
+    }
+
+    /// Whether `root` paid this voucher out to the user named `receiver`
+    /// (matched case-insensitively): only then does their credit stand.
+    pub fn redeemed_by(&self, root: &UserContractState, receiver: &str) -> bool {
+        root.voucher_redeemer(&self.id)
+            .is_some_and(|redeemer| redeemer.to_lowercase() == receiver.to_lowercase())
+    }
+
+    /// The ledger credit that books this voucher to `receiver`.
+    pub fn credit(&self, id: u32, receiver: &str, timestamp: String) -> WalletTransaction {
+        WalletTransaction {
+            id,
+            kind: TransactionKind::Credit,
+            amount: self.amount,
+            description: self.description(),
+            sender: crate::identity::ROOT_USER_NAME.to_string(),
+            receiver: receiver.to_string(),
+            tx_ref: self.tx_ref(),
+            timestamp,
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SignableVoucher<'a> {
+    domain: &'static str,
+    id: &'a str,
+    amount: u64,
+    issuer: &'a UserId,
+    issuer_name: &'a str,
+    note: &'a str,
+    issued_at: &'a DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn voucher(key: &SigningKey, amount: u64) -> Voucher {
+        let mut voucher = Voucher {
+            id: "v-1".into(),
+            amount,
+            issuer: UserId(key.verifying_key()),
+            issuer_name: "Gary".into(),
+            note: "Happy birthday!".into(),
+            issued_at: Utc::now(),
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        voucher.signature = key.sign(&voucher.signable_bytes());
+        voucher
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        let voucher = voucher(&SigningKey::from_bytes(&[5u8; 32]), 250);
+        let code = voucher.code();
+        assert!(code.starts_with(VOUCHER_CODE_PREFIX));
+        assert_eq!(Voucher::from_code(&format!("  {code}\n")), Ok(voucher));
+        assert_eq!(Voucher::from_code("CREAM-V1-!!!"), Err(VoucherError::Malformed));
+        assert_eq!(Voucher::from_code("250 CURD please"), Err(VoucherError::Malformed));
+
+        let worthless = self::voucher(&SigningKey::from_bytes(&[5u8; 32]), 0);
+        assert_eq!(Voucher::from_code(&worthless.code()), Err(VoucherError::ZeroAmount));
+    }
+
+    #[cfg(not(feature = "dev"))]
+    #[test]
+    fn tampered_codes_are_rejected() {
+        let mut voucher = voucher(&SigningKey::from_bytes(&[5u8; 32]), 250);
+        voucher.amount = 25_000;
+        assert_eq!(Voucher::from_code(&voucher.code()), Err(VoucherError::BadSignature));
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::identity::UserId;
+use crate::provenance::{StateSection, WriterStamp};
+
 /// A single wallet transaction (credit or debit) in the on-network ledger.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WalletTransaction {
@@ -17,13 +20,298 @@ pub struct WalletTransaction {
     /// Used for contract-level deduplication to prevent double-minting.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub lightning_payment_hash: Option<String>,
+    /// For credits written into another user's contract: the paying user.
+    /// Their stamp must be the credit's `provenance`; for root's payments,
+    /// the root key. `None` on mints, voucher payouts and entries from before
+    /// payers were recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payer: Option<UserId>,
+    /// For credits written into another user's contract: the payer's stamp
+    /// over [`WalletTransaction::credit_provenance_bytes`]. Mints and voucher
+    /// payouts are backed by their records instead, and credits from before
+    /// stamps stay as they are.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<WriterStamp>,
+    /// Device that wrote the entry into its owner's own contract, so entries
+    /// from the same account signed in elsewhere can be told apart. `None`
+    /// for entries written into someone else's contract, and for entries
+    /// written before devices were recorded. Not stamped: it's a label, not
+    /// a claim anyone relies on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
     /// Extension fields — preserves unknown fields across contract versions.
     #[serde(flatten, default)]
     pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Serialize)]
+struct SignableCredit<'a> {
+    amount: u64,
+    description: &'a str,
+    sender: &'a str,
+    receiver: &'a str,
+    tx_ref: &'a str,
+    timestamp: &'a str,
+    lightning_payment_hash: &'a Option<String>,
+    /// Left out when unset, so credits stamped before it existed still verify.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    payer: Option<&'a UserId>,
+}
+
+impl WalletTransaction {
+    /// Content bytes the payer stamps on a credit: everything except the
+    /// per-contract `id` and the stamp itself.
+    pub fn credit_provenance_bytes(&self) -> Vec<u8> {
+        let signable = SignableCredit {
+            amount: self.amount,
+            description: &self.description,
+            sender: &self.sender,
+            receiver: &self.receiver,
+            tx_ref: &self.tx_ref,
+            timestamp: &self.timestamp,
+            lightning_payment_hash: &self.lightning_payment_hash,
+            payer: self.payer.as_ref(),
+        };
+        serde_json::to_vec(&signable).expect("serialization should not fail")
+    }
+
+    /// Whether the entry's stamp (if any) is a valid payer stamp. Only
+    /// credits may carry one, and on a credit naming its payer it must be
+    /// the payer's.
+    pub fn verify_provenance(&self) -> bool {
+        self.provenance.as_ref().is_none_or(|stamp| {
+            self.kind == TransactionKind::Credit
+                && self.payer.as_ref().is_none_or(|payer| payer.0 == stamp.last_writer)
+                && stamp.verify(StateSection::LedgerCredit, &self.credit_provenance_bytes())
+        })
+    }
+
+    /// Whether this is a credit naming its payer and stamped by them, as
+    /// credits from other users must be when they arrive.
+    pub fn stamped_by_payer(&self) -> bool {
+        self.payer.is_some() && self.provenance.is_some() && self.verify_provenance()
+    }
+}
+
+/// Declaration order matters: credits sort before debits at equal timestamps.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum TransactionKind {
     Credit,
     Debit,
 }
+
+/// Apply a single ledger entry to a running balance.
+///
+/// Returns `None` for a debit larger than the balance — such a debit is a
+/// double-spend (e.g. concurrent spends from two devices) and is not applied.
+pub fn apply_transaction(balance: u64, tx: &WalletTransaction) -> Option<u64> {
+    match tx.kind {
+        TransactionKind::Credit => Some(balance.saturating_add(tx.amount)),
+        TransactionKind::Debit => balance.checked_sub(tx.amount),
+    }
+}
+
+// ─── Ledger export ──────────────────────────────────────────────────────────
+
+/// A ledger entry annotated for export (bookkeeping / tax records).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerExportRow {
+    pub id: u32,
+    pub timestamp: String,
+    pub kind: TransactionKind,
+    pub amount: u64,
+    /// Balance after applying this entry, starting from the opening balance.
+    pub running_balance: u64,
+    pub description: String,
+    pub sender: String,
+    pub receiver: String,
+    pub tx_ref: String,
+    /// Id of the opposite-kind entry in the same ledger sharing this `tx_ref`
+    /// (e.g. an escrow refund paired with its original debit).
+    pub paired_tx_id: Option<u32>,
+    pub lightning_payment_hash: Option<String>,
+    /// Debit rejected as a double-spend (not applied to the running balance).
+    pub conflicted: bool,
+}
+
+/// A full ledger export: opening/closing balances plus annotated rows.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerExport {
+    pub opening_balance: u64,
+    pub closing_balance: u64,
+    pub transactions: Vec<LedgerExportRow>,
+}
+
+impl LedgerExport {
+    /// Build an export from a ledger. `opening_balance` is the balance before
+    /// the first entry (the user contract's `checkpoint_balance`).
+    ///
+    /// Running balances follow the same rules as
+    /// `UserContractState::derive_balance` (see [`apply_transaction`]).
+    pub fn new(opening_balance: u64, ledger: &[WalletTransaction]) -> Self {
+        let mut balance = opening_balance;
+        let transactions = ledger
+            .iter()
+            .map(|tx| {
+                let applied = apply_transaction(balance, tx);
+                if let Some(next) = applied {
+                    balance = next;
+                }
+                let paired_tx_id = ledger
+                    .iter()
+                    .find(|other| other.tx_ref == tx.tx_ref && other.kind != tx.kind)
+                    .map(|other| other.id);
+                LedgerExportRow {
+                    id: tx.id,
+                    timestamp: tx.timestamp.clone(),
+                    kind: tx.kind.clone(),
+                    amount: tx.amount,
+                    running_balance: balance,
+                    description: tx.description.clone(),
+                    sender: tx.sender.clone(),
+                    receiver: tx.receiver.clone(),
+                    tx_ref: tx.tx_ref.clone(),
+                    paired_tx_id,
+                    lightning_payment_hash: tx.lightning_payment_hash.clone(),
+                    conflicted: applied.is_none(),
+                }
+            })
+            .collect();
+        LedgerExport {
+            opening_balance,
+            closing_balance: balance,
+            transactions,
+        }
+    }
+
+    /// Render as CSV with a header row. Fields containing commas, quotes or
+    /// newlines are quoted per RFC 4180.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "id,timestamp,kind,amount,running_balance,description,sender,receiver,tx_ref,paired_tx_id,lightning_payment_hash,conflicted\n",
+        );
+        for row in &self.transactions {
+            let kind = match row.kind {
+                TransactionKind::Credit => "credit",
+                TransactionKind::Debit => "debit",
+            };
+            let fields = [
+                row.id.to_string(),
+                csv_field(&row.timestamp),
+                kind.to_string(),
+                row.amount.to_string(),
+                row.running_balance.to_string(),
+                csv_field(&row.description),
+                csv_field(&row.sender),
+                csv_field(&row.receiver),
+                csv_field(&row.tx_ref),
+                row.paired_tx_id.map(|id| id.to_string()).unwrap_or_default(),
+                csv_field(row.lightning_payment_hash.as_deref().unwrap_or("")),
+                row.conflicted.to_string(),
+            ];
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Render as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_tx(id: u32, kind: TransactionKind, amount: u64, tx_ref: &str) -> WalletTransaction {
+        WalletTransaction {
+            id,
+            kind,
+            amount,
+            description: "test".into(),
+            sender: "Alice".into(),
+            receiver: "Bob".into(),
+            tx_ref: tx_ref.into(),
+            timestamp: format!("2026-01-01T00:{:02}:00.000Z", id),
+            lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn export_running_balance_from_opening() {
+        let ledger = vec![
+            make_tx(0, TransactionKind::Credit, 1_000, "a"),
+            make_tx(1, TransactionKind::Debit, 300, "b"),
+            make_tx(2, TransactionKind::Credit, 50, "c"),
+        ];
+        let export = LedgerExport::new(500, &ledger);
+        let balances: Vec<u64> = export.transactions.iter().map(|r| r.running_balance).collect();
+        assert_eq!(balances, vec![1_500, 1_200, 1_250]);
+        assert_eq!(export.opening_balance, 500);
+        assert_eq!(export.closing_balance, 1_250);
+    }
+
+    #[test]
+    fn export_flags_overdrawing_debit() {
+        let ledger = vec![
+            make_tx(0, TransactionKind::Debit, 80, "a"),
+            make_tx(1, TransactionKind::Debit, 80, "b"),
+        ];
+        let export = LedgerExport::new(100, &ledger);
+        assert!(!export.transactions[0].conflicted);
+        assert!(export.transactions[1].conflicted);
+        assert_eq!(export.transactions[1].running_balance, 20);
+        assert_eq!(export.closing_balance, 20);
+    }
+
+    #[test]
+    fn export_pairs_opposite_kinds_by_tx_ref() {
+        let ledger = vec![
+            make_tx(0, TransactionKind::Debit, 100, "escrow:1"),
+            make_tx(1, TransactionKind::Credit, 100, "escrow:1"),
+            make_tx(2, TransactionKind::Debit, 10, "toll:1"),
+        ];
+        let export = LedgerExport::new(1_000, &ledger);
+        assert_eq!(export.transactions[0].paired_tx_id, Some(1));
+        assert_eq!(export.transactions[1].paired_tx_id, Some(0));
+        assert_eq!(export.transactions[2].paired_tx_id, None);
+    }
+
+    #[test]
+    fn export_csv_escapes_fields() {
+        let mut tx = make_tx(0, TransactionKind::Credit, 5, "r:1");
+        tx.description = "Milk, \"raw\"".into();
+        let csv = LedgerExport::new(0, &[tx]).to_csv();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("id,timestamp,kind"));
+        assert_eq!(
+            lines.next().unwrap(),
+            "0,2026-01-01T00:00:00.000Z,credit,5,5,\"Milk, \"\"raw\"\"\",Alice,Bob,r:1,,,false"
+        );
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn export_json_roundtrips_fields() {
+        let ledger = vec![make_tx(0, TransactionKind::Credit, 42, "x")];
+        let json = LedgerExport::new(0, &ledger).to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["closing_balance"], 42);
+        assert_eq!(value["transactions"][0]["tx_ref"], "x");
+        assert_eq!(value["transactions"][0]["kind"], "Credit");
+    }
+}
@@ -1,19 +1,79 @@
 #[cfg(feature = "contract")]
 mod contract_impl {
-    use cream_common::directory::{DirectoryState, DirectorySummary};
+    use cream_common::directory::{
+        DirectoryShard, DirectoryShardParameters, DirectoryState, DirectorySummary,
+    };
+    use cream_common::credential::parse_certifier_keys;
+    use cream_common::identity::UserId;
+    use cream_common::metrics::is_metrics_request;
+    use cream_common::related::validate_directory_storefronts;
     use freenet_stdlib::prelude::*;
 
     pub struct Contract;
 
-    fn merge_validated(directory: &mut DirectoryState, bytes: &[u8]) -> Result<(), ContractError> {
+    fn limit_error(e: cream_common::limits::LimitError) -> ContractError {
+        ContractError::Other(format!("size limit exceeded: {e}"))
+    }
+
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
+    fn name_error(e: Box<cream_common::directory::NameTaken>) -> ContractError {
+        ContractError::Other(format!("name taken: {e}"))
+    }
+
+    /// Empty parameters = the unsharded directory; otherwise a geographic shard.
+    fn shard_of(parameters: &Parameters<'static>) -> Result<Option<DirectoryShard>, ContractError> {
+        DirectoryShardParameters::from_bytes(parameters.as_ref())
+            .map_err(|e| ContractError::Deser(e.to_string()))
+    }
+
+    /// The guardians' FROST group key that moderation records must be signed
+    /// with, fixed at build time (hex). Unset, no moderation record is honored
+    /// outside dev builds.
+    fn moderator() -> Option<UserId> {
+        option_env!("CREAM_MODERATOR_KEY").and_then(|hex| hex.parse().ok())
+    }
+
+    /// Keys of the certifiers whose credentials are accepted: the
+    /// comma-separated hex keys in `CREAM_CERTIFIER_KEYS` at build time, plus
+    /// the guardians' group key.
+    fn certifiers() -> Vec<UserId> {
+        let mut certifiers = parse_certifier_keys(option_env!("CREAM_CERTIFIER_KEYS").unwrap_or(""));
+        certifiers.extend(moderator());
+        certifiers
+    }
+
+    fn merge_validated(
+        directory: &mut DirectoryState,
+        bytes: &[u8],
+        shard: Option<DirectoryShard>,
+    ) -> Result<(), ContractError> {
         if bytes.is_empty() {
             return Ok(());
         }
         let update: DirectoryState =
             serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
-        if !update.validate_all_signatures() {
+        let moderator = moderator();
+        if !update.validate_all_signatures()
+            || !update.belongs_to_shard(shard)
+            || !update.validate_moderation(moderator.as_ref().map(|m| &m.0))
+            || !update.validate_credentials(&certifiers())
+        {
             return Err(ContractError::InvalidUpdate);
         }
+        update.check_limits().map_err(limit_error)?;
+        let now = cream_common::clock::host_now();
+        update.check_timestamps(now).map_err(timestamp_error)?;
+        directory.check_timestamp_progress(&update).map_err(timestamp_error)?;
+        directory.check_claims_recent(&update, now).map_err(timestamp_error)?;
+        // A supplier's own registration is refused, so they hear that the
+        // name is taken. In a peer's state losing entries are left for the
+        // merge to set back, so the rest of it still gets through.
+        if update.entries.len() == 1 {
+            directory.check_names(&update).map_err(name_error)?;
+        }
         directory.merge(update);
         Ok(())
     }
@@ -21,10 +81,11 @@ mod contract_impl {
     #[contract]
     impl ContractInterface for Contract {
         fn validate_state(
-            _parameters: Parameters<'static>,
+            parameters: Parameters<'static>,
             state: State<'static>,
-            _related: RelatedContracts<'static>,
+            related: RelatedContracts<'static>,
         ) -> Result<ValidateResult, ContractError> {
+            let shard = shard_of(&parameters)?;
             let bytes = state.as_ref();
             if bytes.is_empty() {
                 return Ok(ValidateResult::Valid);
@@ -33,18 +94,30 @@ mod contract_impl {
             let directory: DirectoryState =
                 serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
 
-            if !directory.validate_all_signatures() {
+            let moderator = moderator();
+            if !directory.validate_all_signatures()
+                || !directory.belongs_to_shard(shard)
+                || !directory.validate_moderation(moderator.as_ref().map(|m| &m.0))
+                || !directory.validate_credentials(&certifiers())
+                || directory.check_limits().is_err()
+            {
                 return Ok(ValidateResult::Invalid);
             }
 
-            Ok(ValidateResult::Valid)
+            // Each entry must point at its own supplier's storefront. Dev
+            // builds skip the cross-check along with signatures.
+            if cfg!(feature = "dev") {
+                return Ok(ValidateResult::Valid);
+            }
+            Ok(validate_directory_storefronts(&directory, &related))
         }
 
         fn update_state(
-            _parameters: Parameters<'static>,
+            parameters: Parameters<'static>,
             state: State<'static>,
             data: Vec<UpdateData<'static>>,
         ) -> Result<UpdateModification<'static>, ContractError> {
+            let shard = shard_of(&parameters)?;
             let mut directory = if state.is_empty() {
                 DirectoryState::default()
             } else {
@@ -55,19 +128,21 @@ mod contract_impl {
             for ud in data {
                 match ud {
                     UpdateData::State(s) => {
-                        merge_validated(&mut directory, s.as_ref())?;
+                        merge_validated(&mut directory, s.as_ref(), shard)?;
                     }
                     UpdateData::Delta(d) => {
-                        merge_validated(&mut directory, d.as_ref())?;
+                        merge_validated(&mut directory, d.as_ref(), shard)?;
                     }
                     UpdateData::StateAndDelta { state, delta } => {
-                        merge_validated(&mut directory, state.as_ref())?;
-                        merge_validated(&mut directory, delta.as_ref())?;
+                        merge_validated(&mut directory, state.as_ref(), shard)?;
+                        merge_validated(&mut directory, delta.as_ref(), shard)?;
                     }
                     _ => return Err(ContractError::InvalidUpdate),
                 }
             }
 
+            directory.check_limits().map_err(limit_error)?;
+
             let serialized =
                 serde_json::to_vec(&directory).map_err(|e| ContractError::Other(e.to_string()))?;
             Ok(UpdateModification::valid(State::from(serialized)))
@@ -102,6 +177,14 @@ mod contract_impl {
             let directory: DirectoryState = serde_json::from_slice(state.as_ref())
                 .map_err(|e| ContractError::Deser(e.to_string()))?;
 
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = directory.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
             let summary: DirectorySummary = if summary.is_empty() {
                 DirectorySummary::default()
             } else {
@@ -116,3 +199,6 @@ mod contract_impl {
         }
     }
 }
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
@@ -1,10 +1,19 @@
 #[cfg(feature = "contract")]
 mod contract_impl {
     use cream_common::inbox::{InboxParameters, InboxState, InboxSummary};
+    use cream_common::metrics::is_metrics_request;
     use freenet_stdlib::prelude::*;
 
     pub struct Contract;
 
+    fn limit_error(e: cream_common::limits::LimitError) -> ContractError {
+        ContractError::Other(format!("size limit exceeded: {e}"))
+    }
+
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
     fn merge_validated(
         state: &mut InboxState,
         bytes: &[u8],
@@ -17,6 +26,10 @@ mod contract_impl {
         if !state.validate_update(&update) {
             return Err(ContractError::InvalidUpdate);
         }
+        update.check_limits().map_err(limit_error)?;
+        update
+            .check_timestamps(cream_common::clock::host_now())
+            .map_err(timestamp_error)?;
         state.merge(update);
         Ok(())
     }
@@ -39,7 +52,7 @@ mod contract_impl {
             let inbox_state: InboxState =
                 serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
 
-            if !inbox_state.validate(&params.owner) {
+            if !inbox_state.validate(&params.owner) || inbox_state.check_limits().is_err() {
                 return Ok(ValidateResult::Invalid);
             }
 
@@ -79,6 +92,8 @@ mod contract_impl {
                 }
             }
 
+            inbox_state.check_limits().map_err(limit_error)?;
+
             let serialized =
                 serde_json::to_vec(&inbox_state).map_err(|e| ContractError::Other(e.to_string()))?;
             Ok(UpdateModification::valid(State::from(serialized)))
@@ -113,6 +128,14 @@ mod contract_impl {
             let inbox_state: InboxState = serde_json::from_slice(state.as_ref())
                 .map_err(|e| ContractError::Deser(e.to_string()))?;
 
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = inbox_state.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
             let summary: InboxSummary = if summary.is_empty() {
                 InboxSummary::default()
             } else {
@@ -130,3 +153,6 @@ mod contract_impl {
         }
     }
 }
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
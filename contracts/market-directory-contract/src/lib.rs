@@ -1,10 +1,15 @@
 #[cfg(feature = "contract")]
 mod contract_impl {
     use cream_common::market::{MarketDirectoryState, MarketDirectorySummary};
+    use cream_common::metrics::is_metrics_request;
     use freenet_stdlib::prelude::*;
 
     pub struct Contract;
 
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
     fn merge_validated(
         directory: &mut MarketDirectoryState,
         bytes: &[u8],
@@ -17,6 +22,9 @@ mod contract_impl {
         if !update.validate_all_signatures() {
             return Err(ContractError::InvalidUpdate);
         }
+        update
+            .check_timestamps(cream_common::clock::host_now())
+            .map_err(timestamp_error)?;
         directory.merge(update);
         Ok(())
     }
@@ -105,6 +113,14 @@ mod contract_impl {
             let directory: MarketDirectoryState = serde_json::from_slice(state.as_ref())
                 .map_err(|e| ContractError::Deser(e.to_string()))?;
 
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = directory.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
             let summary: MarketDirectorySummary = if summary.is_empty() {
                 MarketDirectorySummary::default()
             } else {
@@ -119,3 +135,6 @@ mod contract_impl {
         }
     }
 }
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
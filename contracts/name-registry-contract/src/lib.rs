@@ -0,0 +1,147 @@
+#[cfg(feature = "contract")]
+mod contract_impl {
+    use cream_common::registry::{NameRegistryState, NameRegistrySummary};
+    use cream_common::metrics::is_metrics_request;
+    use freenet_stdlib::prelude::*;
+
+    pub struct Contract;
+
+    fn limit_error(e: cream_common::limits::LimitError) -> ContractError {
+        ContractError::Other(format!("size limit exceeded: {e}"))
+    }
+
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
+    fn merge_validated(
+        registry: &mut NameRegistryState,
+        bytes: &[u8],
+    ) -> Result<(), ContractError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let update: NameRegistryState =
+            serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
+        if !update.validate_all_signatures() {
+            return Err(ContractError::InvalidUpdate);
+        }
+        update.check_limits().map_err(limit_error)?;
+        let now = cream_common::clock::host_now();
+        update.check_timestamps(now).map_err(timestamp_error)?;
+        registry.check_timestamp_progress(&update).map_err(timestamp_error)?;
+        registry.check_claims_recent(&update, now).map_err(timestamp_error)?;
+        registry.merge(update);
+        Ok(())
+    }
+
+    #[contract]
+    impl ContractInterface for Contract {
+        fn validate_state(
+            _parameters: Parameters<'static>,
+            state: State<'static>,
+            _related: RelatedContracts<'static>,
+        ) -> Result<ValidateResult, ContractError> {
+            let bytes = state.as_ref();
+            if bytes.is_empty() {
+                return Ok(ValidateResult::Valid);
+            }
+
+            let registry: NameRegistryState =
+                serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
+
+            if !registry.validate_all_signatures() || registry.check_limits().is_err() {
+                return Ok(ValidateResult::Invalid);
+            }
+
+            Ok(ValidateResult::Valid)
+        }
+
+        fn update_state(
+            _parameters: Parameters<'static>,
+            state: State<'static>,
+            data: Vec<UpdateData<'static>>,
+        ) -> Result<UpdateModification<'static>, ContractError> {
+            let mut registry = if state.is_empty() {
+                NameRegistryState::default()
+            } else {
+                serde_json::from_slice(state.as_ref())
+                    .map_err(|e| ContractError::Deser(e.to_string()))?
+            };
+
+            for ud in data {
+                match ud {
+                    UpdateData::State(s) => {
+                        merge_validated(&mut registry, s.as_ref())?;
+                    }
+                    UpdateData::Delta(d) => {
+                        merge_validated(&mut registry, d.as_ref())?;
+                    }
+                    UpdateData::StateAndDelta { state, delta } => {
+                        merge_validated(&mut registry, state.as_ref())?;
+                        merge_validated(&mut registry, delta.as_ref())?;
+                    }
+                    _ => return Err(ContractError::InvalidUpdate),
+                }
+            }
+
+            registry.check_limits().map_err(limit_error)?;
+            let serialized =
+                serde_json::to_vec(&registry).map_err(|e| ContractError::Other(e.to_string()))?;
+            Ok(UpdateModification::valid(State::from(serialized)))
+        }
+
+        fn summarize_state(
+            _parameters: Parameters<'static>,
+            state: State<'static>,
+        ) -> Result<StateSummary<'static>, ContractError> {
+            if state.is_empty() {
+                return Ok(StateSummary::from(vec![]));
+            }
+
+            let registry: NameRegistryState = serde_json::from_slice(state.as_ref())
+                .map_err(|e| ContractError::Deser(e.to_string()))?;
+
+            let summary = registry.summarize();
+            let serialized =
+                serde_json::to_vec(&summary).map_err(|e| ContractError::Other(e.to_string()))?;
+            Ok(StateSummary::from(serialized))
+        }
+
+        fn get_state_delta(
+            _parameters: Parameters<'static>,
+            state: State<'static>,
+            summary: StateSummary<'static>,
+        ) -> Result<StateDelta<'static>, ContractError> {
+            if state.is_empty() {
+                return Ok(StateDelta::from(vec![]));
+            }
+
+            let registry: NameRegistryState = serde_json::from_slice(state.as_ref())
+                .map_err(|e| ContractError::Deser(e.to_string()))?;
+
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = registry.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
+            let summary: NameRegistrySummary = if summary.is_empty() {
+                NameRegistrySummary::default()
+            } else {
+                serde_json::from_slice(summary.as_ref())
+                    .map_err(|e| ContractError::Deser(e.to_string()))?
+            };
+
+            let delta = registry.delta(&summary);
+            let serialized =
+                serde_json::to_vec(&delta).map_err(|e| ContractError::Other(e.to_string()))?;
+            Ok(StateDelta::from(serialized))
+        }
+    }
+}
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
@@ -1,10 +1,29 @@
 #[cfg(feature = "contract")]
 mod contract_impl {
+    use cream_common::metrics::is_metrics_request;
+    use cream_common::related::{directory_instance_ids, validate_storefront_listing};
     use cream_common::storefront::{StorefrontParameters, StorefrontState, StorefrontSummary};
     use freenet_stdlib::prelude::*;
 
     pub struct Contract;
 
+    /// The directory contracts a storefront's owner must be listed in, from
+    /// the directory code's blake3 hash (hex) in `CREAM_DIRECTORY_CODE_HASH`
+    /// at build time. Unset, storefronts aren't cross-checked.
+    fn directories() -> Vec<ContractInstanceId> {
+        option_env!("CREAM_DIRECTORY_CODE_HASH")
+            .and_then(directory_instance_ids)
+            .unwrap_or_default()
+    }
+
+    fn limit_error(e: cream_common::limits::LimitError) -> ContractError {
+        ContractError::Other(format!("size limit exceeded: {e}"))
+    }
+
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
     fn merge_validated(
         storefront: &mut StorefrontState,
         bytes: &[u8],
@@ -15,9 +34,15 @@ mod contract_impl {
         }
         let update: StorefrontState =
             serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
-        if !update.validate(owner) {
+        // Staff keys may only make the kinds of change their grants allow
+        if !update.validate(owner) || !storefront.permits(&update, owner) {
             return Err(ContractError::InvalidUpdate);
         }
+        update.check_limits().map_err(limit_error)?;
+        let now = cream_common::clock::host_now();
+        update.check_timestamps(now).map_err(timestamp_error)?;
+        storefront.check_timestamp_progress(&update).map_err(timestamp_error)?;
+        storefront.check_orders_recent(&update, now).map_err(timestamp_error)?;
         storefront.merge(update);
         Ok(())
     }
@@ -27,7 +52,7 @@ mod contract_impl {
         fn validate_state(
             parameters: Parameters<'static>,
             state: State<'static>,
-            _related: RelatedContracts<'static>,
+            related: RelatedContracts<'static>,
         ) -> Result<ValidateResult, ContractError> {
             let bytes = state.as_ref();
             if bytes.is_empty() {
@@ -40,11 +65,12 @@ mod contract_impl {
             let storefront: StorefrontState =
                 serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
 
-            if !storefront.validate(&params.owner) {
+            if !storefront.validate(&params.owner) || storefront.check_limits().is_err() {
                 return Ok(ValidateResult::Invalid);
             }
 
-            Ok(ValidateResult::Valid)
+            // Orphaned storefronts (owner not in the directory) are invalid
+            Ok(validate_storefront_listing(&params.owner, &directories(), &related))
         }
 
         fn update_state(
@@ -80,6 +106,8 @@ mod contract_impl {
                 }
             }
 
+            storefront.check_limits().map_err(limit_error)?;
+
             let serialized =
                 serde_json::to_vec(&storefront).map_err(|e| ContractError::Other(e.to_string()))?;
             Ok(UpdateModification::valid(State::from(serialized)))
@@ -114,6 +142,14 @@ mod contract_impl {
             let storefront: StorefrontState = serde_json::from_slice(state.as_ref())
                 .map_err(|e| ContractError::Deser(e.to_string()))?;
 
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = storefront.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
             let summary: StorefrontSummary = if summary.is_empty() {
                 StorefrontSummary::default()
             } else {
@@ -128,3 +164,6 @@ mod contract_impl {
         }
     }
 }
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
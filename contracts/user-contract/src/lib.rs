@@ -3,23 +3,36 @@ mod contract_impl {
     use cream_common::user_contract::{
         UserContractParameters, UserContractState, UserContractSummary,
     };
+    use cream_common::metrics::is_metrics_request;
     use freenet_stdlib::prelude::*;
 
     pub struct Contract;
 
+    fn limit_error(e: cream_common::limits::LimitError) -> ContractError {
+        ContractError::Other(format!("size limit exceeded: {e}"))
+    }
+
+    fn timestamp_error(e: cream_common::timestamps::TimestampError) -> ContractError {
+        ContractError::Other(format!("bad timestamp: {e}"))
+    }
+
     fn merge_validated(
         state: &mut UserContractState,
         bytes: &[u8],
-        owner: &ed25519_dalek::VerifyingKey,
+        params: &UserContractParameters,
     ) -> Result<(), ContractError> {
         if bytes.is_empty() {
             return Ok(());
         }
         let update: UserContractState =
             serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
-        if !state.validate_update(&update, owner) {
+        let now = cream_common::clock::host_now();
+        if !state.validate_update_for(&update, params, now) {
             return Err(ContractError::InvalidUpdate);
         }
+        update.check_limits().map_err(limit_error)?;
+        update.check_timestamps(now).map_err(timestamp_error)?;
+        state.check_timestamp_progress(&update).map_err(timestamp_error)?;
         state.merge(update);
         Ok(())
     }
@@ -42,7 +55,7 @@ mod contract_impl {
             let user_state: UserContractState =
                 serde_json::from_slice(bytes).map_err(|e| ContractError::Deser(e.to_string()))?;
 
-            if !user_state.validate(&params.owner) {
+            if !user_state.validate_for(&params) || user_state.check_limits().is_err() {
                 return Ok(ValidateResult::Invalid);
             }
 
@@ -69,19 +82,21 @@ mod contract_impl {
             for ud in data {
                 match ud {
                     UpdateData::State(s) => {
-                        merge_validated(&mut user_state, s.as_ref(), &params.owner)?;
+                        merge_validated(&mut user_state, s.as_ref(), &params)?;
                     }
                     UpdateData::Delta(d) => {
-                        merge_validated(&mut user_state, d.as_ref(), &params.owner)?;
+                        merge_validated(&mut user_state, d.as_ref(), &params)?;
                     }
                     UpdateData::StateAndDelta { state, delta } => {
-                        merge_validated(&mut user_state, state.as_ref(), &params.owner)?;
-                        merge_validated(&mut user_state, delta.as_ref(), &params.owner)?;
+                        merge_validated(&mut user_state, state.as_ref(), &params)?;
+                        merge_validated(&mut user_state, delta.as_ref(), &params)?;
                     }
                     _ => return Err(ContractError::InvalidUpdate),
                 }
             }
 
+            user_state.check_limits().map_err(limit_error)?;
+
             let serialized =
                 serde_json::to_vec(&user_state).map_err(|e| ContractError::Other(e.to_string()))?;
             Ok(UpdateModification::valid(State::from(serialized)))
@@ -116,6 +131,14 @@ mod contract_impl {
             let user_state: UserContractState = serde_json::from_slice(state.as_ref())
                 .map_err(|e| ContractError::Deser(e.to_string()))?;
 
+            // A monitor asking for metrics gets those instead of a delta
+            if is_metrics_request(summary.as_ref()) {
+                let metrics = user_state.metrics(state.as_ref().len());
+                let serialized =
+                    serde_json::to_vec(&metrics).map_err(|e| ContractError::Other(e.to_string()))?;
+                return Ok(StateDelta::from(serialized));
+            }
+
             let summary: UserContractSummary = if summary.is_empty() {
                 UserContractSummary::default()
             } else {
@@ -133,3 +156,6 @@ mod contract_impl {
         }
     }
 }
+
+#[cfg(feature = "contract")]
+pub use contract_impl::Contract;
@@ -0,0 +1,78 @@
+//! The delegate as the Freenet node runs it.
+//!
+//! Each application message carries a JSON [`CreamRequest`]. The node keeps
+//! the [`DelegateState`] as a secret, so a request is handled in two steps:
+//! ask for the secret (the request rides along in the context), then handle
+//! the request against it, store the new state and answer with a JSON
+//! [`CreamResponse`].
+
+use freenet_stdlib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{CreamRequest, DelegateState};
+
+/// Secret the delegate state is stored under.
+const STATE_SECRET: &[u8] = b"cream-delegate-state";
+
+/// A request waiting for the state secret.
+#[derive(Serialize, Deserialize)]
+struct Pending {
+    app: ContractInstanceId,
+    request: Vec<u8>,
+}
+
+pub struct CreamDelegate;
+
+#[delegate]
+impl DelegateInterface for CreamDelegate {
+    fn process(
+        _parameters: Parameters<'static>,
+        _attested: Option<&'static [u8]>,
+        message: InboundDelegateMsg,
+    ) -> Result<Vec<OutboundDelegateMsg>, DelegateError> {
+        match message {
+            InboundDelegateMsg::ApplicationMessage(msg) if !msg.processed => {
+                // Refuse garbage before touching the secret
+                serde_json::from_slice::<CreamRequest>(&msg.payload)
+                    .map_err(|e| DelegateError::Deser(e.to_string()))?;
+                let pending = Pending {
+                    app: msg.app,
+                    request: msg.payload,
+                };
+                let context = serde_json::to_vec(&pending)
+                    .map_err(|e| DelegateError::Other(e.to_string()))?;
+                let mut get = GetSecretRequest::new(SecretsId::new(STATE_SECRET.to_vec()));
+                get.context = DelegateContext::new(context);
+                Ok(vec![OutboundDelegateMsg::GetSecretRequest(get)])
+            }
+            InboundDelegateMsg::GetSecretResponse(response) => {
+                let pending: Pending = serde_json::from_slice(response.context.as_ref())
+                    .map_err(|e| DelegateError::Deser(e.to_string()))?;
+                let request: CreamRequest = serde_json::from_slice(&pending.request)
+                    .map_err(|e| DelegateError::Deser(e.to_string()))?;
+                let mut state: DelegateState = match response.value {
+                    Some(bytes) => serde_json::from_slice(&bytes)
+                        .map_err(|e| DelegateError::Deser(e.to_string()))?,
+                    None => DelegateState::default(),
+                };
+
+                let answer = state.handle_request(request, freenet_stdlib::time::now());
+
+                let state_bytes =
+                    serde_json::to_vec(&state).map_err(|e| DelegateError::Other(e.to_string()))?;
+                let answer_bytes =
+                    serde_json::to_vec(&answer).map_err(|e| DelegateError::Other(e.to_string()))?;
+                Ok(vec![
+                    OutboundDelegateMsg::SetSecretRequest(SetSecretRequest {
+                        key: SecretsId::new(STATE_SECRET.to_vec()),
+                        value: Some(state_bytes),
+                    }),
+                    OutboundDelegateMsg::ApplicationMessage(
+                        ApplicationMessage::new(pending.app, answer_bytes).processed(true),
+                    ),
+                ])
+            }
+            _ => Ok(vec![]),
+        }
+    }
+}
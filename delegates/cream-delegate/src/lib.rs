@@ -1,17 +1,35 @@
+use chrono::{DateTime, Utc};
 use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
 use serde::{Deserialize, Serialize};
 
+use cream_common::attestation::BalanceAttestation;
 use cream_common::directory::DirectoryEntry;
 use cream_common::identity::{UserId, UserIdentity, UserRole};
 use cream_common::order::Order;
 use cream_common::product::Product;
-use cream_common::storefront::{order_signable_bytes, SignedProduct};
+use cream_common::storefront::{order_signable_bytes, product_signable_bytes, SignedProduct};
+use cream_common::user_contract::{UserContractParameters, UserContractState};
+
+#[cfg(feature = "delegate")]
+mod interface;
+pub mod policy;
+
+use policy::{PolicyState, PolicyViolation, SigningPolicy};
 
 /// Requests that can be sent to the CREAM delegate.
 #[derive(Debug, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum CreamRequest {
     // Identity management
-    CreateIdentity { role: UserRole },
+    CreateIdentity {
+        role: UserRole,
+    },
+    /// Take over an existing key (e.g. the one the UI derives from the
+    /// user's credentials), replacing any identity held.
+    ImportIdentity {
+        signing_key: [u8; 32],
+        role: UserRole,
+    },
     GetIdentity,
 
     // Mock wallet
@@ -20,18 +38,38 @@ pub enum CreamRequest {
 
     // Signing
     SignProduct(Product),
-    SignOrder(Order),
+    /// Sign an order to be placed in `supplier`'s storefront, if the
+    /// signing policy allows it.
+    SignOrder {
+        order: Order,
+        supplier: UserId,
+    },
     SignDirectoryEntry(DirectoryEntry),
+    /// Sign the balance derived from this identity's user contract state,
+    /// for storefronts that require an attestation on large orders.
+    AttestBalance(UserContractState),
+
+    // Signing policy
+    GetPolicy,
+    SetPolicy(SigningPolicy),
 }
 
 /// Responses from the CREAM delegate.
 #[derive(Debug, Serialize, Deserialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum CreamResponse {
     Identity(UserIdentity),
     Balance(u64),
     SignedProduct(SignedProduct),
     SignedOrder(Order),
     SignedDirectoryEntry(DirectoryEntry),
+    BalanceAttestation(BalanceAttestation),
+    /// The active signing policy, and a loosened one waiting to replace it.
+    Policy {
+        active: SigningPolicy,
+        pending: Option<(SigningPolicy, DateTime<Utc>)>,
+    },
+    PolicyViolation(PolicyViolation),
     Error(String),
 }
 
@@ -44,12 +82,22 @@ pub struct DelegateState {
     role: Option<UserRole>,
     /// Mock CURD balance.
     balance: u64,
+    /// Order signing policy and recent spending.
+    #[serde(default)]
+    policy: PolicyState,
 }
 
 impl DelegateState {
-    pub fn handle_request(&mut self, request: CreamRequest) -> CreamResponse {
+    /// Handle one request. `now` is the host's clock, for the signing
+    /// policy's daily limit and delays.
+    pub fn handle_request(&mut self, request: CreamRequest, now: DateTime<Utc>) -> CreamResponse {
         match request {
             CreamRequest::CreateIdentity { role } => self.create_identity(role),
+            CreamRequest::ImportIdentity { signing_key, role } => {
+                self.user_key = Some(signing_key.to_vec());
+                self.role = Some(role);
+                self.get_identity()
+            }
             CreamRequest::GetIdentity => self.get_identity(),
             CreamRequest::GetBalance => CreamResponse::Balance(self.balance),
             CreamRequest::SetBalance(amount) => {
@@ -57,8 +105,17 @@ impl DelegateState {
                 CreamResponse::Balance(self.balance)
             }
             CreamRequest::SignProduct(product) => self.sign_product(product),
-            CreamRequest::SignOrder(mut order) => self.sign_order(&mut order),
+            CreamRequest::SignOrder {
+                mut order,
+                supplier,
+            } => self.sign_order(&mut order, &supplier, now),
             CreamRequest::SignDirectoryEntry(mut entry) => self.sign_directory_entry(&mut entry),
+            CreamRequest::AttestBalance(state) => self.attest_balance(&state, now),
+            CreamRequest::GetPolicy => self.get_policy(),
+            CreamRequest::SetPolicy(policy) => {
+                self.policy.set(policy, now);
+                self.get_policy()
+            }
         }
     }
 
@@ -95,25 +152,45 @@ impl DelegateState {
             return CreamResponse::Error("No identity".into());
         };
         let key = signing_key_from_bytes(key_bytes);
-        let msg = serde_json::to_vec(&product).expect("serialization should not fail");
+        let msg = product_signable_bytes(&product);
         let signature = key.sign(&msg);
         CreamResponse::SignedProduct(SignedProduct {
             product,
             signature,
+            provenance: None,
+            price_history: Vec::new(),
             extra: Default::default(),
         })
     }
 
-    fn sign_order(&self, order: &mut Order) -> CreamResponse {
+    fn sign_order(
+        &mut self,
+        order: &mut Order,
+        supplier: &UserId,
+        now: DateTime<Utc>,
+    ) -> CreamResponse {
         let Some(key_bytes) = &self.user_key else {
             return CreamResponse::Error("No identity".into());
         };
+        if let Err(violation) = self
+            .policy
+            .authorize(&order.id, order.total_price, supplier, now)
+        {
+            return CreamResponse::PolicyViolation(violation);
+        }
         let key = signing_key_from_bytes(key_bytes);
         let msg = order_signable_bytes(order);
         order.signature = key.sign(&msg);
         CreamResponse::SignedOrder(order.clone())
     }
 
+    fn get_policy(&self) -> CreamResponse {
+        CreamResponse::Policy {
+            active: self.policy.active().clone(),
+            pending: self.policy.pending().cloned(),
+        }
+    }
+
     fn sign_directory_entry(&self, entry: &mut DirectoryEntry) -> CreamResponse {
         let Some(key_bytes) = &self.user_key else {
             return CreamResponse::Error("No identity".into());
@@ -123,6 +200,29 @@ impl DelegateState {
         entry.signature = key.sign(&msg);
         CreamResponse::SignedDirectoryEntry(entry.clone())
     }
+
+    /// Attest the balance of `state`, which must be this identity's user
+    /// contract and verify as the contract would. The balance comes from the
+    /// ledger, not the mock wallet.
+    fn attest_balance(&self, state: &UserContractState, now: DateTime<Utc>) -> CreamResponse {
+        let Some(key_bytes) = &self.user_key else {
+            return CreamResponse::Error("No identity".into());
+        };
+        let key = signing_key_from_bytes(key_bytes);
+        if state.owner != UserId(key.verifying_key()) {
+            return CreamResponse::Error("Not this identity's user contract".into());
+        }
+        let params = UserContractParameters {
+            owner: key.verifying_key(),
+            spending_key: None,
+        };
+        if !state.validate_for(&params) {
+            return CreamResponse::Error("User contract state does not verify".into());
+        }
+        let mut attestation = BalanceAttestation::of(state, now);
+        attestation.signature = key.sign(&attestation.signable_bytes());
+        CreamResponse::BalanceAttestation(attestation)
+    }
 }
 
 fn signing_key_from_bytes(bytes: &[u8]) -> SigningKey {
@@ -143,9 +243,12 @@ mod tests {
         let mut state = DelegateState::default();
 
         // Create supplier identity
-        let resp = state.handle_request(CreamRequest::CreateIdentity {
-            role: UserRole::Supplier,
-        });
+        let resp = state.handle_request(
+            CreamRequest::CreateIdentity {
+                role: UserRole::Supplier,
+            },
+            Utc::now(),
+        );
         let identity = match resp {
             CreamResponse::Identity(id) => id,
             other => panic!("Expected Identity, got {:?}", other),
@@ -158,14 +261,21 @@ mod tests {
             name: "Raw Milk".into(),
             description: "Fresh raw milk".into(),
             category: ProductCategory::Milk,
+            subcategory: None,
+            attributes: Default::default(),
             price_curd: 500,
             quantity_total: 10,
             expiry_date: None,
             updated_at: Utc::now(),
             created_at: Utc::now(),
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
         };
 
-        let resp = state.handle_request(CreamRequest::SignProduct(product));
+        let resp = state.handle_request(CreamRequest::SignProduct(product), Utc::now());
         let signed = match resp {
             CreamResponse::SignedProduct(sp) => sp,
             other => panic!("Expected SignedProduct, got {:?}", other),
@@ -178,12 +288,50 @@ mod tests {
         assert!(signed.verify_signature(&verifying_key));
     }
 
+    #[test]
+    fn test_import_identity_signs_as_that_key() {
+        let mut state = DelegateState::default();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let resp = state.handle_request(
+            CreamRequest::ImportIdentity {
+                signing_key: key.to_bytes(),
+                role: UserRole::Customer,
+            },
+            Utc::now(),
+        );
+        let identity = match resp {
+            CreamResponse::Identity(id) => id,
+            other => panic!("Expected Identity, got {:?}", other),
+        };
+        assert_eq!(identity.user_id, UserId(key.verifying_key()));
+
+        let now = Utc::now();
+        let resp = state.handle_request(
+            CreamRequest::SignOrder {
+                order: order("o-1", 100, now),
+                supplier: UserId(SigningKey::from_bytes(&[3; 32]).verifying_key()),
+            },
+            now,
+        );
+        let signed = match resp {
+            CreamResponse::SignedOrder(order) => order,
+            other => panic!("Expected SignedOrder, got {:?}", other),
+        };
+        assert!(key
+            .verifying_key()
+            .verify_strict(&order_signable_bytes(&signed), &signed.signature)
+            .is_ok());
+    }
+
     #[test]
     fn test_create_both_identity() {
         let mut state = DelegateState::default();
-        let resp = state.handle_request(CreamRequest::CreateIdentity {
-            role: UserRole::Both,
-        });
+        let resp = state.handle_request(
+            CreamRequest::CreateIdentity {
+                role: UserRole::Both,
+            },
+            Utc::now(),
+        );
         let identity = match resp {
             CreamResponse::Identity(id) => id,
             other => panic!("Expected Identity, got {:?}", other),
@@ -194,27 +342,33 @@ mod tests {
     #[test]
     fn test_mock_wallet() {
         let mut state = DelegateState::default();
-        state.handle_request(CreamRequest::CreateIdentity {
-            role: UserRole::Customer,
-        });
+        state.handle_request(
+            CreamRequest::CreateIdentity {
+                role: UserRole::Customer,
+            },
+            Utc::now(),
+        );
 
         // Default balance should be 10_000
-        let resp = state.handle_request(CreamRequest::GetBalance);
+        let resp = state.handle_request(CreamRequest::GetBalance, Utc::now());
         assert!(matches!(resp, CreamResponse::Balance(10_000)));
 
         // Set balance
-        let resp = state.handle_request(CreamRequest::SetBalance(50_000));
+        let resp = state.handle_request(CreamRequest::SetBalance(50_000), Utc::now());
         assert!(matches!(resp, CreamResponse::Balance(50_000)));
     }
 
     #[test]
     fn test_sign_directory_entry() {
         let mut state = DelegateState::default();
-        state.handle_request(CreamRequest::CreateIdentity {
-            role: UserRole::Supplier,
-        });
+        state.handle_request(
+            CreamRequest::CreateIdentity {
+                role: UserRole::Supplier,
+            },
+            Utc::now(),
+        );
 
-        let identity = match state.handle_request(CreamRequest::GetIdentity) {
+        let identity = match state.handle_request(CreamRequest::GetIdentity, Utc::now()) {
             CreamResponse::Identity(id) => id,
             _ => panic!("expected identity"),
         };
@@ -235,10 +389,12 @@ mod tests {
             user_contract_key: None,
             inbox_contract_key: None,
             updated_at: Utc::now(),
+            name_claimed_at: None,
             signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
         };
 
-        let resp = state.handle_request(CreamRequest::SignDirectoryEntry(entry));
+        let resp = state.handle_request(CreamRequest::SignDirectoryEntry(entry), Utc::now());
         let signed_entry = match resp {
             CreamResponse::SignedDirectoryEntry(e) => e,
             other => panic!("Expected SignedDirectoryEntry, got {:?}", other),
@@ -246,4 +402,205 @@ mod tests {
 
         assert!(signed_entry.verify_signature());
     }
+
+    fn user_contract(key: &SigningKey, balance: u64) -> UserContractState {
+        use cream_common::wallet::{TransactionKind, WalletTransaction};
+        let mut state = UserContractState {
+            owner: UserId(key.verifying_key()),
+            name: "Alice".into(),
+            origin_supplier: "Gary".into(),
+            current_supplier: "Gary".into(),
+            balance_curds: balance,
+            invited_by: "Gary".into(),
+            ledger: vec![WalletTransaction {
+                id: 0,
+                kind: TransactionKind::Credit,
+                amount: balance,
+                description: "Initial CURD allocation".into(),
+                sender: "__cream_root__".into(),
+                receiver: "Alice".into(),
+                tx_ref: "root:1000:42".into(),
+                timestamp: "2026-01-01T00:00:00.000Z".into(),
+                lightning_payment_hash: None,
+                payer: None,
+                provenance: None,
+                device: None,
+                extra: Default::default(),
+            }],
+            next_tx_id: 1,
+            toll_rates: Default::default(),
+            checkpoint_balance: 0,
+            checkpoint_tx_count: 0,
+            checkpoint_at: None,
+            pruned_lightning_hashes: Default::default(),
+            checkpoint_proof: None,
+            spending_key_debits: Default::default(),
+            mint_records: Vec::new(),
+            settlement_statements: Vec::new(),
+            vouchers: Vec::new(),
+            seq: 0,
+            updated_at: Utc::now(),
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        state.signature = key.sign(&state.signable_bytes());
+        state
+    }
+
+    #[test]
+    fn test_attest_balance_from_user_contract() {
+        let mut state = DelegateState::default();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let now = Utc::now();
+        state.handle_request(
+            CreamRequest::ImportIdentity {
+                signing_key: key.to_bytes(),
+                role: UserRole::Customer,
+            },
+            now,
+        );
+
+        // The ledger's balance, not the mock wallet's
+        let resp =
+            state.handle_request(CreamRequest::AttestBalance(user_contract(&key, 2_500)), now);
+        let attestation = match resp {
+            CreamResponse::BalanceAttestation(a) => a,
+            other => panic!("Expected BalanceAttestation, got {:?}", other),
+        };
+        assert_eq!(attestation.balance, 2_500);
+        assert_eq!(attestation.ledger_entries, 1);
+        assert_eq!(attestation.user, UserId(key.verifying_key()));
+        assert!(attestation.verify());
+
+        // Someone else's contract is refused
+        let other = SigningKey::from_bytes(&[8; 32]);
+        let resp = state.handle_request(
+            CreamRequest::AttestBalance(user_contract(&other, 2_500)),
+            now,
+        );
+        assert!(matches!(resp, CreamResponse::Error(_)));
+    }
+
+    fn order(id: &str, total_price: u64, created_at: DateTime<Utc>) -> Order {
+        let customer = UserId(SigningKey::from_bytes(&[2; 32]).verifying_key());
+        Order {
+            id: cream_common::order::OrderId(id.into()),
+            product_id: ProductId("p-1".into()),
+            customer,
+            quantity: 1,
+            deposit_tier: cream_common::order::DepositTier::Reserve2Days,
+            deposit_amount: total_price / 10,
+            total_price,
+            status: cream_common::order::OrderStatus::Reserved {
+                expires_at: created_at,
+            },
+            created_at,
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_signing_policy_limits_orders_and_delays_loosening() {
+        let mut state = DelegateState::default();
+        let now = Utc::now();
+        state.handle_request(
+            CreamRequest::CreateIdentity {
+                role: UserRole::Customer,
+            },
+            now,
+        );
+        let gary = UserId(SigningKey::from_bytes(&[3; 32]).verifying_key());
+        let mallory = UserId(SigningKey::from_bytes(&[4; 32]).verifying_key());
+        let sign = |state: &mut DelegateState, id: &str, total, supplier: &UserId, at| {
+            state.handle_request(
+                CreamRequest::SignOrder {
+                    order: order(id, total, at),
+                    supplier: supplier.clone(),
+                },
+                at,
+            )
+        };
+
+        let policy = SigningPolicy {
+            max_order_value: Some(1_000),
+            max_daily_spend: Some(1_500),
+            allowed_suppliers: [gary.clone()].into(),
+            extra: Default::default(),
+        };
+        let resp = state.handle_request(CreamRequest::SetPolicy(policy.clone()), now);
+        assert!(
+            matches!(resp, CreamResponse::Policy { active, pending: None } if active == policy)
+        );
+
+        assert!(matches!(
+            sign(&mut state, "o-1", 1_200, &gary, now),
+            CreamResponse::PolicyViolation(PolicyViolation::OrderTooLarge { .. })
+        ));
+        assert!(matches!(
+            sign(&mut state, "o-1", 100, &mallory, now),
+            CreamResponse::PolicyViolation(PolicyViolation::SupplierNotAllowed)
+        ));
+        assert!(matches!(
+            sign(&mut state, "o-1", 1_000, &gary, now),
+            CreamResponse::SignedOrder(_)
+        ));
+        // Re-signing the same order doesn't count it twice
+        assert!(matches!(
+            sign(&mut state, "o-1", 1_000, &gary, now),
+            CreamResponse::SignedOrder(_)
+        ));
+        assert!(matches!(
+            sign(&mut state, "o-2", 600, &gary, now),
+            CreamResponse::PolicyViolation(PolicyViolation::DailyLimit { spent: 1_000, .. })
+        ));
+        let tomorrow = now + chrono::Duration::hours(25);
+        assert!(matches!(
+            sign(&mut state, "o-2", 600, &gary, tomorrow),
+            CreamResponse::SignedOrder(_)
+        ));
+
+        // Lifting the limits waits a day; tightening them again cancels that
+        let resp =
+            state.handle_request(CreamRequest::SetPolicy(SigningPolicy::default()), tomorrow);
+        assert!(
+            matches!(resp, CreamResponse::Policy { ref active, pending: Some(_) } if *active == policy)
+        );
+        assert!(matches!(
+            sign(&mut state, "o-3", 5_000, &mallory, tomorrow),
+            CreamResponse::PolicyViolation(_)
+        ));
+        let later = tomorrow + policy::POLICY_LOOSEN_DELAY;
+        assert!(matches!(
+            sign(&mut state, "o-3", 5_000, &mallory, later),
+            CreamResponse::SignedOrder(_)
+        ));
+        state.handle_request(CreamRequest::SetPolicy(SigningPolicy::default()), later);
+        state.handle_request(CreamRequest::SetPolicy(policy.clone()), later);
+        let resp = state.handle_request(CreamRequest::SetPolicy(SigningPolicy::default()), later);
+        assert!(matches!(
+            resp,
+            CreamResponse::Policy {
+                pending: Some(_),
+                ..
+            }
+        ));
+        state.handle_request(CreamRequest::SetPolicy(policy.clone()), later);
+        let resp =
+            state.handle_request(CreamRequest::GetPolicy, later + policy::POLICY_LOOSEN_DELAY);
+        assert!(
+            matches!(resp, CreamResponse::Policy { active, pending: None } if active == policy)
+        );
+    }
 }
@@ -0,0 +1,190 @@
+//! Order signing policy.
+//!
+//! The delegate holds the customer's key, so whatever the UI asks it to sign
+//! gets signed. A [`SigningPolicy`] bounds what a compromised or buggy UI can
+//! do with that: orders over a per-order or rolling 24-hour limit, or with a
+//! supplier outside the allow-list, are refused before the key is touched.
+//!
+//! Tightening a policy takes effect at once. Loosening one is held back for
+//! [`POLICY_LOOSEN_DELAY`], so code that can send `SetPolicy` can't lift the
+//! limits and drain the wallet in the same session.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use cream_common::identity::UserId;
+use cream_common::order::OrderId;
+
+/// How long a loosened policy waits before it replaces the active one.
+pub const POLICY_LOOSEN_DELAY: Duration = Duration::hours(24);
+
+/// The window `max_daily_spend` applies to.
+const SPEND_WINDOW: Duration = Duration::hours(24);
+
+/// Limits on the orders the delegate will sign. The default is no limits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigningPolicy {
+    /// Largest order total, in curds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_order_value: Option<u64>,
+    /// Most that may be signed for in any 24 hours, in curds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_daily_spend: Option<u64>,
+    /// Suppliers orders may be placed with; empty allows any supplier.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub allowed_suppliers: BTreeSet<UserId>,
+    /// Extension fields — preserves unknown fields across contract versions.
+    #[serde(flatten, default)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SigningPolicy {
+    /// Whether every order `other` allows is also allowed by `self`.
+    pub fn is_at_least_as_strict_as(&self, other: &SigningPolicy) -> bool {
+        fn limit_ok(mine: Option<u64>, theirs: Option<u64>) -> bool {
+            match (mine, theirs) {
+                (_, None) => true,
+                (None, Some(_)) => false,
+                (Some(mine), Some(theirs)) => mine <= theirs,
+            }
+        }
+        let suppliers_ok = other.allowed_suppliers.is_empty()
+            || (!self.allowed_suppliers.is_empty()
+                && self.allowed_suppliers.is_subset(&other.allowed_suppliers));
+        limit_ok(self.max_order_value, other.max_order_value)
+            && limit_ok(self.max_daily_spend, other.max_daily_spend)
+            && suppliers_ok
+    }
+}
+
+/// Why an order was refused.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PolicyViolation {
+    OrderTooLarge {
+        total: u64,
+        max: u64,
+    },
+    DailyLimit {
+        spent: u64,
+        requested: u64,
+        max: u64,
+    },
+    SupplierNotAllowed,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyViolation::OrderTooLarge { total, max } => {
+                write!(f, "order total {total} exceeds the {max} per-order limit")
+            }
+            PolicyViolation::DailyLimit { spent, requested, max } => write!(
+                f,
+                "{requested} on top of {spent} spent in the last 24 hours exceeds the {max} daily limit"
+            ),
+            PolicyViolation::SupplierNotAllowed => {
+                write!(f, "the supplier is not on the allow-list")
+            }
+        }
+    }
+}
+
+/// An order signed under the policy, counted towards the daily limit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Spend {
+    order_id: OrderId,
+    amount: u64,
+    at: DateTime<Utc>,
+}
+
+/// The active policy, any loosened policy waiting to take over, and the
+/// spending it has allowed recently. Persisted with the delegate state.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PolicyState {
+    active: SigningPolicy,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending: Option<(SigningPolicy, DateTime<Utc>)>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spends: Vec<Spend>,
+}
+
+impl PolicyState {
+    /// Promote a pending policy whose delay has passed and forget spends
+    /// outside the window.
+    fn advance(&mut self, now: DateTime<Utc>) {
+        if let Some((_, effective_at)) = &self.pending {
+            if *effective_at <= now {
+                self.active = self.pending.take().unwrap().0;
+            }
+        }
+        self.spends.retain(|spend| now - spend.at < SPEND_WINDOW);
+    }
+
+    pub fn active(&self) -> &SigningPolicy {
+        &self.active
+    }
+
+    /// A loosened policy and when it takes effect.
+    pub fn pending(&self) -> Option<&(SigningPolicy, DateTime<Utc>)> {
+        self.pending.as_ref()
+    }
+
+    /// Replace the policy: at once if `policy` is at least as strict as the
+    /// active one, otherwise after [`POLICY_LOOSEN_DELAY`]. A new policy
+    /// always cancels any pending one.
+    pub fn set(&mut self, policy: SigningPolicy, now: DateTime<Utc>) {
+        self.advance(now);
+        if policy.is_at_least_as_strict_as(&self.active) {
+            self.active = policy;
+            self.pending = None;
+        } else {
+            self.pending = Some((policy, now + POLICY_LOOSEN_DELAY));
+        }
+    }
+
+    /// Check an order against the active policy and, if it passes, count it
+    /// towards the daily limit. Re-signing an order already counted doesn't
+    /// count it twice.
+    pub fn authorize(
+        &mut self,
+        order_id: &OrderId,
+        total: u64,
+        supplier: &UserId,
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyViolation> {
+        self.advance(now);
+        let policy = &self.active;
+        if let Some(max) = policy.max_order_value {
+            if total > max {
+                return Err(PolicyViolation::OrderTooLarge { total, max });
+            }
+        }
+        if !policy.allowed_suppliers.is_empty() && !policy.allowed_suppliers.contains(supplier) {
+            return Err(PolicyViolation::SupplierNotAllowed);
+        }
+        let spent = self
+            .spends
+            .iter()
+            .filter(|spend| &spend.order_id != order_id)
+            .fold(0u64, |sum, spend| sum.saturating_add(spend.amount));
+        if let Some(max) = policy.max_daily_spend {
+            if spent.saturating_add(total) > max {
+                return Err(PolicyViolation::DailyLimit {
+                    spent,
+                    requested: total,
+                    max,
+                });
+            }
+        }
+        self.spends.retain(|spend| &spend.order_id != order_id);
+        self.spends.push(Spend {
+            order_id: order_id.clone(),
+            amount: total,
+            at: now,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    cream_fuzz::run::<cream_market_directory_contract::Contract>(data, &cream_fuzz::seeds::market_directory());
+});
@@ -0,0 +1,309 @@
+//! Fuzzing harness for the CREAM contracts.
+//!
+//! A panic inside a contract aborts its WASM instance and takes the node's
+//! update down with it, so every entry point must turn bad input into a
+//! `ContractError` (or an `Invalid` verdict) instead. [`run`] decodes the
+//! fuzzer's bytes into parameters, a state, a summary and a batch of updates
+//! and calls all four entry points of one contract with them; the targets in
+//! `fuzz_targets/` do that per contract.
+//!
+//! Inputs are a mix of raw bytes and structured mutations of valid seed
+//! states ([`seeds`]): truncations, byte flips, spliced garbage, and JSON
+//! nodes swapped for adversarial values (huge numbers, far-future dates, long
+//! strings, deep nesting). The seeds are unsigned, so build with `--features
+//! dev` to get mutated states past signature checks and into the merge logic.
+//!
+//! ```sh
+//! cargo +nightly fuzz run storefront                 # from the repo root
+//! cargo +nightly fuzz run storefront --features dev
+//! cargo test --manifest-path fuzz/Cargo.toml         # deterministic smoke run
+//! ```
+
+use freenet_stdlib::prelude::*;
+use serde_json::Value;
+
+pub mod seeds;
+
+/// Valid example parameters and states for one contract, to mutate.
+pub struct Seeds {
+    pub parameters: Vec<Vec<u8>>,
+    pub states: Vec<Vec<u8>>,
+}
+
+/// Feed `data` to every entry point of contract `C`. Results are ignored:
+/// any `Ok` or `Err` is fine, only a panic is a failure.
+pub fn run<C: ContractInterface>(data: &[u8], seeds: &Seeds) {
+    let mut input = Input::new(data);
+    let parameters = input.blob(&seeds.parameters);
+    let state = input.blob(&seeds.states);
+    let summary = match input.byte() % 3 {
+        0 => Vec::new(),
+        1 => summarize::<C>(&parameters, &state),
+        _ => input.blob(&seeds.states),
+    };
+    let updates: Vec<UpdateData<'static>> = (0..input.byte() % 4)
+        .map(|_| input.update(&seeds.states))
+        .collect();
+
+    let _ = C::validate_state(
+        Parameters::from(parameters.clone()),
+        State::from(state.clone()),
+        RelatedContracts::default(),
+    );
+    let _ = C::summarize_state(Parameters::from(parameters.clone()), State::from(state.clone()));
+    let _ = C::get_state_delta(
+        Parameters::from(parameters.clone()),
+        State::from(state.clone()),
+        StateSummary::from(summary),
+    );
+    let _ = C::update_state(Parameters::from(parameters), State::from(state), updates);
+}
+
+/// The contract's own summary of `state`, or nothing if it has none.
+fn summarize<C: ContractInterface>(parameters: &[u8], state: &[u8]) -> Vec<u8> {
+    C::summarize_state(Parameters::from(parameters.to_vec()), State::from(state.to_vec()))
+        .map(|summary| summary.as_ref().to_vec())
+        .unwrap_or_default()
+}
+
+/// Reads decisions out of the fuzzer's bytes; reads past the end give zeros.
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn byte(&mut self) -> u8 {
+        match self.data.split_first() {
+            Some((first, rest)) => {
+                self.data = rest;
+                *first
+            }
+            None => 0,
+        }
+    }
+
+    fn index(&mut self, len: usize) -> usize {
+        let n = u16::from_le_bytes([self.byte(), self.byte()]) as usize;
+        if len == 0 { 0 } else { n % len }
+    }
+
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let (taken, rest) = self.data.split_at(len.min(self.data.len()));
+        self.data = rest;
+        taken
+    }
+
+    /// Raw bytes, a seed as is, or a mutated seed.
+    fn blob(&mut self, seeds: &[Vec<u8>]) -> Vec<u8> {
+        let seed = || seeds.first().cloned().unwrap_or_default();
+        match self.byte() % 3 {
+            0 => {
+                let len = self.byte() as usize;
+                self.bytes(len).to_vec()
+            }
+            1 => seeds.get(self.index(seeds.len())).cloned().unwrap_or_else(seed),
+            _ => {
+                let base = seeds.get(self.index(seeds.len())).cloned().unwrap_or_else(seed);
+                self.mutate(base)
+            }
+        }
+    }
+
+    fn update(&mut self, seeds: &[Vec<u8>]) -> UpdateData<'static> {
+        match self.byte() % 4 {
+            0 => UpdateData::State(State::from(self.blob(seeds))),
+            1 => UpdateData::Delta(StateDelta::from(self.blob(seeds))),
+            2 => UpdateData::StateAndDelta {
+                state: State::from(self.blob(seeds)),
+                delta: StateDelta::from(self.blob(seeds)),
+            },
+            _ => UpdateData::RelatedState {
+                related_to: ContractInstanceId::new([self.byte(); 32]),
+                state: State::from(self.blob(seeds)),
+            },
+        }
+    }
+
+    fn mutate(&mut self, mut bytes: Vec<u8>) -> Vec<u8> {
+        let rounds = 1 + self.byte() % 4;
+        for _ in 0..rounds {
+            match self.byte() % 5 {
+                0 => bytes.truncate(self.index(bytes.len() + 1)),
+                1 if !bytes.is_empty() => {
+                    let at = self.index(bytes.len());
+                    bytes[at] ^= self.byte().max(1);
+                }
+                2 => {
+                    let at = self.index(bytes.len() + 1);
+                    let len = self.byte() as usize % 16;
+                    let garbage = self.bytes(len).to_vec();
+                    bytes.splice(at..at, garbage);
+                }
+                3 | 4 => bytes = self.mutate_json(bytes),
+                _ => {}
+            }
+        }
+        bytes
+    }
+
+    /// Replace one JSON node with an adversarial value (or drop an object
+    /// field). Bytes that aren't JSON are returned unchanged.
+    fn mutate_json(&mut self, bytes: Vec<u8>) -> Vec<u8> {
+        let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+            return bytes;
+        };
+        let target = self.index(count_nodes(&value));
+        let replacement = if self.byte().is_multiple_of(8) { None } else { Some(self.adversarial()) };
+        visit_nth(&mut value, target, replacement);
+        serde_json::to_vec(&value).unwrap_or(bytes)
+    }
+
+    fn adversarial(&mut self) -> Value {
+        match self.byte() % 16 {
+            0 => Value::Null,
+            1 => Value::Bool(self.byte().is_multiple_of(2)),
+            2 => Value::from(-1),
+            3 => Value::from(u64::MAX),
+            4 => Value::from(i64::MIN),
+            5 => Value::from(1e308),
+            6 => Value::from(""),
+            7 => Value::from("a".repeat(1 + self.index(100_000))),
+            8 => Value::Array(Vec::new()),
+            9 => Value::Object(Default::default()),
+            10 => nested(1 + self.index(200)),
+            // Timestamps at and past chrono's limits
+            11 => Value::from("+262142-12-31T23:59:59Z"),
+            12 => Value::from("-262143-01-01T00:00:00Z"),
+            13 => Value::from("1970-01-01T00:00:00Z"),
+            // Keys and ids of the wrong length or alphabet
+            14 => Value::from("00"),
+            _ => Value::from(u32::MAX),
+        }
+    }
+}
+
+/// `depth` nested arrays.
+fn nested(depth: usize) -> Value {
+    (0..depth).fold(Value::Null, |inner, _| Value::Array(vec![inner]))
+}
+
+fn count_nodes(value: &Value) -> usize {
+    1 + match value {
+        Value::Array(items) => items.iter().map(count_nodes).sum(),
+        Value::Object(fields) => fields.values().map(count_nodes).sum(),
+        _ => 0,
+    }
+}
+
+/// Replace the `n`th node in pre-order with `replacement`; `None` removes it
+/// from its parent object instead (the root is never removed). Returns how
+/// many nodes were visited, for the recursion.
+fn visit_nth(value: &mut Value, n: usize, replacement: Option<Value>) -> usize {
+    if n == 0 {
+        if let Some(replacement) = replacement {
+            *value = replacement;
+        }
+        return 1;
+    }
+    let mut seen = 1;
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                let size = count_nodes(item);
+                if n < seen + size {
+                    return seen + visit_nth(item, n - seen, replacement);
+                }
+                seen += size;
+            }
+        }
+        Value::Object(fields) => {
+            let mut remove = None;
+            for (key, field) in fields.iter_mut() {
+                let size = count_nodes(field);
+                if n < seen + size {
+                    if n == seen && replacement.is_none() {
+                        remove = Some(key.clone());
+                        break;
+                    }
+                    return seen + visit_nth(field, n - seen, replacement);
+                }
+                seen += size;
+            }
+            if let Some(key) = remove {
+                fields.remove(&key);
+            }
+        }
+        _ => {}
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic inputs for the smoke run (xorshift).
+    fn inputs(count: usize) -> impl Iterator<Item = Vec<u8>> {
+        let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..count).map(move |_| {
+            let len = (next() % 256) as usize;
+            (0..len).map(|_| next() as u8).collect()
+        })
+    }
+
+    fn smoke<C: ContractInterface>(seeds: Seeds) {
+        for data in inputs(2_000) {
+            run::<C>(&data, &seeds);
+        }
+    }
+
+    #[test]
+    fn mutation_replaces_and_removes_nodes() {
+        let mut value = serde_json::json!({"a": [1, 2], "b": {"c": 3}});
+        assert_eq!(count_nodes(&value), 6);
+        visit_nth(&mut value, 3, Some(Value::Null));
+        assert_eq!(value, serde_json::json!({"a": [1, null], "b": {"c": 3}}));
+        visit_nth(&mut value, 4, None);
+        assert_eq!(value, serde_json::json!({"a": [1, null]}));
+    }
+
+    #[test]
+    fn directory_never_panics() {
+        smoke::<cream_directory_contract::Contract>(seeds::directory());
+    }
+
+    #[test]
+    fn storefront_never_panics() {
+        smoke::<cream_storefront_contract::Contract>(seeds::storefront());
+    }
+
+    #[test]
+    fn user_contract_never_panics() {
+        smoke::<cream_user_contract::Contract>(seeds::user());
+    }
+
+    #[test]
+    fn inbox_never_panics() {
+        smoke::<cream_inbox_contract::Contract>(seeds::inbox());
+    }
+
+    #[test]
+    fn market_directory_never_panics() {
+        smoke::<cream_market_directory_contract::Contract>(seeds::market_directory());
+    }
+
+    #[test]
+    fn name_registry_never_panics() {
+        smoke::<cream_name_registry_contract::Contract>(seeds::name_registry());
+    }
+}
@@ -0,0 +1,322 @@
+//! Valid example inputs for each contract, for the harness to mutate.
+//!
+//! Built from the real types so they track the schema. Signatures are
+//! zeroed: without the `dev` feature the contracts reject these states at the
+//! signature check, which is itself a path worth fuzzing.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use freenet_stdlib::prelude::{ContractCode, ContractKey, Parameters};
+use serde::Serialize;
+
+use cream_common::directory::{DirectoryEntry, DirectoryShard, DirectoryShardParameters, DirectoryState};
+use cream_common::identity::UserId;
+use cream_common::inbox::{InboxMessage, InboxParameters, InboxState, MessageKind};
+use cream_common::location::GeoLocation;
+use cream_common::market::{MarketDirectoryState, MarketEntry, MarketEvent, SupplierStatus};
+use cream_common::message::{MessageAttachment, MessageId, OrderMessage, OrderThread};
+use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::registry::{normalize_name, NameClaim, NameRegistryState};
+use cream_common::storefront::{
+    SignedProduct, StorefrontInfo, StorefrontParameters, StorefrontState, WeeklySchedule,
+};
+use cream_common::tolls::TollRates;
+use cream_common::user_contract::{UserContractParameters, UserContractState};
+use cream_common::wallet::{TransactionKind, WalletTransaction};
+
+use crate::Seeds;
+
+fn key(seed: u8) -> VerifyingKey {
+    SigningKey::from_bytes(&[seed; 32]).verifying_key()
+}
+
+fn user_id(seed: u8) -> UserId {
+    UserId(key(seed))
+}
+
+fn contract_key(seed: u8) -> ContractKey {
+    ContractKey::from_params_and_code(Parameters::from(vec![seed]), ContractCode::from(vec![0u8]))
+}
+
+fn no_signature() -> Signature {
+    Signature::from_bytes(&[0u8; 64])
+}
+
+fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 4, day, 8, 30, 0).unwrap()
+}
+
+fn json(value: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(value).expect("seed serializes")
+}
+
+fn farm() -> GeoLocation {
+    GeoLocation::new(-30.3, 153.1)
+}
+
+pub fn directory() -> Seeds {
+    let entry = |seed: u8, name: &str, postcode: &str, day: u32| DirectoryEntry {
+        supplier: user_id(seed),
+        name: name.into(),
+        description: "Raw milk and cheese".into(),
+        location: farm(),
+        postcode: Some(postcode.into()),
+        locality: Some("Coffs Harbour".into()),
+        categories: vec![ProductCategory::Milk, ProductCategory::Cheese],
+        storefront_key: contract_key(seed),
+        user_contract_key: Some(contract_key(seed + 100)),
+        inbox_contract_key: None,
+        updated_at: at(day),
+        name_claimed_at: None,
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    let mut state = DirectoryState::default();
+    for e in [entry(1, "Gary's Farm", "2450", 1), entry(2, "Emma's Dairy", "2452", 2)] {
+        state.entries.insert(e.supplier.clone(), e);
+    }
+    state
+        .history
+        .insert(user_id(1), vec![entry(1, "Gary's Old Farm", "2450", 1)]);
+    state.history.get_mut(&user_id(1)).unwrap()[0].updated_at = at(1) - Duration::days(3);
+
+    Seeds {
+        parameters: vec![Vec::new(), DirectoryShardParameters::new(DirectoryShard::Nsw).to_bytes()],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
+
+pub fn storefront() -> Seeds {
+    let owner = user_id(1);
+    let product_id = ProductId("p-1".into());
+    let order_id = OrderId("o-1".into());
+    let product = SignedProduct {
+        product: Product {
+            id: product_id.clone(),
+            name: "Raw milk 2L".into(),
+            description: "Jersey milk".into(),
+            category: ProductCategory::Milk,
+            subcategory: None,
+            attributes: Default::default(),
+            price_curd: 500,
+            quantity_total: 10,
+            expiry_date: None,
+            updated_at: at(1),
+            created_at: at(1),
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
+        },
+        signature: no_signature(),
+        provenance: None,
+        price_history: Vec::new(),
+        extra: Default::default(),
+    };
+    let order = Order {
+        id: order_id.clone(),
+        product_id: product_id.clone(),
+        customer: user_id(2),
+        quantity: 2,
+        deposit_tier: DepositTier::Reserve2Days,
+        deposit_amount: 100,
+        total_price: 1000,
+        status: OrderStatus::Reserved { expires_at: at(3) },
+        created_at: at(1),
+        signature: no_signature(),
+        escrow_token: None,
+        collection_point: None,
+        delivery: None,
+        provenance: None,
+        receipt: None,
+        amendments: Vec::new(),
+        status_history: Vec::new(),
+        cancellation_policy: None,
+        refund_amount: None,
+        balance_attestation: None,
+        nonce: 0,
+        extra: Default::default(),
+    };
+    let message = OrderMessage {
+        id: MessageId("m-1".into()),
+        order_id: order_id.clone(),
+        author: user_id(2),
+        body: "Can I collect Saturday?".into(),
+        created_at: at(2),
+        reply_to: None,
+        attachment: Some(MessageAttachment::new("note.txt".into(), "text/plain".into(), b"gate code 1234")),
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    let mut thread = OrderThread::default();
+    thread.insert(message);
+    let state = StorefrontState {
+        info: StorefrontInfo {
+            owner: owner.clone(),
+            name: "Gary's Farm".into(),
+            description: "Raw milk".into(),
+            location: farm(),
+            schedule: Some(WeeklySchedule::default()),
+            timezone: Some("Australia/Sydney".into()),
+            phone: Some("0400 000 000".into()),
+            email: None,
+            address: None,
+            market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
+            extra: Default::default(),
+        },
+        products: BTreeMap::from([(product_id, product)]),
+        orders: BTreeMap::from([(order_id.clone(), order)]),
+        threads: BTreeMap::from([(order_id, thread)]),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
+        extra: Default::default(),
+    };
+
+    Seeds {
+        parameters: vec![json(&StorefrontParameters { owner: owner.0 })],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
+
+pub fn user() -> Seeds {
+    let transaction = |id: u32, kind, amount| WalletTransaction {
+        id,
+        kind,
+        amount,
+        description: "Milk".into(),
+        sender: "gary".into(),
+        receiver: "emma".into(),
+        tx_ref: format!("gary:{id}:1"),
+        timestamp: at(id).to_rfc3339(),
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+    let state = UserContractState {
+        owner: user_id(1),
+        name: "emma".into(),
+        origin_supplier: "gary".into(),
+        current_supplier: "gary".into(),
+        balance_curds: 700,
+        invited_by: "gary".into(),
+        ledger: vec![
+            transaction(1, TransactionKind::Credit, 1000),
+            transaction(2, TransactionKind::Debit, 300),
+        ],
+        next_tx_id: 3,
+        toll_rates: TollRates::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        seq: 0,
+        updated_at: at(2),
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    let parameters = UserContractParameters { owner: key(1), spending_key: None };
+
+    Seeds {
+        parameters: vec![json(&parameters)],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
+
+pub fn inbox() -> Seeds {
+    let message = |id: u64, kind| InboxMessage {
+        id,
+        kind,
+        from_name: "gary".into(),
+        from_key: None,
+        body: "Market on Saturday?".into(),
+        toll_paid: 1,
+        created_at: at(id as u32),
+        extra: Default::default(),
+    };
+    let state = InboxState {
+        owner: user_id(1),
+        messages: BTreeMap::from([
+            (1, message(1, MessageKind::DirectMessage)),
+            (2, message(2, MessageKind::MarketInvite { market_name: "Bellingen".into() })),
+        ]),
+        updated_at: at(2),
+        extra: Default::default(),
+    };
+
+    Seeds {
+        parameters: vec![json(&InboxParameters { owner: key(1) })],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
+
+pub fn market_directory() -> Seeds {
+    let entry = MarketEntry {
+        organizer: user_id(3),
+        name: "Bellingen Growers Market".into(),
+        description: "Fresh produce".into(),
+        venue_address: "Bellingen Park".into(),
+        location: farm(),
+        postcode: Some("2454".into()),
+        locality: Some("Bellingen".into()),
+        events: vec![MarketEvent {
+            date: NaiveDate::from_ymd_opt(2026, 4, 18).unwrap(),
+            start_time: "07:00".into(),
+            end_time: "13:00".into(),
+            extra: Default::default(),
+        }],
+        timezone: Some("Australia/Sydney".into()),
+        suppliers: BTreeMap::from([("gary".into(), SupplierStatus::Accepted)]),
+        updated_at: at(1),
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    let state = MarketDirectoryState {
+        entries: BTreeMap::from([(entry.name.clone(), entry)]),
+        extra: Default::default(),
+    };
+
+    Seeds {
+        parameters: vec![Vec::new()],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
+
+pub fn name_registry() -> Seeds {
+    let claim = NameClaim {
+        name: "Gary".into(),
+        owner: user_id(1),
+        user_contract_key: contract_key(1).to_string(),
+        claimed_at: at(1),
+        updated_at: at(1),
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    let state = NameRegistryState {
+        claims: BTreeMap::from([(normalize_name(&claim.name), claim)]),
+        extra: Default::default(),
+    };
+
+    Seeds {
+        parameters: vec![Vec::new()],
+        states: vec![Vec::new(), json(&state), json(&state.summarize())],
+    }
+}
@@ -0,0 +1,174 @@
+//! HTTP API: REST endpoints and a GraphQL endpoint over the same views.
+//!
+//! | Route | Answers |
+//! |---|---|
+//! | `GET /health` | connection status and view sizes |
+//! | `GET /suppliers?postcode=&radius_km=&category=` | listed suppliers, nearest first |
+//! | `GET /products?postcode=&radius_km=&category=` | products on offer, cheapest first |
+//! | `GET /orders/{id}` | an order's status, without the customer |
+//! | `POST /graphql` | `suppliers`, `products` and `order` queries |
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use cream_common::postcode::lookup_postcode;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::views::{Area, MarketViews, OrderSummary, ProductListing, Supplier};
+
+/// Radius used when a postcode is given without `radius_km`.
+const DEFAULT_RADIUS_KM: f64 = 50.0;
+
+pub type MarketSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+#[derive(Clone)]
+pub struct ApiState {
+    pub views: Arc<RwLock<MarketViews>>,
+    pub node_connected: Arc<AtomicBool>,
+    pub schema: MarketSchema,
+}
+
+impl ApiState {
+    pub fn new(views: Arc<RwLock<MarketViews>>, node_connected: Arc<AtomicBool>) -> Self {
+        let schema = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .data(views.clone())
+            .finish();
+        Self {
+            views,
+            node_connected,
+            schema,
+        }
+    }
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/health", get(health_handler))
+        .route("/suppliers", get(suppliers_handler))
+        .route("/products", get(products_handler))
+        .route("/orders/{id}", get(order_handler))
+        .route("/graphql", post(graphql_handler))
+        .with_state(state)
+}
+
+// ─── REST ────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: String,
+    node_connected: bool,
+    suppliers: usize,
+    storefronts: usize,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    postcode: Option<String>,
+    radius_km: Option<f64>,
+    category: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+fn error(status: StatusCode, message: String) -> Response {
+    (status, Json(ErrorResponse { error: message })).into_response()
+}
+
+/// The search area for an optional postcode and radius.
+fn area(postcode: Option<&str>, radius_km: Option<f64>) -> Result<Area, String> {
+    let Some(postcode) = postcode.map(str::trim).filter(|p| !p.is_empty()) else {
+        return Ok(Area::default());
+    };
+    let near = lookup_postcode(postcode).ok_or_else(|| format!("Unknown postcode {}", postcode))?;
+    Ok(Area {
+        near: Some(near),
+        radius_km: Some(radius_km.unwrap_or(DEFAULT_RADIUS_KM)),
+    })
+}
+
+async fn health_handler(State(state): State<ApiState>) -> Json<HealthResponse> {
+    let (suppliers, storefronts) = state.views.read().await.counts();
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        node_connected: state.node_connected.load(Ordering::Relaxed),
+        suppliers,
+        storefronts,
+    })
+}
+
+async fn suppliers_handler(State(state): State<ApiState>, Query(params): Query<SearchParams>) -> Response {
+    match area(params.postcode.as_deref(), params.radius_km) {
+        Ok(area) => Json(state.views.read().await.suppliers(&area, params.category.as_deref())).into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+async fn products_handler(State(state): State<ApiState>, Query(params): Query<SearchParams>) -> Response {
+    match area(params.postcode.as_deref(), params.radius_km) {
+        Ok(area) => Json(state.views.read().await.products(&area, params.category.as_deref())).into_response(),
+        Err(e) => error(StatusCode::BAD_REQUEST, e),
+    }
+}
+
+async fn order_handler(State(state): State<ApiState>, Path(id): Path<String>) -> Response {
+    match state.views.read().await.order(&id) {
+        Some(order) => Json(order).into_response(),
+        None => error(StatusCode::NOT_FOUND, format!("No order {}", id)),
+    }
+}
+
+// ─── GraphQL ─────────────────────────────────────────────────────────────────
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Listed suppliers, nearest first when `postcode` is given.
+    async fn suppliers(
+        &self,
+        ctx: &Context<'_>,
+        postcode: Option<String>,
+        radius_km: Option<f64>,
+        category: Option<String>,
+    ) -> async_graphql::Result<Vec<Supplier>> {
+        let area = area(postcode.as_deref(), radius_km)?;
+        let views = ctx.data::<Arc<RwLock<MarketViews>>>()?;
+        Ok(views.read().await.suppliers(&area, category.as_deref()))
+    }
+
+    /// Products on offer, cheapest first.
+    async fn products(
+        &self,
+        ctx: &Context<'_>,
+        postcode: Option<String>,
+        radius_km: Option<f64>,
+        category: Option<String>,
+    ) -> async_graphql::Result<Vec<ProductListing>> {
+        let area = area(postcode.as_deref(), radius_km)?;
+        let views = ctx.data::<Arc<RwLock<MarketViews>>>()?;
+        Ok(views.read().await.products(&area, category.as_deref()))
+    }
+
+    /// An order's status by id.
+    async fn order(&self, ctx: &Context<'_>, id: String) -> async_graphql::Result<Option<OrderSummary>> {
+        let views = ctx.data::<Arc<RwLock<MarketViews>>>()?;
+        Ok(views.read().await.order(&id))
+    }
+}
+
+async fn graphql_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(state.schema.execute(request).await)
+}
@@ -0,0 +1,31 @@
+//! Directory contract keys for the gateway.
+//!
+//! The gateway only reads. It needs the directory contracts' keys to follow
+//! them, and those are derived from the contract code, so the directory WASM
+//! is embedded as in the guardian. Storefront keys come from the directory
+//! listings themselves.
+
+use std::sync::Arc;
+
+use cream_common::directory::{DirectoryShard, DirectoryShardParameters};
+use freenet_stdlib::prelude::*;
+
+const DIRECTORY_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm");
+
+fn contract_key(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractKey {
+    let code = ContractCode::from(wasm_bytes.to_vec());
+    *WrappedContract::new(Arc::new(code), params).key()
+}
+
+/// Keys of the legacy global directory and every regional shard.
+pub fn directory_keys() -> Vec<ContractKey> {
+    std::iter::once(contract_key(DIRECTORY_WASM, Parameters::from(vec![])))
+        .chain(DirectoryShard::ALL.iter().map(|shard| {
+            contract_key(
+                DIRECTORY_WASM,
+                Parameters::from(DirectoryShardParameters::new(*shard).to_bytes()),
+            )
+        }))
+        .collect()
+}
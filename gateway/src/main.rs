@@ -0,0 +1,69 @@
+//! CREAM market gateway.
+//!
+//! A read-only service for clients that can't run a Freenet node — websites,
+//! maps, chat bots. It connects to a node (`--node-url`), follows the
+//! directory and every listed storefront, keeps materialized views of them
+//! in memory (see [`views`]) and serves queries over REST and GraphQL (see
+//! [`api`]): suppliers near a postcode, products by category and order
+//! status lookups.
+//!
+//! The gateway holds no keys and never writes to the network; anything it
+//! serves can be checked against the contracts themselves.
+
+mod api;
+mod contracts;
+mod node;
+mod views;
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use axum::http::Method;
+use clap::Parser;
+use tokio::sync::RwLock;
+use tower_http::cors::{Any, CorsLayer};
+
+use crate::api::ApiState;
+use crate::views::MarketViews;
+
+#[derive(Parser)]
+#[command(name = "cream-gateway", about = "CREAM REST/GraphQL market gateway")]
+struct Cli {
+    /// WebSocket URL of the Freenet node to follow
+    #[arg(long, default_value = "ws://localhost:3001/v1/contract/command?encodingProtocol=native")]
+    node_url: String,
+
+    /// HTTP port to listen on
+    #[arg(long, default_value_t = 3030)]
+    port: u16,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    let views = Arc::new(RwLock::new(MarketViews::default()));
+    let node_connected = Arc::new(AtomicBool::new(false));
+
+    let directory_keys = contracts::directory_keys();
+    println!("Gateway: following {} directory contracts", directory_keys.len());
+    tokio::spawn(node::follow_market(
+        cli.node_url.clone(),
+        directory_keys,
+        views.clone(),
+        node_connected.clone(),
+    ));
+
+    let cors = CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers(Any);
+    let app = api::router(ApiState::new(views, node_connected)).layer(cors);
+
+    let addr = format!("0.0.0.0:{}", cli.port);
+    println!("Gateway listening on {}", addr);
+    let listener = tokio::net::TcpListener::bind(&addr)
+        .await
+        .expect("Failed to bind");
+    axum::serve(listener, app).await.expect("Server error");
+}
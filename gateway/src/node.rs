@@ -0,0 +1,126 @@
+//! Node follower: keeps [`MarketViews`] in step with the network.
+//!
+//! Connects to the node over WebSocket, follows (GET + subscribe) the
+//! directory and its shards, then every storefront they list, and applies
+//! each state seen to the views. Reconnects with backoff, following
+//! everything again.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use cream_common::retry::RetryPolicy;
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest, ContractResponse, HostResponse, WebApi};
+use freenet_stdlib::prelude::{ContractKey, UpdateData};
+use tokio::sync::RwLock;
+
+use crate::views::MarketViews;
+
+const BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// Follow the market on the node at `node_url` forever, updating `views`.
+/// `connected` reflects whether the follower is currently connected.
+pub async fn follow_market(
+    node_url: String,
+    directory_keys: Vec<ContractKey>,
+    views: Arc<RwLock<MarketViews>>,
+    connected: Arc<AtomicBool>,
+) {
+    let is_directory = |key: &ContractKey| directory_keys.iter().any(|k| k.id() == key.id());
+    let mut backoff = RetryPolicy::exponential(BACKOFF_INITIAL, BACKOFF_MAX).backoff(rand::random());
+
+    loop {
+        println!("Node follower: connecting to {} ...", node_url);
+        let ws_conn = match tokio_tungstenite::connect_async(&node_url).await {
+            Ok((conn, _)) => conn,
+            Err(e) => {
+                let delay = backoff.next_delay().unwrap_or(BACKOFF_MAX);
+                println!("Node follower: WebSocket connect failed: {} (retrying in {:?})", e, delay);
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        };
+        let mut api = WebApi::start(ws_conn);
+
+        // Storefronts followed on this connection
+        let mut followed: HashSet<ContractKey> = HashSet::new();
+        let mut setup_failed = false;
+        for key in &directory_keys {
+            if let Err(e) = api.send(follow_request(key)).await {
+                println!("Node follower: failed to follow directory {}: {}", key, e);
+                setup_failed = true;
+                break;
+            }
+        }
+        if setup_failed {
+            tokio::time::sleep(backoff.next_delay().unwrap_or(BACKOFF_MAX)).await;
+            continue;
+        }
+
+        connected.store(true, Ordering::Relaxed);
+        backoff.reset();
+        println!("Node follower: connected — following {} directory contracts", directory_keys.len());
+
+        loop {
+            let (key, state) = match api.recv().await {
+                Ok(HostResponse::ContractResponse(ContractResponse::GetResponse { key, state, .. })) => {
+                    (key, state.as_ref().to_vec())
+                }
+                Ok(HostResponse::ContractResponse(ContractResponse::UpdateNotification { key, update, .. })) => {
+                    match update {
+                        UpdateData::State(s) | UpdateData::StateAndDelta { state: s, .. } => {
+                            (key, s.as_ref().to_vec())
+                        }
+                        _ => {
+                            // Not a full state — fetch the merged one
+                            if let Err(e) = api.send(follow_request(&key)).await {
+                                println!("Node follower: failed to GET {}: {}", key, e);
+                            }
+                            continue;
+                        }
+                    }
+                }
+                Ok(HostResponse::ContractResponse(ContractResponse::NotFound { instance_id })) => {
+                    println!("Node follower: contract {} not found", instance_id);
+                    continue;
+                }
+                Ok(_) => continue,
+                Err(e) => {
+                    println!("Node follower: connection error: {} — reconnecting", e);
+                    break;
+                }
+            };
+
+            if is_directory(&key) {
+                let listed = views.write().await.apply_directory(&state);
+                for storefront in listed {
+                    if followed.insert(storefront) {
+                        if let Err(e) = api.send(follow_request(&storefront)).await {
+                            println!("Node follower: failed to follow storefront {}: {}", storefront, e);
+                        }
+                    }
+                }
+            } else {
+                let mut views = views.write().await;
+                if views.is_listed(key.id()) && !views.apply_storefront(*key.id(), &state) {
+                    println!("Node follower: ignoring unparseable storefront state for {}", key);
+                }
+            }
+        }
+
+        connected.store(false, Ordering::Relaxed);
+        tokio::time::sleep(backoff.next_delay().unwrap_or(BACKOFF_MAX)).await;
+    }
+}
+
+/// GET `key` and subscribe to it.
+fn follow_request(key: &ContractKey) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Get {
+        key: *key.id(),
+        return_contract_code: false,
+        subscribe: true,
+        blocking_subscribe: false,
+    })
+}
@@ -0,0 +1,227 @@
+//! Materialized views of the market.
+//!
+//! The gateway keeps the merged directory (legacy directory plus every shard)
+//! and the latest state of each listed storefront in memory, and answers
+//! queries from them. Views only ever hold what the node has validated; the
+//! customer behind an order is never exposed.
+
+use std::collections::HashMap;
+
+use async_graphql::SimpleObject;
+use chrono::Utc;
+use cream_common::directory::{DirectoryEntry, DirectoryState};
+use cream_common::location::GeoLocation;
+use cream_common::order::Order;
+use cream_common::storefront::StorefrontState;
+use freenet_stdlib::prelude::{ContractInstanceId, ContractKey};
+use serde::Serialize;
+
+/// A listed supplier.
+#[derive(Clone, Debug, Serialize, SimpleObject)]
+pub struct Supplier {
+    pub name: String,
+    pub description: String,
+    pub postcode: Option<String>,
+    pub locality: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Distance from the queried postcode, when one was given.
+    pub distance_km: Option<f64>,
+    pub categories: Vec<String>,
+    pub storefront_key: String,
+    /// Products in the storefront, or 0 if it hasn't been fetched yet.
+    pub product_count: u32,
+}
+
+/// A product on offer at a listed storefront.
+#[derive(Clone, Debug, Serialize, SimpleObject)]
+pub struct ProductListing {
+    pub supplier: String,
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub price_curd: u64,
+    pub available: u32,
+    /// Distance to the supplier from the queried postcode, when one was given.
+    pub distance_km: Option<f64>,
+}
+
+/// An order's public progress, without the customer's identity.
+#[derive(Clone, Debug, Serialize, SimpleObject)]
+pub struct OrderSummary {
+    pub id: String,
+    pub supplier: String,
+    pub product_id: String,
+    pub product_name: Option<String>,
+    pub quantity: u32,
+    pub total_price: u64,
+    pub status: String,
+    /// RFC 3339 timestamp.
+    pub created_at: String,
+}
+
+/// Where to search from, and how far.
+#[derive(Clone, Debug, Default)]
+pub struct Area {
+    pub near: Option<GeoLocation>,
+    pub radius_km: Option<f64>,
+}
+
+impl Area {
+    /// Distance from the search centre to `location` (`Some(None)` when
+    /// there is no centre), or `None` if it is outside the radius.
+    fn distance(&self, location: &GeoLocation) -> Option<Option<f64>> {
+        let Some(near) = &self.near else {
+            return Some(None);
+        };
+        let distance = near.distance_km(location);
+        match self.radius_km {
+            Some(radius) if distance > radius => None,
+            _ => Some(Some(distance)),
+        }
+    }
+}
+
+/// The directory and storefronts as last seen on the node.
+#[derive(Default)]
+pub struct MarketViews {
+    directory: DirectoryState,
+    storefronts: HashMap<ContractInstanceId, StorefrontState>,
+}
+
+impl MarketViews {
+    /// Merge a directory (or shard) state into the view. Returns the
+    /// storefronts it lists, to follow; a state that doesn't parse lists
+    /// nothing.
+    pub fn apply_directory(&mut self, state: &[u8]) -> Vec<ContractKey> {
+        let Ok(directory) = serde_json::from_slice::<DirectoryState>(state) else {
+            return Vec::new();
+        };
+        let listed = directory.entries.values().map(|e| e.storefront_key).collect();
+        self.directory.merge(directory);
+        listed
+    }
+
+    /// Whether `id` is a storefront listed in the directory.
+    pub fn is_listed(&self, id: &ContractInstanceId) -> bool {
+        self.directory.entries.values().any(|e| e.storefront_key.id() == id)
+    }
+
+    /// Merge a storefront state into the view. Returns false if it doesn't
+    /// parse.
+    pub fn apply_storefront(&mut self, id: ContractInstanceId, state: &[u8]) -> bool {
+        let Ok(storefront) = serde_json::from_slice::<StorefrontState>(state) else {
+            return false;
+        };
+        match self.storefronts.get_mut(&id) {
+            Some(existing) => existing.merge(storefront),
+            None => {
+                self.storefronts.insert(id, storefront);
+            }
+        }
+        true
+    }
+
+    /// Listed suppliers in `area`, nearest first when searching from a
+    /// postcode, otherwise by name. `category` (a top-level label, any case)
+    /// keeps only suppliers selling it.
+    pub fn suppliers(&self, area: &Area, category: Option<&str>) -> Vec<Supplier> {
+        let mut suppliers: Vec<Supplier> = self
+            .listed()
+            .filter(|entry| {
+                category.is_none_or(|c| entry.categories.iter().any(|ec| ec.label().eq_ignore_ascii_case(c)))
+            })
+            .filter_map(|entry| {
+                let distance_km = area.distance(&entry.location)?;
+                let product_count = self
+                    .storefronts
+                    .get(entry.storefront_key.id())
+                    .map_or(0, |sf| sf.products.len() as u32);
+                Some(Supplier {
+                    name: entry.name.clone(),
+                    description: entry.description.clone(),
+                    postcode: entry.postcode.clone(),
+                    locality: entry.locality.clone(),
+                    latitude: entry.location.latitude,
+                    longitude: entry.location.longitude,
+                    distance_km,
+                    categories: entry.categories.iter().map(|c| c.label().to_string()).collect(),
+                    storefront_key: entry.storefront_key.to_string(),
+                    product_count,
+                })
+            })
+            .collect();
+        suppliers.sort_by(|a, b| {
+            a.distance_km
+                .partial_cmp(&b.distance_km)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        suppliers
+    }
+
+    /// Products of listed suppliers in `area`, optionally only those in
+    /// `category` (a top-level label, any case), cheapest first.
+    pub fn products(&self, area: &Area, category: Option<&str>) -> Vec<ProductListing> {
+        let mut products = Vec::new();
+        for entry in self.listed() {
+            let Some(distance_km) = area.distance(&entry.location) else {
+                continue;
+            };
+            let Some(sf) = self.storefronts.get(entry.storefront_key.id()) else {
+                continue;
+            };
+            for (id, signed) in &sf.products {
+                let product = &signed.product;
+                let (top_level, _) = product.taxonomy();
+                if category.is_some_and(|c| !top_level.label().eq_ignore_ascii_case(c)) {
+                    continue;
+                }
+                products.push(ProductListing {
+                    supplier: entry.name.clone(),
+                    id: id.0.clone(),
+                    name: product.name.clone(),
+                    description: product.description.clone(),
+                    category: product.category_label(),
+                    price_curd: product.price_curd,
+                    available: sf.available_quantity(id),
+                    distance_km,
+                });
+            }
+        }
+        products.sort_by(|a, b| a.price_curd.cmp(&b.price_curd).then_with(|| a.name.cmp(&b.name)));
+        products
+    }
+
+    /// Look up an order by id across every followed storefront.
+    pub fn order(&self, order_id: &str) -> Option<OrderSummary> {
+        self.listed().find_map(|entry| {
+            let sf = self.storefronts.get(entry.storefront_key.id())?;
+            let order = sf.orders.values().find(|o| o.id.0 == order_id)?;
+            Some(summarize_order(&entry.name, sf, order))
+        })
+    }
+
+    /// Counts for the health endpoint: listed suppliers and fetched storefronts.
+    pub fn counts(&self) -> (usize, usize) {
+        (self.listed().count(), self.storefronts.len())
+    }
+
+    fn listed(&self) -> impl Iterator<Item = &DirectoryEntry> {
+        self.directory.listed_entries(Utc::now())
+    }
+}
+
+fn summarize_order(supplier: &str, sf: &StorefrontState, order: &Order) -> OrderSummary {
+    OrderSummary {
+        id: order.id.0.clone(),
+        supplier: supplier.to_string(),
+        product_id: order.product_id.0.clone(),
+        product_name: sf.products.get(&order.product_id).map(|p| p.product.name.clone()),
+        quantity: order.quantity,
+        total_price: order.total_price,
+        status: order.status.label().to_string(),
+        created_at: order.created_at.to_rfc3339(),
+    }
+}
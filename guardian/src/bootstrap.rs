@@ -0,0 +1,280 @@
+//! Network bootstrap (`--bootstrap`).
+//!
+//! Once the guardian's keys are ready (DKG complete or loaded from disk), the
+//! bootstrapping guardian deploys the core contracts to its node: the
+//! directory, its regional shards, and the root user contract owned by the
+//! FROST group key, with a group-signed genesis state. Before anything is sent
+//! it checks that every peer holds the same group key and that the root
+//! contract built by [`contracts`] is parameterised by that key; after each
+//! PUT it checks the node derived the same contract key. Contracts the node
+//! already has are left alone, so re-running the bootstrap is harmless.
+
+use std::time::Duration;
+
+use cream_common::directory::{DirectoryShard, DirectoryState};
+use cream_common::identity::{UserId, ROOT_USER_NAME};
+use cream_common::retry::{retry, CancellationToken, RetryPolicy};
+use cream_common::signing_request::SigningRequest;
+use cream_common::user_contract::{UserContractParameters, UserContractState};
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest, ContractResponse, HostResponse, WebApi};
+use freenet_stdlib::prelude::{ContractContainer, ContractKey, RelatedContracts, WrappedState};
+use frost_ed25519 as frost;
+
+use crate::snapshots::CriticalContract;
+use crate::{
+    contracts, get_request, mint, poll_until, snapshot_state, AppState, PEER_BACKOFF_INITIAL, PEER_BACKOFF_MAX,
+    READY_POLL_INTERVAL,
+};
+
+/// How long to wait for the node to answer a GET or PUT.
+const NODE_TIMEOUT: Duration = Duration::from_secs(30);
+/// Attempts to reach each peer's `/public-key` before going on without it.
+const PEER_ATTEMPTS: u32 = 20;
+
+/// A contract to deploy, with the state to deploy it with.
+struct Deployment {
+    name: String,
+    contract: ContractContainer,
+    state: Vec<u8>,
+    /// Snapshot it like the node monitor would, so it can be repaired.
+    snapshot: Option<CriticalContract>,
+}
+
+/// Wait for keys, verify them, then deploy the core contracts to `node_url`.
+pub async fn run(state: &AppState, node_url: &str) -> Result<(), String> {
+    poll_until(READY_POLL_INTERVAL, || std::future::ready(state.is_ready())).await;
+    let pubkey_package = state
+        .public_key_package
+        .read()
+        .await
+        .clone()
+        .ok_or("Guardian not ready")?;
+    let group_key = cream_common::frost::group_verifying_key(&pubkey_package);
+    println!("Bootstrap: group key {}", hex_key(&group_key));
+
+    let peers = state.peers.read().await.clone();
+    verify_peers_agree(&peers, &group_key).await?;
+    let root_contract = contracts::root_user_contract(&pubkey_package);
+    verify_root_parameters(&root_contract, &group_key)?;
+    if group_key != cream_common::frost::dev_root_verifying_key() {
+        println!(
+            "Bootstrap: NOTE group key differs from the dev trusted-dealer key; clients that \
+             derive the root contract from the dev key will not find this one"
+        );
+    }
+
+    println!("Bootstrap: connecting to {} ...", node_url);
+    let (conn, _) = tokio_tungstenite::connect_async(node_url)
+        .await
+        .map_err(|e| format!("WebSocket connect to {} failed: {}", node_url, e))?;
+    let mut api = WebApi::start(conn);
+
+    // Guardians won't sign a genesis state once they've seen the root
+    // contract, so only ask when the node doesn't have it (one it has is
+    // left alone below, state unused)
+    let genesis = match fetch(&mut api, &root_contract.key()).await? {
+        Some(_) => Vec::new(),
+        None => genesis_root_state(state, &group_key).await?,
+    };
+
+    let empty_directory = serde_json::to_vec(&DirectoryState::default())
+        .map_err(|e| format!("Failed to serialize directory: {}", e))?;
+    let mut deployments = vec![
+        Deployment {
+            name: "directory".to_string(),
+            contract: contracts::directory_contract(),
+            state: empty_directory.clone(),
+            snapshot: Some(CriticalContract::Directory),
+        },
+        Deployment {
+            name: "root user contract".to_string(),
+            contract: root_contract,
+            state: genesis,
+            snapshot: Some(CriticalContract::RootUserContract),
+        },
+    ];
+    deployments.extend(DirectoryShard::ALL.iter().map(|shard| Deployment {
+        name: format!("directory shard {}", shard.label()),
+        contract: contracts::directory_shard_contract(*shard),
+        state: empty_directory.clone(),
+        snapshot: None,
+    }));
+
+    for deployment in deployments {
+        let key = deployment.contract.key();
+        if let Some(existing) = fetch(&mut api, &key).await? {
+            println!("Bootstrap: {} already deployed at {} — leaving it", deployment.name, key);
+            if let Some(which) = deployment.snapshot {
+                snapshot_state(state, which, &key, &existing, &group_key);
+            }
+            continue;
+        }
+        put(&mut api, &deployment).await?;
+        println!("Bootstrap: deployed {} at {}", deployment.name, key);
+        if let Some(which) = deployment.snapshot {
+            snapshot_state(state, which, &key, &deployment.state, &group_key);
+        }
+    }
+    Ok(())
+}
+
+/// Every reachable peer must report the same group key as ours; a mismatch
+/// means the DKG went wrong and nothing should be deployed. Peers still
+/// finishing the DKG answer 503, so each is retried for a while.
+async fn verify_peers_agree(peers: &[String], group_key: &ed25519_dalek::VerifyingKey) -> Result<(), String> {
+    let client = reqwest::Client::new();
+    let policy = RetryPolicy::exponential(PEER_BACKOFF_INITIAL, PEER_BACKOFF_MAX).with_max_attempts(PEER_ATTEMPTS);
+    for peer in peers {
+        let fetched = retry(
+            policy.backoff(rand::random()),
+            &CancellationToken::new(),
+            tokio::time::sleep,
+            |_| {
+                let client = &client;
+                async move {
+                    client
+                        .get(format!("{}/public-key", peer))
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status())
+                        .map_err(|e| e.to_string())?
+                        .json::<frost::keys::PublicKeyPackage>()
+                        .await
+                        .map_err(|e| format!("bad public key: {}", e))
+                }
+            },
+        )
+        .await;
+        let package = match fetched {
+            Ok(package) => package,
+            Err(e) => {
+                println!("Bootstrap: peer {} unavailable to confirm the group key: {}", peer, e);
+                continue;
+            }
+        };
+        let theirs = cream_common::frost::group_verifying_key(&package);
+        if theirs != *group_key {
+            return Err(format!(
+                "Peer {} has group key {}, ours is {}",
+                peer,
+                hex_key(&theirs),
+                hex_key(group_key)
+            ));
+        }
+        println!("Bootstrap: peer {} agrees on the group key", peer);
+    }
+    Ok(())
+}
+
+/// The root contract's parameters must name the group key as owner, with no
+/// spending key, or its contract key isn't the one clients and the node
+/// monitor derive.
+fn verify_root_parameters(contract: &ContractContainer, group_key: &ed25519_dalek::VerifyingKey) -> Result<(), String> {
+    let params: UserContractParameters = serde_json::from_slice(contract.params().as_ref())
+        .map_err(|e| format!("Root contract parameters don't parse: {}", e))?;
+    if params.owner != *group_key {
+        return Err(format!(
+            "Root contract is owned by {}, not the group key {}",
+            hex_key(&params.owner),
+            hex_key(group_key)
+        ));
+    }
+    if params.spending_key.is_some() {
+        return Err("Root contract parameters carry a spending key".to_string());
+    }
+    println!("Bootstrap: root user contract key {}", contract.key());
+    Ok(())
+}
+
+/// An empty root user contract, signed by the guardian group.
+async fn genesis_root_state(state: &AppState, group_key: &ed25519_dalek::VerifyingKey) -> Result<Vec<u8>, String> {
+    let mut root = UserContractState {
+        owner: UserId(*group_key),
+        name: ROOT_USER_NAME.to_string(),
+        origin_supplier: String::new(),
+        current_supplier: String::new(),
+        balance_curds: 0,
+        invited_by: String::new(),
+        ledger: Vec::new(),
+        next_tx_id: 0,
+        toll_rates: Default::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        seq: 0,
+        updated_at: chrono::Utc::now(),
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    };
+    // sign_as_group checks the aggregate against the group key
+    let peers = state.peers.read().await.clone();
+    let request = SigningRequest::Genesis { root_state: root.clone() };
+    root.signature = mint::sign_as_group(state, &peers, &request).await?;
+    println!("Bootstrap: genesis root state signed by the group");
+    serde_json::to_vec(&root).map_err(|e| format!("Failed to serialize root state: {}", e))
+}
+
+/// The node's current state for `key`, or `None` if it doesn't have it.
+async fn fetch(api: &mut WebApi, key: &ContractKey) -> Result<Option<Vec<u8>>, String> {
+    api.send(get_request(key))
+        .await
+        .map_err(|e| format!("Failed to GET {}: {}", key, e))?;
+    loop {
+        match recv(api).await? {
+            HostResponse::ContractResponse(ContractResponse::GetResponse { key: got, state, .. })
+                if got.id() == key.id() =>
+            {
+                return Ok(Some(state.as_ref().to_vec()));
+            }
+            HostResponse::ContractResponse(ContractResponse::NotFound { instance_id })
+                if instance_id == *key.id() =>
+            {
+                return Ok(None);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// PUT a deployment and wait for the node to confirm it under the key we
+/// derived.
+async fn put(api: &mut WebApi, deployment: &Deployment) -> Result<(), String> {
+    let expected = deployment.contract.key();
+    api.send(ClientRequest::ContractOp(ContractRequest::Put {
+        contract: deployment.contract.clone(),
+        state: WrappedState::new(deployment.state.clone()),
+        related_contracts: RelatedContracts::default(),
+        subscribe: false,
+        blocking_subscribe: false,
+    }))
+    .await
+    .map_err(|e| format!("Failed to PUT {}: {}", deployment.name, e))?;
+    loop {
+        if let HostResponse::ContractResponse(ContractResponse::PutResponse { key }) = recv(api).await? {
+            if key.id() != expected.id() {
+                return Err(format!(
+                    "Node put {} at {}, but we derived {}",
+                    deployment.name, key, expected
+                ));
+            }
+            return Ok(());
+        }
+    }
+}
+
+async fn recv(api: &mut WebApi) -> Result<HostResponse, String> {
+    tokio::time::timeout(NODE_TIMEOUT, api.recv())
+        .await
+        .map_err(|_| "Timed out waiting for the node".to_string())?
+        .map_err(|e| format!("Node error: {}", e))
+}
+
+fn hex_key(key: &ed25519_dalek::VerifyingKey) -> String {
+    key.as_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,238 @@
+//! The guardian config file, and peer discovery through DNS seeds.
+//!
+//! Settings that would otherwise be passed as flags on every start can live
+//! in a TOML file (`--config`, or `guardian.toml` under the user config
+//! directory's `cream/` if present):
+//!
+//! ```toml
+//! share_index = 2
+//! port = 3011
+//! min_signers = 2
+//! max_signers = 3
+//! node_url = "ws://localhost:3005/v1/contract/command?encodingProtocol=native"
+//! peers = ["http://guardian-1.example:3010", "http://guardian-3.example:3012"]
+//! dns_seeds = ["guardians.example:3010"]
+//! public_url = "http://guardian-2.example:3011"
+//! max_mint_curds = 100000000
+//! ```
+//!
+//! A flag given on the command line wins over the same setting in the file.
+//!
+//! `peers` are listed in share-index order, as DKG, refresh and redeal
+//! require. DNS seeds add the guardians their names resolve to, in no
+//! particular order, so they only widen the set asked to co-sign mints and
+//! bootstrap records; a ceremony still needs the ordered list.
+//!
+//! On SIGHUP the guardian re-reads the file and re-resolves the seeds (see
+//! [`watch_for_reload`]), updating its signing peers and admin keys. The
+//! other settings take effect on the next restart.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+
+use crate::{AppState, Cli};
+
+/// Port assumed for a DNS seed given without one (guardian 1's default).
+pub const DEFAULT_SEED_PORT: u16 = 3010;
+
+/// Guardian settings read from the config file. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuardianConfig {
+    pub share_index: Option<u16>,
+    pub port: Option<u16>,
+    pub max_signers: Option<u16>,
+    pub min_signers: Option<u16>,
+    /// Peer guardian URLs in share-index order, excluding this guardian.
+    #[serde(default)]
+    pub peers: Vec<String>,
+    /// `host[:port]` names resolving to guardians, one address per guardian.
+    #[serde(default)]
+    pub dns_seeds: Vec<String>,
+    /// This guardian's URL as peers reach it, dropped from seed results.
+    pub public_url: Option<String>,
+    pub node_url: Option<String>,
+    pub max_storefronts: Option<usize>,
+    #[serde(default)]
+    pub admin_pubkeys: Vec<String>,
+    pub pegin_limit_sats: Option<u64>,
+    pub daily_pegout_limit_sats: Option<u64>,
+    pub max_mint_curds: Option<u64>,
+    pub max_root_debit_curds: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// The config file used when `--config` isn't given.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("cream").join("guardian.toml"))
+}
+
+pub fn load(path: &Path) -> Result<GuardianConfig, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+    toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+}
+
+/// The config file in effect: `explicit` (which must load), else the
+/// default file if one exists. `None` without either.
+pub fn locate(explicit: Option<&Path>) -> Result<Option<(PathBuf, GuardianConfig)>, ConfigError> {
+    match explicit {
+        Some(path) => load(path).map(|config| Some((path.to_path_buf(), config))),
+        None => match default_path().filter(|path| path.exists()) {
+            Some(path) => load(&path).map(|config| Some((path, config))),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Whether the flag for `field` was left to its default.
+fn unset(matches: &ArgMatches, field: &str) -> bool {
+    !matches!(matches.value_source(field), Some(ValueSource::CommandLine | ValueSource::EnvVariable))
+}
+
+impl GuardianConfig {
+    /// Fill the settings `cli` left unset on the command line from the file.
+    pub fn apply(&self, cli: &mut Cli, matches: &ArgMatches) {
+        if let Some(v) = self.share_index.filter(|_| unset(matches, "share_index")) {
+            cli.share_index = v;
+        }
+        if cli.port.is_none() {
+            cli.port = self.port;
+        }
+        if let Some(v) = self.max_signers.filter(|_| unset(matches, "max_signers")) {
+            cli.max_signers = v;
+        }
+        if let Some(v) = self.min_signers.filter(|_| unset(matches, "min_signers")) {
+            cli.min_signers = v;
+        }
+        if cli.peers.is_empty() {
+            cli.peers = self.peers.clone();
+        }
+        if cli.dns_seeds.is_empty() {
+            cli.dns_seeds = self.dns_seeds.clone();
+        }
+        if cli.public_url.is_none() {
+            cli.public_url = self.public_url.clone();
+        }
+        if cli.node_url.is_none() {
+            cli.node_url = self.node_url.clone();
+        }
+        if let Some(v) = self.max_storefronts.filter(|_| unset(matches, "max_storefronts")) {
+            cli.max_storefronts = v;
+        }
+        if cli.admin_pubkeys.is_empty() {
+            cli.admin_pubkeys = self.admin_pubkeys.clone();
+        }
+        if cli.pegin_limit_sats.is_none() {
+            cli.pegin_limit_sats = self.pegin_limit_sats;
+        }
+        if cli.daily_pegout_limit_sats.is_none() {
+            cli.daily_pegout_limit_sats = self.daily_pegout_limit_sats;
+        }
+        if cli.max_mint_curds.is_none() {
+            cli.max_mint_curds = self.max_mint_curds;
+        }
+        if cli.max_root_debit_curds.is_none() {
+            cli.max_root_debit_curds = self.max_root_debit_curds;
+        }
+    }
+}
+
+/// Guardian URLs the `seeds` resolve to, as `http://addr:port`, without
+/// `public_url`. Seeds that fail to resolve are logged and skipped.
+pub async fn resolve_seeds(seeds: &[String], public_url: Option<&str>) -> Vec<String> {
+    let mut urls = Vec::new();
+    for seed in seeds {
+        let target = if seed.contains(':') { seed.clone() } else { format!("{}:{}", seed, DEFAULT_SEED_PORT) };
+        match tokio::net::lookup_host(&target).await {
+            Ok(addrs) => {
+                for addr in addrs {
+                    let url = format!("http://{}", addr);
+                    if Some(url.as_str()) != public_url && !urls.contains(&url) {
+                        urls.push(url);
+                    }
+                }
+            }
+            Err(e) => eprintln!("WARNING: DNS seed {} did not resolve: {}", seed, e),
+        }
+    }
+    urls
+}
+
+/// The peers to co-sign with: the listed `peers`, then any further
+/// guardians found through `seeds`.
+pub async fn signing_peers(peers: &[String], seeds: &[String], public_url: Option<&str>) -> Vec<String> {
+    let mut all = peers.to_vec();
+    for url in resolve_seeds(seeds, public_url).await {
+        if !all.contains(&url) {
+            all.push(url);
+        }
+    }
+    all
+}
+
+/// What a reload starts from: the config file and the flags that override it.
+pub struct ReloadSource {
+    pub path: PathBuf,
+    /// `--peers` / `--dns-seed` / `--public-url` from the command line, if given.
+    pub peers: Option<Vec<String>>,
+    pub dns_seeds: Option<Vec<String>>,
+    pub public_url: Option<String>,
+}
+
+/// On each SIGHUP, re-read the config file and re-resolve the DNS seeds,
+/// replacing the signing peers and adding any new admin keys. A file that
+/// fails to load leaves everything as it was.
+pub async fn watch_for_reload(state: Arc<AppState>, source: ReloadSource) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = match signal(SignalKind::hangup()) {
+        Ok(hangups) => hangups,
+        Err(e) => {
+            eprintln!("WARNING: Cannot listen for SIGHUP, config reload disabled: {}", e);
+            return;
+        }
+    };
+    while hangups.recv().await.is_some() {
+        let config = match load(&source.path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("ERROR: Config not reloaded: {}", e);
+                continue;
+            }
+        };
+        let peers = source.peers.clone().unwrap_or(config.peers);
+        let seeds = source.dns_seeds.clone().unwrap_or(config.dns_seeds);
+        let public_url = source.public_url.clone().or(config.public_url);
+        let signing = signing_peers(&peers, &seeds, public_url.as_deref()).await;
+        println!("Config reloaded from {}: {} signing peer(s)", source.path.display(), signing.len());
+        *state.peers.write().await = signing;
+
+        let mut admins = state.admin_pubkeys.write().await;
+        for key in config.admin_pubkeys {
+            let key = key.to_lowercase();
+            if !admins.contains(&key) {
+                println!("Config reload: added admin {}", key);
+                admins.push(key);
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
-//! Contract key helpers for guardian node monitoring.
+//! Contract helpers for guardian node monitoring.
 //!
-//! Derives deterministic contract keys for the directory and root user contracts
-//! so the guardian can subscribe to them on its co-located Freenet node.
+//! Builds the directory, root user and storefront contracts (with embedded
+//! WASM) so the guardian can subscribe to them on its co-located Freenet node
+//! by their deterministic keys, and re-PUT them if the node loses them.
 
 use std::sync::Arc;
 
+use cream_common::directory::{DirectoryShard, DirectoryShardParameters};
 use freenet_stdlib::prelude::*;
 use frost_ed25519 as frost;
 
@@ -12,6 +14,8 @@ const DIRECTORY_WASM: &[u8] =
     include_bytes!("../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm");
 const USER_CONTRACT_WASM: &[u8] =
     include_bytes!("../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm");
+const STOREFRONT_WASM: &[u8] =
+    include_bytes!("../../target/wasm32-unknown-unknown/release/cream_storefront_contract.wasm");
 
 fn make_contract(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractContainer {
     let code = ContractCode::from(wasm_bytes.to_vec());
@@ -19,22 +23,37 @@ fn make_contract(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractCont
     ContractContainer::Wasm(ContractWasmAPIVersion::V1(wrapped))
 }
 
-/// Deterministic directory contract key (empty parameters).
-pub fn directory_contract_key() -> ContractKey {
-    let contract = make_contract(DIRECTORY_WASM, Parameters::from(vec![]));
-    contract.key()
+/// The directory contract (empty parameters).
+pub fn directory_contract() -> ContractContainer {
+    make_contract(DIRECTORY_WASM, Parameters::from(vec![]))
 }
 
-/// Root user contract key derived from the FROST group verifying key.
-pub fn root_user_contract_key(pubkey_package: &frost::keys::PublicKeyPackage) -> ContractKey {
+/// One regional directory shard.
+pub fn directory_shard_contract(shard: DirectoryShard) -> ContractContainer {
+    make_contract(
+        DIRECTORY_WASM,
+        Parameters::from(DirectoryShardParameters::new(shard).to_bytes()),
+    )
+}
+
+/// A supplier's storefront contract, parameterised by their verifying key.
+pub fn storefront_contract(owner: &ed25519_dalek::VerifyingKey) -> ContractContainer {
+    let params = cream_common::storefront::StorefrontParameters { owner: *owner };
+    let params_bytes = serde_json::to_vec(&params).unwrap();
+    make_contract(STOREFRONT_WASM, Parameters::from(params_bytes))
+}
+
+/// The root user contract, parameterised by the FROST group verifying key.
+pub fn root_user_contract(pubkey_package: &frost::keys::PublicKeyPackage) -> ContractContainer {
     let vk = cream_common::frost::group_verifying_key(pubkey_package);
-    user_contract_key_for_verifying_key(&vk)
+    user_contract_for_verifying_key(&vk)
 }
 
-/// Derive a user contract key from an arbitrary ed25519 verifying key.
-pub fn user_contract_key_for_verifying_key(vk: &ed25519_dalek::VerifyingKey) -> ContractKey {
-    let params = cream_common::user_contract::UserContractParameters { owner: *vk };
+fn user_contract_for_verifying_key(vk: &ed25519_dalek::VerifyingKey) -> ContractContainer {
+    let params = cream_common::user_contract::UserContractParameters {
+        owner: *vk,
+        spending_key: None,
+    };
     let params_bytes = serde_json::to_vec(&params).unwrap();
-    let contract = make_contract(USER_CONTRACT_WASM, Parameters::from(params_bytes));
-    contract.key()
+    make_contract(USER_CONTRACT_WASM, Parameters::from(params_bytes))
 }
@@ -7,33 +7,70 @@
 //! 2. **`--peers` provided, no keys** → run DKG ceremony with peer guardians
 //! 3. **No `--peers`, no keys** → `dev_root_frost_keys()` fallback (trusted dealer)
 //!
+//! Any guardian can coordinate a CURD minting ceremony (`POST /mint`, see
+//! [`mint`]).
+//!
 //! Optionally connects to a co-located Freenet node (`--node-url`) and subscribes
-//! to critical contracts (directory, root user) to strengthen replication.
+//! to critical contracts (directory, root user) to strengthen replication,
+//! re-PUTting a local snapshot if the node loses them. It also follows the
+//! storefronts listed in the directory (up to `--max-storefronts`, rotating
+//! through the rest) and repairs them the same way.
+//!
+//! With `--bootstrap`, once its keys are ready the guardian also deploys the
+//! core contracts (directory and FROST-owned root user contract) to that node
+//! (see [`bootstrap`]).
+//!
+//! Settings can also come from a TOML config file, with peers optionally
+//! discovered through DNS seeds; SIGHUP reloads it (see [`config`]).
+//!
+//! Peers sign through `/round1` and `/round2` under session tokens this
+//! guardian issues, each bound to one message and usable once (see
+//! [`signing`]). What they're asked to sign is a typed request the guardian
+//! checks against its own view of the contracts first (see [`policy`]).
+//!
+//! A DKG ceremony leaves a hash-chained transcript of its packages, served at
+//! `GET /dkg/transcript`; `--verify-transcripts` cross-checks every guardian's
+//! (see [`transcript`]).
 
+mod bootstrap;
+mod config;
 mod contracts;
 mod lightning;
+mod mint;
+mod policy;
+mod signing;
+mod snapshots;
+mod storefronts;
+mod transcript;
 
 use std::collections::BTreeMap;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::future::Future;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use axum::extract::State;
+use axum::extract::{ConnectInfo, State};
 use axum::http::Method;
 use axum::routing::{get, post};
 use axum::{Json, Router};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use cream_common::directory::DirectoryShard;
+use cream_common::retry::{retry, CancellationToken, RetryPolicy};
+use cream_common::signing_request::{SigningPolicy, SigningRequest};
 use freenet_stdlib::client_api::{ClientRequest, ContractRequest, ContractResponse, HostResponse};
+use freenet_stdlib::prelude::{
+    ContractInstanceId, ContractKey, RelatedContracts, UpdateData, WrappedState,
+};
 use frost_ed25519 as frost;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{Mutex, RwLock};
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::lightning::{LightningState, LndConfig, LndGateway};
-
-/// TTL for stored nonces (seconds). Expired nonces are cleaned on each round1 call.
-const NONCE_TTL_SECS: u64 = 30;
+use crate::snapshots::CriticalContract;
+use crate::storefronts::{ListedStorefront, StorefrontWatch};
 
 #[derive(Parser)]
 #[command(name = "cream-guardian", about = "CREAM FROST guardian daemon")]
@@ -112,10 +149,49 @@ struct Cli {
     #[arg(long)]
     daily_pegout_limit_sats: Option<u64>,
 
+    /// Largest single mint this guardian co-signs, in curds (default: unlimited).
+    #[arg(long)]
+    max_mint_curds: Option<u64>,
+
+    /// Largest single debit from root, outside escrow settlements, this
+    /// guardian co-signs, in curds (default: unlimited).
+    #[arg(long)]
+    max_root_debit_curds: Option<u64>,
+
     /// Comma-separated list of user pubkeys (hex) that are admins.
     /// If empty, all users are treated as admins (dev compatibility).
     #[arg(long, value_delimiter = ',')]
     admin_pubkeys: Vec<String>,
+
+    /// Maximum storefront contracts to follow at once (default: 100).
+    /// With more listed, the guardian rotates through them.
+    #[arg(long, default_value_t = 100)]
+    max_storefronts: usize,
+
+    /// Once keys are ready, deploy the directory and the root user contract
+    /// (with a group-signed genesis state) to --node-url. Run on one guardian.
+    #[arg(long)]
+    bootstrap: bool,
+
+    /// Comma-separated guardian URLs: fetch each one's DKG transcript,
+    /// check they all saw the same ceremony, and exit.
+    #[arg(long, value_delimiter = ',')]
+    verify_transcripts: Vec<String>,
+
+    /// TOML config file (default: cream/guardian.toml in the user config
+    /// directory, if present). Flags override its settings; SIGHUP reloads it.
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Comma-separated DNS seeds ("host[:port]") resolving to guardians;
+    /// those found join --peers as mint co-signers.
+    #[arg(long = "dns-seed", value_delimiter = ',')]
+    dns_seeds: Vec<String>,
+
+    /// This guardian's URL as its peers reach it, so DNS seeds don't list it
+    /// as its own peer.
+    #[arg(long)]
+    public_url: Option<String>,
 }
 
 struct AppState {
@@ -125,13 +201,23 @@ struct AppState {
     min_signers: std::sync::atomic::AtomicU16,
     key_package: RwLock<Option<frost::keys::KeyPackage>>,
     public_key_package: RwLock<Option<frost::keys::PublicKeyPackage>>,
-    nonces: Mutex<BTreeMap<String, (frost::round1::SigningNonces, Instant)>>,
+    signing_sessions: Mutex<signing::SigningSessions>,
+    /// Limits on what this guardian signs (see [`policy`]).
+    signing_policy: SigningPolicy,
     dkg_state: Mutex<DkgState>,
+    /// This guardian's record of the DKG ceremony that made its keys.
+    dkg_transcript: RwLock<Option<transcript::Transcript>>,
     refresh_state: Mutex<DkgState>,
     refreshing: AtomicBool,
     node_connected: AtomicBool,
     lightning: Option<Arc<LightningState>>,
     admin_pubkeys: RwLock<Vec<String>>,
+    /// Peer guardian URLs, for coordinating mint ceremonies: the listed
+    /// peers plus any found through DNS seeds. Replaced on config reload.
+    peers: RwLock<Vec<String>>,
+    /// Requests for the node monitor to send (mint updates to the root
+    /// contract). Nothing receives them without `--node-url`.
+    node_requests: tokio::sync::mpsc::UnboundedSender<ClientRequest<'static>>,
 }
 
 impl AppState {
@@ -158,24 +244,35 @@ struct DkgState {
 
 #[derive(Deserialize)]
 struct Round1Request {
-    session_id: String,
+    /// What round 2 will sign; the session is bound to its message.
+    request: SigningRequest,
 }
 
+/// A guardian's commitments, as passed to every participant in round 2.
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Round1Response {
     pub identifier: frost::Identifier,
     pub commitments: frost::round1::SigningCommitments,
 }
 
+/// What `/round1` answers: the commitments, and the token to sign with in
+/// round 2. The token is for the caller only.
+#[derive(Serialize, Deserialize)]
+pub struct Round1Session {
+    pub session_token: String,
+    pub identifier: frost::Identifier,
+    pub commitments: frost::round1::SigningCommitments,
+}
+
 #[derive(Deserialize)]
 struct Round2Request {
-    session_id: String,
-    message_hex: String,
+    session_token: String,
+    request: SigningRequest,
     /// Commitments from all participating guardians (collected by the coordinator).
     signing_commitments: Vec<Round1Response>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 struct Round2Response {
     identifier: frost::Identifier,
     signature_share: frost::round2::SignatureShare,
@@ -237,10 +334,21 @@ struct DkgRoundResponse {
 
 // ─── Signing Handlers ───────────────────────────────────────────────────────
 
+/// A signing session error as an HTTP response.
+fn session_error(e: signing::SessionError) -> (axum::http::StatusCode, Json<ErrorResponse>) {
+    (e.status(), Json(ErrorResponse { error: e.to_string() }))
+}
+
+/// A refusal to sign as an HTTP response.
+fn refusal(e: policy::Refusal) -> (axum::http::StatusCode, Json<ErrorResponse>) {
+    (e.status(), Json(ErrorResponse { error: e.to_string() }))
+}
+
 async fn round1_handler(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Json(req): Json<Round1Request>,
-) -> Result<Json<Round1Response>, (axum::http::StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Round1Session>, (axum::http::StatusCode, Json<ErrorResponse>)> {
     use rand::rngs::OsRng;
 
     let key_package_guard = state.key_package.read().await;
@@ -252,15 +360,22 @@ async fn round1_handler(
             }),
         )
     })?;
+    policy::check(&state, &req.request).await.map_err(|e| {
+        println!("Signing: refused {} request from {}: {}", req.request.kind(), client.ip(), e);
+        refusal(e)
+    })?;
+    let message = req.request.message();
 
     let (nonces, commitments) = frost::round1::commit(key_package.signing_share(), &mut OsRng);
+    let session_token = state
+        .signing_sessions
+        .lock()
+        .await
+        .open(client.ip(), &message, nonces, Instant::now())
+        .map_err(session_error)?;
 
-    let mut nonce_map = state.nonces.lock().await;
-    // Clean expired nonces
-    nonce_map.retain(|_, (_, created)| created.elapsed().as_secs() < NONCE_TTL_SECS);
-    nonce_map.insert(req.session_id, (nonces, Instant::now()));
-
-    Ok(Json(Round1Response {
+    Ok(Json(Round1Session {
+        session_token,
         identifier: state.identifier,
         commitments,
     }))
@@ -268,6 +383,7 @@ async fn round1_handler(
 
 async fn round2_handler(
     State(state): State<Arc<AppState>>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
     Json(req): Json<Round2Request>,
 ) -> Result<Json<Round2Response>, (axum::http::StatusCode, Json<ErrorResponse>)> {
     let key_package_guard = state.key_package.read().await;
@@ -279,35 +395,15 @@ async fn round2_handler(
             }),
         )
     })?;
+    let message = req.request.message();
 
-    // Retrieve and consume stored nonces
-    let nonces = {
-        let mut nonce_map = state.nonces.lock().await;
-        match nonce_map.remove(&req.session_id) {
-            Some((nonces, _)) => nonces,
-            None => {
-                return Err((
-                    axum::http::StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: format!("No nonces found for session_id '{}'", req.session_id),
-                    }),
-                ));
-            }
-        }
-    };
-
-    // Decode the message
-    let message = match hex::decode(&req.message_hex) {
-        Ok(m) => m,
-        Err(e) => {
-            return Err((
-                axum::http::StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: format!("Invalid message_hex: {}", e),
-                }),
-            ));
-        }
-    };
+    // Spend the session: its nonces are never handed out twice
+    let nonces = state
+        .signing_sessions
+        .lock()
+        .await
+        .take(client.ip(), &req.session_token, &message, Instant::now())
+        .map_err(session_error)?;
 
     // Build commitments map
     let commitments_map: BTreeMap<frost::Identifier, frost::round1::SigningCommitments> = req
@@ -359,6 +455,20 @@ async fn dkg_round2_handler(
     Json(DkgRoundResponse { ok: true })
 }
 
+async fn dkg_transcript_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<transcript::Transcript>, (axum::http::StatusCode, Json<ErrorResponse>)> {
+    match state.dkg_transcript.read().await.as_ref() {
+        Some(transcript) => Ok(Json(transcript.clone())),
+        None => Err((
+            axum::http::StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: "No DKG transcript (keys weren't made by a ceremony on this guardian)".to_string(),
+            }),
+        )),
+    }
+}
+
 async fn public_key_handler(
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<frost::keys::PublicKeyPackage>, (axum::http::StatusCode, Json<ErrorResponse>)> {
@@ -1005,6 +1115,152 @@ async fn admin_revoke_handler(
     Ok(Json(AdminListResponse { admins: admins.clone() }))
 }
 
+// ─── Minting ─────────────────────────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct MintRequest {
+    amount: u64,
+    reason: String,
+    /// Hex pubkey of the admin asking for the mint.
+    requester: String,
+}
+
+#[derive(Serialize)]
+struct MintResponse {
+    record: cream_common::user_contract::MintRecord,
+    /// Whether the record was handed to the node for the root contract.
+    submitted: bool,
+}
+
+/// Run a minting ceremony with the peer guardians and append the signed
+/// record to the root user contract.
+async fn mint_handler(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<MintRequest>,
+) -> Result<Json<MintResponse>, (axum::http::StatusCode, Json<ErrorResponse>)> {
+    let error = |status, error: String| (status, Json(ErrorResponse { error }));
+    {
+        let admins = state.admin_pubkeys.read().await;
+        if !admins.is_empty() && !admins.contains(&req.requester.to_lowercase()) {
+            return Err(error(
+                axum::http::StatusCode::FORBIDDEN,
+                "Only admins can mint".to_string(),
+            ));
+        }
+    }
+    if req.amount == 0 || req.reason.trim().is_empty() {
+        return Err(error(
+            axum::http::StatusCode::BAD_REQUEST,
+            "Mint needs a positive amount and a reason".to_string(),
+        ));
+    }
+    let minted_at = chrono::Utc::now();
+    let mut record = cream_common::user_contract::MintRecord {
+        id: format!("{}-{}-{:08x}", minted_at.timestamp_millis(), state.share_index, rand::random::<u32>()),
+        amount: req.amount,
+        reason: req.reason.trim().to_string(),
+        minted_at,
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    };
+    let peers = state.peers.read().await.clone();
+    let request = SigningRequest::Mint { record: record.clone() };
+    record.signature = mint::sign_as_group(&state, &peers, &request)
+        .await
+        .map_err(|e| error(axum::http::StatusCode::BAD_GATEWAY, e))?;
+    println!("Mint {}: {} CURD signed ({})", record.id, record.amount, record.reason);
+
+    let submitted = match submit_mint(&state, &record).await {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("WARNING: Mint {} signed but not submitted: {}", record.id, e);
+            false
+        }
+    };
+    Ok(Json(MintResponse { record, submitted }))
+}
+
+/// Queue an update appending `record` to our snapshot of the root user
+/// contract for the node monitor to send.
+async fn submit_mint(state: &AppState, record: &cream_common::user_contract::MintRecord) -> Result<(), String> {
+    let pubkey_package = state
+        .public_key_package
+        .read()
+        .await
+        .clone()
+        .ok_or("Guardian not ready")?;
+    let root_owner = cream_common::frost::group_verifying_key(&pubkey_package);
+    let root_key = contracts::root_user_contract(&pubkey_package).key();
+    let root_state = snapshots::load(
+        state.share_index,
+        CriticalContract::RootUserContract,
+        &root_key.to_string(),
+        &root_owner,
+    )?;
+    let updated = mint::append_to_root(&root_state, record)?;
+    state
+        .node_requests
+        .send(ClientRequest::ContractOp(ContractRequest::Update {
+            key: root_key,
+            data: UpdateData::State(freenet_stdlib::prelude::State::from(updated)),
+        }))
+        .map_err(|_| "Not connected to a node (no --node-url)".to_string())
+}
+
+// ─── Waiting / Retry ────────────────────────────────────────────────────────
+
+/// Poll interval while collecting round packages from peers.
+const PACKAGE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Poll interval while waiting for local signing readiness.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Backoff bounds for peer health checks.
+const PEER_BACKOFF_INITIAL: Duration = Duration::from_millis(500);
+const PEER_BACKOFF_MAX: Duration = Duration::from_secs(5);
+/// Backoff bounds for node monitor reconnects.
+const MONITOR_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const MONITOR_BACKOFF_MAX: Duration = Duration::from_secs(30);
+/// How often the followed storefront window moves on when more storefronts
+/// are listed than `--max-storefronts`.
+const STOREFRONT_ROTATION_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Poll `ready` at a fixed interval until it returns true.
+async fn poll_until<F, Fut>(interval: Duration, mut ready: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let policy = RetryPolicy::fixed(interval);
+    // Unbounded and never cancelled, so this only returns once ready.
+    let _ = retry(
+        policy.backoff(0),
+        &CancellationToken::new(),
+        tokio::time::sleep,
+        |_| {
+            let check = ready();
+            async move { if check.await { Ok(()) } else { Err(()) } }
+        },
+    )
+    .await;
+}
+
+/// Wait until `peer` answers `/health`, backing off between attempts.
+async fn wait_for_peer_health(client: &reqwest::Client, peer: &str) {
+    let policy = RetryPolicy::exponential(PEER_BACKOFF_INITIAL, PEER_BACKOFF_MAX);
+    let _ = retry(
+        policy.backoff(rand::random()),
+        &CancellationToken::new(),
+        tokio::time::sleep,
+        |_| async move {
+            match client.get(format!("{}/health", peer)).send().await {
+                Ok(resp) if resp.status().is_success() => Ok(()),
+                Ok(resp) => Err(resp.status().to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        },
+    )
+    .await;
+}
+
 // ─── DKG Ceremony ───────────────────────────────────────────────────────────
 
 async fn run_dkg(state: Arc<AppState>, peers: Vec<String>) {
@@ -1024,12 +1280,7 @@ async fn run_dkg(state: Arc<AppState>, peers: Vec<String>) {
     let client = reqwest::Client::new();
     for peer in &peers {
         println!("DKG: waiting for peer {} ...", peer);
-        loop {
-            match client.get(format!("{}/health", peer)).send().await {
-                Ok(resp) if resp.status().is_success() => break,
-                _ => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
-            }
-        }
+        wait_for_peer_health(&client, peer).await;
         println!("DKG: peer {} is up", peer);
     }
 
@@ -1059,13 +1310,11 @@ async fn run_dkg(state: Arc<AppState>, peers: Vec<String>) {
 
     // Wait for round1 packages from all peers
     let expected = n_peers;
-    loop {
-        let count = state.dkg_state.lock().await.round1_packages.len();
-        if count >= expected {
-            break;
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    poll_until(PACKAGE_POLL_INTERVAL, || {
+        let state = &state;
+        async move { state.dkg_state.lock().await.round1_packages.len() >= expected }
+    })
+    .await;
 
     let round1_packages = {
         let dkg = state.dkg_state.lock().await;
@@ -1104,13 +1353,11 @@ async fn run_dkg(state: Arc<AppState>, peers: Vec<String>) {
     }
 
     // Wait for round2 packages from all peers
-    loop {
-        let count = state.dkg_state.lock().await.round2_packages.len();
-        if count >= expected {
-            break;
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    poll_until(PACKAGE_POLL_INTERVAL, || {
+        let state = &state;
+        async move { state.dkg_state.lock().await.round2_packages.len() >= expected }
+    })
+    .await;
 
     let round2_received = {
         let dkg = state.dkg_state.lock().await;
@@ -1127,6 +1374,21 @@ async fn run_dkg(state: Arc<AppState>, peers: Vec<String>) {
         frost::keys::dkg::part3(&round2_secret, &round1_packages, &round2_received)
             .expect("DKG part3 should not fail");
 
+    // Record what we saw, for cross-checking with the other guardians
+    let mut round1_seen = round1_packages.clone();
+    round1_seen.insert(state.identifier, round1_package);
+    let round2_seen: BTreeMap<_, _> = round2_packages
+        .into_iter()
+        .map(|(to, package)| ((state.identifier, to), package))
+        .chain(round2_received.iter().map(|(from, package)| ((*from, state.identifier), package.clone())))
+        .collect();
+    let dkg_transcript = transcript::Transcript::record(state.identifier, &round1_seen, &round2_seen);
+    if let Err(e) = transcript::save(state.share_index, &dkg_transcript) {
+        eprintln!("DKG: failed to save transcript: {}", e);
+    }
+    println!("DKG: transcript round 1 head {}", dkg_transcript.round1_head);
+    *state.dkg_transcript.write().await = Some(dkg_transcript);
+
     // Persist keys
     let persisted = PersistedKeys {
         key_package: key_package.clone(),
@@ -1264,12 +1526,7 @@ async fn run_refresh(state: Arc<AppState>, peers: Vec<String>) {
     let client = reqwest::Client::new();
     for peer in &peers {
         println!("Refresh: waiting for peer {} ...", peer);
-        loop {
-            match client.get(format!("{}/health", peer)).send().await {
-                Ok(resp) if resp.status().is_success() => break,
-                _ => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
-            }
-        }
+        wait_for_peer_health(&client, peer).await;
         println!("Refresh: peer {} is up", peer);
     }
 
@@ -1295,13 +1552,11 @@ async fn run_refresh(state: Arc<AppState>, peers: Vec<String>) {
 
     // Wait for round1 packages from all peers
     let expected = peers.len();
-    loop {
-        let count = state.refresh_state.lock().await.round1_packages.len();
-        if count >= expected {
-            break;
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    poll_until(PACKAGE_POLL_INTERVAL, || {
+        let state = &state;
+        async move { state.refresh_state.lock().await.round1_packages.len() >= expected }
+    })
+    .await;
 
     let round1_packages = {
         let refresh = state.refresh_state.lock().await;
@@ -1337,13 +1592,11 @@ async fn run_refresh(state: Arc<AppState>, peers: Vec<String>) {
     }
 
     // Wait for round2 packages from all peers
-    loop {
-        let count = state.refresh_state.lock().await.round2_packages.len();
-        if count >= expected {
-            break;
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
-    }
+    poll_until(PACKAGE_POLL_INTERVAL, || {
+        let state = &state;
+        async move { state.refresh_state.lock().await.round2_packages.len() >= expected }
+    })
+    .await;
 
     let round2_received = {
         let refresh = state.refresh_state.lock().await;
@@ -1488,12 +1741,7 @@ async fn run_redeal(
     // Wait for all new peers to be healthy before distributing
     for peer in &new_peers {
         println!("Redeal: waiting for new peer {} ...", peer);
-        loop {
-            match client.get(format!("{}/health", peer)).send().await {
-                Ok(resp) if resp.status().is_success() => break,
-                _ => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
-            }
-        }
+        wait_for_peer_health(&client, peer).await;
         println!("Redeal: new peer {} is up", peer);
     }
 
@@ -1551,16 +1799,27 @@ async fn run_redeal(
 /// Connect to the co-located Freenet node and subscribe to critical contracts.
 ///
 /// Waits for signing readiness (keys loaded/DKG complete), then connects via
-/// WebSocket and subscribes to the directory and root user contracts. Runs an
-/// event loop logging `UpdateNotification` events. Reconnects on disconnect.
-async fn monitor_contracts(state: Arc<AppState>, node_url: String) {
+/// WebSocket, subscribes to the directory and root user contracts and GETs
+/// their current state. Every verified state seen (GET responses and update
+/// notifications) is kept as a local snapshot; if the node answers NotFound —
+/// typically after a restart that lost its store — the snapshot is re-PUT with
+/// the embedded contract WASM, so guardians act as availability anchors for
+/// critical state. Reconnects on disconnect.
+///
+/// The directory shards are followed too, and every storefront they list is
+/// followed, snapshotted and repaired the same way — at most
+/// `max_storefronts` at a time, rotating through the rest (see
+/// [`storefronts`]).
+///
+/// Requests queued on `requests` (mint updates) are sent while connected.
+async fn monitor_contracts(
+    state: Arc<AppState>,
+    node_url: String,
+    max_storefronts: usize,
+    mut requests: tokio::sync::mpsc::UnboundedReceiver<ClientRequest<'static>>,
+) {
     // Wait until keys are ready (DKG may still be running)
-    loop {
-        if state.is_ready() {
-            break;
-        }
-        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-    }
+    poll_until(READY_POLL_INTERVAL, || std::future::ready(state.is_ready())).await;
 
     let pubkey_package = state
         .public_key_package
@@ -1569,8 +1828,11 @@ async fn monitor_contracts(state: Arc<AppState>, node_url: String) {
         .clone()
         .expect("public key package must be set when ready");
 
-    let directory_key = contracts::directory_contract_key();
-    let root_user_key = contracts::root_user_contract_key(&pubkey_package);
+    let directory_contract = contracts::directory_contract();
+    let root_user_contract = contracts::root_user_contract(&pubkey_package);
+    let directory_key = directory_contract.key();
+    let root_user_key = root_user_contract.key();
+    let root_owner = cream_common::frost::group_verifying_key(&pubkey_package);
 
     println!(
         "Node monitor: directory contract key = {}",
@@ -1581,8 +1843,27 @@ async fn monitor_contracts(state: Arc<AppState>, node_url: String) {
         root_user_key
     );
 
-    let mut backoff = std::time::Duration::from_secs(1);
-    let max_backoff = std::time::Duration::from_secs(30);
+    let critical = [
+        (CriticalContract::Directory, &directory_key, &directory_contract),
+        (CriticalContract::RootUserContract, &root_user_key, &root_user_contract),
+    ];
+    let lookup = |id: &ContractInstanceId| {
+        critical
+            .iter()
+            .find(|(_, key, _)| key.id() == id)
+            .map(|(which, key, contract)| (*which, *key, *contract))
+    };
+
+    // Directory shards are read for their storefront listings only
+    let shard_keys: Vec<ContractKey> = DirectoryShard::ALL
+        .iter()
+        .map(|shard| contracts::directory_shard_contract(*shard).key())
+        .collect();
+    let is_shard = |id: &ContractInstanceId| shard_keys.iter().any(|key| key.id() == id);
+    let mut watch = StorefrontWatch::new(max_storefronts);
+
+    let mut backoff = RetryPolicy::exponential(MONITOR_BACKOFF_INITIAL, MONITOR_BACKOFF_MAX)
+        .backoff(rand::random());
 
     loop {
         println!("Node monitor: connecting to {} ...", node_url);
@@ -1590,60 +1871,178 @@ async fn monitor_contracts(state: Arc<AppState>, node_url: String) {
         let ws_conn = match tokio_tungstenite::connect_async(&node_url).await {
             Ok((conn, _)) => conn,
             Err(e) => {
-                println!("Node monitor: WebSocket connect failed: {} (retrying in {:?})", e, backoff);
+                let delay = backoff.next_delay().unwrap_or(MONITOR_BACKOFF_MAX);
+                println!("Node monitor: WebSocket connect failed: {} (retrying in {:?})", e, delay);
                 state.node_connected.store(false, Ordering::Relaxed);
-                tokio::time::sleep(backoff).await;
-                backoff = (backoff * 2).min(max_backoff);
+                tokio::time::sleep(delay).await;
                 continue;
             }
         };
 
         let mut api = freenet_stdlib::client_api::WebApi::start(ws_conn);
 
-        // Subscribe to directory contract
-        if let Err(e) = api
-            .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: *directory_key.id(),
-                summary: None,
-            }))
-            .await
-        {
-            println!("Node monitor: failed to subscribe to directory: {}", e);
-            state.node_connected.store(false, Ordering::Relaxed);
-            tokio::time::sleep(backoff).await;
-            backoff = (backoff * 2).min(max_backoff);
-            continue;
+        // Subscribe to each critical contract, then GET it so a contract the
+        // node has lost shows up as NotFound straight away.
+        let mut setup_failed = false;
+        for (which, key, _) in &critical {
+            if let Err(e) = api
+                .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
+                    key: *key.id(),
+                    summary: None,
+                }))
+                .await
+            {
+                println!("Node monitor: failed to subscribe to {}: {}", which.name(), e);
+                setup_failed = true;
+                break;
+            }
+            println!("Node monitor: subscribed to {}", which.name());
+
+            if let Err(e) = api.send(get_request(key)).await {
+                println!("Node monitor: failed to GET {}: {}", which.name(), e);
+                setup_failed = true;
+                break;
+            }
         }
-        println!("Node monitor: subscribed to directory contract");
-
-        // Subscribe to root user contract
-        if let Err(e) = api
-            .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: *root_user_key.id(),
-                summary: None,
-            }))
-            .await
-        {
-            println!("Node monitor: failed to subscribe to root user contract: {}", e);
+        // Shards and the storefront window from before a reconnect; a shard
+        // that can't be followed only costs us its listings.
+        for key in &shard_keys {
+            if let Err(e) = api.send(follow_request(key)).await {
+                println!("Node monitor: failed to follow directory shard {}: {}", key, e);
+            }
+        }
+        follow_storefronts(&mut api, watch.current()).await;
+        if setup_failed {
             state.node_connected.store(false, Ordering::Relaxed);
-            tokio::time::sleep(backoff).await;
-            backoff = (backoff * 2).min(max_backoff);
+            tokio::time::sleep(backoff.next_delay().unwrap_or(MONITOR_BACKOFF_MAX)).await;
             continue;
         }
-        println!("Node monitor: subscribed to root user contract");
 
         state.node_connected.store(true, Ordering::Relaxed);
-        backoff = std::time::Duration::from_secs(1); // Reset backoff on success
+        backoff.reset(); // Reset backoff on success
         println!("Node monitor: connected and subscribed — listening for updates");
 
-        // Event loop: log notifications
+        let mut rotation = tokio::time::interval_at(
+            tokio::time::Instant::now() + STOREFRONT_ROTATION_INTERVAL,
+            STOREFRONT_ROTATION_INTERVAL,
+        );
+
+        // Event loop: snapshot verified states, re-PUT anything the node lost
         loop {
-            match api.recv().await {
+            let response = tokio::select! {
+                response = api.recv() => response,
+                _ = rotation.tick() => {
+                    let window = watch.rotate();
+                    if !window.is_empty() {
+                        println!("Node monitor: rotating to the next {} storefronts", window.len());
+                    }
+                    follow_storefronts(&mut api, window).await;
+                    continue;
+                }
+                Some(request) = requests.recv() => {
+                    if let Err(e) = api.send(request).await {
+                        println!("Node monitor: failed to send queued request: {}", e);
+                    }
+                    continue;
+                }
+            };
+            match response {
+                Ok(HostResponse::ContractResponse(ContractResponse::GetResponse {
+                    key,
+                    state: contract_state,
+                    ..
+                })) => {
+                    if let Some((which, _, _)) = lookup(key.id()) {
+                        snapshot_state(&state, which, &key, contract_state.as_ref(), &root_owner);
+                    }
+                    let directory = matches!(lookup(key.id()), Some((CriticalContract::Directory, _, _)));
+                    if directory || is_shard(key.id()) {
+                        let listed = watch.add_listing_bytes(contract_state.as_ref());
+                        follow_storefronts(&mut api, listed).await;
+                    } else if let Some(listed) = watch.lookup(key.id()) {
+                        let which = CriticalContract::Storefront { owner: listed.owner };
+                        snapshot_state(&state, which, &key, contract_state.as_ref(), &root_owner);
+                    }
+                }
                 Ok(HostResponse::ContractResponse(ContractResponse::UpdateNotification {
                     key,
+                    update,
                     ..
                 })) => {
                     println!("Node monitor: update notification for contract {}", key);
+                    let which = match lookup(key.id()) {
+                        Some((which, _, _)) => Some(which),
+                        None => watch
+                            .lookup(key.id())
+                            .map(|listed| CriticalContract::Storefront { owner: listed.owner }),
+                    };
+                    let directory = which == Some(CriticalContract::Directory) || is_shard(key.id());
+                    if which.is_none() && !directory {
+                        continue;
+                    }
+                    match update {
+                        UpdateData::State(s) | UpdateData::StateAndDelta { state: s, .. } => {
+                            if let Some(which) = which {
+                                snapshot_state(&state, which, &key, s.as_ref(), &root_owner);
+                            }
+                            if directory {
+                                let listed = watch.add_listing_bytes(s.as_ref());
+                                follow_storefronts(&mut api, listed).await;
+                            }
+                        }
+                        _ => {
+                            // Not a full state — fetch the merged one to snapshot
+                            if let Err(e) = api.send(get_request(&key)).await {
+                                println!("Node monitor: failed to GET {}: {}", key, e);
+                            }
+                        }
+                    }
+                }
+                Ok(HostResponse::ContractResponse(ContractResponse::NotFound { instance_id })) => {
+                    let found = match lookup(&instance_id) {
+                        Some((which, key, contract)) => Some((which, *key, contract.clone())),
+                        None => watch.lookup(&instance_id).and_then(|listed| {
+                            let contract = contracts::storefront_contract(&listed.owner);
+                            // A listing made with different contract code can't be rebuilt here
+                            (contract.key() == listed.key).then_some((
+                                CriticalContract::Storefront { owner: listed.owner },
+                                listed.key,
+                                contract,
+                            ))
+                        }),
+                    };
+                    let Some((which, key, contract)) = found else {
+                        println!("Node monitor: contract {} not found", instance_id);
+                        continue;
+                    };
+                    match snapshots::load(state.share_index, which, &key.to_string(), &root_owner) {
+                        Ok(bytes) => {
+                            println!(
+                                "Node monitor: {} missing on node — re-PUTting local snapshot",
+                                which.name()
+                            );
+                            let put = ClientRequest::ContractOp(ContractRequest::Put {
+                                contract,
+                                state: WrappedState::new(bytes),
+                                related_contracts: RelatedContracts::default(),
+                                subscribe: true,
+                                blocking_subscribe: false,
+                            });
+                            if let Err(e) = api.send(put).await {
+                                println!("Node monitor: failed to re-PUT {}: {}", which.name(), e);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "WARNING: {} missing on node and no usable snapshot: {}",
+                                which.name(),
+                                e
+                            );
+                        }
+                    }
+                }
+                Ok(HostResponse::ContractResponse(ContractResponse::PutResponse { key })) => {
+                    println!("Node monitor: PUT confirmed for contract {}", key);
                 }
                 Ok(HostResponse::Ok) => {
                     // Subscription acknowledgement or other OK — ignore
@@ -1659,8 +2058,52 @@ async fn monitor_contracts(state: Arc<AppState>, node_url: String) {
             }
         }
 
-        tokio::time::sleep(backoff).await;
-        backoff = (backoff * 2).min(max_backoff);
+        tokio::time::sleep(backoff.next_delay().unwrap_or(MONITOR_BACKOFF_MAX)).await;
+    }
+}
+
+/// GET `key` and subscribe to it.
+fn follow_request(key: &ContractKey) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Get {
+        key: *key.id(),
+        return_contract_code: false,
+        subscribe: true,
+        blocking_subscribe: false,
+    })
+}
+
+async fn follow_storefronts(
+    api: &mut freenet_stdlib::client_api::WebApi,
+    storefronts: Vec<ListedStorefront>,
+) {
+    for listed in storefronts {
+        if let Err(e) = api.send(follow_request(&listed.key)).await {
+            println!("Node monitor: failed to follow storefront {}: {}", listed.key, e);
+        }
+    }
+}
+
+fn get_request(key: &ContractKey) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Get {
+        key: *key.id(),
+        return_contract_code: false,
+        subscribe: false,
+        blocking_subscribe: false,
+    })
+}
+
+fn snapshot_state(
+    state: &AppState,
+    which: CriticalContract,
+    key: &ContractKey,
+    bytes: &[u8],
+    root_owner: &ed25519_dalek::VerifyingKey,
+) {
+    if bytes.is_empty() {
+        return;
+    }
+    if let Err(e) = snapshots::save(state.share_index, which, &key.to_string(), bytes, root_owner) {
+        eprintln!("WARNING: Not snapshotting {}: {}", which.name(), e);
     }
 }
 
@@ -1695,7 +2138,34 @@ fn identifier_to_peer_url(
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // What the command line set itself, which a reload keeps over the file
+    let reload_source = |path: PathBuf| config::ReloadSource {
+        path,
+        peers: (!cli.peers.is_empty()).then(|| cli.peers.clone()),
+        dns_seeds: (!cli.dns_seeds.is_empty()).then(|| cli.dns_seeds.clone()),
+        public_url: cli.public_url.clone(),
+    };
+    let reload = match config::locate(cli.config.as_deref()) {
+        Ok(Some((path, file))) => {
+            println!("Using config file {}", path.display());
+            let source = reload_source(path);
+            file.apply(&mut cli, &matches);
+            Some(source)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            eprintln!("ERROR: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let signing_peers = config::signing_peers(&cli.peers, &cli.dns_seeds, cli.public_url.as_deref()).await;
+
+    if !cli.verify_transcripts.is_empty() {
+        let agree = transcript::verify_remote(&cli.verify_transcripts).await;
+        std::process::exit(if agree { 0 } else { 1 });
+    }
 
     let identifier = frost::Identifier::try_from(cli.share_index)
         .expect("Invalid share_index (must be 1..=max_signers)");
@@ -1728,6 +2198,7 @@ async fn main() {
         None
     };
 
+    let (node_requests, node_requests_rx) = tokio::sync::mpsc::unbounded_channel();
     let state = Arc::new(AppState {
         identifier,
         share_index: cli.share_index,
@@ -1735,8 +2206,13 @@ async fn main() {
         min_signers: std::sync::atomic::AtomicU16::new(cli.min_signers),
         key_package: RwLock::new(None),
         public_key_package: RwLock::new(None),
-        nonces: Mutex::new(BTreeMap::new()),
+        signing_sessions: Mutex::new(signing::SigningSessions::default()),
+        signing_policy: SigningPolicy {
+            max_mint: cli.max_mint_curds,
+            max_root_debit: cli.max_root_debit_curds,
+        },
         dkg_state: Mutex::new(DkgState::default()),
+        dkg_transcript: RwLock::new(transcript::load(cli.share_index)),
         refresh_state: Mutex::new(DkgState::default()),
         refreshing: AtomicBool::new(false),
         node_connected: AtomicBool::new(false),
@@ -1752,6 +2228,8 @@ async fn main() {
             }
             keys
         }),
+        peers: RwLock::new(signing_peers),
+        node_requests,
     });
 
     // ── Key initialization ──
@@ -1806,6 +2284,7 @@ async fn main() {
         .route("/round2", post(round2_handler))
         .route("/dkg/round1", post(dkg_round1_handler))
         .route("/dkg/round2", post(dkg_round2_handler))
+        .route("/dkg/transcript", get(dkg_transcript_handler))
         .route("/refresh/round1", post(refresh_round1_handler))
         .route("/refresh/round2", post(refresh_round2_handler))
         .route("/redeal/share", post(redeal_share_handler))
@@ -1816,7 +2295,8 @@ async fn main() {
         .route("/admin-check", get(admin_check_handler))
         .route("/admin-list", get(admin_list_handler))
         .route("/admin-grant", post(admin_grant_handler))
-        .route("/admin-revoke", post(admin_revoke_handler));
+        .route("/admin-revoke", post(admin_revoke_handler))
+        .route("/mint", post(mint_handler));
 
     // Lightning routes (conditional on --lightning-gateway)
     if state.lightning.is_some() {
@@ -1890,15 +2370,38 @@ async fn main() {
         });
     }
 
+    // Deploy the core contracts once keys are ready (after DKG if running)
+    if cli.bootstrap {
+        let node_url = cli
+            .node_url
+            .clone()
+            .expect("--bootstrap requires --node-url");
+        let bootstrap_state = state.clone();
+        tokio::spawn(async move {
+            match bootstrap::run(&bootstrap_state, &node_url).await {
+                Ok(()) => println!("Bootstrap: core contracts deployed"),
+                Err(e) => eprintln!("ERROR: Bootstrap failed: {}", e),
+            }
+        });
+    }
+
+    if let Some(source) = reload {
+        tokio::spawn(config::watch_for_reload(state.clone(), source));
+    }
+
     // Spawn node monitor if --node-url provided
     if let Some(node_url) = cli.node_url {
         let monitor_state = state.clone();
+        let max_storefronts = cli.max_storefronts;
         tokio::spawn(async move {
-            monitor_contracts(monitor_state, node_url).await;
+            monitor_contracts(monitor_state, node_url, max_storefronts, node_requests_rx).await;
         });
     }
 
-    axum::serve(listener, app).await.expect("Server failed");
+    // Client addresses key the signing sessions and their rate limits
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .expect("Server failed");
 }
 
 // ─── Hex helpers ─────────────────────────────────────────────────────────────
@@ -0,0 +1,158 @@
+//! CURD minting ceremonies.
+//!
+//! New CURD only enters the system through a [`MintRecord`] signed by the
+//! guardians' FROST group key and appended to the root user contract, next to
+//! the ledger credit it backs. The user contract rejects a mint credit without
+//! a matching signed record, so issuance takes a threshold of guardians and
+//! every mint is publicly visible in root's state.
+//!
+//! The guardian that receives `POST /mint` coordinates the ceremony: it
+//! commits and signs with its own share and collects the rest from peers
+//! through their `/round1` and `/round2` endpoints, each peer's round 2 under
+//! the session token its round 1 issued. Every guardian, the coordinator
+//! included, checks the request against its own policy first (see
+//! [`policy`](crate::policy)).
+
+use std::collections::BTreeMap;
+
+use cream_common::signing_request::SigningRequest;
+use cream_common::user_contract::{MintRecord, UserContractState};
+use frost_ed25519 as frost;
+use rand::rngs::OsRng;
+
+use crate::{policy, AppState, Round1Response, Round1Session, Round2Response};
+
+#[derive(serde::Serialize)]
+struct Round1Call<'a> {
+    request: &'a SigningRequest,
+}
+
+#[derive(serde::Serialize)]
+struct Round2Call<'a> {
+    session_token: &'a str,
+    request: &'a SigningRequest,
+    signing_commitments: &'a [Round1Response],
+}
+
+/// Threshold-sign `request` with the group key: this guardian's share plus
+/// enough of `peers` to reach `min_signers`. Peers that don't answer round 1,
+/// or refuse the request, are skipped.
+pub async fn sign_as_group(
+    state: &AppState,
+    peers: &[String],
+    request: &SigningRequest,
+) -> Result<ed25519_dalek::Signature, String> {
+    policy::check(state, request).await.map_err(|e| e.to_string())?;
+    let key_package = state
+        .key_package
+        .read()
+        .await
+        .clone()
+        .ok_or("Guardian not ready (DKG in progress)")?;
+    let public_key_package = state
+        .public_key_package
+        .read()
+        .await
+        .clone()
+        .ok_or("Guardian not ready (DKG in progress)")?;
+    let min_signers = *key_package.min_signers() as usize;
+    let message = request.message();
+
+    // ── Round 1: our commitments, then peers' until the threshold ──
+    let (nonces, commitments) = frost::round1::commit(key_package.signing_share(), &mut OsRng);
+    let mut signing_commitments = vec![Round1Response {
+        identifier: state.identifier,
+        commitments,
+    }];
+    // Each co-signer with the session token it issued us
+    let mut signers: Vec<(&str, String)> = Vec::new();
+    let client = reqwest::Client::new();
+    for peer in peers {
+        if signing_commitments.len() >= min_signers {
+            break;
+        }
+        let response = client
+            .post(format!("{}/round1", peer))
+            .json(&Round1Call { request })
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        match response {
+            Ok(response) => match response.json::<Round1Session>().await {
+                Ok(session) => {
+                    signing_commitments.push(Round1Response {
+                        identifier: session.identifier,
+                        commitments: session.commitments,
+                    });
+                    signers.push((peer, session.session_token));
+                }
+                Err(e) => println!("Mint: bad round1 response from {}: {}", peer, e),
+            },
+            Err(e) => println!("Mint: peer {} unavailable for round1: {}", peer, e),
+        }
+    }
+    if signing_commitments.len() < min_signers {
+        return Err(format!(
+            "Only {} of the {} guardians needed are available",
+            signing_commitments.len(),
+            min_signers
+        ));
+    }
+
+    // ── Round 2: signature shares from every participant ──
+    let commitments_map: BTreeMap<_, _> = signing_commitments
+        .iter()
+        .map(|c| (c.identifier, c.commitments))
+        .collect();
+    let signing_package = frost::SigningPackage::new(commitments_map, &message);
+    let own_share = frost::round2::sign(&signing_package, &nonces, &key_package)
+        .map_err(|e| format!("FROST round2 signing failed: {}", e))?;
+    let mut signature_shares = BTreeMap::from([(state.identifier, own_share)]);
+    for (peer, session_token) in signers {
+        let share: Round2Response = client
+            .post(format!("{}/round2", peer))
+            .json(&Round2Call {
+                session_token: &session_token,
+                request,
+                signing_commitments: &signing_commitments,
+            })
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Round2 with {} failed: {}", peer, e))?
+            .json()
+            .await
+            .map_err(|e| format!("Bad round2 response from {}: {}", peer, e))?;
+        signature_shares.insert(share.identifier, share.signature_share);
+    }
+
+    let group_signature = frost::aggregate(&signing_package, &signature_shares, &public_key_package)
+        .map_err(|e| format!("FROST aggregation failed: {}", e))?;
+    let bytes: [u8; 64] = group_signature
+        .serialize()
+        .map_err(|e| format!("Signature serialization failed: {}", e))?
+        .try_into()
+        .map_err(|_| "Signature is not 64 bytes".to_string())?;
+    let signature = ed25519_dalek::Signature::from_bytes(&bytes);
+
+    let group_key = cream_common::frost::group_verifying_key(&public_key_package);
+    group_key
+        .verify_strict(&message, &signature)
+        .map_err(|e| format!("Group signature does not verify: {}", e))?;
+    Ok(signature)
+}
+
+/// `root_state` with `record` and the ledger credit it backs appended, ready
+/// to send as an update. The contract accepts it without a new state
+/// signature because the record carries the group's.
+pub fn append_to_root(root_state: &[u8], record: &MintRecord) -> Result<Vec<u8>, String> {
+    let mut root: UserContractState = serde_json::from_slice(root_state)
+        .map_err(|e| format!("Invalid root user contract state: {}", e))?;
+    if root.mint_records.iter().any(|r| r.id == record.id) {
+        return Err(format!("Mint {} is already recorded", record.id));
+    }
+    root.ledger.push(record.credit(root.next_tx_id, &root.name));
+    root.next_tx_id += 1;
+    root.mint_records.push(record.clone());
+    serde_json::to_vec(&root).map_err(|e| format!("Failed to serialize root state: {}", e))
+}
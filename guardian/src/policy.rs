@@ -0,0 +1,95 @@
+//! What this guardian agrees to sign.
+//!
+//! `/round1`, `POST /mint` and the bootstrap take a [`SigningRequest`] rather
+//! than raw bytes, and check it here against the guardian's own snapshots of
+//! the root user contract and, for a settlement, the supplier's storefront
+//! (see [`snapshots`]), which the node monitor keeps current. A guardian
+//! without a snapshot to check against refuses: root updates need
+//! `--node-url`, and settlements a storefront the guardian follows.
+//!
+//! `--max-mint-curds` and `--max-root-debit-curds` cap single mints and
+//! root debits outside settlements.
+
+use std::fmt;
+
+use axum::http::StatusCode;
+use cream_common::signing_request::{ContractView, PolicyViolation, SigningRequest};
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+use serde::de::DeserializeOwned;
+
+use crate::snapshots::{self, CriticalContract};
+use crate::{contracts, AppState};
+
+/// Why this guardian won't sign a request.
+#[derive(Debug)]
+pub enum Refusal {
+    /// No keys yet, so no group key to find the contracts by.
+    NotReady,
+    Policy(PolicyViolation),
+}
+
+impl Refusal {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Refusal::NotReady => StatusCode::SERVICE_UNAVAILABLE,
+            Refusal::Policy(_) => StatusCode::FORBIDDEN,
+        }
+    }
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Refusal::NotReady => write!(f, "Guardian not ready (DKG in progress)"),
+            Refusal::Policy(violation) => write!(f, "Refused to sign: {}", violation),
+        }
+    }
+}
+
+/// Check `request` against the signing policy and this guardian's view of
+/// the contracts it touches.
+pub async fn check(state: &AppState, request: &SigningRequest) -> Result<(), Refusal> {
+    let pubkey_package = state
+        .public_key_package
+        .read()
+        .await
+        .clone()
+        .ok_or(Refusal::NotReady)?;
+    let root_owner = cream_common::frost::group_verifying_key(&pubkey_package);
+    let root_key = contracts::root_user_contract(&pubkey_package).key();
+    let root: Option<UserContractState> = load(
+        state.share_index,
+        CriticalContract::RootUserContract,
+        &root_key.to_string(),
+        &root_owner,
+    );
+    let storefront: Option<StorefrontState> = match request {
+        SigningRequest::Settlement { supplier, .. } => {
+            let key = contracts::storefront_contract(&supplier.0).key();
+            load(
+                state.share_index,
+                CriticalContract::Storefront { owner: supplier.0 },
+                &key.to_string(),
+                &root_owner,
+            )
+        }
+        _ => None,
+    };
+    let view = ContractView {
+        root: root.as_ref(),
+        storefront: storefront.as_ref(),
+    };
+    state.signing_policy.check(request, view).map_err(Refusal::Policy)
+}
+
+/// The verified snapshot of `contract`, parsed; `None` without one.
+fn load<T: DeserializeOwned>(
+    share_index: u16,
+    contract: CriticalContract,
+    contract_key: &str,
+    root_owner: &ed25519_dalek::VerifyingKey,
+) -> Option<T> {
+    let state = snapshots::load(share_index, contract, contract_key, root_owner).ok()?;
+    serde_json::from_slice(&state).ok()
+}
@@ -0,0 +1,200 @@
+//! Signing sessions behind `/round1` and `/round2`.
+//!
+//! `/round1` takes what is to be signed — a request that must first pass the
+//! guardian's policy (see [`policy`](crate::policy)) — and answers with
+//! fresh commitments and a session token chosen by this guardian, bound to
+//! the SHA-256 of the request's message and to the client that asked.
+//! `/round2` signs only for that client, with that token and the same
+//! message, and only once: the nonces are taken out on first use. Signing two messages with the same
+//! FROST nonces would give away the signing share, so a token can be neither
+//! replayed nor pointed at another message.
+//!
+//! Sessions expire after [`SESSION_TTL`]. Spent and expired tokens are
+//! remembered for a while longer, so a late or repeated round 2 is told
+//! which it was instead of "unknown session". Each client (by IP address)
+//! may open at most [`MAX_SESSIONS_PER_MINUTE`] sessions a minute and hold
+//! at most [`MAX_OPEN_SESSIONS`] unspent ones.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+use axum::http::StatusCode;
+use frost_ed25519 as frost;
+use sha2::{Digest, Sha256};
+
+/// How long a session's nonces stay usable after round 1.
+pub const SESSION_TTL: Duration = Duration::from_secs(30);
+
+/// How long a spent or expired token is still recognised.
+const REMEMBER_FOR: Duration = Duration::from_secs(600);
+
+/// Sessions one client may open per minute.
+pub const MAX_SESSIONS_PER_MINUTE: usize = 30;
+
+/// Unspent, unexpired sessions one client may hold at once.
+pub const MAX_OPEN_SESSIONS: usize = 8;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Why a session couldn't be opened or used.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// No such token was issued to this client (or it was forgotten).
+    Unknown,
+    /// The token is older than [`SESSION_TTL`].
+    Expired,
+    /// Round 2 already ran for the token.
+    Consumed,
+    /// Round 2 named a different message than round 1. The session is spent.
+    MessageMismatch,
+    /// The client opened [`MAX_SESSIONS_PER_MINUTE`] sessions in the last minute.
+    RateLimited { retry_after: Duration },
+    /// The client holds [`MAX_OPEN_SESSIONS`] unspent sessions.
+    TooManyOpen,
+}
+
+impl SessionError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            SessionError::Unknown => StatusCode::NOT_FOUND,
+            SessionError::Expired => StatusCode::GONE,
+            SessionError::Consumed => StatusCode::CONFLICT,
+            SessionError::MessageMismatch => StatusCode::BAD_REQUEST,
+            SessionError::RateLimited { .. } | SessionError::TooManyOpen => StatusCode::TOO_MANY_REQUESTS,
+        }
+    }
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Unknown => write!(f, "Unknown signing session"),
+            SessionError::Expired => {
+                write!(f, "Signing session expired (sessions last {}s)", SESSION_TTL.as_secs())
+            }
+            SessionError::Consumed => write!(f, "Signing session already used"),
+            SessionError::MessageMismatch => {
+                write!(f, "Message differs from the one the session was opened for")
+            }
+            SessionError::RateLimited { retry_after } => write!(
+                f,
+                "Too many signing sessions (max {} a minute), retry in {}s",
+                MAX_SESSIONS_PER_MINUTE,
+                retry_after.as_secs().max(1)
+            ),
+            SessionError::TooManyOpen => {
+                write!(f, "Too many open signing sessions (max {})", MAX_OPEN_SESSIONS)
+            }
+        }
+    }
+}
+
+struct Session {
+    client: IpAddr,
+    message_hash: [u8; 32],
+    opened: Instant,
+    /// Dropped once spent or expired.
+    nonces: Option<frost::round1::SigningNonces>,
+    consumed: bool,
+}
+
+impl Session {
+    fn is_open(&self) -> bool {
+        self.nonces.is_some()
+    }
+}
+
+/// Open signing sessions, plus recently spent and expired ones.
+#[derive(Default)]
+pub struct SigningSessions {
+    sessions: HashMap<String, Session>,
+    /// When each client opened its sessions in the last minute.
+    opened_by: HashMap<IpAddr, VecDeque<Instant>>,
+}
+
+fn message_hash(message: &[u8]) -> [u8; 32] {
+    Sha256::digest(message).into()
+}
+
+impl SigningSessions {
+    /// Keep `nonces` for `client` to sign `message` with, returning the
+    /// session token.
+    pub fn open(
+        &mut self,
+        client: IpAddr,
+        message: &[u8],
+        nonces: frost::round1::SigningNonces,
+        now: Instant,
+    ) -> Result<String, SessionError> {
+        self.prune(now);
+        let recent = self.opened_by.entry(client).or_default();
+        if recent.len() >= MAX_SESSIONS_PER_MINUTE {
+            let oldest = recent.front().copied().unwrap_or(now);
+            return Err(SessionError::RateLimited {
+                retry_after: RATE_WINDOW.saturating_sub(now.duration_since(oldest)),
+            });
+        }
+        let open = self.sessions.values().filter(|s| s.client == client && s.is_open()).count();
+        if open >= MAX_OPEN_SESSIONS {
+            return Err(SessionError::TooManyOpen);
+        }
+        recent.push_back(now);
+
+        let token: String = rand::random::<[u8; 32]>().iter().map(|b| format!("{:02x}", b)).collect();
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                client,
+                message_hash: message_hash(message),
+                opened: now,
+                nonces: Some(nonces),
+                consumed: false,
+            },
+        );
+        Ok(token)
+    }
+
+    /// Spend the session `token` to sign `message`, returning its nonces.
+    pub fn take(
+        &mut self,
+        client: IpAddr,
+        token: &str,
+        message: &[u8],
+        now: Instant,
+    ) -> Result<frost::round1::SigningNonces, SessionError> {
+        self.prune(now);
+        let session = match self.sessions.get_mut(token) {
+            Some(session) if session.client == client => session,
+            _ => return Err(SessionError::Unknown),
+        };
+        if session.consumed {
+            return Err(SessionError::Consumed);
+        }
+        let nonces = session.nonces.take().ok_or(SessionError::Expired)?;
+        session.consumed = true;
+        if session.message_hash != message_hash(message) {
+            return Err(SessionError::MessageMismatch);
+        }
+        Ok(nonces)
+    }
+
+    /// Drop the nonces of expired sessions, forget old tokens and clear
+    /// rate-limit entries older than a minute.
+    fn prune(&mut self, now: Instant) {
+        self.sessions.retain(|_, session| {
+            let age = now.duration_since(session.opened);
+            if age >= SESSION_TTL {
+                session.nonces = None;
+            }
+            age < REMEMBER_FOR
+        });
+        self.opened_by.retain(|_, opened| {
+            while opened.front().is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW) {
+                opened.pop_front();
+            }
+            !opened.is_empty()
+        });
+    }
+}
@@ -0,0 +1,163 @@
+//! Local snapshots of critical contract state.
+//!
+//! The guardian keeps the last verified state of the directory and root user
+//! contracts — and of the storefronts it replicates — on disk so it can
+//! re-PUT them if its co-located node comes back from a restart without them. Only states whose signatures check out are
+//! stored, and a snapshot is re-verified before it is handed back.
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use cream_common::directory::DirectoryState;
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// A contract the guardian acts as an availability anchor for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CriticalContract {
+    Directory,
+    RootUserContract,
+    /// A supplier's storefront, replicated from the directory listing.
+    Storefront { owner: ed25519_dalek::VerifyingKey },
+}
+
+impl CriticalContract {
+    pub fn name(self) -> &'static str {
+        match self {
+            CriticalContract::Directory => "directory",
+            CriticalContract::RootUserContract => "root user contract",
+            CriticalContract::Storefront { .. } => "storefront",
+        }
+    }
+
+    /// Snapshot file, relative to the snapshots directory. Storefronts get
+    /// one file per contract.
+    fn file_name(self, contract_key: &str) -> String {
+        match self {
+            CriticalContract::Directory => "directory.json".to_string(),
+            CriticalContract::RootUserContract => "root-user-contract.json".to_string(),
+            CriticalContract::Storefront { .. } => format!("storefronts/{}.json", contract_key),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    /// Contract key the state belongs to; a snapshot for a different key
+    /// (e.g. after a redeal changed the root key) is ignored.
+    contract_key: String,
+    /// The state exactly as the node returned it.
+    state: String,
+    /// SHA-256 of `state`, hex-encoded.
+    sha256: String,
+    /// Unix seconds when the snapshot was taken.
+    saved_at: u64,
+}
+
+fn snapshot_path(share_index: u16, contract: CriticalContract, contract_key: &str) -> PathBuf {
+    let cache = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
+    cache
+        .join("freenet")
+        .join(format!("guardian-{}", share_index))
+        .join("snapshots")
+        .join(contract.file_name(contract_key))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check that `state` is a well-formed, correctly signed state for `contract`.
+fn verify_state(
+    contract: CriticalContract,
+    state: &[u8],
+    root_owner: &ed25519_dalek::VerifyingKey,
+) -> Result<(), String> {
+    match contract {
+        CriticalContract::Directory => {
+            let dir: DirectoryState = serde_json::from_slice(state)
+                .map_err(|e| format!("Invalid directory state: {}", e))?;
+            if !dir.validate_all_signatures() {
+                return Err("Directory state has invalid entry signatures".to_string());
+            }
+        }
+        CriticalContract::RootUserContract => {
+            let uc: UserContractState = serde_json::from_slice(state)
+                .map_err(|e| format!("Invalid user contract state: {}", e))?;
+            if !uc.validate(root_owner) {
+                return Err("Root user contract state has an invalid signature".to_string());
+            }
+        }
+        CriticalContract::Storefront { owner } => {
+            let sf: StorefrontState = serde_json::from_slice(state)
+                .map_err(|e| format!("Invalid storefront state: {}", e))?;
+            if !sf.validate(&owner) {
+                return Err("Storefront state has invalid signatures".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Store `state` as the latest snapshot for `contract`, if it verifies.
+pub fn save(
+    share_index: u16,
+    contract: CriticalContract,
+    contract_key: &str,
+    state: &[u8],
+    root_owner: &ed25519_dalek::VerifyingKey,
+) -> Result<(), String> {
+    verify_state(contract, state, root_owner)?;
+    let text = std::str::from_utf8(state).map_err(|e| format!("State is not UTF-8: {}", e))?;
+
+    let path = snapshot_path(share_index, contract, contract_key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    let snapshot = Snapshot {
+        contract_key: contract_key.to_string(),
+        state: text.to_string(),
+        sha256: sha256_hex(state),
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let data = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize: {}", e))?;
+    std::fs::write(&path, data).map_err(|e| format!("Failed to write: {}", e))?;
+    println!("Saved {} snapshot to {}", contract.name(), path.display());
+    Ok(())
+}
+
+/// Load the snapshot for `contract`, returning the state bytes if it belongs to
+/// `contract_key`, is intact and still verifies.
+pub fn load(
+    share_index: u16,
+    contract: CriticalContract,
+    contract_key: &str,
+    root_owner: &ed25519_dalek::VerifyingKey,
+) -> Result<Vec<u8>, String> {
+    let path = snapshot_path(share_index, contract, contract_key);
+    let data = std::fs::read_to_string(&path)
+        .map_err(|e| format!("No snapshot at {}: {}", path.display(), e))?;
+    let snapshot: Snapshot =
+        serde_json::from_str(&data).map_err(|e| format!("Failed to parse snapshot: {}", e))?;
+    if snapshot.contract_key != contract_key {
+        return Err(format!(
+            "Snapshot is for contract {}, expected {}",
+            snapshot.contract_key, contract_key
+        ));
+    }
+    let state = snapshot.state.into_bytes();
+    if sha256_hex(&state) != snapshot.sha256 {
+        return Err("Snapshot checksum mismatch".to_string());
+    }
+    verify_state(contract, &state, root_owner)?;
+    Ok(state)
+}
@@ -0,0 +1,128 @@
+//! Storefront replication.
+//!
+//! Besides the critical contracts, the guardian follows the storefronts listed
+//! in the directory, so supplier data has a second holder on small networks
+//! where otherwise only the supplier's own node keeps it. Followed storefronts
+//! are snapshotted like the critical contracts and re-PUT if the node loses
+//! them.
+//!
+//! At most `cap` storefronts are followed at a time. When more are listed the
+//! window rotates through them, so each one is fetched, snapshotted and
+//! repaired in turn. Freenet has no unsubscribe: storefronts rotated out stay
+//! subscribed on the node until the monitor reconnects, but the guardian only
+//! tracks (and writes snapshots for) the current window.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+
+use cream_common::directory::DirectoryState;
+use cream_common::identity::UserId;
+use freenet_stdlib::prelude::{ContractInstanceId, ContractKey};
+
+/// A storefront listed in the directory.
+#[derive(Clone, Debug)]
+pub struct ListedStorefront {
+    pub owner: ed25519_dalek::VerifyingKey,
+    pub key: ContractKey,
+}
+
+/// Which listed storefronts the guardian is following right now.
+pub struct StorefrontWatch {
+    cap: usize,
+    /// Every storefront listed in a directory state seen so far, by supplier.
+    listed: BTreeMap<UserId, ListedStorefront>,
+    /// The current window, by storefront instance id.
+    followed: HashMap<ContractInstanceId, UserId>,
+    /// Last supplier taken into the window; the next rotation starts after it.
+    cursor: Option<UserId>,
+}
+
+impl StorefrontWatch {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            cap,
+            listed: BTreeMap::new(),
+            followed: HashMap::new(),
+            cursor: None,
+        }
+    }
+
+    /// Record the storefronts listed in `directory` (one shard's state).
+    /// Returns the newly listed ones that fit in the window, to follow now.
+    pub fn add_listing(&mut self, directory: &DirectoryState) -> Vec<ListedStorefront> {
+        for entry in directory.entries.values() {
+            self.listed.insert(
+                entry.supplier.clone(),
+                ListedStorefront {
+                    owner: entry.supplier.0,
+                    key: entry.storefront_key,
+                },
+            );
+        }
+        self.fill()
+    }
+
+    /// [`StorefrontWatch::add_listing`] for a raw directory state; a state
+    /// that doesn't parse lists nothing.
+    pub fn add_listing_bytes(&mut self, state: &[u8]) -> Vec<ListedStorefront> {
+        match serde_json::from_slice::<DirectoryState>(state) {
+            Ok(directory) => self.add_listing(&directory),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Move the window on to the next `cap` listed storefronts and return
+    /// them. A no-op while everything listed fits in the window.
+    pub fn rotate(&mut self) -> Vec<ListedStorefront> {
+        if self.listed.len() <= self.cap {
+            return Vec::new();
+        }
+        self.followed.clear();
+        self.fill()
+    }
+
+    /// The storefronts in the current window (to re-follow after reconnecting).
+    pub fn current(&self) -> Vec<ListedStorefront> {
+        self.followed
+            .values()
+            .filter_map(|supplier| self.listed.get(supplier).cloned())
+            .collect()
+    }
+
+    /// The followed storefront with instance id `id`, if any.
+    pub fn lookup(&self, id: &ContractInstanceId) -> Option<&ListedStorefront> {
+        self.followed.get(id).and_then(|supplier| self.listed.get(supplier))
+    }
+
+    /// Fill free window slots in rotation order (after the cursor, wrapping).
+    fn fill(&mut self) -> Vec<ListedStorefront> {
+        let in_window: HashSet<UserId> = self.followed.values().cloned().collect();
+        let order: Vec<(UserId, ListedStorefront)> = match &self.cursor {
+            Some(cursor) => self
+                .listed
+                .range((Bound::Excluded(cursor), Bound::Unbounded))
+                .chain(self.listed.range(..=cursor))
+                .map(|(supplier, listed)| (supplier.clone(), listed.clone()))
+                .collect(),
+            None => self
+                .listed
+                .iter()
+                .map(|(supplier, listed)| (supplier.clone(), listed.clone()))
+                .collect(),
+        };
+
+        let mut added = Vec::new();
+        for (supplier, listed) in order {
+            if self.followed.len() >= self.cap {
+                break;
+            }
+            if in_window.contains(&supplier) {
+                continue;
+            }
+            self.followed.insert(*listed.key.id(), supplier.clone());
+            self.cursor = Some(supplier);
+            added.push(listed);
+        }
+        added
+    }
+}
@@ -0,0 +1,284 @@
+//! DKG ceremony transcripts.
+//!
+//! During the DKG ceremony each guardian records the packages it handled:
+//! every round-1 package (broadcast, so all guardians should hold the same
+//! set, their own included) and the round-2 packages it sent and received.
+//! Round-2 packages carry secret shares, so only their hashes are kept.
+//!
+//! Entries are hash-chained in a canonical order — round 1 by sender, then
+//! round 2 by sender and recipient — so honest guardians' round-1 chains end
+//! at the same hash whatever order the packages arrived in. The transcript is
+//! saved next to the keys and served at `GET /dkg/transcript`.
+//!
+//! `--verify-transcripts` fetches every guardian's transcript and runs
+//! [`verify`] over them. A guardian that was shown a different round-1
+//! package than the others (a man in the middle, or a peer equivocating), or
+//! that received a round-2 package other than the one its sender recorded,
+//! shows up as a mismatch.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use frost_ed25519 as frost;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Where every chain starts.
+const CHAIN_SEED: &[u8] = b"cream-dkg-transcript";
+
+/// One package in a [`Transcript`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    /// DKG round, 1 or 2.
+    pub round: u8,
+    pub from: frost::Identifier,
+    /// Recipient of a round-2 package; round-1 packages go to everyone.
+    pub to: Option<frost::Identifier>,
+    /// SHA-256 of the package (hex).
+    pub package_hash: String,
+    /// SHA-256 over the previous entry's hash and this entry (hex).
+    pub hash: String,
+}
+
+/// One guardian's record of a DKG ceremony.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    /// The guardian that recorded it.
+    pub guardian: frost::Identifier,
+    pub entries: Vec<TranscriptEntry>,
+    /// Hash of the last round-1 entry, the same for every honest guardian.
+    pub round1_head: String,
+    /// Hash of the last entry.
+    pub head: String,
+}
+
+#[derive(Serialize)]
+struct ChainedEntry<'a> {
+    round: u8,
+    from: &'a frost::Identifier,
+    to: &'a Option<frost::Identifier>,
+    package_hash: &'a str,
+}
+
+impl Transcript {
+    /// Chain the packages `guardian` saw: all round-1 packages, its own
+    /// included, and the round-2 packages it sent and received, keyed by
+    /// (sender, recipient).
+    pub fn record(
+        guardian: frost::Identifier,
+        round1: &BTreeMap<frost::Identifier, frost::keys::dkg::round1::Package>,
+        round2: &BTreeMap<(frost::Identifier, frost::Identifier), frost::keys::dkg::round2::Package>,
+    ) -> Self {
+        let mut transcript = Transcript {
+            guardian,
+            entries: Vec::new(),
+            round1_head: String::new(),
+            head: String::new(),
+        };
+        for (from, package) in round1 {
+            transcript.push(1, *from, None, package_hash(package));
+        }
+        transcript.round1_head = transcript.last_hash();
+        for ((from, to), package) in round2 {
+            transcript.push(2, *from, Some(*to), package_hash(package));
+        }
+        transcript.head = transcript.last_hash();
+        transcript
+    }
+
+    fn push(&mut self, round: u8, from: frost::Identifier, to: Option<frost::Identifier>, package_hash: String) {
+        let hash = chain(&self.last_hash(), round, &from, &to, &package_hash);
+        self.entries.push(TranscriptEntry { round, from, to, package_hash, hash });
+    }
+
+    fn last_hash(&self) -> String {
+        self.entries
+            .last()
+            .map_or_else(|| hex(&Sha256::digest(CHAIN_SEED)), |e| e.hash.clone())
+    }
+
+    /// Whether every entry's hash follows from the one before, and the heads
+    /// match the chain.
+    pub fn chain_intact(&self) -> bool {
+        let mut previous = hex(&Sha256::digest(CHAIN_SEED));
+        let mut round1_head = previous.clone();
+        for entry in &self.entries {
+            if chain(&previous, entry.round, &entry.from, &entry.to, &entry.package_hash) != entry.hash {
+                return false;
+            }
+            if entry.round == 1 {
+                round1_head = entry.hash.clone();
+            }
+            previous = entry.hash.clone();
+        }
+        self.round1_head == round1_head && self.head == previous
+    }
+
+    fn round2_entry(&self, from: &frost::Identifier, to: &frost::Identifier) -> Option<&TranscriptEntry> {
+        self.entries
+            .iter()
+            .find(|e| e.round == 2 && e.from == *from && e.to.as_ref() == Some(to))
+    }
+}
+
+fn chain(
+    previous: &str,
+    round: u8,
+    from: &frost::Identifier,
+    to: &Option<frost::Identifier>,
+    package_hash: &str,
+) -> String {
+    let entry = ChainedEntry { round, from, to, package_hash };
+    let mut hasher = Sha256::new();
+    hasher.update(previous.as_bytes());
+    hasher.update(serde_json::to_vec(&entry).expect("serialization should not fail"));
+    hex(&hasher.finalize())
+}
+
+fn package_hash<P: Serialize>(package: &P) -> String {
+    hex(&Sha256::digest(serde_json::to_vec(package).expect("serialization should not fail")))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Readable name for a guardian in a report.
+fn name(id: &frost::Identifier) -> String {
+    serde_json::to_string(id).unwrap_or_default().trim_matches('"').to_string()
+}
+
+/// Cross-check the transcripts of a ceremony's guardians. Returns what
+/// doesn't match; empty if they all saw the same ceremony.
+pub fn verify(transcripts: &[Transcript]) -> Vec<String> {
+    let mut problems = Vec::new();
+    for t in transcripts {
+        if !t.chain_intact() {
+            problems.push(format!("guardian {}: hash chain doesn't check out", name(&t.guardian)));
+        }
+    }
+
+    // Round 1 was broadcast: everyone must hold the same packages
+    if let Some((first, rest)) = transcripts.split_first() {
+        let round1 = |t: &Transcript| -> BTreeMap<frost::Identifier, String> {
+            t.entries
+                .iter()
+                .filter(|e| e.round == 1)
+                .map(|e| (e.from, e.package_hash.clone()))
+                .collect()
+        };
+        let expected = round1(first);
+        for t in rest.iter().filter(|t| t.round1_head != first.round1_head) {
+            let seen = round1(t);
+            for from in expected.keys().chain(seen.keys()).collect::<BTreeSet<_>>() {
+                if expected.get(from) != seen.get(from) {
+                    problems.push(format!(
+                        "round 1 package from {}: guardians {} and {} hold different ones",
+                        name(from),
+                        name(&first.guardian),
+                        name(&t.guardian)
+                    ));
+                }
+            }
+        }
+    }
+
+    // Round 2 was point to point: sender and recipient must agree
+    let by_guardian: BTreeMap<_, _> = transcripts.iter().map(|t| (t.guardian, t)).collect();
+    for t in transcripts {
+        for entry in t.entries.iter().filter(|e| e.round == 2 && e.from == t.guardian) {
+            let Some(to) = entry.to else { continue };
+            let Some(peer) = by_guardian.get(&to) else { continue };
+            match peer.round2_entry(&entry.from, &to) {
+                Some(received) if received.package_hash == entry.package_hash => {}
+                Some(_) => problems.push(format!(
+                    "round 2 package from {} to {}: received a different one than was sent",
+                    name(&entry.from),
+                    name(&to)
+                )),
+                None => problems.push(format!(
+                    "round 2 package from {} to {}: recipient has no record of it",
+                    name(&entry.from),
+                    name(&to)
+                )),
+            }
+        }
+        for entry in t.entries.iter().filter(|e| e.round == 2 && e.to == Some(t.guardian)) {
+            let sent = by_guardian.get(&entry.from).map(|peer| peer.round2_entry(&entry.from, &t.guardian));
+            if let Some(None) = sent {
+                problems.push(format!(
+                    "round 2 package from {} to {}: sender has no record of sending it",
+                    name(&entry.from),
+                    name(&t.guardian)
+                ));
+            }
+        }
+    }
+    problems
+}
+
+/// Fetch each guardian's transcript from `urls`, cross-check them and print
+/// the result. Returns whether they all agree.
+pub async fn verify_remote(urls: &[String]) -> bool {
+    let client = reqwest::Client::new();
+    let mut transcripts = Vec::new();
+    for url in urls {
+        let fetched = match client.get(format!("{}/dkg/transcript", url)).send().await {
+            Ok(response) if response.status().is_success() => {
+                response.json::<Transcript>().await.map_err(|e| e.to_string())
+            }
+            Ok(response) => Err(format!("HTTP {}", response.status())),
+            Err(e) => Err(e.to_string()),
+        };
+        match fetched {
+            Ok(transcript) => {
+                println!(
+                    "{}: guardian {}, {} entries, round 1 head {}",
+                    url,
+                    name(&transcript.guardian),
+                    transcript.entries.len(),
+                    transcript.round1_head
+                );
+                transcripts.push(transcript);
+            }
+            Err(e) => {
+                eprintln!("{}: no transcript ({})", url, e);
+                return false;
+            }
+        }
+    }
+    let problems = verify(&transcripts);
+    if problems.is_empty() {
+        println!("All {} transcripts agree", transcripts.len());
+        return true;
+    }
+    for problem in &problems {
+        eprintln!("MISMATCH: {}", problem);
+    }
+    false
+}
+
+pub fn transcript_path(share_index: u16) -> PathBuf {
+    let cache = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("/tmp"));
+    cache
+        .join("freenet")
+        .join(format!("guardian-{}", share_index))
+        .join("dkg-transcript.json")
+}
+
+pub fn load(share_index: u16) -> Option<Transcript> {
+    let data = std::fs::read_to_string(transcript_path(share_index)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save(share_index: u16, transcript: &Transcript) -> Result<(), String> {
+    let path = transcript_path(share_index);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create dir: {}", e))?;
+    }
+    let data =
+        serde_json::to_string_pretty(transcript).map_err(|e| format!("Failed to serialize: {}", e))?;
+    std::fs::write(&path, data).map_err(|e| format!("Failed to write: {}", e))?;
+    println!("Saved DKG transcript to {}", path.display());
+    Ok(())
+}
@@ -37,7 +37,7 @@ async function registerSupplier(
 	keypair: { privKey: Uint8Array; pubKeyHex: string },
 ) {
 	const normalizedName = name.toLowerCase();
-	const message = `${normalizedName}|${address}|${storefrontKey}`;
+	const message = `${normalizedName}|${address}|${storefrontKey}||`;
 	const signature = await signMessage(keypair.privKey, message);
 
 	return SELF.fetch('http://localhost/register', {
@@ -176,6 +176,8 @@ describe('Lookup (GET /lookup/:name)', () => {
 			name: 'grace',
 			address: 'ws://grace:3001',
 			storefront_key: 'sf_key_grace',
+			public_key: kp.pubKeyHex,
+			signature: await signMessage(kp.privKey, 'grace|ws://grace:3001|sf_key_grace||'),
 		});
 	});
 
@@ -200,13 +202,15 @@ describe('Lookup (GET /lookup/:name)', () => {
 		expect((await res.json() as Record<string, unknown>).error).toBe('Name required');
 	});
 
-	it('13: lookup response excludes public_key', async () => {
+	it('13: lookup response carries the supplier key and registration signature', async () => {
 		const kp = await makeKeypair();
 		await registerSupplier('iris', 'ws://iris:3001', 'sf_key_iris', kp);
 
 		const res = await SELF.fetch('http://localhost/lookup/iris');
 		const body = await res.json() as Record<string, unknown>;
-		expect(body).not.toHaveProperty('public_key');
+		expect(body.public_key).toBe(kp.pubKeyHex);
+		expect(body.signature).toBe(await signMessage(kp.privKey, 'iris|ws://iris:3001|sf_key_iris||'));
+		expect(body).not.toHaveProperty('registered_address');
 	});
 });
 
@@ -232,7 +236,12 @@ describe('Heartbeat (POST /heartbeat)', () => {
 		expect(res.status).toBe(200);
 
 		const lookup = await SELF.fetch('http://localhost/lookup/jack');
-		expect((await lookup.json() as Record<string, unknown>).address).toBe(newAddress);
+		const body = await lookup.json() as Record<string, unknown>;
+		expect(body.address).toBe(newAddress);
+		// The registration was signed for the old address; the heartbeat
+		// signature vouches for the new one
+		expect(body.registered_address).toBe('ws://jack:3001');
+		expect(body.address_signature).toBe(signature);
 	});
 
 	it('15: heartbeat for non-existent supplier returns 404', async () => {
@@ -18,6 +18,11 @@ interface SupplierRecord {
   public_key: string;
   user_contract_key?: string;
   inbox_contract_key?: string;
+  // The supplier's signatures, returned on lookup so clients can check the
+  // record against the supplier's directory key rather than trusting us.
+  signature?: string;
+  registered_address?: string;
+  address_signature?: string;
 }
 
 interface RegisterBody {
@@ -108,6 +113,8 @@ async function handleRegister(request: Request, env: Env): Promise<Response> {
     public_key,
     ...(user_contract_key ? { user_contract_key } : {}),
     ...(inbox_contract_key ? { inbox_contract_key } : {}),
+    signature,
+    registered_address: address,
   };
 
   await env.SUPPLIERS.put(normalizedName, JSON.stringify(record), {
@@ -152,6 +159,7 @@ async function handleHeartbeat(request: Request, env: Env): Promise<Response> {
   const record: SupplierRecord = {
     ...existing,
     address,
+    address_signature: signature,
   };
 
   await env.SUPPLIERS.put(normalizedName, JSON.stringify(record), {
@@ -175,6 +183,11 @@ async function handleLookup(name: string, env: Env): Promise<Response> {
     storefront_key: record.storefront_key,
     ...(record.user_contract_key ? { user_contract_key: record.user_contract_key } : {}),
     ...(record.inbox_contract_key ? { inbox_contract_key: record.inbox_contract_key } : {}),
+    public_key: record.public_key,
+    ...(record.signature ? { signature: record.signature } : {}),
+    ...(record.registered_address && record.registered_address !== record.address
+      ? { registered_address: record.registered_address, address_signature: record.address_signature }
+      : {}),
   });
 }
 
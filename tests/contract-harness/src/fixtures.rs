@@ -0,0 +1,255 @@
+//! Signed example states for the harness.
+//!
+//! Unlike the fuzz seeds these carry real signatures from fixed keys, so
+//! they validate with signature checks on. Times are fixed and in the past,
+//! to get through the contracts' clock checks.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, TimeZone, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey};
+
+use cream_common::identity::UserId;
+use cream_common::inbox::{InboxMessage, InboxParameters, InboxState, MessageKind};
+use cream_common::location::GeoLocation;
+use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::storefront::{
+    order_signable_bytes, SignedProduct, StorefrontInfo, StorefrontParameters, StorefrontState,
+};
+use cream_common::tolls::TollRates;
+use cream_common::user_contract::{UserContractParameters, UserContractState};
+use cream_common::wallet::{TransactionKind, WalletTransaction};
+
+/// The supplier in every fixture.
+pub fn supplier() -> SigningKey {
+    SigningKey::from_bytes(&[1u8; 32])
+}
+
+/// A customer of [`supplier`].
+pub fn customer() -> SigningKey {
+    SigningKey::from_bytes(&[2u8; 32])
+}
+
+/// Someone with no business writing to [`supplier`]'s contracts.
+pub fn stranger() -> SigningKey {
+    SigningKey::from_bytes(&[3u8; 32])
+}
+
+pub fn user_id(key: &SigningKey) -> UserId {
+    UserId(key.verifying_key())
+}
+
+/// Noon on `day` April 2026.
+pub fn at(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 4, day, 12, 0, 0).unwrap()
+}
+
+fn no_signature() -> Signature {
+    Signature::from_bytes(&[0u8; 64])
+}
+
+pub fn storefront_parameters() -> StorefrontParameters {
+    StorefrontParameters {
+        owner: supplier().verifying_key(),
+    }
+}
+
+/// [`supplier`]'s storefront with no products or orders.
+pub fn storefront() -> StorefrontState {
+    StorefrontState {
+        info: StorefrontInfo {
+            owner: user_id(&supplier()),
+            name: "Gary's Farm".into(),
+            description: "Raw milk".into(),
+            location: GeoLocation::new(-30.3, 153.1),
+            schedule: None,
+            timezone: Some("Australia/Sydney".into()),
+            phone: None,
+            email: None,
+            address: None,
+            market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
+            extra: Default::default(),
+        },
+        products: BTreeMap::new(),
+        orders: BTreeMap::new(),
+        threads: BTreeMap::new(),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
+        extra: Default::default(),
+    }
+}
+
+/// A listing called `name`, created on `day` and signed by `signer`.
+pub fn product(signer: &SigningKey, name: &str, day: u32) -> SignedProduct {
+    let mut signed = SignedProduct {
+        product: Product {
+            id: ProductId::derive(&user_id(&supplier()), name, at(day), day as u64),
+            name: name.into(),
+            description: "Jersey milk".into(),
+            category: ProductCategory::Milk,
+            subcategory: None,
+            attributes: Default::default(),
+            price_curd: 500,
+            quantity_total: 10,
+            expiry_date: None,
+            updated_at: at(day),
+            created_at: at(day),
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
+        },
+        signature: no_signature(),
+        provenance: None,
+        price_history: Vec::new(),
+        extra: Default::default(),
+    };
+    signed.signature = signer.sign(&signed.signable_bytes());
+    signed
+}
+
+/// [`customer`]'s order for `quantity` of `product`, placed on `day`.
+pub fn order(product: &SignedProduct, quantity: u32, day: u32) -> Order {
+    let customer = customer();
+    let total_price = product.product.price_curd * quantity as u64;
+    let deposit_tier = DepositTier::Reserve2Days;
+    let mut order = Order {
+        id: OrderId::derive(&user_id(&customer), &product.product.id, at(day), 0),
+        product_id: product.product.id.clone(),
+        customer: user_id(&customer),
+        quantity,
+        deposit_tier,
+        deposit_amount: deposit_tier.calculate_deposit(total_price),
+        total_price,
+        status: OrderStatus::Reserved {
+            expires_at: at(day) + deposit_tier.hold_period(),
+        },
+        created_at: at(day),
+        signature: no_signature(),
+        escrow_token: None,
+        collection_point: None,
+        delivery: None,
+        provenance: None,
+        receipt: None,
+        amendments: Vec::new(),
+        status_history: Vec::new(),
+        cancellation_policy: None,
+        refund_amount: None,
+        balance_attestation: None,
+        nonce: 0,
+        extra: Default::default(),
+    };
+    order.signature = customer.sign(&order_signable_bytes(&order));
+    order
+}
+
+/// [`storefront`] listing `products`.
+pub fn storefront_with(products: &[SignedProduct]) -> StorefrontState {
+    let mut state = storefront();
+    for signed in products {
+        state.products.insert(signed.product.id.clone(), signed.clone());
+    }
+    state
+}
+
+pub fn user_contract_parameters() -> UserContractParameters {
+    UserContractParameters {
+        owner: customer().verifying_key(),
+        spending_key: None,
+    }
+}
+
+/// A ledger entry paying `amount` to or from [`customer`] on `day`.
+pub fn transaction(id: u32, kind: TransactionKind, amount: u64, day: u32) -> WalletTransaction {
+    WalletTransaction {
+        id,
+        kind,
+        amount,
+        description: "Milk".into(),
+        sender: "gary".into(),
+        receiver: "emma".into(),
+        tx_ref: format!("gary:{id}:{day}"),
+        timestamp: at(day).to_rfc3339(),
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    }
+}
+
+/// [`customer`]'s user contract with `ledger`, updated on `day` and signed.
+pub fn user_contract(ledger: Vec<WalletTransaction>, day: u32) -> UserContractState {
+    let mut state = UserContractState {
+        owner: user_id(&customer()),
+        name: "emma".into(),
+        origin_supplier: "gary".into(),
+        current_supplier: "gary".into(),
+        balance_curds: 0,
+        invited_by: "gary".into(),
+        next_tx_id: ledger.len() as u32 + 1,
+        ledger,
+        toll_rates: TollRates::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        seq: 0,
+        updated_at: at(day),
+        signature: no_signature(),
+        extra: Default::default(),
+    };
+    state.balance_curds = state.derive_balance();
+    sign_user_contract(&mut state, &customer());
+    state
+}
+
+/// Re-sign `state` with `signer`, after editing it.
+pub fn sign_user_contract(state: &mut UserContractState, signer: &SigningKey) {
+    state.signature = signer.sign(&state.signable_bytes());
+}
+
+pub fn inbox_parameters() -> InboxParameters {
+    InboxParameters {
+        owner: supplier().verifying_key(),
+    }
+}
+
+/// A direct message to [`supplier`], sent on `day`.
+pub fn inbox_message(id: u64, body: &str, day: u32) -> InboxMessage {
+    InboxMessage {
+        id,
+        kind: MessageKind::DirectMessage,
+        from_name: "emma".into(),
+        from_key: None,
+        body: body.into(),
+        toll_paid: 1,
+        created_at: at(day),
+        extra: Default::default(),
+    }
+}
+
+/// [`supplier`]'s inbox holding `messages`.
+pub fn inbox(messages: Vec<InboxMessage>) -> InboxState {
+    InboxState {
+        owner: user_id(&supplier()),
+        updated_at: messages.iter().map(|m| m.created_at).max().unwrap_or(at(1)),
+        messages: messages.into_iter().map(|m| (m.id, m)).collect(),
+        extra: Default::default(),
+    }
+}
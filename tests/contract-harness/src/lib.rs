@@ -0,0 +1,204 @@
+//! Runs the CREAM contracts natively, without a node.
+//!
+//! A [`Harness`] holds one contract instance — its parameters and current
+//! state — in memory and drives the contract's entry points the way a node
+//! would: a PUT validates the state before storing it, an update merges and
+//! then validates the result, and two instances sync by exchanging a summary
+//! for a delta. The contracts are linked as plain libraries (the `contract`
+//! feature without `freenet-main-contract`), so a regression test is an
+//! ordinary `#[test]` against [`fixtures`] states, with no WASM build and no
+//! network.
+//!
+//! ```sh
+//! cargo test --manifest-path tests/contract-harness/Cargo.toml
+//! cargo test --manifest-path tests/contract-harness/Cargo.toml --features dev
+//! ```
+//!
+//! Storefronts are checked against the directory only when
+//! `CREAM_DIRECTORY_CODE_HASH` is set at build time; leave it unset here.
+//!
+//! The contracts read the host's clock through
+//! [`cream_common::clock::host_now`]: the system clock, unless the instance
+//! was given another with [`Harness::with_clock`].
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use cream_common::clock::{set_host_clock, Clock};
+use freenet_stdlib::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub mod fixtures;
+
+/// Why the harness refused a PUT or an update.
+#[derive(Debug)]
+pub enum HarnessError {
+    /// The contract returned an error.
+    Contract(ContractError),
+    /// `validate_state` judged the state invalid.
+    Invalid,
+    /// `validate_state` asked for related contracts it wasn't given.
+    NeedsRelated(Vec<ContractInstanceId>),
+}
+
+impl fmt::Display for HarnessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HarnessError::Contract(e) => write!(f, "Contract error: {}", e),
+            HarnessError::Invalid => write!(f, "State is invalid"),
+            HarnessError::NeedsRelated(ids) => write!(f, "Needs {} related contract(s)", ids.len()),
+        }
+    }
+}
+
+impl From<ContractError> for HarnessError {
+    fn from(e: ContractError) -> Self {
+        HarnessError::Contract(e)
+    }
+}
+
+fn json(value: &impl Serialize) -> Vec<u8> {
+    serde_json::to_vec(value).expect("fixture serializes")
+}
+
+/// One instance of contract `C`: its parameters, current state and the
+/// clock its host keeps.
+pub struct Harness<C> {
+    parameters: Vec<u8>,
+    state: Vec<u8>,
+    clock: Option<Arc<dyn Clock>>,
+    _contract: PhantomData<C>,
+}
+
+impl<C> Clone for Harness<C> {
+    fn clone(&self) -> Self {
+        Self {
+            parameters: self.parameters.clone(),
+            state: self.state.clone(),
+            clock: self.clock.clone(),
+            _contract: PhantomData,
+        }
+    }
+}
+
+impl<C: ContractInterface> Harness<C> {
+    /// An instance with `parameters` (serialized as JSON) and no state yet.
+    pub fn new(parameters: &impl Serialize) -> Self {
+        Self::from_raw_parameters(json(parameters))
+    }
+
+    /// An instance with parameters already serialized.
+    pub fn from_raw_parameters(parameters: Vec<u8>) -> Self {
+        Self {
+            parameters,
+            state: Vec::new(),
+            clock: None,
+            _contract: PhantomData,
+        }
+    }
+
+    /// This instance with its host reading `clock` instead of the system
+    /// clock, such as a [`cream_common::clock::MockClock`] a test moves.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    fn parameters(&self) -> Parameters<'static> {
+        Parameters::from(self.parameters.clone())
+    }
+
+    /// Call into the contract with its host reading this instance's clock.
+    fn on_host<T>(&self, call: impl FnOnce() -> T) -> T {
+        set_host_clock(self.clock.clone());
+        let result = call();
+        set_host_clock(None);
+        result
+    }
+
+    /// `validate_state` on `state`, with no related contracts.
+    pub fn validate(&self, state: &impl Serialize) -> Result<ValidateResult, ContractError> {
+        self.validate_bytes(json(state))
+    }
+
+    /// `validate_state` on raw state bytes, with no related contracts.
+    pub fn validate_bytes(&self, state: Vec<u8>) -> Result<ValidateResult, ContractError> {
+        self.on_host(|| C::validate_state(self.parameters(), State::from(state), RelatedContracts::default()))
+    }
+
+    /// Store `state` as a PUT would: only if it validates.
+    pub fn put(&mut self, state: &impl Serialize) -> Result<(), HarnessError> {
+        let bytes = json(state);
+        check(self.validate_bytes(bytes.clone())?)?;
+        self.state = bytes;
+        Ok(())
+    }
+
+    /// Send `update` as a full state.
+    pub fn update(&mut self, update: &impl Serialize) -> Result<(), HarnessError> {
+        self.apply(vec![UpdateData::State(State::from(json(update)))])
+    }
+
+    /// Send `delta` as a delta.
+    pub fn update_delta(&mut self, delta: &impl Serialize) -> Result<(), HarnessError> {
+        self.apply(vec![UpdateData::Delta(StateDelta::from(json(delta)))])
+    }
+
+    /// `update_state` with `data`, then `validate_state` on the result, as a
+    /// node does before keeping it. The current state is left alone unless
+    /// both succeed.
+    pub fn apply(&mut self, data: Vec<UpdateData<'static>>) -> Result<(), HarnessError> {
+        let modification = self.on_host(|| C::update_state(self.parameters(), State::from(self.state.clone()), data))?;
+        if let Some(new_state) = modification.new_state {
+            let bytes = new_state.as_ref().to_vec();
+            check(self.validate_bytes(bytes.clone())?)?;
+            self.state = bytes;
+        }
+        Ok(())
+    }
+
+    /// The current state, decoded.
+    pub fn state<S: DeserializeOwned>(&self) -> S {
+        serde_json::from_slice(&self.state).expect("contract state decodes")
+    }
+
+    /// The current state as stored.
+    pub fn state_bytes(&self) -> &[u8] {
+        &self.state
+    }
+
+    /// `summarize_state` on the current state.
+    pub fn summary(&self) -> Result<Vec<u8>, ContractError> {
+        self.on_host(|| C::summarize_state(self.parameters(), State::from(self.state.clone())))
+            .map(|summary| summary.as_ref().to_vec())
+    }
+
+    /// `get_state_delta` from the current state against `summary`.
+    pub fn delta(&self, summary: &[u8]) -> Result<Vec<u8>, ContractError> {
+        self.on_host(|| {
+            C::get_state_delta(
+                self.parameters(),
+                State::from(self.state.clone()),
+                StateSummary::from(summary.to_vec()),
+            )
+        })
+        .map(|delta| delta.as_ref().to_vec())
+    }
+
+    /// Catch up with `other` the way peers sync: send it this instance's
+    /// summary and apply the delta it answers with.
+    pub fn sync_from(&mut self, other: &Self) -> Result<(), HarnessError> {
+        let delta = other.delta(&self.summary()?)?;
+        self.apply(vec![UpdateData::Delta(StateDelta::from(delta))])
+    }
+}
+
+fn check(result: ValidateResult) -> Result<(), HarnessError> {
+    match result {
+        ValidateResult::Valid => Ok(()),
+        ValidateResult::Invalid => Err(HarnessError::Invalid),
+        ValidateResult::RequestRelated(ids) => Err(HarnessError::NeedsRelated(ids)),
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use chrono::{TimeZone, Utc};
+use cream_common::clock::MockClock;
+use cream_common::inbox::InboxState;
+use cream_contract_harness::fixtures::{self, inbox_message};
+use cream_contract_harness::{Harness, HarnessError};
+use cream_inbox_contract::Contract;
+
+fn with_one_message() -> Harness<Contract> {
+    let mut harness = Harness::new(&fixtures::inbox_parameters());
+    let state = fixtures::inbox(vec![inbox_message(1, "Market on Saturday?", 1)]);
+    harness.put(&state).expect("inbox validates");
+    harness
+}
+
+#[test]
+fn new_message_merges_and_syncs() {
+    let mut inbox = with_one_message();
+    let mut replica = inbox.clone();
+
+    let delta = fixtures::inbox(vec![inbox_message(2, "Two litres please", 2)]);
+    inbox.update_delta(&delta).expect("append merges");
+    assert_eq!(inbox.state::<InboxState>().messages.len(), 2);
+
+    replica.sync_from(&inbox).expect("delta applies");
+    assert_eq!(replica.state::<InboxState>().messages, inbox.state::<InboxState>().messages);
+    assert!(inbox.delta(&replica.summary().unwrap()).unwrap().is_empty());
+}
+
+#[test]
+fn editing_a_message_is_refused() {
+    let mut inbox = with_one_message();
+    let edited = fixtures::inbox(vec![inbox_message(1, "Never mind", 1)]);
+    assert!(matches!(inbox.update(&edited), Err(HarnessError::Contract(_))));
+    assert_eq!(inbox.state::<InboxState>().messages[&1].body, "Market on Saturday?");
+}
+
+#[test]
+fn future_dated_message_is_refused() {
+    let mut inbox = with_one_message();
+    let mut message = inbox_message(2, "From the future", 2);
+    message.created_at = Utc.with_ymd_and_hms(2100, 1, 1, 0, 0, 0).unwrap();
+    let delta = fixtures::inbox(vec![message]);
+    assert!(matches!(inbox.update_delta(&delta), Err(HarnessError::Contract(_))));
+    assert_eq!(inbox.state::<InboxState>().messages.len(), 1);
+}
+
+#[test]
+fn messages_are_judged_by_the_host_clock() {
+    let clock = Arc::new(MockClock::new(fixtures::at(1)));
+    let mut inbox = with_one_message().with_clock(clock.clone());
+    let delta = fixtures::inbox(vec![inbox_message(2, "See you Friday", 5)]);
+    assert!(matches!(inbox.update_delta(&delta), Err(HarnessError::Contract(_))));
+
+    clock.set(fixtures::at(5));
+    inbox.update_delta(&delta).expect("no longer ahead of the host");
+    assert_eq!(inbox.state::<InboxState>().messages.len(), 2);
+}
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use cream_common::clock::MockClock;
+use cream_common::storefront::StorefrontState;
+use cream_contract_harness::fixtures::{self, supplier};
+use cream_contract_harness::{Harness, HarnessError};
+use cream_storefront_contract::Contract;
+use freenet_stdlib::prelude::ValidateResult;
+
+fn listed() -> Harness<Contract> {
+    let mut harness = Harness::new(&fixtures::storefront_parameters());
+    let milk = fixtures::product(&supplier(), "Raw milk 2L", 1);
+    harness.put(&fixtures::storefront_with(&[milk])).expect("listing validates");
+    harness
+}
+
+#[test]
+fn empty_state_is_valid() {
+    let harness = Harness::<Contract>::new(&fixtures::storefront_parameters());
+    assert_eq!(harness.validate_bytes(Vec::new()).unwrap(), ValidateResult::Valid);
+}
+
+#[test]
+fn update_before_put_is_refused() {
+    let mut harness = Harness::<Contract>::new(&fixtures::storefront_parameters());
+    let result = harness.update(&fixtures::storefront());
+    assert!(matches!(result, Err(HarnessError::Contract(_))));
+}
+
+#[test]
+fn order_merges_and_reaches_other_replicas() {
+    // New orders must be placed on the host's day
+    let mut shop = listed().with_clock(Arc::new(MockClock::new(fixtures::at(2))));
+    let mut replica = shop.clone();
+
+    let state: StorefrontState = shop.state();
+    let milk = state.products.values().next().unwrap().clone();
+    let mut placed = state.clone();
+    let order = fixtures::order(&milk, 2, 2);
+    placed.orders.insert(order.id.clone(), order.clone());
+    shop.update(&placed).expect("customer's order merges");
+    assert_eq!(shop.state::<StorefrontState>().orders.get(&order.id), Some(&order));
+
+    replica.sync_from(&shop).expect("delta applies");
+    assert_eq!(replica.state::<StorefrontState>().orders, shop.state::<StorefrontState>().orders);
+
+    // Already in sync: syncing again changes nothing
+    let before = replica.state_bytes().to_vec();
+    replica.sync_from(&shop).unwrap();
+    assert_eq!(replica.state_bytes(), before);
+}
+
+#[test]
+fn order_filed_under_another_id_is_invalid() {
+    let shop = listed();
+    let mut state: StorefrontState = shop.state();
+    let milk = state.products.values().next().unwrap().clone();
+    let order = fixtures::order(&milk, 1, 2);
+    let other = fixtures::order(&milk, 1, 3);
+    state.orders.insert(other.id, order);
+    assert_eq!(shop.validate(&state).unwrap(), ValidateResult::Invalid);
+}
+
+#[cfg(not(feature = "dev"))]
+#[test]
+fn listing_signed_by_someone_else_is_invalid() {
+    let mut harness = Harness::<Contract>::new(&fixtures::storefront_parameters());
+    let forged = fixtures::product(&fixtures::customer(), "Raw milk 2L", 1);
+    let result = harness.put(&fixtures::storefront_with(&[forged]));
+    assert!(matches!(result, Err(HarnessError::Invalid)));
+}
+
+#[cfg(not(feature = "dev"))]
+#[test]
+fn forged_listing_update_leaves_state_alone() {
+    let mut shop = listed();
+    let before = shop.state_bytes().to_vec();
+    let forged = fixtures::product(&fixtures::customer(), "Cheap milk", 2);
+    let mut state: StorefrontState = shop.state();
+    state.products.insert(forged.product.id.clone(), forged);
+    assert!(shop.update(&state).is_err());
+    assert_eq!(shop.state_bytes(), before);
+}
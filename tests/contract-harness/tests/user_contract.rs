@@ -0,0 +1,64 @@
+use cream_common::user_contract::UserContractState;
+use cream_common::wallet::TransactionKind;
+use cream_contract_harness::fixtures::{self, transaction};
+use cream_contract_harness::{Harness, HarnessError};
+use cream_user_contract::Contract;
+
+fn funded() -> Harness<Contract> {
+    let mut harness = Harness::new(&fixtures::user_contract_parameters());
+    let state = fixtures::user_contract(vec![transaction(1, TransactionKind::Credit, 1000, 1)], 1);
+    harness.put(&state).expect("signed state validates");
+    harness
+}
+
+#[test]
+fn update_before_put_is_refused() {
+    let mut harness = Harness::<Contract>::new(&fixtures::user_contract_parameters());
+    let state = fixtures::user_contract(Vec::new(), 1);
+    assert!(matches!(harness.update(&state), Err(HarnessError::Contract(_))));
+}
+
+#[test]
+fn owner_debit_merges_and_syncs() {
+    let mut wallet = funded();
+    let mut replica = wallet.clone();
+
+    let spent = fixtures::user_contract(
+        vec![
+            transaction(1, TransactionKind::Credit, 1000, 1),
+            transaction(2, TransactionKind::Debit, 300, 2),
+        ],
+        2,
+    );
+    wallet.update(&spent).expect("owner-signed debit merges");
+    let state: UserContractState = wallet.state();
+    assert_eq!(state.ledger.len(), 2);
+    assert_eq!(state.derive_balance(), 700);
+
+    replica.sync_from(&wallet).expect("delta applies");
+    assert_eq!(replica.state::<UserContractState>().ledger, state.ledger);
+}
+
+#[test]
+fn summary_of_an_up_to_date_copy_gets_no_delta() {
+    let wallet = funded();
+    let delta = wallet.delta(&wallet.summary().unwrap()).unwrap();
+    assert!(delta.is_empty());
+}
+
+#[cfg(not(feature = "dev"))]
+#[test]
+fn debit_signed_by_someone_else_is_refused() {
+    let mut wallet = funded();
+    let before = wallet.state_bytes().to_vec();
+    let mut spent = fixtures::user_contract(
+        vec![
+            transaction(1, TransactionKind::Credit, 1000, 1),
+            transaction(2, TransactionKind::Debit, 1000, 2),
+        ],
+        2,
+    );
+    fixtures::sign_user_contract(&mut spent, &fixtures::stranger());
+    assert!(wallet.update(&spent).is_err());
+    assert_eq!(wallet.state_bytes(), before);
+}
@@ -0,0 +1,473 @@
+//! Latency and throughput benchmark for storefront contracts on a local node cluster.
+//!
+//! Measures the latency distribution of PUT, cross-node GET, Update and
+//! subscriber notification, then ramps the Update rate on a single storefront
+//! to find the highest rate the cluster sustains. The report is printed as a
+//! table and can be written as JSON and compared against an earlier run.
+//!
+//! Exits 1 if a baseline is given and any figure regressed beyond the
+//! tolerance, 0 otherwise.
+//!
+//! Usage:
+//!   node-bench [--ports 3001,3002,3003,3004] [--samples N] [--step-secs S]
+//!              [--max-latency-ms MS] [--json PATH] [--baseline PATH] [--tolerance PCT]
+//!
+//! Expects contracts built in dev mode (`build-contracts-dev`), like the
+//! stress tests, since benchmark products are unsigned.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use cream_common::location::GeoLocation;
+use cream_common::product::ProductId;
+use cream_common::storefront::{SignedProduct, StorefrontInfo, StorefrontState};
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest, WebApi};
+use freenet_stdlib::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use cream_node_integration::{
+    connect_to_node_at, is_subscribe_success, is_update_notification, is_update_response,
+    make_dummy_product, make_dummy_user, make_storefront_contract, node_url, recv_matching,
+    timed_wait_for_get, wait_for_put,
+};
+
+const OP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Update rates tried in the throughput ramp, in updates per second.
+const RAMP_RATES: [f64; 8] = [1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0];
+/// How long to wait for outstanding acks once a ramp step stops sending.
+const RAMP_DRAIN: Duration = Duration::from_secs(10);
+
+struct Args {
+    ports: Vec<u16>,
+    samples: usize,
+    step_secs: u64,
+    max_latency: Duration,
+    json: Option<String>,
+    baseline: Option<String>,
+    tolerance: f64,
+}
+
+impl Args {
+    fn parse() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value = |flag: &str| args.windows(2).find(|w| w[0] == flag).map(|w| w[1].clone());
+        Self {
+            ports: value("--ports")
+                .map(|p| p.split(',').filter_map(|s| s.trim().parse().ok()).collect())
+                .unwrap_or_else(|| vec![3001, 3002, 3003, 3004]),
+            samples: value("--samples").and_then(|s| s.parse().ok()).unwrap_or(20),
+            step_secs: value("--step-secs").and_then(|s| s.parse().ok()).unwrap_or(10),
+            max_latency: Duration::from_millis(
+                value("--max-latency-ms").and_then(|s| s.parse().ok()).unwrap_or(2000),
+            ),
+            json: value("--json"),
+            baseline: value("--baseline"),
+            tolerance: value("--tolerance").and_then(|s| s.parse().ok()).unwrap_or(20.0) / 100.0,
+        }
+    }
+}
+
+/// Latency distribution of one operation, in milliseconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LatencyStats {
+    count: usize,
+    failures: usize,
+    min_ms: f64,
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+    mean_ms: f64,
+}
+
+impl LatencyStats {
+    fn from_samples(samples: &[Duration], failures: usize) -> Self {
+        let mut ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(|a, b| a.total_cmp(b));
+        // Nearest-rank percentile
+        let pct = |p: f64| {
+            if ms.is_empty() {
+                return 0.0;
+            }
+            let rank = ((p / 100.0) * ms.len() as f64).ceil() as usize;
+            ms[rank.clamp(1, ms.len()) - 1]
+        };
+        Self {
+            count: ms.len(),
+            failures,
+            min_ms: ms.first().copied().unwrap_or(0.0),
+            p50_ms: pct(50.0),
+            p90_ms: pct(90.0),
+            p99_ms: pct(99.0),
+            max_ms: ms.last().copied().unwrap_or(0.0),
+            mean_ms: if ms.is_empty() { 0.0 } else { ms.iter().sum::<f64>() / ms.len() as f64 },
+        }
+    }
+}
+
+/// One throughput ramp step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RampStep {
+    target_rate: f64,
+    sent: usize,
+    acked: usize,
+    p95_ack_ms: f64,
+    sustained: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Report {
+    run_at: chrono::DateTime<chrono::Utc>,
+    ports: Vec<u16>,
+    samples: usize,
+    operations: BTreeMap<String, LatencyStats>,
+    ramp: Vec<RampStep>,
+    /// Highest ramp rate with every update acknowledged within the latency bound.
+    max_sustained_rate: f64,
+}
+
+fn bench_storefront(name: &str) -> StorefrontState {
+    let (owner, _) = make_dummy_user(name);
+    StorefrontState {
+        info: StorefrontInfo {
+            owner,
+            name: format!("{name}'s Farm"),
+            description: "Benchmark storefront".into(),
+            location: GeoLocation::new(-33.87, 151.21),
+            schedule: None,
+            timezone: None,
+            phone: None,
+            email: None,
+            address: None,
+            market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
+            extra: Default::default(),
+        },
+        products: BTreeMap::new(),
+        orders: BTreeMap::new(),
+        threads: BTreeMap::new(),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
+        extra: Default::default(),
+    }
+}
+
+/// A delta adding one product, so update payloads stay the same size.
+fn product_delta(base: &StorefrontState, seq: usize) -> UpdateData<'static> {
+    let mut product: SignedProduct = make_dummy_product(&format!("Bench Milk {seq}"));
+    product.product.id = ProductId(format!("p-bench-{seq}"));
+    let mut delta = bench_storefront("unused");
+    delta.info = base.info.clone();
+    delta.products.insert(product.product.id.clone(), product);
+    UpdateData::Delta(StateDelta::from(serde_json::to_vec(&delta).unwrap()))
+}
+
+fn update_request(key: ContractKey, data: UpdateData<'static>) -> ClientRequest<'static> {
+    ClientRequest::ContractOp(ContractRequest::Update { key, data })
+}
+
+/// PUT `samples` storefronts round-robin across the nodes, then GET each from
+/// the next node along (so the GET includes propagation).
+async fn bench_put_get(
+    args: &Args,
+    run_id: &str,
+    report: &mut Report,
+) -> Vec<(ContractKey, StorefrontState, u16)> {
+    let mut apis = Vec::new();
+    for &port in &args.ports {
+        apis.push(connect_to_node_at(&node_url(port)).await);
+    }
+
+    let (mut put_samples, mut put_failures) = (Vec::new(), 0);
+    let mut storefronts = Vec::new();
+    for i in 0..args.samples {
+        let name = format!("Bench{run_id}x{i}");
+        let (_, vk) = make_dummy_user(&name);
+        let (contract, key) = make_storefront_contract(&vk);
+        let state = bench_storefront(&name);
+        let idx = i % args.ports.len();
+        let bytes = serde_json::to_vec(&state).unwrap();
+
+        let start = Instant::now();
+        match wait_for_put(&mut apis[idx], contract, WrappedState::new(bytes), OP_TIMEOUT).await {
+            Some(_) => {
+                put_samples.push(start.elapsed());
+                storefronts.push((key, state, args.ports[idx]));
+            }
+            None => put_failures += 1,
+        }
+    }
+    report
+        .operations
+        .insert("put".into(), LatencyStats::from_samples(&put_samples, put_failures));
+
+    let (mut get_samples, mut get_failures) = (Vec::new(), 0);
+    for (i, (key, _, _)) in storefronts.iter().enumerate() {
+        let idx = (i % args.ports.len() + 1) % args.ports.len();
+        match timed_wait_for_get(&mut apis[idx], *key.id(), OP_TIMEOUT).await {
+            Some((_, latency)) => get_samples.push(latency),
+            None => get_failures += 1,
+        }
+    }
+    report
+        .operations
+        .insert("get_remote".into(), LatencyStats::from_samples(&get_samples, get_failures));
+
+    storefronts
+}
+
+/// Sequential updates on one storefront, timing the owner's UpdateResponse and
+/// the notification at a subscriber on another node.
+async fn bench_update_notify(
+    args: &Args,
+    target: &(ContractKey, StorefrontState, u16),
+    report: &mut Report,
+) {
+    let (key, state, port) = target;
+    let sub_port = args.ports.iter().copied().find(|p| p != port).unwrap_or(*port);
+    let mut writer = connect_to_node_at(&node_url(*port)).await;
+    let mut subscriber = connect_to_node_at(&node_url(sub_port)).await;
+
+    subscriber
+        .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
+            key: *key.id(),
+            summary: None,
+        }))
+        .await
+        .unwrap();
+    if recv_matching(&mut subscriber, is_subscribe_success, OP_TIMEOUT).await.is_none() {
+        eprintln!("WARNING: subscriber on port {sub_port} could not subscribe; skipping notification latency");
+    }
+
+    let (mut update_samples, mut update_failures) = (Vec::new(), 0);
+    let (mut notify_samples, mut notify_failures) = (Vec::new(), 0);
+    for seq in 0..args.samples {
+        let start = Instant::now();
+        writer.send(update_request(*key, product_delta(state, seq))).await.unwrap();
+
+        match recv_matching(&mut writer, is_update_response, OP_TIMEOUT).await {
+            Some(_) => update_samples.push(start.elapsed()),
+            None => update_failures += 1,
+        }
+        match recv_matching(&mut subscriber, is_update_notification, OP_TIMEOUT).await {
+            Some(_) => notify_samples.push(start.elapsed()),
+            None => notify_failures += 1,
+        }
+    }
+    report
+        .operations
+        .insert("update".into(), LatencyStats::from_samples(&update_samples, update_failures));
+    report
+        .operations
+        .insert("notification".into(), LatencyStats::from_samples(&notify_samples, notify_failures));
+}
+
+/// Send updates at `rate` per second for `duration`, draining acks in between.
+/// Acks are matched to sends in order.
+async fn ramp_step(
+    api: &mut WebApi,
+    target: &(ContractKey, StorefrontState, u16),
+    rate: f64,
+    duration: Duration,
+    max_latency: Duration,
+    seq: &mut usize,
+) -> RampStep {
+    let (key, state, _) = target;
+    let interval = Duration::from_secs_f64(1.0 / rate);
+    let stop_sending = Instant::now() + duration;
+    let mut next_send = Instant::now();
+    let mut in_flight: VecDeque<Instant> = VecDeque::new();
+    let mut ack_latencies = Vec::new();
+    let mut sent = 0;
+
+    loop {
+        let now = Instant::now();
+        if now < stop_sending && now >= next_send {
+            api.send(update_request(*key, product_delta(state, 10_000 + *seq))).await.unwrap();
+            *seq += 1;
+            sent += 1;
+            in_flight.push_back(now);
+            next_send += interval;
+            continue;
+        }
+        if now >= stop_sending && in_flight.is_empty() {
+            break;
+        }
+        let wait_until = if now < stop_sending { next_send } else { stop_sending + RAMP_DRAIN };
+        let wait = wait_until.saturating_duration_since(now);
+        if now >= stop_sending && wait.is_zero() {
+            break;
+        }
+        match tokio::time::timeout(wait, api.recv()).await {
+            Ok(Ok(resp)) if is_update_response(&resp) => {
+                if let Some(sent_at) = in_flight.pop_front() {
+                    ack_latencies.push(sent_at.elapsed());
+                }
+            }
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => {
+                eprintln!("WARNING: node error during ramp at {rate}/s: {e:?}");
+                break;
+            }
+            Err(_) => {}
+        }
+    }
+
+    let mut sorted = ack_latencies.clone();
+    sorted.sort();
+    let p95 = sorted
+        .get(((sorted.len() as f64 * 0.95).ceil() as usize).saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    let acked = ack_latencies.len();
+    RampStep {
+        target_rate: rate,
+        sent,
+        acked,
+        p95_ack_ms: p95.as_secs_f64() * 1000.0,
+        sustained: sent > 0 && acked == sent && p95 <= max_latency,
+    }
+}
+
+async fn bench_ramp(args: &Args, target: &(ContractKey, StorefrontState, u16), report: &mut Report) {
+    let mut api = connect_to_node_at(&node_url(target.2)).await;
+    let mut seq = 0;
+    for rate in RAMP_RATES {
+        let step = ramp_step(
+            &mut api,
+            target,
+            rate,
+            Duration::from_secs(args.step_secs),
+            args.max_latency,
+            &mut seq,
+        )
+        .await;
+        println!(
+            "  ramp {:>6.1}/s: sent={:<5} acked={:<5} p95={:>8.1}ms {}",
+            step.target_rate,
+            step.sent,
+            step.acked,
+            step.p95_ack_ms,
+            if step.sustained { "ok" } else { "NOT SUSTAINED" }
+        );
+        let sustained = step.sustained;
+        report.ramp.push(step);
+        if !sustained {
+            break;
+        }
+        report.max_sustained_rate = rate;
+    }
+}
+
+fn print_report(report: &Report) {
+    println!();
+    println!(
+        "── node-bench {} | ports {:?} | {} samples ──",
+        report.run_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        report.ports,
+        report.samples
+    );
+    println!(
+        "{:<14} {:>6} {:>5} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}",
+        "operation", "count", "fail", "min", "p50", "p90", "p99", "max", "mean"
+    );
+    for (op, s) in &report.operations {
+        println!(
+            "{:<14} {:>6} {:>5} {:>9.1} {:>9.1} {:>9.1} {:>9.1} {:>9.1} {:>9.1}",
+            op, s.count, s.failures, s.min_ms, s.p50_ms, s.p90_ms, s.p99_ms, s.max_ms, s.mean_ms
+        );
+    }
+    println!("max sustained update rate: {:.1}/s", report.max_sustained_rate);
+}
+
+/// Compare against a baseline report. Returns the regressions found.
+fn compare(report: &Report, baseline: &Report, tolerance: f64) -> Vec<String> {
+    let mut regressions = Vec::new();
+    for (op, now) in &report.operations {
+        let Some(base) = baseline.operations.get(op) else {
+            continue;
+        };
+        for (label, now_ms, base_ms) in [("p50", now.p50_ms, base.p50_ms), ("p99", now.p99_ms, base.p99_ms)] {
+            if base_ms > 0.0 && now_ms > base_ms * (1.0 + tolerance) {
+                regressions.push(format!("{op} {label}: {base_ms:.1}ms -> {now_ms:.1}ms"));
+            }
+        }
+        if now.failures > base.failures {
+            regressions.push(format!("{op} failures: {} -> {}", base.failures, now.failures));
+        }
+    }
+    if report.max_sustained_rate < baseline.max_sustained_rate * (1.0 - tolerance) {
+        regressions.push(format!(
+            "max sustained rate: {:.1}/s -> {:.1}/s",
+            baseline.max_sustained_rate, report.max_sustained_rate
+        ));
+    }
+    regressions
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::try_init().ok();
+    let args = Args::parse();
+    if args.ports.is_empty() || args.samples == 0 {
+        eprintln!("Need at least one port and one sample");
+        std::process::exit(2);
+    }
+
+    let run_at = chrono::Utc::now();
+    let run_id = run_at.timestamp_millis().to_string();
+    let mut report = Report {
+        run_at,
+        ports: args.ports.clone(),
+        samples: args.samples,
+        operations: BTreeMap::new(),
+        ramp: Vec::new(),
+        max_sustained_rate: 0.0,
+    };
+
+    println!("PUT/GET latency ({} storefronts)...", args.samples);
+    let storefronts = bench_put_get(&args, &run_id, &mut report).await;
+    let Some(target) = storefronts.first() else {
+        eprintln!("No storefront could be PUT; is the network running?");
+        std::process::exit(1);
+    };
+
+    println!("Update/notification latency...");
+    bench_update_notify(&args, target, &mut report).await;
+
+    println!("Update rate ramp ({}s per step)...", args.step_secs);
+    bench_ramp(&args, target, &mut report).await;
+
+    print_report(&report);
+
+    if let Some(path) = &args.json {
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        std::fs::write(path, json).unwrap_or_else(|e| panic!("Failed to write {path}: {e}"));
+        println!("Report written to {path}");
+    }
+
+    if let Some(path) = &args.baseline {
+        let baseline: Report = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_else(|| panic!("Failed to read baseline report {path}"));
+        let regressions = compare(&report, &baseline, args.tolerance);
+        if regressions.is_empty() {
+            println!("No regressions against {path} (tolerance {:.0}%)", args.tolerance * 100.0);
+        } else {
+            println!("REGRESSIONS against {path} (tolerance {:.0}%):", args.tolerance * 100.0);
+            for r in &regressions {
+                println!("  {r}");
+            }
+            std::process::exit(1);
+        }
+    }
+}
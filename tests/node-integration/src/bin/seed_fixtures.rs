@@ -0,0 +1,112 @@
+//! Seed a node cluster (or a JSON file) with generated fixtures.
+//!
+//! PUTs one storefront per generated supplier, then registers the suppliers
+//! in their directory shards. The same seed always produces the same
+//! suppliers, so a demo environment can be rebuilt identically. Suppliers
+//! and customers log into the UI with their name as the password.
+//!
+//! Usage:
+//!   seed-fixtures [--seed N] [--suppliers N] [--products M] [--orders K]
+//!                 [--customers C] [--port PORT] [--json PATH]
+//!
+//! With --json the fixtures are written to PATH and nothing is sent.
+//! --port: Freenet node WebSocket port (default: 3002).
+//!
+//! Expects contracts built in dev mode (`build-contracts-dev`), since
+//! fixture products and orders are unsigned.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use cream_common::directory::{DirectoryShard, DirectoryState};
+use freenet_stdlib::prelude::*;
+
+use cream_node_integration::fixtures::{generate, FixtureSpec};
+use cream_node_integration::{
+    connect_to_node_at, make_directory_shard_contract, make_storefront_contract, node_url,
+    wait_for_put,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let value = |flag: &str| args.windows(2).find(|w| w[0] == flag).map(|w| w[1].clone());
+    let number = |flag: &str, default: usize| {
+        value(flag).and_then(|v| v.parse().ok()).unwrap_or(default)
+    };
+
+    let mut spec = FixtureSpec::new(
+        value("--seed").and_then(|v| v.parse().ok()).unwrap_or(1),
+        number("--suppliers", 20),
+        number("--products", 5),
+        number("--orders", 3),
+    );
+    spec.customers = number("--customers", spec.customers);
+    let fixtures = generate(&spec);
+
+    if let Some(path) = value("--json") {
+        let json = serde_json::to_string_pretty(&fixtures).unwrap();
+        std::fs::write(&path, json).unwrap_or_else(|e| panic!("write {path}: {e}"));
+        println!(
+            "Wrote {} suppliers and {} customers to {path}",
+            fixtures.suppliers.len(),
+            fixtures.customers.len()
+        );
+        return;
+    }
+
+    let port = value("--port").and_then(|v| v.parse().ok()).unwrap_or(3002);
+    let mut api = connect_to_node_at(&node_url(port)).await;
+    let mut failed = 0;
+
+    let mut shards: BTreeMap<&'static str, (DirectoryShard, DirectoryState)> = BTreeMap::new();
+    for supplier in &fixtures.suppliers {
+        let (contract, _) = make_storefront_contract(&supplier.verifying_key);
+        let state = serde_json::to_vec(&supplier.storefront).unwrap();
+        if wait_for_put(&mut api, contract, WrappedState::new(state), TIMEOUT)
+            .await
+            .is_none()
+        {
+            println!("  [FAIL] storefront for {}", supplier.name);
+            failed += 1;
+            continue;
+        }
+        println!(
+            "  [OK] {} ({} products, {} orders)",
+            supplier.name,
+            supplier.storefront.products.len(),
+            supplier.storefront.orders.len()
+        );
+
+        let Some(shard) = supplier.shard() else { continue };
+        shards
+            .entry(shard.label())
+            .or_insert_with(|| (shard, DirectoryState::default()))
+            .1
+            .entries
+            .insert(supplier.id.clone(), supplier.directory_entry());
+    }
+
+    // PUT merges into an existing shard, so re-seeding is harmless
+    for (label, (shard, state)) in shards {
+        let (contract, _) = make_directory_shard_contract(shard);
+        let count = state.entries.len();
+        let bytes = serde_json::to_vec(&state).unwrap();
+        if wait_for_put(&mut api, contract, WrappedState::new(bytes), TIMEOUT)
+            .await
+            .is_none()
+        {
+            println!("  [FAIL] directory shard {label}");
+            failed += 1;
+            continue;
+        }
+        println!("  [OK] {count} suppliers registered in {label}");
+    }
+
+    if failed > 0 {
+        println!("{failed} operation(s) failed");
+        std::process::exit(1);
+    }
+}
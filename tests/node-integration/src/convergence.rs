@@ -0,0 +1,292 @@
+//! Assertions for Freenet's eventual consistency.
+//!
+//! A write acknowledged on one node may not be visible on another — or even
+//! to a GET on the same connection — for a while. Rather than each step
+//! hand-rolling a retry loop, tests poll with these:
+//!
+//! - [`eventually!`](crate::eventually) re-evaluates any (async) condition
+//!   every `interval` until it holds or `timeout` passes.
+//! - [`converge`] polls GETs of one contract until chosen fields of the
+//!   parsed state reach their expected values ([`Expect`]), and on failure
+//!   reports each field that didn't, with what it was stuck at.
+//! - [`replicas_converge`] polls the same contract on several nodes until the
+//!   parsed states agree, and on failure lists the JSON paths that differ.
+
+use std::fmt::Debug;
+use std::time::Duration;
+
+use freenet_stdlib::client_api::WebApi;
+use freenet_stdlib::prelude::ContractInstanceId;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tokio::time::Instant;
+
+use crate::{connect_to_node_at, wait_for_get};
+
+/// Differences listed in a failure message before the rest are elided.
+const MAX_REPORTED_DIFFS: usize = 20;
+
+/// Re-evaluate `$cond` every `$interval` until it is true, panicking once
+/// `$timeout` has passed. `$cond` may `.await`. An optional format string
+/// and arguments describe what was awaited.
+///
+/// ```ignore
+/// eventually!(inbox_len(&mut probe).await == 1, Duration::from_secs(30), Duration::from_secs(1));
+/// ```
+#[macro_export]
+macro_rules! eventually {
+    ($cond:expr, $timeout:expr, $interval:expr $(,)?) => {
+        $crate::eventually!($cond, $timeout, $interval, "{}", stringify!($cond))
+    };
+    ($cond:expr, $timeout:expr, $interval:expr, $($msg:tt)+) => {{
+        let timeout: ::std::time::Duration = $timeout;
+        let interval: ::std::time::Duration = $interval;
+        let started = ::tokio::time::Instant::now();
+        let mut attempts = 0u32;
+        loop {
+            attempts += 1;
+            if $cond {
+                break;
+            }
+            if started.elapsed() >= timeout {
+                panic!(
+                    "not eventually true after {:?} ({} attempts): {}",
+                    started.elapsed(),
+                    attempts,
+                    format!($($msg)+),
+                );
+            }
+            ::tokio::time::sleep(interval).await;
+        }
+    }};
+}
+
+/// How long to keep polling, and how often.
+#[derive(Clone, Copy, Debug)]
+pub struct Poll {
+    pub timeout: Duration,
+    pub interval: Duration,
+}
+
+impl Poll {
+    pub fn new(timeout: Duration, interval: Duration) -> Self {
+        Self { timeout, interval }
+    }
+}
+
+impl Default for Poll {
+    /// 30s, every second.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), Duration::from_secs(1))
+    }
+}
+
+type Check<T> = Box<dyn Fn(&T) -> Option<String> + Send + Sync>;
+
+/// The fields a contract state has to reach, each with a label for the
+/// failure report.
+pub struct Expect<T> {
+    checks: Vec<(String, Check<T>)>,
+}
+
+impl<T> Default for Expect<T> {
+    fn default() -> Self {
+        Self { checks: Vec::new() }
+    }
+}
+
+impl<T> Expect<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `get(state)` must equal `want`.
+    pub fn field<V>(mut self, label: &str, want: V, get: impl Fn(&T) -> V + Send + Sync + 'static) -> Self
+    where
+        V: PartialEq + Debug + Send + Sync + 'static,
+    {
+        self.checks.push((
+            label.to_string(),
+            Box::new(move |state| {
+                let got = get(state);
+                (got != want).then(|| format!("want {want:?}, got {got:?}"))
+            }),
+        ));
+        self
+    }
+
+    /// `holds(state)` must be true; `describe(state)` says what it was instead.
+    pub fn that(
+        mut self,
+        label: &str,
+        holds: impl Fn(&T) -> bool + Send + Sync + 'static,
+        describe: impl Fn(&T) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.checks.push((
+            label.to_string(),
+            Box::new(move |state| (!holds(state)).then(|| describe(state))),
+        ));
+        self
+    }
+
+    /// The fields `state` hasn't reached yet, as "label: want …, got …".
+    pub fn mismatches(&self, state: &T) -> Vec<String> {
+        self.checks
+            .iter()
+            .filter_map(|(label, check)| check(state).map(|diff| format!("{label}: {diff}")))
+            .collect()
+    }
+}
+
+/// GET and parse contract `id` through `api`.
+pub async fn get_state<T: DeserializeOwned>(api: &mut WebApi, id: ContractInstanceId, timeout: Duration) -> Option<T> {
+    let bytes = wait_for_get(api, id, timeout).await?;
+    match serde_json::from_slice(&bytes) {
+        Ok(state) => Some(state),
+        Err(e) => {
+            tracing::warn!("get_state: {id} didn't parse: {e}");
+            None
+        }
+    }
+}
+
+/// Poll GETs of contract `id` through `api` until `expect` holds, returning
+/// the converged state. Panics with `what` and the fields that never
+/// converged (from the last state seen) once `poll.timeout` has passed.
+pub async fn converge<T: DeserializeOwned>(
+    api: &mut WebApi,
+    id: ContractInstanceId,
+    what: &str,
+    expect: &Expect<T>,
+    poll: Poll,
+) -> T {
+    let started = Instant::now();
+    let mut attempts = 0u32;
+    loop {
+        attempts += 1;
+        let remaining = poll.timeout.saturating_sub(started.elapsed()).max(Duration::from_secs(1));
+        let mismatches = match get_state::<T>(api, id, remaining).await {
+            Some(state) => {
+                let mismatches = expect.mismatches(&state);
+                if mismatches.is_empty() {
+                    return state;
+                }
+                mismatches
+            }
+            None => vec!["GET failed".to_string()],
+        };
+        if started.elapsed() >= poll.timeout {
+            panic!(
+                "{what} did not converge after {:?} ({attempts} attempts):\n  {}",
+                started.elapsed(),
+                mismatches.join("\n  "),
+            );
+        }
+        tracing::info!("{what}: not converged yet (attempt {attempts}): {}", mismatches.join("; "));
+        tokio::time::sleep(poll.interval).await;
+    }
+}
+
+/// Poll contract `id` on every node in `urls` until all parse to the same
+/// state, returning it. Panics with the differing JSON paths once
+/// `poll.timeout` has passed.
+pub async fn replicas_converge<T: DeserializeOwned>(
+    urls: &[String],
+    id: ContractInstanceId,
+    what: &str,
+    poll: Poll,
+) -> T {
+    assert!(!urls.is_empty(), "replicas_converge needs at least one node");
+    let mut apis = Vec::with_capacity(urls.len());
+    for url in urls {
+        apis.push(connect_to_node_at(url).await);
+    }
+    let started = Instant::now();
+    let mut attempts = 0u32;
+    loop {
+        attempts += 1;
+        let remaining = poll.timeout.saturating_sub(started.elapsed()).max(Duration::from_secs(1));
+        let mut states = Vec::with_capacity(apis.len());
+        for api in &mut apis {
+            states.push(get_state::<Value>(api, id, remaining).await);
+        }
+        let mut diffs = Vec::new();
+        match states.first().cloned().flatten() {
+            Some(first) => {
+                for (url, state) in urls.iter().zip(&states).skip(1) {
+                    match state {
+                        Some(state) => diffs.extend(
+                            json_diff(&first, state).into_iter().map(|d| format!("{url}: {d}")),
+                        ),
+                        None => diffs.push(format!("{url}: GET failed")),
+                    }
+                }
+                if diffs.is_empty() {
+                    return serde_json::from_value(first)
+                        .unwrap_or_else(|e| panic!("{what}: converged state doesn't parse: {e}"));
+                }
+            }
+            None => diffs.push(format!("{}: GET failed", urls[0])),
+        }
+        if started.elapsed() >= poll.timeout {
+            let elided = diffs.len().saturating_sub(MAX_REPORTED_DIFFS);
+            diffs.truncate(MAX_REPORTED_DIFFS);
+            if elided > 0 {
+                diffs.push(format!("… and {elided} more"));
+            }
+            panic!(
+                "{what} replicas did not converge after {:?} ({attempts} attempts), compared with {}:\n  {}",
+                started.elapsed(),
+                urls[0],
+                diffs.join("\n  "),
+            );
+        }
+        tracing::info!("{what}: replicas differ (attempt {attempts}): {} path(s)", diffs.len());
+        tokio::time::sleep(poll.interval).await;
+    }
+}
+
+/// The JSON paths at which `b` differs from `a`, as "path: a ≠ b".
+pub fn json_diff(a: &Value, b: &Value) -> Vec<String> {
+    let mut diffs = Vec::new();
+    diff_at("$", a, b, &mut diffs);
+    diffs
+}
+
+fn diff_at(path: &str, a: &Value, b: &Value, diffs: &mut Vec<String>) {
+    match (a, b) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (key, a_val) in a {
+                match b.get(key) {
+                    Some(b_val) => diff_at(&format!("{path}.{key}"), a_val, b_val, diffs),
+                    None => diffs.push(format!("{path}.{key}: {} ≠ (missing)", brief(a_val))),
+                }
+            }
+            for (key, b_val) in b {
+                if !a.contains_key(key) {
+                    diffs.push(format!("{path}.{key}: (missing) ≠ {}", brief(b_val)));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                diffs.push(format!("{path}: {} items ≠ {} items", a.len(), b.len()));
+            }
+            for (idx, (a_val, b_val)) in a.iter().zip(b).enumerate() {
+                diff_at(&format!("{path}[{idx}]"), a_val, b_val, diffs);
+            }
+        }
+        (a, b) if a != b => diffs.push(format!("{path}: {} ≠ {}", brief(a), brief(b))),
+        _ => {}
+    }
+}
+
+/// A value for a failure message, cut short if long.
+fn brief(value: &Value) -> String {
+    let text = value.to_string();
+    if text.chars().count() > 60 {
+        format!("{}…", text.chars().take(60).collect::<String>())
+    } else {
+        text
+    }
+}
@@ -0,0 +1,320 @@
+//! Seeded fixture generation at scale.
+//!
+//! [`generate`] builds N suppliers with M products and K orders each, plus a
+//! pool of customers to place those orders. Everything — names, postcodes,
+//! prices, quantities, timestamps — is derived from [`FixtureSpec::seed`], so
+//! the same spec always yields the same fixtures (for a given `rand` version).
+//! Identities come from [`crate::make_dummy_user`], so a generated supplier
+//! can log into the UI with their name as the password.
+//!
+//! Used by the stress tests and by the `seed-fixtures` binary, which loads
+//! the fixtures into a demo cluster.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use ed25519_dalek::VerifyingKey;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use cream_common::directory::{DirectoryEntry, DirectoryShard};
+use cream_common::identity::UserId;
+use cream_common::location::GeoLocation;
+use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
+use cream_common::postcode::{lookup_postcode_info, timezone_for_postcode};
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::storefront::{SignedProduct, StorefrontInfo, StorefrontState, WeeklySchedule};
+
+use crate::{make_directory_entry, make_dummy_user, make_storefront_contract};
+
+const FIRST_NAMES: &[&str] = &[
+    "Ada", "Bram", "Cora", "Dale", "Edie", "Finn", "Greta", "Hugh", "Ivy", "Jonah", "Kit",
+    "Lena", "Milo", "Nell", "Otis", "Pia", "Quinn", "Rosa", "Saul", "Tess", "Uma", "Vern",
+    "Wren", "Yara",
+];
+
+const PLACE_NAMES: &[&str] = &[
+    "Creek", "Hollow", "Ridge", "Meadow", "Valley", "Brook", "Glen", "Downs", "Flat", "Springs",
+];
+
+/// Postcodes suppliers are spread over, covering every directory shard.
+const POSTCODES: &[&str] = &[
+    "2450", "2000", "2480", "2340", "2650", "2800", "2600", "3000", "3350", "3550", "4000",
+    "4350", "4870", "5000", "5290", "6000", "6230", "7000", "7250", "0810",
+];
+
+/// Product name and a constructor for its category.
+type ProductTemplate = (&'static str, fn() -> ProductCategory);
+
+const PRODUCTS: &[ProductTemplate] = &[
+    ("Raw Milk", || ProductCategory::Milk),
+    ("A2 Milk", || ProductCategory::Milk),
+    ("Aged Cheddar", || ProductCategory::Cheese),
+    ("Fresh Ricotta", || ProductCategory::Cheese),
+    ("Cultured Butter", || ProductCategory::Butter),
+    ("Double Cream", || ProductCategory::Cream),
+    ("Greek Yogurt", || ProductCategory::Yogurt),
+    ("Milk Kefir", || ProductCategory::Kefir),
+    ("Ghee", || ProductCategory::Other("Ghee".into())),
+];
+
+const TIERS: [DepositTier; 3] = [
+    DepositTier::Reserve2Days,
+    DepositTier::Reserve1Week,
+    DepositTier::FullPayment,
+];
+
+/// What to generate.
+#[derive(Debug, Clone)]
+pub struct FixtureSpec {
+    pub seed: u64,
+    pub suppliers: usize,
+    pub products_per_supplier: usize,
+    pub orders_per_supplier: usize,
+    /// Customers placing the orders (orders are spread across them).
+    pub customers: usize,
+    /// "Now" for generated timestamps: products and orders are dated in the
+    /// days before it. Fixed by default so output doesn't depend on the clock.
+    pub epoch: DateTime<Utc>,
+}
+
+impl FixtureSpec {
+    pub fn new(seed: u64, suppliers: usize, products_per_supplier: usize, orders_per_supplier: usize) -> Self {
+        Self {
+            seed,
+            suppliers,
+            products_per_supplier,
+            orders_per_supplier,
+            customers: 10,
+            epoch: Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+}
+
+/// A generated supplier and their initial storefront.
+#[derive(Debug, Clone, Serialize)]
+pub struct SupplierFixture {
+    pub name: String,
+    pub id: UserId,
+    pub verifying_key: VerifyingKey,
+    pub postcode: String,
+    pub locality: String,
+    pub storefront: StorefrontState,
+}
+
+impl SupplierFixture {
+    /// Directory entry pointing at this supplier's storefront contract.
+    pub fn directory_entry(&self) -> DirectoryEntry {
+        let (_, sf_key) = make_storefront_contract(&self.verifying_key);
+        make_directory_entry(
+            &self.id,
+            &self.name,
+            &self.storefront.info.description,
+            &self.postcode,
+            &self.locality,
+            self.storefront.info.location.clone(),
+            sf_key,
+            None,
+            None,
+        )
+    }
+
+    /// Directory shard this supplier registers in.
+    pub fn shard(&self) -> Option<DirectoryShard> {
+        DirectoryShard::for_postcode(&self.postcode)
+    }
+}
+
+/// A generated customer.
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomerFixture {
+    pub name: String,
+    pub id: UserId,
+    pub verifying_key: VerifyingKey,
+}
+
+/// Output of [`generate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Fixtures {
+    pub suppliers: Vec<SupplierFixture>,
+    pub customers: Vec<CustomerFixture>,
+}
+
+/// Generate fixtures for `spec`. Deterministic in the spec.
+pub fn generate(spec: &FixtureSpec) -> Fixtures {
+    let mut rng = StdRng::seed_from_u64(spec.seed);
+    let mut taken = BTreeSet::new();
+
+    let customers: Vec<CustomerFixture> = (0..spec.customers.max(1))
+        .map(|_| {
+            let name = unique_name(&mut rng, &mut taken, "");
+            let (id, verifying_key) = make_dummy_user(&name);
+            CustomerFixture { name, id, verifying_key }
+        })
+        .collect();
+
+    let suppliers = (0..spec.suppliers)
+        .map(|_| {
+            let name = unique_name(&mut rng, &mut taken, " Farm");
+            supplier(&mut rng, spec, name, &customers)
+        })
+        .collect();
+
+    Fixtures { suppliers, customers }
+}
+
+/// "First Place" plus `suffix`, numbered if the combination is already taken.
+fn unique_name(rng: &mut StdRng, taken: &mut BTreeSet<String>, suffix: &str) -> String {
+    let first = FIRST_NAMES.choose(rng).unwrap();
+    let place = PLACE_NAMES.choose(rng).unwrap();
+    let base = format!("{first} {place}{suffix}");
+    let name = (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{base} {n}") })
+        .find(|n| !taken.contains(n))
+        .unwrap();
+    taken.insert(name.clone());
+    name
+}
+
+fn supplier(rng: &mut StdRng, spec: &FixtureSpec, name: String, customers: &[CustomerFixture]) -> SupplierFixture {
+    let (id, verifying_key) = make_dummy_user(&name);
+    let slug = name.to_lowercase().replace(' ', "-");
+
+    let postcode = POSTCODES.choose(rng).unwrap().to_string();
+    let info = lookup_postcode_info(&postcode).expect("fixture postcodes are in the dataset");
+    // Scatter suppliers around the postcode centre (roughly ±5km)
+    let location = GeoLocation::new(
+        info.location.latitude + rng.gen_range(-0.05..0.05),
+        info.location.longitude + rng.gen_range(-0.05..0.05),
+    );
+
+    let mut schedule = WeeklySchedule::new();
+    let open = rng.gen_range(12..20u8); // 6:00–9:30
+    let close = rng.gen_range(30..40u8); // 15:00–19:30
+    for day in 0..6u8 {
+        schedule.set_range(day, open, close, true);
+    }
+
+    let mut products = BTreeMap::new();
+    for j in 0..spec.products_per_supplier {
+        let (product_name, category) = PRODUCTS.choose(rng).unwrap();
+        let created_at = spec.epoch - Duration::days(rng.gen_range(1..60));
+        let product = Product {
+            id: ProductId(format!("p-{slug}-{j}")),
+            name: product_name.to_string(),
+            description: format!("Fresh {product_name} from {name}"),
+            category: category(),
+            subcategory: None,
+            attributes: Default::default(),
+            price_curd: rng.gen_range(20..200) * 10,
+            quantity_total: rng.gen_range(10..50),
+            expiry_date: None,
+            updated_at: created_at,
+            created_at,
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
+        };
+        products.insert(
+            product.id.clone(),
+            SignedProduct {
+                product,
+                signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                provenance: None,
+                price_history: Vec::new(),
+                extra: Default::default(),
+            },
+        );
+    }
+
+    // Orders never reserve more than a product has in stock
+    let mut remaining: BTreeMap<ProductId, u32> = products
+        .values()
+        .map(|p| (p.product.id.clone(), p.product.quantity_total))
+        .collect();
+    let mut orders = BTreeMap::new();
+    for k in 0..spec.orders_per_supplier {
+        let in_stock: Vec<&SignedProduct> = products
+            .values()
+            .filter(|p| remaining[&p.product.id] > 0)
+            .collect();
+        let Some(product) = in_stock.choose(rng) else { break };
+        let product = &product.product;
+        let quantity = rng.gen_range(1..=remaining[&product.id].min(3));
+        *remaining.get_mut(&product.id).unwrap() -= quantity;
+
+        let tier = *TIERS.choose(rng).unwrap();
+        let created_at = spec.epoch - Duration::hours(rng.gen_range(1..48));
+        let expires_at = created_at + tier.hold_period();
+        let total_price = product.price_curd * quantity as u64;
+        let order = Order {
+            id: OrderId(format!("o-{slug}-{k}")),
+            product_id: product.id.clone(),
+            customer: customers.choose(rng).unwrap().id.clone(),
+            quantity,
+            deposit_amount: tier.calculate_deposit(total_price),
+            deposit_tier: tier,
+            total_price,
+            status: OrderStatus::Reserved { expires_at },
+            created_at,
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            escrow_token: None,
+            collection_point: None,
+            delivery: None,
+            provenance: None,
+            receipt: None,
+            amendments: Vec::new(),
+            status_history: Vec::new(),
+            cancellation_policy: None,
+            refund_amount: None,
+            balance_attestation: None,
+            nonce: 0,
+            extra: Default::default(),
+        };
+        orders.insert(order.id.clone(), order);
+    }
+
+    let storefront = StorefrontState {
+        info: StorefrontInfo {
+            owner: id.clone(),
+            name: name.clone(),
+            description: format!("{name} — dairy from {}", info.place_name),
+            location,
+            schedule: Some(schedule),
+            timezone: timezone_for_postcode(&postcode),
+            phone: None,
+            email: None,
+            address: None,
+            market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
+            extra: Default::default(),
+        },
+        products,
+        orders,
+        threads: BTreeMap::new(),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
+        extra: Default::default(),
+    };
+
+    SupplierFixture {
+        name,
+        id,
+        verifying_key,
+        postcode,
+        locality: info.place_name,
+        storefront,
+    }
+}
+
@@ -1,338 +1,39 @@
+//! The fixed cast the node integration tests run with.
+//!
+//! The participants themselves live in [`cream_sim::actors`]; they're
+//! re-exported here so tests can keep naming them through the harness.
+
 use std::collections::BTreeMap;
+use std::sync::Arc;
 use std::time::Duration;
 
-use ed25519_dalek::VerifyingKey;
-use freenet_stdlib::client_api::{ClientRequest, ContractRequest, WebApi};
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest};
 use freenet_stdlib::prelude::*;
 
-use cream_common::directory::DirectoryState;
-use cream_common::identity::UserId;
+use cream_common::clock::MockClock;
+use cream_common::directory::{DirectoryShard, DirectoryState};
 use cream_common::location::GeoLocation;
-use cream_common::order::Order;
-use cream_common::product::{Product, ProductCategory, ProductId};
-use cream_common::storefront::{SignedProduct, StorefrontInfo, StorefrontState, WeeklySchedule};
+use cream_common::storefront::WeeklySchedule;
 
-use cream_common::user_contract::UserContractState;
-use cream_common::wallet::{TransactionKind, WalletTransaction};
+pub use cream_sim::actors::{
+    user_contract_key_for, Customer, Rendezvous, RendezvousEntry, Supplier, SupplierLink,
+    SYSTEM_FLOAT,
+};
+pub use cream_sim::invariants::check_curd_conservation;
 
+use cream_sim::actors::{
+    deploy_inbox, deploy_inbox_customer, deploy_root_contract, deploy_supplier_user_contract,
+    deploy_user_contract, make_initial_storefront, put_storefront, register_supplier_in_directory,
+};
 use crate::{
-    connect_to_node_at, extract_get_response_state, extract_notification_bytes, is_get_response,
-    is_put_response, is_subscribe_success, is_update_notification, is_update_response,
-    make_directory_contract, make_directory_entry, make_dummy_market_entry, make_dummy_user,
-    make_inbox_contract, make_market_directory_contract, make_storefront_contract,
-    make_user_contract, node_url, recv_matching, wait_for_get, wait_for_put,
+    assert_embedded_contracts, connect_to_node_at, is_put_response, is_update_response,
+    make_directory_shard_contract, make_dummy_market_entry, make_dummy_user,
+    make_market_directory_contract, make_storefront_contract, node_url, recv_matching,
+    wait_for_get,
 };
 
 const TIMEOUT: Duration = Duration::from_secs(60);
 
-/// A supplier participant in the test harness.
-pub struct Supplier {
-    pub name: String,
-    pub id: UserId,
-    pub verifying_key: VerifyingKey,
-    pub api: WebApi,
-    pub storefront_key: ContractKey,
-    pub storefront: StorefrontState,
-    pub postcode: String,
-    pub locality: String,
-    /// Contract key for this supplier's user contract.
-    pub user_contract_key: Option<ContractKey>,
-    /// Contract key for this supplier's inbox contract.
-    pub inbox_key: Option<ContractKey>,
-}
-
-impl Supplier {
-    /// Add a product to this supplier's storefront, send the update, and wait for confirmation.
-    pub async fn add_product(
-        &mut self,
-        name: &str,
-        category: ProductCategory,
-        price_curd: u64,
-    ) -> &SignedProduct {
-        let now = chrono::Utc::now();
-        let product = SignedProduct {
-            product: Product {
-                id: ProductId(format!(
-                    "p-{}-{}",
-                    self.name.to_lowercase(),
-                    now.timestamp_millis()
-                )),
-                name: name.to_string(),
-                description: format!("Fresh {name}"),
-                category,
-                price_curd,
-                quantity_total: 10,
-                expiry_date: None,
-                updated_at: now,
-                created_at: now,
-                extra: Default::default(),
-            },
-            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-            extra: Default::default(),
-        };
-
-        let pid = product.product.id.clone();
-        self.storefront.products.insert(pid.clone(), product);
-
-        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Update {
-                key: self.storefront_key,
-                data: UpdateData::State(State::from(sf_bytes)),
-            }))
-            .await
-            .unwrap();
-
-        // Wait for UpdateResponse confirmation
-        recv_matching(&mut self.api, is_update_response, TIMEOUT)
-            .await
-            .expect("Expected UpdateResponse after adding product");
-
-        // Small delay so next product gets a distinct timestamp-based ID
-        tokio::time::sleep(Duration::from_millis(10)).await;
-
-        self.storefront.products.get(&pid).unwrap()
-    }
-
-    /// Add a pre-built order to this supplier's storefront, send the update, and wait for confirmation.
-    pub async fn add_order(&mut self, order: Order) {
-        let order_id = order.id.clone();
-        self.storefront.orders.insert(order_id, order);
-
-        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Update {
-                key: self.storefront_key,
-                data: UpdateData::State(State::from(sf_bytes)),
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_update_response, TIMEOUT)
-            .await
-            .expect("Expected UpdateResponse after adding order");
-    }
-
-    /// Run expire_orders on the local storefront, push update if any changed.
-    /// Returns true if any orders were expired.
-    pub async fn expire_orders(&mut self) -> bool {
-        let now = chrono::Utc::now();
-        if self.storefront.expire_orders(now) {
-            let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
-            self.api
-                .send(ClientRequest::ContractOp(ContractRequest::Update {
-                    key: self.storefront_key,
-                    data: UpdateData::State(State::from(sf_bytes)),
-                }))
-                .await
-                .unwrap();
-
-            recv_matching(&mut self.api, is_update_response, TIMEOUT)
-                .await
-                .expect("Expected UpdateResponse after expiring orders");
-            true
-        } else {
-            false
-        }
-    }
-
-    /// Update the supplier's opening hours schedule and push to the network.
-    pub async fn update_schedule(&mut self, schedule: WeeklySchedule, timezone: &str) {
-        self.storefront.info.schedule = Some(schedule);
-        self.storefront.info.timezone = Some(timezone.to_string());
-
-        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Update {
-                key: self.storefront_key,
-                data: UpdateData::State(State::from(sf_bytes)),
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_update_response, TIMEOUT)
-            .await
-            .unwrap_or_else(|| panic!("UpdateResponse for {}'s schedule", self.name));
-    }
-
-    /// Fulfill an order on this supplier's storefront (Reserved/Paid → Fulfilled).
-    pub async fn fulfill_order(&mut self, order_id: &str) {
-        use cream_common::order::OrderStatus;
-
-        let oid = cream_common::order::OrderId(order_id.to_string());
-        let order = self
-            .storefront
-            .orders
-            .get_mut(&oid)
-            .unwrap_or_else(|| panic!("Order {} not found on {}'s storefront", order_id, self.name));
-        assert!(
-            order.status.can_transition_to(&OrderStatus::Fulfilled),
-            "Cannot fulfill order {} in status {}",
-            order_id,
-            order.status
-        );
-        order.status = OrderStatus::Fulfilled;
-
-        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Update {
-                key: self.storefront_key,
-                data: UpdateData::State(State::from(sf_bytes)),
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_update_response, TIMEOUT)
-            .await
-            .expect("Expected UpdateResponse after fulfilling order");
-    }
-
-    /// Return a reference to the local storefront state copy.
-    pub fn get_storefront_state(&self) -> &StorefrontState {
-        &self.storefront
-    }
-
-    /// Subscribe to the directory contract.
-    pub async fn subscribe_to_directory(&mut self, dir_key: &ContractKey) {
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: *dir_key.id(),
-                summary: None,
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
-            .await
-            .expect("Expected SubscribeResponse for directory");
-    }
-}
-
-/// A customer participant in the test harness.
-pub struct Customer {
-    pub name: String,
-    pub id: UserId,
-    pub verifying_key: VerifyingKey,
-    pub api: WebApi,
-    /// CURD wallet balance (derived from on-network ledger).
-    pub balance: u64,
-    /// Contract key for this customer's user contract.
-    pub user_contract_key: Option<ContractKey>,
-    /// Contract key for this customer's inbox contract.
-    pub inbox_key: Option<ContractKey>,
-}
-
-impl Customer {
-    /// Place an order if balance is sufficient. Decrements balance and pushes the order
-    /// to the supplier's storefront. Returns `Err` if the customer can't afford the deposit.
-    pub async fn place_order(
-        &mut self,
-        order: Order,
-        supplier: &mut Supplier,
-    ) -> Result<(), String> {
-        let deposit = order.deposit_amount;
-        if self.balance < deposit {
-            return Err(format!(
-                "Insufficient balance: have {}, need {} deposit",
-                self.balance, deposit
-            ));
-        }
-        self.balance -= deposit;
-        supplier.add_order(order).await;
-        Ok(())
-    }
-
-    /// GET a supplier's storefront state.
-    pub async fn get_storefront(&mut self, supplier: &Supplier) -> StorefrontState {
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Get {
-                key: *supplier.storefront_key.id(),
-                return_contract_code: false,
-                subscribe: false,
-                blocking_subscribe: false,
-            }))
-            .await
-            .unwrap();
-
-        let resp = recv_matching(&mut self.api, is_get_response, TIMEOUT)
-            .await
-            .expect("Expected GetResponse for storefront");
-
-        let bytes = extract_get_response_state(&resp).expect("state bytes from GET");
-        serde_json::from_slice(&bytes).expect("deserialize storefront from GET")
-    }
-
-    /// Subscribe to a supplier's storefront contract.
-    pub async fn subscribe_to_storefront(&mut self, supplier: &Supplier) {
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: *supplier.storefront_key.id(),
-                summary: None,
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
-            .await
-            .expect("Expected SubscribeResponse for storefront");
-    }
-
-    /// Subscribe to the directory contract.
-    pub async fn subscribe_to_directory(&mut self, dir_key: &ContractKey) {
-        self.api
-            .send(ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: *dir_key.id(),
-                summary: None,
-            }))
-            .await
-            .unwrap();
-
-        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
-            .await
-            .expect("Expected SubscribeResponse for directory");
-    }
-
-    /// Wait for an UpdateNotification and parse it as a StorefrontState.
-    pub async fn recv_storefront_update(&mut self) -> StorefrontState {
-        let notif = recv_matching(&mut self.api, is_update_notification, TIMEOUT)
-            .await
-            .expect("Expected UpdateNotification for storefront");
-
-        let bytes = extract_notification_bytes(&notif).expect("notification bytes");
-        serde_json::from_slice(&bytes).expect("deserialize storefront from notification")
-    }
-
-    /// Wait for an UpdateNotification and parse it as a DirectoryState.
-    pub async fn recv_directory_update(&mut self) -> DirectoryState {
-        let notif = recv_matching(&mut self.api, is_update_notification, TIMEOUT)
-            .await
-            .expect("Expected UpdateNotification for directory");
-
-        let bytes = extract_notification_bytes(&notif).expect("notification bytes");
-        serde_json::from_slice(&bytes).expect("deserialize directory from notification")
-    }
-}
-
-/// Total CURD in the system. Root starts with this; all transfers are double-entry
-/// through root, so the sum of all user contract balances must always equal this.
-pub const SYSTEM_FLOAT: u64 = 1_000_000;
-
-/// Compute the ContractKey for a user's contract given their name.
-///
-/// "system_root" uses the well-known `root_user_id()` (FROST dev key).
-/// All others derive via `make_dummy_user(name)`.
-pub fn user_contract_key_for(name: &str) -> ContractKey {
-    let vk = if name == "system_root" {
-        let root_id = cream_common::identity::root_user_id();
-        ed25519_dalek::VerifyingKey::from_bytes(root_id.0.as_bytes()).unwrap()
-    } else {
-        let (_, vk) = crate::make_dummy_user(name);
-        vk
-    };
-    let (_, key) = crate::make_user_contract(&vk);
-    key
-}
-
 /// Top-level test fixture with named participants and a shared directory.
 pub struct TestHarness {
     pub gary: Supplier,
@@ -344,6 +45,9 @@ pub struct TestHarness {
     pub directory_key: ContractKey,
     pub root_contract_key: ContractKey,
     pub market_directory_key: ContractKey,
+    /// Clock driving time-dependent checks (order expiry). Starts at the real
+    /// time of setup and only moves when a test advances it.
+    pub clock: Arc<MockClock>,
 }
 
 impl TestHarness {
@@ -358,6 +62,9 @@ impl TestHarness {
     /// network topology formation and is known to be flaky under load.
     pub async fn setup() -> Self {
         tracing_subscriber::fmt::try_init().ok();
+        assert_embedded_contracts();
+
+        let clock = Arc::new(MockClock::new(chrono::Utc::now()));
 
         let url_n1 = node_url(3002);
         let url_n2 = node_url(3003);
@@ -382,8 +89,9 @@ impl TestHarness {
         let (emma_sf_contract, emma_sf_key) = make_storefront_contract(&emma_vk);
         let (iris_sf_contract, iris_sf_key) = make_storefront_contract(&iris_vk);
 
-        // Create directory contract
-        let (dir_contract, dir_key) = make_directory_contract();
+        // Create directory contract: Gary, Emma and Iris all have NSW postcodes,
+        // so the NSW shard (the one the UI follows for them) holds the fixture.
+        let (dir_contract, dir_key) = make_directory_shard_contract(DirectoryShard::Nsw);
 
         // Build initial storefront states
         let mut gary_sf = make_initial_storefront(
@@ -426,6 +134,8 @@ impl TestHarness {
             locality: "Boambee".to_string(),
             user_contract_key: None,
             inbox_key: None,
+            node_url: url_n1.clone(),
+            clock: clock.clone(),
         };
         let mut emma = Supplier {
             name: "Emma".to_string(),
@@ -438,6 +148,8 @@ impl TestHarness {
             locality: "Boambee".to_string(),
             user_contract_key: None,
             inbox_key: None,
+            node_url: url_n2.clone(),
+            clock: clock.clone(),
         };
         let mut iris = Supplier {
             name: "Iris".to_string(),
@@ -450,6 +162,8 @@ impl TestHarness {
             locality: "Sydney".to_string(),
             user_contract_key: None,
             inbox_key: None,
+            node_url: url_n1.clone(),
+            clock: clock.clone(),
         };
 
         let mut alice = Customer {
@@ -514,57 +228,8 @@ impl TestHarness {
 
         put_storefront(&mut emma, emma_sf_contract).await;
 
-        // Deploy root user contract with 1,000,000 CURD genesis credit.
-        let root_id = cream_common::identity::root_user_id();
-        let root_vk = *root_id.0.as_bytes();
-        let root_vk = ed25519_dalek::VerifyingKey::from_bytes(&root_vk).unwrap();
-        let (root_contract, root_key) = make_user_contract(&root_vk);
-
-        let genesis_tx = WalletTransaction {
-            id: 0,
-            kind: TransactionKind::Credit,
-            amount: 1_000_000,
-            description: "Genesis".to_string(),
-            sender: String::new(),
-            receiver: cream_common::identity::ROOT_USER_NAME.to_string(),
-            tx_ref: "genesis:0:0".to_string(),
-            timestamp: chrono::Utc::now().to_rfc3339(),
-            lightning_payment_hash: None,
-            extra: Default::default(),
-        };
-
-        let mut root_state = UserContractState {
-            owner: cream_common::identity::root_user_id(),
-            name: cream_common::identity::ROOT_USER_NAME.to_string(),
-            origin_supplier: String::new(),
-            current_supplier: String::new(),
-            balance_curds: 1_000_000,
-            invited_by: String::new(),
-            toll_rates: Default::default(),
-            checkpoint_balance: 0,
-            checkpoint_tx_count: 0,
-            checkpoint_at: None,
-            pruned_lightning_hashes: Default::default(),
-            ledger: vec![genesis_tx],
-            next_tx_id: 1,
-            updated_at: chrono::Utc::now(),
-            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-            extra: Default::default(),
-        };
-        root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
-        let root_state_bytes = serde_json::to_vec(&root_state).unwrap();
-
-        // Deploy root contract on node-1 (via Gary's connection, reuse a fresh one)
-        let mut root_api = connect_to_node_at(&url_n1).await;
-        wait_for_put(
-            &mut root_api,
-            root_contract,
-            WrappedState::new(root_state_bytes),
-            TIMEOUT,
-        )
-        .await
-        .expect("PutResponse for root user contract");
-        drop(root_api);
+        // Deploy root user contract with 1,000,000 CURD genesis credit on node-1.
+        let root_key = deploy_root_contract(&url_n1).await;
 
         // Deploy user contracts for Alice, Bob, and root admin with initial 10,000 CURD allocation.
         deploy_user_contract(&mut alice, &root_key, &url_n1, "Gary").await;
@@ -645,6 +310,7 @@ impl TestHarness {
             directory_key: dir_key,
             root_contract_key: root_key,
             market_directory_key: mkt_dir_key,
+            clock,
         }
     }
 
@@ -657,555 +323,3 @@ impl TestHarness {
         check_curd_conservation(&format!("step {} ({})", step, phase), 3002).await;
     }
 }
-
-/// Standalone CURD conservation check. Connects to a Freenet node, dynamically
-/// discovers all user contracts from system_root's ledger, and verifies that
-/// balances sum to SYSTEM_FLOAT and each contract's cached balance matches its
-/// ledger replay.
-///
-/// Discovery: GETs system_root's contract first, scans its ledger for all unique
-/// counterparty names (debit receivers + credit senders), computes their contract
-/// keys via `user_contract_key_for()`, and checks all of them.
-///
-/// `label` is a human-readable string for log/panic messages (e.g. "step 5 (pre)").
-/// `port` is the Freenet node WebSocket port to read from (e.g. 3002 for node tests,
-/// 3001 for E2E which operates through the gateway).
-///
-/// Retries up to 10 times with 1s backoff for Freenet eventual consistency.
-pub async fn check_curd_conservation(label: &str, port: u16) {
-    use std::collections::BTreeSet;
-    use cream_common::wallet::TransactionKind;
-
-    let url = node_url(port);
-
-    for attempt in 1..=10 {
-        let mut api = connect_to_node_at(&url).await;
-
-        // Phase 1: GET system_root to discover all participants
-        let root_key = user_contract_key_for("system_root");
-        let root_bytes = match wait_for_get(&mut api, *root_key.id(), TIMEOUT).await {
-            Some(b) => b,
-            None => {
-                if attempt < 10 {
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                    continue;
-                }
-                panic!("Invariant check failed at {}: could not GET system_root after 10 attempts", label);
-            }
-        };
-        let root_state: UserContractState = match serde_json::from_slice(&root_bytes) {
-            Ok(s) => s,
-            Err(_) => {
-                if attempt < 10 {
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                    continue;
-                }
-                panic!("Invariant check failed at {}: could not parse system_root state", label);
-            }
-        };
-
-        // Discover all user names from root's ledger.
-        // Use genesis tx_refs ("genesis:name") for reliable discovery — these are
-        // always present for allocated users and use the canonical lowercase name.
-        // Also discover non-genesis counterparties from sender/receiver fields,
-        // but skip known non-user placeholders.
-        let mut user_names: BTreeSet<String> = BTreeSet::new();
-        for tx in &root_state.ledger {
-            // Primary discovery: genesis tx_refs
-            if let Some(name) = tx.tx_ref.strip_prefix("genesis:") {
-                if !name.is_empty() && !name.contains(':') {
-                    user_names.insert(name.to_string());
-                    continue;
-                }
-            }
-
-            // Secondary discovery: sender/receiver fields for non-genesis transfers
-            let counterparty = match tx.kind {
-                TransactionKind::Debit => &tx.receiver,
-                TransactionKind::Credit => &tx.sender,
-            };
-            if !counterparty.is_empty()
-                && counterparty != cream_common::identity::ROOT_USER_NAME
-                && counterparty != "customer" // placeholder from cancel escrow
-            {
-                user_names.insert(counterparty.clone());
-            }
-        }
-
-        // Build contract list: system_root + all discovered users.
-        // Dedup by contract key since different name casings map to the same key
-        // (derive_user_signing_key normalizes to lowercase internally).
-        let mut seen_keys = std::collections::HashSet::new();
-        let mut contracts: Vec<(String, ContractKey)> = vec![
-            ("system_root".to_string(), root_key),
-        ];
-        seen_keys.insert(root_key);
-        for name in &user_names {
-            let key = user_contract_key_for(name);
-            if seen_keys.insert(key) {
-                contracts.push((name.clone(), key));
-            }
-        }
-
-        // Build a map of genesis allocations from root's ledger so we can
-        // substitute for contracts that haven't propagated yet.
-        let genesis_allocations: std::collections::HashMap<String, u64> = root_state.ledger.iter()
-            .filter_map(|tx| {
-                tx.tx_ref.strip_prefix("genesis:")
-                    .filter(|name| !name.is_empty() && !name.contains(':'))
-                    .map(|name| (name.to_string(), tx.amount))
-            })
-            .collect();
-
-        // Phase 2: GET all user contracts and collect states.
-        // Contracts that haven't propagated yet (GET fails or returns empty state)
-        // are substituted with their genesis allocation from root's ledger.
-        let mut user_states: Vec<(String, UserContractState)> = Vec::new();
-        let mut substituted_balances: Vec<(String, u64)> = Vec::new();
-        user_states.push(("system_root".to_string(), root_state));
-
-        let mut all_ok = true;
-        for (name, key) in contracts.iter().skip(1) {
-            let bytes = match wait_for_get(&mut api, *key.id(), TIMEOUT).await {
-                Some(b) => b,
-                None => {
-                    if let Some(&alloc) = genesis_allocations.get(name) {
-                        substituted_balances.push((name.clone(), alloc));
-                        continue;
-                    }
-                    all_ok = false;
-                    break;
-                }
-            };
-            let state: UserContractState = match serde_json::from_slice(&bytes) {
-                Ok(s) => s,
-                Err(_) => {
-                    all_ok = false;
-                    break;
-                }
-            };
-            if state.balance_curds == 0 && state.ledger.is_empty() {
-                if let Some(&alloc) = genesis_allocations.get(name) {
-                    substituted_balances.push((name.clone(), alloc));
-                    continue;
-                }
-            }
-            user_states.push((name.clone(), state));
-        }
-
-        if !all_ok {
-            if attempt < 10 {
-                tokio::time::sleep(Duration::from_secs(1)).await;
-                continue;
-            }
-            panic!(
-                "Invariant check failed at {}: could not GET all user contracts after 10 attempts (discovered {} users)",
-                label, contracts.len()
-            );
-        }
-
-        // Check cached == derived for each contract
-        let mut cache_mismatch = false;
-        for (name, state) in &user_states {
-            if state.balance_curds != state.derive_balance() {
-                if attempt < 10 {
-                    cache_mismatch = true;
-                    break;
-                }
-                panic!(
-                    "Invariant FAILED at {}: {}'s balance_curds ({}) != derive_balance() ({})",
-                    label, name, state.balance_curds, state.derive_balance()
-                );
-            }
-        }
-        if cache_mismatch {
-            tokio::time::sleep(Duration::from_secs(1)).await;
-            continue;
-        }
-
-        // Compute deduped balances for conservation check.
-        // Freenet merge races can produce duplicate ledger entries with the same
-        // (tx_ref, kind). We dedup locally to get the true balance, and warn if
-        // duplicates are found.
-        let mut total_dups = 0usize;
-        let mut deduped_balances: Vec<(String, u64)> = Vec::new(); // (name, deduped_balance)
-        for (name, state) in &user_states {
-            let mut seen = std::collections::HashSet::new();
-            let deduped_balance = state.ledger.iter().fold(state.checkpoint_balance, |acc, tx| {
-                let key = (tx.tx_ref.clone(), tx.kind.clone());
-                if !seen.insert(key) {
-                    total_dups += 1;
-                    return acc; // skip duplicate
-                }
-                match tx.kind {
-                    TransactionKind::Credit => acc.saturating_add(tx.amount),
-                    TransactionKind::Debit => acc.saturating_sub(tx.amount),
-                }
-            });
-            deduped_balances.push((name.clone(), deduped_balance));
-        }
-
-        if total_dups > 0 {
-            println!(
-                "  [invariant] {} WARNING: {} duplicate ledger entries detected (Freenet merge race)",
-                label, total_dups
-            );
-        }
-
-        // Check CURD conservation using deduped balances + substituted balances
-        let total: u64 = deduped_balances.iter().map(|(_, bal)| bal).sum::<u64>()
-            + substituted_balances.iter().map(|(_, bal)| bal).sum::<u64>();
-        let mut detail: Vec<String> = deduped_balances
-            .iter()
-            .map(|(name, bal)| format!("{}={}", name, bal))
-            .collect();
-        for (name, bal) in &substituted_balances {
-            detail.push(format!("{}={}*", name, bal));
-        }
-
-        if total != SYSTEM_FLOAT {
-            if attempt < 10 {
-                println!(
-                    "  [invariant retry {}/10] {}: total={} (want {}) [{} users]",
-                    attempt, label, total, SYSTEM_FLOAT, deduped_balances.len(),
-                );
-                tokio::time::sleep(Duration::from_secs(1)).await;
-                continue;
-            }
-            panic!(
-                "Invariant FAILED at {}: CURD total {} != {} ({}) [{} users discovered]",
-                label, total, SYSTEM_FLOAT, detail.join(", "), deduped_balances.len()
-            );
-        }
-
-        // All checks passed
-        let sub_note = if !substituted_balances.is_empty() {
-            format!(", {}* not-yet-propagated (genesis substituted)", substituted_balances.len())
-        } else {
-            String::new()
-        };
-        let user_count = deduped_balances.len() + substituted_balances.len();
-        println!(
-            "  [invariant] {} OK: total={} ({}) [{} users{}]",
-            label, total, detail.join(", "), user_count, sub_note
-        );
-        return;
-    }
-}
-
-/// Build an empty StorefrontState for a supplier.
-fn make_initial_storefront(
-    owner: &UserId,
-    name: &str,
-    description: &str,
-    location: GeoLocation,
-) -> StorefrontState {
-    StorefrontState {
-        info: StorefrontInfo {
-            owner: owner.clone(),
-            name: name.to_string(),
-            description: description.to_string(),
-            location,
-            schedule: None,
-            timezone: None,
-            phone: None,
-            email: None,
-            address: None,
-            market_products: BTreeMap::new(),
-            extra: Default::default(),
-        },
-        products: BTreeMap::new(),
-        orders: BTreeMap::new(),
-        extra: Default::default(),
-    }
-}
-
-/// PUT a storefront contract via the supplier's connection and wait for confirmation.
-async fn put_storefront(supplier: &mut Supplier, contract: ContractContainer) {
-    let state_bytes = serde_json::to_vec(&supplier.storefront).unwrap();
-    wait_for_put(
-        &mut supplier.api,
-        contract,
-        WrappedState::new(state_bytes),
-        TIMEOUT,
-    )
-    .await
-    .unwrap_or_else(|| panic!("PutResponse for {}'s storefront", supplier.name));
-}
-
-/// Register a supplier in the directory via Update.
-async fn register_supplier_in_directory(supplier: &mut Supplier, dir_key: &ContractKey) {
-    let entry = make_directory_entry(
-        &supplier.id,
-        &supplier.name,
-        &supplier.storefront.info.description,
-        &supplier.postcode,
-        &supplier.locality,
-        supplier.storefront.info.location.clone(),
-        supplier.storefront_key,
-        supplier.user_contract_key,
-        supplier.inbox_key,
-    );
-    let mut entries = BTreeMap::new();
-    entries.insert(supplier.id.clone(), entry);
-    let delta = DirectoryState { entries, extra: Default::default() };
-    let delta_bytes = serde_json::to_vec(&delta).unwrap();
-
-    supplier
-        .api
-        .send(ClientRequest::ContractOp(ContractRequest::Update {
-            key: *dir_key,
-            data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-        }))
-        .await
-        .unwrap();
-
-    recv_matching(&mut supplier.api, is_update_response, TIMEOUT)
-        .await
-        .unwrap_or_else(|| {
-            panic!(
-                "UpdateResponse for {}'s directory registration",
-                supplier.name
-            )
-        });
-}
-
-/// Deploy a user contract for a supplier with initial 10,000 CURD from root.
-async fn deploy_supplier_user_contract(
-    supplier: &mut Supplier,
-    root_key: &ContractKey,
-    node_url: &str,
-) {
-    let (uc_contract, uc_key) = make_user_contract(&supplier.verifying_key);
-
-    // Deterministic tx_ref so UI re-registration deduplicates against this credit.
-    let tx_ref = format!("genesis:{}", supplier.name.to_lowercase());
-    let now_str = chrono::Utc::now().to_rfc3339();
-
-    let initial_credit = WalletTransaction {
-        id: 0,
-        kind: TransactionKind::Credit,
-        amount: 10_000,
-        description: "Initial CURD allocation".to_string(),
-        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
-        receiver: supplier.name.clone(),
-        tx_ref: tx_ref.clone(),
-        timestamp: now_str.clone(),
-        lightning_payment_hash: None,
-        extra: Default::default(),
-    };
-
-    let uc_state = UserContractState {
-        owner: cream_common::identity::UserId(supplier.verifying_key),
-        name: supplier.name.clone(),
-        origin_supplier: supplier.name.clone(),
-        current_supplier: supplier.name.clone(),
-        balance_curds: 10_000,
-        invited_by: String::new(),
-        toll_rates: Default::default(),
-        checkpoint_balance: 0,
-        checkpoint_tx_count: 0,
-        checkpoint_at: None,
-        pruned_lightning_hashes: Default::default(),
-        ledger: vec![initial_credit],
-        next_tx_id: 1,
-        updated_at: chrono::Utc::now(),
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        extra: Default::default(),
-    };
-
-    let uc_state_bytes = serde_json::to_vec(&uc_state).unwrap();
-
-    let mut deploy_api = connect_to_node_at(node_url).await;
-    wait_for_put(
-        &mut deploy_api,
-        uc_contract,
-        WrappedState::new(uc_state_bytes),
-        TIMEOUT,
-    )
-    .await
-    .unwrap_or_else(|| panic!("PutResponse for {}'s supplier user contract", supplier.name));
-    drop(deploy_api);
-
-    supplier.user_contract_key = Some(uc_key);
-
-    // Also record the debit on root's contract
-    let root_debit = WalletTransaction {
-        id: 0,
-        kind: TransactionKind::Debit,
-        amount: 10_000,
-        description: format!("Initial CURD allocation for {}", supplier.name),
-        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
-        receiver: supplier.name.clone(),
-        tx_ref,
-        timestamp: now_str,
-        lightning_payment_hash: None,
-        extra: Default::default(),
-    };
-
-    // GET root state, append debit, Update
-    let mut root_api = connect_to_node_at(node_url).await;
-    let root_bytes = wait_for_get(&mut root_api, *root_key.id(), TIMEOUT)
-        .await
-        .expect("GET root contract for supplier debit");
-    let mut root_state: UserContractState = serde_json::from_slice(&root_bytes).unwrap();
-    root_state.ledger.push(root_debit);
-    root_state.balance_curds = root_state.derive_balance();
-    root_state.next_tx_id = root_state.ledger.iter().map(|t| t.id).max().unwrap_or(0) + 1;
-    root_state.updated_at = chrono::Utc::now();
-    root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
-
-    let root_update_bytes = serde_json::to_vec(&root_state).unwrap();
-    root_api
-        .send(ClientRequest::ContractOp(ContractRequest::Update {
-            key: *root_key,
-            data: UpdateData::State(State::from(root_update_bytes)),
-        }))
-        .await
-        .unwrap();
-
-    recv_matching(&mut root_api, is_update_response, TIMEOUT)
-        .await
-        .expect("UpdateResponse for root debit (supplier)");
-    drop(root_api);
-}
-
-/// Deploy a user contract for a customer with initial 10,000 CURD from root.
-async fn deploy_user_contract(
-    customer: &mut Customer,
-    root_key: &ContractKey,
-    node_url: &str,
-    invited_by: &str,
-) {
-    let (uc_contract, uc_key) = make_user_contract(&customer.verifying_key);
-
-    // Deterministic tx_ref so UI re-registration deduplicates against this credit.
-    let tx_ref = format!("genesis:{}", customer.name.to_lowercase());
-    let now_str = chrono::Utc::now().to_rfc3339();
-
-    let initial_credit = WalletTransaction {
-        id: 0,
-        kind: TransactionKind::Credit,
-        amount: 10_000,
-        description: "Initial CURD allocation".to_string(),
-        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
-        receiver: customer.name.clone(),
-        tx_ref: tx_ref.clone(),
-        timestamp: now_str.clone(),
-        lightning_payment_hash: None,
-        extra: Default::default(),
-    };
-
-    let uc_state = UserContractState {
-        owner: customer.id.clone(),
-        name: customer.name.clone(),
-        origin_supplier: invited_by.to_string(),
-        current_supplier: invited_by.to_string(),
-        balance_curds: 10_000,
-        invited_by: invited_by.to_string(),
-        toll_rates: Default::default(),
-        checkpoint_balance: 0,
-        checkpoint_tx_count: 0,
-        checkpoint_at: None,
-        pruned_lightning_hashes: Default::default(),
-        ledger: vec![initial_credit],
-        next_tx_id: 1,
-        updated_at: chrono::Utc::now(),
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        extra: Default::default(),
-    };
-
-    let uc_state_bytes = serde_json::to_vec(&uc_state).unwrap();
-
-    let mut deploy_api = connect_to_node_at(node_url).await;
-    wait_for_put(
-        &mut deploy_api,
-        uc_contract,
-        WrappedState::new(uc_state_bytes),
-        TIMEOUT,
-    )
-    .await
-    .unwrap_or_else(|| panic!("PutResponse for {}'s user contract", customer.name));
-    drop(deploy_api);
-
-    customer.user_contract_key = Some(uc_key);
-
-    // Also record the debit on root's contract
-    let root_debit = WalletTransaction {
-        id: 0,
-        kind: TransactionKind::Debit,
-        amount: 10_000,
-        description: format!("Initial CURD allocation for {}", customer.name),
-        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
-        receiver: customer.name.clone(),
-        tx_ref,
-        timestamp: now_str,
-        lightning_payment_hash: None,
-        extra: Default::default(),
-    };
-
-    // GET root state, append debit, Update
-    let mut root_api = connect_to_node_at(node_url).await;
-    let root_bytes = wait_for_get(&mut root_api, *root_key.id(), TIMEOUT)
-        .await
-        .expect("GET root contract for debit");
-    let mut root_state: UserContractState = serde_json::from_slice(&root_bytes).unwrap();
-    root_state.ledger.push(root_debit);
-    root_state.balance_curds = root_state.derive_balance();
-    root_state.next_tx_id = root_state.ledger.iter().map(|t| t.id).max().unwrap_or(0) + 1;
-    root_state.updated_at = chrono::Utc::now();
-    root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
-
-    let root_update_bytes = serde_json::to_vec(&root_state).unwrap();
-    root_api
-        .send(ClientRequest::ContractOp(ContractRequest::Update {
-            key: *root_key,
-            data: UpdateData::State(State::from(root_update_bytes)),
-        }))
-        .await
-        .unwrap();
-
-    recv_matching(&mut root_api, is_update_response, TIMEOUT)
-        .await
-        .expect("UpdateResponse for root debit");
-    drop(root_api);
-}
-
-/// Deploy an inbox contract for an entity (supplier or customer).
-async fn deploy_inbox(
-    _api: &mut WebApi,
-    owner_vk: &VerifyingKey,
-    node_url_str: &str,
-    inbox_key_out: &mut Option<ContractKey>,
-) {
-    use cream_common::identity::UserId;
-    use cream_common::inbox::InboxState;
-
-    let (inbox_contract, inbox_key) = make_inbox_contract(owner_vk);
-    let ib_state = InboxState {
-        owner: UserId(*owner_vk),
-        messages: std::collections::BTreeMap::new(),
-        updated_at: chrono::Utc::now(),
-        extra: Default::default(),
-    };
-    let ib_state_bytes = serde_json::to_vec(&ib_state).unwrap();
-
-    let mut deploy_api = connect_to_node_at(node_url_str).await;
-    wait_for_put(
-        &mut deploy_api,
-        inbox_contract,
-        WrappedState::new(ib_state_bytes),
-        TIMEOUT,
-    )
-    .await
-    .expect("PutResponse for inbox contract");
-    drop(deploy_api);
-
-    *inbox_key_out = Some(inbox_key);
-}
-
-/// Deploy an inbox contract for a customer.
-async fn deploy_inbox_customer(customer: &mut Customer, node_url_str: &str) {
-    let vk = customer.verifying_key;
-    deploy_inbox(&mut customer.api, &vk, node_url_str, &mut customer.inbox_key).await;
-}
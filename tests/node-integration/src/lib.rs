@@ -1,475 +1,12 @@
-use std::sync::Arc;
-use std::time::Duration;
+//! Node integration tests against a local multi-node Freenet network.
+//!
+//! The node helpers and simulated participants live in `cream-sim`, which is
+//! re-exported here; this crate adds the fixed test cast ([`harness`]),
+//! seeded fixtures at scale ([`fixtures`]) and polling assertions for
+//! eventual consistency ([`convergence`]).
 
-use freenet_stdlib::client_api::{
-    ClientRequest, ContractRequest, ContractResponse, HostResponse, WebApi,
-};
-use freenet_stdlib::prelude::*;
-use tokio::time::Instant;
-
-use cream_common::directory::DirectoryEntry;
-use cream_common::identity::UserId;
-use cream_common::location::GeoLocation;
-use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
-use cream_common::product::{Product, ProductCategory, ProductId};
-use cream_common::storefront::{SignedProduct, StorefrontParameters};
+pub use cream_sim::*;
 
+pub mod convergence;
+pub mod fixtures;
 pub mod harness;
-
-/// Build a full WebSocket URL for a Freenet node on the given port.
-pub fn node_url(port: u16) -> String {
-    format!("ws://localhost:{port}/v1/contract/command?encodingProtocol=native")
-}
-
-/// Connect a native WebApi client to a Freenet node at an arbitrary URL.
-pub async fn connect_to_node_at(url: &str) -> WebApi {
-    let (ws_conn, _) = tokio_tungstenite::connect_async(url)
-        .await
-        .unwrap_or_else(|e| panic!("Failed to connect to Freenet node at {url}: {e}"));
-    WebApi::start(ws_conn)
-}
-
-/// Build a ContractContainer from WASM bytes and parameters.
-fn make_contract(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractContainer {
-    let code = ContractCode::from(wasm_bytes.to_vec());
-    let wrapped = WrappedContract::new(Arc::new(code), params);
-    ContractContainer::Wasm(ContractWasmAPIVersion::V1(wrapped))
-}
-
-/// Embedded contract WASM blobs (same ones the UI uses).
-const DIRECTORY_WASM: &[u8] =
-    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm");
-const STOREFRONT_WASM: &[u8] =
-    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_storefront_contract.wasm");
-const USER_CONTRACT_WASM: &[u8] =
-    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm");
-const INBOX_CONTRACT_WASM: &[u8] =
-    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_inbox_contract.wasm");
-const MARKET_DIRECTORY_WASM: &[u8] =
-    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_market_directory_contract.wasm");
-
-/// Create a directory contract container + its key.
-pub fn make_directory_contract() -> (ContractContainer, ContractKey) {
-    let contract = make_contract(DIRECTORY_WASM, Parameters::from(vec![]));
-    let key = contract.key();
-    (contract, key)
-}
-
-/// Create a storefront contract container + its key for a given owner.
-pub fn make_storefront_contract(
-    owner: &ed25519_dalek::VerifyingKey,
-) -> (ContractContainer, ContractKey) {
-    let params = StorefrontParameters { owner: *owner };
-    let params_bytes = serde_json::to_vec(&params).unwrap();
-    let contract = make_contract(STOREFRONT_WASM, Parameters::from(params_bytes));
-    let key = contract.key();
-    (contract, key)
-}
-
-/// Create a user contract container + its key for a given owner.
-pub fn make_user_contract(
-    owner: &ed25519_dalek::VerifyingKey,
-) -> (ContractContainer, ContractKey) {
-    let params = cream_common::user_contract::UserContractParameters { owner: *owner };
-    let params_bytes = serde_json::to_vec(&params).unwrap();
-    let contract = make_contract(USER_CONTRACT_WASM, Parameters::from(params_bytes));
-    let key = contract.key();
-    (contract, key)
-}
-
-/// Create a market directory contract container + its key.
-pub fn make_market_directory_contract() -> (ContractContainer, ContractKey) {
-    let contract = make_contract(MARKET_DIRECTORY_WASM, Parameters::from(vec![]));
-    let key = contract.key();
-    (contract, key)
-}
-
-/// Create an inbox contract container + its key for a given owner.
-pub fn make_inbox_contract(
-    owner: &ed25519_dalek::VerifyingKey,
-) -> (ContractContainer, ContractKey) {
-    let params = cream_common::inbox::InboxParameters { owner: *owner };
-    let params_bytes = serde_json::to_vec(&params).unwrap();
-    let contract = make_contract(INBOX_CONTRACT_WASM, Parameters::from(params_bytes));
-    let key = contract.key();
-    (contract, key)
-}
-
-/// Create a deterministic user identity from a name.
-///
-/// Uses `derive_user_signing_key(name, password)` with `password = name.to_lowercase()`
-/// so that the test harness produces the same keys as the UI when a user logs
-/// in with their name as the password.
-pub fn make_dummy_user(name: &str) -> (UserId, ed25519_dalek::VerifyingKey) {
-    let password = name.to_lowercase();
-    let signing_key = cream_common::identity::derive_user_signing_key(name, &password);
-    let verifying_key = ed25519_dalek::VerifyingKey::from(&signing_key);
-    (UserId(verifying_key), verifying_key)
-}
-
-/// Create a dummy directory entry for a supplier.
-pub fn make_directory_entry(
-    user_id: &UserId,
-    name: &str,
-    description: &str,
-    postcode: &str,
-    locality: &str,
-    location: GeoLocation,
-    storefront_key: ContractKey,
-    user_contract_key: Option<ContractKey>,
-    inbox_contract_key: Option<ContractKey>,
-) -> DirectoryEntry {
-    DirectoryEntry {
-        supplier: user_id.clone(),
-        name: name.to_string(),
-        description: description.to_string(),
-        location,
-        postcode: Some(postcode.to_string()),
-        locality: Some(locality.to_string()),
-        categories: vec![],
-        storefront_key,
-        user_contract_key,
-        inbox_contract_key,
-        updated_at: chrono::Utc::now(),
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        extra: Default::default(),
-    }
-}
-
-/// Create a dummy market entry.
-pub fn make_dummy_market_entry(
-    organizer: &UserId,
-    name: &str,
-    venue_address: &str,
-    location: GeoLocation,
-    suppliers: std::collections::BTreeMap<String, cream_common::market::SupplierStatus>,
-) -> cream_common::market::MarketEntry {
-    use chrono::NaiveDate;
-    cream_common::market::MarketEntry {
-        organizer: organizer.clone(),
-        name: name.to_string(),
-        description: format!("{name} — fresh produce from local farmers"),
-        venue_address: venue_address.to_string(),
-        location,
-        postcode: Some("2450".to_string()),
-        locality: Some("Coffs Harbour".to_string()),
-        events: vec![
-            cream_common::market::MarketEvent {
-                date: NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
-                start_time: "07:00".to_string(),
-                end_time: "13:00".to_string(),
-                extra: Default::default(),
-            },
-            cream_common::market::MarketEvent {
-                date: NaiveDate::from_ymd_opt(2026, 4, 19).unwrap(),
-                start_time: "07:00".to_string(),
-                end_time: "13:00".to_string(),
-                extra: Default::default(),
-            },
-        ],
-        timezone: Some("Australia/Sydney".to_string()),
-        suppliers,
-        updated_at: chrono::Utc::now(),
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        extra: Default::default(),
-    }
-}
-
-/// Create a dummy product.
-pub fn make_dummy_product(name: &str) -> SignedProduct {
-    let now = chrono::Utc::now();
-    SignedProduct {
-        product: Product {
-            id: ProductId(format!("p-{}", now.timestamp_millis())),
-            name: name.to_string(),
-            description: format!("Fresh {name}"),
-            category: ProductCategory::Milk,
-            price_curd: 500,
-            quantity_total: 10,
-            expiry_date: None,
-            updated_at: now,
-            created_at: now,
-            extra: Default::default(),
-        },
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        extra: Default::default(),
-    }
-}
-
-/// Create a dummy order with a configurable deposit tier and creation timestamp.
-///
-/// The `created_at` parameter lets tests create backdated orders whose reservation
-/// has already expired relative to "now", enabling expiry tests without real waits.
-pub fn make_dummy_order(
-    product_id: &ProductId,
-    user_id: &UserId,
-    tier: DepositTier,
-    quantity: u32,
-    price_per_unit: u64,
-    created_at: chrono::DateTime<chrono::Utc>,
-) -> Order {
-    let total_price = price_per_unit * quantity as u64;
-    let deposit_amount = tier.calculate_deposit(total_price);
-    let expires_at = match tier {
-        DepositTier::Reserve2Days => created_at + chrono::Duration::days(2),
-        DepositTier::Reserve1Week => created_at + chrono::Duration::weeks(1),
-        DepositTier::FullPayment => created_at + chrono::Duration::days(365),
-    };
-    let order_id = OrderId(format!("o-{}", created_at.timestamp_millis()));
-    Order {
-        id: order_id,
-        product_id: product_id.clone(),
-        customer: user_id.clone(),
-        quantity,
-        deposit_tier: tier,
-        deposit_amount,
-        total_price,
-        status: OrderStatus::Reserved { expires_at },
-        created_at,
-        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
-        escrow_token: None,
-        collection_point: None,
-        extra: Default::default(),
-    }
-}
-
-/// Wait for a HostResponse matching a predicate, with timeout.
-/// Non-matching responses are logged and discarded.
-pub async fn recv_matching<F>(
-    api: &mut WebApi,
-    predicate: F,
-    timeout: Duration,
-) -> Option<HostResponse>
-where
-    F: Fn(&HostResponse) -> bool,
-{
-    let deadline = Instant::now() + timeout;
-    loop {
-        let remaining = deadline.saturating_duration_since(Instant::now());
-        if remaining.is_zero() {
-            return None;
-        }
-        match tokio::time::timeout(remaining, api.recv()).await {
-            Ok(Ok(resp)) if predicate(&resp) => return Some(resp),
-            Ok(Ok(other)) => {
-                tracing::debug!("Discarding non-matching response: {:?}", other);
-                continue;
-            }
-            Ok(Err(e)) => {
-                tracing::error!("Node error while waiting: {:?}", e);
-                return None;
-            }
-            Err(_) => return None, // timeout
-        }
-    }
-}
-
-/// Check if a HostResponse is a ContractResponse::UpdateNotification.
-pub fn is_update_notification(resp: &HostResponse) -> bool {
-    matches!(
-        resp,
-        HostResponse::ContractResponse(ContractResponse::UpdateNotification { .. })
-    )
-}
-
-/// Check if a HostResponse is a SubscribeResponse with subscribed=true.
-pub fn is_subscribe_success(resp: &HostResponse) -> bool {
-    matches!(
-        resp,
-        HostResponse::ContractResponse(ContractResponse::SubscribeResponse {
-            subscribed: true,
-            ..
-        })
-    )
-}
-
-/// Check if a HostResponse is a PutResponse.
-pub fn is_put_response(resp: &HostResponse) -> bool {
-    matches!(
-        resp,
-        HostResponse::ContractResponse(ContractResponse::PutResponse { .. })
-    )
-}
-
-/// Check if a HostResponse is an UpdateResponse.
-pub fn is_update_response(resp: &HostResponse) -> bool {
-    matches!(
-        resp,
-        HostResponse::ContractResponse(ContractResponse::UpdateResponse { .. })
-    )
-}
-
-/// Check if a HostResponse is a GetResponse.
-pub fn is_get_response(resp: &HostResponse) -> bool {
-    matches!(
-        resp,
-        HostResponse::ContractResponse(ContractResponse::GetResponse { .. })
-    )
-}
-
-/// Extract the state bytes from a GetResponse.
-pub fn extract_get_response_state(resp: &HostResponse) -> Option<Vec<u8>> {
-    if let HostResponse::ContractResponse(ContractResponse::GetResponse { state, .. }) = resp {
-        Some(state.as_ref().to_vec())
-    } else {
-        None
-    }
-}
-
-/// Retry GET on a contract until it succeeds or the timeout expires.
-/// Returns the state bytes on success, None on timeout.
-pub async fn wait_for_get(
-    api: &mut WebApi,
-    key: ContractInstanceId,
-    timeout: Duration,
-) -> Option<Vec<u8>> {
-    let deadline = Instant::now() + timeout;
-    loop {
-        let remaining = deadline.saturating_duration_since(Instant::now());
-        if remaining.is_zero() {
-            return None;
-        }
-
-        api.send(ClientRequest::ContractOp(ContractRequest::Get {
-            key,
-            return_contract_code: true,
-            subscribe: false,
-            blocking_subscribe: false,
-        }))
-        .await
-        .unwrap();
-
-        match tokio::time::timeout(Duration::from_secs(5), api.recv()).await {
-            Ok(Ok(HostResponse::ContractResponse(ContractResponse::GetResponse {
-                state, ..
-            }))) => {
-                return Some(state.as_ref().to_vec());
-            }
-            Ok(Ok(other)) => {
-                tracing::debug!("wait_for_get: non-GET response: {:?}", other);
-            }
-            Ok(Err(e)) => {
-                tracing::debug!("wait_for_get: error: {:?}", e);
-            }
-            Err(_) => {
-                tracing::debug!("wait_for_get: recv timeout, will retry");
-            }
-        }
-
-        tokio::time::sleep(Duration::from_secs(1)).await;
-    }
-}
-
-/// Send PUT and drain messages until a PutResponse is received, or timeout expires.
-/// If the recv times out without a PutResponse, re-sends the PUT and tries again.
-pub async fn wait_for_put(
-    api: &mut WebApi,
-    contract: ContractContainer,
-    state: WrappedState,
-    timeout: Duration,
-) -> Option<HostResponse> {
-    let deadline = Instant::now() + timeout;
-    let mut attempt = 0u32;
-    loop {
-        attempt += 1;
-        let remaining = deadline.saturating_duration_since(Instant::now());
-        if remaining.is_zero() {
-            tracing::error!("wait_for_put: timed out after {attempt} attempts");
-            return None;
-        }
-
-        api.send(ClientRequest::ContractOp(ContractRequest::Put {
-            contract: contract.clone(),
-            state: state.clone(),
-            related_contracts: RelatedContracts::default(),
-            subscribe: false,
-            blocking_subscribe: false,
-        }))
-        .await
-        .unwrap();
-
-        // Drain messages for up to 30s looking for the PutResponse
-        let drain_deadline = Instant::now() + Duration::from_secs(30).min(remaining);
-        loop {
-            let drain_remaining = drain_deadline.saturating_duration_since(Instant::now());
-            if drain_remaining.is_zero() {
-                tracing::debug!("wait_for_put: drain timeout on attempt {attempt}, will re-send");
-                break;
-            }
-            match tokio::time::timeout(drain_remaining, api.recv()).await {
-                Ok(Ok(resp)) if is_put_response(&resp) => {
-                    if attempt > 1 {
-                        tracing::info!("wait_for_put: succeeded on attempt {attempt}");
-                    }
-                    return Some(resp);
-                }
-                Ok(Ok(_other)) => {
-                    // Non-PUT message (e.g. notification), keep draining
-                    continue;
-                }
-                Ok(Err(e)) => {
-                    tracing::debug!("wait_for_put: error on attempt {attempt}: {:?}", e);
-                    break;
-                }
-                Err(_) => {
-                    tracing::debug!(
-                        "wait_for_put: drain timeout on attempt {attempt}, will re-send"
-                    );
-                    break;
-                }
-            }
-        }
-
-        tokio::time::sleep(Duration::from_secs(1)).await;
-    }
-}
-
-/// Like `wait_for_get`, but also returns how long it took.
-pub async fn timed_wait_for_get(
-    api: &mut WebApi,
-    key: ContractInstanceId,
-    timeout: Duration,
-) -> Option<(Vec<u8>, Duration)> {
-    let start = Instant::now();
-    let bytes = wait_for_get(api, key, timeout).await?;
-    Some((bytes, start.elapsed()))
-}
-
-/// Compute the "root" admin user's pubkey as a lowercase hex string.
-///
-/// Uses the same deterministic key derivation as `make_dummy_user("root")`.
-pub fn root_admin_pubkey_hex() -> String {
-    let (_id, vk) = make_dummy_user("root");
-    vk.as_bytes().iter().map(|b| format!("{:02x}", b)).collect()
-}
-
-/// Like `recv_matching`, but also returns how long it took.
-pub async fn timed_recv_matching<F>(
-    api: &mut WebApi,
-    predicate: F,
-    timeout: Duration,
-) -> Option<(HostResponse, Duration)>
-where
-    F: Fn(&HostResponse) -> bool,
-{
-    let start = Instant::now();
-    let resp = recv_matching(api, predicate, timeout).await?;
-    Some((resp, start.elapsed()))
-}
-
-/// Extract UpdateNotification bytes from a HostResponse.
-pub fn extract_notification_bytes(resp: &HostResponse) -> Option<Vec<u8>> {
-    if let HostResponse::ContractResponse(ContractResponse::UpdateNotification { update, .. }) =
-        resp
-    {
-        let bytes = match update {
-            UpdateData::State(s) => s.as_ref().to_vec(),
-            UpdateData::Delta(d) => d.as_ref().to_vec(),
-            UpdateData::StateAndDelta { state, .. } => state.as_ref().to_vec(),
-            _ => return None,
-        };
-        Some(bytes)
-    } else {
-        None
-    }
-}
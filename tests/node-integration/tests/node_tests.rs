@@ -52,6 +52,7 @@ macro_rules! run_step_post_only {
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn cumulative_node_tests() {
     tracing_subscriber::fmt::try_init().ok();
+    cream_node_integration::assert_embedded_contracts();
 
     // ═══════════════════════════════════════════════════════════════════
     // Step 1: Directory subscribe → update → notification
@@ -114,7 +115,7 @@ async fn cumulative_node_tests() {
 
         let mut entries = BTreeMap::new();
         entries.insert(supplier_id, entry);
-        let delta = DirectoryState { entries, extra: Default::default() };
+        let delta = DirectoryState { entries, ..Default::default() };
         let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
         client_a
@@ -163,10 +164,21 @@ async fn cumulative_node_tests() {
                 email: None,
                 address: None,
                 market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
                 extra: Default::default(),
             },
             products: BTreeMap::new(),
             orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
             extra: Default::default(),
         };
         let state_bytes = serde_json::to_vec(&initial_sf).unwrap();
@@ -258,10 +270,21 @@ async fn cumulative_node_tests() {
                 email: None,
                 address: None,
                 market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
                 extra: Default::default(),
             },
             products: BTreeMap::new(),
             orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
             extra: Default::default(),
         };
         let state_bytes = serde_json::to_vec(&initial_sf).unwrap();
@@ -379,10 +402,21 @@ async fn cumulative_node_tests() {
                 email: None,
                 address: None,
                 market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
                 extra: Default::default(),
             },
             products: BTreeMap::new(),
             orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
             extra: Default::default(),
         };
         let state_bytes = serde_json::to_vec(&initial_sf).unwrap();
@@ -814,59 +848,24 @@ async fn cumulative_node_tests() {
         use cream_common::identity::ROOT_USER_NAME;
         use cream_common::user_contract::UserContractState;
         use cream_common::wallet::TransactionKind;
+        use cream_node_integration::convergence::{converge, Expect, Poll};
 
-        // GET root contract state from gateway — retry until all 6 transfers
-        // have propagated (Freenet eventual consistency means the last UPDATE
-        // may not be visible immediately on a different node).
+        // Poll the root contract until all 6 transfers have propagated
+        // (Freenet eventual consistency means the last UPDATE may not be
+        // visible immediately on a different node).
         let expected_balance = 1_000_000 - (6 * 10_000);
         let expected_ledger_len = 7; // genesis credit + 6 debits (Gary, Emma, Iris, Alice, Bob, root)
-        let mut root_state: UserContractState;
-        let mut converged = false;
-        for attempt in 1..=10 {
-            let mut probe_check = connect_to_node_at(&node_url(3002)).await;
-            let root_bytes = cream_node_integration::wait_for_get(
-                &mut probe_check,
-                *h.root_contract_key.id(),
-                TIMEOUT,
-            )
-            .await
-            .expect("GET root contract");
-            root_state = serde_json::from_slice(&root_bytes).expect("deserialize root contract");
-
-            if root_state.balance_curds == expected_balance
-                && root_state.ledger.len() == expected_ledger_len
-            {
-                converged = true;
-                break;
-            }
-            if attempt < 10 {
-                println!(
-                    "  [RETRY {}/10] balance={} (want {}), ledger={} (want {}) — waiting 2s",
-                    attempt, root_state.balance_curds, expected_balance,
-                    root_state.ledger.len(), expected_ledger_len,
-                );
-                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-            } else {
-                panic!(
-                    "9: root balance did not converge after {} attempts: balance={} (want {}), ledger={} (want {})",
-                    attempt, root_state.balance_curds, expected_balance,
-                    root_state.ledger.len(), expected_ledger_len,
-                );
-            }
-        }
-        assert!(converged);
-
-        // Re-fetch the converged state for the cross-check below
         let mut probe = connect_to_node_at(&node_url(3002)).await;
-        let root_bytes = cream_node_integration::wait_for_get(
+        let root_state: UserContractState = converge(
             &mut probe,
             *h.root_contract_key.id(),
-            TIMEOUT,
+            "9: root contract",
+            &Expect::new()
+                .field("balance_curds", expected_balance, |s: &UserContractState| s.balance_curds)
+                .field("ledger.len()", expected_ledger_len, |s: &UserContractState| s.ledger.len()),
+            Poll::new(Duration::from_secs(20), Duration::from_secs(2)),
         )
-        .await
-        .expect("GET root contract (final)");
-        let root_state: UserContractState =
-            serde_json::from_slice(&root_bytes).expect("deserialize root contract");
+        .await;
 
         // Verify each debit has a matching credit on the recipient's user contract
         for debit in root_state.ledger.iter().filter(|t| t.kind == TransactionKind::Debit) {
@@ -929,6 +928,7 @@ async fn cumulative_node_tests() {
     run_step!(10, "fulfill_order_settles_escrow", h, {
         use cream_common::user_contract::UserContractState;
         use cream_common::wallet::{TransactionKind, WalletTransaction};
+        use cream_node_integration::convergence::{converge, Expect, Poll};
 
         // Snapshot root balance before this step
         let mut probe = connect_to_node_at(&node_url(3002)).await;
@@ -1007,6 +1007,9 @@ async fn cumulative_node_tests() {
             tx_ref: tx_ref.clone(),
             timestamp: now_str.clone(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         };
 
@@ -1038,6 +1041,9 @@ async fn cumulative_node_tests() {
             tx_ref: tx_ref.clone(),
             timestamp: now_str.clone(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         };
 
@@ -1058,8 +1064,9 @@ async fn cumulative_node_tests() {
         cream_node_integration::recv_matching(&mut probe, cream_node_integration::is_update_response, TIMEOUT)
             .await.expect("UpdateResponse for root escrow credit");
 
-        // Gary fulfills the order
-        h.gary.fulfill_order(&order_id).await;
+        // Gary proposes fulfillment; Alice counter-signs the receipt
+        h.gary.propose_fulfillment(&order_id).await;
+        h.alice.confirm_fulfillment(&order_id, &mut h.gary).await;
 
         // Alice receives the fulfilled notification (may get stale notifications first)
         loop {
@@ -1086,6 +1093,9 @@ async fn cumulative_node_tests() {
             tx_ref: settle_tx_ref.clone(),
             timestamp: settle_now_str.clone(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         };
 
@@ -1117,6 +1127,9 @@ async fn cumulative_node_tests() {
             tx_ref: settle_tx_ref.clone(),
             timestamp: settle_now_str.clone(),
             lightning_payment_hash: None,
+            payer: None,
+            provenance: None,
+            device: None,
             extra: Default::default(),
         };
 
@@ -1140,28 +1153,28 @@ async fn cumulative_node_tests() {
         // Freenet may serve stale state from GET immediately after a successful
         // UpdateResponse on the same connection — the update is acknowledged but
         // not yet visible to reads. Retry with backoff.
-        let mut gary_final: UserContractState;
-        loop {
-            let gary_final_bytes = cream_node_integration::wait_for_get(&mut probe, *gary_uc_key.id(), TIMEOUT)
-                .await.expect("GET Gary final");
-            gary_final = serde_json::from_slice(&gary_final_bytes).unwrap();
-            if gary_final.balance_curds == gary_balance_before + deposit_amount {
-                break;
-            }
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-        }
+        converge(
+            &mut probe,
+            *gary_uc_key.id(),
+            "10: Gary's user contract",
+            &Expect::new().field(
+                "balance_curds",
+                gary_balance_before + deposit_amount,
+                |s: &UserContractState| s.balance_curds,
+            ),
+            Poll::default(),
+        )
+        .await;
 
         // Same stale-GET issue for root balance
-        let mut root_final: UserContractState;
-        loop {
-            let root_final_bytes = cream_node_integration::wait_for_get(&mut probe, *h.root_contract_key.id(), TIMEOUT)
-                .await.expect("GET root final");
-            root_final = serde_json::from_slice(&root_final_bytes).unwrap();
-            if root_final.balance_curds == root_balance_before {
-                break;
-            }
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-        }
+        converge(
+            &mut probe,
+            *h.root_contract_key.id(),
+            "10: root contract",
+            &Expect::new().field("balance_curds", root_balance_before, |s: &UserContractState| s.balance_curds),
+            Poll::default(),
+        )
+        .await;
 
         drop(probe);
     });
@@ -1360,5 +1373,121 @@ async fn cumulative_node_tests() {
         println!("   Probe received market update notification with Iris added");
     });
 
+    // ═══════════════════════════════════════════════════════════════════
+    // Step 13: Customer onboarding via rendezvous
+    //
+    // Gary and Emma register with an in-memory rendezvous service. A new
+    // customer, Olive, looks Gary up by name, connects to the node URL he
+    // advertises, subscribes to his storefront by the advertised key and
+    // places an order over her own connection. The order must then show up
+    // on Gary's side.
+    // ═══════════════════════════════════════════════════════════════════
+    run_step!(13, "customer_onboarding_via_rendezvous", h, {
+        use cream_node_integration::harness::{Customer, Rendezvous};
+
+        let mut rendezvous = Rendezvous::default();
+        rendezvous.register(&h.gary);
+        rendezvous.register(&h.emma);
+
+        let (mut olive, mut link) =
+            Customer::onboard_via_rendezvous("Olive", 10_000, &rendezvous, "Gary").await;
+        assert_eq!(link.entry.address, h.gary.node_url, "13: rendezvous should point at Gary's node");
+        assert_eq!(
+            link.storefront_key.id(),
+            h.gary.storefront_key.id(),
+            "13: rendezvous should resolve Gary's storefront"
+        );
+
+        let product = link
+            .storefront
+            .products
+            .values()
+            .find(|sp| sp.product.name == "Raw Milk")
+            .expect("13: Olive should see Gary's Raw Milk")
+            .product
+            .clone();
+        let order = make_dummy_order(
+            &product.id,
+            &olive.id,
+            DepositTier::Reserve2Days,
+            1,
+            product.price_curd,
+            chrono::Utc::now(),
+        );
+        let order_id = order.id.clone();
+        let deposit = order.deposit_amount;
+
+        olive
+            .place_order_via(&mut link, order)
+            .await
+            .expect("13: Olive can afford the deposit");
+        assert_eq!(olive.balance, 10_000 - deposit, "13: deposit deducted from Olive");
+        println!("   Olive placed order {} via Gary's node", order_id.0);
+
+        assert!(
+            h.gary.wait_for_order(&order_id.0).await,
+            "13: Olive's order should appear on Gary's storefront"
+        );
+        let placed = &h.gary.storefront.orders[&order_id];
+        assert_eq!(placed.customer, olive.id, "13: order belongs to Olive");
+        assert!(
+            matches!(placed.status, OrderStatus::Reserved { .. }),
+            "13: order should be Reserved, got {}",
+            placed.status
+        );
+        println!("   Gary sees Olive's order on his storefront");
+    });
+
+    // ═══════════════════════════════════════════════════════════════════
+    // Step 14: Invalid storefront updates are refused by the node, and
+    // leave Gary's storefront as it was
+    // ═══════════════════════════════════════════════════════════════════
+    run_step!(14, "invalid_updates_rejected", h, {
+        use cream_common::limits::MAX_DESCRIPTION_LEN;
+        use cream_node_integration::expect_update_rejected;
+
+        let mut probe = connect_to_node_at(&node_url(3002)).await;
+        let before_bytes = wait_for_get(&mut probe, *h.gary.storefront_key.id(), TIMEOUT)
+            .await
+            .expect("14: GET Gary's storefront");
+        let before: StorefrontState = serde_json::from_slice(&before_bytes).unwrap();
+
+        // Over the size limits
+        let mut oversized = before.clone();
+        oversized.info.description = "x".repeat(MAX_DESCRIPTION_LEN + 1);
+        let cause = expect_update_rejected(
+            &mut probe,
+            h.gary.storefront_key,
+            UpdateData::State(State::from(serde_json::to_vec(&oversized).unwrap())),
+            TIMEOUT,
+        )
+        .await;
+        println!("   Oversized description rejected: {cause}");
+
+        // A bumped info seq with no writer stamp to back it
+        let mut unstamped = before.clone();
+        unstamped.info.seq += 1;
+        unstamped.info.provenance = None;
+        unstamped.info.description = "Hijacked".into();
+        let cause = expect_update_rejected(
+            &mut probe,
+            h.gary.storefront_key,
+            UpdateData::State(State::from(serde_json::to_vec(&unstamped).unwrap())),
+            TIMEOUT,
+        )
+        .await;
+        println!("   Unstamped info rejected: {cause}");
+
+        let after_bytes = wait_for_get(&mut probe, *h.gary.storefront_key.id(), TIMEOUT)
+            .await
+            .expect("14: GET Gary's storefront after rejections");
+        let after: StorefrontState = serde_json::from_slice(&after_bytes).unwrap();
+        assert_eq!(
+            after.info.description, before.info.description,
+            "14: rejected updates must not change the storefront"
+        );
+        drop(probe);
+    });
+
     println!("\n══ All node-integration steps passed ══");
 }
@@ -11,15 +11,17 @@ use cream_common::directory::DirectoryState;
 use cream_common::identity::UserId;
 use cream_common::location::GeoLocation;
 use cream_common::order::DepositTier;
+use cream_common::retry::RetryPolicy;
 use cream_common::storefront::{StorefrontInfo, StorefrontState};
 use ed25519_dalek::VerifyingKey;
 use freenet_stdlib::client_api::{ClientRequest, ContractRequest, WebApi};
 use freenet_stdlib::prelude::*;
 
+use cream_node_integration::fixtures::{generate, FixtureSpec};
 use cream_node_integration::{
     connect_to_node_at, extract_notification_bytes,
     is_put_response, is_subscribe_success, is_update_notification, is_update_response,
-    make_directory_contract, make_directory_entry, make_dummy_order,
+    make_directory_contract, make_dummy_order,
     make_dummy_product, make_dummy_user, make_storefront_contract, node_url, recv_matching,
     timed_recv_matching, timed_wait_for_get, wait_for_get, wait_for_put,
 };
@@ -41,6 +43,9 @@ async fn update_with_retry(
     data: UpdateData<'static>,
     label: &str,
 ) {
+    let mut backoff = RetryPolicy::fixed(UPDATE_RETRY_DELAY)
+        .with_max_attempts(UPDATE_RETRIES)
+        .backoff(rand::random());
     for attempt in 1..=UPDATE_RETRIES {
         api.send(ClientRequest::ContractOp(ContractRequest::Update {
             key,
@@ -60,12 +65,12 @@ async fn update_with_retry(
                 return;
             }
             None => {
-                if attempt < UPDATE_RETRIES {
+                if let Some(delay) = backoff.next_delay() {
                     println!(
-                        "  [RETRY] {label}: Update attempt {attempt} failed, retrying in {}s...",
-                        UPDATE_RETRY_DELAY.as_secs()
+                        "  [RETRY] {label}: Update attempt {attempt} failed, retrying in {:?}...",
+                        delay
                     );
-                    tokio::time::sleep(UPDATE_RETRY_DELAY).await;
+                    tokio::time::sleep(delay).await;
                 }
             }
         }
@@ -102,10 +107,21 @@ async fn setup_storefront_on_port(
             email: None,
             address: None,
             market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
             extra: Default::default(),
         },
         products: BTreeMap::new(),
         orders: BTreeMap::new(),
+        threads: BTreeMap::new(),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
         extra: Default::default(),
     };
 
@@ -393,6 +409,9 @@ async fn rapid_fire_updates() {
     // Final GET from port 3003 to confirm all 10 products are present
     // Retry a few times since propagation may lag
     let mut all_present = false;
+    let mut backoff = RetryPolicy::fixed(Duration::from_secs(3))
+        .with_max_attempts(5)
+        .backoff(rand::random());
     for attempt in 1..=5 {
         let mut verifier = connect_to_node_at(&node_url(3003)).await;
         let (bytes, latency) = timed_wait_for_get(&mut verifier, *sf_key.id(), STRESS_TIMEOUT)
@@ -407,13 +426,14 @@ async fn rapid_fire_updates() {
             all_present = true;
             break;
         }
-        if attempt < 5 {
+        if let Some(delay) = backoff.next_delay() {
             println!(
-                "  [RETRY] Final GET attempt {attempt}: only {}/{} products present, retrying in 3s...",
+                "  [RETRY] Final GET attempt {attempt}: only {}/{} products present, retrying in {:?}...",
                 final_sf.products.len(),
-                added_product_ids.len()
+                added_product_ids.len(),
+                delay
             );
-            tokio::time::sleep(Duration::from_secs(3)).await;
+            tokio::time::sleep(delay).await;
         } else {
             panic!(
                 "After {attempt} attempts, only {}/{} products present on port 3003",
@@ -736,32 +756,18 @@ async fn directory_contention() {
     // Cross-node broadcast propagation is tested separately by subscription_fanout
     // and rapid_fire_updates.
     let dir_port = 3002;
-    let supplier_names = ["DirAlpha", "DirBeta", "DirGamma", "DirDelta"];
+    let fixtures = generate(&FixtureSpec::new(0xD1, 4, 0, 0));
+    let supplier_names: Vec<String> = fixtures.suppliers.iter().map(|s| s.name.clone()).collect();
     let mut reg_handles = Vec::new();
 
-    for (i, name) in supplier_names.iter().enumerate() {
+    for supplier in fixtures.suppliers {
         let dk = dir_key;
-        let name = name.to_string();
+        let name = supplier.name.clone();
 
         reg_handles.push(tokio::spawn(async move {
-            let (supplier_id, vk) = make_dummy_user(&name);
-            let (_, sf_key) = make_storefront_contract(&vk);
-
-            let entry = make_directory_entry(
-                &supplier_id,
-                &name,
-                &format!("{name}'s dairy"),
-                "2000",
-                "Sydney",
-                GeoLocation::new(-33.87 + (i as f64 * 0.01), 151.21),
-                sf_key,
-                None,
-                None,
-            );
-
             let mut entries = BTreeMap::new();
-            entries.insert(supplier_id, entry);
-            let delta = DirectoryState { entries, extra: Default::default() };
+            entries.insert(supplier.id.clone(), supplier.directory_entry());
+            let delta = DirectoryState { entries, ..Default::default() };
             let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
             let mut api = connect_to_node_at(&node_url(dir_port)).await;
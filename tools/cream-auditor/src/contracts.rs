@@ -0,0 +1,44 @@
+//! Contract keys the auditor derives itself.
+//!
+//! The directory contracts are found from their code, and so are the user
+//! contracts of order customers, which no listing points to. Both WASMs are
+//! embedded, so the auditor must be built after the contracts.
+
+use std::sync::Arc;
+
+use cream_common::directory::{DirectoryShard, DirectoryShardParameters};
+use cream_common::user_contract::UserContractParameters;
+use freenet_stdlib::prelude::*;
+
+const DIRECTORY_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm");
+const USER_CONTRACT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm");
+
+fn contract_key(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractKey {
+    let code = ContractCode::from(wasm_bytes.to_vec());
+    *WrappedContract::new(Arc::new(code), params).key()
+}
+
+/// The legacy global directory and every regional shard, with a label each.
+pub fn directory_keys() -> Vec<(String, ContractKey)> {
+    std::iter::once(("directory".to_string(), contract_key(DIRECTORY_WASM, Parameters::from(vec![]))))
+        .chain(DirectoryShard::ALL.iter().map(|shard| {
+            let key = contract_key(
+                DIRECTORY_WASM,
+                Parameters::from(DirectoryShardParameters::new(*shard).to_bytes()),
+            );
+            (format!("directory shard {}", shard.label()), key)
+        }))
+        .collect()
+}
+
+/// The (ordinary, spending-key-free) user contract owned by `owner`.
+pub fn user_contract_key(owner: &ed25519_dalek::VerifyingKey) -> ContractKey {
+    let params = UserContractParameters {
+        owner: *owner,
+        spending_key: None,
+    };
+    let params_bytes = serde_json::to_vec(&params).unwrap();
+    contract_key(USER_CONTRACT_WASM, Parameters::from(params_bytes))
+}
@@ -0,0 +1,247 @@
+//! CREAM state integrity auditor.
+//!
+//! Walks every contract it can reach from the directory on a node
+//! (`--node-url`): the directory and its shards, each listed storefront, and
+//! the user contracts of suppliers and of every customer with an order. Each
+//! state is checked with [`cream_common::audit`] — signatures and stamps,
+//! cached balances, duplicate entries — and then the ledgers are checked
+//! against each other for double entry. With `--root-key` the root user
+//! contract is included, so issuance pairs up too.
+//!
+//! The report is JSON ([`AuditReport`]) on stdout or in `--output`. Exits 1
+//! if the report has errors and 2 if the node can't be reached, so it can run
+//! nightly from cron or CI.
+//!
+//! Build without the `dev` feature: dev builds don't check signatures.
+
+mod contracts;
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use chrono::Utc;
+use clap::Parser;
+use cream_common::audit::{self, AuditReport, Check, Finding};
+use cream_common::directory::DirectoryState;
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+use ed25519_dalek::VerifyingKey;
+use freenet_stdlib::client_api::{ClientRequest, ContractRequest, ContractResponse, HostResponse, WebApi};
+use freenet_stdlib::prelude::ContractKey;
+use serde::de::DeserializeOwned;
+
+#[derive(Parser)]
+#[command(name = "cream-auditor", about = "Check CREAM contract states for integrity")]
+struct Cli {
+    /// WebSocket URL of the Freenet node to read from
+    #[arg(long, default_value = "ws://localhost:3001/v1/contract/command?encodingProtocol=native")]
+    node_url: String,
+
+    /// Root user's verifying key (the guardians' group key), hex. Includes
+    /// the root user contract in the audit.
+    #[arg(long)]
+    root_key: Option<String>,
+
+    /// Write the report here instead of stdout
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Seconds to wait for each contract
+    #[arg(long, default_value_t = 30)]
+    timeout_secs: u64,
+}
+
+/// A read-only client that GETs one contract at a time.
+struct Node {
+    api: WebApi,
+    timeout: Duration,
+}
+
+enum Fetched<T> {
+    State(T),
+    /// The node doesn't have the contract (or didn't answer in time).
+    Missing,
+    Unparseable(String),
+}
+
+impl Node {
+    async fn get<T: DeserializeOwned>(&mut self, key: &ContractKey) -> Result<Fetched<T>, String> {
+        self.api
+            .send(ClientRequest::ContractOp(ContractRequest::Get {
+                key: *key.id(),
+                return_contract_code: false,
+                subscribe: false,
+                blocking_subscribe: false,
+            }))
+            .await
+            .map_err(|e| format!("failed to GET {}: {}", key, e))?;
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        loop {
+            let response = match tokio::time::timeout_at(deadline, self.api.recv()).await {
+                Ok(response) => response.map_err(|e| format!("connection error: {}", e))?,
+                Err(_) => return Ok(Fetched::Missing),
+            };
+            match response {
+                HostResponse::ContractResponse(ContractResponse::GetResponse { key: got, state, .. })
+                    if got.id() == key.id() =>
+                {
+                    return Ok(match serde_json::from_slice(state.as_ref()) {
+                        Ok(parsed) => Fetched::State(parsed),
+                        Err(e) => Fetched::Unparseable(e.to_string()),
+                    });
+                }
+                HostResponse::ContractResponse(ContractResponse::NotFound { instance_id })
+                    if instance_id == *key.id() =>
+                {
+                    return Ok(Fetched::Missing);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn parse_key(hex: &str) -> Result<VerifyingKey, String> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err("expected 64 hex digits".to_string());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|e| e.to_string())?;
+    }
+    VerifyingKey::from_bytes(&bytes).map_err(|e| e.to_string())
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let root_key = cli.root_key.as_deref().map(|hex| {
+        parse_key(hex).unwrap_or_else(|e| {
+            eprintln!("Invalid --root-key: {}", e);
+            std::process::exit(2);
+        })
+    });
+
+    let api = match tokio_tungstenite::connect_async(&cli.node_url).await {
+        Ok((conn, _)) => WebApi::start(conn),
+        Err(e) => {
+            eprintln!("Auditor: cannot connect to {}: {}", cli.node_url, e);
+            std::process::exit(2);
+        }
+    };
+    let mut node = Node {
+        api,
+        timeout: Duration::from_secs(cli.timeout_secs),
+    };
+
+    let report = match run_audit(&mut node, root_key).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Auditor: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let json = serde_json::to_string_pretty(&report).expect("serialization should not fail");
+    match &cli.output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(path, &json) {
+                eprintln!("Auditor: failed to write {}: {}", path.display(), e);
+                std::process::exit(2);
+            }
+        }
+        None => println!("{}", json),
+    }
+    eprintln!(
+        "Auditor: {} directories, {} storefronts, {} user contracts — {} errors, {} warnings",
+        report.directories,
+        report.storefronts,
+        report.user_contracts,
+        report.errors(),
+        report.findings.len() - report.errors(),
+    );
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+}
+
+/// Directory → storefronts → user contracts, then the ledgers together.
+async fn run_audit(node: &mut Node, root_key: Option<VerifyingKey>) -> Result<AuditReport, String> {
+    let mut report = AuditReport::new(Utc::now());
+
+    // ── Directory and shards ──
+    let mut directory = DirectoryState::default();
+    for (label, key) in contracts::directory_keys() {
+        match node.get::<DirectoryState>(&key).await? {
+            Fetched::State(state) => {
+                report.directories += 1;
+                report.findings.extend(audit::audit_directory(&label, &state));
+                directory.merge(state);
+            }
+            // Shards with no suppliers yet were never created
+            Fetched::Missing => {}
+            Fetched::Unparseable(e) => report.findings.push(Finding::error(&label, Check::Unreadable, e)),
+        }
+    }
+
+    // ── Storefronts, collecting the user contracts they lead to ──
+    // Keyed by contract key so a supplier who is also a customer is audited once
+    let mut users: BTreeMap<String, (String, ContractKey, VerifyingKey)> = BTreeMap::new();
+    if let Some(root) = root_key {
+        let key = contracts::user_contract_key(&root);
+        users.insert(key.to_string(), ("root user contract".to_string(), key, root));
+    }
+    for entry in directory.entries.values() {
+        let label = format!("storefront {}", entry.name);
+        let owner = entry.supplier.0;
+        let user_key = entry.user_contract_key.unwrap_or_else(|| contracts::user_contract_key(&owner));
+        users
+            .entry(user_key.to_string())
+            .or_insert_with(|| (format!("user contract of {}", entry.name), user_key, owner));
+        match node.get::<StorefrontState>(&entry.storefront_key).await? {
+            Fetched::State(storefront) => {
+                report.storefronts += 1;
+                report.findings.extend(audit::audit_storefront(&label, &storefront, &owner));
+                for order in storefront.orders.values() {
+                    let customer = order.customer.0;
+                    let key = contracts::user_contract_key(&customer);
+                    users
+                        .entry(key.to_string())
+                        .or_insert_with(|| (format!("user contract of customer {}", order.customer), key, customer));
+                }
+            }
+            Fetched::Missing => report.findings.push(Finding::warning(
+                &label,
+                Check::Unreadable,
+                "listed but not found on the node".to_string(),
+            )),
+            Fetched::Unparseable(e) => report.findings.push(Finding::error(&label, Check::Unreadable, e)),
+        }
+    }
+
+    // ── User contracts ──
+    let mut ledgers: Vec<(String, UserContractState)> = Vec::new();
+    for (label, key, owner) in users.into_values() {
+        match node.get::<UserContractState>(&key).await? {
+            Fetched::State(state) => {
+                report.user_contracts += 1;
+                report.findings.extend(audit::audit_user_contract(&label, &state, &owner));
+                ledgers.push((label, state));
+            }
+            Fetched::Missing => report.findings.push(Finding::warning(
+                &label,
+                Check::Unreadable,
+                "not found on the node".to_string(),
+            )),
+            Fetched::Unparseable(e) => report.findings.push(Finding::error(&label, Check::Unreadable, e)),
+        }
+    }
+    let ledgers: Vec<(&str, &UserContractState)> =
+        ledgers.iter().map(|(label, state)| (label.as_str(), state)).collect();
+    report.findings.extend(audit::audit_ledgers(&ledgers));
+
+    report.finished_at = Utc::now();
+    Ok(report)
+}
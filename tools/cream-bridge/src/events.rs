@@ -0,0 +1,124 @@
+//! What happened in a storefront between two of its states.
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+
+use cream_common::message::OrderMessage;
+use cream_common::order::{Order, OrderStatus};
+use cream_common::storefront::StorefrontState;
+
+/// The kinds of event the bridge forwards, as named in webhook payloads and
+/// `--webhook` filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    OrderPlaced,
+    OrderCancelled,
+    MessagePosted,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 3] = [EventKind::OrderPlaced, EventKind::OrderCancelled, EventKind::MessagePosted];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EventKind::OrderPlaced => "order.placed",
+            EventKind::OrderCancelled => "order.cancelled",
+            EventKind::MessagePosted => "message.posted",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        EventKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+impl fmt::Display for EventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// One event, ready to POST.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub kind: EventKind,
+    /// Stable across retries and restarts ("order.placed:o-…"), so receivers
+    /// can drop duplicates.
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    /// The JSON body.
+    pub payload: Value,
+}
+
+impl Event {
+    fn new(kind: EventKind, subject: &str, occurred_at: DateTime<Utc>, storefront: &StorefrontState, data: Value) -> Self {
+        let id = format!("{}:{}", kind, subject);
+        let payload = json!({
+            "id": id,
+            "event": kind.name(),
+            "occurred_at": occurred_at,
+            "storefront": storefront.info.name,
+            "data": data,
+        });
+        Event { kind, id, occurred_at, payload }
+    }
+}
+
+/// Events that take `old` to `new`: orders that appeared, orders that became
+/// cancelled, and messages that appeared, oldest first.
+pub fn between(old: &StorefrontState, new: &StorefrontState) -> Vec<Event> {
+    let mut events = Vec::new();
+    for (id, order) in &new.orders {
+        match old.orders.get(id) {
+            None => events.push(Event::new(EventKind::OrderPlaced, &id.0, order.created_at, new, order_json(new, order))),
+            Some(before) if before.status != OrderStatus::Cancelled && order.status == OrderStatus::Cancelled => {
+                let at = order.status_history.last().map_or_else(Utc::now, |change| change.at);
+                events.push(Event::new(EventKind::OrderCancelled, &id.0, at, new, order_json(new, order)));
+            }
+            Some(_) => {}
+        }
+    }
+    for (order_id, thread) in &new.threads {
+        let held = old.threads.get(order_id);
+        for (message_id, message) in &thread.messages {
+            if held.is_none_or(|t| !t.messages.contains_key(message_id)) {
+                let data = message_json(new, message);
+                events.push(Event::new(EventKind::MessagePosted, &message_id.0, message.created_at, new, data));
+            }
+        }
+    }
+    events.sort_by_key(|event| event.occurred_at);
+    events
+}
+
+fn order_json(storefront: &StorefrontState, order: &Order) -> Value {
+    let product = storefront.products.get(&order.product_id).map(|sp| sp.product.name.clone());
+    json!({
+        "order_id": order.id.0,
+        "product_id": order.product_id.0,
+        "product_name": product,
+        "customer": order.customer.to_string(),
+        "quantity": order.quantity,
+        "total_price": order.current_total_price(),
+        "deposit_amount": order.deposit_amount,
+        "status": order.status.label(),
+        "created_at": order.created_at,
+        "delivery_address": order.delivery.as_ref().map(|d| format!("{} {}", d.address, d.postcode)),
+        "refund_amount": order.refund_amount,
+    })
+}
+
+fn message_json(storefront: &StorefrontState, message: &OrderMessage) -> Value {
+    let from_customer = storefront.orders.get(&message.order_id).is_some_and(|o| o.customer == message.author);
+    json!({
+        "message_id": message.id.0,
+        "order_id": message.order_id.0,
+        "author": message.author.to_string(),
+        "from_customer": from_customer,
+        "body": message.body,
+        "created_at": message.created_at,
+        "attachment": message.attachment.as_ref().map(|a| a.file_name.clone()),
+    })
+}
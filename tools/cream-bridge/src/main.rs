@@ -0,0 +1,156 @@
+//! CREAM event bridge: a supplier's storefront, as webhooks.
+//!
+//! Follows one storefront contract (`--storefront`, its instance id) on a
+//! node and POSTs a JSON webhook for each new order, cancelled order and new
+//! order message to the URLs given with `--webhook`, so a farm's point of
+//! sale, mailer or spreadsheet can react without running CREAM itself:
+//!
+//! ```text
+//! cream-bridge --storefront 7Hj…Qe \
+//!     --webhook https://pos.example/cream \
+//!     --webhook message.posted=https://mail.example/hooks/cream
+//! ```
+//!
+//! Set `CREAM_BRIDGE_SECRET` to have every request signed (see [`webhook`]).
+//! Each payload carries an `id` that is the same on every retry, for the
+//! receiver to drop duplicates by.
+//!
+//! Events are found by comparing each state the node sends with the one
+//! before. The first state fetched is the baseline, so nothing already in
+//! the storefront when the bridge starts is sent. After a lost connection
+//! the bridge reconnects and compares against the last state it saw, so
+//! what happened in between is still sent.
+
+mod events;
+mod webhook;
+
+use std::time::Duration;
+
+use clap::Parser;
+use cream_client::CreamClient;
+use cream_common::retry::RetryPolicy;
+use cream_common::storefront::StorefrontState;
+use freenet_stdlib::client_api::WebApi;
+use freenet_stdlib::prelude::ContractInstanceId;
+
+use webhook::{Dispatcher, Webhook};
+
+#[derive(Parser)]
+#[command(name = "cream-bridge", about = "Forward a CREAM storefront's orders and messages to webhooks")]
+struct Cli {
+    /// WebSocket URL of the Freenet node to follow the storefront through
+    #[arg(long, default_value = "ws://localhost:3001/v1/contract/command?encodingProtocol=native")]
+    node_url: String,
+
+    /// Instance id (Base58) of the storefront contract to follow
+    #[arg(long)]
+    storefront: String,
+
+    /// Where to POST events: a URL for every event, or
+    /// `EVENT[,EVENT...]=URL` for some (order.placed, order.cancelled,
+    /// message.posted). Repeat for more endpoints.
+    #[arg(long = "webhook", required = true)]
+    webhooks: Vec<Webhook>,
+
+    /// Attempts per delivery before it is dropped
+    #[arg(long, default_value_t = 8)]
+    max_attempts: u32,
+}
+
+/// Backoff between attempts to reach the node: 1s doubling to at most 60s.
+fn reconnect_policy() -> RetryPolicy {
+    RetryPolicy::exponential(Duration::from_secs(1), Duration::from_secs(60))
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let storefront = ContractInstanceId::from_bytes(&cli.storefront).unwrap_or_else(|e| {
+        eprintln!("Invalid --storefront: {}", e);
+        std::process::exit(2);
+    });
+    let secret = std::env::var("CREAM_BRIDGE_SECRET").ok().filter(|s| !s.is_empty());
+    if secret.is_none() {
+        eprintln!("WARNING: CREAM_BRIDGE_SECRET not set, webhooks will be sent unsigned");
+    }
+    for webhook in &cli.webhooks {
+        let events = if webhook.events.is_empty() {
+            "every event".to_string()
+        } else {
+            webhook.events.iter().map(|kind| kind.name()).collect::<Vec<_>>().join(", ")
+        };
+        println!("Webhook {}: {}", webhook.url, events);
+    }
+    let dispatcher = Dispatcher::start(cli.webhooks, secret, cli.max_attempts);
+
+    let mut last: Option<StorefrontState> = None;
+    let mut backoff = reconnect_policy().backoff(rand::random());
+    loop {
+        let reached = follow(&cli.node_url, storefront, &mut last, &dispatcher).await;
+        if reached {
+            backoff.reset();
+        }
+        let delay = backoff.next_delay().unwrap_or(Duration::from_secs(60));
+        eprintln!("Reconnecting in {:.0?}", delay);
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Follow `storefront` until the connection fails, sending the events
+/// between each state and `last`. Returns whether the node was reached.
+async fn follow(
+    node_url: &str,
+    storefront: ContractInstanceId,
+    last: &mut Option<StorefrontState>,
+    dispatcher: &Dispatcher,
+) -> bool {
+    let api = match tokio_tungstenite::connect_async(node_url).await {
+        Ok((conn, _)) => WebApi::start(conn),
+        Err(e) => {
+            eprintln!("ERROR: Cannot connect to {}: {}", node_url, e);
+            return false;
+        }
+    };
+    let mut client = CreamClient::new(api);
+
+    let mut current = match client.get_storefront(storefront).await {
+        Ok(current) => current,
+        Err(e) => {
+            eprintln!("ERROR: Cannot fetch storefront {}: {}", storefront, e);
+            return true;
+        }
+    };
+    match last.as_ref() {
+        Some(previous) => send(dispatcher, previous, &current),
+        None => println!(
+            "Following {} ({} orders); sending events from now on",
+            current.info.name,
+            current.orders.len()
+        ),
+    }
+    *last = Some(current.clone());
+
+    if let Err(e) = client.subscribe(storefront).await {
+        eprintln!("ERROR: Cannot subscribe to storefront {}: {}", storefront, e);
+        return true;
+    }
+    loop {
+        let update = match client.next_update::<StorefrontState>(storefront).await {
+            Ok(update) => update,
+            Err(e) => {
+                eprintln!("ERROR: Lost storefront {}: {}", storefront, e);
+                return true;
+            }
+        };
+        let previous = current.clone();
+        current.merge(update);
+        send(dispatcher, &previous, &current);
+        *last = Some(current.clone());
+    }
+}
+
+fn send(dispatcher: &Dispatcher, previous: &StorefrontState, current: &StorefrontState) {
+    for event in events::between(previous, current) {
+        dispatcher.dispatch(&event);
+    }
+}
@@ -0,0 +1,172 @@
+//! Delivering events to webhook URLs.
+//!
+//! Each webhook has its own queue and worker, so a slow or failing endpoint
+//! holds up only its own deliveries, and each endpoint sees events in order.
+//! A delivery is retried with backoff until the endpoint answers 2xx or
+//! `--max-attempts` is used up; then it is logged and dropped.
+//!
+//! With a secret set, every request carries
+//! `X-Cream-Signature: sha256=<hex>`, the HMAC-SHA256 of
+//! `<X-Cream-Timestamp>.<body>` under the secret, so the receiver can check
+//! the request came from this bridge and isn't a replay.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::sync::mpsc;
+
+use cream_common::retry::{retry, CancellationToken, RetryPolicy};
+
+use crate::events::{Event, EventKind};
+
+/// A URL and the events it wants, from `--webhook [EVENT,...=]URL`.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    /// Empty for every event.
+    pub events: Vec<EventKind>,
+}
+
+impl Webhook {
+    pub fn wants(&self, kind: EventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+impl FromStr for Webhook {
+    type Err = String;
+
+    /// `https://…` for every event, or `order.placed,order.cancelled=https://…`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (events, url) = match s.split_once('=') {
+            Some((events, url)) if !events.contains("://") => (events, url),
+            _ => ("", s),
+        };
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(format!("not an http(s) URL: {url}"));
+        }
+        let events = events
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                EventKind::from_name(name).ok_or_else(|| {
+                    let known: Vec<_> = EventKind::ALL.iter().map(|k| k.name()).collect();
+                    format!("unknown event {name} (expected one of {})", known.join(", "))
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Webhook { url: url.to_string(), events })
+    }
+}
+
+/// Why one delivery attempt failed.
+#[derive(Debug)]
+pub enum DeliveryError {
+    Request(reqwest::Error),
+    Status(reqwest::StatusCode),
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::Request(e) => write!(f, "request failed: {}", e),
+            DeliveryError::Status(status) => write!(f, "endpoint answered {}", status),
+        }
+    }
+}
+
+/// Sends each event to the webhooks that want it.
+pub struct Dispatcher {
+    queues: Vec<(Webhook, mpsc::UnboundedSender<Event>)>,
+}
+
+impl Dispatcher {
+    /// Start a delivery worker per webhook.
+    pub fn start(webhooks: Vec<Webhook>, secret: Option<String>, max_attempts: u32) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("HTTP client should build");
+        let policy = RetryPolicy::exponential(Duration::from_secs(2), Duration::from_secs(300))
+            .with_max_attempts(max_attempts);
+        let queues = webhooks
+            .into_iter()
+            .map(|webhook| {
+                let (send, receive) = mpsc::unbounded_channel();
+                tokio::spawn(deliver_all(webhook.url.clone(), receive, client.clone(), secret.clone(), policy.clone()));
+                (webhook, send)
+            })
+            .collect();
+        Dispatcher { queues }
+    }
+
+    pub fn dispatch(&self, event: &Event) {
+        for (webhook, queue) in &self.queues {
+            if webhook.wants(event.kind) && queue.send(event.clone()).is_err() {
+                eprintln!("ERROR: Delivery worker for {} has stopped", webhook.url);
+            }
+        }
+    }
+}
+
+async fn deliver_all(
+    url: String,
+    mut events: mpsc::UnboundedReceiver<Event>,
+    client: reqwest::Client,
+    secret: Option<String>,
+    policy: RetryPolicy,
+) {
+    let never = CancellationToken::new();
+    while let Some(event) = events.recv().await {
+        let body = serde_json::to_vec(&event.payload).expect("serialization should not fail");
+        let outcome = retry(policy.backoff(rand::random()), &never, tokio::time::sleep, |attempt| {
+            if attempt > 0 {
+                eprintln!("Retrying {} to {} (attempt {})", event.id, url, attempt + 1);
+            }
+            post(&client, &url, &event, &body, secret.as_deref())
+        })
+        .await;
+        match outcome {
+            Ok(()) => println!("Delivered {} to {}", event.id, url),
+            Err(e) => eprintln!("ERROR: Dropped {} for {}: {}", event.id, url, e),
+        }
+    }
+}
+
+async fn post(
+    client: &reqwest::Client,
+    url: &str,
+    event: &Event,
+    body: &[u8],
+    secret: Option<&str>,
+) -> Result<(), DeliveryError> {
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let mut request = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Cream-Event", event.kind.name())
+        .header("X-Cream-Delivery", &event.id)
+        .header("X-Cream-Timestamp", &timestamp);
+    if let Some(secret) = secret {
+        request = request.header("X-Cream-Signature", format!("sha256={}", sign(secret, &timestamp, body)));
+    }
+    let response = request.body(body.to_vec()).send().await.map_err(DeliveryError::Request)?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(DeliveryError::Status(response.status()))
+    }
+}
+
+/// Hex HMAC-SHA256 of `<timestamp>.<body>` under `secret`.
+fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.finalize().into_bytes().iter().map(|b| format!("{b:02x}")).collect()
+}
@@ -0,0 +1,45 @@
+//! A supplier's contracts, rebuilt from their key.
+//!
+//! The storefront, user contract and inbox are each parameterised by the
+//! owner's verifying key alone, so their keys follow from it and the code.
+//! The WASMs are embedded, so the CLI must be built after the contracts.
+
+use std::sync::Arc;
+
+use cream_common::inbox::InboxParameters;
+use cream_common::storefront::StorefrontParameters;
+use cream_common::user_contract::UserContractParameters;
+use ed25519_dalek::VerifyingKey;
+use freenet_stdlib::prelude::*;
+
+const STOREFRONT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_storefront_contract.wasm");
+const USER_CONTRACT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm");
+const INBOX_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_inbox_contract.wasm");
+
+fn make_contract(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractContainer {
+    let code = ContractCode::from(wasm_bytes.to_vec());
+    let wrapped = WrappedContract::new(Arc::new(code), params);
+    ContractContainer::Wasm(ContractWasmAPIVersion::V1(wrapped))
+}
+
+pub fn storefront(owner: &VerifyingKey) -> ContractContainer {
+    let params = serde_json::to_vec(&StorefrontParameters { owner: *owner }).unwrap();
+    make_contract(STOREFRONT_WASM, Parameters::from(params))
+}
+
+/// The supplier's (spending-key-free) user contract.
+pub fn user_contract(owner: &VerifyingKey) -> ContractContainer {
+    let params = UserContractParameters {
+        owner: *owner,
+        spending_key: None,
+    };
+    make_contract(USER_CONTRACT_WASM, Parameters::from(serde_json::to_vec(&params).unwrap()))
+}
+
+pub fn inbox(owner: &VerifyingKey) -> ContractContainer {
+    let params = serde_json::to_vec(&InboxParameters { owner: *owner }).unwrap();
+    make_contract(INBOX_WASM, Parameters::from(params))
+}
@@ -0,0 +1,292 @@
+//! CREAM command-line tool.
+//!
+//! `cream-cli export` and `cream-cli import` move a supplier to a new Freenet
+//! node (see [`cream_common::migration`]):
+//!
+//! ```text
+//! CREAM_PASSWORD=… cream-cli export --name "Gary" --node-url ws://old:3001/… --out gary.json
+//! CREAM_PASSWORD=… cream-cli import --name "Gary" --node-url ws://new:3001/… --bundle gary.json
+//! ```
+//!
+//! Export fetches the supplier's storefront, user contract and inbox from the
+//! old node into a bundle file. Import checks the bundle is the supplier's,
+//! rebuilds each contract from their key and checks it keeps its key, PUTs the
+//! states on the new node and reads them back, then re-registers with the
+//! rendezvous service at the new address.
+//!
+//! The supplier's key is derived from `--name` and the password in
+//! `CREAM_PASSWORD`, as when logging in to the UI.
+
+mod contracts;
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use cream_client::CreamClient;
+use cream_common::identity::{derive_user_signing_key, UserId};
+use cream_common::migration::{check_key, rendezvous_name, MigrationBundle, RendezvousRecord};
+use ed25519_dalek::SigningKey;
+use freenet_stdlib::client_api::WebApi;
+
+const DEFAULT_NODE_URL: &str = "ws://localhost:3001/v1/contract/command?encodingProtocol=native";
+
+#[derive(Parser)]
+#[command(name = "cream-cli", about = "CREAM command-line tool")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Export a supplier's contract states from a node to a migration bundle
+    Export {
+        /// Supplier name, as used to log in
+        #[arg(long)]
+        name: String,
+        /// WebSocket URL of the node to export from
+        #[arg(long, default_value = DEFAULT_NODE_URL)]
+        node_url: String,
+        /// Bundle file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Re-PUT a migration bundle on a new node and point the rendezvous
+    /// service at it
+    Import {
+        /// Supplier name, as used to log in
+        #[arg(long)]
+        name: String,
+        /// WebSocket URL of the node to import into
+        #[arg(long, default_value = DEFAULT_NODE_URL)]
+        node_url: String,
+        /// Bundle file written by `export`
+        #[arg(long)]
+        bundle: PathBuf,
+        /// Address customers reach the new node at, for the rendezvous
+        /// record (default: --node-url)
+        #[arg(long)]
+        address: Option<String>,
+        /// Rendezvous service URL
+        #[arg(long, default_value = "https://cream-rendezvous.workers.dev")]
+        rendezvous_url: String,
+        /// Leave the rendezvous record alone
+        #[arg(long)]
+        no_rendezvous: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Export { name, node_url, out } => export(&name, &node_url, &out).await,
+        Command::Import {
+            name,
+            node_url,
+            bundle,
+            address,
+            rendezvous_url,
+            no_rendezvous,
+        } => {
+            let rendezvous = (!no_rendezvous).then(|| (rendezvous_url, address.unwrap_or_else(|| node_url.clone())));
+            import(&name, &node_url, &bundle, rendezvous).await
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("ERROR: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// The supplier's signing key, from `name` and `CREAM_PASSWORD`.
+fn supplier_key(name: &str) -> Result<SigningKey, String> {
+    let password = std::env::var("CREAM_PASSWORD")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .ok_or("Set CREAM_PASSWORD to the supplier's password")?;
+    Ok(derive_user_signing_key(name, &password))
+}
+
+async fn connect(node_url: &str) -> Result<CreamClient<WebApi>, String> {
+    let (conn, _) = tokio_tungstenite::connect_async(node_url)
+        .await
+        .map_err(|e| format!("Cannot connect to {}: {}", node_url, e))?;
+    Ok(CreamClient::new(WebApi::start(conn)))
+}
+
+async fn export(name: &str, node_url: &str, out: &Path) -> Result<(), String> {
+    let key = supplier_key(name)?;
+    let owner = key.verifying_key();
+    let mut client = connect(node_url).await?;
+
+    let sf_key = contracts::storefront(&owner).key();
+    let storefront = client
+        .get_storefront(*sf_key.id())
+        .await
+        .map_err(|e| format!("Cannot fetch storefront {}: {}", sf_key, e))?;
+    println!("Storefront {}: {} products, {} orders", sf_key, storefront.products.len(), storefront.orders.len());
+    let mut bundle = MigrationBundle::new(name, sf_key.to_string(), storefront, chrono::Utc::now());
+
+    let uc_key = contracts::user_contract(&owner).key();
+    match client.get_user_contract(*uc_key.id()).await {
+        Ok(state) => {
+            println!("User contract {}: {} ledger entries", uc_key, state.ledger.len());
+            bundle = bundle.with_user_contract(uc_key.to_string(), state);
+        }
+        Err(e) => eprintln!("WARNING: User contract {} not exported: {}", uc_key, e),
+    }
+    let ib_key = contracts::inbox(&owner).key();
+    match client.get_inbox(*ib_key.id()).await {
+        Ok(state) => {
+            println!("Inbox {}: {} messages", ib_key, state.messages.len());
+            bundle = bundle.with_inbox(ib_key.to_string(), state);
+        }
+        Err(e) => eprintln!("WARNING: Inbox {} not exported: {}", ib_key, e),
+    }
+
+    bundle.check_owner(&UserId(owner)).map_err(|e| e.to_string())?;
+    let json = bundle.to_json().map_err(|e| e.to_string())?;
+    std::fs::write(out, json).map_err(|e| format!("Cannot write {}: {}", out.display(), e))?;
+    println!("Wrote {}", out.display());
+    Ok(())
+}
+
+async fn import(
+    name: &str,
+    node_url: &str,
+    path: &Path,
+    rendezvous: Option<(String, String)>,
+) -> Result<(), String> {
+    let key = supplier_key(name)?;
+    let owner = key.verifying_key();
+    let supplier = UserId(owner);
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let bundle = MigrationBundle::from_json(&contents).map_err(|e| e.to_string())?;
+    bundle.check_owner(&supplier).map_err(|e| e.to_string())?;
+
+    // Every contract must come out under the key it was exported from
+    let sf_contract = contracts::storefront(&owner);
+    check_key("storefront", &bundle.storefront_key, &sf_contract.key().to_string()).map_err(|e| e.to_string())?;
+    let uc_contract = contracts::user_contract(&owner);
+    if let Some(exported) = &bundle.user_contract_key {
+        check_key("user", exported, &uc_contract.key().to_string()).map_err(|e| e.to_string())?;
+    }
+    let ib_contract = contracts::inbox(&owner);
+    if let Some(exported) = &bundle.inbox_key {
+        check_key("inbox", exported, &ib_contract.key().to_string()).map_err(|e| e.to_string())?;
+    }
+
+    let mut client = connect(node_url).await?;
+    let sf_key = sf_contract.key();
+    client
+        .put_state(sf_contract, &bundle.storefront, true)
+        .await
+        .map_err(|e| format!("Storefront PUT failed: {}", e))?;
+    let fetched = client
+        .get_storefront(*sf_key.id())
+        .await
+        .map_err(|e| format!("Storefront not readable after PUT: {}", e))?;
+    if fetched.info.owner != supplier {
+        return Err(format!("Storefront {} on the new node has a different owner", sf_key));
+    }
+    println!("Storefront {}: {} products, {} orders", sf_key, fetched.products.len(), fetched.orders.len());
+
+    if let Some(state) = &bundle.user_contract {
+        let uc_key = uc_contract.key();
+        client
+            .put_state(uc_contract, state, true)
+            .await
+            .map_err(|e| format!("User contract PUT failed: {}", e))?;
+        let fetched = client
+            .get_user_contract(*uc_key.id())
+            .await
+            .map_err(|e| format!("User contract not readable after PUT: {}", e))?;
+        if fetched.owner != supplier {
+            return Err(format!("User contract {} on the new node has a different owner", uc_key));
+        }
+        println!("User contract {}: {} ledger entries", uc_key, fetched.ledger.len());
+    }
+    if let Some(state) = &bundle.inbox {
+        let ib_key = ib_contract.key();
+        client
+            .put_state(ib_contract, state, true)
+            .await
+            .map_err(|e| format!("Inbox PUT failed: {}", e))?;
+        let fetched = client
+            .get_inbox(*ib_key.id())
+            .await
+            .map_err(|e| format!("Inbox not readable after PUT: {}", e))?;
+        if fetched.owner != supplier {
+            return Err(format!("Inbox {} on the new node has a different owner", ib_key));
+        }
+        println!("Inbox {}: {} messages", ib_key, fetched.messages.len());
+    }
+
+    if let Some((rendezvous_url, address)) = rendezvous {
+        let record = RendezvousRecord::sign(
+            &key,
+            &rendezvous_name(&bundle.name),
+            &address,
+            &bundle.storefront_key,
+            bundle.user_contract_key.as_deref(),
+            bundle.inbox_key.as_deref(),
+        );
+        register(&rendezvous_url, &record).await?;
+        println!("Rendezvous: '{}' now points at {}", record.name, record.address);
+    }
+    Ok(())
+}
+
+/// Register `record` with the rendezvous service, then send its address
+/// signature as a heartbeat, and check the service hands it back.
+async fn register(rendezvous_url: &str, record: &RendezvousRecord) -> Result<(), String> {
+    let http = reqwest::Client::new();
+    let mut registration = serde_json::json!({
+        "name": record.name,
+        "address": record.address,
+        "storefront_key": record.storefront_key,
+        "public_key": record.public_key,
+        "signature": record.signature,
+    });
+    if let Some(key) = &record.user_contract_key {
+        registration["user_contract_key"] = key.clone().into();
+    }
+    if let Some(key) = &record.inbox_contract_key {
+        registration["inbox_contract_key"] = key.clone().into();
+    }
+    let heartbeat = serde_json::json!({
+        "name": record.name,
+        "address": record.address,
+        "public_key": record.public_key,
+        "signature": record.address_signature,
+    });
+    for (path, body) in [("register", registration), ("heartbeat", heartbeat)] {
+        http.post(format!("{}/{}", rendezvous_url, path))
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Rendezvous {} failed: {}", path, e))?;
+    }
+
+    let listed: serde_json::Value = http
+        .get(format!("{}/lookup/{}", rendezvous_url, record.name))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Rendezvous lookup failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Bad rendezvous lookup response: {}", e))?;
+    let listed_address = listed.get("address").and_then(|a| a.as_str());
+    if listed_address != Some(record.address.as_str()) {
+        return Err(format!(
+            "Rendezvous lists '{}' at {}, not {}",
+            record.name,
+            listed_address.unwrap_or("no address"),
+            record.address
+        ));
+    }
+    Ok(())
+}
@@ -151,12 +151,12 @@ pub fn apply_update(
         }
 
         ContractType::UserContract => {
-            let owner = extract_user_contract_owner(params_bytes)?;
+            let params = extract_user_contract_params(params_bytes)?;
             let mut state: UserContractState = serde_json::from_slice(current_state_bytes)
                 .map_err(|e| ContractError::InvalidState(e.to_string()))?;
             let update: UserContractState = serde_json::from_slice(update_bytes)
                 .map_err(|e| ContractError::InvalidUpdate(e.to_string()))?;
-            if !state.validate_update(&update, &owner) {
+            if !state.validate_update_for(&update, &params, chrono::Utc::now()) {
                 return Err(ContractError::ValidationFailed(
                     "user contract validation failed".into(),
                 ));
@@ -238,10 +238,9 @@ fn extract_storefront_owner(params_bytes: &[u8]) -> Result<ed25519_dalek::Verify
     Ok(params.owner)
 }
 
-fn extract_user_contract_owner(params_bytes: &[u8]) -> Result<ed25519_dalek::VerifyingKey, ContractError> {
-    let params: UserContractParameters = serde_json::from_slice(params_bytes)
-        .map_err(|e| ContractError::InvalidState(format!("bad user contract params: {e}")))?;
-    Ok(params.owner)
+fn extract_user_contract_params(params_bytes: &[u8]) -> Result<UserContractParameters, ContractError> {
+    serde_json::from_slice(params_bytes)
+        .map_err(|e| ContractError::InvalidState(format!("bad user contract params: {e}")))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -251,7 +251,7 @@ async fn handle_put(
 
     let mut responses = vec![serialize_host_result(&Ok(
         HostResponse::ContractResponse(ContractResponse::PutResponse {
-            key: contract_key.clone(),
+            key: contract_key,
         }),
     ))];
 
@@ -297,7 +297,7 @@ async fn handle_get(
 
             let mut responses = vec![serialize_host_result(&Ok(
                 HostResponse::ContractResponse(ContractResponse::GetResponse {
-                    key: contract_key.clone(),
+                    key: contract_key,
                     contract: contract_container,
                     state: wrapped_state,
                 }),
@@ -377,7 +377,7 @@ async fn handle_update(
 
     // Build UpdateNotification for subscribers
     let notification = HostResponse::ContractResponse(ContractResponse::UpdateNotification {
-        key: key.clone(),
+        key: *key,
         update: UpdateData::State(State::from(new_state_bytes)),
     });
     let notification_bytes = serialize_host_result(&Ok(notification));
@@ -388,7 +388,7 @@ async fn handle_update(
 
     vec![serialize_host_result(&Ok(
         HostResponse::ContractResponse(ContractResponse::UpdateResponse {
-            key: key.clone(),
+            key: *key,
             summary,
         }),
     ))]
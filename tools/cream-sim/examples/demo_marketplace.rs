@@ -0,0 +1,117 @@
+//! Populate a dev node with a small demo marketplace.
+//!
+//! Spawns a handful of suppliers across three states with products, and
+//! customers who order from them: some orders left reserved, one fulfilled,
+//! one expired. Suppliers and customers log into the UI with their name as
+//! the password.
+//!
+//! Usage:
+//!   cargo run -p cream-sim --example demo_marketplace -- [--port PORT]
+//!
+//! --port: Freenet node WebSocket port (default: 3001).
+//!
+//! Expects contracts built in dev mode (`build-contracts-dev`), since the
+//! simulated products and orders are unsigned.
+
+use cream_common::order::DepositTier;
+use cream_common::product::ProductCategory;
+use cream_sim::actors::SupplierProfile;
+use cream_sim::node_url;
+use cream_sim::script::{Action, Sim};
+
+fn supplier(name: &str, postcode: &str) -> Action {
+    let profile = SupplierProfile::new(name, postcode)
+        .unwrap_or_else(|| panic!("{postcode} is not in the postcode table"));
+    Action::SpawnSupplier(profile)
+}
+
+fn customer(name: &str, invited_by: &str) -> Action {
+    Action::SpawnCustomer { name: name.into(), invited_by: invited_by.into() }
+}
+
+fn product(supplier: &str, product: &str, category: ProductCategory, price_curd: u64) -> Action {
+    Action::AddProduct {
+        supplier: supplier.into(),
+        product: product.into(),
+        category,
+        price_curd,
+    }
+}
+
+fn order(customer: &str, supplier: &str, product: &str, quantity: u32, order: &str) -> Action {
+    Action::PlaceOrder {
+        customer: customer.into(),
+        supplier: supplier.into(),
+        product: product.into(),
+        quantity,
+        tier: DepositTier::Reserve2Days,
+        order: order.into(),
+    }
+}
+
+fn status(supplier: &str, order: &str, status: &str) -> Action {
+    Action::ExpectOrderStatus {
+        supplier: supplier.into(),
+        order: order.into(),
+        status: status.into(),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::try_init().ok();
+
+    let args: Vec<String> = std::env::args().collect();
+    let port = args
+        .windows(2)
+        .find(|w| w[0] == "--port")
+        .and_then(|w| w[1].parse().ok())
+        .unwrap_or(3001);
+
+    let mut sim = Sim::bootstrap(&node_url(port)).await;
+    let script = vec![
+        supplier("Gary", "2450"),
+        supplier("Emma", "2454"),
+        supplier("Iris", "2000"),
+        supplier("Hamish", "3550"),
+        product("Gary", "Raw Milk", ProductCategory::Milk, 450),
+        product("Gary", "Cultured Butter", ProductCategory::Butter, 900),
+        product("Emma", "Vintage Cheddar", ProductCategory::Cheese, 1_500),
+        product("Emma", "Pouring Cream", ProductCategory::Cream, 650),
+        product("Iris", "Greek Yogurt", ProductCategory::Yogurt, 700),
+        product("Hamish", "Goat Kefir", ProductCategory::Kefir, 800),
+        customer("Alice", "Gary"),
+        customer("Bob", "Emma"),
+        customer("Carol", "Iris"),
+        order("Alice", "Gary", "Raw Milk", 2, "alice-milk"),
+        order("Alice", "Emma", "Vintage Cheddar", 1, "alice-cheddar"),
+        order("Bob", "Emma", "Pouring Cream", 3, "bob-cream"),
+        order("Carol", "Iris", "Greek Yogurt", 2, "carol-yogurt"),
+        order("Carol", "Hamish", "Goat Kefir", 1, "carol-kefir"),
+        // Alice collects her milk
+        Action::ProposeFulfillment { supplier: "Gary".into(), order: "alice-milk".into() },
+        Action::ConfirmFulfillment {
+            customer: "Alice".into(),
+            supplier: "Gary".into(),
+            order: "alice-milk".into(),
+        },
+        status("Gary", "alice-milk", "Fulfilled"),
+        // Carol never picks up her kefir
+        Action::AdvanceClock(chrono::Duration::days(3)),
+        Action::ExpireOrders { supplier: "Hamish".into() },
+        status("Hamish", "carol-kefir", "Expired"),
+        status("Emma", "bob-cream", "Reserved"),
+        Action::ExpectConserved,
+    ];
+
+    if let Err(e) = sim.run(script).await {
+        eprintln!("Demo marketplace failed: {e}");
+        std::process::exit(1);
+    }
+    println!(
+        "Demo marketplace ready: {} suppliers, {} customers, {} orders",
+        sim.suppliers.len(),
+        sim.customers.len(),
+        sim.orders.len()
+    );
+}
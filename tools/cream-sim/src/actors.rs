@@ -0,0 +1,1053 @@
+//! Simulated marketplace participants.
+//!
+//! A [`Supplier`] or [`Customer`] owns a WebSocket connection to a Freenet
+//! node and keeps its own copy of the contracts it writes, pushing the whole
+//! state on every change the way the UI does. [`Supplier::spawn`] and
+//! [`Customer::spawn`] bring a new participant into a running network with
+//! its contracts deployed and funded; the lower-level `deploy_*` functions are
+//! there for fixtures that assemble participants by hand.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ed25519_dalek::VerifyingKey;
+use freenet_stdlib::client_api::{ContractResponse, HostResponse, WebApi};
+use freenet_stdlib::prelude::*;
+
+use cream_client::request;
+use cream_common::clock::{Clock, MockClock};
+use cream_common::directory::DirectoryState;
+use cream_common::identity::UserId;
+use cream_common::location::GeoLocation;
+use cream_common::order::{FulfillmentReceipt, Order, OrderStatus};
+use cream_common::postcode::lookup_postcode_info;
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::storefront::{
+    receipt_signable_bytes, SignedProduct, StorefrontInfo, StorefrontState, WeeklySchedule,
+};
+
+use cream_common::user_contract::UserContractState;
+use cream_common::wallet::{TransactionKind, WalletTransaction};
+
+use crate::{
+    connect_to_node_at, extract_get_response_state, extract_notification_bytes, is_get_response,
+    is_subscribe_success, is_update_notification, is_update_response, make_directory_entry,
+    make_dummy_user, make_inbox_contract, make_storefront_contract, make_user_contract,
+    recv_matching, wait_for_get, wait_for_put,
+};
+
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(60);
+
+
+/// A simulated supplier.
+pub struct Supplier {
+    pub name: String,
+    pub id: UserId,
+    pub verifying_key: VerifyingKey,
+    pub api: WebApi,
+    pub storefront_key: ContractKey,
+    pub storefront: StorefrontState,
+    pub postcode: String,
+    pub locality: String,
+    /// Contract key for this supplier's user contract.
+    pub user_contract_key: Option<ContractKey>,
+    /// Contract key for this supplier's inbox contract.
+    pub inbox_key: Option<ContractKey>,
+    /// WebSocket URL of the node this supplier runs on (advertised via rendezvous).
+    pub node_url: String,
+    /// Simulation clock used for expiry checks, shared by every participant.
+    pub clock: Arc<MockClock>,
+}
+
+/// Who a spawned [`Supplier`] is and where they farm.
+#[derive(Clone, Debug)]
+pub struct SupplierProfile {
+    /// Login name; the password is the name in lowercase, as with every
+    /// simulated identity.
+    pub name: String,
+    pub storefront_name: String,
+    pub description: String,
+    pub postcode: String,
+    pub locality: String,
+    pub location: GeoLocation,
+}
+
+impl SupplierProfile {
+    /// A profile for `name` farming at `postcode`, with the locality and
+    /// location taken from the postcode table. `None` for an unknown postcode.
+    pub fn new(name: &str, postcode: &str) -> Option<Self> {
+        let info = lookup_postcode_info(postcode)?;
+        Some(SupplierProfile {
+            name: name.to_string(),
+            storefront_name: format!("{name}'s Farm"),
+            description: format!("Fresh dairy from {}", info.place_name),
+            postcode: postcode.to_string(),
+            locality: info.place_name,
+            location: info.location,
+        })
+    }
+}
+
+impl Supplier {
+    /// Spawn a supplier on the node at `node_url`: PUT their storefront,
+    /// deploy their user contract with the initial 10,000 CURD allocation from
+    /// root, deploy their inbox, and list them in the directory at
+    /// `directory_key` (the shard for their postcode).
+    pub async fn spawn(
+        node_url: &str,
+        profile: SupplierProfile,
+        root_key: &ContractKey,
+        directory_key: &ContractKey,
+        clock: Arc<MockClock>,
+    ) -> Supplier {
+        let (id, verifying_key) = make_dummy_user(&profile.name);
+        let (sf_contract, storefront_key) = make_storefront_contract(&verifying_key);
+        let storefront = make_initial_storefront(
+            &id,
+            &profile.storefront_name,
+            &profile.description,
+            profile.location,
+        );
+        let mut supplier = Supplier {
+            name: profile.name,
+            id,
+            verifying_key,
+            api: connect_to_node_at(node_url).await,
+            storefront_key,
+            storefront,
+            postcode: profile.postcode,
+            locality: profile.locality,
+            user_contract_key: None,
+            inbox_key: None,
+            node_url: node_url.to_string(),
+            clock,
+        };
+
+        put_storefront(&mut supplier, sf_contract).await;
+        deploy_supplier_user_contract(&mut supplier, root_key, node_url).await;
+        let vk = supplier.verifying_key;
+        deploy_inbox(&mut supplier.api, &vk, node_url, &mut supplier.inbox_key).await;
+        register_supplier_in_directory(&mut supplier, directory_key).await;
+        supplier
+    }
+
+    /// Add a product to this supplier's storefront, send the update, and wait for confirmation.
+    pub async fn add_product(
+        &mut self,
+        name: &str,
+        category: ProductCategory,
+        price_curd: u64,
+    ) -> &SignedProduct {
+        let now = chrono::Utc::now();
+        let product = SignedProduct {
+            product: Product {
+                id: ProductId(format!(
+                    "p-{}-{}",
+                    self.name.to_lowercase(),
+                    now.timestamp_millis()
+                )),
+                name: name.to_string(),
+                description: format!("Fresh {name}"),
+                category,
+                subcategory: None,
+                attributes: Default::default(),
+                price_curd,
+                quantity_total: 10,
+                expiry_date: None,
+                updated_at: now,
+                created_at: now,
+                low_stock: None,
+                paused: false,
+                special: None,
+                wholesale: None,
+                extra: Default::default(),
+            },
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            provenance: None,
+            price_history: Vec::new(),
+            extra: Default::default(),
+        };
+
+        let pid = product.product.id.clone();
+        self.storefront.products.insert(pid.clone(), product);
+
+        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
+        self.api
+            .send(request::update(self.storefront_key, sf_bytes))
+            .await
+            .unwrap();
+
+        // Wait for UpdateResponse confirmation
+        recv_matching(&mut self.api, is_update_response, TIMEOUT)
+            .await
+            .expect("Expected UpdateResponse after adding product");
+
+        // Small delay so next product gets a distinct timestamp-based ID
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        self.storefront.products.get(&pid).unwrap()
+    }
+
+    /// Add a pre-built order to this supplier's storefront, send the update, and wait for confirmation.
+    pub async fn add_order(&mut self, order: Order) {
+        let order_id = order.id.clone();
+        self.storefront.orders.insert(order_id, order);
+
+        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
+        self.api
+            .send(request::update(self.storefront_key, sf_bytes))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_update_response, TIMEOUT)
+            .await
+            .expect("Expected UpdateResponse after adding order");
+    }
+
+    /// Run expire_orders on the local storefront, push update if any changed.
+    /// Returns true if any orders were expired.
+    pub async fn expire_orders(&mut self) -> bool {
+        let now = self.clock.now();
+        if self.storefront.expire_orders(now) {
+            let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
+            self.api
+                .send(request::update(self.storefront_key, sf_bytes))
+                .await
+                .unwrap();
+
+            recv_matching(&mut self.api, is_update_response, TIMEOUT)
+                .await
+                .expect("Expected UpdateResponse after expiring orders");
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Update the supplier's opening hours schedule and push to the network.
+    pub async fn update_schedule(&mut self, schedule: WeeklySchedule, timezone: &str) {
+        self.storefront.info.schedule = Some(schedule);
+        self.storefront.info.timezone = Some(timezone.to_string());
+
+        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
+        self.api
+            .send(request::update(self.storefront_key, sf_bytes))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_update_response, TIMEOUT)
+            .await
+            .unwrap_or_else(|| panic!("UpdateResponse for {}'s schedule", self.name));
+    }
+
+    /// Propose fulfillment of an order (Reserved/Paid → FulfillmentProposed).
+    /// The customer completes it with [`Customer::confirm_fulfillment`].
+    pub async fn propose_fulfillment(&mut self, order_id: &str) {
+        let proposed = OrderStatus::FulfillmentProposed {
+            proposed_at: chrono::Utc::now(),
+        };
+        let oid = cream_common::order::OrderId(order_id.to_string());
+        let order = self
+            .storefront
+            .orders
+            .get_mut(&oid)
+            .unwrap_or_else(|| panic!("Order {} not found on {}'s storefront", order_id, self.name));
+        assert!(
+            order.status.can_transition_to(&proposed),
+            "Cannot propose fulfillment of order {} in status {}",
+            order_id,
+            order.status
+        );
+        order.status = proposed;
+
+        self.send_order_update("proposing fulfillment").await;
+    }
+
+    /// Record a customer's receipt for a proposed fulfillment (→ Fulfilled).
+    pub async fn record_fulfillment(&mut self, order_id: &str, receipt: FulfillmentReceipt) {
+        let oid = cream_common::order::OrderId(order_id.to_string());
+        let order = self
+            .storefront
+            .orders
+            .get_mut(&oid)
+            .unwrap_or_else(|| panic!("Order {} not found on {}'s storefront", order_id, self.name));
+        assert!(
+            order.status.can_transition_to(&OrderStatus::Fulfilled),
+            "Cannot fulfill order {} in status {}",
+            order_id,
+            order.status
+        );
+        order.status = OrderStatus::Fulfilled;
+        order.receipt = Some(receipt);
+
+        self.send_order_update("fulfilling order").await;
+    }
+
+    async fn send_order_update(&mut self, what: &str) {
+        let sf_bytes = serde_json::to_vec(&self.storefront).unwrap();
+        self.api
+            .send(request::update(self.storefront_key, sf_bytes))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_update_response, TIMEOUT)
+            .await
+            .unwrap_or_else(|| panic!("Expected UpdateResponse after {}", what));
+    }
+
+    /// Poll this supplier's storefront on their own node until `order_id`
+    /// shows up (e.g. placed by a customer over another connection), adopting
+    /// the fetched state. Returns false if it never arrives.
+    pub async fn wait_for_order(&mut self, order_id: &str) -> bool {
+        let oid = cream_common::order::OrderId(order_id.to_string());
+        let mut backoff = crate::consistency_retry_policy().backoff(rand::random());
+        loop {
+            if let Some(bytes) = wait_for_get(&mut self.api, *self.storefront_key.id(), TIMEOUT).await {
+                let state: StorefrontState =
+                    serde_json::from_slice(&bytes).expect("deserialize storefront from GET");
+                if state.orders.contains_key(&oid) {
+                    self.storefront = state;
+                    return true;
+                }
+            }
+            if !crate::backoff_sleep(&mut backoff).await {
+                return false;
+            }
+        }
+    }
+
+    /// The entry this supplier registers with the rendezvous service — same
+    /// name normalisation and key formatting as the UI's `RegisterSupplier`.
+    pub fn rendezvous_entry(&self) -> RendezvousEntry {
+        RendezvousEntry {
+            name: rendezvous_name(&self.name),
+            address: self.node_url.clone(),
+            storefront_key: format!("{}", self.storefront_key),
+            user_contract_key: self.user_contract_key.map(|k| format!("{}", k)),
+            inbox_contract_key: self.inbox_key.map(|k| format!("{}", k)),
+        }
+    }
+
+    /// Return a reference to the local storefront state copy.
+    pub fn get_storefront_state(&self) -> &StorefrontState {
+        &self.storefront
+    }
+
+    /// Subscribe to the directory contract.
+    pub async fn subscribe_to_directory(&mut self, dir_key: &ContractKey) {
+        self.api
+            .send(request::subscribe(*dir_key.id()))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
+            .await
+            .expect("Expected SubscribeResponse for directory");
+    }
+}
+
+/// A simulated customer.
+pub struct Customer {
+    pub name: String,
+    pub id: UserId,
+    pub verifying_key: VerifyingKey,
+    pub api: WebApi,
+    /// CURD wallet balance (derived from on-network ledger).
+    pub balance: u64,
+    /// Contract key for this customer's user contract.
+    pub user_contract_key: Option<ContractKey>,
+    /// Contract key for this customer's inbox contract.
+    pub inbox_key: Option<ContractKey>,
+}
+
+impl Customer {
+    /// Spawn a customer on the node at `node_url`, with a user contract holding
+    /// the initial 10,000 CURD allocation from root and an inbox. `invited_by`
+    /// is the supplier recorded as their origin.
+    pub async fn spawn(
+        node_url: &str,
+        name: &str,
+        root_key: &ContractKey,
+        invited_by: &str,
+    ) -> Customer {
+        let (id, verifying_key) = make_dummy_user(name);
+        let mut customer = Customer {
+            name: name.to_string(),
+            id,
+            verifying_key,
+            api: connect_to_node_at(node_url).await,
+            balance: 10_000,
+            user_contract_key: None,
+            inbox_key: None,
+        };
+        deploy_user_contract(&mut customer, root_key, node_url, invited_by).await;
+        deploy_inbox_customer(&mut customer, node_url).await;
+        customer
+    }
+
+    /// Place an order if balance is sufficient. Decrements balance and pushes the order
+    /// to the supplier's storefront. Returns `Err` if the customer can't afford the deposit.
+    pub async fn place_order(
+        &mut self,
+        order: Order,
+        supplier: &mut Supplier,
+    ) -> Result<(), String> {
+        let deposit = order.deposit_amount;
+        if self.balance < deposit {
+            return Err(format!(
+                "Insufficient balance: have {}, need {} deposit",
+                self.balance, deposit
+            ));
+        }
+        self.balance -= deposit;
+        supplier.add_order(order).await;
+        Ok(())
+    }
+
+    /// Counter-sign a supplier's fulfillment proposal and hand the receipt
+    /// to the supplier, completing the order.
+    pub async fn confirm_fulfillment(&mut self, order_id: &str, supplier: &mut Supplier) {
+        use ed25519_dalek::Signer;
+
+        let oid = cream_common::order::OrderId(order_id.to_string());
+        let order = supplier
+            .storefront
+            .orders
+            .get(&oid)
+            .unwrap_or_else(|| panic!("Order {} not found on {}'s storefront", order_id, supplier.name));
+        let OrderStatus::FulfillmentProposed { proposed_at } = order.status else {
+            panic!("Order {} has no pending fulfillment (status {})", order_id, order.status);
+        };
+
+        let signing_key =
+            cream_common::identity::derive_user_signing_key(&self.name, &self.name.to_lowercase());
+        let confirmed_at = chrono::Utc::now();
+        let msg = receipt_signable_bytes(order, &proposed_at, &confirmed_at, false);
+        let receipt = FulfillmentReceipt {
+            proposed_at,
+            confirmed_at,
+            auto_confirmed: false,
+            signature: signing_key.sign(&msg),
+            extra: Default::default(),
+        };
+        supplier.record_fulfillment(order_id, receipt).await;
+    }
+
+    /// Onboard a new customer the way the UI's customer mode does: look the
+    /// supplier up via rendezvous, connect to the node URL they advertise,
+    /// then GET and subscribe to their storefront by the advertised key.
+    pub async fn onboard_via_rendezvous(
+        name: &str,
+        balance: u64,
+        rendezvous: &Rendezvous,
+        supplier_name: &str,
+    ) -> (Customer, SupplierLink) {
+        let entry = rendezvous
+            .lookup(supplier_name)
+            .unwrap_or_else(|| panic!("{} is not registered with rendezvous", supplier_name))
+            .clone();
+        let sf_id = ContractInstanceId::from_bytes(&entry.storefront_key)
+            .unwrap_or_else(|e| panic!("Invalid storefront key '{}': {:?}", entry.storefront_key, e));
+
+        let (id, verifying_key) = make_dummy_user(name);
+        let mut customer = Customer {
+            name: name.to_string(),
+            id,
+            verifying_key,
+            api: connect_to_node_at(&entry.address).await,
+            balance,
+            user_contract_key: None,
+            inbox_key: None,
+        };
+
+        // Ask for the contract code too: the full key is needed to send updates,
+        // and rendezvous only hands out the instance id.
+        customer
+            .api
+            .send(request::get(sf_id, true))
+            .await
+            .unwrap();
+        let resp = recv_matching(&mut customer.api, is_get_response, TIMEOUT)
+            .await
+            .expect("Expected GetResponse for rendezvous storefront");
+        let HostResponse::ContractResponse(ContractResponse::GetResponse { key, state, .. }) = resp
+        else {
+            unreachable!("is_get_response matched a non-GetResponse");
+        };
+        let storefront: StorefrontState =
+            serde_json::from_slice(state.as_ref()).expect("deserialize storefront from GET");
+
+        customer
+            .api
+            .send(request::subscribe(sf_id))
+            .await
+            .unwrap();
+        recv_matching(&mut customer.api, is_subscribe_success, TIMEOUT)
+            .await
+            .expect("Expected SubscribeResponse for rendezvous storefront");
+
+        let link = SupplierLink {
+            entry,
+            storefront_key: key,
+            storefront,
+        };
+        (customer, link)
+    }
+
+    /// Place an order on the linked supplier's storefront over this customer's
+    /// own connection, without going through the supplier. Decrements balance
+    /// like `place_order`.
+    pub async fn place_order_via(
+        &mut self,
+        link: &mut SupplierLink,
+        order: Order,
+    ) -> Result<(), String> {
+        let deposit = order.deposit_amount;
+        if self.balance < deposit {
+            return Err(format!(
+                "Insufficient balance: have {}, need {} deposit",
+                self.balance, deposit
+            ));
+        }
+        link.storefront.orders.insert(order.id.clone(), order);
+
+        let sf_bytes = serde_json::to_vec(&link.storefront).unwrap();
+        self.api
+            .send(request::update(link.storefront_key, sf_bytes))
+            .await
+            .unwrap();
+        recv_matching(&mut self.api, is_update_response, TIMEOUT)
+            .await
+            .expect("Expected UpdateResponse after placing order via rendezvous link");
+
+        self.balance -= deposit;
+        Ok(())
+    }
+
+    /// GET a supplier's storefront state.
+    pub async fn get_storefront(&mut self, supplier: &Supplier) -> StorefrontState {
+        self.api
+            .send(request::get(*supplier.storefront_key.id(), false))
+            .await
+            .unwrap();
+
+        let resp = recv_matching(&mut self.api, is_get_response, TIMEOUT)
+            .await
+            .expect("Expected GetResponse for storefront");
+
+        let bytes = extract_get_response_state(&resp).expect("state bytes from GET");
+        serde_json::from_slice(&bytes).expect("deserialize storefront from GET")
+    }
+
+    /// Subscribe to a supplier's storefront contract.
+    pub async fn subscribe_to_storefront(&mut self, supplier: &Supplier) {
+        self.api
+            .send(request::subscribe(*supplier.storefront_key.id()))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
+            .await
+            .expect("Expected SubscribeResponse for storefront");
+    }
+
+    /// Subscribe to the directory contract.
+    pub async fn subscribe_to_directory(&mut self, dir_key: &ContractKey) {
+        self.api
+            .send(request::subscribe(*dir_key.id()))
+            .await
+            .unwrap();
+
+        recv_matching(&mut self.api, is_subscribe_success, TIMEOUT)
+            .await
+            .expect("Expected SubscribeResponse for directory");
+    }
+
+    /// Wait for an UpdateNotification and parse it as a StorefrontState.
+    pub async fn recv_storefront_update(&mut self) -> StorefrontState {
+        let notif = recv_matching(&mut self.api, is_update_notification, TIMEOUT)
+            .await
+            .expect("Expected UpdateNotification for storefront");
+
+        let bytes = extract_notification_bytes(&notif).expect("notification bytes");
+        serde_json::from_slice(&bytes).expect("deserialize storefront from notification")
+    }
+
+    /// Wait for an UpdateNotification and parse it as a DirectoryState.
+    pub async fn recv_directory_update(&mut self) -> DirectoryState {
+        let notif = recv_matching(&mut self.api, is_update_notification, TIMEOUT)
+            .await
+            .expect("Expected UpdateNotification for directory");
+
+        let bytes = extract_notification_bytes(&notif).expect("notification bytes");
+        serde_json::from_slice(&bytes).expect("deserialize directory from notification")
+    }
+}
+
+/// A supplier's rendezvous registration: where their node is and which
+/// contracts to use. Mirrors the rendezvous service's entries (keys as strings).
+#[derive(Clone, Debug)]
+pub struct RendezvousEntry {
+    pub name: String,
+    pub address: String,
+    pub storefront_key: String,
+    pub user_contract_key: Option<String>,
+    pub inbox_contract_key: Option<String>,
+}
+
+/// In-memory stand-in for the rendezvous service. Suppliers register under a
+/// normalised name; customers look them up by the name they were given.
+#[derive(Default)]
+pub struct Rendezvous {
+    entries: BTreeMap<String, RendezvousEntry>,
+}
+
+impl Rendezvous {
+    pub fn register(&mut self, supplier: &Supplier) {
+        let entry = supplier.rendezvous_entry();
+        self.entries.insert(entry.name.clone(), entry);
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&RendezvousEntry> {
+        self.entries.get(&rendezvous_name(name))
+    }
+}
+
+fn rendezvous_name(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "-")
+}
+
+/// A customer's view of the supplier they onboarded with via rendezvous.
+pub struct SupplierLink {
+    pub entry: RendezvousEntry,
+    /// Full storefront key, resolved from the GET (rendezvous only has the id).
+    pub storefront_key: ContractKey,
+    /// Storefront state as last fetched by the customer.
+    pub storefront: StorefrontState,
+}
+
+/// Total CURD in the system. Root starts with this; all transfers are double-entry
+/// through root, so the sum of all user contract balances must always equal this.
+pub const SYSTEM_FLOAT: u64 = 1_000_000;
+
+/// Compute the ContractKey for a user's contract given their name.
+///
+/// "system_root" uses the well-known `root_user_id()` (FROST dev key).
+/// All others derive via `make_dummy_user(name)`.
+pub fn user_contract_key_for(name: &str) -> ContractKey {
+    let vk = if name == "system_root" {
+        let root_id = cream_common::identity::root_user_id();
+        ed25519_dalek::VerifyingKey::from_bytes(root_id.0.as_bytes()).unwrap()
+    } else {
+        let (_, vk) = make_dummy_user(name);
+        vk
+    };
+    let (_, key) = make_user_contract(&vk);
+    key
+}
+
+/// Deploy system_root's user contract on the node at `node_url`, holding the
+/// whole [`SYSTEM_FLOAT`] as its genesis credit. Returns its key.
+pub async fn deploy_root_contract(node_url: &str) -> ContractKey {
+    let root_id = cream_common::identity::root_user_id();
+    let root_vk = *root_id.0.as_bytes();
+    let root_vk = ed25519_dalek::VerifyingKey::from_bytes(&root_vk).unwrap();
+    let (root_contract, root_key) = make_user_contract(&root_vk);
+
+    let genesis_tx = WalletTransaction {
+        id: 0,
+        kind: TransactionKind::Credit,
+        amount: SYSTEM_FLOAT,
+        description: "Genesis".to_string(),
+        sender: String::new(),
+        receiver: cream_common::identity::ROOT_USER_NAME.to_string(),
+        tx_ref: "genesis:0:0".to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+
+    let mut root_state = UserContractState {
+        owner: cream_common::identity::root_user_id(),
+        name: cream_common::identity::ROOT_USER_NAME.to_string(),
+        origin_supplier: String::new(),
+        current_supplier: String::new(),
+        balance_curds: SYSTEM_FLOAT,
+        invited_by: String::new(),
+        toll_rates: Default::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        ledger: vec![genesis_tx],
+        next_tx_id: 1,
+        seq: 0,
+        updated_at: chrono::Utc::now(),
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    };
+    root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
+    let root_state_bytes = serde_json::to_vec(&root_state).unwrap();
+
+    let mut root_api = connect_to_node_at(node_url).await;
+    wait_for_put(
+        &mut root_api,
+        root_contract,
+        WrappedState::new(root_state_bytes),
+        TIMEOUT,
+    )
+    .await
+    .expect("PutResponse for root user contract");
+    root_key
+}
+
+pub fn make_initial_storefront(
+    owner: &UserId,
+    name: &str,
+    description: &str,
+    location: GeoLocation,
+) -> StorefrontState {
+    StorefrontState {
+        info: StorefrontInfo {
+            owner: owner.clone(),
+            name: name.to_string(),
+            description: description.to_string(),
+            location,
+            schedule: None,
+            timezone: None,
+            phone: None,
+            email: None,
+            address: None,
+            market_products: BTreeMap::new(),
+            delivery_zones: Vec::new(),
+            pickup_locations: Vec::new(),
+            sealed_contact: None,
+            cancellation_policy: None,
+            attestation_threshold: None,
+            provenance: None,
+            seq: 0,
+            extra: Default::default(),
+        },
+        products: BTreeMap::new(),
+        orders: BTreeMap::new(),
+        threads: BTreeMap::new(),
+        staff: BTreeMap::new(),
+        blocklist: BTreeMap::new(),
+        order_history: BTreeMap::new(),
+        extra: Default::default(),
+    }
+}
+
+/// PUT a storefront contract via the supplier's connection and wait for confirmation.
+pub async fn put_storefront(supplier: &mut Supplier, contract: ContractContainer) {
+    let state_bytes = serde_json::to_vec(&supplier.storefront).unwrap();
+    wait_for_put(
+        &mut supplier.api,
+        contract,
+        WrappedState::new(state_bytes),
+        TIMEOUT,
+    )
+    .await
+    .unwrap_or_else(|| panic!("PutResponse for {}'s storefront", supplier.name));
+}
+
+/// Register a supplier in the directory via Update.
+pub async fn register_supplier_in_directory(supplier: &mut Supplier, dir_key: &ContractKey) {
+    let entry = make_directory_entry(
+        &supplier.id,
+        &supplier.name,
+        &supplier.storefront.info.description,
+        &supplier.postcode,
+        &supplier.locality,
+        supplier.storefront.info.location.clone(),
+        supplier.storefront_key,
+        supplier.user_contract_key,
+        supplier.inbox_key,
+    );
+    let mut entries = BTreeMap::new();
+    entries.insert(supplier.id.clone(), entry);
+    let delta = DirectoryState { entries, ..Default::default() };
+    let delta_bytes = serde_json::to_vec(&delta).unwrap();
+
+    supplier
+        .api
+        .send(request::update_delta(*dir_key, delta_bytes))
+        .await
+        .unwrap();
+
+    recv_matching(&mut supplier.api, is_update_response, TIMEOUT)
+        .await
+        .unwrap_or_else(|| {
+            panic!(
+                "UpdateResponse for {}'s directory registration",
+                supplier.name
+            )
+        });
+}
+
+/// Deploy a user contract for a supplier with initial 10,000 CURD from root.
+pub async fn deploy_supplier_user_contract(
+    supplier: &mut Supplier,
+    root_key: &ContractKey,
+    node_url: &str,
+) {
+    let (uc_contract, uc_key) = make_user_contract(&supplier.verifying_key);
+
+    // Deterministic tx_ref so UI re-registration deduplicates against this credit.
+    let tx_ref = format!("genesis:{}", supplier.name.to_lowercase());
+    let now_str = chrono::Utc::now().to_rfc3339();
+
+    let initial_credit = WalletTransaction {
+        id: 0,
+        kind: TransactionKind::Credit,
+        amount: 10_000,
+        description: "Initial CURD allocation".to_string(),
+        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
+        receiver: supplier.name.clone(),
+        tx_ref: tx_ref.clone(),
+        timestamp: now_str.clone(),
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+
+    let uc_state = UserContractState {
+        owner: cream_common::identity::UserId(supplier.verifying_key),
+        name: supplier.name.clone(),
+        origin_supplier: supplier.name.clone(),
+        current_supplier: supplier.name.clone(),
+        balance_curds: 10_000,
+        invited_by: String::new(),
+        toll_rates: Default::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        ledger: vec![initial_credit],
+        next_tx_id: 1,
+        seq: 0,
+        updated_at: chrono::Utc::now(),
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    };
+
+    let uc_state_bytes = serde_json::to_vec(&uc_state).unwrap();
+
+    let mut deploy_api = connect_to_node_at(node_url).await;
+    wait_for_put(
+        &mut deploy_api,
+        uc_contract,
+        WrappedState::new(uc_state_bytes),
+        TIMEOUT,
+    )
+    .await
+    .unwrap_or_else(|| panic!("PutResponse for {}'s supplier user contract", supplier.name));
+    drop(deploy_api);
+
+    supplier.user_contract_key = Some(uc_key);
+
+    // Also record the debit on root's contract
+    let root_debit = WalletTransaction {
+        id: 0,
+        kind: TransactionKind::Debit,
+        amount: 10_000,
+        description: format!("Initial CURD allocation for {}", supplier.name),
+        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
+        receiver: supplier.name.clone(),
+        tx_ref,
+        timestamp: now_str,
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+
+    // GET root state, append debit, Update
+    let mut root_api = connect_to_node_at(node_url).await;
+    let root_bytes = wait_for_get(&mut root_api, *root_key.id(), TIMEOUT)
+        .await
+        .expect("GET root contract for supplier debit");
+    let mut root_state: UserContractState = serde_json::from_slice(&root_bytes).unwrap();
+    root_state.ledger.push(root_debit);
+    root_state.balance_curds = root_state.derive_balance();
+    root_state.next_tx_id = root_state.ledger.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    root_state.touch(chrono::Utc::now());
+    root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
+
+    let root_update_bytes = serde_json::to_vec(&root_state).unwrap();
+    root_api
+        .send(request::update(*root_key, root_update_bytes))
+        .await
+        .unwrap();
+
+    recv_matching(&mut root_api, is_update_response, TIMEOUT)
+        .await
+        .expect("UpdateResponse for root debit (supplier)");
+    drop(root_api);
+}
+
+/// Deploy a user contract for a customer with initial 10,000 CURD from root.
+pub async fn deploy_user_contract(
+    customer: &mut Customer,
+    root_key: &ContractKey,
+    node_url: &str,
+    invited_by: &str,
+) {
+    let (uc_contract, uc_key) = make_user_contract(&customer.verifying_key);
+
+    // Deterministic tx_ref so UI re-registration deduplicates against this credit.
+    let tx_ref = format!("genesis:{}", customer.name.to_lowercase());
+    let now_str = chrono::Utc::now().to_rfc3339();
+
+    let initial_credit = WalletTransaction {
+        id: 0,
+        kind: TransactionKind::Credit,
+        amount: 10_000,
+        description: "Initial CURD allocation".to_string(),
+        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
+        receiver: customer.name.clone(),
+        tx_ref: tx_ref.clone(),
+        timestamp: now_str.clone(),
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+
+    let uc_state = UserContractState {
+        owner: customer.id.clone(),
+        name: customer.name.clone(),
+        origin_supplier: invited_by.to_string(),
+        current_supplier: invited_by.to_string(),
+        balance_curds: 10_000,
+        invited_by: invited_by.to_string(),
+        toll_rates: Default::default(),
+        checkpoint_balance: 0,
+        checkpoint_tx_count: 0,
+        checkpoint_at: None,
+        pruned_lightning_hashes: Default::default(),
+        checkpoint_proof: None,
+        spending_key_debits: Default::default(),
+        mint_records: Vec::new(),
+        settlement_statements: Vec::new(),
+        vouchers: Vec::new(),
+        ledger: vec![initial_credit],
+        next_tx_id: 1,
+        seq: 0,
+        updated_at: chrono::Utc::now(),
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    };
+
+    let uc_state_bytes = serde_json::to_vec(&uc_state).unwrap();
+
+    let mut deploy_api = connect_to_node_at(node_url).await;
+    wait_for_put(
+        &mut deploy_api,
+        uc_contract,
+        WrappedState::new(uc_state_bytes),
+        TIMEOUT,
+    )
+    .await
+    .unwrap_or_else(|| panic!("PutResponse for {}'s user contract", customer.name));
+    drop(deploy_api);
+
+    customer.user_contract_key = Some(uc_key);
+
+    // Also record the debit on root's contract
+    let root_debit = WalletTransaction {
+        id: 0,
+        kind: TransactionKind::Debit,
+        amount: 10_000,
+        description: format!("Initial CURD allocation for {}", customer.name),
+        sender: cream_common::identity::ROOT_USER_NAME.to_string(),
+        receiver: customer.name.clone(),
+        tx_ref,
+        timestamp: now_str,
+        lightning_payment_hash: None,
+        payer: None,
+        provenance: None,
+        device: None,
+        extra: Default::default(),
+    };
+
+    // GET root state, append debit, Update
+    let mut root_api = connect_to_node_at(node_url).await;
+    let root_bytes = wait_for_get(&mut root_api, *root_key.id(), TIMEOUT)
+        .await
+        .expect("GET root contract for debit");
+    let mut root_state: UserContractState = serde_json::from_slice(&root_bytes).unwrap();
+    root_state.ledger.push(root_debit);
+    root_state.balance_curds = root_state.derive_balance();
+    root_state.next_tx_id = root_state.ledger.iter().map(|t| t.id).max().unwrap_or(0) + 1;
+    root_state.touch(chrono::Utc::now());
+    root_state.signature = cream_common::identity::root_sign(&root_state.signable_bytes());
+
+    let root_update_bytes = serde_json::to_vec(&root_state).unwrap();
+    root_api
+        .send(request::update(*root_key, root_update_bytes))
+        .await
+        .unwrap();
+
+    recv_matching(&mut root_api, is_update_response, TIMEOUT)
+        .await
+        .expect("UpdateResponse for root debit");
+    drop(root_api);
+}
+
+/// Deploy an inbox contract for an entity (supplier or customer).
+pub async fn deploy_inbox(
+    _api: &mut WebApi,
+    owner_vk: &VerifyingKey,
+    node_url_str: &str,
+    inbox_key_out: &mut Option<ContractKey>,
+) {
+    use cream_common::identity::UserId;
+    use cream_common::inbox::InboxState;
+
+    let (inbox_contract, inbox_key) = make_inbox_contract(owner_vk);
+    let ib_state = InboxState {
+        owner: UserId(*owner_vk),
+        messages: std::collections::BTreeMap::new(),
+        updated_at: chrono::Utc::now(),
+        extra: Default::default(),
+    };
+    let ib_state_bytes = serde_json::to_vec(&ib_state).unwrap();
+
+    let mut deploy_api = connect_to_node_at(node_url_str).await;
+    wait_for_put(
+        &mut deploy_api,
+        inbox_contract,
+        WrappedState::new(ib_state_bytes),
+        TIMEOUT,
+    )
+    .await
+    .expect("PutResponse for inbox contract");
+    drop(deploy_api);
+
+    *inbox_key_out = Some(inbox_key);
+}
+
+/// Deploy an inbox contract for a customer.
+pub async fn deploy_inbox_customer(customer: &mut Customer, node_url_str: &str) {
+    let vk = customer.verifying_key;
+    deploy_inbox(&mut customer.api, &vk, node_url_str, &mut customer.inbox_key).await;
+}
@@ -0,0 +1,252 @@
+//! Network-wide invariants a simulation must keep.
+
+use freenet_stdlib::prelude::*;
+
+use cream_common::user_contract::UserContractState;
+
+use crate::actors::{user_contract_key_for, SYSTEM_FLOAT, TIMEOUT};
+use crate::{connect_to_node_at, node_url, wait_for_get};
+
+/// Standalone CURD conservation check. Connects to a Freenet node, dynamically
+/// discovers all user contracts from system_root's ledger, and verifies that
+/// balances sum to SYSTEM_FLOAT and each contract's cached balance matches its
+/// ledger replay.
+///
+/// Discovery: GETs system_root's contract first, scans its ledger for all unique
+/// counterparty names (debit receivers + credit senders), computes their contract
+/// keys via `user_contract_key_for()`, and checks all of them.
+///
+/// `label` is a human-readable string for log/panic messages (e.g. "step 5 (pre)").
+/// `port` is the Freenet node WebSocket port to read from (e.g. 3002 for node tests,
+/// 3001 for E2E which operates through the gateway).
+///
+/// Unmatched transfers between the fetched ledgers (see
+/// [`cream_common::audit::audit_ledgers`]) are logged as warnings.
+///
+/// Retries up to 10 times with 1s backoff for Freenet eventual consistency.
+pub async fn check_curd_conservation(label: &str, port: u16) {
+    check_curd_conservation_at(label, &node_url(port)).await;
+}
+
+/// [`check_curd_conservation`] against the node at a full WebSocket URL.
+pub async fn check_curd_conservation_at(label: &str, url: &str) {
+    use std::collections::BTreeSet;
+    use cream_common::wallet::TransactionKind;
+
+    let policy = crate::consistency_retry_policy();
+    let max_attempts = policy.max_attempts.unwrap_or(1);
+    let mut backoff = policy.backoff(rand::random());
+
+    for attempt in 1..=max_attempts {
+        let mut api = connect_to_node_at(url).await;
+
+        // Phase 1: GET system_root to discover all participants
+        let root_key = user_contract_key_for("system_root");
+        let root_bytes = match wait_for_get(&mut api, *root_key.id(), TIMEOUT).await {
+            Some(b) => b,
+            None => {
+                if attempt < max_attempts {
+                    crate::backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+                panic!("Invariant check failed at {}: could not GET system_root after {} attempts", label, max_attempts);
+            }
+        };
+        let root_state: UserContractState = match serde_json::from_slice(&root_bytes) {
+            Ok(s) => s,
+            Err(_) => {
+                if attempt < max_attempts {
+                    crate::backoff_sleep(&mut backoff).await;
+                    continue;
+                }
+                panic!("Invariant check failed at {}: could not parse system_root state", label);
+            }
+        };
+
+        // Discover all user names from root's ledger.
+        // Use genesis tx_refs ("genesis:name") for reliable discovery — these are
+        // always present for allocated users and use the canonical lowercase name.
+        // Also discover non-genesis counterparties from sender/receiver fields,
+        // but skip known non-user placeholders.
+        let mut user_names: BTreeSet<String> = BTreeSet::new();
+        for tx in &root_state.ledger {
+            // Primary discovery: genesis tx_refs
+            if let Some(name) = tx.tx_ref.strip_prefix("genesis:") {
+                if !name.is_empty() && !name.contains(':') {
+                    user_names.insert(name.to_string());
+                    continue;
+                }
+            }
+
+            // Secondary discovery: sender/receiver fields for non-genesis transfers
+            let counterparty = match tx.kind {
+                TransactionKind::Debit => &tx.receiver,
+                TransactionKind::Credit => &tx.sender,
+            };
+            if !counterparty.is_empty()
+                && counterparty != cream_common::identity::ROOT_USER_NAME
+                && counterparty != "customer" // placeholder from cancel escrow
+            {
+                user_names.insert(counterparty.clone());
+            }
+        }
+
+        // Build contract list: system_root + all discovered users.
+        // Dedup by contract key since different name casings map to the same key
+        // (derive_user_signing_key normalizes to lowercase internally).
+        let mut seen_keys = std::collections::HashSet::new();
+        let mut contracts: Vec<(String, ContractKey)> = vec![
+            ("system_root".to_string(), root_key),
+        ];
+        seen_keys.insert(root_key);
+        for name in &user_names {
+            let key = user_contract_key_for(name);
+            if seen_keys.insert(key) {
+                contracts.push((name.clone(), key));
+            }
+        }
+
+        // Build a map of genesis allocations from root's ledger so we can
+        // substitute for contracts that haven't propagated yet.
+        let genesis_allocations: std::collections::HashMap<String, u64> = root_state.ledger.iter()
+            .filter_map(|tx| {
+                tx.tx_ref.strip_prefix("genesis:")
+                    .filter(|name| !name.is_empty() && !name.contains(':'))
+                    .map(|name| (name.to_string(), tx.amount))
+            })
+            .collect();
+
+        // Phase 2: GET all user contracts and collect states.
+        // Contracts that haven't propagated yet (GET fails or returns empty state)
+        // are substituted with their genesis allocation from root's ledger.
+        let mut user_states: Vec<(String, UserContractState)> = Vec::new();
+        let mut substituted_balances: Vec<(String, u64)> = Vec::new();
+        user_states.push(("system_root".to_string(), root_state));
+
+        let mut all_ok = true;
+        for (name, key) in contracts.iter().skip(1) {
+            let bytes = match wait_for_get(&mut api, *key.id(), TIMEOUT).await {
+                Some(b) => b,
+                None => {
+                    if let Some(&alloc) = genesis_allocations.get(name) {
+                        substituted_balances.push((name.clone(), alloc));
+                        continue;
+                    }
+                    all_ok = false;
+                    break;
+                }
+            };
+            let state: UserContractState = match serde_json::from_slice(&bytes) {
+                Ok(s) => s,
+                Err(_) => {
+                    all_ok = false;
+                    break;
+                }
+            };
+            if state.balance_curds == 0 && state.ledger.is_empty() {
+                if let Some(&alloc) = genesis_allocations.get(name) {
+                    substituted_balances.push((name.clone(), alloc));
+                    continue;
+                }
+            }
+            user_states.push((name.clone(), state));
+        }
+
+        if !all_ok {
+            if attempt < max_attempts {
+                crate::backoff_sleep(&mut backoff).await;
+                continue;
+            }
+            panic!(
+                "Invariant check failed at {}: could not GET all user contracts after {} attempts (discovered {} users)",
+                label, max_attempts, contracts.len()
+            );
+        }
+
+        // Check cached == derived for each contract
+        let mut cache_mismatch = false;
+        for (name, state) in &user_states {
+            if state.balance_curds != state.derive_balance() {
+                if attempt < max_attempts {
+                    cache_mismatch = true;
+                    break;
+                }
+                panic!(
+                    "Invariant FAILED at {}: {}'s balance_curds ({}) != derive_balance() ({})",
+                    label, name, state.balance_curds, state.derive_balance()
+                );
+            }
+        }
+        if cache_mismatch {
+            crate::backoff_sleep(&mut backoff).await;
+            continue;
+        }
+
+        // Compute deduped balances for conservation check.
+        // Freenet merge races can produce duplicate ledger entries with the same
+        // (tx_ref, kind). We dedup locally to get the true balance, and warn if
+        // duplicates are found.
+        let mut total_dups = 0usize;
+        let mut deduped_balances: Vec<(String, u64)> = Vec::new(); // (name, deduped_balance)
+        for (name, state) in &user_states {
+            let (deduped_balance, dups) = cream_common::audit::deduplicated_balance(state);
+            total_dups += dups;
+            deduped_balances.push((name.clone(), deduped_balance));
+        }
+
+        if total_dups > 0 {
+            println!(
+                "  [invariant] {} WARNING: {} duplicate ledger entries detected (Freenet merge race)",
+                label, total_dups
+            );
+        }
+
+        // Check CURD conservation using deduped balances + substituted balances
+        let total: u64 = deduped_balances.iter().map(|(_, bal)| bal).sum::<u64>()
+            + substituted_balances.iter().map(|(_, bal)| bal).sum::<u64>();
+        let mut detail: Vec<String> = deduped_balances
+            .iter()
+            .map(|(name, bal)| format!("{}={}", name, bal))
+            .collect();
+        for (name, bal) in &substituted_balances {
+            detail.push(format!("{}={}*", name, bal));
+        }
+
+        if total != SYSTEM_FLOAT {
+            if attempt < max_attempts {
+                println!(
+                    "  [invariant retry {}/{}] {}: total={} (want {}) [{} users]",
+                    attempt, max_attempts, label, total, SYSTEM_FLOAT, deduped_balances.len(),
+                );
+                crate::backoff_sleep(&mut backoff).await;
+                continue;
+            }
+            panic!(
+                "Invariant FAILED at {}: CURD total {} != {} ({}) [{} users discovered]",
+                label, total, SYSTEM_FLOAT, detail.join(", "), deduped_balances.len()
+            );
+        }
+
+        // Double entry across the fetched ledgers. Propagation lag can leave
+        // one side of a transfer behind, so mismatches are reported, not fatal.
+        let ledgers: Vec<(&str, &UserContractState)> =
+            user_states.iter().map(|(name, state)| (name.as_str(), state)).collect();
+        for finding in cream_common::audit::audit_ledgers(&ledgers) {
+            println!("  [invariant] {} WARNING: {}: {}", label, finding.contract, finding.detail);
+        }
+
+        // All checks passed
+        let sub_note = if !substituted_balances.is_empty() {
+            format!(", {}* not-yet-propagated (genesis substituted)", substituted_balances.len())
+        } else {
+            String::new()
+        };
+        let user_count = deduped_balances.len() + substituted_balances.len();
+        println!(
+            "  [invariant] {} OK: total={} ({}) [{} users{}]",
+            label, total, detail.join(", "), user_count, sub_note
+        );
+        return;
+    }
+}
+
@@ -0,0 +1,586 @@
+//! Simulated CREAM participants for tests, examples and demos.
+//!
+//! [`actors`] has the suppliers and customers the node integration tests
+//! drive: each holds its own WebSocket connection to a Freenet node and its
+//! own copy of the contracts it writes, and can be spawned into a running
+//! network with its contracts deployed and funded. [`script`] runs scripted
+//! sequences of their actions against a [`script::Sim`], with assertions on
+//! the resulting network state; [`invariants`] has the CURD conservation
+//! check. The free functions here are the lower-level node helpers they are
+//! built on: connecting, building contract containers from the embedded
+//! WASM, and waiting out Freenet's eventual consistency. Requests are built,
+//! and responses classified, by `cream-client` (its response helpers are
+//! re-exported here).
+//!
+//! The contract WASM is embedded at build time, so build the contracts
+//! (`cargo make build-contracts-dev`) before building this crate.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use freenet_stdlib::client_api::{
+    ClientError, ClientRequest, ContractRequest, ContractResponse, HostResponse, WebApi,
+};
+use freenet_stdlib::prelude::*;
+use tokio::time::Instant;
+
+use cream_client::request;
+pub use cream_client::response::*;
+
+use cream_common::directory::DirectoryEntry;
+use cream_common::retry::{Backoff, RetryPolicy};
+use cream_common::identity::UserId;
+use cream_common::location::GeoLocation;
+use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::storefront::{SignedProduct, StorefrontParameters};
+
+pub mod actors;
+pub mod invariants;
+pub mod script;
+
+/// Poll policy for Freenet eventual consistency: ~1s apart (with jitter), 10 attempts.
+pub fn consistency_retry_policy() -> RetryPolicy {
+    RetryPolicy::fixed(Duration::from_secs(1))
+        .with_jitter(0.2)
+        .with_max_attempts(10)
+}
+
+/// Re-send interval for GET/PUT polling; the caller's deadline bounds the attempts.
+fn resend_backoff() -> Backoff {
+    RetryPolicy::fixed(Duration::from_secs(1))
+        .with_jitter(0.2)
+        .backoff(rand::random())
+}
+
+/// Sleep for the backoff's next delay. Returns false (without sleeping) once
+/// the backoff is exhausted.
+pub async fn backoff_sleep(backoff: &mut Backoff) -> bool {
+    match backoff.next_delay() {
+        Some(delay) => {
+            tokio::time::sleep(delay).await;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Build a full WebSocket URL for a Freenet node on the given port.
+pub fn node_url(port: u16) -> String {
+    format!("ws://localhost:{port}/v1/contract/command?encodingProtocol=native")
+}
+
+/// Connect a native WebApi client to a Freenet node at an arbitrary URL.
+pub async fn connect_to_node_at(url: &str) -> WebApi {
+    let (ws_conn, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .unwrap_or_else(|e| panic!("Failed to connect to Freenet node at {url}: {e}"));
+    WebApi::start(ws_conn)
+}
+
+/// Build a ContractContainer from WASM bytes and parameters.
+fn make_contract(wasm_bytes: &[u8], params: Parameters<'static>) -> ContractContainer {
+    let code = ContractCode::from(wasm_bytes.to_vec());
+    let wrapped = WrappedContract::new(Arc::new(code), params);
+    ContractContainer::Wasm(ContractWasmAPIVersion::V1(wrapped))
+}
+
+/// Embedded contract WASM blobs (same ones the UI uses).
+const DIRECTORY_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm");
+const STOREFRONT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_storefront_contract.wasm");
+const USER_CONTRACT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm");
+const INBOX_CONTRACT_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_inbox_contract.wasm");
+const MARKET_DIRECTORY_WASM: &[u8] =
+    include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_market_directory_contract.wasm");
+
+/// Panic if any embedded contract WASM is stale against the artifact
+/// manifest: tests against stale contracts fail in confusing ways (or
+/// pass against keys the UI never uses). A build without a manifest only
+/// warns.
+pub fn assert_embedded_contracts() {
+    use cream_common::artifacts::{check_embedded, ArtifactError};
+    let embedded: [(&str, &[u8]); 5] = [
+        ("directory", DIRECTORY_WASM),
+        ("storefront", STOREFRONT_WASM),
+        ("user", USER_CONTRACT_WASM),
+        ("inbox", INBOX_CONTRACT_WASM),
+        ("market_directory", MARKET_DIRECTORY_WASM),
+    ];
+    for (name, wasm) in embedded {
+        match check_embedded(name, wasm) {
+            Ok(()) => {}
+            Err(ArtifactError::NoManifest) => {
+                tracing::warn!("{}", ArtifactError::NoManifest);
+                return;
+            }
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+/// Create a directory contract container + its key.
+pub fn make_directory_contract() -> (ContractContainer, ContractKey) {
+    let contract = make_contract(DIRECTORY_WASM, Parameters::from(vec![]));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create a directory shard contract container + its key.
+pub fn make_directory_shard_contract(
+    shard: cream_common::directory::DirectoryShard,
+) -> (ContractContainer, ContractKey) {
+    let params = cream_common::directory::DirectoryShardParameters::new(shard);
+    let contract = make_contract(DIRECTORY_WASM, Parameters::from(params.to_bytes()));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create a storefront contract container + its key for a given owner.
+pub fn make_storefront_contract(
+    owner: &ed25519_dalek::VerifyingKey,
+) -> (ContractContainer, ContractKey) {
+    let params = StorefrontParameters { owner: *owner };
+    let params_bytes = serde_json::to_vec(&params).unwrap();
+    let contract = make_contract(STOREFRONT_WASM, Parameters::from(params_bytes));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create a user contract container + its key for a given owner.
+pub fn make_user_contract(
+    owner: &ed25519_dalek::VerifyingKey,
+) -> (ContractContainer, ContractKey) {
+    let params = cream_common::user_contract::UserContractParameters {
+        owner: *owner,
+        spending_key: None,
+    };
+    let params_bytes = serde_json::to_vec(&params).unwrap();
+    let contract = make_contract(USER_CONTRACT_WASM, Parameters::from(params_bytes));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create a market directory contract container + its key.
+pub fn make_market_directory_contract() -> (ContractContainer, ContractKey) {
+    let contract = make_contract(MARKET_DIRECTORY_WASM, Parameters::from(vec![]));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create an inbox contract container + its key for a given owner.
+pub fn make_inbox_contract(
+    owner: &ed25519_dalek::VerifyingKey,
+) -> (ContractContainer, ContractKey) {
+    let params = cream_common::inbox::InboxParameters { owner: *owner };
+    let params_bytes = serde_json::to_vec(&params).unwrap();
+    let contract = make_contract(INBOX_CONTRACT_WASM, Parameters::from(params_bytes));
+    let key = contract.key();
+    (contract, key)
+}
+
+/// Create a deterministic user identity from a name.
+///
+/// Uses `derive_user_signing_key(name, password)` with `password = name.to_lowercase()`
+/// so that the test harness produces the same keys as the UI when a user logs
+/// in with their name as the password.
+pub fn make_dummy_user(name: &str) -> (UserId, ed25519_dalek::VerifyingKey) {
+    let password = name.to_lowercase();
+    let signing_key = cream_common::identity::derive_user_signing_key(name, &password);
+    let verifying_key = ed25519_dalek::VerifyingKey::from(&signing_key);
+    (UserId(verifying_key), verifying_key)
+}
+
+/// Create a dummy directory entry for a supplier.
+#[allow(clippy::too_many_arguments)]
+pub fn make_directory_entry(
+    user_id: &UserId,
+    name: &str,
+    description: &str,
+    postcode: &str,
+    locality: &str,
+    location: GeoLocation,
+    storefront_key: ContractKey,
+    user_contract_key: Option<ContractKey>,
+    inbox_contract_key: Option<ContractKey>,
+) -> DirectoryEntry {
+    DirectoryEntry {
+        supplier: user_id.clone(),
+        name: name.to_string(),
+        description: description.to_string(),
+        location,
+        postcode: Some(postcode.to_string()),
+        locality: Some(locality.to_string()),
+        categories: vec![],
+        storefront_key,
+        user_contract_key,
+        inbox_contract_key,
+        updated_at: chrono::Utc::now(),
+        name_claimed_at: None,
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    }
+}
+
+/// Create a dummy market entry.
+pub fn make_dummy_market_entry(
+    organizer: &UserId,
+    name: &str,
+    venue_address: &str,
+    location: GeoLocation,
+    suppliers: std::collections::BTreeMap<String, cream_common::market::SupplierStatus>,
+) -> cream_common::market::MarketEntry {
+    use chrono::NaiveDate;
+    cream_common::market::MarketEntry {
+        organizer: organizer.clone(),
+        name: name.to_string(),
+        description: format!("{name} — fresh produce from local farmers"),
+        venue_address: venue_address.to_string(),
+        location,
+        postcode: Some("2450".to_string()),
+        locality: Some("Coffs Harbour".to_string()),
+        events: vec![
+            cream_common::market::MarketEvent {
+                date: NaiveDate::from_ymd_opt(2026, 4, 5).unwrap(),
+                start_time: "07:00".to_string(),
+                end_time: "13:00".to_string(),
+                extra: Default::default(),
+            },
+            cream_common::market::MarketEvent {
+                date: NaiveDate::from_ymd_opt(2026, 4, 19).unwrap(),
+                start_time: "07:00".to_string(),
+                end_time: "13:00".to_string(),
+                extra: Default::default(),
+            },
+        ],
+        timezone: Some("Australia/Sydney".to_string()),
+        suppliers,
+        updated_at: chrono::Utc::now(),
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        extra: Default::default(),
+    }
+}
+
+/// Create a dummy product.
+pub fn make_dummy_product(name: &str) -> SignedProduct {
+    let now = chrono::Utc::now();
+    SignedProduct {
+        product: Product {
+            id: ProductId(format!("p-{}", now.timestamp_millis())),
+            name: name.to_string(),
+            description: format!("Fresh {name}"),
+            category: ProductCategory::Milk,
+            subcategory: None,
+            attributes: Default::default(),
+            price_curd: 500,
+            quantity_total: 10,
+            expiry_date: None,
+            updated_at: now,
+            created_at: now,
+            low_stock: None,
+            paused: false,
+            special: None,
+            wholesale: None,
+            extra: Default::default(),
+        },
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        provenance: None,
+        price_history: Vec::new(),
+        extra: Default::default(),
+    }
+}
+
+/// Create a dummy order with a configurable deposit tier and creation timestamp.
+///
+/// The `created_at` parameter lets tests create backdated orders whose reservation
+/// has already expired relative to "now", enabling expiry tests without real waits.
+pub fn make_dummy_order(
+    product_id: &ProductId,
+    user_id: &UserId,
+    tier: DepositTier,
+    quantity: u32,
+    price_per_unit: u64,
+    created_at: chrono::DateTime<chrono::Utc>,
+) -> Order {
+    let total_price = price_per_unit * quantity as u64;
+    let deposit_amount = tier.calculate_deposit(total_price);
+    let expires_at = created_at + tier.hold_period();
+    let nonce: u64 = rand::random();
+    Order {
+        id: OrderId::derive(user_id, product_id, created_at, nonce),
+        product_id: product_id.clone(),
+        customer: user_id.clone(),
+        quantity,
+        deposit_tier: tier,
+        deposit_amount,
+        total_price,
+        status: OrderStatus::Reserved { expires_at },
+        created_at,
+        signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+        escrow_token: None,
+        collection_point: None,
+        delivery: None,
+        provenance: None,
+        receipt: None,
+        amendments: Vec::new(),
+        status_history: Vec::new(),
+        cancellation_policy: None,
+        refund_amount: None,
+        balance_attestation: None,
+        nonce,
+        extra: Default::default(),
+    }
+}
+
+/// Wait for a HostResponse matching a predicate, with timeout.
+/// Non-matching responses are logged and discarded.
+pub async fn recv_matching<F>(
+    api: &mut WebApi,
+    predicate: F,
+    timeout: Duration,
+) -> Option<HostResponse>
+where
+    F: Fn(&HostResponse) -> bool,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return None;
+        }
+        match tokio::time::timeout(remaining, api.recv()).await {
+            Ok(Ok(resp)) if predicate(&resp) => return Some(resp),
+            Ok(Ok(other)) => {
+                tracing::debug!("Discarding non-matching response: {:?}", other);
+                continue;
+            }
+            Ok(Err(e)) => {
+                tracing::error!("Node error while waiting: {:?}", e);
+                return None;
+            }
+            Err(_) => return None, // timeout
+        }
+    }
+}
+
+/// How the node answered a request.
+#[derive(Debug)]
+pub enum RequestOutcome {
+    /// The response the caller was waiting for.
+    Accepted(Box<HostResponse>),
+    /// The node returned an error instead.
+    Rejected(ClientError),
+    /// Neither arrived before the timeout.
+    TimedOut,
+}
+
+/// Send `request` and wait for the response matching `accepted` or an error,
+/// whichever comes first. Other responses (e.g. notifications) are discarded.
+///
+/// Unlike [`wait_for_put`] this doesn't re-send: a request that is expected
+/// to fail must fail the first time.
+pub async fn send_for_outcome<F>(
+    api: &mut WebApi,
+    request: ClientRequest<'static>,
+    accepted: F,
+    timeout: Duration,
+) -> RequestOutcome
+where
+    F: Fn(&HostResponse) -> bool,
+{
+    api.send(request).await.unwrap();
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return RequestOutcome::TimedOut;
+        }
+        match tokio::time::timeout(remaining, api.recv()).await {
+            Ok(Ok(resp)) if accepted(&resp) => return RequestOutcome::Accepted(Box::new(resp)),
+            Ok(Ok(other)) => {
+                tracing::debug!("Discarding non-matching response: {:?}", other);
+            }
+            Ok(Err(e)) => return RequestOutcome::Rejected(e),
+            Err(_) => return RequestOutcome::TimedOut,
+        }
+    }
+}
+
+/// Send an UPDATE that the contract must refuse, and return the reason the
+/// node gave. Panics if the update is accepted, fails some other way, or
+/// gets no answer.
+pub async fn expect_update_rejected(
+    api: &mut WebApi,
+    key: ContractKey,
+    data: UpdateData<'static>,
+    timeout: Duration,
+) -> String {
+    let request = ClientRequest::ContractOp(ContractRequest::Update { key, data });
+    match send_for_outcome(api, request, is_update_response, timeout).await {
+        RequestOutcome::Rejected(e) => update_rejection(&e, &key)
+            .unwrap_or_else(|| panic!("expected update of {key} to be rejected, got error: {e}")),
+        RequestOutcome::Accepted(resp) => {
+            panic!("expected update of {key} to be rejected, but it was accepted: {resp:?}")
+        }
+        RequestOutcome::TimedOut => {
+            panic!("expected update of {key} to be rejected, got no answer within {timeout:?}")
+        }
+    }
+}
+
+/// Send a PUT that the contract must refuse, and return the reason the node
+/// gave. Panics if the put is accepted, fails some other way, or gets no
+/// answer.
+pub async fn expect_put_rejected(
+    api: &mut WebApi,
+    contract: ContractContainer,
+    state: WrappedState,
+    timeout: Duration,
+) -> String {
+    let key = contract.key();
+    let request = request::put(contract, state.as_ref().to_vec(), false);
+    match send_for_outcome(api, request, is_put_response, timeout).await {
+        RequestOutcome::Rejected(e) => put_rejection(&e, &key)
+            .unwrap_or_else(|| panic!("expected put of {key} to be rejected, got error: {e}")),
+        RequestOutcome::Accepted(resp) => {
+            panic!("expected put of {key} to be rejected, but it was accepted: {resp:?}")
+        }
+        RequestOutcome::TimedOut => {
+            panic!("expected put of {key} to be rejected, got no answer within {timeout:?}")
+        }
+    }
+}
+
+/// Retry GET on a contract until it succeeds or the timeout expires.
+/// Returns the state bytes on success, None on timeout.
+pub async fn wait_for_get(
+    api: &mut WebApi,
+    key: ContractInstanceId,
+    timeout: Duration,
+) -> Option<Vec<u8>> {
+    let deadline = Instant::now() + timeout;
+    let mut backoff = resend_backoff();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return None;
+        }
+
+        api.send(request::get(key, true)).await.unwrap();
+
+        match tokio::time::timeout(Duration::from_secs(5), api.recv()).await {
+            Ok(Ok(HostResponse::ContractResponse(ContractResponse::GetResponse {
+                state, ..
+            }))) => {
+                return Some(state.as_ref().to_vec());
+            }
+            Ok(Ok(other)) => {
+                tracing::debug!("wait_for_get: non-GET response: {:?}", other);
+            }
+            Ok(Err(e)) => {
+                tracing::debug!("wait_for_get: error: {:?}", e);
+            }
+            Err(_) => {
+                tracing::debug!("wait_for_get: recv timeout, will retry");
+            }
+        }
+
+        backoff_sleep(&mut backoff).await;
+    }
+}
+
+/// Send PUT and drain messages until a PutResponse is received, or timeout expires.
+/// If the recv times out without a PutResponse, re-sends the PUT and tries again.
+pub async fn wait_for_put(
+    api: &mut WebApi,
+    contract: ContractContainer,
+    state: WrappedState,
+    timeout: Duration,
+) -> Option<HostResponse> {
+    let deadline = Instant::now() + timeout;
+    let mut backoff = resend_backoff();
+    let mut attempt = 0u32;
+    loop {
+        attempt += 1;
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            tracing::error!("wait_for_put: timed out after {attempt} attempts");
+            return None;
+        }
+
+        api.send(request::put(contract.clone(), state.as_ref().to_vec(), false))
+            .await
+            .unwrap();
+
+        // Drain messages for up to 30s looking for the PutResponse
+        let drain_deadline = Instant::now() + Duration::from_secs(30).min(remaining);
+        loop {
+            let drain_remaining = drain_deadline.saturating_duration_since(Instant::now());
+            if drain_remaining.is_zero() {
+                tracing::debug!("wait_for_put: drain timeout on attempt {attempt}, will re-send");
+                break;
+            }
+            match tokio::time::timeout(drain_remaining, api.recv()).await {
+                Ok(Ok(resp)) if is_put_response(&resp) => {
+                    if attempt > 1 {
+                        tracing::info!("wait_for_put: succeeded on attempt {attempt}");
+                    }
+                    return Some(resp);
+                }
+                Ok(Ok(_other)) => {
+                    // Non-PUT message (e.g. notification), keep draining
+                    continue;
+                }
+                Ok(Err(e)) => {
+                    tracing::debug!("wait_for_put: error on attempt {attempt}: {:?}", e);
+                    break;
+                }
+                Err(_) => {
+                    tracing::debug!(
+                        "wait_for_put: drain timeout on attempt {attempt}, will re-send"
+                    );
+                    break;
+                }
+            }
+        }
+
+        backoff_sleep(&mut backoff).await;
+    }
+}
+
+/// Like `wait_for_get`, but also returns how long it took.
+pub async fn timed_wait_for_get(
+    api: &mut WebApi,
+    key: ContractInstanceId,
+    timeout: Duration,
+) -> Option<(Vec<u8>, Duration)> {
+    let start = Instant::now();
+    let bytes = wait_for_get(api, key, timeout).await?;
+    Some((bytes, start.elapsed()))
+}
+
+/// Compute the "root" admin user's pubkey as a lowercase hex string.
+///
+/// Uses the same deterministic key derivation as `make_dummy_user("root")`.
+pub fn root_admin_pubkey_hex() -> String {
+    let (_id, vk) = make_dummy_user("root");
+    vk.as_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Like `recv_matching`, but also returns how long it took.
+pub async fn timed_recv_matching<F>(
+    api: &mut WebApi,
+    predicate: F,
+    timeout: Duration,
+) -> Option<(HostResponse, Duration)>
+where
+    F: Fn(&HostResponse) -> bool,
+{
+    let start = Instant::now();
+    let resp = recv_matching(api, predicate, timeout).await?;
+    Some((resp, start.elapsed()))
+}
+
@@ -0,0 +1,326 @@
+//! Scripted simulations.
+//!
+//! A [`Sim`] is a marketplace on one node: the shared contracts plus the
+//! suppliers and customers spawned into it, addressed by name. A script is a
+//! list of [`Action`]s — spawning participants, the things they do, and
+//! expectations about the state that results — run in order by [`Sim::run`].
+//! Expectations are checked against what the network holds, not the actors'
+//! local copies.
+//!
+//! ```ignore
+//! let mut sim = Sim::bootstrap(&node_url(3001)).await;
+//! sim.run(vec![
+//!     Action::SpawnSupplier(SupplierProfile::new("Gary", "2450").unwrap()),
+//!     Action::SpawnCustomer { name: "Alice".into(), invited_by: "Gary".into() },
+//!     Action::AddProduct {
+//!         supplier: "Gary".into(),
+//!         product: "Raw Milk".into(),
+//!         category: ProductCategory::Milk,
+//!         price_curd: 500,
+//!     },
+//!     Action::PlaceOrder {
+//!         customer: "Alice".into(),
+//!         supplier: "Gary".into(),
+//!         product: "Raw Milk".into(),
+//!         quantity: 2,
+//!         tier: DepositTier::Reserve2Days,
+//!         order: "alice-milk".into(),
+//!     },
+//!     Action::ExpectOrderStatus {
+//!         supplier: "Gary".into(),
+//!         order: "alice-milk".into(),
+//!         status: "Reserved".into(),
+//!     },
+//!     Action::ExpectConserved,
+//! ])
+//! .await?;
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use freenet_stdlib::prelude::*;
+
+use cream_client::request;
+use cream_common::clock::{Clock, MockClock};
+use cream_common::directory::{DirectoryShard, DirectoryState};
+use cream_common::order::{DepositTier, OrderId};
+use cream_common::product::ProductCategory;
+use cream_common::storefront::StorefrontState;
+
+use crate::actors::{
+    deploy_root_contract, user_contract_key_for, Customer, Rendezvous, Supplier, SupplierProfile,
+    TIMEOUT,
+};
+use crate::invariants::check_curd_conservation_at;
+use crate::{
+    connect_to_node_at, is_put_response, make_directory_shard_contract, make_dummy_order,
+    recv_matching, wait_for_get,
+};
+
+/// One step of a script. Participants, products and orders are referred to
+/// by name; order names are the script's own, mapped to the ids the orders
+/// get when placed.
+#[derive(Clone, Debug)]
+pub enum Action {
+    /// Spawn a supplier and list them in their postcode's directory shard.
+    SpawnSupplier(SupplierProfile),
+    /// Spawn a customer whose origin supplier is `invited_by`.
+    SpawnCustomer { name: String, invited_by: String },
+    AddProduct {
+        supplier: String,
+        product: String,
+        category: ProductCategory,
+        price_curd: u64,
+    },
+    /// Place an order for `quantity` of a supplier's product, remembered as `order`.
+    PlaceOrder {
+        customer: String,
+        supplier: String,
+        product: String,
+        quantity: u32,
+        tier: DepositTier,
+        order: String,
+    },
+    ProposeFulfillment { supplier: String, order: String },
+    /// The customer counter-signs a proposed fulfillment, completing it.
+    ConfirmFulfillment { customer: String, supplier: String, order: String },
+    /// Run order expiry on a supplier's storefront at the simulation clock's time.
+    ExpireOrders { supplier: String },
+    /// Move the simulation clock forward.
+    AdvanceClock(chrono::Duration),
+    /// The order's status on the network has this label (see
+    /// [`cream_common::order::OrderStatus::label`]).
+    ExpectOrderStatus { supplier: String, order: String, status: String },
+    /// A customer's CURD balance, as the simulation tracks it.
+    ExpectBalance { customer: String, balance: u64 },
+    /// CURD is conserved across every user contract on the node.
+    ExpectConserved,
+}
+
+/// A marketplace on one node and everyone in it.
+pub struct Sim {
+    /// WebSocket URL of the node every participant connects to.
+    pub node_url: String,
+    pub root_contract_key: ContractKey,
+    /// Directory shards deployed so far, one per postcode state in use.
+    pub directories: BTreeMap<DirectoryShard, ContractKey>,
+    /// Drives order placement and expiry. Starts a week before bootstrap, so
+    /// scripts can advance it past a reservation's hold period without dating
+    /// anything ahead of the node's clock, which contracts reject.
+    pub clock: Arc<MockClock>,
+    pub suppliers: BTreeMap<String, Supplier>,
+    pub customers: BTreeMap<String, Customer>,
+    /// Every spawned supplier is registered, so customers can onboard by name.
+    pub rendezvous: Rendezvous,
+    /// Script order names to the ids the orders were placed with.
+    pub orders: BTreeMap<String, OrderId>,
+    expect_step: usize,
+}
+
+impl Sim {
+    /// Start a simulation against the node at `node_url`, deploying system_root's
+    /// user contract unless the node already has it.
+    pub async fn bootstrap(node_url: &str) -> Self {
+        let root_key = user_contract_key_for("system_root");
+        let mut probe = connect_to_node_at(node_url).await;
+        let root_contract_key = if wait_for_get(&mut probe, *root_key.id(), Duration::from_secs(5))
+            .await
+            .is_some()
+        {
+            tracing::info!("system_root already deployed, reusing it");
+            root_key
+        } else {
+            deploy_root_contract(node_url).await
+        };
+
+        Sim {
+            node_url: node_url.to_string(),
+            root_contract_key,
+            directories: BTreeMap::new(),
+            clock: Arc::new(MockClock::new(chrono::Utc::now() - chrono::Duration::weeks(1))),
+            suppliers: BTreeMap::new(),
+            customers: BTreeMap::new(),
+            rendezvous: Rendezvous::default(),
+            orders: BTreeMap::new(),
+            expect_step: 0,
+        }
+    }
+
+    /// The directory shard contract for `shard`, PUT on first use.
+    async fn directory(&mut self, shard: DirectoryShard) -> ContractKey {
+        if let Some(key) = self.directories.get(&shard) {
+            return *key;
+        }
+        let (contract, key) = make_directory_shard_contract(shard);
+        let state = serde_json::to_vec(&DirectoryState::default()).unwrap();
+        let mut api = connect_to_node_at(&self.node_url).await;
+        api.send(request::put(contract, state, false))
+        .await
+        .unwrap();
+        if recv_matching(&mut api, is_put_response, Duration::from_secs(2)).await.is_none() {
+            tracing::info!("{} directory already exists, continuing", shard.label());
+        }
+        self.directories.insert(shard, key);
+        key
+    }
+
+    /// Spawn a supplier into the simulation; see [`Supplier::spawn`].
+    pub async fn spawn_supplier(
+        &mut self,
+        profile: SupplierProfile,
+    ) -> Result<&mut Supplier, String> {
+        let shard = DirectoryShard::for_postcode(&profile.postcode)
+            .ok_or_else(|| format!("{} has no directory shard", profile.postcode))?;
+        let directory_key = self.directory(shard).await;
+        let name = profile.name.clone();
+        let supplier = Supplier::spawn(
+            &self.node_url,
+            profile,
+            &self.root_contract_key,
+            &directory_key,
+            self.clock.clone(),
+        )
+        .await;
+        self.rendezvous.register(&supplier);
+        Ok(self.suppliers.entry(name).or_insert(supplier))
+    }
+
+    /// Spawn a customer into the simulation; see [`Customer::spawn`].
+    pub async fn spawn_customer(&mut self, name: &str, invited_by: &str) -> &mut Customer {
+        let customer =
+            Customer::spawn(&self.node_url, name, &self.root_contract_key, invited_by).await;
+        self.customers.entry(name.to_string()).or_insert(customer)
+    }
+
+    pub fn supplier(&mut self, name: &str) -> Result<&mut Supplier, String> {
+        self.suppliers
+            .get_mut(name)
+            .ok_or_else(|| format!("no supplier named {}", name))
+    }
+
+    pub fn customer(&mut self, name: &str) -> Result<&mut Customer, String> {
+        self.customers
+            .get_mut(name)
+            .ok_or_else(|| format!("no customer named {}", name))
+    }
+
+    fn order_id(&self, order: &str) -> Result<String, String> {
+        self.orders
+            .get(order)
+            .map(|id| id.0.clone())
+            .ok_or_else(|| format!("no order named {}", order))
+    }
+
+    /// Run a script, stopping at the first action that can't be carried out or
+    /// expectation that isn't met.
+    pub async fn run(&mut self, script: Vec<Action>) -> Result<(), String> {
+        for action in script {
+            self.perform(action).await?;
+        }
+        Ok(())
+    }
+
+    /// Carry out one action.
+    pub async fn perform(&mut self, action: Action) -> Result<(), String> {
+        match action {
+            Action::SpawnSupplier(profile) => {
+                self.spawn_supplier(profile).await?;
+            }
+            Action::SpawnCustomer { name, invited_by } => {
+                self.spawn_customer(&name, &invited_by).await;
+            }
+            Action::AddProduct { supplier, product, category, price_curd } => {
+                self.supplier(&supplier)?.add_product(&product, category, price_curd).await;
+            }
+            Action::PlaceOrder { customer, supplier, product, quantity, tier, order } => {
+                let now = self.clock.now();
+                let seller = self
+                    .suppliers
+                    .get_mut(&supplier)
+                    .ok_or_else(|| format!("no supplier named {}", supplier))?;
+                let buyer = self
+                    .customers
+                    .get_mut(&customer)
+                    .ok_or_else(|| format!("no customer named {}", customer))?;
+                let listed = seller
+                    .storefront
+                    .products
+                    .values()
+                    .find(|p| p.product.name == product)
+                    .ok_or_else(|| format!("{} doesn't sell {}", supplier, product))?;
+                let placed = make_dummy_order(
+                    &listed.product.id,
+                    &buyer.id,
+                    tier,
+                    quantity,
+                    listed.product.price_curd,
+                    now,
+                );
+                let id = placed.id.clone();
+                buyer.place_order(placed, seller).await?;
+                self.orders.insert(order, id);
+            }
+            Action::ProposeFulfillment { supplier, order } => {
+                let id = self.order_id(&order)?;
+                self.supplier(&supplier)?.propose_fulfillment(&id).await;
+            }
+            Action::ConfirmFulfillment { customer, supplier, order } => {
+                let id = self.order_id(&order)?;
+                let seller = self
+                    .suppliers
+                    .get_mut(&supplier)
+                    .ok_or_else(|| format!("no supplier named {}", supplier))?;
+                let buyer = self
+                    .customers
+                    .get_mut(&customer)
+                    .ok_or_else(|| format!("no customer named {}", customer))?;
+                buyer.confirm_fulfillment(&id, seller).await;
+            }
+            Action::ExpireOrders { supplier } => {
+                self.supplier(&supplier)?.expire_orders().await;
+            }
+            Action::AdvanceClock(by) => {
+                self.clock.advance(by);
+            }
+            Action::ExpectOrderStatus { supplier, order, status } => {
+                let id = self.order_id(&order)?;
+                let storefront = self.storefront_on_network(&supplier).await?;
+                let actual = storefront
+                    .orders
+                    .get(&OrderId(id))
+                    .map(|o| o.status.label())
+                    .ok_or_else(|| format!("order {} is not on {}'s storefront", order, supplier))?;
+                if actual != status {
+                    return Err(format!("order {} is {}, expected {}", order, actual, status));
+                }
+            }
+            Action::ExpectBalance { customer, balance } => {
+                let actual = self.customer(&customer)?.balance;
+                if actual != balance {
+                    return Err(format!("{} has {} CURD, expected {}", customer, actual, balance));
+                }
+            }
+            Action::ExpectConserved => {
+                self.expect_step += 1;
+                let label = format!("sim check {}", self.expect_step);
+                check_curd_conservation_at(&label, &self.node_url).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// GET a supplier's storefront as the network has it.
+    pub async fn storefront_on_network(
+        &mut self,
+        supplier: &str,
+    ) -> Result<StorefrontState, String> {
+        let seller = self.supplier(supplier)?;
+        let id = *seller.storefront_key.id();
+        let bytes = wait_for_get(&mut seller.api, id, TIMEOUT)
+            .await
+            .ok_or_else(|| format!("could not GET {}'s storefront", supplier))?;
+        serde_json::from_slice(&bytes).map_err(|e| format!("{}'s storefront: {}", supplier, e))
+    }
+}
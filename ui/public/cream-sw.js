@@ -0,0 +1,205 @@
+// CREAM background sync service worker.
+//
+// Watches the user's storefront and inbox while no CREAM tab is open and
+// raises notifications for new orders, order status changes and new
+// messages. The page configures it (see background_sync.rs): the node's
+// WebSocket URL and, per watched contract, a ready-encoded GET request, so
+// this worker never has to speak the node protocol itself. Each GET response
+// ends with the contract's JSON state, which is all we read from it.
+//
+// The browser decides when we run: on each periodic sync (where supported)
+// and whenever the page asks for a check.
+
+const CONFIG_CACHE = 'cream-sw';
+const CONFIG_URL = 'cream-sw/config';
+const SEEN_URL = 'cream-sw/seen';
+const SYNC_TAG = 'cream-sync';
+const RESPONSE_TIMEOUT_MS = 15000;
+
+self.addEventListener('install', () => self.skipWaiting());
+self.addEventListener('activate', (event) => event.waitUntil(self.clients.claim()));
+
+async function load(url) {
+  const cache = await caches.open(CONFIG_CACHE);
+  const response = await cache.match(url);
+  return response ? response.json() : null;
+}
+
+async function store(url, value) {
+  const cache = await caches.open(CONFIG_CACHE);
+  await cache.put(url, new Response(JSON.stringify(value)));
+}
+
+self.addEventListener('message', (event) => {
+  const message = event.data || {};
+  if (message.type === 'configure') {
+    // Take a fresh baseline so nothing already seen in the tab is announced.
+    event.waitUntil(store(CONFIG_URL, message.config).then(() => check({ notify: false })));
+  } else if (message.type === 'check') {
+    event.waitUntil(check({ notify: true }));
+  }
+});
+
+self.addEventListener('periodicsync', (event) => {
+  if (event.tag === SYNC_TAG) {
+    event.waitUntil(check({ notify: true }));
+  }
+});
+
+self.addEventListener('notificationclick', (event) => {
+  event.notification.close();
+  event.waitUntil(
+    self.clients.matchAll({ type: 'window', includeUncontrolled: true }).then((windows) => {
+      const open = windows.find((w) => 'focus' in w);
+      return open ? open.focus() : self.clients.openWindow(self.registration.scope);
+    }),
+  );
+});
+
+// ─── Checking ────────────────────────────────────────────────────────────────
+
+async function check({ notify }) {
+  const config = await load(CONFIG_URL);
+  if (!config || !config.node_url || !config.watches || config.watches.length === 0) {
+    return;
+  }
+  const states = await fetchStates(config.node_url, config.watches);
+  const seen = (await load(SEEN_URL)) || {};
+
+  // A visible CREAM tab shows changes itself; just move the baseline.
+  const windows = await self.clients.matchAll({ type: 'window' });
+  const tabVisible = windows.some((w) => w.visibilityState === 'visible');
+
+  for (const [index, watch] of config.watches.entries()) {
+    const state = states[index];
+    if (!state) continue;
+    const key = watch.kind + ':' + watch.instance_id.join(',');
+    const now = snapshot(watch, state);
+    const before = seen[key];
+    if (before && notify && !tabVisible && Notification.permission === 'granted') {
+      const body = describe(watch, before, now);
+      if (body) {
+        await self.registration.showNotification(watch.title, { body, tag: key });
+      }
+    }
+    seen[key] = now;
+  }
+  await store(SEEN_URL, seen);
+}
+
+// What we remember of a state between checks: order ids (and, for a
+// customer, each of their orders' status), or inbox message ids.
+function snapshot(watch, state) {
+  switch (watch.kind) {
+    case 'new_orders':
+      return Object.keys(state.orders || {});
+    case 'order_status': {
+      const statuses = {};
+      for (const [id, order] of Object.entries(state.orders || {})) {
+        if (order.customer === watch.customer) {
+          statuses[id] = typeof order.status === 'string' ? order.status : Object.keys(order.status)[0];
+        }
+      }
+      return statuses;
+    }
+    case 'new_messages':
+      return Object.keys(state.messages || {});
+    default:
+      return null;
+  }
+}
+
+function plural(n, one, many) {
+  return n === 1 ? `1 ${one}` : `${n} ${many}`;
+}
+
+function describe(watch, before, now) {
+  switch (watch.kind) {
+    case 'new_orders': {
+      const added = now.filter((id) => !before.includes(id)).length;
+      return added > 0 ? plural(added, 'new order', 'new orders') : null;
+    }
+    case 'order_status': {
+      const changed = Object.keys(now).filter((id) => before[id] && before[id] !== now[id]);
+      if (changed.length === 1) return `An order is now ${now[changed[0]]}`;
+      return changed.length > 1 ? `${changed.length} orders have changed status` : null;
+    }
+    case 'new_messages': {
+      const added = now.filter((id) => !before.includes(id)).length;
+      return added > 0 ? plural(added, 'new message', 'new messages') : null;
+    }
+    default:
+      return null;
+  }
+}
+
+// ─── Node connection ─────────────────────────────────────────────────────────
+
+// Send every watch's GET over one WebSocket and collect the JSON states, in
+// watch order (`null` where the node didn't answer in time).
+function fetchStates(nodeUrl, watches) {
+  return new Promise((resolve) => {
+    const states = watches.map(() => null);
+    let pending = watches.length;
+    let socket;
+    const finish = () => {
+      clearTimeout(timer);
+      if (socket && socket.readyState <= WebSocket.OPEN) socket.close();
+      resolve(states);
+    };
+    const timer = setTimeout(finish, RESPONSE_TIMEOUT_MS);
+
+    try {
+      socket = new WebSocket(nodeUrl);
+    } catch {
+      finish();
+      return;
+    }
+    socket.binaryType = 'arraybuffer';
+    socket.onopen = () => {
+      for (const watch of watches) socket.send(new Uint8Array(watch.get_request));
+    };
+    socket.onmessage = (event) => {
+      if (!(event.data instanceof ArrayBuffer)) return;
+      const frame = new Uint8Array(event.data);
+      const index = watches.findIndex(
+        (watch, i) => states[i] === null && contains(frame, watch.instance_id),
+      );
+      if (index < 0) return;
+      const state = trailingJson(frame);
+      if (state !== null) {
+        states[index] = state;
+        if (--pending === 0) finish();
+      }
+    };
+    socket.onerror = finish;
+    socket.onclose = finish;
+  });
+}
+
+function contains(haystack, needle) {
+  outer: for (let i = 0; i + needle.length <= haystack.length; i++) {
+    for (let j = 0; j < needle.length; j++) {
+      if (haystack[i + j] !== needle[j]) continue outer;
+    }
+    return true;
+  }
+  return false;
+}
+
+// The contract state at the end of a GET response: a JSON object preceded
+// by its length as a little-endian u64 and running to the end of the frame.
+function trailingJson(frame) {
+  const view = new DataView(frame.buffer, frame.byteOffset, frame.byteLength);
+  for (let start = 8; start < frame.length; start++) {
+    if (frame[start] !== 0x7b /* { */) continue;
+    const length = Number(view.getBigUint64(start - 8, true));
+    if (length !== frame.length - start) continue;
+    try {
+      return JSON.parse(new TextDecoder().decode(frame.subarray(start)));
+    } catch {
+      return null;
+    }
+  }
+  return null;
+}
@@ -4,10 +4,15 @@ use dioxus_router::Navigator;
 use cream_common::postcode::{
     is_valid_postcode, lookup_all_localities, lookup_postcode, PostcodeInfo,
 };
+use cream_common::retry::{retry, CancellationToken, RetryPolicy};
 
+use super::contract_inspector_view::ContractInspectorView;
+use super::debug_log_view::DebugLogView;
 use super::directory_view::DirectoryView;
+use super::error_messages::Remedy;
 use super::faq_view::FaqView;
 use super::guardian_admin::GuardianAdmin;
+use super::i18n::{use_i18n, LocalePicker, Msg, PrecisionPicker};
 use super::iaq_view::IaqView;
 use super::key_manager::KeyManager;
 use super::market_dashboard::MarketDashboard;
@@ -15,11 +20,12 @@ use super::market_view::MarketView;
 use super::markets_list_view::MarketsListView;
 use super::my_orders::MyOrders;
 use super::node_api::{use_node_action, use_node_coroutine, NodeAction};
-use super::shared_state::{use_shared_state, SharedState};
+use super::node_settings::NodeSettingsView;
+use super::shared_state::{use_shared_state, PendingOp, SharedState, UnreadCounts};
 use super::messages_view::MessagesView;
 use super::storefront_view::StorefrontView;
-use super::supplier_dashboard::SupplierDashboard;
-use super::user_state::{use_user_state, UserState};
+use super::supplier_dashboard::{use_auto_pause, SupplierDashboard};
+use super::user_state::{use_user_state, Persona, UserState};
 use super::wallet_view::WalletView;
 #[allow(unused_imports)] // SessionStatus used in WASM cfg block
 use super::chat_client::{ChatState, ChatWsHandle, SessionStatus};
@@ -49,7 +55,7 @@ fn handle_dc_control(chat: &mut Signal<ChatState>, session_id: &str, ctrl: &str)
             if let Some(s) = chat.write().sessions.get_mut(session_id) {
                 s.payment_request = Some(PaymentRequest::ReceivedPending { curd_per_interval: amount });
             }
-            web_sys::console::log_1(&format!("[WEBRTC] Peer requests {} CURD/interval for {}", amount, session_id).into());
+            web_sys::console::log_1(&format!("[WEBRTC] Peer requests {} curds/interval for {}", amount, session_id).into());
         }
     } else if ctrl == "pay_accept" {
         let mut state = chat.write();
@@ -128,6 +134,12 @@ pub enum Route {
     Guardian {},
     #[route("/profile")]
     Profile {},
+    #[route("/settings/nodes")]
+    NodeSettings {},
+    #[route("/debug")]
+    DebugInspector {},
+    #[route("/debug/log")]
+    DebugLog {},
     #[redirect("/", || Route::Directory {})]
     #[route("/:..segments")]
     NotFound { segments: Vec<String> },
@@ -150,6 +162,7 @@ pub fn App() -> Element {
     #[cfg(target_family = "wasm")]
     use_context_provider(|| Signal::new(WebRtcSessions::default()));
     use_node_coroutine();
+    super::background_sync::use_background_sync();
 
     // Derive toll rates reactively from root user contract (no polling needed)
     {
@@ -182,7 +195,10 @@ pub fn App() -> Element {
     let key_manager: Signal<Option<KeyManager>> = use_context();
     let user_state = use_user_state();
 
-    let setup_needed = key_manager.read().is_none() || user_state.read().moniker.is_none();
+    // Guests browse the directory and storefronts read-only; identity
+    // creation waits until they try to order or message.
+    let setup_needed = !user_state.read().is_guest
+        && (key_manager.read().is_none() || user_state.read().moniker.is_none());
 
     let content = if setup_needed {
         rsx! { SetupScreen {} }
@@ -206,9 +222,17 @@ fn nav_class(current: &Route, target: &Route) -> &'static str {
     if matches { "nav-active" } else { "" }
 }
 
-/// Render the navigation buttons for the app header.
-fn nav_buttons(nav: Navigator, order_count: usize, displayed_balance: u64, is_supplier: bool, connected_supplier: Option<String>, inbox_count: usize, admin_status: super::toll_rates::AdminStatus) -> Element {
+/// Render the navigation buttons for the app header: the connected
+/// storefront's in customer mode, else the active persona's.
+#[allow(clippy::too_many_arguments)]
+fn nav_buttons(nav: Navigator, order_count: usize, displayed_balance: u64, persona: Persona, is_supplier: bool, connected_supplier: Option<String>, unread: UnreadCounts, admin_status: super::toll_rates::AdminStatus) -> Element {
     let current_route = use_route::<Route>();
+    let i18n = use_i18n();
+    let wallet_label = format!("{} ({})", i18n.t(Msg::NavWallet), i18n.amount(displayed_balance));
+    let orders_label = format!("{} ({order_count})", i18n.t(Msg::NavMyOrders));
+    let messages_unread = unread.total_messages();
+    let my_orders_unread = unread.my_orders;
+    let storefront_unread = unread.storefront_orders;
     if let Some(supplier) = connected_supplier {
         // Customer mode: single-storefront nav
         rsx! {
@@ -220,80 +244,120 @@ fn nav_buttons(nav: Navigator, order_count: usize, displayed_balance: u64, is_su
                         button {
                             class: cls,
                             onclick: move |_| { nav.push(Route::Supplier { name: supplier_clone.clone() }); },
-                            "Storefront"
+                            {i18n.t(Msg::NavStorefront)}
                         }
                     }
                 }
                 button {
                     class: nav_class(&current_route, &Route::Orders {}),
                     onclick: move |_| { nav.push(Route::Orders {}); },
-                    "My Orders ({order_count})"
+                    "{orders_label}"
+                    if my_orders_unread > 0 { span { class: "nav-badge", "{my_orders_unread}" } }
                 }
                 button {
                     class: nav_class(&current_route, &Route::Messages {}),
                     onclick: move |_| { nav.push(Route::Messages {}); },
-                    if inbox_count > 0 { "Inbox ({inbox_count})" } else { "Inbox" }
+                    {i18n.t(Msg::NavInbox)}
+                    if messages_unread > 0 { span { class: "nav-badge", "{messages_unread}" } }
                 }
                 button {
                     class: nav_class(&current_route, &Route::Wallet {}),
                     onclick: move |_| { nav.push(Route::Wallet {}); },
-                    "Wallet ({displayed_balance} CURD)"
+                    "{wallet_label}"
                 }
                 if admin_status.admin {
                     button {
                         class: nav_class(&current_route, &Route::Guardian {}),
                         onclick: move |_| { nav.push(Route::Guardian {}); },
-                        if admin_status.root { "Root" } else { "Admin" }
+                        if admin_status.root { {i18n.t(Msg::RoleRoot)} } else { {i18n.t(Msg::NavAdmin)} }
+                    }
+                }
+            }
+        }
+    } else if persona == Persona::Supplier {
+        // Supplier persona: the storefront and the markets it sells at
+        rsx! {
+            nav {
+                button {
+                    class: nav_class(&current_route, &Route::Dashboard {}),
+                    onclick: move |_| { nav.push(Route::Dashboard {}); },
+                    {i18n.t(Msg::NavMyStorefront)}
+                    if storefront_unread > 0 { span { class: "nav-badge", "{storefront_unread}" } }
+                }
+                button {
+                    class: nav_class(&current_route, &Route::MyMarket {}),
+                    onclick: move |_| { nav.push(Route::MyMarket {}); },
+                    {i18n.t(Msg::NavMyMarkets)}
+                }
+                button {
+                    class: nav_class(&current_route, &Route::Markets {}),
+                    onclick: move |_| { nav.push(Route::Markets {}); },
+                    {i18n.t(Msg::NavMarkets)}
+                }
+                button {
+                    class: nav_class(&current_route, &Route::Messages {}),
+                    onclick: move |_| { nav.push(Route::Messages {}); },
+                    {i18n.t(Msg::NavInbox)}
+                    if messages_unread > 0 { span { class: "nav-badge", "{messages_unread}" } }
+                }
+                button {
+                    class: nav_class(&current_route, &Route::Wallet {}),
+                    onclick: move |_| { nav.push(Route::Wallet {}); },
+                    "{wallet_label}"
+                }
+                if admin_status.admin {
+                    button {
+                        class: nav_class(&current_route, &Route::Guardian {}),
+                        onclick: move |_| { nav.push(Route::Guardian {}); },
+                        if admin_status.root { {i18n.t(Msg::RoleRoot)} } else { {i18n.t(Msg::NavAdmin)} }
                     }
                 }
             }
         }
     } else {
-        // Supplier / browser mode: full directory nav
+        // Customer persona: full directory nav
         rsx! {
             nav {
                 button {
                     class: nav_class(&current_route, &Route::Directory {}),
                     onclick: move |_| { nav.push(Route::Directory {}); },
-                    "Suppliers"
+                    {i18n.t(Msg::NavSuppliers)}
                 }
                 button {
                     class: nav_class(&current_route, &Route::Markets {}),
                     onclick: move |_| { nav.push(Route::Markets {}); },
-                    "Markets"
+                    {i18n.t(Msg::NavMarkets)}
                 }
                 button {
                     class: nav_class(&current_route, &Route::Orders {}),
                     onclick: move |_| { nav.push(Route::Orders {}); },
-                    "My Orders ({order_count})"
+                    "{orders_label}"
+                    if my_orders_unread > 0 { span { class: "nav-badge", "{my_orders_unread}" } }
                 }
                 button {
                     class: nav_class(&current_route, &Route::Messages {}),
                     onclick: move |_| { nav.push(Route::Messages {}); },
-                    if inbox_count > 0 { "Inbox ({inbox_count})" } else { "Inbox" }
+                    {i18n.t(Msg::NavInbox)}
+                    if messages_unread > 0 { span { class: "nav-badge", "{messages_unread}" } }
                 }
-                if is_supplier {
+                // Suppliers organise markets from their supplier persona
+                if !is_supplier {
                     button {
-                        class: nav_class(&current_route, &Route::Dashboard {}),
-                        onclick: move |_| { nav.push(Route::Dashboard {}); },
-                        "My Storefront"
+                        class: nav_class(&current_route, &Route::MyMarket {}),
+                        onclick: move |_| { nav.push(Route::MyMarket {}); },
+                        {i18n.t(Msg::NavMyMarkets)}
                     }
                 }
-                button {
-                    class: nav_class(&current_route, &Route::MyMarket {}),
-                    onclick: move |_| { nav.push(Route::MyMarket {}); },
-                    "My Markets"
-                }
                 button {
                     class: nav_class(&current_route, &Route::Wallet {}),
                     onclick: move |_| { nav.push(Route::Wallet {}); },
-                    "Wallet ({displayed_balance} CURD)"
+                    "{wallet_label}"
                 }
                 if admin_status.admin {
                     button {
                         class: nav_class(&current_route, &Route::Guardian {}),
                         onclick: move |_| { nav.push(Route::Guardian {}); },
-                        if admin_status.root { "Root" } else { "Admin" }
+                        if admin_status.root { {i18n.t(Msg::RoleRoot)} } else { {i18n.t(Msg::NavAdmin)} }
                     }
                 }
             }
@@ -301,6 +365,58 @@ fn nav_buttons(nav: Navigator, order_count: usize, displayed_balance: u64, is_su
     }
 }
 
+/// Navigation for guests: only the pages that need no identity.
+fn guest_nav_buttons(nav: Navigator) -> Element {
+    let current_route = use_route::<Route>();
+    let i18n = use_i18n();
+    rsx! {
+        nav {
+            button {
+                class: nav_class(&current_route, &Route::Directory {}),
+                onclick: move |_| { nav.push(Route::Directory {}); },
+                {i18n.t(Msg::NavSuppliers)}
+            }
+            button {
+                class: nav_class(&current_route, &Route::Markets {}),
+                onclick: move |_| { nav.push(Route::Markets {}); },
+                {i18n.t(Msg::NavMarkets)}
+            }
+        }
+    }
+}
+
+/// Whether a page acts as the user (orders, inbox, wallet, ...) and so
+/// needs an identity; guests are asked to sign up instead.
+fn needs_identity(route: &Route) -> bool {
+    !matches!(
+        route,
+        Route::Directory {}
+            | Route::Markets {}
+            | Route::Supplier { .. }
+            | Route::Market { .. }
+            | Route::Faq {}
+            | Route::Iaq {}
+            | Route::NodeSettings {}
+            | Route::NotFound { .. }
+    )
+}
+
+/// Shown to guests in place of a page that needs an identity.
+#[component]
+fn SignUpPrompt() -> Element {
+    let mut user_state = use_user_state();
+    let i18n = use_i18n();
+    rsx! {
+        div { class: "sign-up-prompt",
+            p { {i18n.t(Msg::SignUpToContinue)} }
+            button {
+                onclick: move |_| user_state.write().sign_up(),
+                {i18n.t(Msg::SignUp)}
+            }
+        }
+    }
+}
+
 /// Try to derive KeyManager from credentials stored in sessionStorage.
 /// Returns Some(km) if moniker + password are available, None otherwise.
 fn auto_derive_key_manager() -> Option<KeyManager> {
@@ -617,9 +733,25 @@ fn AppLayout() -> Element {
     let is_supplier = state.is_supplier;
     let is_root = state.is_root;
     let connected_supplier = state.connected_supplier.clone();
+    let read_markers = state.read_markers.clone();
+    let own_storefront = state.own_storefront().map(str::to_string);
+    let is_guest = state.is_guest;
+    let persona = state.active_persona();
+    let can_switch_persona = state.can_switch_persona();
     drop(state);
+    let current_route = use_route::<Route>();
 
-    // Determine user role: Supplier (has products), User, or Guest
+    // Following a link (or going back) to the other persona's page switches to it
+    let page_persona = route_persona(&current_route);
+    use_effect(use_reactive!(|page_persona| {
+        if let Some(page_persona) = page_persona {
+            if user_state.peek().active_persona() != page_persona {
+                user_state.write().set_persona(page_persona);
+            }
+        }
+    }));
+
+    // Role badge: Root, the supplier persona, Guest, or User
     let shared_read = shared.read();
     // Balance comes from the on-network user contract (root uses root_user_contract)
     let balance = if is_root {
@@ -627,23 +759,21 @@ fn AppLayout() -> Element {
     } else {
         shared_read.user_contract.as_ref().map(|uc| uc.balance_curds).unwrap_or(0)
     };
-    let has_products = shared_read
-        .storefronts
-        .get(&moniker)
-        .map(|sf| !sf.products.is_empty())
-        .unwrap_or(false);
-    let role_label = if is_root {
-        "Root"
-    } else if has_products {
-        "Supplier"
+    let i18n = use_i18n();
+    let role_label = i18n.t(if is_guest {
+        Msg::RoleGuest
+    } else if is_root {
+        Msg::RoleRoot
+    } else if persona == Persona::Supplier {
+        Msg::RoleSupplier
     } else if is_customer {
-        "Guest"
+        Msg::RoleGuest
     } else {
-        "User"
-    };
+        Msg::RoleUser
+    });
 
-    // Compute displayed balance: base + incoming deposit credits for suppliers
-    let incoming_deposits: u64 = if !is_customer && is_supplier {
+    // Compute displayed balance: base + incoming deposit credits, when selling
+    let incoming_deposits: u64 = if persona == Persona::Supplier {
         shared_read
             .storefronts
             .get(&moniker)
@@ -655,9 +785,10 @@ fn AppLayout() -> Element {
                             o.status,
                             cream_common::order::OrderStatus::Reserved { .. }
                                 | cream_common::order::OrderStatus::Paid
+                                | cream_common::order::OrderStatus::FulfillmentProposed { .. }
                         )
                     })
-                    .map(|o| o.deposit_amount)
+                    .map(|o| o.escrowed_deposit())
                     .sum()
             })
             .unwrap_or(0)
@@ -665,28 +796,43 @@ fn AppLayout() -> Element {
         0
     };
     let is_connected = shared_read.connected;
+    let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+    let unread = shared_read.unread(&read_markers, my_id.as_ref(), own_storefront.as_deref());
     drop(shared_read);
     let displayed_balance = balance + incoming_deposits;
 
     // Connect to chat relay when KeyManager is available
     use_chat_connection();
+    // Pause own listings that orders bring low on stock
+    use_auto_pause();
+    let admin_status = *use_context::<Signal<super::toll_rates::AdminStatus>>().read();
 
     rsx! {
         div { class: "cream-app",
             header { class: "app-header",
                 div { class: "header-top",
-                    h1 { "CREAM " span { class: "tagline", "rises to the top" } }
+                    h1 { "CREAM " span { class: "tagline", {i18n.t(Msg::Tagline)} } }
                     div { class: "user-info",
-                        Link {
-                            class: "user-moniker clickable",
-                            to: Route::Profile {},
-                            "{moniker}"
+                        if !is_guest {
+                            Link {
+                                class: "user-moniker clickable",
+                                to: Route::Profile {},
+                                "{moniker}"
+                            }
                         }
                         span { class: "role-badge", " [{role_label}]" }
                         if is_connected {
-                            span { class: "connection-badge connected", "Connected" }
+                            Link {
+                                class: "connection-badge connected",
+                                to: Route::NodeSettings {},
+                                {i18n.t(Msg::Connected)}
+                            }
                         } else {
-                            span { class: "connection-badge disconnected", "Disconnected" }
+                            Link {
+                                class: "connection-badge disconnected",
+                                to: Route::NodeSettings {},
+                                {i18n.t(Msg::Disconnected)}
+                            }
                         }
                         button {
                             class: "iaq-btn",
@@ -698,33 +844,243 @@ fn AppLayout() -> Element {
                             onclick: move |_| { nav.push(Route::Iaq {}); },
                             "IAQ"
                         }
-                        button {
-                            class: "logout-btn",
-                            onclick: move |_| {
-                                UserState::clear_session();
-                                key_manager.set(None);
-                                user_state.set(UserState::new());
-                            },
-                            "Log out"
+                        if is_guest {
+                            button {
+                                class: "logout-btn",
+                                onclick: move |_| user_state.write().sign_up(),
+                                {i18n.t(Msg::SignUp)}
+                            }
+                        } else {
+                            button {
+                                class: "logout-btn",
+                                onclick: move |_| {
+                                    UserState::clear_session();
+                                    key_manager.set(None);
+                                    user_state.set(UserState::new());
+                                },
+                                {i18n.t(Msg::LogOut)}
+                            }
                         }
+                        if can_switch_persona {
+                            PersonaToggle {}
+                        }
+                        LocalePicker {}
+                        PrecisionPicker {}
                     }
                 }
-                p { "The decentralized, private 24/7 farmer's market" }
-                {
-                    let inbox_count = shared.read().inbox.as_ref().map(|i| i.messages.len()).unwrap_or(0);
-                    let admin_status = *use_context::<Signal<super::toll_rates::AdminStatus>>().read();
-                    nav_buttons(nav.clone(), order_count, displayed_balance, is_supplier, connected_supplier.clone(), inbox_count, admin_status)
+                p { {i18n.t(Msg::Subtitle)} }
+                if is_guest {
+                    {guest_nav_buttons(nav.clone())}
+                } else {
+                    {nav_buttons(nav.clone(), order_count, displayed_balance, persona, is_supplier, connected_supplier.clone(), unread, admin_status)}
                 }
             }
             ChatInviteBanner {}
+            PendingOpsBanner {}
+            OtherDeviceBanner {}
+            Toasts {}
             main {
-                Outlet::<Route> {}
+                if is_guest && needs_identity(&current_route) {
+                    SignUpPrompt {}
+                } else {
+                    Outlet::<Route> {}
+                }
             }
             ChatPanel {}
         }
     }
 }
 
+/// Switch between shopping and selling, for suppliers. Each persona lands on
+/// its own home page.
+#[component]
+fn PersonaToggle() -> Element {
+    let mut user_state = use_user_state();
+    let nav = use_navigator();
+    let i18n = use_i18n();
+    let active = user_state.read().active_persona();
+
+    rsx! {
+        div { class: "persona-toggle",
+            for persona in Persona::ALL {
+                button {
+                    class: if persona == active { "persona-btn active" } else { "persona-btn" },
+                    onclick: move |_| {
+                        user_state.write().set_persona(persona);
+                        nav.push(persona_home(persona));
+                    },
+                    {i18n.t(persona)}
+                }
+            }
+        }
+    }
+}
+
+/// Where switching to `persona` lands.
+fn persona_home(persona: Persona) -> Route {
+    match persona {
+        Persona::Customer => Route::Directory {},
+        Persona::Supplier => Route::Dashboard {},
+    }
+}
+
+/// The persona a page belongs to, for pages that only make sense in one.
+fn route_persona(route: &Route) -> Option<Persona> {
+    match route {
+        Route::Dashboard {} => Some(Persona::Supplier),
+        Route::Orders {} => Some(Persona::Customer),
+        _ => None,
+    }
+}
+
+/// Contract operations that failed to send: retrying in the background, or
+/// given up on and waiting for the user to retry or dismiss.
+#[component]
+fn PendingOpsBanner() -> Element {
+    let shared = use_shared_state();
+    let node = use_node_action();
+    let ops: Vec<(String, PendingOp)> = shared
+        .read()
+        .pending_ops
+        .iter()
+        .map(|(key, op)| (key.clone(), op.clone()))
+        .collect();
+
+    if ops.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "pending-ops-banner",
+            for (key, op) in ops {
+                {
+                    let retry_key = key.clone();
+                    let dismiss_key = key.clone();
+                    let status = if op.failed() {
+                        format!("failed after {} attempts", op.attempts)
+                    } else {
+                        format!("retrying (attempt {})", op.attempts + 1)
+                    };
+                    rsx! {
+                        div {
+                            class: if op.failed() { "pending-op failed" } else { "pending-op" },
+                            key: "{key}",
+                            span { class: "pending-op-text",
+                                strong { "{op.label}" }
+                                " {status}: {op.last_error}"
+                            }
+                            button {
+                                class: "pending-op-btn",
+                                onclick: move |_| node.send(NodeAction::RetryOperation { key: retry_key.clone() }),
+                                "Retry"
+                            }
+                            if op.failed() {
+                                button {
+                                    class: "pending-op-btn secondary",
+                                    onclick: move |_| node.send(NodeAction::DismissOperation { key: dismiss_key.clone() }),
+                                    "Dismiss"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How recently another device must have written to the ledger for the
+/// account to count as signed in there too.
+const OTHER_DEVICE_ACTIVE_HOURS: i64 = 24;
+
+/// Warn that the account is in use on another device, going by ledger
+/// entries it wrote recently, and point to the wallet to review them.
+#[component]
+fn OtherDeviceBanner() -> Element {
+    let shared = use_shared_state();
+    let nav = use_navigator();
+    let mut dismissed = use_signal(|| None::<String>);
+
+    let this_device = super::platform::device_id();
+    let cutoff = chrono::Utc::now() - chrono::Duration::hours(OTHER_DEVICE_ACTIVE_HOURS);
+    let active = shared.read().user_contract.as_ref().and_then(|uc| {
+        uc.other_devices(&this_device)
+            .into_iter()
+            .filter(|(_, last)| {
+                chrono::DateTime::parse_from_rfc3339(last).is_ok_and(|at| at.with_timezone(&chrono::Utc) > cutoff)
+            })
+            .max_by_key(|(_, last)| *last)
+            .map(|(device, last)| (device.to_string(), last.to_string()))
+    });
+    // Dismissing hides the banner until that device writes again
+    let Some((device, last)) = active.filter(|(_, last)| dismissed.read().as_ref() != Some(last)) else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: "other-device-banner",
+            span { "This account was also used on another device ({device}) in the last day. Transactions from both are kept." }
+            button {
+                class: "pending-op-btn",
+                onclick: move |_| { nav.push(Route::Wallet {}); },
+                "Review"
+            }
+            button {
+                class: "pending-op-btn secondary",
+                onclick: move |_| dismissed.set(Some(last.clone())),
+                "Dismiss"
+            }
+        }
+    }
+}
+
+/// Notices for the user, such as a change the network rejected and that
+/// has been undone, with what to do about it. Each stays until dismissed.
+#[component]
+fn Toasts() -> Element {
+    let mut shared = use_shared_state();
+    let nav = use_navigator();
+    let toasts = shared.read().toasts.clone();
+
+    if toasts.is_empty() {
+        return rsx! {};
+    }
+
+    rsx! {
+        div { class: "toasts",
+            for toast in toasts {
+                div { class: "toast", key: "{toast.id}",
+                    div { class: "toast-text",
+                        p { "{toast.message}" }
+                        if let Some(hint) = toast.hint {
+                            p { class: "toast-hint", "{hint}" }
+                        }
+                        if let Some(remedy) = toast.remedy {
+                            button {
+                                class: "toast-action",
+                                onclick: move |_| {
+                                    nav.push(match remedy {
+                                        Remedy::NodeSettings => Route::NodeSettings {},
+                                        Remedy::Wallet => Route::Wallet {},
+                                        Remedy::DebugLog => Route::DebugLog {},
+                                    });
+                                    shared.write().dismiss_toast(toast.id);
+                                },
+                                "{remedy.label()}"
+                            }
+                        }
+                    }
+                    button {
+                        class: "toast-dismiss",
+                        onclick: move |_| shared.write().dismiss_toast(toast.id),
+                        "×"
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Route component: renders the directory view.
 #[component]
 fn Directory() -> Element {
@@ -825,6 +1181,24 @@ fn Profile() -> Element {
     rsx! { ProfileView {} }
 }
 
+/// Route component: renders the node URL settings and health checks.
+#[component]
+fn NodeSettings() -> Element {
+    rsx! { NodeSettingsView {} }
+}
+
+/// Route component: renders the contract state inspector (not linked from the nav).
+#[component]
+fn DebugInspector() -> Element {
+    rsx! { ContractInspectorView {} }
+}
+
+/// Route component: renders the structured debug log (not linked from the nav).
+#[component]
+fn DebugLog() -> Element {
+    rsx! { DebugLogView {} }
+}
+
 /// Catch-all for unknown routes — redirects to directory (or storefront in customer mode).
 #[component]
 fn NotFound(segments: Vec<String>) -> Element {
@@ -850,6 +1224,7 @@ fn SetupScreen() -> Element {
     let mut postcode_input = use_signal(String::new);
     let mut is_supplier = use_signal(|| false);
     let mut supplier_desc = use_signal(String::new);
+    let storefront_template = use_signal(|| None::<cream_common::template::StorefrontTemplate>);
     let mut postcode_error = use_signal(|| None::<String>);
 
     // Locality selection
@@ -878,23 +1253,28 @@ fn SetupScreen() -> Element {
         if let Some(name) = url_supplier.read().clone() {
             supplier_lookup_loading.set(true);
             spawn(async move {
-                let mut last_err = String::new();
-                for attempt in 0..3u32 {
-                    if attempt > 0 {
-                        gloo_timers::future::TimeoutFuture::new(1_000).await;
-                    }
-                    match super::rendezvous::lookup_supplier(&name).await {
-                        Ok(entry) => {
-                            supplier_lookup_result.set(Some(entry));
-                            supplier_lookup_loading.set(false);
-                            return;
-                        }
-                        Err(e) => {
-                            last_err = e;
-                        }
-                    }
+                let policy = RetryPolicy::fixed(std::time::Duration::from_secs(1))
+                    .with_jitter(0.2)
+                    .with_max_attempts(3);
+                let seed = chrono::Utc::now().timestamp_subsec_nanos() as u64;
+                let result = retry(
+                    policy.backoff(seed),
+                    &CancellationToken::new(),
+                    super::platform::sleep,
+                    |_| {
+                        // Re-read each attempt: the directory may still be loading
+                        let listed = super::rendezvous::directory_listing(
+                            &_shared_state.read().directory,
+                            &name,
+                        );
+                        super::rendezvous::lookup_supplier(&name, listed)
+                    },
+                )
+                .await;
+                match result {
+                    Ok(entry) => supplier_lookup_result.set(Some(entry)),
+                    Err(e) => supplier_lookup_error.set(Some(e.into_last_error().unwrap_or_default())),
                 }
-                supplier_lookup_error.set(Some(last_err));
                 supplier_lookup_loading.set(false);
             });
         }
@@ -1083,8 +1463,12 @@ fn SetupScreen() -> Element {
                                                 supplier_lookup_loading.set(true);
                                                 supplier_lookup_error.set(None);
                                                 supplier_lookup_result.set(None);
+                                                let listed = super::rendezvous::directory_listing(
+                                                    &_shared_state.read().directory,
+                                                    &name,
+                                                );
                                                 spawn(async move {
-                                                    match super::rendezvous::lookup_supplier(&name).await {
+                                                    match super::rendezvous::lookup_supplier(&name, listed).await {
                                                         Ok(entry) => {
                                                             supplier_lookup_result.set(Some(entry));
                                                         }
@@ -1134,6 +1518,7 @@ fn SetupScreen() -> Element {
                                         oninput: move |evt| supplier_desc.set(evt.value()),
                                     }
                                 }
+                                super::template_picker::TemplatePicker { template: storefront_template }
                             }
 
                             if !*is_supplier.read() {
@@ -1168,8 +1553,12 @@ fn SetupScreen() -> Element {
                                                         supplier_lookup_loading.set(true);
                                                         supplier_lookup_error.set(None);
                                                         supplier_lookup_result.set(None);
+                                                        let listed = super::rendezvous::directory_listing(
+                                                            &_shared_state.read().directory,
+                                                            &name,
+                                                        );
                                                         spawn(async move {
-                                                            match super::rendezvous::lookup_supplier(&name).await {
+                                                            match super::rendezvous::lookup_supplier(&name, listed).await {
                                                                 Ok(entry) => {
                                                                     supplier_lookup_result.set(Some(entry));
                                                                 }
@@ -1277,6 +1666,7 @@ fn SetupScreen() -> Element {
                                         postcode,
                                         locality: locality_val,
                                         description: desc,
+                                        template: storefront_template.read().clone(),
                                     });
                                     // Deploy user contract for the supplier (every supplier is also a user)
                                     node.send(NodeAction::RegisterUser {
@@ -1308,6 +1698,17 @@ fn SetupScreen() -> Element {
                             },
                             "Get Started"
                         }
+                        button {
+                            class: "browse-as-guest",
+                            onclick: move |_| {
+                                let mut state = user_state.write();
+                                state.is_guest = true;
+                                state.save();
+                            },
+                            "Just browse — sign up later"
+                        }
+
+                        super::backup::BackupImportPanel {}
                     }
                 }
             }
@@ -0,0 +1,231 @@
+//! Background sync: a service worker that watches the user's storefront and
+//! inbox while the tab is closed and raises browser notifications.
+//!
+//! The worker (`public/cream-sw.js`) doesn't speak the node protocol. The
+//! page hands it the node URL and, for each watched contract, a GET request
+//! already encoded for the node's WebSocket. When the browser wakes it — a
+//! periodic background sync, as often as the browser allows — it sends the
+//! GETs, reads the JSON state off the end of each response and compares it
+//! with the last check: new orders on the supplier's own storefront, status
+//! changes to the customer's own orders, new inbox messages. Nothing is
+//! raised while a CREAM tab is visible.
+//!
+//! Opt-in through [`NotificationSettings`]; with everything off the worker is
+//! told to stop watching.
+
+use dioxus::prelude::*;
+use freenet_stdlib::prelude::ContractInstanceId;
+use serde::Serialize;
+
+use cream_client::request;
+
+use super::key_manager::KeyManager;
+use super::shared_state::{use_shared_state, SharedState};
+use super::user_state::{use_user_state, UserState};
+
+/// The worker script, relative to the app so it works under a web
+/// container's path.
+#[allow(dead_code)] // used in WASM builds
+const SERVICE_WORKER_URL: &str = "./cream-sw.js";
+
+/// Periodic background sync tag the worker listens for.
+#[allow(dead_code)] // used in WASM builds
+const PERIODIC_SYNC_TAG: &str = "cream-sync";
+
+/// Shortest interval to ask the browser for; it may wake the worker less often.
+#[allow(dead_code)] // used in WASM builds
+const PERIODIC_SYNC_INTERVAL_MS: u32 = 15 * 60 * 1000;
+
+/// What the worker compares between checks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchKind {
+    /// Order ids on a supplier's own storefront.
+    NewOrders,
+    /// The status of one customer's orders on a supplier's storefront.
+    OrderStatus,
+    /// Message ids in the user's inbox.
+    NewMessages,
+}
+
+/// One contract the worker checks.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Watch {
+    pub kind: WatchKind,
+    /// Notification title.
+    pub title: String,
+    /// Customer (hex public key) whose orders to follow, for `OrderStatus`.
+    pub customer: Option<String>,
+    /// Raw contract instance id, to match responses to watches.
+    pub instance_id: Vec<u8>,
+    /// The GET for the contract's state, encoded as the node expects it.
+    pub get_request: Vec<u8>,
+}
+
+impl Watch {
+    fn new(kind: WatchKind, title: String, instance_id: &str, customer: Option<String>) -> Option<Self> {
+        let id = ContractInstanceId::from_bytes(instance_id).ok()?;
+        let get = request::get(id, false);
+        Some(Watch {
+            kind,
+            title,
+            customer,
+            instance_id: id.as_bytes().to_vec(),
+            get_request: bincode::serialize(&get).ok()?,
+        })
+    }
+}
+
+/// Everything the worker needs, sent whenever it changes.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SyncConfig {
+    pub node_url: Option<String>,
+    pub watches: Vec<Watch>,
+}
+
+impl SyncConfig {
+    /// The watches the user's notification settings call for, given what's
+    /// known so far of their contracts. `own_id` is the user's hex public key.
+    pub fn new(user: &UserState, shared: &SharedState, own_id: Option<String>) -> Self {
+        let settings = &user.notifications;
+        let mut watches = Vec::new();
+        let storefront_key = |name: &str| {
+            shared
+                .storefront_keys
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, key)| key.clone())
+        };
+        if settings.orders {
+            if user.is_supplier {
+                let own = user.moniker.as_deref().and_then(storefront_key);
+                watches.extend(own.and_then(|key| {
+                    Watch::new(WatchKind::NewOrders, "New orders".into(), &key, None)
+                }));
+            } else if let Some(supplier) = user.connected_supplier.as_deref() {
+                let key = storefront_key(supplier).or_else(|| user.supplier_storefront_key.clone());
+                watches.extend(key.and_then(|key| {
+                    Watch::new(
+                        WatchKind::OrderStatus,
+                        format!("Your orders with {supplier}"),
+                        &key,
+                        own_id.clone(),
+                    )
+                }));
+            }
+        }
+        if settings.messages {
+            watches.extend(shared.inbox_contract_key.as_deref().and_then(|key| {
+                Watch::new(WatchKind::NewMessages, "New messages".into(), key, None)
+            }));
+        }
+        SyncConfig {
+            node_url: shared.node_url.clone(),
+            watches,
+        }
+    }
+}
+
+/// Keep the background sync worker configured from the user's settings and
+/// contract keys. Call once, from the app root.
+pub fn use_background_sync() {
+    let user_state = use_user_state();
+    let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let config = use_memo(move || {
+        let own_id = key_manager.read().as_ref().map(|km| km.pubkey_hex());
+        SyncConfig::new(&user_state.read(), &shared_state.read(), own_id)
+    });
+    use_effect(move || {
+        let config = config.read().clone();
+        spawn(async move {
+            if let Err(e) = configure(&config).await {
+                tracing::warn!("Background sync not available: {e}");
+            }
+        });
+    });
+}
+
+/// Ask for permission to show notifications. True if granted.
+pub async fn request_permission() -> bool {
+    #[cfg(target_family = "wasm")]
+    {
+        let Ok(promise) = web_sys::Notification::request_permission() else {
+            return false;
+        };
+        matches!(
+            wasm_bindgen_futures::JsFuture::from(promise).await.ok().and_then(|v| v.as_string()),
+            Some(p) if p == "granted"
+        )
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        false
+    }
+}
+
+/// Send `config` to the worker, registering it and its periodic sync when
+/// there's something to watch. With nothing to watch, an already registered
+/// worker is told so and its periodic sync dropped.
+#[cfg(target_family = "wasm")]
+async fn configure(config: &SyncConfig) -> Result<(), String> {
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+
+    let js_err = |e: JsValue| format!("{:?}", e);
+    let window = web_sys::window().ok_or("No window")?;
+    let container = window.navigator().service_worker();
+
+    let registration = if config.watches.is_empty() {
+        let existing = JsFuture::from(container.get_registration_with_document_url(SERVICE_WORKER_URL))
+            .await
+            .map_err(js_err)?;
+        if existing.is_undefined() {
+            return Ok(());
+        }
+        existing
+    } else {
+        JsFuture::from(container.register(SERVICE_WORKER_URL)).await.map_err(js_err)?;
+        JsFuture::from(container.ready().map_err(js_err)?).await.map_err(js_err)?
+    };
+    let registration: web_sys::ServiceWorkerRegistration =
+        registration.dyn_into().map_err(|_| "Not a service worker registration")?;
+
+    let message = serde_json::json!({ "type": "configure", "config": config }).to_string();
+    let message = js_sys::JSON::parse(&message).map_err(js_err)?;
+    if let Some(worker) = registration.active() {
+        worker.post_message(&message).map_err(js_err)?;
+    }
+
+    // Periodic background sync isn't in every browser (or web-sys), so go
+    // through reflection and treat it as best effort.
+    let periodic = js_sys::Reflect::get(&registration, &"periodicSync".into()).map_err(js_err)?;
+    if periodic.is_undefined() {
+        return Ok(());
+    }
+    let method = if config.watches.is_empty() { "unregister" } else { "register" };
+    let call: js_sys::Function = js_sys::Reflect::get(&periodic, &method.into())
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(|_| "periodicSync method missing")?;
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"minInterval".into(), &PERIODIC_SYNC_INTERVAL_MS.into())
+        .map_err(js_err)?;
+    let promise: js_sys::Promise = call
+        .call2(&periodic, &PERIODIC_SYNC_TAG.into(), &options)
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(|_| "periodicSync did not return a promise")?;
+    if let Err(e) = JsFuture::from(promise).await {
+        tracing::info!("Periodic background sync unavailable: {:?}", e);
+    }
+    Ok(())
+}
+
+#[cfg(not(target_family = "wasm"))]
+async fn configure(config: &SyncConfig) -> Result<(), String> {
+    if config.watches.is_empty() {
+        return Ok(());
+    }
+    Err("service workers are only available in the browser".into())
+}
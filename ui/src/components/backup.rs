@@ -0,0 +1,363 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::Argon2;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::key_manager::KeyManager;
+#[cfg(target_family = "wasm")]
+use super::platform::download_file;
+use super::shared_state::{use_shared_state, SharedState};
+use super::user_state::{use_user_state, UserState};
+
+/// Identifies a CREAM backup file.
+const BACKUP_FORMAT: &str = "cream-backup";
+/// Current backup file version.
+const BACKUP_VERSION: u32 = 1;
+/// Shortest passphrase accepted for a backup.
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Contract keys learned from the network, so a restored client can show
+/// cached data before it has re-resolved everything.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ContractKeyCache {
+    #[serde(default)]
+    pub storefront_keys: HashMap<String, String>,
+    #[serde(default)]
+    pub directory_contract_keys: Vec<String>,
+    #[serde(default)]
+    pub user_contract_key: Option<String>,
+    #[serde(default)]
+    pub root_contract_key: Option<String>,
+    #[serde(default)]
+    pub supplier_user_contract_key: Option<String>,
+    #[serde(default)]
+    pub inbox_contract_key: Option<String>,
+    #[serde(default)]
+    pub market_directory_key: Option<String>,
+}
+
+impl ContractKeyCache {
+    fn from_shared(shared: &SharedState) -> Self {
+        Self {
+            storefront_keys: shared.storefront_keys.clone(),
+            directory_contract_keys: shared.directory_contract_keys.clone(),
+            user_contract_key: shared.user_contract_key.clone(),
+            root_contract_key: shared.root_contract_key.clone(),
+            supplier_user_contract_key: shared.supplier_user_contract_key.clone(),
+            inbox_contract_key: shared.inbox_contract_key.clone(),
+            market_directory_key: shared.market_directory_key.clone(),
+        }
+    }
+
+    /// Fill in keys the client hasn't learned yet; never overrides live ones.
+    fn apply(self, shared: &mut SharedState) {
+        for (name, key) in self.storefront_keys {
+            shared.storefront_keys.entry(name).or_insert(key);
+        }
+        if shared.directory_contract_keys.is_empty() {
+            shared.directory_contract_keys = self.directory_contract_keys;
+        }
+        shared.user_contract_key = shared.user_contract_key.take().or(self.user_contract_key);
+        shared.root_contract_key = shared.root_contract_key.take().or(self.root_contract_key);
+        shared.supplier_user_contract_key =
+            shared.supplier_user_contract_key.take().or(self.supplier_user_contract_key);
+        shared.inbox_contract_key = shared.inbox_contract_key.take().or(self.inbox_contract_key);
+        shared.market_directory_key = shared.market_directory_key.take().or(self.market_directory_key);
+    }
+}
+
+/// Everything needed to pick up where this browser left off.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackupPayload {
+    pub user_state: UserState,
+    /// The identity's ed25519 signing key, hex-encoded.
+    pub signing_key: String,
+    /// Login password for the session, so a restored tab survives a refresh.
+    #[serde(default)]
+    pub session_password: Option<String>,
+    #[serde(default)]
+    pub contract_keys: ContractKeyCache,
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// On-disk form: the payload encrypted with AES-256-GCM under a key derived
+/// from the passphrase with Argon2id.
+#[derive(Serialize, Deserialize)]
+struct BackupFile {
+    format: String,
+    version: u32,
+    kdf: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug)]
+pub enum BackupError {
+    PassphraseTooShort,
+    NotABackup,
+    UnsupportedVersion(u32),
+    /// Wrong passphrase or a corrupted file (GCM can't tell these apart).
+    Decrypt,
+    Crypto(String),
+    Serde(String),
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PassphraseTooShort => {
+                write!(f, "Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN)
+            }
+            Self::NotABackup => write!(f, "This file is not a CREAM backup"),
+            Self::UnsupportedVersion(v) => write!(f, "Unsupported backup version {}", v),
+            Self::Decrypt => write!(f, "Wrong passphrase, or the backup file is damaged"),
+            Self::Crypto(e) => write!(f, "Encryption failed: {}", e),
+            Self::Serde(e) => write!(f, "Invalid backup contents: {}", e),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], BackupError> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| BackupError::Crypto(e.to_string()))?;
+    Ok(key)
+}
+
+/// Encrypt `payload` under `passphrase`, returning the backup file contents.
+pub fn encrypt_backup(payload: &BackupPayload, passphrase: &str) -> Result<String, BackupError> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(BackupError::PassphraseTooShort);
+    }
+    let plaintext = serde_json::to_vec(payload).map_err(|e| BackupError::Serde(e.to_string()))?;
+
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| BackupError::Crypto(e.to_string()))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|e| BackupError::Crypto(e.to_string()))?;
+
+    let file = BackupFile {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        kdf: "argon2id".to_string(),
+        salt: to_hex(&salt),
+        nonce: to_hex(&nonce),
+        ciphertext: to_hex(&ciphertext),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| BackupError::Serde(e.to_string()))
+}
+
+/// Decrypt a backup file produced by [`encrypt_backup`].
+pub fn decrypt_backup(contents: &str, passphrase: &str) -> Result<BackupPayload, BackupError> {
+    let file: BackupFile = serde_json::from_str(contents).map_err(|_| BackupError::NotABackup)?;
+    if file.format != BACKUP_FORMAT || file.kdf != "argon2id" {
+        return Err(BackupError::NotABackup);
+    }
+    if file.version != BACKUP_VERSION {
+        return Err(BackupError::UnsupportedVersion(file.version));
+    }
+    let (Some(salt), Some(nonce), Some(ciphertext)) =
+        (from_hex(&file.salt), from_hex(&file.nonce), from_hex(&file.ciphertext))
+    else {
+        return Err(BackupError::NotABackup);
+    };
+    if nonce.len() != 12 {
+        return Err(BackupError::NotABackup);
+    }
+
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| BackupError::Crypto(e.to_string()))?;
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| BackupError::Decrypt)?;
+    serde_json::from_slice(&plaintext).map_err(|e| BackupError::Serde(e.to_string()))
+}
+
+/// Snapshot the current client into a backup payload.
+fn current_payload(user_state: &UserState, key_manager: &KeyManager, shared: &SharedState) -> BackupPayload {
+    BackupPayload {
+        user_state: user_state.clone(),
+        signing_key: to_hex(&key_manager.signing_key_bytes()),
+        session_password: UserState::load_password(),
+        contract_keys: ContractKeyCache::from_shared(shared),
+        exported_at: chrono::Utc::now(),
+    }
+}
+
+/// Profile section: export this client to an encrypted backup file.
+#[component]
+pub fn BackupExportPanel() -> Element {
+    let user_state = use_user_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let shared = use_shared_state();
+    let mut passphrase = use_signal(String::new);
+    let mut confirm = use_signal(String::new);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let mismatch = !confirm.read().is_empty() && *passphrase.read() != *confirm.read();
+    let can_export = passphrase.read().chars().count() >= MIN_PASSPHRASE_LEN && *passphrase.read() == *confirm.read();
+
+    rsx! {
+        div { class: "profile-section backup-panel",
+            h3 { "Backup" }
+            p { class: "hint",
+                "Download your identity, settings and orders as a file encrypted with a passphrase. "
+                "Restore it on the setup screen of another browser."
+            }
+            input {
+                r#type: "password",
+                placeholder: "Passphrase (at least {MIN_PASSPHRASE_LEN} characters)",
+                value: "{passphrase}",
+                oninput: move |e| passphrase.set(e.value()),
+            }
+            input {
+                r#type: "password",
+                placeholder: "Repeat passphrase",
+                value: "{confirm}",
+                oninput: move |e| confirm.set(e.value()),
+            }
+            if mismatch {
+                p { class: "field-error", "Passphrases don't match" }
+            }
+            button {
+                disabled: !can_export,
+                onclick: move |_| {
+                    let Some(km) = key_manager.read().clone() else {
+                        status.set(Some(Err("Not logged in".to_string())));
+                        return;
+                    };
+                    let payload = current_payload(&user_state.read(), &km, &shared.read());
+                    let result = encrypt_backup(&payload, &passphrase.read()).map_err(|e| e.to_string());
+                    match result {
+                        Ok(contents) => {
+                            let moniker = payload.user_state.moniker.clone().unwrap_or_default().to_lowercase();
+                            let file_name = format!(
+                                "cream-backup-{}-{}.json",
+                                moniker,
+                                payload.exported_at.format("%Y%m%d")
+                            );
+                            #[cfg(target_family = "wasm")]
+                            if let Err(e) = download_file(&file_name, &contents) {
+                                status.set(Some(Err(e)));
+                                return;
+                            }
+                            let _ = contents;
+                            passphrase.set(String::new());
+                            confirm.set(String::new());
+                            status.set(Some(Ok(format!("Saved {}", file_name))));
+                        }
+                        Err(e) => status.set(Some(Err(e))),
+                    }
+                },
+                "Download encrypted backup"
+            }
+            match status.read().as_ref() {
+                Some(Ok(msg)) => rsx! { p { class: "backup-ok", "{msg}" } },
+                Some(Err(e)) => rsx! { p { class: "field-error", "{e}" } },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Setup-screen section: restore a client from a backup file.
+#[component]
+pub fn BackupImportPanel() -> Element {
+    let mut user_state = use_user_state();
+    let mut key_manager: Signal<Option<KeyManager>> = use_context();
+    let mut shared = use_shared_state();
+    let mut contents = use_signal(|| None::<String>);
+    let mut passphrase = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let can_restore = contents.read().is_some() && !passphrase.read().is_empty();
+
+    rsx! {
+        details { class: "backup-panel",
+            summary { "Restore from a backup file" }
+            input {
+                r#type: "file",
+                accept: ".json,application/json",
+                onchange: move |e| {
+                    error.set(None);
+                    let files = e.files();
+                    spawn(async move {
+                        let Some(file) = files.into_iter().next() else {
+                            return;
+                        };
+                        match file.read_string().await {
+                            Ok(text) => contents.set(Some(text)),
+                            Err(e) => error.set(Some(format!("Could not read file: {e}"))),
+                        }
+                    });
+                },
+            }
+            input {
+                r#type: "password",
+                placeholder: "Backup passphrase",
+                value: "{passphrase}",
+                oninput: move |e| passphrase.set(e.value()),
+            }
+            if let Some(err) = error.read().as_ref() {
+                p { class: "field-error", "{err}" }
+            }
+            button {
+                disabled: !can_restore,
+                onclick: move |_| {
+                    let Some(text) = contents.read().clone() else {
+                        return;
+                    };
+                    let payload = match decrypt_backup(&text, &passphrase.read()) {
+                        Ok(payload) => payload,
+                        Err(e) => {
+                            error.set(Some(e.to_string()));
+                            return;
+                        }
+                    };
+                    let key_bytes: Option<[u8; 32]> =
+                        from_hex(&payload.signing_key).and_then(|b| b.try_into().ok());
+                    let Some(key_bytes) = key_bytes else {
+                        error.set(Some(BackupError::Serde("bad signing key".into()).to_string()));
+                        return;
+                    };
+                    let km = KeyManager::from_signing_key_bytes(&key_bytes);
+
+                    payload.user_state.save();
+                    if let Some(pw) = payload.session_password.as_deref() {
+                        UserState::save_password(pw);
+                    }
+                    payload.contract_keys.apply(&mut shared.write());
+                    user_state.set(payload.user_state);
+                    key_manager.set(Some(km));
+                },
+                "Restore"
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use dioxus::prelude::*;
+
+use cream_common::product::{Product, ProductCategory};
+
+/// Selected taxonomy filter: a top-level category and optionally one of its
+/// subcategories, by label. Empty matches everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CategoryFilter {
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+}
+
+impl CategoryFilter {
+    pub fn matches(&self, product: &Product) -> bool {
+        let (category, subcategory) = product.taxonomy();
+        let category_ok = self.category.as_deref().is_none_or(|c| c == category.label());
+        let sub_ok = self
+            .subcategory
+            .as_deref()
+            .is_none_or(|s| subcategory.as_ref().is_some_and(|sub| sub.label() == s));
+        category_ok && sub_ok
+    }
+}
+
+/// The part of the taxonomy the given products use: each category label with
+/// the subcategory labels present under it, in taxonomy order.
+pub fn taxonomy_present<'a>(products: impl Iterator<Item = &'a Product>) -> Vec<(String, Vec<String>)> {
+    let mut found: Vec<(ProductCategory, Vec<String>)> = Vec::new();
+    for product in products {
+        let (category, subcategory) = product.taxonomy();
+        let idx = match found.iter().position(|(c, _)| *c == category) {
+            Some(idx) => idx,
+            None => {
+                found.push((category, Vec::new()));
+                found.len() - 1
+            }
+        };
+        if let Some(sub) = subcategory {
+            let label = sub.label().to_string();
+            if !found[idx].1.contains(&label) {
+                found[idx].1.push(label);
+            }
+        }
+    }
+
+    // Taxonomy order first, then free-text categories alphabetically
+    let rank = |c: &ProductCategory| {
+        ProductCategory::TOP_LEVEL
+            .iter()
+            .position(|t| t == c)
+            .unwrap_or(ProductCategory::TOP_LEVEL.len())
+    };
+    found.sort_by(|(a, _), (b, _)| rank(a).cmp(&rank(b)).then_with(|| a.label().cmp(b.label())));
+    found
+        .into_iter()
+        .map(|(category, mut subs)| {
+            let order: Vec<&str> = category.subcategories().iter().map(|s| s.label()).collect();
+            subs.sort_by_key(|s| order.iter().position(|o| o == s));
+            (category.label().to_string(), subs)
+        })
+        .collect()
+}
+
+/// Filter chips for the categories (and, once one is picked, subcategories) in use.
+#[component]
+pub fn CategoryFilterChips(present: Vec<(String, Vec<String>)>, filter: Signal<CategoryFilter>) -> Element {
+    if present.len() < 2 && present.iter().all(|(_, subs)| subs.len() < 2) {
+        // Nothing to narrow down
+        return rsx! {};
+    }
+    let current = filter.read().clone();
+    let subcategories: Vec<String> = current
+        .category
+        .as_ref()
+        .and_then(|c| present.iter().find(|(label, _)| label == c))
+        .map(|(_, subs)| subs.clone())
+        .unwrap_or_default();
+    let all_class = if current.category.is_none() { "filter-chip active" } else { "filter-chip" };
+
+    rsx! {
+        div { class: "category-filter",
+            div { class: "filter-chips",
+                button {
+                    class: "{all_class}",
+                    onclick: move |_| filter.set(CategoryFilter::default()),
+                    "All"
+                }
+                for (label, _) in present {
+                    {
+                        let active = current.category.as_deref() == Some(label.as_str());
+                        let class = if active { "filter-chip active" } else { "filter-chip" };
+                        let picked = label.clone();
+                        rsx! {
+                            button {
+                                key: "{label}",
+                                class: "{class}",
+                                onclick: move |_| filter.set(CategoryFilter {
+                                    category: Some(picked.clone()),
+                                    subcategory: None,
+                                }),
+                                "{label}"
+                            }
+                        }
+                    }
+                }
+            }
+            if !subcategories.is_empty() {
+                div { class: "filter-chips sub",
+                    for label in subcategories {
+                        {
+                            let active = current.subcategory.as_deref() == Some(label.as_str());
+                            let class = if active { "filter-chip active" } else { "filter-chip" };
+                            let picked = label.clone();
+                            rsx! {
+                                button {
+                                    key: "{label}",
+                                    class: "{class}",
+                                    onclick: move |_| {
+                                        let mut f = filter.write();
+                                        f.subcategory = if active { None } else { Some(picked.clone()) };
+                                    },
+                                    "{label}"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
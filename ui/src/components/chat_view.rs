@@ -1,10 +1,13 @@
 use dioxus::prelude::*;
 
+use cream_common::currency::parse_amount;
+
 use super::toll_rates::use_toll_rates;
 
 use super::chat_client::{ChatMessage, ChatSession, ChatState, ChatWsHandle, ClientMsg, PaymentRequest, SessionStatus};
 #[cfg(target_family = "wasm")]
 use super::chat_client::WebRtcSessions;
+use super::i18n::use_i18n;
 use super::node_api::{use_node_action, NodeAction};
 use super::shared_state::use_shared_state;
 use super::user_state::use_user_state;
@@ -181,6 +184,7 @@ fn send_chat_message(
 /// Floating chat panel — slide-in from right side.
 #[component]
 pub fn ChatPanel() -> Element {
+    let i18n = use_i18n();
     let mut chat = use_context::<Signal<ChatState>>();
     let ws_handle = use_context::<Signal<ChatWsHandle>>();
     #[cfg(target_family = "wasm")]
@@ -543,18 +547,19 @@ pub fn ChatPanel() -> Element {
                                             div { class: "chat-pay-request",
                                                 input {
                                                     r#type: "number",
-                                                    min: "1",
+                                                    min: "0.01",
+                                                    step: "0.01",
                                                     placeholder: "CURD/interval",
                                                     value: "{pay_amount_input}",
                                                     oninput: move |e| pay_amount_input.set(e.value()),
                                                 }
                                                 button {
                                                     class: "chat-pay-btn",
-                                                    disabled: pay_amount_input.read().parse::<u64>().unwrap_or(0) == 0,
+                                                    disabled: parse_amount(&pay_amount_input.read()).unwrap_or(0) == 0,
                                                     onclick: {
                                                         let sid = sid.clone();
                                                         move |_| {
-                                                            let amount: u64 = pay_amount_input.read().parse().unwrap_or(0);
+                                                            let amount = parse_amount(&pay_amount_input.read()).unwrap_or(0);
                                                             if amount == 0 { return; }
                                                             #[cfg(target_family = "wasm")]
                                                             {
@@ -577,7 +582,7 @@ pub fn ChatPanel() -> Element {
                                         let sid = sid.clone();
                                         rsx! {
                                             div { class: "chat-pay-status",
-                                                span { "Requested {curd_per_interval} CURD/interval..." }
+                                                span { "Requested {i18n.amount(curd_per_interval)}/interval..." }
                                                 button {
                                                     class: "chat-pay-cancel",
                                                     onclick: {
@@ -603,7 +608,7 @@ pub fn ChatPanel() -> Element {
                                         let sid = sid.clone();
                                         rsx! {
                                             div { class: "chat-pay-status",
-                                                span { "Peer requests {curd_per_interval} CURD/interval" }
+                                                span { "Peer requests {i18n.amount(curd_per_interval)}/interval" }
                                                 button {
                                                     class: "chat-pay-accept",
                                                     onclick: {
@@ -647,7 +652,7 @@ pub fn ChatPanel() -> Element {
                                         let sid = sid.clone();
                                         rsx! {
                                             div { class: "chat-pay-status chat-pay-active",
-                                                span { "Paying {curd_per_interval} CURD/interval" }
+                                                span { "Paying {i18n.amount(curd_per_interval)}/interval" }
                                                 button {
                                                     class: "chat-pay-stop",
                                                     onclick: {
@@ -673,7 +678,7 @@ pub fn ChatPanel() -> Element {
                                         let sid = sid.clone();
                                         rsx! {
                                             div { class: "chat-pay-status chat-pay-active",
-                                                span { "Receiving {curd_per_interval} CURD/interval" }
+                                                span { "Receiving {i18n.amount(curd_per_interval)}/interval" }
                                                 button {
                                                     class: "chat-pay-stop",
                                                     onclick: {
@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+
+use super::app::Route;
+use super::node_api::{use_node_action, NodeAction};
+use super::shared_state::{use_shared_state, ContractDebugInfo, ContractKind, SharedState};
+
+/// One row of the inspector: a known contract and what we've seen for it.
+#[derive(Clone, Debug, PartialEq)]
+struct InspectedContract {
+    instance_id: String,
+    info: ContractDebugInfo,
+    /// Our current parsed view of the state, pretty-printed.
+    parsed: Option<String>,
+}
+
+/// Every contract instance id the client knows about: those the node has
+/// answered for, plus the keys recorded in `SharedState`.
+fn known_contracts(shared: &SharedState) -> Vec<InspectedContract> {
+    let mut contracts = shared.contracts.clone();
+    let mut note = |key: &Option<String>, kind: ContractKind| {
+        if let Some(key) = key {
+            contracts
+                .entry(key.clone())
+                .or_insert_with(|| ContractDebugInfo::new(kind));
+        }
+    };
+    for key in &shared.directory_contract_keys {
+        note(&Some(key.clone()), ContractKind::Directory);
+    }
+    note(&shared.market_directory_key, ContractKind::MarketDirectory);
+    note(&shared.name_registry_key, ContractKind::NameRegistry);
+    note(&shared.root_contract_key, ContractKind::RootContract);
+    note(&shared.user_contract_key, ContractKind::UserContract);
+    note(&shared.inbox_contract_key, ContractKind::Inbox);
+    for (name, key) in &shared.storefront_keys {
+        note(&Some(key.clone()), ContractKind::Storefront(Some(name.clone())));
+    }
+
+    contracts
+        .into_iter()
+        .map(|(instance_id, info)| {
+            let parsed = parsed_state(shared, &info.kind);
+            InspectedContract { instance_id, info, parsed }
+        })
+        .collect()
+}
+
+/// Pretty-printed parsed state for a contract kind, as currently held in `SharedState`.
+fn parsed_state(shared: &SharedState, kind: &ContractKind) -> Option<String> {
+    let value = match kind {
+        ContractKind::Directory => serde_json::to_value(&shared.directory),
+        ContractKind::MarketDirectory => serde_json::to_value(&shared.market_directory),
+        ContractKind::NameRegistry => serde_json::to_value(&shared.name_registry),
+        ContractKind::Storefront(Some(name)) => serde_json::to_value(shared.storefronts.get(name)?),
+        ContractKind::Storefront(None) => return None,
+        ContractKind::UserContract => serde_json::to_value(shared.user_contract.as_ref()?),
+        ContractKind::RootContract => serde_json::to_value(shared.root_user_contract.as_ref()?),
+        ContractKind::Inbox => serde_json::to_value(shared.inbox.as_ref()?),
+    };
+    value.ok().and_then(|v| serde_json::to_string_pretty(&v).ok())
+}
+
+/// Raw payload bytes rendered as text (pretty-printed if it is JSON).
+fn raw_payload(bytes: &[u8]) -> String {
+    match serde_json::from_slice::<serde_json::Value>(bytes) {
+        Ok(v) => serde_json::to_string_pretty(&v).unwrap_or_default(),
+        Err(_) => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+fn format_age(at: Option<DateTime<Utc>>) -> String {
+    match at {
+        None => "never".to_string(),
+        Some(at) => {
+            let secs = (Utc::now() - at).num_seconds().max(0);
+            let ago = match secs {
+                0..=59 => format!("{}s ago", secs),
+                60..=3599 => format!("{}m ago", secs / 60),
+                _ => format!("{}h ago", secs / 3600),
+            };
+            format!("{} ({})", at.format("%H:%M:%S"), ago)
+        }
+    }
+}
+
+/// Hidden contract state inspector, reachable at /debug.
+#[component]
+pub fn ContractInspectorView() -> Element {
+    let shared_state = use_shared_state();
+    let mut expanded = use_signal(|| None::<String>);
+
+    let contracts = known_contracts(&shared_state.read());
+    let connected = shared_state.read().connected;
+    let count = contracts.len();
+
+    rsx! {
+        div { class: "contract-inspector",
+            h2 { "Contract Inspector" }
+            p { class: "inspector-summary",
+                "{count} known contracts — "
+                if connected { "connected" } else { "disconnected" }
+                " — "
+                Link { to: Route::DebugLog {}, "debug log" }
+            }
+            if contracts.is_empty() {
+                p { class: "empty-state", "No contracts known yet." }
+            }
+            for contract in contracts {
+                {
+                    let is_open = expanded.read().as_deref() == Some(contract.instance_id.as_str());
+                    let id = contract.instance_id.clone();
+                    rsx! {
+                        InspectorRow {
+                            key: "{contract.instance_id}",
+                            contract,
+                            is_open,
+                            on_toggle: move |_| {
+                                let next = if is_open { None } else { Some(id.clone()) };
+                                expanded.set(next);
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn InspectorRow(contract: InspectedContract, is_open: bool, on_toggle: EventHandler<()>) -> Element {
+    let send_action = use_node_action();
+    let info = &contract.info;
+    let label = info.kind.to_string();
+    let last_get = format_age(info.last_get_at);
+    let last_notification = format_age(info.last_notification_at);
+    let subscription = match (info.not_found, info.subscribed) {
+        (true, _) => "not found",
+        (false, Some(true)) => "subscribed",
+        (false, Some(false)) => "subscribe failed",
+        (false, None) => "no subscribe response",
+    };
+    let payload_label = if info.last_payload_is_delta { "Last payload (delta)" } else { "Last payload (state)" };
+    let raw = info.last_payload.as_deref().map(raw_payload);
+    let notification_count = info.notification_count;
+    let get_id = contract.instance_id.clone();
+    let sub_id = contract.instance_id.clone();
+
+    rsx! {
+        div { class: "inspector-row",
+            div { class: "inspector-header",
+                onclick: move |_| on_toggle.call(()),
+                span { class: "inspector-label", "{label}" }
+                code { class: "inspector-key", "{contract.instance_id}" }
+            }
+            div { class: "inspector-meta",
+                span { "Last GET: {last_get}" }
+                span { "Last notification: {last_notification} · {notification_count} total" }
+                span { "{subscription}" }
+                button {
+                    onclick: move |_| send_action.send(NodeAction::ReissueContractRequest {
+                        instance_id: get_id.clone(),
+                        subscribe: false,
+                    }),
+                    "Re-GET"
+                }
+                button {
+                    onclick: move |_| send_action.send(NodeAction::ReissueContractRequest {
+                        instance_id: sub_id.clone(),
+                        subscribe: true,
+                    }),
+                    "Re-subscribe"
+                }
+            }
+            if is_open {
+                div { class: "inspector-states",
+                    div {
+                        h4 { "{payload_label}" }
+                        pre { {raw.unwrap_or_else(|| "(nothing received)".to_string())} }
+                    }
+                    div {
+                        h4 { "Parsed (current)" }
+                        pre { {contract.parsed.clone().unwrap_or_else(|| "(not parsed)".to_string())} }
+                    }
+                }
+            }
+        }
+    }
+}
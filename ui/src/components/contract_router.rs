@@ -0,0 +1,708 @@
+//! Routing of contract responses to per-contract handlers.
+//!
+//! Every contract the client follows is registered with the [`ContractRouter`]
+//! under its instance id, together with a [`ContractHandler`] that knows how
+//! to parse its state and fold it into [`SharedState`]. Responses for ids
+//! nobody registered go to the fallback handler — storefronts, which are
+//! discovered from the directory rather than known up front.
+//!
+//! Adding a contract type means writing a handler and registering it once
+//! the instance id is known; the router itself never changes.
+
+use std::collections::{BTreeMap, HashMap};
+
+use dioxus::prelude::*;
+use freenet_stdlib::client_api::{ClientRequest, ContractResponse};
+use freenet_stdlib::prelude::*;
+use serde::Serialize;
+
+use cream_client::request;
+use cream_common::directory::{DirectoryShard, DirectoryState};
+use cream_common::inbox::InboxState;
+use cream_common::market::MarketDirectoryState;
+use cream_common::registry::NameRegistryState;
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+
+use super::directory_loader::{DirectoryChunk, DirectoryLoader};
+use super::ledger_status::LedgerOwner;
+use super::optimistic::Rollback;
+use super::resync;
+use super::shared_state::{ContractKind, SharedState};
+use super::subscriptions::{Interest, SubscriptionManager};
+
+/// What a handler gets to work with while handling one response.
+pub(crate) struct RouteContext<'a> {
+    pub shared: &'a mut Signal<SharedState>,
+    pub subscriptions: &'a mut SubscriptionManager,
+    /// Supplier name → storefront contract key, for later storefront updates.
+    pub sf_contract_keys: &'a mut BTreeMap<String, ContractKey>,
+    /// The supplier this tab is connected to, in customer mode.
+    pub customer_supplier_name: Option<&'a str>,
+    /// Loads large directory states a chunk at a time.
+    pub directory_loader: &'a DirectoryLoader,
+}
+
+/// Handles responses for one kind of contract.
+///
+/// `on_state` and `on_update` receive the raw (non-empty) payload and return
+/// follow-up requests to send to the node.
+pub(crate) trait ContractHandler {
+    /// Classification shown in the contract inspector.
+    fn kind(&self, id: &ContractInstanceId, ctx: &RouteContext) -> ContractKind;
+
+    /// Full state from a GET.
+    fn on_state(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8])
+        -> Vec<ClientRequest<'static>>;
+
+    /// State (or delta) from an update notification.
+    fn on_update(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8])
+        -> Vec<ClientRequest<'static>>;
+
+    /// Full state from the GET after a reconnect. Handlers whose cached
+    /// state may hold changes the network lost reconcile the two (see
+    /// [`super::resync`]); the rest treat it like any GET.
+    fn on_resync(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8])
+        -> Vec<ClientRequest<'static>> {
+        self.on_state(ctx, key, bytes)
+    }
+
+    /// Request that creates the contract when the node reports it missing.
+    /// `None` for contracts we can't (or shouldn't) create ourselves.
+    fn create_missing(&self) -> Option<ClientRequest<'static>> {
+        None
+    }
+}
+
+/// Dispatches contract responses to the handler registered for the contract.
+pub(crate) struct ContractRouter {
+    handlers: HashMap<ContractInstanceId, Box<dyn ContractHandler>>,
+    fallback: Box<dyn ContractHandler>,
+    subscriptions: SubscriptionManager,
+    directory_loader: DirectoryLoader,
+}
+
+impl ContractRouter {
+    /// A router sending unregistered contracts to `fallback`, and the rest
+    /// of large directory states to `directory_loader`'s channel.
+    pub fn new(fallback: impl ContractHandler + 'static, directory_loader: DirectoryLoader) -> Self {
+        Self {
+            handlers: HashMap::new(),
+            fallback: Box::new(fallback),
+            subscriptions: SubscriptionManager::default(),
+            directory_loader,
+        }
+    }
+
+    /// Route responses for `id` to `handler`, replacing any earlier handler.
+    pub fn register(&mut self, id: ContractInstanceId, handler: impl ContractHandler + 'static) {
+        self.handlers.insert(id, Box::new(handler));
+    }
+
+    /// Follow contract `id`, routing its responses to `handler`. Returns the
+    /// GET and Subscribe to send if it wasn't followed already.
+    pub fn follow(
+        &mut self,
+        id: ContractInstanceId,
+        interest: Interest,
+        handler: impl ContractHandler + 'static,
+    ) -> Vec<ClientRequest<'static>> {
+        self.register(id, handler);
+        self.subscriptions.want(id, interest)
+    }
+
+    /// The contracts the client follows.
+    pub fn subscriptions(&mut self) -> &mut SubscriptionManager {
+        &mut self.subscriptions
+    }
+
+    /// Request creating contract `id` after the node reported it missing,
+    /// if its handler knows how.
+    pub fn create_missing(&self, id: &ContractInstanceId) -> Option<ClientRequest<'static>> {
+        self.handlers.get(id)?.create_missing()
+    }
+
+    /// Merge a later chunk of a large directory state (see
+    /// [`super::directory_loader`]). Returns GET+subscribe for storefronts
+    /// it lists that aren't followed yet.
+    pub fn merge_directory_chunk(
+        &mut self,
+        shared: &mut Signal<SharedState>,
+        chunk: DirectoryChunk,
+    ) -> Vec<ClientRequest<'static>> {
+        tracing::info!("Directory chunk ({}): {} entries", chunk.shard.label(), chunk.state.entries.len());
+        let follow_ups = self.subscriptions.follow_storefronts(&chunk.state);
+        shared.write().directory.merge(chunk.state);
+        follow_ups
+    }
+
+    /// Handle a contract response from the node.
+    /// Returns follow-up requests to send back (e.g. PUT after NotFound,
+    /// or GET+subscribe for newly discovered storefronts).
+    pub fn handle(
+        &mut self,
+        shared: &mut Signal<SharedState>,
+        sf_contract_keys: &mut BTreeMap<String, ContractKey>,
+        customer_supplier_name: Option<&str>,
+        response: ContractResponse,
+    ) -> Vec<ClientRequest<'static>> {
+        let response_id = response_id_of(&response);
+        let handler = response_id
+            .and_then(|id| self.handlers.get(&id))
+            .map_or(&*self.fallback, |h| &**h);
+        let mut ctx = RouteContext {
+            shared,
+            subscriptions: &mut self.subscriptions,
+            sf_contract_keys,
+            customer_supplier_name,
+            directory_loader: &self.directory_loader,
+        };
+
+        if let Some(id) = response_id {
+            let kind = handler.kind(&id, &ctx);
+            record_debug(ctx.shared, id, kind, &response);
+        }
+
+        match response {
+            ContractResponse::GetResponse { key, state, .. } => {
+                let resync = ctx.subscriptions.take_resync(key.id());
+                let bytes = state.as_ref();
+                if bytes.is_empty() {
+                    return vec![];
+                }
+                if resync {
+                    handler.on_resync(&mut ctx, key, bytes)
+                } else {
+                    handler.on_state(&mut ctx, key, bytes)
+                }
+            }
+
+            ContractResponse::UpdateNotification { key, update, .. } => {
+                let bytes = match &update {
+                    UpdateData::State(s) => s.as_ref(),
+                    UpdateData::Delta(d) => d.as_ref(),
+                    UpdateData::StateAndDelta { state, .. } => state.as_ref(),
+                    _ => return vec![],
+                };
+                if bytes.is_empty() {
+                    return vec![];
+                }
+                handler.on_update(&mut ctx, key, bytes)
+            }
+
+            ContractResponse::PutResponse { key } => {
+                tracing::info!("Contract put OK: {:?}", key);
+                vec![]
+            }
+
+            ContractResponse::UpdateResponse { key, .. } => {
+                tracing::info!("UpdateResponse OK: {:?}", key);
+                ctx.shared.write().confirm_update(&key.id().to_string());
+                vec![]
+            }
+
+            ContractResponse::SubscribeResponse { key, subscribed } => {
+                tracing::info!(
+                    "Subscription {:?}: {}",
+                    key,
+                    if subscribed { "active" } else { "failed" }
+                );
+                ctx.subscriptions.on_subscribe_response(key.id(), subscribed);
+                vec![]
+            }
+
+            ContractResponse::NotFound { instance_id } => {
+                match handler.create_missing() {
+                    Some(create) => {
+                        tracing::info!("Contract {:?} not found, creating it...", instance_id);
+                        vec![create]
+                    }
+                    None => {
+                        tracing::warn!("Contract not found: {:?}", instance_id);
+                        vec![]
+                    }
+                }
+            }
+
+            _ => {
+                tracing::debug!("Unhandled contract response");
+                vec![]
+            }
+        }
+    }
+}
+
+/// Instance id a contract response is about, if any.
+fn response_id_of(response: &ContractResponse) -> Option<ContractInstanceId> {
+    match response {
+        ContractResponse::GetResponse { key, .. }
+        | ContractResponse::UpdateNotification { key, .. }
+        | ContractResponse::SubscribeResponse { key, .. } => Some(*key.id()),
+        ContractResponse::NotFound { instance_id } => Some(*instance_id),
+        _ => None,
+    }
+}
+
+/// Record what the node sent for the contract inspector.
+fn record_debug(
+    shared: &mut Signal<SharedState>,
+    id: ContractInstanceId,
+    kind: ContractKind,
+    response: &ContractResponse,
+) {
+    let now = chrono::Utc::now();
+    let mut state = shared.write();
+    let info = state.contract_debug(id.to_string(), kind);
+    match response {
+        ContractResponse::GetResponse { state: payload, .. } => {
+            info.last_payload = Some(payload.as_ref().to_vec());
+            info.last_payload_is_delta = false;
+            info.last_get_at = Some(now);
+            info.not_found = false;
+        }
+        ContractResponse::UpdateNotification { update, .. } => {
+            match update {
+                UpdateData::State(s) | UpdateData::StateAndDelta { state: s, .. } => {
+                    info.last_payload = Some(s.as_ref().to_vec());
+                    info.last_payload_is_delta = false;
+                }
+                UpdateData::Delta(d) => {
+                    info.last_payload = Some(d.as_ref().to_vec());
+                    info.last_payload_is_delta = true;
+                }
+                _ => {}
+            }
+            info.last_notification_at = Some(now);
+            info.notification_count += 1;
+            if let Some(lag) = state.propagation.get_mut(&id.to_string()) {
+                lag.notified(now);
+            }
+        }
+        ContractResponse::SubscribeResponse { subscribed, .. } => {
+            info.subscribed = Some(*subscribed);
+        }
+        ContractResponse::NotFound { .. } => info.not_found = true,
+        _ => {}
+    }
+}
+
+/// PUT `contract` with `state` and subscribe to it.
+fn put_and_subscribe(contract: ContractContainer, state: &impl Serialize) -> ClientRequest<'static> {
+    request::put(contract, serde_json::to_vec(state).unwrap(), true)
+}
+
+// ─── Handlers ────────────────────────────────────────────────────────────────
+
+/// One directory shard. Newly listed suppliers get their storefronts
+/// followed; a shard nobody has created yet is PUT empty. Large states are
+/// merged a chunk at a time (see [`super::directory_loader`]).
+pub(crate) struct DirectoryHandler {
+    pub shard: DirectoryShard,
+    /// Builds the shard's contract (deterministic parameters).
+    pub contract: fn(DirectoryShard) -> ContractContainer,
+}
+
+impl ContractHandler for DirectoryHandler {
+    fn kind(&self, _: &ContractInstanceId, _: &RouteContext) -> ContractKind {
+        ContractKind::Directory
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match ctx.directory_loader.load(self.shard, bytes) {
+            Ok(directory) => {
+                tracing::info!(
+                    "Directory GET ({}): {} entries: {:?}",
+                    self.shard.label(),
+                    directory.entries.len(),
+                    directory.entries.values().map(|e| e.name.as_str()).collect::<Vec<_>>()
+                );
+                let follow_ups = ctx.subscriptions.follow_storefronts(&directory);
+                tracing::info!("Sending {} follow-up requests", follow_ups.len());
+                // Each shard holds part of the directory, so merge
+                ctx.shared.write().directory.merge(directory);
+                follow_ups
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse directory GetResponse: {e}");
+                vec![]
+            }
+        }
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match ctx.directory_loader.load(self.shard, bytes) {
+            Ok(dir_update) => {
+                tracing::info!(
+                    "Directory notification ({}): {} entries",
+                    self.shard.label(),
+                    dir_update.entries.len()
+                );
+                let follow_ups = ctx.subscriptions.follow_storefronts(&dir_update);
+                ctx.shared.write().directory.merge(dir_update);
+                follow_ups
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse directory notification: {e}");
+                vec![]
+            }
+        }
+    }
+
+    fn create_missing(&self) -> Option<ClientRequest<'static>> {
+        // We're the first tab in this region
+        Some(put_and_subscribe((self.contract)(self.shard), &DirectoryState::default()))
+    }
+}
+
+/// A supplier storefront, keyed in shared state by the supplier's
+/// directory name.
+pub(crate) struct StorefrontHandler;
+
+impl ContractHandler for StorefrontHandler {
+    fn kind(&self, id: &ContractInstanceId, ctx: &RouteContext) -> ContractKind {
+        ContractKind::Storefront(
+            ctx.subscriptions
+                .name(id)
+                .cloned()
+                .or(ctx.customer_supplier_name.map(|s| s.to_string())),
+        )
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<StorefrontState>(bytes) {
+            Ok(storefront) => {
+                let name = storefront_name(ctx, key, &storefront);
+                tracing::info!(
+                    "Storefront GET: keyed as '{}' (info.name='{}', owner={:?}, {} products)",
+                    name, storefront.info.name, storefront.info.owner, storefront.products.len()
+                );
+                // Store the ContractKey for later use (e.g. PlaceOrder)
+                ctx.sf_contract_keys.insert(name.clone(), key);
+                store_if_changed(ctx, name, storefront);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse storefront GetResponse: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn on_resync(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<StorefrontState>(bytes) {
+            Ok(fresh) => {
+                let name = storefront_name(ctx, key, &fresh);
+                tracing::info!("Storefront GET after reconnect: '{}' ({} products)", name, fresh.products.len());
+                ctx.sf_contract_keys.insert(name.clone(), key);
+                let cached = ctx.shared.read().storefronts.get(&name).cloned();
+                let reconciled = resync::reconcile(cached, fresh.clone(), StorefrontState::merge);
+                store_if_changed(ctx, name.clone(), reconciled.state);
+                let resend = reconciled.resend.map(|cached| {
+                    (cached, Rollback::Storefront { name, previous: Some(fresh) })
+                });
+                ctx.shared.write().settle_after_reconnect(key, resend)
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse storefront GetResponse: {e}");
+                vec![]
+            }
+        }
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<StorefrontState>(bytes) {
+            Ok(sf_update) => {
+                let dir_name = {
+                    let state = ctx.shared.read();
+                    state.directory.entries.get(&sf_update.info.owner)
+                        .map(|e| e.name.clone())
+                };
+                let name_from_map = ctx.subscriptions.name(key.id()).cloned();
+                let name = ctx.customer_supplier_name.map(|s| s.to_string())
+                    .or(dir_name)
+                    .or(name_from_map)
+                    .unwrap_or_else(|| sf_update.info.name.clone());
+                tracing::info!(
+                    "Storefront notification: keyed as '{}' ({} products)",
+                    name, sf_update.products.len()
+                );
+                let merged = match ctx.shared.read().storefronts.get(&name) {
+                    Some(existing) => {
+                        let mut merged = existing.clone();
+                        merged.merge(sf_update);
+                        merged
+                    }
+                    None => sf_update,
+                };
+                store_if_changed(ctx, name, merged);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse storefront notification: {e}");
+            }
+        }
+        vec![]
+    }
+}
+
+/// Key a storefront from a GET under its supplier's directory name.
+fn storefront_name(ctx: &RouteContext, key: ContractKey, storefront: &StorefrontState) -> String {
+    // Look up the directory entry by the storefront's owner (UserId).
+    // This maps e.g. info.name "Gary's Farm" → directory name "Gary".
+    let dir_name = {
+        let state = ctx.shared.read();
+        state.directory.entries.get(&storefront.info.owner)
+            .map(|e| e.name.clone())
+    };
+    let name_from_map = ctx.subscriptions.name(key.id()).cloned();
+    // Prefer directory name (correct case) over rendezvous name
+    // (lowercase). Route parameters are resolved case-insensitively
+    // so "gary" from rendezvous will still match "Gary" from directory.
+    dir_name
+        .or(ctx.customer_supplier_name.map(|s| s.to_string()))
+        .or(name_from_map)
+        .unwrap_or_else(|| storefront.info.name.clone())
+}
+
+/// Store a storefront received from the network, leaving shared state
+/// untouched (and nothing re-rendering) if it matches what we already have.
+fn store_if_changed(ctx: &mut RouteContext, name: String, storefront: StorefrontState) {
+    let diff = ctx.shared.read().storefront_diff(&name, &storefront);
+    if diff.is_empty() {
+        tracing::debug!("Storefront '{}' unchanged, skipping update", name);
+        return;
+    }
+    tracing::debug!(
+        "Storefront '{}' changed: info={} products={} orders={} threads={}",
+        name, diff.info, diff.products.len(), diff.orders.len(), diff.threads.len()
+    );
+    ctx.shared.write().apply_storefront(name, storefront, &diff);
+}
+
+/// A user contract: the signed-in user's own, or root's.
+pub(crate) enum UserContractHandler {
+    Own,
+    Root,
+}
+
+impl UserContractHandler {
+    fn slot<'s>(&self, state: &'s mut SharedState) -> &'s mut Option<UserContractState> {
+        match self {
+            UserContractHandler::Own => &mut state.user_contract,
+            UserContractHandler::Root => &mut state.root_user_contract,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            UserContractHandler::Own => "User contract",
+            UserContractHandler::Root => "Root contract",
+        }
+    }
+
+    fn ledger_owner(&self) -> LedgerOwner {
+        match self {
+            UserContractHandler::Own => LedgerOwner::Own,
+            UserContractHandler::Root => LedgerOwner::Root,
+        }
+    }
+
+    fn rollback(&self, previous: Option<UserContractState>) -> Rollback {
+        match self {
+            UserContractHandler::Own => Rollback::UserContract(previous),
+            UserContractHandler::Root => Rollback::RootContract(previous),
+        }
+    }
+}
+
+impl ContractHandler for UserContractHandler {
+    fn kind(&self, _: &ContractInstanceId, _: &RouteContext) -> ContractKind {
+        match self {
+            UserContractHandler::Own => ContractKind::UserContract,
+            UserContractHandler::Root => ContractKind::RootContract,
+        }
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<UserContractState>(bytes) {
+            Ok(uc_state) => {
+                tracing::info!(
+                    "{} GET: name='{}', balance={}, ledger_len={}",
+                    self.label(), uc_state.name, uc_state.balance_curds, uc_state.ledger.len()
+                );
+                let mut state = ctx.shared.write();
+                state.confirm_ledger_entries(self.ledger_owner(), &uc_state.ledger);
+                *self.slot(&mut state) = Some(uc_state);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse {} GetResponse: {e}", self.label().to_lowercase());
+            }
+        }
+        vec![]
+    }
+
+    fn on_resync(&self, ctx: &mut RouteContext, key: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<UserContractState>(bytes) {
+            Ok(fresh) => {
+                tracing::info!(
+                    "{} GET after reconnect: balance={}, ledger_len={}",
+                    self.label(), fresh.balance_curds, fresh.ledger.len()
+                );
+                let mut state = ctx.shared.write();
+                state.confirm_ledger_entries(self.ledger_owner(), &fresh.ledger);
+                let cached = self.slot(&mut state).clone();
+                let reconciled = resync::reconcile(cached, fresh.clone(), UserContractState::merge);
+                *self.slot(&mut state) = Some(reconciled.state);
+                let resend = reconciled.resend.map(|cached| (cached, self.rollback(Some(fresh))));
+                state.settle_after_reconnect(key, resend)
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse {} GetResponse: {e}", self.label().to_lowercase());
+                vec![]
+            }
+        }
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<UserContractState>(bytes) {
+            Ok(uc_update) => {
+                tracing::info!(
+                    "{} notification: name='{}', balance={}, ledger_len={}",
+                    self.label(), uc_update.name, uc_update.balance_curds, uc_update.ledger.len()
+                );
+                let mut state = ctx.shared.write();
+                state.confirm_ledger_entries(self.ledger_owner(), &uc_update.ledger);
+                let slot = self.slot(&mut state);
+                if let Some(existing) = slot.as_mut() {
+                    existing.merge(uc_update);
+                } else {
+                    *slot = Some(uc_update);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse {} notification: {e}", self.label().to_lowercase());
+            }
+        }
+        vec![]
+    }
+}
+
+/// The signed-in user's inbox.
+pub(crate) struct InboxHandler;
+
+impl ContractHandler for InboxHandler {
+    fn kind(&self, _: &ContractInstanceId, _: &RouteContext) -> ContractKind {
+        ContractKind::Inbox
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<InboxState>(bytes) {
+            Ok(inbox_state) => {
+                tracing::info!("Inbox contract GET: {} messages", inbox_state.messages.len());
+                ctx.shared.write().inbox = Some(inbox_state);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse inbox GetResponse: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<InboxState>(bytes) {
+            Ok(inbox_update) => {
+                tracing::info!("Inbox notification: {} messages", inbox_update.messages.len());
+                let mut state = ctx.shared.write();
+                if let Some(existing) = state.inbox.as_mut() {
+                    existing.merge(inbox_update);
+                } else {
+                    state.inbox = Some(inbox_update);
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse inbox notification: {e}");
+            }
+        }
+        vec![]
+    }
+}
+
+/// The global market directory; PUT empty if nobody has created it yet.
+pub(crate) struct MarketDirectoryHandler {
+    pub contract: fn() -> ContractContainer,
+}
+
+impl ContractHandler for MarketDirectoryHandler {
+    fn kind(&self, _: &ContractInstanceId, _: &RouteContext) -> ContractKind {
+        ContractKind::MarketDirectory
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<MarketDirectoryState>(bytes) {
+            Ok(mkt_state) => {
+                tracing::info!("Market directory GET: {} markets", mkt_state.entries.len());
+                ctx.shared.write().market_directory = mkt_state;
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse market directory GetResponse: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<MarketDirectoryState>(bytes) {
+            Ok(mkt_update) => {
+                tracing::info!("Market directory notification: {} markets", mkt_update.entries.len());
+                ctx.shared.write().market_directory.merge(mkt_update);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse market directory notification: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn create_missing(&self) -> Option<ClientRequest<'static>> {
+        Some(put_and_subscribe((self.contract)(), &MarketDirectoryState::default()))
+    }
+}
+
+/// The global username registry; PUT empty if nobody has created it yet.
+pub(crate) struct NameRegistryHandler {
+    pub contract: fn() -> ContractContainer,
+}
+
+impl ContractHandler for NameRegistryHandler {
+    fn kind(&self, _: &ContractInstanceId, _: &RouteContext) -> ContractKind {
+        ContractKind::NameRegistry
+    }
+
+    fn on_state(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<NameRegistryState>(bytes) {
+            Ok(reg_state) => {
+                tracing::info!("Name registry GET: {} names", reg_state.claims.len());
+                ctx.shared.write().name_registry = reg_state;
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse name registry GetResponse: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn on_update(&self, ctx: &mut RouteContext, _: ContractKey, bytes: &[u8]) -> Vec<ClientRequest<'static>> {
+        match serde_json::from_slice::<NameRegistryState>(bytes) {
+            Ok(reg_update) => {
+                tracing::info!("Name registry notification: {} names", reg_update.claims.len());
+                ctx.shared.write().name_registry.merge(reg_update);
+            }
+            Err(e) => {
+                tracing::error!("Failed to parse name registry notification: {e}");
+            }
+        }
+        vec![]
+    }
+
+    fn create_missing(&self) -> Option<ClientRequest<'static>> {
+        Some(put_and_subscribe((self.contract)(), &NameRegistryState::default()))
+    }
+}
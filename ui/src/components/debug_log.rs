@@ -0,0 +1,173 @@
+//! Structured logging facade for the UI.
+//!
+//! Every `tracing` event is sent to the browser console (via `tracing-wasm`)
+//! and also recorded in an in-memory ring buffer, together with the spans it
+//! was emitted under (`node_action{action=PlaceOrder}`,
+//! `contract_response{kind=UpdateNotification}`, ...). The debug log view
+//! reads the buffer so field issues can be diagnosed from inside the app.
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::Mutex;
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Maximum number of events kept in the ring buffer.
+pub const RING_CAPACITY: usize = 500;
+
+/// One recorded `tracing` event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugEvent {
+    /// Monotonic sequence number (survives `clear()`).
+    pub seq: u64,
+    pub at: chrono::DateTime<chrono::Utc>,
+    pub level: Level,
+    pub target: String,
+    /// Enclosing spans, outermost first, formatted as `name{field=value}`.
+    pub spans: Vec<String>,
+    pub message: String,
+    /// Structured fields other than the message, in recording order.
+    pub fields: Vec<(String, String)>,
+}
+
+struct RingBuffer {
+    next_seq: u64,
+    events: VecDeque<DebugEvent>,
+}
+
+static RING: Mutex<RingBuffer> = Mutex::new(RingBuffer {
+    next_seq: 0,
+    events: VecDeque::new(),
+});
+
+fn with_ring<R>(f: impl FnOnce(&mut RingBuffer) -> R) -> R {
+    let mut ring = RING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(&mut ring)
+}
+
+/// Snapshot of the buffered events, oldest first.
+pub fn recent_events() -> Vec<DebugEvent> {
+    with_ring(|ring| ring.events.iter().cloned().collect())
+}
+
+/// Sequence number the next event will get; changes whenever an event is recorded.
+pub fn next_seq() -> u64 {
+    with_ring(|ring| ring.next_seq)
+}
+
+/// Drop all buffered events.
+pub fn clear() {
+    with_ring(|ring| ring.events.clear());
+}
+
+/// Install the global subscriber: console output plus the ring buffer.
+///
+/// Must run before `dioxus::launch`, which otherwise installs its own logger.
+pub fn init() {
+    let filter = Targets::new()
+        .with_target("cream_ui", Level::DEBUG)
+        .with_target("cream_common", Level::DEBUG)
+        .with_default(Level::INFO);
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(RingBufferLayer);
+    #[cfg(target_family = "wasm")]
+    let subscriber = subscriber.with(tracing_wasm::WASMLayer::new(
+        tracing_wasm::WASMLayerConfig::default(),
+    ));
+    let _ = tracing::subscriber::set_global_default(subscriber);
+}
+
+/// Collects an event's or span's fields into strings.
+#[derive(Default)]
+struct FieldCollector {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl FieldCollector {
+    fn push(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+
+    fn joined(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.push(field, format!("{:?}", value));
+    }
+}
+
+/// Formatted span fields, stashed in the span's extensions on creation.
+struct SpanFields(String);
+
+/// `tracing_subscriber` layer that appends every event to the ring buffer.
+struct RingBufferLayer;
+
+impl<S> Layer<S> for RingBufferLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = FieldCollector::default();
+        attrs.record(&mut fields);
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanFields(fields.joined()));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = FieldCollector::default();
+        event.record(&mut fields);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| match span.extensions().get::<SpanFields>() {
+                        Some(SpanFields(f)) if !f.is_empty() => format!("{}{{{}}}", span.name(), f),
+                        _ => span.name().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let meta = event.metadata();
+        with_ring(|ring| {
+            if ring.events.len() >= RING_CAPACITY {
+                ring.events.pop_front();
+            }
+            ring.events.push_back(DebugEvent {
+                seq: ring.next_seq,
+                at: chrono::Utc::now(),
+                level: *meta.level(),
+                target: meta.target().to_string(),
+                spans,
+                message: fields.message,
+                fields: fields.fields,
+            });
+            ring.next_seq += 1;
+        });
+    }
+}
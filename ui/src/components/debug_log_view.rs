@@ -0,0 +1,114 @@
+use dioxus::prelude::*;
+use tracing::Level;
+
+use super::debug_log::{self, DebugEvent};
+
+/// How often the view re-reads the ring buffer while open.
+#[cfg(target_family = "wasm")]
+const REFRESH_INTERVAL_MS: u32 = 1_000;
+
+/// In-app view of the structured log ring buffer (newest first).
+#[component]
+pub fn DebugLogView() -> Element {
+    let mut events = use_signal(debug_log::recent_events);
+    let mut min_level = use_signal(|| Level::DEBUG);
+    let mut span_filter = use_signal(String::new);
+
+    // Poll the buffer; only touch the signal when something new was recorded.
+    #[cfg(target_family = "wasm")]
+    use_future(move || async move {
+        let mut seen = debug_log::next_seq();
+        loop {
+            gloo_timers::future::TimeoutFuture::new(REFRESH_INTERVAL_MS).await;
+            let latest = debug_log::next_seq();
+            if latest != seen {
+                seen = latest;
+                events.set(debug_log::recent_events());
+            }
+        }
+    });
+
+    let level = *min_level.read();
+    let query = span_filter.read().to_lowercase();
+    let shown: Vec<DebugEvent> = events
+        .read()
+        .iter()
+        .rev()
+        // tracing orders levels by verbosity: ERROR < WARN < ... < TRACE
+        .filter(|e| e.level <= level)
+        .filter(|e| {
+            query.is_empty()
+                || e.spans.iter().any(|s| s.to_lowercase().contains(&query))
+                || e.message.to_lowercase().contains(&query)
+        })
+        .cloned()
+        .collect();
+    let shown_count = shown.len();
+    let total = events.read().len();
+
+    rsx! {
+        div { class: "debug-log-view",
+            h2 { "Debug Log" }
+            div { class: "debug-log-controls",
+                select {
+                    value: "{level}",
+                    onchange: move |evt| {
+                        let parsed = evt.value().parse::<Level>().unwrap_or(Level::DEBUG);
+                        min_level.set(parsed);
+                    },
+                    for l in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG, Level::TRACE] {
+                        option { value: "{l}", selected: l == level, "{l} and above" }
+                    }
+                }
+                input {
+                    r#type: "text",
+                    placeholder: "Filter by span or message...",
+                    value: "{span_filter}",
+                    oninput: move |evt| span_filter.set(evt.value()),
+                }
+                button {
+                    onclick: move |_| events.set(debug_log::recent_events()),
+                    "Refresh"
+                }
+                button {
+                    onclick: move |_| {
+                        debug_log::clear();
+                        events.set(Vec::new());
+                    },
+                    "Clear"
+                }
+                span { class: "debug-log-count", "{shown_count} of {total} events" }
+            }
+            if shown.is_empty() {
+                p { class: "empty-state", "No events recorded." }
+            } else {
+                ul { class: "debug-log-list",
+                    for event in shown {
+                        DebugEventRow { key: "{event.seq}", event }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn DebugEventRow(event: DebugEvent) -> Element {
+    let class = format!("debug-log-event level-{}", event.level.as_str().to_lowercase());
+    let time = event.at.format("%H:%M:%S%.3f").to_string();
+    let spans = event.spans.join(" > ");
+
+    rsx! {
+        li { class: "{class}",
+            span { class: "debug-log-time", "{time}" }
+            span { class: "debug-log-level", "{event.level}" }
+            if !spans.is_empty() {
+                span { class: "debug-log-spans", "{spans}" }
+            }
+            span { class: "debug-log-message", "{event.message}" }
+            for (name, value) in event.fields.iter() {
+                span { class: "debug-log-field", "{name}={value}" }
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Signing through the node's CREAM delegate.
+//!
+//! With [`UserState::delegate_signing`](super::user_state::UserState) on,
+//! listings, orders, directory entries and balance attestations are signed
+//! by the `cream-delegate` running in the user's Freenet node rather than by
+//! the key held in the page, so the key's custody can move out of page
+//! JavaScript. The first
+//! signature on a node registers the delegate there and, if it doesn't hold
+//! this user's key yet, imports it.
+//!
+//! Each signature is one round trip on a connection of its own, so it never
+//! waits behind the main node loop. If the delegate can't be reached the page
+//! signs locally instead — except for an order the delegate's signing policy
+//! refused, which is not placed at all.
+
+use std::sync::{Arc, Mutex};
+
+use ed25519_dalek::Signature;
+use freenet_stdlib::client_api::{ClientRequest, DelegateRequest, HostResponse};
+use freenet_stdlib::prelude::{
+    ApplicationMessage, ContractInstanceId, Delegate, DelegateCode, DelegateContainer, DelegateWasmAPIVersion,
+    InboundDelegateMsg, OutboundDelegateMsg, Parameters,
+};
+use futures::channel::mpsc;
+use futures::StreamExt;
+
+use cream_common::attestation::BalanceAttestation;
+use cream_common::directory::DirectoryEntry;
+use cream_common::identity::{UserId, UserRole};
+use cream_common::order::Order;
+use cream_common::product::Product;
+use cream_common::user_contract::UserContractState;
+use cream_delegate::{CreamRequest, CreamResponse};
+
+use super::key_manager::KeyManager;
+use super::platform;
+
+/// Embedded delegate WASM (built with `cargo make build-delegate`).
+const DELEGATE_WASM: &[u8] = include_bytes!("../../../target/wasm32-unknown-unknown/release/cream_delegate.wasm");
+
+/// How long to wait for each answer from the node.
+const DELEGATE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// The CREAM delegate (no parameters, so one per node).
+fn cream_delegate() -> DelegateContainer {
+    let code = DelegateCode::from(DELEGATE_WASM.to_vec());
+    let delegate = Delegate::from((&code, &Parameters::from(vec![])));
+    DelegateContainer::Wasm(DelegateWasmAPIVersion::V1(delegate))
+}
+
+/// What this session has set up on which node.
+#[derive(Clone, Default)]
+pub struct DelegateSession {
+    /// Node URL whose delegate is registered and holds our key.
+    ready_at: Arc<Mutex<Option<String>>>,
+}
+
+/// Signs for one action: through the delegate when enabled, else (or when
+/// it can't be reached) with the page's key.
+pub struct KeySigner<'a> {
+    key_manager: &'a KeyManager,
+    /// The session and node URL to use, when delegate signing is on.
+    delegate: Option<(&'a DelegateSession, &'a str)>,
+    role: UserRole,
+}
+
+impl<'a> KeySigner<'a> {
+    pub fn new(
+        key_manager: &'a KeyManager,
+        delegate: Option<(&'a DelegateSession, &'a str)>,
+        role: UserRole,
+    ) -> Self {
+        KeySigner { key_manager, delegate, role }
+    }
+
+    /// Signature over `product`, for a [`SignedProduct`](cream_common::storefront::SignedProduct).
+    pub async fn sign_product(&self, product: &Product) -> Signature {
+        match self.ask(CreamRequest::SignProduct(product.clone())).await {
+            Some(CreamResponse::SignedProduct(signed)) => signed.signature,
+            _ => self.key_manager.sign_product(product),
+        }
+    }
+
+    /// Sign `order`, placed with `supplier`, in place. Fails only if the
+    /// delegate's signing policy refuses the order.
+    pub async fn sign_order(&self, order: &mut Order, supplier: &UserId) -> Result<(), String> {
+        let request = CreamRequest::SignOrder { order: order.clone(), supplier: supplier.clone() };
+        match self.ask(request).await {
+            Some(CreamResponse::SignedOrder(signed)) => order.signature = signed.signature,
+            Some(CreamResponse::PolicyViolation(violation)) => return Err(violation.to_string()),
+            _ => self.key_manager.sign_order(order),
+        }
+        Ok(())
+    }
+
+    /// Sign a directory entry in place.
+    pub async fn sign_directory_entry(&self, entry: &mut DirectoryEntry) {
+        match self.ask(CreamRequest::SignDirectoryEntry(entry.clone())).await {
+            Some(CreamResponse::SignedDirectoryEntry(signed)) => entry.signature = signed.signature,
+            _ => self.key_manager.sign_directory_entry(entry),
+        }
+    }
+
+    /// Attest the balance of our user contract `state`, for a storefront
+    /// that requires it on large orders.
+    pub async fn attest_balance(&self, state: &UserContractState) -> BalanceAttestation {
+        match self.ask(CreamRequest::AttestBalance(state.clone())).await {
+            Some(CreamResponse::BalanceAttestation(attestation)) => attestation,
+            _ => {
+                let mut attestation = BalanceAttestation::of(state, chrono::Utc::now());
+                self.key_manager.sign_balance_attestation(&mut attestation);
+                attestation
+            }
+        }
+    }
+
+    /// The delegate's answer to `request`, or `None` if delegate signing is
+    /// off or the delegate couldn't be reached.
+    async fn ask(&self, request: CreamRequest) -> Option<CreamResponse> {
+        let (session, url) = self.delegate?;
+        match self.round_trip(session, url, request).await {
+            Ok(CreamResponse::Error(e)) => {
+                tracing::warn!("Delegate refused to sign ({e}), signing in the page");
+                None
+            }
+            Ok(response) => Some(response),
+            Err(e) => {
+                tracing::warn!("Delegate unreachable ({e}), signing in the page");
+                None
+            }
+        }
+    }
+
+    async fn round_trip(
+        &self,
+        session: &DelegateSession,
+        url: &str,
+        request: CreamRequest,
+    ) -> Result<CreamResponse, String> {
+        let (send_responses, mut responses) = mpsc::unbounded();
+        let mut api = platform::connect(url, send_responses).await?;
+        let result = async {
+            let ready = session.ready_at.lock().unwrap().as_deref() == Some(url);
+            if !ready {
+                self.set_up(&mut api, &mut responses).await?;
+                *session.ready_at.lock().unwrap() = Some(url.to_string());
+            }
+            send_message(&mut api, &mut responses, &request).await
+        }
+        .await;
+        platform::disconnect(api);
+        result
+    }
+
+    /// Register the delegate on the node and make sure it holds our key.
+    async fn set_up(&self, api: &mut platform::NodeApi, responses: &mut Responses) -> Result<(), String> {
+        let register = ClientRequest::DelegateOp(DelegateRequest::RegisterDelegate {
+            delegate: cream_delegate(),
+            cipher: DelegateRequest::DEFAULT_CIPHER,
+            nonce: DelegateRequest::DEFAULT_NONCE,
+        });
+        api.send(register).await.map_err(|e| format!("{e:?}"))?;
+        next_response(responses).await?;
+
+        let ours = self.key_manager.user_id();
+        if let CreamResponse::Identity(identity) = send_message(api, responses, &CreamRequest::GetIdentity).await? {
+            if identity.user_id == ours {
+                return Ok(());
+            }
+        }
+        let import = CreamRequest::ImportIdentity {
+            signing_key: self.key_manager.signing_key_bytes(),
+            role: self.role.clone(),
+        };
+        match send_message(api, responses, &import).await? {
+            CreamResponse::Identity(identity) if identity.user_id == ours => {
+                tracing::info!("Imported key into the node's CREAM delegate");
+                Ok(())
+            }
+            other => Err(format!("key import failed: {other:?}")),
+        }
+    }
+}
+
+type Responses = mpsc::UnboundedReceiver<Result<HostResponse, freenet_stdlib::client_api::ClientError>>;
+
+/// Send one request to the delegate and wait for its answer.
+async fn send_message(
+    api: &mut platform::NodeApi,
+    responses: &mut Responses,
+    request: &CreamRequest,
+) -> Result<CreamResponse, String> {
+    let payload = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+    let message = ApplicationMessage::new(ContractInstanceId::new([0u8; 32]), payload);
+    let delegate = cream_delegate();
+    api.send(ClientRequest::DelegateOp(DelegateRequest::ApplicationMessages {
+        key: delegate.key().clone(),
+        params: Parameters::from(vec![]),
+        inbound: vec![InboundDelegateMsg::ApplicationMessage(message)],
+    }))
+    .await
+    .map_err(|e| format!("{e:?}"))?;
+    loop {
+        if let HostResponse::DelegateResponse { values, .. } = next_response(responses).await? {
+            let answer = values.into_iter().find_map(|msg| match msg {
+                OutboundDelegateMsg::ApplicationMessage(reply) => Some(reply.payload),
+                _ => None,
+            });
+            if let Some(payload) = answer {
+                return serde_json::from_slice(&payload).map_err(|e| format!("bad delegate answer: {e}"));
+            }
+        }
+    }
+}
+
+async fn next_response(responses: &mut Responses) -> Result<HostResponse, String> {
+    let timeout = Box::pin(platform::sleep(DELEGATE_TIMEOUT));
+    match futures::future::select(responses.next(), timeout).await {
+        futures::future::Either::Left((Some(Ok(response)), _)) => Ok(response),
+        futures::future::Either::Left((Some(Err(e)), _)) => Err(e.to_string()),
+        futures::future::Either::Left((None, _)) => Err("connection closed".to_string()),
+        futures::future::Either::Right(_) => Err("timed out".to_string()),
+    }
+}
@@ -0,0 +1,109 @@
+//! Loading large directory shards without freezing the page.
+//!
+//! A shard listing hundreds of suppliers takes a while to deserialize — every
+//! entry carries keys and signatures to decode — and to render. A state with
+//! more than [`CHUNK_ENTRIES`] entries is therefore read as untyped JSON and
+//! cut into chunks: the first is deserialized and merged straight away, the
+//! rest one at a time in a background task that yields to the event loop
+//! between chunks and hands each to the node loop as a [`DirectoryChunk`].
+//! The directory fills in while the page stays responsive.
+//!
+//! Moderation records and credentials travel with the first chunk, so a
+//! removed supplier is never listed in between. Superseded entries travel
+//! with their supplier's current one.
+
+use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use serde_json::{Map, Value};
+
+use cream_common::directory::{DirectoryShard, DirectoryState};
+
+use super::platform;
+
+/// Entries per chunk.
+pub(crate) const CHUNK_ENTRIES: usize = 50;
+
+/// Part of a shard's state, ready to merge into the directory.
+pub(crate) struct DirectoryChunk {
+    pub shard: DirectoryShard,
+    pub state: DirectoryState,
+}
+
+/// Splits large directory states and sends the chunks after the first to
+/// the node loop.
+#[derive(Clone)]
+pub(crate) struct DirectoryLoader {
+    chunks: UnboundedSender<DirectoryChunk>,
+}
+
+/// A loader, and the chunks it will send.
+pub(crate) fn channel() -> (DirectoryLoader, UnboundedReceiver<DirectoryChunk>) {
+    let (chunks, receiver) = mpsc::unbounded();
+    (DirectoryLoader { chunks }, receiver)
+}
+
+impl DirectoryLoader {
+    /// `bytes` as a directory state: the whole of it if small, otherwise its
+    /// first chunk, with the rest to follow.
+    pub fn load(&self, shard: DirectoryShard, bytes: &[u8]) -> Result<DirectoryState, serde_json::Error> {
+        let mut chunks = split(serde_json::from_slice(bytes)?).into_iter();
+        let first = serde_json::from_value(chunks.next().unwrap_or_default())?;
+        let rest: Vec<Value> = chunks.collect();
+        if !rest.is_empty() {
+            tracing::info!("Directory ({}): {} more chunks to load", shard.label(), rest.len());
+            let sender = self.chunks.clone();
+            platform::spawn_local(async move {
+                for chunk in rest {
+                    // Let the page render what it has so far
+                    platform::sleep_ms(0).await;
+                    match serde_json::from_value(chunk) {
+                        Ok(state) => {
+                            if sender.unbounded_send(DirectoryChunk { shard, state }).is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => tracing::error!("Failed to parse directory chunk ({}): {e}", shard.label()),
+                    }
+                }
+            });
+        }
+        Ok(first)
+    }
+}
+
+/// Cut a directory state into states of at most [`CHUNK_ENTRIES`] entries,
+/// each supplier's history going with their entry and everything else with
+/// the first.
+fn split(mut state: Value) -> Vec<Value> {
+    let Some(object) = state.as_object_mut() else {
+        return vec![state];
+    };
+    let entries = match object.remove("entries") {
+        Some(Value::Object(entries)) if entries.len() > CHUNK_ENTRIES => entries,
+        Some(entries) => {
+            object.insert("entries".into(), entries);
+            return vec![state];
+        }
+        None => return vec![state],
+    };
+    let mut history = match object.remove("history") {
+        Some(Value::Object(history)) => history,
+        _ => Map::new(),
+    };
+    let mut rest = std::mem::take(object);
+    let mut entries = entries.into_iter().peekable();
+    let mut chunks = Vec::new();
+    while entries.peek().is_some() {
+        let part: Map<String, Value> = entries.by_ref().take(CHUNK_ENTRIES).collect();
+        let part_history: Map<String, Value> = part
+            .keys()
+            .filter_map(|id| history.remove(id).map(|versions| (id.clone(), versions)))
+            .collect();
+        let mut chunk = std::mem::take(&mut rest);
+        chunk.insert("entries".into(), Value::Object(part));
+        if !part_history.is_empty() {
+            chunk.insert("history".into(), Value::Object(part_history));
+        }
+        chunks.push(Value::Object(chunk));
+    }
+    chunks
+}
@@ -1,21 +1,63 @@
 use dioxus::prelude::*;
 
-use cream_common::postcode::{distance_between_postcodes, format_postcode};
+use cream_common::directory::DirectoryChangeKind;
+use cream_common::identity::UserId;
+use cream_common::location::{food_miles_label, sort_by_proximity, GeoLocation};
+use cream_common::postcode::{format_postcode, lookup_postcode};
+use cream_common::storefront::WeeklySchedule;
 
 use super::app::Route;
+use super::i18n::{use_i18n, Msg};
 use super::shared_state::use_shared_state;
+use super::storefront_view::{CredentialBadges, OpenClosedBadge};
+use super::supplier_map::{MapPoint, SupplierMap};
 use super::user_state::use_user_state;
 
+/// Above this many suppliers the list is virtualized: only the cards in
+/// view, and a few either side, are rendered.
+const VIRTUALIZE_ABOVE: usize = 50;
+
+/// Height of a card in the virtualized list, margin included; matches
+/// `.supplier-list-virtual .supplier-card` in input.css.
+const ROW_HEIGHT_PX: f64 = 240.0;
+
+/// Height assumed for the virtualized list until a scroll reports it.
+const VIEWPORT_PX: f64 = 720.0;
+
+/// Cards rendered beyond each edge of the view.
+const OVERSCAN_ROWS: usize = 4;
+
+/// The rows of a `count`-row virtualized list to render, scrolled to
+/// `scroll_top` in a `viewport`-high view.
+fn visible_rows(count: usize, scroll_top: f64, viewport: f64) -> std::ops::Range<usize> {
+    let first = (scroll_top.max(0.0) / ROW_HEIGHT_PX) as usize;
+    let in_view = (viewport / ROW_HEIGHT_PX).ceil() as usize + 1;
+    let start = first.saturating_sub(OVERSCAN_ROWS).min(count);
+    let end = (first + in_view + OVERSCAN_ROWS).min(count);
+    start..end
+}
+
+/// How the directory is shown.
+#[derive(Clone, Copy, PartialEq)]
+enum DirectoryMode {
+    List,
+    Map,
+    Changes,
+}
 
 /// A supplier entry for display in the directory.
 #[derive(Clone, Debug)]
 struct SupplierEntry {
+    supplier: UserId,
     name: String,
     description: String,
     postcode: String,
     locality: Option<String>,
-    distance_km: Option<f64>,
+    location: GeoLocation,
     product_count: usize,
+    /// Opening hours and timezone, once the storefront has been fetched.
+    schedule: Option<WeeklySchedule>,
+    timezone: Option<String>,
 }
 
 #[component]
@@ -23,9 +65,13 @@ pub fn DirectoryView() -> Element {
     let user_state = use_user_state();
     let shared_state = use_shared_state();
     let mut search_query = use_signal(String::new);
+    let mut mode = use_signal(|| DirectoryMode::List);
+    let mut scroll_top = use_signal(|| 0.0f64);
+    let mut viewport = use_signal(|| VIEWPORT_PX);
+    let i18n = use_i18n();
 
     let state = user_state.read();
-    let user_postcode = state.postcode.clone().unwrap_or_default();
+    let user_location = state.postcode.as_deref().and_then(lookup_postcode);
 
     // Build supplier list from the network directory
     let mut suppliers: Vec<SupplierEntry> = Vec::new();
@@ -40,7 +86,8 @@ pub fn DirectoryView() -> Element {
     };
     {
         let shared = shared_state.read();
-        for entry in shared.directory.entries.values() {
+        // Removed and currently suspended suppliers aren't listed
+        for entry in shared.directory.listed_entries(chrono::Utc::now()) {
             // Skip our own entry — suppliers manage their storefront via "My Storefront"
             if let Some(ref my_id) = my_supplier_id {
                 if &entry.supplier == my_id {
@@ -49,39 +96,29 @@ pub fn DirectoryView() -> Element {
             }
 
             let postcode = entry.postcode.clone().unwrap_or_default();
-            let dist = distance_between_postcodes(&user_postcode, &postcode);
-            let product_count = shared
-                .storefronts
-                .get(&entry.name)
-                .map(|sf| sf.products.len())
-                .unwrap_or(0);
-
-            #[cfg(target_family = "wasm")]
-            web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(
-                &format!("[CREAM] DirectoryView: {} → {} products", entry.name, product_count)
-            ));
+            let storefront = shared.storefronts.get(&entry.name);
+            let product_count = storefront.map(|sf| sf.products.len()).unwrap_or(0);
 
             suppliers.push(SupplierEntry {
+                supplier: entry.supplier.clone(),
                 name: entry.name.clone(),
                 description: entry.description.clone(),
                 postcode,
                 locality: entry.locality.clone(),
-                distance_km: dist,
+                location: entry.location.clone(),
                 product_count,
+                schedule: storefront.and_then(|sf| sf.info.schedule.clone()),
+                timezone: storefront.and_then(|sf| sf.info.timezone.clone()),
             });
         }
     }
 
-    // Sort by distance (closest first), unknowns at the end
-    suppliers.sort_by(|a, b| {
-        let da = a.distance_km.unwrap_or(f64::MAX);
-        let db = b.distance_km.unwrap_or(f64::MAX);
-        da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
-    });
+    // Sort by distance (closest first)
+    sort_by_proximity(&mut suppliers, user_location.as_ref(), |s| Some(&s.location));
 
     // Filter by search query
     let query = search_query.read().to_lowercase();
-    let filtered: Vec<_> = suppliers
+    let mut filtered: Vec<_> = suppliers
         .into_iter()
         .filter(|s| {
             query.is_empty()
@@ -90,49 +127,184 @@ pub fn DirectoryView() -> Element {
                 || s.postcode.contains(&query)
         })
         .collect();
+    let map_points: Vec<MapPoint> = filtered
+        .iter()
+        .map(|s| MapPoint { name: s.name.clone(), location: s.location.clone() })
+        .collect();
+
+    // Large directories render only the cards around the scroll position,
+    // with spacers standing in for the rest
+    let total = filtered.len();
+    let virtualized = total > VIRTUALIZE_ABOVE;
+    let rows = if virtualized {
+        visible_rows(total, *scroll_top.read(), *viewport.read())
+    } else {
+        0..total
+    };
+    let above_px = rows.start as f64 * ROW_HEIGHT_PX;
+    let below_px = (total - rows.end) as f64 * ROW_HEIGHT_PX;
+    let shown: Vec<SupplierEntry> = filtered.drain(rows).collect();
 
     rsx! {
         div { class: "directory-view",
-            h2 { "Supplier Directory" }
+            h2 { {i18n.t(Msg::SupplierDirectory)} }
 
             div { class: "search-bar",
                 input {
                     r#type: "text",
-                    placeholder: "Search suppliers...",
+                    placeholder: i18n.t(Msg::SearchSuppliers),
                     value: "{search_query}",
                     oninput: move |evt| search_query.set(evt.value()),
                 }
             }
-            div { class: "supplier-list",
-                if filtered.is_empty() {
-                    p { class: "empty-state", "No suppliers found." }
-                } else {
-                    {filtered.into_iter().map(|supplier| {
-                        let distance_text = match supplier.distance_km {
-                            Some(d) if d < 1.0 => "< 1 km away".to_string(),
-                            Some(d) => format!("{:.0} km away", d),
-                            None => "Distance unknown".to_string(),
-                        };
-                        rsx! {
-                            div { class: "supplier-card",
-                                key: "{supplier.name}",
-                                h3 { "{supplier.name}" }
-                                p { "{supplier.description}" }
-                                {
-                                    let location_name = format_postcode(&supplier.postcode, supplier.locality.as_deref());
-                                    rsx! { p { class: "location", "{location_name} - {distance_text}" } }
-                                }
-                                p { class: "product-count", "{supplier.product_count} products" }
-                                Link {
-                                    to: Route::Supplier { name: supplier.name.clone() },
-                                    "View Storefront"
+            div { class: "view-toggle",
+                button {
+                    class: if *mode.read() == DirectoryMode::List { "active" } else { "" },
+                    onclick: move |_| mode.set(DirectoryMode::List),
+                    {i18n.t(Msg::ViewList)}
+                }
+                button {
+                    class: if *mode.read() == DirectoryMode::Map { "active" } else { "" },
+                    onclick: move |_| mode.set(DirectoryMode::Map),
+                    {i18n.t(Msg::ViewMap)}
+                }
+                button {
+                    class: if *mode.read() == DirectoryMode::Changes { "active" } else { "" },
+                    onclick: move |_| mode.set(DirectoryMode::Changes),
+                    {i18n.t(Msg::ViewChanges)}
+                }
+            }
+            if *mode.read() == DirectoryMode::Map {
+                SupplierMap { points: map_points, home: user_location.clone(), linked: true }
+            } else if *mode.read() == DirectoryMode::Changes {
+                DirectoryChangelog {}
+            } else {
+                div { class: if virtualized { "supplier-list supplier-list-virtual" } else { "supplier-list" },
+                    onscroll: move |evt: Event<ScrollData>| {
+                        scroll_top.set(evt.data().scroll_top());
+                        viewport.set(f64::from(evt.data().client_height()));
+                    },
+                    if total == 0 {
+                        p { class: "empty-state", {i18n.t(Msg::NoSuppliersFound)} }
+                    } else {
+                        if virtualized {
+                            div { class: "virtual-spacer", style: "height: {above_px}px" }
+                        }
+                        {shown.into_iter().map(|supplier| {
+                            let distance_text = match user_location.as_ref() {
+                                Some(home) => format!(
+                                    "{} {}",
+                                    food_miles_label(supplier.location.distance_km(home)),
+                                    i18n.t(Msg::Away)
+                                ),
+                                None => i18n.t(Msg::DistanceUnknown).to_string(),
+                            };
+                            rsx! {
+                                div { class: "supplier-card",
+                                    key: "{supplier.name}",
+                                    h3 { "{supplier.name}" }
+                                    if let Some(schedule) = supplier.schedule.clone() {
+                                        OpenClosedBadge { schedule, timezone: supplier.timezone.clone() }
+                                    }
+                                    CredentialBadges { supplier: supplier.supplier.clone() }
+                                    p { "{supplier.description}" }
+                                    {
+                                        let location_name = format_postcode(&supplier.postcode, supplier.locality.as_deref());
+                                        rsx! { p { class: "location", "{location_name} - {distance_text}" } }
+                                    }
+                                    p { class: "product-count", "{supplier.product_count} {i18n.t(Msg::Products)}" }
+                                    Link {
+                                        to: Route::Supplier { name: supplier.name.clone() },
+                                        {i18n.t(Msg::ViewStorefront)}
+                                    }
                                 }
                             }
+                        })}
+                        if virtualized {
+                            div { class: "virtual-spacer", style: "height: {below_px}px" }
                         }
-                    })}
+                    }
                 }
             }
         }
     }
 }
 
+
+/// Recent directory changes, newest first, from the entry history kept in the
+/// directory. Changes that repoint a supplier's contracts are flagged: a
+/// stolen supplier key would be used to redirect customers that way.
+#[component]
+fn DirectoryChangelog() -> Element {
+    let shared_state = use_shared_state();
+    let i18n = use_i18n();
+    let mut days = use_signal(|| 7i64);
+
+    let since = chrono::Utc::now() - chrono::Duration::days(*days.read());
+    let shared = shared_state.read();
+    let changes = shared.directory.changes_since(since);
+    let rows: Vec<(String, String, String, bool)> = changes
+        .iter()
+        .map(|change| {
+            let what = match &change.kind {
+                DirectoryChangeKind::Listed => i18n.t(Msg::JoinedDirectory).to_string(),
+                DirectoryChangeKind::Updated { fields, .. } if fields.is_empty() => {
+                    i18n.t(Msg::RepublishedListing).to_string()
+                }
+                DirectoryChangeKind::Updated { fields, .. } => {
+                    let labels: Vec<&str> = fields.iter().map(|f| i18n.t(*f)).collect();
+                    format!("{} {}", i18n.t(Msg::Changed), labels.join(", "))
+                }
+                DirectoryChangeKind::Moderated(record) => format!(
+                    "{} {}: {}",
+                    i18n.t(&record.action),
+                    i18n.t(Msg::ByGuardians),
+                    record.reason
+                ),
+            };
+            (
+                i18n.datetime(&change.at),
+                change.entry.name.clone(),
+                what,
+                change.is_suspicious(),
+            )
+        })
+        .collect();
+    drop(shared);
+
+    rsx! {
+        div { class: "directory-changelog",
+            div { class: "changelog-window",
+                label { "{i18n.t(Msg::ChangesFromLast)} " }
+                select {
+                    value: "{days}",
+                    onchange: move |evt| {
+                        if let Ok(d) = evt.value().parse() {
+                            days.set(d);
+                        }
+                    },
+                    option { value: "1", {i18n.t(Msg::PeriodDay)} }
+                    option { value: "7", {i18n.t(Msg::PeriodWeek)} }
+                    option { value: "30", {i18n.t(Msg::Period30Days)} }
+                    option { value: "365", {i18n.t(Msg::PeriodYear)} }
+                }
+            }
+            if rows.is_empty() {
+                p { class: "empty-state", {i18n.t(Msg::NoDirectoryChanges)} }
+            } else {
+                ul { class: "changelog",
+                    for (at, name, what, suspicious) in rows {
+                        li { class: if suspicious { "change change-suspicious" } else { "change" },
+                            span { class: "change-time", "{at}" }
+                            strong { " {name} " }
+                            span { "{what}" }
+                            if suspicious {
+                                p { class: "change-warning", {i18n.t(Msg::ContractsRepointedWarning)} }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Plain-language versions of the errors the node reports.
+//!
+//! Contract rejections reach the client as a `ClientError` whose cause is
+//! whatever string the contract's `ContractError` displayed as — "invalid
+//! contract update", "bad timestamp: …" and so on. [`explain_rejection`] and
+//! [`explain_client_error`] turn the cases CREAM's contracts produce into a
+//! message saying what went wrong, a hint at what to do about it and, where
+//! there is one, a page that fixes it ([`Remedy`]). The raw error still goes
+//! to [`SharedState::last_error`] and the debug log.
+
+use freenet_stdlib::client_api::{ClientError, ContractError, ErrorKind, RequestError};
+
+use super::shared_state::SharedState;
+
+/// A page that helps with an error, offered as a button beside it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Remedy {
+    NodeSettings,
+    Wallet,
+    DebugLog,
+}
+
+impl Remedy {
+    pub fn label(self) -> &'static str {
+        match self {
+            Remedy::NodeSettings => "Node settings",
+            Remedy::Wallet => "Open wallet",
+            Remedy::DebugLog => "Show log",
+        }
+    }
+}
+
+/// An error as the user is told about it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FriendlyError {
+    pub message: String,
+    /// What to try next.
+    pub hint: Option<&'static str>,
+    pub remedy: Option<Remedy>,
+}
+
+impl FriendlyError {
+    fn new(message: impl Into<String>, hint: Option<&'static str>, remedy: Option<Remedy>) -> Self {
+        FriendlyError { message: message.into(), hint, remedy }
+    }
+}
+
+/// Actions whose rejection most likely means the storefront wouldn't take
+/// the order as placed, rather than a signing problem.
+const ORDER_ACTIONS: [&str; 2] = ["PlaceOrder", "AmendOrder"];
+
+/// What a contract refusing the changes made by `actions` (labels like
+/// "PlaceOrder") with `cause` means for the user.
+pub fn explain_rejection(actions: &[String], cause: &str) -> FriendlyError {
+    let lower = cause.to_lowercase();
+    let ordering = actions.iter().any(|a| ORDER_ACTIONS.contains(&a.as_str()));
+    if lower.contains("size limit exceeded") {
+        FriendlyError::new(
+            "That would make the contract larger than the network allows.",
+            Some("Remove things you no longer need, such as old products or messages, and try again."),
+            None,
+        )
+    } else if lower.contains("bad timestamp") {
+        FriendlyError::new(
+            "The change was dated wrongly, so the network refused it.",
+            Some("Check your device's date and time settings, then try again."),
+            None,
+        )
+    } else if lower.contains("insufficient") || lower.contains("balance") {
+        FriendlyError::new(
+            "There isn't enough CURD in your account for that.",
+            Some("Top up your wallet, or reduce the amount."),
+            Some(Remedy::Wallet),
+        )
+    } else if lower.contains("name taken") {
+        FriendlyError::new(
+            "Another supplier is already listed under that name.",
+            Some("Suppliers' names must differ in more than capital letters. Choose another name and try again."),
+            None,
+        )
+    } else if lower.contains("deserializ") {
+        FriendlyError::new(
+            "The network couldn't read the change; this copy of CREAM may be out of date.",
+            Some("Reload the page to get the latest version, then try again."),
+            None,
+        )
+    } else if ordering {
+        // The storefront refuses orders it can't honour with a bare
+        // InvalidUpdate, so the cause can't tell us which check failed
+        FriendlyError::new(
+            "The supplier's storefront didn't accept the order.",
+            Some(
+                "The product may have sold out, its price may have changed, or a large order may need a \
+                 balance check. Look at the storefront again and place a new order.",
+            ),
+            None,
+        )
+    } else if lower.contains("invalid") || lower.contains("signature") {
+        FriendlyError::new(
+            "The network refused the change because it wasn't signed by a key allowed to make it.",
+            Some("Make sure you're signed in as the right user. Staff can only make the changes their grant allows."),
+            None,
+        )
+    } else {
+        FriendlyError::new(format!("The network refused the change: {cause}"), None, Some(Remedy::DebugLog))
+    }
+}
+
+/// What an error from the node means for the user.
+pub fn explain_client_error(err: &ClientError) -> FriendlyError {
+    match err.kind() {
+        ErrorKind::Disconnect | ErrorKind::ChannelClosed => FriendlyError::new(
+            "Lost the connection to your Freenet node.",
+            Some("CREAM reconnects by itself. If it doesn't, check the node is running."),
+            Some(Remedy::NodeSettings),
+        ),
+        ErrorKind::RequestError(RequestError::ContractError(contract_error)) => match contract_error {
+            ContractError::MissingContract { .. } => FriendlyError::new(
+                "That contract couldn't be found on the network.",
+                Some("The supplier may have closed their storefront, or the network hasn't found it yet. Try again in a minute."),
+                None,
+            ),
+            ContractError::Update { cause, .. } | ContractError::Put { cause, .. } => explain_rejection(&[], cause),
+            other => FriendlyError::new(format!("The network reported a problem: {other}"), None, Some(Remedy::DebugLog)),
+        },
+        _ => FriendlyError::new(format!("Your node reported an error: {err}"), None, Some(Remedy::DebugLog)),
+    }
+}
+
+impl SharedState {
+    /// Record `err` for the debug views and tell the user what it means.
+    pub fn report_error(&mut self, err: &ClientError) {
+        self.last_error = Some(format!("{err:?}"));
+        self.push_error(explain_client_error(err));
+    }
+}
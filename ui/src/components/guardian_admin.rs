@@ -1,4 +1,5 @@
-//! Guardian admin dashboard: toll rate management + Lightning gateway management.
+//! Guardian admin dashboard: toll rate management, directory moderation,
+//! supplier credentials and Lightning gateway management.
 //!
 //! Accessible to admin users (determined by guardian `--admin-pubkeys`).
 //! Toll rate editor is always shown. Lightning sections only appear when
@@ -6,6 +7,10 @@
 
 use dioxus::prelude::*;
 
+use cream_common::credential::CredentialKind;
+use cream_common::currency::{amount_input, parse_amount};
+use cream_common::identity::UserId;
+use cream_common::moderation::ModerationAction;
 use cream_common::tolls::TollRates;
 
 use super::key_manager::KeyManager;
@@ -13,6 +18,7 @@ use super::lightning_remote::{
     BalanceResponse, ChannelInfo, LightningClient, LndInfo, PegTransaction, ReconciliationReport,
 };
 use super::node_api::{use_node_action, NodeAction};
+use super::shared_state::use_shared_state;
 use super::toll_rates::AdminStatus;
 
 #[component]
@@ -28,9 +34,9 @@ pub fn GuardianAdmin() -> Element {
     // Sync input fields when toll_rates context updates (initial fetch + periodic poll)
     use_effect(move || {
         let current = toll_rates.read().clone();
-        session_toll.set(current.session_toll_curd.to_string());
+        session_toll.set(amount_input(current.session_toll_curd));
         session_interval.set(current.session_interval_secs.to_string());
-        inbox_message.set(current.inbox_message_curd.to_string());
+        inbox_message.set(amount_input(current.inbox_message_curd));
         curd_per_sat.set(current.curd_per_sat.to_string());
     });
     let mut toll_feedback = use_signal(|| None::<String>);
@@ -67,6 +73,83 @@ pub fn GuardianAdmin() -> Element {
         });
     }
 
+    // ── Moderation state ──
+    let shared_state = use_shared_state();
+    let mut mod_supplier = use_signal(String::new);
+    let mut mod_action = use_signal(|| "suspend".to_string());
+    let mut mod_days = use_signal(|| "30".to_string());
+    let mut mod_reason = use_signal(String::new);
+    let mut mod_feedback = use_signal(|| None::<String>);
+    let (mod_suppliers, mod_records) = {
+        let shared = shared_state.read();
+        let mut suppliers: Vec<(String, String)> = shared
+            .directory
+            .entries
+            .values()
+            .map(|e| (e.supplier.to_string(), e.name.clone()))
+            .collect();
+        suppliers.sort_by(|a, b| a.1.cmp(&b.1));
+        let records: Vec<(String, String, String, String)> = shared
+            .directory
+            .moderation
+            .values()
+            .map(|r| {
+                let name = shared
+                    .directory
+                    .entries
+                    .get(&r.supplier)
+                    .map(|e| e.name.clone())
+                    .unwrap_or_else(|| {
+                        let hex = r.supplier.to_string();
+                        format!("{}...", &hex[..8])
+                    });
+                let status = match &r.action {
+                    ModerationAction::Suspend { until } => {
+                        format!("Suspended until {}", until.format("%d %b %Y"))
+                    }
+                    other => other.label().to_string(),
+                };
+                (r.supplier.to_string(), name, status, r.reason.clone())
+            })
+            .collect();
+        (suppliers, records)
+    };
+    let cred_suppliers = mod_suppliers.clone();
+
+    // ── Credential state ──
+    let mut cred_supplier = use_signal(String::new);
+    let mut cred_kind = use_signal(|| "raw-milk".to_string());
+    let mut cred_reference = use_signal(String::new);
+    let mut cred_days = use_signal(|| "365".to_string());
+    let mut cred_feedback = use_signal(|| None::<String>);
+    // Credentials the federation itself has issued and not revoked
+    let cred_records: Vec<(String, UserId, CredentialKind, String, String)> = {
+        let shared = shared_state.read();
+        let federation = cream_common::identity::root_user_id();
+        shared
+            .directory
+            .credentials
+            .values()
+            .filter(|c| c.issuer == federation && !c.revoked)
+            .map(|c| {
+                let name = shared
+                    .directory
+                    .entries
+                    .get(&c.supplier)
+                    .map(|e| e.name.clone())
+                    .unwrap_or_else(|| {
+                        let hex = c.supplier.to_string();
+                        format!("{}...", &hex[..8])
+                    });
+                let expiry = c
+                    .expires_at
+                    .map(|t| t.format("%d %b %Y").to_string())
+                    .unwrap_or_else(|| "Never".into());
+                (c.id(), c.supplier.clone(), c.kind.clone(), name, expiry)
+            })
+            .collect()
+    };
+
     // ── Lightning state ──
     let mut lnd_info = use_signal(|| None::<LndInfo>);
     let mut balance = use_signal(|| None::<BalanceResponse>);
@@ -140,6 +223,7 @@ pub fn GuardianAdmin() -> Element {
                         input {
                             r#type: "number",
                             min: "0",
+                            step: "0.01",
                             value: "{session_toll}",
                             oninput: move |e| session_toll.set(e.value()),
                         }
@@ -154,10 +238,11 @@ pub fn GuardianAdmin() -> Element {
                         input {
                             r#type: "number",
                             min: "0",
+                            step: "0.01",
                             value: "{inbox_message}",
                             oninput: move |e| inbox_message.set(e.value()),
                         }
-                        label { "CURD per Sat" }
+                        label { "Curds per Sat" }
                         input {
                             r#type: "number",
                             min: "1",
@@ -169,9 +254,9 @@ pub fn GuardianAdmin() -> Element {
                         class: "btn-primary",
                         onclick: move |_| {
                             let new_rates = TollRates {
-                                session_toll_curd: session_toll.read().parse().unwrap_or(1),
+                                session_toll_curd: parse_amount(&session_toll.read()).unwrap_or(1),
                                 session_interval_secs: session_interval.read().parse().unwrap_or(10),
-                                inbox_message_curd: inbox_message.read().parse().unwrap_or(1),
+                                inbox_message_curd: parse_amount(&inbox_message.read()).unwrap_or(1),
                                 curd_per_sat: curd_per_sat.read().parse().unwrap_or(10),
                                 extra: Default::default(),
                             };
@@ -297,6 +382,209 @@ pub fn GuardianAdmin() -> Element {
                 }
             }
 
+            // ── Directory Moderation ──
+            div { class: "card",
+                h3 { "Directory Moderation" }
+                p { class: "hint",
+                    "Moderation records are threshold-signed by the guardians and honored by the directory contract."
+                }
+                if let Some(ref msg) = *mod_feedback.read() {
+                    div { class: "alert alert-success", "{msg}" }
+                }
+                div { class: "form-grid",
+                    label { "Supplier" }
+                    select {
+                        value: "{mod_supplier}",
+                        onchange: move |e| mod_supplier.set(e.value()),
+                        option { value: "", "Choose a supplier…" }
+                        for (hex, name) in mod_suppliers {
+                            option { value: "{hex}", "{name}" }
+                        }
+                    }
+                    label { "Action" }
+                    select {
+                        value: "{mod_action}",
+                        onchange: move |e| mod_action.set(e.value()),
+                        option { value: "suspend", "Suspend" }
+                        option { value: "remove", "Remove" }
+                        option { value: "reinstate", "Reinstate" }
+                    }
+                    if *mod_action.read() == "suspend" {
+                        label { "Days" }
+                        input {
+                            r#type: "number",
+                            min: "1",
+                            value: "{mod_days}",
+                            oninput: move |e| mod_days.set(e.value()),
+                        }
+                    }
+                    label { "Reason" }
+                    input {
+                        r#type: "text",
+                        value: "{mod_reason}",
+                        oninput: move |e| mod_reason.set(e.value()),
+                    }
+                }
+                button {
+                    class: "btn-danger",
+                    disabled: mod_supplier.read().is_empty() || mod_reason.read().trim().is_empty(),
+                    onclick: move |_| {
+                        let Ok(supplier) = mod_supplier.read().parse::<UserId>() else {
+                            return;
+                        };
+                        let action = match mod_action.read().as_str() {
+                            "remove" => ModerationAction::Remove,
+                            "reinstate" => ModerationAction::Reinstate,
+                            _ => {
+                                let days = mod_days.read().parse().unwrap_or(30);
+                                ModerationAction::Suspend {
+                                    until: chrono::Utc::now() + chrono::Duration::days(days),
+                                }
+                            }
+                        };
+                        let label = action.label();
+                        node_action.send(NodeAction::ModerateSupplier {
+                            supplier,
+                            action,
+                            reason: mod_reason.read().trim().to_string(),
+                        });
+                        mod_feedback.set(Some(format!("{label} — sent for guardian signing")));
+                        mod_reason.set(String::new());
+                    },
+                    "Sign & Publish"
+                }
+                if !mod_records.is_empty() {
+                    h4 { "Current records" }
+                    table {
+                        thead {
+                            tr {
+                                th { "Supplier" }
+                                th { "Status" }
+                                th { "Reason" }
+                            }
+                        }
+                        tbody {
+                            for (hex, name, status, reason) in mod_records {
+                                tr { key: "{hex}",
+                                    td { "{name}" }
+                                    td { "{status}" }
+                                    td { "{reason}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // ── Supplier Credentials ──
+            div { class: "card",
+                h3 { "Supplier Credentials" }
+                p { class: "hint",
+                    "Credentials are threshold-signed by the guardians and shown as badges on the supplier's listing."
+                }
+                if let Some(ref msg) = *cred_feedback.read() {
+                    div { class: "alert alert-success", "{msg}" }
+                }
+                div { class: "form-grid",
+                    label { "Supplier" }
+                    select {
+                        value: "{cred_supplier}",
+                        onchange: move |e| cred_supplier.set(e.value()),
+                        option { value: "", "Choose a supplier…" }
+                        for (hex, name) in cred_suppliers {
+                            option { value: "{hex}", "{name}" }
+                        }
+                    }
+                    label { "Credential" }
+                    select {
+                        value: "{cred_kind}",
+                        onchange: move |e| cred_kind.set(e.value()),
+                        option { value: "raw-milk", "Certified raw milk producer" }
+                        option { value: "organic", "Certified organic" }
+                        option { value: "food-safety", "Licensed food business" }
+                        option { value: "herd-health", "Herd health tested" }
+                    }
+                    label { "Reference" }
+                    input {
+                        r#type: "text",
+                        placeholder: "Licence or certificate number",
+                        value: "{cred_reference}",
+                        oninput: move |e| cred_reference.set(e.value()),
+                    }
+                    label { "Valid for (days)" }
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        value: "{cred_days}",
+                        oninput: move |e| cred_days.set(e.value()),
+                    }
+                }
+                button {
+                    disabled: cred_supplier.read().is_empty(),
+                    onclick: move |_| {
+                        let Ok(supplier) = cred_supplier.read().parse::<UserId>() else {
+                            return;
+                        };
+                        let kind = match cred_kind.read().as_str() {
+                            "organic" => CredentialKind::Organic,
+                            "food-safety" => CredentialKind::FoodSafetyLicence,
+                            "herd-health" => CredentialKind::HerdHealthTested,
+                            _ => CredentialKind::RawMilkProducer,
+                        };
+                        let reference = cred_reference.read().trim().to_string();
+                        let days = cred_days.read().parse().unwrap_or(365);
+                        cred_feedback.set(Some(format!("{} — sent for guardian signing", kind.label())));
+                        node_action.send(NodeAction::IssueCredential {
+                            supplier,
+                            kind,
+                            reference: (!reference.is_empty()).then_some(reference),
+                            expires_at: Some(chrono::Utc::now() + chrono::Duration::days(days)),
+                            revoked: false,
+                        });
+                        cred_reference.set(String::new());
+                    },
+                    "Sign & Publish"
+                }
+                if !cred_records.is_empty() {
+                    h4 { "Issued credentials" }
+                    table {
+                        thead {
+                            tr {
+                                th { "Supplier" }
+                                th { "Credential" }
+                                th { "Expires" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            for (id, supplier, kind, name, expiry) in cred_records {
+                                tr { key: "{id}",
+                                    td { "{name}" }
+                                    td { "{kind.label()}" }
+                                    td { "{expiry}" }
+                                    td {
+                                        button {
+                                            class: "btn-danger",
+                                            onclick: move |_| {
+                                                cred_feedback.set(Some(format!("Revoking {} — sent for guardian signing", kind.label())));
+                                                node_action.send(NodeAction::IssueCredential {
+                                                    supplier: supplier.clone(),
+                                                    kind: kind.clone(),
+                                                    reference: None,
+                                                    expires_at: None,
+                                                    revoked: true,
+                                                });
+                                            },
+                                            "Revoke"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
             // ── Lightning sections (only if gateway configured) ──
             if has_lightning {
                 if is_loading {
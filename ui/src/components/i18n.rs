@@ -0,0 +1,438 @@
+//! UI string catalog and locale-aware formatting.
+//!
+//! Every translatable string is a [`Msg`] key; each locale's table is an
+//! exhaustive `match`, so adding a key without translating it fails to
+//! compile. Components get an [`I18n`] from [`use_i18n`] and format amounts
+//! and dates through it (see [`cream_common::currency`]) rather than
+//! hard-coding en-AU conventions.
+//!
+//! The locale is part of [`super::user_state::UserState`], defaulting to the
+//! browser's (or, on desktop, the environment's) language, as is the
+//! [`Precision`] amounts are shown to.
+
+use chrono::{Datelike, Timelike};
+use dioxus::prelude::*;
+
+use cream_common::currency::{self, Curds, Locale, Precision};
+use cream_common::directory::EntryField;
+use cream_common::moderation::ModerationAction;
+
+use super::user_state::{use_user_state, Persona};
+
+/// A translatable UI string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // App header and navigation
+    Tagline,
+    Subtitle,
+    Connected,
+    Disconnected,
+    LogOut,
+    SignUp,
+    SignUpToContinue,
+    Language,
+    AmountPrecision,
+    PrecisionAuto,
+    PrecisionCurds,
+    PrecisionWhole,
+    RoleRoot,
+    RoleSupplier,
+    RoleGuest,
+    RoleUser,
+    PersonaCustomer,
+    PersonaSupplier,
+    NavStorefront,
+    NavSuppliers,
+    NavMarkets,
+    NavMyOrders,
+    NavInbox,
+    NavMyStorefront,
+    NavMyMarkets,
+    NavWallet,
+    NavAdmin,
+
+    // Directory
+    SupplierDirectory,
+    SearchSuppliers,
+    ViewList,
+    ViewMap,
+    ViewChanges,
+    NoSuppliersFound,
+    Products,
+    Away,
+    DistanceUnknown,
+    ViewStorefront,
+    ChangesFromLast,
+    PeriodDay,
+    PeriodWeek,
+    Period30Days,
+    PeriodYear,
+    NoDirectoryChanges,
+    JoinedDirectory,
+    RepublishedListing,
+    Changed,
+    ByGuardians,
+    ContractsRepointedWarning,
+    FieldName,
+    FieldDescription,
+    FieldLocation,
+    FieldCategories,
+    FieldStorefront,
+    FieldUserContract,
+    FieldInbox,
+    Removed,
+    Suspended,
+    Reinstated,
+
+    // Storefront
+    BackToProducts,
+    ContactDetails,
+    Phone,
+    Email,
+    Address,
+    ContactAfterOrder,
+    PickupLocations,
+    ByArrangement,
+    OwnStorefrontNote,
+    NoProductsAvailable,
+    Was,
+    Available,
+    FoodMiles,
+    Order,
+    RegisterToOrder,
+    PausedListing,
+    Special,
+    SpecialFrom,
+    Wholesale,
+    Open,
+    Closed,
+    Opens,
+    IssuedBy,
+    Reference,
+}
+
+impl Msg {
+    pub fn text(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::EnAu => en_au(self),
+            Locale::DeDe => de_de(self),
+        }
+    }
+}
+
+fn en_au(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Tagline => "rises to the top",
+        Msg::Subtitle => "The decentralized, private 24/7 farmer's market",
+        Msg::Connected => "Connected",
+        Msg::Disconnected => "Disconnected",
+        Msg::LogOut => "Log out",
+        Msg::SignUp => "Sign up",
+        Msg::SignUpToContinue => "You're browsing as a guest. Sign up to use this page — it only takes a moment.",
+        Msg::Language => "Language",
+        Msg::AmountPrecision => "Show amounts",
+        Msg::PrecisionAuto => "5 / 5.50 CURD",
+        Msg::PrecisionCurds => "5.00 CURD",
+        Msg::PrecisionWhole => "Whole CURD",
+        Msg::RoleRoot => "Root",
+        Msg::RoleSupplier => "Supplier",
+        Msg::RoleGuest => "Guest",
+        Msg::RoleUser => "User",
+        Msg::PersonaCustomer => "Shopping",
+        Msg::PersonaSupplier => "Selling",
+        Msg::NavStorefront => "Storefront",
+        Msg::NavSuppliers => "Suppliers",
+        Msg::NavMarkets => "Markets",
+        Msg::NavMyOrders => "My Orders",
+        Msg::NavInbox => "Inbox",
+        Msg::NavMyStorefront => "My Storefront",
+        Msg::NavMyMarkets => "My Markets",
+        Msg::NavWallet => "Wallet",
+        Msg::NavAdmin => "Admin",
+
+        Msg::SupplierDirectory => "Supplier Directory",
+        Msg::SearchSuppliers => "Search suppliers...",
+        Msg::ViewList => "List",
+        Msg::ViewMap => "Map",
+        Msg::ViewChanges => "Changes",
+        Msg::NoSuppliersFound => "No suppliers found.",
+        Msg::Products => "products",
+        Msg::Away => "away",
+        Msg::DistanceUnknown => "Distance unknown",
+        Msg::ViewStorefront => "View Storefront",
+        Msg::ChangesFromLast => "Show changes from the last",
+        Msg::PeriodDay => "day",
+        Msg::PeriodWeek => "week",
+        Msg::Period30Days => "30 days",
+        Msg::PeriodYear => "year",
+        Msg::NoDirectoryChanges => "No directory changes in this period.",
+        Msg::JoinedDirectory => "Joined the directory",
+        Msg::RepublishedListing => "Re-published listing",
+        Msg::Changed => "Changed",
+        Msg::ByGuardians => "by guardians",
+        Msg::ContractsRepointedWarning => {
+            "This supplier's listing now points at different contracts. \
+             Check with them directly before ordering."
+        }
+        Msg::FieldName => "name",
+        Msg::FieldDescription => "description",
+        Msg::FieldLocation => "location",
+        Msg::FieldCategories => "categories",
+        Msg::FieldStorefront => "storefront",
+        Msg::FieldUserContract => "user contract",
+        Msg::FieldInbox => "inbox",
+        Msg::Removed => "Removed",
+        Msg::Suspended => "Suspended",
+        Msg::Reinstated => "Reinstated",
+
+        Msg::BackToProducts => "Back to Products",
+        Msg::ContactDetails => "Contact Details",
+        Msg::Phone => "Phone",
+        Msg::Email => "Email",
+        Msg::Address => "Address",
+        Msg::ContactAfterOrder => "Contact details are shared with customers once they place an order.",
+        Msg::PickupLocations => "Pickup Locations",
+        Msg::ByArrangement => "By arrangement",
+        Msg::OwnStorefrontNote => {
+            "(This is your storefront — use the \"My Storefront\" tab to add products)"
+        }
+        Msg::NoProductsAvailable => "No products available.",
+        Msg::Was => "Was",
+        Msg::Available => "Available",
+        Msg::FoodMiles => "Food miles",
+        Msg::Order => "Order",
+        Msg::RegisterToOrder => "Register to place orders",
+        Msg::PausedListing => "Not taking orders right now",
+        Msg::Special => "Special",
+        Msg::SpecialFrom => "Special from",
+        Msg::Wholesale => "Wholesale",
+        Msg::Open => "Open",
+        Msg::Closed => "Closed",
+        Msg::Opens => "opens",
+        Msg::IssuedBy => "Issued by",
+        Msg::Reference => "ref.",
+    }
+}
+
+fn de_de(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Tagline => "setzt sich oben ab",
+        Msg::Subtitle => "Der dezentrale, private Bauernmarkt rund um die Uhr",
+        Msg::Connected => "Verbunden",
+        Msg::Disconnected => "Getrennt",
+        Msg::LogOut => "Abmelden",
+        Msg::SignUp => "Registrieren",
+        Msg::SignUpToContinue => "Du bist als Gast unterwegs. Registriere dich, um diese Seite zu nutzen — das dauert nur einen Moment.",
+        Msg::Language => "Sprache",
+        Msg::AmountPrecision => "Beträge anzeigen",
+        Msg::PrecisionAuto => "5 / 5,50 CURD",
+        Msg::PrecisionCurds => "5,00 CURD",
+        Msg::PrecisionWhole => "Ganze CURD",
+        Msg::RoleRoot => "Root",
+        Msg::RoleSupplier => "Anbieter",
+        Msg::RoleGuest => "Gast",
+        Msg::RoleUser => "Nutzer",
+        Msg::PersonaCustomer => "Einkaufen",
+        Msg::PersonaSupplier => "Verkaufen",
+        Msg::NavStorefront => "Hofladen",
+        Msg::NavSuppliers => "Anbieter",
+        Msg::NavMarkets => "Märkte",
+        Msg::NavMyOrders => "Meine Bestellungen",
+        Msg::NavInbox => "Posteingang",
+        Msg::NavMyStorefront => "Mein Hofladen",
+        Msg::NavMyMarkets => "Meine Märkte",
+        Msg::NavWallet => "Geldbörse",
+        Msg::NavAdmin => "Verwaltung",
+
+        Msg::SupplierDirectory => "Anbieterverzeichnis",
+        Msg::SearchSuppliers => "Anbieter suchen...",
+        Msg::ViewList => "Liste",
+        Msg::ViewMap => "Karte",
+        Msg::ViewChanges => "Änderungen",
+        Msg::NoSuppliersFound => "Keine Anbieter gefunden.",
+        Msg::Products => "Produkte",
+        Msg::Away => "entfernt",
+        Msg::DistanceUnknown => "Entfernung unbekannt",
+        Msg::ViewStorefront => "Zum Hofladen",
+        Msg::ChangesFromLast => "Änderungen anzeigen für den letzten Zeitraum:",
+        Msg::PeriodDay => "Tag",
+        Msg::PeriodWeek => "Woche",
+        Msg::Period30Days => "30 Tage",
+        Msg::PeriodYear => "Jahr",
+        Msg::NoDirectoryChanges => "Keine Änderungen am Verzeichnis in diesem Zeitraum.",
+        Msg::JoinedDirectory => "Ins Verzeichnis aufgenommen",
+        Msg::RepublishedListing => "Eintrag neu veröffentlicht",
+        Msg::Changed => "Geändert:",
+        Msg::ByGuardians => "durch die Guardians",
+        Msg::ContractsRepointedWarning => {
+            "Der Eintrag dieses Anbieters verweist jetzt auf andere Verträge. \
+             Bitte vor einer Bestellung direkt beim Anbieter nachfragen."
+        }
+        Msg::FieldName => "Name",
+        Msg::FieldDescription => "Beschreibung",
+        Msg::FieldLocation => "Standort",
+        Msg::FieldCategories => "Kategorien",
+        Msg::FieldStorefront => "Hofladen",
+        Msg::FieldUserContract => "Nutzervertrag",
+        Msg::FieldInbox => "Posteingang",
+        Msg::Removed => "Entfernt",
+        Msg::Suspended => "Gesperrt",
+        Msg::Reinstated => "Wieder freigegeben",
+
+        Msg::BackToProducts => "Zurück zu den Produkten",
+        Msg::ContactDetails => "Kontakt",
+        Msg::Phone => "Telefon",
+        Msg::Email => "E-Mail",
+        Msg::Address => "Adresse",
+        Msg::ContactAfterOrder => "Die Kontaktdaten sind nach einer Bestellung sichtbar.",
+        Msg::PickupLocations => "Abholorte",
+        Msg::ByArrangement => "Nach Vereinbarung",
+        Msg::OwnStorefrontNote => {
+            "(Das ist dein Hofladen — Produkte fügst du im Tab „Mein Hofladen“ hinzu)"
+        }
+        Msg::NoProductsAvailable => "Keine Produkte verfügbar.",
+        Msg::Was => "Vorher",
+        Msg::Available => "Verfügbar",
+        Msg::FoodMiles => "Transportweg",
+        Msg::Order => "Bestellen",
+        Msg::RegisterToOrder => "Zum Bestellen registrieren",
+        Msg::PausedListing => "Derzeit keine Bestellungen möglich",
+        Msg::Special => "Angebot",
+        Msg::SpecialFrom => "Angebot ab",
+        Msg::Wholesale => "Großhandel",
+        Msg::Open => "Geöffnet",
+        Msg::Closed => "Geschlossen",
+        Msg::Opens => "öffnet",
+        Msg::IssuedBy => "Ausgestellt von",
+        Msg::Reference => "Az.",
+    }
+}
+
+impl From<EntryField> for Msg {
+    fn from(field: EntryField) -> Msg {
+        match field {
+            EntryField::Name => Msg::FieldName,
+            EntryField::Description => Msg::FieldDescription,
+            EntryField::Location => Msg::FieldLocation,
+            EntryField::Categories => Msg::FieldCategories,
+            EntryField::StorefrontKey => Msg::FieldStorefront,
+            EntryField::UserContractKey => Msg::FieldUserContract,
+            EntryField::InboxContractKey => Msg::FieldInbox,
+        }
+    }
+}
+
+impl From<&ModerationAction> for Msg {
+    fn from(action: &ModerationAction) -> Msg {
+        match action {
+            ModerationAction::Remove => Msg::Removed,
+            ModerationAction::Suspend { .. } => Msg::Suspended,
+            ModerationAction::Reinstate => Msg::Reinstated,
+        }
+    }
+}
+
+impl From<Persona> for Msg {
+    fn from(persona: Persona) -> Msg {
+        match persona {
+            Persona::Customer => Msg::PersonaCustomer,
+            Persona::Supplier => Msg::PersonaSupplier,
+        }
+    }
+}
+
+impl From<Precision> for Msg {
+    fn from(precision: Precision) -> Msg {
+        match precision {
+            Precision::Auto => Msg::PrecisionAuto,
+            Precision::Curds => Msg::PrecisionCurds,
+            Precision::Whole => Msg::PrecisionWhole,
+        }
+    }
+}
+
+/// Strings and formatting for the current locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct I18n {
+    pub locale: Locale,
+    pub precision: Precision,
+}
+
+impl I18n {
+    /// Look up a catalog string.
+    pub fn t(self, msg: impl Into<Msg>) -> &'static str {
+        msg.into().text(self.locale)
+    }
+
+    pub fn amount(self, amount_curds: Curds) -> String {
+        currency::format_amount_with(amount_curds, self.locale, self.precision)
+    }
+
+    pub fn datetime<T: Datelike + Timelike>(self, at: &T) -> String {
+        currency::format_datetime(at, self.locale)
+    }
+
+    pub fn weekday_time<T: Datelike + Timelike>(self, at: &T) -> String {
+        currency::format_weekday_time(at, self.locale)
+    }
+}
+
+/// The current user's [`I18n`]; re-renders the caller when the locale changes.
+pub fn use_i18n() -> I18n {
+    let user_state = use_user_state();
+    let state = user_state.read();
+    I18n {
+        locale: state.locale,
+        precision: state.precision,
+    }
+}
+
+/// Language picker; the choice is kept in the user state.
+#[component]
+pub fn LocalePicker() -> Element {
+    let mut user_state = use_user_state();
+    let i18n = use_i18n();
+
+    rsx! {
+        select {
+            class: "locale-picker",
+            title: i18n.t(Msg::Language),
+            value: i18n.locale.tag(),
+            onchange: move |evt| {
+                if let Some(locale) = Locale::from_tag(&evt.value()) {
+                    let mut state = user_state.write();
+                    state.locale = locale;
+                    state.save();
+                }
+            },
+            for locale in Locale::ALL {
+                option { value: locale.tag(), "{locale.native_name()}" }
+            }
+        }
+    }
+}
+
+/// Amount precision picker; the choice is kept in the user state.
+#[component]
+pub fn PrecisionPicker() -> Element {
+    let mut user_state = use_user_state();
+    let i18n = use_i18n();
+
+    rsx! {
+        select {
+            class: "locale-picker",
+            title: i18n.t(Msg::AmountPrecision),
+            value: i18n.precision.tag(),
+            onchange: move |evt| {
+                if let Some(precision) = Precision::from_tag(&evt.value()) {
+                    let mut state = user_state.write();
+                    state.precision = precision;
+                    state.save();
+                }
+            },
+            for precision in Precision::ALL {
+                option { value: precision.tag(), {i18n.t(precision)} }
+            }
+        }
+    }
+}
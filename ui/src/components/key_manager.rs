@@ -1,12 +1,26 @@
+use std::collections::BTreeMap;
 use std::fmt;
 
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
 use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
 
+use cream_common::attestation::BalanceAttestation;
+use cream_common::blocklist::BlockEntry;
+use cream_common::contact::{ContactDetails, SealedContact};
 use cream_common::directory::DirectoryEntry;
 use cream_common::identity::UserId;
-use cream_common::order::Order;
+use cream_common::message::{MessageAttachment, MessageId, OrderMessage};
+use cream_common::order::{AmendmentAck, FulfillmentReceipt, Order, OrderAmendment, OrderId, PickupToken};
 use cream_common::product::Product;
-use cream_common::storefront::order_signable_bytes;
+use cream_common::provenance::{StateSection, WriterStamp};
+use cream_common::registry::NameClaim;
+use cream_common::settlement::SettlementStatement;
+use cream_common::staff::StaffGrant;
+use cream_common::storefront::{
+    amendment_ack_signable_bytes, amendment_signable_bytes, order_signable_bytes, product_signable_bytes,
+    receipt_signable_bytes,
+};
 
 /// Manages cryptographic identity derived from name + password credentials.
 ///
@@ -52,6 +66,13 @@ impl KeyManager {
         }
     }
 
+    /// Rebuild a KeyManager from raw signing key bytes (e.g. from a backup).
+    pub fn from_signing_key_bytes(bytes: &[u8; 32]) -> Self {
+        Self {
+            signing_key: SigningKey::from_bytes(bytes),
+        }
+    }
+
     pub fn user_id(&self) -> UserId {
         UserId(self.verifying_key())
     }
@@ -62,7 +83,7 @@ impl KeyManager {
 
     /// Sign a product listing.
     pub fn sign_product(&self, product: &Product) -> Signature {
-        let bytes = serde_json::to_vec(product).expect("serialization should not fail");
+        let bytes = product_signable_bytes(product);
         self.signing_key.sign(&bytes)
     }
 
@@ -73,12 +94,60 @@ impl KeyManager {
         entry.signature = self.signing_key.sign(&bytes);
     }
 
+    /// Sign a username registry claim in-place.
+    pub fn sign_name_claim(&self, claim: &mut NameClaim) {
+        let bytes = claim.signable_bytes();
+        claim.signature = self.signing_key.sign(&bytes);
+    }
+
+    /// Sign a staff grant for our storefront in-place.
+    pub fn sign_staff_grant(&self, grant: &mut StaffGrant) {
+        let bytes = grant.signable_bytes();
+        grant.signature = self.signing_key.sign(&bytes);
+    }
+
+    /// Sign a blocklist entry for our storefront in-place.
+    pub fn sign_block_entry(&self, entry: &mut BlockEntry) {
+        let bytes = entry.signable_bytes();
+        entry.signature = self.signing_key.sign(&bytes);
+    }
+
+    /// Sign a settlement statement for our user contract in-place.
+    pub fn sign_settlement_statement(&self, statement: &mut SettlementStatement) {
+        let bytes = statement.signable_bytes();
+        statement.signature = self.signing_key.sign(&bytes);
+    }
+
     /// Sign an order in-place.
     pub fn sign_order(&self, order: &mut Order) {
         let bytes = order_signable_bytes(order);
         order.signature = self.signing_key.sign(&bytes);
     }
 
+    /// Sign a balance attestation in-place.
+    pub fn sign_balance_attestation(&self, attestation: &mut BalanceAttestation) {
+        let bytes = attestation.signable_bytes();
+        attestation.signature = self.signing_key.sign(&bytes);
+    }
+
+    /// Sign a customer's amendment to `order` in-place.
+    pub fn sign_amendment(&self, order: &Order, amendment: &mut OrderAmendment) {
+        let bytes = amendment_signable_bytes(order, amendment);
+        amendment.signature = self.signing_key.sign(&bytes);
+    }
+
+    /// Answer a customer's amendment to `order` as its supplier, now.
+    pub fn amendment_ack(&self, order: &Order, amendment: &OrderAmendment, accepted: bool) -> AmendmentAck {
+        let acknowledged_at = chrono::Utc::now();
+        let msg = amendment_ack_signable_bytes(order, amendment, accepted, &acknowledged_at);
+        AmendmentAck {
+            accepted,
+            acknowledged_at,
+            signature: self.signing_key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+
     /// Sign arbitrary bytes. Returns the 64-byte signature.
     pub fn sign_raw(&self, message: &[u8]) -> [u8; 64] {
         self.signing_key.sign(message).to_bytes()
@@ -89,6 +158,12 @@ impl KeyManager {
         self.signing_key.to_bytes()
     }
 
+    /// The signing key itself, for records signed in `cream-common`
+    /// (rendezvous records on migration).
+    pub fn signing_key(&self) -> &SigningKey {
+        &self.signing_key
+    }
+
     /// Returns the public key as a hex string (for chat relay identity).
     pub fn pubkey_hex(&self) -> String {
         let vk = self.verifying_key();
@@ -99,4 +174,95 @@ impl KeyManager {
     pub fn sign_user_contract(&self, message: &[u8]) -> Signature {
         self.signing_key.sign(message)
     }
+
+    /// Sign a fulfillment receipt for `order`, proposed at `proposed_at` and
+    /// confirmed now. Customers counter-sign; suppliers only auto-confirm.
+    pub fn fulfillment_receipt(
+        &self,
+        order: &Order,
+        proposed_at: chrono::DateTime<chrono::Utc>,
+        auto_confirmed: bool,
+    ) -> FulfillmentReceipt {
+        let confirmed_at = chrono::Utc::now();
+        let msg = receipt_signable_bytes(order, &proposed_at, &confirmed_at, auto_confirmed);
+        FulfillmentReceipt {
+            proposed_at,
+            confirmed_at,
+            auto_confirmed,
+            signature: self.signing_key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+
+    /// Sign a pickup token for `order`, to show the supplier at collection.
+    pub fn pickup_token(&self, order: &Order) -> PickupToken {
+        let issued_at = chrono::DateTime::from_timestamp(chrono::Utc::now().timestamp(), 0)
+            .expect("current time is representable");
+        PickupToken {
+            order_id: order.id.clone(),
+            issued_at,
+            signature: self.signing_key.sign(&PickupToken::signable_bytes(order, &issued_at)),
+        }
+    }
+
+    /// Write and sign a message for `order_id`'s conversation thread.
+    pub fn order_message(
+        &self,
+        order_id: OrderId,
+        body: String,
+        reply_to: Option<MessageId>,
+        attachment: Option<MessageAttachment>,
+    ) -> OrderMessage {
+        let created_at = chrono::Utc::now();
+        let mut message = OrderMessage {
+            id: MessageId(format!("m-{}-{}", &self.pubkey_hex()[..8], created_at.timestamp_millis())),
+            order_id,
+            author: self.user_id(),
+            body,
+            created_at,
+            reply_to,
+            attachment,
+            signature: Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        message.signature = self.signing_key.sign(&message.signable_bytes());
+        message
+    }
+
+    /// Seal contact details for the customers of the orders in `recipients`;
+    /// see [`cream_common::contact`].
+    pub fn seal_contact(&self, details: &ContactDetails, recipients: &BTreeMap<OrderId, UserId>) -> SealedContact {
+        SealedContact::seal(details, &self.signing_key, recipients, fresh_seed())
+    }
+
+    /// Seal our own sealed contact details again for `recipients`. `None` if
+    /// they weren't sealed by this user.
+    pub fn reseal_contact(&self, sealed: &SealedContact, recipients: &BTreeMap<OrderId, UserId>) -> Option<SealedContact> {
+        sealed.reseal(&self.signing_key, recipients, fresh_seed())
+    }
+
+    /// Read `supplier`'s sealed contact details as the customer of `order`,
+    /// or as the supplier themselves with no order.
+    pub fn open_contact(&self, sealed: &SealedContact, supplier: &UserId, order: Option<&OrderId>) -> Option<ContactDetails> {
+        sealed.open(&self.signing_key, &supplier.0, order)
+    }
+
+        /// Stamp a section of contract state as last written by this user, now.
+    pub fn stamp(&self, section: StateSection, content: &[u8]) -> WriterStamp {
+        let written_at = chrono::Utc::now();
+        let msg = WriterStamp::signable_bytes(section, &written_at, content);
+        WriterStamp {
+            last_writer: self.verifying_key(),
+            written_at,
+            signature: self.signing_key.sign(&msg),
+            extra: Default::default(),
+        }
+    }
+}
+
+/// Fresh random bytes to seal contact details with.
+fn fresh_seed() -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    OsRng.fill_bytes(&mut seed);
+    seed
 }
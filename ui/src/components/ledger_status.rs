@@ -0,0 +1,137 @@
+//! Confirmation of the ledger entries this client writes.
+//!
+//! A transfer shows up in the local ledger as soon as it is made, before the
+//! network has taken the update. Each entry written to the user's own or
+//! root's contract is tracked here as [`LedgerStatus::Pending`] until a
+//! notification (or GET) of that contract carries it, which makes it
+//! [`LedgerStatus::Confirmed`]. An entry still unseen after
+//! [`CONFIRM_TIMEOUT_SECS`] is [`LedgerStatus::Failed`] and is re-submitted,
+//! up to [`MAX_SUBMISSIONS`] times in all; after that it waits for the user
+//! to retry from the wallet.
+//!
+//! Re-submitting is safe: the ledger merges as a union keyed by `tx_ref` and
+//! kind, so an entry that did land the first time isn't booked twice.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+use freenet_stdlib::prelude::ContractKey;
+
+use cream_common::wallet::{TransactionKind, WalletTransaction};
+
+use super::shared_state::SharedState;
+
+/// How long an entry may go unseen on the network before it is re-submitted.
+pub const CONFIRM_TIMEOUT_SECS: i64 = 60;
+
+/// How often pending entries are checked against the timeout.
+pub const CONFIRM_CHECK_INTERVAL_MS: u32 = 10_000;
+
+/// Submissions (the first one included) before giving up automatically.
+pub const MAX_SUBMISSIONS: u32 = 3;
+
+/// Whose ledger an entry was written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LedgerOwner {
+    /// The signed-in user's own contract.
+    Own,
+    Root,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LedgerStatus {
+    /// Sent, not yet seen on the network.
+    Pending,
+    /// Seen in a notification or GET of the contract.
+    Confirmed,
+    /// Not seen within the timeout.
+    Failed,
+}
+
+/// A ledger entry this client wrote, and what has become of it.
+#[derive(Clone, Debug)]
+pub struct TrackedEntry {
+    pub owner: LedgerOwner,
+    pub contract_key: ContractKey,
+    pub tx: WalletTransaction,
+    pub status: LedgerStatus,
+    /// When it was last submitted.
+    pub sent_at: DateTime<Utc>,
+    pub submissions: u32,
+}
+
+impl TrackedEntry {
+    /// Whether a failed entry will still be re-submitted without the user.
+    pub fn retries_left(&self) -> bool {
+        self.submissions < MAX_SUBMISSIONS
+    }
+}
+
+/// Tracked entries by owner, `tx_ref` and whether it's the credit side.
+pub type LedgerEntries = BTreeMap<(LedgerOwner, String, bool), TrackedEntry>;
+
+fn entry_key(owner: LedgerOwner, tx_ref: &str, kind: &TransactionKind) -> (LedgerOwner, String, bool) {
+    (owner, tx_ref.to_string(), *kind == TransactionKind::Credit)
+}
+
+impl SharedState {
+    /// Track `tx`, just submitted to `owner`'s contract. A re-submission of a
+    /// tracked entry goes back to pending.
+    pub fn track_ledger_entry(&mut self, owner: LedgerOwner, contract_key: ContractKey, tx: WalletTransaction) {
+        let key = entry_key(owner, &tx.tx_ref, &tx.kind);
+        let now = Utc::now();
+        match self.ledger_entries.get_mut(&key) {
+            Some(entry) => {
+                entry.status = LedgerStatus::Pending;
+                entry.sent_at = now;
+                entry.submissions += 1;
+            }
+            None => {
+                self.ledger_entries.insert(
+                    key,
+                    TrackedEntry { owner, contract_key, tx, status: LedgerStatus::Pending, sent_at: now, submissions: 1 },
+                );
+            }
+        }
+    }
+
+    /// Confirm the tracked entries of `owner` that `ledger`, as received from
+    /// the network, carries.
+    pub fn confirm_ledger_entries(&mut self, owner: LedgerOwner, ledger: &[WalletTransaction]) {
+        for tx in ledger {
+            if let Some(entry) = self.ledger_entries.get_mut(&entry_key(owner, &tx.tx_ref, &tx.kind)) {
+                entry.status = LedgerStatus::Confirmed;
+            }
+        }
+    }
+
+    /// Mark pending entries older than the timeout as failed. Returns the
+    /// failed entries that should be re-submitted now.
+    pub fn overdue_ledger_entries(&mut self, now: DateTime<Utc>) -> Vec<TrackedEntry> {
+        let timeout = Duration::seconds(CONFIRM_TIMEOUT_SECS);
+        let mut due = Vec::new();
+        for entry in self.ledger_entries.values_mut() {
+            if entry.status == LedgerStatus::Pending && now - entry.sent_at >= timeout {
+                entry.status = LedgerStatus::Failed;
+                if entry.retries_left() {
+                    due.push(entry.clone());
+                }
+            }
+        }
+        due
+    }
+
+    /// Failed entries that ran out of automatic retries, for a manual retry.
+    pub fn failed_ledger_entries(&self) -> Vec<TrackedEntry> {
+        self.ledger_entries
+            .values()
+            .filter(|entry| entry.status == LedgerStatus::Failed && !entry.retries_left())
+            .cloned()
+            .collect()
+    }
+
+    /// Status of `tx` in `owner`'s ledger, if this client wrote it.
+    pub fn ledger_entry(&self, owner: LedgerOwner, tx: &WalletTransaction) -> Option<&TrackedEntry> {
+        self.ledger_entries.get(&entry_key(owner, &tx.tx_ref, &tx.kind))
+    }
+}
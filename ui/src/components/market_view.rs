@@ -1,15 +1,20 @@
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
 use cream_common::postcode::format_postcode;
 
 use super::app::Route;
+use super::category_filter::{taxonomy_present, CategoryFilter, CategoryFilterChips};
+use super::i18n::use_i18n;
+use super::key_manager::KeyManager;
 use super::shared_state::use_shared_state;
 
 /// Market detail view — next event, accepted suppliers, aggregated products.
 #[component]
 pub fn MarketView(market_organizer: String) -> Element {
+    let i18n = use_i18n();
     let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let category_filter = use_signal(CategoryFilter::default);
 
     let shared = shared_state.read();
 
@@ -62,10 +67,15 @@ pub fn MarketView(market_organizer: String) -> Element {
         product_id: String,
         price_curd: u64,
         available: u32,
+        paused: bool,
         category: String,
     }
 
     let mut products: Vec<MarketProduct> = Vec::new();
+    let mut listed = Vec::new();
+    let filter = category_filter.read().clone();
+    // Wholesale listings are for other suppliers only
+    let sees_wholesale = key_manager.read().as_ref().is_some_and(|km| shared.is_supplier(&km.user_id()));
     for supplier_name in &accepted_names {
         if let Some(sf) = shared.storefronts.get(supplier_name) {
             // Check if supplier has a product selection for this market
@@ -77,18 +87,27 @@ pub fn MarketView(market_organizer: String) -> Element {
                         continue;
                     }
                 }
+                if sp.product.wholesale.is_some() && !sees_wholesale {
+                    continue;
+                }
+                listed.push(&sp.product);
+                if !filter.matches(&sp.product) {
+                    continue;
+                }
                 let available = sf.available_quantity(&sp.product.id);
                 products.push(MarketProduct {
                     supplier_name: supplier_name.clone(),
                     product_name: sp.product.name.clone(),
                     product_id: sp.product.id.0.clone(),
-                    price_curd: sp.product.price_curd,
+                    price_curd: sp.product.effective_price(chrono::Utc::now()),
                     available,
-                    category: format!("{:?}", sp.product.category),
+                    paused: sp.product.paused,
+                    category: sp.product.category_label(),
                 });
             }
         }
     }
+    let taxonomy = taxonomy_present(listed.into_iter());
     drop(shared);
 
     rsx! {
@@ -144,13 +163,20 @@ pub fn MarketView(market_organizer: String) -> Element {
 
             div { class: "market-products",
                 h3 { "Products Available ({products.len()})" }
+                CategoryFilterChips { present: taxonomy, filter: category_filter }
                 if products.is_empty() {
                     p { class: "empty-state", "No products currently listed by market suppliers." }
                 } else {
                     div { class: "product-grid",
                         {products.into_iter().map(|p| {
-                            let price_str = format_amount(p.price_curd);
-                            let avail_class = if p.available == 0 { "out-of-stock" } else { "" };
+                            let price_str = i18n.amount(p.price_curd);
+                            let avail_class = if p.paused {
+                                "paused"
+                            } else if p.available == 0 {
+                                "out-of-stock"
+                            } else {
+                                ""
+                            };
                             rsx! {
                                 div {
                                     class: "product-card {avail_class}",
@@ -160,13 +186,15 @@ pub fn MarketView(market_organizer: String) -> Element {
                                     p { class: "product-category", "{p.category}" }
                                     p { class: "product-price", "{price_str}" }
                                     p { class: "product-availability",
-                                        if p.available > 0 {
+                                        if p.paused {
+                                            "Not taking orders right now"
+                                        } else if p.available > 0 {
                                             "{p.available} available"
                                         } else {
                                             "Out of stock"
                                         }
                                     }
-                                    if p.available > 0 {
+                                    if p.available > 0 && !p.paused {
                                         Link {
                                             to: Route::Supplier { name: p.supplier_name.clone() },
                                             class: "order-link",
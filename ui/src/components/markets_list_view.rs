@@ -7,7 +7,7 @@ use cream_common::inbox::MessageKind;
 use super::app::Route;
 use super::node_api::{use_node_action, NodeAction};
 use super::shared_state::use_shared_state;
-use super::user_state::use_user_state;
+use super::user_state::{use_user_state, Persona};
 
 /// Lists all markets in the system that have upcoming events scheduled.
 #[component]
@@ -31,7 +31,8 @@ pub fn MarketsListView() -> Element {
     }
 
     let current_moniker = user_state.read().moniker.clone().unwrap_or_default().to_lowercase();
-    let is_supplier = user_state.read().is_supplier;
+    // Asking to take part in a market is for the supplier persona
+    let is_supplier = user_state.read().active_persona() == Persona::Supplier;
 
     let mut markets: Vec<MarketInfo> = Vec::new();
     {
@@ -9,7 +9,7 @@ use super::chat_client::{
 };
 use super::chat_view::use_peer_presence;
 use super::node_api::{use_node_action, NodeAction};
-use super::shared_state::use_shared_state;
+use super::shared_state::{use_mark_read, use_shared_state};
 use super::user_state::use_user_state;
 
 /// A recipient entry: display name + pubkey hex.
@@ -31,6 +31,7 @@ pub fn MessagesView() -> Element {
     let mut chat = use_context::<Signal<ChatState>>();
     let ws_handle = use_context::<Signal<ChatWsHandle>>();
     let node_action = use_node_action();
+    use_mark_read(|shared, markers, _, _| shared.mark_messages_read(markers));
     let mut prefill_recipient: Signal<Option<String>> = use_context();
     let mut compose_to = use_signal(String::new);
     let mut compose_body = use_signal(String::new);
@@ -0,0 +1,505 @@
+//! In-memory stand-in for a Freenet node, for working on the UI.
+//!
+//! With the `mock-node` feature, [`platform::connect`](super::platform::connect)
+//! hands out a [`MockApi`] instead of a WebSocket connection, whatever the
+//! URL, so the whole app runs with no node and no other processes:
+//!
+//! ```sh
+//! cargo make dev-mock    # dx serve --features mock-node
+//! ```
+//!
+//! Every connection shares one node, kept in a thread-local for the life of
+//! the page. It runs the real CREAM contracts, linked as plain libraries as
+//! in `tests/contract-harness` and found by the code hash of the embedded
+//! WASM: a PUT is validated and an update merged and validated, as a node
+//! would, but the answer and the notifications to subscribers go out at
+//! once. The node starts with the same demo suppliers every time (see
+//! [`DEMO_SUPPLIERS`]); their keys follow the harness convention, so
+//! signing in as "Gary" with password "gary" opens his storefront.
+//!
+//! Delegates aren't hosted: delegate requests fail, and signing falls back
+//! to the page.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, TimeZone, Utc};
+use cream_common::directory::{DirectoryEntry, DirectoryShard, DirectoryState};
+use cream_common::location::GeoLocation;
+use cream_common::product::{Product, ProductCategory, ProductId};
+use cream_common::storefront::{SignedProduct, StorefrontInfo, StorefrontParameters, StorefrontState};
+use freenet_stdlib::client_api::{
+    ClientError, ClientRequest, ContractError as RequestContractError, ContractRequest, ContractResponse,
+    ErrorKind, HostResponse, RequestError,
+};
+use freenet_stdlib::prelude::*;
+
+use super::key_manager::KeyManager;
+use super::node_api::{
+    directory_shard_contract, make_contract, EMBEDDED_CONTRACTS, STOREFRONT_CONTRACT_WASM,
+};
+use super::platform::ResponseSender;
+
+/// A demo supplier the node starts with.
+struct DemoSupplier {
+    name: &'static str,
+    storefront: &'static str,
+    description: &'static str,
+    postcode: &'static str,
+    locality: &'static str,
+    latitude: f64,
+    longitude: f64,
+    /// Name, category, price in CURD and quantity of each product.
+    products: &'static [(&'static str, ProductCategory, u64, u32)],
+}
+
+/// The suppliers every mock node starts with, the harness's three.
+const DEMO_SUPPLIERS: [DemoSupplier; 3] = [
+    DemoSupplier {
+        name: "Gary",
+        storefront: "Gary's Farm",
+        description: "Raw milk and cream from a small Jersey herd",
+        postcode: "2450",
+        locality: "Coffs Harbour",
+        latitude: -30.296,
+        longitude: 153.114,
+        products: &[
+            ("Raw milk 2L", ProductCategory::Milk, 1200, 20),
+            ("Pouring cream 500ml", ProductCategory::Cream, 900, 10),
+        ],
+    },
+    DemoSupplier {
+        name: "Emma",
+        storefront: "Emma's Dairy",
+        description: "Cultured butter and yoghurt, made weekly",
+        postcode: "2454",
+        locality: "Bellingen",
+        latitude: -30.452,
+        longitude: 152.898,
+        products: &[
+            ("Cultured butter 250g", ProductCategory::Butter, 800, 15),
+            ("Natural yoghurt 1kg", ProductCategory::Yogurt, 1000, 12),
+        ],
+    },
+    DemoSupplier {
+        name: "Iris",
+        storefront: "Iris Creamery",
+        description: "Farmhouse cheese and kefir",
+        postcode: "2456",
+        locality: "Woolgoolga",
+        latitude: -30.110,
+        longitude: 153.200,
+        products: &[
+            ("Farmhouse cheddar 500g", ProductCategory::Cheese, 1500, 8),
+            ("Milk kefir 1L", ProductCategory::Kefir, 700, 10),
+        ],
+    },
+];
+
+/// When the demo data was "written", so it is the same on every start.
+fn demo_time() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 5, 9, 0, 0).unwrap()
+}
+
+type ValidateFn =
+    fn(Parameters<'static>, State<'static>, RelatedContracts<'static>) -> Result<ValidateResult, ContractError>;
+type UpdateFn = fn(
+    Parameters<'static>,
+    State<'static>,
+    Vec<UpdateData<'static>>,
+) -> Result<UpdateModification<'static>, ContractError>;
+
+/// A contract's entry points, as the node calls them.
+#[derive(Clone, Copy)]
+struct Code {
+    validate: ValidateFn,
+    update: UpdateFn,
+}
+
+impl Code {
+    /// The entry points of the embedded contract called `name`.
+    fn named(name: &str) -> Option<Code> {
+        macro_rules! code {
+            ($contract:ty) => {
+                Code { validate: <$contract>::validate_state, update: <$contract>::update_state }
+            };
+        }
+        Some(match name {
+            "directory" => code!(cream_directory_contract::Contract),
+            "storefront" => code!(cream_storefront_contract::Contract),
+            "user" => code!(cream_user_contract::Contract),
+            "inbox" => code!(cream_inbox_contract::Contract),
+            "market_directory" => code!(cream_market_directory_contract::Contract),
+            "name_registry" => code!(cream_name_registry_contract::Contract),
+            _ => return None,
+        })
+    }
+}
+
+/// One contract instance the node holds.
+struct Stored {
+    key: ContractKey,
+    code: Code,
+    parameters: Vec<u8>,
+    state: Vec<u8>,
+}
+
+impl Stored {
+    fn validate(&self, state: &[u8]) -> Result<(), String> {
+        let result = (self.code.validate)(
+            Parameters::from(self.parameters.clone()),
+            State::from(state.to_vec()),
+            RelatedContracts::default(),
+        );
+        match result {
+            // Related contracts aren't fetched here; a node would, and they
+            // only ever confirm what the page already checked.
+            Ok(ValidateResult::Valid | ValidateResult::RequestRelated(_)) => Ok(()),
+            Ok(ValidateResult::Invalid) => Err("invalid contract state".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Merge `data` and validate the result. The state is left alone unless
+    /// both succeed; `false` if nothing changed.
+    fn apply(&mut self, data: UpdateData<'static>) -> Result<bool, String> {
+        let modification = (self.code.update)(
+            Parameters::from(self.parameters.clone()),
+            State::from(self.state.clone()),
+            vec![data],
+        )
+        .map_err(|e| e.to_string())?;
+        let Some(new_state) = modification.new_state else {
+            return Ok(false);
+        };
+        let bytes = new_state.as_ref().to_vec();
+        self.validate(&bytes)?;
+        let changed = bytes != self.state;
+        self.state = bytes;
+        Ok(changed)
+    }
+}
+
+/// A client connected to the node.
+struct Connection {
+    responses: ResponseSender,
+    subscriptions: HashSet<ContractInstanceId>,
+}
+
+#[derive(Default)]
+struct MockNode {
+    codes: HashMap<CodeHash, Code>,
+    contracts: HashMap<ContractInstanceId, Stored>,
+    connections: HashMap<u64, Connection>,
+    next_connection: u64,
+}
+
+thread_local! {
+    static NODE: RefCell<Option<MockNode>> = const { RefCell::new(None) };
+}
+
+/// Run `f` on the node, starting it on first use.
+fn with_node<R>(f: impl FnOnce(&mut MockNode) -> R) -> R {
+    NODE.with(|node| f(node.borrow_mut().get_or_insert_with(MockNode::seeded)))
+}
+
+impl MockNode {
+    /// A node holding the demo data.
+    fn seeded() -> Self {
+        let codes = EMBEDDED_CONTRACTS
+            .iter()
+            .filter_map(|(name, wasm)| {
+                let code_hash = make_contract(wasm, Parameters::from(vec![])).key().code_hash().clone();
+                Some((code_hash, Code::named(name)?))
+            })
+            .collect();
+        let mut node = MockNode { codes, ..Default::default() };
+
+        let mut shards: BTreeMap<DirectoryShard, DirectoryState> = BTreeMap::new();
+        for supplier in &DEMO_SUPPLIERS {
+            let (entry, storefront) = supplier.build();
+            let params = serde_json::to_vec(&StorefrontParameters { owner: entry.supplier.0 }).unwrap();
+            node.seed(make_contract(STOREFRONT_CONTRACT_WASM, Parameters::from(params)), &storefront);
+            if let Some(shard) = DirectoryShard::for_entry(&entry) {
+                shards.entry(shard).or_default().entries.insert(entry.supplier.clone(), entry);
+            }
+        }
+        for (shard, directory) in shards {
+            node.seed(directory_shard_contract(shard), &directory);
+        }
+        tracing::info!("Mock node started with {} demo contracts", node.contracts.len());
+        node
+    }
+
+    fn seed(&mut self, contract: ContractContainer, state: &impl serde::Serialize) {
+        let state = serde_json::to_vec(state).expect("demo state serializes");
+        if let Err(e) = self.put(&contract, state) {
+            tracing::warn!("Mock node refused demo contract {}: {e}", contract.key());
+        }
+    }
+
+    /// Store `state` for `contract`, or merge it into the copy already held.
+    /// Returns the key and whether anything changed.
+    fn put(&mut self, contract: &ContractContainer, state: Vec<u8>) -> Result<(ContractKey, bool), String> {
+        let key = contract.key();
+        if let Some(stored) = self.contracts.get_mut(key.id()) {
+            let changed = stored.apply(UpdateData::State(State::from(state)))?;
+            return Ok((key, changed));
+        }
+        let code = *self
+            .codes
+            .get(key.code_hash())
+            .ok_or_else(|| "contract code is not one of CREAM's".to_string())?;
+        let stored = Stored { key, code, parameters: contract.params().as_ref().to_vec(), state };
+        stored.validate(&stored.state)?;
+        self.contracts.insert(*key.id(), stored);
+        Ok((key, true))
+    }
+
+    fn connect(&mut self, responses: ResponseSender) -> u64 {
+        let id = self.next_connection;
+        self.next_connection += 1;
+        self.connections.insert(id, Connection { responses, subscriptions: HashSet::new() });
+        id
+    }
+
+    /// Answer `request` from `connection`, then notify subscribers if it
+    /// changed a contract. Everything is queued on the connections' channels
+    /// before this returns.
+    fn handle(&mut self, connection: u64, request: ClientRequest<'static>) {
+        let (answers, changed) = match request {
+            ClientRequest::ContractOp(request) => match self.contract_op(connection, request) {
+                Ok((answers, changed)) => (Ok(answers), changed),
+                Err(e) => (Err(e), None),
+            },
+            // Fail rather than answer `Ok`, so the delegate signer doesn't
+            // wait for a reply and signs in the page instead.
+            ClientRequest::DelegateOp(_) => {
+                (Err(ClientError::from("the mock node doesn't host delegates".to_string())), None)
+            }
+            ClientRequest::Disconnect { .. } | ClientRequest::Close => return,
+            _ => (Ok(vec![HostResponse::Ok]), None),
+        };
+        match answers {
+            Ok(answers) => answers.into_iter().for_each(|answer| self.respond(connection, Ok(answer))),
+            Err(e) => self.respond(connection, Err(e)),
+        }
+        if let Some(key) = changed {
+            self.notify(&key);
+        }
+    }
+
+    fn contract_op(
+        &mut self,
+        connection: u64,
+        request: ContractRequest<'static>,
+    ) -> Result<(Vec<HostResponse>, Option<ContractKey>), ClientError> {
+        let (answers, changed) = match request {
+            ContractRequest::Put { contract, state, subscribe, .. } => {
+                let (key, changed) = self.put(&contract, state.as_ref().to_vec()).map_err(|cause| {
+                    contract_error(RequestContractError::Put { key: contract.key(), cause: cause.into() })
+                })?;
+                let mut answers = vec![ContractResponse::PutResponse { key }];
+                if subscribe {
+                    answers.push(self.subscribe(connection, key));
+                }
+                (answers, changed.then_some(key))
+            }
+            ContractRequest::Get { key: id, subscribe, .. } => {
+                let Some(stored) = self.contracts.get(&id) else {
+                    return Ok((vec![not_found(id)], None));
+                };
+                let key = stored.key;
+                let mut answers = vec![ContractResponse::GetResponse {
+                    key,
+                    contract: None,
+                    state: WrappedState::new(stored.state.clone()),
+                }];
+                if subscribe {
+                    answers.push(self.subscribe(connection, key));
+                }
+                (answers, None)
+            }
+            ContractRequest::Update { key, data } => {
+                let Some(stored) = self.contracts.get_mut(key.id()) else {
+                    return Err(contract_error(RequestContractError::MissingContract { key: *key.id() }));
+                };
+                let changed = stored
+                    .apply(data)
+                    .map_err(|cause| contract_error(RequestContractError::Update { key, cause: cause.into() }))?;
+                let key = stored.key;
+                let answer = ContractResponse::UpdateResponse { key, summary: StateSummary::from(vec![]) };
+                (vec![answer], changed.then_some(key))
+            }
+            ContractRequest::Subscribe { key: id, .. } => match self.contracts.get(&id) {
+                Some(stored) => {
+                    let key = stored.key;
+                    (vec![self.subscribe(connection, key)], None)
+                }
+                None => return Ok((vec![not_found(id)], None)),
+            },
+            _ => return Ok((vec![HostResponse::Ok], None)),
+        };
+        Ok((answers.into_iter().map(HostResponse::ContractResponse).collect(), changed))
+    }
+
+    fn subscribe(&mut self, connection: u64, key: ContractKey) -> ContractResponse<'static> {
+        if let Some(connection) = self.connections.get_mut(&connection) {
+            connection.subscriptions.insert(*key.id());
+        }
+        ContractResponse::SubscribeResponse { key, subscribed: true }
+    }
+
+    /// Send the new state of `key` to everyone subscribed to it.
+    fn notify(&mut self, key: &ContractKey) {
+        let Some(stored) = self.contracts.get(key.id()) else { return };
+        let (key, state) = (stored.key, stored.state.clone());
+        let subscribers: Vec<u64> = self
+            .connections
+            .iter()
+            .filter(|(_, connection)| connection.subscriptions.contains(key.id()))
+            .map(|(id, _)| *id)
+            .collect();
+        for subscriber in subscribers {
+            let notification = ContractResponse::UpdateNotification {
+                key,
+                update: UpdateData::State(State::from(state.clone())),
+            };
+            self.respond(subscriber, Ok(HostResponse::ContractResponse(notification)));
+        }
+    }
+
+    /// Queue `response` for `connection`, dropping the connection if its
+    /// receiver is gone.
+    fn respond(&mut self, connection: u64, response: Result<HostResponse, ClientError>) {
+        let Some(open) = self.connections.get(&connection) else { return };
+        if open.responses.unbounded_send(response).is_err() {
+            self.connections.remove(&connection);
+        }
+    }
+}
+
+fn not_found(id: ContractInstanceId) -> HostResponse {
+    HostResponse::ContractResponse(ContractResponse::NotFound { instance_id: id })
+}
+
+fn contract_error(error: RequestContractError) -> ClientError {
+    ErrorKind::RequestError(RequestError::ContractError(error)).into()
+}
+
+impl DemoSupplier {
+    /// This supplier's signed directory entry and storefront.
+    fn build(&self) -> (DirectoryEntry, StorefrontState) {
+        let key_manager = KeyManager::from_credentials(self.name, &self.name.to_lowercase())
+            .expect("demo keys derive");
+        let supplier = key_manager.user_id();
+        let at = demo_time();
+        let location = GeoLocation::new(self.latitude, self.longitude);
+
+        let mut products = BTreeMap::new();
+        for (name, category, price_curd, quantity_total) in self.products {
+            let id = ProductId(format!(
+                "demo-{}-{}",
+                self.name.to_lowercase(),
+                name.to_lowercase().replace(' ', "-")
+            ));
+            let product = Product {
+                id: id.clone(),
+                name: name.to_string(),
+                description: format!("{name} from {}", self.storefront),
+                category: category.clone(),
+                subcategory: None,
+                attributes: Default::default(),
+                price_curd: *price_curd,
+                quantity_total: *quantity_total,
+                expiry_date: None,
+                updated_at: at,
+                created_at: at,
+                low_stock: None,
+                paused: false,
+                special: None,
+                wholesale: None,
+                extra: Default::default(),
+            };
+            let signature = key_manager.sign_product(&product);
+            products.insert(
+                id,
+                SignedProduct { product, signature, provenance: None, price_history: Vec::new(), extra: Default::default() },
+            );
+        }
+
+        let storefront = StorefrontState {
+            info: StorefrontInfo {
+                owner: supplier.clone(),
+                name: self.storefront.to_string(),
+                description: self.description.to_string(),
+                location: location.clone(),
+                schedule: None,
+                timezone: Some("Australia/Sydney".to_string()),
+                phone: None,
+                email: None,
+                address: None,
+                market_products: BTreeMap::new(),
+                delivery_zones: Vec::new(),
+                pickup_locations: Vec::new(),
+                sealed_contact: None,
+                cancellation_policy: None,
+                attestation_threshold: None,
+                provenance: None,
+                seq: 0,
+                extra: Default::default(),
+            },
+            products,
+            orders: BTreeMap::new(),
+            threads: BTreeMap::new(),
+            staff: BTreeMap::new(),
+            blocklist: BTreeMap::new(),
+            order_history: BTreeMap::new(),
+            extra: Default::default(),
+        };
+
+        let params = serde_json::to_vec(&StorefrontParameters { owner: supplier.0 }).unwrap();
+        let mut entry = DirectoryEntry {
+            supplier,
+            name: self.storefront.to_string(),
+            description: self.description.to_string(),
+            location,
+            postcode: Some(self.postcode.to_string()),
+            locality: Some(self.locality.to_string()),
+            categories: self.products.iter().map(|(_, category, ..)| category.clone()).collect(),
+            storefront_key: make_contract(STOREFRONT_CONTRACT_WASM, Parameters::from(params)).key(),
+            user_contract_key: None,
+            inbox_contract_key: None,
+            updated_at: at,
+            name_claimed_at: Some(at),
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        key_manager.sign_directory_entry(&mut entry);
+        (entry, storefront)
+    }
+}
+
+/// A connection to the in-memory node, standing in for the WebSocket API.
+pub(crate) struct MockApi {
+    connection: u64,
+}
+
+impl MockApi {
+    pub(crate) async fn send(&mut self, request: ClientRequest<'static>) -> Result<(), ClientError> {
+        with_node(|node| node.handle(self.connection, request));
+        Ok(())
+    }
+}
+
+/// Connect to the in-memory node; `url` is only logged.
+pub(crate) async fn connect(url: &str, responses: ResponseSender) -> Result<MockApi, String> {
+    let connection = with_node(|node| node.connect(responses));
+    tracing::info!("Connected to the mock node (instead of {url})");
+    Ok(MockApi { connection })
+}
+
+/// Close a connection opened with [`connect`].
+pub(crate) fn disconnect(api: MockApi) {
+    with_node(|node| node.connections.remove(&api.connection));
+}
@@ -1,28 +1,54 @@
 pub mod accordion_md;
 pub mod app;
+pub mod background_sync;
+pub mod backup;
+pub mod category_filter;
+pub mod contract_inspector_view;
+pub mod contract_router;
+pub mod debug_log;
+pub mod debug_log_view;
+pub mod delegate_signer;
+pub mod directory_loader;
 pub mod directory_view;
+pub mod error_messages;
 pub mod faq_view;
 pub mod guardian_admin;
+pub mod i18n;
 pub mod iaq_view;
 pub mod key_manager;
+pub mod ledger_status;
 pub mod market_dashboard;
 pub mod market_view;
 pub mod markets_list_view;
 pub mod my_orders;
 pub mod node_api;
+pub mod node_settings;
+pub mod onboarding_checklist;
+pub mod optimistic;
+pub mod order_filter;
 pub mod order_form;
+pub mod order_thread;
+pub mod platform;
+pub mod price_history_chart;
+pub mod propagation;
 pub mod rendezvous;
+pub mod resync;
+pub mod retry_queue;
 pub mod schedule_editor;
 pub mod shared_state;
 pub mod signing_service;
 pub mod storefront_view;
+pub mod subscriptions;
 pub mod supplier_dashboard;
+pub mod supplier_map;
+pub mod template_picker;
 pub mod user_state;
-#[cfg(target_family = "wasm")]
 pub mod wallet_native;
 pub mod wallet_fedimint;
 pub mod wallet_router;
 pub mod lightning_mock;
+#[cfg(feature = "mock-node")]
+pub mod mock_node;
 #[allow(dead_code)] // types used in WASM builds only
 pub mod lightning_remote;
 pub mod toll_rates;
@@ -1,25 +1,205 @@
+use chrono::Datelike;
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
+use cream_common::location::{food_miles_label, total_food_miles};
+use cream_common::order::{CollectionPoint, FulfillmentReceipt, OrderId, OrderStatus};
+use cream_common::postcode::lookup_postcode;
 
+use super::i18n::{use_i18n, Msg};
+use super::key_manager::KeyManager;
+use super::node_api::{use_node_action, NodeAction};
+use super::order_filter::{OrderFilter, OrderFilterBar, OrderSort};
+use super::order_thread::OrderThreadPanel;
+use super::shared_state::{use_mark_read, use_shared_state};
 use super::user_state::use_user_state;
 
+/// A supplier's fulfillment proposal awaiting this customer's receipt.
+#[derive(Clone, Debug, PartialEq)]
+struct PendingConfirmation {
+    supplier_name: String,
+    order_id: String,
+    product_name: String,
+    quantity: u32,
+    /// When the supplier may confirm on the customer's behalf.
+    auto_confirm_at: chrono::DateTime<chrono::Utc>,
+}
+
 #[component]
 pub fn MyOrders() -> Element {
+    let i18n = use_i18n();
     let user_state = use_user_state();
+    let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let send_action = use_node_action();
+    let order_filter = use_signal(OrderFilter::default);
+    let order_sort = use_signal(OrderSort::default);
+    use_mark_read(|shared, markers, me, own| shared.mark_my_orders_read(markers, me, own));
     let state = user_state.read();
     let orders = &state.orders;
 
+    // Food-miles insight: total distance travelled this month by the customer's
+    // (non-cancelled) orders, from each supplier's farm to their postcode.
+    let monthly_food_miles: Option<(f64, usize)> = {
+        let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+        let home = state.postcode.as_deref().and_then(lookup_postcode);
+        match (my_id, home) {
+            (Some(my_id), Some(home)) => {
+                let now = chrono::Utc::now();
+                let shared = shared_state.read();
+                let sources: Vec<_> = shared
+                    .storefronts
+                    .values()
+                    .flat_map(|sf| {
+                        sf.orders
+                            .values()
+                            .filter(|o| {
+                                o.customer == my_id
+                                    && o.created_at.year() == now.year()
+                                    && o.created_at.month() == now.month()
+                                    && !matches!(o.status, OrderStatus::Cancelled | OrderStatus::Expired)
+                            })
+                            .map(move |_| &sf.info.location)
+                    })
+                    .collect();
+                if sources.is_empty() {
+                    None
+                } else {
+                    Some((total_food_miles(&home, sources.iter().copied()), sources.len()))
+                }
+            }
+            _ => None,
+        }
+    };
+
+    let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+
+    let pending: Vec<PendingConfirmation> = {
+        let my_id = my_id.as_ref();
+        let shared = shared_state.read();
+        let mut pending: Vec<_> = shared
+            .storefronts
+            .iter()
+            .flat_map(|(supplier_name, sf)| {
+                sf.orders.values().filter_map(move |o| match o.status {
+                    OrderStatus::FulfillmentProposed { proposed_at } if Some(&o.customer) == my_id => {
+                        Some(PendingConfirmation {
+                            supplier_name: supplier_name.clone(),
+                            order_id: o.id.0.clone(),
+                            product_name: sf
+                                .products
+                                .get(&o.product_id)
+                                .map(|sp| sp.product.name.clone())
+                                .unwrap_or_else(|| o.product_id.0.clone()),
+                            quantity: o.quantity,
+                            auto_confirm_at: FulfillmentReceipt::auto_confirm_after(proposed_at),
+                        })
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+        pending.sort_by_key(|p| p.auto_confirm_at);
+        pending
+    };
+
+    // Network orders this customer placed, filtered and sorted:
+    // (supplier, order id, label, still to be collected, listing's new price)
+    let placed = shared_state.read().placed_order_index(my_id.as_ref(), state.own_storefront());
+    let conversations: Vec<(String, String, String, bool, Option<String>)> = {
+        let shared = shared_state.read();
+        let mut rows = placed.query(&order_filter.read(), |row| row.supplier.clone());
+        order_sort.read().sort(&mut rows);
+        rows.into_iter()
+            .filter_map(|row| {
+                let sf = shared.storefronts.get(&row.supplier)?;
+                let o = sf.orders.get(&row.id)?;
+                let label = format!("{} × {} from {} — {}", o.current_quantity(), row.product_name, row.supplier, o.status);
+                let collectable = matches!(
+                    o.status,
+                    OrderStatus::Reserved { .. } | OrderStatus::Paid | OrderStatus::FulfillmentProposed { .. }
+                );
+                let repriced = sf.price_changed_since(o).map(|now| {
+                    format!(
+                        "Price changed since your order: now {} each, yours stays at {}",
+                        i18n.amount(now),
+                        i18n.amount(o.unit_price())
+                    )
+                });
+                Some((row.supplier.clone(), o.id.0.clone(), label, collectable, repriced))
+            })
+            .collect()
+    };
+
     rsx! {
         div { class: "my-orders",
             h2 { "My Orders" }
+            if !pending.is_empty() {
+                div { class: "pending-confirmations",
+                    h3 { "Awaiting your confirmation" }
+                    for p in pending {
+                        {
+                            let deadline = p.auto_confirm_at.format("%a %d %b %H:%M").to_string();
+                            let action = NodeAction::ConfirmFulfillment {
+                                supplier_name: p.supplier_name.clone(),
+                                order_id: p.order_id.clone(),
+                            };
+                            rsx! {
+                                div { class: "order-card", key: "{p.supplier_name}-{p.order_id}",
+                                    p { "{p.supplier_name} marked {p.quantity} × {p.product_name} as handed over." }
+                                    p { class: "confirm-deadline", "Confirms automatically after {deadline} UTC." }
+                                    button {
+                                        class: "confirm-receipt-btn",
+                                        onclick: move |_| send_action.send(action.clone()),
+                                        "Confirm receipt"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if !placed.rows().is_empty() {
+                div { class: "order-conversations",
+                    h3 { "Order conversations" }
+                    OrderFilterBar {
+                        statuses: placed.status_counts(),
+                        products: placed.products(),
+                        name_label: "Supplier".to_string(),
+                        filter: order_filter,
+                        sort: order_sort,
+                        shown: conversations.len(),
+                        total: placed.rows().len(),
+                    }
+                    for (supplier_name, order_id, label, collectable, repriced) in conversations {
+                        div { class: "order-card", key: "{supplier_name}-{order_id}",
+                            p { "{label}" }
+                            StatusTimeline { supplier_name: supplier_name.clone(), order_id: order_id.clone() }
+                            if let Some(repriced) = repriced {
+                                p { class: "price-changed", "{repriced}" }
+                            }
+                            if collectable {
+                                ChangeOrder { supplier_name: supplier_name.clone(), order_id: order_id.clone() }
+                                WithdrawOrder { supplier_name: supplier_name.clone(), order_id: order_id.clone() }
+                                PickupCode { supplier_name: supplier_name.clone(), order_id: order_id.clone() }
+                                SupplierContact { supplier_name: supplier_name.clone(), order_id: order_id.clone() }
+                            }
+                            OrderThreadPanel { supplier_name, order_id }
+                        }
+                    }
+                }
+            }
+            if let Some((km, count)) = monthly_food_miles {
+                p { class: "food-miles-insight",
+                    "Your food travelled {food_miles_label(km)} this month across {count} orders."
+                }
+            }
             if orders.is_empty() {
                 p { class: "empty-state", "You haven't placed any orders yet. Browse suppliers to get started!" }
             } else {
                 div { class: "order-list",
                     {orders.iter().map(|order| {
                         let total = order.price_per_unit * order.quantity as u64;
-                        let total_str = format_amount(total);
+                        let total_str = i18n.amount(total);
                         rsx! {
                             div { class: "order-card",
                                 key: "{order.id}",
@@ -39,3 +219,306 @@ pub fn MyOrders() -> Element {
         }
     }
 }
+
+/// The supplier's private contact details, which the customer can read while
+/// the order is in progress (see [`cream_common::contact`]).
+#[component]
+fn SupplierContact(supplier_name: String, order_id: String) -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let details = shared_state.read().storefronts.get(&supplier_name).and_then(|sf| {
+        let sealed = sf.info.sealed_contact.as_ref()?;
+        key_manager
+            .read()
+            .as_ref()?
+            .open_contact(sealed, &sf.info.owner, Some(&OrderId(order_id.clone())))
+    });
+    let Some(details) = details else {
+        return rsx! {};
+    };
+
+    rsx! {
+        div { class: "contact-details",
+            h4 { {i18n.t(Msg::ContactDetails)} }
+            if let Some(phone) = details.phone {
+                p {
+                    span { class: "contact-label", "{i18n.t(Msg::Phone)}: " }
+                    a { href: "tel:{phone}", "{phone}" }
+                }
+            }
+            if let Some(email) = details.email {
+                p {
+                    span { class: "contact-label", "{i18n.t(Msg::Email)}: " }
+                    span { "{email}" }
+                }
+            }
+            if let Some(address) = details.address {
+                p {
+                    span { class: "contact-label", "{i18n.t(Msg::Address)}: " }
+                    span { "{address}" }
+                }
+            }
+        }
+    }
+}
+
+/// Every status an order has had, when, and who moved it on — the record both
+/// sides see if they disagree about what happened.
+#[component]
+fn StatusTimeline(supplier_name: String, order_id: String) -> Element {
+    let shared_state = use_shared_state();
+    let shared = shared_state.read();
+    let Some(order) = shared
+        .storefronts
+        .get(&supplier_name)
+        .and_then(|sf| sf.orders.get(&OrderId(order_id.clone())))
+    else {
+        return rsx! {};
+    };
+    let steps: Vec<(String, String, String)> = order
+        .status_history
+        .iter()
+        .map(|change| {
+            let by = match &change.actor {
+                Some(actor) if *actor == order.customer => "you".to_string(),
+                Some(_) => supplier_name.clone(),
+                None => "automatically".to_string(),
+            };
+            (change.status.to_string(), change.at.format("%a %d %b %H:%M").to_string(), by)
+        })
+        .collect();
+
+    rsx! {
+        if !steps.is_empty() {
+            ol { class: "status-timeline",
+                for (status, at, by) in steps {
+                    li { key: "{status}-{at}",
+                        span { class: "timeline-status", "{status}" }
+                        " {at} UTC, by {by}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Change an order's quantity or collection point before it's handed over.
+/// The supplier must accept the change; until they answer, it shows as
+/// pending and the order can't be changed again.
+#[component]
+fn ChangeOrder(supplier_name: String, order_id: String) -> Element {
+    let shared_state = use_shared_state();
+    let send_action = use_node_action();
+    let mut editing = use_signal(|| false);
+    let mut quantity = use_signal(String::new);
+    let mut pickup = use_signal(String::new);
+
+    let shared = shared_state.read();
+    let Some(order) = shared
+        .storefronts
+        .get(&supplier_name)
+        .and_then(|sf| sf.orders.get(&OrderId(order_id.clone())))
+    else {
+        return rsx! {};
+    };
+    let pending = order.pending_amendment().map(|a| a.quantity);
+    let declined = order
+        .amendments
+        .last()
+        .and_then(|a| a.acknowledgement.as_ref())
+        .is_some_and(|ack| !ack.accepted);
+    let can_amend = order.can_amend();
+    let current_quantity = order.current_quantity();
+    // Farm gate, or any market the supplier is listed at
+    let markets: Vec<String> = shared
+        .market_directory
+        .entries
+        .values()
+        .filter(|m| m.suppliers.contains_key(&supplier_name))
+        .map(|m| m.name.clone())
+        .collect();
+    // ... or one of the supplier's own pickup locations
+    let pickup_locations: Vec<String> = shared
+        .storefronts
+        .get(&supplier_name)
+        .map(|sf| sf.info.pickup_locations.iter().map(|l| l.name.clone()).collect())
+        .unwrap_or_default();
+    drop(shared);
+
+    let submit = move |_| {
+        let Ok(qty) = quantity.read().trim().parse::<u32>() else {
+            return;
+        };
+        let collection_point = match pickup.read().as_str() {
+            "" => None,
+            "farm-gate" => Some(CollectionPoint::FarmGate),
+            other => match other.strip_prefix("pickup:") {
+                Some(location) => Some(CollectionPoint::Pickup { location_name: location.to_string() }),
+                None => Some(CollectionPoint::Market { market_name: other.to_string() }),
+            },
+        };
+        send_action.send(NodeAction::AmendOrder {
+            supplier_name: supplier_name.clone(),
+            order_id: order_id.clone(),
+            quantity: qty,
+            collection_point,
+        });
+        editing.set(false);
+    };
+
+    rsx! {
+        if let Some(qty) = pending {
+            p { class: "order-amendment pending", "Change to {qty} awaiting the supplier's answer." }
+        } else if declined {
+            p { class: "order-amendment declined", "The supplier declined your last change." }
+        }
+        if can_amend {
+            if *editing.read() {
+                div { class: "order-amendment-form",
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        placeholder: "{current_quantity}",
+                        value: "{quantity}",
+                        oninput: move |e| quantity.set(e.value()),
+                    }
+                    select {
+                        value: "{pickup}",
+                        onchange: move |e| pickup.set(e.value()),
+                        option { value: "", "Same collection point" }
+                        option { value: "farm-gate", "Farm gate" }
+                        for market in markets {
+                            option { value: "{market}", "{market} market" }
+                        }
+                        for location in pickup_locations {
+                            option { value: "pickup:{location}", "{location}" }
+                        }
+                    }
+                    button { onclick: submit, "Request change" }
+                    button { onclick: move |_| editing.set(false), "Cancel" }
+                }
+            } else {
+                button {
+                    class: "change-order-btn",
+                    onclick: move |_| {
+                        quantity.set(current_quantity.to_string());
+                        editing.set(true);
+                    },
+                    "Change order"
+                }
+            }
+        }
+    }
+}
+
+/// Cancel an order before it's handed over. The refund follows the
+/// cancellation policy the order was placed under, so the confirmation step
+/// says what comes back (and what the supplier keeps) if cancelled now.
+#[component]
+fn WithdrawOrder(supplier_name: String, order_id: String) -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let send_action = use_node_action();
+    let mut confirming = use_signal(|| false);
+
+    let shared = shared_state.read();
+    let Some(order) = shared
+        .storefronts
+        .get(&supplier_name)
+        .and_then(|sf| sf.orders.get(&OrderId(order_id.clone())))
+    else {
+        return rsx! {};
+    };
+    if !order.status.can_transition_to(&OrderStatus::Cancelled) {
+        return rsx! {};
+    }
+    let deposit = order.escrowed_deposit();
+    let refund = order.refund_due(Some(&order.customer), chrono::Utc::now());
+    let terms = order.cancellation_policy.map(|policy| {
+        let until = policy.full_refund_until(order.created_at).format("%a %d %b %H:%M");
+        format!("{}. Free until {until} UTC.", policy.describe())
+    });
+    drop(shared);
+
+    let refund_str = i18n.amount(refund);
+    let penalty_str = i18n.amount(deposit - refund);
+
+    rsx! {
+        if *confirming.read() {
+            div { class: "order-withdraw-confirm",
+                if let Some(terms) = terms {
+                    p { class: "cancellation-policy", "{terms}" }
+                }
+                if refund < deposit {
+                    p { "Cancel now and get {refund_str} back; {supplier_name} keeps {penalty_str}." }
+                } else {
+                    p { "Cancel now and get your {refund_str} deposit back in full." }
+                }
+                button {
+                    class: "cancel-order-btn",
+                    onclick: move |_| {
+                        send_action.send(NodeAction::WithdrawOrder {
+                            supplier_name: supplier_name.clone(),
+                            order_id: order_id.clone(),
+                        });
+                        confirming.set(false);
+                    },
+                    "Cancel order"
+                }
+                button { onclick: move |_| confirming.set(false), "Keep order" }
+            }
+        } else {
+            button {
+                class: "withdraw-order-btn",
+                onclick: move |_| confirming.set(true),
+                "Cancel order…"
+            }
+        }
+    }
+}
+
+/// Signed pickup token for one order, shown as a QR code for the supplier to
+/// scan at collection. Scanning it completes the handoff: the token is this
+/// customer's receipt, so no separate confirmation is needed.
+#[component]
+fn PickupCode(supplier_name: String, order_id: String) -> Element {
+    let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let mut code = use_signal(|| None::<(String, String)>);
+
+    let show = move |_| {
+        let shared = shared_state.read();
+        let order = shared
+            .storefronts
+            .get(&supplier_name)
+            .and_then(|sf| sf.orders.get(&OrderId(order_id.clone())));
+        let (Some(order), Some(km)) = (order, key_manager.read().as_ref().cloned()) else {
+            return;
+        };
+        let token = km.pickup_token(order).encode();
+        match qrcode::QrCode::new(token.as_bytes()) {
+            Ok(qr) => {
+                let svg = qr
+                    .render::<qrcode::render::svg::Color>()
+                    .min_dimensions(200, 200)
+                    .build();
+                code.set(Some((svg, token)));
+            }
+            Err(e) => tracing::error!("Failed to render pickup QR code: {e}"),
+        }
+    };
+
+    rsx! {
+        if let Some((svg, token)) = code.read().clone() {
+            div { class: "pickup-code",
+                div { class: "pickup-qr", dangerous_inner_html: "{svg}" }
+                p { class: "hint", "Show this to the supplier when you collect. It confirms you received the order." }
+                code { class: "pickup-token", "{token}" }
+                button { onclick: move |_| code.set(None), "Hide" }
+            }
+        } else {
+            button { class: "pickup-code-btn", onclick: show, "Show pickup code" }
+        }
+    }
+}
@@ -10,6 +10,8 @@ pub enum NodeAction {
         postcode: String,
         locality: Option<String>,
         description: String,
+        /// Template to start the new storefront from (schedule, products).
+        template: Option<cream_common::template::StorefrontTemplate>,
     },
     /// Deploy a new storefront contract for this supplier.
     #[allow(dead_code)] // handled via RegisterSupplier for now
@@ -23,10 +25,17 @@ pub enum NodeAction {
     AddProduct {
         name: String,
         category: String,
+        /// Subcategory label under `category`, if one was chosen.
+        subcategory: Option<String>,
+        attributes: cream_common::product::ProductAttributes,
         description: String,
         price_curd: u64,
         quantity_total: u32,
     },
+    /// Add or replace several products in one storefront update (bulk
+    /// import; see [`cream_common::catalog::plan_import`]). Each is signed
+    /// here; products that already exist keep their price history.
+    ImportProducts { products: Vec<cream_common::product::Product> },
     /// Remove a product from the storefront.
     #[allow(dead_code)] // TODO: implement
     RemoveProduct { product_id: String },
@@ -38,6 +47,7 @@ pub enum NodeAction {
         deposit_tier: String,
         price_per_unit: u64,
         collection_point: Option<cream_common::order::CollectionPoint>,
+        delivery: Option<cream_common::delivery::DeliveryDetails>,
     },
     /// Subscribe to a specific storefront's updates.
     #[allow(dead_code)] // auto-subscribed via directory; kept for manual use
@@ -50,19 +60,105 @@ pub enum NodeAction {
     },
     /// Cancel an order on the supplier's storefront (refund deposit).
     CancelOrder { order_id: String },
-    /// Fulfill an order: transition to Fulfilled and settle escrowed deposit to supplier.
+    /// Supplier: propose fulfillment of an order. Once the customer's
+    /// confirmation window has lapsed, auto-confirm it and settle the escrowed deposit.
     FulfillOrder { order_id: String },
+    /// Supplier: [`NodeAction::FulfillOrder`] for several orders at once, in a
+    /// single storefront update; the escrows that become due settle together.
+    FulfillOrders { order_ids: Vec<String> },
+    /// Customer: counter-sign a proposed fulfillment and settle the escrowed deposit to the supplier.
+    ConfirmFulfillment { supplier_name: String, order_id: String },
+    /// Supplier: complete a handoff with the customer's scanned pickup token
+    /// (see [`cream_common::order::PickupToken`]).
+    ConfirmPickup { token: String },
+    /// Customer: ask to change an order's quantity and/or collection point
+    /// before fulfillment (see [`cream_common::order::OrderAmendment`]).
+    AmendOrder {
+        supplier_name: String,
+        order_id: String,
+        quantity: u32,
+        collection_point: Option<cream_common::order::CollectionPoint>,
+    },
+    /// Supplier: accept or decline an order's pending amendment, refunding
+    /// any deposit the customer no longer owes.
+    AnswerAmendment { order_id: String, accept: bool },
+    /// Customer: cancel one of our orders under the supplier's cancellation
+    /// policy, refunding the deposit less any late-cancellation penalty,
+    /// which goes to the supplier.
+    WithdrawOrder { supplier_name: String, order_id: String },
+    /// Post to an order's conversation thread (as its customer or the supplier).
+    PostOrderMessage {
+        supplier_name: String,
+        order_id: String,
+        body: String,
+        reply_to: Option<String>,
+        attachment: Option<cream_common::message::MessageAttachment>,
+    },
     /// Update a product's price and/or quantity on the supplier's storefront.
     UpdateProduct {
         product_id: String,
         price_curd: u64,
         quantity_total: u32,
     },
-    /// Update supplier contact details (phone, email, address).
+    /// Set a product's low-stock rule and whether it's paused (re-signing
+    /// the listing). Sent by the dashboard, and when a listing pauses itself.
+    UpdateStockControls {
+        product_id: String,
+        low_stock: Option<cream_common::product::LowStockRule>,
+        paused: bool,
+    },
+    /// Put a product on special, or with `None` take it off (re-signing the
+    /// listing).
+    UpdateSpecial {
+        product_id: String,
+        special: Option<cream_common::product::Special>,
+    },
+    /// Offer a product wholesale to other suppliers, or with `None` list it
+    /// for everyone again (re-signing the listing).
+    UpdateWholesale {
+        product_id: String,
+        wholesale: Option<cream_common::product::WholesaleTerms>,
+    },
+    /// Update supplier contact details (phone, email, address). Private
+    /// details are sealed for customers with an order in progress instead of
+    /// being shown to everyone.
     UpdateContactDetails {
         phone: Option<String>,
         email: Option<String>,
         address: Option<String>,
+        private: bool,
+    },
+    /// Replace the supplier's delivery zones.
+    UpdateDeliveryZones {
+        zones: Vec<cream_common::delivery::DeliveryZone>,
+    },
+    /// Replace the supplier's pickup locations.
+    UpdatePickupLocations {
+        locations: Vec<cream_common::pickup::PickupLocation>,
+    },
+    /// Set (or, with `None`, clear) the supplier's cancellation policy for new orders.
+    UpdateCancellationPolicy {
+        policy: Option<cream_common::order::CancellationPolicy>,
+    },
+    /// Require (or, with `None`, stop requiring) a balance attestation on
+    /// new orders priced above `threshold` CURD.
+    UpdateAttestationThreshold {
+        threshold: Option<u64>,
+    },
+    /// Grant a staff key permissions on our storefront, change them, or
+    /// (with `revoked` set) withdraw them.
+    UpdateStaff {
+        key: cream_common::identity::UserId,
+        name: String,
+        permissions: Vec<cream_common::staff::StaffPermission>,
+        revoked: bool,
+    },
+    /// Block a customer from our storefront, with a note on how to appeal,
+    /// or (with `blocked` cleared) lift the block.
+    UpdateBlocklist {
+        customer: cream_common::identity::UserId,
+        blocked: bool,
+        appeal_note: String,
     },
     /// Deploy a user contract for the current user.
     RegisterUser {
@@ -106,10 +202,41 @@ pub enum NodeAction {
         amount: u64,
         description: String,
     },
+    /// Send CURD to another user's contract. `recipient_contract_key` is the
+    /// recipient's encoded user contract key, or a registered username or
+    /// supplier name to look up.
+    SendCurd {
+        recipient_contract_key: String,
+        amount: u64,
+        description: String,
+    },
+    /// Supplier: sign a voucher worth `amount` CURD and pay its value into
+    /// root's escrow. The code is kept in `SharedState::issued_vouchers`.
+    IssueVoucher { amount: u64, note: String },
+    /// Redeem a voucher code: root pays its value into the user's contract.
+    RedeemVoucher { code: String },
     /// Update toll rates on the root user contract (admin only, FROST-signed).
     SetTollRates {
         rates: cream_common::tolls::TollRates,
     },
+    /// Threshold-sign a moderation record for a supplier and publish it to
+    /// the directory (admin only, FROST-signed).
+    ModerateSupplier {
+        supplier: cream_common::identity::UserId,
+        action: cream_common::moderation::ModerationAction,
+        reason: String,
+    },
+    /// Threshold-sign a credential for a supplier on behalf of the guardian
+    /// federation and publish it to the directory (admin only, FROST-signed).
+    /// With `revoked` set, withdraws the federation's earlier credential of
+    /// that kind.
+    IssueCredential {
+        supplier: cream_common::identity::UserId,
+        kind: cream_common::credential::CredentialKind,
+        reference: Option<String>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+        revoked: bool,
+    },
     /// Register a new market in the market directory.
     RegisterMarket {
         name: String,
@@ -160,6 +287,97 @@ pub enum NodeAction {
     },
     /// Checkpoint the user's ledger: fold old transactions into checkpoint_balance.
     CheckpointLedger,
+    /// Re-submit ledger entries the network never confirmed, after the
+    /// automatic retries gave up.
+    RetryFailedTransfers,
+    /// Supplier signs settlement statements for every `period` with escrow
+    /// settled since it was last issued, and stores them on their user contract.
+    IssueSettlementStatements { period: cream_common::settlement::SettlementPeriod },
+    /// Debug inspector: re-issue a GET (or Subscribe) for a contract instance id (Base58).
+    ReissueContractRequest { instance_id: String, subscribe: bool },
+    /// Network health panel: subscribe again to a followed contract
+    /// (instance id, Base58) now rather than waiting for the next retry.
+    Resubscribe { instance_id: String },
+    /// Supplier moving to this node: re-PUT the contract states in a
+    /// migration bundle (its JSON) and point the rendezvous service here.
+    ImportMigration { bundle: String },
+    /// Re-send a queued operation now (key from `SharedState::pending_ops`).
+    RetryOperation { key: String },
+    /// Drop a queued operation without sending it.
+    DismissOperation { key: String },
+    /// Reconnect, trying nodes in the user's current preference order.
+    SwitchNode,
+}
+
+impl NodeAction {
+    /// Variant name, recorded as the `action` field of the `node_action` span.
+    #[allow(dead_code)] // used in WASM builds only
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeAction::RegisterSupplier { .. } => "RegisterSupplier",
+            NodeAction::DeployStorefront { .. } => "DeployStorefront",
+            NodeAction::AddProduct { .. } => "AddProduct",
+            NodeAction::ImportProducts { .. } => "ImportProducts",
+            NodeAction::RemoveProduct { .. } => "RemoveProduct",
+            NodeAction::PlaceOrder { .. } => "PlaceOrder",
+            NodeAction::SubscribeStorefront { .. } => "SubscribeStorefront",
+            NodeAction::SubscribeCustomerStorefront { .. } => "SubscribeCustomerStorefront",
+            NodeAction::UpdateSchedule { .. } => "UpdateSchedule",
+            NodeAction::CancelOrder { .. } => "CancelOrder",
+            NodeAction::FulfillOrder { .. } => "FulfillOrder",
+            NodeAction::FulfillOrders { .. } => "FulfillOrders",
+            NodeAction::ConfirmFulfillment { .. } => "ConfirmFulfillment",
+            NodeAction::ConfirmPickup { .. } => "ConfirmPickup",
+            NodeAction::AmendOrder { .. } => "AmendOrder",
+            NodeAction::AnswerAmendment { .. } => "AnswerAmendment",
+            NodeAction::WithdrawOrder { .. } => "WithdrawOrder",
+            NodeAction::PostOrderMessage { .. } => "PostOrderMessage",
+            NodeAction::UpdateProduct { .. } => "UpdateProduct",
+            NodeAction::UpdateStockControls { .. } => "UpdateStockControls",
+            NodeAction::UpdateSpecial { .. } => "UpdateSpecial",
+            NodeAction::UpdateWholesale { .. } => "UpdateWholesale",
+            NodeAction::UpdateContactDetails { .. } => "UpdateContactDetails",
+            NodeAction::UpdateDeliveryZones { .. } => "UpdateDeliveryZones",
+            NodeAction::UpdatePickupLocations { .. } => "UpdatePickupLocations",
+            NodeAction::UpdateCancellationPolicy { .. } => "UpdateCancellationPolicy",
+            NodeAction::UpdateAttestationThreshold { .. } => "UpdateAttestationThreshold",
+            NodeAction::UpdateStaff { .. } => "UpdateStaff",
+            NodeAction::UpdateBlocklist { .. } => "UpdateBlocklist",
+            NodeAction::RegisterUser { .. } => "RegisterUser",
+            NodeAction::UpdateUserContract { .. } => "UpdateUserContract",
+            NodeAction::PegIn { .. } => "PegIn",
+            NodeAction::PegInAllocate { .. } => "PegInAllocate",
+            NodeAction::PegOut { .. } => "PegOut",
+            NodeAction::PegOutViaGateway { .. } => "PegOutViaGateway",
+            NodeAction::FaucetTopUp => "FaucetTopUp",
+            NodeAction::SendInboxMessage { .. } => "SendInboxMessage",
+            NodeAction::SessionToll => "SessionToll",
+            NodeAction::PeerTransfer { .. } => "PeerTransfer",
+            NodeAction::SendCurd { .. } => "SendCurd",
+            NodeAction::IssueVoucher { .. } => "IssueVoucher",
+            NodeAction::RedeemVoucher { .. } => "RedeemVoucher",
+            NodeAction::SetTollRates { .. } => "SetTollRates",
+            NodeAction::ModerateSupplier { .. } => "ModerateSupplier",
+            NodeAction::IssueCredential { .. } => "IssueCredential",
+            NodeAction::RegisterMarket { .. } => "RegisterMarket",
+            NodeAction::InviteMarketSupplier { .. } => "InviteMarketSupplier",
+            NodeAction::AcceptMarketInvite { .. } => "AcceptMarketInvite",
+            NodeAction::ConfirmMarketAcceptance { .. } => "ConfirmMarketAcceptance",
+            NodeAction::UpdateMarketEvents { .. } => "UpdateMarketEvents",
+            NodeAction::UpdateMarketDetails { .. } => "UpdateMarketDetails",
+            NodeAction::RemoveMarketSupplier { .. } => "RemoveMarketSupplier",
+            NodeAction::UpdateMarketProducts { .. } => "UpdateMarketProducts",
+            NodeAction::CheckpointLedger => "CheckpointLedger",
+            NodeAction::RetryFailedTransfers => "RetryFailedTransfers",
+            NodeAction::IssueSettlementStatements { .. } => "IssueSettlementStatements",
+            NodeAction::ReissueContractRequest { .. } => "ReissueContractRequest",
+            NodeAction::Resubscribe { .. } => "Resubscribe",
+            NodeAction::ImportMigration { .. } => "ImportMigration",
+            NodeAction::RetryOperation { .. } => "RetryOperation",
+            NodeAction::DismissOperation { .. } => "DismissOperation",
+            NodeAction::SwitchNode => "SwitchNode",
+        }
+    }
 }
 
 /// Get a handle to send actions to the node communication coroutine.
@@ -175,96 +393,137 @@ pub fn use_node_coroutine() {
     use_coroutine(|rx: UnboundedReceiver<NodeAction>| node_comms(rx));
 }
 
-// ─── WASM re-exports for wallet backend ─────────────────────────────────────
+// ─── Re-exports for wallet backend ──────────────────────────────────────────
 
-#[cfg(target_family = "wasm")]
-pub(crate) use wasm_impl::{generate_tx_ref, now_iso8601, record_transfer, ContractRole};
+pub(crate) use node_impl::{
+    check_node, generate_tx_ref, node_candidates, now_iso8601, record_transfer, record_transfers, ContractRole,
+    TransferLeg,
+};
+// Contract containers for the in-memory node (see `mock_node`).
+#[cfg(feature = "mock-node")]
+pub(crate) use node_impl::{
+    directory_shard_contract, make_contract, EMBEDDED_CONTRACTS, STOREFRONT_CONTRACT_WASM,
+};
 
-// ─── WASM implementation ────────────────────────────────────────────────────
+// ─── Node client (browser and desktop) ──────────────────────────────────────
 
-#[cfg(target_family = "wasm")]
-mod wasm_impl {
-    use std::collections::{BTreeMap, HashSet};
+mod node_impl {
+    use std::collections::BTreeMap;
     use std::sync::Arc;
 
     use dioxus::prelude::*;
     use futures::channel::mpsc;
     use futures::{SinkExt, StreamExt};
-    use wasm_bindgen::JsCast;
+    use tracing::Instrument;
 
-    use cream_common::directory::{DirectoryEntry, DirectoryState};
+    use cream_client::request;
+    use cream_common::clock::{Clock, SystemClock};
+    use cream_common::contact::{active_recipients, ContactDetails};
+    use cream_common::directory::{
+        DirectoryEntry, DirectoryShard, DirectoryShardParameters, DirectoryState,
+    };
+    use cream_common::limits::MAX_THREAD_ATTACHMENTS;
     use cream_common::location::GeoLocation;
-    use cream_common::order::{DepositTier, Order, OrderId, OrderStatus};
+    use cream_common::message::{may_post, MessageId, MAX_ATTACHMENT_BYTES, MAX_MESSAGE_BODY};
+    use cream_common::order::{
+        DepositTier, FulfillmentReceipt, Order, OrderId, OrderStatus, PickupToken, StatusChange,
+    };
     use cream_common::product::{Product, ProductCategory, ProductId};
+    use cream_common::provenance::StateSection;
+    use cream_common::settlement::{
+        compile_statements, settlement_description, statements_to_issue, INBOX_TOLL_DESCRIPTION,
+        SESSION_TOLL_DESCRIPTION, SETTLEMENT_DESCRIPTION_PREFIX,
+    };
+    use cream_common::signing_request::SigningRequest;
     use cream_common::storefront::{
-        SignedProduct, StorefrontInfo, StorefrontParameters, StorefrontState,
+        order_provenance_bytes, SignedProduct, StorefrontInfo, StorefrontParameters, StorefrontState,
     };
     use cream_common::user_contract::{UserContractParameters, UserContractState};
-    use freenet_stdlib::client_api::{
-        ClientError, ClientRequest, ContractRequest, ContractResponse, HostResponse,
-    };
+    use cream_common::voucher::Voucher;
+    use freenet_stdlib::client_api::{ClientError, ClientRequest, ContractResponse, HostResponse};
     use freenet_stdlib::prelude::*;
 
     use super::NodeAction;
+    use crate::components::contract_router::{
+        ContractRouter, DirectoryHandler, InboxHandler, MarketDirectoryHandler, NameRegistryHandler,
+        StorefrontHandler, UserContractHandler,
+    };
+    use crate::components::platform;
+    use crate::components::retry_queue::RetryingApi;
+    use crate::components::delegate_signer::{DelegateSession, KeySigner};
+    use crate::components::directory_loader;
+    use crate::components::error_messages::explain_rejection;
     use crate::components::key_manager::KeyManager;
+    use crate::components::ledger_status::{LedgerOwner, TrackedEntry, CONFIRM_CHECK_INTERVAL_MS};
+    use crate::components::node_settings::NodeHealth;
     use crate::components::shared_state::use_shared_state;
+    use crate::components::subscriptions::{Interest, RETRY_CHECK_INTERVAL_MS};
     use crate::components::wallet_native::CreamNativeWallet;
 
-    /// Sleep for the given number of milliseconds (WASM-compatible).
-    #[allow(dead_code)] // used in supplier mode heartbeat
-    async fn gloo_timers_sleep(ms: u32) {
-        let (tx, rx) = futures::channel::oneshot::channel::<()>();
-        let closure = wasm_bindgen::closure::Closure::once(move || {
-            let _ = tx.send(());
-        });
-        web_sys::window()
-            .unwrap()
-            .set_timeout_with_callback_and_timeout_and_arguments_0(
-                closure.as_ref().unchecked_ref(),
-                ms as i32,
-            )
-            .unwrap();
-        closure.forget();
-        let _ = rx.await;
-    }
-
-    /// Log a message to the browser console.
-    fn clog(msg: &str) {
-        web_sys::console::log_1(&wasm_bindgen::JsValue::from_str(msg));
+    /// Variant name of a contract response, recorded on the `contract_response` span.
+    fn response_kind(response: &ContractResponse) -> &'static str {
+        match response {
+            ContractResponse::GetResponse { .. } => "GetResponse",
+            ContractResponse::PutResponse { .. } => "PutResponse",
+            ContractResponse::UpdateNotification { .. } => "UpdateNotification",
+            ContractResponse::UpdateResponse { .. } => "UpdateResponse",
+            ContractResponse::SubscribeResponse { .. } => "SubscribeResponse",
+            ContractResponse::NotFound { .. } => "NotFound",
+            _ => "Other",
+        }
     }
 
-    /// Generate a random u32 using JS Math.random().
-    fn rand_u32() -> u32 {
-        (web_sys::js_sys::Math::random() * u32::MAX as f64) as u32
-    }
+    /// Retry policy for inbox message sends: 2 attempts, ~2s apart.
+    const INBOX_SEND_RETRY: cream_common::retry::RetryPolicy = cream_common::retry::RetryPolicy {
+        max_attempts: Some(2),
+        initial_delay: std::time::Duration::from_millis(2000),
+        max_delay: std::time::Duration::from_millis(2000),
+        multiplier: 1,
+        jitter: 0.2,
+    };
 
     /// Embedded directory contract WASM (built with `cargo make build-contracts-dev`).
-    const DIRECTORY_CONTRACT_WASM: &[u8] = include_bytes!(
+    pub(crate) const DIRECTORY_CONTRACT_WASM: &[u8] = include_bytes!(
         "../../../target/wasm32-unknown-unknown/release/cream_directory_contract.wasm"
     );
 
     /// Embedded storefront contract WASM (built with `cargo make build-contracts-dev`).
-    const STOREFRONT_CONTRACT_WASM: &[u8] = include_bytes!(
+    pub(crate) const STOREFRONT_CONTRACT_WASM: &[u8] = include_bytes!(
         "../../../target/wasm32-unknown-unknown/release/cream_storefront_contract.wasm"
     );
 
     /// Embedded user contract WASM (built with `cargo make build-contracts-dev`).
-    const USER_CONTRACT_WASM: &[u8] = include_bytes!(
+    pub(crate) const USER_CONTRACT_WASM: &[u8] = include_bytes!(
         "../../../target/wasm32-unknown-unknown/release/cream_user_contract.wasm"
     );
 
     /// Embedded inbox contract WASM (built with `cargo make build-contracts-dev`).
-    const INBOX_CONTRACT_WASM: &[u8] = include_bytes!(
+    pub(crate) const INBOX_CONTRACT_WASM: &[u8] = include_bytes!(
         "../../../target/wasm32-unknown-unknown/release/cream_inbox_contract.wasm"
     );
 
     /// Embedded market directory contract WASM (built with `cargo make build-contracts-dev`).
-    const MARKET_DIRECTORY_CONTRACT_WASM: &[u8] = include_bytes!(
+    pub(crate) const MARKET_DIRECTORY_CONTRACT_WASM: &[u8] = include_bytes!(
         "../../../target/wasm32-unknown-unknown/release/cream_market_directory_contract.wasm"
     );
 
+    /// Embedded username registry contract WASM (built with `cargo make build-contracts-dev`).
+    pub(crate) const NAME_REGISTRY_CONTRACT_WASM: &[u8] = include_bytes!(
+        "../../../target/wasm32-unknown-unknown/release/cream_name_registry_contract.wasm"
+    );
+
+    /// Every embedded contract, by its name in the artifact manifest.
+    pub(crate) const EMBEDDED_CONTRACTS: [(&str, &[u8]); 6] = [
+        ("directory", DIRECTORY_CONTRACT_WASM),
+        ("storefront", STOREFRONT_CONTRACT_WASM),
+        ("user", USER_CONTRACT_WASM),
+        ("inbox", INBOX_CONTRACT_WASM),
+        ("market_directory", MARKET_DIRECTORY_CONTRACT_WASM),
+        ("name_registry", NAME_REGISTRY_CONTRACT_WASM),
+    ];
+
     /// Build a ContractContainer from raw WASM bytes and parameters.
-    fn make_contract(
+    pub(crate) fn make_contract(
         wasm_bytes: &[u8],
         params: Parameters<'static>,
     ) -> ContractContainer {
@@ -273,196 +532,291 @@ mod wasm_impl {
         ContractContainer::Wasm(ContractWasmAPIVersion::V1(wrapped))
     }
 
-    /// Main node communication loop.
-    pub async fn node_comms(mut rx: UnboundedReceiver<NodeAction>) {
-        let mut shared = use_shared_state();
-        let key_manager_signal: Signal<Option<KeyManager>> = use_context();
-        let user_state: Signal<crate::components::user_state::UserState> = use_context();
-        let toll_rates: Signal<cream_common::tolls::TollRates> = use_context();
+    /// Check every embedded contract against the artifact manifest, through
+    /// the code hash in the key each one derives. A build without a manifest
+    /// is only warned about; stale WASM is an error, since its contracts
+    /// would live under keys nobody else uses.
+    fn check_embedded_contracts() -> Result<(), cream_common::artifacts::ArtifactError> {
+        use cream_common::artifacts::{check_code_hash, ArtifactError};
+        for (name, wasm) in EMBEDDED_CONTRACTS {
+            let key = make_contract(wasm, Parameters::from(vec![])).key();
+            match check_code_hash(name, key.code_hash().as_ref()) {
+                Ok(()) => {}
+                Err(ArtifactError::NoManifest) => {
+                    tracing::warn!("{}", ArtifactError::NoManifest);
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
 
-        // ── Connect to node via WebSocket ───────────────────────────────
-        // Default node URL; overridable at compile-time via CREAM_NODE_URL env var,
-        // or at runtime via ?node=<port> query parameter (e.g. ?node=3003).
-        // In customer mode, the supplier's node URL from UserState takes priority.
-        const DEFAULT_NODE_URL: &str =
-            "ws://localhost:3001/v1/contract/command?encodingProtocol=native";
-        let compile_time_url = option_env!("CREAM_NODE_URL").unwrap_or(DEFAULT_NODE_URL);
-
-        let node_url = {
-            // Customer mode: use supplier node URL from UserState if available
-            let customer_url = user_state.read().supplier_node_url.clone();
-
-            if let Some(url) = customer_url {
-                url
-            } else {
-                let url = web_sys::window()
-                    .and_then(|w| w.location().search().ok())
-                    .and_then(|qs| {
-                        web_sys::UrlSearchParams::new_with_str(&qs)
-                            .ok()?
-                            .get("node")
-                    })
-                    .map(|port| {
-                        format!(
-                            "ws://localhost:{port}/v1/contract/command?encodingProtocol=native"
-                        )
-                    });
-                match url {
-                    Some(u) => u,
-                    None => compile_time_url.to_string(),
+    /// The (singleton) market directory contract.
+    fn market_directory_contract() -> ContractContainer {
+        make_contract(MARKET_DIRECTORY_CONTRACT_WASM, Parameters::from(vec![]))
+    }
+
+    /// The (singleton) username registry contract.
+    fn name_registry_contract() -> ContractContainer {
+        make_contract(NAME_REGISTRY_CONTRACT_WASM, Parameters::from(vec![]))
+    }
+
+    /// Directory shard contract for `shard` (deterministic parameters).
+    pub(crate) fn directory_shard_contract(shard: DirectoryShard) -> ContractContainer {
+        make_contract(
+            DIRECTORY_CONTRACT_WASM,
+            Parameters::from(DirectoryShardParameters::new(shard).to_bytes()),
+        )
+    }
+
+    /// Follow the directory shards near `postcode` (all of them if
+    /// unknown). A shard nobody has created yet comes back NotFound and is
+    /// PUT empty at that point.
+    async fn follow_directory_shards(
+        api: &mut RetryingApi,
+        router: &mut ContractRouter,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        postcode: Option<&str>,
+    ) {
+        for shard in DirectoryShard::nearby(postcode) {
+            let key = directory_shard_contract(shard).key();
+            tracing::info!("Following directory shard {}: {:?}", shard.label(), key);
+            shared.write().directory_contract_keys.push(format!("{}", key));
+
+            let handler = DirectoryHandler { shard, contract: directory_shard_contract };
+            let requests = router.follow(*key.id(), Interest::DirectoryShard(shard), handler);
+            for request in requests {
+                if let Err(e) = api.send(request).await {
+                    tracing::error!("Failed to follow directory shard {}: {:?}", shard.label(), e);
+                    shared.write().last_error =
+                        Some(format!("Failed to get directory contract: {:?}", e));
                 }
             }
-        };
+        }
+    }
 
-        let conn = match web_sys::WebSocket::new(&node_url) {
-            Ok(c) => c,
-            Err(e) => {
-                shared.write().last_error =
-                    Some(format!("WebSocket connection failed: {:?}", e));
-                return;
+    /// Send requests that follow `what`, logging failures (the retry queue
+    /// has them).
+    async fn send_follow(api: &mut RetryingApi, requests: Vec<ClientRequest<'static>>, what: &str) {
+        for request in requests {
+            if let Err(e) = api.send(request).await {
+                tracing::error!(error = ?e, "Failed to follow {what}");
             }
+        }
+    }
+
+    /// Backoff between attempts to reconnect to the node: 1s doubling to at
+    /// most 30s, for as long as it takes.
+    const RECONNECT: cream_common::retry::RetryPolicy = cream_common::retry::RetryPolicy {
+        max_attempts: None,
+        initial_delay: std::time::Duration::from_secs(1),
+        max_delay: std::time::Duration::from_secs(30),
+        multiplier: 2,
+        jitter: 0.2,
+    };
+
+    /// Default node URL; overridable at compile-time via CREAM_NODE_URL env var,
+    /// or at runtime via ?node=<port> query parameter (e.g. ?node=3003).
+    const DEFAULT_NODE_URL: &str =
+        "ws://localhost:3001/v1/contract/command?encodingProtocol=native";
+
+    /// Node URLs to try, in order. In customer mode the supplier's node comes
+    /// first; then the nodes the user added in settings, most preferred
+    /// first; then the runtime override or compile-time default.
+    pub(crate) fn node_candidates(user_state: &crate::components::user_state::UserState) -> Vec<String> {
+        let fallback = match platform::node_port_override() {
+            Some(port) => format!("ws://localhost:{port}/v1/contract/command?encodingProtocol=native"),
+            None => option_env!("CREAM_NODE_URL").unwrap_or(DEFAULT_NODE_URL).to_string(),
         };
+        let mut urls: Vec<String> = Vec::new();
+        let ordered = user_state
+            .supplier_node_url
+            .iter()
+            .chain(user_state.node_urls.iter())
+            .chain(std::iter::once(&fallback));
+        for url in ordered {
+            if !urls.contains(url) {
+                urls.push(url.clone());
+            }
+        }
+        urls
+    }
 
-        let (send_responses, mut host_responses) = mpsc::unbounded();
-        let (send_half, mut requests) = mpsc::unbounded::<ClientRequest<'static>>();
+    /// Connect to the first node in `candidates` that answers. Returns its
+    /// URL with the connection, or the last error if none did.
+    async fn connect_first(
+        candidates: &[String],
+        responses: platform::ResponseSender,
+    ) -> Result<(String, platform::NodeApi), String> {
+        let mut last_error = "No node URLs configured".to_string();
+        for url in candidates {
+            match platform::connect(url, responses.clone()).await {
+                Ok(api) => return Ok((url.clone(), api)),
+                Err(e) => {
+                    tracing::warn!("Could not connect to {url}: {e}");
+                    last_error = e;
+                }
+            }
+        }
+        Err(last_error)
+    }
 
-        let result_handler = {
-            let sender = send_responses.clone();
-            move |result: Result<HostResponse, ClientError>| {
-                let mut sender = sender.clone();
-                let _ = wasm_bindgen_futures::future_to_promise(async move {
-                    sender.send(result).await.expect("channel open");
-                    Ok(wasm_bindgen::JsValue::NULL)
-                });
+    /// Connect to one of `candidates` again, backing off between rounds in
+    /// which none answered. Returns the URL connected to, the new connection
+    /// and its response stream.
+    async fn reconnect(
+        candidates: &[String],
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+    ) -> (String, platform::NodeApi, mpsc::UnboundedReceiver<Result<HostResponse, ClientError>>) {
+        let mut backoff = RECONNECT.backoff(platform::rand_u32() as u64);
+        loop {
+            let delay = backoff.next_delay().unwrap_or(RECONNECT.max_delay);
+            platform::sleep(delay).await;
+            let (send_responses, host_responses) = mpsc::unbounded();
+            match connect_first(candidates, send_responses).await {
+                Ok((url, api)) => return (url, api, host_responses),
+                Err(e) => {
+                    tracing::warn!("Reconnect attempt {} failed: {e}", backoff.retries());
+                    shared.write().last_error = Some(e);
+                }
             }
-        };
+        }
+    }
+
+    /// How long a health check waits for the node to answer a directory GET.
+    const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
-        let (tx_connected, rx_connected) = futures::channel::oneshot::channel();
-        let onopen_handler = move || {
-            let _ = tx_connected.send(());
-            tracing::info!("Connected to Freenet node");
+    /// Probe the node at `url` on a connection of its own: how long the
+    /// WebSocket takes to open, then how long a GET of the home directory
+    /// shard takes to answer (NotFound counts as an answer).
+    pub(crate) async fn check_node(url: &str, postcode: Option<&str>) -> NodeHealth {
+        let started = chrono::Utc::now();
+        let elapsed_ms = |since: chrono::DateTime<chrono::Utc>| {
+            (chrono::Utc::now() - since).num_milliseconds().max(0) as u64
         };
+        let (send_responses, mut responses) = mpsc::unbounded();
+        let mut api = match platform::connect(url, send_responses).await {
+            Ok(api) => api,
+            Err(e) => return NodeHealth::failed(e),
+        };
+        let ping_ms = elapsed_ms(started);
 
-        let mut api = freenet_stdlib::client_api::WebApi::start(
-            conn,
-            result_handler,
-            |err| {
-                tracing::error!("Node error: {err}");
+        let shard = DirectoryShard::nearby(postcode)[0];
+        let get = request::get(*directory_shard_contract(shard).key().id(), false);
+        let asked = chrono::Utc::now();
+        if let Err(e) = api.send(get).await {
+            return NodeHealth { ping_ms: Some(ping_ms), ..NodeHealth::failed(format!("{e:?}")) };
+        }
+        let timeout = Box::pin(platform::sleep(HEALTH_CHECK_TIMEOUT));
+        let answer = match futures::future::select(responses.next(), timeout).await {
+            futures::future::Either::Left((response, _)) => response,
+            futures::future::Either::Right(_) => None,
+        };
+        platform::disconnect(api);
+        match answer {
+            Some(Ok(_)) => NodeHealth {
+                ping_ms: Some(ping_ms),
+                directory_ms: Some(elapsed_ms(asked)),
+                error: None,
+            },
+            Some(Err(e)) => NodeHealth { ping_ms: Some(ping_ms), ..NodeHealth::failed(format!("{e}")) },
+            None => NodeHealth {
+                ping_ms: Some(ping_ms),
+                ..NodeHealth::failed("Directory GET timed out".to_string())
             },
-            onopen_handler,
-        );
-
-        // Wait for connection
-        if rx_connected.await.is_err() {
-            shared.write().last_error = Some("WebSocket connection dropped".into());
-            return;
         }
-        shared.write().connected = true;
-        clog("[CREAM] Connected to Freenet node");
+    }
 
-        // ── Set up contracts ─────────────────────────────────────────
-        let directory_contract =
-            make_contract(DIRECTORY_CONTRACT_WASM, Parameters::from(vec![]));
-        let directory_key = directory_contract.key();
+    /// Main node communication loop.
+    pub async fn node_comms(mut rx: UnboundedReceiver<NodeAction>) {
+        let mut shared = use_shared_state();
+        let key_manager_signal: Signal<Option<KeyManager>> = use_context();
+        let user_state: Signal<crate::components::user_state::UserState> = use_context();
+        let toll_rates: Signal<cream_common::tolls::TollRates> = use_context();
 
-        let is_customer = user_state.read().connected_supplier.is_some();
+        // ── Connect to node via WebSocket ───────────────────────────────
+        // Try each candidate node in preference order. Reconnects walk the
+        // list again as currently configured, so a dead node fails over to
+        // the next.
+        let candidates = node_candidates(&user_state.read());
+
+        if let Err(e) = check_embedded_contracts() {
+            tracing::error!(error = %e, "Embedded contracts failed their check");
+            shared.write().last_error = Some(e.to_string());
+            return;
+        }
 
-        // In customer mode, we use a dummy directory instance ID that will never
-        // match any response, since we skip directory operations entirely.
-        let directory_instance_id = if is_customer {
-            // Customer mode: storefront subscription is triggered by
-            // SubscribeCustomerStorefront action after setup completes.
-            // Use a zeroed-out dummy so no response matches the directory branch.
-            ContractInstanceId::new([0u8; 32])
-        } else {
-            let id = *directory_key.id();
-
-            tracing::info!("Directory contract key: {:?}", directory_key);
-            shared.write().directory_contract_key =
-                Some(format!("{}", directory_key));
-
-            // Try to GET the existing directory.
-            // If it doesn't exist yet, we'll PUT it when we get NotFound.
-            let get_request = ClientRequest::ContractOp(ContractRequest::Get {
-                key: id,
-                return_contract_code: false,
-                subscribe: false,
-                blocking_subscribe: false,
-            });
+        let (send_responses, mut host_responses) = mpsc::unbounded();
+        let (send_half, mut requests) = mpsc::unbounded::<ClientRequest<'static>>();
 
-            tracing::info!("Getting directory contract (will PUT if not found)...");
-            if let Err(e) = api.send(get_request).await {
-                tracing::error!("Failed to GET directory contract: {:?}", e);
-                shared.write().last_error =
-                    Some(format!("Failed to get directory contract: {:?}", e));
+        let (send_retry_tick, mut retry_ticks) = mpsc::unbounded();
+        let (mut node_url, mut api) = match connect_first(&candidates, send_responses).await {
+            Ok((url, api)) => (url, RetryingApi::new(api, shared, send_retry_tick)),
+            Err(e) => {
+                shared.write().last_error = Some(e);
+                return;
             }
+        };
+        shared.write().node_url = Some(node_url.clone());
+        shared.write().connected = true;
+        tracing::info!("Connected to Freenet node");
 
-            // Explicitly subscribe to directory updates
-            let subscribe_dir = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: id,
-                summary: None,
-            });
-            tracing::info!("Subscribing to directory contract...");
-            if let Err(e) = api.send(subscribe_dir).await {
-                tracing::error!("Failed to subscribe to directory: {:?}", e);
-            }
+        // ── Set up directory shards ──────────────────────────────────
+        let is_customer = user_state.read().connected_supplier.is_some();
 
-            id
-        };
+        // Responses are routed by contract instance id; anything unregistered
+        // is a storefront discovered through the directory.
+        let (directory_loader, mut directory_chunks) = directory_loader::channel();
+        let mut router = ContractRouter::new(StorefrontHandler, directory_loader);
+
+        // The directory is sharded by state. Follow the shards for our saved
+        // region and its neighbours. Before a postcode is known (first run) the
+        // setup screen needs the whole directory to recognise returning
+        // suppliers, so every shard is followed for that session.
+        // In customer mode we skip directory operations entirely, so no
+        // response ever matches the directory branch.
+        if !is_customer {
+            let postcode = user_state.read().postcode.clone();
+            follow_directory_shards(&mut api, &mut router, &mut shared, postcode.as_deref()).await;
+        }
 
         // ── Set up market directory contract ───────────────────────────
-        let market_directory_contract =
-            make_contract(MARKET_DIRECTORY_CONTRACT_WASM, Parameters::from(vec![]));
-        let market_directory_key = market_directory_contract.key();
-
-        let market_directory_instance_id = if is_customer {
-            ContractInstanceId::new([0u8; 32])
-        } else {
-            let id = *market_directory_key.id();
+        let market_directory_key = market_directory_contract().key();
 
+        if !is_customer {
             tracing::info!("Market directory contract key: {:?}", market_directory_key);
             shared.write().market_directory_key =
                 Some(format!("{}", market_directory_key));
 
-            let get_request = ClientRequest::ContractOp(ContractRequest::Get {
-                key: id,
-                return_contract_code: false,
-                subscribe: false,
-                blocking_subscribe: false,
-            });
-            if let Err(e) = api.send(get_request).await {
-                tracing::error!("Failed to GET market directory contract: {:?}", e);
-            }
+            let requests = router.follow(
+                *market_directory_key.id(),
+                Interest::MarketDirectory,
+                MarketDirectoryHandler { contract: market_directory_contract },
+            );
+            send_follow(&mut api, requests, "market directory").await;
+        }
 
-            let subscribe_mkt = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: id,
-                summary: None,
-            });
-            if let Err(e) = api.send(subscribe_mkt).await {
-                tracing::error!("Failed to subscribe to market directory: {:?}", e);
-            }
+        // ── Set up username registry contract ──────────────────────────
+        let name_registry_key = name_registry_contract().key();
 
-            id
-        };
+        if !is_customer {
+            tracing::info!("Name registry contract key: {:?}", name_registry_key);
+            shared.write().name_registry_key = Some(format!("{}", name_registry_key));
+
+            let requests = router.follow(
+                *name_registry_key.id(),
+                Interest::NameRegistry,
+                NameRegistryHandler { contract: name_registry_contract },
+            );
+            send_follow(&mut api, requests, "name registry").await;
+        }
 
         // Local map of supplier name -> ContractKey for storefront updates
         let mut sf_contract_keys: BTreeMap<String, ContractKey> = BTreeMap::new();
 
-        // Track which storefronts we've already subscribed to
-        let mut subscribed_storefronts: HashSet<ContractInstanceId> = HashSet::new();
-
-        // Forward lookup: ContractInstanceId → directory entry name.
-        // Populated when we subscribe to storefronts so we can correctly
-        // key incoming GET/Update responses by the directory name (e.g. "Gary")
-        // rather than the storefront's own info.name (e.g. "Gary's Farm").
-        let mut instance_to_name: std::collections::HashMap<ContractInstanceId, String> = std::collections::HashMap::new();
-
-        // Track the user's own user contract instance ID for response routing.
-        let mut user_contract_instance_id: Option<ContractInstanceId> = None;
         // Track the user contract key for updates.
         let mut user_contract_key: Option<ContractKey> = None;
-        // Track the user's inbox contract instance ID for response routing.
-        let mut inbox_contract_instance_id: Option<ContractInstanceId> = None;
         // Track the inbox contract key for updates.
         let mut inbox_contract_key: Option<ContractKey> = None;
 
@@ -471,24 +825,10 @@ mod wasm_impl {
             let saved_key = user_state.read().user_contract_key.clone();
             if let Some(key_str) = saved_key {
                 if let Ok(instance_id) = ContractInstanceId::from_bytes(&key_str) {
-                    clog(&format!("[CREAM] Restoring user contract subscription: {}", key_str));
-                    user_contract_instance_id = Some(instance_id);
-                    let get_req = ClientRequest::ContractOp(ContractRequest::Get {
-                        key: instance_id,
-                        return_contract_code: false,
-                        subscribe: false,
-                        blocking_subscribe: false,
-                    });
-                    if let Err(e) = api.send(get_req).await {
-                        clog(&format!("[CREAM] ERROR: Failed to GET user contract: {:?}", e));
-                    }
-                    let sub_req = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                        key: instance_id,
-                        summary: None,
-                    });
-                    if let Err(e) = api.send(sub_req).await {
-                        clog(&format!("[CREAM] ERROR: Failed to subscribe to user contract: {:?}", e));
-                    }
+                    tracing::info!(key = %key_str, "Restoring user contract subscription");
+                    let requests =
+                        router.follow(instance_id, Interest::OwnUserContract, UserContractHandler::Own);
+                    send_follow(&mut api, requests, "user contract").await;
                 }
             }
         }
@@ -506,8 +846,9 @@ mod wasm_impl {
                 let ib_key = inbox_container.key();
                 let ib_instance_id = *ib_key.id();
 
-                clog(&format!("[CREAM] Inbox contract key: {}", ib_key));
-                inbox_contract_instance_id = Some(ib_instance_id);
+                tracing::info!(key = %ib_key, "Inbox contract key");
+                router.register(ib_instance_id, InboxHandler);
+                router.subscriptions().adopt(ib_instance_id, Interest::Inbox);
                 inbox_contract_key = Some(ib_key);
 
                 let ib_state = cream_common::inbox::InboxState {
@@ -519,15 +860,9 @@ mod wasm_impl {
                 let ib_state_bytes = serde_json::to_vec(&ib_state).unwrap();
 
                 // PUT with subscribe=true ensures the contract exists AND we're subscribed
-                let put_inbox = ClientRequest::ContractOp(ContractRequest::Put {
-                    contract: inbox_container,
-                    state: WrappedState::new(ib_state_bytes.clone()),
-                    related_contracts: RelatedContracts::default(),
-                    subscribe: true,
-                    blocking_subscribe: false,
-                });
+                let put_inbox = request::put(inbox_container, ib_state_bytes.clone(), true);
                 if let Err(e) = api.send(put_inbox).await {
-                    clog(&format!("[CREAM] WARNING: Failed to PUT inbox contract: {:?}", e));
+                    tracing::warn!(error = ?e, "Failed to PUT inbox contract");
                 }
 
                 {
@@ -541,89 +876,144 @@ mod wasm_impl {
         // ── Subscribe to root user contract ─────────────────────────────
         // Root's identity is deterministic, so we can derive its contract key.
         let root_vk = cream_common::identity::root_user_id().0;
-        let root_params = UserContractParameters { owner: root_vk };
+        let root_params = UserContractParameters { owner: root_vk, spending_key: None };
         let root_params_bytes = serde_json::to_vec(&root_params).unwrap();
         let root_contract_container = make_contract(USER_CONTRACT_WASM, Parameters::from(root_params_bytes));
         let root_contract_full_key: ContractKey = root_contract_container.key();
-        let root_contract_instance_id: Option<ContractInstanceId> = {
+        {
             let root_key_str = format!("{}", root_contract_full_key);
             let root_instance = *root_contract_full_key.id();
 
-            clog(&format!("[CREAM] Root contract key: {}", root_key_str));
+            tracing::info!(key = %root_key_str, "Root contract key");
             shared.write().root_contract_key = Some(root_key_str);
 
-            // GET + subscribe to root contract
-            let get_root = ClientRequest::ContractOp(ContractRequest::Get {
-                key: root_instance,
-                return_contract_code: false,
-                subscribe: false,
-                blocking_subscribe: false,
-            });
-            if let Err(e) = api.send(get_root).await {
-                clog(&format!("[CREAM] ERROR: Failed to GET root contract: {:?}", e));
-            }
-            let sub_root = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                key: root_instance,
-                summary: None,
-            });
-            if let Err(e) = api.send(sub_root).await {
-                clog(&format!("[CREAM] ERROR: Failed to subscribe to root contract: {:?}", e));
-            }
-
-            Some(root_instance)
-        };
+            let requests =
+                router.follow(root_instance, Interest::RootUserContract, UserContractHandler::Root);
+            send_follow(&mut api, requests, "root contract").await;
+        }
 
         // ── Create signing service ───────────────────────────────────────
         let signing_service = crate::components::signing_service::SigningService::from_env();
+        let delegate_session = DelegateSession::default();
+
+        // ── Ledger confirmation checks ───────────────────────────────────
+        let (send_ledger_tick, mut ledger_ticks) = mpsc::unbounded::<()>();
+        platform::spawn_local(async move {
+            loop {
+                platform::sleep_ms(CONFIRM_CHECK_INTERVAL_MS).await;
+                if send_ledger_tick.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // ── Subscription retries ─────────────────────────────────────────
+        let (send_subscription_tick, mut subscription_ticks) = mpsc::unbounded::<()>();
+        platform::spawn_local(async move {
+            loop {
+                platform::sleep_ms(RETRY_CHECK_INTERVAL_MS).await;
+                if send_subscription_tick.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        // ── Sealed contact details ───────────────────────────────────────
+        let (send_contact_tick, mut contact_ticks) = mpsc::unbounded::<()>();
+        platform::spawn_local(async move {
+            loop {
+                platform::sleep_ms(CONTACT_RESEAL_INTERVAL_MS).await;
+                if send_contact_tick.unbounded_send(()).is_err() {
+                    break;
+                }
+            }
+        });
 
         // ── Main event loop ─────────────────────────────────────────────
+        // When the connection drops, actions and background requests wait in
+        // their channels while we reconnect; then every followed contract is
+        // fetched and subscribed to again, and what comes back is reconciled
+        // with what we had (see `resync`).
+        let mut connection_lost = false;
         loop {
+            if let Some(snapshot) = router.subscriptions().take_snapshot() {
+                shared.write().subscriptions = snapshot;
+            }
+            if connection_lost {
+                connection_lost = false;
+                shared.write().connected = false;
+                tracing::info!("Lost connection to Freenet node, reconnecting");
+                // Re-read the candidates: the user may have changed them in settings
+                let candidates = node_candidates(&user_state.read());
+                let (url, new_api, responses) = reconnect(&candidates, &mut shared).await;
+                api.reconnected(new_api);
+                host_responses = responses;
+                node_url = url;
+                {
+                    let mut state = shared.write();
+                    state.connected = true;
+                    state.node_url = Some(node_url.clone());
+                }
+                tracing::info!(%node_url, "Reconnected to Freenet node");
+
+                router.subscriptions().connection_lost();
+                let requests = router.subscriptions().reconcile();
+                api.set_label("Reconnect");
+                send_follow(&mut api, requests, "contract after reconnect").await;
+            }
+
             futures::select! {
                 action = rx.next() => {
                     let Some(action) = action else { break };
+                    if matches!(action, NodeAction::SwitchNode) {
+                        connection_lost = true;
+                        continue;
+                    }
                     let km = key_manager_signal.read().clone();
                     let Some(km) = km else {
-                        tracing::warn!("Action received but no KeyManager available, dropping: {:?}", action);
+                        tracing::warn!(action = action.kind(), "Action received but no KeyManager available, dropping");
                         continue;
                     };
+                    let span = tracing::info_span!("node_action", action = action.kind());
+                    api.set_label(action.kind());
                     handle_action(
                         action,
                         &mut api,
                         &mut shared,
-                        &directory_key,
                         &mut sf_contract_keys,
                         &km,
                         &node_url,
                         is_customer,
                         &user_state,
                         &send_half,
-                        &mut user_contract_instance_id,
+                        &mut router,
                         &mut user_contract_key,
                         &root_contract_full_key,
                         &signing_service,
-                        &mut inbox_contract_instance_id,
                         &mut inbox_contract_key,
                         &toll_rates,
                         &market_directory_key,
-                    ).await;
+                        &name_registry_key,
+                        &delegate_session,
+                    ).instrument(span).await;
                 }
 
                 response = host_responses.next() => {
-                    let Some(response) = response else { break };
+                    let Some(response) = response else {
+                        connection_lost = true;
+                        continue;
+                    };
                     match response {
                         Ok(HostResponse::ContractResponse(cr)) => {
                             let csn = user_state.read().connected_supplier.clone();
-                            let follow_ups = handle_contract_response(
-                                &mut shared, cr, directory_instance_id,
-                                &mut subscribed_storefronts,
-                                &mut instance_to_name,
+                            let span = tracing::info_span!("contract_response", kind = response_kind(&cr));
+                            let follow_ups = span.in_scope(|| router.handle(
+                                &mut shared,
                                 &mut sf_contract_keys,
                                 csn.as_deref(),
-                                user_contract_instance_id,
-                                root_contract_instance_id,
-                                inbox_contract_instance_id,
-                                market_directory_instance_id,
-                            );
+                                cr,
+                            ));
+                            api.set_label("ContractResponse");
                             for follow_up in follow_ups {
                                 if let Err(e) = api.send(follow_up).await {
                                     tracing::error!("Failed to send follow-up: {:?}", e);
@@ -634,85 +1024,125 @@ mod wasm_impl {
                             tracing::debug!("Node OK");
                         }
                         Ok(other) => {
-                            clog(&format!("[CREAM] Unhandled response: {:?}", other));
+                            tracing::info!(response = ?other, "Unhandled response");
+                        }
+                        Err(e) if matches!(
+                            e.kind(),
+                            freenet_stdlib::client_api::ErrorKind::Disconnect
+                                | freenet_stdlib::client_api::ErrorKind::ChannelClosed
+                        ) => {
+                            connection_lost = true;
+                        }
+                        Err(e) if matches!(
+                            e.kind(),
+                            freenet_stdlib::client_api::ErrorKind::RequestError(
+                                freenet_stdlib::client_api::RequestError::ContractError(
+                                    freenet_stdlib::client_api::ContractError::Update { .. }
+                                )
+                            )
+                        ) => {
+                            let freenet_stdlib::client_api::ErrorKind::RequestError(
+                                freenet_stdlib::client_api::RequestError::ContractError(
+                                    freenet_stdlib::client_api::ContractError::Update { key, cause }
+                                )
+                            ) = e.kind() else { unreachable!() };
+                            // The contract refused a change we already show:
+                            // undo it, and fetch what the network does hold
+                            tracing::info!(%key, %cause, "Update rejected");
+                            let undone = shared.write().roll_back_updates(&key.id().to_string());
+                            if !undone.is_empty() {
+                                let mut error = explain_rejection(&undone, cause);
+                                error.message = format!("{} has been undone. {}", undone.join(", "), error.message);
+                                shared.write().push_error(error);
+                            }
+                            api.set_label("ContractResponse");
+                            let refetch = request::get(*key.id(), false);
+                            if let Err(e) = api.send(refetch).await {
+                                tracing::error!("Failed to refetch rejected contract: {:?}", e);
+                            }
                         }
                         Err(e) => {
-                            // Check if this is a MissingContract error for the
-                            // directory — treat it like NotFound and PUT.
-                            let is_missing_directory = matches!(
-                                e.kind(),
-                                freenet_stdlib::client_api::ErrorKind::RequestError(
-                                    freenet_stdlib::client_api::RequestError::ContractError(
-                                        freenet_stdlib::client_api::ContractError::MissingContract { key }
-                                    )
-                                ) if *key == directory_instance_id
-                            );
-                            let is_missing_market_directory = matches!(
-                                e.kind(),
+                            // A MissingContract error for a contract we can
+                            // create (directory shard, market directory, name
+                            // registry) — treat it like NotFound and PUT.
+                            let create = match e.kind() {
                                 freenet_stdlib::client_api::ErrorKind::RequestError(
                                     freenet_stdlib::client_api::RequestError::ContractError(
                                         freenet_stdlib::client_api::ContractError::MissingContract { key }
                                     )
-                                ) if *key == market_directory_instance_id
-                            );
-                            if is_missing_directory {
-                                tracing::info!("Directory contract missing, creating it...");
-                                let dir_contract = make_contract(
-                                    DIRECTORY_CONTRACT_WASM,
-                                    Parameters::from(vec![]),
-                                );
-                                let empty_dir = DirectoryState::default();
-                                let initial_state =
-                                    serde_json::to_vec(&empty_dir).unwrap();
-                                let put_req = ClientRequest::ContractOp(
-                                    ContractRequest::Put {
-                                        contract: dir_contract,
-                                        state: WrappedState::new(initial_state),
-                                        related_contracts: RelatedContracts::default(),
-                                        subscribe: true,
-                                        blocking_subscribe: false,
-                                    },
-                                );
-                                if let Err(e) = api.send(put_req).await {
-                                    tracing::error!("Failed to PUT directory: {:?}", e);
-                                }
-                            } else if is_missing_market_directory {
-                                tracing::info!("Market directory contract missing, creating it...");
-                                let mkt_contract = make_contract(
-                                    MARKET_DIRECTORY_CONTRACT_WASM,
-                                    Parameters::from(vec![]),
-                                );
-                                let empty_mkt = cream_common::market::MarketDirectoryState::default();
-                                let initial_state =
-                                    serde_json::to_vec(&empty_mkt).unwrap();
-                                let put_req = ClientRequest::ContractOp(
-                                    ContractRequest::Put {
-                                        contract: mkt_contract,
-                                        state: WrappedState::new(initial_state),
-                                        related_contracts: RelatedContracts::default(),
-                                        subscribe: true,
-                                        blocking_subscribe: false,
-                                    },
-                                );
+                                ) => router.create_missing(key),
+                                _ => None,
+                            };
+                            if let Some(put_req) = create {
+                                tracing::info!("Contract missing, creating it...");
+                                api.set_label("ContractResponse");
                                 if let Err(e) = api.send(put_req).await {
-                                    tracing::error!("Failed to PUT market directory: {:?}", e);
+                                    tracing::error!("Failed to PUT missing contract: {:?}", e);
                                 }
                             } else {
-                                clog(&format!("[CREAM] Node error: {:?}", e));
-                                shared.write().last_error = Some(format!("{:?}", e));
+                                tracing::info!(error = ?e, "Node error");
+                                shared.write().report_error(&e);
                             }
                         }
                     }
                 }
 
+                chunk = directory_chunks.next() => {
+                    let Some(chunk) = chunk else { break };
+                    let follow_ups = router.merge_directory_chunk(&mut shared, chunk);
+                    api.set_label("ContractResponse");
+                    for follow_up in follow_ups {
+                        if let Err(e) = api.send(follow_up).await {
+                            tracing::error!("Failed to send follow-up: {:?}", e);
+                        }
+                    }
+                }
+
                 request = requests.next() => {
                     let Some(request) = request else { break };
+                    api.set_label("Background");
                     if let Err(e) = api.send(request).await {
                         tracing::error!("Failed to send request: {:?}", e);
                         shared.write().last_error =
                             Some(format!("Send failed: {:?}", e));
                     }
                 }
+
+                tick = retry_ticks.next() => {
+                    let Some(tick) = tick else { break };
+                    api.on_tick(tick).await;
+                }
+
+                tick = subscription_ticks.next() => {
+                    if tick.is_none() { break }
+                    let requests = router.subscriptions().retry_due(chrono::Utc::now());
+                    if !requests.is_empty() {
+                        api.set_label("Resubscribe");
+                        send_follow(&mut api, requests, "contract").await;
+                    }
+                }
+
+                tick = contact_ticks.next() => {
+                    if tick.is_none() { break }
+                    let km = key_manager_signal.read().clone();
+                    if let Some(km) = km.filter(|_| !is_customer) {
+                        reseal_contact(&mut api, &mut shared, &sf_contract_keys, &km).await;
+                    }
+                }
+
+                tick = ledger_ticks.next() => {
+                    if tick.is_none() { break }
+                    let overdue = shared.write().overdue_ledger_entries(chrono::Utc::now());
+                    api.set_label("ResubmitLedgerEntry");
+                    for entry in overdue {
+                        tracing::info!(
+                            tx_ref = %entry.tx.tx_ref,
+                            submissions = entry.submissions,
+                            "Ledger entry unconfirmed, re-submitting",
+                        );
+                        resubmit_ledger_entry(&mut api, &mut shared, entry, &signing_service).await;
+                    }
+                }
             }
         }
 
@@ -721,14 +1151,14 @@ mod wasm_impl {
 
     /// Generate a unique transaction reference string.
     pub(crate) fn generate_tx_ref(sender: &str) -> String {
-        let ts = web_sys::js_sys::Date::now() as u64;
-        let rnd = rand_u32();
+        let ts = chrono::Utc::now().timestamp_millis() as u64;
+        let rnd = platform::rand_u32();
         format!("{}:{}:{}", sender, ts, rnd)
     }
 
     /// Get current time as ISO 8601 string.
     pub(crate) fn now_iso8601() -> String {
-        web_sys::js_sys::Date::new_0().to_iso_string().into()
+        chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
     }
 
     /// Identifies a user contract by role (for record_transfer).
@@ -738,12 +1168,98 @@ mod wasm_impl {
         ThirdParty(ContractKey),
     }
 
+    /// Claim `name` in the username registry for the caller's user contract.
+    ///
+    /// A name already held by someone else is left alone (the registry would
+    /// ignore the later claim anyway); re-claiming our own name just moves it
+    /// to `user_contract_key`.
+    async fn claim_name(
+        api: &mut RetryingApi,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        key_manager: &KeyManager,
+        name_registry_key: &ContractKey,
+        name: &str,
+        user_contract_key: &ContractKey,
+    ) {
+        use cream_common::registry::{normalize_name, NameClaim, NameRegistryState};
+
+        let owner = key_manager.user_id();
+        let existing = shared.read().name_registry.lookup(name).cloned();
+        if existing.as_ref().is_some_and(|c| c.owner != owner) {
+            tracing::warn!(%name, "Name is already registered to another user");
+            return;
+        }
+        let uc_key_str = format!("{}", user_contract_key);
+        if existing.as_ref().is_some_and(|c| c.user_contract_key == uc_key_str) {
+            return;
+        }
+
+        let now = chrono::Utc::now();
+        let mut claim = NameClaim {
+            name: name.to_string(),
+            owner,
+            user_contract_key: uc_key_str,
+            claimed_at: existing.map(|c| c.claimed_at).unwrap_or(now),
+            updated_at: now,
+            signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+            extra: Default::default(),
+        };
+        key_manager.sign_name_claim(&mut claim);
+
+        let key = normalize_name(name);
+        let delta = NameRegistryState {
+            claims: [(key.clone(), claim.clone())].into_iter().collect(),
+            extra: Default::default(),
+        };
+        let update = request::update(*name_registry_key, serde_json::to_vec(&delta).unwrap());
+        if let Err(e) = api.send(update).await {
+            tracing::error!(%name, error = ?e, "Failed to claim name");
+            return;
+        }
+        tracing::info!(%name, "Claimed name in the username registry");
+        shared.write().name_registry.claims.insert(key, claim);
+    }
+
+    /// Resolve a transfer recipient to their user contract key and display
+    /// name: an encoded contract key as-is, then a registered username, then
+    /// a supplier name from the directory. User contracts share the root
+    /// contract's code.
+    fn resolve_user_contract(
+        shared: &Signal<crate::components::shared_state::SharedState>,
+        root_contract_key: &ContractKey,
+        recipient: &str,
+    ) -> Option<(ContractKey, String)> {
+        let recipient = recipient.trim();
+        if let Ok(id) = ContractInstanceId::from_bytes(recipient) {
+            let key = ContractKey::from_id_and_code(id, root_contract_key.code_hash().clone());
+            return Some((key, "peer".to_string()));
+        }
+        let registered = shared.read().name_registry.lookup(recipient).and_then(|claim| {
+            let id = ContractInstanceId::from_bytes(&claim.user_contract_key).ok()?;
+            Some((ContractKey::from_id_and_code(id, root_contract_key.code_hash().clone()), claim.name.clone()))
+        });
+        if registered.is_some() {
+            return registered;
+        }
+        let (owner, name) = shared
+            .read()
+            .directory
+            .entries
+            .values()
+            .find(|e| e.name.eq_ignore_ascii_case(recipient))
+            .map(|e| (e.supplier.0, e.name.clone()))?;
+        let params = UserContractParameters { owner, spending_key: None };
+        let params_bytes = serde_json::to_vec(&params).unwrap();
+        let key = make_contract(USER_CONTRACT_WASM, Parameters::from(params_bytes)).key();
+        Some((key, name))
+    }
+
     /// Record a double-entry transfer between two user contracts.
     ///
     /// Appends a debit to the sender's contract and a credit to the receiver's contract,
     /// linked by a shared `tx_ref`. Both contracts are updated on the network.
     pub(crate) async fn record_transfer(
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         shared: &mut Signal<crate::components::shared_state::SharedState>,
         sender: ContractRole,
         receiver: ContractRole,
@@ -756,37 +1272,97 @@ mod wasm_impl {
         override_tx_ref: Option<String>,
         signing_service: &crate::components::signing_service::SigningService,
         lightning_payment_hash: Option<String>,
+        payer_keys: Option<&KeyManager>,
     ) {
         let tx_ref = override_tx_ref.unwrap_or_else(|| generate_tx_ref(&sender_name));
-        let timestamp = now_iso8601();
+        let leg = TransferLeg { amount, description, tx_ref, lightning_payment_hash };
+        record_transfers(
+            api,
+            shared,
+            sender,
+            receiver,
+            root_contract_key,
+            user_contract_key,
+            vec![leg],
+            sender_name,
+            receiver_name,
+            signing_service,
+            payer_keys,
+        )
+        .await;
+    }
 
-        // Build debit entry (for sender's contract)
-        let debit = cream_common::wallet::WalletTransaction {
-            id: 0,
-            kind: cream_common::wallet::TransactionKind::Debit,
-            amount,
-            description: description.clone(),
-            sender: sender_name.clone(),
-            receiver: receiver_name.clone(),
-            tx_ref: tx_ref.clone(),
-            timestamp: timestamp.clone(),
-            lightning_payment_hash: lightning_payment_hash.clone(),
-            extra: Default::default(),
-        };
+    /// One transfer in a batch recorded by [`record_transfers`].
+    pub(crate) struct TransferLeg {
+        pub amount: u64,
+        pub description: String,
+        pub tx_ref: String,
+        pub lightning_payment_hash: Option<String>,
+    }
 
-        // Build credit entry (for receiver's contract)
-        let credit = cream_common::wallet::WalletTransaction {
-            id: 0,
-            kind: cream_common::wallet::TransactionKind::Credit,
-            amount,
-            description,
-            sender: sender_name.clone(),
-            receiver: receiver_name.clone(),
-            tx_ref: tx_ref.clone(),
-            timestamp,
-            lightning_payment_hash,
-            extra: Default::default(),
-        };
+    /// Record several double-entry transfers between the same two contracts.
+    ///
+    /// All the debits go to the sender's contract in one update and all the
+    /// credits to the receiver's in another, so a batch paid from root needs
+    /// a single group signature.
+    pub(crate) async fn record_transfers(
+        api: &mut RetryingApi,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        sender: ContractRole,
+        receiver: ContractRole,
+        root_contract_key: &ContractKey,
+        user_contract_key: Option<&ContractKey>,
+        legs: Vec<TransferLeg>,
+        sender_name: String,
+        receiver_name: String,
+        signing_service: &crate::components::signing_service::SigningService,
+        payer_keys: Option<&KeyManager>,
+    ) {
+        if legs.is_empty() {
+            return;
+        }
+        let timestamp = now_iso8601();
+        // Entries in our own contract carry this device's id (the wallet
+        // also pays suppliers' contracts in the User role)
+        let device = platform::device_id();
+        let own_contract = shared.read().user_contract_key.clone();
+        let is_own = user_contract_key.is_some_and(|key| own_contract.as_deref() == Some(key.to_string().as_str()));
+        let own_device = |role: &ContractRole| (matches!(role, ContractRole::User) && is_own).then(|| device.clone());
+        let mut debits = Vec::with_capacity(legs.len());
+        let mut credits = Vec::with_capacity(legs.len());
+        for leg in &legs {
+            // Build debit entry (for sender's contract)
+            let debit = cream_common::wallet::WalletTransaction {
+                id: 0,
+                kind: cream_common::wallet::TransactionKind::Debit,
+                amount: leg.amount,
+                description: leg.description.clone(),
+                sender: sender_name.clone(),
+                receiver: receiver_name.clone(),
+                tx_ref: leg.tx_ref.clone(),
+                timestamp: timestamp.clone(),
+                lightning_payment_hash: leg.lightning_payment_hash.clone(),
+                payer: None,
+                provenance: None,
+                device: own_device(&sender),
+                extra: Default::default(),
+            };
+
+            // Build credit entry (for receiver's contract)
+            let mut credit = cream_common::wallet::WalletTransaction {
+                kind: cream_common::wallet::TransactionKind::Credit,
+                device: own_device(&receiver),
+                ..debit.clone()
+            };
+            // A user paying into someone else's contract stamps the credit, so the
+            // recipient's contract can check who wrote it
+            if let (ContractRole::User, Some(keys)) = (&sender, payer_keys) {
+                credit.payer = Some(keys.user_id());
+                credit.provenance = Some(keys.stamp(StateSection::LedgerCredit, &credit.credit_provenance_bytes()));
+            }
+            debits.push(debit);
+            credits.push(credit);
+        }
 
         // Resolve sender key
         let sender_key = match &sender {
@@ -795,9 +1371,15 @@ mod wasm_impl {
             ContractRole::ThirdParty(key) => Some(*key),
         };
         if let Some(key) = sender_key {
-            update_contract_ledger(api, shared, &sender, key, debit, signing_service).await;
+            update_contract_ledger(api, shared, &sender, key, debits, signing_service).await;
         } else {
-            clog("[CREAM] WARNING: sender contract key not available");
+            tracing::warn!("sender contract key not available");
+        }
+
+        // Root's payments carry the guardians' stamp, given once the debits
+        // are on root, so the receiver's contract accepts the credits
+        if matches!(sender, ContractRole::Root) {
+            stamp_root_credits(shared, &mut credits, signing_service).await;
         }
 
         // Resolve receiver key
@@ -807,25 +1389,89 @@ mod wasm_impl {
             ContractRole::ThirdParty(key) => Some(*key),
         };
         if let Some(key) = receiver_key {
-            update_contract_ledger(api, shared, &receiver, key, credit, signing_service).await;
+            update_contract_ledger(api, shared, &receiver, key, credits, signing_service).await;
         } else {
-            clog("[CREAM] WARNING: receiver contract key not available");
+            tracing::warn!("receiver contract key not available");
+        }
+
+        for leg in &legs {
+            tracing::info!(
+                amount = leg.amount,
+                sender = %sender_name,
+                receiver = %receiver_name,
+                tx_ref = %leg.tx_ref,
+                "Transfer recorded",
+            );
+        }
+    }
+
+    /// Stamp root's `credits` with the group key, naming root as their payer.
+    async fn stamp_root_credits(
+        shared: &Signal<crate::components::shared_state::SharedState>,
+        credits: &mut [cream_common::wallet::WalletTransaction],
+        signing_service: &crate::components::signing_service::SigningService,
+    ) {
+        let Some(root) = shared.read().root_user_contract.as_ref().map(|root| root.owner.clone()) else {
+            tracing::warn!("root contract not loaded; root credits left unstamped");
+            return;
+        };
+        for credit in credits {
+            credit.payer = Some(root.clone());
+            let written_at = chrono::Utc::now();
+            let request = SigningRequest::RootCredit { credit: credit.clone(), written_at };
+            match signing_service.sign(&request).await {
+                Ok(signature) => {
+                    credit.provenance = Some(cream_common::provenance::WriterStamp {
+                        last_writer: root.0,
+                        written_at,
+                        signature,
+                        extra: Default::default(),
+                    });
+                }
+                Err(e) => tracing::error!(error = %e, tx_ref = %credit.tx_ref, "FROST signing of root credit failed"),
+            }
         }
+    }
 
-        clog(&format!("[CREAM] Transfer recorded: {} CURD from {} to {} (tx_ref={})",
-            amount, sender_name, receiver_name, tx_ref));
+    /// What to ask the guardians to sign for a root state adding `txs`: a
+    /// settlement when every debit settles escrow to the same listed
+    /// supplier, so guardians can check the orders against the storefront;
+    /// otherwise a plain root ledger update.
+    fn root_signing_request(
+        directory: &DirectoryState,
+        txs: &[cream_common::wallet::WalletTransaction],
+        root_state: UserContractState,
+    ) -> SigningRequest {
+        let debits: Vec<_> = txs
+            .iter()
+            .filter(|tx| tx.kind == cream_common::wallet::TransactionKind::Debit)
+            .collect();
+        let receiver = debits.first().map(|tx| tx.receiver.as_str());
+        let settling = !debits.is_empty()
+            && debits.iter().all(|tx| {
+                tx.description.starts_with(SETTLEMENT_DESCRIPTION_PREFIX) && Some(tx.receiver.as_str()) == receiver
+            });
+        let supplier = directory
+            .entries
+            .values()
+            .find(|entry| settling && Some(entry.name.as_str()) == receiver)
+            .map(|entry| entry.supplier.clone());
+        match supplier {
+            Some(supplier) => SigningRequest::Settlement { supplier, root_state },
+            None => SigningRequest::RootLedger { root_state },
+        }
     }
 
-    /// Append a transaction entry to a user contract and push the update to the network.
+    /// Append transaction entries to a user contract and push the update to the network.
     async fn update_contract_ledger(
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         shared: &mut Signal<crate::components::shared_state::SharedState>,
         role: &ContractRole,
         contract_key: ContractKey,
-        tx: cream_common::wallet::WalletTransaction,
+        txs: Vec<cream_common::wallet::WalletTransaction>,
         signing_service: &crate::components::signing_service::SigningService,
     ) {
-        // ThirdParty: construct a minimal state with just the transaction entry.
+        // ThirdParty: construct a minimal state with just the transaction entries.
         // The merge logic does ledger union unconditionally, so the credit gets
         // appended without overwriting the target's metadata. No network GET needed.
         if matches!(role, ContractRole::ThirdParty(_)) {
@@ -844,19 +1490,22 @@ mod wasm_impl {
                 checkpoint_tx_count: 0,
                 checkpoint_at: None,
                 pruned_lightning_hashes: Default::default(),
-                ledger: vec![tx],
+                checkpoint_proof: None,
+                spending_key_debits: Default::default(),
+                mint_records: Vec::new(),
+                settlement_statements: Vec::new(),
+                vouchers: Vec::new(),
+                ledger: txs,
                 next_tx_id: 0,
+                seq: 0,
                 updated_at: chrono::DateTime::<chrono::Utc>::from_timestamp(0, 0).unwrap(),
                 signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
                 extra: Default::default(),
             };
             let uc_bytes = serde_json::to_vec(&minimal_state).unwrap();
-            let update = ClientRequest::ContractOp(ContractRequest::Update {
-                key: contract_key,
-                data: UpdateData::State(State::from(uc_bytes)),
-            });
+            let update = request::update(contract_key, uc_bytes);
             if let Err(e) = api.send(update).await {
-                clog(&format!("[CREAM] ERROR: Failed to update third-party contract: {:?}", e));
+                tracing::error!(error = ?e, "Failed to update third-party contract");
             }
             return;
         }
@@ -872,15 +1521,21 @@ mod wasm_impl {
         };
 
         if let Some(ref mut uc) = uc_state {
-            uc.ledger.push(tx);
+            // A re-submitted entry may still be in the local ledger
+            for tx in &txs {
+                if !uc.ledger.iter().any(|t| t.tx_ref == tx.tx_ref && t.kind == tx.kind) {
+                    uc.ledger.push(tx.clone());
+                }
+            }
             uc.balance_curds = uc.derive_balance();
             uc.next_tx_id = uc.ledger.iter().map(|t| t.id).max().unwrap_or(0) + 1;
-            uc.updated_at = chrono::Utc::now();
+            uc.touch(chrono::Utc::now());
             uc.signature = match role {
                 ContractRole::Root => {
-                    signing_service.sign(&uc.signable_bytes()).await
+                    let request = root_signing_request(&shared.read().directory, &txs, uc.clone());
+                    signing_service.sign(&request).await
                         .unwrap_or_else(|e| {
-                            clog(&format!("[CREAM] ERROR: FROST signing failed: {}", e));
+                            tracing::error!(error = %e, "FROST signing failed");
                             ed25519_dalek::Signature::from_bytes(&[0u8; 64])
                         })
                 }
@@ -888,56 +1543,183 @@ mod wasm_impl {
             };
 
             let uc_bytes = serde_json::to_vec(&uc).unwrap();
-            let update = ClientRequest::ContractOp(ContractRequest::Update {
-                key: contract_key,
-                data: UpdateData::State(State::from(uc_bytes)),
-            });
+            let update = request::update(contract_key, uc_bytes);
 
             // Update SharedState
-            {
+            let rollback = {
                 let mut state = shared.write();
                 match role {
-                    ContractRole::Root => state.root_user_contract = Some(uc.clone()),
-                    ContractRole::User => state.user_contract = Some(uc.clone()),
+                    ContractRole::Root => state.set_root_contract_optimistic(uc.clone()),
+                    ContractRole::User => state.set_user_contract_optimistic(uc.clone()),
                     ContractRole::ThirdParty(_) => unreachable!(),
                 }
+            };
+
+            let owner = match role {
+                ContractRole::Root => LedgerOwner::Root,
+                _ => LedgerOwner::Own,
+            };
+            for tx in txs {
+                shared.write().track_ledger_entry(owner, contract_key, tx);
             }
+            if let Err(e) = api.send_optimistic(update, rollback).await {
+                tracing::error!(error = ?e, "Failed to update contract");
+            }
+        } else {
+            tracing::warn!("No state found for contract");
+        }
+    }
+
+    /// Submit a tracked ledger entry again, to the contract it was written to.
+    async fn resubmit_ledger_entry(
+        api: &mut RetryingApi,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        entry: TrackedEntry,
+        signing_service: &crate::components::signing_service::SigningService,
+    ) {
+        let role = match entry.owner {
+            LedgerOwner::Root => ContractRole::Root,
+            LedgerOwner::Own => ContractRole::User,
+        };
+        update_contract_ledger(api, shared, &role, entry.contract_key, vec![entry.tx], signing_service).await;
+    }
 
+    /// Name the guardian federation signs its credentials under.
+    const GUARDIAN_CERTIFIER_NAME: &str = "CREAM Guardians";
+
+    /// Merge a guardian-signed directory delta about `supplier` locally and
+    /// publish it to the supplier's shard — or to every shard if their entry
+    /// is unknown here (e.g. already removed).
+    async fn publish_supplier_delta(
+        api: &mut RetryingApi,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        supplier: &cream_common::identity::UserId,
+        delta: DirectoryState,
+    ) {
+        let shards = shared
+            .read()
+            .directory
+            .entries
+            .get(supplier)
+            .and_then(DirectoryShard::for_entry)
+            .map(|shard| vec![shard])
+            .unwrap_or_else(|| DirectoryShard::ALL.to_vec());
+
+        let delta_bytes = serde_json::to_vec(&delta).unwrap();
+        shared.write().directory.merge(delta);
+
+        for shard in shards {
+            let update = request::update_delta(directory_shard_contract(shard).key(), delta_bytes.clone());
             if let Err(e) = api.send(update).await {
-                clog(&format!("[CREAM] ERROR: Failed to update contract: {:?}", e));
+                tracing::error!(shard = %shard.label(), error = ?e, "Failed to publish to directory shard");
             }
-        } else {
-            clog("[CREAM] WARNING: No state found for contract");
+        }
+    }
+
+    /// How often a supplier's sealed contact details are checked against
+    /// their orders in progress.
+    const CONTACT_RESEAL_INTERVAL_MS: u32 = 30_000;
+
+    /// Seal our storefront's private contact details again if its orders in
+    /// progress have changed since, so new customers can read them and those
+    /// whose orders are done can't read later changes.
+    async fn reseal_contact(
+        api: &mut RetryingApi,
+        shared: &mut Signal<crate::components::shared_state::SharedState>,
+        sf_contract_keys: &BTreeMap<String, ContractKey>,
+        key_manager: &KeyManager,
+    ) {
+        let my_supplier_id = key_manager.user_id();
+        let (supplier_name, sf_key) = {
+            let state = shared.read();
+            state
+                .directory
+                .entries
+                .get(&my_supplier_id)
+                .map(|entry| (entry.name.clone(), entry.storefront_key))
+                .or_else(|| {
+                    sf_contract_keys
+                        .iter()
+                        .next()
+                        .map(|(name, key)| (name.clone(), *key))
+                })
+                .unzip()
+        };
+        let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+            return;
+        };
+        let (existing_sf, settling) = {
+            let state = shared.read();
+            (
+                state.storefronts.get(&supplier_name).cloned(),
+                state.optimistic.contains_key(&sf_key.id().to_string()),
+            )
+        };
+        // Leave a storefront with changes still pending until they settle
+        let Some(mut sf) = existing_sf.filter(|_| !settling) else {
+            return;
+        };
+        let Some(sealed) = sf.info.sealed_contact.as_ref() else {
+            return;
+        };
+        let recipients = active_recipients(sf.orders.values());
+        if sealed.sealed_for(&recipients) {
+            return;
+        }
+        let Some(resealed) = key_manager.reseal_contact(sealed, &recipients) else {
+            return;
+        };
+        tracing::info!(orders = recipients.len(), "Re-sealing contact details for orders in progress");
+        sf.info.sealed_contact = Some(resealed);
+        sf.info.seq += 1;
+        sf.info.provenance = Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
+
+        let update = request::update(sf_key, serde_json::to_vec(&sf).unwrap());
+        let rollback = shared.write().set_storefront_optimistic(supplier_name, sf);
+        api.set_label("ResealContact");
+        if let Err(e) = api.send_optimistic(update, rollback).await {
+            tracing::error!(error = ?e, "Failed to re-seal contact details");
         }
     }
 
     /// Convert a UI action into contract operations and send them.
     async fn handle_action(
         action: NodeAction,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         shared: &mut Signal<crate::components::shared_state::SharedState>,
-        directory_key: &ContractKey,
         sf_contract_keys: &mut BTreeMap<String, ContractKey>,
         key_manager: &KeyManager,
-        #[allow(unused_variables)] node_url: &str,
+        node_url: &str,
         is_customer: bool,
         user_state: &Signal<crate::components::user_state::UserState>,
         send_half: &mpsc::UnboundedSender<ClientRequest<'static>>,
-        user_contract_instance_id: &mut Option<ContractInstanceId>,
+        router: &mut ContractRouter,
         user_contract_key_ref: &mut Option<ContractKey>,
         root_contract_key: &ContractKey,
         signing_service: &crate::components::signing_service::SigningService,
-        inbox_contract_instance_id: &mut Option<ContractInstanceId>,
         inbox_contract_key_ref: &mut Option<ContractKey>,
         toll_rates: &Signal<cream_common::tolls::TollRates>,
         market_directory_key: &ContractKey,
+        name_registry_key: &ContractKey,
+        delegate_session: &DelegateSession,
     ) {
+        // Listings, orders and directory entries may be signed by the node's delegate
+        let signer = {
+            let state = user_state.read();
+            KeySigner::new(
+                key_manager,
+                state.delegate_signing.then_some((delegate_session, node_url)),
+                state.role(),
+            )
+        };
+
         // Construct wallet backend for this action dispatch
         let mut wallet = CreamNativeWallet::new(
             *shared,
             *root_contract_key,
             *user_contract_key_ref,
             signing_service.clone(),
+            key_manager.clone(),
         );
 
         match action {
@@ -946,6 +1728,7 @@ mod wasm_impl {
                 postcode,
                 locality,
                 description,
+                template,
             } => {
                 let supplier_id = key_manager.user_id();
                 let owner_key = key_manager.verifying_key();
@@ -957,35 +1740,21 @@ mod wasm_impl {
                     .get(&supplier_id).cloned();
 
                 if let Some(entry) = existing_entry {
-                    clog(&format!("[CREAM] RegisterSupplier: {} already in directory, skipping PUT", name));
+                    tracing::info!(%name, "RegisterSupplier: already in directory, skipping PUT");
                     let sf_key = entry.storefront_key;
                     sf_contract_keys.insert(name.clone(), sf_key);
                     shared.write().storefront_keys
                         .insert(name.clone(), format!("{}", sf_key));
 
-                    // GET + subscribe to our own storefront so SharedState is populated
-                    let get_sf = ClientRequest::ContractOp(ContractRequest::Get {
-                        key: *sf_key.id(),
-                        return_contract_code: false,
-                        subscribe: false,
-                        blocking_subscribe: false,
-                    });
-                    if let Err(e) = api.send(get_sf).await {
-                        clog(&format!("[CREAM] ERROR: Failed to GET own storefront: {:?}", e));
-                    }
-                    let sub_sf = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                        key: *sf_key.id(),
-                        summary: None,
-                    });
-                    if let Err(e) = api.send(sub_sf).await {
-                        clog(&format!("[CREAM] ERROR: Failed to subscribe to own storefront: {:?}", e));
-                    }
+                    // Follow our own storefront so SharedState is populated
+                    let requests = router.subscriptions().want(*sf_key.id(), Interest::Storefront);
+                    send_follow(api, requests, "own storefront").await;
 
                     // Deploy inbox contract if it doesn't exist yet.
                     // (The test harness pre-populates directory/storefront/user
                     // contracts but NOT inbox contracts, so for harness-created
                     // users the inbox must be created on first UI login.)
-                    if inbox_contract_instance_id.is_none() {
+                    if inbox_contract_key_ref.is_none() {
                         let owner_key = key_manager.verifying_key();
                         let inbox_params = cream_common::inbox::InboxParameters { owner: owner_key };
                         let params_bytes = serde_json::to_vec(&inbox_params).unwrap();
@@ -998,18 +1767,13 @@ mod wasm_impl {
                             extra: Default::default(),
                         };
                         let ib_state_bytes = serde_json::to_vec(&ib_state).unwrap();
-                        let put_inbox = ClientRequest::ContractOp(ContractRequest::Put {
-                            contract: inbox_contract,
-                            state: WrappedState::new(ib_state_bytes),
-                            related_contracts: RelatedContracts::default(),
-                            subscribe: true,
-                            blocking_subscribe: false,
-                        });
-                        clog(&format!("[CREAM] Deploying inbox contract for existing user {}: {:?}", name, ib_key));
+                        let put_inbox = request::put(inbox_contract, ib_state_bytes, true);
+                        tracing::info!(%name, key = ?ib_key, "Deploying inbox contract for existing user");
                         if let Err(e) = api.send(put_inbox).await {
-                            clog(&format!("[CREAM] ERROR: Failed to deploy inbox contract: {:?}", e));
+                            tracing::error!(error = ?e, "Failed to deploy inbox contract");
                         }
-                        *inbox_contract_instance_id = Some(*ib_key.id());
+                        router.register(*ib_key.id(), InboxHandler);
+                        router.subscriptions().adopt(*ib_key.id(), Interest::Inbox);
                         *inbox_contract_key_ref = Some(ib_key);
                         {
                             let mut state = shared.write();
@@ -1020,7 +1784,7 @@ mod wasm_impl {
 
                     // Still register with rendezvous service so customers can discover us
                     if !is_customer {
-                        let rendezvous_name = name.to_lowercase().replace(' ', "-");
+                        let rendezvous_name = crate::components::rendezvous::rendezvous_name(&name);
                         let node_address = node_url.to_string();
                         let sf_key_str = format!("{}", sf_key);
                         let uc_key_str = entry.user_contract_key.as_ref().map(|k| format!("{}", k));
@@ -1034,13 +1798,17 @@ mod wasm_impl {
                         let rname = rendezvous_name.clone();
                         let raddr = node_address.clone();
                         let reg_pub_hex = pub_key_hex.clone();
-                        wasm_bindgen_futures::spawn_local(async move {
+                        let mut reg_shared = shared.clone();
+                        platform::spawn_local(async move {
                             match crate::components::rendezvous::register_supplier(
                                 &rname, &raddr, &sf_key_str, &reg_pub_hex, &sig_hex,
                                 uc_key_str.as_deref(), ib_key_str.as_deref(),
                             ).await {
-                                Ok(()) => clog(&format!("[CREAM] Registered with rendezvous as '{}'", rname)),
-                                Err(e) => clog(&format!("[CREAM] WARNING: Rendezvous registration failed: {}", e)),
+                                Ok(()) => {
+                                    reg_shared.write().rendezvous_registered = true;
+                                    tracing::info!(name = %rname, "Registered with rendezvous");
+                                }
+                                Err(e) => tracing::warn!(error = %e, "Rendezvous registration failed"),
                             }
                         });
                     }
@@ -1048,8 +1816,22 @@ mod wasm_impl {
                     return;
                 }
 
-                clog(&format!("[CREAM] RegisterSupplier: {} not found in directory (supplier_id={:?}), deploying NEW storefront. \
-                    Note: for harness data, password must be the lowercase name (e.g. \"gary\")", name, supplier_id));
+                tracing::info!(
+                    %name,
+                    ?supplier_id,
+                    "RegisterSupplier: not found in directory, deploying NEW storefront. \
+                    Note: for harness data, password must be the lowercase name (e.g. \"gary\")",
+                );
+
+                // Supplier names are unique in the directory; the contract
+                // would refuse the entry, so don't deploy anything for it.
+                if !shared.read().directory.is_name_available(&name, &supplier_id) {
+                    tracing::info!(%name, "RegisterSupplier: name is already taken in the directory");
+                    shared.write().push_toast(format!(
+                        "Another supplier is already listed as \"{name}\". Sign up under a different name."
+                    ));
+                    return;
+                }
 
                 // Look up postcode (+ locality if available) to get coordinates
                 let location = locality
@@ -1067,54 +1849,69 @@ mod wasm_impl {
                 );
                 let sf_key = sf_contract.key();
 
-                // Create initial storefront state
+                // Create initial storefront state, seeded from the template if one was picked
+                let now = chrono::Utc::now();
+                let mut products = BTreeMap::new();
+                if let Some(template) = &template {
+                    match template.instantiate(now) {
+                        Ok(templated) => {
+                            for product in templated {
+                                let signature = signer.sign_product(&product).await;
+                                let mut signed_product = SignedProduct {
+                                    product,
+                                    signature,
+                                    provenance: None,
+                                    price_history: Vec::new(),
+                                    extra: Default::default(),
+                                };
+                                signed_product.provenance =
+                                    Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
+                                products.insert(signed_product.product.id.clone(), signed_product);
+                            }
+                        }
+                        Err(errors) => tracing::warn!(
+                            template = %template.name,
+                            ?errors,
+                            "Template has invalid products, starting empty",
+                        ),
+                    }
+                }
                 let sf_state = StorefrontState {
                     info: StorefrontInfo {
                         owner: supplier_id.clone(),
                         name: name.clone(),
                         description: description.clone(),
                         location: location.clone(),
-                        schedule: None,
-                        timezone: None,
+                        schedule: template.as_ref().and_then(|t| t.schedule.clone()),
+                        timezone: template.as_ref().and_then(|t| t.timezone.clone()),
                         phone: None,
                         email: None,
                         address: None,
                         market_products: BTreeMap::new(),
+                        delivery_zones: Vec::new(),
+                        pickup_locations: Vec::new(),
+                        sealed_contact: None,
+                        cancellation_policy: None,
+                        attestation_threshold: None,
+                        provenance: None,
+                        seq: 0,
                         extra: Default::default(),
                     },
-                    products: BTreeMap::new(),
+                    products,
                     orders: BTreeMap::new(),
+                    threads: BTreeMap::new(),
+                    staff: BTreeMap::new(),
+                    blocklist: BTreeMap::new(),
+                    order_history: BTreeMap::new(),
                     extra: Default::default(),
                 };
                 let sf_state_bytes = serde_json::to_vec(&sf_state).unwrap();
 
-                // PUT the storefront contract
-                let put_sf = ClientRequest::ContractOp(ContractRequest::Put {
-                    contract: sf_contract,
-                    state: WrappedState::new(sf_state_bytes),
-                    related_contracts: RelatedContracts::default(),
-                    subscribe: true,
-                    blocking_subscribe: false,
-                });
-
-                clog(&format!("[CREAM] Deploying storefront for {}: {:?}", name, sf_key));
-                if let Err(e) = api.send(put_sf).await {
-                    clog(&format!("[CREAM] ERROR: Failed to deploy storefront: {:?}", e));
-                    return;
-                }
-
-                // Store the storefront key and initial state
-                sf_contract_keys.insert(name.clone(), sf_key);
-                {
-                    let mut state = shared.write();
-                    state.storefront_keys
-                        .insert(name.clone(), format!("{}", sf_contract_keys[&name]));
-                    state.storefronts
-                        .insert(name.clone(), sf_state);
-                }
-
                 // Deploy a user contract for the supplier (same pattern as customer RegisterUser)
-                let supplier_uc_params = UserContractParameters { owner: owner_key };
+                let supplier_uc_params = UserContractParameters {
+                    owner: owner_key,
+                    spending_key: None,
+                };
                 let supplier_uc_params_bytes = serde_json::to_vec(&supplier_uc_params).unwrap();
                 let supplier_uc_contract = make_contract(
                     USER_CONTRACT_WASM,
@@ -1134,30 +1931,31 @@ mod wasm_impl {
                     checkpoint_tx_count: 0,
                     checkpoint_at: None,
                     pruned_lightning_hashes: Default::default(),
+                    checkpoint_proof: None,
+                    spending_key_debits: Default::default(),
+                    mint_records: Vec::new(),
+                    settlement_statements: Vec::new(),
+                    vouchers: Vec::new(),
                     ledger: Vec::new(),
                     next_tx_id: 0,
+                    seq: 0,
                     updated_at: chrono::Utc::now(),
                     signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
                     extra: Default::default(),
                 };
                 let supplier_uc_state_bytes = serde_json::to_vec(&supplier_uc_state).unwrap();
 
-                let put_supplier_uc = ClientRequest::ContractOp(ContractRequest::Put {
-                    contract: supplier_uc_contract,
-                    state: WrappedState::new(supplier_uc_state_bytes),
-                    related_contracts: RelatedContracts::default(),
-                    subscribe: false,
-                    blocking_subscribe: false,
-                });
+                let put_supplier_uc = request::put(supplier_uc_contract, supplier_uc_state_bytes, false);
 
-                clog(&format!("[CREAM] Deploying supplier user contract for {}: {:?}", name, supplier_uc_key));
+                tracing::info!(%name, key = ?supplier_uc_key, "Deploying supplier user contract");
                 if let Err(e) = api.send(put_supplier_uc).await {
-                    clog(&format!("[CREAM] ERROR: Failed to deploy supplier user contract: {:?}", e));
+                    tracing::error!(error = ?e, "Failed to deploy supplier user contract");
                 }
 
                 // Store supplier user contract key
                 let supplier_uc_key_str = format!("{}", supplier_uc_key);
                 shared.write().supplier_user_contract_key = Some(supplier_uc_key_str);
+                claim_name(api, shared, key_manager, name_registry_key, &name, &supplier_uc_key).await;
 
                 // Transfer initial 10,000 CURD from root → supplier.
                 // Use a deterministic tx_ref so re-registration deduplicates.
@@ -1171,6 +1969,7 @@ mod wasm_impl {
                 ).await;
 
                 // Now register in the directory with a real signature
+                let now = chrono::Utc::now();
                 let mut entry = DirectoryEntry {
                     supplier: supplier_id,
                     name: name.clone(),
@@ -1182,11 +1981,12 @@ mod wasm_impl {
                     storefront_key: sf_key,
                     user_contract_key: Some(supplier_uc_key),
                     inbox_contract_key: inbox_contract_key_ref.clone(),
-                    updated_at: chrono::Utc::now(),
+                    updated_at: now,
+                    name_claimed_at: Some(now),
                     signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
                     extra: Default::default(),
                 };
-                key_manager.sign_directory_entry(&mut entry);
+                signer.sign_directory_entry(&mut entry).await;
 
                 // Update local state immediately so this tab sees its own supplier
                 shared
@@ -1195,25 +1995,52 @@ mod wasm_impl {
                     .entries
                     .insert(entry.supplier.clone(), entry.clone());
 
-                let mut entries = BTreeMap::new();
-                entries.insert(entry.supplier.clone(), entry);
-                let dir_update = DirectoryState { entries, extra: Default::default() };
-                let delta_bytes = serde_json::to_vec(&dir_update).unwrap();
+                // The entry goes to the shard for the supplier's postcode
+                match DirectoryShard::for_entry(&entry) {
+                    Some(shard) => {
+                        let shard_key = directory_shard_contract(shard).key();
+                        let mut entries = BTreeMap::new();
+                        entries.insert(entry.supplier.clone(), entry);
+                        let dir_update = DirectoryState { entries, ..Default::default() };
+                        let delta_bytes = serde_json::to_vec(&dir_update).unwrap();
+
+                        let update_dir =
+                            request::update_delta(shard_key, delta_bytes);
+
+                        tracing::info!(%name, shard = %shard.label(), "Registering in directory shard");
+                        if let Err(e) = api.send(update_dir).await {
+                            tracing::error!(error = ?e, "Failed to update directory");
+                        }
+                    }
+                    None => {
+                        tracing::error!(%name, "No directory shard for postcode, not registering");
+                    }
+                }
+
+                // PUT the storefront contract. Only now that its directory entry is
+                // out: the storefront contract rejects storefronts whose owner
+                // isn't listed.
+                let put_sf = request::put(sf_contract, sf_state_bytes, true);
 
-                let update_dir =
-                    ClientRequest::ContractOp(ContractRequest::Update {
-                        key: *directory_key,
-                        data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                    });
+                tracing::info!(%name, key = ?sf_key, "Deploying storefront");
+                if let Err(e) = api.send(put_sf).await {
+                    tracing::error!(error = ?e, "Failed to deploy storefront");
+                    return;
+                }
 
-                clog(&format!("[CREAM] Registering {} in directory", name));
-                if let Err(e) = api.send(update_dir).await {
-                    clog(&format!("[CREAM] ERROR: Failed to update directory: {:?}", e));
+                // Store the storefront key and initial state
+                router.subscriptions().adopt(*sf_key.id(), Interest::Storefront);
+                sf_contract_keys.insert(name.clone(), sf_key);
+                {
+                    let mut state = shared.write();
+                    state.storefront_keys
+                        .insert(name.clone(), format!("{}", sf_contract_keys[&name]));
+                    state.set_storefront(name.clone(), sf_state);
                 }
 
                 // Register with rendezvous service (supplier mode only)
                 if !is_customer {
-                    let rendezvous_name = name.to_lowercase().replace(' ', "-");
+                    let rendezvous_name = crate::components::rendezvous::rendezvous_name(&name);
                     let node_address = node_url.to_string();
                     let sf_key_str = format!("{}", sf_key);
                     let uc_key_str = Some(format!("{}", supplier_uc_key));
@@ -1227,13 +2054,17 @@ mod wasm_impl {
                     let rname = rendezvous_name.clone();
                     let raddr = node_address.clone();
                     let reg_pub_hex = pub_key_hex.clone();
-                    wasm_bindgen_futures::spawn_local(async move {
+                    let mut reg_shared = shared.clone();
+                    platform::spawn_local(async move {
                         match crate::components::rendezvous::register_supplier(
                             &rname, &raddr, &sf_key_str, &reg_pub_hex, &sig_hex,
                             uc_key_str.as_deref(), ib_key_str.as_deref(),
                         ).await {
-                            Ok(()) => clog(&format!("[CREAM] Registered with rendezvous as '{}'", rname)),
-                            Err(e) => clog(&format!("[CREAM] WARNING: Rendezvous registration failed: {}", e)),
+                            Ok(()) => {
+                                reg_shared.write().rendezvous_registered = true;
+                                tracing::info!(name = %rname, "Registered with rendezvous");
+                            }
+                            Err(e) => tracing::warn!(error = %e, "Rendezvous registration failed"),
                         }
                     });
 
@@ -1242,60 +2073,69 @@ mod wasm_impl {
                     let hb_addr = node_address;
                     let hb_pub_hex = pub_key_hex;
                     let hb_km = key_manager.clone();
-                    wasm_bindgen_futures::spawn_local(async move {
+                    platform::spawn_local(async move {
                         loop {
-                            gloo_timers_sleep(5 * 60 * 1000).await;
+                            platform::sleep_ms(5 * 60 * 1000).await;
                             let sign_msg = format!("{}|{}", hb_name, hb_addr);
                             let sig_bytes = hb_km.sign_raw(sign_msg.as_bytes());
                             let sig_hex: String = sig_bytes.iter().map(|b| format!("{:02x}", b)).collect();
                             match crate::components::rendezvous::heartbeat(
                                 &hb_name, &hb_addr, &hb_pub_hex, &sig_hex,
                             ).await {
-                                Ok(()) => clog("[CREAM] Heartbeat sent"),
-                                Err(e) => clog(&format!("[CREAM] WARNING: Heartbeat failed: {}", e)),
+                                Ok(()) => tracing::info!("Heartbeat sent"),
+                                Err(e) => tracing::warn!(error = %e, "Heartbeat failed"),
                             }
                         }
                     });
 
                     // Start background order-expiry task (checks once per hour,
-                    // expires Reserved orders whose hold date has passed).
+                    // expires Reserved orders whose hold date has passed and
+                    // moves legacy free-text product categories onto the taxonomy).
                     let expiry_supplier = name.clone();
+                    let expiry_km = key_manager.clone();
                     let mut expiry_shared = shared.clone();
                     let expiry_sf_keys = sf_contract_keys.clone();
                     let mut expiry_sender = send_half.clone();
-                    wasm_bindgen_futures::spawn_local(async move {
+                    platform::spawn_local(async move {
                         let mut last_run_date: Option<chrono::NaiveDate> = None;
                         loop {
-                            gloo_timers_sleep(60 * 60 * 1000).await; // 1 hour
-                            let today = chrono::Utc::now().date_naive();
+                            platform::sleep_ms(60 * 60 * 1000).await; // 1 hour
+                            let now = SystemClock.now();
+                            let today = now.date_naive();
                             if last_run_date == Some(today) {
                                 continue;
                             }
                             last_run_date = Some(today);
 
-                            // Clone the storefront, run expiry, and update if changed
+                            // Clone the storefront, run expiry and migration, and update if changed
                             let sf_opt = expiry_shared.read().storefronts
                                 .get(&expiry_supplier).cloned();
                             if let Some(mut sf) = sf_opt {
-                                let now = chrono::Utc::now();
                                 let orders_changed = sf.expire_orders(now);
-                                if orders_changed {
-                                    clog(&format!("[CREAM] Expired orders for '{}'", expiry_supplier));
-                                    expiry_shared.write().storefronts
-                                        .insert(expiry_supplier.clone(), sf.clone());
+                                let mut products_migrated = false;
+                                for sp in sf.products.values_mut() {
+                                    if sp.product.migrate_legacy_category(now) {
+                                        sp.signature = expiry_km.sign_product(&sp.product);
+                                        sp.provenance = Some(
+                                            expiry_km.stamp(StateSection::Product, &sp.provenance_bytes()),
+                                        );
+                                        products_migrated = true;
+                                    }
+                                }
+                                if orders_changed || products_migrated {
+                                    tracing::info!(
+                                        supplier = %expiry_supplier,
+                                        expired = orders_changed,
+                                        migrated = products_migrated,
+                                        "Storefront maintenance",
+                                    );
+                                    expiry_shared.write().set_storefront(expiry_supplier.clone(), sf.clone());
                                     // Push to network via the internal request channel
                                     if let Some(sf_key) = expiry_sf_keys.get(&expiry_supplier) {
                                         let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                                        let update = ClientRequest::ContractOp(
-                                            ContractRequest::Update {
-                                                key: *sf_key,
-                                                data: UpdateData::State(
-                                                    State::from(sf_bytes),
-                                                ),
-                                            },
-                                        );
+                                        let update = request::update(*sf_key, sf_bytes);
                                         if let Err(e) = expiry_sender.send(update).await {
-                                            clog(&format!("[CREAM] ERROR: Failed to send expiry update: {:?}", e));
+                                            tracing::error!(error = ?e, "Failed to send expiry update");
                                         }
                                     }
                                 }
@@ -1310,9 +2150,15 @@ mod wasm_impl {
                 tracing::debug!("DeployStorefront handled via RegisterSupplier");
             }
 
+            NodeAction::SwitchNode => {
+                // Handled by the main loop, which owns the connection
+            }
+
             NodeAction::AddProduct {
                 name,
                 category,
+                subcategory,
+                attributes,
                 description,
                 price_curd,
                 quantity_total,
@@ -1321,11 +2167,14 @@ mod wasm_impl {
                 // This works whether the storefront was deployed by this tab (RegisterSupplier)
                 // or pre-populated by the test harness / another session.
                 let my_supplier_id = key_manager.user_id();
-                clog(&format!("[CREAM] AddProduct: looking up storefront for supplier {:?}", my_supplier_id));
+                tracing::info!(supplier_id = ?my_supplier_id, "AddProduct: looking up storefront");
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
-                    clog(&format!("[CREAM] AddProduct: sf_contract_keys has {} entries, directory has {} entries",
-                        sf_contract_keys.len(), state.directory.entries.len()));
+                    tracing::info!(
+                        sf_contract_keys = sf_contract_keys.len(),
+                        directory_entries = state.directory.entries.len(),
+                        "AddProduct: storefront lookup tables",
+                    );
                     // Look up by our supplier ID in the directory (most reliable —
                     // sf_contract_keys contains ALL storefronts, not just ours)
                     let result = state.directory.entries.get(&my_supplier_id)
@@ -1334,46 +2183,50 @@ mod wasm_impl {
                             sf_contract_keys.iter().next()
                                 .map(|(name, key)| (name.clone(), *key))
                         });
-                    clog(&format!("[CREAM] AddProduct: found storefront = {}", result.is_some()));
+                    tracing::info!(found = result.is_some(), "AddProduct: storefront lookup done");
                     result.unzip()
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found for supplier, can't add product");
+                    tracing::error!("No storefront found for supplier, can't add product");
                     return;
                 };
-                clog(&format!("[CREAM] AddProduct: supplier_name={}, sf_key={:?}", supplier_name, sf_key));
+                tracing::info!(supplier = %supplier_name, sf_key = ?sf_key, "AddProduct: storefront resolved");
 
                 // Build a product update with the existing storefront state
                 let now = chrono::Utc::now();
-                let product_id = ProductId(format!("p-{}", now.timestamp_millis()));
-                let cat = match category.as_str() {
-                    "Milk" => ProductCategory::Milk,
-                    "Cheese" => ProductCategory::Cheese,
-                    "Butter" => ProductCategory::Butter,
-                    "Cream" => ProductCategory::Cream,
-                    "Yogurt" => ProductCategory::Yogurt,
-                    "Kefir" => ProductCategory::Kefir,
-                    other => ProductCategory::Other(other.to_string()),
-                };
+                let nonce = platform::rand_u64();
+                let product_id = ProductId::derive(&key_manager.user_id(), &name, now, nonce);
+                let cat = ProductCategory::from_label(&category);
+                let subcategory = subcategory.and_then(|label| cat.subcategory_from_label(&label));
                 let product = Product {
                     id: product_id.clone(),
                     name,
                     description,
                     category: cat,
+                    subcategory,
+                    attributes,
                     price_curd,
                     quantity_total,
                     expiry_date: None,
                     updated_at: now,
                     created_at: now,
+                    low_stock: None,
+                    paused: false,
+                    special: None,
+                    wholesale: None,
                     extra: Default::default(),
                 };
-                let signature = key_manager.sign_product(&product);
-                let signed_product = SignedProduct {
+                let signature = signer.sign_product(&product).await;
+                let mut signed_product = SignedProduct {
                     product,
                     signature,
+                    provenance: None,
+                    price_history: Vec::new(),
                     extra: Default::default(),
                 };
+                signed_product.provenance =
+                    Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
 
                 // Get the current storefront state and add the product
                 let existing_sf = {
@@ -1387,25 +2240,91 @@ mod wasm_impl {
                 if let Some(mut sf) = existing_sf {
                     sf.products.insert(product_id.clone(), signed_product.clone());
                     let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                    clog(&format!("[CREAM] AddProduct: sending Update with {} products, {} bytes",
-                        sf.products.len(), sf_bytes.len()));
+                    tracing::info!(products = sf.products.len(), bytes = sf_bytes.len(), "AddProduct: sending Update");
 
                     let update =
-                        ClientRequest::ContractOp(ContractRequest::Update {
-                            key: sf_key,
-                            data: UpdateData::State(State::from(sf_bytes)),
-                        });
+                        request::update(sf_key, sf_bytes);
 
                     // Update local SharedState immediately so the supplier sees their product
-                    shared.write().storefronts.insert(supplier_name.clone(), sf);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to add product: {:?}", e));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to add product");
                     } else {
-                        clog("[CREAM] AddProduct: Update sent successfully");
+                        tracing::info!("AddProduct: Update sent successfully");
                     }
                 } else {
-                    clog(&format!("[CREAM] ERROR: Storefront state not found for {}", supplier_name));
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::ImportProducts { products } => {
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't import products");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                let Some(mut sf) = existing_sf else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let count = products.len();
+                for product in products {
+                    let signature = signer.sign_product(&product).await;
+                    let mut signed_product = match sf.products.get(&product.id) {
+                        Some(before) => {
+                            let mut updated = before.clone();
+                            updated.product = product;
+                            updated.signature = signature;
+                            updated.merge_price_history(before);
+                            updated
+                        }
+                        None => SignedProduct {
+                            product,
+                            signature,
+                            provenance: None,
+                            price_history: Vec::new(),
+                            extra: Default::default(),
+                        },
+                    };
+                    signed_product.provenance =
+                        Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
+                    sf.products.insert(signed_product.product.id.clone(), signed_product);
+                }
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                tracing::info!(
+                    imported = count,
+                    products = sf.products.len(),
+                    bytes = sf_bytes.len(),
+                    "ImportProducts: sending Update",
+                );
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to import products");
+                } else {
+                    tracing::info!("ImportProducts: Update sent successfully");
                 }
             }
 
@@ -1420,9 +2339,9 @@ mod wasm_impl {
                 deposit_tier,
                 price_per_unit,
                 collection_point,
+                delivery,
             } => {
-                clog(&format!("[CREAM] PlaceOrder on {}: {} x{} ({})",
-                    storefront_name, product_id, quantity, deposit_tier));
+                tracing::info!(storefront = %storefront_name, %product_id, quantity, %deposit_tier, "PlaceOrder");
 
                 // Find the storefront's contract key.
                 // Check sf_contract_keys first (populated from GetResponse),
@@ -1437,7 +2356,7 @@ mod wasm_impl {
                     });
 
                 let Some(sf_key) = sf_key else {
-                    clog(&format!("[CREAM] ERROR: No storefront key found for {}, can't place order", storefront_name));
+                    tracing::error!(storefront = %storefront_name, "No storefront key found, can't place order");
                     return;
                 };
 
@@ -1447,38 +2366,53 @@ mod wasm_impl {
                 };
 
                 let Some(mut sf) = existing_sf else {
-                    clog(&format!("[CREAM] ERROR: Storefront state not found for {}", storefront_name));
+                    tracing::error!(storefront = %storefront_name, "Storefront state not found");
                     return;
                 };
 
+                // The contract refuses orders on paused listings
+                if sf.products.get(&ProductId(product_id.clone())).is_some_and(|sp| sp.product.paused) {
+                    shared.write().push_toast(format!("{} has paused this product; order not placed.", storefront_name));
+                    return;
+                }
+                if let Some(sp) = sf.products.get(&ProductId(product_id.clone())).filter(|sp| !sp.product.accepts_quantity(quantity)) {
+                    let min = sp.product.wholesale.as_ref().map_or(1, |terms| terms.min_quantity);
+                    shared.write().push_toast(format!("{} sells this product wholesale in lots of at least {}; order not placed.", storefront_name, min));
+                    return;
+                }
+
                 // Parse deposit tier
                 let tier = match deposit_tier.as_str() {
                     "2-Day Reserve (10%)" => DepositTier::Reserve2Days,
                     "1-Week Reserve (20%)" => DepositTier::Reserve1Week,
                     "Full Payment (100%)" => DepositTier::FullPayment,
                     _ => {
-                        clog(&format!("[CREAM] ERROR: Unknown deposit tier: {}", deposit_tier));
+                        tracing::error!(%deposit_tier, "Unknown deposit tier");
                         return;
                     }
                 };
 
-                // Calculate pricing
+                // Calculate pricing: the listing's price now, in case a
+                // special started or ended since the form was opened, at the
+                // wholesale tier the quantity reaches
                 let now = chrono::Utc::now();
+                let price_per_unit = sf
+                    .products
+                    .get(&ProductId(product_id.clone()))
+                    .map_or(price_per_unit, |sp| sp.product.unit_price_for(quantity, now));
                 let total_price = price_per_unit * quantity as u64;
                 let deposit_amount = tier.calculate_deposit(total_price);
 
                 // Calculate reservation expiry
-                let expires_at = match tier {
-                    DepositTier::Reserve2Days => now + chrono::Duration::days(2),
-                    DepositTier::Reserve1Week => now + chrono::Duration::weeks(1),
-                    DepositTier::FullPayment => now + chrono::Duration::days(365),
-                };
+                let expires_at = now + tier.hold_period();
 
-                // Build the order
-                let order_id = OrderId(format!("o-{}", now.timestamp_millis()));
+                // Build the order, under an id derived from its content
+                let product_id = ProductId(product_id);
+                let nonce = platform::rand_u64();
+                let order_id = OrderId::derive(&key_manager.user_id(), &product_id, now, nonce);
                 let mut order = Order {
                     id: order_id.clone(),
-                    product_id: ProductId(product_id),
+                    product_id,
                     customer: key_manager.user_id(),
                     quantity,
                     deposit_tier: tier,
@@ -1489,31 +2423,67 @@ mod wasm_impl {
                     signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
                     escrow_token: None,
                     collection_point,
+                    delivery,
+                    provenance: None,
+                    receipt: None,
+                    amendments: Vec::new(),
+                    status_history: vec![StatusChange::new(
+                        OrderStatus::Reserved { expires_at },
+                        Some(key_manager.user_id()),
+                        now,
+                    )],
+                    cancellation_policy: sf.info.cancellation_policy,
+                    refund_amount: None,
+                    balance_attestation: None,
+                    nonce,
                     extra: Default::default(),
                 };
 
                 // Sign the order with the customer key
-                key_manager.sign_order(&mut order);
+                if let Err(e) = signer.sign_order(&mut order, &sf.info.owner).await {
+                    tracing::error!(error = %e, "Order refused by the signing policy");
+                    shared.write().push_toast(format!("Order not placed: {}", e));
+                    return;
+                }
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(&order)));
+
+                // Large orders carry an attestation of our ledger balance
+                if sf.info.attestation_threshold.is_some_and(|threshold| total_price > threshold) {
+                    let own_contract = shared.read().user_contract.clone();
+                    let Some(uc) = own_contract else {
+                        shared.write().push_toast(format!(
+                            "Order not placed: {} needs your balance attested, and your wallet hasn't loaded yet.",
+                            storefront_name
+                        ));
+                        return;
+                    };
+                    let attestation = signer.attest_balance(&uc).await;
+                    if !attestation.covers(&order) {
+                        shared.write().push_toast(format!(
+                            "Order not placed: your balance of {} CURD doesn't cover the {} CURD deposit.",
+                            attestation.balance, deposit_amount
+                        ));
+                        return;
+                    }
+                    order.balance_attestation = Some(attestation);
+                }
 
                 // Insert into storefront and send update
                 sf.orders.insert(order_id.clone(), order);
 
                 let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                clog(&format!("[CREAM] PlaceOrder: sending Update with {} orders, {} bytes",
-                    sf.orders.len(), sf_bytes.len()));
+                tracing::info!(orders = sf.orders.len(), bytes = sf_bytes.len(), "PlaceOrder: sending Update");
 
-                let update = ClientRequest::ContractOp(ContractRequest::Update {
-                    key: sf_key,
-                    data: UpdateData::State(State::from(sf_bytes)),
-                });
+                let update = request::update(sf_key, sf_bytes);
 
                 // Update local SharedState immediately
-                shared.write().storefronts.insert(storefront_name.clone(), sf);
+                let rollback = shared.write().set_storefront_optimistic(storefront_name.clone(), sf);
 
-                if let Err(e) = api.send(update).await {
-                    clog(&format!("[CREAM] ERROR: Failed to place order: {:?}", e));
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to place order");
                 } else {
-                    clog("[CREAM] PlaceOrder: Update sent successfully");
+                    tracing::info!("PlaceOrder: Update sent successfully");
 
                     // Record double-entry transfer: customer → root (deposit)
                     let customer_name = user_state.read().moniker.clone().unwrap_or_default();
@@ -1539,21 +2509,12 @@ mod wasm_impl {
                 };
 
                 if let Some(instance_id) = sf_key {
-                    let subscribe =
-                        ClientRequest::ContractOp(ContractRequest::Get {
-                            key: instance_id,
-                            return_contract_code: false,
-                            subscribe: true,
-                            blocking_subscribe: false,
-                        });
-
                     tracing::info!(
                         "Subscribing to storefront for {}",
                         supplier_name
                     );
-                    if let Err(e) = api.send(subscribe).await {
-                        tracing::error!("Failed to subscribe to storefront: {:?}", e);
-                    }
+                    let requests = router.subscriptions().want(instance_id, Interest::Storefront);
+                    send_follow(api, requests, "storefront").await;
                 } else {
                     tracing::warn!(
                         "No directory entry found for {}, can't subscribe",
@@ -1563,7 +2524,7 @@ mod wasm_impl {
             }
 
             NodeAction::UpdateSchedule { schedule } => {
-                clog("[CREAM] UpdateSchedule: updating opening hours");
+                tracing::info!("UpdateSchedule: updating opening hours");
                 let my_supplier_id = key_manager.user_id();
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
@@ -1584,7 +2545,7 @@ mod wasm_impl {
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found, can't update schedule");
+                    tracing::error!("No storefront found, can't update schedule");
                     return;
                 };
 
@@ -1599,35 +2560,26 @@ mod wasm_impl {
                     };
                     sf.info.schedule = Some(schedule);
                     sf.info.timezone = tz;
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
 
                     let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: sf_key,
-                        data: UpdateData::State(State::from(sf_bytes)),
-                    });
-                    shared
-                        .write()
-                        .storefronts
-                        .insert(supplier_name.clone(), sf);
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!(
-                            "[CREAM] ERROR: Failed to update schedule: {:?}",
-                            e
-                        ));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update schedule");
                     } else {
-                        clog("[CREAM] UpdateSchedule: sent successfully");
+                        tracing::info!("UpdateSchedule: sent successfully");
                     }
                 } else {
-                    clog(&format!(
-                        "[CREAM] ERROR: Storefront state not found for {}",
-                        supplier_name
-                    ));
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
                 }
             }
 
             NodeAction::CancelOrder { order_id } => {
-                clog(&format!("[CREAM] CancelOrder: {}", order_id));
+                tracing::info!(%order_id, "CancelOrder");
                 let my_supplier_id = key_manager.user_id();
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
@@ -1646,7 +2598,7 @@ mod wasm_impl {
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found, can't cancel order");
+                    tracing::error!("No storefront found, can't cancel order");
                     return;
                 };
 
@@ -1655,41 +2607,32 @@ mod wasm_impl {
                     let oid = OrderId(order_id.clone());
                     if let Some(order) = sf.orders.get_mut(&oid) {
                         if !order.status.can_transition_to(&OrderStatus::Cancelled) {
-                            clog(&format!(
-                                "[CREAM] ERROR: Cannot cancel order {} in status {}",
-                                order_id, order.status
-                            ));
+                            tracing::error!(%order_id, status = %order.status, "Cannot cancel order in this status");
                             return;
                         }
 
-                        // Capture refund info before mutating
-                        let deposit_amount = order.deposit_amount;
+                        // A supplier's cancellation refunds the whole deposit
                         let customer_vk = order.customer.0;
-
-                        order.status = OrderStatus::Cancelled;
+                        let deposit_amount = order.cancel(key_manager.user_id(), chrono::Utc::now());
+                        order.provenance =
+                            Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
 
                         let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                        let update = ClientRequest::ContractOp(ContractRequest::Update {
-                            key: sf_key,
-                            data: UpdateData::State(State::from(sf_bytes)),
-                        });
-                        shared
-                            .write()
-                            .storefronts
-                            .insert(supplier_name.clone(), sf);
+                        let update = request::update(sf_key, sf_bytes);
+                        let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
 
-                        if let Err(e) = api.send(update).await {
-                            clog(&format!(
-                                "[CREAM] ERROR: Failed to cancel order: {:?}",
-                                e
-                            ));
+                        if let Err(e) = api.send_optimistic(update, rollback).await {
+                            tracing::error!(error = ?e, "Failed to cancel order");
                         } else {
-                            clog("[CREAM] CancelOrder: sent successfully");
+                            tracing::info!("CancelOrder: sent successfully");
                         }
 
                         // Refund escrow deposit: root → customer's user contract
                         if deposit_amount > 0 {
-                            let customer_uc_params = UserContractParameters { owner: customer_vk };
+                            let customer_uc_params = UserContractParameters {
+                                owner: customer_vk,
+                                spending_key: None,
+                            };
                             let customer_params_bytes = serde_json::to_vec(&customer_uc_params).unwrap();
                             let customer_uc_contract = make_contract(
                                 USER_CONTRACT_WASM,
@@ -1705,27 +2648,18 @@ mod wasm_impl {
                                 "customer".to_string(),
                             ).await;
 
-                            clog(&format!(
-                                "[CREAM] CancelOrder: refunded {} CURD to customer",
-                                deposit_amount
-                            ));
+                            tracing::info!(amount = deposit_amount, "CancelOrder: refunded CURD to customer");
                         }
                     } else {
-                        clog(&format!(
-                            "[CREAM] ERROR: Order {} not found in storefront",
-                            order_id
-                        ));
+                        tracing::error!(%order_id, "Order not found in storefront");
                     }
                 } else {
-                    clog(&format!(
-                        "[CREAM] ERROR: Storefront state not found for {}",
-                        supplier_name
-                    ));
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
                 }
             }
 
             NodeAction::FulfillOrder { order_id } => {
-                clog(&format!("[CREAM] FulfillOrder: {}", order_id));
+                tracing::info!(%order_id, "FulfillOrder");
                 let my_supplier_id = key_manager.user_id();
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
@@ -1744,7 +2678,7 @@ mod wasm_impl {
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found, can't fulfill order");
+                    tracing::error!("No storefront found, can't fulfill order");
                     return;
                 };
 
@@ -1752,33 +2686,47 @@ mod wasm_impl {
                 if let Some(mut sf) = existing_sf {
                     let oid = OrderId(order_id.clone());
                     if let Some(order) = sf.orders.get_mut(&oid) {
-                        if !order.status.can_transition_to(&OrderStatus::Fulfilled) {
-                            clog(&format!(
-                                "[CREAM] ERROR: Cannot fulfill order {} in status {}",
-                                order_id, order.status
-                            ));
+                        if order.pending_amendment().is_some() {
+                            tracing::error!(%order_id, "Order has a change awaiting your answer");
                             return;
                         }
-                        let deposit_amount = order.deposit_amount;
-                        order.status = OrderStatus::Fulfilled;
+                        // Two-step handshake: propose first; confirm ourselves only
+                        // once the customer has let the confirmation window lapse.
+                        let now = chrono::Utc::now();
+                        let settle = match order.status.clone() {
+                            OrderStatus::FulfillmentProposed { proposed_at } => {
+                                if now < FulfillmentReceipt::auto_confirm_after(proposed_at) {
+                                    tracing::info!(%order_id, "FulfillOrder: awaiting customer confirmation");
+                                    return;
+                                }
+                                order.receipt = Some(key_manager.fulfillment_receipt(order, proposed_at, true));
+                                order.set_status(OrderStatus::Fulfilled, Some(key_manager.user_id()), now);
+                                true
+                            }
+                            status if status.can_transition_to(&OrderStatus::FulfillmentProposed { proposed_at: now }) => {
+                                order.set_status(OrderStatus::FulfillmentProposed { proposed_at: now }, Some(key_manager.user_id()), now);
+                                false
+                            }
+                            status => {
+                                tracing::error!(%order_id, %status, "Cannot fulfill order in this status");
+                                return;
+                            }
+                        };
+                        let deposit_amount = order.escrowed_deposit();
+                        order.provenance =
+                            Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
 
                         let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                        let update = ClientRequest::ContractOp(ContractRequest::Update {
-                            key: sf_key,
-                            data: UpdateData::State(State::from(sf_bytes)),
-                        });
-                        shared
-                            .write()
-                            .storefronts
-                            .insert(supplier_name.clone(), sf);
+                        let update = request::update(sf_key, sf_bytes);
+                        let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
 
-                        if let Err(e) = api.send(update).await {
-                            clog(&format!(
-                                "[CREAM] ERROR: Failed to fulfill order: {:?}",
-                                e
-                            ));
+                        if let Err(e) = api.send_optimistic(update, rollback).await {
+                            tracing::error!(error = ?e, "Failed to fulfill order");
                         } else {
-                            clog("[CREAM] FulfillOrder: sent successfully");
+                            tracing::info!("FulfillOrder: sent successfully");
+                        }
+                        if !settle {
+                            return;
                         }
 
                         // Settle escrow: transfer deposit from root → supplier's user contract
@@ -1791,39 +2739,27 @@ mod wasm_impl {
                                 api,
                                 uc_key,
                                 deposit_amount,
-                                format!("Escrow settlement for order {}", order_id),
+                                settlement_description(&order_id),
                                 supplier_name.clone(),
                             ).await;
-                            clog(&format!(
-                                "[CREAM] FulfillOrder: settled {} CURD escrow to {}",
-                                deposit_amount, supplier_name
-                            ));
+                            tracing::info!(
+                                amount = deposit_amount,
+                                supplier = %supplier_name,
+                                "FulfillOrder: settled CURD escrow",
+                            );
                         } else {
-                            clog("[CREAM] WARNING: No supplier user contract key, escrow not settled");
+                            tracing::warn!("No supplier user contract key, escrow not settled");
                         }
                     } else {
-                        clog(&format!(
-                            "[CREAM] ERROR: Order {} not found in storefront",
-                            order_id
-                        ));
+                        tracing::error!(%order_id, "Order not found in storefront");
                     }
                 } else {
-                    clog(&format!(
-                        "[CREAM] ERROR: Storefront state not found for {}",
-                        supplier_name
-                    ));
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
                 }
             }
 
-            NodeAction::UpdateProduct {
-                product_id,
-                price_curd,
-                quantity_total,
-            } => {
-                clog(&format!(
-                    "[CREAM] UpdateProduct: {} price={} qty={}",
-                    product_id, price_curd, quantity_total
-                ));
+            NodeAction::FulfillOrders { order_ids } => {
+                tracing::info!(orders = order_ids.len(), "FulfillOrders");
                 let my_supplier_id = key_manager.user_id();
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
@@ -1842,49 +2778,749 @@ mod wasm_impl {
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found, can't update product");
+                    tracing::error!("No storefront found, can't fulfill orders");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
                     return;
                 };
 
-                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
-                if let Some(mut sf) = existing_sf {
-                    let pid = ProductId(product_id.clone());
-                    if let Some(signed_product) = sf.products.get_mut(&pid) {
-                        signed_product.product.price_curd = price_curd;
-                        signed_product.product.quantity_total = quantity_total;
-                        signed_product.product.updated_at = chrono::Utc::now();
-                        signed_product.signature =
-                            key_manager.sign_product(&signed_product.product);
-
-                        let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                        let update = ClientRequest::ContractOp(ContractRequest::Update {
-                            key: sf_key,
-                            data: UpdateData::State(State::from(sf_bytes)),
-                        });
-                        shared
-                            .write()
-                            .storefronts
-                            .insert(supplier_name.clone(), sf);
-
-                        if let Err(e) = api.send(update).await {
-                            clog(&format!(
-                                "[CREAM] ERROR: Failed to update product: {:?}",
-                                e
-                            ));
-                        } else {
-                            clog("[CREAM] UpdateProduct: sent successfully");
-                        }
-                    } else {
-                        clog(&format!(
-                            "[CREAM] ERROR: Product {} not found in storefront",
-                            product_id
-                        ));
-                    }
-                } else {
-                    clog(&format!(
-                        "[CREAM] ERROR: Storefront state not found for {}",
-                        supplier_name
-                    ));
+                // Same handshake as FulfillOrder, order by order; ones that
+                // can't move yet are skipped rather than failing the batch.
+                let now = chrono::Utc::now();
+                let mut changed = 0;
+                let mut settlements = Vec::new();
+                for order_id in order_ids {
+                    let Some(order) = sf.orders.get_mut(&OrderId(order_id.clone())) else {
+                        tracing::warn!(%order_id, "Order not found in storefront");
+                        continue;
+                    };
+                    if order.pending_amendment().is_some() {
+                        tracing::warn!(%order_id, "Order has a change awaiting your answer, skipped");
+                        continue;
+                    }
+                    match order.status.clone() {
+                        OrderStatus::FulfillmentProposed { proposed_at } => {
+                            if now < FulfillmentReceipt::auto_confirm_after(proposed_at) {
+                                continue;
+                            }
+                            order.receipt = Some(key_manager.fulfillment_receipt(order, proposed_at, true));
+                            order.set_status(OrderStatus::Fulfilled, Some(key_manager.user_id()), now);
+                            settlements.push((order.escrowed_deposit(), settlement_description(&order_id)));
+                        }
+                        status if status.can_transition_to(&OrderStatus::FulfillmentProposed { proposed_at: now }) => {
+                            order.set_status(OrderStatus::FulfillmentProposed { proposed_at: now }, Some(key_manager.user_id()), now);
+                        }
+                        status => {
+                            tracing::warn!(%order_id, %status, "Cannot fulfill order in this status, skipped");
+                            continue;
+                        }
+                    }
+                    order.provenance = Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+                    changed += 1;
+                }
+                if changed == 0 {
+                    tracing::info!("FulfillOrders: nothing to update");
+                    return;
+                }
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to fulfill orders");
+                } else {
+                    tracing::info!(orders = changed, "FulfillOrders: orders updated");
+                }
+                if settlements.is_empty() {
+                    return;
+                }
+
+                // Settle the confirmed orders' escrows in one root ledger update
+                let supplier_uc_key = shared.read().directory.entries
+                    .get(&my_supplier_id)
+                    .and_then(|entry| entry.user_contract_key);
+                if let Some(uc_key) = supplier_uc_key {
+                    let total: u64 = settlements.iter().map(|(amount, _)| amount).sum();
+                    let count = settlements.len();
+                    wallet.settle_escrows_to_supplier(api, uc_key, settlements, supplier_name.clone()).await;
+                    tracing::info!(
+                        escrows = count,
+                        amount = total,
+                        supplier = %supplier_name,
+                        "FulfillOrders: settled CURD escrows",
+                    );
+                } else {
+                    tracing::warn!("No supplier user contract key, escrows not settled");
+                }
+            }
+
+            NodeAction::ConfirmFulfillment { supplier_name, order_id } => {
+                tracing::info!(%order_id, supplier = %supplier_name, "ConfirmFulfillment");
+                let Some(sf_key) = sf_contract_keys.get(&supplier_name).copied() else {
+                    tracing::error!(supplier = %supplier_name, "No storefront key");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let oid = OrderId(order_id.clone());
+                let Some(order) = sf.orders.get_mut(&oid) else {
+                    tracing::error!(%order_id, "Order not found in storefront");
+                    return;
+                };
+                let OrderStatus::FulfillmentProposed { proposed_at } = order.status else {
+                    tracing::error!(%order_id, status = %order.status, "Order has no pending fulfillment");
+                    return;
+                };
+                if order.customer != key_manager.user_id() {
+                    tracing::error!("Only the ordering customer can confirm fulfillment");
+                    return;
+                }
+
+                let deposit_amount = order.escrowed_deposit();
+                order.receipt = Some(key_manager.fulfillment_receipt(order, proposed_at, false));
+                order.set_status(OrderStatus::Fulfilled, Some(key_manager.user_id()), chrono::Utc::now());
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+
+                let supplier_uc_key = shared
+                    .read()
+                    .directory
+                    .entries
+                    .get(&sf.info.owner)
+                    .and_then(|entry| entry.user_contract_key);
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to confirm fulfillment");
+                    return;
+                }
+
+                // The receipt releases the escrow: root → supplier's user contract
+                if let Some(uc_key) = supplier_uc_key {
+                    wallet.settle_escrow_to_supplier(
+                        api,
+                        uc_key,
+                        deposit_amount,
+                        settlement_description(&order_id),
+                        supplier_name.clone(),
+                    ).await;
+                    tracing::info!(
+                        amount = deposit_amount,
+                        supplier = %supplier_name,
+                        "ConfirmFulfillment: settled CURD escrow",
+                    );
+                } else {
+                    tracing::warn!("No supplier user contract key, escrow not settled");
+                }
+            }
+
+            NodeAction::ConfirmPickup { token } => {
+                let token = match PickupToken::decode(&token) {
+                    Ok(token) => token,
+                    Err(e) => {
+                        tracing::error!(error = %e, "ConfirmPickup: invalid pickup token");
+                        return;
+                    }
+                };
+                tracing::info!(order_id = %token.order_id.0, "ConfirmPickup");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't confirm pickup");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let Some(order) = sf.orders.get_mut(&token.order_id) else {
+                    tracing::error!(order_id = %token.order_id.0, "Order not found in storefront");
+                    return;
+                };
+                if !token.verify(order) {
+                    tracing::error!("Pickup token is not signed by the ordering customer");
+                    return;
+                }
+                if order.pending_amendment().is_some() {
+                    tracing::error!(order_id = %order.id.0, "Order has a change awaiting your answer");
+                    return;
+                }
+                if !matches!(
+                    order.status,
+                    OrderStatus::Reserved { .. } | OrderStatus::Paid | OrderStatus::FulfillmentProposed { .. }
+                ) {
+                    tracing::error!(
+                        order_id = %order.id.0,
+                        status = %order.status,
+                        "Cannot hand over order in this status",
+                    );
+                    return;
+                }
+
+                // The token is the customer's receipt: straight to Fulfilled
+                let order_id = order.id.0.clone();
+                let deposit_amount = order.escrowed_deposit();
+                order.receipt = Some(token.into_receipt());
+                order.set_status(OrderStatus::Fulfilled, Some(key_manager.user_id()), chrono::Utc::now());
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to confirm pickup");
+                    return;
+                }
+
+                let supplier_uc_key = shared.read().directory.entries
+                    .get(&my_supplier_id)
+                    .and_then(|entry| entry.user_contract_key);
+                if let Some(uc_key) = supplier_uc_key {
+                    wallet.settle_escrow_to_supplier(
+                        api,
+                        uc_key,
+                        deposit_amount,
+                        settlement_description(&order_id),
+                        supplier_name.clone(),
+                    ).await;
+                    tracing::info!(
+                        amount = deposit_amount,
+                        supplier = %supplier_name,
+                        "ConfirmPickup: settled CURD escrow",
+                    );
+                } else {
+                    tracing::warn!("No supplier user contract key, escrow not settled");
+                }
+            }
+
+            NodeAction::AmendOrder { supplier_name, order_id, quantity, collection_point } => {
+                tracing::info!(%order_id, supplier = %supplier_name, quantity, "AmendOrder");
+                let Some(sf_key) = sf_contract_keys.get(&supplier_name).copied() else {
+                    tracing::error!(supplier = %supplier_name, "No storefront key");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let oid = OrderId(order_id.clone());
+                let Some(order) = sf.orders.get(&oid) else {
+                    tracing::error!(%order_id, "Order not found in storefront");
+                    return;
+                };
+                if order.customer != key_manager.user_id() {
+                    tracing::error!("Only the ordering customer can change an order");
+                    return;
+                }
+                if !order.can_amend() {
+                    tracing::error!(
+                        %order_id,
+                        status = %order.status,
+                        "Order can't be changed in this status, or a change is pending",
+                    );
+                    return;
+                }
+                if quantity == 0 {
+                    tracing::error!("Quantity must be at least 1 — cancel the order instead");
+                    return;
+                }
+                // Only the extra units need to be in stock
+                let extra = quantity.saturating_sub(order.current_quantity());
+                if extra > sf.available_quantity(&order.product_id) {
+                    tracing::error!(
+                        available = sf.available_quantity(&order.product_id),
+                        "Not that many more available",
+                    );
+                    return;
+                }
+
+                let mut amendment = order.amendment(quantity, collection_point, chrono::Utc::now());
+                key_manager.sign_amendment(order, &mut amendment);
+                // The customer escrows any deposit increase now; the supplier
+                // refunds it if they decline.
+                let top_up = amendment.deposit_amount.saturating_sub(order.current_deposit());
+
+                let order = sf.orders.get_mut(&oid).expect("order checked above");
+                order.amendments.push(amendment);
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to change order");
+                    return;
+                }
+                tracing::info!("AmendOrder: sent successfully");
+
+                if top_up > 0 {
+                    let customer_name = user_state.read().moniker.clone().unwrap_or_default();
+                    wallet.transfer_to_root(
+                        api,
+                        top_up,
+                        format!("Deposit top-up: changed order {}", order_id),
+                        customer_name,
+                    ).await;
+                }
+            }
+
+            NodeAction::WithdrawOrder { supplier_name, order_id } => {
+                tracing::info!(%order_id, supplier = %supplier_name, "WithdrawOrder");
+                let Some(sf_key) = sf_contract_keys.get(&supplier_name).copied() else {
+                    tracing::error!(supplier = %supplier_name, "No storefront key");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let oid = OrderId(order_id.clone());
+                let Some(order) = sf.orders.get_mut(&oid) else {
+                    tracing::error!(%order_id, "Order not found in storefront");
+                    return;
+                };
+                if order.customer != key_manager.user_id() {
+                    tracing::error!("Only the ordering customer can withdraw an order");
+                    return;
+                }
+                if !order.status.can_transition_to(&OrderStatus::Cancelled) {
+                    tracing::error!(%order_id, status = %order.status, "Cannot cancel order in this status");
+                    return;
+                }
+
+                let deposit = order.escrowed_deposit();
+                let refund = order.cancel(key_manager.user_id(), chrono::Utc::now());
+                let penalty = deposit - refund;
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+
+                let supplier_uc_key = shared
+                    .read()
+                    .directory
+                    .entries
+                    .get(&sf.info.owner)
+                    .and_then(|entry| entry.user_contract_key);
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to cancel order");
+                    return;
+                }
+                tracing::info!("WithdrawOrder: sent successfully");
+
+                // Escrow splits as the policy says: refund → us, penalty → supplier
+                if refund > 0 {
+                    let customer_name = user_state.read().moniker.clone().unwrap_or_default();
+                    wallet.transfer_from_root(
+                        api,
+                        refund,
+                        format!("Escrow refund: cancelled order {}", order_id),
+                        customer_name,
+                    ).await;
+                }
+                if penalty > 0 {
+                    if let Some(uc_key) = supplier_uc_key {
+                        wallet.settle_escrow_to_supplier(
+                            api,
+                            uc_key,
+                            penalty,
+                            format!("Cancellation penalty: order {}", order_id),
+                            supplier_name.clone(),
+                        ).await;
+                    } else {
+                        tracing::warn!("No supplier user contract key, penalty not settled");
+                    }
+                }
+                tracing::info!(
+                    refund,
+                    penalty,
+                    supplier = %supplier_name,
+                    "WithdrawOrder: refunded CURD, penalty paid to supplier",
+                );
+            }
+
+            NodeAction::AnswerAmendment { order_id, accept } => {
+                tracing::info!(%order_id, accept, "AnswerAmendment");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't answer order change");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let oid = OrderId(order_id.clone());
+                let Some(order) = sf.orders.get_mut(&oid) else {
+                    tracing::error!(%order_id, "Order not found in storefront");
+                    return;
+                };
+                let Some(pending) = order.pending_amendment().cloned() else {
+                    tracing::error!(%order_id, "Order has no change to answer");
+                    return;
+                };
+
+                // What the customer has escrowed, and what they owe once answered
+                let escrowed = order.escrowed_deposit();
+                let ack = key_manager.amendment_ack(order, &pending, accept);
+                if let Some(last) = order.amendments.last_mut() {
+                    last.acknowledgement = Some(ack);
+                }
+                let refund = escrowed.saturating_sub(order.current_deposit());
+                let customer_vk = order.customer.0;
+                order.provenance =
+                    Some(key_manager.stamp(StateSection::Order, &order_provenance_bytes(order)));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to answer order change");
+                    return;
+                }
+                tracing::info!("AnswerAmendment: sent successfully");
+
+                // Refund deposit the customer no longer owes: root → customer's user contract
+                if refund > 0 {
+                    let customer_uc_params = UserContractParameters {
+                        owner: customer_vk,
+                        spending_key: None,
+                    };
+                    let customer_params_bytes = serde_json::to_vec(&customer_uc_params).unwrap();
+                    let customer_uc_contract = make_contract(
+                        USER_CONTRACT_WASM,
+                        Parameters::from(customer_params_bytes),
+                    );
+                    wallet.transfer_from_root_to_third_party(
+                        api,
+                        customer_uc_contract.key(),
+                        refund,
+                        format!("Escrow refund: changed order {}", order_id),
+                        "customer".to_string(),
+                    ).await;
+                    tracing::info!(refund, "AnswerAmendment: refunded CURD to customer");
+                }
+            }
+
+            NodeAction::PostOrderMessage { supplier_name, order_id, body, reply_to, attachment } => {
+                tracing::info!(%order_id, supplier = %supplier_name, "PostOrderMessage");
+                let body = body.trim().to_string();
+                if (body.is_empty() && attachment.is_none()) || body.len() > MAX_MESSAGE_BODY {
+                    tracing::error!(max_bytes = MAX_MESSAGE_BODY, "Message must be between 1 byte and the maximum");
+                    return;
+                }
+                if attachment.as_ref().is_some_and(|a| !a.is_valid()) {
+                    tracing::error!(
+                        max_bytes = MAX_ATTACHMENT_BYTES,
+                        "Attachments must be named, typed and within the size limit",
+                    );
+                    return;
+                }
+                let Some(sf_key) = sf_contract_keys.get(&supplier_name).copied() else {
+                    tracing::error!(supplier = %supplier_name, "No storefront key");
+                    return;
+                };
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+
+                let oid = OrderId(order_id.clone());
+                let Some(order) = sf.orders.get(&oid) else {
+                    tracing::error!(%order_id, "Order not found in storefront");
+                    return;
+                };
+                if !may_post(order, &sf.supplier_keys(&sf.info.owner.0), &key_manager.user_id()) {
+                    tracing::error!("Only the order's customer and supplier can post to its thread");
+                    return;
+                }
+
+                let attached = sf.threads.get(&oid).map_or(0, |t| {
+                    t.messages.values().filter(|m| m.attachment.is_some()).count()
+                });
+                if attachment.is_some() && attached >= MAX_THREAD_ATTACHMENTS {
+                    tracing::error!(max = MAX_THREAD_ATTACHMENTS, "Too many attachments in the thread");
+                    return;
+                }
+
+                let message = key_manager.order_message(oid.clone(), body, reply_to.map(MessageId), attachment);
+                sf.threads.entry(oid).or_default().insert(message);
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to post order message");
+                } else {
+                    tracing::info!("PostOrderMessage: sent successfully");
+                }
+            }
+
+            NodeAction::UpdateProduct {
+                product_id,
+                price_curd,
+                quantity_total,
+            } => {
+                tracing::info!(%product_id, price = price_curd, quantity = quantity_total, "UpdateProduct");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update product");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                if let Some(mut sf) = existing_sf {
+                    let pid = ProductId(product_id.clone());
+                    if let Some(signed_product) = sf.products.get_mut(&pid) {
+                        let before = signed_product.clone();
+                        signed_product.product.price_curd = price_curd;
+                        signed_product.product.quantity_total = quantity_total;
+                        // A special is only valid below the listed price
+                        if signed_product.product.special.is_some_and(|special| !special.is_valid(price_curd)) {
+                            signed_product.product.special = None;
+                        }
+                        signed_product.product.updated_at = chrono::Utc::now();
+                        signed_product.signature = signer.sign_product(&signed_product.product).await;
+                        signed_product.merge_price_history(&before);
+                        signed_product.provenance = Some(
+                            key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()),
+                        );
+
+                        let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                        let update = request::update(sf_key, sf_bytes);
+                        let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                        if let Err(e) = api.send_optimistic(update, rollback).await {
+                            tracing::error!(error = ?e, "Failed to update product");
+                        } else {
+                            tracing::info!("UpdateProduct: sent successfully");
+                        }
+                    } else {
+                        tracing::error!(%product_id, "Product not found in storefront");
+                    }
+                } else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdateStockControls { product_id, low_stock, paused } => {
+                tracing::info!(%product_id, ?low_stock, paused, "UpdateStockControls");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update stock controls");
+                    return;
+                };
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                let Some(mut sf) = existing_sf else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+                let Some(signed_product) = sf.products.get_mut(&ProductId(product_id.clone())) else {
+                    tracing::error!(%product_id, "Product not found in storefront");
+                    return;
+                };
+                if signed_product.product.low_stock == low_stock && signed_product.product.paused == paused {
+                    return;
+                }
+                signed_product.product.low_stock = low_stock;
+                signed_product.product.paused = paused;
+                signed_product.product.updated_at = chrono::Utc::now();
+                signed_product.signature = signer.sign_product(&signed_product.product).await;
+                signed_product.provenance =
+                    Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name, sf);
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to update stock controls");
+                }
+            }
+
+            NodeAction::UpdateSpecial { product_id, special } => {
+                tracing::info!(%product_id, ?special, "UpdateSpecial");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update special");
+                    return;
+                };
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                let Some(mut sf) = existing_sf else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+                let Some(signed_product) = sf.products.get_mut(&ProductId(product_id.clone())) else {
+                    tracing::error!(%product_id, "Product not found in storefront");
+                    return;
+                };
+                if special.is_some_and(|special| !special.is_valid(signed_product.product.price_curd)) {
+                    shared.write().push_toast("A special must be cheaper than the listed price and end after it starts.".to_string());
+                    return;
+                }
+                if signed_product.product.special == special {
+                    return;
+                }
+                signed_product.product.special = special;
+                signed_product.product.updated_at = chrono::Utc::now();
+                signed_product.signature = signer.sign_product(&signed_product.product).await;
+                signed_product.provenance =
+                    Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name, sf);
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to update special");
+                }
+            }
+
+            NodeAction::UpdateWholesale { product_id, wholesale } => {
+                tracing::info!(%product_id, ?wholesale, "UpdateWholesale");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update wholesale terms");
+                    return;
+                };
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                let Some(mut sf) = existing_sf else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+                let Some(signed_product) = sf.products.get_mut(&ProductId(product_id.clone())) else {
+                    tracing::error!(%product_id, "Product not found in storefront");
+                    return;
+                };
+                if wholesale.as_ref().is_some_and(|terms| !terms.is_valid(signed_product.product.price_curd)) {
+                    shared.write().push_toast(
+                        "Wholesale tiers must start above the minimum quantity and get cheaper than the listed price as the quantity rises.".to_string(),
+                    );
+                    return;
+                }
+                if signed_product.product.wholesale == wholesale {
+                    return;
+                }
+                signed_product.product.wholesale = wholesale;
+                signed_product.product.updated_at = chrono::Utc::now();
+                signed_product.signature = signer.sign_product(&signed_product.product).await;
+                signed_product.provenance =
+                    Some(key_manager.stamp(StateSection::Product, &signed_product.provenance_bytes()));
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name, sf);
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to update wholesale terms");
                 }
             }
 
@@ -1892,8 +3528,9 @@ mod wasm_impl {
                 phone,
                 email,
                 address,
+                private,
             } => {
-                clog("[CREAM] UpdateContactDetails: updating contact info");
+                tracing::info!("UpdateContactDetails: updating contact info");
                 let my_supplier_id = key_manager.user_id();
                 let (supplier_name, sf_key) = {
                     let state = shared.read();
@@ -1912,39 +3549,344 @@ mod wasm_impl {
                 };
 
                 let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
-                    clog("[CREAM] ERROR: No storefront found, can't update contact details");
+                    tracing::error!("No storefront found, can't update contact details");
                     return;
                 };
 
                 let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
                 if let Some(mut sf) = existing_sf {
-                    sf.info.phone = phone;
-                    sf.info.email = email;
-                    sf.info.address = address;
+                    let details = ContactDetails { phone, email, address, extra: Default::default() };
+                    if private {
+                        let recipients = active_recipients(sf.orders.values());
+                        sf.info.sealed_contact =
+                            (!details.is_empty()).then(|| key_manager.seal_contact(&details, &recipients));
+                        sf.info.phone = None;
+                        sf.info.email = None;
+                        sf.info.address = None;
+                    } else {
+                        sf.info.sealed_contact = None;
+                        sf.info.phone = details.phone;
+                        sf.info.email = details.email;
+                        sf.info.address = details.address;
+                    }
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
 
                     let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: sf_key,
-                        data: UpdateData::State(State::from(sf_bytes)),
-                    });
-                    shared
-                        .write()
-                        .storefronts
-                        .insert(supplier_name.clone(), sf);
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!(
-                            "[CREAM] ERROR: Failed to update contact details: {:?}",
-                            e
-                        ));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update contact details");
                     } else {
-                        clog("[CREAM] UpdateContactDetails: sent successfully");
+                        tracing::info!("UpdateContactDetails: sent successfully");
                     }
                 } else {
-                    clog(&format!(
-                        "[CREAM] ERROR: Storefront state not found for {}",
-                        supplier_name
-                    ));
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdateDeliveryZones { zones } => {
+                tracing::info!(zones = zones.len(), "UpdateDeliveryZones");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update delivery zones");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                if let Some(mut sf) = existing_sf {
+                    sf.info.delivery_zones = zones;
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
+
+                    let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update delivery zones");
+                    } else {
+                        tracing::info!("UpdateDeliveryZones: sent successfully");
+                    }
+                } else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdatePickupLocations { locations } => {
+                tracing::info!(locations = locations.len(), "UpdatePickupLocations");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update pickup locations");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                if let Some(mut sf) = existing_sf {
+                    sf.info.pickup_locations = locations;
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
+
+                    let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update pickup locations");
+                    } else {
+                        tracing::info!("UpdatePickupLocations: sent successfully");
+                    }
+                } else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdateCancellationPolicy { policy } => {
+                tracing::info!(?policy, "UpdateCancellationPolicy");
+                if policy.is_some_and(|p| !p.is_valid()) {
+                    tracing::error!("A cancellation penalty can't exceed 100%");
+                    return;
+                }
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update cancellation policy");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                if let Some(mut sf) = existing_sf {
+                    sf.info.cancellation_policy = policy;
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
+
+                    let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update cancellation policy");
+                    } else {
+                        tracing::info!("UpdateCancellationPolicy: sent successfully");
+                    }
+                } else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdateAttestationThreshold { threshold } => {
+                tracing::info!(?threshold, "UpdateAttestationThreshold");
+                let my_supplier_id = key_manager.user_id();
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update attestation threshold");
+                    return;
+                };
+
+                let existing_sf = shared.read().storefronts.get(&supplier_name).cloned();
+                if let Some(mut sf) = existing_sf {
+                    sf.info.attestation_threshold = threshold;
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
+
+                    let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                    let update = request::update(sf_key, sf_bytes);
+                    let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update attestation threshold");
+                    } else {
+                        tracing::info!("UpdateAttestationThreshold: sent successfully");
+                    }
+                } else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                }
+            }
+
+            NodeAction::UpdateStaff { key, name, permissions, revoked } => {
+                tracing::info!(%name, %key, revoked, "UpdateStaff");
+                let my_supplier_id = key_manager.user_id();
+                if key == my_supplier_id {
+                    tracing::error!("The storefront owner can't be added as staff");
+                    return;
+                }
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update staff");
+                    return;
+                };
+
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+                if sf.info.owner != my_supplier_id {
+                    tracing::error!("Only the storefront owner can change its staff");
+                    return;
+                }
+
+                let mut grant = cream_common::staff::StaffGrant {
+                    key: key.clone(),
+                    name,
+                    permissions: permissions.into_iter().collect(),
+                    updated_at: chrono::Utc::now(),
+                    revoked,
+                    signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                    extra: Default::default(),
+                };
+                key_manager.sign_staff_grant(&mut grant);
+                sf.staff.insert(key, grant);
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to update staff");
+                } else {
+                    tracing::info!("UpdateStaff: sent successfully");
+                }
+            }
+
+            NodeAction::UpdateBlocklist { customer, blocked, appeal_note } => {
+                tracing::info!(%customer, blocked, "UpdateBlocklist");
+                let my_supplier_id = key_manager.user_id();
+                if customer == my_supplier_id {
+                    tracing::error!("The storefront owner can't block themselves");
+                    return;
+                }
+                let (supplier_name, sf_key) = {
+                    let state = shared.read();
+                    state
+                        .directory
+                        .entries
+                        .get(&my_supplier_id)
+                        .map(|entry| (entry.name.clone(), entry.storefront_key))
+                        .or_else(|| {
+                            sf_contract_keys
+                                .iter()
+                                .next()
+                                .map(|(name, key)| (name.clone(), *key))
+                        })
+                        .unzip()
+                };
+
+                let (Some(supplier_name), Some(sf_key)) = (supplier_name, sf_key) else {
+                    tracing::error!("No storefront found, can't update blocklist");
+                    return;
+                };
+
+                let Some(mut sf) = shared.read().storefronts.get(&supplier_name).cloned() else {
+                    tracing::error!(supplier = %supplier_name, "Storefront state not found");
+                    return;
+                };
+                if sf.info.owner != my_supplier_id {
+                    tracing::error!("Only the storefront owner can change its blocklist");
+                    return;
+                }
+
+                let mut entry = cream_common::blocklist::BlockEntry {
+                    customer: customer.clone(),
+                    blocked,
+                    appeal_note,
+                    updated_at: chrono::Utc::now(),
+                    signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                    extra: Default::default(),
+                };
+                key_manager.sign_block_entry(&mut entry);
+                sf.blocklist.insert(customer, entry);
+
+                let sf_bytes = serde_json::to_vec(&sf).unwrap();
+                let update = request::update(sf_key, sf_bytes);
+                let rollback = shared.write().set_storefront_optimistic(supplier_name.clone(), sf);
+
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "Failed to update blocklist");
+                } else {
+                    tracing::info!("UpdateBlocklist: sent successfully");
                 }
             }
 
@@ -1954,11 +3896,16 @@ mod wasm_impl {
                 current_supplier,
                 invited_by,
             } => {
-                clog(&format!("[CREAM] RegisterUser: {} (origin={}, current={}, invited_by={})",
-                    name, origin_supplier, current_supplier, invited_by));
+                tracing::info!(
+                    %name,
+                    origin = %origin_supplier,
+                    current = %current_supplier,
+                    %invited_by,
+                    "RegisterUser",
+                );
 
                 let owner_key = key_manager.verifying_key();
-                let uc_params = UserContractParameters { owner: owner_key };
+                let uc_params = UserContractParameters { owner: owner_key, spending_key: None };
                 let params_bytes = serde_json::to_vec(&uc_params).unwrap();
                 let uc_contract = make_contract(
                     USER_CONTRACT_WASM,
@@ -1979,31 +3926,32 @@ mod wasm_impl {
                     checkpoint_tx_count: 0,
                     checkpoint_at: None,
                     pruned_lightning_hashes: Default::default(),
+                    checkpoint_proof: None,
+                    spending_key_debits: Default::default(),
+                    mint_records: Vec::new(),
+                    settlement_statements: Vec::new(),
+                    vouchers: Vec::new(),
                     ledger: Vec::new(),
                     next_tx_id: 0,
+                    seq: 0,
                     updated_at: now,
                     signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
                     extra: Default::default(),
                 };
                 let uc_state_bytes = serde_json::to_vec(&uc_state).unwrap();
 
-                let put_uc = ClientRequest::ContractOp(ContractRequest::Put {
-                    contract: uc_contract,
-                    state: WrappedState::new(uc_state_bytes),
-                    related_contracts: RelatedContracts::default(),
-                    subscribe: true,
-                    blocking_subscribe: false,
-                });
+                let put_uc = request::put(uc_contract, uc_state_bytes, true);
 
-                clog(&format!("[CREAM] Deploying user contract for {}: {:?}", name, uc_key));
+                tracing::info!(%name, key = ?uc_key, "Deploying user contract");
                 if let Err(e) = api.send(put_uc).await {
-                    clog(&format!("[CREAM] ERROR: Failed to deploy user contract: {:?}", e));
+                    tracing::error!(error = ?e, "Failed to deploy user contract");
                     return;
                 }
 
                 // Store the user contract key
                 let uc_key_str = format!("{}", uc_key);
-                *user_contract_instance_id = Some(*uc_key.id());
+                router.register(*uc_key.id(), UserContractHandler::Own);
+                router.subscriptions().adopt(*uc_key.id(), Interest::OwnUserContract);
                 *user_contract_key_ref = Some(uc_key);
                 {
                     let mut state = shared.write();
@@ -2017,6 +3965,7 @@ mod wasm_impl {
                     us.user_contract_key = Some(uc_key_str);
                     us.save();
                 }
+                claim_name(api, shared, key_manager, name_registry_key, &name, &uc_key).await;
 
                 // Transfer initial 10,000 CURD from root → new user.
                 // Use a deterministic tx_ref so re-registration deduplicates
@@ -2047,19 +3996,14 @@ mod wasm_impl {
                     extra: Default::default(),
                 };
                 let ib_state_bytes = serde_json::to_vec(&ib_state).unwrap();
-                let put_inbox = ClientRequest::ContractOp(ContractRequest::Put {
-                    contract: inbox_contract,
-                    state: WrappedState::new(ib_state_bytes),
-                    related_contracts: RelatedContracts::default(),
-                    subscribe: true,
-                    blocking_subscribe: false,
-                });
+                let put_inbox = request::put(inbox_contract, ib_state_bytes, true);
 
-                clog(&format!("[CREAM] Deploying inbox contract for {}: {:?}", name, ib_key));
+                tracing::info!(%name, key = ?ib_key, "Deploying inbox contract");
                 if let Err(e) = api.send(put_inbox).await {
-                    clog(&format!("[CREAM] ERROR: Failed to deploy inbox contract: {:?}", e));
+                    tracing::error!(error = ?e, "Failed to deploy inbox contract");
                 }
-                *inbox_contract_instance_id = Some(*ib_key.id());
+                router.register(*ib_key.id(), InboxHandler);
+                router.subscriptions().adopt(*ib_key.id(), Interest::Inbox);
                 *inbox_contract_key_ref = Some(ib_key);
                 {
                     let mut state = shared.write();
@@ -2072,7 +4016,7 @@ mod wasm_impl {
                 current_supplier,
             } => {
                 let Some(uc_key) = *user_contract_key_ref else {
-                    clog("[CREAM] UpdateUserContract: no user contract key, skipping");
+                    tracing::info!("UpdateUserContract: no user contract key, skipping");
                     return;
                 };
 
@@ -2081,25 +4025,22 @@ mod wasm_impl {
                     if let Some(supplier) = current_supplier {
                         uc_state.current_supplier = supplier;
                     }
-                    uc_state.updated_at = chrono::Utc::now();
+                    uc_state.touch(chrono::Utc::now());
                     uc_state.balance_curds = uc_state.derive_balance();
                     // Re-sign (dev mode: signature is ignored)
                     uc_state.signature = ed25519_dalek::Signature::from_bytes(&[0u8; 64]);
 
                     let uc_bytes = serde_json::to_vec(&uc_state).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: uc_key,
-                        data: UpdateData::State(State::from(uc_bytes)),
-                    });
-                    shared.write().user_contract = Some(uc_state);
+                    let update = request::update(uc_key, uc_bytes);
+                    let rollback = shared.write().set_user_contract_optimistic(uc_state);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to update user contract: {:?}", e));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update user contract");
                     } else {
-                        clog("[CREAM] UpdateUserContract: sent successfully");
+                        tracing::info!("UpdateUserContract: sent successfully");
                     }
                 } else {
-                    clog("[CREAM] UpdateUserContract: no existing user contract state");
+                    tracing::info!("UpdateUserContract: no existing user contract state");
                 }
             }
 
@@ -2108,13 +4049,13 @@ mod wasm_impl {
                 use super::super::lightning_mock::MockLightningGateway;
                 let curd_per_sat = toll_rates.read().curd_per_sat;
 
-                clog(&format!("[CREAM] PegIn: {} sats → {} CURD", amount_sats, amount_sats * curd_per_sat));
+                tracing::info!(sats = amount_sats, curds = amount_sats * curd_per_sat, "PegIn");
                 let mut gw = MockLightningGateway::new();
 
                 let invoice = match gw.create_invoice(amount_sats, "CURD peg-in") {
                     Ok(inv) => inv,
                     Err(e) => {
-                        clog(&format!("[CREAM] ERROR: PegIn create_invoice failed: {}", e));
+                        tracing::error!(error = %e, "PegIn create_invoice failed");
                         return;
                     }
                 };
@@ -2129,20 +4070,20 @@ mod wasm_impl {
                             format!("Lightning peg-in ({} sats)", amount_sats),
                             user_name,
                         ).await;
-                        clog(&format!("[CREAM] PegIn: credited {} CURD", curd_amount));
+                        tracing::info!(curds = curd_amount, "PegIn: credited");
                     }
                     Ok(_) => {
-                        clog("[CREAM] ERROR: PegIn invoice not yet paid");
+                        tracing::error!("PegIn invoice not yet paid");
                     }
                     Err(e) => {
-                        clog(&format!("[CREAM] ERROR: PegIn check_invoice failed: {}", e));
+                        tracing::error!(error = %e, "PegIn check_invoice failed");
                     }
                 }
             }
 
             NodeAction::PegInAllocate { amount_sats, payment_hash } => {
                 let curd_amount = amount_sats * toll_rates.read().curd_per_sat;
-                clog(&format!("[CREAM] PegInAllocate: {} sats → {} CURD (hash: {})", amount_sats, curd_amount, payment_hash));
+                tracing::info!(sats = amount_sats, curds = curd_amount, %payment_hash, "PegInAllocate");
 
                 let user_name = user_state.read().moniker.clone().unwrap_or_default();
                 wallet.transfer_from_root_with_lightning_hash(
@@ -2152,13 +4093,13 @@ mod wasm_impl {
                     user_name,
                     payment_hash.clone(),
                 ).await;
-                clog(&format!("[CREAM] PegInAllocate: credited {} CURD, settling invoice", curd_amount));
+                tracing::info!(curds = curd_amount, "PegInAllocate: credited, settling invoice");
 
                 // Settle the hold invoice via the gateway
                 if let Some(client) = super::super::lightning_remote::LightningClient::from_env() {
                     match client.settle_pegin(&payment_hash).await {
-                        Ok(()) => clog("[CREAM] PegInAllocate: invoice settled"),
-                        Err(e) => clog(&format!("[CREAM] ERROR: PegInAllocate settle failed: {}", e)),
+                        Ok(()) => tracing::info!("PegInAllocate: invoice settled"),
+                        Err(e) => tracing::error!(error = %e, "PegInAllocate settle failed"),
                     }
                 }
             }
@@ -2168,16 +4109,13 @@ mod wasm_impl {
                 use super::super::lightning_mock::MockLightningGateway;
 
                 let sats_out = amount_curd / toll_rates.read().curd_per_sat;
-                clog(&format!("[CREAM] PegOut: {} CURD → {} sats", amount_curd, sats_out));
+                tracing::info!(curds = amount_curd, sats = sats_out, "PegOut");
 
                 // Check balance
                 let current_balance = shared.read().user_contract
                     .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
                 if current_balance < amount_curd {
-                    clog(&format!(
-                        "[CREAM] ERROR: PegOut insufficient balance: have {}, need {}",
-                        current_balance, amount_curd
-                    ));
+                    tracing::error!(have = current_balance, need = amount_curd, "PegOut: insufficient balance");
                     return;
                 }
 
@@ -2194,11 +4132,11 @@ mod wasm_impl {
                 let mut gw = MockLightningGateway::new();
                 match gw.pay_invoice(&bolt11) {
                     Ok(PaymentStatus::Success { .. }) => {
-                        clog(&format!("[CREAM] PegOut: paid {} sats to {}", sats_out, bolt11));
+                        tracing::info!(sats = sats_out, %bolt11, "PegOut: paid");
                     }
                     Ok(_) | Err(_) => {
                         // Refund on failure
-                        clog("[CREAM] ERROR: PegOut Lightning payment failed, refunding");
+                        tracing::error!("PegOut Lightning payment failed, refunding");
                         wallet.transfer_from_root(
                             api,
                             amount_curd,
@@ -2211,16 +4149,13 @@ mod wasm_impl {
 
             NodeAction::PegOutViaGateway { amount_curd, bolt11 } => {
                 let sats_out = amount_curd / toll_rates.read().curd_per_sat;
-                clog(&format!("[CREAM] PegOutViaGateway: {} CURD → {} sats", amount_curd, sats_out));
+                tracing::info!(curds = amount_curd, sats = sats_out, "PegOutViaGateway");
 
                 // Check balance
                 let current_balance = shared.read().user_contract
                     .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
                 if current_balance < amount_curd {
-                    clog(&format!(
-                        "[CREAM] ERROR: PegOut insufficient balance: have {}, need {}",
-                        current_balance, amount_curd
-                    ));
+                    tracing::error!(have = current_balance, need = amount_curd, "PegOut: insufficient balance");
                     return;
                 }
 
@@ -2241,10 +4176,10 @@ mod wasm_impl {
                 if let Some(client) = super::super::lightning_remote::LightningClient::from_env() {
                     match client.pay_invoice(&bolt11, sats_out).await {
                         Ok(resp) if resp.success => {
-                            clog(&format!("[CREAM] PegOutViaGateway: paid {} sats", sats_out));
+                            tracing::info!(sats = sats_out, "PegOutViaGateway: paid");
                         }
                         Ok(resp) => {
-                            clog(&format!("[CREAM] ERROR: PegOutViaGateway payment failed: {:?}, refunding", resp.error));
+                            tracing::error!(error = ?resp.error, "PegOutViaGateway payment failed, refunding");
                             wallet.transfer_from_root(
                                 api,
                                 amount_curd,
@@ -2253,7 +4188,7 @@ mod wasm_impl {
                             ).await;
                         }
                         Err(e) => {
-                            clog(&format!("[CREAM] ERROR: PegOutViaGateway request failed: {}, refunding", e));
+                            tracing::error!(error = %e, "PegOutViaGateway request failed, refunding");
                             wallet.transfer_from_root(
                                 api,
                                 amount_curd,
@@ -2263,7 +4198,7 @@ mod wasm_impl {
                         }
                     }
                 } else {
-                    clog("[CREAM] ERROR: PegOutViaGateway: no gateway configured, refunding");
+                    tracing::error!("PegOutViaGateway: no gateway configured, refunding");
                     wallet.transfer_from_root(
                         api,
                         amount_curd,
@@ -2274,7 +4209,7 @@ mod wasm_impl {
             }
 
             NodeAction::FaucetTopUp => {
-                clog("[CREAM] FaucetTopUp: transferring 1000 CURD from root");
+                tracing::info!("FaucetTopUp: transferring 1000 curds from root");
                 let user_name = user_state.read().moniker.clone().unwrap_or_default();
                 wallet.transfer_from_root(
                     api,
@@ -2290,7 +4225,7 @@ mod wasm_impl {
                 kind,
                 recipient_pubkey_hex,
             } => {
-                clog(&format!("[CREAM] SendInboxMessage to {}: {} chars", recipient_name, body.len()));
+                tracing::info!(recipient = %recipient_name, chars = body.len(), "SendInboxMessage");
 
                 let cost = toll_rates.read().inbox_message_curd;
 
@@ -2298,7 +4233,7 @@ mod wasm_impl {
                 let current_balance = shared.read().user_contract
                     .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
                 if current_balance < cost {
-                    clog("[CREAM] ERROR: Insufficient balance for inbox message toll");
+                    tracing::error!("Insufficient balance for inbox message toll");
                     return;
                 }
 
@@ -2307,7 +4242,7 @@ mod wasm_impl {
                 wallet.transfer_to_root(
                     api,
                     cost,
-                    "Inbox message toll".to_string(),
+                    INBOX_TOLL_DESCRIPTION.to_string(),
                     sender_name.clone(),
                 ).await;
 
@@ -2339,11 +4274,14 @@ mod wasm_impl {
                                     let inbox_container = make_contract(INBOX_CONTRACT_WASM, Parameters::from(params_bytes));
                                     (inbox_container.key(), owner)
                                 } else {
-                                    clog(&format!("[CREAM] ERROR: Invalid pubkey hex for {}", recipient_name));
+                                    tracing::error!(recipient = %recipient_name, "Invalid pubkey hex");
                                     return;
                                 }
                             } else {
-                                clog(&format!("[CREAM] ERROR: Recipient {} not found in directory or has no inbox", recipient_name));
+                                tracing::error!(
+                                    recipient = %recipient_name,
+                                    "Recipient not found in directory or has no inbox",
+                                );
                                 return;
                             }
                         }
@@ -2354,21 +4292,16 @@ mod wasm_impl {
                 // The response will be processed by the main polling loop; we just
                 // need to give Freenet a moment to fetch and cache the contract
                 // before we send the UPDATE.
-                let get_req = ClientRequest::ContractOp(ContractRequest::Get {
-                    key: *recipient_inbox_key.id(),
-                    return_contract_code: true,
-                    subscribe: false,
-                    blocking_subscribe: false,
-                });
+                let get_req = request::get(*recipient_inbox_key.id(), true);
                 if let Err(e) = api.send(get_req).await {
-                    clog(&format!("[CREAM] WARNING: Failed to GET recipient inbox: {:?}", e));
+                    tracing::warn!(error = ?e, "Failed to GET recipient inbox");
                 }
-                gloo_timers::future::TimeoutFuture::new(2_000).await;
+                platform::sleep_ms(2_000).await;
 
                 let now = chrono::Utc::now();
                 let msg_id: u64 = (now.timestamp_millis() as u64)
                     .wrapping_mul(1000)
-                    .wrapping_add(rand_u32() as u64);
+                    .wrapping_add(platform::rand_u32() as u64);
 
                 let sender_key = user_state.read().user_contract_key.clone();
 
@@ -2392,28 +4325,29 @@ mod wasm_impl {
                 };
 
                 let update_bytes = serde_json::to_vec(&update_state).unwrap();
-                let update = ClientRequest::ContractOp(ContractRequest::Update {
-                    key: recipient_inbox_key,
-                    data: UpdateData::State(State::from(update_bytes)),
-                });
+                let update = request::update(recipient_inbox_key, update_bytes);
 
-                // Try sending the update, with one retry after a delay
+                // Try sending the update, retrying with backoff
                 let mut sent_ok = false;
-                for attempt in 0..2 {
-                    if attempt > 0 {
-                        clog("[CREAM] SendInboxMessage: retrying after delay...");
-                        gloo_timers_sleep(2000).await;
-                    }
+                let mut backoff = INBOX_SEND_RETRY.backoff(platform::rand_u32() as u64);
+                let mut attempt = 1;
+                loop {
                     match api.send(update.clone()).await {
                         Ok(_) => {
-                            clog("[CREAM] SendInboxMessage: sent successfully");
+                            tracing::info!("SendInboxMessage: sent successfully");
                             sent_ok = true;
                             break;
                         }
                         Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to send inbox message (attempt {}): {:?}", attempt + 1, e));
+                            tracing::error!(attempt, error = ?e, "Failed to send inbox message");
                         }
                     }
+                    let Some(delay) = backoff.next_delay() else {
+                        break;
+                    };
+                    tracing::info!(delay_ms = delay.as_millis() as u64, "SendInboxMessage: retrying");
+                    platform::sleep_ms(delay.as_millis() as u32).await;
+                    attempt += 1;
                 }
                 if sent_ok {
                     // Track sent message locally for display
@@ -2428,12 +4362,12 @@ mod wasm_impl {
 
             NodeAction::SessionToll => {
                 let cost = toll_rates.read().session_toll_curd;
-                clog(&format!("[CREAM] SessionToll: charging {} CURD", cost));
+                tracing::info!(curds = cost, "SessionToll: charging");
 
                 let current_balance = shared.read().user_contract
                     .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
                 if current_balance < cost {
-                    clog("[CREAM] SessionToll: insufficient balance, skipping");
+                    tracing::info!("SessionToll: insufficient balance, skipping");
                     return;
                 }
 
@@ -2441,19 +4375,19 @@ mod wasm_impl {
                 wallet.transfer_to_root(
                     api,
                     cost,
-                    "Chat session toll".to_string(),
+                    SESSION_TOLL_DESCRIPTION.to_string(),
                     user_name,
                 ).await;
-                clog(&format!("[CREAM] SessionToll: paid {} CURD", cost));
+                tracing::info!(curds = cost, "SessionToll: paid");
             }
 
             NodeAction::PeerTransfer { peer_pubkey_hex, amount, description } => {
-                clog(&format!("[CREAM] PeerTransfer: {} CURD to {}", amount, &peer_pubkey_hex[..16]));
+                tracing::info!(curds = amount, peer = %&peer_pubkey_hex[..16], "PeerTransfer");
 
                 let current_balance = shared.read().user_contract
                     .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
                 if current_balance < amount {
-                    clog("[CREAM] PeerTransfer: insufficient balance, skipping");
+                    tracing::info!("PeerTransfer: insufficient balance, skipping");
                     return;
                 }
 
@@ -2463,7 +4397,7 @@ mod wasm_impl {
                     .filter_map(|i| u8::from_str_radix(&peer_pubkey_hex[i..i+2], 16).ok())
                     .collect();
                 if pubkey_bytes.len() != 32 {
-                    clog("[CREAM] PeerTransfer: invalid peer pubkey hex length");
+                    tracing::info!("PeerTransfer: invalid peer pubkey hex length");
                     return;
                 }
                 let mut key_bytes = [0u8; 32];
@@ -2471,11 +4405,11 @@ mod wasm_impl {
                 let peer_vk = match ed25519_dalek::VerifyingKey::from_bytes(&key_bytes) {
                     Ok(vk) => vk,
                     Err(e) => {
-                        clog(&format!("[CREAM] PeerTransfer: invalid peer pubkey: {:?}", e));
+                        tracing::info!(error = ?e, "PeerTransfer: invalid peer pubkey");
                         return;
                     }
                 };
-                let peer_params = UserContractParameters { owner: peer_vk };
+                let peer_params = UserContractParameters { owner: peer_vk, spending_key: None };
                 let peer_params_bytes = serde_json::to_vec(&peer_params).unwrap();
                 let peer_contract = make_contract(USER_CONTRACT_WASM, Parameters::from(peer_params_bytes));
                 let peer_key = peer_contract.key();
@@ -2490,34 +4424,139 @@ mod wasm_impl {
                     user_name,
                     "peer".to_string(),
                 ).await;
-                clog(&format!("[CREAM] PeerTransfer: paid {} CURD", amount));
+                tracing::info!(curds = amount, "PeerTransfer: paid");
+            }
+
+            NodeAction::SendCurd { recipient_contract_key, amount, description } => {
+                tracing::info!(curds = amount, recipient = %recipient_contract_key, "SendCurd");
+
+                let current_balance = shared.read().user_contract
+                    .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
+                if amount == 0 || current_balance < amount {
+                    tracing::info!("SendCurd: insufficient balance, skipping");
+                    return;
+                }
+
+                let Some((recipient_key, recipient_name)) =
+                    resolve_user_contract(shared, root_contract_key, &recipient_contract_key)
+                else {
+                    tracing::error!(recipient = %recipient_contract_key, "SendCurd: no user contract found");
+                    return;
+                };
+                if user_contract_key_ref.as_ref() == Some(&recipient_key) {
+                    tracing::info!("SendCurd: refusing to send to own contract");
+                    return;
+                }
+
+                let user_name = user_state.read().moniker.clone().unwrap_or_default();
+                let receipt = wallet.do_transfer(
+                    api,
+                    ContractRole::User,
+                    ContractRole::ThirdParty(recipient_key),
+                    amount,
+                    description,
+                    user_name,
+                    recipient_name.clone(),
+                ).await;
+                tracing::info!(curds = amount, recipient = %recipient_name, tx_ref = %receipt.tx_ref, "SendCurd: sent");
+            }
+
+            NodeAction::IssueVoucher { amount, note } => {
+                tracing::info!(curds = amount, "IssueVoucher");
+
+                let current_balance = shared.read().user_contract
+                    .as_ref().map(|uc| uc.balance_curds).unwrap_or(0);
+                if amount == 0 || current_balance < amount {
+                    shared.write().push_toast("Voucher not issued: insufficient balance.".to_string());
+                    return;
+                }
+
+                let issuer_name = user_state.read().moniker.clone().unwrap_or_default();
+                let mut voucher = Voucher {
+                    id: generate_tx_ref(&issuer_name),
+                    amount,
+                    issuer: key_manager.user_id(),
+                    issuer_name: issuer_name.clone(),
+                    note,
+                    issued_at: chrono::Utc::now(),
+                    signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                    extra: Default::default(),
+                };
+                voucher.signature = ed25519_dalek::Signature::from_bytes(&key_manager.sign_raw(&voucher.signable_bytes()));
+
+                // The voucher's value waits in root's escrow until it is redeemed
+                wallet.do_transfer_with_ref(
+                    api,
+                    ContractRole::User,
+                    ContractRole::Root,
+                    amount,
+                    "Voucher issued".to_string(),
+                    issuer_name,
+                    cream_common::identity::ROOT_USER_NAME.to_string(),
+                    voucher.escrow_tx_ref(),
+                ).await;
+                tracing::info!(curds = amount, voucher = %voucher.id, "IssueVoucher: escrowed");
+                shared.write().issued_vouchers.push(voucher);
+            }
+
+            NodeAction::RedeemVoucher { code } => {
+                let voucher = match Voucher::from_code(&code) {
+                    Ok(voucher) => voucher,
+                    Err(e) => {
+                        shared.write().push_toast(format!("Voucher not redeemed: {}", e));
+                        return;
+                    }
+                };
+                tracing::info!(
+                    voucher = %voucher.id,
+                    curds = voucher.amount,
+                    issuer = %voucher.issuer_name,
+                    "RedeemVoucher",
+                );
+
+                let root = shared.read().root_user_contract.clone();
+                let Some(root) = root else {
+                    shared.write().push_toast("Voucher not redeemed: the root ledger hasn't loaded yet.".to_string());
+                    return;
+                };
+                let already = shared.read().user_contract.as_ref().map(|uc| uc.has_redeemed(&voucher.id));
+                let checked = match already {
+                    None => Err("you have no user contract yet".to_string()),
+                    Some(true) => Err(cream_common::voucher::VoucherError::AlreadyRedeemed.to_string()),
+                    Some(false) => voucher.check_redeemable(&root).map_err(|e| e.to_string()),
+                };
+                if let Err(e) = checked {
+                    shared.write().push_toast(format!("Voucher not redeemed: {}", e));
+                    return;
+                }
+
+                // The credit's update carries the voucher that backs it
+                if let Some(uc) = shared.write().user_contract.as_mut() {
+                    uc.vouchers.push(voucher.clone());
+                }
+                let user_name = user_state.read().moniker.clone().unwrap_or_default();
+                wallet.transfer_from_root_idempotent(
+                    api,
+                    voucher.amount,
+                    voucher.description(),
+                    user_name,
+                    voucher.tx_ref(),
+                ).await;
+                shared.write().push_toast(format!(
+                    "Redeemed a {} CURD voucher from {}.",
+                    voucher.amount, voucher.issuer_name
+                ));
             }
 
             NodeAction::SubscribeCustomerStorefront { storefront_key } => {
-                clog(&format!("[CREAM] Customer mode: subscribing to storefront key '{}'", storefront_key));
+                tracing::info!(%storefront_key, "Customer mode: subscribing to storefront");
                 match ContractInstanceId::from_bytes(&storefront_key) {
                     Ok(sf_instance_id) => {
-                        // GET the storefront state
-                        let get_sf = ClientRequest::ContractOp(ContractRequest::Get {
-                            key: sf_instance_id,
-                            return_contract_code: false,
-                            subscribe: false,
-                            blocking_subscribe: false,
-                        });
-                        if let Err(e) = api.send(get_sf).await {
-                            clog(&format!("[CREAM] ERROR: Failed to GET storefront: {:?}", e));
-                        }
-                        // Subscribe for live updates
-                        let sub_sf = ClientRequest::ContractOp(ContractRequest::Subscribe {
-                            key: sf_instance_id,
-                            summary: None,
-                        });
-                        if let Err(e) = api.send(sub_sf).await {
-                            clog(&format!("[CREAM] ERROR: Failed to subscribe to storefront: {:?}", e));
-                        }
+                        let requests = router.subscriptions().want(sf_instance_id, Interest::Storefront);
+                        send_follow(api, requests, "storefront").await;
                     }
                     Err(e) => {
-                        clog(&format!("[CREAM] ERROR: Invalid storefront key '{}': {:?}", storefront_key, e));
+                        tracing::error!(%storefront_key, error = ?e, "Invalid storefront key");
                     }
                 }
             }
@@ -2530,7 +4569,7 @@ mod wasm_impl {
                 locality,
                 timezone,
             } => {
-                clog(&format!("[CREAM] RegisterMarket: '{}'", name));
+                tracing::info!(%name, "RegisterMarket");
                 let organizer = key_manager.user_id();
                 let location = cream_common::postcode::lookup_postcode(&postcode)
                     .unwrap_or(cream_common::location::GeoLocation::new(0.0, 0.0));
@@ -2562,20 +4601,17 @@ mod wasm_impl {
                 let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                 let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                let update = ClientRequest::ContractOp(ContractRequest::Update {
-                    key: *market_directory_key,
-                    data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                });
+                let update = request::update_delta(*market_directory_key, delta_bytes);
                 if let Err(e) = api.send(update).await {
-                    clog(&format!("[CREAM] ERROR: Failed to update market directory: {:?}", e));
+                    tracing::error!(error = ?e, "Failed to update market directory");
                 } else {
                     shared.write().market_directory.entries.insert(market_key, signed_entry);
-                    clog("[CREAM] RegisterMarket: sent to network");
+                    tracing::info!("RegisterMarket: sent to network");
                 }
             }
 
             NodeAction::InviteMarketSupplier { market_name, supplier_name } => {
-                clog(&format!("[CREAM] InviteMarketSupplier: '{}' → '{}'", market_name, supplier_name));
+                tracing::info!(market = %market_name, supplier = %supplier_name, "InviteMarketSupplier");
 
                 let existing = shared.read().market_directory.entries.get(&market_name).cloned();
                 if let Some(mut entry) = existing {
@@ -2593,29 +4629,26 @@ mod wasm_impl {
                     let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                     let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: *market_directory_key,
-                        data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                    });
+                    let update = request::update_delta(*market_directory_key, delta_bytes);
                     if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to invite market supplier: {:?}", e));
+                        tracing::error!(error = ?e, "Failed to invite market supplier");
                     } else {
                         shared.write().market_directory.entries.insert(market_name, entry);
-                        clog("[CREAM] InviteMarketSupplier: updated directory (UI sends inbox separately)");
+                        tracing::info!("InviteMarketSupplier: updated directory (UI sends inbox separately)");
                     }
                 } else {
-                    clog("[CREAM] InviteMarketSupplier: no existing market entry");
+                    tracing::info!("InviteMarketSupplier: no existing market entry");
                 }
             }
 
             NodeAction::AcceptMarketInvite { market_name } => {
                 // This is a no-op at the directory level — the organizer confirms acceptance.
                 // The UI sends a SendInboxMessage(MarketAccept) separately.
-                clog(&format!("[CREAM] AcceptMarketInvite: '{}' (inbox sent by UI)", market_name));
+                tracing::info!(market = %market_name, "AcceptMarketInvite (inbox sent by UI)");
             }
 
             NodeAction::ConfirmMarketAcceptance { market_name, supplier_name } => {
-                clog(&format!("[CREAM] ConfirmMarketAcceptance: '{}' in '{}'", supplier_name, market_name));
+                tracing::info!(supplier = %supplier_name, market = %market_name, "ConfirmMarketAcceptance");
 
                 let existing = shared.read().market_directory.entries.get(&market_name).cloned();
                 if let Some(mut entry) = existing {
@@ -2632,15 +4665,12 @@ mod wasm_impl {
                             let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                             let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                            let update = ClientRequest::ContractOp(ContractRequest::Update {
-                                key: *market_directory_key,
-                                data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                            });
+                            let update = request::update_delta(*market_directory_key, delta_bytes);
                             if let Err(e) = api.send(update).await {
-                                clog(&format!("[CREAM] ERROR: Failed to confirm acceptance: {:?}", e));
+                                tracing::error!(error = ?e, "Failed to confirm acceptance");
                             } else {
                                 shared.write().market_directory.entries.insert(market_name, entry);
-                                clog("[CREAM] ConfirmMarketAcceptance: sent to network");
+                                tracing::info!("ConfirmMarketAcceptance: sent to network");
                             }
                         }
                     }
@@ -2648,7 +4678,7 @@ mod wasm_impl {
             }
 
             NodeAction::UpdateMarketEvents { market_name, events } => {
-                clog(&format!("[CREAM] UpdateMarketEvents: '{}' {} events", market_name, events.len()));
+                tracing::info!(market = %market_name, events = events.len(), "UpdateMarketEvents");
 
                 let existing = shared.read().market_directory.entries.get(&market_name).cloned();
                 if let Some(mut entry) = existing {
@@ -2663,15 +4693,12 @@ mod wasm_impl {
                     let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                     let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: *market_directory_key,
-                        data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                    });
+                    let update = request::update_delta(*market_directory_key, delta_bytes);
                     if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to update market events: {:?}", e));
+                        tracing::error!(error = ?e, "Failed to update market events");
                     } else {
                         shared.write().market_directory.entries.insert(market_name, entry);
-                        clog("[CREAM] UpdateMarketEvents: sent to network");
+                        tracing::info!("UpdateMarketEvents: sent to network");
                     }
                 }
             }
@@ -2685,7 +4712,7 @@ mod wasm_impl {
                 locality,
                 timezone,
             } => {
-                clog(&format!("[CREAM] UpdateMarketDetails: '{}' → '{}'", market_name, name));
+                tracing::info!(market = %market_name, new_name = %name, "UpdateMarketDetails");
 
                 let existing = shared.read().market_directory.entries.get(&market_name).cloned();
                 if let Some(mut entry) = existing {
@@ -2715,21 +4742,18 @@ mod wasm_impl {
                     let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                     let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: *market_directory_key,
-                        data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                    });
+                    let update = request::update_delta(*market_directory_key, delta_bytes);
                     if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to update market details: {:?}", e));
+                        tracing::error!(error = ?e, "Failed to update market details");
                     } else {
                         shared.write().market_directory.entries.insert(new_key, entry);
-                        clog("[CREAM] UpdateMarketDetails: sent to network");
+                        tracing::info!("UpdateMarketDetails: sent to network");
                     }
                 }
             }
 
             NodeAction::RemoveMarketSupplier { market_name, supplier_name } => {
-                clog(&format!("[CREAM] RemoveMarketSupplier: '{}' from '{}'", supplier_name, market_name));
+                tracing::info!(supplier = %supplier_name, market = %market_name, "RemoveMarketSupplier");
 
                 let existing = shared.read().market_directory.entries.get(&market_name).cloned();
                 if let Some(mut entry) = existing {
@@ -2745,24 +4769,21 @@ mod wasm_impl {
                         let delta = cream_common::market::MarketDirectoryState { entries, extra: Default::default() };
                         let delta_bytes = serde_json::to_vec(&delta).unwrap();
 
-                        let update = ClientRequest::ContractOp(ContractRequest::Update {
-                            key: *market_directory_key,
-                            data: UpdateData::Delta(StateDelta::from(delta_bytes)),
-                        });
+                        let update = request::update_delta(*market_directory_key, delta_bytes);
                         if let Err(e) = api.send(update).await {
-                            clog(&format!("[CREAM] ERROR: Failed to remove market supplier: {:?}", e));
+                            tracing::error!(error = ?e, "Failed to remove market supplier");
                         } else {
                             shared.write().market_directory.entries.insert(market_name, entry);
-                            clog("[CREAM] RemoveMarketSupplier: sent to network");
+                            tracing::info!("RemoveMarketSupplier: sent to network");
                         }
                     } else {
-                        clog("[CREAM] RemoveMarketSupplier: not the organizer");
+                        tracing::info!("RemoveMarketSupplier: not the organizer");
                     }
                 }
             }
 
             NodeAction::UpdateMarketProducts { market_name, product_ids } => {
-                clog(&format!("[CREAM] UpdateMarketProducts: '{}' with {} products", market_name, product_ids.len()));
+                tracing::info!(market = %market_name, products = product_ids.len(), "UpdateMarketProducts");
                 let my_supplier_id = key_manager.user_id();
                 let moniker = user_state.read().moniker.clone().unwrap_or_default();
 
@@ -2778,447 +4799,343 @@ mod wasm_impl {
                     let product_id_set: std::collections::BTreeSet<cream_common::product::ProductId> =
                         product_ids.into_iter().map(cream_common::product::ProductId).collect();
                     sf.info.market_products.insert(market_name, product_id_set);
+                    sf.info.seq += 1;
+                    sf.info.provenance =
+                        Some(key_manager.stamp(StateSection::StorefrontInfo, &sf.info.provenance_bytes()));
 
                     let sf_bytes = serde_json::to_vec(&sf).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key,
-                        data: UpdateData::State(State::from(sf_bytes)),
-                    });
+                    let update = request::update(key, sf_bytes);
                     if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to update market products: {:?}", e));
+                        tracing::error!(error = ?e, "Failed to update market products");
                     } else {
-                        shared.write().storefronts.insert(moniker, sf);
-                        clog("[CREAM] UpdateMarketProducts: sent to network");
+                        shared.write().set_storefront(moniker, sf);
+                        tracing::info!("UpdateMarketProducts: sent to network");
+                    }
+                }
+            }
+
+            NodeAction::ReissueContractRequest { instance_id, subscribe } => {
+                let Ok(id) = ContractInstanceId::from_bytes(&instance_id) else {
+                    tracing::error!(%instance_id, "Invalid contract instance id");
+                    return;
+                };
+                let reissue = if subscribe { request::subscribe(id) } else { request::get(id, false) };
+                tracing::info!(%instance_id, subscribe, "Debug: re-issuing request");
+                if let Err(e) = api.send(reissue).await {
+                    tracing::error!(%instance_id, error = ?e, "Failed to re-issue request");
+                }
+            }
+
+            NodeAction::Resubscribe { instance_id } => {
+                let Ok(id) = ContractInstanceId::from_bytes(&instance_id) else {
+                    tracing::error!(%instance_id, "Invalid contract instance id");
+                    return;
+                };
+                tracing::info!(%instance_id, "Resubscribing");
+                let requests = router.subscriptions().resubscribe(&id);
+                send_follow(api, requests, "contract").await;
+            }
+
+            NodeAction::ImportMigration { bundle } => {
+                use cream_common::migration::{check_key, rendezvous_name, MigrationBundle, RendezvousRecord};
+
+                let bundle = match MigrationBundle::from_json(&bundle)
+                    .and_then(|bundle| bundle.check_owner(&key_manager.user_id()).map(|()| bundle))
+                {
+                    Ok(bundle) => bundle,
+                    Err(e) => {
+                        shared.write().push_toast(format!("Migration not imported: {}", e));
+                        return;
+                    }
+                };
+
+                // Rebuilt from our key, every contract must keep its old key
+                let owner_key = key_manager.verifying_key();
+                let sf_params = serde_json::to_vec(&StorefrontParameters { owner: owner_key }).unwrap();
+                let sf_contract = make_contract(STOREFRONT_CONTRACT_WASM, Parameters::from(sf_params));
+                let uc_params = serde_json::to_vec(&UserContractParameters { owner: owner_key, spending_key: None }).unwrap();
+                let uc_contract = make_contract(USER_CONTRACT_WASM, Parameters::from(uc_params));
+                let ib_params = serde_json::to_vec(&cream_common::inbox::InboxParameters { owner: owner_key }).unwrap();
+                let ib_contract = make_contract(INBOX_CONTRACT_WASM, Parameters::from(ib_params));
+                let (sf_key, uc_key, ib_key) = (sf_contract.key(), uc_contract.key(), ib_contract.key());
+                let continuity = check_key("storefront", &bundle.storefront_key, &sf_key.to_string())
+                    .and_then(|()| {
+                        bundle
+                            .user_contract_key
+                            .as_deref()
+                            .map_or(Ok(()), |exported| check_key("user", exported, &uc_key.to_string()))
+                    })
+                    .and_then(|()| {
+                        bundle
+                            .inbox_key
+                            .as_deref()
+                            .map_or(Ok(()), |exported| check_key("inbox", exported, &ib_key.to_string()))
+                    });
+                if let Err(e) = continuity {
+                    shared.write().push_toast(format!("Migration not imported: {}", e));
+                    return;
+                }
+
+                let name = bundle.name.clone();
+                tracing::info!(%name, key = %sf_key, "ImportMigration: re-PUTting storefront");
+                let put_sf = request::put(sf_contract, serde_json::to_vec(&bundle.storefront).unwrap(), true);
+                if let Err(e) = api.send(put_sf).await {
+                    tracing::error!(error = ?e, "Failed to PUT migrated storefront");
+                    shared.write().push_toast("Migration failed: the storefront could not be sent to this node.".to_string());
+                    return;
+                }
+                router.subscriptions().adopt(*sf_key.id(), Interest::Storefront);
+                sf_contract_keys.insert(name.clone(), sf_key);
+                if let Some(state) = &bundle.user_contract {
+                    let put_uc = request::put(uc_contract, serde_json::to_vec(state).unwrap(), false);
+                    if let Err(e) = api.send(put_uc).await {
+                        tracing::error!(error = ?e, "Failed to PUT migrated user contract");
+                    }
+                }
+                if let Some(state) = &bundle.inbox {
+                    let put_inbox = request::put(ib_contract, serde_json::to_vec(state).unwrap(), true);
+                    if let Err(e) = api.send(put_inbox).await {
+                        tracing::error!(error = ?e, "Failed to PUT migrated inbox");
                     }
                 }
+                {
+                    let mut state = shared.write();
+                    state.storefront_keys.insert(name.clone(), sf_key.to_string());
+                    state.set_storefront(name.clone(), bundle.storefront.clone());
+                    if let Some(uc_state) = bundle.user_contract.clone() {
+                        state.supplier_user_contract_key = Some(uc_key.to_string());
+                        state.user_contract = Some(uc_state);
+                    }
+                    if let Some(inbox) = bundle.inbox.clone() {
+                        state.inbox_contract_key = Some(ib_key.to_string());
+                        state.inbox = Some(inbox);
+                    }
+                }
+
+                // Re-register at this node's address: a new registration
+                // signature, then the address signature as a heartbeat
+                let record = RendezvousRecord::sign(
+                    key_manager.signing_key(),
+                    &rendezvous_name(&name),
+                    node_url,
+                    &bundle.storefront_key,
+                    bundle.user_contract_key.as_deref(),
+                    bundle.inbox_key.as_deref(),
+                );
+                let mut reg_shared = shared.clone();
+                platform::spawn_local(async move {
+                    let registered = crate::components::rendezvous::register_supplier(
+                        &record.name, &record.address, &record.storefront_key, &record.public_key, &record.signature,
+                        record.user_contract_key.as_deref(), record.inbox_contract_key.as_deref(),
+                    )
+                    .await;
+                    let moved = match registered {
+                        Ok(()) => crate::components::rendezvous::heartbeat(
+                            &record.name, &record.address, &record.public_key, &record.address_signature,
+                        )
+                        .await,
+                        Err(e) => Err(e),
+                    };
+                    let mut state = reg_shared.write();
+                    match moved {
+                        Ok(()) => {
+                            state.rendezvous_registered = true;
+                            state.push_toast(format!("Storefront moved to this node; customers now reach you at {}.", record.address));
+                        }
+                        Err(e) => state.push_toast(format!(
+                            "Storefront moved, but the rendezvous service was not updated: {}. It will be retried on your next login.",
+                            e
+                        )),
+                    }
+                });
+            }
+
+            NodeAction::RetryOperation { key } => {
+                tracing::info!(%key, "Retrying queued operation");
+                api.retry(&key).await;
+            }
+
+            NodeAction::DismissOperation { key } => {
+                tracing::info!(%key, "Dismissing queued operation");
+                api.dismiss(&key);
+            }
+
+            NodeAction::RetryFailedTransfers => {
+                let failed = shared.read().failed_ledger_entries();
+                for entry in failed {
+                    resubmit_ledger_entry(api, shared, entry, signing_service).await;
+                }
+            }
+
+            NodeAction::IssueSettlementStatements { period } => {
+                let existing = shared.read().user_contract.clone();
+                let (Some(mut uc_state), Some(uc_key)) = (existing, *user_contract_key_ref) else {
+                    tracing::info!("IssueSettlementStatements: no user contract available");
+                    return;
+                };
+                let now = chrono::Utc::now();
+                let compiled = compile_statements(&uc_state.ledger, period, now);
+                let issued = statements_to_issue(&uc_state.settlement_statements, compiled);
+                if issued.is_empty() {
+                    tracing::info!("IssueSettlementStatements: statements already up to date");
+                    return;
+                }
+                let count = issued.len();
+                let signed: Vec<_> = issued
+                    .into_iter()
+                    .map(|mut statement| {
+                        key_manager.sign_settlement_statement(&mut statement);
+                        statement
+                    })
+                    .collect();
+                cream_common::settlement::merge_statements(&mut uc_state.settlement_statements, &signed);
+                uc_state.touch(now);
+                uc_state.signature = key_manager.sign_user_contract(&uc_state.signable_bytes());
+
+                let uc_bytes = serde_json::to_vec(&uc_state).unwrap();
+                let update = request::update(uc_key, uc_bytes);
+                let rollback = shared.write().set_user_contract_optimistic(uc_state);
+                if let Err(e) = api.send_optimistic(update, rollback).await {
+                    tracing::error!(error = ?e, "IssueSettlementStatements update failed");
+                } else {
+                    tracing::info!(count, period = %period.label(), "IssueSettlementStatements: issued statements");
+                }
             }
 
             NodeAction::CheckpointLedger => {
-                clog("[CREAM] CheckpointLedger: starting checkpoint");
+                tracing::info!("CheckpointLedger: starting checkpoint");
                 let existing = shared.read().user_contract.clone();
                 if let Some(mut uc_state) = existing {
                     let ledger_len = uc_state.ledger.len();
                     if ledger_len == 0 {
-                        clog("[CREAM] CheckpointLedger: no transactions to checkpoint");
+                        tracing::info!("CheckpointLedger: no transactions to checkpoint");
                         return;
                     }
                     let pruned = uc_state.checkpoint(cream_common::user_contract::PRUNE_KEEP_RECENT, chrono::Utc::now());
-                    uc_state.updated_at = chrono::Utc::now();
+                    uc_state.touch(chrono::Utc::now());
                     uc_state.balance_curds = uc_state.derive_balance();
 
-                    // Sign the updated state
-                    let msg = uc_state.signable_bytes();
+                    // Sign the checkpoint proof, then the updated state
                     let key_manager: Signal<Option<crate::components::key_manager::KeyManager>> = use_context();
                     if let Some(ref km) = *key_manager.read() {
+                        let cp_msg = uc_state.checkpoint_signable_bytes();
+                        if let Some(proof) = uc_state.checkpoint_proof.as_mut() {
+                            proof.signature = km.sign_user_contract(&cp_msg);
+                        }
+                        let msg = uc_state.signable_bytes();
                         uc_state.signature = km.sign_user_contract(&msg);
                     }
 
                     let uc_bytes = serde_json::to_vec(&uc_state).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: user_contract_key_ref.unwrap(),
-                        data: UpdateData::State(State::from(uc_bytes)),
-                    });
-                    shared.write().user_contract = Some(uc_state);
+                    let update = request::update(user_contract_key_ref.unwrap(), uc_bytes);
+                    let rollback = shared.write().set_user_contract_optimistic(uc_state);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: CheckpointLedger update failed: {:?}", e));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "CheckpointLedger update failed");
                     } else {
-                        clog(&format!("[CREAM] CheckpointLedger: pruned {} entries, {} remain",
-                            pruned, cream_common::user_contract::PRUNE_KEEP_RECENT.min(ledger_len)));
+                        tracing::info!(
+                            pruned,
+                            remaining = cream_common::user_contract::PRUNE_KEEP_RECENT.min(ledger_len),
+                            "CheckpointLedger: pruned entries",
+                        );
                     }
                 } else {
-                    clog("[CREAM] CheckpointLedger: no user contract available");
+                    tracing::info!("CheckpointLedger: no user contract available");
                 }
             }
 
             NodeAction::SetTollRates { rates } => {
-                clog(&format!("[CREAM] SetTollRates: {:?}", rates));
+                tracing::info!(?rates, "SetTollRates");
 
                 let existing = shared.read().root_user_contract.clone();
                 if let Some(mut root_state) = existing {
                     root_state.toll_rates = rates;
-                    root_state.updated_at = chrono::Utc::now();
+                    root_state.touch(chrono::Utc::now());
                     root_state.balance_curds = root_state.derive_balance();
 
                     // Sign via FROST (root contract requires real signature)
-                    let msg = root_state.signable_bytes();
-                    match signing_service.sign(&msg).await {
+                    let request = SigningRequest::RootLedger { root_state: root_state.clone() };
+                    match signing_service.sign(&request).await {
                         Ok(sig) => root_state.signature = sig,
                         Err(e) => {
-                            clog(&format!("[CREAM] ERROR: FROST signing failed for SetTollRates: {}", e));
+                            tracing::error!(error = %e, "FROST signing failed for SetTollRates");
                             return;
                         }
                     }
 
                     let uc_bytes = serde_json::to_vec(&root_state).unwrap();
-                    let update = ClientRequest::ContractOp(ContractRequest::Update {
-                        key: *root_contract_key,
-                        data: UpdateData::State(State::from(uc_bytes)),
-                    });
-                    shared.write().root_user_contract = Some(root_state);
+                    let update = request::update(*root_contract_key, uc_bytes);
+                    let rollback = shared.write().set_root_contract_optimistic(root_state);
 
-                    if let Err(e) = api.send(update).await {
-                        clog(&format!("[CREAM] ERROR: Failed to update root contract with toll rates: {:?}", e));
+                    if let Err(e) = api.send_optimistic(update, rollback).await {
+                        tracing::error!(error = ?e, "Failed to update root contract with toll rates");
                     } else {
-                        clog("[CREAM] SetTollRates: root contract updated successfully");
+                        tracing::info!("SetTollRates: root contract updated successfully");
                     }
                 } else {
-                    clog("[CREAM] SetTollRates: no root user contract available");
+                    tracing::info!("SetTollRates: no root user contract available");
                 }
             }
-        }
-    }
 
-    /// Handle contract responses from the node.
-    /// Returns follow-up requests to send back (e.g. PUT after NotFound,
-    /// or GET+subscribe for newly discovered storefronts).
-    fn handle_contract_response(
-        shared: &mut Signal<crate::components::shared_state::SharedState>,
-        response: ContractResponse,
-        directory_instance_id: ContractInstanceId,
-        subscribed: &mut HashSet<ContractInstanceId>,
-        instance_to_name: &mut std::collections::HashMap<ContractInstanceId, String>,
-        sf_contract_keys: &mut BTreeMap<String, ContractKey>,
-        customer_supplier_name: Option<&str>,
-        user_contract_instance_id: Option<ContractInstanceId>,
-        root_contract_instance_id: Option<ContractInstanceId>,
-        inbox_contract_instance_id: Option<ContractInstanceId>,
-        market_directory_instance_id: ContractInstanceId,
-    ) -> Vec<ClientRequest<'static>> {
-        match response {
-            ContractResponse::GetResponse { key, state, .. } => {
-                let bytes = state.as_ref();
-                if bytes.is_empty() {
-                    return vec![];
-                }
-                let is_directory = *key.id() == directory_instance_id;
-                let is_user_contract = user_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_root_contract = root_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_inbox = inbox_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_market_directory = *key.id() == market_directory_instance_id;
-                clog(&format!("[CREAM] GetResponse: is_directory={}, is_user_contract={}, is_root={}, is_inbox={}, is_market_dir={}, bytes_len={}",
-                    is_directory, is_user_contract, is_root_contract, is_inbox, is_market_directory, bytes.len()));
-                if is_market_directory {
-                    match serde_json::from_slice::<cream_common::market::MarketDirectoryState>(bytes) {
-                        Ok(mkt_state) => {
-                            clog(&format!("[CREAM] Market directory GET: {} markets",
-                                mkt_state.entries.len()));
-                            shared.write().market_directory = mkt_state;
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse market directory GetResponse: {e}"));
-                        }
-                    }
-                } else if is_inbox {
-                    match serde_json::from_slice::<cream_common::inbox::InboxState>(bytes) {
-                        Ok(inbox_state) => {
-                            clog(&format!("[CREAM] Inbox contract GET: {} messages",
-                                inbox_state.messages.len()));
-                            shared.write().inbox = Some(inbox_state);
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse inbox GetResponse: {e}"));
-                        }
-                    }
-                } else if is_root_contract {
-                    match serde_json::from_slice::<UserContractState>(bytes) {
-                        Ok(uc_state) => {
-                            clog(&format!("[CREAM] Root contract GET: balance={}, ledger_len={}",
-                                uc_state.balance_curds, uc_state.ledger.len()));
-                            shared.write().root_user_contract = Some(uc_state);
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse root contract GetResponse: {e}"));
-                        }
-                    }
-                } else if is_user_contract {
-                    match serde_json::from_slice::<UserContractState>(bytes) {
-                        Ok(uc_state) => {
-                            clog(&format!("[CREAM] User contract GET: name='{}', balance={}",
-                                uc_state.name, uc_state.balance_curds));
-                            shared.write().user_contract = Some(uc_state);
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse user contract GetResponse: {e}"));
-                        }
-                    }
-                } else if is_directory {
-                    match serde_json::from_slice::<DirectoryState>(bytes) {
-                        Ok(directory) => {
-                            clog(&format!("[CREAM] Directory GET: {} entries: {:?}",
-                                directory.entries.len(),
-                                directory.entries.values().map(|e| e.name.as_str()).collect::<Vec<_>>()
-                            ));
-                            let follow_ups =
-                                subscribe_new_storefronts(&directory, subscribed, instance_to_name);
-                            clog(&format!("[CREAM] Sending {} follow-up requests", follow_ups.len()));
-                            shared.write().directory = directory;
-                            return follow_ups;
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse directory GetResponse: {e}"));
-                        }
-                    }
-                } else {
-                    match serde_json::from_slice::<StorefrontState>(bytes) {
-                        Ok(storefront) => {
-                            // Look up the directory entry by the storefront's owner (UserId).
-                            // This maps e.g. info.name "Gary's Farm" → directory name "Gary".
-                            let dir_name = {
-                                let state = shared.read();
-                                state.directory.entries.get(&storefront.info.owner)
-                                    .map(|e| e.name.clone())
-                            };
-                            let name_from_map = instance_to_name.get(key.id()).cloned();
-                            // Prefer directory name (correct case) over rendezvous name
-                            // (lowercase). Route parameters are resolved case-insensitively
-                            // so "gary" from rendezvous will still match "Gary" from directory.
-                            let name = dir_name
-                                .or(customer_supplier_name.map(|s| s.to_string()))
-                                .or(name_from_map)
-                                .unwrap_or_else(|| storefront.info.name.clone());
-                            clog(&format!("[CREAM] Storefront GET: keyed as '{}' (info.name='{}', owner={:?}, {} products)",
-                                name, storefront.info.name, storefront.info.owner, storefront.products.len()));
-                            // Store the ContractKey for later use (e.g. PlaceOrder)
-                            sf_contract_keys.insert(name.clone(), key);
-                            shared.write().storefronts.insert(name, storefront);
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse storefront GetResponse: {e}"));
-                        }
-                    }
-                }
-            }
+            NodeAction::ModerateSupplier { supplier, action, reason } => {
+                tracing::info!(%supplier, action = %action.label(), "ModerateSupplier");
 
-            ContractResponse::UpdateNotification { key, update, .. } => {
-                let bytes = match &update {
-                    UpdateData::State(s) => s.as_ref(),
-                    UpdateData::Delta(d) => d.as_ref(),
-                    UpdateData::StateAndDelta { state, .. } => state.as_ref(),
-                    _ => return vec![],
-                };
-                if bytes.is_empty() {
-                    return vec![];
-                }
-                let is_directory = *key.id() == directory_instance_id;
-                let is_user_contract = user_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_root_contract = root_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_inbox = inbox_contract_instance_id
-                    .map(|id| *key.id() == id)
-                    .unwrap_or(false);
-                let is_market_directory = *key.id() == market_directory_instance_id;
-                clog(&format!("[CREAM] UpdateNotification: is_directory={}, is_user_contract={}, is_root={}, is_inbox={}, is_market_dir={}, bytes_len={}",
-                    is_directory, is_user_contract, is_root_contract, is_inbox, is_market_directory, bytes.len()));
-                if is_market_directory {
-                    match serde_json::from_slice::<cream_common::market::MarketDirectoryState>(bytes) {
-                        Ok(mkt_update) => {
-                            clog(&format!("[CREAM] Market directory notification: {} markets",
-                                mkt_update.entries.len()));
-                            shared.write().market_directory.merge(mkt_update);
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse market directory notification: {e}"));
-                        }
-                    }
-                } else if is_inbox {
-                    match serde_json::from_slice::<cream_common::inbox::InboxState>(bytes) {
-                        Ok(inbox_update) => {
-                            clog(&format!("[CREAM] Inbox notification: {} messages",
-                                inbox_update.messages.len()));
-                            let mut state = shared.write();
-                            if let Some(existing) = state.inbox.as_mut() {
-                                existing.merge(inbox_update);
-                            } else {
-                                state.inbox = Some(inbox_update);
-                            }
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse inbox notification: {e}"));
-                        }
-                    }
-                } else if is_root_contract {
-                    match serde_json::from_slice::<UserContractState>(bytes) {
-                        Ok(uc_update) => {
-                            clog(&format!("[CREAM] Root contract notification: balance={}, ledger_len={}",
-                                uc_update.balance_curds, uc_update.ledger.len()));
-                            let mut state = shared.write();
-                            if let Some(existing) = state.root_user_contract.as_mut() {
-                                existing.merge(uc_update);
-                            } else {
-                                state.root_user_contract = Some(uc_update);
-                            }
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse root contract notification: {e}"));
-                        }
-                    }
-                } else if is_user_contract {
-                    match serde_json::from_slice::<UserContractState>(bytes) {
-                        Ok(uc_update) => {
-                            clog(&format!("[CREAM] User contract notification: name='{}', balance={}",
-                                uc_update.name, uc_update.balance_curds));
-                            let mut state = shared.write();
-                            if let Some(existing) = state.user_contract.as_mut() {
-                                existing.merge(uc_update);
-                            } else {
-                                state.user_contract = Some(uc_update);
-                            }
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse user contract notification: {e}"));
-                        }
-                    }
-                } else if is_directory {
-                    match serde_json::from_slice::<DirectoryState>(bytes) {
-                        Ok(dir_update) => {
-                            clog(&format!("[CREAM] Directory notification: {} entries", dir_update.entries.len()));
-                            let follow_ups =
-                                subscribe_new_storefronts(&dir_update, subscribed, instance_to_name);
-                            shared.write().directory.merge(dir_update);
-                            return follow_ups;
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse directory notification: {e}"));
-                        }
-                    }
-                } else {
-                    match serde_json::from_slice::<StorefrontState>(bytes) {
-                        Ok(sf_update) => {
-                            let dir_name = {
-                                let state = shared.read();
-                                state.directory.entries.get(&sf_update.info.owner)
-                                    .map(|e| e.name.clone())
-                            };
-                            let name_from_map = instance_to_name.get(key.id()).cloned();
-                            let name = customer_supplier_name.map(|s| s.to_string())
-                                .or(dir_name)
-                                .or(name_from_map)
-                                .unwrap_or_else(|| sf_update.info.name.clone());
-                            clog(&format!("[CREAM] Storefront notification: keyed as '{}' ({} products)",
-                                name, sf_update.products.len()));
-                            let mut state = shared.write();
-                            if let Some(existing) = state.storefronts.get_mut(&name) {
-                                existing.merge(sf_update);
-                            } else {
-                                state.storefronts.insert(name, sf_update);
-                            }
-                        }
-                        Err(e) => {
-                            clog(&format!("[CREAM] ERROR: Failed to parse storefront notification: {e}"));
-                        }
+                let mut record = cream_common::moderation::ModerationRecord {
+                    supplier: supplier.clone(),
+                    action,
+                    reason,
+                    issued_at: chrono::Utc::now(),
+                    signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                    extra: Default::default(),
+                };
+                // Sign via FROST: the directory contract only honors the group key
+                match signing_service.sign(&SigningRequest::Moderation { record: record.clone() }).await {
+                    Ok(sig) => record.signature = sig,
+                    Err(e) => {
+                        tracing::error!(error = %e, "FROST signing failed for ModerateSupplier");
+                        return;
                     }
                 }
-            }
-
-            ContractResponse::PutResponse { key } => {
-                tracing::info!("Contract put OK: {:?}", key);
-            }
 
-            ContractResponse::UpdateResponse { key, .. } => {
-                clog(&format!("[CREAM] UpdateResponse OK: {:?}", key));
+                let mut moderation = BTreeMap::new();
+                moderation.insert(supplier.clone(), record);
+                let delta = DirectoryState { moderation, ..Default::default() };
+                publish_supplier_delta(api, shared, &supplier, delta).await;
             }
 
-            ContractResponse::SubscribeResponse { key, subscribed } => {
-                tracing::info!(
-                    "Subscription {:?}: {}",
-                    key,
-                    if subscribed { "active" } else { "failed" }
-                );
-            }
+            NodeAction::IssueCredential { supplier, kind, reference, expires_at, revoked } => {
+                tracing::info!(credential = %kind.label(), %supplier, revoked, "IssueCredential");
 
-            ContractResponse::NotFound { instance_id } => {
-                if instance_id == directory_instance_id {
-                    // Directory contract doesn't exist yet — we're the first tab.
-                    // PUT it with empty state + subscribe.
-                    tracing::info!("Directory not found, creating it...");
-                    let directory_contract = make_contract(
-                        DIRECTORY_CONTRACT_WASM,
-                        Parameters::from(vec![]),
-                    );
-                    let empty_dir = DirectoryState::default();
-                    let initial_state =
-                        serde_json::to_vec(&empty_dir).unwrap();
-                    return vec![ClientRequest::ContractOp(
-                        ContractRequest::Put {
-                            contract: directory_contract,
-                            state: WrappedState::new(initial_state),
-                            related_contracts: RelatedContracts::default(),
-                            subscribe: true,
-                            blocking_subscribe: false,
-                        },
-                    )];
-                } else {
-                    tracing::warn!(
-                        "Contract not found: {:?}",
-                        instance_id
-                    );
+                let mut credential = cream_common::credential::Credential {
+                    supplier: supplier.clone(),
+                    kind,
+                    // The federation certifies under its FROST group key
+                    issuer: cream_common::identity::root_user_id(),
+                    issuer_name: GUARDIAN_CERTIFIER_NAME.to_string(),
+                    reference,
+                    issued_at: chrono::Utc::now(),
+                    expires_at,
+                    revoked,
+                    signature: ed25519_dalek::Signature::from_bytes(&[0u8; 64]),
+                    extra: Default::default(),
+                };
+                match signing_service.sign(&SigningRequest::Credential { credential: credential.clone() }).await {
+                    Ok(sig) => credential.signature = sig,
+                    Err(e) => {
+                        tracing::error!(error = %e, "FROST signing failed for IssueCredential");
+                        return;
+                    }
                 }
-            }
-
-            _ => {
-                tracing::debug!("Unhandled contract response");
-            }
-        }
-        vec![]
-    }
 
-    /// For each supplier in the directory whose storefront we haven't
-    /// subscribed to yet, emit a GET request and an explicit Subscribe.
-    fn subscribe_new_storefronts(
-        directory: &DirectoryState,
-        subscribed: &mut HashSet<ContractInstanceId>,
-        instance_to_name: &mut std::collections::HashMap<ContractInstanceId, String>,
-    ) -> Vec<ClientRequest<'static>> {
-        let mut requests = Vec::new();
-        for entry in directory.entries.values() {
-            let instance_id = *entry.storefront_key.id();
-            // Always update the name mapping (in case directory was updated)
-            instance_to_name.insert(instance_id, entry.name.clone());
-            if subscribed.insert(instance_id) {
-                clog(&format!("[CREAM] Auto-subscribing to storefront for {} (instance_id={:?})", entry.name, instance_id));
-                // GET the current state
-                requests.push(ClientRequest::ContractOp(
-                    ContractRequest::Get {
-                        key: instance_id,
-                        return_contract_code: false,
-                        subscribe: false,
-                        blocking_subscribe: false,
-                    },
-                ));
-                // Explicitly subscribe for live updates
-                requests.push(ClientRequest::ContractOp(
-                    ContractRequest::Subscribe {
-                        key: instance_id,
-                        summary: None,
-                    },
-                ));
+                let mut credentials = BTreeMap::new();
+                credentials.insert(credential.id(), credential);
+                let delta = DirectoryState { credentials, ..Default::default() };
+                publish_supplier_delta(api, shared, &supplier, delta).await;
             }
         }
-        requests
     }
 }
 
-#[cfg(target_family = "wasm")]
 async fn node_comms(rx: UnboundedReceiver<NodeAction>) {
-    wasm_impl::node_comms(rx).await;
-}
-
-// Non-WASM stub (e.g. running tests natively)
-#[cfg(not(target_family = "wasm"))]
-async fn node_comms(mut rx: UnboundedReceiver<NodeAction>) {
-    use futures::StreamExt;
-    tracing::warn!("Not running in WASM; node_comms is a no-op");
-    while let Some(action) = rx.next().await {
-        tracing::debug!("Node action (native stub): {:?}", action);
-    }
+    node_impl::node_comms(rx).await;
 }
@@ -0,0 +1,534 @@
+use std::collections::BTreeMap;
+
+use cream_common::migration::MigrationBundle;
+use dioxus::prelude::*;
+
+use super::background_sync::request_permission;
+use super::node_api::{check_node, node_candidates, use_node_action, NodeAction};
+#[cfg(target_family = "wasm")]
+use super::platform::download_file;
+use super::propagation::{format_lag, PropagationHealth, LOST_AFTER_SECS};
+use super::shared_state::{use_shared_state, SharedState};
+use super::subscriptions::{Interest, SubscriptionStatus};
+use super::user_state::{use_user_state, NotificationSettings, UserState};
+
+/// How often the open settings screen re-checks every node.
+const RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Result of probing one node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeHealth {
+    /// Time to open the WebSocket, if it opened.
+    pub ping_ms: Option<u64>,
+    /// Time for the node to answer a directory GET, if it answered.
+    pub directory_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl NodeHealth {
+    pub fn failed(error: String) -> Self {
+        NodeHealth { error: Some(error), ..Default::default() }
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.error.is_none() && self.directory_ms.is_some()
+    }
+}
+
+/// Turn what the user typed into a node WebSocket URL: a bare port means a
+/// local node, `host:port` a remote one, and a `ws://` or `wss://` URL is
+/// taken as given.
+fn normalize_node_url(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.starts_with("ws://") || input.starts_with("wss://") {
+        return Some(input.to_string());
+    }
+    let (host, port) = match input.rsplit_once(':') {
+        Some((host, port)) => (host, port),
+        None => ("localhost", input),
+    };
+    if host.is_empty() || host.contains('/') || port.parse::<u16>().is_err() {
+        return None;
+    }
+    Some(format!("ws://{host}:{port}/v1/contract/command?encodingProtocol=native"))
+}
+
+/// Probe each of `urls` in the background, recording results in `health`
+/// (`None` while a check is in flight).
+fn check_nodes(urls: Vec<String>, postcode: Option<String>, mut health: Signal<BTreeMap<String, Option<NodeHealth>>>) {
+    for url in urls {
+        health.write().insert(url.clone(), None);
+        let postcode = postcode.clone();
+        spawn(async move {
+            let result = check_node(&url, postcode.as_deref()).await;
+            health.write().insert(url, Some(result));
+        });
+    }
+}
+
+/// Save notification settings, asking the browser for permission first when
+/// turning any on. Without permission the settings are left unchanged.
+fn enable_notifications(
+    settings: NotificationSettings,
+    mut user_state: Signal<UserState>,
+    mut notice: Signal<Option<String>>,
+) {
+    if !settings.any() {
+        notice.set(None);
+        user_state.write().set_notifications(settings);
+        return;
+    }
+    spawn(async move {
+        if request_permission().await {
+            notice.set(None);
+            user_state.write().set_notifications(settings);
+        } else {
+            notice.set(Some("Notifications are blocked in this browser".into()));
+        }
+    });
+}
+
+/// Settings screen for the nodes the app connects to: add, remove and order
+/// them, with a live health check of each.
+#[component]
+pub fn NodeSettingsView() -> Element {
+    let mut user_state = use_user_state();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut draft = use_signal(String::new);
+    let mut input_error = use_signal(|| None::<String>);
+    let health = use_signal(BTreeMap::<String, Option<NodeHealth>>::new);
+
+    let candidates = node_candidates(&user_state.read());
+    let configured = user_state.read().node_urls.clone();
+    let connected_to = shared_state.read().node_url.clone();
+    let notifications = user_state.read().notifications.clone();
+    let notice = use_signal(|| None::<String>);
+
+    // Probe every candidate now and every RECHECK_INTERVAL while open.
+    use_future(move || async move {
+        loop {
+            let (urls, postcode) = {
+                let state = user_state.peek();
+                (node_candidates(&state), state.postcode.clone())
+            };
+            check_nodes(urls, postcode, health);
+            super::platform::sleep(RECHECK_INTERVAL).await;
+        }
+    });
+
+    rsx! {
+        div { class: "node-settings",
+            h2 { "Nodes" }
+            p { class: "hint",
+                "Nodes are tried in order; if one stops answering the app fails over to the next. "
+                "Nodes you add come before the default node."
+            }
+            table { class: "node-table",
+                thead {
+                    tr {
+                        th { "Node" }
+                        th { "WebSocket" }
+                        th { "Directory GET" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for (rank, url) in candidates.iter().enumerate() {
+                        {
+                            let status = health.read().get(url).cloned();
+                            let is_current = connected_to.as_deref() == Some(url.as_str());
+                            let is_configured = configured.contains(url);
+                            let is_preferred = configured.first() == Some(url);
+                            let position = rank + 1;
+                            let prefer_url = url.clone();
+                            let remove_url = url.clone();
+                            rsx! {
+                                tr { key: "{url}",
+                                    class: if is_current { "node-current" } else { "" },
+                                    td {
+                                        span { class: "node-rank", "{position}. " }
+                                        code { "{url}" }
+                                        if is_current {
+                                            span { class: "connection-badge connected", " connected" }
+                                        }
+                                    }
+                                    match status {
+                                        None => rsx! { td { "—" } td { "—" } },
+                                        Some(None) => rsx! { td { "checking…" } td { "checking…" } },
+                                        Some(Some(h)) => rsx! {
+                                            td { {h.ping_ms.map(|ms| format!("{ms} ms")).unwrap_or_else(|| "failed".into())} }
+                                            td {
+                                                class: if h.is_healthy() { "node-ok" } else { "node-down" },
+                                                {h.directory_ms.map(|ms| format!("{ms} ms")).unwrap_or_else(|| h.error.clone().unwrap_or_default())}
+                                            }
+                                        },
+                                    }
+                                    td {
+                                        if is_configured && !is_preferred {
+                                            button {
+                                                onclick: move |_| {
+                                                    user_state.write().prefer_node_url(&prefer_url);
+                                                    node_action.send(NodeAction::SwitchNode);
+                                                },
+                                                "Use first"
+                                            }
+                                        }
+                                        if is_configured {
+                                            button {
+                                                onclick: move |_| user_state.write().remove_node_url(&remove_url),
+                                                "Remove"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "node-add",
+                input {
+                    placeholder: "Port, host:port, or ws:// URL",
+                    value: "{draft}",
+                    oninput: move |e| {
+                        draft.set(e.value());
+                        input_error.set(None);
+                    },
+                }
+                button {
+                    disabled: draft.read().trim().is_empty(),
+                    onclick: move |_| {
+                        let input = draft.read().clone();
+                        match normalize_node_url(&input) {
+                            Some(url) => {
+                                user_state.write().add_node_url(url.clone());
+                                draft.set(String::new());
+                                check_nodes(vec![url], user_state.peek().postcode.clone(), health);
+                            }
+                            None => input_error.set(Some("Not a port, host:port, or ws:// URL".into())),
+                        }
+                    },
+                    "Add node"
+                }
+                button {
+                    onclick: move |_| {
+                        let state = user_state.peek();
+                        check_nodes(node_candidates(&state), state.postcode.clone(), health);
+                    },
+                    "Check now"
+                }
+            }
+            if let Some(err) = input_error.read().as_ref() {
+                p { class: "field-error", "{err}" }
+            }
+            PropagationPanel {}
+            SubscriptionsPanel {}
+            if user_state.read().is_supplier {
+                MigrationPanel {}
+            }
+            h3 { "Signing" }
+            label { class: "checkbox-label",
+                input {
+                    r#type: "checkbox",
+                    checked: user_state.read().delegate_signing,
+                    onchange: move |e| user_state.write().set_delegate_signing(e.checked()),
+                }
+                " Sign with the node's CREAM delegate"
+            }
+            p { class: "hint",
+                "Listings, orders and directory entries are signed by the delegate in your node, "
+                "which also enforces its order spending limits. If it can't be reached, the app signs itself."
+            }
+            h3 { "Notifications" }
+            label { class: "checkbox-label",
+                input {
+                    r#type: "checkbox",
+                    checked: notifications.orders,
+                    onchange: {
+                        let notifications = notifications.clone();
+                        move |e: Event<FormData>| {
+                            let settings = NotificationSettings { orders: e.checked(), ..notifications.clone() };
+                            enable_notifications(settings, user_state, notice);
+                        }
+                    },
+                }
+                if user_state.read().is_supplier { " New orders" } else { " Changes to my orders" }
+            }
+            label { class: "checkbox-label",
+                input {
+                    r#type: "checkbox",
+                    checked: notifications.messages,
+                    onchange: {
+                        let notifications = notifications.clone();
+                        move |e: Event<FormData>| {
+                            let settings = NotificationSettings { messages: e.checked(), ..notifications.clone() };
+                            enable_notifications(settings, user_state, notice);
+                        }
+                    },
+                }
+                " New messages"
+            }
+            p { class: "hint",
+                "Checked in the background while CREAM is closed, as often as your browser allows."
+            }
+            if let Some(text) = notice.read().as_ref() {
+                p { class: "field-error", "{text}" }
+            }
+        }
+    }
+}
+
+/// How long the contracts this client updated take to come back from the
+/// network, so missing data can be told apart as lag or loss.
+#[component]
+fn PropagationPanel() -> Element {
+    let shared_state = use_shared_state();
+    // Re-render periodically so waiting updates age into "lost"
+    let mut now = use_signal(chrono::Utc::now);
+    use_future(move || async move {
+        loop {
+            super::platform::sleep(std::time::Duration::from_secs(5)).await;
+            now.set(chrono::Utc::now());
+        }
+    });
+    let now = *now.read();
+
+    let shared = shared_state.read();
+    let rows: Vec<_> = shared
+        .propagation
+        .iter()
+        .map(|(id, lag)| {
+            let name = shared
+                .contracts
+                .get(id)
+                .map(|info| info.kind.to_string())
+                .unwrap_or_else(|| format!("{}…", &id[..id.len().min(8)]));
+            (id.clone(), name, lag.clone())
+        })
+        .collect();
+
+    rsx! {
+        h3 { "Network Health" }
+        p { class: "hint",
+            "How long your updates take to come back from the network. Slow means data arrives late; "
+            "an update with no answer after {LOST_AFTER_SECS} s is counted as lost."
+        }
+        if rows.is_empty() {
+            p { class: "empty-state", "No updates sent yet this session." }
+        } else {
+            table { class: "node-table propagation-table",
+                thead {
+                    tr {
+                        th { "Contract" }
+                        th { "Median" }
+                        th { "Slowest" }
+                        th { "Waiting" }
+                        th { "Lost" }
+                        th { "Status" }
+                    }
+                }
+                tbody {
+                    for (id, name, lag) in rows {
+                        {
+                            let health = lag.health(now);
+                            let class = match health {
+                                PropagationHealth::Healthy => "node-ok",
+                                PropagationHealth::Slow => "propagation-slow",
+                                PropagationHealth::Lossy => "node-down",
+                                PropagationHealth::NoData => "",
+                            };
+                            rsx! {
+                                tr { key: "{id}",
+                                    td { "{name}" }
+                                    td { {lag.median_ms().map(format_lag).unwrap_or_else(|| "—".into())} }
+                                    td { {lag.max_ms().map(format_lag).unwrap_or_else(|| "—".into())} }
+                                    td { "{lag.waiting(now)}" }
+                                    td { "{lag.lost(now)}" }
+                                    td { class: "{class}", "{health.label()}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether the node is sending this client updates for each contract it
+/// follows. Failed subscriptions are retried automatically; a storefront's
+/// can also be retried from here straight away.
+#[component]
+fn SubscriptionsPanel() -> Element {
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    // Re-render periodically so retry countdowns stay current
+    let mut now = use_signal(chrono::Utc::now);
+    use_future(move || async move {
+        loop {
+            super::platform::sleep(std::time::Duration::from_secs(5)).await;
+            now.set(chrono::Utc::now());
+        }
+    });
+    let now = *now.read();
+
+    let mut rows: Vec<_> = shared_state.read().subscriptions.iter().map(|(id, info)| (id.clone(), info.clone())).collect();
+    rows.sort_by_key(|(_, info)| (info.status != SubscriptionStatus::Failed, info.label()));
+    let failed = rows.iter().filter(|(_, info)| info.status == SubscriptionStatus::Failed).count();
+    let time = |at: Option<chrono::DateTime<chrono::Utc>>| {
+        at.map(|at| at.with_timezone(&chrono::Local).format("%H:%M:%S").to_string()).unwrap_or_else(|| "—".into())
+    };
+
+    rsx! {
+        h3 { "Subscriptions" }
+        p { class: "hint",
+            "Contracts your node sends you updates for. A refused subscription is retried by itself, "
+            "waiting longer after each refusal."
+        }
+        if failed > 0 {
+            p { class: "node-down", "{failed} subscription(s) failed; their data may be out of date." }
+        }
+        if rows.is_empty() {
+            p { class: "empty-state", "Not following any contracts yet." }
+        } else {
+            table { class: "node-table subscription-table",
+                thead {
+                    tr {
+                        th { "Contract" }
+                        th { "Status" }
+                        th { "Requested" }
+                        th { "Last renewed" }
+                        th { "Failures" }
+                        th { "" }
+                    }
+                }
+                tbody {
+                    for (id, info) in rows {
+                        {
+                            let class = match info.status {
+                                SubscriptionStatus::Active => "node-ok",
+                                SubscriptionStatus::Failed => "node-down",
+                                SubscriptionStatus::Wanted | SubscriptionStatus::Requested => "",
+                            };
+                            let retry = info
+                                .retry_at
+                                .filter(|_| info.status == SubscriptionStatus::Failed)
+                                .map(|at| format!(" · retry in {} s", (at - now).num_seconds().max(0)));
+                            let instance_id = id.clone();
+                            let can_resubscribe =
+                                info.status == SubscriptionStatus::Failed && info.interest == Interest::Storefront;
+                            rsx! {
+                                tr { key: "{id}",
+                                    td { "{info.label()}" }
+                                    td { class: "{class}", "{info.status.label()}{retry.unwrap_or_default()}" }
+                                    td { {time(info.requested_at)} }
+                                    td { {time(info.renewed_at)} }
+                                    td { "{info.failures}" }
+                                    td {
+                                        if can_resubscribe {
+                                            button {
+                                                onclick: move |_| node_action.send(NodeAction::Resubscribe { instance_id: instance_id.clone() }),
+                                                "Resubscribe"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The supplier's contract states as a migration bundle, from what this
+/// client holds.
+fn migration_bundle(shared: &SharedState, name: &str) -> Result<MigrationBundle, String> {
+    let storefront = shared.storefronts.get(name).ok_or("Your storefront hasn't loaded yet")?;
+    let storefront_key = shared.storefront_keys.get(name).ok_or("Your storefront key isn't known yet")?;
+    let mut bundle = MigrationBundle::new(name, storefront_key.clone(), storefront.clone(), chrono::Utc::now());
+    if let (Some(key), Some(state)) = (&shared.supplier_user_contract_key, &shared.user_contract) {
+        bundle = bundle.with_user_contract(key.clone(), state.clone());
+    }
+    if let (Some(key), Some(state)) = (&shared.inbox_contract_key, &shared.inbox) {
+        bundle = bundle.with_inbox(key.clone(), state.clone());
+    }
+    Ok(bundle)
+}
+
+/// Moving a storefront to another node: export here, import there.
+#[component]
+fn MigrationPanel() -> Element {
+    let user_state = use_user_state();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut contents = use_signal(|| None::<String>);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    rsx! {
+        h3 { "Move to another node" }
+        p { class: "hint",
+            "1. On this node, download your storefront, wallet and inbox. "
+            "2. Log in on the new node with the same name and password. "
+            "3. Import the file there: it checks the file is yours and that your contracts keep their keys, "
+            "sends them to the new node and tells the rendezvous service where customers can now find you."
+        }
+        button {
+            onclick: move |_| {
+                let name = user_state.read().moniker.clone().unwrap_or_default();
+                let bundle = migration_bundle(&shared_state.read(), &name);
+                let result = bundle.and_then(|bundle| {
+                    let json = bundle.to_json().map_err(|e| e.to_string())?;
+                    let file_name = format!(
+                        "cream-migration-{}-{}.json",
+                        name.to_lowercase(),
+                        bundle.exported_at.format("%Y%m%d")
+                    );
+                    #[cfg(target_family = "wasm")]
+                    download_file(&file_name, &json)?;
+                    let _ = json;
+                    Ok(format!("Saved {}", file_name))
+                });
+                status.set(Some(result));
+            },
+            "Download migration file"
+        }
+        div { class: "migration-import",
+            input {
+                r#type: "file",
+                accept: ".json,application/json",
+                onchange: move |e| {
+                    status.set(None);
+                    let files = e.files();
+                    spawn(async move {
+                        let Some(file) = files.into_iter().next() else {
+                            return;
+                        };
+                        match file.read_string().await {
+                            Ok(text) => contents.set(Some(text)),
+                            Err(e) => status.set(Some(Err(format!("Could not read file: {e}")))),
+                        }
+                    });
+                },
+            }
+            button {
+                disabled: contents.read().is_none(),
+                onclick: move |_| {
+                    let Some(bundle) = contents.write().take() else {
+                        return;
+                    };
+                    node_action.send(NodeAction::ImportMigration { bundle });
+                    status.set(Some(Ok("Importing; progress appears in notifications".to_string())));
+                },
+                "Import to this node"
+            }
+        }
+        match status.read().as_ref() {
+            Some(Ok(msg)) => rsx! { p { class: "backup-ok", "{msg}" } },
+            Some(Err(e)) => rsx! { p { class: "field-error", "{e}" } },
+            None => rsx! {},
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use dioxus::prelude::*;
+
+use cream_common::onboarding::{Onboarding, OnboardingStep};
+
+/// Dashboard section each step is set up in, as an element id.
+fn anchor(step: OnboardingStep) -> &'static str {
+    match step {
+        OnboardingStep::Listing | OnboardingStep::Rendezvous => "storefront-info",
+        OnboardingStep::Products => "your-products",
+        OnboardingStep::Schedule => "opening-hours",
+        OnboardingStep::Contact => "contact-details",
+    }
+}
+
+/// What to do about a step that isn't done yet.
+fn hint(step: OnboardingStep) -> &'static str {
+    match step {
+        OnboardingStep::Listing => {
+            "Your listing is published when you log in as a supplier; it can take a minute to appear."
+        }
+        OnboardingStep::Products => "Customers can only order what you list.",
+        OnboardingStep::Schedule => "Let customers know when to collect.",
+        OnboardingStep::Contact => "A phone number, email or address customers can reach you on.",
+        OnboardingStep::Rendezvous => {
+            "Registration runs each time you log in; once it succeeds your share link works."
+        }
+    }
+}
+
+/// Checklist of the setup a new storefront still needs, hidden once it's all
+/// done. Each pending step links to its dashboard section; `on_open` is told
+/// which step was picked so the dashboard can open that step's editor.
+#[component]
+pub fn OnboardingChecklist(onboarding: Onboarding, on_open: EventHandler<OnboardingStep>) -> Element {
+    let Some(current) = onboarding.current() else {
+        return rsx! {};
+    };
+    let (done, total) = onboarding.progress();
+
+    rsx! {
+        div { class: "dashboard-section onboarding-checklist",
+            h3 { "Getting Started ({done}/{total})" }
+            ul {
+                for step in OnboardingStep::ALL {
+                    {
+                        let is_done = onboarding.is_done(step);
+                        let class = if is_done {
+                            "onboarding-step done"
+                        } else if step == current {
+                            "onboarding-step current"
+                        } else {
+                            "onboarding-step"
+                        };
+                        rsx! {
+                            li { class: "{class}", key: "{step:?}",
+                                span { class: "onboarding-mark", if is_done { "✓" } else { "○" } }
+                                if is_done {
+                                    span { "{step.label()}" }
+                                } else {
+                                    a {
+                                        href: "#{anchor(step)}",
+                                        onclick: move |_| on_open.call(step),
+                                        "{step.label()}"
+                                    }
+                                    if step == current {
+                                        p { class: "onboarding-hint", "{hint(step)}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Optimistic updates: local changes shown before the node accepts them.
+//!
+//! Actions write their change into [`SharedState`] straight away and then
+//! send the contract update. The write hands back a [`Rollback`] — the state
+//! as it was — which [`RetryingApi::send_optimistic`](super::retry_queue::RetryingApi::send_optimistic)
+//! files under the updated contract's instance id in
+//! [`SharedState::optimistic`].
+//!
+//! An `UpdateResponse` from the node confirms the oldest pending change for
+//! that contract. If the contract rejects an update instead, every change
+//! still pending for it is undone (later ones were made on top of the
+//! rejected one), the contract is fetched again so anything the node did
+//! accept comes back, and the user gets a toast saying what was undone.
+//! Changes still pending when the connection drops are settled after the
+//! reconnect by [`super::resync`].
+
+use cream_common::storefront::StorefrontState;
+use cream_common::user_contract::UserContractState;
+
+use super::error_messages::{FriendlyError, Remedy};
+use super::shared_state::SharedState;
+
+/// How to put back the part of [`SharedState`] a local change replaced.
+#[derive(Clone, Debug)]
+pub enum Rollback {
+    /// The storefront under a supplier name, `None` if we didn't hold one.
+    Storefront {
+        name: String,
+        previous: Option<StorefrontState>,
+    },
+    /// The signed-in user's own contract.
+    UserContract(Option<UserContractState>),
+    /// Root's contract.
+    RootContract(Option<UserContractState>),
+}
+
+impl Rollback {
+    fn restore(self, shared: &mut SharedState) {
+        match self {
+            Rollback::Storefront { name, previous: Some(storefront) } => shared.set_storefront(name, storefront),
+            Rollback::Storefront { name, previous: None } => shared.remove_storefront(&name),
+            Rollback::UserContract(previous) => shared.user_contract = previous,
+            Rollback::RootContract(previous) => shared.root_user_contract = previous,
+        }
+    }
+}
+
+/// A local change sent to the node and not yet confirmed.
+#[derive(Clone, Debug)]
+pub struct OptimisticUpdate {
+    /// Action that made it (e.g. "AddProduct").
+    pub label: String,
+    pub rollback: Rollback,
+}
+
+/// A short notice for the user, shown until dismissed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toast {
+    pub id: u64,
+    pub message: String,
+    /// What the user can do about it.
+    pub hint: Option<&'static str>,
+    pub remedy: Option<Remedy>,
+}
+
+impl SharedState {
+    /// Store `storefront` under `name` ahead of the node accepting it.
+    pub fn set_storefront_optimistic(&mut self, name: String, storefront: StorefrontState) -> Rollback {
+        let previous = self.storefronts.get(&name).cloned();
+        self.set_storefront(name.clone(), storefront);
+        Rollback::Storefront { name, previous }
+    }
+
+    /// Replace the user's own contract state ahead of the node accepting it.
+    pub fn set_user_contract_optimistic(&mut self, state: UserContractState) -> Rollback {
+        Rollback::UserContract(self.user_contract.replace(state))
+    }
+
+    /// Replace root's contract state ahead of the node accepting it.
+    pub fn set_root_contract_optimistic(&mut self, state: UserContractState) -> Rollback {
+        Rollback::RootContract(self.root_user_contract.replace(state))
+    }
+
+    /// File a change just sent to contract `instance_id`.
+    pub fn track_update(&mut self, instance_id: String, label: &str, rollback: Rollback) {
+        self.optimistic.entry(instance_id).or_default().push(OptimisticUpdate {
+            label: label.to_string(),
+            rollback,
+        });
+    }
+
+    /// The node accepted the oldest pending change to `instance_id`.
+    pub fn confirm_update(&mut self, instance_id: &str) {
+        let Some(pending) = self.optimistic.get_mut(instance_id) else {
+            return;
+        };
+        if !pending.is_empty() {
+            pending.remove(0);
+        }
+        if pending.is_empty() {
+            self.optimistic.remove(instance_id);
+        }
+    }
+
+    /// Undo every change still pending for `instance_id`, back to the state
+    /// before the oldest. Returns the labels of the undone changes.
+    pub fn roll_back_updates(&mut self, instance_id: &str) -> Vec<String> {
+        let Some(pending) = self.optimistic.remove(instance_id) else {
+            return Vec::new();
+        };
+        let labels = pending.iter().map(|update| update.label.clone()).collect();
+        if let Some(oldest) = pending.into_iter().next() {
+            oldest.rollback.restore(self);
+        }
+        labels
+    }
+
+    /// Show `message` to the user.
+    pub fn push_toast(&mut self, message: String) {
+        self.push_error(FriendlyError { message, hint: None, remedy: None });
+    }
+
+    /// Show an explained error to the user, with its hint and remedy.
+    pub fn push_error(&mut self, error: FriendlyError) {
+        let id = self.toasts.last().map_or(0, |toast| toast.id + 1);
+        self.toasts.push(Toast { id, message: error.message, hint: error.hint, remedy: error.remedy });
+    }
+
+    pub fn dismiss_toast(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+}
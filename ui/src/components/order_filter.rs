@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use dioxus::prelude::*;
+
+use cream_common::identity::UserId;
+use cream_common::order::OrderId;
+use cream_common::storefront::StorefrontState;
+
+/// Statuses in the order they're offered as filters.
+const STATUSES: [&str; 6] = ["Reserved", "Paid", "Awaiting confirmation", "Fulfilled", "Cancelled", "Expired"];
+
+/// One order as the order lists show it, denormalized from its storefront.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderRow {
+    pub supplier: String,
+    pub id: OrderId,
+    pub product_name: String,
+    pub customer: UserId,
+    /// [`OrderStatus::label`](cream_common::order::OrderStatus::label) of the current status.
+    pub status: &'static str,
+    pub created_at: DateTime<Utc>,
+    pub total: u64,
+}
+
+/// A storefront's orders with lookups by status and product, rebuilt by
+/// [`SharedState::apply_storefront`](super::shared_state::SharedState::apply_storefront)
+/// whenever an order or product changes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OrderIndex {
+    rows: Vec<OrderRow>,
+    by_status: BTreeMap<&'static str, Vec<usize>>,
+    by_product: BTreeMap<String, Vec<usize>>,
+}
+
+impl OrderIndex {
+    pub fn build(supplier: &str, storefront: &StorefrontState) -> Self {
+        Self::from_rows(storefront.orders.values().map(|order| OrderRow {
+            supplier: supplier.to_string(),
+            id: order.id.clone(),
+            product_name: storefront
+                .products
+                .get(&order.product_id)
+                .map(|sp| sp.product.name.clone())
+                .unwrap_or_else(|| order.product_id.0.clone()),
+            customer: order.customer.clone(),
+            status: order.status.label(),
+            created_at: order.created_at,
+            total: order.current_total_price(),
+        }))
+    }
+
+    /// An index over `rows`, e.g. one customer's orders across storefronts.
+    pub fn from_rows(rows: impl IntoIterator<Item = OrderRow>) -> Self {
+        let mut index = OrderIndex::default();
+        for row in rows {
+            let idx = index.rows.len();
+            index.by_status.entry(row.status).or_default().push(idx);
+            index.by_product.entry(row.product_name.clone()).or_default().push(idx);
+            index.rows.push(row);
+        }
+        index
+    }
+
+    pub fn rows(&self) -> &[OrderRow] {
+        &self.rows
+    }
+
+    /// Statuses present, in lifecycle order, with how many orders have each.
+    pub fn status_counts(&self) -> Vec<(&'static str, usize)> {
+        STATUSES
+            .iter()
+            .filter_map(|s| self.by_status.get(s).map(|rows| (*s, rows.len())))
+            .collect()
+    }
+
+    /// Product names that have orders, alphabetically.
+    pub fn products(&self) -> Vec<String> {
+        self.by_product.keys().cloned().collect()
+    }
+
+    /// The rows `filter` lets through. Status and product narrow through the
+    /// index before the remaining criteria are checked row by row.
+    pub fn query<'a>(&'a self, filter: &OrderFilter, name_of: impl Fn(&OrderRow) -> String) -> Vec<&'a OrderRow> {
+        let status = filter.status.as_deref().map(|s| self.by_status.get(s).map_or(&[][..], Vec::as_slice));
+        let product = filter.product.as_deref().map(|p| self.by_product.get(p).map_or(&[][..], Vec::as_slice));
+        let candidates: Box<dyn Iterator<Item = usize>> = match (status, product) {
+            (Some(s), Some(p)) => Box::new(s.iter().copied().filter(move |i| p.contains(i))),
+            (Some(only), None) | (None, Some(only)) => Box::new(only.iter().copied()),
+            (None, None) => Box::new(0..self.rows.len()),
+        };
+        candidates
+            .map(|i| &self.rows[i])
+            .filter(|row| filter.matches(row, &name_of(row)))
+            .collect()
+    }
+}
+
+/// What the user has narrowed an order list down to. Empty fields match
+/// everything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OrderFilter {
+    /// An [`OrderStatus::label`](cream_common::order::OrderStatus::label).
+    pub status: Option<String>,
+    /// First and last day placed, inclusive (UTC).
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub product: Option<String>,
+    /// Case-insensitive part of the other party's name: the customer in a
+    /// supplier's list, the supplier in a customer's.
+    pub name: String,
+}
+
+impl OrderFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == OrderFilter::default()
+    }
+
+    /// Whether `row`, whose other party is called `name`, passes.
+    pub fn matches(&self, row: &OrderRow, name: &str) -> bool {
+        let day = row.created_at.date_naive();
+        let needle = self.name.trim().to_lowercase();
+        self.status.as_deref().is_none_or(|s| s == row.status)
+            && self.product.as_deref().is_none_or(|p| p == row.product_name)
+            && self.from.is_none_or(|from| day >= from)
+            && self.to.is_none_or(|to| day <= to)
+            && (needle.is_empty() || name.to_lowercase().contains(&needle))
+    }
+}
+
+/// How an order list is ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OrderSort {
+    #[default]
+    Newest,
+    Oldest,
+    Status,
+    Product,
+    /// Largest total first.
+    Total,
+}
+
+impl OrderSort {
+    pub const ALL: [OrderSort; 5] =
+        [OrderSort::Newest, OrderSort::Oldest, OrderSort::Status, OrderSort::Product, OrderSort::Total];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OrderSort::Newest => "Newest first",
+            OrderSort::Oldest => "Oldest first",
+            OrderSort::Status => "Status",
+            OrderSort::Product => "Product",
+            OrderSort::Total => "Total",
+        }
+    }
+
+    fn from_label(label: &str) -> Self {
+        OrderSort::ALL.into_iter().find(|s| s.label() == label).unwrap_or_default()
+    }
+
+    /// Sort `rows`, newest first among equals.
+    pub fn sort(self, rows: &mut [&OrderRow]) {
+        let rank = |status: &str| STATUSES.iter().position(|s| *s == status);
+        rows.sort_by(|a, b| {
+            let newest = b.created_at.cmp(&a.created_at);
+            match self {
+                OrderSort::Newest => newest,
+                OrderSort::Oldest => a.created_at.cmp(&b.created_at),
+                OrderSort::Status => rank(a.status).cmp(&rank(b.status)).then(newest),
+                OrderSort::Product => a.product_name.cmp(&b.product_name).then(newest),
+                OrderSort::Total => b.total.cmp(&a.total).then(newest),
+            }
+        });
+    }
+}
+
+/// Filter and sort controls for an order list. `name_label` names the other
+/// party ("Customer" or "Supplier"); `shown` and `total` are the row counts
+/// after and before filtering.
+#[component]
+pub fn OrderFilterBar(
+    statuses: Vec<(&'static str, usize)>,
+    products: Vec<String>,
+    name_label: String,
+    filter: Signal<OrderFilter>,
+    sort: Signal<OrderSort>,
+    shown: usize,
+    total: usize,
+) -> Element {
+    let current = filter.read().clone();
+    let all_class = if current.status.is_none() { "filter-chip active" } else { "filter-chip" };
+    let from = current.from.map(|d| d.to_string()).unwrap_or_default();
+    let to = current.to.map(|d| d.to_string()).unwrap_or_default();
+    let product = current.product.clone().unwrap_or_default();
+    let sort_label = sort.read().label();
+    let placeholder = format!("{name_label} name");
+
+    rsx! {
+        div { class: "order-filter",
+            div { class: "filter-chips",
+                button {
+                    class: "{all_class}",
+                    onclick: move |_| filter.write().status = None,
+                    "All ({total})"
+                }
+                for (status, count) in statuses {
+                    {
+                        let active = current.status.as_deref() == Some(status);
+                        let class = if active { "filter-chip active" } else { "filter-chip" };
+                        rsx! {
+                            button {
+                                key: "{status}",
+                                class: "{class}",
+                                onclick: move |_| {
+                                    filter.write().status = if active { None } else { Some(status.to_string()) };
+                                },
+                                "{status} ({count})"
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "order-filter-fields",
+                input {
+                    r#type: "search",
+                    placeholder: "{placeholder}",
+                    value: "{current.name}",
+                    oninput: move |e| filter.write().name = e.value(),
+                }
+                if products.len() > 1 {
+                    select {
+                        value: "{product}",
+                        onchange: move |e| {
+                            let picked = e.value();
+                            filter.write().product = (!picked.is_empty()).then_some(picked);
+                        },
+                        option { value: "", "All products" }
+                        for name in products {
+                            option { key: "{name}", value: "{name}", "{name}" }
+                        }
+                    }
+                }
+                label { "From" }
+                input {
+                    r#type: "date",
+                    value: "{from}",
+                    oninput: move |e| filter.write().from = NaiveDate::parse_from_str(&e.value(), "%Y-%m-%d").ok(),
+                }
+                label { "To" }
+                input {
+                    r#type: "date",
+                    value: "{to}",
+                    oninput: move |e| filter.write().to = NaiveDate::parse_from_str(&e.value(), "%Y-%m-%d").ok(),
+                }
+                select {
+                    value: "{sort_label}",
+                    onchange: move |e| sort.set(OrderSort::from_label(&e.value())),
+                    for s in OrderSort::ALL {
+                        option { key: "{s.label()}", value: "{s.label()}", "{s.label()}" }
+                    }
+                }
+                if !current.is_empty() {
+                    button { onclick: move |_| filter.set(OrderFilter::default()), "Clear" }
+                }
+            }
+            if shown < total {
+                p { class: "order-filter-count", "Showing {shown} of {total} orders" }
+            }
+        }
+    }
+}
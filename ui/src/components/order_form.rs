@@ -1,22 +1,61 @@
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
+use cream_common::blocklist::block_on;
+use cream_common::delivery::{zone_for, DeliveryDetails};
+use cream_common::order::CollectionPoint;
+use cream_common::postcode::lookup_postcode;
+use cream_common::product::ProductId;
 
+use super::i18n::use_i18n;
+use super::key_manager::KeyManager;
 use super::node_api::{use_node_action, NodeAction};
 use super::shared_state::use_shared_state;
 use super::user_state::use_user_state;
 
 #[component]
 pub fn OrderForm(supplier_name: String, product_id: String, product_name: String, price_per_unit: u64) -> Element {
+    let i18n = use_i18n();
     let mut user_state = use_user_state();
     let shared_state = use_shared_state();
     let node_action = use_node_action();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
     let mut quantity = use_signal(|| 1u32);
     let mut deposit_tier = use_signal(|| "2-Day Reserve (10%)".to_string());
     let mut submitted_id = use_signal(|| None::<u32>);
     let mut insufficient_funds = use_signal(|| false);
+    let mut wants_delivery = use_signal(|| false);
+    let mut delivery_address = use_signal(String::new);
+    let mut delivery_postcode = use_signal(String::new);
+    // Pickup location name; empty = the farm
+    let mut pickup_at = use_signal(String::new);
+    let (zones, pickup_locations, cancellation_policy, wholesale) = shared_state
+        .read()
+        .storefronts
+        .get(&supplier_name)
+        .map(|sf| {
+            let wholesale = sf
+                .products
+                .get(&ProductId(product_id.clone()))
+                .and_then(|sp| sp.product.wholesale.clone());
+            (
+                sf.info.delivery_zones.clone(),
+                sf.info.pickup_locations.clone(),
+                sf.info.cancellation_policy,
+                wholesale,
+            )
+        })
+        .unwrap_or_default();
+    // Wholesale listings get cheaper per unit the more is ordered
+    let unit_price = {
+        let wholesale = wholesale.clone();
+        move |qty: u32| wholesale.as_ref().map_or(price_per_unit, |terms| terms.unit_price(price_per_unit, qty))
+    };
+    let min_quantity = wholesale.as_ref().map_or(1, |terms| terms.min_quantity);
+    let below_minimum = *quantity.read() < min_quantity;
+    let terms_str = wholesale.as_ref().map(|terms| terms.summary(|curds| i18n.amount(curds)));
+
     if let Some(order_id) = *submitted_id.read() {
-        let confirm_total = format_amount(price_per_unit * *quantity.read() as u64);
+        let confirm_total = i18n.amount(unit_price(*quantity.read()) * *quantity.read() as u64);
         return rsx! {
             div { class: "order-confirmation",
                 h3 { "Order Submitted!" }
@@ -30,20 +69,65 @@ pub fn OrderForm(supplier_name: String, product_id: String, product_name: String
         };
     }
 
-    let total = price_per_unit * *quantity.read() as u64;
-    let price_each_str = format_amount(price_per_unit);
-    let total_str = format_amount(total);
+    // The supplier has blocked us: show their appeal note instead
+    let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+    let block_note = my_id.and_then(|me| {
+        let shared = shared_state.read();
+        let sf = shared.storefronts.get(&supplier_name)?;
+        block_on(&sf.blocklist, &me).map(|entry| entry.appeal_note.clone())
+    });
+    if let Some(note) = block_note {
+        return rsx! {
+            div { class: "order-form",
+                h2 { "Order: {product_name}" }
+                p { class: "alert alert-error", "{supplier_name} isn't taking orders from you." }
+                if !note.is_empty() {
+                    p { class: "appeal-note", "{note}" }
+                }
+            }
+        };
+    }
+
+    let total = unit_price(*quantity.read()) * *quantity.read() as u64;
+    let price_each_str = i18n.amount(unit_price(*quantity.read()));
+    let total_str = i18n.amount(total);
+    let offers_delivery = !zones.is_empty();
+    // Quote the cheapest zone serving the entered postcode
+    let delivery_quote = if *wants_delivery.read() {
+        let postcode = delivery_postcode.read().trim().to_string();
+        let location = lookup_postcode(&postcode);
+        zone_for(&zones, &postcode, location.as_ref()).map(|zone| DeliveryDetails {
+            address: delivery_address.read().trim().to_string(),
+            postcode,
+            location,
+            zone: zone.name.clone(),
+            fee_curd: zone.fee_curd,
+            extra: Default::default(),
+        })
+    } else {
+        None
+    };
+    let delivery_blocked = *wants_delivery.read()
+        && (delivery_quote.is_none() || delivery_address.read().trim().is_empty());
+    let collection_point = if *wants_delivery.read() || pickup_at.read().is_empty() {
+        None
+    } else {
+        Some(CollectionPoint::Pickup { location_name: pickup_at.read().clone() })
+    };
 
     rsx! {
         div { class: "order-form",
             h2 { "Order: {product_name}" }
             p { "From: {supplier_name}" }
             p { "Price: {price_each_str} each" }
+            if let Some(terms) = terms_str {
+                p { class: "wholesale-terms", "Wholesale: {terms}" }
+            }
             div { class: "form-group",
                 label { "Quantity:" }
                 input {
                     r#type: "number",
-                    min: "1",
+                    min: "{min_quantity}",
                     value: "{quantity}",
                     oninput: move |evt| {
                         if let Ok(v) = evt.value().parse::<u32>() {
@@ -62,20 +146,86 @@ pub fn OrderForm(supplier_name: String, product_id: String, product_name: String
                     option { value: "Full Payment (100%)", "Full Payment (100%)" }
                 }
             }
+            if offers_delivery {
+                div { class: "form-group",
+                    label { "Fulfilment:" }
+                    select {
+                        onchange: move |evt| wants_delivery.set(evt.value() == "delivery"),
+                        option { value: "pickup", selected: !*wants_delivery.read(), "Pickup" }
+                        option { value: "delivery", selected: *wants_delivery.read(), "Delivery" }
+                    }
+                }
+                if *wants_delivery.read() {
+                    div { class: "form-group",
+                        label { "Delivery address:" }
+                        input {
+                            r#type: "text",
+                            value: "{delivery_address}",
+                            oninput: move |evt| delivery_address.set(evt.value()),
+                        }
+                    }
+                    div { class: "form-group",
+                        label { "Postcode:" }
+                        input {
+                            r#type: "text",
+                            value: "{delivery_postcode}",
+                            oninput: move |evt| delivery_postcode.set(evt.value()),
+                        }
+                    }
+                    match &delivery_quote {
+                        Some(quote) => {
+                            let fee = i18n.amount(quote.fee_curd);
+                            let zone = quote.zone.clone();
+                            rsx! { p { class: "delivery-fee", "Delivery ({zone}): {fee}, payable on delivery" } }
+                        }
+                        None if !delivery_postcode.read().trim().is_empty() => rsx! {
+                            p { class: "error-message", "This supplier doesn't deliver to that postcode." }
+                        },
+                        None => rsx! {},
+                    }
+                }
+            }
+            if !pickup_locations.is_empty() && !*wants_delivery.read() {
+                div { class: "form-group",
+                    label { "Collect from:" }
+                    select {
+                        value: "{pickup_at}",
+                        onchange: move |evt| pickup_at.set(evt.value()),
+                        option { value: "", "The farm" }
+                        for location in pickup_locations.iter() {
+                            option { value: "{location.name}", "{location.name}" }
+                        }
+                    }
+                }
+            }
             p { class: "order-total", "Total: {total_str}" }
+            p { class: "cancellation-policy",
+                match cancellation_policy {
+                    Some(policy) => format!("Cancellation: {}.", policy.describe()),
+                    None => "Cancellation: free; your deposit is refunded in full.".to_string(),
+                }
+            }
+            if below_minimum {
+                p { class: "error-message", "The minimum order for this product is {min_quantity}." }
+            }
             if *insufficient_funds.read() {
                 p { class: "error-message", "Insufficient balance to place this order." }
             }
             button {
+                disabled: delivery_blocked || below_minimum,
                 onclick: {
                     let supplier = supplier_name.clone();
                     let product = product_name.clone();
+                    let unit_price = unit_price.clone();
                     let product_id = product_id.clone();
+                    let delivery = delivery_quote.clone();
+                    let collection_point = collection_point.clone();
                     move |_| {
                         let qty = *quantity.read();
                         let tier = deposit_tier.read().clone();
 
                         // Check balance from on-network user contract
+                        let price_per_unit = unit_price(qty);
                         let total = price_per_unit * qty as u64;
                         let deposit = match tier.as_str() {
                             "2-Day Reserve (10%)" => total / 10,
@@ -108,7 +258,8 @@ pub fn OrderForm(supplier_name: String, product_id: String, product_name: String
                                 quantity: qty,
                                 deposit_tier: tier,
                                 price_per_unit,
-                                collection_point: None,
+                                collection_point: collection_point.clone(),
+                                delivery: delivery.clone(),
                             });
 
                             submitted_id.set(Some(id));
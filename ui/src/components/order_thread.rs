@@ -0,0 +1,213 @@
+use dioxus::prelude::*;
+
+use cream_common::message::{MessageAttachment, MAX_ATTACHMENT_BYTES, MAX_MESSAGE_BODY};
+use cream_common::order::OrderId;
+
+use super::key_manager::KeyManager;
+use super::node_api::{use_node_action, NodeAction};
+use super::shared_state::{use_shared_state, use_thread_revision};
+
+/// A message as shown in the thread.
+#[derive(Clone, Debug, PartialEq)]
+struct ThreadLine {
+    id: String,
+    author: &'static str,
+    body: String,
+    at: String,
+    /// Start of the message this one answers, if any.
+    quoting: Option<String>,
+    attachment: Option<MessageAttachment>,
+}
+
+/// Collapsible conversation between an order's customer and the supplier.
+#[component]
+pub fn OrderThreadPanel(supplier_name: String, order_id: String) -> Element {
+    let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let node_action = use_node_action();
+    let mut draft = use_signal(String::new);
+    let mut reply_to = use_signal(|| None::<String>);
+    let mut attachment = use_signal(|| None::<MessageAttachment>);
+    let mut attach_error = use_signal(|| None::<String>);
+
+    let revision = use_thread_revision(supplier_name.clone(), OrderId(order_id.clone()));
+    // Subscribe via the thread's revision; the messages are read with peek()
+    let _ = revision();
+
+    let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+    let lines: Vec<ThreadLine> = {
+        let shared = shared_state.peek();
+        let Some(sf) = shared.storefronts.get(&supplier_name) else {
+            return rsx! {};
+        };
+        let owner = sf.info.owner.clone();
+        sf.threads
+            .get(&OrderId(order_id.clone()))
+            .map(|thread| {
+                thread
+                    .chronological()
+                    .into_iter()
+                    .map(|m| ThreadLine {
+                        id: m.id.0.clone(),
+                        author: if Some(&m.author) == my_id.as_ref() {
+                            "You"
+                        } else if m.author == owner {
+                            "Supplier"
+                        } else {
+                            "Customer"
+                        },
+                        body: m.body.clone(),
+                        at: m.created_at.format("%a %d %b %H:%M").to_string(),
+                        quoting: m.reply_to.as_ref().and_then(|r| {
+                            thread.messages.get(r).map(|q| q.body.chars().take(40).collect())
+                        }),
+                        attachment: m.attachment.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let count = lines.len();
+    let replying = reply_to.read().as_ref().and_then(|r| lines.iter().find(|l| &l.id == r)).map(|l| {
+        l.body.chars().take(40).collect::<String>()
+    });
+    let too_long = draft.read().len() > MAX_MESSAGE_BODY;
+    let has_content = !draft.read().trim().is_empty() || attachment.read().is_some();
+    let can_send = has_content && !too_long;
+    let attached_name = attachment.read().as_ref().map(|a| a.file_name.clone());
+
+    rsx! {
+        details { class: "order-thread",
+            summary { "Messages ({count})" }
+            if lines.is_empty() {
+                p { class: "empty-state", "No messages about this order yet." }
+            }
+            for line in lines {
+                {
+                    let reply_id = line.id.clone();
+                    rsx! {
+                        div { class: "thread-message", key: "{line.id}",
+                            div { class: "thread-meta",
+                                span { class: "thread-author", "{line.author}" }
+                                span { class: "thread-time", " · {line.at}" }
+                                button {
+                                    class: "thread-reply-btn",
+                                    onclick: move |_| reply_to.set(Some(reply_id.clone())),
+                                    "Reply"
+                                }
+                            }
+                            if let Some(ref quoted) = line.quoting {
+                                p { class: "thread-quote", "↪ {quoted}" }
+                            }
+                            if !line.body.is_empty() {
+                                p { class: "thread-body", "{line.body}" }
+                            }
+                            if let Some(ref file) = line.attachment {
+                                if file.is_image() {
+                                    img {
+                                        class: "thread-image",
+                                        src: "{file.data_url()}",
+                                        alt: "{file.file_name}",
+                                    }
+                                } else {
+                                    a {
+                                        class: "thread-download",
+                                        href: "{file.data_url()}",
+                                        download: "{file.file_name}",
+                                        "📎 {file.file_name}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            if let Some(quoted) = replying {
+                p { class: "thread-quote",
+                    "Replying to: {quoted} "
+                    button {
+                        class: "thread-reply-btn",
+                        onclick: move |_| reply_to.set(None),
+                        "×"
+                    }
+                }
+            }
+            div { class: "thread-compose",
+                textarea {
+                    placeholder: "Write a message about this order…",
+                    value: "{draft}",
+                    oninput: move |e| draft.set(e.value()),
+                }
+                if too_long {
+                    p { class: "field-error", "Messages are limited to {MAX_MESSAGE_BODY} characters." }
+                }
+                input {
+                    r#type: "file",
+                    onchange: move |e: Event<FormData>| {
+                        attach_error.set(None);
+                        let files = e.files();
+                        spawn(async move {
+                            let Some(file) = files.into_iter().next() else {
+                                return;
+                            };
+                            let bytes = match file.read_bytes().await {
+                                Ok(bytes) => bytes,
+                                Err(e) => {
+                                    attach_error.set(Some(format!("Could not read file: {e}")));
+                                    return;
+                                }
+                            };
+                            if bytes.len() > MAX_ATTACHMENT_BYTES {
+                                attach_error.set(Some(format!(
+                                    "Attachments are limited to {} KB.",
+                                    MAX_ATTACHMENT_BYTES / 1024
+                                )));
+                                return;
+                            }
+                            let mime_type = file
+                                .content_type()
+                                .filter(|t| !t.is_empty())
+                                .unwrap_or_else(|| "application/octet-stream".to_string());
+                            let candidate = MessageAttachment::new(file.name(), mime_type, &bytes);
+                            if candidate.is_valid() {
+                                attachment.set(Some(candidate));
+                            } else {
+                                attach_error.set(Some("This file can't be attached.".to_string()));
+                            }
+                        });
+                    },
+                }
+                if let Some(name) = attached_name {
+                    p { class: "thread-quote",
+                        "Attached: {name} "
+                        button {
+                            class: "thread-reply-btn",
+                            onclick: move |_| attachment.set(None),
+                            "×"
+                        }
+                    }
+                }
+                if let Some(err) = attach_error.read().as_ref() {
+                    p { class: "field-error", "{err}" }
+                }
+                button {
+                    disabled: !can_send,
+                    onclick: move |_| {
+                        node_action.send(NodeAction::PostOrderMessage {
+                            supplier_name: supplier_name.clone(),
+                            order_id: order_id.clone(),
+                            body: draft.read().clone(),
+                            reply_to: reply_to.read().clone(),
+                            attachment: attachment.read().clone(),
+                        });
+                        draft.set(String::new());
+                        reply_to.set(None);
+                        attachment.set(None);
+                    },
+                    "Send"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Platform layer for the node client.
+//!
+//! [`super::node_api`] is shared between the browser build (WASM, talking to
+//! the node through `web_sys::WebSocket`) and the native desktop build
+//! (tokio + tokio-tungstenite). Everything that differs between the two —
+//! the connection, timers, randomness, task spawning, and where the node
+//! port override and preferred language come from — lives here behind one
+//! set of signatures. With the `mock-node` feature, both builds connect to
+//! the in-memory node in [`super::mock_node`] instead.
+
+use std::future::Future;
+use std::time::Duration;
+
+use freenet_stdlib::client_api::{ClientError, HostResponse};
+use futures::channel::mpsc::UnboundedSender;
+
+/// Sink for responses from the node, in arrival order. A dropped connection
+/// is reported as a final `ErrorKind::Disconnect`.
+pub(crate) type ResponseSender = UnboundedSender<Result<HostResponse, ClientError>>;
+
+/// Sleep for `duration`.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(target_family = "wasm")]
+    gloo_timers::future::sleep(duration).await;
+    #[cfg(not(target_family = "wasm"))]
+    tokio::time::sleep(duration).await;
+}
+
+/// Sleep for the given number of milliseconds.
+pub(crate) async fn sleep_ms(ms: u32) {
+    sleep(Duration::from_millis(ms as u64)).await;
+}
+
+/// A random u32 (not cryptographic: tx refs, jitter seeds).
+pub(crate) fn rand_u32() -> u32 {
+    #[cfg(target_family = "wasm")]
+    {
+        (web_sys::js_sys::Math::random() * u32::MAX as f64) as u32
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        rand::random()
+    }
+}
+
+/// A random u64 from two [`rand_u32`]s, for content id nonces.
+pub(crate) fn rand_u64() -> u64 {
+    (u64::from(rand_u32()) << 32) | u64::from(rand_u32())
+}
+
+/// localStorage key holding this browser's device id.
+#[allow(dead_code)] // used in WASM builds
+const DEVICE_ID_KEY: &str = "cream_device_id";
+
+/// This device's id, tagged on the ledger entries it writes so other logins
+/// of the same account can be recognised. In the browser a random id kept in
+/// localStorage (shared by the browser's tabs, unlike the session); on
+/// desktop `CREAM_DEVICE_ID`, else the host name.
+pub(crate) fn device_id() -> String {
+    #[cfg(target_family = "wasm")]
+    {
+        let storage = web_sys::window().and_then(|w| w.local_storage().ok().flatten());
+        if let Some(id) = storage.as_ref().and_then(|s| s.get_item(DEVICE_ID_KEY).ok().flatten()) {
+            return id;
+        }
+        let id = format!("web-{:08x}{:08x}", rand_u32(), rand_u32());
+        if let Some(storage) = storage {
+            let _ = storage.set_item(DEVICE_ID_KEY, &id);
+        }
+        id
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        std::env::var("CREAM_DEVICE_ID")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .unwrap_or_else(|_| "desktop".to_string())
+    }
+}
+
+/// Run `future` in the background on the UI's executor.
+pub(crate) fn spawn_local(future: impl Future<Output = ()> + 'static) {
+    #[cfg(target_family = "wasm")]
+    wasm_bindgen_futures::spawn_local(future);
+    #[cfg(not(target_family = "wasm"))]
+    {
+        dioxus::prelude::spawn(future);
+    }
+}
+
+/// Node port chosen at runtime: the `?node=<port>` query parameter in the
+/// browser, the `CREAM_NODE_PORT` environment variable on desktop.
+pub(crate) fn node_port_override() -> Option<String> {
+    #[cfg(target_family = "wasm")]
+    {
+        let qs = web_sys::window()?.location().search().ok()?;
+        web_sys::UrlSearchParams::new_with_str(&qs).ok()?.get("node")
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        std::env::var("CREAM_NODE_PORT").ok()
+    }
+}
+
+/// The user's preferred language tag: `navigator.language` in the browser,
+/// `LANG` on desktop.
+pub(crate) fn preferred_language() -> Option<String> {
+    #[cfg(target_family = "wasm")]
+    {
+        web_sys::window()?.navigator().language()
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        std::env::var("LANG").ok()
+    }
+}
+
+/// Offer `contents` to the browser as a file download.
+#[cfg(target_family = "wasm")]
+pub(crate) fn download_file(file_name: &str, contents: &str) -> Result<(), String> {
+    use wasm_bindgen::JsCast;
+
+    let parts = js_sys::Array::of1(&wasm_bindgen::JsValue::from_str(contents));
+    let blob = web_sys::Blob::new_with_str_sequence(&parts).map_err(|e| format!("{e:?}"))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(|e| format!("{e:?}"))?;
+    let document = web_sys::window()
+        .and_then(|w| w.document())
+        .ok_or("No document")?;
+    let anchor: web_sys::HtmlAnchorElement = document
+        .create_element("a")
+        .map_err(|e| format!("{e:?}"))?
+        .dyn_into()
+        .map_err(|_| "Not an anchor element".to_string())?;
+    anchor.set_href(&url);
+    anchor.set_download(file_name);
+    anchor.click();
+    let _ = web_sys::Url::revoke_object_url(&url);
+    Ok(())
+}
+
+#[cfg(all(target_family = "wasm", not(feature = "mock-node")))]
+pub(crate) use freenet_stdlib::client_api::WebApi as NodeApi;
+
+#[cfg(feature = "mock-node")]
+pub(crate) use super::mock_node::{connect, disconnect, MockApi as NodeApi};
+
+/// Open a WebSocket to the node at `url` and wait until it is connected.
+/// Responses are delivered to `responses`.
+#[cfg(all(target_family = "wasm", not(feature = "mock-node")))]
+pub(crate) async fn connect(url: &str, responses: ResponseSender) -> Result<NodeApi, String> {
+    use freenet_stdlib::client_api::{Error, ErrorKind};
+    use futures::SinkExt;
+
+    let conn = web_sys::WebSocket::new(url)
+        .map_err(|e| format!("WebSocket connection failed: {:?}", e))?;
+
+    let closed = responses.clone();
+    let result_handler = move |result: Result<HostResponse, ClientError>| {
+        let mut sender = responses.clone();
+        let _ = wasm_bindgen_futures::future_to_promise(async move {
+            sender.send(result).await.expect("channel open");
+            Ok(wasm_bindgen::JsValue::NULL)
+        });
+    };
+
+    let (tx_connected, rx_connected) = futures::channel::oneshot::channel();
+    let onopen_handler = move || {
+        let _ = tx_connected.send(());
+        tracing::info!("Connected to Freenet node");
+    };
+
+    let api = NodeApi::start(
+        conn,
+        result_handler,
+        move |err| {
+            tracing::error!("Node error: {err}");
+            if matches!(&err, Error::ConnectionError(e) if e["source"] == "close") {
+                let _ = closed.unbounded_send(Err(ErrorKind::Disconnect.into()));
+            }
+        },
+        onopen_handler,
+    );
+
+    rx_connected
+        .await
+        .map_err(|_| "WebSocket connection dropped".to_string())?;
+    Ok(api)
+}
+
+/// Close a connection opened with [`connect`].
+#[cfg(all(target_family = "wasm", not(feature = "mock-node")))]
+pub(crate) fn disconnect(api: NodeApi) {
+    api.disconnect("done");
+}
+
+/// Native handle to the node connection.
+///
+/// The native `WebApi` needs `&mut` for both sending and receiving, so it
+/// lives in a pump task that forwards requests from this handle and pushes
+/// responses to the [`ResponseSender`] — the same shape as the browser API.
+#[cfg(not(any(target_family = "wasm", feature = "mock-node")))]
+pub(crate) struct NodeApi {
+    requests: UnboundedSender<freenet_stdlib::client_api::ClientRequest<'static>>,
+}
+
+#[cfg(not(any(target_family = "wasm", feature = "mock-node")))]
+impl NodeApi {
+    pub(crate) async fn send(
+        &mut self,
+        request: freenet_stdlib::client_api::ClientRequest<'static>,
+    ) -> Result<(), ClientError> {
+        self.requests
+            .unbounded_send(request)
+            .map_err(|_| freenet_stdlib::client_api::ErrorKind::ChannelClosed.into())
+    }
+}
+
+#[cfg(not(any(target_family = "wasm", feature = "mock-node")))]
+pub(crate) async fn connect(url: &str, responses: ResponseSender) -> Result<NodeApi, String> {
+    use freenet_stdlib::client_api::{ErrorKind, WebApi};
+    use futures::StreamExt;
+
+    let (stream, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| format!("WebSocket connection failed: {e}"))?;
+    let mut api = WebApi::start(stream);
+    tracing::info!("Connected to Freenet node");
+
+    let (requests, mut outgoing) = futures::channel::mpsc::unbounded();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                request = outgoing.next() => {
+                    let Some(request) = request else { break };
+                    if let Err(e) = api.send(request).await {
+                        tracing::error!("Node error: {e}");
+                        break;
+                    }
+                }
+                response = api.recv() => {
+                    let closed = matches!(&response, Err(e) if matches!(e.kind(), ErrorKind::ChannelClosed));
+                    if responses.unbounded_send(response).is_err() || closed {
+                        break;
+                    }
+                }
+            }
+        }
+        tracing::warn!("Node connection closed");
+        let _ = responses.unbounded_send(Err(ErrorKind::Disconnect.into()));
+    });
+
+    Ok(NodeApi { requests })
+}
+
+/// Close a connection opened with [`connect`]: dropping the handle ends the
+/// pump task, which drops the socket.
+#[cfg(not(any(target_family = "wasm", feature = "mock-node")))]
+pub(crate) fn disconnect(api: NodeApi) {
+    drop(api);
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use dioxus::prelude::*;
+
+use super::i18n::use_i18n;
+
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 80.0;
+const PAD: f64 = 4.0;
+
+/// Step chart of a product's price over time, ending at today.
+#[component]
+pub fn PriceHistoryChart(points: Vec<(DateTime<Utc>, u64)>) -> Element {
+    let i18n = use_i18n();
+    if points.len() < 2 {
+        return rsx! {
+            p { class: "price-history-empty", "No price changes recorded." }
+        };
+    }
+
+    let now = Utc::now();
+    let start = points[0].0;
+    let span = (now - start).num_seconds().max(1) as f64;
+    let min = points.iter().map(|(_, p)| *p).min().unwrap_or(0);
+    let max = points.iter().map(|(_, p)| *p).max().unwrap_or(0);
+    let range = (max - min).max(1) as f64;
+
+    let x = |at: DateTime<Utc>| PAD + (at - start).num_seconds() as f64 / span * (WIDTH - 2.0 * PAD);
+    let y = |price: u64| HEIGHT - PAD - (price - min) as f64 / range * (HEIGHT - 2.0 * PAD);
+
+    // Each price holds until the next change, so draw horizontal steps
+    let mut path = Vec::with_capacity(points.len() * 2 + 1);
+    for (i, (at, price)) in points.iter().enumerate() {
+        if i > 0 {
+            path.push(format!("{:.1},{:.1}", x(*at), y(points[i - 1].1)));
+        }
+        path.push(format!("{:.1},{:.1}", x(*at), y(*price)));
+    }
+    let last_price = points[points.len() - 1].1;
+    path.push(format!("{:.1},{:.1}", x(now), y(last_price)));
+    let polyline = path.join(" ");
+
+    let low = i18n.amount(min);
+    let high = i18n.amount(max);
+    let since = start.format("%d %b %Y").to_string();
+    let changes = points.len() - 1;
+
+    rsx! {
+        div { class: "price-history",
+            h4 { "Price history" }
+            svg {
+                class: "price-history-chart",
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                polyline { points: "{polyline}", fill: "none", stroke: "currentColor", stroke_width: "2" }
+            }
+            p { class: "price-history-summary",
+                "{changes} changes since {since} · low {low} · high {high}"
+            }
+        }
+    }
+}
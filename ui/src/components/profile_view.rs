@@ -1,9 +1,12 @@
 use dioxus::prelude::*;
 
+use super::backup::BackupExportPanel;
+use super::i18n::use_i18n;
 use super::key_manager::KeyManager;
 use super::shared_state::use_shared_state;
 use super::toll_rates::AdminStatus;
 use super::user_state::use_user_state;
+use cream_common::identity::UserRole;
 use cream_common::postcode::format_postcode;
 
 /// Copy text to clipboard via the navigator.clipboard API.
@@ -18,6 +21,7 @@ pub async fn copy_to_clipboard(text: &str) {
 
 #[component]
 pub fn ProfileView() -> Element {
+    let i18n = use_i18n();
     let user_state = use_user_state();
     let key_manager: Signal<Option<KeyManager>> = use_context();
     let shared = use_shared_state();
@@ -29,6 +33,7 @@ pub fn ProfileView() -> Element {
     let locality = state.locality.clone();
     let postcode_display = format_postcode(&postcode_raw, locality.as_deref());
     let is_supplier = state.is_supplier;
+    let role = state.role();
     let is_root = state.is_root;
     let supplier_description = state.supplier_description.clone();
     let is_customer = state.connected_supplier.is_some();
@@ -49,13 +54,9 @@ pub fn ProfileView() -> Element {
     .map(|uc| uc.balance_curds)
     .unwrap_or(0);
 
-    let has_products = shared_read
-        .storefronts
-        .get(&moniker)
-        .map(|sf| !sf.products.is_empty())
-        .unwrap_or(false);
-    let role_label = if has_products {
-        "Supplier"
+    // The account's role, whichever persona the header is showing
+    let role_label = if role == UserRole::Both {
+        "Supplier and customer"
     } else if is_customer {
         "Guest"
     } else {
@@ -123,8 +124,10 @@ pub fn ProfileView() -> Element {
 
             div { class: "profile-section",
                 h3 { "CURD Balance" }
-                p { class: "balance-display", "{balance} CURD" }
+                p { class: "balance-display", "{i18n.amount(balance)}" }
             }
+
+            BackupExportPanel {}
         }
     }
 }
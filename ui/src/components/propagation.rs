@@ -0,0 +1,144 @@
+//! Propagation lag: how long the network takes to hand an update back.
+//!
+//! Every update this client sends is timestamped against its contract. The
+//! next update notification for that contract is taken as the update having
+//! propagated, and the time in between is recorded as its lag. An update
+//! still unanswered after [`LOST_AFTER_SECS`] is counted as lost instead.
+//!
+//! Each contract keeps its last [`WINDOW`] outcomes, so the figures follow
+//! current conditions. Slow answers mean data is late; lost ones mean it may
+//! never arrive — the distinction the network health panel is there to show.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Outcomes kept per contract.
+pub const WINDOW: usize = 20;
+
+/// How long an update may go unanswered before it counts as lost.
+pub const LOST_AFTER_SECS: i64 = 60;
+
+/// Median lag above which a contract is reported as slow.
+pub const SLOW_AFTER_MS: i64 = 5_000;
+
+/// How a contract's updates are getting through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropagationHealth {
+    /// Nothing sent yet.
+    NoData,
+    Healthy,
+    /// Updates come back, but late.
+    Slow,
+    /// Updates have gone unanswered.
+    Lossy,
+}
+
+impl PropagationHealth {
+    pub fn label(self) -> &'static str {
+        match self {
+            PropagationHealth::NoData => "No updates yet",
+            PropagationHealth::Healthy => "Healthy",
+            PropagationHealth::Slow => "Slow",
+            PropagationHealth::Lossy => "Losing updates",
+        }
+    }
+}
+
+/// Rolling propagation stats for one contract.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PropagationLag {
+    /// Send times of updates waiting for a notification, oldest first.
+    pub outstanding: Vec<DateTime<Utc>>,
+    /// Recent outcomes, oldest first: the lag in milliseconds, or `None`
+    /// for an update that was never answered.
+    pub recent: VecDeque<Option<i64>>,
+    pub last_notified_at: Option<DateTime<Utc>>,
+}
+
+impl PropagationLag {
+    /// An update to the contract went out at `at`.
+    pub fn sent(&mut self, at: DateTime<Utc>) {
+        self.expire(at);
+        self.outstanding.push(at);
+    }
+
+    /// A notification for the contract arrived at `at`: every update still
+    /// waiting has propagated.
+    pub fn notified(&mut self, at: DateTime<Utc>) {
+        self.expire(at);
+        for sent_at in std::mem::take(&mut self.outstanding) {
+            self.record(Some((at - sent_at).num_milliseconds().max(0)));
+        }
+        self.last_notified_at = Some(at);
+    }
+
+    /// Count updates unanswered for [`LOST_AFTER_SECS`] as lost.
+    fn expire(&mut self, now: DateTime<Utc>) {
+        let cutoff = now - Duration::seconds(LOST_AFTER_SECS);
+        let lost = self.outstanding.iter().take_while(|sent_at| **sent_at < cutoff).count();
+        for _ in self.outstanding.drain(..lost) {
+            self.recent.push_back(None);
+        }
+        while self.recent.len() > WINDOW {
+            self.recent.pop_front();
+        }
+    }
+
+    fn record(&mut self, outcome: Option<i64>) {
+        self.recent.push_back(outcome);
+        if self.recent.len() > WINDOW {
+            self.recent.pop_front();
+        }
+    }
+
+    fn lags(&self) -> Vec<i64> {
+        let mut lags: Vec<i64> = self.recent.iter().flatten().copied().collect();
+        lags.sort_unstable();
+        lags
+    }
+
+    /// Median lag over the window.
+    pub fn median_ms(&self) -> Option<i64> {
+        let lags = self.lags();
+        lags.get(lags.len() / 2).copied()
+    }
+
+    /// Longest lag over the window.
+    pub fn max_ms(&self) -> Option<i64> {
+        self.lags().last().copied()
+    }
+
+    /// Updates still waiting at `now` (not yet counted as lost).
+    pub fn waiting(&self, now: DateTime<Utc>) -> usize {
+        let cutoff = now - Duration::seconds(LOST_AFTER_SECS);
+        self.outstanding.iter().filter(|sent_at| **sent_at >= cutoff).count()
+    }
+
+    /// Updates lost over the window, including any overdue at `now`.
+    pub fn lost(&self, now: DateTime<Utc>) -> usize {
+        self.recent.iter().filter(|outcome| outcome.is_none()).count() + self.outstanding.len()
+            - self.waiting(now)
+    }
+
+    pub fn health(&self, now: DateTime<Utc>) -> PropagationHealth {
+        if self.lost(now) > 0 {
+            PropagationHealth::Lossy
+        } else if self.median_ms().is_some_and(|ms| ms > SLOW_AFTER_MS) {
+            PropagationHealth::Slow
+        } else if self.recent.is_empty() && self.outstanding.is_empty() {
+            PropagationHealth::NoData
+        } else {
+            PropagationHealth::Healthy
+        }
+    }
+}
+
+/// "850 ms" or "12.3 s".
+pub fn format_lag(ms: i64) -> String {
+    if ms < 1_000 {
+        format!("{ms} ms")
+    } else {
+        format!("{:.1} s", ms as f64 / 1_000.0)
+    }
+}
@@ -1,3 +1,6 @@
+use cream_common::directory::{DirectoryEntry, DirectoryState};
+use cream_common::identity::UserId;
+use ed25519_dalek::{Signature, Verifier};
 use serde::{Deserialize, Serialize};
 
 /// Default rendezvous service URL, overridable at compile time.
@@ -19,6 +22,99 @@ pub struct RendezvousEntry {
     pub user_contract_key: Option<String>,
     #[serde(default)]
     pub inbox_contract_key: Option<String>,
+    /// The supplier's public key (hex), which must match their directory entry.
+    #[serde(default)]
+    pub public_key: String,
+    /// The supplier's registration signature (hex) over
+    /// `name|registered_address|storefront_key|user_contract_key|inbox_contract_key`.
+    #[serde(default)]
+    pub signature: String,
+    /// The address the supplier registered with, when a heartbeat has since
+    /// moved them to `address`.
+    #[serde(default)]
+    pub registered_address: Option<String>,
+    /// The supplier's heartbeat signature (hex) over `name|address`, present
+    /// when `address` differs from the registered one.
+    #[serde(default)]
+    pub address_signature: Option<String>,
+}
+
+impl RendezvousEntry {
+    /// Check the entry is the supplier's own: signed by the key their
+    /// directory entry lists, over the storefront the directory lists and the
+    /// address returned. Guards against a rendezvous service pointing
+    /// customers at a node or storefront the supplier never registered.
+    #[allow(dead_code)] // used in WASM builds
+    pub fn verify(&self, listed: &DirectoryEntry) -> Result<(), String> {
+        let key: UserId = self
+            .public_key
+            .parse()
+            .map_err(|_| format!("Rendezvous entry for '{}' has no valid public key", self.name))?;
+        if key != listed.supplier {
+            return Err(format!(
+                "Rendezvous entry for '{}' is not signed by the supplier's directory key",
+                self.name
+            ));
+        }
+        if self.storefront_key != listed.storefront_key.to_string() {
+            return Err(format!(
+                "Rendezvous entry for '{}' points at a storefront not in the directory",
+                self.name
+            ));
+        }
+
+        let registered_address = self.registered_address.as_deref().unwrap_or(&self.address);
+        let registration = format!(
+            "{}|{}|{}|{}|{}",
+            self.name,
+            registered_address,
+            self.storefront_key,
+            self.user_contract_key.as_deref().unwrap_or(""),
+            self.inbox_contract_key.as_deref().unwrap_or(""),
+        );
+        verify_hex(&key, &registration, &self.signature)
+            .map_err(|e| format!("Rendezvous entry for '{}': registration {}", self.name, e))?;
+
+        if registered_address != self.address {
+            let moved = format!("{}|{}", self.name, self.address);
+            let signature = self.address_signature.as_deref().unwrap_or("");
+            verify_hex(&key, &moved, signature)
+                .map_err(|e| format!("Rendezvous entry for '{}': address {}", self.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+pub use cream_common::migration::rendezvous_name;
+
+/// The directory entry of the supplier registered under `name`, if listed.
+pub fn directory_listing(directory: &DirectoryState, name: &str) -> Option<DirectoryEntry> {
+    let wanted = rendezvous_name(name);
+    directory
+        .entries
+        .values()
+        .find(|entry| rendezvous_name(&entry.name) == wanted)
+        .cloned()
+}
+
+#[allow(dead_code)] // used in WASM builds
+fn verify_hex(key: &UserId, message: &str, signature_hex: &str) -> Result<(), String> {
+    let bytes = from_hex(signature_hex).ok_or("signature is missing or not hex")?;
+    let signature = Signature::from_slice(&bytes).map_err(|_| "signature is malformed")?;
+    key.0
+        .verify(message.as_bytes(), &signature)
+        .map_err(|_| "signature does not verify".to_string())
+}
+
+#[allow(dead_code)] // used in WASM builds
+fn from_hex(s: &str) -> Option<Vec<u8>> {
+    if s.is_empty() || s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
 }
 
 /// Error response from the rendezvous service.
@@ -153,16 +249,24 @@ mod wasm_impl {
 
 // ─── Public API ──────────────────────────────────────────────────────────────
 
-/// Look up a supplier by name from the rendezvous service.
+/// Look up a supplier by name from the rendezvous service, accepting the
+/// entry only if it verifies against the supplier's directory listing.
 #[allow(dead_code)] // used in WASM builds
-pub async fn lookup_supplier(name: &str) -> Result<RendezvousEntry, String> {
+pub async fn lookup_supplier(
+    name: &str,
+    listed: Option<DirectoryEntry>,
+) -> Result<RendezvousEntry, String> {
+    let listed = listed
+        .ok_or_else(|| format!("'{}' is not in the directory, so can't be verified", name))?;
     #[cfg(target_family = "wasm")]
     {
-        wasm_impl::lookup_supplier(name).await
+        let entry = wasm_impl::lookup_supplier(name).await?;
+        entry.verify(&listed)?;
+        Ok(entry)
     }
     #[cfg(not(target_family = "wasm"))]
     {
-        let _ = name;
+        let _ = (name, listed);
         Err("Rendezvous client only available in WASM".into())
     }
 }
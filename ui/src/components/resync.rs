@@ -0,0 +1,84 @@
+//! Catching up with the network after a reconnect.
+//!
+//! While the connection is down the client keeps showing what it had, and
+//! updates sent just before the drop may never have reached the network.
+//! After a reconnect [`SubscriptionManager::reconcile`] GETs every followed
+//! contract again. For storefronts and user contracts that answer is not
+//! simply stored over the cached copy; the cached state, the fresh state and
+//! the changes still pending from before the drop are reconciled:
+//!
+//! - The fresh state is merged with the cached one using the contract's own
+//!   merge, so nothing newer the client already knew about disappears.
+//! - If the merge added anything the node didn't have, the network lost it:
+//!   the cached state is sent again as an update, which the contract merges
+//!   the same way.
+//! - Pending changes (see [`super::optimistic`]) will never be confirmed on
+//!   the old connection. If the fresh state already holds them they are
+//!   done; otherwise they ride on the re-sent update and are undone, back to
+//!   the fresh state, if the contract rejects it.
+//!
+//! [`SubscriptionManager::reconcile`]: super::subscriptions::SubscriptionManager::reconcile
+
+use freenet_stdlib::client_api::ClientRequest;
+use freenet_stdlib::prelude::ContractKey;
+use serde::Serialize;
+
+use cream_client::request;
+
+use super::optimistic::{OptimisticUpdate, Rollback};
+use super::shared_state::SharedState;
+
+/// A post-reconnect GET folded into what the client had cached.
+pub(crate) struct Reconciled<S> {
+    /// What to show: the fresh state merged with the cached one.
+    pub state: S,
+    /// The cached state, when it held something the fresh one lacks.
+    pub resend: Option<S>,
+}
+
+/// Merge `fresh` from the node with `cached` using `merge`.
+pub(crate) fn reconcile<S: Clone + Serialize>(
+    cached: Option<S>,
+    fresh: S,
+    merge: impl Fn(&mut S, S),
+) -> Reconciled<S> {
+    let Some(cached) = cached else {
+        return Reconciled { state: fresh, resend: None };
+    };
+    // Merges canonicalise (sorting, compaction), so compare against the
+    // fresh state merged with itself rather than as received.
+    let mut baseline = fresh.clone();
+    merge(&mut baseline, fresh.clone());
+    let mut merged = fresh;
+    merge(&mut merged, cached.clone());
+    let lost = serde_json::to_vec(&merged).ok() != serde_json::to_vec(&baseline).ok();
+    Reconciled { state: merged, resend: lost.then_some(cached) }
+}
+
+impl SharedState {
+    /// Settle the changes to `key` still pending from before a reconnect.
+    /// With `resend` — the state to send again and how to undo it — they are
+    /// tracked against that update instead, which is returned to be sent.
+    pub fn settle_after_reconnect(
+        &mut self,
+        key: ContractKey,
+        resend: Option<(impl Serialize, Rollback)>,
+    ) -> Vec<ClientRequest<'static>> {
+        let instance_id = key.id().to_string();
+        let pending = self.optimistic.remove(&instance_id).unwrap_or_default();
+        let Some((state, rollback)) = resend else {
+            if !pending.is_empty() {
+                tracing::info!("{} pending change(s) to {instance_id} reached the network", pending.len());
+            }
+            return vec![];
+        };
+        let label = if pending.is_empty() {
+            "Resync".to_string()
+        } else {
+            pending.iter().map(|update| update.label.as_str()).collect::<Vec<_>>().join(", ")
+        };
+        tracing::info!("Re-sending {instance_id} after reconnect ({label})");
+        self.optimistic.insert(instance_id, vec![OptimisticUpdate { label, rollback }]);
+        vec![request::update_state(key, &state)]
+    }
+}
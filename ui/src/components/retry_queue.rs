@@ -0,0 +1,221 @@
+//! Retry queue for outgoing contract requests.
+//!
+//! [`RetryingApi`] wraps the node connection. A request that fails to send is
+//! not dropped: it is queued under an idempotency key and re-sent with
+//! exponential backoff. Queued operations are mirrored into
+//! [`SharedState::pending_ops`] so the UI can show what is still in flight,
+//! and once the automatic attempts run out the operation stays there as
+//! failed until the user retries or dismisses it.
+//!
+//! Re-sending is safe because contract updates are merges: applying the same
+//! delta twice leaves the state unchanged. The key only stops the queue from
+//! racing itself (a stale timer firing after a manual retry went through).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use freenet_stdlib::client_api::{ClientError, ClientRequest, ContractRequest};
+use freenet_stdlib::prelude::ContractInstanceId;
+use futures::channel::mpsc::UnboundedSender;
+
+use cream_common::retry::{Backoff, RetryPolicy};
+
+use super::node_api::generate_tx_ref;
+use super::optimistic::Rollback;
+use super::platform::{self, NodeApi};
+use super::shared_state::{PendingOp, SharedState};
+
+/// Retry policy for failed sends: 5 attempts, 1s doubling to at most 30s.
+const SEND_RETRY: RetryPolicy = RetryPolicy {
+    max_attempts: Some(5),
+    initial_delay: Duration::from_secs(1),
+    max_delay: Duration::from_secs(30),
+    multiplier: 2,
+    jitter: 0.2,
+};
+
+/// Timer firing for a queued operation: idempotency key and the schedule
+/// generation it was set for.
+pub(crate) type RetryTick = (String, u32);
+
+struct QueuedOp {
+    request: ClientRequest<'static>,
+    backoff: Backoff,
+    /// Bumped on every (re)schedule so stale timers are ignored.
+    generation: u32,
+}
+
+/// Node connection that queues and retries requests it fails to send.
+pub(crate) struct RetryingApi {
+    api: NodeApi,
+    queue: HashMap<String, QueuedOp>,
+    ticks: UnboundedSender<RetryTick>,
+    shared: Signal<SharedState>,
+    /// Shown against queued operations (the action being handled).
+    label: &'static str,
+}
+
+impl RetryingApi {
+    /// Wrap `api`; retry timers are delivered on `ticks` and should be
+    /// passed back to [`RetryingApi::on_tick`].
+    pub fn new(api: NodeApi, shared: Signal<SharedState>, ticks: UnboundedSender<RetryTick>) -> Self {
+        Self {
+            api,
+            queue: HashMap::new(),
+            ticks,
+            shared,
+            label: "Startup",
+        }
+    }
+
+    /// Carry on over a fresh connection after the old one dropped. Queued
+    /// operations go out on it at their next tick.
+    pub fn reconnected(&mut self, api: NodeApi) {
+        self.api = api;
+    }
+
+    /// Label for requests sent from now on.
+    pub fn set_label(&mut self, label: &'static str) {
+        self.label = label;
+    }
+
+    /// Send `request`. On failure it is queued for retry and the error is
+    /// still returned so the caller can report it.
+    pub async fn send(&mut self, request: ClientRequest<'static>) -> Result<(), ClientError> {
+        match self.api.send(request.clone()).await {
+            Ok(()) => {
+                self.note_sent(&request);
+                Ok(())
+            }
+            Err(e) => {
+                self.enqueue(request, &e);
+                Err(e)
+            }
+        }
+    }
+
+    /// Send an update whose change is already in shared state; `rollback`
+    /// undoes it if the contract rejects the update (see [`super::optimistic`]).
+    pub async fn send_optimistic(
+        &mut self,
+        request: ClientRequest<'static>,
+        rollback: Rollback,
+    ) -> Result<(), ClientError> {
+        if let Some(id) = update_target(&request) {
+            self.shared.write().track_update(id.to_string(), self.label, rollback);
+        }
+        self.send(request).await
+    }
+
+    /// Start timing an update's propagation (see [`super::propagation`]).
+    fn note_sent(&mut self, request: &ClientRequest) {
+        if let Some(id) = update_target(request) {
+            self.shared.write().propagation.entry(id.to_string()).or_default().sent(chrono::Utc::now());
+        }
+    }
+
+    fn enqueue(&mut self, request: ClientRequest<'static>, error: &ClientError) {
+        let key = generate_tx_ref(self.label);
+        tracing::warn!("Queued {} for retry ({key}): {error}", self.label);
+        self.shared.write().pending_ops.insert(
+            key.clone(),
+            PendingOp {
+                label: self.label.to_string(),
+                attempts: 1,
+                last_error: error.to_string(),
+                next_attempt_at: None,
+            },
+        );
+        self.queue.insert(
+            key.clone(),
+            QueuedOp {
+                request,
+                backoff: SEND_RETRY.backoff(platform::rand_u32() as u64),
+                generation: 0,
+            },
+        );
+        self.schedule(&key);
+    }
+
+    /// Arm the next backoff timer for `key`, or mark it failed once the
+    /// attempts are used up.
+    fn schedule(&mut self, key: &str) {
+        let Some(op) = self.queue.get_mut(key) else { return };
+        op.generation += 1;
+        let delay = op.backoff.next_delay();
+        let mut shared = self.shared.write();
+        let Some(pending) = shared.pending_ops.get_mut(key) else { return };
+        match delay {
+            Some(delay) => {
+                pending.next_attempt_at = chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|d| chrono::Utc::now() + d);
+                let tick = (key.to_string(), op.generation);
+                let ticks = self.ticks.clone();
+                platform::spawn_local(async move {
+                    platform::sleep(delay).await;
+                    let _ = ticks.unbounded_send(tick);
+                });
+            }
+            None => {
+                pending.next_attempt_at = None;
+                tracing::error!(
+                    "Giving up on {} after {} attempts: {}",
+                    pending.label, pending.attempts, pending.last_error
+                );
+            }
+        }
+    }
+
+    /// A backoff timer fired.
+    pub async fn on_tick(&mut self, (key, generation): RetryTick) {
+        if self.queue.get(&key).is_some_and(|op| op.generation == generation) {
+            self.resend(&key).await;
+        }
+    }
+
+    /// User-initiated retry: send now and restart the backoff if it fails.
+    pub async fn retry(&mut self, key: &str) {
+        let Some(op) = self.queue.get_mut(key) else { return };
+        op.backoff.reset();
+        op.generation += 1; // cancel any pending timer
+        self.resend(key).await;
+    }
+
+    /// Drop a queued operation without sending it, undoing its local change.
+    pub fn dismiss(&mut self, key: &str) {
+        let mut shared = self.shared.write();
+        if let Some(id) = self.queue.remove(key).and_then(|op| update_target(&op.request)) {
+            shared.roll_back_updates(&id.to_string());
+        }
+        shared.pending_ops.remove(key);
+    }
+
+    async fn resend(&mut self, key: &str) {
+        let Some(request) = self.queue.get(key).map(|op| op.request.clone()) else { return };
+        match self.api.send(request.clone()).await {
+            Ok(()) => {
+                tracing::info!("Retried {key} successfully");
+                self.note_sent(&request);
+                self.queue.remove(key);
+                self.shared.write().pending_ops.remove(key);
+            }
+            Err(e) => {
+                if let Some(pending) = self.shared.write().pending_ops.get_mut(key) {
+                    pending.attempts += 1;
+                    pending.last_error = e.to_string();
+                }
+                self.schedule(key);
+            }
+        }
+    }
+}
+
+/// The contract an update request is for.
+fn update_target(request: &ClientRequest) -> Option<ContractInstanceId> {
+    match request {
+        ClientRequest::ContractOp(ContractRequest::Update { key, .. }) => Some(*key.id()),
+        _ => None,
+    }
+}
@@ -1,12 +1,28 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
 
 use dioxus::prelude::*;
 
 use cream_common::directory::{DirectoryEntry, DirectoryState};
 use cream_common::inbox::{InboxMessage, InboxState};
+use cream_common::identity::UserId;
 use cream_common::market::MarketDirectoryState;
-use cream_common::storefront::StorefrontState;
+use cream_common::order::{Order, OrderId};
+use cream_common::product::ProductId;
+use cream_common::provenance::{updated_ago, WriterStamp};
+use cream_common::registry::NameRegistryState;
+use cream_common::storefront::{StorefrontDiff, StorefrontState};
 use cream_common::user_contract::UserContractState;
+use cream_common::voucher::Voucher;
+
+use super::key_manager::KeyManager;
+use super::ledger_status::LedgerEntries;
+use super::optimistic::{OptimisticUpdate, Toast};
+use super::order_filter::{OrderIndex, OrderRow};
+use super::propagation::PropagationLag;
+use super::subscriptions::SubscriptionInfo;
+use super::user_state::{use_user_state, ReadMarkers};
 
 /// Network-sourced state shared across all components.
 ///
@@ -16,16 +32,24 @@ use cream_common::user_contract::UserContractState;
 pub struct SharedState {
     /// Supplier directory from the directory contract.
     pub directory: DirectoryState,
-    /// Subscribed storefronts keyed by supplier name.
+    /// Subscribed storefronts keyed by supplier name. Write through
+    /// [`SharedState::set_storefront`] so the revisions stay current.
     pub storefronts: HashMap<String, StorefrontState>,
+    /// Per-storefront change counters, keyed like `storefronts`.
+    pub storefront_revisions: HashMap<String, StorefrontRevisions>,
+    /// Per-storefront order lists for filtering and sorting, keyed like
+    /// `storefronts`.
+    pub order_index: HashMap<String, OrderIndex>,
     /// Map from supplier name to their storefront contract key (as string).
     #[allow(dead_code)] // used in WASM builds only
     pub storefront_keys: HashMap<String, String>,
     /// Whether we're connected to a Freenet node.
     pub connected: bool,
-    /// Directory contract key (set after PUT or GET).
+    /// URL of the node we're connected to (or last connected to).
+    pub node_url: Option<String>,
+    /// Keys of the directory shard contracts we follow.
     #[allow(dead_code)] // used in WASM builds only
-    pub directory_contract_key: Option<String>,
+    pub directory_contract_keys: Vec<String>,
     /// Last error message from node communication.
     pub last_error: Option<String>,
     /// The current user's own contract state from the network.
@@ -51,12 +75,102 @@ pub struct SharedState {
     pub inbox_contract_key: Option<String>,
     /// Locally-tracked sent messages (not stored on-chain, for UI display).
     pub sent_messages: Vec<SentMessage>,
+    /// Vouchers issued this session, newest last. Only the code carries a
+    /// voucher, so the dashboard shows them until the page is reloaded.
+    pub issued_vouchers: Vec<Voucher>,
     /// Market directory state from the market directory contract.
     #[allow(dead_code)] // populated in WASM builds
     pub market_directory: MarketDirectoryState,
     /// The market directory contract key (Base58).
     #[allow(dead_code)] // used in WASM builds only
     pub market_directory_key: Option<String>,
+    /// Username registry state from the name registry contract.
+    #[allow(dead_code)] // populated in WASM builds
+    pub name_registry: NameRegistryState,
+    /// The name registry contract key (Base58).
+    #[allow(dead_code)] // used in WASM builds only
+    pub name_registry_key: Option<String>,
+    /// Per-contract diagnostics for the /debug inspector, keyed by instance id (Base58).
+    pub contracts: BTreeMap<String, ContractDebugInfo>,
+    /// Propagation lag of the updates this client sent, by contract
+    /// instance id (Base58).
+    pub propagation: BTreeMap<String, PropagationLag>,
+    /// The node-side state of every contract subscription, by contract
+    /// instance id (Base58). See [`super::subscriptions`].
+    pub subscriptions: BTreeMap<String, SubscriptionInfo>,
+    /// Contract operations that failed to send, keyed by idempotency key.
+    pub pending_ops: BTreeMap<String, PendingOp>,
+    /// Local changes the node hasn't accepted yet, oldest first per contract
+    /// instance id (Base58). See [`super::optimistic`].
+    pub optimistic: BTreeMap<String, Vec<OptimisticUpdate>>,
+    /// Notices for the user, oldest first.
+    pub toasts: Vec<Toast>,
+    /// Ledger entries this client wrote this session, and whether the
+    /// network has them yet. See [`super::ledger_status`].
+    pub ledger_entries: LedgerEntries,
+    /// Whether this supplier's node has registered with the rendezvous
+    /// service this session.
+    pub rendezvous_registered: bool,
+}
+
+/// Which CREAM contract an instance id belongs to.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ContractKind {
+    Directory,
+    MarketDirectory,
+    NameRegistry,
+    /// A supplier storefront, with the directory name if known.
+    Storefront(Option<String>),
+    UserContract,
+    RootContract,
+    Inbox,
+}
+
+impl std::fmt::Display for ContractKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContractKind::Directory => write!(f, "Directory"),
+            ContractKind::MarketDirectory => write!(f, "Market directory"),
+            ContractKind::NameRegistry => write!(f, "Name registry"),
+            ContractKind::Storefront(Some(name)) => write!(f, "Storefront: {}", name),
+            ContractKind::Storefront(None) => write!(f, "Storefront"),
+            ContractKind::UserContract => write!(f, "User contract"),
+            ContractKind::RootContract => write!(f, "Root contract"),
+            ContractKind::Inbox => write!(f, "Inbox"),
+        }
+    }
+}
+
+/// What the client has seen from the node for one contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContractDebugInfo {
+    pub kind: ContractKind,
+    /// Most recent payload received (full state or delta), exactly as received.
+    pub last_payload: Option<Vec<u8>>,
+    /// Whether `last_payload` was a delta rather than a full state.
+    pub last_payload_is_delta: bool,
+    pub last_get_at: Option<DateTime<Utc>>,
+    pub last_notification_at: Option<DateTime<Utc>>,
+    pub notification_count: u32,
+    /// Outcome of the most recent Subscribe, if one has been answered.
+    pub subscribed: Option<bool>,
+    /// The node answered NotFound for this contract.
+    pub not_found: bool,
+}
+
+impl ContractDebugInfo {
+    pub fn new(kind: ContractKind) -> Self {
+        Self {
+            kind,
+            last_payload: None,
+            last_payload_is_delta: false,
+            last_get_at: None,
+            last_notification_at: None,
+            notification_count: 0,
+            subscribed: None,
+            not_found: false,
+        }
+    }
 }
 
 /// A message the current user sent, tracked locally for display in the Messages view.
@@ -66,11 +180,238 @@ pub struct SentMessage {
     pub message: InboxMessage,
 }
 
+/// Change counters for the parts of one storefront that are rendered
+/// item by item, bumped from each [`StorefrontDiff`].
+///
+/// Components select the counter for the item they show through a memo
+/// (see [`use_product_revision`]), so an update re-renders only the products
+/// and threads it touched rather than everything reading the storefront.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StorefrontRevisions {
+    /// Listing or available quantity changed.
+    products: HashMap<ProductId, u64>,
+    threads: HashMap<OrderId, u64>,
+    latest: u64,
+}
+
+impl StorefrontRevisions {
+    fn record(&mut self, diff: &StorefrontDiff) {
+        self.latest += 1;
+        for id in diff.products.iter().chain(&diff.stock) {
+            self.products.insert(id.clone(), self.latest);
+        }
+        for id in &diff.threads {
+            self.threads.insert(id.clone(), self.latest);
+        }
+    }
+
+    pub fn product(&self, id: &ProductId) -> u64 {
+        self.products.get(id).copied().unwrap_or(0)
+    }
+
+    pub fn thread(&self, id: &OrderId) -> u64 {
+        self.threads.get(id).copied().unwrap_or(0)
+    }
+}
+
+/// An outgoing contract operation that failed to send and is queued for retry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingOp {
+    /// Action that issued it (e.g. "PlaceOrder").
+    pub label: String,
+    /// Send attempts so far.
+    pub attempts: u32,
+    pub last_error: String,
+    /// When the next automatic attempt is due; `None` once retries are
+    /// exhausted and the operation waits for the user.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl PendingOp {
+    /// Automatic retries are exhausted.
+    pub fn failed(&self) -> bool {
+        self.next_attempt_at.is_none()
+    }
+}
+
+/// Unread activity since the user last looked, for nav badges.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnreadCounts {
+    /// Unread inbox messages per sender (suppliers are keyed by storefront name).
+    pub messages: BTreeMap<String, usize>,
+    /// New or changed orders on the user's own storefront.
+    pub storefront_orders: usize,
+    /// Status changes on orders the user placed.
+    pub my_orders: usize,
+}
+
+impl UnreadCounts {
+    pub fn total_messages(&self) -> usize {
+        self.messages.values().sum()
+    }
+}
+
+fn order_marker_key(supplier: &str, order: &Order) -> String {
+    format!("{}/{}", supplier, order.id.0)
+}
+
 impl SharedState {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// What replacing the storefront stored under `name` with `storefront`
+    /// would change. Check this under a read lock first: taking the write
+    /// lock re-renders every reader even if nothing changes.
+    pub fn storefront_diff(&self, name: &str, storefront: &StorefrontState) -> StorefrontDiff {
+        match self.storefronts.get(name) {
+            Some(existing) => existing.diff(storefront),
+            None => StorefrontDiff::all(storefront),
+        }
+    }
+
+    /// Store `storefront` under `name`, bumping the revisions of whatever
+    /// changed.
+    pub fn set_storefront(&mut self, name: String, storefront: StorefrontState) {
+        let diff = self.storefront_diff(&name, &storefront);
+        self.apply_storefront(name, storefront, &diff);
+    }
+
+    /// Store `storefront` under `name` given its precomputed `diff`.
+    pub fn apply_storefront(&mut self, name: String, storefront: StorefrontState, diff: &StorefrontDiff) {
+        self.storefront_revisions.entry(name.clone()).or_default().record(diff);
+        if !diff.orders.is_empty() || !diff.products.is_empty() {
+            self.order_index.insert(name.clone(), OrderIndex::build(&name, &storefront));
+        }
+        self.storefronts.insert(name, storefront);
+    }
+
+    /// Forget the storefront stored under `name`.
+    pub fn remove_storefront(&mut self, name: &str) {
+        self.storefronts.remove(name);
+        self.order_index.remove(name);
+    }
+
+    /// Diagnostics entry for a contract, created on first sight.
+    /// A storefront name learned later replaces an unnamed kind.
+    #[allow(dead_code)] // used in WASM builds only
+    pub fn contract_debug(&mut self, instance_id: String, kind: ContractKind) -> &mut ContractDebugInfo {
+        let info = self
+            .contracts
+            .entry(instance_id)
+            .or_insert_with(|| ContractDebugInfo::new(kind.clone()));
+        if info.kind == ContractKind::Storefront(None) {
+            info.kind = kind;
+        }
+        info
+    }
+
+    /// "Updated by Gary 5 min ago" for a writer stamp. Writers not in the
+    /// directory (e.g. customers) are shown by a short key prefix.
+    pub fn provenance_label(&self, stamp: &WriterStamp) -> String {
+        let writer = UserId(stamp.last_writer);
+        let name = self
+            .directory
+            .entries
+            .get(&writer)
+            .map(|e| e.name.clone())
+            .unwrap_or_else(|| writer.to_string()[..8].to_string());
+        format!("Updated by {} {}", name, updated_ago(stamp.written_at, Utc::now()))
+    }
+
+    /// Orders on the user's own storefront, with the supplier name they're marked under.
+    fn storefront_orders<'a>(&'a self, own_storefront: Option<&'a str>) -> impl Iterator<Item = (&'a str, &'a Order)> {
+        own_storefront
+            .and_then(|name| self.storefronts.get_key_value(name))
+            .into_iter()
+            .flat_map(|(name, sf)| sf.orders.values().map(move |o| (name.as_str(), o)))
+    }
+
+    /// Orders placed by `me` on other suppliers' storefronts.
+    fn placed_orders<'a>(&'a self, me: Option<&'a UserId>, own_storefront: Option<&'a str>) -> impl Iterator<Item = (&'a str, &'a Order)> {
+        self.storefronts
+            .iter()
+            .filter(move |(name, _)| Some(name.as_str()) != own_storefront)
+            .flat_map(move |(name, sf)| {
+                sf.orders
+                    .values()
+                    .filter(move |o| Some(&o.customer) == me)
+                    .map(move |o| (name.as_str(), o))
+            })
+    }
+
+    /// Orders `me` placed on other suppliers' storefronts, indexed together.
+    pub fn placed_order_index(&self, me: Option<&UserId>, own_storefront: Option<&str>) -> OrderIndex {
+        OrderIndex::from_rows(
+            self.order_index
+                .iter()
+                .filter(|(name, _)| Some(name.as_str()) != own_storefront)
+                .flat_map(|(_, index)| index.rows())
+                .filter(|row| Some(&row.customer) == me)
+                .cloned(),
+        )
+    }
+
+    /// A customer's name for order lists: their claimed username, else their
+    /// directory name, else a short key prefix.
+    pub fn customer_name(&self, row: &OrderRow) -> String {
+        self.name_registry
+            .claims
+            .values()
+            .find(|claim| claim.owner == row.customer)
+            .map(|claim| claim.name.clone())
+            .or_else(|| self.directory.entries.get(&row.customer).map(|e| e.name.clone()))
+            .unwrap_or_else(|| row.customer.to_string()[..8].to_string())
+    }
+
+    /// Count what has arrived or changed since the user's read markers.
+    pub fn unread(&self, markers: &ReadMarkers, me: Option<&UserId>, own_storefront: Option<&str>) -> UnreadCounts {
+        let mut counts = UnreadCounts::default();
+        if let Some(inbox) = &self.inbox {
+            for msg in inbox.messages.values() {
+                let seen = markers.messages_seen.get(&msg.from_name);
+                if seen.is_none_or(|at| msg.created_at > *at) {
+                    *counts.messages.entry(msg.from_name.clone()).or_default() += 1;
+                }
+            }
+        }
+        let changed = |(supplier, order): (&str, &Order)| {
+            markers.order_status_seen.get(&order_marker_key(supplier, order)).map(String::as_str)
+                != Some(order.status.label())
+        };
+        counts.storefront_orders = self.storefront_orders(own_storefront).filter(|o| changed(*o)).count();
+        counts.my_orders = self.placed_orders(me, own_storefront).filter(|o| changed(*o)).count();
+        counts
+    }
+
+    /// Mark every inbox message as read. Returns whether anything changed.
+    pub fn mark_messages_read(&self, markers: &mut ReadMarkers) -> bool {
+        let mut changed = false;
+        for msg in self.inbox.iter().flat_map(|i| i.messages.values()) {
+            let seen = markers.messages_seen.get(&msg.from_name);
+            if seen.is_none_or(|at| msg.created_at > *at) {
+                markers.messages_seen.insert(msg.from_name.clone(), msg.created_at);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Mark the current status of orders on the user's own storefront as seen.
+    pub fn mark_storefront_orders_read(&self, markers: &mut ReadMarkers, own_storefront: Option<&str>) -> bool {
+        mark_orders(markers, self.storefront_orders(own_storefront))
+    }
+
+    /// Mark the current status of orders the user placed as seen.
+    pub fn mark_my_orders_read(&self, markers: &mut ReadMarkers, me: Option<&UserId>, own_storefront: Option<&str>) -> bool {
+        mark_orders(markers, self.placed_orders(me, own_storefront))
+    }
+
+    /// Whether `user` is a registered supplier, and so may see wholesale listings.
+    pub fn is_supplier(&self, user: &UserId) -> bool {
+        self.directory.entries.contains_key(user)
+    }
+
     /// Get all directory entries sorted by name.
     #[allow(dead_code)] // useful utility, will be used
     pub fn supplier_entries(&self) -> Vec<&DirectoryEntry> {
@@ -80,6 +421,66 @@ impl SharedState {
     }
 }
 
+fn mark_orders<'a>(markers: &mut ReadMarkers, orders: impl Iterator<Item = (&'a str, &'a Order)>) -> bool {
+    let mut changed = false;
+    for (supplier, order) in orders {
+        let label = order.status.label();
+        let key = order_marker_key(supplier, order);
+        if markers.order_status_seen.get(&key).map(String::as_str) != Some(label) {
+            markers.order_status_seen.insert(key, label.to_string());
+            changed = true;
+        }
+    }
+    changed
+}
+
 pub fn use_shared_state() -> Signal<SharedState> {
     use_context::<Signal<SharedState>>()
 }
+
+/// Revision of one product in a storefront: changes exactly when the
+/// product's listing or available quantity does. Read it to subscribe, then
+/// read the product itself with `peek()`.
+pub fn use_product_revision(supplier_name: String, product_id: ProductId) -> Memo<u64> {
+    let shared = use_shared_state();
+    use_memo(use_reactive!(|supplier_name, product_id| {
+        shared
+            .read()
+            .storefront_revisions
+            .get(&supplier_name)
+            .map_or(0, |revs| revs.product(&product_id))
+    }))
+}
+
+/// Revision of one order's message thread; see [`use_product_revision`].
+pub fn use_thread_revision(supplier_name: String, order_id: OrderId) -> Memo<u64> {
+    let shared = use_shared_state();
+    use_memo(use_reactive!(|supplier_name, order_id| {
+        shared
+            .read()
+            .storefront_revisions
+            .get(&supplier_name)
+            .map_or(0, |revs| revs.thread(&order_id))
+    }))
+}
+
+/// Keep read markers current while a view is open. `mark` runs whenever
+/// shared state changes; the markers are saved when it reports a change.
+pub fn use_mark_read(mark: fn(&SharedState, &mut ReadMarkers, Option<&UserId>, Option<&str>) -> bool) {
+    let shared = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    let mut user_state = use_user_state();
+    use_effect(move || {
+        let my_id = key_manager.read().as_ref().map(|km| km.user_id());
+        let shared = shared.read();
+        let (mut markers, own_storefront) = {
+            let us = user_state.peek();
+            (us.read_markers.clone(), us.own_storefront().map(str::to_string))
+        };
+        if mark(&shared, &mut markers, my_id.as_ref(), own_storefront.as_deref()) {
+            let mut us = user_state.write();
+            us.read_markers = markers;
+            us.save();
+        }
+    });
+}
@@ -3,7 +3,12 @@
 //! Provides two modes:
 //! - **Local**: all shares in-process (wraps `root_sign()` — current trusted-dealer behavior)
 //! - **Remote**: coordinates with guardian daemons over HTTP for distributed signing
+//!
+//! Either way the caller says what it wants signed as a [`SigningRequest`];
+//! guardians check it against their own view of the contracts before
+//! contributing a share.
 
+use cream_common::signing_request::SigningRequest;
 use serde::{Deserialize, Serialize};
 
 /// Compile-time guardian URLs, overridable via `CREAM_GUARDIAN_URLS` env var.
@@ -48,13 +53,13 @@ impl SigningService {
         }
     }
 
-    /// Sign a message using FROST threshold signatures.
-    pub async fn sign(&self, message: &[u8]) -> Result<ed25519_dalek::Signature, String> {
+    /// Sign `request`'s message using FROST threshold signatures.
+    pub async fn sign(&self, request: &SigningRequest) -> Result<ed25519_dalek::Signature, String> {
         match self {
-            SigningService::Local => Ok(cream_common::identity::root_sign(message)),
+            SigningService::Local => Ok(cream_common::identity::root_sign(&request.message())),
             #[cfg(target_family = "wasm")]
             SigningService::Remote { guardian_urls, cached_pubkey } => {
-                wasm_impl::remote_sign(guardian_urls, message, cached_pubkey).await
+                wasm_impl::remote_sign(guardian_urls, request, cached_pubkey).await
             }
             #[cfg(not(target_family = "wasm"))]
             SigningService::Remote { .. } => {
@@ -66,9 +71,9 @@ impl SigningService {
 
 // ─── API types (shared with guardian daemon) ─────────────────────────────────
 
-#[derive(Serialize, Deserialize)]
-struct Round1Request {
-    session_id: String,
+#[derive(Serialize)]
+struct Round1Request<'a> {
+    request: &'a SigningRequest,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -77,10 +82,19 @@ struct Round1Response {
     commitments: frost_ed25519::round1::SigningCommitments,
 }
 
+/// A guardian's round 1 answer: its commitments, and the token to sign
+/// with in round 2.
+#[derive(Deserialize)]
+struct Round1Session {
+    session_token: String,
+    identifier: frost_ed25519::Identifier,
+    commitments: frost_ed25519::round1::SigningCommitments,
+}
+
 #[derive(Serialize)]
-struct Round2Request {
-    session_id: String,
-    message_hex: String,
+struct Round2Request<'a> {
+    session_token: String,
+    request: &'a SigningRequest,
     signing_commitments: Vec<Round1Response>,
 }
 
@@ -120,11 +134,10 @@ mod wasm_impl {
     /// Perform distributed FROST signing via guardian HTTP daemons.
     pub async fn remote_sign(
         guardian_urls: &[String],
-        message: &[u8],
+        request: &SigningRequest,
         cached_pubkey: &std::sync::Arc<std::sync::Mutex<Option<frost::keys::PublicKeyPackage>>>,
     ) -> Result<ed25519_dalek::Signature, String> {
-        let session_id = generate_session_id()?;
-        let message_hex = bytes_to_hex(message);
+        let message = request.message();
         let min_signers = fetch_min_signers(&guardian_urls[0]).await;
 
         // Round 1: collect commitments from all guardians concurrently
@@ -132,26 +145,23 @@ mod wasm_impl {
             .iter()
             .map(|url| {
                 let url = url.clone();
-                let session_id = session_id.clone();
                 async move {
-                    let req = Round1Request {
-                        session_id: session_id.clone(),
-                    };
+                    let req = Round1Request { request };
                     let body = serde_json::to_string(&req).map_err(|e| e.to_string())?;
                     let resp_text =
                         fetch_json(&format!("{}/round1", url), "POST", Some(body)).await?;
-                    let resp: Round1Response =
+                    let resp: Round1Session =
                         serde_json::from_str(&resp_text).map_err(|e| {
                             format!("Failed to parse round1 response from {}: {}", url, e)
                         })?;
-                    Ok::<(String, Round1Response), String>((url, resp))
+                    Ok::<(String, Round1Session), String>((url, resp))
                 }
             })
             .collect();
 
         // Await all and take the first min_signers successes
         let results = futures::future::join_all(round1_futures).await;
-        let mut successes: Vec<(String, Round1Response)> = Vec::new();
+        let mut successes: Vec<(String, Round1Session)> = Vec::new();
         let mut errors: Vec<String> = Vec::new();
 
         for result in results {
@@ -172,23 +182,28 @@ mod wasm_impl {
         }
 
         // Take exactly min_signers participants
-        let participants: Vec<(String, Round1Response)> =
+        let participants: Vec<(String, Round1Session)> =
             successes.into_iter().take(min_signers).collect();
-        let all_commitments: Vec<Round1Response> =
-            participants.iter().map(|(_, r)| r.clone()).collect();
+        let all_commitments: Vec<Round1Response> = participants
+            .iter()
+            .map(|(_, s)| Round1Response {
+                identifier: s.identifier,
+                commitments: s.commitments,
+            })
+            .collect();
 
-        // Round 2: send commitments map + message to each participant
+        // Round 2: send commitments map + request to each participant, under
+        // the session token it issued
         let round2_futures: Vec<_> = participants
             .iter()
-            .map(|(url, _)| {
+            .map(|(url, session)| {
                 let url = url.clone();
-                let session_id = session_id.clone();
-                let message_hex = message_hex.clone();
+                let session_token = session.session_token.clone();
                 let signing_commitments = all_commitments.clone();
                 async move {
                     let req = Round2Request {
-                        session_id,
-                        message_hex,
+                        session_token,
+                        request,
                         signing_commitments,
                     };
                     let body = serde_json::to_string(&req).map_err(|e| e.to_string())?;
@@ -235,7 +250,7 @@ mod wasm_impl {
                 .collect();
 
         // Aggregate signature shares
-        let signing_package = frost::SigningPackage::new(commitments_map, message);
+        let signing_package = frost::SigningPackage::new(commitments_map, &message);
 
         // Fetch public key from guardian (cached after first call)
         let pubkey_package = {
@@ -328,16 +343,4 @@ mod wasm_impl {
 
         Ok(text_str)
     }
-
-    /// Generate a random session ID (hex-encoded 16 bytes).
-    fn generate_session_id() -> Result<String, String> {
-        let mut bytes = [0u8; 16];
-        getrandom::getrandom(&mut bytes).map_err(|e| format!("RNG error: {}", e))?;
-        Ok(bytes_to_hex(&bytes))
-    }
-
-    /// Encode bytes as lowercase hex string.
-    fn bytes_to_hex(bytes: &[u8]) -> String {
-        bytes.iter().map(|b| format!("{:02x}", b)).collect()
-    }
 }
@@ -1,26 +1,47 @@
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
-use cream_common::storefront::WeeklySchedule;
+use cream_common::clock::{Clock, SystemClock};
+use cream_common::identity::UserId;
+use cream_common::location::{food_miles_label, GeoLocation};
+use cream_common::pickup::PickupLocation;
+use cream_common::postcode::lookup_postcode;
+use cream_common::product::{ProductId, Special};
+use cream_common::storefront::{is_open_now, next_opening, WeeklySchedule};
 
+use super::category_filter::{taxonomy_present, CategoryFilter, CategoryFilterChips};
 use super::chat_view::ChatWithSupplierButton;
+use super::i18n::{use_i18n, Msg};
+use super::key_manager::KeyManager;
 use super::order_form::OrderForm;
+use super::price_history_chart::PriceHistoryChart;
 use super::schedule_editor::ScheduleSummary;
-use super::shared_state::use_shared_state;
+use super::shared_state::{use_product_revision, use_shared_state};
+use super::supplier_map::{MapPoint, SupplierMap};
 use super::user_state::use_user_state;
 
 #[component]
 pub fn StorefrontView(supplier_name: String) -> Element {
     let user_state = use_user_state();
     let shared_state = use_shared_state();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
     let mut selected_product = use_signal(|| None::<(String, String, u64)>);
+    let category_filter = use_signal(CategoryFilter::default);
+    let i18n = use_i18n();
 
     if let Some((product_id, product_name, price)) = selected_product.read().clone() {
+        let price_points = shared_state
+            .read()
+            .storefronts
+            .get(&supplier_name)
+            .and_then(|sf| sf.products.get(&ProductId(product_id.clone())))
+            .map(|sp| sp.price_points())
+            .unwrap_or_default();
         return rsx! {
             button {
                 onclick: move |_| selected_product.set(None),
-                "Back to Products"
+                {i18n.t(Msg::BackToProducts)}
             }
+            PriceHistoryChart { points: price_points }
             OrderForm {
                 supplier_name: supplier_name.clone(),
                 product_id,
@@ -45,8 +66,18 @@ pub fn StorefrontView(supplier_name: String) -> Element {
     let state = user_state.read();
     let is_own = state.moniker.as_ref() == Some(&supplier_name);
     let is_registered = state.user_contract_key.is_some();
+    let user_location = state.postcode.as_deref().and_then(lookup_postcode);
     drop(state);
 
+    // Food miles: distance from the supplier's farm to the customer's postcode
+    let food_miles: Option<String> = {
+        let shared = shared_state.read();
+        match (shared.storefronts.get(&supplier_name), user_location.as_ref()) {
+            (Some(sf), Some(home)) if !is_own => Some(food_miles_label(sf.info.location.distance_km(home))),
+            _ => None,
+        }
+    };
+
     // Get schedule + timezone + contact details for the storefront header
     let (storefront_schedule, storefront_timezone, contact_phone, contact_email, contact_address): (
         Option<WeeklySchedule>, Option<String>, Option<String>, Option<String>, Option<String>,
@@ -64,31 +95,59 @@ pub fn StorefrontView(supplier_name: String) -> Element {
             ))
             .unwrap_or((None, None, None, None, None))
     };
+    let storefront_owner: Option<UserId> = shared_state
+        .read()
+        .storefronts
+        .get(&supplier_name)
+        .map(|sf| sf.info.owner.clone());
+    // Who last changed the schedule / contact details
+    let info_provenance: Option<String> = {
+        let shared = shared_state.read();
+        shared
+            .storefronts
+            .get(&supplier_name)
+            .and_then(|sf| sf.info.provenance.as_ref())
+            .map(|stamp| shared.provenance_label(stamp))
+    };
     let has_contact = contact_phone.is_some() || contact_email.is_some() || contact_address.is_some();
+    let contact_sealed = shared_state
+        .read()
+        .storefronts
+        .get(&supplier_name)
+        .is_some_and(|sf| sf.info.sealed_contact.is_some());
+    // Places besides the farm where orders are handed over
+    let (farm_location, pickup_locations): (Option<GeoLocation>, Vec<PickupLocation>) = shared_state
+        .read()
+        .storefronts
+        .get(&supplier_name)
+        .map(|sf| (Some(sf.info.location.clone()), sf.info.pickup_locations.clone()))
+        .unwrap_or_default();
 
-    // Always get products from SharedState (network-sourced storefronts)
-    // Tuple: (product_id, name, category, price, available_quantity)
-    let products: Vec<(String, String, String, u64, u32)> = {
+    // Always get products from SharedState (network-sourced storefronts).
+    // Only ids here: each card reads its own product (see ProductCard).
+    let taxonomy: Vec<(String, Vec<String>)> = shared_state
+        .read()
+        .storefronts
+        .get(&supplier_name)
+        .map(|sf| taxonomy_present(sf.products.values().map(|sp| &sp.product)))
+        .unwrap_or_default();
+    let product_ids: Vec<String> = {
         let shared = shared_state.read();
-        if let Some(storefront) = shared.storefronts.get(&supplier_name) {
-            storefront
-                .products
-                .values()
-                .map(|sp| {
-                    let cat = format!("{:?}", sp.product.category);
-                    let available = storefront.available_quantity(&sp.product.id);
-                    (
-                        sp.product.id.0.clone(),
-                        sp.product.name.clone(),
-                        cat,
-                        sp.product.price_curd,
-                        available,
-                    )
-                })
-                .collect()
-        } else {
-            Vec::new()
-        }
+        let filter = category_filter.read();
+        // Wholesale listings are for other suppliers only
+        let sees_wholesale = is_own || key_manager.read().as_ref().is_some_and(|km| shared.is_supplier(&km.user_id()));
+        shared
+            .storefronts
+            .get(&supplier_name)
+            .map(|sf| {
+                sf.products
+                    .values()
+                    .filter(|sp| sees_wholesale || sp.product.wholesale.is_none())
+                    .filter(|sp| filter.matches(&sp.product))
+                    .map(|sp| sp.product.id.0.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
     };
 
     rsx! {
@@ -98,126 +157,296 @@ pub fn StorefrontView(supplier_name: String) -> Element {
                 if let Some(ref schedule) = storefront_schedule {
                     OpenClosedBadge { schedule: schedule.clone(), timezone: storefront_timezone.clone() }
                 }
+                if let Some(ref owner) = storefront_owner {
+                    CredentialBadges { supplier: owner.clone() }
+                }
             }
             if let Some(ref schedule) = storefront_schedule {
                 ScheduleSummary { schedule: schedule.clone() }
             }
+            if let Some(ref updated) = info_provenance {
+                p { class: "provenance", "{updated}" }
+            }
             if has_contact {
                 div { class: "contact-details",
-                    h3 { "Contact Details" }
+                    h3 { {i18n.t(Msg::ContactDetails)} }
                     if let Some(ref phone) = contact_phone {
                         p {
-                            span { class: "contact-label", "Phone: " }
+                            span { class: "contact-label", "{i18n.t(Msg::Phone)}: " }
                             a { href: "tel:{phone}", "{phone}" }
                         }
                     }
                     if let Some(ref email) = contact_email {
                         p {
-                            span { class: "contact-label", "Email: " }
+                            span { class: "contact-label", "{i18n.t(Msg::Email)}: " }
                             span { "{email}" }
                         }
                     }
                     if let Some(ref address) = contact_address {
                         p {
-                            span { class: "contact-label", "Address: " }
+                            span { class: "contact-label", "{i18n.t(Msg::Address)}: " }
                             span { "{address}" }
                         }
                     }
                 }
+            } else if contact_sealed {
+                p { class: "contact-sealed", {i18n.t(Msg::ContactAfterOrder)} }
             }
-            if is_own {
-                p { class: "own-storefront-note",
-                    "(This is your storefront — use the \"My Storefront\" tab to add products)"
+            if !pickup_locations.is_empty() {
+                PickupLocationList {
+                    supplier_name: supplier_name.clone(),
+                    farm: farm_location,
+                    locations: pickup_locations,
+                    home: user_location.clone(),
                 }
             }
+            if is_own {
+                p { class: "own-storefront-note", {i18n.t(Msg::OwnStorefrontNote)} }
+            }
             if is_registered && !is_own {
                 ChatWithSupplierButton { supplier_name: supplier_name.clone() }
             }
+            CategoryFilterChips { present: taxonomy, filter: category_filter }
             div { class: "product-list",
-                if products.is_empty() {
-                    p { class: "empty-state", "No products available." }
+                if product_ids.is_empty() {
+                    p { class: "empty-state", {i18n.t(Msg::NoProductsAvailable)} }
                 } else {
-                    {products.into_iter().map(|(product_id, name, category, price, qty)| {
-                        let pid = product_id.clone();
-                        let name_clone = name.clone();
-                        let is_own_store = is_own;
-                        let price_str = format_amount(price);
-                        rsx! {
-                            div { class: "product-card",
-                                key: "{product_id}",
-                                h3 { "{name}" }
-                                span { class: "category", "{category}" }
-                                p { class: "price", "{price_str}" }
-                                p { class: "quantity", "Available: {qty}" }
-                                if !is_own_store && is_registered {
-                                    button {
-                                        onclick: move |_| selected_product.set(Some((pid.clone(), name_clone.clone(), price))),
-                                        "Order"
-                                    }
-                                }
-                                if !is_own_store && !is_registered {
-                                    p { class: "guest-hint", "Register to place orders" }
-                                }
-                            }
+                    for product_id in product_ids {
+                        ProductCard {
+                            key: "{product_id}",
+                            supplier_name: supplier_name.clone(),
+                            product_id: product_id.clone(),
+                            food_miles: food_miles.clone(),
+                            is_own,
+                            is_registered,
+                            selected_product,
                         }
-                    })}
+                    }
                 }
             }
         }
     }
 }
 
-/// Badge showing "Open" (green) or "Closed" (red) based on the current time.
+/// The supplier's pickup locations on a map alongside the farm, each with
+/// the hours the supplier is there.
 #[component]
-fn OpenClosedBadge(schedule: WeeklySchedule, timezone: Option<String>) -> Element {
-    let is_open = use_memo(move || {
-        let offset = timezone
-            .as_deref()
-            .and_then(get_utc_offset_minutes)
-            .unwrap_or(0);
-        schedule.is_currently_open(offset)
-    });
+fn PickupLocationList(
+    supplier_name: String,
+    farm: Option<GeoLocation>,
+    locations: Vec<PickupLocation>,
+    home: Option<GeoLocation>,
+) -> Element {
+    let i18n = use_i18n();
+    let points: Vec<MapPoint> = farm
+        .map(|location| MapPoint { name: supplier_name.clone(), location })
+        .into_iter()
+        .chain(locations.iter().map(|l| MapPoint { name: l.name.clone(), location: l.location.clone() }))
+        .collect();
 
-    let (class, label) = if is_open() {
-        ("badge badge-open", "Open")
-    } else {
-        ("badge badge-closed", "Closed")
+    rsx! {
+        div { class: "pickup-locations",
+            h3 { {i18n.t(Msg::PickupLocations)} }
+            SupplierMap { points, home, linked: false }
+            for location in locations {
+                div { class: "pickup-location", key: "{location.name}",
+                    h4 { "{location.name}" }
+                    match location.schedule {
+                        Some(schedule) => rsx! { ScheduleSummary { schedule } },
+                        None => rsx! { p { {i18n.t(Msg::ByArrangement)} } },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// One product in a storefront listing. Subscribes to this product's
+/// revision only, so a storefront update re-renders just the cards whose
+/// listing or stock it changed.
+#[component]
+fn ProductCard(
+    supplier_name: String,
+    product_id: String,
+    food_miles: Option<String>,
+    is_own: bool,
+    is_registered: bool,
+    selected_product: Signal<Option<(String, String, u64)>>,
+) -> Element {
+    let shared_state = use_shared_state();
+    let mut user_state = use_user_state();
+    let is_guest = user_state.read().is_guest;
+    let i18n = use_i18n();
+    let revision = use_product_revision(supplier_name.clone(), ProductId(product_id.clone()));
+    // Subscribe via the revision; the product itself is read with peek()
+    let _ = revision();
+
+    let now = SystemClock.now();
+    let (name, category, price, was, available, paused, special, wholesale, updated) = {
+        let shared = shared_state.peek();
+        let Some(sf) = shared.storefronts.get(&supplier_name) else {
+            return rsx! {};
+        };
+        let Some(sp) = sf.products.get(&ProductId(product_id.clone())) else {
+            return rsx! {};
+        };
+        let price = sp.product.effective_price(now);
+        (
+            sp.product.name.clone(),
+            sp.product.category_label(),
+            price,
+            // On special, it was the listed price
+            sp.product
+                .special_at(now)
+                .map(|_| sp.product.price_curd)
+                .or(sp.previous_price().filter(|prev| *prev > price))
+                .map(|prev| i18n.amount(prev)),
+            sf.available_quantity(&sp.product.id),
+            sp.product.paused,
+            sp.product.special.filter(|special| special.ends_at > now),
+            sp.product.wholesale.as_ref().map(|terms| terms.summary(|amount| i18n.amount(amount))),
+            sp.provenance.as_ref().map(|stamp| shared.provenance_label(stamp)),
+        )
     };
+    let price_str = i18n.amount(price);
+    let name_for_order = name.clone();
 
     rsx! {
-        span { class: "{class}", "{label}" }
+        div { class: if paused { "product-card paused" } else { "product-card" },
+            h3 { "{name}" }
+            span { class: "category", "{category}" }
+            p { class: "price", "{price_str}" }
+            if let Some(was) = was {
+                p { class: "price-was", "{i18n.t(Msg::Was)} {was}" }
+            }
+            if let Some(special) = special {
+                SpecialBadge { special }
+            }
+            if let Some(terms) = wholesale {
+                p { class: "wholesale",
+                    span { class: "badge badge-wholesale", {i18n.t(Msg::Wholesale)} }
+                    " {terms}"
+                }
+            }
+            p { class: "quantity", "{i18n.t(Msg::Available)}: {available}" }
+            if let Some(ref miles) = food_miles {
+                p { class: "food-miles", "{i18n.t(Msg::FoodMiles)}: {miles}" }
+            }
+            if let Some(ref updated) = updated {
+                p { class: "provenance", "{updated}" }
+            }
+            if paused {
+                p { class: "paused-note", {i18n.t(Msg::PausedListing)} }
+            } else if !is_own && is_registered {
+                button {
+                    onclick: move |_| selected_product.set(Some((product_id.clone(), name_for_order.clone(), price))),
+                    {i18n.t(Msg::Order)}
+                }
+            }
+            if !is_own && !is_registered && !paused {
+                if is_guest {
+                    button {
+                        class: "guest-sign-up",
+                        onclick: move |_| user_state.write().sign_up(),
+                        {i18n.t(Msg::RegisterToOrder)}
+                    }
+                } else {
+                    p { class: "guest-hint", {i18n.t(Msg::RegisterToOrder)} }
+                }
+            }
+        }
     }
 }
 
-/// Get the current UTC offset in minutes for an IANA timezone name.
-/// Uses JavaScript's Intl API in WASM builds; returns None on failure.
-fn get_utc_offset_minutes(tz: &str) -> Option<i32> {
-    #[cfg(target_family = "wasm")]
-    {
-        // Use JS to get offset: new Date().toLocaleString("en-US", {timeZone}) then compare
-        // Simpler: use Intl.DateTimeFormat resolvedOptions + getTimezoneOffset trick
-        let js_code = format!(
-            r#"(function() {{
-                try {{
-                    var now = new Date();
-                    var utcStr = now.toLocaleString("en-US", {{timeZone: "UTC"}});
-                    var tzStr = now.toLocaleString("en-US", {{timeZone: "{}"}});
-                    var utcDate = new Date(utcStr);
-                    var tzDate = new Date(tzStr);
-                    return Math.round((tzDate - utcDate) / 60000);
-                }} catch(e) {{
-                    return null;
-                }}
-            }})()"#,
-            tz
-        );
-        let result = web_sys::js_sys::eval(&js_code).ok()?;
-        result.as_f64().map(|f| f as i32)
+/// A product's special: its price and a countdown while it runs, or when
+/// it starts. Re-renders every half minute to keep the countdown current.
+#[component]
+fn SpecialBadge(special: Special) -> Element {
+    let i18n = use_i18n();
+    let mut now = use_signal(|| SystemClock.now());
+    use_future(move || async move {
+        loop {
+            super::platform::sleep(std::time::Duration::from_secs(30)).await;
+            now.set(SystemClock.now());
+        }
+    });
+    let now = now();
+    let price = i18n.amount(special.price_curd);
+    if special.covers(now) {
+        let left = special.time_left(now).unwrap_or_default();
+        rsx! {
+            p { class: "special",
+                span { class: "badge badge-special", {i18n.t(Msg::Special)} }
+                " {price} · "
+                span { class: "special-countdown", "{left}" }
+            }
+        }
+    } else if now < special.starts_at {
+        rsx! {
+            p { class: "special upcoming",
+                "{i18n.t(Msg::SpecialFrom)} {i18n.weekday_time(&special.starts_at)}: {price}"
+            }
+        }
+    } else {
+        rsx! {}
     }
-    #[cfg(not(target_family = "wasm"))]
-    {
-        let _ = tz;
-        None
+}
+
+/// Verification badges for a supplier: current credentials from trusted
+/// certifiers (the directory contract rejects any others). Hovering shows who
+/// issued each one.
+#[component]
+pub fn CredentialBadges(supplier: UserId) -> Element {
+    let shared = use_shared_state();
+    let i18n = use_i18n();
+    let badges: Vec<(String, String)> = shared
+        .read()
+        .directory
+        .badges(&supplier, SystemClock.now())
+        .into_iter()
+        .map(|c| {
+            let detail = match &c.reference {
+                Some(reference) => format!(
+                    "{} {} ({} {})",
+                    i18n.t(Msg::IssuedBy),
+                    c.issuer_name,
+                    i18n.t(Msg::Reference),
+                    reference
+                ),
+                None => format!("{} {}", i18n.t(Msg::IssuedBy), c.issuer_name),
+            };
+            (c.kind.label().to_string(), detail)
+        })
+        .collect();
+
+    rsx! {
+        for (label, detail) in badges {
+            span { class: "badge badge-credential", title: "{detail}", "✓ {label}" }
+        }
+    }
+}
+
+/// Badge showing "Open" (green) or "Closed" (red) based on the current time
+/// in the supplier's timezone, with the next opening time when closed.
+#[component]
+pub fn OpenClosedBadge(schedule: WeeklySchedule, timezone: Option<String>) -> Element {
+    let i18n = use_i18n();
+    let status = use_memo(use_reactive!(|i18n| {
+        let now = SystemClock.now();
+        let tz = timezone.as_deref();
+        if is_open_now(&schedule, tz, now) {
+            None
+        } else {
+            Some(next_opening(&schedule, tz, now).map(|at| i18n.weekday_time(&at)))
+        }
+    }));
+
+    match status() {
+        None => rsx! { span { class: "badge badge-open", {i18n.t(Msg::Open)} } },
+        Some(Some(opens)) => rsx! {
+            span { class: "badge badge-closed", "{i18n.t(Msg::Closed)} · {i18n.t(Msg::Opens)} {opens}" }
+        },
+        Some(None) => rsx! { span { class: "badge badge-closed", {i18n.t(Msg::Closed)} } },
     }
 }
 
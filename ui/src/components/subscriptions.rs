@@ -0,0 +1,322 @@
+//! What the client wants to hear about from the node, and what it has.
+//!
+//! Subscriptions live on the node side of a WebSocket, so they all vanish
+//! when the connection drops. The [`SubscriptionManager`] keeps the client's
+//! side of the bargain — every contract it follows and why — separately
+//! from the node's, so that after a reconnect [`SubscriptionManager::reconcile`]
+//! can re-request exactly the subscriptions that are missing. The GETs it
+//! sends are flagged, so their answers are reconciled with what the client
+//! already had (see [`super::resync`]).
+//!
+//! A subscription the node refuses is asked for again after a backoff
+//! ([`SubscriptionManager::retry_due`]), or at once from the network health
+//! panel. Each change is published to [`SharedState::subscriptions`] as a
+//! [`SubscriptionInfo`] for that panel to show.
+//!
+//! [`SharedState::subscriptions`]: super::shared_state::SharedState::subscriptions
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use freenet_stdlib::client_api::ClientRequest;
+use freenet_stdlib::prelude::*;
+
+use cream_client::request;
+use cream_common::directory::{DirectoryShard, DirectoryState};
+
+/// How often failed subscriptions are checked for a retry.
+pub const RETRY_CHECK_INTERVAL_MS: u32 = 5_000;
+
+/// Wait before the first automatic retry; doubled after each further
+/// failure, up to [`RETRY_MAX_SECS`].
+const RETRY_BASE_SECS: i64 = 10;
+const RETRY_MAX_SECS: i64 = 600;
+
+/// Why the client follows a contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interest {
+    DirectoryShard(DirectoryShard),
+    MarketDirectory,
+    NameRegistry,
+    Storefront,
+    OwnUserContract,
+    RootUserContract,
+    Inbox,
+}
+
+impl Interest {
+    pub fn label(self) -> String {
+        match self {
+            Interest::DirectoryShard(shard) => format!("Directory ({:?})", shard),
+            Interest::MarketDirectory => "Market directory".into(),
+            Interest::NameRegistry => "Name registry".into(),
+            Interest::Storefront => "Storefront".into(),
+            Interest::OwnUserContract => "User contract".into(),
+            Interest::RootUserContract => "Root contract".into(),
+            Interest::Inbox => "Inbox".into(),
+        }
+    }
+}
+
+/// Where a subscription stands on the current connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionStatus {
+    /// Wanted but not yet asked for on this connection.
+    Wanted,
+    /// Subscribe sent, no answer yet.
+    Requested,
+    /// The node confirmed the subscription.
+    Active,
+    /// The node refused; asked for again after a backoff, or on the next
+    /// reconcile.
+    Failed,
+}
+
+impl SubscriptionStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            SubscriptionStatus::Wanted => "Waiting to reconnect",
+            SubscriptionStatus::Requested => "Requested",
+            SubscriptionStatus::Active => "Active",
+            SubscriptionStatus::Failed => "Failed",
+        }
+    }
+}
+
+struct Subscription {
+    interest: Interest,
+    status: SubscriptionStatus,
+    requested_at: Option<DateTime<Utc>>,
+    /// When the node last confirmed the subscription.
+    renewed_at: Option<DateTime<Utc>>,
+    /// Refusals since it was last active.
+    failures: u32,
+    /// When a failed subscription is next asked for automatically.
+    retry_at: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    fn new(interest: Interest, status: SubscriptionStatus) -> Self {
+        Subscription { interest, status, requested_at: None, renewed_at: None, failures: 0, retry_at: None }
+    }
+
+    fn request(&mut self, now: DateTime<Utc>) {
+        self.status = SubscriptionStatus::Requested;
+        self.requested_at = Some(now);
+        self.retry_at = None;
+    }
+}
+
+/// One followed contract's subscription, as the network health panel shows it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionInfo {
+    pub interest: Interest,
+    /// Directory name, for storefronts.
+    pub name: Option<String>,
+    pub status: SubscriptionStatus,
+    pub requested_at: Option<DateTime<Utc>>,
+    pub renewed_at: Option<DateTime<Utc>>,
+    pub failures: u32,
+    pub retry_at: Option<DateTime<Utc>>,
+}
+
+impl SubscriptionInfo {
+    /// "Storefront: Gary", "Inbox", ...
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{}: {}", self.interest.label(), name),
+            None => self.interest.label(),
+        }
+    }
+}
+
+/// The contracts the client follows, reconciled against the node's
+/// subscriptions on connect and reconnect.
+#[derive(Default)]
+pub(crate) struct SubscriptionManager {
+    subscriptions: HashMap<ContractInstanceId, Subscription>,
+    /// Populated from the directory so GET/Update responses are keyed by the
+    /// directory name (e.g. "Gary") rather than the storefront's own
+    /// info.name (e.g. "Gary's Farm").
+    names: HashMap<ContractInstanceId, String>,
+    /// Re-fetched after a reconnect, GET answer not yet handled.
+    resyncing: HashSet<ContractInstanceId>,
+    /// Something changed since the last [`SubscriptionManager::take_snapshot`].
+    changed: bool,
+}
+
+impl SubscriptionManager {
+    /// Follow `id`. Returns a GET and a Subscribe the first time; nothing if
+    /// it is already followed.
+    pub fn want(&mut self, id: ContractInstanceId, interest: Interest) -> Vec<ClientRequest<'static>> {
+        if self.subscriptions.contains_key(&id) {
+            return vec![];
+        }
+        let mut subscription = Subscription::new(interest, SubscriptionStatus::Wanted);
+        subscription.request(Utc::now());
+        self.subscriptions.insert(id, subscription);
+        self.changed = true;
+        get_and_subscribe(id)
+    }
+
+    /// Record a subscription made some other way (a PUT with
+    /// `subscribe: true`), so it is restored after a reconnect.
+    pub fn adopt(&mut self, id: ContractInstanceId, interest: Interest) {
+        let mut subscription = Subscription::new(interest, SubscriptionStatus::Active);
+        subscription.renewed_at = Some(Utc::now());
+        self.subscriptions.insert(id, subscription);
+        self.changed = true;
+    }
+
+    /// The node answered a Subscribe for `id`. A refusal schedules a retry,
+    /// waiting longer after each one in a row.
+    pub fn on_subscribe_response(&mut self, id: &ContractInstanceId, subscribed: bool) {
+        let Some(subscription) = self.subscriptions.get_mut(id) else {
+            return;
+        };
+        let now = Utc::now();
+        if subscribed {
+            subscription.status = SubscriptionStatus::Active;
+            subscription.renewed_at = Some(now);
+            subscription.failures = 0;
+            subscription.retry_at = None;
+        } else {
+            subscription.status = SubscriptionStatus::Failed;
+            subscription.failures += 1;
+            let backoff = (RETRY_BASE_SECS << (subscription.failures - 1).min(8)).min(RETRY_MAX_SECS);
+            subscription.retry_at = Some(now + Duration::seconds(backoff));
+            tracing::warn!(
+                "Subscription to {:?} ({:?}) refused, retrying in {}s",
+                subscription.interest,
+                id,
+                backoff
+            );
+        }
+        self.changed = true;
+    }
+
+    /// The connection dropped, taking every node-side subscription with it.
+    pub fn connection_lost(&mut self) {
+        for subscription in self.subscriptions.values_mut() {
+            subscription.status = SubscriptionStatus::Wanted;
+            subscription.retry_at = None;
+        }
+        self.changed = true;
+    }
+
+    /// GET and Subscribe for every followed contract the node isn't known to
+    /// be sending us. The GET catches up on updates missed while
+    /// disconnected.
+    pub fn reconcile(&mut self) -> Vec<ClientRequest<'static>> {
+        let now = Utc::now();
+        let mut requests = Vec::new();
+        for (id, subscription) in &mut self.subscriptions {
+            if matches!(
+                subscription.status,
+                SubscriptionStatus::Wanted | SubscriptionStatus::Failed
+            ) {
+                tracing::info!("Re-subscribing to {:?} ({:?})", subscription.interest, id);
+                subscription.request(now);
+                self.resyncing.insert(*id);
+                requests.extend(get_and_subscribe(*id));
+            }
+        }
+        self.changed |= !requests.is_empty();
+        requests
+    }
+
+    /// Whether a GET answer for `id` is the catch-up after a reconnect.
+    /// Clears the flag, so only the first answer counts.
+    pub fn take_resync(&mut self, id: &ContractInstanceId) -> bool {
+        self.resyncing.remove(id)
+    }
+
+    /// GET and Subscribe for failed subscriptions whose retry is due.
+    pub fn retry_due(&mut self, now: DateTime<Utc>) -> Vec<ClientRequest<'static>> {
+        let mut requests = Vec::new();
+        for (id, subscription) in &mut self.subscriptions {
+            if subscription.status == SubscriptionStatus::Failed && subscription.retry_at.is_some_and(|at| at <= now) {
+                tracing::info!(
+                    "Retrying subscription to {:?} ({:?}) after {} failure(s)",
+                    subscription.interest,
+                    id,
+                    subscription.failures
+                );
+                subscription.request(now);
+                requests.extend(get_and_subscribe(*id));
+            }
+        }
+        self.changed |= !requests.is_empty();
+        requests
+    }
+
+    /// GET and Subscribe for `id` now, whatever its state, if it's followed.
+    pub fn resubscribe(&mut self, id: &ContractInstanceId) -> Vec<ClientRequest<'static>> {
+        let Some(subscription) = self.subscriptions.get_mut(id) else {
+            return vec![];
+        };
+        subscription.request(Utc::now());
+        self.changed = true;
+        get_and_subscribe(*id)
+    }
+
+    /// Every subscription by instance id (Base58), if any changed since the
+    /// last call.
+    pub fn take_snapshot(&mut self) -> Option<BTreeMap<String, SubscriptionInfo>> {
+        if !std::mem::take(&mut self.changed) {
+            return None;
+        }
+        Some(
+            self.subscriptions
+                .iter()
+                .map(|(id, subscription)| {
+                    let info = SubscriptionInfo {
+                        interest: subscription.interest,
+                        name: self.names.get(id).cloned(),
+                        status: subscription.status,
+                        requested_at: subscription.requested_at,
+                        renewed_at: subscription.renewed_at,
+                        failures: subscription.failures,
+                        retry_at: subscription.retry_at,
+                    };
+                    (id.to_string(), info)
+                })
+                .collect(),
+        )
+    }
+
+    /// Directory name of the storefront with this instance id, if known.
+    pub fn name(&self, id: &ContractInstanceId) -> Option<&String> {
+        self.names.get(id)
+    }
+
+    /// Follow the storefront of every supplier in `directory` not already
+    /// followed.
+    pub fn follow_storefronts(&mut self, directory: &DirectoryState) -> Vec<ClientRequest<'static>> {
+        let mut requests = Vec::new();
+        for entry in directory.entries.values() {
+            let instance_id = *entry.storefront_key.id();
+            // Always update the name mapping (in case directory was updated)
+            if self.names.insert(instance_id, entry.name.clone()).as_ref() != Some(&entry.name) {
+                self.changed = true;
+            }
+            let follow = self.want(instance_id, Interest::Storefront);
+            if !follow.is_empty() {
+                tracing::info!(
+                    "Auto-subscribing to storefront for {} (instance_id={:?})",
+                    entry.name,
+                    instance_id
+                );
+            }
+            requests.extend(follow);
+        }
+        requests
+    }
+}
+
+fn get_and_subscribe(id: ContractInstanceId) -> Vec<ClientRequest<'static>> {
+    vec![
+        request::get(id, false),
+        request::subscribe(id),
+    ]
+}
@@ -1,16 +1,77 @@
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
-use cream_common::postcode::format_postcode;
-use cream_common::storefront::WeeklySchedule;
+use cream_common::blocklist::BlockEntry;
+use cream_common::catalog::{
+    parse_records, plan_import, records_to_csv, records_to_json, ImportError, ProductRecord, CSV_COLUMNS,
+};
+use cream_common::contact::ContactDetails;
+use cream_common::currency::{amount_input, parse_amount, Curds};
+use cream_common::delivery::{DeliveryZone, ZoneArea};
+use cream_common::order::{CancellationPolicy, FulfillmentReceipt, Order, OrderStatus, PickupToken};
+use cream_common::pickup::PickupLocation;
+use cream_common::postcode::{format_postcode, lookup_postcode};
+use cream_common::identity::UserId;
+use cream_common::onboarding::{Onboarding, OnboardingStep};
+use cream_common::product::{
+    AttributeKind, LowStockRule, PriceTier, Product, ProductAttributes, ProductCategory, Special, WholesaleTerms,
+};
+use cream_common::settlement::{SettlementPeriod, SettlementStatement};
+use cream_common::staff::{StaffGrant, StaffPermission};
+use cream_common::storefront::{utc_offset_minutes, WeeklySchedule};
+use cream_common::template::StorefrontTemplate;
+use cream_common::voucher::VoucherError;
 
+use super::i18n::use_i18n;
+use super::key_manager::KeyManager;
 use super::schedule_editor::{ScheduleEditor, ScheduleSummary};
 use super::node_api::{use_node_action, NodeAction};
-use super::shared_state::use_shared_state;
+use super::onboarding_checklist::OnboardingChecklist;
+use super::order_filter::{OrderFilter, OrderFilterBar, OrderSort};
+use super::order_thread::OrderThreadPanel;
+#[cfg(target_family = "wasm")]
+use super::platform::download_file;
+use super::shared_state::{use_mark_read, use_shared_state};
 use super::user_state::use_user_state;
 
+/// Pause the supplier's own listings that orders have brought under their
+/// low-stock threshold, whichever page is open. Each listing revision is
+/// asked about once, so a pause still in flight isn't sent again.
+pub fn use_auto_pause() {
+    let user_state = use_user_state();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut requested = use_signal(std::collections::BTreeSet::<(String, chrono::DateTime<chrono::Utc>)>::new);
+    use_effect(move || {
+        let Some(own) = user_state.read().own_storefront().map(str::to_string) else {
+            return;
+        };
+        let to_pause: Vec<Product> = shared_state
+            .read()
+            .storefronts
+            .get(&own)
+            .map(|sf| {
+                sf.products_to_pause()
+                    .iter()
+                    .filter_map(|id| sf.products.get(id).map(|sp| sp.product.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        for product in to_pause {
+            if !requested.write().insert((product.id.0.clone(), product.updated_at)) {
+                continue;
+            }
+            node_action.send(NodeAction::UpdateStockControls {
+                product_id: product.id.0,
+                low_stock: product.low_stock,
+                paused: true,
+            });
+        }
+    });
+}
+
 #[component]
 pub fn SupplierDashboard() -> Element {
+    let i18n = use_i18n();
     let user_state = use_user_state();
     let mut shared_state = use_shared_state();
     let mut show_add_product = use_signal(|| false);
@@ -19,14 +80,33 @@ pub fn SupplierDashboard() -> Element {
     let mut editing_product = use_signal(|| None::<String>);
     let mut edit_price = use_signal(String::new);
     let mut edit_quantity = use_signal(String::new);
+    let mut edit_threshold = use_signal(String::new);
+    let mut edit_auto_pause = use_signal(|| false);
+    let mut editing_special = use_signal(|| None::<String>);
+    let mut special_price = use_signal(String::new);
+    let mut special_start = use_signal(String::new);
+    let mut special_days = use_signal(|| "7".to_string());
     let mut editing_contact = use_signal(|| false);
     let mut contact_phone = use_signal(String::new);
     let mut contact_email = use_signal(String::new);
     let mut contact_address = use_signal(String::new);
+    let mut contact_private = use_signal(|| false);
+    let mut zone_name = use_signal(String::new);
+    let mut zone_postcodes = use_signal(String::new);
+    let mut zone_fee = use_signal(String::new);
+    let mut pickup_token = use_signal(String::new);
+    let mut pickup_feedback = use_signal(|| None::<Result<String, String>>);
+    let mut selected_orders = use_signal(std::collections::BTreeSet::<String>::new);
+    let order_filter = use_signal(OrderFilter::default);
+    let order_sort = use_signal(OrderSort::default);
     let node_action = use_node_action();
+    let key_manager: Signal<Option<KeyManager>> = use_context();
+    use_mark_read(|shared, markers, _, own| shared.mark_storefront_orders_read(markers, own));
 
     let state = user_state.read();
     let moniker = state.moniker.clone().unwrap_or_default();
+    // Own storefront name, for order threads (moniker moves into the schedule editor)
+    let storefront_name = moniker.clone();
     let postcode = format_postcode(
         &state.postcode.clone().unwrap_or("?".into()),
         state.locality.as_deref(),
@@ -52,15 +132,65 @@ pub fn SupplierDashboard() -> Element {
                 .collect()
         })
         .unwrap_or_default();
+    // Listings under their low-stock threshold: (name, available, threshold, paused)
+    let low_stock: Vec<(String, u32, u32, bool)> = storefront
+        .map(|sf| {
+            sf.low_stock()
+                .into_iter()
+                .map(|(sp, available)| {
+                    let threshold = sp.product.low_stock.map_or(0, |rule| rule.threshold);
+                    (sp.product.name.clone(), available, threshold, sp.product.paused)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let timezone: Option<String> = storefront.and_then(|sf| sf.info.timezone.clone());
+    let now = chrono::Utc::now();
     let current_schedule: WeeklySchedule = storefront
         .and_then(|sf| sf.info.schedule.clone())
         .unwrap_or_default();
-    let current_phone: Option<String> = storefront.and_then(|sf| sf.info.phone.clone());
-    let current_email: Option<String> = storefront.and_then(|sf| sf.info.email.clone());
-    let current_address: Option<String> = storefront.and_then(|sf| sf.info.address.clone());
+    // Private contact details are sealed; open our own copy to show and edit
+    let sealed_contact: Option<ContactDetails> = storefront.and_then(|sf| {
+        let sealed = sf.info.sealed_contact.as_ref()?;
+        key_manager.read().as_ref()?.open_contact(sealed, &sf.info.owner, None)
+    });
+    let contact_is_private = sealed_contact.is_some();
+    let (current_phone, current_email, current_address): (Option<String>, Option<String>, Option<String>) =
+        match sealed_contact {
+            Some(details) => (details.phone, details.email, details.address),
+            None => (
+                storefront.and_then(|sf| sf.info.phone.clone()),
+                storefront.and_then(|sf| sf.info.email.clone()),
+                storefront.and_then(|sf| sf.info.address.clone()),
+            ),
+        };
+    let delivery_zones: Vec<DeliveryZone> = storefront
+        .map(|sf| sf.info.delivery_zones.clone())
+        .unwrap_or_default();
+    let pickup_locations: Vec<PickupLocation> = storefront
+        .map(|sf| sf.info.pickup_locations.clone())
+        .unwrap_or_default();
+    let cancellation_policy: Option<CancellationPolicy> =
+        storefront.and_then(|sf| sf.info.cancellation_policy);
+    let attestation_threshold = storefront.and_then(|sf| sf.info.attestation_threshold);
     let network_orders: Vec<_> = storefront
         .map(|sf| sf.orders.values().cloned().collect())
         .unwrap_or_default();
+    // The orders the filter lets through, in the chosen order
+    let order_index = shared.order_index.get(&moniker).cloned().unwrap_or_default();
+    let listed_orders: Vec<Order> = {
+        let mut rows = order_index.query(&order_filter.read(), |row| shared.customer_name(row));
+        order_sort.read().sort(&mut rows);
+        rows.iter()
+            .filter_map(|row| storefront.and_then(|sf| sf.orders.get(&row.id)).cloned())
+            .collect()
+    };
+    let staff: Vec<StaffGrant> = storefront
+        .map(|sf| sf.staff.values().cloned().collect())
+        .unwrap_or_default();
+    let blocklist: Vec<BlockEntry> = storefront
+        .map(|sf| sf.blocklist.values().filter(|entry| entry.blocked).cloned().collect())
+        .unwrap_or_default();
     // Map product IDs to names for readable order display
     let product_names: std::collections::HashMap<String, String> = storefront
         .map(|sf| {
@@ -70,15 +200,77 @@ pub fn SupplierDashboard() -> Element {
                 .collect()
         })
         .unwrap_or_default();
+    // Open orders placed before a price change, with the listing's new price
+    let repriced: std::collections::HashMap<String, u64> = storefront
+        .map(|sf| {
+            sf.orders
+                .values()
+                .filter_map(|o| sf.price_changed_since(o).map(|now| (o.id.0.clone(), now)))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Orders that can be handed over or confirmed right now
+    let ready_orders: Vec<String> = network_orders
+        .iter()
+        .filter(|o| fulfill_step(o).0)
+        .map(|o| o.id.0.clone())
+        .collect();
+    let selected_ready: Vec<String> = ready_orders
+        .iter()
+        .filter(|oid| selected_orders.read().contains(*oid))
+        .cloned()
+        .collect();
+    // Who last changed each order ("Updated by ... 5 min ago")
+    let order_provenance: std::collections::HashMap<String, String> = network_orders
+        .iter()
+        .filter_map(|o| o.provenance.as_ref().map(|stamp| (o.id.0.clone(), shared.provenance_label(stamp))))
+        .collect();
+    let onboarding: Option<Onboarding> = storefront.map(|sf| {
+        Onboarding::assess(
+            sf,
+            shared.directory.entries.get(&sf.info.owner),
+            shared.rendezvous_registered,
+        )
+    });
     drop(shared);
 
     let moniker_for_contact = moniker.clone();
+    let moniker_for_zones = moniker.clone();
+    let zone_form_ok = !zone_name.read().trim().is_empty()
+        && !zone_postcodes.read().trim().is_empty()
+        && parse_amount(&zone_fee.read()).is_ok();
 
     rsx! {
         div { class: "supplier-dashboard",
             h2 { "My Storefront" }
 
-            div { class: "dashboard-section",
+            if let Some(onboarding) = onboarding {
+                OnboardingChecklist {
+                    onboarding,
+                    on_open: {
+                        let cp = current_phone.clone();
+                        let ce = current_email.clone();
+                        let ca = current_address.clone();
+                        move |step: OnboardingStep| match step {
+                            OnboardingStep::Products => show_add_product.set(true),
+                            OnboardingStep::Schedule => {
+                                schedule_edit_gen += 1;
+                                editing_schedule.set(true);
+                            }
+                            OnboardingStep::Contact => {
+                                contact_phone.set(cp.clone().unwrap_or_default());
+                                contact_email.set(ce.clone().unwrap_or_default());
+                                contact_address.set(ca.clone().unwrap_or_default());
+                                contact_private.set(contact_is_private);
+                                editing_contact.set(true);
+                            }
+                            OnboardingStep::Listing | OnboardingStep::Rendezvous => {}
+                        }
+                    },
+                }
+            }
+
+            div { class: "dashboard-section", id: "storefront-info",
                 h3 { "Storefront Info" }
                 p { "Name: {moniker}" }
                 p { "Location: {postcode}" }
@@ -86,7 +278,7 @@ pub fn SupplierDashboard() -> Element {
                 ShareableUrl { moniker: moniker.clone() }
             }
 
-            div { class: "dashboard-section",
+            div { class: "dashboard-section", id: "opening-hours",
                 h3 { "Opening Hours" }
                 if *editing_schedule.read() {
                     ScheduleEditor {
@@ -124,11 +316,27 @@ pub fn SupplierDashboard() -> Element {
                 }
             }
 
-            div { class: "dashboard-section",
+            div { class: "dashboard-section", id: "contact-details",
                 h3 { "Contact Details" }
                 if *editing_contact.read() {
-                    div { class: "privacy-warning",
-                        "Contact details are publicly visible to all customers. Only share what you're comfortable with."
+                    div { class: "form-group",
+                        label {
+                            input {
+                                r#type: "checkbox",
+                                checked: *contact_private.read(),
+                                onchange: move |evt| contact_private.set(evt.checked()),
+                            }
+                            " Only show to customers with an order in progress"
+                        }
+                    }
+                    if *contact_private.read() {
+                        div { class: "privacy-note",
+                            "Your details are encrypted. Customers can read them from their orders page while an order with you is in progress."
+                        }
+                    } else {
+                        div { class: "privacy-warning",
+                            "Contact details are publicly visible to all customers. Only share what you're comfortable with."
+                        }
                     }
                     div { class: "form-group",
                         label { "Phone:" }
@@ -175,17 +383,20 @@ pub fn SupplierDashboard() -> Element {
                                     if v.is_empty() { None } else { Some(v) }
                                 };
 
-                                // Optimistic update
-                                {
+                                let private = *contact_private.read();
+
+                                // Optimistic update (sealing happens with the real one)
+                                if !private {
                                     let mut shared = shared_state.write();
                                     if let Some(sf) = shared.storefronts.get_mut(&moniker) {
                                         sf.info.phone = phone.clone();
                                         sf.info.email = email.clone();
                                         sf.info.address = address.clone();
+                                        sf.info.sealed_contact = None;
                                     }
                                 }
 
-                                node_action.send(NodeAction::UpdateContactDetails { phone, email, address });
+                                node_action.send(NodeAction::UpdateContactDetails { phone, email, address, private });
                                 editing_contact.set(false);
                             }},
                             "Save Contact Details"
@@ -197,6 +408,9 @@ pub fn SupplierDashboard() -> Element {
                     }
                 } else {
                     if current_phone.is_some() || current_email.is_some() || current_address.is_some() {
+                        if contact_is_private {
+                            p { class: "privacy-note", "Only customers with an order in progress can see these." }
+                        }
                         div { class: "contact-details",
                             if let Some(ref phone) = current_phone {
                                 p { "Phone: {phone}" }
@@ -220,6 +434,7 @@ pub fn SupplierDashboard() -> Element {
                                 contact_phone.set(cp.clone().unwrap_or_default());
                                 contact_email.set(ce.clone().unwrap_or_default());
                                 contact_address.set(ca.clone().unwrap_or_default());
+                                contact_private.set(contact_is_private);
                                 editing_contact.set(true);
                             }
                         },
@@ -229,6 +444,123 @@ pub fn SupplierDashboard() -> Element {
             }
 
             div { class: "dashboard-section",
+                h3 { "Delivery Zones" }
+                if delivery_zones.is_empty() {
+                    p { class: "empty-state", "Pickup only — no delivery zones set." }
+                } else {
+                    div { class: "delivery-zones",
+                        for (i, zone) in delivery_zones.iter().enumerate() {
+                            {
+                                let area = match &zone.area {
+                                    ZoneArea::Postcodes(pcs) => pcs.iter().cloned().collect::<Vec<_>>().join(", "),
+                                    ZoneArea::Polygon(vertices) => format!("map area ({} points)", vertices.len()),
+                                };
+                                let fee = i18n.amount(zone.fee_curd);
+                                let remaining: Vec<DeliveryZone> = delivery_zones
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(j, _)| *j != i)
+                                    .map(|(_, z)| z.clone())
+                                    .collect();
+                                rsx! {
+                                    div { class: "delivery-zone", key: "{zone.name}",
+                                        span { class: "zone-name", "{zone.name}" }
+                                        span { " — {area} — {fee}" }
+                                        button {
+                                            onclick: move |_| {
+                                                node_action.send(NodeAction::UpdateDeliveryZones {
+                                                    zones: remaining.clone(),
+                                                });
+                                            },
+                                            "Remove"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "form-group",
+                    label { "Zone name:" }
+                    input {
+                        r#type: "text",
+                        placeholder: "e.g., Town",
+                        value: "{zone_name}",
+                        oninput: move |evt| zone_name.set(evt.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Postcodes (comma-separated):" }
+                    input {
+                        r#type: "text",
+                        placeholder: "e.g., 2450, 2452",
+                        value: "{zone_postcodes}",
+                        oninput: move |evt| zone_postcodes.set(evt.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Delivery fee (CURD):" }
+                    input {
+                        r#type: "number",
+                        min: "0",
+                        step: "0.01",
+                        value: "{zone_fee}",
+                        oninput: move |evt| zone_fee.set(evt.value()),
+                    }
+                }
+                button {
+                    disabled: !zone_form_ok,
+                    onclick: {
+                        let zones = delivery_zones.clone();
+                        let moniker = moniker_for_zones.clone();
+                        move |_| {
+                            let name = zone_name.read().trim().to_string();
+                            let postcodes = zone_postcodes
+                                .read()
+                                .split(',')
+                                .map(|p| p.trim().to_string())
+                                .filter(|p| !p.is_empty())
+                                .collect();
+                            let fee_curd = parse_amount(&zone_fee.read()).unwrap_or(0);
+                            // Same name replaces the existing zone
+                            let mut zones: Vec<DeliveryZone> =
+                                zones.iter().filter(|z| z.name != name).cloned().collect();
+                            zones.push(DeliveryZone {
+                                name,
+                                area: ZoneArea::Postcodes(postcodes),
+                                fee_curd,
+                                extra: Default::default(),
+                            });
+
+                            // Optimistic update
+                            if let Some(sf) = shared_state.write().storefronts.get_mut(&moniker) {
+                                sf.info.delivery_zones = zones.clone();
+                            }
+
+                            node_action.send(NodeAction::UpdateDeliveryZones { zones });
+                            zone_name.set(String::new());
+                            zone_postcodes.set(String::new());
+                            zone_fee.set(String::new());
+                        }
+                    },
+                    "Save Zone"
+                }
+            }
+
+            PickupLocationsPanel { locations: pickup_locations }
+
+            CancellationPolicyPanel { policy: cancellation_policy }
+
+            AttestationThresholdPanel { threshold: attestation_threshold }
+
+            StaffPanel { staff }
+
+            BlocklistPanel { blocklist }
+
+            SettlementPanel { storefront_name: storefront_name.clone() }
+            VoucherPanel {}
+
+            div { class: "dashboard-section", id: "your-products",
                 h3 { "Your Products ({products.len()})" }
                 button {
                     onclick: move |_| show_add_product.set(!show_add_product()),
@@ -239,24 +571,60 @@ pub fn SupplierDashboard() -> Element {
                     AddProductForm { on_added: move || show_add_product.set(false) }
                 }
 
+                ProductImportExport { storefront_name: storefront_name.clone() }
+
+                if !low_stock.is_empty() {
+                    div { class: "alert alert-warning low-stock-alert",
+                        strong { "Low stock" }
+                        ul {
+                            for (name, available, threshold, paused) in low_stock.iter() {
+                                li {
+                                    "{name}: {available} left (alert under {threshold})"
+                                    if *paused { " — paused" }
+                                }
+                            }
+                        }
+                    }
+                }
+
                 if products.is_empty() {
                     p { class: "empty-state", "No products yet. Add your first product above." }
                 } else {
                     div { class: "product-list",
                         {products.iter().map(|(product, available)| {
                             let pid = product.id.0.clone();
-                            let price_str = format_amount(product.price_curd);
+                            let price_str = i18n.amount(product.price_curd);
                             let is_editing = editing_product.read().as_deref() == Some(&pid);
                             let pid_edit = pid.clone();
                             let pid_save = pid.clone();
                             let current_price = product.price_curd;
                             let current_qty = product.quantity_total;
+                            let current_low_stock = product.low_stock;
+                            let paused = product.paused;
+                            let low = product.is_low_on_stock(*available);
+                            let pid_pause = pid.clone();
+                            let pid_special = pid.clone();
+                            let pid_special_save = pid.clone();
+                            let pid_special_end = pid.clone();
+                            let special = product.special.filter(|special| special.ends_at > now);
+                            let is_editing_special = editing_special.read().as_deref() == Some(&pid);
+                            let timezone = timezone.clone();
+                            let category_label = product.category_label();
+                            let attributes = product.attributes.summary();
                             rsx! {
-                                div { class: "product-card",
+                                div { class: if paused { "product-card paused" } else { "product-card" },
                                     key: "{pid}",
                                     div { class: "product-header",
                                         h4 { "{product.name}" }
-                                        span { class: "category", "{product.category:?}" }
+                                        span { class: "category", "{category_label}" }
+                                        if paused {
+                                            span { class: "badge badge-paused", "Paused" }
+                                        } else if low {
+                                            span { class: "badge badge-low-stock", "Low stock" }
+                                        }
+                                    }
+                                    if !attributes.is_empty() {
+                                        p { class: "product-attributes", "{attributes}" }
                                     }
                                     p { "{product.description}" }
                                     if is_editing {
@@ -265,7 +633,8 @@ pub fn SupplierDashboard() -> Element {
                                                 label { "Price (CURD):" }
                                                 input {
                                                     r#type: "number",
-                                                    min: "1",
+                                                    min: "0.01",
+                                                    step: "0.01",
                                                     value: "{edit_price}",
                                                     oninput: move |evt| edit_price.set(evt.value()),
                                                 }
@@ -279,9 +648,28 @@ pub fn SupplierDashboard() -> Element {
                                                     oninput: move |evt| edit_quantity.set(evt.value()),
                                                 }
                                             }
+                                            div { class: "form-group",
+                                                label { "Alert when fewer than (blank for no alert):" }
+                                                input {
+                                                    r#type: "number",
+                                                    min: "1",
+                                                    value: "{edit_threshold}",
+                                                    oninput: move |evt| edit_threshold.set(evt.value()),
+                                                }
+                                            }
+                                            div { class: "form-group",
+                                                label {
+                                                    input {
+                                                        r#type: "checkbox",
+                                                        checked: edit_auto_pause(),
+                                                        onchange: move |evt| edit_auto_pause.set(evt.checked()),
+                                                    }
+                                                    " Pause automatically when low"
+                                                }
+                                            }
                                             button {
                                                 onclick: move |_| {
-                                                    let p = edit_price.read().trim().parse::<u64>().unwrap_or(0);
+                                                    let p = parse_amount(&edit_price.read()).unwrap_or(0);
                                                     let q = edit_quantity.read().trim().parse::<u32>().unwrap_or(0);
                                                     if p > 0 {
                                                         node_action.send(NodeAction::UpdateProduct {
@@ -290,6 +678,20 @@ pub fn SupplierDashboard() -> Element {
                                                             quantity_total: q,
                                                         });
                                                     }
+                                                    let low_stock = edit_threshold
+                                                        .read()
+                                                        .trim()
+                                                        .parse::<u32>()
+                                                        .ok()
+                                                        .filter(|threshold| *threshold > 0)
+                                                        .map(|threshold| LowStockRule { threshold, auto_pause: edit_auto_pause() });
+                                                    if low_stock != current_low_stock {
+                                                        node_action.send(NodeAction::UpdateStockControls {
+                                                            product_id: pid_save.clone(),
+                                                            low_stock,
+                                                            paused,
+                                                        });
+                                                    }
                                                     editing_product.set(None);
                                                 },
                                                 "Save"
@@ -306,11 +708,108 @@ pub fn SupplierDashboard() -> Element {
                                         button {
                                             onclick: move |_| {
                                                 editing_product.set(Some(pid_edit.clone()));
-                                                edit_price.set(current_price.to_string());
+                                                edit_price.set(amount_input(current_price));
                                                 edit_quantity.set(current_qty.to_string());
+                                                edit_threshold.set(
+                                                    current_low_stock.map(|rule| rule.threshold.to_string()).unwrap_or_default(),
+                                                );
+                                                edit_auto_pause.set(current_low_stock.is_some_and(|rule| rule.auto_pause));
                                             },
                                             "Edit"
                                         }
+                                        button {
+                                            onclick: move |_| {
+                                                node_action.send(NodeAction::UpdateStockControls {
+                                                    product_id: pid_pause.clone(),
+                                                    low_stock: current_low_stock,
+                                                    paused: !paused,
+                                                });
+                                            },
+                                            if paused { "Resume" } else { "Pause" }
+                                        }
+                                        if let Some(special) = special {
+                                            p { class: "special",
+                                                span { class: "badge badge-special", "Special" }
+                                                " {i18n.amount(special.price_curd)} · "
+                                                {special.time_left(now).filter(|_| special.covers(now)).unwrap_or_else(|| {
+                                                    format!("from {}", special.starts_at.format("%d %b %H:%M UTC"))
+                                                })}
+                                                button {
+                                                    onclick: move |_| {
+                                                        node_action.send(NodeAction::UpdateSpecial {
+                                                            product_id: pid_special_end.clone(),
+                                                            special: None,
+                                                        });
+                                                    },
+                                                    "End special"
+                                                }
+                                            }
+                                        } else if is_editing_special {
+                                            div { class: "special-form",
+                                                div { class: "form-group",
+                                                    label { "Special price (CURD):" }
+                                                    input {
+                                                        r#type: "number",
+                                                        min: "0.01",
+                                                        step: "0.01",
+                                                        value: "{special_price}",
+                                                        oninput: move |evt| special_price.set(evt.value()),
+                                                    }
+                                                }
+                                                div { class: "form-group",
+                                                    label { "Starts:" }
+                                                    input {
+                                                        r#type: "date",
+                                                        value: "{special_start}",
+                                                        oninput: move |evt| special_start.set(evt.value()),
+                                                    }
+                                                }
+                                                div { class: "form-group",
+                                                    label { "Runs for (days):" }
+                                                    input {
+                                                        r#type: "number",
+                                                        min: "1",
+                                                        value: "{special_days}",
+                                                        oninput: move |evt| special_days.set(evt.value()),
+                                                    }
+                                                }
+                                                button {
+                                                    onclick: move |_| {
+                                                        let price = parse_amount(&special_price.read()).unwrap_or(0);
+                                                        let days = special_days.read().trim().parse::<u32>().unwrap_or(0);
+                                                        let start = chrono::NaiveDate::parse_from_str(special_start.read().trim(), "%Y-%m-%d");
+                                                        if let (Ok(start), true) = (start, days > 0) {
+                                                            let (starts_at, ends_at) =
+                                                                special_window(start, days, timezone.as_deref(), chrono::Utc::now());
+                                                            node_action.send(NodeAction::UpdateSpecial {
+                                                                product_id: pid_special_save.clone(),
+                                                                special: Some(Special { price_curd: price, starts_at, ends_at }),
+                                                            });
+                                                            editing_special.set(None);
+                                                        }
+                                                    },
+                                                    "Save Special"
+                                                }
+                                                button {
+                                                    onclick: move |_| editing_special.set(None),
+                                                    "Cancel"
+                                                }
+                                            }
+                                        } else {
+                                            button {
+                                                onclick: move |_| {
+                                                    editing_special.set(Some(pid_special.clone()));
+                                                    special_price.set(String::new());
+                                                    special_start.set(chrono::Local::now().format("%Y-%m-%d").to_string());
+                                                    special_days.set("7".to_string());
+                                                },
+                                                "Put on Special"
+                                            }
+                                        }
+                                        WholesaleEditor {
+                                            product_id: pid.clone(),
+                                            terms: product.wholesale.clone(),
+                                        }
                                     }
                                 }
                             }
@@ -321,11 +820,100 @@ pub fn SupplierDashboard() -> Element {
 
             div { class: "dashboard-section",
                 h3 { "Incoming Orders ({network_orders.len()})" }
+                div { class: "pickup-scan",
+                    label { "Pickup code" }
+                    input {
+                        r#type: "text",
+                        placeholder: "Scan or paste the customer's pickup code",
+                        value: "{pickup_token}",
+                        oninput: move |e| {
+                            pickup_token.set(e.value());
+                            pickup_feedback.set(None);
+                        },
+                    }
+                    button {
+                        disabled: pickup_token.read().trim().is_empty(),
+                        onclick: {
+                            let moniker = storefront_name.clone();
+                            move |_| {
+                                let text = pickup_token.read().clone();
+                                // Check here for immediate feedback; the handler re-verifies
+                                let checked = PickupToken::decode(&text).and_then(|token| {
+                                    let shared = shared_state.read();
+                                    let order = shared
+                                        .storefronts
+                                        .get(&moniker)
+                                        .and_then(|sf| sf.orders.get(&token.order_id))
+                                        .ok_or("No such order in your storefront")?;
+                                    if !token.verify(order) {
+                                        return Err("Code is not signed by the ordering customer".into());
+                                    }
+                                    if !matches!(
+                                        order.status,
+                                        OrderStatus::Reserved { .. } | OrderStatus::Paid | OrderStatus::FulfillmentProposed { .. }
+                                    ) {
+                                        return Err(format!("Order is already {}", order.status));
+                                    }
+                                    if order.pending_amendment().is_some() {
+                                        return Err("Answer the customer's change to this order first".into());
+                                    }
+                                    Ok(format!("Order #{} handed over", token.order_id.0))
+                                });
+                                if checked.is_ok() {
+                                    node_action.send(NodeAction::ConfirmPickup { token: text });
+                                    pickup_token.set(String::new());
+                                }
+                                pickup_feedback.set(Some(checked));
+                            }
+                        },
+                        "Confirm Pickup"
+                    }
+                    match pickup_feedback.read().clone() {
+                        Some(Ok(msg)) => rsx! { div { class: "alert alert-success", "{msg}" } },
+                        Some(Err(msg)) => rsx! { div { class: "alert alert-error", "{msg}" } },
+                        None => rsx! {},
+                    }
+                }
                 if network_orders.is_empty() {
                     p { class: "empty-state", "No orders yet." }
                 } else {
+                    OrderFilterBar {
+                        statuses: order_index.status_counts(),
+                        products: order_index.products(),
+                        name_label: "Customer".to_string(),
+                        filter: order_filter,
+                        sort: order_sort,
+                        shown: listed_orders.len(),
+                        total: network_orders.len(),
+                    }
+                    if !ready_orders.is_empty() {
+                        div { class: "bulk-fulfill",
+                            button {
+                                onclick: {
+                                    let ready = ready_orders.clone();
+                                    move |_| selected_orders.set(ready.iter().cloned().collect())
+                                },
+                                "Select all ready ({ready_orders.len()})"
+                            }
+                            button {
+                                class: "fulfill-order-btn",
+                                disabled: selected_ready.is_empty(),
+                                onclick: {
+                                    let order_ids = selected_ready.clone();
+                                    move |_| {
+                                        node_action.send(NodeAction::FulfillOrders { order_ids: order_ids.clone() });
+                                        selected_orders.write().clear();
+                                    }
+                                },
+                                "Fulfill selected ({selected_ready.len()})"
+                            }
+                        }
+                    }
+                    if listed_orders.is_empty() {
+                        p { class: "empty-state", "No orders match the filter." }
+                    }
                     div { class: "order-list",
-                        {network_orders.iter().map(|order| {
+                        {listed_orders.iter().map(|order| {
                             let oid = order.id.0.clone();
                             let short_id = if oid.len() > 4 { &oid[oid.len()-4..] } else { &oid };
                             let product_name = product_names
@@ -333,13 +921,42 @@ pub fn SupplierDashboard() -> Element {
                                 .cloned()
                                 .unwrap_or_else(|| order.product_id.0.clone());
                             let status = order.status.to_string();
-                            let deposit_str = format_amount(order.deposit_amount);
-                            let total_str = format_amount(order.total_price);
+                            let deposit_str = i18n.amount(order.current_deposit());
+                            let total_str = i18n.amount(order.current_total_price());
+                            let quantity = order.current_quantity();
+                            let pickup = order.current_collection_point().map(|cp| cp.to_string());
+                            let price_note = repriced.get(&oid).map(|now| {
+                                format!(
+                                    "Price changed since order: listed at {} now, locked at {}",
+                                    i18n.amount(*now),
+                                    i18n.amount(order.unit_price())
+                                )
+                            });
+                            // A change the customer asked for, awaiting our answer
+                            let pending_change = order.pending_amendment().map(|a| {
+                                let mut change = format!("x{} — {}", a.quantity, i18n.amount(a.total_price));
+                                if let Some(ref cp) = a.collection_point {
+                                    change.push_str(&format!(", collect at {}", cp));
+                                }
+                                change
+                            });
                             let deposit_info = match &order.status {
                                 cream_common::order::OrderStatus::Reserved { expires_at } => {
                                     let pct = (order.deposit_tier.deposit_fraction() * 100.0) as u32;
                                     format!("Held until {} ({pct}% deposit: {deposit_str})", expires_at.format("%d %b %Y"))
                                 }
+                                cream_common::order::OrderStatus::Cancelled => match order.refund_amount {
+                                    Some(refund) => {
+                                        let escrowed = order.escrowed_deposit();
+                                        format!(
+                                            "Refunded {} of {}, kept {}",
+                                            i18n.amount(refund),
+                                            i18n.amount(escrowed),
+                                            i18n.amount(escrowed.saturating_sub(refund))
+                                        )
+                                    }
+                                    None => format!("Deposit: {} ({deposit_str})", order.deposit_tier),
+                                },
                                 _ => {
                                     format!("Deposit: {} ({deposit_str})", order.deposit_tier)
                                 }
@@ -349,20 +966,82 @@ pub fn SupplierDashboard() -> Element {
                                 cream_common::order::OrderStatus::Reserved { .. }
                                     | cream_common::order::OrderStatus::Paid
                             );
-                            let can_fulfill = matches!(
-                                order.status,
-                                cream_common::order::OrderStatus::Reserved { .. }
-                                    | cream_common::order::OrderStatus::Paid
-                            );
+                            let (can_fulfill, fulfill_label) = fulfill_step(order);
+                            let is_selected = selected_orders.read().contains(&oid);
+                            let select_oid = oid.clone();
                             let cancel_oid = oid.clone();
+                            let accept_oid = oid.clone();
+                            let decline_oid = oid.clone();
                             let fulfill_oid = oid.clone();
+                            let thread_oid = oid.clone();
+                            let thread_supplier = storefront_name.clone();
+                            let updated = order_provenance.get(&oid).cloned();
                             rsx! {
                                 div { class: "order-card",
                                     key: "{oid}",
+                                    if can_fulfill {
+                                        input {
+                                            class: "order-select",
+                                            r#type: "checkbox",
+                                            checked: is_selected,
+                                            onchange: move |e| {
+                                                if e.checked() {
+                                                    selected_orders.write().insert(select_oid.clone());
+                                                } else {
+                                                    selected_orders.write().remove(&select_oid);
+                                                }
+                                            },
+                                        }
+                                    }
                                     span { class: "order-id", "Order #{short_id}" }
                                     span { class: "order-status", " — {status}" }
-                                    p { "{product_name} x{order.quantity} — {total_str}" }
+                                    p { "{product_name} x{quantity} — {total_str}" }
                                     p { "{deposit_info}" }
+                                    if let Some(ref note) = price_note {
+                                        p { class: "price-changed", "{note}" }
+                                    }
+                                    if let Some(ref pickup) = pickup {
+                                        p { class: "collection-point", "Collect at: {pickup}" }
+                                    }
+                                    if let Some(ref change) = pending_change {
+                                        div { class: "order-amendment",
+                                            p { "Customer asks to change this order to {change}" }
+                                            button {
+                                                class: "accept-amendment-btn",
+                                                onclick: move |_| {
+                                                    node_action.send(NodeAction::AnswerAmendment {
+                                                        order_id: accept_oid.clone(),
+                                                        accept: true,
+                                                    });
+                                                },
+                                                "Accept Change"
+                                            }
+                                            button {
+                                                class: "decline-amendment-btn",
+                                                onclick: move |_| {
+                                                    node_action.send(NodeAction::AnswerAmendment {
+                                                        order_id: decline_oid.clone(),
+                                                        accept: false,
+                                                    });
+                                                },
+                                                "Decline"
+                                            }
+                                        }
+                                    }
+                                    if let Some(ref delivery) = order.delivery {
+                                        p { class: "delivery-address",
+                                            {format!(
+                                                "Deliver to: {}, {} ({}, fee {})",
+                                                delivery.address,
+                                                delivery.postcode,
+                                                delivery.zone,
+                                                i18n.amount(delivery.fee_curd)
+                                            )}
+                                        }
+                                    }
+                                    if let Some(ref updated) = updated {
+                                        p { class: "provenance", "{updated}" }
+                                    }
                                     if can_fulfill {
                                         button {
                                             class: "fulfill-order-btn",
@@ -371,8 +1050,10 @@ pub fn SupplierDashboard() -> Element {
                                                     order_id: fulfill_oid.clone(),
                                                 });
                                             },
-                                            "Mark Fulfilled"
+                                            "{fulfill_label}"
                                         }
+                                    } else if !fulfill_label.is_empty() {
+                                        p { class: "confirm-deadline", "{fulfill_label}" }
                                     }
                                     if can_cancel {
                                         button {
@@ -385,6 +1066,7 @@ pub fn SupplierDashboard() -> Element {
                                             "Cancel Order"
                                         }
                                     }
+                                    OrderThreadPanel { supplier_name: thread_supplier, order_id: thread_oid }
                                 }
                             }
                         })}
@@ -493,22 +1175,601 @@ pub fn SupplierDashboard() -> Element {
     }
 }
 
+/// Keys the supplier has let work on the storefront, and a form to add one.
+/// Re-saving a key replaces its permissions.
+/// Whether the supplier can move `order` on now, and the button label (or,
+/// when they can't, what it's waiting for). Propose from Reserved/Paid; once
+/// proposed, only auto-confirm after the customer's confirmation window has lapsed.
+/// The window of a special starting on `start` (a date in the storefront's
+/// `timezone`) and running `days` days. One starting today begins now.
+fn special_window(
+    start: chrono::NaiveDate,
+    days: u32,
+    timezone: Option<&str>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> (chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>) {
+    let offset = timezone.and_then(|tz| utc_offset_minutes(tz, now)).unwrap_or(0);
+    let midnight = start.and_time(chrono::NaiveTime::MIN).and_utc() - chrono::Duration::minutes(i64::from(offset));
+    (midnight.max(now), midnight + chrono::Duration::days(i64::from(days)))
+}
+
+fn fulfill_step(order: &Order) -> (bool, String) {
+    match &order.status {
+        _ if order.pending_amendment().is_some() => (false, String::new()),
+        OrderStatus::Reserved { .. } | OrderStatus::Paid => (true, "Mark Handed Over".to_string()),
+        OrderStatus::FulfillmentProposed { proposed_at } => {
+            let after = FulfillmentReceipt::auto_confirm_after(*proposed_at);
+            if chrono::Utc::now() >= after {
+                (true, "Confirm Fulfilled".to_string())
+            } else {
+                (false, format!("Awaiting customer until {}", after.format("%d %b %H:%M")))
+            }
+        }
+        _ => (false, String::new()),
+    }
+}
+
+/// Tiers written as "quantity:price" pairs, e.g. "20:9.50, 50:8", with
+/// prices in CURD. `None` if any pair doesn't parse.
+fn parse_tiers(text: &str) -> Option<Vec<PriceTier>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (quantity, price) = pair.split_once(':')?;
+            Some(PriceTier { min_quantity: quantity.trim().parse().ok()?, price_curd: parse_amount(price).ok()? })
+        })
+        .collect()
+}
+
+/// Offering a product wholesale: only registered suppliers see it, and
+/// orders must be for at least the minimum quantity, with optional cheaper
+/// tiers for larger ones.
+#[component]
+fn WholesaleEditor(product_id: String, terms: Option<WholesaleTerms>) -> Element {
+    let i18n = use_i18n();
+    let node_action = use_node_action();
+    let mut editing = use_signal(|| false);
+    let mut min_quantity = use_signal(String::new);
+    let mut tiers = use_signal(String::new);
+    let parsed = min_quantity.read().trim().parse::<u32>().ok().zip(parse_tiers(&tiers.read()));
+
+    if *editing.read() {
+        let pid_save = product_id.clone();
+        return rsx! {
+            div { class: "wholesale-form",
+                div { class: "form-group",
+                    label { "Minimum order quantity:" }
+                    input {
+                        r#type: "number",
+                        min: "1",
+                        value: "{min_quantity}",
+                        oninput: move |evt| min_quantity.set(evt.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Volume tiers (quantity:price CURD):" }
+                    input {
+                        r#type: "text",
+                        placeholder: "e.g., 20:9.50, 50:8",
+                        value: "{tiers}",
+                        oninput: move |evt| tiers.set(evt.value()),
+                    }
+                }
+                button {
+                    disabled: parsed.is_none(),
+                    onclick: move |_| {
+                        if let Some((min_quantity, tiers)) = parsed.clone() {
+                            node_action.send(NodeAction::UpdateWholesale {
+                                product_id: pid_save.clone(),
+                                wholesale: Some(WholesaleTerms { min_quantity, tiers }),
+                            });
+                            editing.set(false);
+                        }
+                    },
+                    "Save Wholesale"
+                }
+                button { onclick: move |_| editing.set(false), "Cancel" }
+            }
+        };
+    }
+
+    let current = terms.clone();
+    let edit_label = if current.is_some() { "Edit Wholesale" } else { "Sell Wholesale" };
+    rsx! {
+        if let Some(terms) = terms {
+            p { class: "wholesale",
+                span { class: "badge badge-wholesale", "Wholesale" }
+                " {terms.summary(|curds| i18n.amount(curds))}"
+                button {
+                    onclick: move |_| {
+                        node_action.send(NodeAction::UpdateWholesale { product_id: product_id.clone(), wholesale: None });
+                    },
+                    "List for Everyone"
+                }
+            }
+        }
+        button {
+            onclick: move |_| {
+                let current = current.clone().unwrap_or(WholesaleTerms { min_quantity: 10, tiers: Vec::new() });
+                min_quantity.set(current.min_quantity.to_string());
+                tiers.set(
+                    current
+                        .tiers
+                        .iter()
+                        .map(|tier| format!("{}:{}", tier.min_quantity, amount_input(tier.price_curd)))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+                editing.set(true);
+            },
+            "{edit_label}"
+        }
+    }
+}
+
+/// Places away from the farm, such as a market stall, where customers can
+/// collect their orders, each with the hours the supplier is there.
+#[component]
+fn PickupLocationsPanel(locations: Vec<PickupLocation>) -> Element {
+    let node_action = use_node_action();
+    let mut name = use_signal(String::new);
+    let mut postcode = use_signal(String::new);
+    // The location whose hours are being edited
+    let mut editing_hours = use_signal(|| None::<usize>);
+
+    let located = lookup_postcode(postcode.read().trim());
+    let new_name = name.read().trim().to_string();
+    let form_ok = located.is_some() && !new_name.is_empty() && !locations.iter().any(|l| l.name == new_name);
+    let unknown_postcode = located.is_none() && !postcode.read().trim().is_empty();
+
+    rsx! {
+        div { class: "dashboard-section", id: "pickup-locations",
+            h3 { "Pickup Locations" }
+            if locations.is_empty() {
+                p { class: "empty-state", "Orders are collected from the farm only." }
+            } else {
+                div { class: "pickup-locations",
+                    for (i, location) in locations.iter().enumerate() {
+                        {
+                            let remaining: Vec<PickupLocation> = locations
+                                .iter()
+                                .enumerate()
+                                .filter(|(j, _)| *j != i)
+                                .map(|(_, l)| l.clone())
+                                .collect();
+                            let all = locations.clone();
+                            let editing = *editing_hours.read() == Some(i);
+                            rsx! {
+                                div { class: "pickup-location", key: "{location.name}",
+                                    span { class: "pickup-location-name", "{location.name}" }
+                                    match &location.schedule {
+                                        Some(schedule) => rsx! { ScheduleSummary { schedule: schedule.clone() } },
+                                        None => rsx! { span { " — by arrangement" } },
+                                    }
+                                    if editing {
+                                        ScheduleEditor {
+                                            key: "{location.name}",
+                                            schedule: location.schedule.clone().unwrap_or_default(),
+                                            on_save: move |schedule: WeeklySchedule| {
+                                                let mut locations = all.clone();
+                                                locations[i].schedule = Some(schedule).filter(|s| !s.is_always_closed());
+                                                node_action.send(NodeAction::UpdatePickupLocations { locations });
+                                                editing_hours.set(None);
+                                            },
+                                            on_cancel: move |_| editing_hours.set(None),
+                                        }
+                                    } else {
+                                        button {
+                                            onclick: move |_| editing_hours.set(Some(i)),
+                                            "Set Hours"
+                                        }
+                                        button {
+                                            onclick: move |_| {
+                                                editing_hours.set(None);
+                                                node_action.send(NodeAction::UpdatePickupLocations {
+                                                    locations: remaining.clone(),
+                                                });
+                                            },
+                                            "Remove"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "form-group",
+                label { "Location name:" }
+                input {
+                    r#type: "text",
+                    placeholder: "e.g., Bellingen Growers Market",
+                    value: "{name}",
+                    oninput: move |evt| name.set(evt.value()),
+                }
+            }
+            div { class: "form-group",
+                label { "Postcode:" }
+                input {
+                    r#type: "text",
+                    placeholder: "e.g., 2454",
+                    value: "{postcode}",
+                    oninput: move |evt| postcode.set(evt.value()),
+                }
+            }
+            if unknown_postcode {
+                p { class: "error-message", "Unknown postcode." }
+            }
+            button {
+                disabled: !form_ok,
+                onclick: {
+                    let locations = locations.clone();
+                    move |_| {
+                        let Some(location) = located.clone() else { return };
+                        let mut locations = locations.clone();
+                        locations.push(PickupLocation {
+                            name: name.read().trim().to_string(),
+                            location,
+                            schedule: None,
+                            extra: Default::default(),
+                        });
+                        node_action.send(NodeAction::UpdatePickupLocations { locations });
+                        name.set(String::new());
+                        postcode.set(String::new());
+                    }
+                },
+                "Add Location"
+            }
+        }
+    }
+}
+
+/// The supplier's terms for customers cancelling their orders. Applies to
+/// orders placed from now on; existing orders keep the terms they were
+/// placed under.
+#[component]
+fn CancellationPolicyPanel(policy: Option<CancellationPolicy>) -> Element {
+    let mut hours = use_signal(String::new);
+    let mut penalty = use_signal(String::new);
+    let node_action = use_node_action();
+
+    let parsed = match (hours.read().trim().parse::<u32>(), penalty.read().trim().parse::<u8>()) {
+        (Ok(full_refund_hours), Ok(late_penalty_percent)) => {
+            Some(CancellationPolicy { full_refund_hours, late_penalty_percent }).filter(|p| p.is_valid())
+        }
+        _ => None,
+    };
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Cancellation Policy" }
+            match policy {
+                Some(policy) => rsx! {
+                    p { "{policy.describe()}." }
+                    button {
+                        onclick: move |_| node_action.send(NodeAction::UpdateCancellationPolicy { policy: None }),
+                        "Always Refund in Full"
+                    }
+                },
+                None => rsx! {
+                    p { class: "empty-state", "Customers who cancel get their whole deposit back." }
+                },
+            }
+            div { class: "form-group",
+                label { "Full refund within (hours):" }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    placeholder: "e.g., 24",
+                    value: "{hours}",
+                    oninput: move |evt| hours.set(evt.value()),
+                }
+            }
+            div { class: "form-group",
+                label { "Deposit kept after that (%):" }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    max: "100",
+                    placeholder: "e.g., 50",
+                    value: "{penalty}",
+                    oninput: move |evt| penalty.set(evt.value()),
+                }
+            }
+            button {
+                disabled: parsed.is_none(),
+                onclick: move |_| {
+                    if let Some(policy) = parsed {
+                        node_action.send(NodeAction::UpdateCancellationPolicy { policy: Some(policy) });
+                        hours.set(String::new());
+                        penalty.set(String::new());
+                    }
+                },
+                "Save Policy"
+            }
+        }
+    }
+}
+
+/// Orders above the threshold must carry the customer's balance, attested
+/// by their delegate from their user contract, rather than relying on the
+/// customer's own page to have checked it.
+#[component]
+fn AttestationThresholdPanel(threshold: Option<Curds>) -> Element {
+    let i18n = use_i18n();
+    let mut amount = use_signal(String::new);
+    let node_action = use_node_action();
+    let parsed = parse_amount(&amount.read()).ok();
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Balance Attestation" }
+            match threshold {
+                Some(threshold) => rsx! {
+                    p { "Orders over {i18n.amount(threshold)} need the customer's attested balance to cover the deposit." }
+                    button {
+                        onclick: move |_| node_action.send(NodeAction::UpdateAttestationThreshold { threshold: None }),
+                        "Stop Requiring"
+                    }
+                },
+                None => rsx! {
+                    p { class: "empty-state", "Orders are taken on the customer's own balance check." }
+                },
+            }
+            div { class: "form-group",
+                label { "Require for orders over (CURD):" }
+                input {
+                    r#type: "number",
+                    min: "0",
+                    step: "0.01",
+                    placeholder: "e.g., 50",
+                    value: "{amount}",
+                    oninput: move |evt| amount.set(evt.value()),
+                }
+            }
+            button {
+                disabled: parsed.is_none(),
+                onclick: move |_| {
+                    if let Some(threshold) = parsed {
+                        node_action.send(NodeAction::UpdateAttestationThreshold { threshold: Some(threshold) });
+                        amount.set(String::new());
+                    }
+                },
+                "Save Threshold"
+            }
+        }
+    }
+}
+
+#[component]
+fn StaffPanel(staff: Vec<StaffGrant>) -> Element {
+    let mut staff_name = use_signal(String::new);
+    let mut staff_key = use_signal(String::new);
+    let mut staff_permissions = use_signal(|| vec![StaffPermission::Products, StaffPermission::Orders]);
+    let node_action = use_node_action();
+
+    let parsed_key = staff_key.read().trim().parse::<UserId>();
+    let form_ok = parsed_key.is_ok() && !staff_name.read().trim().is_empty() && !staff_permissions.read().is_empty();
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Staff" }
+            if staff.is_empty() {
+                p { class: "empty-state", "Only you can manage this storefront." }
+            } else {
+                div { class: "staff-list",
+                    for grant in staff.iter() {
+                        {
+                            let allowed = grant
+                                .permissions
+                                .iter()
+                                .map(|p| p.label())
+                                .collect::<Vec<_>>()
+                                .join(", ");
+                            let grant = grant.clone();
+                            let short_key: String = grant.key.to_string().chars().take(12).collect();
+                            rsx! {
+                                div { class: "staff-member", key: "{grant.key}",
+                                    span { class: "staff-name", "{grant.name}" }
+                                    span { class: "staff-key", " ({short_key}…) " }
+                                    if grant.revoked {
+                                        span { class: "staff-revoked", "— access revoked" }
+                                    } else {
+                                        span { "— {allowed}" }
+                                        button {
+                                            onclick: move |_| {
+                                                node_action.send(NodeAction::UpdateStaff {
+                                                    key: grant.key.clone(),
+                                                    name: grant.name.clone(),
+                                                    permissions: grant.permissions.iter().copied().collect(),
+                                                    revoked: true,
+                                                });
+                                            },
+                                            "Revoke"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "form-group",
+                label { "Name:" }
+                input {
+                    r#type: "text",
+                    placeholder: "e.g., Sam (weekend markets)",
+                    value: "{staff_name}",
+                    oninput: move |evt| staff_name.set(evt.value()),
+                }
+            }
+            div { class: "form-group",
+                label { "Public key (hex):" }
+                input {
+                    r#type: "text",
+                    placeholder: "64 hex digits from their Profile page",
+                    value: "{staff_key}",
+                    oninput: move |evt| staff_key.set(evt.value()),
+                }
+            }
+            div { class: "form-group",
+                for permission in StaffPermission::ALL {
+                    label { class: "checkbox-label",
+                        input {
+                            r#type: "checkbox",
+                            checked: staff_permissions.read().contains(&permission),
+                            onchange: move |evt| {
+                                let mut permissions = staff_permissions.write();
+                                permissions.retain(|p| *p != permission);
+                                if evt.checked() {
+                                    permissions.push(permission);
+                                }
+                            },
+                        }
+                        " {permission.label()}"
+                    }
+                }
+            }
+            button {
+                disabled: !form_ok,
+                onclick: move |_| {
+                    let Ok(key) = staff_key.read().trim().parse::<UserId>() else {
+                        return;
+                    };
+                    node_action.send(NodeAction::UpdateStaff {
+                        key,
+                        name: staff_name.read().trim().to_string(),
+                        permissions: staff_permissions.read().clone(),
+                        revoked: false,
+                    });
+                    staff_name.set(String::new());
+                    staff_key.set(String::new());
+                },
+                "Save Staff Member"
+            }
+        }
+    }
+}
+
+/// Customers the supplier won't serve, and a form to block one. Blocked
+/// customers see the appeal note in place of the order form.
+#[component]
+fn BlocklistPanel(blocklist: Vec<BlockEntry>) -> Element {
+    let mut customer_key = use_signal(String::new);
+    let mut appeal_note = use_signal(String::new);
+    let node_action = use_node_action();
+
+    let form_ok = customer_key.read().trim().parse::<UserId>().is_ok();
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Blocked Customers" }
+            if blocklist.is_empty() {
+                p { class: "empty-state", "You're serving everyone." }
+            } else {
+                div { class: "blocklist",
+                    for entry in blocklist.iter() {
+                        {
+                            let entry = entry.clone();
+                            let short_key: String = entry.customer.to_string().chars().take(12).collect();
+                            let since = entry.updated_at.format("%d %b %Y").to_string();
+                            rsx! {
+                                div { class: "blocked-customer", key: "{entry.customer}",
+                                    span { class: "staff-key", "{short_key}… " }
+                                    span { "— blocked {since}" }
+                                    if !entry.appeal_note.is_empty() {
+                                        p { class: "appeal-note", "{entry.appeal_note}" }
+                                    }
+                                    button {
+                                        onclick: move |_| {
+                                            node_action.send(NodeAction::UpdateBlocklist {
+                                                customer: entry.customer.clone(),
+                                                blocked: false,
+                                                appeal_note: String::new(),
+                                            });
+                                        },
+                                        "Lift Block"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            div { class: "form-group",
+                label { "Customer public key (hex):" }
+                input {
+                    r#type: "text",
+                    placeholder: "64 hex digits",
+                    value: "{customer_key}",
+                    oninput: move |evt| customer_key.set(evt.value()),
+                }
+            }
+            div { class: "form-group",
+                label { "Appeal note (shown to the customer):" }
+                textarea {
+                    placeholder: "e.g., Email us at the farm address if you think this is a mistake",
+                    value: "{appeal_note}",
+                    oninput: move |evt| appeal_note.set(evt.value()),
+                }
+            }
+            button {
+                disabled: !form_ok,
+                onclick: move |_| {
+                    let Ok(customer) = customer_key.read().trim().parse::<UserId>() else {
+                        return;
+                    };
+                    node_action.send(NodeAction::UpdateBlocklist {
+                        customer,
+                        blocked: true,
+                        appeal_note: appeal_note.read().trim().to_string(),
+                    });
+                    customer_key.set(String::new());
+                    appeal_note.set(String::new());
+                },
+                "Block Customer"
+            }
+        }
+    }
+}
+
 #[component]
 fn AddProductForm(on_added: EventHandler<()>) -> Element {
     let mut user_state = use_user_state();
     let mut name = use_signal(String::new);
     let mut category = use_signal(|| "Milk".to_string());
+    let mut subcategory = use_signal(String::new);
+    let mut fat_percent = use_signal(String::new);
+    let mut aging_days = use_signal(String::new);
     let mut description = use_signal(String::new);
     let mut price = use_signal(String::new);
     let mut quantity = use_signal(String::new);
 
     let can_submit = use_memo(move || {
         let name_ok = !name.read().trim().is_empty();
-        let price_ok = price.read().trim().parse::<u64>().is_ok();
+        let price_ok = parse_amount(&price.read()).is_ok();
         let qty_ok = quantity.read().trim().parse::<u32>().is_ok();
         name_ok && price_ok && qty_ok
     });
 
+    let category_labels: Vec<String> = ProductCategory::TOP_LEVEL
+        .iter()
+        .map(|c| c.label().to_string())
+        .collect();
+    // Subcategory choices and attribute inputs follow the selected category
+    let selected_category = ProductCategory::from_label(&category.read());
+    let subcategory_options: Vec<String> = selected_category
+        .subcategories()
+        .iter()
+        .map(|s| s.label().to_string())
+        .collect();
+    let attribute_kinds = selected_category.attributes();
+    let wants_fat = attribute_kinds.contains(&AttributeKind::FatPercent);
+    let wants_aging = attribute_kinds.contains(&AttributeKind::AgingDays);
+    let fat_label = AttributeKind::FatPercent.label();
+    let aging_label = AttributeKind::AgingDays.label();
+
     rsx! {
         div { class: "add-product-form",
             div { class: "form-group",
@@ -524,22 +1785,62 @@ fn AddProductForm(on_added: EventHandler<()>) -> Element {
                 label { "Category:" }
                 select {
                     value: "{category}",
-                    onchange: move |evt| category.set(evt.value()),
-                    option { value: "Milk", "Milk" }
-                    option { value: "Cheese", "Cheese" }
-                    option { value: "Butter", "Butter" }
-                    option { value: "Cream", "Cream" }
-                    option { value: "Yogurt", "Yogurt" }
-                    option { value: "Kefir", "Kefir" }
+                    onchange: move |evt| {
+                        category.set(evt.value());
+                        subcategory.set(String::new());
+                    },
+                    for label in category_labels {
+                        option { value: "{label}", "{label}" }
+                    }
                     option { value: "Other", "Other" }
                 }
             }
+            if !subcategory_options.is_empty() {
+                div { class: "form-group",
+                    label { "Type:" }
+                    select {
+                        value: "{subcategory}",
+                        onchange: move |evt| subcategory.set(evt.value()),
+                        option { value: "", "(unspecified)" }
+                        for label in subcategory_options {
+                            option { value: "{label}", "{label}" }
+                        }
+                    }
+                }
+            }
+            if wants_fat {
+                div { class: "form-group",
+                    label { "{fat_label}:" }
+                    input {
+                        r#type: "number",
+                        min: "0",
+                        max: "100",
+                        step: "0.1",
+                        placeholder: "4.0",
+                        value: "{fat_percent}",
+                        oninput: move |evt| fat_percent.set(evt.value()),
+                    }
+                }
+            }
+            if wants_aging {
+                div { class: "form-group",
+                    label { "{aging_label}:" }
+                    input {
+                        r#type: "number",
+                        min: "0",
+                        placeholder: "180",
+                        value: "{aging_days}",
+                        oninput: move |evt| aging_days.set(evt.value()),
+                    }
+                }
+            }
             div { class: "form-group",
                 label { "Price (CURD):" }
                 input {
                     r#type: "number",
-                    min: "1",
-                    placeholder: "500",
+                    min: "0.01",
+                    step: "0.01",
+                    placeholder: "5.00",
                     value: "{price}",
                     oninput: move |evt| price.set(evt.value()),
                 }
@@ -565,10 +1866,16 @@ fn AddProductForm(on_added: EventHandler<()>) -> Element {
             button {
                 disabled: !can_submit(),
                 onclick: move |_| {
-                    let p = price.read().trim().parse::<u64>().unwrap_or(0);
+                    let p = parse_amount(&price.read()).unwrap_or(0);
                     let q = quantity.read().trim().parse::<u32>().unwrap_or(0);
                     let prod_name = name.read().trim().to_string();
                     let prod_cat = category.read().clone();
+                    let prod_sub = Some(subcategory.read().clone()).filter(|s| !s.is_empty());
+                    let prod_attrs = ProductAttributes {
+                        fat_percent: if wants_fat { fat_percent.read().trim().parse().ok() } else { None },
+                        aging_days: if wants_aging { aging_days.read().trim().parse().ok() } else { None },
+                        extra: Default::default(),
+                    };
                     let prod_desc = description.read().trim().to_string();
 
                     // Add to local state
@@ -586,6 +1893,8 @@ fn AddProductForm(on_added: EventHandler<()>) -> Element {
                         node.send(NodeAction::AddProduct {
                             name: prod_name,
                             category: prod_cat,
+                            subcategory: prod_sub,
+                            attributes: prod_attrs,
                             description: prod_desc,
                             price_curd: p,
                             quantity_total: q,
@@ -600,6 +1909,384 @@ fn AddProductForm(on_added: EventHandler<()>) -> Element {
     }
 }
 
+/// Bulk product import from, and export to, CSV or JSON files (see
+/// [`cream_common::catalog`]). An import is previewed, then sent as one
+/// storefront update.
+#[component]
+fn ProductImportExport(storefront_name: String) -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut planned = use_signal(|| None::<Result<Vec<Product>, Vec<ImportError>>>);
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let columns = CSV_COLUMNS.join(", ");
+    let mut export = {
+        let storefront_name = storefront_name.clone();
+        move |format: &'static str| {
+            let records: Vec<ProductRecord> = shared_state
+                .read()
+                .storefronts
+                .get(&storefront_name)
+                .map(|sf| sf.products.values().map(|sp| ProductRecord::from_product(&sp.product)).collect())
+                .unwrap_or_default();
+            let contents = match format {
+                "csv" => records_to_csv(&records),
+                _ => records_to_json(&records),
+            };
+            let file_name = format!(
+                "cream-products-{}-{}.{}",
+                storefront_name.to_lowercase(),
+                chrono::Utc::now().format("%Y%m%d"),
+                format
+            );
+            #[cfg(target_family = "wasm")]
+            if let Err(e) = download_file(&file_name, &contents) {
+                status.set(Some(Err(e)));
+                return;
+            }
+            let _ = contents;
+            status.set(Some(Ok(format!("Saved {} products to {}", records.len(), file_name))));
+        }
+    };
+    let mut export_csv = export.clone();
+    let export_template = {
+        let storefront_name = storefront_name.clone();
+        move |_| {
+            let Some(template) = shared_state
+                .read()
+                .storefronts
+                .get(&storefront_name)
+                .map(StorefrontTemplate::from_storefront)
+            else {
+                return;
+            };
+            let contents = template.to_json();
+            let file_name = format!("cream-template-{}.json", storefront_name.to_lowercase());
+            #[cfg(target_family = "wasm")]
+            if let Err(e) = download_file(&file_name, &contents) {
+                status.set(Some(Err(e)));
+                return;
+            }
+            let _ = contents;
+            status.set(Some(Ok(format!(
+                "Saved a template with {} products to {}",
+                template.products.len(),
+                file_name
+            ))));
+        }
+    };
+
+    rsx! {
+        details { class: "product-import-export",
+            summary { "Import or export products" }
+            p { class: "hint",
+                "Export your products to a spreadsheet, edit or add rows, and import the file again. "
+                "Rows with an id update that product; rows without one add a new product. "
+                "CSV columns: {columns}. "
+                "A template holds your products (without stock) and opening hours, for new suppliers to start from."
+            }
+            div { class: "import-export-actions",
+                button { onclick: move |_| export_csv("csv"), "Export CSV" }
+                button { onclick: move |_| export("json"), "Export JSON" }
+                button { onclick: export_template, "Export as template" }
+            }
+            input {
+                r#type: "file",
+                accept: ".csv,.json,text/csv,application/json",
+                onchange: {
+                    let storefront_name = storefront_name.clone();
+                    move |e: Event<FormData>| {
+                        status.set(None);
+                        planned.set(None);
+                        let files = e.files();
+                        let storefront_name = storefront_name.clone();
+                        spawn(async move {
+                            let Some(file) = files.into_iter().next() else {
+                                return;
+                            };
+                            let text = match file.read_string().await {
+                                Ok(text) => text,
+                                Err(e) => {
+                                    status.set(Some(Err(format!("Could not read file: {e}"))));
+                                    return;
+                                }
+                            };
+                            let existing = shared_state
+                                .read()
+                                .storefronts
+                                .get(&storefront_name)
+                                .map(|sf| sf.products.clone())
+                                .unwrap_or_default();
+                            let plan = parse_records(&text)
+                                .and_then(|records| plan_import(&records, &existing, chrono::Utc::now()));
+                            planned.set(Some(plan));
+                        });
+                    }
+                },
+            }
+            match planned.read().as_ref() {
+                Some(Ok(products)) if products.is_empty() => rsx! {
+                    p { class: "hint", "Nothing to import: every product in the file is already up to date." }
+                },
+                Some(Ok(products)) => {
+                    let existing = shared_state
+                        .read()
+                        .storefronts
+                        .get(&storefront_name)
+                        .map(|sf| sf.products.keys().cloned().collect::<std::collections::BTreeSet<_>>())
+                        .unwrap_or_default();
+                    let updated = products.iter().filter(|p| existing.contains(&p.id)).count();
+                    let added = products.len() - updated;
+                    let products = products.clone();
+                    rsx! {
+                        div { class: "import-preview",
+                            p { "{added} new and {updated} changed products." }
+                            ul {
+                                for product in products.iter().take(20) {
+                                    li {
+                                        "{product.name} — {product.category_label()} — {i18n.amount(product.price_curd)} × {product.quantity_total}"
+                                    }
+                                }
+                                if products.len() > 20 {
+                                    li { "… and {products.len() - 20} more" }
+                                }
+                            }
+                            button {
+                                onclick: move |_| {
+                                    let count = products.len();
+                                    node_action.send(NodeAction::ImportProducts { products: products.clone() });
+                                    planned.set(None);
+                                    status.set(Some(Ok(format!("Imported {count} products"))));
+                                },
+                                "Import {products.len()} products"
+                            }
+                        }
+                    }
+                }
+                Some(Err(errors)) => rsx! {
+                    div { class: "import-errors",
+                        p { class: "field-error", "Nothing was imported. Fix these rows and try again:" }
+                        ul {
+                            for error in errors.iter() {
+                                li { "{error}" }
+                            }
+                        }
+                    }
+                },
+                None => rsx! {},
+            }
+            match status.read().as_ref() {
+                Some(Ok(msg)) => rsx! { p { class: "backup-ok", "{msg}" } },
+                Some(Err(e)) => rsx! { p { class: "field-error", "{e}" } },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Signed settlement statements: escrow income per week or month, net of
+/// tolls, for reconciling against fulfilled orders.
+#[component]
+fn SettlementPanel(storefront_name: String) -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut status = use_signal(|| None::<Result<String, String>>);
+
+    let statements: Vec<SettlementStatement> = shared_state
+        .read()
+        .user_contract
+        .as_ref()
+        .map(|uc| uc.settlement_statements.iter().rev().cloned().collect())
+        .unwrap_or_default();
+
+    let download = move |file_name: String, contents: String| {
+        #[cfg(target_family = "wasm")]
+        if let Err(e) = download_file(&file_name, &contents) {
+            status.set(Some(Err(e)));
+            return;
+        }
+        let _ = contents;
+        status.set(Some(Ok(format!("Saved {}", file_name))));
+    };
+    let mut download_all = download.clone();
+    let all_statements = statements.clone();
+    let all_name = format!("cream-settlements-{}.json", storefront_name.to_lowercase());
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Settlement Statements" }
+            p { class: "hint", "Signed summaries of the escrow settled to you, less the tolls you paid." }
+            div { class: "ledger-export",
+                for period in [SettlementPeriod::Week, SettlementPeriod::Month] {
+                    button {
+                        onclick: move |_| {
+                            node_action.send(NodeAction::IssueSettlementStatements { period });
+                        },
+                        "Issue {period.label().to_lowercase()} statements"
+                    }
+                }
+                if !statements.is_empty() {
+                    button {
+                        onclick: move |_| {
+                            let json = serde_json::to_string_pretty(&all_statements).unwrap_or_default();
+                            download_all(all_name.clone(), json);
+                        },
+                        "Download all (JSON)"
+                    }
+                }
+            }
+            if statements.is_empty() {
+                p { class: "empty-state", "No statements yet." }
+            } else {
+                table { class: "tx-history",
+                    thead {
+                        tr {
+                            th { "Period" }
+                            th { "Orders" }
+                            th { "Gross" }
+                            th { "Tolls" }
+                            th { "Net" }
+                            th { "" }
+                        }
+                    }
+                    tbody {
+                        for statement in statements {
+                            {
+                                let mut download = download.clone();
+                                let last_day = statement.ends() - chrono::Days::new(1);
+                                let file_name = format!(
+                                    "cream-settlement-{}-{}-{}.csv",
+                                    storefront_name.to_lowercase(),
+                                    statement.period.label().to_lowercase(),
+                                    statement.starts
+                                );
+                                let csv = statement.to_csv(&storefront_name);
+                                rsx! {
+                                    tr { key: "{statement.period:?}-{statement.starts}",
+                                        td { "{statement.period.label()}: {statement.starts} – {last_day}" }
+                                        td { "{statement.orders.len()}" }
+                                        td { "{i18n.amount(statement.gross_curd)}" }
+                                        td { "{i18n.amount(statement.tolls_curd)}" }
+                                        td { "{i18n.amount(statement.net_curd)}" }
+                                        td {
+                                            button {
+                                                onclick: move |_| download(file_name.clone(), csv.clone()),
+                                                "CSV"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            match status.read().as_ref() {
+                Some(Ok(msg)) => rsx! { p { class: "backup-ok", "{msg}" } },
+                Some(Err(e)) => rsx! { p { class: "field-error", "{e}" } },
+                None => rsx! {},
+            }
+        }
+    }
+}
+
+/// Issue gift vouchers: each is paid for from the supplier's balance into
+/// escrow, and its code is shown until the page is reloaded.
+#[component]
+fn VoucherPanel() -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let node_action = use_node_action();
+    let mut amount = use_signal(String::new);
+    let mut note = use_signal(String::new);
+
+    let balance = shared_state.read().user_contract.as_ref().map_or(0, |uc| uc.balance_curds);
+    let amount_val = parse_amount(&amount.read()).unwrap_or(0);
+    let issued: Vec<(cream_common::voucher::Voucher, bool)> = {
+        let shared = shared_state.read();
+        shared
+            .issued_vouchers
+            .iter()
+            .rev()
+            .map(|voucher| {
+                let redeemed = shared.root_user_contract.as_ref().is_some_and(|root| {
+                    voucher.check_redeemable(root) == Err(VoucherError::AlreadyRedeemed)
+                });
+                (voucher.clone(), redeemed)
+            })
+            .collect()
+    };
+
+    rsx! {
+        div { class: "dashboard-section",
+            h3 { "Gift Vouchers" }
+            p { class: "hint", "A voucher's value comes out of your balance now and is paid to whoever redeems its code." }
+            div { class: "voucher-form",
+                div { class: "form-group",
+                    label { "Amount (CURD)" }
+                    input {
+                        r#type: "number",
+                        min: "0.01",
+                        step: "0.01",
+                        value: "{amount}",
+                        oninput: move |e| amount.set(e.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Message (optional)" }
+                    input {
+                        r#type: "text",
+                        maxlength: "200",
+                        value: "{note}",
+                        oninput: move |e| note.set(e.value()),
+                    }
+                }
+                if amount_val > balance {
+                    p { class: "error-message", "Insufficient balance." }
+                }
+                button {
+                    disabled: amount_val == 0 || amount_val > balance,
+                    onclick: move |_| {
+                        node_action.send(NodeAction::IssueVoucher {
+                            amount: amount_val,
+                            note: note.read().trim().to_string(),
+                        });
+                        amount.set(String::new());
+                        note.set(String::new());
+                    },
+                    "Issue voucher"
+                }
+            }
+            if !issued.is_empty() {
+                p { class: "hint", "Copy each code now: codes aren't kept after you leave this page." }
+                ul { class: "voucher-list",
+                    for (voucher, redeemed) in issued {
+                        li { key: "{voucher.id}", class: if redeemed { "voucher redeemed" } else { "voucher" },
+                            div { class: "voucher-summary",
+                                strong { "{i18n.amount(voucher.amount)}" }
+                                if !voucher.note.is_empty() {
+                                    span { " — {voucher.note}" }
+                                }
+                                span { class: "voucher-status",
+                                    if redeemed { "Redeemed" } else { "Not yet redeemed" }
+                                }
+                            }
+                            textarea {
+                                class: "voucher-code",
+                                readonly: true,
+                                rows: "2",
+                                value: "{voucher.code()}",
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[component]
 fn ShareableUrl(moniker: String) -> Element {
     #[cfg(target_family = "wasm")]
@@ -0,0 +1,194 @@
+use dioxus::prelude::*;
+
+use cream_common::location::GeoLocation;
+
+use super::app::Route;
+
+const WIDTH: f64 = 400.0;
+const HEIGHT: f64 = 300.0;
+const PAD: f64 = 20.0;
+/// Markers closer than this (in SVG units) are drawn as one cluster.
+const CLUSTER_RADIUS: f64 = 18.0;
+
+/// A supplier to plot on the map.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapPoint {
+    pub name: String,
+    pub location: GeoLocation,
+}
+
+/// Lat/long bounding box of the visible area.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bounds {
+    min_lat: f64,
+    max_lat: f64,
+    min_lon: f64,
+    max_lon: f64,
+}
+
+impl Bounds {
+    fn around<'a>(locations: impl Iterator<Item = &'a GeoLocation>) -> Option<Bounds> {
+        locations.fold(None, |acc, l| {
+            let b = acc.unwrap_or(Bounds {
+                min_lat: l.latitude,
+                max_lat: l.latitude,
+                min_lon: l.longitude,
+                max_lon: l.longitude,
+            });
+            Some(Bounds {
+                min_lat: b.min_lat.min(l.latitude),
+                max_lat: b.max_lat.max(l.latitude),
+                min_lon: b.min_lon.min(l.longitude),
+                max_lon: b.max_lon.max(l.longitude),
+            })
+        })
+    }
+
+    fn contains(&self, l: &GeoLocation) -> bool {
+        (self.min_lat..=self.max_lat).contains(&l.latitude)
+            && (self.min_lon..=self.max_lon).contains(&l.longitude)
+    }
+}
+
+/// Equirectangular projection of `bounds` into the SVG viewport, keeping the
+/// aspect ratio. Longitude is scaled by cos(latitude) so distances look right
+/// away from the equator.
+struct Projection {
+    bounds: Bounds,
+    scale: f64,
+    lon_factor: f64,
+    x_off: f64,
+    y_off: f64,
+}
+
+impl Projection {
+    fn new(bounds: Bounds) -> Self {
+        let mid_lat = (bounds.min_lat + bounds.max_lat) / 2.0;
+        let lon_factor = mid_lat.to_radians().cos().max(0.1);
+        // At least ~1km of span, so a lone supplier isn't infinitely zoomed
+        let w = ((bounds.max_lon - bounds.min_lon) * lon_factor).max(0.01);
+        let h = (bounds.max_lat - bounds.min_lat).max(0.01);
+        let scale = ((WIDTH - 2.0 * PAD) / w).min((HEIGHT - 2.0 * PAD) / h);
+        let x_off = (WIDTH - w * scale) / 2.0;
+        let y_off = (HEIGHT - h * scale) / 2.0;
+        Projection { bounds, scale, lon_factor, x_off, y_off }
+    }
+
+    fn project(&self, l: &GeoLocation) -> (f64, f64) {
+        let x = self.x_off + (l.longitude - self.bounds.min_lon) * self.lon_factor * self.scale;
+        let y = self.y_off + (self.bounds.max_lat - l.latitude) * self.scale;
+        (x, y)
+    }
+}
+
+/// Greedily group projected markers: each joins the first cluster whose
+/// centre is within [`CLUSTER_RADIUS`], else starts a new one.
+fn cluster(projected: Vec<(f64, f64, MapPoint)>) -> Vec<(f64, f64, Vec<MapPoint>)> {
+    let mut clusters: Vec<(f64, f64, Vec<MapPoint>)> = Vec::new();
+    for (x, y, point) in projected {
+        match clusters
+            .iter_mut()
+            .find(|(cx, cy, _)| (cx - x).hypot(cy - y) < CLUSTER_RADIUS)
+        {
+            Some((cx, cy, members)) => {
+                let n = members.len() as f64;
+                *cx = (*cx * n + x) / (n + 1.0);
+                *cy = (*cy * n + y) / (n + 1.0);
+                members.push(point);
+            }
+            None => clusters.push((x, y, vec![point])),
+        }
+    }
+    clusters
+}
+
+/// Map of suppliers. Clicking a marker opens the storefront, if `linked`;
+/// clicking a cluster zooms in on its members.
+#[component]
+pub fn SupplierMap(points: Vec<MapPoint>, home: Option<GeoLocation>, linked: bool) -> Element {
+    let nav = navigator();
+    let mut zoom = use_signal(|| None::<Bounds>);
+
+    let all_bounds = Bounds::around(points.iter().map(|p| &p.location).chain(home.as_ref()));
+    let Some(bounds) = (*zoom.read()).or(all_bounds) else {
+        return rsx! {
+            p { class: "empty-state", "No suppliers to show on the map." }
+        };
+    };
+
+    let projection = Projection::new(bounds);
+    let projected: Vec<(f64, f64, MapPoint)> = points
+        .iter()
+        .filter(|p| bounds.contains(&p.location))
+        .map(|p| {
+            let (x, y) = projection.project(&p.location);
+            (x, y, p.clone())
+        })
+        .collect();
+    let clusters = cluster(projected);
+    let home_xy = home
+        .as_ref()
+        .filter(|h| bounds.contains(h))
+        .map(|h| projection.project(h));
+    let zoomed = zoom.read().is_some();
+
+    rsx! {
+        div { class: "supplier-map",
+            svg {
+                class: "supplier-map-canvas",
+                view_box: "0 0 {WIDTH} {HEIGHT}",
+                rect { width: "{WIDTH}", height: "{HEIGHT}", class: "supplier-map-bg" }
+                if let Some((hx, hy)) = home_xy {
+                    {
+                        let (tx, ty) = (hx + 8.0, hy + 4.0);
+                        rsx! {
+                            g { class: "map-home",
+                                circle { cx: "{hx:.1}", cy: "{hy:.1}", r: "5" }
+                                text { x: "{tx:.1}", y: "{ty:.1}", "You" }
+                            }
+                        }
+                    }
+                }
+                {clusters.into_iter().map(|(x, y, members)| {
+                    let label_y = y + 4.0;
+                    if members.len() == 1 {
+                        let name = members[0].name.clone();
+                        let label = name.clone();
+                        let label_x = x + 9.0;
+                        rsx! {
+                            g { class: "map-marker",
+                                key: "{name}",
+                                onclick: move |_| {
+                                    if linked {
+                                        nav.push(Route::Supplier { name: name.clone() });
+                                    }
+                                },
+                                circle { cx: "{x:.1}", cy: "{y:.1}", r: "6" }
+                                text { x: "{label_x:.1}", y: "{label_y:.1}", "{label}" }
+                            }
+                        }
+                    } else {
+                        let count = members.len();
+                        let key = members[0].name.clone();
+                        let member_bounds = Bounds::around(members.iter().map(|m| &m.location));
+                        rsx! {
+                            g { class: "map-cluster",
+                                key: "cluster-{key}",
+                                onclick: move |_| zoom.set(member_bounds),
+                                circle { cx: "{x:.1}", cy: "{y:.1}", r: "12" }
+                                text { x: "{x:.1}", y: "{label_y:.1}", text_anchor: "middle", "{count}" }
+                            }
+                        }
+                    }
+                })}
+            }
+            if zoomed {
+                button {
+                    class: "map-reset-btn",
+                    onclick: move |_| zoom.set(None),
+                    "Show all"
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Starting point for a new supplier's storefront: blank, a copy of a
+//! storefront already on the network, or a template file exported by
+//! another supplier (see [`cream_common::template`]).
+
+use dioxus::prelude::*;
+
+use cream_common::template::StorefrontTemplate;
+
+use super::shared_state::use_shared_state;
+
+/// `<select>` value for starting from a template file.
+const FROM_FILE: &str = "__file__";
+
+/// Pick the template a new storefront starts from; `template` holds the
+/// choice (`None` for a blank storefront).
+#[component]
+pub fn TemplatePicker(template: Signal<Option<StorefrontTemplate>>) -> Element {
+    let shared_state = use_shared_state();
+    let mut source = use_signal(String::new);
+    let mut file_error = use_signal(|| None::<String>);
+
+    let mut storefronts: Vec<String> = shared_state
+        .read()
+        .storefronts
+        .iter()
+        .filter(|(_, sf)| !sf.products.is_empty())
+        .map(|(name, _)| name.clone())
+        .collect();
+    storefronts.sort();
+
+    rsx! {
+        div { class: "form-group template-picker",
+            label { "Start from:" }
+            select {
+                value: "{source}",
+                onchange: move |evt: Event<FormData>| {
+                    let picked = evt.value();
+                    file_error.set(None);
+                    let copied = shared_state
+                        .read()
+                        .storefronts
+                        .get(&picked)
+                        .map(StorefrontTemplate::from_storefront);
+                    template.set(copied);
+                    source.set(picked);
+                },
+                option { value: "", "A blank storefront" }
+                for name in storefronts {
+                    option {
+                        value: "{name}",
+                        selected: *source.read() == name,
+                        "A copy of {name}'s storefront"
+                    }
+                }
+                option { value: FROM_FILE, "A template file…" }
+            }
+            if *source.read() == FROM_FILE {
+                input {
+                    r#type: "file",
+                    accept: ".json,application/json",
+                    onchange: move |e: Event<FormData>| {
+                        file_error.set(None);
+                        template.set(None);
+                        let files = e.files();
+                        spawn(async move {
+                            let Some(file) = files.into_iter().next() else {
+                                return;
+                            };
+                            match file.read_string().await {
+                                Ok(text) => match StorefrontTemplate::from_json(&text) {
+                                    Ok(loaded) => template.set(Some(loaded)),
+                                    Err(errors) => {
+                                        let reasons: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                                        file_error.set(Some(reasons.join("; ")));
+                                    }
+                                },
+                                Err(e) => file_error.set(Some(format!("Could not read file: {e}"))),
+                            }
+                        });
+                    },
+                }
+            }
+            if let Some(err) = file_error.read().as_ref() {
+                span { class: "field-error", "{err}" }
+            }
+            if let Some(summary) = template.read().as_ref().map(summarize) {
+                p { class: "hint", "{summary}" }
+            }
+        }
+    }
+}
+
+fn summarize(template: &StorefrontTemplate) -> String {
+    let mut summary = format!("{} products from {}", template.products.len(), template.name);
+    if !template.categories.is_empty() {
+        summary.push_str(&format!(" ({})", template.categories.join(", ")));
+    }
+    if template.schedule.is_some() {
+        summary.push_str(", with its opening hours");
+    }
+    summary.push_str(". Set your stock levels from the dashboard once you're registered.");
+    summary
+}
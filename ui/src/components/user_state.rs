@@ -1,6 +1,12 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use cream_common::currency::{Locale, Precision};
+use cream_common::identity::UserRole;
+
 #[cfg(target_family = "wasm")]
 const STORAGE_KEY: &str = "cream_user_state";
 #[cfg(target_family = "wasm")]
@@ -29,6 +35,54 @@ pub struct ListedProduct {
     pub quantity_total: u32,
 }
 
+/// What the user has already seen, so new activity can be badged as unread.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadMarkers {
+    /// Newest inbox message seen, per sender name.
+    #[serde(default)]
+    pub messages_seen: BTreeMap<String, DateTime<Utc>>,
+    /// Last order status seen, keyed by `"<supplier>/<order id>"`.
+    #[serde(default)]
+    pub order_status_seen: BTreeMap<String, String>,
+}
+
+/// Which side of the marketplace the UI is showing. Suppliers buy too (their
+/// role is [`UserRole::Both`]) and switch between the two from the header;
+/// everyone else is always a customer. The persona picks the navigation, the
+/// role badge and which orders, badges and balances are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Persona {
+    /// Browsing suppliers and placing orders.
+    #[default]
+    Customer,
+    /// Running the user's own storefront.
+    Supplier,
+}
+
+impl Persona {
+    pub const ALL: [Persona; 2] = [Persona::Customer, Persona::Supplier];
+}
+
+/// Which browser notifications the background sync service worker raises
+/// while the tab is closed. Both off until the user opts in.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationSettings {
+    /// New orders on the supplier's storefront, or status changes to the
+    /// customer's own orders.
+    #[serde(default)]
+    pub orders: bool,
+    /// New messages in the user's inbox.
+    #[serde(default)]
+    pub messages: bool,
+}
+
+impl NotificationSettings {
+    pub fn any(&self) -> bool {
+        self.orders || self.messages
+    }
+}
+
 /// Shared application state accessible from all components.
 ///
 /// Balance and transaction history now live on-network in UserContractState.
@@ -40,6 +94,9 @@ pub struct UserState {
     #[serde(default)]
     pub locality: Option<String>,
     pub is_supplier: bool,
+    /// The persona last chosen in the header; see [`UserState::active_persona`].
+    #[serde(default)]
+    pub persona: Persona,
     pub supplier_description: Option<String>,
     pub products: Vec<ListedProduct>,
     pub orders: Vec<PlacedOrder>,
@@ -60,6 +117,38 @@ pub struct UserState {
     /// Whether this user is logged in as the system root.
     #[serde(default)]
     pub is_root: bool,
+    /// Read markers for inbox messages and order status changes.
+    #[serde(default)]
+    pub read_markers: ReadMarkers,
+    /// Display language and formatting conventions.
+    #[serde(default = "default_locale")]
+    pub locale: Locale,
+    /// Decimal places amounts are shown with.
+    #[serde(default)]
+    pub precision: Precision,
+    /// Browsing read-only without an identity. Cleared when the user asks
+    /// to sign up, which brings back the setup screen.
+    #[serde(default)]
+    pub is_guest: bool,
+    /// Node URLs the user has added, most preferred first. The connection
+    /// tries them in this order before falling back to the default node.
+    #[serde(default)]
+    pub node_urls: Vec<String>,
+    /// Sign listings, orders and directory entries through the node's CREAM
+    /// delegate instead of in the page, falling back to the page's key if
+    /// the delegate can't be reached.
+    #[serde(default)]
+    pub delegate_signing: bool,
+    /// Background notifications, see [`super::background_sync`].
+    #[serde(default)]
+    pub notifications: NotificationSettings,
+}
+
+/// The platform's preferred language if we have a catalog for it, else en-AU.
+fn default_locale() -> Locale {
+    super::platform::preferred_language()
+        .and_then(|tag| Locale::from_tag(&tag))
+        .unwrap_or_default()
 }
 
 impl UserState {
@@ -72,6 +161,7 @@ impl UserState {
             postcode: None,
             locality: None,
             is_supplier: false,
+            persona: Persona::default(),
             supplier_description: None,
             products: Vec::new(),
             orders: Vec::new(),
@@ -82,6 +172,13 @@ impl UserState {
             supplier_storefront_key: None,
             user_contract_key: None,
             is_root: false,
+            read_markers: ReadMarkers::default(),
+            locale: default_locale(),
+            precision: Precision::default(),
+            is_guest: false,
+            node_urls: Vec::new(),
+            delegate_signing: false,
+            notifications: NotificationSettings::default(),
         }
     }
 
@@ -155,6 +252,89 @@ impl UserState {
         }
     }
 
+    /// Leave guest mode for the setup screen, to create an identity.
+    pub fn sign_up(&mut self) {
+        self.is_guest = false;
+        self.save();
+    }
+
+    /// The user's role in the marketplace. Suppliers buy from one another,
+    /// so a supplier's role is always [`UserRole::Both`].
+    pub fn role(&self) -> UserRole {
+        if self.is_supplier {
+            UserRole::Both
+        } else {
+            UserRole::Customer
+        }
+    }
+
+    /// Whether the user can switch between personas: a signed-in supplier,
+    /// not in customer mode (connected to another supplier's storefront).
+    pub fn can_switch_persona(&self) -> bool {
+        self.role() == UserRole::Both && self.connected_supplier.is_none() && !self.is_guest
+    }
+
+    /// The persona the UI is showing: the chosen one if the user can switch,
+    /// else customer.
+    pub fn active_persona(&self) -> Persona {
+        if self.can_switch_persona() {
+            self.persona
+        } else {
+            Persona::Customer
+        }
+    }
+
+    /// Switch to `persona`, if the user can.
+    pub fn set_persona(&mut self, persona: Persona) {
+        if self.can_switch_persona() && self.persona != persona {
+            self.persona = persona;
+            self.save();
+        }
+    }
+
+    /// Name of the user's own storefront, when acting as a supplier (not in customer mode).
+    pub fn own_storefront(&self) -> Option<&str> {
+        if self.is_supplier && self.connected_supplier.is_none() {
+            self.moniker.as_deref()
+        } else {
+            None
+        }
+    }
+
+    /// Add a node URL at the end of the preference list (no-op if present).
+    pub fn add_node_url(&mut self, url: String) {
+        if !self.node_urls.contains(&url) {
+            self.node_urls.push(url);
+            self.save();
+        }
+    }
+
+    /// Turn signing through the node's delegate on or off.
+    pub fn set_delegate_signing(&mut self, on: bool) {
+        self.delegate_signing = on;
+        self.save();
+    }
+
+    /// Change which background notifications are raised.
+    pub fn set_notifications(&mut self, notifications: NotificationSettings) {
+        self.notifications = notifications;
+        self.save();
+    }
+
+    /// Move a node URL to the front of the preference list.
+    pub fn prefer_node_url(&mut self, url: &str) {
+        if let Some(pos) = self.node_urls.iter().position(|u| u == url) {
+            let url = self.node_urls.remove(pos);
+            self.node_urls.insert(0, url);
+            self.save();
+        }
+    }
+
+    pub fn remove_node_url(&mut self, url: &str) {
+        self.node_urls.retain(|u| u != url);
+        self.save();
+    }
+
     pub fn place_order(
         &mut self,
         supplier: String,
@@ -8,14 +8,17 @@ use cream_common::wallet_backend::{TransferReceipt, WalletBackend, WalletError};
 use dioxus::prelude::*;
 use freenet_stdlib::prelude::ContractKey;
 
-use super::node_api::{generate_tx_ref, now_iso8601, record_transfer, ContractRole};
+use super::key_manager::KeyManager;
+use super::retry_queue::RetryingApi;
+use super::node_api::{generate_tx_ref, now_iso8601, record_transfer, record_transfers, ContractRole, TransferLeg};
 use super::shared_state::SharedState;
 use super::signing_service::SigningService;
 
 /// CREAM-native wallet backed by on-network double-entry user contracts.
 ///
-/// Holds only Copy types (Dioxus signals, contract keys) plus a signing service.
-/// The `WebApi` handle is passed into each operation via `with_api()` since
+/// Holds only Copy types (Dioxus signals, contract keys) plus a signing service
+/// and the user's keys (for stamping credits the user pays).
+/// The node API handle is passed into each operation via `with_api()` since
 /// it's borrowed mutably by the broader action handler and can't live inside
 /// the wallet struct.
 pub struct CreamNativeWallet {
@@ -23,6 +26,7 @@ pub struct CreamNativeWallet {
     pub root_contract_key: ContractKey,
     pub user_contract_key: Option<ContractKey>,
     pub signing_service: SigningService,
+    pub key_manager: KeyManager,
 }
 
 impl CreamNativeWallet {
@@ -31,21 +35,23 @@ impl CreamNativeWallet {
         root_contract_key: ContractKey,
         user_contract_key: Option<ContractKey>,
         signing_service: SigningService,
+        key_manager: KeyManager,
     ) -> Self {
         Self {
             shared,
             root_contract_key,
             user_contract_key,
             signing_service,
+            key_manager,
         }
     }
 
-    /// Execute a transfer using the provided WebApi handle.
+    /// Execute a transfer using the provided node API handle.
     ///
     /// This is the core method — trait methods call through here.
     pub async fn do_transfer(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         sender: ContractRole,
         receiver: ContractRole,
         amount: u64,
@@ -59,7 +65,7 @@ impl CreamNativeWallet {
     /// Inner transfer method supporting optional tx_ref and lightning_payment_hash.
     async fn do_transfer_inner(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         sender: ContractRole,
         receiver: ContractRole,
         amount: u64,
@@ -86,6 +92,7 @@ impl CreamNativeWallet {
             Some(tx_ref.clone()),
             &self.signing_service,
             lightning_payment_hash,
+            Some(&self.key_manager),
         )
         .await;
 
@@ -100,7 +107,7 @@ impl CreamNativeWallet {
     /// Execute a transfer with a caller-supplied tx_ref (for idempotency).
     pub async fn do_transfer_with_ref(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         sender: ContractRole,
         receiver: ContractRole,
         amount: u64,
@@ -115,7 +122,7 @@ impl CreamNativeWallet {
     /// Transfer from root to user (e.g. registration bonus, faucet, escrow release).
     pub async fn transfer_from_root(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         amount: u64,
         description: String,
         recipient_name: String,
@@ -137,7 +144,7 @@ impl CreamNativeWallet {
     /// Transfer from root to user with a lightning payment hash (for peg-in dedup).
     pub async fn transfer_from_root_with_lightning_hash(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         amount: u64,
         description: String,
         recipient_name: String,
@@ -161,7 +168,7 @@ impl CreamNativeWallet {
     /// Used for initial CURD allocation so re-registration doesn't double-allocate.
     pub async fn transfer_from_root_idempotent(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         amount: u64,
         description: String,
         recipient_name: String,
@@ -183,7 +190,7 @@ impl CreamNativeWallet {
     /// Transfer from root to a third-party contract (e.g. supplier registration).
     pub async fn transfer_from_root_to_third_party(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         recipient_key: ContractKey,
         amount: u64,
         description: String,
@@ -204,7 +211,7 @@ impl CreamNativeWallet {
     /// Transfer from root to third-party with a deterministic tx_ref for idempotency.
     pub async fn transfer_from_root_to_third_party_idempotent(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         recipient_key: ContractKey,
         amount: u64,
         description: String,
@@ -227,7 +234,7 @@ impl CreamNativeWallet {
     /// Transfer from user to root (e.g. order deposit, message toll).
     pub async fn transfer_to_root(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         amount: u64,
         description: String,
         sender_name: String,
@@ -249,7 +256,7 @@ impl CreamNativeWallet {
     /// Transfer from user to root with a lightning payment hash (for peg-out dedup).
     pub async fn transfer_to_root_with_lightning_hash(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         amount: u64,
         description: String,
         sender_name: String,
@@ -275,7 +282,7 @@ impl CreamNativeWallet {
     /// key for the duration of this transfer, then restores it.
     pub async fn settle_escrow_to_supplier(
         &mut self,
-        api: &mut freenet_stdlib::client_api::WebApi,
+        api: &mut RetryingApi,
         supplier_uc_key: ContractKey,
         amount: u64,
         description: String,
@@ -297,6 +304,54 @@ impl CreamNativeWallet {
         self.user_contract_key = saved_key;
         receipt
     }
+
+    /// Settle several escrows to one supplier at once: every `(amount,
+    /// description)` becomes its own ledger entry, but root's side is a
+    /// single signed update.
+    pub async fn settle_escrows_to_supplier(
+        &mut self,
+        api: &mut RetryingApi,
+        supplier_uc_key: ContractKey,
+        settlements: Vec<(u64, String)>,
+        supplier_name: String,
+    ) -> Vec<TransferReceipt> {
+        let root_name = cream_common::identity::ROOT_USER_NAME.to_string();
+        let timestamp = now_iso8601();
+        let legs: Vec<TransferLeg> = settlements
+            .into_iter()
+            .map(|(amount, description)| TransferLeg {
+                amount,
+                description,
+                tx_ref: generate_tx_ref(&root_name),
+                lightning_payment_hash: None,
+            })
+            .collect();
+        let receipts = legs
+            .iter()
+            .map(|leg| TransferReceipt {
+                tx_ref: leg.tx_ref.clone(),
+                amount: leg.amount,
+                timestamp: timestamp.clone(),
+                bearer_token: None,
+            })
+            .collect();
+
+        record_transfers(
+            api,
+            &mut self.shared,
+            ContractRole::Root,
+            ContractRole::User,
+            &self.root_contract_key,
+            Some(&supplier_uc_key),
+            legs,
+            root_name,
+            supplier_name,
+            &self.signing_service,
+            Some(&self.key_manager),
+        )
+        .await;
+        receipts
+    }
 }
 
 impl WalletBackend for CreamNativeWallet {
@@ -317,7 +372,7 @@ impl WalletBackend for CreamNativeWallet {
         _description: String,
         _recipient: String,
     ) -> Result<TransferReceipt, WalletError> {
-        // Trait method can't accept &mut WebApi. For CREAM-native, callers use
+        // Trait method can't accept &mut RetryingApi. For CREAM-native, callers use
         // the typed helpers (transfer_from_root, transfer_to_root, etc.) which
         // accept the api handle. This trait method exists for the Fedimint
         // backend which owns its own connection.
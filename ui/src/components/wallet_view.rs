@@ -1,14 +1,18 @@
 use dioxus::prelude::*;
 
-use cream_common::currency::format_amount;
+use cream_common::currency::parse_amount;
 use cream_common::identity::ROOT_USER_NAME;
 use super::toll_rates::use_toll_rates;
-use cream_common::wallet::TransactionKind;
+use cream_common::voucher::Voucher;
+use cream_common::wallet::{LedgerExport, TransactionKind};
 
+use super::i18n::use_i18n;
+use super::ledger_status::{LedgerOwner, LedgerStatus};
 use super::lightning_remote::LightningClient;
 use super::node_api::{use_node_action, NodeAction};
+use super::platform::device_id;
 use super::shared_state::use_shared_state;
-use super::user_state::use_user_state;
+use super::user_state::{use_user_state, Persona};
 
 /// Map internal names to display names.
 fn display_name(name: &str) -> &str {
@@ -43,6 +47,7 @@ enum PegInState {
 
 #[component]
 pub fn WalletView() -> Element {
+    let i18n = use_i18n();
     let user_state = use_user_state();
     let shared_state = use_shared_state();
     let toll_rates = use_toll_rates();
@@ -50,7 +55,8 @@ pub fn WalletView() -> Element {
 
     let node_action = use_node_action();
     let moniker = user_state.read().moniker.clone().unwrap_or_default();
-    let is_supplier = user_state.read().is_supplier;
+    // Escrowed deposits count towards the balance when selling
+    let is_supplier = user_state.read().active_persona() == Persona::Supplier;
 
     let is_root = user_state.read().is_root;
 
@@ -85,6 +91,32 @@ pub fn WalletView() -> Element {
     } else {
         (0, Vec::new())
     };
+    // Debits rejected as double-spends (concurrent spends from another device)
+    let conflicted_refs: Vec<String> = uc_source
+        .map(|uc| uc.conflicting_debits().iter().map(|tx| tx.tx_ref.clone()).collect())
+        .unwrap_or_default();
+    // Full ledger (since the last checkpoint) for CSV/JSON export
+    let ledger_export = uc_source.map(|uc| LedgerExport::new(uc.checkpoint_balance, &uc.ledger));
+    // Whether the network has the entries this client wrote
+    let ledger_owner = if is_root { LedgerOwner::Root } else { LedgerOwner::Own };
+    let tx_notes: Vec<Option<(&'static str, &'static str)>> = recent_txs
+        .iter()
+        .map(|tx| {
+            shared.ledger_entry(ledger_owner, tx).and_then(|entry| match entry.status {
+                LedgerStatus::Pending => Some(("pending", "tx-status")),
+                LedgerStatus::Confirmed => None,
+                LedgerStatus::Failed if entry.retries_left() => {
+                    Some(("not confirmed, retrying", "tx-status tx-status-failed"))
+                }
+                LedgerStatus::Failed => Some(("failed", "tx-status tx-status-failed")),
+            })
+        })
+        .collect();
+    let failed_transfers = shared
+        .failed_ledger_entries()
+        .iter()
+        .filter(|entry| entry.owner == ledger_owner)
+        .count();
     drop(shared);
 
     // Compute incoming deposit credits from network orders on this supplier's storefront
@@ -101,9 +133,10 @@ pub fn WalletView() -> Element {
                             o.status,
                             cream_common::order::OrderStatus::Reserved { .. }
                                 | cream_common::order::OrderStatus::Paid
+                                | cream_common::order::OrderStatus::FulfillmentProposed { .. }
                         )
                     })
-                    .map(|o| o.deposit_amount)
+                    .map(|o| o.escrowed_deposit())
                     .sum()
             })
             .unwrap_or(0)
@@ -112,8 +145,8 @@ pub fn WalletView() -> Element {
     };
 
     let displayed_balance = base_balance + incoming_deposits;
-    let balance_str = format_amount(displayed_balance);
-    let deposits_str = format_amount(incoming_deposits);
+    let balance_str = i18n.amount(displayed_balance);
+    let deposits_str = i18n.amount(incoming_deposits);
 
     let has_gateway = LightningClient::is_available();
 
@@ -123,11 +156,22 @@ pub fn WalletView() -> Element {
     // Peg-out state
     let mut pegout_curd = use_signal(|| String::new());
     let mut pegout_bolt11 = use_signal(|| String::new());
+    // Send state
+    let mut send_to = use_signal(String::new);
+    let mut send_curd = use_signal(String::new);
+    let mut send_note = use_signal(String::new);
+    let mut voucher_code = use_signal(String::new);
 
     // Read signals eagerly so Dioxus subscribes to changes for button disabled state
     let pegin_sats_val: u64 = pegin_sats.read().parse().unwrap_or(0);
-    let pegout_curd_val: u64 = pegout_curd.read().parse().unwrap_or(0);
+    let pegout_curd_val = parse_amount(&pegout_curd.read()).unwrap_or(0);
     let pegout_bolt11_empty = pegout_bolt11.read().is_empty();
+    let send_curd_val = parse_amount(&send_curd.read()).unwrap_or(0);
+    let send_to_empty = send_to.read().trim().is_empty();
+    let voucher_preview = {
+        let code = voucher_code.read();
+        (!code.trim().is_empty()).then(|| Voucher::from_code(&code))
+    };
     let current_pegin_state = pegin_state.read().clone();
 
     // Polling coroutine for pending peg-in
@@ -197,7 +241,7 @@ pub fn WalletView() -> Element {
                 p { class: "wallet-deposits", "Includes {deposits_str} held in escrow" }
             }
 
-            p { class: "exchange-rate", "Exchange rate: 1 sat = {curd_per_sat} CURD" }
+            p { class: "exchange-rate", "Exchange rate: 1 sat = {i18n.amount(curd_per_sat)}" }
 
             // ── Peg-In ──
             div { class: "peg-section",
@@ -248,7 +292,7 @@ pub fn WalletView() -> Element {
                     },
                     PegInState::Accepted { amount_sats, .. } => rsx! {
                         div { class: "pegin-pending",
-                            p { class: "pegin-status pegin-accepted", "Payment received! Allocating {amount_sats * curd_per_sat} CURD..." }
+                            p { class: "pegin-status pegin-accepted", "Payment received! Allocating {i18n.amount(amount_sats * curd_per_sat)}..." }
                         }
                     },
                     PegInState::Complete => rsx! {
@@ -278,7 +322,7 @@ pub fn WalletView() -> Element {
                             if pegin_sats_val > 0 {
                                 {
                                     let curd = pegin_sats_val * curd_per_sat;
-                                    rsx! { p { class: "peg-preview", "You will receive {format_amount(curd)}" } }
+                                    rsx! { p { class: "peg-preview", "You will receive {i18n.amount(curd)}" } }
                                 }
                             }
                         }
@@ -335,8 +379,9 @@ pub fn WalletView() -> Element {
                     label { "Amount (CURD)" }
                     input {
                         r#type: "number",
-                        min: "1",
-                        placeholder: "e.g. 500",
+                        min: "0.01",
+                        step: "0.01",
+                        placeholder: "e.g. 5.00",
                         value: "{pegout_curd}",
                         oninput: move |e| pegout_curd.set(e.value()),
                     }
@@ -359,7 +404,7 @@ pub fn WalletView() -> Element {
                 button {
                     disabled: pegout_curd_val == 0 || pegout_bolt11_empty,
                     onclick: move |_| {
-                        let curd: u64 = pegout_curd.read().parse().unwrap_or(0);
+                        let curd = parse_amount(&pegout_curd.read()).unwrap_or(0);
                         let bolt11 = pegout_bolt11.read().clone();
                         if curd > 0 && !bolt11.is_empty() {
                             if has_gateway {
@@ -375,13 +420,98 @@ pub fn WalletView() -> Element {
                 }
             }
 
+            // ── Send to another user ──
+            div { class: "peg-section",
+                h3 { "Send CURD" }
+                div { class: "form-group",
+                    label { "Recipient (username or contract key)" }
+                    input {
+                        r#type: "text",
+                        placeholder: "e.g. Gary's Dairy",
+                        value: "{send_to}",
+                        oninput: move |e| send_to.set(e.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Amount (CURD)" }
+                    input {
+                        r#type: "number",
+                        min: "0.01",
+                        step: "0.01",
+                        value: "{send_curd}",
+                        oninput: move |e| send_curd.set(e.value()),
+                    }
+                }
+                div { class: "form-group",
+                    label { "Note" }
+                    input {
+                        r#type: "text",
+                        value: "{send_note}",
+                        oninput: move |e| send_note.set(e.value()),
+                    }
+                }
+                if send_curd_val > base_balance {
+                    p { class: "error-message", "Insufficient balance." }
+                }
+                button {
+                    disabled: send_curd_val == 0 || send_to_empty || send_curd_val > base_balance,
+                    onclick: move |_| {
+                        let note = send_note.read().trim().to_string();
+                        node_action.send(NodeAction::SendCurd {
+                            recipient_contract_key: send_to.read().trim().to_string(),
+                            amount: send_curd_val,
+                            description: if note.is_empty() { "Transfer".to_string() } else { note },
+                        });
+                        send_to.set(String::new());
+                        send_curd.set(String::new());
+                        send_note.set(String::new());
+                    },
+                    "Send"
+                }
+            }
+
+            // ── Redeem a voucher ──
+            if !is_root {
+                div { class: "peg-section",
+                    h3 { "Redeem a Voucher" }
+                    div { class: "form-group",
+                        label { "Voucher code" }
+                        textarea {
+                            class: "voucher-code",
+                            rows: "2",
+                            placeholder: "CREAM-V1-...",
+                            value: "{voucher_code}",
+                            oninput: move |e| voucher_code.set(e.value()),
+                        }
+                    }
+                    match &voucher_preview {
+                        Some(Ok(voucher)) => rsx! {
+                            p { class: "peg-preview",
+                                "{i18n.amount(voucher.amount)} from {voucher.issuer_name}"
+                                if !voucher.note.is_empty() { ": “{voucher.note}”" }
+                            }
+                        },
+                        Some(Err(e)) => rsx! { p { class: "error-message", "This code can't be redeemed: {e}." } },
+                        None => rsx! {},
+                    }
+                    button {
+                        disabled: !matches!(voucher_preview, Some(Ok(_))),
+                        onclick: move |_| {
+                            node_action.send(NodeAction::RedeemVoucher { code: voucher_code.read().trim().to_string() });
+                            voucher_code.set(String::new());
+                        },
+                        "Redeem"
+                    }
+                }
+            }
+
             // ── Faucet (dev only) ──
             div { class: "wallet-actions",
                 button {
                     onclick: move |_| {
                         node_action.send(NodeAction::FaucetTopUp);
                     },
-                    "Faucet (+1000 CURD)"
+                    "Faucet (+{i18n.amount(1_000)})"
                 }
             }
 
@@ -395,9 +525,9 @@ pub fn WalletView() -> Element {
                     div { class: "fee-schedule",
                         h3 { "Toll Rates" }
                         div { class: "toll-rates-compact",
-                            p { "Chat Session: {session_cost} CURD / {session_secs}s" }
-                            p { "Send Message: {message_cost} CURD" }
-                            p { "Exchange Rate: 1 sat = {curd_per_sat} CURD" }
+                            p { "Chat Session: {i18n.amount(session_cost)} / {session_secs}s" }
+                            p { "Send Message: {i18n.amount(message_cost)}" }
+                            p { "Exchange Rate: 1 sat = {i18n.amount(curd_per_sat)}" }
                         }
                     }
                 }
@@ -405,6 +535,38 @@ pub fn WalletView() -> Element {
 
             if !recent_txs.is_empty() {
                 h3 { "Recent Transactions" }
+                if failed_transfers > 0 {
+                    div { class: "ledger-failed",
+                        span { "{failed_transfers} transaction(s) never reached the network." }
+                        button {
+                            onclick: move |_| {
+                                node_action.send(NodeAction::RetryFailedTransfers);
+                            },
+                            "Retry"
+                        }
+                    }
+                }
+                if let Some(export) = ledger_export {
+                    div { class: "ledger-export",
+                        button {
+                            onclick: {
+                                let export = export.clone();
+                                move |_| {
+                                    download_file("cream-ledger.csv", "text/csv", &export.to_csv());
+                                }
+                            },
+                            "Export CSV"
+                        }
+                        button {
+                            onclick: move |_| {
+                                if let Ok(json) = export.to_json() {
+                                    download_file("cream-ledger.json", "application/json", &json);
+                                }
+                            },
+                            "Export JSON"
+                        }
+                    }
+                }
                 table { class: "tx-history",
                     thead {
                         tr {
@@ -415,24 +577,34 @@ pub fn WalletView() -> Element {
                         }
                     }
                     tbody {
-                        for tx in &recent_txs {
+                        for (tx, note) in recent_txs.iter().zip(tx_notes.iter().copied()) {
                             {
                                 let counterparty = match tx.kind {
                                     TransactionKind::Credit => display_name(&tx.sender),
                                     TransactionKind::Debit => display_name(&tx.receiver),
                                 };
+                                let is_conflicted = tx.kind == TransactionKind::Debit
+                                    && conflicted_refs.contains(&tx.tx_ref);
                                 rsx! {
-                                    tr {
+                                    tr { class: if is_conflicted { "tx-conflicted" } else { "" },
                                         td { class: "tx-time", "{short_timestamp(&tx.timestamp)}" }
-                                        td { "{tx.description}" }
+                                        td {
+                                            "{tx.description}"
+                                            if is_conflicted {
+                                                span { class: "tx-conflict-note", " (rejected: insufficient balance)" }
+                                            }
+                                            if let Some((note, class)) = note {
+                                                span { class: class, " ({note})" }
+                                            }
+                                        }
                                         td { "{counterparty}" }
                                         td { class: match tx.kind {
                                                 TransactionKind::Credit => "tx-credit",
                                                 TransactionKind::Debit => "tx-debit",
                                             },
                                             {match tx.kind {
-                                                TransactionKind::Credit => format!("+{}", tx.amount),
-                                                TransactionKind::Debit => format!("-{}", tx.amount),
+                                                TransactionKind::Credit => format!("+{}", i18n.amount(tx.amount)),
+                                                TransactionKind::Debit => format!("-{}", i18n.amount(tx.amount)),
                                             }}
                                         }
                                     }
@@ -442,10 +614,128 @@ pub fn WalletView() -> Element {
                     }
                 }
             }
+            if !is_root {
+                OtherDevicesPanel {}
+            }
         }
     }
 }
 
+/// Entries this account wrote from its other logins, for reconciling two
+/// devices' spending. Both devices' transactions are always kept; a debit
+/// that overdraws once they're combined is marked and left out of the
+/// balance on every device alike.
+#[component]
+fn OtherDevicesPanel() -> Element {
+    let i18n = use_i18n();
+    let shared_state = use_shared_state();
+    let shared = shared_state.read();
+    let Some(uc) = shared.user_contract.as_ref() else {
+        return rsx! {};
+    };
+    let this_device = device_id();
+    let devices: Vec<(String, String)> = uc
+        .other_devices(&this_device)
+        .into_iter()
+        .map(|(device, last)| (device.to_string(), short_timestamp(last)))
+        .collect();
+    if devices.is_empty() {
+        return rsx! {};
+    }
+    let conflicted: Vec<String> = uc.conflicting_debits().iter().map(|tx| tx.tx_ref.clone()).collect();
+    let entries: Vec<(String, String, String, String, bool)> = uc
+        .entries_from_other_devices(&this_device)
+        .into_iter()
+        .rev()
+        .map(|tx| {
+            let amount = match tx.kind {
+                TransactionKind::Credit => format!("+{}", i18n.amount(tx.amount)),
+                TransactionKind::Debit => format!("-{}", i18n.amount(tx.amount)),
+            };
+            let is_conflicted = tx.kind == TransactionKind::Debit && conflicted.contains(&tx.tx_ref);
+            (
+                tx.device.clone().unwrap_or_default(),
+                short_timestamp(&tx.timestamp),
+                tx.description.clone(),
+                amount,
+                is_conflicted,
+            )
+        })
+        .collect();
+    let any_conflicted = entries.iter().any(|e| e.4);
+    drop(shared);
+
+    rsx! {
+        div { class: "other-devices",
+            h3 { "Other Devices" }
+            p {
+                "This account has also been used on "
+                {devices.iter().map(|(d, last)| format!("{d} (last {last})")).collect::<Vec<_>>().join(", ")}
+                ". Transactions from every device are merged into one ledger; none are dropped."
+            }
+            if any_conflicted {
+                p { class: "tx-conflict-note",
+                    "Some spends from different devices overdrew the balance once combined. "
+                    "They're marked below and don't count against your balance."
+                }
+            }
+            table { class: "tx-history",
+                thead {
+                    tr {
+                        th { "Device" }
+                        th { "Time" }
+                        th { "Description" }
+                        th { "Amount" }
+                    }
+                }
+                tbody {
+                    for (device, time, description, amount, is_conflicted) in entries {
+                        tr { class: if is_conflicted { "tx-conflicted" } else { "" },
+                            td { class: "tx-device", "{device}" }
+                            td { class: "tx-time", "{time}" }
+                            td {
+                                "{description}"
+                                if is_conflicted {
+                                    span { class: "tx-conflict-note", " (rejected: insufficient balance)" }
+                                }
+                            }
+                            td { "{amount}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Trigger a browser download of `contents` via a temporary data-URL link.
+/// No-op outside WASM.
+fn download_file(filename: &str, mime: &str, contents: &str) {
+    #[cfg(target_family = "wasm")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let doc = match web_sys::window().and_then(|w| w.document()) {
+            Some(d) => d,
+            None => return,
+        };
+        let link = match doc.create_element("a") {
+            Ok(el) => el,
+            Err(_) => return,
+        };
+        let encoded = String::from(js_sys::encode_uri_component(contents));
+        let _ = link.set_attribute("href", &format!("data:{mime};charset=utf-8,{encoded}"));
+        let _ = link.set_attribute("download", filename);
+        if let Ok(link) = link.dyn_into::<web_sys::HtmlElement>() {
+            link.click();
+        }
+    }
+    #[cfg(not(target_family = "wasm"))]
+    {
+        let _ = (filename, mime, contents);
+    }
+}
+
 /// Format an ISO 8601 timestamp to a short display form.
 fn short_timestamp(ts: &str) -> String {
     // "2026-02-23T10:30:00.000Z" → "Feb 23, 10:30"
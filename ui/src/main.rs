@@ -1,5 +1,6 @@
 mod components;
 
 fn main() {
+    components::debug_log::init();
     dioxus::launch(components::app::App);
 }